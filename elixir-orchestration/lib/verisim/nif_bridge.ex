@@ -94,10 +94,61 @@ def list_hexads(_limit, _offset), do: {:error, :nif_not_loaded}
   def get_drift_score(_hexad_id), do: {:error, :nif_not_loaded}
 
   @doc """
-  Trigger normalisation (self-repair) for a drifted entity.
+  Queue normalisation (self-repair) of a drifted entity for the normaliser's
+  background workers.
   """
   def trigger_normalise(_hexad_id), do: {:error, :nif_not_loaded}
 
+  @doc """
+  Get drift status: global metrics per drift type, then each namespace's.
+  """
+  def get_drift_status, do: {:error, :nif_not_loaded}
+
+  @doc """
+  Replace the drift thresholds.
+
+  Accepts the `PUT /drift/thresholds` body as JSON; a `"namespace"` key sets
+  that namespace's override. The change is audited in provenance.
+  """
+  def set_thresholds(_thresholds_json), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Send `{:drift_event, json}` to `pid` for every drift detected from now on,
+  until `pid` exits.
+  """
+  def subscribe_drift_events(_pid), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Create many hexads atomically from a JSON array of hexad inputs.
+
+  Either every hexad is created or none is. Returns `{"ids": [...]}` JSON.
+  """
+  def create_hexads_batch(_json_inputs), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Begin a transaction.
+
+  Accepts the transaction options as JSON (an empty string for the
+  defaults) and returns the transaction status JSON, whose `id` names it.
+  """
+  def begin_transaction(_options_json), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Execute a VQL statement, buffering its writes in the given transaction
+  (an empty string for none).
+  """
+  def execute_vql(_query, _transaction_id), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Commit a transaction, applying its buffered writes atomically.
+  """
+  def commit_transaction(_transaction_id), do: {:error, :nif_not_loaded}
+
+  @doc """
+  Roll back a transaction, discarding its buffered writes.
+  """
+  def rollback_transaction(_transaction_id), do: {:error, :nif_not_loaded}
+
   @doc """
   Check whether the NIF bridge is loaded and operational.
   """
@@ -168,6 +168,76 @@ def delete_hexad(entity_id) do
     end
   end
 
+  @doc """
+  Create many hexads atomically: all of them, or none if one fails.
+
+  Returns `{:ok, ids}` with the new IDs in input order.
+  """
+  def create_hexads_batch(inputs) when is_list(inputs) do
+    case post("/hexads/batch", inputs) do
+      {:ok, %{status: 201, body: %{"ids" => ids}}} -> {:ok, ids}
+      {:ok, %{status: status, body: body}} -> {:error, {status, body}}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  # Transactions
+
+  @doc """
+  Begin a transaction. `options` may set `isolation`, `timeout_secs` and
+  `idle_timeout_secs`; the returned status's `"id"` names the transaction.
+  """
+  def begin_transaction(options \\ %{}) do
+    case post("/transactions/begin", options) do
+      {:ok, %{status: 201, body: body}} -> {:ok, body}
+      {:ok, %{status: status, body: body}} -> {:error, {status, body}}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  @doc """
+  Execute a VQL statement, buffering its writes in `transaction_id` when
+  one is given.
+  """
+  def execute_vql(query, transaction_id \\ nil) do
+    body =
+      if transaction_id, do: %{query: query, transaction: transaction_id}, else: %{query: query}
+
+    case post("/vql/execute", body) do
+      {:ok, %{status: 200, body: body}} -> {:ok, body}
+      {:ok, %{status: status, body: body}} -> {:error, {status, body}}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  @doc """
+  Commit a transaction, applying its buffered writes atomically.
+  """
+  def commit_transaction(transaction_id) do
+    case post("/transactions/#{transaction_id}/commit", %{}) do
+      {:ok, %{status: 200, body: body}} ->
+        clear_cache()
+        {:ok, body}
+
+      {:ok, %{status: status, body: body}} ->
+        {:error, {status, body}}
+
+      {:error, reason} ->
+        {:error, reason}
+    end
+  end
+
+  @doc """
+  Roll back a transaction, discarding its buffered writes.
+  """
+  def rollback_transaction(transaction_id) do
+    case post("/transactions/#{transaction_id}/rollback", %{}) do
+      {:ok, %{status: 200, body: body}} -> {:ok, body}
+      {:ok, %{status: status, body: body}} -> {:error, {status, body}}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
   # Search Operations
 
   @doc """
@@ -237,6 +307,26 @@ def drift_status do
     end
   end
 
+  @doc """
+  Replace the drift thresholds, or a namespace's override when
+  `thresholds` has a `"namespace"` key.
+  """
+  def set_drift_thresholds(thresholds) do
+    {namespace, body} = Map.pop(thresholds, "namespace")
+
+    path =
+      case namespace do
+        nil -> "/drift/thresholds"
+        ns -> "/drift/thresholds?namespace=#{URI.encode_www_form(ns)}"
+      end
+
+    case put(path, body) do
+      {:ok, %{status: 200, body: body}} -> {:ok, body}
+      {:ok, %{status: status, body: body}} -> {:error, {status, body}}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
   @doc """
   Trigger normalization for an entity.
   """
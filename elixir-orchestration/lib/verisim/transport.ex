@@ -94,6 +94,22 @@ def create_hexad(input) do
     end
   end
 
+  @doc """
+  Create many hexads atomically: all of them, or none if one fails.
+
+  Returns `{:ok, ids}` with the new IDs in input order.
+  """
+  def create_hexads_batch(inputs) when is_list(inputs) do
+    if use_nif?() do
+      case NifBridge.create_hexads_batch(Jason.encode!(inputs)) do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)["ids"]}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.create_hexads_batch(inputs)
+    end
+  end
+
   @doc """
   Get a hexad by ID.
   """
@@ -122,6 +138,66 @@ def delete_hexad(entity_id) do
     end
   end
 
+  @doc """
+  Begin a transaction and return its status; `"id"` names it in later calls.
+
+  Writes are buffered in it with `execute_vql/2` and applied atomically by
+  `commit_transaction/1`.
+  """
+  def begin_transaction(options \\ %{}) do
+    if use_nif?() do
+      case NifBridge.begin_transaction(Jason.encode!(options)) do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.begin_transaction(options)
+    end
+  end
+
+  @doc """
+  Execute a VQL statement, buffering its writes in `transaction_id` when
+  one is given.
+  """
+  def execute_vql(query, transaction_id \\ nil) do
+    if use_nif?() do
+      case NifBridge.execute_vql(query, transaction_id || "") do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.execute_vql(query, transaction_id)
+    end
+  end
+
+  @doc """
+  Commit a transaction, applying its buffered writes atomically.
+  """
+  def commit_transaction(transaction_id) do
+    if use_nif?() do
+      case NifBridge.commit_transaction(transaction_id) do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.commit_transaction(transaction_id)
+    end
+  end
+
+  @doc """
+  Roll back a transaction, discarding its buffered writes.
+  """
+  def rollback_transaction(transaction_id) do
+    if use_nif?() do
+      case NifBridge.rollback_transaction(transaction_id) do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.rollback_transaction(transaction_id)
+    end
+  end
+
   @doc """
   Full-text search across the document modality.
   """
@@ -180,7 +256,7 @@ def get_drift_score(entity_id) do
   end
 
   @doc """
-  Trigger normalisation for a drifted entity.
+  Queue normalisation for a drifted entity.
   """
   def trigger_normalise(entity_id) do
     if use_nif?() do
@@ -189,7 +265,48 @@ def trigger_normalise(entity_id) do
         {:error, reason} -> {:error, reason}
       end
     else
-      RustClient.trigger_normalization(entity_id)
+      RustClient.normalize(entity_id)
+    end
+  end
+
+  @doc """
+  Get drift status, global and per namespace.
+  """
+  def get_drift_status do
+    if use_nif?() do
+      case NifBridge.get_drift_status() do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.drift_status()
+    end
+  end
+
+  @doc """
+  Replace the drift thresholds, or a namespace's override when
+  `thresholds` has a `"namespace"` key.
+  """
+  def set_thresholds(thresholds) when is_map(thresholds) do
+    if use_nif?() do
+      case NifBridge.set_thresholds(Jason.encode!(thresholds)) do
+        result when is_binary(result) -> {:ok, Jason.decode!(result)}
+        {:error, reason} -> {:error, reason}
+      end
+    else
+      RustClient.set_drift_thresholds(thresholds)
+    end
+  end
+
+  @doc """
+  Send `{:drift_event, json}` to `pid` for every drift detected until it
+  exits. Needs the NIF; over HTTP, poll `get_drift_status/0` instead.
+  """
+  def subscribe_drift_events(pid \\ self()) do
+    if use_nif?() do
+      NifBridge.subscribe_drift_events(pid)
+    else
+      {:error, :nif_not_loaded}
     end
   end
 end
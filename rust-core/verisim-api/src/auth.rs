@@ -5,26 +5,41 @@
 //!
 //! Supports two authentication methods:
 //! - **API Key**: Passed via `X-API-Key` header
-//! - **JWT Bearer Token**: Passed via `Authorization: Bearer <token>` header
+//! - **JWT Bearer Token**: Passed via `Authorization: Bearer <token>` header,
+//!   signed with the shared `jwt_secret` (HS256) or, with an
+//!   [`OidcConfig`](crate::oidc::OidcConfig), by an OpenID Connect provider
+//!
+//! API keys belong either to a client registered directly or to a
+//! [service account](crate::service_accounts), which may hold several.
+//! Over TLS, clients may instead authenticate with a
+//! [client certificate](crate::client_certs), the only credential accepted
+//! when it is configured as exclusive.
+//!
+//! Any of these credentials can be exchanged for a [session](crate::sessions)
+//! of short-lived access tokens (JWTs signed with the `jwt_secret`) and
+//! rotating refresh tokens.
 //!
 //! Rate limiting is per-client (identified by API key or IP address).
 
 use axum::{
     extract::{Request, State},
-    http::{header, StatusCode},
+    http::{header, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
+use verisim_provenance::{ActorRegistry, PrincipalKind};
 
 /// Authentication configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AuthConfig {
     /// Whether authentication is enabled. When disabled, all requests pass through.
     pub enabled: bool,
@@ -35,6 +50,19 @@ pub struct AuthConfig {
     /// JWT secret for HMAC-SHA256 verification (if using JWT).
     #[serde(skip_serializing)]
     pub jwt_secret: Option<String>,
+    /// OpenID Connect provider whose bearer tokens are accepted.
+    pub oidc: Option<crate::oidc::OidcConfig>,
+    /// Service accounts and their key hashes, loaded at startup.
+    pub service_accounts: Vec<crate::service_accounts::ServiceAccount>,
+    /// Seconds before a service account key expires that it is reported
+    /// as expiring in `/metrics`.
+    pub key_expiry_warning_secs: u64,
+    /// Client certificates accepted by `serve_tls`, and the principals and
+    /// roles their names map to.
+    pub client_certificates: Option<crate::client_certs::ClientCertConfig>,
+    /// Lifetimes of the tokens issued at `/auth/token`.
+    #[serde(default)]
+    pub sessions: crate::sessions::SessionConfig,
 }
 
 impl Default for AuthConfig {
@@ -44,6 +72,11 @@ fn default() -> Self {
             allow_public_health: true,
             rate_limit_per_minute: 0,
             jwt_secret: None,
+            oidc: None,
+            service_accounts: Vec::new(),
+            key_expiry_warning_secs: 7 * 24 * 3600,
+            client_certificates: None,
+            sessions: Default::default(),
         }
     }
 }
@@ -51,14 +84,84 @@ fn default() -> Self {
 /// Client identity extracted from an authenticated request.
 #[derive(Debug, Clone)]
 pub struct ClientIdentity {
-    /// The client identifier (API key hash or JWT subject).
+    /// The client identifier (API key hash, service account name, JWT
+    /// subject, qualified by its issuer for OpenID Connect tokens, or
+    /// certificate principal).
     pub id: String,
     /// Role assigned to this client.
     pub role: ClientRole,
+    /// How the client authenticated.
+    pub kind: PrincipalKind,
+    /// Human-readable name (API key label), if known.
+    pub display_name: Option<String>,
+    /// Attributes the identity provider asserts, read by entity policy
+    /// expressions as `principal.<name>`.
+    pub attributes: HashMap<String, String>,
+    /// The credential the client presented.
+    pub credential: Credential,
+}
+
+/// The credential a client authenticated with, which a session started
+/// from it is checked against on every refresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credential {
+    /// A registered API key, by hash.
+    ApiKey(String),
+    /// One key of a service account.
+    ServiceAccountKey { account: String, key_id: String },
+    /// An access token of a session this server issued, by session ID.
+    Session(String),
+    /// A token signed by the configured secret or an OpenID Connect
+    /// provider, valid until its `exp`, if it has one.
+    Token { expires_at: Option<DateTime<Utc>> },
+    /// A client certificate, checked by the TLS handshake.
+    Certificate,
+}
+
+impl Default for Credential {
+    fn default() -> Self {
+        Self::Token { expires_at: None }
+    }
 }
 
-/// Role-based access level.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// When a token whose `claims` are given expires
+pub(crate) fn token_expiry(claims: &serde_json::Value) -> Option<DateTime<Utc>> {
+    claims.get("exp").and_then(|v| v.as_i64()).and_then(|exp| DateTime::from_timestamp(exp, 0))
+}
+
+impl ClientIdentity {
+    /// Name of the client's principal, qualified by how it authenticated
+    /// so that credentials of different kinds never share one:
+    /// `api_key:<label>`, `token:<subject>` (`token:<issuer>/<subject>`
+    /// from an OpenID Connect provider), `service_account:<name>` or
+    /// `certificate:<principal>`.
+    pub fn principal_id(&self) -> String {
+        let name = match (self.kind, &self.display_name) {
+            (PrincipalKind::ApiKey, Some(label)) => label,
+            _ => &self.id,
+        };
+        format!("{}:{}", self.kind, name)
+    }
+
+    /// Who the client's store reads and writes are checked for by the
+    /// entity policy, named by [`principal_id`](Self::principal_id).
+    /// Admins are not restricted.  Besides its asserted attributes, the
+    /// principal carries its `role` and `kind`.
+    pub fn principal(&self) -> verisim_hexad::Principal {
+        let id = self.principal_id();
+        let mut principal = match self.role {
+            ClientRole::Admin => verisim_hexad::Principal::unrestricted(id),
+            _ => verisim_hexad::Principal::new(id),
+        };
+        principal.attributes = self.attributes.clone();
+        principal
+            .with_attribute("role", format!("{:?}", self.role).to_lowercase())
+            .with_attribute("kind", self.kind.to_string())
+    }
+}
+
+/// Role-based access level, ordered from least to most access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ClientRole {
     /// Read-only access to all endpoints.
     Reader,
@@ -121,6 +224,12 @@ pub fn validate(&self, plaintext_key: &str) -> Option<ApiKeyEntry> {
             .cloned()
     }
 
+    /// Whether the key with hash `key_hash` is registered and active.
+    pub fn is_active(&self, key_hash: &str) -> bool {
+        let keys = self.keys.lock().expect("key registry lock");
+        keys.get(key_hash).is_some_and(|entry| entry.active)
+    }
+
     /// Revoke an API key by its hash.
     pub fn revoke(&self, key_hash: &str) -> bool {
         let mut keys = self.keys.lock().expect("key registry lock");
@@ -210,28 +319,64 @@ pub struct AuthState {
     pub rate_limiter: RateLimiter,
     /// RBAC state for fine-grained authorization checks.
     pub rbac: crate::rbac::RbacState,
+    /// Principal → canonical actor IRI registry used for provenance attribution.
+    pub actors: ActorRegistry,
+    /// Credentials and scopes of federation peers.
+    pub peers: crate::peer_auth::PeerAuth,
+    /// Verifier for OpenID Connect tokens, when a provider is configured.
+    pub oidc: Option<Arc<crate::oidc::OidcVerifier>>,
+    /// Service accounts, whose keys are accepted alongside the registry's.
+    pub service_accounts: crate::service_accounts::ServiceAccountRegistry,
+    /// Sessions issued at `/auth/token`, and which of them are revoked.
+    pub sessions: crate::sessions::Sessions,
 }
 
 impl AuthState {
     /// Create auth state from config.
     pub fn new(config: AuthConfig) -> Self {
-        let rate_limiter = RateLimiter::new(config.rate_limit_per_minute);
-        Self {
-            config,
-            key_registry: ApiKeyRegistry::new(),
-            rate_limiter,
-            rbac: crate::rbac::RbacState::default(),
-        }
+        Self::with_rbac(config, crate::rbac::RbacState::default())
     }
 
     /// Create auth state from config with a custom RBAC policy.
     pub fn with_rbac(config: AuthConfig, rbac: crate::rbac::RbacState) -> Self {
         let rate_limiter = RateLimiter::new(config.rate_limit_per_minute);
+        let oidc = config.oidc.clone().map(|c| Arc::new(crate::oidc::OidcVerifier::new(c)));
+        let service_accounts = crate::service_accounts::ServiceAccountRegistry::new(config.service_accounts.clone());
+        let sessions = crate::sessions::Sessions::new(config.sessions.clone(), config.jwt_secret.clone());
         Self {
             config,
             key_registry: ApiKeyRegistry::new(),
             rate_limiter,
             rbac,
+            actors: ActorRegistry::default(),
+            peers: Default::default(),
+            oidc,
+            service_accounts,
+            sessions,
+        }
+    }
+
+    /// Whether `credential` would still authenticate a request: API keys
+    /// and service account keys must still be registered and active.
+    /// Tokens and certificates cannot be recalled, so stay valid.
+    pub fn credential_valid(&self, credential: &Credential) -> bool {
+        match credential {
+            Credential::ApiKey(hash) => self.key_registry.is_active(hash),
+            Credential::ServiceAccountKey { account, key_id } => {
+                self.service_accounts.key_active(account, key_id, chrono::Utc::now())
+            }
+            Credential::Session(_) => false,
+            Credential::Token { .. } | Credential::Certificate => true,
+        }
+    }
+
+    /// When `credential` stops authenticating of its own accord, which no
+    /// session started with it may outlive
+    pub fn credential_expires_at(&self, credential: &Credential) -> Option<DateTime<Utc>> {
+        match credential {
+            Credential::ServiceAccountKey { account, key_id } => self.service_accounts.key_expires_at(account, key_id),
+            Credential::Token { expires_at } => *expires_at,
+            Credential::ApiKey(_) | Credential::Session(_) | Credential::Certificate => None,
         }
     }
 }
@@ -252,23 +397,111 @@ pub struct AuthError {
 /// Axum middleware that performs authentication and rate limiting.
 ///
 /// This middleware:
-/// 1. Checks if auth is enabled (passes through if disabled)
-/// 2. Allows public health endpoints if configured
-/// 3. Extracts API key from `X-API-Key` header or JWT from `Authorization: Bearer`
-/// 4. Validates the credential against the key registry
-/// 5. Checks rate limits for the identified client
+/// 1. Admits federation peers presenting a token within its scope, and
+///    refuses peer-only endpoints to anyone else while peers are configured
+/// 2. Checks if auth is enabled (passes through if disabled)
+/// 3. Allows public health endpoints if configured, and the session
+///    refresh and revocation endpoints, whose tokens are checked there
+/// 4. Extracts API key from `X-API-Key` header, JWT from `Authorization: Bearer`
+///    or the names of the connection's client certificate
+/// 5. Validates the credential against the key registry, the JWT secret
+///    (refusing access tokens of revoked sessions), the OIDC provider or
+///    the client certificate mappings
+/// 6. Checks rate limits for the identified client
+/// 7. Resolves the client to its canonical actor and attaches both the
+///    [`ClientIdentity`] and [`ActorIdentity`](verisim_provenance::ActorIdentity)
+///    to the request extensions for downstream provenance attribution
 pub async fn auth_middleware(
     State(auth): State<AuthState>,
     request: Request,
     next: Next,
 ) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    admit(&auth, &method, &path, request, next).await
+}
+
+/// [`auth_middleware`] for a request judged as a `method` request on
+/// `path`: the REST request it stands for, when it is made over another
+/// protocol.
+pub(crate) async fn admit(
+    auth: &AuthState,
+    method: &Method,
+    path: &str,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // Federation peers authenticate with scoped tokens, whether or not
+    // client authentication is enabled.
+    if let Some(token) = request
+        .headers()
+        .get(crate::peer_auth::TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        let certificate = request
+            .extensions()
+            .get::<crate::peer_auth::PeerCertificate>()
+            .and_then(|c| c.0.clone());
+        let claims = match auth.peers.verify(token, certificate.as_deref()) {
+            Ok(claims) => claims,
+            Err(msg) => return denied(StatusCode::UNAUTHORIZED, msg),
+        };
+        if !claims.scope.covers(method, path) {
+            warn!(peer = %claims.peer, scope = ?claims.scope, path = %path, "Federation token out of scope");
+            return denied(
+                StatusCode::FORBIDDEN,
+                format!("Federation token for {:?} does not cover {}", claims.scope, path),
+            );
+        }
+        // Transaction calls run for the principal the coordinator acts for,
+        // and their VQL only inside the transaction they name.
+        let carried = match claims.scope {
+            crate::peer_auth::PeerScope::Transaction => {
+                match crate::peer_auth::carried_principal(request.headers()) {
+                    Ok(principal) => Some(principal),
+                    Err(msg) => return denied(StatusCode::FORBIDDEN, msg),
+                }
+            }
+            _ => None,
+        };
+        if carried.is_some()
+            && path == "/vql/execute"
+            && !request.headers().contains_key(crate::peer_auth::TRANSACTION_HEADER)
+        {
+            return denied(
+                StatusCode::FORBIDDEN,
+                "Federation token for Transaction covers VQL in a transaction only".to_string(),
+            );
+        }
+        let identity = ClientIdentity {
+            id: format!("peer:{}", claims.peer),
+            role: ClientRole::Writer,
+            kind: PrincipalKind::Token,
+            display_name: Some(claims.peer),
+            attributes: HashMap::new(),
+            credential: Credential::Token {
+                expires_at: DateTime::from_timestamp(claims.exp, 0),
+            },
+        };
+        let actor = auth
+            .actors
+            .resolve(identity.kind, &identity.id, identity.display_name.as_deref())
+            .await;
+        // Peers otherwise replicate and sync whole stores
+        let principal = carried.unwrap_or_else(|| verisim_hexad::Principal::unrestricted(identity.id.clone()));
+        request.extensions_mut().insert(identity);
+        request.extensions_mut().insert(actor);
+        return verisim_hexad::security::scope(principal, next.run(request)).await;
+    }
+    if auth.peers.required_for(method, path) {
+        return denied(StatusCode::UNAUTHORIZED, "Federation token required".to_string());
+    }
+
     // If auth is disabled, pass through.
     if !auth.config.enabled {
         return next.run(request).await;
     }
 
-    let path = request.uri().path().to_string();
-
     // Allow public health endpoints without auth.
     if auth.config.allow_public_health
         && (path == "/health" || path == "/ready" || path == "/metrics")
@@ -276,8 +509,18 @@ pub async fn auth_middleware(
         return next.run(request).await;
     }
 
+    // Refresh and revocation are authenticated by the session token in the
+    // body.
+    if path == "/auth/refresh" || path == "/auth/revoke" {
+        return next.run(request).await;
+    }
+
     // Extract credential.
-    let identity = match extract_identity(&request, &auth) {
+    let certificate = request
+        .extensions()
+        .get::<crate::client_certs::ClientCertificate>()
+        .and_then(|c| c.0.clone());
+    let identity = match extract_identity(request.headers(), certificate.as_deref(), auth).await {
         Ok(id) => id,
         Err(response) => return response,
     };
@@ -302,12 +545,13 @@ pub async fn auth_middleware(
     }
 
     // RBAC authorization check.
-    let method = request.method().clone();
+    let namespace = auth.rbac.namespace_for(&identity, path).await;
     if let Err(authz_err) = crate::rbac::check_authorization(
         &identity,
-        &path,
-        &method,
+        path,
+        method,
         &auth.rbac,
+        namespace.as_deref(),
     ) {
         warn!(
             client = %identity.id,
@@ -319,14 +563,54 @@ pub async fn auth_middleware(
         return authz_err.into_response();
     }
 
-    next.run(request).await
+    let actor = auth
+        .actors
+        .resolve(identity.kind, &identity.id, identity.display_name.as_deref())
+        .await;
+    let principal = identity
+        .principal()
+        .with_request("method", method.as_str())
+        .with_request("path", path);
+    request.extensions_mut().insert(identity);
+    request.extensions_mut().insert(actor);
+
+    verisim_hexad::security::scope(principal, next.run(request)).await
+}
+
+/// An authentication failure response.
+fn denied(status: StatusCode, error: String) -> Response {
+    (
+        status,
+        Json(AuthError {
+            error,
+            code: status.as_u16(),
+        }),
+    )
+        .into_response()
 }
 
-/// Extract client identity from request headers.
-fn extract_identity(request: &Request, auth: &AuthState) -> Result<ClientIdentity, Response> {
+/// Extract client identity from request headers, or from the names of the
+/// client certificate the connection presented.
+async fn extract_identity(
+    headers: &header::HeaderMap,
+    certificate: Option<&[String]>,
+    auth: &AuthState,
+) -> Result<ClientIdentity, Response> {
+    let certificates = auth.config.client_certificates.as_ref();
+    if certificates.is_some_and(|c| c.exclusive) {
+        if headers.contains_key("x-api-key") || headers.contains_key(header::AUTHORIZATION) {
+            return Err(denied(
+                StatusCode::UNAUTHORIZED,
+                "Only client certificates are accepted".to_string(),
+            ));
+        }
+        if certificate.is_none() {
+            return Err(denied(StatusCode::UNAUTHORIZED, "Client certificate required".to_string()));
+        }
+    }
+
     // Try X-API-Key header first.
-    if let Some(api_key) = request
-        .headers()
+    if let Some(api_key) = headers
         .get("x-api-key")
         .and_then(|v| v.to_str().ok())
     {
@@ -335,6 +619,24 @@ fn extract_identity(request: &Request, auth: &AuthState) -> Result<ClientIdentit
             return Ok(ClientIdentity {
                 id: entry.key_hash.clone(),
                 role: entry.role,
+                kind: PrincipalKind::ApiKey,
+                display_name: Some(entry.label.clone()),
+                attributes: HashMap::new(),
+                credential: Credential::ApiKey(entry.key_hash.clone()),
+            });
+        }
+        if let Some((account, key_id)) = auth.service_accounts.authenticate(api_key) {
+            info!(account = %account.name, key = %key_id, role = ?account.role, "Service account authenticated");
+            return Ok(ClientIdentity {
+                id: account.name.clone(),
+                role: account.role,
+                kind: PrincipalKind::ServiceAccount,
+                display_name: Some(account.name.clone()),
+                attributes: HashMap::new(),
+                credential: Credential::ServiceAccountKey {
+                    account: account.name,
+                    key_id,
+                },
             });
         }
         return Err((
@@ -348,13 +650,24 @@ fn extract_identity(request: &Request, auth: &AuthState) -> Result<ClientIdentit
     }
 
     // Try Authorization: Bearer header.
-    if let Some(auth_header) = request
-        .headers()
+    if let Some(auth_header) = headers
         .get(header::AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
     {
         if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            match validate_jwt(token, &auth.config) {
+            let validated = match &auth.oidc {
+                Some(oidc) if jwt_algorithm(token).is_some_and(|alg| crate::oidc::is_oidc_algorithm(&alg)) => {
+                    oidc.verify(token).await
+                }
+                _ => validate_jwt(token, &auth.config).and_then(|identity| {
+                    if auth.sessions.revoked(token) {
+                        Err("Session revoked".to_string())
+                    } else {
+                        Ok(identity)
+                    }
+                }),
+            };
+            match validated {
                 Ok(identity) => {
                     info!(subject = %identity.id, role = ?identity.role, "JWT authenticated");
                     return Ok(identity);
@@ -373,6 +686,17 @@ fn extract_identity(request: &Request, auth: &AuthState) -> Result<ClientIdentit
         }
     }
 
+    // Fall back to the client certificate.
+    if let (Some(config), Some(names)) = (certificates, certificate) {
+        return match config.identify(names) {
+            Ok(identity) => {
+                info!(principal = %identity.id, role = ?identity.role, "Client certificate authenticated");
+                Ok(identity)
+            }
+            Err(msg) => Err(denied(StatusCode::UNAUTHORIZED, msg)),
+        };
+    }
+
     // No credentials provided.
     Err((
         StatusCode::UNAUTHORIZED,
@@ -385,17 +709,8 @@ fn extract_identity(request: &Request, auth: &AuthState) -> Result<ClientIdentit
         .into_response())
 }
 
-/// Validate a JWT token (HMAC-SHA256).
-///
-/// VeriSimDB uses a minimal JWT implementation: we only verify the signature
-/// and extract the `sub` (subject) and `role` claims. Expiration is checked
-/// via the `exp` claim.
-fn validate_jwt(token: &str, config: &AuthConfig) -> Result<ClientIdentity, String> {
-    let secret = config
-        .jwt_secret
-        .as_deref()
-        .ok_or_else(|| "JWT authentication not configured".to_string())?;
-
+/// Check an HS256 JWT's signature with `secret`, returning its payload.
+pub(crate) fn verify_jwt(token: &str, secret: &str) -> Result<Vec<u8>, String> {
     let parts: Vec<&str> = token.split('.').collect();
     if parts.len() != 3 {
         return Err("Invalid JWT format".to_string());
@@ -414,6 +729,22 @@ fn validate_jwt(token: &str, config: &AuthConfig) -> Result<ClientIdentity, Stri
     if expected_sig != actual_sig {
         return Err("Invalid JWT signature".to_string());
     }
+    Ok(payload_bytes)
+}
+
+/// Validate a JWT token (HMAC-SHA256).
+///
+/// VeriSimDB uses a minimal JWT implementation: we only verify the signature
+/// and extract the `sub` (subject) and `role` claims. Expiration is checked
+/// via the `exp` claim.  Session access tokens also carry the `kind` and
+/// `name` of the credential the session was started with, and are rebuilt
+/// into that same identity.
+pub(crate) fn validate_jwt(token: &str, config: &AuthConfig) -> Result<ClientIdentity, String> {
+    let secret = config
+        .jwt_secret
+        .as_deref()
+        .ok_or_else(|| "JWT authentication not configured".to_string())?;
+    let payload_bytes = verify_jwt(token, secret)?;
 
     // Parse claims.
     let claims: serde_json::Value = serde_json::from_slice(&payload_bytes)
@@ -446,14 +777,62 @@ fn validate_jwt(token: &str, config: &AuthConfig) -> Result<ClientIdentity, Stri
         })
         .unwrap_or(ClientRole::Reader);
 
+    // The credential a session token was issued for; other tokens are
+    // their own.
+    let kind = claims
+        .get("kind")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or(PrincipalKind::Token);
+    let display_name = claims.get("name").and_then(|v| v.as_str()).map(str::to_string);
+
+    // Attributes of the credential a session token was issued for.
+    let attributes = claims
+        .get("attributes")
+        .and_then(|v| v.as_object())
+        .map(|attributes| {
+            attributes
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Session tokens are answerable to their session.
+    let credential = claims
+        .get("sid")
+        .and_then(|v| v.as_str())
+        .map_or(Credential::Token { expires_at: token_expiry(&claims) }, |sid| {
+            Credential::Session(sid.to_string())
+        });
+
     Ok(ClientIdentity {
         id: subject,
         role,
+        kind,
+        display_name,
+        attributes,
+        credential,
     })
 }
 
+/// Sign `claims` as an HS256 JWT that [`validate_jwt`] accepts.
+pub(crate) fn sign_jwt(claims: &serde_json::Value, secret: &str) -> String {
+    let header = base64url_encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = base64url_encode(claims.to_string().as_bytes());
+    let signing_input = format!("{}.{}", header, payload);
+    let signature = hmac_sha256(signing_input.as_bytes(), secret.as_bytes());
+    format!("{}.{}", signing_input, base64url_encode(&signature))
+}
+
+/// The `alg` named in a JWT's header.
+fn jwt_algorithm(token: &str) -> Option<String> {
+    let header = base64url_decode(token.split('.').next()?).ok()?;
+    let header: serde_json::Value = serde_json::from_slice(&header).ok()?;
+    header.get("alg")?.as_str().map(str::to_string)
+}
+
 /// Hash an API key with SHA-256 for storage.
-fn hash_key(key: &str) -> String {
+pub(crate) fn hash_key(key: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(key.as_bytes());
     let hash = hasher.finalize();
@@ -461,7 +840,7 @@ fn hash_key(key: &str) -> String {
 }
 
 /// Compute HMAC-SHA256.
-fn hmac_sha256(data: &[u8], key: &[u8]) -> Vec<u8> {
+pub(crate) fn hmac_sha256(data: &[u8], key: &[u8]) -> Vec<u8> {
     // HMAC: H((key XOR opad) || H((key XOR ipad) || message))
     let block_size = 64;
     let mut key_block = vec![0u8; block_size];
@@ -495,8 +874,36 @@ fn hmac_sha256(data: &[u8], key: &[u8]) -> Vec<u8> {
     outer_hasher.finalize().to_vec()
 }
 
+/// Base64url encode (RFC 4648 without padding).
+pub(crate) fn base64url_encode(input: &[u8]) -> String {
+    const CHARSET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::new();
+    let mut i = 0;
+    while i < input.len() {
+        let a = input[i];
+        let b = if i + 1 < input.len() { input[i + 1] } else { 0 };
+        let c = if i + 2 < input.len() { input[i + 2] } else { 0 };
+
+        result.push(CHARSET[(a >> 2) as usize] as char);
+        result.push(CHARSET[((a & 0x03) << 4 | b >> 4) as usize] as char);
+
+        if i + 1 < input.len() {
+            result.push(CHARSET[((b & 0x0f) << 2 | c >> 6) as usize] as char);
+        }
+        if i + 2 < input.len() {
+            result.push(CHARSET[(c & 0x3f) as usize] as char);
+        }
+        i += 3;
+    }
+
+    // Convert to URL-safe base64 (no padding).
+    result.replace('+', "-").replace('/', "_")
+}
+
 /// Base64url decode (RFC 4648 without padding).
-fn base64url_decode(input: &str) -> Result<Vec<u8>, &'static str> {
+pub(crate) fn base64url_decode(input: &str) -> Result<Vec<u8>, &'static str> {
     // Add padding if needed.
     let padded = match input.len() % 4 {
         2 => format!("{input}=="),
@@ -685,6 +1092,7 @@ fn test_jwt_validation() {
             allow_public_health: true,
             rate_limit_per_minute: 0,
             jwt_secret: Some("test-secret".to_string()),
+            ..Default::default()
         };
 
         // Create JWT: header.payload.signature
@@ -712,6 +1120,7 @@ fn test_jwt_expired() {
             allow_public_health: true,
             rate_limit_per_minute: 0,
             jwt_secret: Some("test-secret".to_string()),
+            ..Default::default()
         };
 
         let header = base64url_encode(b"{\"alg\":\"HS256\",\"typ\":\"JWT\"}");
@@ -733,6 +1142,7 @@ fn test_jwt_invalid_signature() {
             allow_public_health: true,
             rate_limit_per_minute: 0,
             jwt_secret: Some("correct-secret".to_string()),
+            ..Default::default()
         };
 
         let header = base64url_encode(b"{\"alg\":\"HS256\",\"typ\":\"JWT\"}");
@@ -747,32 +1157,4 @@ fn test_jwt_invalid_signature() {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("signature"));
     }
-
-    /// Base64url encode for test helpers.
-    fn base64url_encode(input: &[u8]) -> String {
-        const CHARSET: &[u8] =
-            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-
-        let mut result = String::new();
-        let mut i = 0;
-        while i < input.len() {
-            let a = input[i];
-            let b = if i + 1 < input.len() { input[i + 1] } else { 0 };
-            let c = if i + 2 < input.len() { input[i + 2] } else { 0 };
-
-            result.push(CHARSET[(a >> 2) as usize] as char);
-            result.push(CHARSET[((a & 0x03) << 4 | b >> 4) as usize] as char);
-
-            if i + 1 < input.len() {
-                result.push(CHARSET[((b & 0x0f) << 2 | c >> 6) as usize] as char);
-            }
-            if i + 2 < input.len() {
-                result.push(CHARSET[(c & 0x3f) as usize] as char);
-            }
-            i += 3;
-        }
-
-        // Convert to URL-safe base64 (no padding).
-        result.replace('+', "-").replace('/', "_")
-    }
 }
@@ -0,0 +1,455 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Authorization audit stream
+//!
+//! Security reviews need to know who was allowed or refused what, and by
+//! which policy, without scraping request logs.  Every RBAC decision the
+//! authentication middleware makes on a route, and every allow and deny of
+//! the entity policy in the hexad store, is appended to a dedicated series
+//! of a time-series store, queried through `GET /admin/audit/authz`.
+//!
+//! Decisions are recorded on the request's task and appended by a
+//! background task, so a slow or failing store never holds up a request; an
+//! append that fails is logged and the decision dropped.  When the task
+//! falls [`PENDING_DECISIONS`] behind, further decisions are dropped and
+//! counted in `/metrics`.  The same task removes decisions older than the
+//! retention period every [`PRUNE_INTERVAL`].
+//!
+//! Queries return at most a page of decisions, oldest first, with a cursor
+//! to pass as `after` for the next page.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+use verisim_hexad::{HexadListener, PolicyDecision};
+use verisim_temporal::{TimePoint, TimeRange, TemporalError, TimeSeriesStore};
+
+use crate::rbac::{AccessDecision, AuditEntry};
+
+/// Series id under which decisions are stored
+pub const SERIES: &str = "authz";
+
+/// Decisions waiting to be appended before further ones are dropped
+pub const PENDING_DECISIONS: usize = 4096;
+
+/// How often decisions past the retention period are removed
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// The policy that made a decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// Role-based access to a route
+    Rbac,
+    /// Entity policy of the hexad store
+    EntityPolicy,
+}
+
+/// One recorded authorization decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthzRecord {
+    /// Principal the decision was made for
+    pub principal: String,
+    /// Role of the principal, for route decisions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+    /// Permission (`read`, `write`, `admin`) or action (`read`, `write`)
+    /// judged
+    pub action: String,
+    pub decision: AccessDecision,
+    pub source: DecisionSource,
+    /// Grant or rule that allowed the action, or why it was denied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched: Option<String>,
+}
+
+impl From<&AuditEntry> for AuthzRecord {
+    fn from(entry: &AuditEntry) -> Self {
+        Self {
+            principal: entry.principal.clone(),
+            role: Some(entry.client_role.clone()),
+            method: Some(entry.method.clone()),
+            route: Some(entry.resource_path.clone()),
+            entity: entry.entity.clone(),
+            action: entry.required_permission.to_string(),
+            decision: entry.decision,
+            source: DecisionSource::Rbac,
+            matched: entry.reason.clone(),
+        }
+    }
+}
+
+impl From<&PolicyDecision> for AuthzRecord {
+    fn from(decision: &PolicyDecision) -> Self {
+        Self {
+            principal: decision.principal.clone(),
+            role: None,
+            method: decision.request.get("method").cloned(),
+            route: decision.request.get("path").cloned(),
+            entity: Some(decision.entity.clone()),
+            action: decision.action.to_string(),
+            decision: if decision.allowed() {
+                AccessDecision::Allowed
+            } else {
+                AccessDecision::Denied
+            },
+            source: DecisionSource::EntityPolicy,
+            matched: decision.rule.as_ref().map(ToString::to_string),
+        }
+    }
+}
+
+/// A decision with its time, as returned by audit queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthzEvent {
+    pub time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub record: AuthzRecord,
+}
+
+/// Where a page of decisions ended: its last decision's time, and how many
+/// decisions at that time the pages so far returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthzCursor {
+    time: DateTime<Utc>,
+    seen: usize,
+}
+
+impl AuthzCursor {
+    /// Parse a cursor returned as `next`
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (nanos, seen) = cursor.split_once('.')?;
+        Some(Self {
+            time: DateTime::from_timestamp_nanos(nanos.parse().ok()?),
+            seen: seen.parse().ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for AuthzCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Stored decisions always have nanosecond timestamps.
+        write!(f, "{}.{}", self.time.timestamp_nanos_opt().unwrap_or_default(), self.seen)
+    }
+}
+
+/// One page of decisions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuthzPage {
+    pub events: Vec<AuthzEvent>,
+    /// Cursor to pass as `after` for the next page, if there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+/// Time-series store holding the audit stream
+pub type AuthzAuditStore = dyn TimeSeriesStore<Value = AuthzRecord>;
+
+/// Which decisions a query returns; every field left unset matches all
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthzFilter {
+    pub principal: Option<String>,
+    /// `allowed` or `denied`, in any case
+    pub decision: Option<String>,
+    /// `rbac` or `entity_policy`
+    pub source: Option<DecisionSource>,
+    /// Route prefix, such as `/hexads`
+    pub route: Option<String>,
+    pub entity: Option<String>,
+}
+
+impl AuthzFilter {
+    fn matches(&self, record: &AuthzRecord) -> bool {
+        self.principal.as_ref().is_none_or(|p| *p == record.principal)
+            && self
+                .decision
+                .as_ref()
+                .is_none_or(|d| d.eq_ignore_ascii_case(&record.decision.to_string()))
+            && self.source.is_none_or(|s| s == record.source)
+            && self
+                .route
+                .as_ref()
+                .is_none_or(|r| record.route.as_ref().is_some_and(|route| route.starts_with(r.as_str())))
+            && self.entity.as_ref().is_none_or(|e| record.entity.as_ref() == Some(e))
+    }
+}
+
+enum Message {
+    Record(Box<TimePoint<AuthzRecord>>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Recorder of authorization decisions into the audit stream
+#[derive(Clone)]
+pub struct AuthzAudit {
+    store: Arc<AuthzAuditStore>,
+    sender: mpsc::Sender<Message>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for AuthzAudit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthzAudit").finish_non_exhaustive()
+    }
+}
+
+impl AuthzAudit {
+    /// Record into `store`, appending from a background task that also
+    /// removes decisions older than `retention`
+    pub fn spawn(store: Arc<AuthzAuditStore>, retention: chrono::Duration) -> Self {
+        let (sender, mut receiver) = mpsc::channel(PENDING_DECISIONS);
+        let writer = store.clone();
+        tokio::spawn(async move {
+            let prune = || async {
+                let Some(before) = Utc::now().checked_sub_signed(retention) else {
+                    return;
+                };
+                if let Err(e) = writer.prune(SERIES, before).await {
+                    warn!(error = %e, "Authorization audit pruning failed");
+                }
+            };
+            prune().await;
+            let start = tokio::time::Instant::now() + PRUNE_INTERVAL;
+            let mut interval = tokio::time::interval_at(start, PRUNE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => prune().await,
+                    message = receiver.recv() => match message {
+                        Some(Message::Record(point)) => {
+                            if let Err(e) = writer.append(SERIES, *point).await {
+                                warn!(error = %e, "Authorization audit append failed");
+                            }
+                        }
+                        Some(Message::Flush(done)) => {
+                            let _ = done.send(());
+                        }
+                        None => break,
+                    },
+                }
+            }
+        });
+        Self {
+            store,
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record a decision made now, dropping it if the writer is too far
+    /// behind
+    pub fn record(&self, record: AuthzRecord) {
+        let principal = record.principal.clone();
+        let decision = record.decision.to_string().to_lowercase();
+        let point = TimePoint::now(record)
+            .with_label("principal", principal)
+            .with_label("decision", decision);
+        // Otherwise only fails once the runtime is shutting down.
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.sender.try_send(Message::Record(Box::new(point))) {
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                warn!("Authorization audit writer is behind; dropping decisions");
+            }
+        }
+    }
+
+    /// Decisions dropped because the writer was behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every decision recorded so far has been appended
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(Message::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+
+    /// At most `limit` decisions within `range` matching `filter`, oldest
+    /// first, starting after the page `after` ended
+    pub async fn query(
+        &self,
+        range: &TimeRange,
+        filter: &AuthzFilter,
+        after: Option<AuthzCursor>,
+        limit: usize,
+    ) -> Result<AuthzPage, TemporalError> {
+        self.flush().await;
+        let after = after.filter(|cursor| cursor.time >= range.start);
+        let start = after.map_or(range.start, |cursor| cursor.time);
+        if start >= range.end {
+            return Ok(AuthzPage::default());
+        }
+        let range = TimeRange { start, end: range.end };
+        let mut events: Vec<AuthzEvent> = self
+            .store
+            .query(SERIES, &range)
+            .await?
+            .into_iter()
+            .filter(|point| filter.matches(&point.value))
+            .map(|point| AuthzEvent {
+                time: point.time,
+                record: point.value,
+            })
+            .collect();
+        // Stable, so decisions at the same time keep the store's order.
+        events.sort_by_key(|e| e.time);
+
+        let seen = after.map_or(0, |cursor| cursor.seen);
+        let skipped = events.iter().take(seen).take_while(|e| e.time == start).count();
+        events.drain(..skipped);
+        let more = events.len() > limit;
+        events.truncate(limit);
+        let next = match events.last() {
+            Some(last) if more => {
+                let mut seen = events.iter().rev().take_while(|e| e.time == last.time).count();
+                if last.time == start {
+                    seen += skipped;
+                }
+                Some(AuthzCursor { time: last.time, seen }.to_string())
+            }
+            _ => None,
+        };
+        Ok(AuthzPage { events, next })
+    }
+}
+
+/// Records the hexad store's entity policy decisions
+pub struct AuthzAuditListener(pub AuthzAudit);
+
+impl HexadListener for AuthzAuditListener {
+    fn name(&self) -> &str {
+        "authz-audit"
+    }
+
+    fn on_decision(&self, decision: &PolicyDecision) {
+        self.0.record(decision.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rbac::Permission;
+    use verisim_hexad::{Action, PolicyRule};
+    use verisim_temporal::InMemoryTimeSeriesStore;
+
+    fn spawn() -> AuthzAudit {
+        AuthzAudit::spawn(Arc::new(InMemoryTimeSeriesStore::new()), chrono::Duration::days(1))
+    }
+
+    fn record(principal: &str) -> AuthzRecord {
+        AuthzRecord {
+            principal: principal.to_string(),
+            role: None,
+            method: None,
+            route: None,
+            entity: None,
+            action: "read".to_string(),
+            decision: AccessDecision::Allowed,
+            source: DecisionSource::Rbac,
+            matched: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decisions_are_recorded_and_filtered() {
+        let audit = spawn();
+        audit.record(AuthzRecord::from(&AuditEntry {
+            timestamp: 0,
+            client_id: "7c3a".to_string(),
+            principal: "alice".to_string(),
+            client_role: "reader".to_string(),
+            resource_path: "/hexads/h1".to_string(),
+            method: "DELETE".to_string(),
+            required_permission: Permission::Write,
+            decision: AccessDecision::Denied,
+            reason: Some("Role 'reader' does not have 'write' permission".to_string()),
+            entity: Some("h1".to_string()),
+        }));
+        AuthzAuditListener(audit.clone()).on_decision(&PolicyDecision {
+            principal: "bob".to_string(),
+            action: Action::Read,
+            entity: "h2".to_string(),
+            rule: Some(PolicyRule::Owner { field: "owner".to_string() }),
+            request: [("path".to_string(), "/hexads/h2".to_string())].into(),
+        });
+
+        let range = TimeRange::last(chrono::Duration::minutes(1));
+        let all = audit.query(&range, &AuthzFilter::default(), None, 100).await.unwrap().events;
+        assert_eq!(all.len(), 2);
+
+        let denied = AuthzFilter {
+            decision: Some("denied".to_string()),
+            ..Default::default()
+        };
+        let denied = audit.query(&range, &denied, None, 100).await.unwrap().events;
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].record.principal, "alice");
+        assert_eq!(denied[0].record.entity.as_deref(), Some("h1"));
+        assert_eq!(denied[0].record.action, "write");
+
+        let policy = AuthzFilter {
+            source: Some(DecisionSource::EntityPolicy),
+            route: Some("/hexads".to_string()),
+            ..Default::default()
+        };
+        let policy = audit.query(&range, &policy, None, 100).await.unwrap().events;
+        assert_eq!(policy.len(), 1);
+        assert_eq!(policy[0].record.matched.as_deref(), Some("owner(owner)"));
+        assert_eq!(policy[0].record.decision, AccessDecision::Allowed);
+    }
+
+    #[tokio::test]
+    async fn test_queries_are_paged_by_cursor() {
+        let audit = spawn();
+        let store = audit.store.clone();
+        // Several decisions at the same time must not be split or repeated
+        // across pages
+        let now = Utc::now();
+        for (i, offset) in [0, 1, 1, 1, 2].into_iter().enumerate() {
+            let time = now - chrono::Duration::seconds(10 - offset);
+            store.append(SERIES, TimePoint::new(time, record(&format!("p{i}")))).await.unwrap();
+        }
+
+        let range = TimeRange::last(chrono::Duration::minutes(1));
+        let mut principals = Vec::new();
+        let mut after = None;
+        loop {
+            let page = audit.query(&range, &AuthzFilter::default(), after, 2).await.unwrap();
+            assert!(page.events.len() <= 2);
+            principals.extend(page.events.into_iter().map(|e| e.record.principal));
+            match page.next {
+                Some(next) => after = Some(AuthzCursor::parse(&next).unwrap()),
+                None => break,
+            }
+        }
+        assert_eq!(principals, ["p0", "p1", "p2", "p3", "p4"]);
+        assert!(AuthzCursor::parse("yesterday").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decisions_past_retention_are_removed() {
+        let store: Arc<AuthzAuditStore> = Arc::new(InMemoryTimeSeriesStore::new());
+        let old = Utc::now() - chrono::Duration::hours(2);
+        store.append(SERIES, TimePoint::new(old, record("old"))).await.unwrap();
+        // The first pruning runs as the writer starts
+        let audit = AuthzAudit::spawn(store, chrono::Duration::hours(1));
+        audit.record(record("new"));
+
+        let range = TimeRange::last(chrono::Duration::days(1));
+        let page = audit.query(&range, &AuthzFilter::default(), None, 10).await.unwrap();
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.events[0].record.principal, "new");
+        assert_eq!(audit.dropped(), 0);
+    }
+}
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Client certificate authentication.
+//!
+//! Deployments that forbid bearer credentials can authenticate clients by
+//! the certificate they present to `serve_tls` instead.  With a
+//! [`ClientCertConfig`], the server asks every client for a certificate and
+//! keeps the subject alternative names — DNS names and URIs, such as SPIFFE
+//! IDs — of those signed by the client CA.  Each request on the connection
+//! carries them as a [`ClientCertificate`], and the auth middleware maps
+//! them to a principal and role.
+//!
+//! ## Mappings
+//!
+//! A mapping's `san` matches a name exactly, as a suffix when it starts
+//! with `*.` (`*.svc.example.com`), or as a prefix when it ends with `*`
+//! (`spiffe://example.org/ns/prod/*`).  The first mapping matching any of
+//! the certificate's names wins: its `principal`, or else the matched name,
+//! is who RBAC, provenance and the entity policy see.  A certificate no
+//! mapping matches is given `default_role` under its first name, and
+//! refused when there is none.
+//!
+//! With `exclusive`, certificates are the only credential: API keys and
+//! bearer tokens are refused.  Otherwise they are still accepted and take
+//! precedence, and a request carrying neither falls back to its
+//! certificate.
+
+use std::sync::Arc;
+
+use rustls::pki_types::{CertificateDer, UnixTime};
+use rustls::server::danger::ClientCertVerifier;
+use rustls::RootCertStore;
+use serde::{Deserialize, Serialize};
+use verisim_provenance::PrincipalKind;
+
+use crate::auth::{ClientIdentity, ClientRole};
+use crate::ApiError;
+
+/// Client certificate authentication settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCertConfig {
+    /// PEM bundle of the CAs whose client certificates are accepted.
+    pub ca_path: String,
+    /// Whether certificates are the only credential, refusing API keys and
+    /// bearer tokens.
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Principals and roles by subject alternative name; the first match
+    /// wins.
+    #[serde(default)]
+    pub mappings: Vec<SanMapping>,
+    /// Role granted when no mapping matches; `None` refuses such
+    /// certificates.
+    #[serde(default)]
+    pub default_role: Option<ClientRole>,
+}
+
+/// Principal and role for certificates with a matching name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanMapping {
+    /// Name to match: exact, `*.`-prefixed for a DNS suffix, or
+    /// `*`-suffixed for a prefix.
+    pub san: String,
+    /// Principal the certificate authenticates as; the matched name when
+    /// not given.
+    #[serde(default)]
+    pub principal: Option<String>,
+    /// Role granted.
+    pub role: ClientRole,
+}
+
+impl SanMapping {
+    fn matches(&self, name: &str) -> bool {
+        if let Some(suffix) = self.san.strip_prefix("*.") {
+            return name
+                .strip_suffix(suffix)
+                .is_some_and(|host| host.len() > 1 && host.ends_with('.'));
+        }
+        match self.san.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == self.san,
+        }
+    }
+}
+
+impl ClientCertConfig {
+    /// The identity a certificate with `names` authenticates as.
+    pub fn identify(&self, names: &[String]) -> Result<ClientIdentity, String> {
+        let matched = self
+            .mappings
+            .iter()
+            .find_map(|m| names.iter().find(|name| m.matches(name)).map(|name| (m, name)));
+        let (principal, role) = match matched {
+            Some((mapping, name)) => (mapping.principal.clone().unwrap_or_else(|| name.clone()), mapping.role),
+            None => match (names.first(), self.default_role) {
+                (Some(name), Some(role)) => (name.clone(), role),
+                _ => return Err("Client certificate is not mapped to a principal".to_string()),
+            },
+        };
+        Ok(ClientIdentity {
+            id: principal.clone(),
+            role,
+            kind: PrincipalKind::Certificate,
+            display_name: Some(principal),
+            attributes: Default::default(),
+            credential: crate::auth::Credential::Certificate,
+        })
+    }
+}
+
+/// Subject alternative names of the client certificate a connection
+/// presented, when the client CA signed it, added to each of its requests
+/// by [`PeerCertAcceptor`](crate::peer_auth::PeerCertAcceptor)
+#[derive(Debug, Clone)]
+pub struct ClientCertificate(pub Option<Vec<String>>);
+
+/// The client CA, checking the certificates clients present
+#[derive(Debug, Clone)]
+pub struct ClientCa {
+    verifier: Arc<dyn ClientCertVerifier>,
+}
+
+impl ClientCa {
+    /// Trust the CAs in the PEM bundle at `path`.
+    pub fn load(path: &str) -> Result<Self, ApiError> {
+        Self::new(crate::peer_auth::load_roots(path)?).map_err(|e| crate::peer_auth::tls_error(path, e))
+    }
+
+    pub fn new(roots: RootCertStore) -> Result<Self, rustls::server::VerifierBuilderError> {
+        let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+            Arc::new(roots),
+            crate::peer_auth::provider(),
+        )
+        .allow_unauthenticated()
+        .build()?;
+        Ok(Self { verifier })
+    }
+
+    /// Verifier for the TLS handshake
+    pub fn verifier(&self) -> Arc<dyn ClientCertVerifier> {
+        self.verifier.clone()
+    }
+
+    /// DNS and URI names of the presented chain's certificate, if the
+    /// client CA signed it
+    pub fn names(&self, chain: &[CertificateDer<'_>]) -> Option<Vec<String>> {
+        let (end_entity, intermediates) = chain.split_first()?;
+        // The handshake also accepts peers' certificates, so check this one
+        // was the client CA's
+        self.verifier
+            .verify_client_cert(end_entity, intermediates, UnixTime::now())
+            .ok()?;
+        let cert = webpki::EndEntityCert::try_from(end_entity).ok()?;
+        Some(
+            cert.valid_dns_names()
+                .chain(cert.valid_uri_names())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::pem::PemObject;
+
+    /// Client CA, and a certificate it signed for
+    /// `DNS:ingest.svc.example.com` and `URI:spiffe://example.org/ns/prod/ingest`
+    const CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBrTCCAVOgAwIBAgIUchxlzsFuQ/9sctQE8TIBu7O2k/IwCgYIKoZIzj0EAwIw
+IzEhMB8GA1UEAwwYVmVyaVNpbURCIFRlc3QgQ2xpZW50IENBMCAXDTI2MTAxNTIw
+NTgwNFoYDzIxMjYwOTIxMjA1ODA0WjAjMSEwHwYDVQQDDBhWZXJpU2ltREIgVGVz
+dCBDbGllbnQgQ0EwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARQUycPVsWBboua
+3ltsId8cTkoQ45TsRm6XNE8h4AQB2nR2cGh29jTVn464G9AaPYOFuLru9YsMeHfp
+IvS9MXlUo2MwYTAdBgNVHQ4EFgQUhsFufARCvi9j8aHTWWRI45l7kpcwHwYDVR0j
+BBgwFoAUhsFufARCvi9j8aHTWWRI45l7kpcwDwYDVR0TAQH/BAUwAwEB/zAOBgNV
+HQ8BAf8EBAMCAQYwCgYIKoZIzj0EAwIDSAAwRQIgbJ/JdmZnVCBpwg3IjHT6R8TV
+AsRxEpnAUf7XqFrzsP4CIQCwEBNwvYYe9rFxP5XrPgo52QvUyEj8ex5iwH9s4r9C
+ww==
+-----END CERTIFICATE-----";
+    const CLIENT: &str = "-----BEGIN CERTIFICATE-----
+MIIB9DCCAZqgAwIBAgIULWbggrfDm1LkUIpVpFr8JOQ7okIwCgYIKoZIzj0EAwIw
+IzEhMB8GA1UEAwwYVmVyaVNpbURCIFRlc3QgQ2xpZW50IENBMCAXDTI2MTAxNTIw
+NTgwNFoYDzIxMjYwOTIxMjA1ODA0WjARMQ8wDQYDVQQDDAZpbmdlc3QwWTATBgcq
+hkjOPQIBBggqhkjOPQMBBwNCAARRxdaSd4KjBzmkAqK4oewmSF8TPXVwtoV1LK1R
+JKrAw4ny4Y4zeSzG7cdBOQp/oUTohdTUl8Wzfra6U/nuGg+Ko4G7MIG4MAkGA1Ud
+EwQCMAAwDgYDVR0PAQH/BAQDAgeAMBMGA1UdJQQMMAoGCCsGAQUFBwMCMEYGA1Ud
+EQQ/MD2CFmluZ2VzdC5zdmMuZXhhbXBsZS5jb22GI3NwaWZmZTovL2V4YW1wbGUu
+b3JnL25zL3Byb2QvaW5nZXN0MB0GA1UdDgQWBBRlcOPSVcv7+0fcZqtYA9stu164
+szAfBgNVHSMEGDAWgBSGwW58BEK+L2PxodNZZEjjmXuSlzAKBggqhkjOPQQDAgNI
+ADBFAiEA6112yWcbeeH5EuoN14TZ4jfs79J0gN2iBvwH1AOTntACIFpBTzlHew1u
+m8gBEjWZSEfabOXZUiyf9Kudu+DE2zWG
+-----END CERTIFICATE-----";
+    /// Self-signed, with the same DNS name
+    const ROGUE: &str = "-----BEGIN CERTIFICATE-----
+MIIBsTCCAVegAwIBAgIUZbDXg7S9txFUZx7PtNmcBdt1pGgwCgYIKoZIzj0EAwIw
+EDEOMAwGA1UEAwwFcm9ndWUwIBcNMjYxMDE1MjA1ODA0WhgPMjEyNjA5MjEyMDU4
+MDRaMBAxDjAMBgNVBAMMBXJvZ3VlMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE
+NAAa6kw0YxHF7DjDfjMVSGfEmniy8cweFIr+JX11qMJiyI65umTOT6onCJ5kltZf
+1pscdFkYSI1gQ1VKv6wRHKOBjDCBiTAdBgNVHQ4EFgQUovoW3f4NEdT1AApwTt/V
+uYamw2IwHwYDVR0jBBgwFoAUovoW3f4NEdT1AApwTt/VuYamw2IwDwYDVR0TAQH/
+BAUwAwEB/zAhBgNVHREEGjAYghZpbmdlc3Quc3ZjLmV4YW1wbGUuY29tMBMGA1Ud
+JQQMMAoGCCsGAQUFBwMCMAoGCCqGSM49BAMCA0gAMEUCIAKjff+n+htTzKPOMHmy
+hmT1GmLudjfv+/ehvbc6qJ9jAiEAtNCfYe/snDKYqhZuXarkaf9AOtuiCIgSS7oi
+OAPHicE=
+-----END CERTIFICATE-----";
+
+    fn cert(pem: &str) -> CertificateDer<'static> {
+        CertificateDer::from_pem_slice(pem.as_bytes()).unwrap()
+    }
+
+    fn mapping(san: &str, principal: Option<&str>, role: ClientRole) -> SanMapping {
+        SanMapping {
+            san: san.to_string(),
+            principal: principal.map(str::to_string),
+            role,
+        }
+    }
+
+    #[test]
+    fn test_names_of_certificates_the_client_ca_signed() {
+        let mut roots = RootCertStore::empty();
+        roots.add(cert(CA)).unwrap();
+        let ca = ClientCa::new(roots).unwrap();
+
+        assert_eq!(
+            ca.names(&[cert(CLIENT)]).unwrap(),
+            vec!["ingest.svc.example.com", "spiffe://example.org/ns/prod/ingest"]
+        );
+        assert_eq!(ca.names(&[cert(ROGUE)]), None);
+        assert_eq!(ca.names(&[]), None);
+    }
+
+    #[test]
+    fn test_names_map_to_principals_and_roles() {
+        let config = ClientCertConfig {
+            ca_path: String::new(),
+            exclusive: true,
+            mappings: vec![
+                mapping("spiffe://example.org/ns/prod/*", None, ClientRole::Writer),
+                mapping("*.ops.example.com", Some("operators"), ClientRole::Admin),
+            ],
+            default_role: None,
+        };
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let ingest = config
+            .identify(&names(&["ingest.svc.example.com", "spiffe://example.org/ns/prod/ingest"]))
+            .unwrap();
+        assert_eq!(ingest.id, "spiffe://example.org/ns/prod/ingest");
+        assert_eq!(ingest.role, ClientRole::Writer);
+        assert_eq!(ingest.kind, PrincipalKind::Certificate);
+
+        let ops = config.identify(&names(&["alice.ops.example.com"])).unwrap();
+        assert_eq!((ops.id.as_str(), ops.role), ("operators", ClientRole::Admin));
+
+        // A wildcard covers subdomains only, not the domain or lookalikes
+        assert!(config.identify(&names(&["ops.example.com"])).is_err());
+        assert!(config.identify(&names(&["evilops.example.com"])).is_err());
+        assert!(config.identify(&names(&["spiffe://example.org/ns/dev/ingest"])).is_err());
+
+        let fallback = ClientCertConfig {
+            default_role: Some(ClientRole::Reader),
+            ..config
+        };
+        let other = fallback.identify(&names(&["batch.svc.example.com"])).unwrap();
+        assert_eq!((other.id.as_str(), other.role), ("batch.svc.example.com", ClientRole::Reader));
+        assert!(fallback.identify(&[]).is_err());
+    }
+}
@@ -0,0 +1,1494 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! CRDT multi-master synchronization.
+//!
+//! Instances that write while disconnected (edge deployments) converge by
+//! exchanging conflict-free entity states instead of replaying each other's
+//! writes.  Each entity's state is:
+//!
+//! - a last-writer-wins register per field — document title, body and each
+//!   document field; the embedding, tensor and spatial data as wholes; each
+//!   semantic property
+//! - an observed-remove set of relationships and one of semantic types: a
+//!   removal removes only the additions it has seen, so a concurrent
+//!   addition survives
+//! - a last-writer-wins deletion flag, which loses to any write made after
+//!   it
+//!
+//! Writes are stamped with a hybrid logical clock (milliseconds, never
+//! behind a stamp already seen) and the node ID, which breaks ties.
+//!
+//! ## Capture
+//!
+//! The [`CrdtListener`] change hook marks written entities.  Before states
+//! are exchanged, each marked entity's new versions are read back from its
+//! version history, which records every write's input and time, and folded
+//! into its state.  Writes that sync itself made are skipped.
+//!
+//! ## Exchange
+//!
+//! `GET /crdt/state?since=N` serves the states changed since a node's
+//! sequence number `N`; `POST /crdt/merge` merges states into this node and
+//! writes the entities whose merged value changed.  `POST /crdt/sync` does
+//! both with a peer, pulling its changes and pushing ours, and remembers
+//! how far each direction got.  Merging is commutative, associative and
+//! idempotent, so peers syncing in any order, any number of times, end with
+//! the same entities.  A merge that writes an entity links its provenance
+//! to the head of the sending node's chain for it.
+//!
+//! ## Conflicts
+//!
+//! Each register also records the latest write of every node it replaced,
+//! so a merge can tell a value that supersedes another from one written
+//! without knowledge of it.  Two such concurrent writes of different values
+//! are a conflict, settled by the [`ConflictPolicy`] of the entity's class
+//! (its first semantic type with one configured):
+//!
+//! - `newest_version`, the default: the later write wins
+//! - `prefer_origin`: the write made on the node that created the entity
+//!   wins, if either was
+//! - `modality_priority`: every conflicting field is taken from the side
+//!   that wrote the highest-ranked modality in conflict last, so an entity's
+//!   modalities are not mixed from two sides
+//! - `manual`: each node keeps its value and queues the conflict until an
+//!   operator picks a side through `POST /crdt/conflicts/{id}/resolve`
+//!
+//! Conflicts settled or queued are recorded in the entity's provenance as
+//! `sync_conflict` events.  Relationships, types and deletion merge as
+//! before; they cannot conflict.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+use verisim_hexad::{
+    ChainLink, Hexad, HexadDocumentInput, HexadGraphInput, HexadId, HexadInput, HexadListener, HexadProvenanceInput,
+    HexadSemanticInput, HexadStore, ProvenanceStore,
+};
+
+use crate::peer_auth::PeerScope;
+use crate::{ApiError, AppState};
+
+/// Time allowed for one request to a peer
+const PEER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Entities listed per page when looking for writes made before sync began
+const SCAN_PAGE_SIZE: usize = 500;
+
+/// When and where a write was made; later stamps win
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    /// Hybrid logical clock, in milliseconds
+    pub ts: u64,
+    /// Node that made the write, breaking ties
+    pub node: String,
+}
+
+/// Last-writer-wins register
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lww<T> {
+    pub value: T,
+    pub stamp: Stamp,
+    /// Latest write of each node that this value replaced, directly or
+    /// through earlier values; a write it has not seen is concurrent with it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub seen: BTreeMap<String, u64>,
+}
+
+impl<T: Clone + PartialEq> Lww<T> {
+    fn new(value: T, stamp: Stamp) -> Self {
+        Self {
+            value,
+            stamp,
+            seen: BTreeMap::new(),
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.stamp > self.stamp {
+            *self = other.clone();
+        }
+    }
+
+    /// A write of `value` replacing this one
+    fn replaced(&self, value: T, stamp: Stamp) -> Self {
+        Self {
+            value,
+            stamp,
+            seen: self.seen_with(None),
+        }
+    }
+
+    /// Whether this value was written knowing of the write stamped `stamp`
+    fn has_seen(&self, stamp: &Stamp) -> bool {
+        (self.stamp.node == stamp.node && self.stamp.ts >= stamp.ts)
+            || self.seen.get(&stamp.node).is_some_and(|ts| *ts >= stamp.ts)
+    }
+
+    /// What this value has seen, and `other` has, counting both writes
+    fn seen_with(&self, other: Option<&Self>) -> BTreeMap<String, u64> {
+        let mut seen = self.seen.clone();
+        let mut note = |node: &String, ts: u64| {
+            let entry = seen.entry(node.clone()).or_insert(ts);
+            *entry = (*entry).max(ts);
+        };
+        note(&self.stamp.node, self.stamp.ts);
+        if let Some(other) = other {
+            for (node, ts) in &other.seen {
+                note(node, *ts);
+            }
+            note(&other.stamp.node, other.stamp.ts);
+        }
+        seen
+    }
+
+    /// Fold in another node's value: whichever has seen the other stands.
+    /// Concurrent values that differ are a conflict, left as they are for
+    /// the caller to settle; returns whether they were.
+    fn merge_causal(&mut self, other: &Self) -> bool {
+        if self.has_seen(&other.stamp) {
+            return false;
+        }
+        if other.has_seen(&self.stamp) {
+            *self = other.clone();
+            return false;
+        }
+        if self.value != other.value {
+            return true;
+        }
+        let seen = self.seen_with(Some(other));
+        if other.stamp > self.stamp {
+            self.stamp = other.stamp.clone();
+        }
+        self.seen = seen;
+        false
+    }
+
+    /// `winner`'s value, having seen `loser`
+    fn settled(winner: &Self, loser: &Self) -> Self {
+        Self {
+            value: winner.value.clone(),
+            stamp: winner.stamp.clone(),
+            seen: winner.seen_with(Some(loser)),
+        }
+    }
+}
+
+/// Additions of one element of an [`OrSet`] and those removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrEntry {
+    pub adds: BTreeSet<Stamp>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub removes: BTreeSet<Stamp>,
+}
+
+/// Observed-remove set: an element is present while some addition of it
+/// has not been removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrSet {
+    #[serde(default)]
+    pub entries: BTreeMap<String, OrEntry>,
+}
+
+impl OrSet {
+    pub fn contains(&self, element: &str) -> bool {
+        self.entries
+            .get(element)
+            .is_some_and(|e| e.adds.iter().any(|a| !e.removes.contains(a)))
+    }
+
+    /// Present elements, in order
+    pub fn elements(&self) -> Vec<String> {
+        self.entries.keys().filter(|e| self.contains(e)).cloned().collect()
+    }
+
+    fn add(&mut self, element: &str, stamp: &Stamp) {
+        self.entries.entry(element.to_string()).or_default().adds.insert(stamp.clone());
+    }
+
+    /// Remove the additions of `element` seen so far
+    fn remove(&mut self, element: &str) {
+        if let Some(entry) = self.entries.get_mut(element) {
+            let adds = entry.adds.clone();
+            entry.removes.extend(adds);
+        }
+    }
+
+    fn merge(&mut self, other: &OrSet) {
+        for (element, theirs) in &other.entries {
+            let ours = self.entries.entry(element.clone()).or_default();
+            ours.adds.extend(theirs.adds.iter().cloned());
+            ours.removes.extend(theirs.removes.iter().cloned());
+        }
+    }
+
+    fn max_stamp(&self) -> Option<&Stamp> {
+        self.entries.values().flat_map(|e| &e.adds).max()
+    }
+}
+
+/// How concurrent writes of different values to a field are settled
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The later write wins
+    #[default]
+    NewestVersion,
+    /// The write made on the node that created the entity wins, if either
+    /// was; otherwise the later
+    PreferOrigin,
+    /// Every conflicting field comes from the side that last wrote the
+    /// highest-ranked modality in conflict (`document`, `vector`, `tensor`,
+    /// `spatial`, `semantic`); unlisted modalities rank last
+    ModalityPriority { modalities: Vec<String> },
+    /// Each node keeps its value and queues the conflict for an operator
+    Manual,
+}
+
+impl ConflictPolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NewestVersion => "newest_version",
+            Self::PreferOrigin => "prefer_origin",
+            Self::ModalityPriority { .. } => "modality_priority",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// The conflict policy of one class of entities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassPolicy {
+    /// Semantic type IRI
+    pub class: String,
+    #[serde(flatten)]
+    pub policy: ConflictPolicy,
+}
+
+/// Conflict policies by entity class
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConflictPolicies {
+    /// For entities of no listed class
+    #[serde(default)]
+    pub default: ConflictPolicy,
+    /// Checked in order; an entity takes the first whose class it has
+    #[serde(default)]
+    pub classes: Vec<ClassPolicy>,
+}
+
+impl ConflictPolicies {
+    /// The class and policy for an entity of `types`
+    pub fn for_types(&self, types: &[String]) -> (Option<&str>, &ConflictPolicy) {
+        match self.classes.iter().find(|c| types.contains(&c.class)) {
+            Some(class) => (Some(&class.class), &class.policy),
+            None => (None, &self.default),
+        }
+    }
+}
+
+/// Concurrent writes of different values to one field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldConflict {
+    /// Register path, e.g. `document.title`
+    pub field: String,
+    pub ours: Lww<Value>,
+    pub theirs: Lww<Value>,
+}
+
+impl FieldConflict {
+    /// Modality the field belongs to
+    fn modality(&self) -> &str {
+        self.field.split('.').next().unwrap_or_default()
+    }
+}
+
+/// Conflicts a merge found in one entity
+#[derive(Debug, Clone, Default)]
+pub struct MergeConflicts {
+    pub class: Option<String>,
+    pub policy: ConflictPolicy,
+    /// Settled by the policy
+    pub settled: Vec<FieldConflict>,
+    /// Left for an operator
+    pub pending: Vec<FieldConflict>,
+}
+
+impl MergeConflicts {
+    pub fn is_empty(&self) -> bool {
+        self.settled.is_empty() && self.pending.is_empty()
+    }
+}
+
+/// Register key prefix of document fields
+const DOCUMENT_FIELD: &str = "document.fields.";
+
+/// Register key prefix of semantic properties
+const SEMANTIC_PROPERTY: &str = "semantic.properties.";
+
+/// Conflict-free state of one entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityState {
+    /// Field registers by path; `null` records a removed field
+    #[serde(default)]
+    pub registers: BTreeMap<String, Lww<Value>>,
+    /// `[predicate, target]` pairs, JSON-encoded
+    #[serde(default)]
+    pub relationships: OrSet,
+    /// Semantic type IRIs
+    #[serde(default)]
+    pub types: OrSet,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<Lww<bool>>,
+    /// Latest write to any field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub written: Option<Stamp>,
+    /// First write to the entity, on the node that created it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Stamp>,
+}
+
+impl EntityState {
+    /// Whether the entity's latest deletion is newer than every write to it
+    pub fn is_deleted(&self) -> bool {
+        match &self.deleted {
+            Some(deleted) if deleted.value => self.written.as_ref().is_none_or(|w| deleted.stamp > *w),
+            _ => false,
+        }
+    }
+
+    /// Fold another node's state of the entity into this one, the later
+    /// write winning each conflict
+    pub fn merge(&mut self, other: &EntityState) {
+        self.merge_with(other, &ConflictPolicies::default());
+    }
+
+    /// Fold another node's state of the entity into this one, settling
+    /// conflicts by the policy of the entity's class
+    pub fn merge_with(&mut self, other: &EntityState, policies: &ConflictPolicies) -> MergeConflicts {
+        self.relationships.merge(&other.relationships);
+        self.types.merge(&other.types);
+        match (&mut self.deleted, &other.deleted) {
+            (Some(ours), Some(theirs)) => ours.merge(theirs),
+            (None, Some(theirs)) => self.deleted = Some(theirs.clone()),
+            _ => {}
+        }
+        if other.written > self.written {
+            self.written = other.written.clone();
+        }
+        if other.origin.is_some() && (self.origin.is_none() || other.origin < self.origin) {
+            self.origin = other.origin.clone();
+        }
+
+        let mut conflicts = Vec::new();
+        for (key, theirs) in &other.registers {
+            match self.registers.get_mut(key) {
+                Some(ours) => {
+                    if ours.merge_causal(theirs) {
+                        conflicts.push(FieldConflict {
+                            field: key.clone(),
+                            ours: ours.clone(),
+                            theirs: theirs.clone(),
+                        });
+                    }
+                }
+                None => {
+                    self.registers.insert(key.clone(), theirs.clone());
+                }
+            }
+        }
+        let (class, policy) = policies.for_types(&self.types.elements());
+        let mut outcome = MergeConflicts {
+            class: class.map(str::to_string),
+            policy: policy.clone(),
+            ..Default::default()
+        };
+        if conflicts.is_empty() {
+            return outcome;
+        }
+
+        let origin = self.origin.as_ref().map(|o| o.node.as_str());
+        let theirs_win: Vec<bool> = match policy {
+            ConflictPolicy::NewestVersion => conflicts.iter().map(|c| c.theirs.stamp > c.ours.stamp).collect(),
+            ConflictPolicy::PreferOrigin => conflicts
+                .iter()
+                .map(|c| match (Some(c.ours.stamp.node.as_str()) == origin, Some(c.theirs.stamp.node.as_str()) == origin) {
+                    (true, false) => false,
+                    (false, true) => true,
+                    _ => c.theirs.stamp > c.ours.stamp,
+                })
+                .collect(),
+            ConflictPolicy::ModalityPriority { modalities } => {
+                let rank = |c: &FieldConflict| modalities.iter().position(|m| m == c.modality()).unwrap_or(modalities.len());
+                let top = conflicts.iter().map(rank).min().unwrap_or_default();
+                let latest = |side: fn(&FieldConflict) -> &Lww<Value>| {
+                    conflicts.iter().filter(|c| rank(c) == top).map(|c| side(c).stamp.clone()).max()
+                };
+                let theirs = latest(|c| &c.theirs) > latest(|c| &c.ours);
+                vec![theirs; conflicts.len()]
+            }
+            ConflictPolicy::Manual => {
+                outcome.pending = conflicts;
+                return outcome;
+            }
+        };
+        for (conflict, theirs) in conflicts.into_iter().zip(theirs_win) {
+            let settled = if theirs {
+                Lww::settled(&conflict.theirs, &conflict.ours)
+            } else {
+                Lww::settled(&conflict.ours, &conflict.theirs)
+            };
+            self.registers.insert(conflict.field.clone(), settled);
+            outcome.settled.push(conflict);
+        }
+        outcome
+    }
+
+    /// Latest stamp anywhere in the state
+    fn max_stamp(&self) -> Option<&Stamp> {
+        self.registers
+            .values()
+            .map(|r| &r.stamp)
+            .chain(self.relationships.max_stamp())
+            .chain(self.types.max_stamp())
+            .chain(self.deleted.as_ref().map(|d| &d.stamp))
+            .chain(self.written.as_ref())
+            .max()
+    }
+
+    /// Set a register, if `value` differs from what it holds
+    fn set(&mut self, key: String, value: Value, stamp: &Stamp) -> bool {
+        let register = match self.registers.get(&key) {
+            Some(r) if r.value == value => return false,
+            Some(r) => r.replaced(value, stamp.clone()),
+            None => Lww::new(value, stamp.clone()),
+        };
+        self.registers.insert(key, register);
+        true
+    }
+
+    /// Set the registers under `prefix` to `values`, clearing the others
+    fn set_all(&mut self, prefix: &str, values: &std::collections::HashMap<String, String>, stamp: &Stamp) -> bool {
+        let mut changed = false;
+        for (key, value) in values {
+            changed |= self.set(format!("{prefix}{key}"), Value::String(value.clone()), stamp);
+        }
+        let dropped: Vec<String> = self
+            .registers
+            .iter()
+            .filter(|(key, r)| !r.value.is_null() && key.strip_prefix(prefix).is_some_and(|k| !values.contains_key(k)))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in dropped {
+            changed |= self.set(key, Value::Null, stamp);
+        }
+        changed
+    }
+
+    /// Record a local write of `input`; whether anything changed
+    pub fn apply_input(&mut self, input: &HexadInput, stamp: &Stamp) -> bool {
+        let mut changed = false;
+        if let Some(document) = &input.document {
+            changed |= self.set("document.title".to_string(), Value::String(document.title.clone()), stamp);
+            changed |= self.set("document.body".to_string(), Value::String(document.body.clone()), stamp);
+            changed |= self.set_all(DOCUMENT_FIELD, &document.fields, stamp);
+        }
+        for (key, value) in [
+            ("vector", input.vector.as_ref().and_then(|v| serde_json::to_value(v).ok())),
+            ("tensor", input.tensor.as_ref().and_then(|t| serde_json::to_value(t).ok())),
+            ("spatial", input.spatial.as_ref().and_then(|s| serde_json::to_value(s).ok())),
+        ] {
+            if let Some(value) = value {
+                changed |= self.set(key.to_string(), value, stamp);
+            }
+        }
+        if let Some(semantic) = &input.semantic {
+            changed |= self.set_all(SEMANTIC_PROPERTY, &semantic.properties, stamp);
+            for present in self.types.elements() {
+                if !semantic.types.contains(&present) {
+                    self.types.remove(&present);
+                    changed = true;
+                }
+            }
+            for t in &semantic.types {
+                if !self.types.contains(t) {
+                    self.types.add(t, stamp);
+                    changed = true;
+                }
+            }
+        }
+        if let Some(graph) = &input.graph {
+            for relationship in &graph.relationships {
+                let element = relationship_element(relationship);
+                if !self.relationships.contains(&element) {
+                    self.relationships.add(&element, stamp);
+                    changed = true;
+                }
+            }
+        }
+        if changed && self.written.as_ref() < Some(stamp) {
+            self.written = Some(stamp.clone());
+        }
+        if changed && self.origin.is_none() {
+            self.origin = Some(stamp.clone());
+        }
+        changed
+    }
+
+    /// Present relationships
+    pub fn relationships(&self) -> Vec<(String, String)> {
+        self.relationships
+            .elements()
+            .iter()
+            .filter_map(|e| serde_json::from_str(e).ok())
+            .collect()
+    }
+
+    /// The entity as an input writing every field the state holds
+    pub fn to_input(&self) -> HexadInput {
+        let value = |key: &str| self.registers.get(key).map(|r| &r.value).filter(|v| !v.is_null());
+        let text = |key: &str| value(key).and_then(Value::as_str).map(str::to_string);
+        let prefixed = |prefix: &str| -> std::collections::HashMap<String, String> {
+            self.registers
+                .iter()
+                .filter_map(|(key, r)| Some((key.strip_prefix(prefix)?.to_string(), r.value.as_str()?.to_string())))
+                .collect()
+        };
+
+        let mut input = HexadInput::default();
+        let (title, body) = (text("document.title"), text("document.body"));
+        if title.is_some() || body.is_some() {
+            input.document = Some(HexadDocumentInput {
+                title: title.unwrap_or_default(),
+                body: body.unwrap_or_default(),
+                fields: prefixed(DOCUMENT_FIELD),
+            });
+        }
+        input.vector = value("vector").and_then(|v| serde_json::from_value(v.clone()).ok());
+        input.tensor = value("tensor").and_then(|v| serde_json::from_value(v.clone()).ok());
+        input.spatial = value("spatial").and_then(|v| serde_json::from_value(v.clone()).ok());
+        let (types, properties) = (self.types.elements(), prefixed(SEMANTIC_PROPERTY));
+        if !types.is_empty() || !properties.is_empty() {
+            input.semantic = Some(HexadSemanticInput { types, properties });
+        }
+        let relationships = self.relationships();
+        if !relationships.is_empty() {
+            input.graph = Some(HexadGraphInput { relationships });
+        }
+        input
+    }
+
+    /// What the state makes of the entity, for telling whether a merge
+    /// changed it
+    fn view(&self) -> (bool, Value) {
+        (self.is_deleted(), serde_json::to_value(self.to_input()).unwrap_or(Value::Null))
+    }
+}
+
+/// OR-set element of a relationship
+fn relationship_element(relationship: &(String, String)) -> String {
+    serde_json::to_string(relationship).unwrap_or_default()
+}
+
+/// One entity's state, as exchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityEntry {
+    pub id: String,
+    pub state: EntityState,
+    /// Hash of the latest record of the entity's provenance chain on the
+    /// sending node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance_head: Option<String>,
+}
+
+/// States a node has changed since a sequence number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBatch {
+    pub node_id: String,
+    /// Changes when a node loses its sync state; cursors from another
+    /// incarnation start again from 0
+    pub incarnation: String,
+    /// The node's sequence number as of this batch
+    pub seq: u64,
+    pub entities: Vec<EntityEntry>,
+}
+
+/// Outcome of merging a batch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// Entity states merged
+    pub merged: usize,
+    /// Entities rewritten or deleted because the merge changed them
+    pub changed: Vec<String>,
+    /// Entities with conflicting writes, settled or queued
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+/// Outcome of syncing with a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub peer: String,
+    /// Entity states received from the peer
+    pub pulled: usize,
+    /// Entity states sent to the peer
+    pub pushed: usize,
+    /// Entities here that the peer's states changed
+    pub changed: Vec<String>,
+    /// Entities with conflicting writes, settled or queued
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+/// How far syncing with one peer has got
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerCursor {
+    /// Peer's node ID, once synced
+    #[serde(default)]
+    pub node_id: Option<String>,
+    /// Peer's incarnation the sequence numbers belong to
+    pub incarnation: Option<String>,
+    /// Peer's sequence number pulled through
+    pub pulled: u64,
+    /// Our sequence number pushed through
+    pub pushed: u64,
+    pub last_sync: Option<DateTime<Utc>>,
+}
+
+/// Sync state of this node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrdtStatus {
+    pub node_id: String,
+    pub incarnation: String,
+    pub seq: u64,
+    /// Entities with a state
+    pub entities: usize,
+    /// Entities written since their state was last brought up to date
+    pub pending: usize,
+    /// Entities with conflicts queued for manual resolution
+    pub conflicts: usize,
+    pub peers: BTreeMap<String, PeerCursor>,
+}
+
+/// Conflicts in one entity awaiting an operator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingConflict {
+    pub id: String,
+    pub class: Option<String>,
+    /// Node the conflicting writes came from
+    pub peer: String,
+    pub fields: Vec<FieldConflict>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Side an operator keeps when resolving a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictSide {
+    /// This node's values
+    Ours,
+    /// The peer's values
+    Theirs,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrackedEntity {
+    state: EntityState,
+    /// Our sequence number when the state last changed
+    seq: u64,
+    /// Latest version folded into the state
+    captured_version: u64,
+    /// Versions written by merges, not to be captured
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    own_versions: BTreeSet<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CrdtInner {
+    incarnation: String,
+    clock: u64,
+    seq: u64,
+    entities: BTreeMap<String, TrackedEntity>,
+    peers: BTreeMap<String, PeerCursor>,
+    /// Conflicts queued for manual resolution, by entity
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    conflicts: BTreeMap<String, PendingConflict>,
+    /// Entities written since last captured
+    #[serde(skip)]
+    dirty: BTreeSet<String>,
+    /// Whether writes made before sync began have been looked for
+    #[serde(skip)]
+    scanned: bool,
+}
+
+impl Default for CrdtInner {
+    fn default() -> Self {
+        Self {
+            incarnation: uuid::Uuid::new_v4().to_string(),
+            clock: 0,
+            seq: 0,
+            entities: BTreeMap::new(),
+            peers: BTreeMap::new(),
+            conflicts: BTreeMap::new(),
+            dirty: BTreeSet::new(),
+            scanned: false,
+        }
+    }
+}
+
+impl CrdtInner {
+    /// Next stamp for a write made at `at`
+    fn tick(&mut self, node: &str, at: DateTime<Utc>) -> Stamp {
+        self.clock = (at.timestamp_millis().max(0) as u64).max(self.clock + 1);
+        Stamp {
+            ts: self.clock,
+            node: node.to_string(),
+        }
+    }
+
+    /// Note that `id`'s state changed
+    fn touch(&mut self, id: &str) {
+        self.seq += 1;
+        let seq = self.seq;
+        self.entities.entry(id.to_string()).or_default().seq = seq;
+    }
+}
+
+/// This node's CRDT sync: entity states and peer cursors
+#[derive(Debug)]
+pub struct CrdtSync {
+    node_id: String,
+    inner: Mutex<CrdtInner>,
+    /// Held while capturing or merging, so they do not interleave
+    syncing: tokio::sync::Mutex<()>,
+    persist_path: Option<PathBuf>,
+    client: OnceLock<reqwest::Client>,
+    policies: ConflictPolicies,
+}
+
+impl CrdtSync {
+    pub fn new(node_id: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.into(),
+            inner: Mutex::new(CrdtInner::default()),
+            syncing: tokio::sync::Mutex::new(()),
+            persist_path: None,
+            client: OnceLock::new(),
+            policies: ConflictPolicies::default(),
+        }
+    }
+
+    /// Settle conflicts by `policies` rather than by newest version
+    pub fn with_policies(mut self, policies: ConflictPolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Keep the sync state in `path`, loading what is there.
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self, ApiError> {
+        let path = path.into();
+        let persistence = |e: &dyn std::fmt::Display| ApiError::Internal(format!("{}: {}", path.display(), e));
+        let inner: CrdtInner = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| persistence(&e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CrdtInner::default(),
+            Err(e) => return Err(persistence(&e)),
+        };
+        info!(path = %path.display(), entities = inner.entities.len(), peers = inner.peers.len(), "Loaded CRDT sync state");
+        self.inner = Mutex::new(inner);
+        self.persist_path = Some(path);
+        Ok(self)
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// URL of the peer with node ID `node_id`, if we have synced with it
+    pub fn peer_endpoint(&self, node_id: &str) -> Option<String> {
+        self.lock()
+            .peers
+            .iter()
+            .find(|(_, cursor)| cursor.node_id.as_deref() == Some(node_id))
+            .map(|(url, _)| url.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CrdtInner> {
+        self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(PEER_TIMEOUT)
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn status(&self) -> CrdtStatus {
+        let inner = self.lock();
+        CrdtStatus {
+            node_id: self.node_id.clone(),
+            incarnation: inner.incarnation.clone(),
+            seq: inner.seq,
+            entities: inner.entities.len(),
+            pending: inner.dirty.len(),
+            conflicts: inner.conflicts.len(),
+            peers: inner.peers.clone(),
+        }
+    }
+
+    /// The state held for one entity
+    pub fn entity(&self, id: &str) -> Option<EntityState> {
+        self.lock().entities.get(id).map(|t| t.state.clone())
+    }
+
+    fn written(&self, id: &str, created: bool) {
+        let mut inner = self.lock();
+        // A local create of an entity sync deleted brings it back
+        if created && inner.entities.get(id).is_some_and(|t| t.state.is_deleted()) {
+            let stamp = inner.tick(&self.node_id, Utc::now());
+            if let Some(tracked) = inner.entities.get_mut(id) {
+                tracked.state.deleted = Some(Lww::new(false, stamp));
+            }
+            inner.touch(id);
+        }
+        inner.dirty.insert(id.to_string());
+    }
+
+    fn deleted(&self, id: &str) {
+        let mut inner = self.lock();
+        // Deletes made by merges are already recorded
+        if inner.entities.get(id).is_some_and(|t| t.state.is_deleted()) {
+            return;
+        }
+        let stamp = inner.tick(&self.node_id, Utc::now());
+        inner.entities.entry(id.to_string()).or_default().state.deleted = Some(Lww::new(true, stamp));
+        inner.touch(id);
+    }
+
+    /// Fold the versions written since the last capture into the states
+    async fn capture(&self, state: &AppState) -> Result<(), ApiError> {
+        let internal = |e: verisim_hexad::HexadError| ApiError::Internal(e.to_string());
+        if !self.lock().scanned {
+            let mut offset = 0;
+            loop {
+                let page = state.hexad_store.list(SCAN_PAGE_SIZE, offset).await.map_err(internal)?;
+                self.lock().dirty.extend(page.iter().map(|h| h.id.to_string()));
+                if page.len() < SCAN_PAGE_SIZE {
+                    break;
+                }
+                offset += page.len();
+            }
+            self.lock().scanned = true;
+        }
+
+        let ids = std::mem::take(&mut self.lock().dirty);
+        let mut remaining = ids.iter();
+        while let Some(id) = remaining.next() {
+            if let Err(e) = self.capture_entity(state, id).await {
+                // Try again next time
+                let mut inner = self.lock();
+                inner.dirty.insert(id.clone());
+                inner.dirty.extend(remaining.cloned());
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn capture_entity(&self, state: &AppState, id: &str) -> Result<(), ApiError> {
+        let internal = |e: verisim_hexad::HexadError| ApiError::Internal(e.to_string());
+        let hexad_id = HexadId::new(id);
+        let Some(status) = state.hexad_store.status(&hexad_id).await.map_err(internal)? else {
+            return Ok(());
+        };
+        let (from, own) = {
+            let mut inner = self.lock();
+            let tracked = inner.entities.entry(id.to_string()).or_default();
+            (tracked.captured_version, tracked.own_versions.clone())
+        };
+        for version in from + 1..=status.version {
+            if own.contains(&version) {
+                continue;
+            }
+            let Some(snapshot) = state.hexad_store.version_snapshot(&hexad_id, version).await.map_err(internal)? else {
+                continue;
+            };
+            let mut inner = self.lock();
+            let stamp = inner.tick(&self.node_id, snapshot.timestamp);
+            let changed = inner
+                .entities
+                .get_mut(id)
+                .is_some_and(|tracked| tracked.state.apply_input(&snapshot.input, &stamp));
+            if changed {
+                inner.touch(id);
+            }
+        }
+        let mut inner = self.lock();
+        if let Some(tracked) = inner.entities.get_mut(id) {
+            tracked.captured_version = tracked.captured_version.max(status.version);
+            tracked.own_versions.retain(|v| *v > status.version);
+        }
+        Ok(())
+    }
+
+    /// States changed since our sequence number `since`
+    fn batch_since(&self, since: u64) -> StateBatch {
+        let inner = self.lock();
+        StateBatch {
+            node_id: self.node_id.clone(),
+            incarnation: inner.incarnation.clone(),
+            seq: inner.seq,
+            entities: inner
+                .entities
+                .iter()
+                .filter(|(_, t)| t.seq > since)
+                .map(|(id, t)| EntityEntry {
+                    id: id.clone(),
+                    state: t.state.clone(),
+                    provenance_head: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// States changed since our sequence number `since`, with the writes
+    /// made so far included
+    pub async fn export(&self, state: &AppState, since: u64) -> Result<StateBatch, ApiError> {
+        let _syncing = self.syncing.lock().await;
+        self.capture(state).await?;
+        self.save()?;
+        with_provenance_heads(state, self.batch_since(since)).await
+    }
+
+    /// Merge another node's states and write the entities they change
+    pub async fn merge(&self, state: &AppState, batch: StateBatch) -> Result<MergeReport, ApiError> {
+        let _syncing = self.syncing.lock().await;
+        self.capture(state).await?;
+        let report = self.merge_entities(state, &batch.node_id, batch.entities).await;
+        self.save()?;
+        report
+    }
+
+    async fn merge_entities(&self, state: &AppState, from: &str, entities: Vec<EntityEntry>) -> Result<MergeReport, ApiError> {
+        let mut report = MergeReport::default();
+        for entry in entities {
+            report.merged += 1;
+            let (planned, conflicts, settled, queued) = {
+                let mut inner = self.lock();
+                let tracked = inner.entities.entry(entry.id.clone()).or_default();
+                let before = tracked.state.clone();
+                let conflicts = tracked.state.merge_with(&entry.state, &self.policies);
+                let after = tracked.state.clone();
+                if let Some(stamp) = after.max_stamp() {
+                    inner.clock = inner.clock.max(stamp.ts);
+                }
+                if after != before {
+                    inner.touch(&entry.id);
+                }
+                let queued = Self::queue(&mut inner, &entry.id, from, &after, &conflicts);
+                let settled = conflicts
+                    .settled
+                    .iter()
+                    .map(|c| {
+                        let kept = after.registers.get(&c.field).map_or("", |r| r.stamp.node.as_str());
+                        format!("{} (kept node {kept})", c.field)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ((after.view() != before.view()).then_some((before, after)), conflicts, settled, queued)
+            };
+            if let Some((before, after)) = planned {
+                let provenance = HexadProvenanceInput {
+                    event_type: "imported".to_string(),
+                    actor: "crdt-sync".to_string(),
+                    source: Some(from.to_string()),
+                    description: format!("Merged changes from node {from}"),
+                    link: entry.provenance_head.clone().map(|head_hash| ChainLink {
+                        instance: from.to_string(),
+                        entity_id: entry.id.clone(),
+                        head_hash,
+                    }),
+                };
+                self.materialize(state, &entry.id, provenance, &before, &after).await?;
+                report.changed.push(entry.id.clone());
+            }
+            if !conflicts.settled.is_empty() {
+                let description = format!("Sync conflict with node {from} settled by {}: {settled}", conflicts.policy.name());
+                record_conflict(state, &entry.id, "crdt-sync", from, &description).await?;
+            }
+            if queued {
+                let description = format!(
+                    "Sync conflict with node {from} queued for manual resolution: {}",
+                    conflicts.pending.iter().map(|c| c.field.as_str()).collect::<Vec<_>>().join(", ")
+                );
+                record_conflict(state, &entry.id, "crdt-sync", from, &description).await?;
+            }
+            if !conflicts.is_empty() {
+                report.conflicts.push(entry.id);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Bring the queue of `id`'s conflicts up to date after a merge: drop
+    /// those since settled and add those found; whether any were added
+    fn queue(inner: &mut CrdtInner, id: &str, from: &str, after: &EntityState, conflicts: &MergeConflicts) -> bool {
+        let mut fields: Vec<FieldConflict> = inner
+            .conflicts
+            .remove(id)
+            .map(|queued| queued.fields)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| {
+                after
+                    .registers
+                    .get(&c.field)
+                    .is_some_and(|r| !r.has_seen(&c.theirs.stamp) && !conflicts.pending.iter().any(|p| p.field == c.field))
+            })
+            .collect();
+        let known = fields.clone();
+        let mut added = false;
+        for conflict in &conflicts.pending {
+            added |= !known.iter().any(|k| k.theirs.stamp == conflict.theirs.stamp && k.field == conflict.field);
+            fields.push(conflict.clone());
+        }
+        if !fields.is_empty() {
+            inner.conflicts.insert(
+                id.to_string(),
+                PendingConflict {
+                    id: id.to_string(),
+                    class: conflicts.class.clone(),
+                    peer: from.to_string(),
+                    fields,
+                    detected_at: Utc::now(),
+                },
+            );
+        }
+        added
+    }
+
+    /// Conflicts queued for manual resolution
+    pub fn conflicts(&self) -> Vec<PendingConflict> {
+        self.lock().conflicts.values().cloned().collect()
+    }
+
+    /// Settle `id`'s queued conflicts by keeping one side, as a new write
+    /// that supersedes both
+    pub async fn resolve(&self, state: &AppState, id: &str, keep: ConflictSide, actor: &str) -> Result<PendingConflict, ApiError> {
+        let _syncing = self.syncing.lock().await;
+        self.capture(state).await?;
+        let (queued, before, after) = {
+            let mut inner = self.lock();
+            let queued = inner
+                .conflicts
+                .remove(id)
+                .ok_or_else(|| ApiError::NotFound(format!("No conflicts queued for {id}")))?;
+            let stamp = inner.tick(&self.node_id, Utc::now());
+            let tracked = inner.entities.entry(id.to_string()).or_default();
+            let before = tracked.state.clone();
+            for conflict in &queued.fields {
+                let ours = tracked.state.registers.get(&conflict.field).unwrap_or(&conflict.ours).clone();
+                let value = match keep {
+                    ConflictSide::Ours => ours.value.clone(),
+                    ConflictSide::Theirs => conflict.theirs.value.clone(),
+                };
+                tracked.state.registers.insert(
+                    conflict.field.clone(),
+                    Lww {
+                        value,
+                        stamp: stamp.clone(),
+                        seen: ours.seen_with(Some(&conflict.theirs)),
+                    },
+                );
+            }
+            tracked.state.written = Some(stamp);
+            let after = tracked.state.clone();
+            inner.touch(id);
+            (queued, before, after)
+        };
+        let description = format!(
+            "Sync conflict with node {} resolved manually, keeping {}: {}",
+            queued.peer,
+            match keep {
+                ConflictSide::Ours => format!("node {}'s values", self.node_id),
+                ConflictSide::Theirs => format!("node {}'s values", queued.peer),
+            },
+            queued.fields.iter().map(|c| c.field.as_str()).collect::<Vec<_>>().join(", ")
+        );
+        if after.view() != before.view() {
+            let provenance = HexadProvenanceInput {
+                event_type: "modified".to_string(),
+                actor: actor.to_string(),
+                source: Some(queued.peer.clone()),
+                description: description.clone(),
+                link: None,
+            };
+            self.materialize(state, id, provenance, &before, &after).await?;
+        }
+        record_conflict(state, id, actor, &queued.peer, &description).await?;
+        self.save()?;
+        Ok(queued)
+    }
+
+    /// Write the entity as `after` has it
+    async fn materialize(
+        &self,
+        state: &AppState,
+        id: &str,
+        provenance: HexadProvenanceInput,
+        before: &EntityState,
+        after: &EntityState,
+    ) -> Result<(), ApiError> {
+        let hexad_id = HexadId::new(id);
+        if after.is_deleted() {
+            return match state.hexad_store.delete(&hexad_id).await {
+                Ok(()) | Err(verisim_hexad::HexadError::NotFound(_)) => Ok(()),
+                Err(e) => Err(ApiError::Internal(e.to_string())),
+            };
+        }
+        let mut input = after.to_input();
+        input.provenance = Some(provenance);
+        let hexad = state
+            .hexad_store
+            .put(&hexad_id, input)
+            .await
+            .map_err(|e| ApiError::Internal(format!("merging {id}: {e}")))?;
+        if let Some(tracked) = self.lock().entities.get_mut(id) {
+            tracked.own_versions.insert(hexad.status.version);
+        }
+        let present = after.relationships();
+        let removed: Vec<(String, String)> = before.relationships().into_iter().filter(|r| !present.contains(r)).collect();
+        if !removed.is_empty() {
+            state
+                .hexad_store
+                .unlink(&hexad_id, &removed)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+        }
+        state.observe_embedding(hexad.embedding.as_ref());
+        Ok(())
+    }
+
+    /// Pull the peer's changes, then push ours
+    pub async fn sync_with(&self, state: &AppState, peer: &str) -> Result<SyncReport, ApiError> {
+        let peer = peer.trim_end_matches('/').to_string();
+        let unavailable = |e: &dyn std::fmt::Display| ApiError::Unavailable(format!("peer {peer}: {e}"));
+        let cursor = self.lock().peers.get(&peer).cloned().unwrap_or_default();
+
+        // Locks are not held across requests: the peer may be syncing with us
+        let peers = &state.auth.peers;
+        let request = |method, path: &str| {
+            let url = format!("{peer}{path}");
+            async move { peers.request(self.client(), method, &url, PeerScope::Replication, PEER_TIMEOUT).await }
+        };
+        let pulled: StateBatch = request(reqwest::Method::GET, "/crdt/state")
+            .await
+            .map_err(|e| unavailable(&e))?
+            .query(&[("since", cursor.pulled)])
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| unavailable(&e))?
+            .json()
+            .await
+            .map_err(|e| unavailable(&e))?;
+        let cursor = match cursor.incarnation {
+            Some(ref incarnation) if *incarnation == pulled.incarnation => cursor,
+            _ => PeerCursor::default(),
+        };
+        let pulled = if cursor.pulled == 0 || pulled.seq >= cursor.pulled {
+            pulled
+        } else {
+            // Sequence numbers went backwards: start over
+            request(reqwest::Method::GET, "/crdt/state")
+                .await
+                .map_err(|e| unavailable(&e))?
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| unavailable(&e))?
+                .json()
+                .await
+                .map_err(|e| unavailable(&e))?
+        };
+
+        let (merged, ours) = {
+            let _syncing = self.syncing.lock().await;
+            self.capture(state).await?;
+            let ours = self.batch_since(cursor.pushed);
+            let merged = self.merge_entities(state, &pulled.node_id, pulled.entities.clone()).await;
+            (merged, ours)
+        };
+        let merged = merged?;
+        let ours = with_provenance_heads(state, ours).await?;
+
+        request(reqwest::Method::POST, "/crdt/merge")
+            .await
+            .map_err(|e| unavailable(&e))?
+            .json(&ours)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| unavailable(&e))?;
+
+        self.lock().peers.insert(
+            peer.clone(),
+            PeerCursor {
+                node_id: Some(pulled.node_id.clone()),
+                incarnation: Some(pulled.incarnation.clone()),
+                pulled: pulled.seq,
+                pushed: ours.seq,
+                last_sync: Some(Utc::now()),
+            },
+        );
+        self.save()?;
+        info!(peer = %peer, pulled = pulled.entities.len(), pushed = ours.entities.len(), changed = merged.changed.len(), "CRDT sync");
+        Ok(SyncReport {
+            peer,
+            pulled: pulled.entities.len(),
+            pushed: ours.entities.len(),
+            changed: merged.changed,
+            conflicts: merged.conflicts,
+        })
+    }
+
+    fn save(&self) -> Result<(), ApiError> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let persistence = |e: &dyn std::fmt::Display| ApiError::Internal(format!("{}: {}", path.display(), e));
+        let json = serde_json::to_string(&*self.lock()).map_err(|e| persistence(&e))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(|e| persistence(&e))?;
+        std::fs::rename(&tmp, path).map_err(|e| persistence(&e))
+    }
+}
+
+/// Record a sync conflict in the entity's provenance
+/// Fill in the provenance heads of a batch's entities
+async fn with_provenance_heads(state: &AppState, mut batch: StateBatch) -> Result<StateBatch, ApiError> {
+    for entry in &mut batch.entities {
+        entry.provenance_head = crate::replication::provenance_head(state, &entry.id).await?;
+    }
+    Ok(batch)
+}
+
+async fn record_conflict(state: &AppState, id: &str, actor: &str, peer: &str, description: &str) -> Result<(), ApiError> {
+    state
+        .hexad_store
+        .provenance_store()
+        .record_event(
+            id,
+            verisim_provenance::ProvenanceEventType::Custom("sync_conflict".to_string()),
+            actor,
+            Some(peer.to_string()),
+            description,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| ApiError::Internal(format!("recording conflict in {id}: {e}")))
+}
+
+/// Change hook that marks entities for capture and records deletes
+pub struct CrdtListener(pub Arc<CrdtSync>);
+
+impl HexadListener for CrdtListener {
+    fn name(&self) -> &str {
+        "crdt-sync"
+    }
+
+    fn on_created(&self, new: &Hexad) {
+        self.0.written(new.id.as_str(), true);
+    }
+
+    fn on_updated(&self, _old: &Hexad, new: &Hexad) {
+        self.0.written(new.id.as_str(), false);
+    }
+
+    fn on_deleted(&self, old: &Hexad) {
+        self.0.deleted(old.id.as_str());
+    }
+}
+
+/// Sync with each of `peers` in the background every `interval`
+pub fn spawn_sync(state: AppState, peers: Vec<String>, interval: std::time::Duration) {
+    let Some(crdt) = state.crdt.clone() else { return };
+    if peers.is_empty() {
+        return;
+    }
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            for peer in &peers {
+                // Peers of an edge node are often unreachable; try again next time
+                if let Err(e) = crdt.sync_with(&state, peer).await {
+                    warn!(peer = %peer, error = %e, "CRDT sync failed");
+                }
+            }
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verisim_hexad::HexadBuilder;
+
+    fn stamp(ts: u64, node: &str) -> Stamp {
+        Stamp {
+            ts,
+            node: node.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_is_commutative_and_idempotent() {
+        let mut base = EntityState::default();
+        base.apply_input(&HexadBuilder::new().with_document("Draft", "body").with_types(vec!["Person"]).build(), &stamp(1, "a"));
+
+        // Concurrent edits: a retitles and retypes, b rewrites the body later
+        let mut a = base.clone();
+        let mut edit = HexadBuilder::new().with_document("Title from a", "body").with_types(vec!["Agent"]).build();
+        edit.document.as_mut().unwrap().fields.insert("lang".to_string(), "en".to_string());
+        a.apply_input(&edit, &stamp(5, "a"));
+        let mut b = base.clone();
+        b.apply_input(&HexadBuilder::new().with_document("Draft", "body from b").build(), &stamp(7, "b"));
+        b.apply_input(&HexadBuilder::new().with_types(vec!["Person", "Author"]).build(), &stamp(8, "b"));
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab, ba);
+        let mut again = ab.clone();
+        again.merge(&b);
+        again.merge(&a);
+        assert_eq!(again, ab);
+
+        let merged = ab.to_input();
+        let document = merged.document.unwrap();
+        // b wrote the title too (unchanged, so not restamped): a's stands
+        assert_eq!(document.title, "Title from a");
+        assert_eq!(document.body, "body from b");
+        assert_eq!(document.fields["lang"], "en");
+        // a's removal of Person only removed the addition it saw
+        assert_eq!(merged.semantic.unwrap().types, ["Agent", "Author"]);
+    }
+
+    #[test]
+    fn test_concurrent_add_survives_remove() {
+        let mut a = OrSet::default();
+        a.add("x", &stamp(1, "a"));
+        let mut b = a.clone();
+        a.remove("x");
+        b.add("x", &stamp(2, "b"));
+        a.merge(&b);
+        assert!(a.contains("x"));
+        a.remove("x");
+        assert!(!a.contains("x"));
+        assert!(a.elements().is_empty());
+    }
+
+    #[test]
+    fn test_write_after_delete_revives() {
+        let mut state = EntityState::default();
+        state.apply_input(&HexadBuilder::new().with_document("Kept", "").build(), &stamp(1, "a"));
+        let mut deleted = state.clone();
+        deleted.deleted = Some(Lww::new(true, stamp(5, "a")));
+        assert!(deleted.is_deleted());
+
+        let mut edited = state.clone();
+        edited.apply_input(&HexadBuilder::new().with_document("Edited", "").build(), &stamp(9, "b"));
+        deleted.merge(&edited);
+        assert!(!deleted.is_deleted());
+
+        let mut stale = state;
+        stale.apply_input(&HexadBuilder::new().with_document("Stale", "").build(), &stamp(3, "b"));
+        let mut deleted_again = EntityState {
+            deleted: Some(Lww::new(true, stamp(5, "a"))),
+            ..Default::default()
+        };
+        deleted_again.merge(&stale);
+        assert!(deleted_again.is_deleted());
+    }
+
+    #[test]
+    fn test_conflicts_settle_by_class_policy() {
+        // Created on a, synced to b, then edited on both without syncing
+        let mut base = EntityState::default();
+        base.apply_input(&HexadBuilder::new().with_document("Draft", "body").with_types(vec!["Sensor"]).build(), &stamp(1, "a"));
+        let mut a = base.clone();
+        a.apply_input(&HexadBuilder::new().with_document("Title from a", "body").with_embedding(vec![1.0]).build(), &stamp(5, "a"));
+        let mut b = base.clone();
+        b.apply_input(&HexadBuilder::new().with_embedding(vec![2.0]).build(), &stamp(4, "b"));
+        b.apply_input(&HexadBuilder::new().with_document("Title from b", "body").build(), &stamp(7, "b"));
+
+        let merged = |policy: ConflictPolicy| {
+            let policies = ConflictPolicies {
+                classes: vec![ClassPolicy {
+                    class: "Sensor".to_string(),
+                    policy,
+                }],
+                ..Default::default()
+            };
+            let mut ab = a.clone();
+            let conflicts = ab.merge_with(&b, &policies);
+            let mut ba = b.clone();
+            ba.merge_with(&a, &policies);
+            assert_eq!(ab, ba);
+            assert_eq!(conflicts.class.as_deref(), Some("Sensor"));
+            let input = ab.to_input();
+            (input.document.unwrap().title, input.vector.unwrap().embedding[0], conflicts.settled.len())
+        };
+        assert_eq!(merged(ConflictPolicy::NewestVersion), ("Title from b".to_string(), 1.0, 2));
+        assert_eq!(merged(ConflictPolicy::PreferOrigin), ("Title from a".to_string(), 1.0, 2));
+        // b wrote the document last, so its embedding comes with it
+        let modalities = vec!["document".to_string(), "vector".to_string()];
+        assert_eq!(merged(ConflictPolicy::ModalityPriority { modalities }), ("Title from b".to_string(), 2.0, 2));
+
+        // Queued conflicts change nothing until resolved
+        let manual = ConflictPolicies {
+            default: ConflictPolicy::Manual,
+            ..Default::default()
+        };
+        let mut queued = a.clone();
+        let conflicts = queued.merge_with(&b, &manual);
+        assert_eq!(conflicts.pending.len(), 2);
+        assert!(conflicts.class.is_none());
+        assert_eq!(queued.to_input().document.unwrap().title, "Title from a");
+
+        // A write made after seeing the other is no conflict
+        let mut later = b.clone();
+        later.merge(&a);
+        later.apply_input(&HexadBuilder::new().with_document("Title after both", "body").build(), &stamp(9, "b"));
+        let mut a_then = a.clone();
+        assert!(a_then.merge_with(&later, &manual).is_empty());
+        assert_eq!(a_then.to_input().document.unwrap().title, "Title after both");
+    }
+
+    #[test]
+    fn test_conflict_policies_parse_from_config() {
+        let policies: ConflictPolicies = serde_json::from_str(
+            r#"{
+                "default": {"policy": "prefer_origin"},
+                "classes": [
+                    {"class": "https://schema.org/Person", "policy": "manual"},
+                    {"class": "Sensor", "policy": "modality_priority", "modalities": ["vector", "document"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let types = |t: &[&str]| t.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(policies.for_types(&types(&["Sensor", "https://schema.org/Person"])).1, &ConflictPolicy::Manual);
+        assert_eq!(policies.for_types(&types(&["Sensor"])).1.name(), "modality_priority");
+        assert_eq!(policies.for_types(&[]), (None, &ConflictPolicy::PreferOrigin));
+        assert_eq!(ConflictPolicies::default().default, ConflictPolicy::NewestVersion);
+    }
+}
@@ -0,0 +1,780 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Physical plan execution — runs the steps of a planned query against the
+//! hexad store and returns result rows.
+//!
+//! The planner decides the order in which a plan's nodes run; the executor
+//! follows it.  The first step produces the candidate entities and every
+//! later step narrows the candidates left by the ones before, so the most
+//! selective input runs against the whole store and the rest only probe
+//! its results.  Post-processing (ORDER BY, GROUP BY, LIMIT, projection)
+//! then runs as a chain of iterator operators over the surviving rows.
+//!
+//! ## Node Operators
+//!
+//! - Document: `Fulltext` searches the text index; a node without it scans
+//! - Vector: `Similarity` is a k-NN search for the query vector when the
+//!   node produces candidates, and scores them by cosine similarity when
+//!   it probes them
+//! - Graph: `Traversal` with an `Equality` on `source` follows that
+//!   entity's edges up to the traversal depth; without a source it keeps
+//!   entities having an outgoing edge with the predicate
+//! - Temporal: `AtTime` replaces each row with the entity as it was then
+//! - `Equality` and `Range` on any node filter on the fields of VQL
+//!   aggregate queries; `model` matches the embedding model
+//!
+//! Condition values of the form `$name` are bound from the parameters.
+//! Tensor operations, proof verification and free-form predicates have no
+//! operator and are rejected.
+//!
+//! ## Re-optimization
+//!
+//! After each step that still has two or more steps behind it, the
+//! executor compares the rows it produced with the planner's estimate.
+//! When they differ by more than the planner's `reoptimize_threshold`
+//! (in either direction) the planner re-plans the remaining steps for
+//! the actual candidate count, and execution continues with the new
+//! order.  Re-planned queries are recorded in the slow-query log.  Plans
+//! hinted `NO_REORDER` are never re-planned.
+//!
+//! ## Result Cache
+//!
+//! Results are cached by plan, parameters and store version epoch, and by
+//! the principal they were read for unless it sees every entity: the
+//! entity policy filters rows per principal, so one principal's result is
+//! never served to another.  Running the same plan with the same parameters
+//! again returns the cached result, marked `cached`, until a write to the
+//! store invalidates it.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use verisim_hexad::{Hexad, HexadId, HexadListener, HexadStore, ModalityMask};
+use verisim_planner::plan::{ConditionKind, PlanNode, PostProcessing};
+use verisim_planner::{
+    ExplainOutput, LogicalPlan, Modality, ParamValue, PhysicalPlan, Profiler, QueryRun, ReplanInfo, ResultCache,
+};
+
+use crate::vql::{
+    aggregate_field_value, aggregate_mask, compare_values, cosine_similarity, parse_timestamp, value_matches,
+    Accumulator, AggregateFunction, AGGREGATE_PAGE_SIZE, MATCH_SCAN_LIMIT,
+};
+use crate::{queries, slow_queries, ApiError, AppState};
+
+/// Columns of an entity row when the plan projects none.
+const DEFAULT_COLUMNS: &[&str] = &["id", "score", "title"];
+
+/// Actual cost of one executed step.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutedStep {
+    /// Step number in the physical plan
+    pub step: usize,
+    /// Logical node the step ran
+    pub node: usize,
+    /// Rows left after the step
+    pub rows: usize,
+    /// Wall-clock time of the step
+    pub time_ms: f64,
+}
+
+/// Result of executing a plan.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanExecution {
+    /// The plan that was run
+    pub plan: PhysicalPlan,
+    /// Result rows, one JSON object each
+    pub rows: Vec<Value>,
+    pub row_count: usize,
+    /// Per-step actuals, in run order
+    pub steps: Vec<ExecutedStep>,
+    pub elapsed_ms: f64,
+    /// Mid-query re-plans, in the order they happened
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub replans: Vec<ReplanInfo>,
+    /// Whether the result came from the result cache
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+/// Change hook that invalidates cached plan results on every store write.
+pub struct ResultCacheInvalidator(pub Arc<ResultCache<PlanExecution>>);
+
+impl ResultCacheInvalidator {
+    /// Invalidate unless `hexad` is a persisted slow-query entry, which
+    /// would otherwise evict the result of every query slow enough to log.
+    fn changed(&self, hexad: &Hexad) {
+        if hexad.id.collection() != Some(slow_queries::SLOW_QUERY_COLLECTION) {
+            self.0.invalidate();
+        }
+    }
+}
+
+impl HexadListener for ResultCacheInvalidator {
+    fn name(&self) -> &str {
+        "result-cache"
+    }
+
+    fn on_created(&self, new: &Hexad) {
+        self.changed(new);
+    }
+
+    fn on_updated(&self, _old: &Hexad, new: &Hexad) {
+        self.changed(new);
+    }
+
+    fn on_deleted(&self, old: &Hexad) {
+        self.changed(old);
+    }
+}
+
+/// An entity flowing through the operators, with its similarity score
+/// once a vector step has scored it.
+#[derive(Debug, Clone)]
+struct Row {
+    hexad: Hexad,
+    score: Option<f32>,
+}
+
+/// A row after post-processing started: an entity, or one group of a
+/// GROUP BY.
+enum Tuple {
+    Entity(Box<Row>),
+    Group(serde_json::Map<String, Value>),
+}
+
+impl Tuple {
+    fn column(&self, name: &str) -> Value {
+        match self {
+            Tuple::Entity(row) => match name {
+                "score" | "similarity" => json!(row.score),
+                name => aggregate_field_value(&row.hexad, name),
+            },
+            Tuple::Group(group) => group.get(name).cloned().unwrap_or(Value::Null),
+        }
+    }
+
+    fn into_json(self, columns: Option<&[String]>) -> Value {
+        match self {
+            Tuple::Group(group) if columns.is_none() => Value::Object(group),
+            tuple => {
+                let columns: Vec<&str> =
+                    columns.map_or_else(|| DEFAULT_COLUMNS.to_vec(), |c| c.iter().map(String::as_str).collect());
+                Value::Object(columns.into_iter().map(|c| (c.to_string(), tuple.column(c))).collect())
+            }
+        }
+    }
+}
+
+/// Executes physical plans against the hexad store.
+pub struct PlanExecutor<'a> {
+    state: &'a AppState,
+    vector: Option<Vec<f32>>,
+    params: HashMap<String, Value>,
+    query: Option<String>,
+}
+
+impl<'a> PlanExecutor<'a> {
+    /// An executor with no query vector and no parameters.
+    pub fn new(state: &'a AppState) -> Self {
+        Self {
+            state,
+            vector: None,
+            params: HashMap::new(),
+            query: None,
+        }
+    }
+
+    /// Set the query text recorded in the slow-query log.
+    pub fn with_query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Set the query vector of `Similarity` conditions.
+    pub fn with_vector(mut self, vector: Vec<f32>) -> Self {
+        self.vector = Some(vector);
+        self
+    }
+
+    /// Bind prepared-statement parameters (named with or without the
+    /// leading `$`).  The first vector parameter becomes the query vector
+    /// unless one was set.
+    pub fn with_params(mut self, params: &HashMap<String, ParamValue>) -> Self {
+        let mut names: Vec<&String> = params.keys().collect();
+        names.sort();
+        for name in names {
+            let value = match &params[name] {
+                ParamValue::String(s) => json!(s),
+                ParamValue::Int(i) => json!(i),
+                ParamValue::Float(f) => json!(f),
+                ParamValue::Bool(b) => json!(b),
+                ParamValue::Null => Value::Null,
+                ParamValue::Vector(v) => {
+                    if self.vector.is_none() {
+                        self.vector = Some(v.clone());
+                    }
+                    json!(v)
+                }
+            };
+            self.params.insert(name.trim_start_matches('$').to_string(), value);
+        }
+        self
+    }
+
+    /// Run `physical`, the optimized form of `logical`, and feed the
+    /// actual step costs back to the planner's statistics.
+    pub async fn execute(&self, logical: &LogicalPlan, physical: PhysicalPlan) -> Result<PlanExecution, ApiError> {
+        let cache = &self.state.result_cache;
+        let reader = verisim_hexad::security::current().filter(|p| !p.unrestricted);
+        let cache_key = cache.key(logical, &(&self.params, &self.vector, &reader));
+        if let Some(hit) = cache.get(&cache_key) {
+            return Ok(PlanExecution { cached: true, ..hit });
+        }
+
+        let started = Instant::now();
+        let started_at = chrono::Utc::now();
+        let mask = self.row_mask(logical);
+
+        let mut physical = physical;
+        let mut rows: Option<Vec<Row>> = None;
+        let mut steps = Vec::with_capacity(physical.steps.len());
+        let mut replans = Vec::new();
+        let mut i = 0;
+        while i < physical.steps.len() {
+            queries::checkpoint()?;
+            let step = &physical.steps[i];
+            let node = logical
+                .nodes
+                .get(step.node)
+                .ok_or_else(|| ApiError::BadRequest(format!("Plan step {} runs unknown node {}", step.step, step.node)))?;
+            let step_started = Instant::now();
+            let last = i + 1 == physical.steps.len();
+            let output = self.run_node(node, rows.take(), mask, last).await?;
+            steps.push(ExecutedStep {
+                step: step.step,
+                node: step.node,
+                rows: output.len(),
+                time_ms: step_started.elapsed().as_secs_f64() * 1000.0,
+            });
+            let estimated_rows = step.cost.estimated_rows;
+            rows = Some(output);
+            i += 1;
+
+            // Feedback point: re-plan the rest when the estimate was far off
+            // and there is more than one step left to reorder (and the query
+            // allows reordering)
+            if physical.steps.len() - i >= 2 && !logical.hints.no_reorder {
+                let actual_rows = steps[i - 1].rows as u64;
+                let error_ratio = actual_rows.max(1).max(estimated_rows.max(1)) as f64
+                    / actual_rows.max(1).min(estimated_rows.max(1)) as f64;
+                let planner = self
+                    .state
+                    .planner
+                    .lock()
+                    .map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+                let threshold = planner.config().reoptimize_threshold;
+                if threshold > 0.0 && error_ratio > threshold {
+                    let replanned = planner
+                        .reoptimize(logical, &physical, i, actual_rows)
+                        .map_err(|e| ApiError::Internal(e.to_string()))?;
+                    replans.push(ReplanInfo {
+                        after_step: i,
+                        estimated_rows,
+                        actual_rows,
+                        error_ratio,
+                        previous_order: physical.steps[i..].iter().map(|s| s.node).collect(),
+                        new_order: replanned.steps[i..].iter().map(|s| s.node).collect(),
+                    });
+                    physical = replanned;
+                }
+            }
+        }
+
+        let projection: Vec<String> = logical.nodes.iter().flat_map(|n| n.projections.iter().cloned()).collect();
+        let rows = post_process(rows.unwrap_or_default(), &logical.post_processing, projection)?;
+
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let log = &self.state.slow_query_log;
+        let capture_explain = log.is_offender(elapsed_ms, &physical, &replans) && log.config().capture_explain;
+        let explain = {
+            let mut planner = self
+                .state
+                .planner
+                .lock()
+                .map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+            let mut profiler = Profiler::new(format!("exec-{}", started_at.timestamp_millis()), &physical);
+            for (i, step) in steps.iter().enumerate() {
+                profiler.record_step(i, step.time_ms, step.rows as u64, started_at, chrono::Utc::now());
+            }
+            let profile = profiler.finish(planner.stats_mut());
+            capture_explain.then(|| ExplainOutput::from_physical_plan(&physical, planner.config()).with_profile(&profile))
+        };
+
+        let step_times: Vec<(Modality, f64, usize)> = physical
+            .steps
+            .iter()
+            .zip(&steps)
+            .map(|(planned, ran)| (planned.modality, ran.time_ms, ran.rows))
+            .collect();
+        let logged = log.record_run(QueryRun {
+            query_text: self.query.as_deref(),
+            actual_ms: elapsed_ms,
+            plan: &physical,
+            step_times: &step_times,
+            replans: &replans,
+            parameters: self.params.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            explain,
+        });
+        if let Some(entry) = logged.filter(|_| log.config().persist) {
+            self.state.slow_query_writer.submit(entry);
+        }
+
+        let execution = PlanExecution {
+            plan: physical,
+            row_count: rows.len(),
+            rows,
+            steps,
+            elapsed_ms,
+            replans,
+            cached: false,
+        };
+        if cache.is_enabled() {
+            let bytes = serde_json::to_vec(&execution).map_or(usize::MAX, |b| b.len());
+            cache.insert(cache_key, execution.clone(), bytes);
+        }
+        Ok(execution)
+    }
+
+    /// Modalities each row must load for the conditions, projections and
+    /// post-processing of `plan`.
+    fn row_mask(&self, plan: &LogicalPlan) -> ModalityMask {
+        let mut fields: Vec<&str> = DEFAULT_COLUMNS.to_vec();
+        for node in &plan.nodes {
+            fields.extend(node.projections.iter().map(String::as_str));
+            fields.extend(node.conditions.iter().filter_map(|c| match c {
+                ConditionKind::Equality { field, .. } | ConditionKind::Range { field, .. } => Some(field.as_str()),
+                _ => None,
+            }));
+        }
+        for op in &plan.post_processing {
+            match op {
+                PostProcessing::OrderBy { fields: order } => fields.extend(order.iter().map(|(f, _)| f.as_str())),
+                PostProcessing::GroupBy { fields: group, .. } => fields.extend(group.iter().map(String::as_str)),
+                PostProcessing::Project { columns } => fields.extend(columns.iter().map(String::as_str)),
+                PostProcessing::Limit { .. } => {}
+            }
+        }
+        let mut mask = aggregate_mask(fields);
+        mask.vector |= plan.nodes.iter().any(|n| {
+            n.modality == Modality::Vector
+                || n.conditions.iter().any(|c| matches!(c, ConditionKind::Equality { field, .. } if field == "model"))
+        });
+        mask
+    }
+
+    /// `value`, or the parameter it names.
+    fn bind(&self, value: &str) -> Result<String, ApiError> {
+        match value.strip_prefix('$') {
+            Some(name) => match self.params.get(name) {
+                Some(Value::String(s)) => Ok(s.clone()),
+                Some(other) => Ok(other.to_string()),
+                None => Err(ApiError::BadRequest(format!("Unbound parameter ${}", name))),
+            },
+            None => Ok(value.to_string()),
+        }
+    }
+
+    fn query_vector(&self) -> Result<&[f32], ApiError> {
+        self.vector
+            .as_deref()
+            .ok_or_else(|| ApiError::BadRequest("Similarity needs a query vector parameter".to_string()))
+    }
+
+    /// Run one plan node: produce its rows when `input` is `None`, else
+    /// keep (and score, or rewind) the input rows it matches.  `last`
+    /// says whether later steps will narrow the output further.
+    async fn run_node(
+        &self,
+        node: &PlanNode,
+        input: Option<Vec<Row>>,
+        mask: ModalityMask,
+        last: bool,
+    ) -> Result<Vec<Row>, ApiError> {
+        let store = &self.state.hexad_store;
+        let internal = |e: verisim_hexad::HexadError| ApiError::Internal(e.to_string());
+
+        let mut filters = Vec::new();
+        let mut source = None;
+        let mut model = None;
+        let mut primary = None;
+        for condition in &node.conditions {
+            match condition {
+                ConditionKind::Equality { field, value } if field == "source" && node.modality == Modality::Graph => {
+                    source = Some(self.bind(value)?)
+                }
+                ConditionKind::Equality { field, value } if field == "model" => model = Some(self.bind(value)?),
+                ConditionKind::Equality { field, value } => filters.push((field.clone(), Some(self.bind(value)?), None)),
+                ConditionKind::Range { field, low, high } => {
+                    filters.push((field.clone(), Some(self.bind(low)?), Some(self.bind(high)?)))
+                }
+                ConditionKind::Fulltext { .. }
+                | ConditionKind::Similarity { .. }
+                | ConditionKind::Traversal { .. }
+                | ConditionKind::AtTime { .. } => primary = Some(condition),
+                ConditionKind::TensorOp { .. } | ConditionKind::ProofVerification { .. } | ConditionKind::Predicate { .. } => {
+                    return Err(ApiError::BadRequest(format!(
+                        "{} condition {:?} cannot be executed",
+                        node.modality, condition
+                    )))
+                }
+            }
+        }
+        let keep = |row: &Row| {
+            let model_matches = model.as_ref().is_none_or(|model| {
+                row.hexad
+                    .embedding
+                    .as_ref()
+                    .and_then(|e| e.metadata.get("model"))
+                    .is_some_and(|m| m == model)
+            });
+            model_matches
+                && filters.iter().all(|(field, low, high)| {
+                    let value = aggregate_field_value(&row.hexad, field);
+                    match (low, high) {
+                        (Some(expected), None) => value_matches(&value, expected),
+                        (Some(low), Some(high)) => {
+                            !value.is_null()
+                                && compare_values(&value, &json!(low)).is_ge()
+                                && compare_values(&value, &json!(high)).is_le()
+                        }
+                        _ => true,
+                    }
+                })
+        };
+        let filtered = model.is_some() || !filters.is_empty();
+        let rows = |hexads: Vec<Hexad>| hexads.into_iter().map(|hexad| Row { hexad, score: None });
+
+        let output: Vec<Row> = match (primary, input) {
+            (Some(ConditionKind::Fulltext { query }), input) => {
+                let query = self.bind(query)?;
+                let limit = match (&input, node.early_limit) {
+                    (None, Some(limit)) if !filtered => limit,
+                    _ => MATCH_SCAN_LIMIT,
+                };
+                let hits = store.search_text(&query, limit).await.map_err(internal)?;
+                semi_join(input, rows(hits).collect())
+            }
+            (Some(ConditionKind::Similarity { k }), Some(input)) => {
+                let vector = self.query_vector()?;
+                input
+                    .into_iter()
+                    .map(|mut row| {
+                        row.score = row.hexad.embedding.as_ref().map(|e| cosine_similarity(vector, &e.vector));
+                        row
+                    })
+                    .filter(|row| keep(row))
+                    .take(if last { usize::MAX } else { MATCH_SCAN_LIMIT.max(*k) })
+                    .collect()
+            }
+            (Some(ConditionKind::Similarity { k }), None) => {
+                // A final k-NN returns k hits, widening the search until
+                // that many pass the filters; one that later steps narrow
+                // returns every neighbour they may need.
+                let vector = self.query_vector()?;
+                let want = if last { node.early_limit.unwrap_or(*k) } else { MATCH_SCAN_LIMIT };
+                let mut fetch = if filtered && last { want.saturating_mul(4).min(MATCH_SCAN_LIMIT) } else { want };
+                loop {
+                    let hits = store.search_similar(vector, fetch).await.map_err(internal)?;
+                    let exhausted = hits.len() < fetch || fetch >= MATCH_SCAN_LIMIT;
+                    let scored: Vec<Row> = hits
+                        .into_iter()
+                        .map(|hexad| Row {
+                            score: hexad.embedding.as_ref().map(|e| cosine_similarity(vector, &e.vector)),
+                            hexad,
+                        })
+                        .filter(|row| keep(row))
+                        .take(want)
+                        .collect();
+                    if scored.len() >= want || exhausted {
+                        break scored;
+                    }
+                    fetch = fetch.saturating_mul(4).min(MATCH_SCAN_LIMIT);
+                }
+            }
+            (Some(ConditionKind::Traversal { predicate, depth }), input) => match source {
+                Some(source) => {
+                    let reached = self.traverse(&HexadId::new(source), predicate, depth.unwrap_or(1)).await?;
+                    semi_join(input, rows(reached).collect())
+                }
+                None => {
+                    // No index from predicate to source: probe each row
+                    let candidates = match input {
+                        Some(input) => input,
+                        None => rows(self.scan(mask, MATCH_SCAN_LIMIT).await?).collect(),
+                    };
+                    let mut matched = Vec::new();
+                    for row in candidates {
+                        if !store.query_related(&row.hexad.id, predicate).await.map_err(internal)?.is_empty() {
+                            matched.push(row);
+                        }
+                    }
+                    matched
+                }
+            },
+            (Some(ConditionKind::AtTime { timestamp }), input) => {
+                let at = parse_timestamp(&self.bind(timestamp)?)?;
+                let candidates = match input {
+                    Some(input) => input,
+                    None => rows(self.scan(mask, MATCH_SCAN_LIMIT).await?).collect(),
+                };
+                let mut rewound = Vec::new();
+                for row in candidates {
+                    if let Some(hexad) = store.at_time(&row.hexad.id, at).await.map_err(internal)? {
+                        rewound.push(Row { hexad, score: row.score });
+                    }
+                }
+                rewound
+            }
+            (_, Some(input)) => input,
+            (_, None) => rows(self.scan(mask, usize::MAX).await?).collect(),
+        };
+
+        let mut output: Vec<Row> = output.into_iter().filter(|row| keep(row)).collect();
+        if let Some(limit) = node.early_limit {
+            output.truncate(limit);
+        }
+        Ok(output)
+    }
+
+    /// Every live entity, up to `limit`, loading `mask`.
+    async fn scan(&self, mask: ModalityMask, limit: usize) -> Result<Vec<Hexad>, ApiError> {
+        let mut hexads = Vec::new();
+        while hexads.len() < limit {
+            queries::checkpoint()?;
+            let page = self
+                .state
+                .hexad_store
+                .list_with(AGGREGATE_PAGE_SIZE.min(limit - hexads.len()), hexads.len(), mask)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            let done = page.len() < AGGREGATE_PAGE_SIZE.min(limit - hexads.len());
+            hexads.extend(page);
+            if done {
+                break;
+            }
+        }
+        Ok(hexads)
+    }
+
+    /// Entities reachable from `source` over `predicate` edges in at most
+    /// `depth` hops, nearest first.
+    async fn traverse(&self, source: &HexadId, predicate: &str, depth: u32) -> Result<Vec<Hexad>, ApiError> {
+        let mut seen = std::collections::HashSet::from([source.to_string()]);
+        let mut frontier = vec![source.clone()];
+        let mut reached = Vec::new();
+        for _ in 0..depth.max(1) {
+            let mut next = Vec::new();
+            for id in &frontier {
+                let related = self
+                    .state
+                    .hexad_store
+                    .query_related(id, predicate)
+                    .await
+                    .map_err(|e| ApiError::Internal(e.to_string()))?;
+                for hexad in related {
+                    if seen.insert(hexad.id.to_string()) {
+                        next.push(hexad.id.clone());
+                        reached.push(hexad);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        Ok(reached)
+    }
+}
+
+/// The rows of `input` (in their order) that `matched` also has, or all of
+/// `matched` when there is no input yet.  Duplicates are dropped.
+fn semi_join(input: Option<Vec<Row>>, matched: Vec<Row>) -> Vec<Row> {
+    let mut seen = std::collections::HashSet::new();
+    let matched: Vec<Row> = matched.into_iter().filter(|row| seen.insert(row.hexad.id.to_string())).collect();
+    match input {
+        Some(input) => input.into_iter().filter(|row| seen.contains(row.hexad.id.as_str())).collect(),
+        None => matched,
+    }
+}
+
+/// Run the post-processing operators over `rows`, in plan order, and
+/// render the result.  `projection` lists the columns the plan's nodes
+/// project, if any.
+fn post_process(rows: Vec<Row>, ops: &[PostProcessing], projection: Vec<String>) -> Result<Vec<Value>, ApiError> {
+    let mut columns = (!projection.is_empty()).then_some(projection);
+    let mut stream: Box<dyn Iterator<Item = Tuple> + Send> = Box::new(rows.into_iter().map(|row| Tuple::Entity(Box::new(row))));
+    for op in ops {
+        stream = match op {
+            PostProcessing::OrderBy { fields } => {
+                let mut tuples: Vec<Tuple> = stream.collect();
+                tuples.sort_by(|a, b| {
+                    fields.iter().fold(std::cmp::Ordering::Equal, |order, (field, ascending)| {
+                        order.then_with(|| {
+                            // Nulls last in either direction
+                            match (a.column(field), b.column(field)) {
+                                (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
+                                (Value::Null, _) => std::cmp::Ordering::Greater,
+                                (_, Value::Null) => std::cmp::Ordering::Less,
+                                (x, y) if *ascending => compare_values(&x, &y),
+                                (x, y) => compare_values(&y, &x),
+                            }
+                        })
+                    })
+                });
+                Box::new(tuples.into_iter())
+            }
+            PostProcessing::Limit { count } => Box::new(stream.take(*count)),
+            PostProcessing::GroupBy { fields, aggregates } => {
+                columns = None;
+                Box::new(group_by(stream, fields, aggregates)?.into_iter())
+            }
+            PostProcessing::Project { columns: project } => {
+                columns = Some(project.clone());
+                stream
+            }
+        };
+    }
+    Ok(stream.map(|tuple| tuple.into_json(columns.as_deref())).collect())
+}
+
+/// Group `tuples` by `fields` and fold each `FUNC(field)` aggregate per
+/// group.  Without fields there is one group, even over no rows.
+fn group_by(
+    tuples: impl Iterator<Item = Tuple>,
+    fields: &[String],
+    aggregates: &[String],
+) -> Result<Vec<Tuple>, ApiError> {
+    let aggregates: Vec<(AggregateFunction, Option<String>, &String)> = aggregates
+        .iter()
+        .map(|aggregate| {
+            let parsed = aggregate.split_once('(').and_then(|(name, rest)| {
+                let field = rest.strip_suffix(')')?.trim();
+                let function = AggregateFunction::parse(name.trim())?;
+                Some((function, (field != "*").then(|| field.to_string()), aggregate))
+            });
+            parsed.ok_or_else(|| ApiError::BadRequest(format!("Unsupported aggregate '{}'", aggregate)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut groups: std::collections::BTreeMap<String, (Vec<Value>, Vec<Accumulator>)> =
+        std::collections::BTreeMap::new();
+    for tuple in tuples {
+        let key: Vec<Value> = fields.iter().map(|f| tuple.column(f)).collect();
+        let (_, accumulators) = groups
+            .entry(Value::Array(key.clone()).to_string())
+            .or_insert_with(|| (key, aggregates.iter().map(|_| Accumulator::default()).collect()));
+        for ((_, field, _), accumulator) in aggregates.iter().zip(accumulators.iter_mut()) {
+            accumulator.add(field.as_ref().map(|f| tuple.column(f)));
+        }
+    }
+    if fields.is_empty() && groups.is_empty() {
+        groups.insert(String::new(), (vec![], aggregates.iter().map(|_| Accumulator::default()).collect()));
+    }
+
+    Ok(groups
+        .into_values()
+        .map(|(key, accumulators)| {
+            let mut group: serde_json::Map<String, Value> = fields.iter().cloned().zip(key).collect();
+            for ((function, _, column), accumulator) in aggregates.iter().zip(&accumulators) {
+                group.insert(column.to_string(), accumulator.finish(*function));
+            }
+            Tuple::Group(group)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, title: &str, score: Option<f32>) -> Row {
+        let now = chrono::Utc::now();
+        let hexad = Hexad {
+            id: HexadId::new(id),
+            status: verisim_hexad::HexadStatus {
+                id: HexadId::new(id),
+                created_at: now,
+                modified_at: now,
+                version: 1,
+                modality_status: Default::default(),
+                expires_at: None,
+            },
+            graph_node: None,
+            embedding: None,
+            tensor: None,
+            semantic: None,
+            document: Some(verisim_hexad::Document {
+                id: id.to_string(),
+                title: title.to_string(),
+                body: String::new(),
+                fields: HashMap::new(),
+                metadata: HashMap::new(),
+            }),
+            version_count: 1,
+            provenance_chain_length: 0,
+            spatial_data: None,
+        };
+        Row { hexad, score }
+    }
+
+    #[test]
+    fn test_post_process_orders_nulls_last_and_limits() {
+        let rows = vec![row("a", "x", Some(0.2)), row("b", "y", None), row("c", "z", Some(0.9))];
+        let ops = [
+            PostProcessing::OrderBy {
+                fields: vec![("similarity".to_string(), false)],
+            },
+            PostProcessing::Limit { count: 2 },
+        ];
+        let out = post_process(rows, &ops, vec![]).unwrap();
+        let ids: Vec<&str> = out.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["c", "a"]);
+        assert!(out[0].get("title").is_some());
+    }
+
+    #[test]
+    fn test_post_process_group_by() {
+        let rows = vec![row("a", "x", None), row("b", "x", None), row("c", "y", None)];
+        let ops = [
+            PostProcessing::GroupBy {
+                fields: vec!["title".to_string()],
+                aggregates: vec!["COUNT(*)".to_string()],
+            },
+            PostProcessing::OrderBy {
+                fields: vec![("COUNT(*)".to_string(), false)],
+            },
+        ];
+        let out = post_process(rows, &ops, vec![]).unwrap();
+        assert_eq!(out, vec![json!({"title": "x", "COUNT(*)": 2}), json!({"title": "y", "COUNT(*)": 1})]);
+
+        let ops = [PostProcessing::GroupBy {
+            fields: vec![],
+            aggregates: vec!["MEDIAN(version)".to_string()],
+        }];
+        assert!(post_process(vec![], &ops, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_semi_join_keeps_input_order() {
+        let input = vec![row("a", "", None), row("b", "", None), row("c", "", None)];
+        let matched = vec![row("c", "", None), row("a", "", None), row("a", "", None)];
+        let ids: Vec<String> = semi_join(Some(input), matched).iter().map(|r| r.hexad.id.to_string()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+}
@@ -17,6 +17,34 @@
 //! - **Repair**: Return results and trigger normalization on drifted stores.
 //! - **Tolerate**: Return all results, annotating drifted ones.
 //! - **Latest**: Return only the most recent version from each store.
+//!
+//! ## Distributed Plans
+//!
+//! A query carrying a logical `plan` is planned with
+//! [`Planner::distribute`]: the plan is pushed to every peer that supports
+//! its modalities and fits the cost budget (each peer's measured response
+//! time is its network cost), run there through `/query/execute`, and the
+//! partial results are merged — top-k by score for vector similarity,
+//! reciprocal rank fusion for full-text, union otherwise.  Text and vector
+//! queries without a plan are merged the same way.
+//!
+//! ## Federated Search
+//!
+//! `/federation/search/text` and `/federation/search/vector` broadcast one
+//! search to every matching peer, each with its own timeout.  Each peer's
+//! scores are normalized (min-max by default for text, whose BM25 scores
+//! differ in scale between indexes; left as they are for vector
+//! similarities), the hits merged by score, and each annotated with the
+//! instance it came from.  Peers that fail or time out are reported rather
+//! than failing the search.  Queries and searches are authenticated like
+//! the local ones, and their answers redacted for the caller's roles.
+//!
+//! ## Cluster Health
+//!
+//! `/federation/status` gathers every peer into a [`Topology`] — registry
+//! peers, the primary and replicas, CRDT peers — merged by ID or endpoint,
+//! checks each reachable one's `/health` for its version, and reports lag,
+//! last sync and pending conflicts alongside.
 
 use axum::{
     extract::{Query, State},
@@ -27,8 +55,18 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::{error, info, warn, instrument};
+use crate::peer_auth::{PeerAuth, PeerCertificate, PeerScope, TokenRequest, TokenResponse};
+use verisim_planner::{
+    DistributedPlan, LogicalPlan, MergeStrategy, MergedRow, NetworkCost, PartialResult, PeerProfile, Planner, RemoteRow,
+};
+
+/// Time allowed for one peer to answer
+const PEER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Longest per-peer timeout a federated search may ask for
+const MAX_PEER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
 
 // ---------------------------------------------------------------------------
 // Types
@@ -76,6 +114,7 @@ pub struct FederationQueryRequest {
     /// Pattern to match stores (e.g., "/universities/*", or a specific store ID).
     pub pattern: String,
     /// Modalities to query.
+    #[serde(default)]
     pub modalities: Vec<String>,
     /// Drift policy.
     #[serde(default)]
@@ -86,6 +125,17 @@ pub struct FederationQueryRequest {
     pub text_query: Option<String>,
     /// Optional vector query.
     pub vector_query: Option<Vec<f32>>,
+    /// Logical plan to distribute across the peers, instead of a text or
+    /// vector query.
+    #[serde(default)]
+    pub plan: Option<LogicalPlan>,
+    /// Parameter bindings sent with the plan.
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+    /// Leave out peers whose estimated cost, network included, exceeds
+    /// this many milliseconds.
+    #[serde(default)]
+    pub budget_ms: Option<f64>,
 }
 
 /// A single result from a federated query.
@@ -101,6 +151,9 @@ pub struct FederationResult {
     pub drifted: bool,
     /// Result data (modality-dependent).
     pub data: serde_json::Value,
+    /// Every store that returned the entity, when merged from several.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
 }
 
 /// Response for federation queries.
@@ -114,6 +167,156 @@ pub struct FederationQueryResponse {
     pub stores_excluded: Vec<String>,
     /// The drift policy applied.
     pub drift_policy: DriftPolicy,
+    /// The distributed plan, for queries carrying a logical plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<DistributedPlan>,
+}
+
+/// How peer scores are made comparable before a federated search merges them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalization {
+    /// Scale each peer's scores to 0.0-1.0, its best hit scoring 1.0.
+    MinMax,
+    /// Use the scores as the peers return them.
+    None,
+}
+
+fn default_search_pattern() -> String {
+    "*".to_string()
+}
+
+/// A federated full-text search.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederationTextSearchRequest {
+    pub query: String,
+    /// Pattern to match stores (default: every peer).
+    #[serde(default = "default_search_pattern")]
+    pub pattern: String,
+    /// Maximum results, overall and per peer.
+    pub limit: Option<usize>,
+    /// Milliseconds each peer has to answer (default 10 000, at most 60 000).
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub drift_policy: DriftPolicy,
+    /// Defaults to min-max.
+    pub normalization: Option<ScoreNormalization>,
+}
+
+/// A federated vector similarity search.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederationVectorSearchRequest {
+    pub vector: Vec<f32>,
+    /// Pattern to match stores (default: every peer).
+    #[serde(default = "default_search_pattern")]
+    pub pattern: String,
+    /// Maximum results, overall and per peer.
+    pub limit: Option<usize>,
+    /// Milliseconds each peer has to answer (default 10 000, at most 60 000).
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub drift_policy: DriftPolicy,
+    /// Defaults to none: similarities are already comparable.
+    pub normalization: Option<ScoreNormalization>,
+}
+
+/// One hit from a federated search.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederationSearchHit {
+    pub hexad_id: String,
+    /// Score after normalization, by which hits are ranked.
+    pub score: f64,
+    /// Score as the origin returned it.
+    pub raw_score: f64,
+    /// Store the hit came from.
+    pub origin: String,
+    /// API endpoint of that store.
+    pub origin_endpoint: String,
+    /// The hit as the origin returned it.
+    pub data: serde_json::Value,
+    /// Every store that returned the entity, when several did.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+}
+
+/// A peer that did not answer a federated search.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeerFailure {
+    pub store_id: String,
+    pub error: String,
+}
+
+/// Response for federated searches.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederationSearchResponse {
+    pub results: Vec<FederationSearchHit>,
+    /// Stores that were searched, including those that failed.
+    pub stores_queried: Vec<String>,
+    /// Stores that failed or timed out.
+    pub stores_failed: Vec<PeerFailure>,
+    /// Stores excluded by drift policy.
+    pub stores_excluded: Vec<String>,
+    pub normalization: ScoreNormalization,
+}
+
+/// How a peer is related to this instance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerRole {
+    /// Registered in the federation registry.
+    Federation,
+    /// The primary this replica follows.
+    Primary,
+    /// A replica following this instance.
+    Replica,
+    /// A CRDT sync peer.
+    Crdt,
+}
+
+/// One peer's health, as seen from this instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerHealth {
+    /// Store, follower or node ID; the endpoint when no ID is known.
+    pub peer: String,
+    pub endpoint: Option<String>,
+    pub roles: Vec<PeerRole>,
+    /// Whether the peer answered a health check; unknown for replicas,
+    /// which poll this instance and have no endpoint to check.
+    pub reachable: Option<bool>,
+    pub response_time_ms: Option<u64>,
+    /// Version the peer reported.
+    pub version: Option<String>,
+    /// Whether that version can exchange data with this one.
+    pub compatible: Option<bool>,
+    /// Replicated changes not yet applied.
+    pub lag_entries: Option<u64>,
+    /// Seconds since the oldest change not yet applied was made.
+    pub lag_seconds: Option<f64>,
+    /// Last replication poll or CRDT exchange.
+    pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last federation heartbeat (RFC 3339).
+    pub last_seen: Option<String>,
+    /// CRDT conflicts with the peer awaiting an operator.
+    pub pending_conflicts: usize,
+    pub error: Option<String>,
+}
+
+/// Response for GET /federation/status.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederationStatus {
+    pub store_id: String,
+    pub version: String,
+    /// `healthy`, or `degraded` when a peer is unreachable, incompatible,
+    /// failing to sync or has conflicts waiting.
+    pub status: String,
+    pub peers: Vec<PeerHealth>,
+}
+
+/// The search a federated search runs on each peer.
+#[derive(Debug, Clone)]
+enum SearchQuery {
+    Text(String),
+    Vector(Vec<f32>),
 }
 
 /// Registration request to join the federation.
@@ -155,6 +358,12 @@ pub struct FederationState {
     /// Parsed from `VERISIM_FEDERATION_KEYS` env var (comma-separated `store_id:key`).
     /// When empty, federation registration is disabled (closed by default).
     federation_keys: Arc<HashMap<String, String>>,
+    /// Planner for distributed plans; without one, plan queries are refused.
+    planner: Option<Arc<Mutex<Planner>>>,
+    /// Network cost model for distributed plans.
+    pub network_cost: NetworkCost,
+    /// Tokens and mutual TLS for calls to peers, and tokens issued to them.
+    pub peer_auth: PeerAuth,
 }
 
 impl FederationState {
@@ -166,6 +375,30 @@ pub fn new(self_store_id: String, self_endpoint: String) -> Self {
             self_endpoint,
             strict_drift_threshold: 0.3,
             federation_keys: Arc::new(keys),
+            planner: None,
+            network_cost: NetworkCost::default(),
+            peer_auth: PeerAuth::default(),
+        }
+    }
+
+    /// Plan distributed queries with `planner`.
+    pub fn with_planner(mut self, planner: Arc<Mutex<Planner>>) -> Self {
+        self.planner = Some(planner);
+        self
+    }
+
+    /// Authenticate calls to and from peers with `peer_auth`.
+    pub fn with_peer_auth(mut self, peer_auth: PeerAuth) -> Self {
+        self.peer_auth = peer_auth;
+        self
+    }
+
+    /// Record a peer's measured response time, the network cost of later plans.
+    fn record_response_time(&self, store_id: &str, elapsed: std::time::Duration) {
+        if let Ok(mut peers) = self.peers.write() {
+            if let Some(peer) = peers.get_mut(store_id) {
+                peer.response_time_ms = Some(elapsed.as_millis() as u64);
+            }
         }
     }
 
@@ -245,14 +478,25 @@ fn sha256_hex(input: &str) -> String {
 // Router
 // ---------------------------------------------------------------------------
 
-/// Build federation API routes.
+/// Build the federation peer protocol routes.
 pub fn federation_router(state: FederationState) -> Router {
     Router::new()
         .route("/federation/peers", get(list_peers))
         .route("/federation/register", post(register_peer))
         .route("/federation/heartbeat", post(heartbeat))
-        .route("/federation/query", post(federation_query))
         .route("/federation/deregister/{store_id}", post(deregister_peer))
+        .route("/federation/token", post(issue_token))
+        .with_state(state)
+}
+
+/// Build the federated query and search routes.  Their answers carry
+/// entity content, so they are to be mounted behind the authentication and
+/// redaction middleware.
+pub fn federation_query_router<S>(state: FederationState) -> Router<S> {
+    Router::new()
+        .route("/federation/query", post(federation_query))
+        .route("/federation/search/text", post(federation_search_text))
+        .route("/federation/search/vector", post(federation_search_vector))
         .with_state(state)
 }
 
@@ -366,6 +610,27 @@ async fn heartbeat(
     }
 }
 
+/// Issue a peer a short-lived token for one scope, in exchange for its
+/// pre-shared secret (and its pinned certificate, if it has one).
+#[instrument(skip(state, headers, certificate))]
+async fn issue_token(
+    State(state): State<FederationState>,
+    headers: HeaderMap,
+    certificate: Option<axum::Extension<PeerCertificate>>,
+    Json(request): Json<TokenRequest>,
+) -> Result<Json<TokenResponse>, (StatusCode, String)> {
+    let secret = headers
+        .get("X-Federation-PSK")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let certificate = certificate.and_then(|c| c.0 .0);
+    state
+        .peer_auth
+        .issue(&request, secret, certificate.as_deref())
+        .map(Json)
+        .inspect_err(|(_, e)| warn!(store_id = %request.store_id, error = %e, "Federation token refused"))
+}
+
 /// Remove a peer from the federation.
 #[instrument(skip(state))]
 async fn deregister_peer(
@@ -390,63 +655,18 @@ async fn deregister_peer(
 }
 
 /// Execute a federated query across matching peer stores.
-#[instrument(skip(state))]
+#[instrument(skip(state, request))]
 async fn federation_query(
     State(state): State<FederationState>,
     Json(request): Json<FederationQueryRequest>,
 ) -> Result<Json<FederationQueryResponse>, StatusCode> {
-    let limit = request.limit.unwrap_or(100).min(1000);
-
-    // Collect matching stores and apply drift policy (hold lock briefly)
-    let (stores_to_query, stores_excluded) = {
-        let peers = state.peers.read().map_err(|_| {
-            tracing::error!("Federation peers RwLock poisoned");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-        let matching: Vec<PeerStore> = peers
-            .values()
-            .filter(|p| pattern_matches(&request.pattern, &p.store_id))
-            .filter(|p| {
-                request
-                    .modalities
-                    .iter()
-                    .all(|m| p.modalities.iter().any(|pm| pm == m))
-            })
-            .cloned()
-            .collect();
-
-        let mut included = Vec::new();
-        let mut excluded = Vec::new();
-
-        for store in matching {
-            // Skip self to prevent infinite recursion
-            if store.store_id == state.self_store_id {
-                continue;
-            }
-
-            let include = match request.drift_policy {
-                DriftPolicy::Strict => {
-                    store.trust_level >= (1.0 - state.strict_drift_threshold)
-                }
-                DriftPolicy::Repair | DriftPolicy::Tolerate | DriftPolicy::Latest => true,
-            };
-
-            if include {
-                included.push(store);
-            } else {
-                info!(
-                    store_id = %store.store_id,
-                    trust = store.trust_level,
-                    "Excluded store due to Strict drift policy"
-                );
-                excluded.push(store.store_id.clone());
-            }
-        }
+    if request.plan.is_some() {
+        return distributed_query(state, request).await.map(Json);
+    }
 
-        (included, excluded)
-    };
-    // RwLock dropped here — safe to make async HTTP calls
+    let limit = request.limit.unwrap_or(100).min(1000);
+    let (stores_to_query, stores_excluded) =
+        select_stores(&state, &request.pattern, &request.modalities, request.drift_policy)?;
 
     let stores_queried: Vec<String> = stores_to_query
         .iter()
@@ -462,18 +682,22 @@ async fn federation_query(
     let mut handles = Vec::new();
     for store in stores_to_query {
         let client = client.clone();
+        let state = state.clone();
         let text_q = text_query.clone();
         let vector_q = vector_query.clone();
 
         let handle = tokio::spawn(async move {
-            let timeout = std::time::Duration::from_secs(10);
-            match tokio::time::timeout(
-                timeout,
-                query_single_peer(&client, &store, text_q.as_deref(), vector_q.as_deref(), limit),
+            let started = std::time::Instant::now();
+            let results = match tokio::time::timeout(
+                PEER_TIMEOUT,
+                query_single_peer(&client, &state.peer_auth, &store, text_q.as_deref(), vector_q.as_deref(), limit),
             )
             .await
             {
-                Ok(Ok(results)) => results,
+                Ok(Ok(results)) => {
+                    state.record_response_time(&store.store_id, started.elapsed());
+                    results
+                }
                 Ok(Err(e)) => {
                     warn!(store_id = %store.store_id, error = %e, "Peer query failed");
                     Vec::new()
@@ -482,35 +706,411 @@ async fn federation_query(
                     warn!(store_id = %store.store_id, "Peer query timed out after 10s");
                     Vec::new()
                 }
+            };
+            PartialResult {
+                store_id: store.store_id,
+                rows: results
+                    .into_iter()
+                    .map(|r| RemoteRow { id: r.hexad_id, score: r.score, data: r.data })
+                    .collect(),
             }
         });
         handles.push(handle);
     }
 
     // Collect results from all peers
-    let mut all_results: Vec<FederationResult> = Vec::new();
+    let mut partials = Vec::new();
     for handle in handles {
         match handle.await {
-            Ok(mut results) => all_results.append(&mut results),
+            Ok(partial) => partials.push(partial),
             Err(e) => warn!(error = %e, "Peer query task panicked"),
         }
     }
 
-    // Sort by score descending and apply global limit
-    all_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-    all_results.truncate(limit);
+    // Text scores are not comparable across stores; vector scores are
+    let merge = if text_query.is_some() {
+        MergeStrategy::RankFusion { k: MergeStrategy::RRF_K }
+    } else if vector_query.is_some() {
+        MergeStrategy::TopK
+    } else {
+        MergeStrategy::Union
+    };
 
     Ok(Json(FederationQueryResponse {
-        results: all_results,
+        results: merge.merge(partials, limit).into_iter().map(merged_result).collect(),
         stores_queried,
         stores_excluded,
         drift_policy,
+        plan: None,
     }))
 }
 
+/// Broadcast a full-text search to the matching peers that index documents.
+#[instrument(skip(state, request))]
+async fn federation_search_text(
+    State(state): State<FederationState>,
+    Json(request): Json<FederationTextSearchRequest>,
+) -> Result<Json<FederationSearchResponse>, StatusCode> {
+    if request.query.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let normalization = request.normalization.unwrap_or(ScoreNormalization::MinMax);
+    federated_search(
+        state,
+        SearchQuery::Text(request.query),
+        &request.pattern,
+        request.drift_policy,
+        request.limit,
+        request.timeout_ms,
+        normalization,
+    )
+    .await
+    .map(Json)
+}
+
+/// Broadcast a vector similarity search to the matching peers with vectors.
+#[instrument(skip(state, request))]
+async fn federation_search_vector(
+    State(state): State<FederationState>,
+    Json(request): Json<FederationVectorSearchRequest>,
+) -> Result<Json<FederationSearchResponse>, StatusCode> {
+    if request.vector.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let normalization = request.normalization.unwrap_or(ScoreNormalization::None);
+    federated_search(
+        state,
+        SearchQuery::Vector(request.vector),
+        &request.pattern,
+        request.drift_policy,
+        request.limit,
+        request.timeout_ms,
+        normalization,
+    )
+    .await
+    .map(Json)
+}
+
+/// Run `query` on every matching peer in parallel, each within the
+/// timeout, and merge the answers.
+async fn federated_search(
+    state: FederationState,
+    query: SearchQuery,
+    pattern: &str,
+    drift_policy: DriftPolicy,
+    limit: Option<usize>,
+    timeout_ms: Option<u64>,
+    normalization: ScoreNormalization,
+) -> Result<FederationSearchResponse, StatusCode> {
+    let limit = limit.unwrap_or(100).min(1000);
+    let timeout = timeout_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(PEER_TIMEOUT)
+        .min(MAX_PEER_TIMEOUT);
+    let modality = match query {
+        SearchQuery::Text(_) => "document",
+        SearchQuery::Vector(_) => "vector",
+    };
+    let (stores, stores_excluded) = select_stores(&state, pattern, &[modality.to_string()], drift_policy)?;
+
+    let client = reqwest::Client::new();
+    let mut handles = Vec::new();
+    for store in stores {
+        let client = client.clone();
+        let state = state.clone();
+        let query = query.clone();
+        handles.push(tokio::spawn(async move {
+            let (text, vector) = match &query {
+                SearchQuery::Text(text) => (Some(text.as_str()), None),
+                SearchQuery::Vector(vector) => (None, Some(vector.as_slice())),
+            };
+            let started = std::time::Instant::now();
+            let answer = match tokio::time::timeout(timeout, query_single_peer(&client, &state.peer_auth, &store, text, vector, limit)).await {
+                Ok(Ok(results)) => {
+                    state.record_response_time(&store.store_id, started.elapsed());
+                    Ok(results)
+                }
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(format!("timed out after {} ms", timeout.as_millis())),
+            };
+            if let Err(e) = &answer {
+                warn!(store_id = %store.store_id, error = %e, "Peer search failed");
+            }
+            (store, answer)
+        }));
+    }
+
+    let mut stores_queried = Vec::new();
+    let mut stores_failed = Vec::new();
+    let mut answers = Vec::new();
+    for handle in handles {
+        let Ok((store, answer)) = handle.await else {
+            warn!("Peer search task panicked");
+            continue;
+        };
+        stores_queried.push(store.store_id.clone());
+        match answer {
+            Ok(results) => answers.push((store, results)),
+            Err(error) => stores_failed.push(PeerFailure { store_id: store.store_id, error }),
+        }
+    }
+
+    Ok(FederationSearchResponse {
+        results: merge_search_hits(answers, normalization, limit),
+        stores_queried,
+        stores_failed,
+        stores_excluded,
+        normalization,
+    })
+}
+
+/// Normalize each peer's scores, then merge the hits best first.  An
+/// entity several peers return is listed once, from the peer that scored
+/// it highest, with every peer in `sources`.
+fn merge_search_hits(
+    answers: Vec<(PeerStore, Vec<FederationResult>)>,
+    normalization: ScoreNormalization,
+    limit: usize,
+) -> Vec<FederationSearchHit> {
+    let mut hits: Vec<FederationSearchHit> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (store, results) in answers {
+        let (min, max) = results
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), r| (min.min(r.score), max.max(r.score)));
+        for result in results {
+            let score = match normalization {
+                ScoreNormalization::None => result.score,
+                ScoreNormalization::MinMax if max > min => (result.score - min) / (max - min),
+                ScoreNormalization::MinMax => 1.0,
+            };
+            let hit = FederationSearchHit {
+                hexad_id: result.hexad_id,
+                score,
+                raw_score: result.score,
+                origin: store.store_id.clone(),
+                origin_endpoint: store.endpoint.clone(),
+                data: result.data,
+                sources: vec![store.store_id.clone()],
+            };
+            match positions.get(&hit.hexad_id) {
+                Some(&i) => {
+                    let mut sources = std::mem::take(&mut hits[i].sources);
+                    sources.push(store.store_id.clone());
+                    if hit.score > hits[i].score {
+                        hits[i] = hit;
+                    }
+                    hits[i].sources = sources;
+                }
+                None => {
+                    positions.insert(hit.hexad_id.clone(), hits.len());
+                    hits.push(hit);
+                }
+            }
+        }
+    }
+    for hit in &mut hits {
+        if hit.sources.len() < 2 {
+            hit.sources.clear();
+        }
+    }
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.hexad_id.cmp(&b.hexad_id)));
+    hits.truncate(limit);
+    hits
+}
+
+/// Plan a logical plan across the matching peers, run each sub-plan on its
+/// peer and merge the partial results.
+async fn distributed_query(
+    state: FederationState,
+    request: FederationQueryRequest,
+) -> Result<FederationQueryResponse, StatusCode> {
+    let Some(logical) = request.plan.as_ref() else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let (stores, mut stores_excluded) =
+        select_stores(&state, &request.pattern, &request.modalities, request.drift_policy)?;
+    let profiles: Vec<PeerProfile> = stores
+        .iter()
+        .map(|store| PeerProfile {
+            store_id: store.store_id.clone(),
+            modalities: store.modalities.iter().filter_map(|m| m.parse().ok()).collect(),
+            round_trip_ms: store.response_time_ms.map(|ms| ms as f64),
+        })
+        .collect();
+
+    let planner = state.planner.as_ref().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let plan = {
+        let planner = planner.lock().map_err(|_| {
+            error!("Planner lock poisoned");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        planner
+            .distribute(logical, &profiles, &state.network_cost, request.budget_ms)
+            .map_err(|e| {
+                warn!(error = %e, "Distributed planning failed");
+                StatusCode::BAD_REQUEST
+            })?
+    };
+    stores_excluded.extend(plan.excluded.iter().map(|e| e.store_id.clone()));
+
+    let client = reqwest::Client::new();
+    let params = Arc::new(request.params.clone());
+    let mut handles = Vec::new();
+    for sub_plan in &plan.sub_plans {
+        let Some(store) = stores.iter().find(|s| s.store_id == sub_plan.store_id).cloned() else {
+            continue;
+        };
+        let client = client.clone();
+        let state = state.clone();
+        let params = params.clone();
+        let body = serde_json::json!({"plan": sub_plan.plan, "params": *params});
+        handles.push(tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            let rows = match tokio::time::timeout(PEER_TIMEOUT, execute_on_peer(&client, &state.peer_auth, &store, &body)).await {
+                Ok(Ok(rows)) => {
+                    state.record_response_time(&store.store_id, started.elapsed());
+                    rows
+                }
+                Ok(Err(e)) => {
+                    warn!(store_id = %store.store_id, error = %e, "Peer sub-plan failed");
+                    Vec::new()
+                }
+                Err(_) => {
+                    warn!(store_id = %store.store_id, "Peer sub-plan timed out after 10s");
+                    Vec::new()
+                }
+            };
+            PartialResult {
+                rows: rows
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, row)| RemoteRow::from_json(&store.store_id, i, row))
+                    .collect(),
+                store_id: store.store_id,
+            }
+        }));
+    }
+
+    // Merge in plan order, so a union lists the cheapest peer first
+    let mut partials = Vec::new();
+    for handle in handles {
+        match handle.await {
+            Ok(partial) => partials.push(partial),
+            Err(e) => warn!(error = %e, "Peer sub-plan task panicked"),
+        }
+    }
+
+    Ok(FederationQueryResponse {
+        results: plan.merge.merge(partials, plan.limit).into_iter().map(merged_result).collect(),
+        stores_queried: plan.sub_plans.iter().map(|s| s.store_id.clone()).collect(),
+        stores_excluded,
+        drift_policy: request.drift_policy,
+        plan: Some(plan),
+    })
+}
+
+/// Stores matching `pattern` and supporting `modalities`, split into those
+/// to query and those `drift_policy` excludes.
+fn select_stores(
+    state: &FederationState,
+    pattern: &str,
+    modalities: &[String],
+    drift_policy: DriftPolicy,
+) -> Result<(Vec<PeerStore>, Vec<String>), StatusCode> {
+    let peers = state.peers.read().map_err(|_| {
+        tracing::error!("Federation peers RwLock poisoned");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let matching: Vec<PeerStore> = peers
+        .values()
+        .filter(|p| pattern_matches(pattern, &p.store_id))
+        .filter(|p| modalities.iter().all(|m| p.modalities.iter().any(|pm| pm == m)))
+        .cloned()
+        .collect();
+
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+
+    for store in matching {
+        // Skip self to prevent infinite recursion
+        if store.store_id == state.self_store_id {
+            continue;
+        }
+
+        let include = match drift_policy {
+            DriftPolicy::Strict => {
+                store.trust_level >= (1.0 - state.strict_drift_threshold)
+            }
+            DriftPolicy::Repair | DriftPolicy::Tolerate | DriftPolicy::Latest => true,
+        };
+
+        if include {
+            included.push(store);
+        } else {
+            info!(
+                store_id = %store.store_id,
+                trust = store.trust_level,
+                "Excluded store due to Strict drift policy"
+            );
+            excluded.push(store.store_id.clone());
+        }
+    }
+
+    // Peers in a stable order, so merges are deterministic
+    included.sort_by(|a, b| a.store_id.cmp(&b.store_id));
+    Ok((included, excluded))
+}
+
+/// A merged row as a federation result.
+fn merged_result(row: MergedRow) -> FederationResult {
+    FederationResult {
+        source_store: row.sources.first().cloned().unwrap_or_default(),
+        hexad_id: row.id,
+        score: row.score,
+        drifted: false,
+        data: row.data,
+        sources: if row.sources.len() > 1 { row.sources } else { Vec::new() },
+    }
+}
+
+/// Run a sub-plan on a peer through its `/query/execute` endpoint,
+/// returning the result rows.
+async fn execute_on_peer(
+    client: &reqwest::Client,
+    peers: &PeerAuth,
+    store: &PeerStore,
+    body: &serde_json::Value,
+) -> Result<Vec<serde_json::Value>, String> {
+    let store_id = &store.store_id;
+    let url = format!("{}/query/execute", store.endpoint);
+    let resp = peers
+        .request(client, reqwest::Method::POST, &url, PeerScope::Query, MAX_PEER_TIMEOUT)
+        .await?
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request to {} failed: {}", store_id, e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Peer {} returned status {}", store_id, resp.status()));
+    }
+
+    let mut execution: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response from {}: {}", store_id, e))?;
+    match execution["rows"].take() {
+        serde_json::Value::Array(rows) => Ok(rows),
+        _ => Err(format!("Peer {} returned no rows", store_id)),
+    }
+}
+
 /// Query a single peer store via HTTP.
 async fn query_single_peer(
     client: &reqwest::Client,
+    peers: &PeerAuth,
     store: &PeerStore,
     text_query: Option<&str>,
     vector_query: Option<&[f32]>,
@@ -522,8 +1122,9 @@ async fn query_single_peer(
     let response_items: Vec<serde_json::Value> = if let Some(q) = text_query {
         // Text search
         let url = format!("{}/search/text", endpoint);
-        let resp = client
-            .get(&url)
+        let resp = peers
+            .request(client, reqwest::Method::GET, &url, PeerScope::Query, MAX_PEER_TIMEOUT)
+            .await?
             .query(&[("q", q), ("limit", &limit.to_string())])
             .send()
             .await
@@ -540,8 +1141,9 @@ async fn query_single_peer(
         // Vector search
         let url = format!("{}/search/vector", endpoint);
         let body = serde_json::json!({ "vector": vec, "k": limit });
-        let resp = client
-            .post(&url)
+        let resp = peers
+            .request(client, reqwest::Method::POST, &url, PeerScope::Query, MAX_PEER_TIMEOUT)
+            .await?
             .json(&body)
             .send()
             .await
@@ -557,8 +1159,9 @@ async fn query_single_peer(
     } else {
         // No specific query — list hexads from the peer's /hexads endpoint
         let url = format!("{}/hexads", endpoint);
-        let resp = client
-            .get(&url)
+        let resp = peers
+            .request(client, reqwest::Method::GET, &url, PeerScope::Query, MAX_PEER_TIMEOUT)
+            .await?
             .query(&[("limit", &limit.to_string())])
             .send()
             .await
@@ -582,12 +1185,144 @@ async fn query_single_peer(
             score: item["score"].as_f64().unwrap_or(0.0),
             drifted: false,
             data: item,
+            sources: Vec::new(),
         })
         .collect();
 
     Ok(results)
 }
 
+// ---------------------------------------------------------------------------
+// Topology
+// ---------------------------------------------------------------------------
+
+/// Time allowed for one peer's health check.
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Peers gathered from the registry, replication and CRDT sync.  A peer
+/// known in several ways — by ID or by endpoint — is listed once.
+#[derive(Debug, Default)]
+pub struct Topology {
+    peers: Vec<PeerHealth>,
+}
+
+impl Topology {
+    /// The entry for a peer, added if it is not yet known, with `role`.
+    pub fn peer(&mut self, id: Option<&str>, endpoint: Option<&str>, role: PeerRole) -> &mut PeerHealth {
+        let endpoint = endpoint.map(|e| e.trim_end_matches('/'));
+        let found = self.peers.iter().position(|p| {
+            id.is_some_and(|id| p.peer == id) || endpoint.is_some_and(|e| p.endpoint.as_deref() == Some(e))
+        });
+        let index = found.unwrap_or_else(|| {
+            self.peers.push(PeerHealth {
+                peer: id.or(endpoint).unwrap_or_default().to_string(),
+                endpoint: None,
+                roles: Vec::new(),
+                reachable: None,
+                response_time_ms: None,
+                version: None,
+                compatible: None,
+                lag_entries: None,
+                lag_seconds: None,
+                last_sync: None,
+                last_seen: None,
+                pending_conflicts: 0,
+                error: None,
+            });
+            self.peers.len() - 1
+        });
+        let peer = &mut self.peers[index];
+        if let (Some(id), Some(known)) = (id, &peer.endpoint) {
+            // First known by endpoint alone
+            if peer.peer == *known {
+                peer.peer = id.to_string();
+            }
+        }
+        if peer.endpoint.is_none() {
+            peer.endpoint = endpoint.map(str::to_string);
+        }
+        if !peer.roles.contains(&role) {
+            peer.roles.push(role);
+        }
+        peer
+    }
+
+    /// Check every peer with an endpoint, concurrently, and report.
+    pub async fn check(mut self, store_id: String, peer_auth: &PeerAuth) -> FederationStatus {
+        let version = env!("CARGO_PKG_VERSION").to_string();
+        let fallback = reqwest::Client::new();
+        let client = peer_auth.client(&fallback);
+        let probes = self.peers.iter().map(|peer| async move {
+            match &peer.endpoint {
+                Some(endpoint) => Some(probe(client, endpoint).await),
+                None => None,
+            }
+        });
+        let probes = futures::future::join_all(probes).await;
+        for (peer, probe) in self.peers.iter_mut().zip(probes) {
+            let Some(probe) = probe else { continue };
+            match probe {
+                Ok((health, elapsed)) => {
+                    peer.reachable = Some(true);
+                    peer.response_time_ms = Some(elapsed.as_millis() as u64);
+                    peer.compatible = Some(versions_compatible(&version, &health.version));
+                    peer.version = Some(health.version);
+                }
+                Err(e) => {
+                    warn!(peer = %peer.peer, error = %e, "Peer health check failed");
+                    peer.reachable = Some(false);
+                    peer.error = Some(e);
+                }
+            }
+        }
+        let degraded = self.peers.iter().any(|p| {
+            p.reachable == Some(false) || p.compatible == Some(false) || p.error.is_some() || p.pending_conflicts > 0
+        });
+        FederationStatus {
+            store_id,
+            version,
+            status: if degraded { "degraded" } else { "healthy" }.to_string(),
+            peers: self.peers,
+        }
+    }
+}
+
+/// Fetch a peer's `/health`, and the time it took to answer.
+async fn probe(
+    client: &reqwest::Client,
+    endpoint: &str,
+) -> Result<(crate::HealthResponse, std::time::Duration), String> {
+    let started = std::time::Instant::now();
+    let response = client
+        .get(format!("{endpoint}/health"))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("health check failed: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("health check returned status {}", response.status()));
+    }
+    let health = response
+        .json()
+        .await
+        .map_err(|e| format!("unreadable health check: {e}"))?;
+    Ok((health, started.elapsed()))
+}
+
+/// Whether two versions can exchange data: the same major version, and
+/// before 1.0 the same minor version too.
+fn versions_compatible(ours: &str, theirs: &str) -> bool {
+    let parts = |v: &str| -> Option<(u64, u64)> {
+        let mut parts = v.trim_start_matches('v').split(['.', '-', '+']);
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    };
+    match (parts(ours), parts(theirs)) {
+        (Some((0, a)), Some((0, b))) => a == b,
+        (Some((a, _)), Some((b, _))) => a == b,
+        _ => false,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -620,6 +1355,76 @@ fn test_pattern_matching() {
         assert!(!pattern_matches("store-1", "store-2"));
     }
 
+    #[test]
+    fn test_search_hits_are_normalized_per_peer_and_merged() {
+        let store = |id: &str| PeerStore {
+            store_id: id.to_string(),
+            endpoint: format!("http://{id}:8080"),
+            modalities: vec!["document".to_string()],
+            trust_level: 1.0,
+            last_seen: None,
+            response_time_ms: None,
+            secret_hash: None,
+        };
+        let hit = |id: &str, score: f64| FederationResult {
+            source_store: String::new(),
+            hexad_id: id.to_string(),
+            score,
+            drifted: false,
+            data: serde_json::json!({}),
+            sources: Vec::new(),
+        };
+        let answers = || {
+            vec![
+                (store("a"), vec![hit("x", 20.0), hit("y", 10.0)]),
+                (store("b"), vec![hit("y", 2.0), hit("z", 1.0)]),
+            ]
+        };
+
+        // Raw scores: the peer with the larger scale dominates
+        let raw = merge_search_hits(answers(), ScoreNormalization::None, 10);
+        let order: Vec<&str> = raw.iter().map(|h| h.hexad_id.as_str()).collect();
+        assert_eq!(order, ["x", "y", "z"]);
+
+        // Min-max: each peer's best hit scores 1.0 and its worst 0.0
+        let merged = merge_search_hits(answers(), ScoreNormalization::MinMax, 10);
+        let y = merged.iter().find(|h| h.hexad_id == "y").unwrap();
+        assert_eq!(y.score, 1.0);
+        assert_eq!(y.origin, "b");
+        assert_eq!(y.raw_score, 2.0);
+        assert_eq!(y.sources, ["a", "b"]);
+        let z = merged.iter().find(|h| h.hexad_id == "z").unwrap();
+        assert_eq!(z.score, 0.0);
+        assert_eq!(z.origin_endpoint, "http://b:8080");
+        assert!(z.sources.is_empty());
+
+        assert_eq!(merge_search_hits(answers(), ScoreNormalization::MinMax, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_topology_merges_peers_known_several_ways() {
+        let mut topology = Topology::default();
+        topology.peer(None, Some("http://a:8080/"), PeerRole::Crdt);
+        topology.peer(Some("a"), Some("http://a:8080"), PeerRole::Federation);
+        topology.peer(Some("a"), None, PeerRole::Replica).lag_entries = Some(3);
+        topology.peer(Some("b"), None, PeerRole::Replica);
+        assert_eq!(topology.peers.len(), 2);
+        let a = &topology.peers[0];
+        assert_eq!(a.peer, "a");
+        assert_eq!(a.endpoint.as_deref(), Some("http://a:8080"));
+        assert_eq!(a.roles, [PeerRole::Crdt, PeerRole::Federation, PeerRole::Replica]);
+        assert_eq!(a.lag_entries, Some(3));
+    }
+
+    #[test]
+    fn test_versions_compatible() {
+        assert!(versions_compatible("1.2.0", "1.9.3"));
+        assert!(!versions_compatible("1.2.0", "2.0.0"));
+        assert!(versions_compatible("0.1.0", "0.1.7-beta"));
+        assert!(!versions_compatible("0.1.0", "0.2.0"));
+        assert!(!versions_compatible("0.1.0", "unknown"));
+    }
+
     #[test]
     fn test_federation_state() {
         let state = FederationState::new("self".to_string(), "http://localhost:8080".to_string());
@@ -3,26 +3,62 @@
 //!
 //! Exposes planner, hexad, search, drift, and normalizer operations
 //! via a GraphQL schema at `/graphql`.
+//!
+//! Subscriptions (`hexadChanged`, `driftDetected`, `normalizationCompleted`)
+//! are served over WebSocket at `/graphql/ws`, speaking both the
+//! `graphql-transport-ws` and the older `graphql-ws` protocol.  They are fed
+//! by a hexad store change hook and the drift detector's and normalizer's
+//! event channels; a client that falls behind misses events rather than
+//! holding up writers.
+//!
+//! Both endpoints sit behind the authentication middleware.  A request as a
+//! whole needs only read access; each query or mutation that reads or
+//! changes more is then authorized as the REST request it stands for, so
+//! `createHexad` needs what `POST /hexads` does and `updatePlannerConfig`
+//! what `PUT /planner/config` does.  Operations sent over the WebSocket run
+//! for the principal that opened it, and subscribers are sent events only
+//! of the entities the entity policy lets them see.
+//!
+//! `Hexad.related` expands graph relationships.  Hexads are fetched through
+//! a per-request dataloader, which collects the ids asked for while a level
+//! of the query resolves and reads them with one `get_many`, so a nested
+//! query costs a store read per level rather than per node.
 
 use async_graphql::{
-    Context, EmptySubscription, InputObject, Object, Schema, SimpleObject,
-    http::GraphiQLSource,
+    ComplexObject, Context, Data, Executor, InputObject, Object, Schema, ServerResult, SimpleObject,
+    Subscription,
+    dataloader::{DataLoader, HashMapCache, Loader},
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest},
+    http::{GraphiQLSource, ALL_WEBSOCKET_PROTOCOLS},
 };
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
-use tracing::error;
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use futures::{stream::BoxStream, Stream, StreamExt};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{error, warn};
 use axum::{
-    extract::State as AxumState,
-    response::{Html, IntoResponse},
+    extract::{ws::WebSocketUpgrade, Path, State as AxumState},
+    http::{HeaderMap, Method},
+    response::{Html, IntoResponse, Response},
+    Extension as AxumExtension,
+    Json,
     routing::{get, post},
     Router,
 };
 
+use verisim_drift::DriftEvent;
+use verisim_hexad::{security::{self, Principal}, HexadError, HexadId, HexadListener, HexadStore};
+use verisim_normalizer::NormalizationResult;
 use verisim_planner::{
     ExplainOutput as PlannerExplainOutput,
     LogicalPlan,
 };
 
-use crate::AppState;
+use crate::auth::ClientIdentity;
+use crate::rbac::{self, RbacState};
+use crate::redaction::{self, Redaction};
+use crate::{transaction, vql, ApiError, AppState, ConcreteHexadStore, HexadRequest, ProvenanceRequest, SpatialRequest, TensorRequest};
 
 // ============================================================================
 // GraphQL Output Types
@@ -37,7 +73,8 @@ struct Health {
 }
 
 /// Hexad summary.
-#[derive(SimpleObject)]
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
 struct Hexad {
     id: String,
     created_at: String,
@@ -51,6 +88,169 @@ struct Hexad {
     version_count: u64,
 }
 
+impl From<&verisim_hexad::Hexad> for Hexad {
+    fn from(h: &verisim_hexad::Hexad) -> Self {
+        Self {
+            id: h.id.to_string(),
+            created_at: h.status.created_at.to_rfc3339(),
+            modified_at: h.status.modified_at.to_rfc3339(),
+            version: h.status.version,
+            has_graph: h.graph_node.is_some(),
+            has_vector: h.embedding.is_some(),
+            has_tensor: h.tensor.is_some(),
+            has_semantic: h.semantic.is_some(),
+            has_document: h.document.is_some(),
+            version_count: h.version_count,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Hexad {
+    /// Hexads this one links to by `predicate`.
+    async fn related(&self, ctx: &Context<'_>, predicate: String) -> async_graphql::Result<Vec<Hexad>> {
+        let state = ctx.data::<AppState>()?;
+        let ids = state
+            .hexad_store
+            .related_ids(&HexadId::new(&self.id), &predicate)
+            .await
+            .map_err(|e| {
+                error!(error = %e, "GraphQL relationship query failed");
+                async_graphql::Error::new("Internal server error")
+            })?;
+        let ids: Vec<String> = ids.into_iter().map(|id| id.to_string()).collect();
+        let mut hexads = load_hexads(ctx, ids.iter().cloned()).await?;
+        Ok(ids.iter().filter_map(|id| hexads.remove(id)).collect())
+    }
+}
+
+/// A hexad was created, updated or deleted.
+#[derive(SimpleObject, Clone)]
+struct HexadChanged {
+    /// `created`, `updated` or `deleted`.
+    change: String,
+    id: String,
+    /// Store version after the change.
+    version: u64,
+    /// The hexad as it is now; absent when deleted.
+    hexad: Option<Hexad>,
+    at: String,
+    /// The hexad as changed, or as it was when deleted, by which
+    /// subscribers are judged
+    #[graphql(skip)]
+    entity: Arc<verisim_hexad::Hexad>,
+}
+
+/// The drift detector raised an alert.
+#[derive(SimpleObject, Clone)]
+struct DriftDetected {
+    drift_type: String,
+    severity: String,
+    score: f64,
+    affected_entities: Vec<String>,
+    description: String,
+    remediation: Option<String>,
+    namespace: Option<String>,
+    detected_at: String,
+}
+
+impl From<&DriftEvent> for DriftDetected {
+    fn from(e: &DriftEvent) -> Self {
+        Self {
+            drift_type: e.drift_type.to_string(),
+            severity: format!("{:?}", e.severity),
+            score: e.score,
+            affected_entities: e.affected_entities.clone(),
+            description: e.description.clone(),
+            remediation: e.remediation.clone(),
+            namespace: e.namespace.clone(),
+            detected_at: e.detected_at.to_rfc3339(),
+        }
+    }
+}
+
+/// The normalizer finished with an entity.
+#[derive(SimpleObject, Clone)]
+struct NormalizationCompleted {
+    id: String,
+    entity_id: String,
+    normalization_type: String,
+    success: bool,
+    /// Whether the repair was written back to the store.
+    applied: bool,
+    changes: u64,
+    duration_ms: u64,
+    completed_at: String,
+}
+
+impl From<&NormalizationResult> for NormalizationCompleted {
+    fn from(r: &NormalizationResult) -> Self {
+        Self {
+            id: r.id.clone(),
+            entity_id: r.entity_id.to_string(),
+            normalization_type: format!("{:?}", r.normalization_type),
+            success: r.success,
+            applied: r.applied,
+            changes: r.changes.len() as u64,
+            duration_ms: r.duration_ms,
+            completed_at: r.completed_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A provenance event was recorded.
+#[derive(SimpleObject)]
+struct ProvenanceRecorded {
+    entity_id: String,
+    chain_length: u64,
+}
+
+/// Transaction status.
+#[derive(SimpleObject)]
+struct Transaction {
+    id: String,
+    /// `Active`, `Prepared`, `Committed` or `RolledBack`.
+    state: String,
+    /// `snapshot` or `read_committed`.
+    isolation: String,
+    operation_count: u64,
+    started_at: String,
+    completed_at: Option<String>,
+    expires_at: Option<String>,
+}
+
+impl From<transaction::TransactionStatus> for Transaction {
+    fn from(s: transaction::TransactionStatus) -> Self {
+        Self {
+            id: s.id,
+            state: format!("{:?}", s.state),
+            isolation: serde_name(&s.isolation),
+            operation_count: s.operation_count as u64,
+            started_at: s.started_at,
+            completed_at: s.completed_at,
+            expires_at: s.expires_at,
+        }
+    }
+}
+
+/// A committed transaction.
+#[derive(SimpleObject)]
+struct TransactionCommit {
+    transaction: Transaction,
+    /// IDs of the entities written, in the order written.
+    ids: Vec<String>,
+}
+
+/// Result of a VQL statement.
+#[derive(SimpleObject)]
+struct VqlResult {
+    success: bool,
+    statement_type: String,
+    row_count: u64,
+    data: async_graphql::Json<serde_json::Value>,
+    message: Option<String>,
+}
+
 /// Search result entry.
 #[derive(SimpleObject)]
 struct SearchResult {
@@ -157,6 +357,50 @@ struct HexadInput {
     tensor_data: Option<Vec<f64>>,
 }
 
+/// A name and value, for string maps.
+#[derive(InputObject)]
+struct PropertyInput {
+    key: String,
+    value: String,
+}
+
+/// Spatial data input.
+#[derive(InputObject)]
+struct SpatialInput {
+    latitude: f64,
+    longitude: f64,
+    altitude: Option<f64>,
+    /// Defaults to `Point`.
+    geometry_type: Option<String>,
+    /// Defaults to 4326.
+    srid: Option<u32>,
+    /// Full geometry as WKT or EWKT.
+    wkt: Option<String>,
+    /// Full geometry as hex-encoded WKB or EWKB.
+    wkb: Option<String>,
+    properties: Option<Vec<PropertyInput>>,
+}
+
+/// Provenance event input.
+#[derive(InputObject)]
+struct ProvenanceInput {
+    /// created, modified, imported, normalized, drift_repaired, deleted or
+    /// merged.
+    event_type: String,
+    actor: String,
+    source: Option<String>,
+    description: String,
+}
+
+/// Transaction options input.
+#[derive(InputObject)]
+struct TransactionInput {
+    /// `snapshot` (the default) or `read_committed`.
+    isolation: Option<String>,
+    timeout_secs: Option<u64>,
+    idle_timeout_secs: Option<u64>,
+}
+
 /// Planner configuration input.
 #[derive(InputObject)]
 struct PlannerConfigInput {
@@ -186,29 +430,8 @@ async fn health(&self, ctx: &Context<'_>) -> async_graphql::Result<Health> {
 
     /// Get a hexad by ID.
     async fn hexad(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<Option<Hexad>> {
-        let state = ctx.data::<AppState>()?;
-        let hexad_id = verisim_hexad::HexadId::new(&id);
-
-        use verisim_hexad::HexadStore;
-        match state.hexad_store.get(&hexad_id).await {
-            Ok(Some(h)) => Ok(Some(Hexad {
-                id: h.id.to_string(),
-                created_at: h.status.created_at.to_rfc3339(),
-                modified_at: h.status.modified_at.to_rfc3339(),
-                version: h.status.version,
-                has_graph: h.graph_node.is_some(),
-                has_vector: h.embedding.is_some(),
-                has_tensor: h.tensor.is_some(),
-                has_semantic: h.semantic.is_some(),
-                has_document: h.document.is_some(),
-                version_count: h.version_count,
-            })),
-            Ok(None) => Ok(None),
-            Err(e) => {
-                error!(error = %e, "GraphQL hexad query failed");
-                Err(async_graphql::Error::new("Internal server error"))
-            }
-        }
+        authorize(ctx, Method::GET, &format!("/hexads/{}", id)).await?;
+        Ok(load_hexads(ctx, [id.clone()]).await?.remove(&id))
     }
 
     /// Search by text.
@@ -218,6 +441,7 @@ async fn search_text(
         query: String,
         limit: Option<i32>,
     ) -> async_graphql::Result<Vec<SearchResult>> {
+        authorize(ctx, Method::GET, "/search/text").await?;
         let state = ctx.data::<AppState>()?;
         let limit = limit.unwrap_or(10) as usize;
 
@@ -244,6 +468,7 @@ async fn search_text(
 
     /// Get drift status for all drift types.
     async fn drift_status(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<DriftStatus>> {
+        authorize(ctx, Method::GET, "/drift/status").await?;
         let state = ctx.data::<AppState>()?;
         let all_metrics = state.drift_detector.all_metrics()
             .map_err(|e| {
@@ -265,6 +490,7 @@ async fn drift_status(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Dri
 
     /// Get current planner configuration.
     async fn planner_config(&self, ctx: &Context<'_>) -> async_graphql::Result<PlannerConfigOutput> {
+        authorize(ctx, Method::GET, "/planner/config").await?;
         let state = ctx.data::<AppState>()?;
         let planner = state.planner.lock().map_err(|_| { error!("Planner lock poisoned in GraphQL"); async_graphql::Error::new("Internal server error") })?;
         let cfg = planner.config();
@@ -278,6 +504,7 @@ async fn planner_config(&self, ctx: &Context<'_>) -> async_graphql::Result<Plann
 
     /// Get planner statistics.
     async fn planner_stats(&self, ctx: &Context<'_>) -> async_graphql::Result<PlannerStats> {
+        authorize(ctx, Method::GET, "/planner/stats").await?;
         let state = ctx.data::<AppState>()?;
         let planner = state.planner.lock().map_err(|_| { error!("Planner lock poisoned in GraphQL"); async_graphql::Error::new("Internal server error") })?;
 
@@ -303,6 +530,7 @@ async fn explain_plan(
         ctx: &Context<'_>,
         plan_json: String,
     ) -> async_graphql::Result<ExplainOutput> {
+        authorize(ctx, Method::POST, "/query/explain").await?;
         let state = ctx.data::<AppState>()?;
         let logical: LogicalPlan = serde_json::from_str(&plan_json)
             .map_err(|e| async_graphql::Error::new(format!("Invalid plan JSON: {}", e)))?;
@@ -333,6 +561,7 @@ async fn create_hexad(
         ctx: &Context<'_>,
         input: HexadInput,
     ) -> async_graphql::Result<Hexad> {
+        authorize(ctx, Method::POST, "/hexads").await?;
         let state = ctx.data::<AppState>()?;
 
         let mut hexad_input = verisim_hexad::HexadInput::default();
@@ -365,23 +594,14 @@ async fn create_hexad(
                 error!(error = %e, "GraphQL hexad creation failed");
                 async_graphql::Error::new("Internal server error")
             })?;
+        state.observe_embedding(h.embedding.as_ref());
 
-        Ok(Hexad {
-            id: h.id.to_string(),
-            created_at: h.status.created_at.to_rfc3339(),
-            modified_at: h.status.modified_at.to_rfc3339(),
-            version: h.status.version,
-            has_graph: h.graph_node.is_some(),
-            has_vector: h.embedding.is_some(),
-            has_tensor: h.tensor.is_some(),
-            has_semantic: h.semantic.is_some(),
-            has_document: h.document.is_some(),
-            version_count: h.version_count,
-        })
+        Ok(Hexad::from(&h))
     }
 
     /// Delete a hexad.
     async fn delete_hexad(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<bool> {
+        authorize(ctx, Method::DELETE, &format!("/hexads/{}", id)).await?;
         let state = ctx.data::<AppState>()?;
         let hexad_id = verisim_hexad::HexadId::new(&id);
 
@@ -398,12 +618,168 @@ async fn delete_hexad(&self, ctx: &Context<'_>, id: String) -> async_graphql::Re
         Ok(true)
     }
 
+    /// Upload a tensor to a hexad, replacing any it has.
+    async fn upload_tensor(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        shape: Vec<usize>,
+        data: Vec<f64>,
+    ) -> async_graphql::Result<Hexad> {
+        let request = HexadRequest {
+            tensor: Some(TensorRequest { shape, data }),
+            ..Default::default()
+        };
+        update_hexad(ctx, id, request).await
+    }
+
+    /// Index a hexad spatially, replacing its spatial data.
+    async fn index_spatial(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: SpatialInput,
+    ) -> async_graphql::Result<Hexad> {
+        let request = HexadRequest {
+            spatial: Some(SpatialRequest {
+                latitude: input.latitude,
+                longitude: input.longitude,
+                altitude: input.altitude,
+                geometry_type: input.geometry_type,
+                srid: input.srid,
+                geometry: None,
+                wkt: input.wkt,
+                wkb: input.wkb,
+                properties: input
+                    .properties
+                    .map(|properties| properties.into_iter().map(|p| (p.key, p.value)).collect()),
+            }),
+            ..Default::default()
+        };
+        update_hexad(ctx, id, request).await
+    }
+
+    /// Annotate a hexad with semantic types, replacing its annotation.
+    async fn annotate_semantic(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        types: Vec<String>,
+    ) -> async_graphql::Result<Hexad> {
+        let request = HexadRequest {
+            types: Some(types),
+            ..Default::default()
+        };
+        update_hexad(ctx, id, request).await
+    }
+
+    /// Record a provenance event on a hexad.
+    async fn record_provenance(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: ProvenanceInput,
+    ) -> async_graphql::Result<ProvenanceRecorded> {
+        authorize(ctx, Method::POST, &format!("/provenance/{}/record", id)).await?;
+        let state = ctx.data::<AppState>()?.clone();
+        let request = ProvenanceRequest {
+            event_type: input.event_type,
+            actor: input.actor,
+            source: input.source,
+            description: input.description,
+        };
+        let Json(recorded) = crate::provenance_record_handler(AxumState(state), Path(id.clone()), None, Json(request))
+            .await
+            .map_err(api_error)?;
+        Ok(ProvenanceRecorded {
+            entity_id: id,
+            chain_length: recorded["chain_length"].as_u64().unwrap_or_default(),
+        })
+    }
+
+    /// Begin a transaction.
+    async fn begin_transaction(
+        &self,
+        ctx: &Context<'_>,
+        input: Option<TransactionInput>,
+    ) -> async_graphql::Result<Transaction> {
+        authorize(ctx, Method::POST, "/transactions/begin").await?;
+        let state = ctx.data::<AppState>()?.clone();
+        let options = match input {
+            Some(input) => transaction::TransactionOptions {
+                isolation: match input.isolation.as_deref() {
+                    None | Some("snapshot") => transaction::IsolationLevel::Snapshot,
+                    Some("read_committed") => transaction::IsolationLevel::ReadCommitted,
+                    Some(other) => return Err(async_graphql::Error::new(format!("Unknown isolation level: {}", other))),
+                },
+                timeout_secs: input.timeout_secs,
+                idle_timeout_secs: input.idle_timeout_secs,
+            },
+            None => transaction::TransactionOptions::default(),
+        };
+        let (_, Json(status)) = crate::transaction_begin_handler(AxumState(state), Some(Json(options)))
+            .await
+            .map_err(api_error)?;
+        Ok(status.into())
+    }
+
+    /// Commit a transaction, applying its buffered writes atomically.
+    async fn commit_transaction(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<TransactionCommit> {
+        authorize(ctx, Method::POST, &format!("/transactions/{}/commit", id)).await?;
+        let state = ctx.data::<AppState>()?.clone();
+        let Json(committed) = crate::transaction_commit_handler(AxumState(state), None, Path(id))
+            .await
+            .map_err(api_error)?;
+        Ok(TransactionCommit {
+            transaction: committed.status.into(),
+            ids: committed.ids,
+        })
+    }
+
+    /// Roll a transaction back, discarding its buffered writes.
+    async fn rollback_transaction(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<Transaction> {
+        authorize(ctx, Method::POST, &format!("/transactions/{}/rollback", id)).await?;
+        let state = ctx.data::<AppState>()?.clone();
+        let Json(status) = crate::transaction_rollback_handler(AxumState(state), Path(id))
+            .await
+            .map_err(api_error)?;
+        Ok(status.into())
+    }
+
+    /// Execute a VQL statement; INSERT, UPDATE and DELETE are buffered in
+    /// the transaction `transaction_id` when given.
+    async fn execute_vql(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        transaction_id: Option<String>,
+    ) -> async_graphql::Result<VqlResult> {
+        authorize(ctx, Method::POST, "/vql/execute").await?;
+        let state = ctx.data::<AppState>()?.clone();
+        let request = vql::VqlExecuteRequest {
+            query,
+            params: Default::default(),
+            transaction: transaction_id,
+        };
+        let Json(response) = vql::vql_execute_handler(AxumState(state), None, HeaderMap::new(), Json(request))
+            .await
+            .map_err(api_error)?;
+        Ok(VqlResult {
+            success: response.success,
+            statement_type: response.statement_type,
+            row_count: response.row_count as u64,
+            data: async_graphql::Json(response.data),
+            message: response.message,
+        })
+    }
+
     /// Optimize a logical plan into a physical plan.
     async fn optimize_plan(
         &self,
         ctx: &Context<'_>,
         plan_json: String,
     ) -> async_graphql::Result<PhysicalPlan> {
+        authorize(ctx, Method::POST, "/query/plan").await?;
         let state = ctx.data::<AppState>()?;
         let logical: LogicalPlan = serde_json::from_str(&plan_json)
             .map_err(|e| async_graphql::Error::new(format!("Invalid plan JSON: {}", e)))?;
@@ -422,6 +798,7 @@ async fn update_planner_config(
         ctx: &Context<'_>,
         input: PlannerConfigInput,
     ) -> async_graphql::Result<PlannerConfigOutput> {
+        authorize(ctx, Method::PUT, "/planner/config").await?;
         let state = ctx.data::<AppState>()?;
         let mut planner = state.planner.lock().map_err(|_| { error!("Planner lock poisoned in GraphQL"); async_graphql::Error::new("Internal server error") })?;
 
@@ -454,46 +831,489 @@ async fn update_planner_config(
     }
 }
 
+// ============================================================================
+// Subscription Root
+// ============================================================================
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Hexad changes, of one hexad when `id` is given.
+    async fn hexad_changed(
+        &self,
+        ctx: &Context<'_>,
+        id: Option<String>,
+    ) -> async_graphql::Result<impl Stream<Item = HexadChanged>> {
+        let state = ctx.data::<AppState>()?;
+        let store = state.hexad_store.clone();
+        let events = receive(state.graphql_events.hexads.subscribe(), "hexadChanged");
+        // Judged by the hexad the event carries, so deletions, which leave
+        // no document to judge, reach the subscribers who could see it
+        Ok(events.filter(move |e| {
+            let wanted = id.as_ref().is_none_or(|id| *id == e.id);
+            std::future::ready(wanted && store.admits_hexad(&e.entity))
+        }))
+    }
+
+    /// Drift alerts, of one drift type when `drift_type` is given.
+    async fn drift_detected(
+        &self,
+        ctx: &Context<'_>,
+        drift_type: Option<String>,
+    ) -> async_graphql::Result<impl Stream<Item = DriftDetected>> {
+        let state = ctx.data::<AppState>()?;
+        let store = state.hexad_store.clone();
+        let events = receive(state.graphql_events.drift.subscribe(), "driftDetected").map(|e| DriftDetected::from(&e));
+        Ok(events
+            .filter(move |e| std::future::ready(drift_type.as_ref().is_none_or(|t| *t == e.drift_type)))
+            .filter_map(move |e| {
+                let store = store.clone();
+                async move {
+                    if e.affected_entities.is_empty() {
+                        return Some(e);
+                    }
+                    // Alerts are passed on naming only the entities the
+                    // subscriber may see, and dropped if it may see none
+                    let mut affected = Vec::with_capacity(e.affected_entities.len());
+                    for entity in &e.affected_entities {
+                        if visible(&store, entity).await {
+                            affected.push(entity.clone());
+                        }
+                    }
+                    (!affected.is_empty()).then_some(DriftDetected { affected_entities: affected, ..e })
+                }
+            }))
+    }
+
+    /// Normalizations completed, of one entity when `entity_id` is given.
+    async fn normalization_completed(
+        &self,
+        ctx: &Context<'_>,
+        entity_id: Option<String>,
+    ) -> async_graphql::Result<impl Stream<Item = NormalizationCompleted>> {
+        let state = ctx.data::<AppState>()?;
+        let store = state.hexad_store.clone();
+        let events = receive(state.graphql_events.normalization.subscribe(), "normalizationCompleted");
+        Ok(events.filter(move |e| {
+            let wanted = entity_id.as_ref().is_none_or(|id| *id == e.entity_id);
+            let store = store.clone();
+            let entity = e.entity_id.clone();
+            async move { wanted && visible(&store, &entity).await }
+        }))
+    }
+}
+
+/// Whether the subscriber may see entity `id` under the entity policy; an
+/// entity that cannot be judged is withheld.
+async fn visible(store: &ConcreteHexadStore, id: &str) -> bool {
+    store.admits(&HexadId::new(id)).await.unwrap_or_else(|e| {
+        warn!(error = %e, entity = id, "Could not judge subscription event");
+        false
+    })
+}
+
+/// The events of `receiver` as a stream; events missed by a lagging
+/// subscriber are skipped.
+pub(crate) fn receive<T: Clone + Send + 'static>(receiver: broadcast::Receiver<T>, name: &'static str) -> impl Stream<Item = T> {
+    futures::stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(subscription = name, missed, "Subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+// ============================================================================
+// Batched Loading
+// ============================================================================
+
+/// Loads hexads by id in batches through [`HexadStore::get_many`].
+struct HexadLoader {
+    store: Arc<ConcreteHexadStore>,
+    /// Whom the request's store calls are made for; loads run on a task of
+    /// their own, outside the request's scope.
+    principal: Option<Principal>,
+}
+
+impl Loader<String> for HexadLoader {
+    type Value = Hexad;
+    type Error = Arc<HexadError>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Hexad>, Self::Error> {
+        let ids: Vec<HexadId> = keys.iter().map(HexadId::new).collect();
+        let load = self.store.get_many(&ids);
+        let hexads = match self.principal.clone() {
+            Some(principal) => security::scope(principal, load).await,
+            None => load.await,
+        };
+        Ok(hexads?.iter().flatten().map(|h| (h.id.to_string(), Hexad::from(h))).collect())
+    }
+}
+
+/// The hexads among `ids` that exist, through the request's loader.
+async fn load_hexads(
+    ctx: &Context<'_>,
+    ids: impl IntoIterator<Item = String>,
+) -> async_graphql::Result<HashMap<String, Hexad>> {
+    ctx.data::<DataLoader<HexadLoader, HashMapCache>>()?.load_many(ids).await.map_err(|e| {
+        error!(error = %e, "GraphQL hexad load failed");
+        async_graphql::Error::new("Internal server error")
+    })
+}
+
+/// Gives each request a loader of its own, so batches and the cache never
+/// span requests, or the principals they are made for.
+struct HexadLoading;
+
+impl ExtensionFactory for HexadLoading {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(HexadLoading)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for HexadLoading {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: async_graphql::Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<async_graphql::Request> {
+        let state = ctx.data_unchecked::<AppState>();
+        let loader = HexadLoader {
+            store: state.hexad_store.clone(),
+            principal: security::current(),
+        };
+        let loader = DataLoader::with_cache(loader, tokio::spawn, HashMapCache::default());
+        next.run(ctx, request.data(loader)).await
+    }
+}
+
+// ============================================================================
+// Event Streams
+// ============================================================================
+
+/// Events buffered per stream before slow subscribers start missing them.
+const EVENT_BUFFER: usize = 1024;
+
+/// Event streams GraphQL subscriptions are fed from.
+#[derive(Clone)]
+pub struct GraphqlEvents {
+    hexads: broadcast::Sender<HexadChanged>,
+    drift: broadcast::Sender<DriftEvent>,
+    normalization: broadcast::Sender<NormalizationCompleted>,
+}
+
+impl Default for GraphqlEvents {
+    fn default() -> Self {
+        Self {
+            hexads: broadcast::channel(EVENT_BUFFER).0,
+            drift: broadcast::channel(EVENT_BUFFER).0,
+            normalization: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl GraphqlEvents {
+    fn hexad_changed(&self, change: &str, hexad: &verisim_hexad::Hexad, deleted: bool) {
+        if self.hexads.receiver_count() == 0 {
+            return;
+        }
+        // Sending only fails when every subscriber left since the check.
+        let _ = self.hexads.send(HexadChanged {
+            change: change.to_string(),
+            id: hexad.id.to_string(),
+            version: hexad.status.version,
+            hexad: (!deleted).then(|| Hexad::from(hexad)),
+            at: chrono::Utc::now().to_rfc3339(),
+            entity: Arc::new(hexad.clone()),
+        });
+    }
+
+    /// A drift event channel whose events are published to subscribers and
+    /// then passed on to `alerts`, if given.
+    pub fn drift_channel(&self, alerts: Option<mpsc::Sender<DriftEvent>>) -> mpsc::Sender<DriftEvent> {
+        let (sender, mut receiver) = mpsc::channel::<DriftEvent>(EVENT_BUFFER);
+        let drift = self.drift.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                let _ = drift.send(event.clone());
+                if let Some(alerts) = &alerts {
+                    if alerts.send(event).await.is_err() {
+                        warn!("Drift alert dispatcher stopped");
+                    }
+                }
+            }
+        });
+        sender
+    }
+
+    /// Drift events as the detector raises them, for streaming APIs other
+    /// than GraphQL.
+    pub fn drift_events(&self) -> broadcast::Receiver<DriftEvent> {
+        self.drift.subscribe()
+    }
+
+    /// A normalization result channel whose results are published to
+    /// subscribers.
+    pub fn normalization_channel(&self) -> mpsc::Sender<NormalizationResult> {
+        let (sender, mut receiver) = mpsc::channel::<NormalizationResult>(EVENT_BUFFER);
+        let normalization = self.normalization.clone();
+        tokio::spawn(async move {
+            while let Some(result) = receiver.recv().await {
+                let _ = normalization.send(NormalizationCompleted::from(&result));
+            }
+        });
+        sender
+    }
+}
+
+/// Change hook publishing hexad changes to subscribers.
+pub struct GraphqlEventListener(pub GraphqlEvents);
+
+impl HexadListener for GraphqlEventListener {
+    fn name(&self) -> &str {
+        "graphql-subscriptions"
+    }
+
+    fn on_created(&self, new: &verisim_hexad::Hexad) {
+        self.0.hexad_changed("created", new, false);
+    }
+
+    fn on_updated(&self, _old: &verisim_hexad::Hexad, new: &verisim_hexad::Hexad) {
+        self.0.hexad_changed("updated", new, false);
+    }
+
+    fn on_deleted(&self, old: &verisim_hexad::Hexad) {
+        self.0.hexad_changed("deleted", old, true);
+    }
+}
+
 // ============================================================================
 // Schema Construction
 // ============================================================================
 
-pub type VeriSimSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+pub type VeriSimSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 /// Build the GraphQL schema with AppState as context data.
 pub fn build_schema(state: AppState) -> VeriSimSchema {
-    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
         .data(state)
+        .extension(HexadLoading)
         .finish()
 }
 
+/// What the GraphQL routes serve requests with.
+#[derive(Clone)]
+struct Endpoint {
+    schema: VeriSimSchema,
+    /// Policy operations are authorized against, as the authentication
+    /// middleware's is
+    rbac: RbacState,
+}
+
+impl Endpoint {
+    /// The client a request is made by; none when authentication is
+    /// disabled.
+    fn caller(&self, identity: Option<AxumExtension<ClientIdentity>>) -> Option<Caller> {
+        identity.map(|AxumExtension(identity)| Caller {
+            identity,
+            rbac: self.rbac.clone(),
+        })
+    }
+}
+
+/// The client operations are made by, as [`authorize`] reads it from the
+/// request or connection data.
+struct Caller {
+    identity: ClientIdentity,
+    rbac: RbacState,
+}
+
 /// GraphQL request handler.
 async fn graphql_handler(
-    AxumState(schema): AxumState<VeriSimSchema>,
+    AxumState(endpoint): AxumState<Endpoint>,
+    identity: Option<AxumExtension<ClientIdentity>>,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let mut request = req.into_inner();
+    if let Some(caller) = endpoint.caller(identity) {
+        request = request.data(caller);
+    }
+    redacted(redaction::current().as_deref(), endpoint.schema.execute(request).await).into()
+}
+
+/// GraphQL WebSocket handler.  The connection outlives the request that
+/// opened it, and with it the request's principal and redaction scopes, so
+/// its operations are run for that principal and redacted for its roles by
+/// [`ScopedSchema`].
+async fn graphql_ws_handler(
+    AxumState(endpoint): AxumState<Endpoint>,
+    identity: Option<AxumExtension<ClientIdentity>>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let mut data = Data::default();
+    if let Some(caller) = endpoint.caller(identity) {
+        data.insert(caller);
+    }
+    let executor = ScopedSchema {
+        schema: endpoint.schema,
+        principal: security::current(),
+        redaction: redaction::current(),
+    };
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| GraphQLWebSocket::new(stream, executor, protocol).with_data(data).serve())
+}
+
+/// The schema, executing for the principal a WebSocket was opened by and
+/// redacting for its roles.
+#[derive(Clone)]
+struct ScopedSchema {
+    schema: VeriSimSchema,
+    principal: Option<Principal>,
+    redaction: Option<Arc<Redaction>>,
+}
+
+impl Executor for ScopedSchema {
+    async fn execute(&self, request: async_graphql::Request) -> async_graphql::Response {
+        let response = match self.principal.clone() {
+            Some(principal) => security::scope(principal, self.schema.execute(request)).await,
+            None => self.schema.execute(request).await,
+        };
+        redacted(self.redaction.as_deref(), response)
+    }
+
+    fn execute_stream(
+        &self,
+        request: async_graphql::Request,
+        session_data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, async_graphql::Response> {
+        let responses = self.schema.execute_stream_with_session_data(request, session_data.unwrap_or_default());
+        let redaction = self.redaction.clone();
+        let Some(principal) = self.principal.clone() else {
+            return responses.map(move |response| redacted(redaction.as_deref(), response)).boxed();
+        };
+        // Each response is produced inside the scope, as are the store
+        // reads it needs
+        futures::stream::unfold(responses, move |mut responses| {
+            let (principal, redaction) = (principal.clone(), redaction.clone());
+            async move {
+                let response = security::scope(principal, responses.next()).await?;
+                Some((redacted(redaction.as_deref(), response), responses))
+            }
+        })
+        .boxed()
+    }
+}
+
+/// `response` with what `redaction` names hidden from its data
+fn redacted(redaction: Option<&Redaction>, mut response: async_graphql::Response) -> async_graphql::Response {
+    let Some(redaction) = redaction else {
+        return response;
+    };
+    let data = std::mem::take(&mut response.data);
+    response.data = match data.into_json() {
+        Ok(mut json) => {
+            redaction.apply(&mut json);
+            async_graphql::Value::from_json(json).unwrap_or_default()
+        }
+        Err(_) => async_graphql::Value::Null,
+    };
+    response
 }
 
 /// GraphiQL playground handler.
 async fn graphiql_handler() -> impl IntoResponse {
-    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+    Html(
+        GraphiQLSource::build()
+            .endpoint("/graphql")
+            .subscription_endpoint("/graphql/ws")
+            .finish(),
+    )
 }
 
-/// Build the GraphQL axum router.
-pub fn graphql_router(state: AppState) -> Router {
-    let schema = build_schema(state);
+/// Build the GraphQL axum router, authorizing operations against `rbac`.
+/// It is to be mounted behind the authentication middleware.
+pub fn graphql_router<S>(state: AppState, rbac: RbacState) -> Router<S> {
+    let endpoint = Endpoint {
+        schema: build_schema(state),
+        rbac,
+    };
 
     Router::new()
         .route("/graphql", post(graphql_handler))
+        .route("/graphql/ws", get(graphql_ws_handler))
         .route("/graphiql", get(graphiql_handler))
-        .with_state(schema)
+        .with_state(endpoint)
 }
 
 // ============================================================================
 // Conversion Helpers
 // ============================================================================
 
+/// Refuse the operation unless the client may make the REST request it
+/// stands for, `method` on `path`, as the authentication middleware would
+/// decide it.  Nothing is checked when authentication is disabled.
+async fn authorize(ctx: &Context<'_>, method: Method, path: &str) -> async_graphql::Result<()> {
+    let Some(Caller { identity, rbac }) = ctx.data_opt::<Caller>() else {
+        return Ok(());
+    };
+    let namespace = rbac.namespace_for(identity, path).await;
+    rbac::check_access_in(identity, path, &method, rbac, namespace.as_deref()).map_err(|e| {
+        warn!(client = %identity.id, path = %path, "GraphQL operation denied: {}", e.error);
+        async_graphql::Error::new(e.error)
+    })
+}
+
+/// Apply `request` to hexad `id` as `PUT /hexads/{id}` does.
+async fn update_hexad(ctx: &Context<'_>, id: String, request: HexadRequest) -> async_graphql::Result<Hexad> {
+    authorize(ctx, Method::PUT, &format!("/hexads/{}", id)).await?;
+    let state = ctx.data::<AppState>()?.clone();
+    let Json(h) = crate::update_hexad_handler(AxumState(state), Path(id), None, Json(request))
+        .await
+        .map_err(api_error)?;
+    Ok(Hexad {
+        id: h.id,
+        created_at: h.status.created_at,
+        modified_at: h.status.modified_at,
+        version: h.status.version,
+        has_graph: h.has_graph,
+        has_vector: h.has_vector,
+        has_tensor: h.has_tensor,
+        has_semantic: h.has_semantic,
+        has_document: h.has_document,
+        version_count: h.version_count,
+    })
+}
+
+/// The GraphQL error for a failed operation; internal errors are logged
+/// and not shown to the client.
+fn api_error(e: ApiError) -> async_graphql::Error {
+    match e {
+        ApiError::Internal(msg) | ApiError::Serialization(msg) => {
+            error!(error = %msg, "GraphQL mutation failed");
+            async_graphql::Error::new("Internal server error")
+        }
+        e => async_graphql::Error::new(e.to_string()),
+    }
+}
+
+/// The serialized name of a unit enum variant.
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
 fn convert_physical_plan(p: &verisim_planner::PhysicalPlan) -> PhysicalPlan {
     PhysicalPlan {
         steps: p
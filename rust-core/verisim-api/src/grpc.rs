@@ -1,22 +1,92 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 //! gRPC API for VeriSimDB.
 //!
-//! Exposes planner and hexad operations via gRPC on a separate port (50051).
+//! Exposes planner, hexad, drift, provenance and VQL operations via gRPC on
+//! a separate port (`grpc_port`, conventionally 50051).  Handlers delegate to the REST handlers, so the
+//! two APIs validate and answer alike; requests too rich for protobuf pass
+//! their REST JSON body in a `*_json` field.
+//!
+//! Searches and VQL results can also be streamed.  `WatchChanges` follows
+//! the replication change feed from a position and then waits for new
+//! writes; `Export` streams every hexad of one read snapshot, and `Import`
+//! takes such a stream back, so a store can be copied between instances
+//! without a request per entity.
+//!
+//! Calls are authenticated as REST requests are, with the same credentials
+//! sent as metadata (`x-api-key`, `authorization`) or the client
+//! certificate, and authorized as the REST request each RPC stands for:
+//! `Create` as `POST /hexads`, `SetConfig` as `PUT /planner/config`.  Calls
+//! on one hexad are authorized as on the collection, since its ID travels
+//! in the message rather than the path; the entity policy still judges the
+//! hexad itself.  The server speaks TLS whenever the REST API does.
+//!
+//! The standard `grpc.health.v1.Health` service reports the same checks as
+//! `GET /health`: a degraded server is `NOT_SERVING`.  Server reflection
+//! (v1 and v1alpha) describes every service, so tools like grpcurl work
+//! without the proto files.
+
+use std::pin::Pin;
+use std::time::Duration;
 
-use tonic::{Request, Response, Status};
-use tracing::error;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::Json;
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tonic::server::NamedService;
+use tonic::service::Routes;
+use tonic::{Request, Response, Status, Streaming};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::pb::{HealthCheckRequest, HealthCheckResponse};
+use tracing::{error, info};
 
+use verisim_hexad::{security, HexadId, HexadInput, HexadStore};
 use verisim_planner::LogicalPlan;
 
-use crate::AppState;
+use crate::auth::AuthState;
+use crate::peer_auth::PeerCertAcceptor;
+use crate::{redaction, replication, vql, ApiError, AppState, HexadRequest};
 
 // Pre-generated protobuf types (from proto/verisim.proto via prost-build).
 // Using a committed file eliminates the protoc build dependency.
 #[path = "proto/verisim.rs"]
 pub mod proto;
 
+/// Encoded descriptors of `proto/verisim.proto`, served by reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("proto/verisim_descriptor.bin");
+
 use proto::veri_sim_planner_server::{VeriSimPlanner, VeriSimPlannerServer};
 use proto::veri_sim_hexad_server::{VeriSimHexad, VeriSimHexadServer};
+use proto::veri_sim_drift_server::{VeriSimDrift, VeriSimDriftServer};
+use proto::veri_sim_provenance_server::{VeriSimProvenance, VeriSimProvenanceServer};
+use proto::veri_sim_vql_server::{VeriSimVql, VeriSimVqlServer};
+
+/// Messages a streaming RPC sends
+type GrpcStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Messages buffered per stream before its producer waits for the client
+const STREAM_BUFFER: usize = 64;
+
+/// Import errors reported back; the rest are only counted
+const MAX_IMPORT_ERRORS: usize = 100;
+
+/// Longest refusal body read back into a gRPC status
+const MAX_REFUSAL_BYTES: usize = 64 * 1024;
+
+/// How often a health watch re-runs the health checks
+const HEALTH_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Services whose health is reported; `""` is the server as a whole
+const SERVICES: &[&str] = &[
+    "",
+    <VeriSimPlannerServer<PlannerService> as NamedService>::NAME,
+    <VeriSimHexadServer<HexadService> as NamedService>::NAME,
+    <VeriSimDriftServer<DriftService> as NamedService>::NAME,
+    <VeriSimProvenanceServer<ProvenanceService> as NamedService>::NAME,
+    <VeriSimVqlServer<VqlService> as NamedService>::NAME,
+];
 
 // ============================================================================
 // Planner gRPC Service
@@ -223,40 +293,22 @@ async fn create(
         request: Request<proto::HexadCreateRequest>,
     ) -> Result<Response<proto::HexadResponse>, Status> {
         let req = request.into_inner();
-        let mut input = verisim_hexad::HexadInput::default();
+        let request = if req.request_json.is_empty() {
+            HexadRequest {
+                title: non_empty(req.title),
+                body: Some(req.body),
+                embedding: (!req.embedding.is_empty()).then_some(req.embedding),
+                types: (!req.types.is_empty()).then_some(req.types),
+                ..Default::default()
+            }
+        } else {
+            from_json(&req.request_json, "request_json")?
+        };
 
-        if !req.title.is_empty() {
-            input.document = Some(verisim_hexad::HexadDocumentInput {
-                title: req.title,
-                body: req.body,
-                fields: std::collections::HashMap::new(),
-            });
-        }
-        if !req.embedding.is_empty() {
-            input.vector = Some(verisim_hexad::HexadVectorInput {
-                embedding: req.embedding,
-                model: None,
-            });
-        }
-        if !req.types.is_empty() {
-            input.semantic = Some(verisim_hexad::HexadSemanticInput {
-                types: req.types,
-                properties: std::collections::HashMap::new(),
-            });
-        }
-
-        use verisim_hexad::HexadStore;
-        let h = self
-            .state
-            .hexad_store
-            .create(input)
+        let (_, Json(hexad)) = crate::create_hexad_handler(State(self.state.clone()), None, Json(request))
             .await
-            .map_err(|e| {
-                error!(error = %e, "gRPC hexad creation failed");
-                Status::internal("Internal server error")
-            })?;
-
-        Ok(Response::new(hexad_to_proto(&h)))
+            .map_err(status)?;
+        Ok(Response::new(hexad.into()))
     }
 
     async fn get(
@@ -266,7 +318,6 @@ async fn get(
         let id = request.into_inner().id;
         let hexad_id = verisim_hexad::HexadId::new(&id);
 
-        use verisim_hexad::HexadStore;
         let h = self
             .state
             .hexad_store
@@ -286,41 +337,22 @@ async fn update(
         request: Request<proto::HexadUpdateRequest>,
     ) -> Result<Response<proto::HexadResponse>, Status> {
         let req = request.into_inner();
-        let hexad_id = verisim_hexad::HexadId::new(&req.id);
-
-        let mut input = verisim_hexad::HexadInput::default();
-        if !req.title.is_empty() {
-            input.document = Some(verisim_hexad::HexadDocumentInput {
-                title: req.title,
-                body: req.body,
-                fields: std::collections::HashMap::new(),
-            });
-        }
-        if !req.embedding.is_empty() {
-            input.vector = Some(verisim_hexad::HexadVectorInput {
-                embedding: req.embedding,
-                model: None,
-            });
-        }
-        if !req.types.is_empty() {
-            input.semantic = Some(verisim_hexad::HexadSemanticInput {
-                types: req.types,
-                properties: std::collections::HashMap::new(),
-            });
-        }
+        let request = if req.request_json.is_empty() {
+            HexadRequest {
+                title: non_empty(req.title),
+                body: Some(req.body),
+                embedding: (!req.embedding.is_empty()).then_some(req.embedding),
+                types: (!req.types.is_empty()).then_some(req.types),
+                ..Default::default()
+            }
+        } else {
+            from_json(&req.request_json, "request_json")?
+        };
 
-        use verisim_hexad::HexadStore;
-        let h = self
-            .state
-            .hexad_store
-            .update(&hexad_id, input)
+        let Json(hexad) = crate::update_hexad_handler(State(self.state.clone()), Path(req.id), None, Json(request))
             .await
-            .map_err(|e| {
-                error!(error = %e, "gRPC hexad update failed");
-                Status::internal("Internal server error")
-            })?;
-
-        Ok(Response::new(hexad_to_proto(&h)))
+            .map_err(status)?;
+        Ok(Response::new(hexad.into()))
     }
 
     async fn delete(
@@ -330,7 +362,6 @@ async fn delete(
         let id = request.into_inner().id;
         let hexad_id = verisim_hexad::HexadId::new(&id);
 
-        use verisim_hexad::HexadStore;
         self.state
             .hexad_store
             .delete(&hexad_id)
@@ -350,7 +381,6 @@ async fn search_text(
         let req = request.into_inner();
         let limit = if req.limit > 0 { req.limit as usize } else { 10 };
 
-        use verisim_hexad::HexadStore;
         let hexads = self
             .state
             .hexad_store
@@ -367,11 +397,7 @@ async fn search_text(
             .map(|(i, h)| proto::SearchResultMsg {
                 id: h.id.to_string(),
                 score: 1.0 - (i as f32 * 0.1),
-                title: h
-                    .document
-                    .as_ref()
-                    .map(|d| d.title.clone())
-                    .unwrap_or_default(),
+                title: redacted_title(h.document.as_ref().map(|d| d.title.clone())),
             })
             .collect();
 
@@ -385,7 +411,6 @@ async fn search_vector(
         let req = request.into_inner();
         let k = if req.k > 0 { req.k as usize } else { 10 };
 
-        use verisim_hexad::HexadStore;
         let hexads = self
             .state
             .hexad_store
@@ -402,43 +427,769 @@ async fn search_vector(
             .map(|(i, h)| proto::SearchResultMsg {
                 id: h.id.to_string(),
                 score: 1.0 - (i as f32 * 0.1),
-                title: h
-                    .document
-                    .as_ref()
-                    .map(|d| d.title.clone())
-                    .unwrap_or_default(),
+                title: redacted_title(h.document.as_ref().map(|d| d.title.clone())),
             })
             .collect();
 
         Ok(Response::new(proto::SearchResponse { results }))
     }
+
+    async fn list(
+        &self,
+        request: Request<proto::ListRequest>,
+    ) -> Result<Response<proto::HexadListResponse>, Status> {
+        let req = request.into_inner();
+        let query = crate::ListQuery {
+            limit: positive(req.limit),
+            offset: positive(req.offset),
+            collection: non_empty(req.collection),
+            snapshot: None,
+        };
+        let Json(hexads) = crate::list_hexads_handler(State(self.state.clone()), Query(query))
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::HexadListResponse {
+            hexads: hexads.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn search_related(
+        &self,
+        request: Request<proto::RelatedSearchRequest>,
+    ) -> Result<Response<proto::HexadListResponse>, Status> {
+        let req = request.into_inner();
+        let query = crate::RelatedQuery {
+            predicate: non_empty(req.predicate),
+        };
+        let Json(hexads) = crate::related_search_handler(State(self.state.clone()), Path(req.id), Query(query))
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::HexadListResponse {
+            hexads: hexads.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    type StreamSearchTextStream = GrpcStream<proto::SearchResultMsg>;
+
+    async fn stream_search_text(
+        &self,
+        request: Request<proto::TextSearchRequest>,
+    ) -> Result<Response<Self::StreamSearchTextStream>, Status> {
+        let req = request.into_inner();
+        let query = crate::SearchQuery {
+            q: Some(req.query),
+            limit: positive(req.limit),
+            collection: None,
+            snapshot: None,
+        };
+        let Json(results) = crate::text_search_handler(State(self.state.clone()), Query(query))
+            .await
+            .map_err(status)?;
+        // Converted here, in the call's redaction scope, not as the stream is read
+        let results: Vec<proto::SearchResultMsg> = results.into_iter().map(Into::into).collect();
+        Ok(Response::new(Box::pin(futures::stream::iter(results.into_iter().map(Ok)))))
+    }
+
+    type StreamSearchVectorStream = GrpcStream<proto::SearchResultMsg>;
+
+    async fn stream_search_vector(
+        &self,
+        request: Request<proto::VectorSearchRequest>,
+    ) -> Result<Response<Self::StreamSearchVectorStream>, Status> {
+        let req = request.into_inner();
+        let search = crate::VectorSearchRequest {
+            vector: req.vector,
+            k: positive(req.k),
+            collection: None,
+            snapshot: None,
+        };
+        let Json(results) = crate::vector_search_handler(State(self.state.clone()), Json(search))
+            .await
+            .map_err(status)?;
+        // Converted here, in the call's redaction scope, not as the stream is read
+        let results: Vec<proto::SearchResultMsg> = results.into_iter().map(Into::into).collect();
+        Ok(Response::new(Box::pin(futures::stream::iter(results.into_iter().map(Ok)))))
+    }
+
+    type WatchChangesStream = GrpcStream<proto::ChangeMsg>;
+
+    async fn watch_changes(
+        &self,
+        request: Request<proto::WatchChangesRequest>,
+    ) -> Result<Response<Self::WatchChangesStream>, Status> {
+        let req = request.into_inner();
+        let log = self.state.replication.log.clone();
+        if !req.epoch.is_empty() && req.epoch != log.epoch() {
+            return Err(Status::out_of_range(format!(
+                "Epoch {} has ended; export a snapshot and follow from its position",
+                req.epoch
+            )));
+        }
+        // Subscribed before the first read, so no change falls in between
+        let mut heads = log.watch();
+        let mut from = if req.latest { log.head() } else { req.from };
+        let epoch = log.epoch().to_string();
+
+        let state = self.state.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        spawn_scoped(async move {
+            loop {
+                let batch = match replication::changes(&state, from, replication::MAX_CHANGE_PAGE, Some(&epoch), None, None).await {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        let _ = sender.send(Err(status(e))).await;
+                        return;
+                    }
+                };
+                if batch.bootstrap_required {
+                    let message = format!(
+                        "Changes after position {from} are not retained; export a snapshot and follow from its position"
+                    );
+                    let _ = sender.send(Err(Status::out_of_range(message))).await;
+                    return;
+                }
+                for change in batch.changes {
+                    if sender.send(change_to_proto(change, &batch.epoch)).await.is_err() {
+                        return;
+                    }
+                }
+                from = batch.through.unwrap_or(from);
+                if from >= batch.head {
+                    tokio::select! {
+                        changed = heads.changed() => if changed.is_err() { return },
+                        _ = sender.closed() => return,
+                    }
+                }
+            }
+        });
+        Ok(Response::new(channel_stream(receiver)))
+    }
+
+    type ExportStream = GrpcStream<proto::HexadRecord>;
+
+    async fn export(
+        &self,
+        _request: Request<proto::Empty>,
+    ) -> Result<Response<Self::ExportStream>, Status> {
+        // The position is read first: changes made while the snapshot is
+        // taken are replayed by a follower, which is harmless
+        let log = &self.state.replication.log;
+        let position = log.head();
+        let snapshot = self
+            .state
+            .hexad_store
+            .read_snapshot()
+            .await
+            .map_err(|e| status(ApiError::Internal(e.to_string())))?;
+
+        let state = self.state.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        spawn_scoped(async move {
+            let page = replication::MAX_SNAPSHOT_PAGE;
+            let mut offset = 0;
+            loop {
+                let (entities, scanned) = match replication::snapshot_entities(&state, &snapshot, offset, page, None).await {
+                    Ok(found) => found,
+                    Err(e) => {
+                        let _ = sender.send(Err(status(e))).await;
+                        return;
+                    }
+                };
+                for entity in entities {
+                    let record = serde_json::to_string(&entity.input)
+                        .map(|input_json| proto::HexadRecord {
+                            id: entity.id,
+                            input_json: redaction::redact_json(input_json),
+                            provenance_head: entity.provenance_head.unwrap_or_default(),
+                        })
+                        .map_err(|e| status(ApiError::Serialization(e.to_string())));
+                    if sender.send(record).await.is_err() {
+                        return;
+                    }
+                }
+                if scanned < page {
+                    return;
+                }
+                offset += scanned;
+            }
+        });
+
+        // Where to follow the change feed from once the export is applied
+        let mut response = Response::new(channel_stream(receiver));
+        if let (Ok(epoch), Ok(position)) = (log.epoch().parse(), position.to_string().parse()) {
+            response.metadata_mut().insert("x-verisim-epoch", epoch);
+            response.metadata_mut().insert("x-verisim-position", position);
+        }
+        Ok(response)
+    }
+
+    async fn import(
+        &self,
+        request: Request<Streaming<proto::HexadRecord>>,
+    ) -> Result<Response<proto::ImportResponse>, Status> {
+        self.state.replication.check_writable().map_err(status)?;
+        let mut records = request.into_inner();
+        let mut response = proto::ImportResponse::default();
+        let mut index = 0usize;
+        while let Some(record) = records.message().await? {
+            match self.import_record(record).await {
+                Ok(()) => response.imported += 1,
+                Err(e) => {
+                    response.failed += 1;
+                    if response.errors.len() < MAX_IMPORT_ERRORS {
+                        response.errors.push(format!("{index}: {}", status(e).message()));
+                    }
+                }
+            }
+            index += 1;
+        }
+        Ok(Response::new(response))
+    }
+}
+
+impl HexadService {
+    /// Write one imported record, under its ID when it has one
+    async fn import_record(&self, record: proto::HexadRecord) -> Result<(), ApiError> {
+        let mut input: HexadInput = serde_json::from_str(&record.input_json)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid input_json: {e}")))?;
+        if input.provenance.is_none() {
+            input.provenance = Some(verisim_hexad::HexadProvenanceInput {
+                event_type: "imported".to_string(),
+                actor: "import".to_string(),
+                source: None,
+                description: "Imported via gRPC".to_string(),
+                link: None,
+            });
+        }
+        let written = if record.id.is_empty() {
+            self.state.hexad_store.create(input).await
+        } else {
+            crate::validate_hexad_id(&record.id)?;
+            self.state.hexad_store.put(&HexadId::new(&record.id), input).await
+        };
+        let hexad = written.map_err(|e| match e {
+            verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+            _ => ApiError::Internal(e.to_string()),
+        })?;
+        self.state.observe_embedding(hexad.embedding.as_ref());
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Drift gRPC Service
+// ============================================================================
+
+pub struct DriftService {
+    state: AppState,
+}
+
+impl DriftService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl VeriSimDrift for DriftService {
+    async fn get_status(
+        &self,
+        request: Request<proto::DriftStatusRequest>,
+    ) -> Result<Response<proto::DriftStatusResponse>, Status> {
+        let query = crate::DriftStatusQuery {
+            namespace: non_empty(request.into_inner().namespace),
+        };
+        let Json(statuses) = crate::drift_status_handler(State(self.state.clone()), Query(query))
+            .await
+            .map_err(status)?;
+
+        let statuses = statuses
+            .into_iter()
+            .map(|s| proto::DriftStatusMsg {
+                drift_type: s.drift_type,
+                namespace: s.namespace.unwrap_or_default(),
+                current_score: s.current_score,
+                moving_average: s.moving_average,
+                max_score: s.max_score,
+                measurement_count: s.measurement_count,
+            })
+            .collect();
+        Ok(Response::new(proto::DriftStatusResponse { statuses }))
+    }
+
+    type WatchDriftStream = GrpcStream<proto::DriftEventMsg>;
+
+    async fn watch_drift(
+        &self,
+        request: Request<proto::DriftWatchRequest>,
+    ) -> Result<Response<Self::WatchDriftStream>, Status> {
+        let drift_type = non_empty(request.into_inner().drift_type);
+        let events = crate::graphql::receive(self.state.graphql_events.drift_events(), "WatchDrift")
+            .filter(move |e| std::future::ready(drift_type.as_ref().is_none_or(|t| *t == e.drift_type.to_string())))
+            .map(|e| {
+                Ok(proto::DriftEventMsg {
+                    drift_type: e.drift_type.to_string(),
+                    severity: format!("{:?}", e.severity),
+                    score: e.score,
+                    affected_entities: e.affected_entities,
+                    description: e.description,
+                    remediation: e.remediation.unwrap_or_default(),
+                    namespace: e.namespace.unwrap_or_default(),
+                    detected_at: e.detected_at.to_rfc3339(),
+                })
+            });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
+
+// ============================================================================
+// Provenance gRPC Service
+// ============================================================================
+
+pub struct ProvenanceService {
+    state: AppState,
+}
+
+impl ProvenanceService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl VeriSimProvenance for ProvenanceService {
+    async fn get_chain(
+        &self,
+        request: Request<proto::HexadIdRequest>,
+    ) -> Result<Response<proto::ProvenanceChainResponse>, Status> {
+        let id = request.into_inner().id;
+        let Json(chain) = crate::provenance_get_chain_handler(State(self.state.clone()), Path(id))
+            .await
+            .map_err(status)?;
+
+        let records = chain
+            .records
+            .into_iter()
+            .map(|r| proto::ProvenanceRecordMsg {
+                event_type: r.event_type,
+                actor: r.actor,
+                timestamp: r.timestamp,
+                source: r.source.unwrap_or_default(),
+                description: r.description,
+                content_hash: r.content_hash,
+            })
+            .collect();
+        Ok(Response::new(proto::ProvenanceChainResponse {
+            entity_id: chain.entity_id,
+            chain_length: chain.chain_length as u64,
+            chain_valid: chain.chain_valid,
+            records,
+        }))
+    }
+
+    async fn record(
+        &self,
+        request: Request<proto::ProvenanceRecordRequest>,
+    ) -> Result<Response<proto::ProvenanceRecordedResponse>, Status> {
+        let req = request.into_inner();
+        let body = crate::ProvenanceRequest {
+            event_type: req.event_type,
+            actor: req.actor,
+            source: non_empty(req.source),
+            description: req.description,
+        };
+        let Json(recorded) = crate::provenance_record_handler(State(self.state.clone()), Path(req.id.clone()), None, Json(body))
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::ProvenanceRecordedResponse {
+            entity_id: req.id,
+            chain_length: recorded["chain_length"].as_u64().unwrap_or(0),
+        }))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<proto::HexadIdRequest>,
+    ) -> Result<Response<proto::ProvenanceVerifyResponse>, Status> {
+        let id = request.into_inner().id;
+        let Json(verified) = crate::provenance_verify_handler(State(self.state.clone()), Path(id.clone()))
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::ProvenanceVerifyResponse {
+            entity_id: id,
+            has_provenance: verified["has_provenance"].as_bool().unwrap_or(false),
+            chain_valid: verified["chain_valid"].as_bool().unwrap_or(false),
+        }))
+    }
+}
+
+// ============================================================================
+// VQL gRPC Service
+// ============================================================================
+
+pub struct VqlService {
+    state: AppState,
+}
+
+impl VqlService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    async fn execute_request(&self, req: proto::VqlRequest) -> Result<vql::VqlExecuteResponse, Status> {
+        let params = if req.params_json.is_empty() {
+            Default::default()
+        } else {
+            from_json(&req.params_json, "params_json")?
+        };
+        let request = vql::VqlExecuteRequest {
+            query: req.query,
+            params,
+            transaction: non_empty(req.transaction_id),
+        };
+        let Json(mut response) = vql::vql_execute_handler(State(self.state.clone()), None, HeaderMap::new(), Json(request))
+            .await
+            .map_err(status)?;
+        redaction::redact(&mut response.data);
+        Ok(response)
+    }
+}
+
+#[tonic::async_trait]
+impl VeriSimVql for VqlService {
+    async fn execute(
+        &self,
+        request: Request<proto::VqlRequest>,
+    ) -> Result<Response<proto::VqlResponse>, Status> {
+        let response = self.execute_request(request.into_inner()).await?;
+        Ok(Response::new(proto::VqlResponse {
+            success: response.success,
+            statement_type: response.statement_type,
+            row_count: response.row_count as u64,
+            data_json: response.data.to_string(),
+            message: response.message.unwrap_or_default(),
+        }))
+    }
+
+    type ExecuteStreamStream = GrpcStream<proto::VqlRowMsg>;
+
+    async fn execute_stream(
+        &self,
+        request: Request<proto::VqlRequest>,
+    ) -> Result<Response<Self::ExecuteStreamStream>, Status> {
+        let response = self.execute_request(request.into_inner()).await?;
+        let rows = match response.data {
+            serde_json::Value::Array(rows) => rows,
+            serde_json::Value::Null => Vec::new(),
+            data => vec![data],
+        };
+        let rows = rows.into_iter().map(|row| Ok(proto::VqlRowMsg { row_json: row.to_string() }));
+        Ok(Response::new(Box::pin(futures::stream::iter(rows))))
+    }
+}
+
+// ============================================================================
+// Health gRPC Service
+// ============================================================================
+
+/// `grpc.health.v1.Health`, answered from the same checks as `GET /health`
+pub struct HealthService {
+    state: AppState,
+}
+
+impl HealthService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Current status of `service`, or `None` when it is not served here
+    fn status(&self, service: &str) -> Option<ServingStatus> {
+        SERVICES.contains(&service).then(|| match crate::health(&self.state).status.as_str() {
+            "healthy" => ServingStatus::Serving,
+            _ => ServingStatus::NotServing,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl Health for HealthService {
+    async fn check(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<HealthCheckResponse>, Status> {
+        let service = request.into_inner().service;
+        let status = self
+            .status(&service)
+            .ok_or_else(|| Status::not_found(format!("Unknown service: {service}")))?;
+        Ok(Response::new(HealthCheckResponse { status: status as i32 }))
+    }
+
+    type WatchStream = GrpcStream<HealthCheckResponse>;
+
+    async fn watch(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let service = request.into_inner().service;
+        let watcher = Self::new(self.state.clone());
+        let (sender, receiver) = mpsc::channel(1);
+        tokio::spawn(async move {
+            // Sends the first status, then each change
+            let mut last = None;
+            loop {
+                let status = watcher.status(&service).unwrap_or(ServingStatus::ServiceUnknown);
+                if last != Some(status) {
+                    let response = HealthCheckResponse { status: status as i32 };
+                    if sender.send(Ok(response)).await.is_err() {
+                        return;
+                    }
+                    last = Some(status);
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(HEALTH_WATCH_INTERVAL) => {}
+                    _ = sender.closed() => return,
+                }
+            }
+        });
+        Ok(Response::new(channel_stream(receiver)))
+    }
 }
 
 // ============================================================================
 // Helpers
 // ============================================================================
 
+impl From<crate::HexadResponse> for proto::HexadResponse {
+    fn from(h: crate::HexadResponse) -> Self {
+        Self {
+            id: h.id,
+            created_at: h.status.created_at,
+            modified_at: h.status.modified_at,
+            version: h.status.version,
+            has_graph: h.has_graph,
+            has_vector: h.has_vector,
+            has_tensor: h.has_tensor,
+            has_semantic: h.has_semantic,
+            has_document: h.has_document,
+            version_count: h.version_count,
+            has_provenance: h.has_provenance,
+            has_spatial: h.has_spatial,
+            provenance_chain_length: h.provenance_chain_length,
+        }
+    }
+}
+
+impl From<crate::SearchResultResponse> for proto::SearchResultMsg {
+    fn from(r: crate::SearchResultResponse) -> Self {
+        Self {
+            id: r.id,
+            score: r.score,
+            title: redacted_title(r.title),
+        }
+    }
+}
+
+/// A search result's title, or what the caller may see of it
+fn redacted_title(title: Option<String>) -> String {
+    let mut result = serde_json::json!({ "title": title });
+    redaction::redact(&mut result);
+    result["title"].as_str().unwrap_or_default().to_string()
+}
+
 fn hexad_to_proto(h: &verisim_hexad::Hexad) -> proto::HexadResponse {
-    proto::HexadResponse {
-        id: h.id.to_string(),
-        created_at: h.status.created_at.to_rfc3339(),
-        modified_at: h.status.modified_at.to_rfc3339(),
-        version: h.status.version,
-        has_graph: h.graph_node.is_some(),
-        has_vector: h.embedding.is_some(),
-        has_tensor: h.tensor.is_some(),
-        has_semantic: h.semantic.is_some(),
-        has_document: h.document.is_some(),
-        version_count: h.version_count,
-    }
-}
-
-/// Build gRPC server routes. Returns a tonic Router that can be served.
-pub fn build_grpc_router(state: AppState) -> tonic::transport::server::Router {
+    crate::HexadResponse::from(h).into()
+}
+
+fn change_to_proto(change: replication::Change, epoch: &str) -> Result<proto::ChangeMsg, Status> {
+    let input_json = match &change.input {
+        Some(input) => serde_json::to_string(input)
+            .map(redaction::redact_json)
+            .map_err(|e| status(ApiError::Serialization(e.to_string())))?,
+        None => String::new(),
+    };
+    Ok(proto::ChangeMsg {
+        position: change.position,
+        epoch: epoch.to_string(),
+        hexad_id: change.hexad_id,
+        op: match change.op {
+            replication::ChangeOp::Put => "put",
+            replication::ChangeOp::Delete => "delete",
+        }
+        .to_string(),
+        at: change.at.to_rfc3339(),
+        input_json,
+    })
+}
+
+/// The gRPC status for an API error; internal details are logged, not sent
+fn status(e: ApiError) -> Status {
+    match e {
+        ApiError::NotFound(msg) => Status::not_found(msg),
+        ApiError::BadRequest(msg) => Status::invalid_argument(msg),
+        ApiError::Unauthorized(msg) => Status::unauthenticated(msg),
+        ApiError::Unavailable(msg) => Status::unavailable(msg),
+        ApiError::Timeout(msg) => Status::deadline_exceeded(msg),
+        ApiError::Cancelled(msg) => Status::cancelled(msg),
+        ApiError::Conflict { message, .. } => Status::aborted(message),
+        ApiError::Internal(msg) | ApiError::Serialization(msg) => {
+            error!(error = %msg, "gRPC request failed");
+            Status::internal("Internal server error")
+        }
+    }
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str, field: &str) -> Result<T, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("Invalid {field}: {e}")))
+}
+
+fn non_empty(s: String) -> Option<String> {
+    (!s.is_empty()).then_some(s)
+}
+
+fn positive(n: i32) -> Option<usize> {
+    usize::try_from(n).ok().filter(|n| *n > 0)
+}
+
+/// The messages sent on `receiver`, as a response stream
+fn channel_stream<T: Send + 'static>(receiver: mpsc::Receiver<Result<T, Status>>) -> GrpcStream<T> {
+    Box::pin(futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|message| (message, receiver))
+    }))
+}
+
+/// Run `future` on a task of its own for the call's principal and
+/// redaction: a streamed response is produced after the call has left its
+/// scopes.
+fn spawn_scoped<F: std::future::Future<Output = ()> + Send + 'static>(future: F) {
+    let principal = security::current();
+    let future = redaction::scope(redaction::current(), future);
+    tokio::spawn(async move {
+        match principal {
+            Some(principal) => security::scope(principal, future).await,
+            None => future.await,
+        }
+    });
+}
+
+/// The REST request a call stands for, as (method, path), which it is
+/// authenticated and authorized as.
+fn rest_equivalent(path: &str) -> (Method, String) {
+    let (get, post) = (Method::GET, Method::POST);
+    let (method, equivalent) = match path.trim_start_matches('/').split_once('/').unwrap_or_default() {
+        ("verisim.VeriSimPlanner", "OptimizePlan") => (post, "/query/plan"),
+        ("verisim.VeriSimPlanner", "ExplainPlan") => (post, "/query/explain"),
+        ("verisim.VeriSimPlanner", "GetConfig") => (get, "/planner/config"),
+        ("verisim.VeriSimPlanner", "SetConfig") => (Method::PUT, "/planner/config"),
+        ("verisim.VeriSimPlanner", "GetStats") => (get, "/planner/stats"),
+        ("verisim.VeriSimHexad", "Create" | "Import") => (post, "/hexads"),
+        ("verisim.VeriSimHexad", "Get" | "List") => (get, "/hexads"),
+        ("verisim.VeriSimHexad", "Update") => (Method::PUT, "/hexads"),
+        ("verisim.VeriSimHexad", "Delete") => (Method::DELETE, "/hexads"),
+        ("verisim.VeriSimHexad", "SearchText" | "StreamSearchText") => (get, "/search/text"),
+        ("verisim.VeriSimHexad", "SearchVector" | "StreamSearchVector") => (post, "/search/vector"),
+        ("verisim.VeriSimHexad", "SearchRelated") => (get, "/search/related"),
+        ("verisim.VeriSimHexad", "WatchChanges") => (get, "/replication/changes"),
+        ("verisim.VeriSimHexad", "Export") => (get, "/replication/snapshot"),
+        ("verisim.VeriSimDrift", "GetStatus") => (get, "/drift/status"),
+        ("verisim.VeriSimDrift", "WatchDrift") => (get, "/drift/alerts"),
+        ("verisim.VeriSimProvenance", "GetChain" | "Verify") => (get, "/provenance"),
+        ("verisim.VeriSimProvenance", "Record") => (post, "/provenance"),
+        ("verisim.VeriSimVql", _) => (post, "/vql/execute"),
+        ("grpc.health.v1.Health", _) => (get, "/health"),
+        // Reflection only describes the services
+        (service, _) if service.starts_with("grpc.reflection.") => (get, path),
+        // Anything else would be a write, and is answered as unimplemented
+        _ => (post, path),
+    };
+    (method, equivalent.to_string())
+}
+
+/// Authenticates and authorizes each call as the REST API's middleware
+/// does the request it stands for, and answers a refused call with the
+/// matching gRPC status.
+async fn auth_middleware(
+    State(auth): State<AuthState>,
+    request: axum::extract::Request,
+    next: Next,
+) -> axum::response::Response {
+    let (method, path) = rest_equivalent(request.uri().path());
+    let response = crate::auth::admit(&auth, &method, &path, request, next).await;
+    // Calls that reach a service are answered with 200 and a gRPC status
+    let code = match response.status() {
+        StatusCode::OK => return response,
+        StatusCode::UNAUTHORIZED => tonic::Code::Unauthenticated,
+        StatusCode::FORBIDDEN => tonic::Code::PermissionDenied,
+        StatusCode::TOO_MANY_REQUESTS => tonic::Code::ResourceExhausted,
+        StatusCode::SERVICE_UNAVAILABLE => tonic::Code::Unavailable,
+        _ => tonic::Code::Unknown,
+    };
+    let body = axum::body::to_bytes(response.into_body(), MAX_REFUSAL_BYTES).await.unwrap_or_default();
+    let message = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|refusal| refusal["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(&body).into_owned());
+    Status::new(code, message).into_http()
+}
+
+/// Build the gRPC services as a router, behind authentication and
+/// redaction.
+pub fn build_grpc_router(state: AppState) -> axum::Router {
+    let health_svc = HealthServer::new(HealthService::new(state.clone()));
+    let reflection_svc = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .expect("embedded file descriptor sets are valid");
+    let reflection_alpha_svc = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build_v1alpha()
+        .expect("embedded file descriptor sets are valid");
     let planner_svc = VeriSimPlannerServer::new(PlannerService::new(state.clone()));
-    let hexad_svc = VeriSimHexadServer::new(HexadService::new(state));
+    let hexad_svc = VeriSimHexadServer::new(HexadService::new(state.clone()));
+    let drift_svc = VeriSimDriftServer::new(DriftService::new(state.clone()));
+    let provenance_svc = VeriSimProvenanceServer::new(ProvenanceService::new(state.clone()));
+    let vql_svc = VeriSimVqlServer::new(VqlService::new(state.clone()));
 
-    tonic::transport::Server::builder()
+    let auth = crate::request_auth(&state);
+    let redactor = redaction::Redactor::new(state.config.redaction.clone(), auth.rbac.clone());
+
+    Routes::new(health_svc)
+        .add_service(reflection_svc)
+        .add_service(reflection_alpha_svc)
         .add_service(planner_svc)
         .add_service(hexad_svc)
+        .add_service(drift_svc)
+        .add_service(provenance_svc)
+        .add_service(vql_svc)
+        .into_axum_router()
+        .layer(axum::middleware::from_fn_with_state(redactor, redaction::redaction_middleware))
+        .layer(axum::middleware::from_fn_with_state(auth, auth_middleware))
+}
+
+/// Serve the gRPC API on `addr` in the background, over TLS when given
+/// `tls`; returns the address bound, whose port is chosen when `addr`
+/// gives port 0
+pub async fn spawn_server(
+    state: AppState,
+    addr: &str,
+    tls: Option<PeerCertAcceptor>,
+) -> Result<std::net::SocketAddr, std::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?.into_std()?;
+    let bound = listener.local_addr()?;
+    info!(addr = %bound, tls = tls.is_some(), "Starting VeriSimDB gRPC server");
+    let app = build_grpc_router(state).into_make_service();
+    tokio::spawn(async move {
+        let server = axum_server::from_tcp(listener);
+        let served = match tls {
+            Some(acceptor) => server.acceptor(acceptor).serve(app).await,
+            None => server.serve(app).await,
+        };
+        if let Err(e) = served {
+            error!(error = %e, "gRPC server failed");
+        }
+    });
+    Ok(bound)
 }
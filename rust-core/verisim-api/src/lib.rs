@@ -4,19 +4,36 @@
 //! HTTP API server for VeriSimDB.
 //! Exposes all database functionality via REST endpoints.
 
+pub mod analyze;
 pub mod auth;
+pub mod authz_audit;
+pub mod client_certs;
+pub mod crdt;
+pub mod executor;
 pub mod federation;
 pub mod graphql;
 pub mod grpc;
+pub mod lineage;
+pub mod object_store;
+pub mod oidc;
+pub mod peer_auth;
+pub mod queries;
 pub mod rbac;
+pub mod redaction;
+pub mod replication;
+pub mod service_accounts;
+pub mod sessions;
+pub mod slow_queries;
+pub mod subscriptions;
 pub mod transaction;
 pub mod vql;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     middleware as axum_middleware,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -24,33 +41,58 @@
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::net::TcpListener;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
 use std::sync::Mutex;
 
 use verisim_document::TantivyDocumentStore;
-use verisim_drift::{DriftDetector, DriftMetrics, DriftThresholds, DriftType};
+use verisim_drift::{
+    ActionPolicy, AlertConfig, AlertDispatcher, CorrelationConfig, DriftAction, DriftCorrelation, DriftDetector, DriftEvent, DriftForecast, DriftHistoryPoint, DriftHistoryStore, DriftMetrics,
+    DriftThresholds, DriftType, ForecastConfig, ForecastMethod, PopulationConfig, PopulationMonitor, PopulationStatus,
+    QuarantineRecord, SinkStats, SuppressionWindow,
+};
 #[cfg(not(feature = "persistent"))]
 use verisim_graph::SimpleGraphStore;
 #[cfg(feature = "persistent")]
 use verisim_graph::RedbGraphStore;
 use verisim_planner::{
-    CacheConfig, ExplainOutput, ExplainAnalyzeOutput, LogicalPlan, ParamValue,
-    PhysicalPlan, PlanCache, Planner, PlannerConfig, PreparedId, PreparedStatement,
-    Profiler, SlowQueryLog, SlowQuerySummary, StatisticsCollector,
+    CacheConfig, ExplainFormat, ExplainOutput, ExplainAnalyzeOutput, LogicalPlan, ParamValue,
+    PhysicalPlan, PlanCache, PlanTreeNode, Planner, PlannerConfig, PreparedId, PreparedStatement,
+    Profiler, ResultCache, ResultCacheStats, SlowQueryConfig, SlowQueryEntry, SlowQueryFilter, SlowQueryLog,
+    SlowQuerySummary, StatisticsCollector,
 };
 use verisim_hexad::{
-    BoundingBox, Coordinates, HexadConfig, HexadDocumentInput, HexadGraphInput,
+    BoundingBox, CollectionStats, ConsistencyCheck, CountFilter, EstimatedCounts, ExpiryStats, ConsistencyReport, Coordinates, EntityConsistency, HexadConfig, HexadDocumentInput, HexadGraphInput,
     HexadId, HexadInput, HexadProvenanceInput, HexadSemanticInput, HexadSnapshot,
-    HexadSpatialInput, HexadStore, HexadTensorInput, HexadVectorInput,
-    InMemoryHexadStore, ProvenanceStore, SpatialStore,
+    HexadSpatialInput, HexadStore, HexadTensorInput, HexadVectorInput, ModalityMask, ReadSnapshot,
+    InMemoryHexadStore, ProvenanceStore, SpatialStore, CheckpointReport, RecoveryReport, WalLag,
+    BackupKind, BackupManifest, RestoreReport,
+};
+use verisim_provenance::{ActorIdentity, InMemoryProvenanceStore};
+use service_accounts::{IssuedKey, ServiceAccount, ServiceAccountError};
+use verisim_spatial::{InMemorySpatialStore, TrajectoryStore};
+use verisim_normalizer::scanner::{DriftScanner, ScanReport, ScannerConfig};
+use verisim_normalizer::campaign::{Campaign, CampaignError, CampaignRun, CampaignScheduler, RunTrigger};
+use verisim_normalizer::conflict::{ConflictConfig, ConflictError, ConflictResolver};
+use verisim_normalizer::embedding::{HttpEmbedder, HttpEmbedderConfig};
+use verisim_normalizer::preview::NormalizationPreview;
+use verisim_normalizer::report::{NormalizerReport, MAX_RECENT_OUTCOMES};
+use verisim_normalizer::rollback::NormalizationRollback;
+use verisim_normalizer::selection::StrategySelection;
+use verisim_normalizer::worker::NormalizationJob;
+use verisim_normalizer::{
+    create_default_normalizer, NormalizationResult, Normalizer, NormalizerConfig, NormalizerError, NormalizerStatus,
 };
-use verisim_provenance::InMemoryProvenanceStore;
-use verisim_spatial::InMemorySpatialStore;
-use verisim_normalizer::{create_default_normalizer, Normalizer, NormalizerStatus};
-use verisim_semantic::InMemorySemanticStore;
-use verisim_semantic::zkp_bridge::{self as zkp_api, PrivacyLevel, ZkpProofRequest as ZkpBridgeRequest};
-use verisim_semantic::circuit_registry::CircuitRegistry;
+use verisim_semantic::{EntityProof, InMemorySemanticStore, SemanticError};
+use verisim_semantic::zkp_bridge::{
+    self as zkp_api, PrivacyLevel, ProvenanceProof, ProvenanceProofRequest, ProvenanceRecordWitness,
+    ZkpProofRequest as ZkpBridgeRequest,
+};
+use verisim_semantic::circuit_compiler::{compile_circuit, CircuitDef};
+use verisim_semantic::circuit_registry::{CircuitError, CircuitInfo, CircuitRegistry, CircuitVersion};
+use verisim_semantic::proof_cache::{ProofCache, ProofCacheConfig, ProofCacheStats};
+use verisim_semantic::disclosure::{self, AnnotationPredicate, DisclosureProof};
+use verisim_semantic::revocation::{ProofRegistry, ProofStatus, Revocation};
 use verisim_temporal::InMemoryVersionStore;
 use verisim_tensor::InMemoryTensorStore;
 use verisim_vector::{DistanceMetric, BruteForceVectorStore};
@@ -94,11 +136,26 @@ pub enum ApiError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Query timed out: {0}")]
+    Timeout(String),
+
+    #[error("Query cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Conflict: {message}")]
+    Conflict { message: String, ids: Vec<String> },
 }
 
 impl IntoResponse for ApiError {
@@ -106,6 +163,7 @@ fn into_response(self) -> axum::response::Response {
         let (status, client_message) = match &self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
             ApiError::Internal(msg) => {
                 error!(error = %msg, "Internal server error");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
@@ -114,22 +172,56 @@ fn into_response(self) -> axum::response::Response {
                 error!(error = %msg, "Serialization error");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
             }
+            ApiError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            ApiError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg.clone()),
+            ApiError::Cancelled(msg) => (StatusCode::CONFLICT, msg.clone()),
+            ApiError::Conflict { message, .. } => (StatusCode::CONFLICT, message.clone()),
+        };
+        let conflicting_ids = match self {
+            ApiError::Conflict { ids, .. } => ids,
+            _ => Vec::new(),
         };
 
         let body = Json(ErrorResponse {
             error: client_message,
             code: status.as_u16(),
+            conflicting_ids,
         });
 
         (status, body).into_response()
     }
 }
 
+impl From<transaction::TransactionError> for ApiError {
+    fn from(e: transaction::TransactionError) -> Self {
+        match e {
+            transaction::TransactionError::NotFound(_) => ApiError::NotFound(e.to_string()),
+            transaction::TransactionError::Wal(_) | transaction::TransactionError::Coordinator(_) => {
+                ApiError::Internal(e.to_string())
+            }
+            transaction::TransactionError::Conflict(ref ids) => ApiError::Conflict {
+                message: e.to_string(),
+                ids: ids.clone(),
+            },
+            transaction::TransactionError::Locked { ref entity_id, .. }
+            | transaction::TransactionError::Deadlock { ref entity_id, .. }
+            | transaction::TransactionError::LockTimeout { ref entity_id, .. } => ApiError::Conflict {
+                message: e.to_string(),
+                ids: vec![entity_id.clone()],
+            },
+            _ => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
 /// Error response body
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub code: u16,
+    /// Entities a conflicting transaction commit touched
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicting_ids: Vec<String>,
 }
 
 /// API configuration
@@ -148,6 +240,174 @@ pub struct ApiConfig {
     /// Persistence directory for the `persistent` feature.
     /// Overrides `VERISIM_PERSISTENCE_DIR` env var when set.
     pub persistence_dir: Option<String>,
+    /// Seconds between background drift scans; `None` disables scheduled
+    /// scanning (`POST /drift/scan` still works)
+    pub drift_scan_interval_secs: Option<u64>,
+    /// Alert sinks for drift events; `None` disables alerting
+    pub drift_alerts: Option<AlertConfig>,
+    /// Trend forecasting for early drift warnings; `None` disables them
+    /// (`GET /drift/forecast` still works)
+    pub drift_forecast: Option<ForecastConfig>,
+    /// Write normalization repairs back to the hexad store; when false the
+    /// normalizer only reports what it would change
+    #[serde(default)]
+    pub normalizer_apply_repairs: bool,
+    /// Embedding service drifted vectors are regenerated with; `None` uses
+    /// the built-in hashed bag-of-words embedder
+    #[serde(default)]
+    pub embedding_service: Option<HttpEmbedderConfig>,
+    /// `DELETE /hexads/{id}` soft-deletes: the entity is hidden but can be
+    /// restored with `POST /hexads/{id}/restore`
+    #[serde(default)]
+    pub soft_delete: bool,
+    /// Seconds after which soft-deleted entities are hard-deleted; `None`
+    /// keeps them indefinitely
+    #[serde(default)]
+    pub soft_delete_purge_after_secs: Option<u64>,
+    /// Purge windows for individual collections, in seconds, overriding
+    /// `soft_delete_purge_after_secs` for their entities
+    #[serde(default)]
+    pub collection_soft_delete_purge_after_secs: std::collections::HashMap<String, u64>,
+    /// Seconds between sweeps that soft-delete hexads past their
+    /// `expires_at`; `None` disables expiry
+    #[serde(default = "default_expiry_sweep_interval_secs")]
+    pub expiry_sweep_interval_secs: Option<u64>,
+    /// Seconds between WAL checkpoints, which snapshot the store and
+    /// truncate the log; `None` only checkpoints on request.  Only used
+    /// with the `persistent` feature, which enables the WAL.
+    #[serde(default = "default_wal_checkpoint_interval_secs")]
+    pub wal_checkpoint_interval_secs: Option<u64>,
+    /// Checkpoint snapshots kept for point-in-time recovery, which can
+    /// reach back to the oldest of them
+    #[serde(default = "default_wal_checkpoint_retention")]
+    pub wal_checkpoint_retention: usize,
+    /// Milliseconds between WAL fsyncs; `None` fsyncs every entry.  Writes
+    /// since the last sync may be lost in a crash, except transaction
+    /// commit points and outcomes, which are always synced.
+    #[serde(default)]
+    pub wal_sync_interval_ms: Option<u64>,
+    /// Directory backups are written to and restored from; `None` disables
+    /// the `/admin/backups` endpoints
+    #[serde(default)]
+    pub backup_dir: Option<String>,
+    /// Backup in `backup_dir` to restore on startup, when the store is
+    /// still empty
+    #[serde(default)]
+    pub restore_backup: Option<String>,
+    /// S3-compatible bucket the persistence directory is kept in, so it
+    /// can live on an ephemeral disk; `None` keeps it only locally.  Only
+    /// used with the `persistent` feature.
+    #[serde(default)]
+    pub object_store: Option<object_store::ObjectStoreSettings>,
+    /// Seconds a read snapshot opened with `POST /snapshots` is kept after
+    /// it was last used
+    #[serde(default = "default_read_snapshot_ttl_secs")]
+    pub read_snapshot_ttl_secs: u64,
+    /// Milliseconds a query may run when neither `SET TIMEOUT` nor the
+    /// `x-query-timeout-ms` header gives a deadline; `None` lets it run
+    /// until it finishes or is killed
+    #[serde(default)]
+    pub default_query_timeout_ms: Option<u64>,
+    /// Seconds after beginning at which a transaction is rolled back,
+    /// unless it asks for another limit
+    #[serde(default = "default_transaction_timeout_secs")]
+    pub transaction_timeout_secs: u64,
+    /// Seconds a transaction may go unused before it is rolled back,
+    /// unless it asks for another limit
+    #[serde(default = "default_transaction_idle_timeout_secs")]
+    pub transaction_idle_timeout_secs: u64,
+    /// Longest timeout, absolute or idle, a transaction may ask for
+    #[serde(default = "default_transaction_max_timeout_secs")]
+    pub transaction_max_timeout_secs: u64,
+    /// API endpoint (with prefix) of a primary to replicate; makes this
+    /// instance a read-only replica
+    #[serde(default)]
+    pub replicate_from: Option<String>,
+    /// Milliseconds between a replica's polls of its primary
+    #[serde(default = "default_replication_poll_interval_ms")]
+    pub replication_poll_interval_ms: u64,
+    /// Which entities each replica of this instance is sent; none sends
+    /// every replica everything
+    #[serde(default)]
+    pub replication_rules: Vec<replication::ReplicationRule>,
+    /// Node ID for CRDT multi-master sync; enables the `/crdt` endpoints
+    #[serde(default)]
+    pub crdt_node_id: Option<String>,
+    /// API endpoints (with prefix) of peers to sync with in the background
+    #[serde(default)]
+    pub crdt_peers: Vec<String>,
+    /// Seconds between background syncs with each peer
+    #[serde(default = "default_crdt_sync_interval_secs")]
+    pub crdt_sync_interval_secs: u64,
+    /// How concurrent writes met in CRDT sync are settled, by entity class
+    #[serde(default)]
+    pub crdt_conflict_policies: crdt::ConflictPolicies,
+    /// Credentials, scopes and certificate pins of federation peers
+    #[serde(default)]
+    pub peer_auth: Option<peer_auth::PeerAuthConfig>,
+    /// Client authentication: API keys, the JWT secret and the OIDC
+    /// provider; disabled by default
+    #[serde(default)]
+    pub auth: auth::AuthConfig,
+    /// Which entities each authenticated client may read and write; no
+    /// rules restricts nothing
+    #[serde(default)]
+    pub entity_policy: verisim_hexad::EntityPolicy,
+    /// Modalities and fields each role may not see in entity responses;
+    /// no rules redacts nothing
+    #[serde(default)]
+    pub redaction: redaction::RedactionPolicy,
+    /// Seconds recorded authorization decisions are kept for
+    #[serde(default = "default_authz_audit_retention_secs")]
+    pub authz_audit_retention_secs: u64,
+    /// Port to serve the gRPC API on, on the same host; `None` does not
+    /// serve it.  gRPC requests are not authenticated and travel in plain
+    /// text, so the port must not be reachable by untrusted clients.
+    #[serde(default)]
+    pub grpc_port: Option<u16>,
+    /// Reuse of generated proofs for identical claims
+    #[serde(default)]
+    pub proof_cache: ProofCacheConfig,
+}
+
+fn default_wal_checkpoint_interval_secs() -> Option<u64> {
+    Some(300)
+}
+
+fn default_expiry_sweep_interval_secs() -> Option<u64> {
+    Some(60)
+}
+
+fn default_wal_checkpoint_retention() -> usize {
+    12
+}
+
+fn default_read_snapshot_ttl_secs() -> u64 {
+    300
+}
+
+fn default_transaction_timeout_secs() -> u64 {
+    300
+}
+
+fn default_transaction_idle_timeout_secs() -> u64 {
+    60
+}
+
+fn default_transaction_max_timeout_secs() -> u64 {
+    86_400
+}
+
+fn default_replication_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_crdt_sync_interval_secs() -> u64 {
+    30
+}
+
+fn default_authz_audit_retention_secs() -> u64 {
+    90 * 86_400
 }
 
 impl Default for ApiConfig {
@@ -159,6 +419,50 @@ fn default() -> Self {
             version_prefix: "/api/v1".to_string(),
             vector_dimension: 384,
             persistence_dir: None,
+            drift_scan_interval_secs: None,
+            drift_alerts: None,
+            drift_forecast: None,
+            normalizer_apply_repairs: false,
+            embedding_service: None,
+            soft_delete: false,
+            soft_delete_purge_after_secs: None,
+            collection_soft_delete_purge_after_secs: std::collections::HashMap::new(),
+            expiry_sweep_interval_secs: default_expiry_sweep_interval_secs(),
+            wal_checkpoint_interval_secs: default_wal_checkpoint_interval_secs(),
+            wal_checkpoint_retention: default_wal_checkpoint_retention(),
+            wal_sync_interval_ms: None,
+            backup_dir: None,
+            restore_backup: None,
+            object_store: None,
+            read_snapshot_ttl_secs: default_read_snapshot_ttl_secs(),
+            default_query_timeout_ms: None,
+            transaction_timeout_secs: default_transaction_timeout_secs(),
+            transaction_idle_timeout_secs: default_transaction_idle_timeout_secs(),
+            transaction_max_timeout_secs: default_transaction_max_timeout_secs(),
+            replicate_from: None,
+            replication_poll_interval_ms: default_replication_poll_interval_ms(),
+            replication_rules: Vec::new(),
+            crdt_node_id: None,
+            crdt_peers: Vec::new(),
+            crdt_sync_interval_secs: default_crdt_sync_interval_secs(),
+            crdt_conflict_policies: crdt::ConflictPolicies::default(),
+            peer_auth: None,
+            auth: auth::AuthConfig::default(),
+            entity_policy: verisim_hexad::EntityPolicy::default(),
+            redaction: redaction::RedactionPolicy::default(),
+            authz_audit_retention_secs: default_authz_audit_retention_secs(),
+            grpc_port: None,
+            proof_cache: ProofCacheConfig::default(),
+        }
+    }
+}
+
+impl ApiConfig {
+    /// How often the WALs fsync
+    pub fn wal_sync_mode(&self) -> verisim_hexad::SyncMode {
+        match self.wal_sync_interval_ms {
+            Some(ms) => verisim_hexad::SyncMode::Periodic(std::time::Duration::from_millis(ms)),
+            None => verisim_hexad::SyncMode::Fsync,
         }
     }
 }
@@ -171,7 +475,8 @@ fn validate_limit(limit: usize) -> usize {
     limit.min(MAX_RESULT_LIMIT)
 }
 
-/// Validate a hexad ID: max 128 chars, alphanumeric + dash + underscore only.
+/// Validate a hexad ID: max 128 chars, alphanumeric + dash + underscore
+/// only, optionally prefixed by a collection name and `:`.
 fn validate_hexad_id(id: &str) -> Result<(), ApiError> {
     if id.is_empty() {
         return Err(ApiError::BadRequest("Hexad ID must not be empty".to_string()));
@@ -179,7 +484,11 @@ fn validate_hexad_id(id: &str) -> Result<(), ApiError> {
     if id.len() > 128 {
         return Err(ApiError::BadRequest("Hexad ID must be at most 128 characters".to_string()));
     }
-    if !id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+    let hexad_id = HexadId::new(id);
+    if let Some(collection) = hexad_id.collection() {
+        HexadId::validate_collection(collection).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    }
+    if !hexad_id.local_id().chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
         return Err(ApiError::BadRequest(
             "Hexad ID must contain only alphanumeric characters, dashes, and underscores".to_string(),
         ));
@@ -208,7 +517,7 @@ pub struct HealthResponse {
 }
 
 /// Hexad create/update request
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HexadRequest {
     /// Document title
     pub title: Option<String>,
@@ -216,6 +525,9 @@ pub struct HexadRequest {
     pub body: Option<String>,
     /// Vector embedding
     pub embedding: Option<Vec<f32>>,
+    /// Model that produced `embedding`, matched by VQL `SIMILAR TO ... USING`
+    #[serde(default)]
+    pub embedding_model: Option<String>,
     /// Semantic types
     pub types: Option<Vec<String>>,
     /// Relationships (predicate, target_id)
@@ -228,6 +540,17 @@ pub struct HexadRequest {
     pub spatial: Option<SpatialRequest>,
     /// Metadata
     pub metadata: Option<std::collections::HashMap<String, String>>,
+    /// Namespace (project or collection) the entity belongs to, stored as
+    /// the document's `namespace` field; needs a document
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Collection to create the entity in; its ID is prefixed with the
+    /// collection name.  Ignored on update.
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// When the entity expires and is soft-deleted by the sweeper
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Provenance event data in request
@@ -256,6 +579,16 @@ pub struct SpatialRequest {
     pub geometry_type: Option<String>,
     /// SRID (defaults to 4326)
     pub srid: Option<u32>,
+    /// Full geometry (LineString, Polygon, ...); its centroid becomes the
+    /// representative point
+    #[serde(default)]
+    pub geometry: Option<verisim_spatial::Geometry>,
+    /// Geometry as WKT or EWKT, e.g. from a PostGIS `ST_AsEWKT` export
+    #[serde(default)]
+    pub wkt: Option<String>,
+    /// Geometry as hex-encoded WKB or EWKB
+    #[serde(default)]
+    pub wkb: Option<String>,
     /// Spatial properties
     pub properties: Option<std::collections::HashMap<String, String>>,
 }
@@ -282,7 +615,7 @@ fn to_hexad_input(&self) -> HexadInput {
         if let Some(embedding) = &self.embedding {
             input.vector = Some(HexadVectorInput {
                 embedding: embedding.clone(),
-                model: None,
+                model: self.embedding_model.clone(),
             });
         }
 
@@ -312,6 +645,7 @@ fn to_hexad_input(&self) -> HexadInput {
                 actor: provenance.actor.clone(),
                 source: provenance.source.clone(),
                 description: provenance.description.clone(),
+                link: None,
             });
         }
 
@@ -322,6 +656,9 @@ fn to_hexad_input(&self) -> HexadInput {
                 altitude: spatial.altitude,
                 geometry_type: spatial.geometry_type.clone(),
                 srid: spatial.srid,
+                geometry: spatial.geometry.clone(),
+                wkt: spatial.wkt.clone(),
+                wkb: spatial.wkb.clone(),
                 properties: spatial.properties.clone().unwrap_or_default(),
             });
         }
@@ -330,10 +667,43 @@ fn to_hexad_input(&self) -> HexadInput {
             input.metadata = metadata.clone();
         }
 
+        if let (Some(namespace), Some(document)) = (&self.namespace, input.document.as_mut()) {
+            document.fields.insert("namespace".to_string(), namespace.clone());
+        }
+
         input
     }
 }
 
+/// Attribute the provenance event of a write to the authenticated actor.
+///
+/// When the request was authenticated, the canonical actor IRI replaces any
+/// caller-supplied `actor` (authentication is authoritative), and a default
+/// event is synthesised if the request did not carry one. Unauthenticated
+/// requests are left untouched.
+fn attribute_actor(
+    input: &mut HexadInput,
+    actor: Option<&ActorIdentity>,
+    default_event: &str,
+    default_description: &str,
+) {
+    let Some(actor) = actor else {
+        return;
+    };
+    match input.provenance.as_mut() {
+        Some(provenance) => provenance.actor = actor.iri.clone(),
+        None => {
+            input.provenance = Some(HexadProvenanceInput {
+                event_type: default_event.to_string(),
+                actor: actor.iri.clone(),
+                source: None,
+                description: default_description.to_string(),
+                link: None,
+            });
+        }
+    }
+}
+
 /// Tensor data in request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TensorRequest {
@@ -363,6 +733,8 @@ pub struct HexadStatusResponse {
     pub created_at: String,
     pub modified_at: String,
     pub version: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 impl From<&verisim_hexad::Hexad> for HexadResponse {
@@ -373,14 +745,16 @@ fn from(h: &verisim_hexad::Hexad) -> Self {
                 created_at: h.status.created_at.to_rfc3339(),
                 modified_at: h.status.modified_at.to_rfc3339(),
                 version: h.status.version,
+                expires_at: h.status.expires_at.map(|at| at.to_rfc3339()),
             },
-            has_graph: h.graph_node.is_some(),
-            has_vector: h.embedding.is_some(),
-            has_tensor: h.tensor.is_some(),
-            has_semantic: h.semantic.is_some(),
-            has_document: h.document.is_some(),
+            // From status, so responses built from masked reads agree
+            has_graph: h.status.modality_status.graph,
+            has_vector: h.status.modality_status.vector,
+            has_tensor: h.status.modality_status.tensor,
+            has_semantic: h.status.modality_status.semantic,
+            has_document: h.status.modality_status.document,
             has_provenance: h.provenance_chain_length > 0,
-            has_spatial: h.spatial_data.is_some(),
+            has_spatial: h.status.modality_status.spatial,
             version_count: h.version_count,
             provenance_chain_length: h.provenance_chain_length,
         }
@@ -394,6 +768,107 @@ pub struct ListQuery {
     pub limit: Option<usize>,
     /// Offset for pagination (default 0)
     pub offset: Option<usize>,
+    /// Only list entities in this collection
+    pub collection: Option<String>,
+    /// List as of this read snapshot, so every page sees the same store
+    pub snapshot: Option<String>,
+}
+
+/// Query parameters for `GET /hexads/count`
+#[derive(Debug, Deserialize)]
+pub struct CountQuery {
+    /// Only count entities in this collection
+    pub collection: Option<String>,
+    /// Comma-separated modalities an entity must have to be counted
+    pub modalities: Option<String>,
+    /// Return the incrementally maintained estimate instead of scanning
+    #[serde(default)]
+    pub estimate: bool,
+}
+
+/// Entity count
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CountResponse {
+    pub count: usize,
+    /// Whether the count is the maintained estimate rather than a scan
+    pub estimated: bool,
+}
+
+/// A read snapshot held open for the caller
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadSnapshotResponse {
+    /// Handle to pass as `snapshot` to list and search requests
+    pub id: String,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    /// Live entities in the snapshot
+    pub hexads: usize,
+    /// Seconds of disuse after which the snapshot is released
+    pub ttl_secs: u64,
+}
+
+/// A read snapshot and when it was last used
+struct HeldSnapshot {
+    snapshot: Arc<ReadSnapshot>,
+    last_used: std::time::Instant,
+}
+
+/// Read snapshots held open by clients, keyed by handle
+#[derive(Clone, Default)]
+pub struct ReadSnapshots {
+    held: Arc<Mutex<std::collections::HashMap<String, HeldSnapshot>>>,
+}
+
+impl ReadSnapshots {
+    /// Hold `snapshot` and return its handle, releasing those unused for
+    /// longer than `ttl`
+    fn hold(&self, snapshot: ReadSnapshot, ttl: std::time::Duration) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut held = self.held.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        held.retain(|_, h| h.last_used.elapsed() <= ttl);
+        held.insert(
+            id.clone(),
+            HeldSnapshot {
+                snapshot: Arc::new(snapshot),
+                last_used: std::time::Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// The snapshot with handle `id`, if it is held and has not lapsed
+    fn get(&self, id: &str, ttl: std::time::Duration) -> Result<Arc<ReadSnapshot>, ApiError> {
+        let mut held = self.held.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        match held.get_mut(id) {
+            Some(h) if h.last_used.elapsed() <= ttl => {
+                h.last_used = std::time::Instant::now();
+                Ok(h.snapshot.clone())
+            }
+            Some(_) => {
+                held.remove(id);
+                Err(ApiError::NotFound(format!("Snapshot {} has expired", id)))
+            }
+            None => Err(ApiError::NotFound(format!("Snapshot {} not found", id))),
+        }
+    }
+
+    /// Stop holding the snapshot with handle `id`
+    fn release(&self, id: &str) -> bool {
+        self.held
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(id)
+            .is_some()
+    }
+}
+
+/// Store-wide statistics for operators
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminStatsResponse {
+    pub version: String,
+    pub uptime_seconds: u64,
+    /// Estimated entity counts
+    pub hexads: EstimatedCounts,
+    pub expiry: ExpiryStats,
 }
 
 /// Search query parameters
@@ -403,6 +878,10 @@ pub struct SearchQuery {
     pub q: Option<String>,
     /// Number of results
     pub limit: Option<usize>,
+    /// Only search entities in this collection
+    pub collection: Option<String>,
+    /// Search the entities of this read snapshot, as they were then
+    pub snapshot: Option<String>,
 }
 
 /// Vector search request
@@ -412,6 +891,12 @@ pub struct VectorSearchRequest {
     pub vector: Vec<f32>,
     /// Number of results
     pub k: Option<usize>,
+    /// Only search entities in this collection
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// Search the entities of this read snapshot, as they were then
+    #[serde(default)]
+    pub snapshot: Option<String>,
 }
 
 /// Search result
@@ -426,6 +911,9 @@ pub struct SearchResultResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriftStatusResponse {
     pub drift_type: String,
+    /// Namespace the metrics are scoped to; absent for global metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     pub current_score: f64,
     pub moving_average: f64,
     pub max_score: f64,
@@ -436,6 +924,7 @@ impl DriftStatusResponse {
     fn from_metrics(drift_type: DriftType, metrics: &DriftMetrics) -> Self {
         Self {
             drift_type: drift_type.to_string(),
+            namespace: None,
             current_score: metrics.current_score,
             moving_average: metrics.moving_average,
             max_score: metrics.max_score,
@@ -451,17 +940,146 @@ pub struct AppState {
     pub hexad_store: Arc<ConcreteHexadStore>,
     pub drift_detector: Arc<DriftDetector>,
     pub normalizer: Arc<Normalizer>,
+    pub conflict_resolver: Arc<ConflictResolver>,
+    pub drift_scanner: Arc<DriftScanner>,
+    pub campaign_scheduler: Arc<CampaignScheduler>,
+    pub alert_dispatcher: Option<Arc<AlertDispatcher>>,
+    pub population_monitor: Arc<PopulationMonitor>,
     pub planner: Arc<Mutex<Planner>>,
     pub plan_cache: Arc<PlanCache>,
+    pub result_cache: Arc<ResultCache<executor::PlanExecution>>,
     pub slow_query_log: Arc<SlowQueryLog>,
+    /// Background writer of persisted slow-query entries
+    pub slow_query_writer: slow_queries::SlowQueryWriter,
     pub transaction_manager: Arc<transaction::TransactionManager>,
+    /// Two-phase commit coordinator for transactions spanning federation peers
+    pub coordinator: Arc<transaction::Coordinator>,
     pub circuit_registry: Arc<CircuitRegistry>,
+    /// Generated proofs, reused for identical claims
+    pub proof_cache: Arc<ProofCache>,
+    /// Expiry of issued proofs, and proofs revoked by entity changes
+    pub proof_registry: Arc<ProofRegistry>,
+    pub trajectories: Arc<verisim_spatial::InMemoryTrajectoryStore>,
+    pub read_snapshots: ReadSnapshots,
+    pub active_queries: queries::ActiveQueries,
+    pub subscriptions: subscriptions::Subscriptions,
+    /// Event streams of GraphQL subscriptions
+    pub graphql_events: graphql::GraphqlEvents,
+    /// Change feed, and the primary followed when this is a replica
+    pub replication: replication::Replication,
+    /// CRDT multi-master sync, when this node has a CRDT node ID
+    pub crdt: Option<Arc<crdt::CrdtSync>>,
+    /// Mirror of the persistence directory in an object store, if configured
+    pub object_store: Option<Arc<object_store::ObjectMirror>>,
     pub federation: federation::FederationState,
     pub auth: auth::AuthState,
+    /// Stream of authorization decisions, for audit
+    pub authz_audit: authz_audit::AuthzAudit,
     pub config: ApiConfig,
 }
 
 impl AppState {
+    /// Feed a newly written embedding to the population drift monitor.
+    pub(crate) fn observe_embedding(&self, embedding: Option<&verisim_hexad::Embedding>) {
+        if let Some(embedding) = embedding {
+            if let Err(e) = self.population_monitor.observe(&embedding.vector) {
+                warn!(error = %e, "Population drift monitor rejected embedding");
+            }
+        }
+    }
+
+    /// Current version of entity `id`
+    async fn entity_version(&self, id: &str) -> Result<u64, ApiError> {
+        self.hexad_store
+            .status(&HexadId::new(id))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .map(|status| status.version)
+            .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))
+    }
+
+    /// Attach a proof of `claim` to version `version` of entity `entity_id`,
+    /// storing its blob under the claim.
+    async fn attach_proof(
+        &self,
+        entity_id: &str,
+        version: u64,
+        claim: &str,
+        proof: &zkp_api::ZkpProof,
+    ) -> Result<EntityProof, ApiError> {
+        use verisim_hexad::SemanticStore;
+        let internal = |e: SemanticError| ApiError::Internal(e.to_string());
+        let semantic = self.hexad_store.semantic_store();
+        let blob = proof.to_blob(claim).map_err(internal)?;
+        let link = EntityProof::new(entity_id, version, &blob).map_err(internal)?;
+        // A proof served from the proof cache is a blob already stored
+        let stored = semantic.get_proofs(claim).await.map_err(internal)?;
+        if !stored.iter().any(|p| p.hash().is_ok_and(|hash| hash == link.proof_hash)) {
+            semantic.store_proof(&blob).await.map_err(internal)?;
+        }
+        semantic.link_proof(&link).await.map_err(internal)?;
+        Ok(link)
+    }
+
+    /// Mark the proofs attached to an entity stale once it has moved past
+    /// the version they were generated against, and record schema drift,
+    /// scored by the share of its proofs now stale, when any newly are.
+    pub(crate) async fn check_proof_staleness(&self, entity_id: &str, version: u64) -> Result<(), ApiError> {
+        use verisim_hexad::SemanticStore;
+        let internal = |e: SemanticError| ApiError::Internal(e.to_string());
+        let semantic = self.hexad_store.semantic_store();
+        let marked = semantic.mark_proofs_stale(entity_id, version).await.map_err(internal)?;
+        if marked.is_empty() {
+            return Ok(());
+        }
+        let proofs = semantic.entity_proofs(entity_id).await.map_err(internal)?;
+        let stale = proofs.iter().filter(|p| p.is_stale()).count();
+        let score = stale as f64 / proofs.len().max(1) as f64;
+        info!(id = %entity_id, stale = marked.len(), "Attached proofs went stale");
+        self.drift_detector
+            .record(DriftType::SchemaDrift, score, vec![entity_id.to_string()])
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Generate a proof through the proof cache, proving again rather than
+    /// reusing a cached proof that has expired or been revoked
+    fn generate_proof(&self, request: &ZkpBridgeRequest) -> Result<(zkp_api::ZkpProof, bool), CircuitError> {
+        self.proof_cache.generate_unless(request, &self.circuit_registry, |proof| {
+            let status = self.proof_registry.status(&proof.fingerprint(), proof.expiry(), chrono::Utc::now());
+            status != ProofStatus::Valid
+        })
+    }
+
+    /// Register a generated proof, expiring `ttl_secs` after it was
+    /// generated and revoked when one of the `depends_on` modalities of
+    /// `entity_id` changes.  Returns the proof and its ID.
+    fn issue_proof(
+        &self,
+        proof: zkp_api::ZkpProof,
+        ttl_secs: Option<u64>,
+        entity_id: Option<&str>,
+        depends_on: &[&str],
+    ) -> (zkp_api::ZkpProof, String) {
+        let proof = match ttl_secs {
+            Some(ttl_secs) => proof.with_ttl(ttl_secs),
+            None => proof,
+        };
+        let proof_id = proof.fingerprint();
+        self.proof_registry.issue(&proof_id, entity_id, depends_on, proof.expiry());
+        (proof, proof_id)
+    }
+
+    /// Revoke the proofs about entity `id` that depend on a `changed`
+    /// modality
+    pub(crate) fn revoke_changed_proofs(&self, id: &str, changed: &verisim_hexad::ModalityStatus) {
+        let revoked = self.proof_registry.revoke_for_change(id, &changed.populated());
+        if !revoked.is_empty() {
+            info!(id = %id, revoked = revoked.len(), "Entity change revoked proofs");
+        }
+    }
+
     /// Create new application state with default configuration (async version).
     ///
     /// With the `persistent` feature enabled, reads `VERISIM_PERSISTENCE_DIR`
@@ -494,6 +1112,25 @@ pub async fn new_async(config: ApiConfig) -> Result<Self, ApiError> {
             .or_else(|| std::env::var("VERISIM_PERSISTENCE_DIR").ok())
             .unwrap_or_else(|| "/var/lib/verisimdb".to_string());
 
+        // Bring back the persistence directory from the object store before
+        // anything opens it
+        #[cfg(feature = "persistent")]
+        let object_store = match &config.object_store {
+            Some(settings) => {
+                let mirror = object_store::ObjectMirror::connect(settings, &persist_dir)?;
+                mirror.hydrate().await?;
+                Some(Arc::new(mirror))
+            }
+            None => None,
+        };
+        #[cfg(not(feature = "persistent"))]
+        let object_store = {
+            if config.object_store.is_some() {
+                warn!("object_store is set but state is kept in memory; ignoring it");
+            }
+            None
+        };
+
         #[cfg(feature = "persistent")]
         let (graph, document) = {
             std::fs::create_dir_all(&persist_dir)
@@ -532,59 +1169,305 @@ pub async fn new_async(config: ApiConfig) -> Result<Self, ApiError> {
             temporal,
             provenance,
             spatial,
-        );
+        )
+        .with_policy(config.entity_policy.clone())
+        .with_private_collection(slow_queries::SLOW_QUERY_COLLECTION);
 
         // Enable WAL for crash recovery when persistent.
         #[cfg(feature = "persistent")]
         let hexad_store_inner = hexad_store_inner
-            .with_wal(
-                format!("{}/wal", persist_dir),
-                verisim_hexad::SyncMode::Fsync,
-            )
-            .map_err(|e| ApiError::Internal(format!("WAL init: {e}")))?;
+            .with_wal(format!("{}/wal", persist_dir), config.wal_sync_mode())
+            .map_err(|e| ApiError::Internal(format!("WAL init: {e}")))?
+            .with_checkpoint_retention(config.wal_checkpoint_retention);
 
         let hexad_store = Arc::new(hexad_store_inner);
 
-        let drift_detector = Arc::new(DriftDetector::new(DriftThresholds::default()));
-        let normalizer = Arc::new(create_default_normalizer(drift_detector.clone()).await);
+        // Drift history goes to the time-series store; with the persistent
+        // backend it survives restarts and is replayed into the detector.
+        #[cfg(not(feature = "persistent"))]
+        let drift_history: Arc<DriftHistoryStore> =
+            Arc::new(verisim_temporal::InMemoryTimeSeriesStore::new());
+        #[cfg(feature = "persistent")]
+        let drift_history: Arc<DriftHistoryStore> = Arc::new(
+            verisim_temporal::RedbTimeSeriesStore::persistent(format!("{}/drift-history.redb", persist_dir))
+                .map_err(|e| ApiError::Internal(format!("drift history: {e}")))?,
+        );
+
+        let mut drift_detector = DriftDetector::new(DriftThresholds::default()).with_history(drift_history);
+        if let Some(forecast) = &config.drift_forecast {
+            drift_detector = drift_detector.with_forecasting(forecast.clone());
+        }
+        #[cfg(feature = "persistent")]
+        {
+            let replayed = drift_detector
+                .restore_from_history(&verisim_temporal::TimeRange::last(chrono::Duration::days(7)))
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            info!(samples = replayed, "Restored drift metrics from history");
+        }
+        // Drift events go to GraphQL subscribers, then to the alert
+        // dispatcher when alerts are configured.
+        let graphql_events = graphql::GraphqlEvents::default();
+        let alert_dispatcher = config
+            .drift_alerts
+            .as_ref()
+            .map(|alerts| Arc::new(AlertDispatcher::from_config(alerts)));
+        let alerts = alert_dispatcher.as_ref().map(|dispatcher| {
+            let (tx, rx) = tokio::sync::mpsc::channel(1024);
+            dispatcher.clone().spawn(rx);
+            tx
+        });
+        let drift_detector = Arc::new(drift_detector.with_event_channel(graphql_events.drift_channel(alerts)));
+        let normalizer_config = NormalizerConfig {
+            apply_repairs: config.normalizer_apply_repairs,
+            ..Default::default()
+        };
+        let mut normalizer = create_default_normalizer(drift_detector.clone())
+            .await
+            .with_config(normalizer_config)
+            .with_store(hexad_store.clone())
+            .with_result_channel(graphql_events.normalization_channel());
+        if let Some(service) = config.embedding_service.clone() {
+            normalizer = normalizer.with_embedder(Arc::new(HttpEmbedder::new(service)));
+        }
+        #[cfg(feature = "persistent")]
+        {
+            normalizer = normalizer
+                .with_strategy_persistence(format!("{}/normalizer-strategies.json", persist_dir))
+                .map_err(|e| ApiError::Internal(format!("normalizer strategies: {e}")))?;
+        }
+        let normalizer = Arc::new(normalizer);
+        normalizer
+            .clone()
+            .spawn_workers()
+            .map_err(|e| ApiError::Internal(format!("normalization workers: {e}")))?;
+        #[cfg(feature = "persistent")]
+        let conflict_resolver = Arc::new(
+            ConflictResolver::persistent(format!("{}/conflict-policy.json", persist_dir))
+                .map_err(|e| ApiError::Internal(format!("conflict policy: {e}")))?,
+        );
+        #[cfg(not(feature = "persistent"))]
+        let conflict_resolver = Arc::new(ConflictResolver::with_defaults());
+        let scanner_config = ScannerConfig {
+            interval_secs: config
+                .drift_scan_interval_secs
+                .unwrap_or(ScannerConfig::default().interval_secs),
+            ..Default::default()
+        };
+        let population_monitor = Arc::new(PopulationMonitor::new(PopulationConfig::default()));
+        let drift_scanner = Arc::new(
+            DriftScanner::new(scanner_config, hexad_store.clone(), drift_detector.clone())
+                .with_graph(hexad_store.graph_store().clone())
+                .with_normalizer(normalizer.clone())
+                .with_population(population_monitor.clone()),
+        );
+        let campaign_scheduler = CampaignScheduler::new(hexad_store.clone(), drift_detector.clone(), normalizer.clone());
+        #[cfg(feature = "persistent")]
+        let campaign_scheduler = campaign_scheduler
+            .with_persistence(format!("{}/normalization-campaigns.json", persist_dir))
+            .map_err(|e| ApiError::Internal(format!("normalization campaigns: {e}")))?;
+        let campaign_scheduler = Arc::new(campaign_scheduler);
 
         let planner = Arc::new(Mutex::new(Planner::new(PlannerConfig::default())));
-        let plan_cache = Arc::new(PlanCache::new(CacheConfig::default()));
+        let cache_config = CacheConfig::default();
+        let plan_cache = Arc::new(PlanCache::new(cache_config.clone()));
+        let result_cache = Arc::new(ResultCache::new(&cache_config));
+        hexad_store.add_listener(Arc::new(executor::ResultCacheInvalidator(result_cache.clone())));
+        let subscriptions = subscriptions::Subscriptions::default();
+        hexad_store.add_listener(Arc::new(subscriptions::SubscriptionListener(subscriptions.clone())));
+        hexad_store.add_listener(Arc::new(graphql::GraphqlEventListener(graphql_events.clone())));
+        let peer_auth = match &config.peer_auth {
+            Some(peer_config) => peer_auth::PeerAuth::new(peer_config.clone())?,
+            None => peer_auth::PeerAuth::default(),
+        };
+        let replication = replication::Replication {
+            log: replication::ChangeLog::default(),
+            follower: config.replicate_from.as_ref().map(|primary| {
+                let follower_id = format!("{}:{}", config.host, config.port);
+                Arc::new(replication::Follower::new(primary.clone(), follower_id).with_peer_auth(peer_auth.clone()))
+            }),
+            rules: replication::ReplicationRules::new(config.replication_rules.clone())?,
+        };
+        hexad_store.add_listener(Arc::new(replication::ChangeLogListener(replication.log.clone())));
+        let crdt = match &config.crdt_node_id {
+            Some(node_id) => {
+                let crdt = crdt::CrdtSync::new(node_id.clone()).with_policies(config.crdt_conflict_policies.clone());
+                #[cfg(feature = "persistent")]
+                let crdt = crdt
+                    .with_persistence(format!("{}/crdt-state.json", persist_dir))
+                    .map_err(|e| ApiError::Internal(format!("CRDT sync state: {e}")))?;
+                let crdt = Arc::new(crdt);
+                hexad_store.add_listener(Arc::new(crdt::CrdtListener(crdt.clone())));
+                Some(crdt)
+            }
+            None => None,
+        };
         let slow_query_log = Arc::new(SlowQueryLog::new(Default::default()));
-        let transaction_manager = Arc::new(
-            transaction::TransactionManager::new(transaction::TransactionConfig::default()),
-        );
+        let slow_query_writer = slow_queries::SlowQueryWriter::spawn(hexad_store.clone());
+        let transaction_manager = transaction::TransactionManager::new(transaction::TransactionConfig {
+            timeout_seconds: config.transaction_timeout_secs,
+            idle_timeout_seconds: config.transaction_idle_timeout_secs,
+            max_timeout_seconds: config.transaction_max_timeout_secs,
+            ..Default::default()
+        });
+        #[cfg(feature = "persistent")]
+        let transaction_manager = transaction_manager
+            .with_wal(format!("{}/txn-wal", persist_dir), config.wal_sync_mode())
+            .map_err(|e| ApiError::Internal(format!("transaction WAL init: {e}")))?;
+        let transaction_manager = Arc::new(transaction_manager);
+        let coordinator = transaction::Coordinator::new().with_peer_auth(peer_auth.clone());
+        #[cfg(feature = "persistent")]
+        let coordinator = coordinator
+            .with_persistence(format!("{}/distributed-transactions.json", persist_dir))
+            .map_err(|e| ApiError::Internal(format!("transaction coordinator: {e}")))?;
+        let coordinator = Arc::new(coordinator);
 
         let self_endpoint = format!("http://{}:{}{}", config.host, config.port, config.version_prefix);
         let federation = federation::FederationState::new(
             "self".to_string(),
             self_endpoint,
-        );
+        )
+        .with_planner(planner.clone())
+        .with_peer_auth(peer_auth.clone());
 
-        let auth = auth::AuthState::default();
+        let auth = auth::AuthState {
+            peers: peer_auth,
+            ..auth::AuthState::new(config.auth.clone())
+        };
+        #[cfg(feature = "persistent")]
+        let auth = auth::AuthState {
+            rbac: rbac::RbacState::persistent(format!("{}/rbac-policy.json", persist_dir)).map_err(rbac_error)?,
+            actors: verisim_provenance::ActorRegistry::default()
+                .with_persistence(format!("{}/actors.json", persist_dir))
+                .map_err(|e| ApiError::Internal(format!("actor registry: {e}")))?,
+            ..auth
+        };
+        // Authorization decisions, from RBAC and the entity policy, go to a
+        // time-series store of their own.
+        #[cfg(not(feature = "persistent"))]
+        let authz_audit_store: Arc<authz_audit::AuthzAuditStore> =
+            Arc::new(verisim_temporal::InMemoryTimeSeriesStore::new());
+        #[cfg(feature = "persistent")]
+        let authz_audit_store: Arc<authz_audit::AuthzAuditStore> = Arc::new(
+            verisim_temporal::RedbTimeSeriesStore::persistent(format!("{}/authz-audit.redb", persist_dir))
+                .map_err(|e| ApiError::Internal(format!("authorization audit: {e}")))?,
+        );
+        let authz_audit_retention = i64::try_from(config.authz_audit_retention_secs)
+            .ok()
+            .and_then(chrono::Duration::try_seconds)
+            .unwrap_or(chrono::Duration::MAX);
+        let authz_audit = authz_audit::AuthzAudit::spawn(authz_audit_store, authz_audit_retention);
+        hexad_store.add_listener(Arc::new(authz_audit::AuthzAuditListener(authz_audit.clone())));
         let circuit_registry = Arc::new(CircuitRegistry::new());
+        let proof_cache = Arc::new(ProofCache::new(config.proof_cache.clone()));
+        let trajectories = Arc::new(verisim_spatial::InMemoryTrajectoryStore::new());
 
-        Ok(Self {
+        let state = Self {
             start_time: std::time::Instant::now(),
             hexad_store,
             drift_detector,
             normalizer,
+            conflict_resolver,
+            drift_scanner,
+            campaign_scheduler,
+            alert_dispatcher,
+            population_monitor,
             planner,
             plan_cache,
+            result_cache,
             slow_query_log,
+            slow_query_writer,
             transaction_manager,
+            coordinator,
             circuit_registry,
+            proof_cache,
+            proof_registry: Arc::new(ProofRegistry::new()),
+            trajectories,
+            read_snapshots: ReadSnapshots::default(),
+            active_queries: queries::ActiveQueries::default(),
+            subscriptions,
+            graphql_events,
+            replication,
+            crdt,
+            object_store,
             federation,
             auth,
+            authz_audit,
             config,
-        })
+        };
+
+        if let (Some(dir), Some(id)) = (&state.config.backup_dir, &state.config.restore_backup) {
+            verisim_hexad::security::system(restore_on_startup(&state, dir, id)).await?;
+        }
+        // Finish commits a crash interrupted (only a persistent WAL has any).
+        let replayed = verisim_hexad::security::system(vql::replay_transactions(&state)).await?;
+        if replayed > 0 {
+            info!(transactions = replayed, "Replayed interrupted transaction commits");
+        }
+        Ok(state)
+    }
+}
+
+/// Restore backup `id` into a store that is still empty, so a fresh
+/// instance comes up with it and a restarted one keeps what it has since
+async fn restore_on_startup(state: &AppState, dir: &str, id: &str) -> Result<(), ApiError> {
+    let existing = state
+        .hexad_store
+        .list(1, 0)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !existing.is_empty() {
+        info!(backup = %id, "Store already holds entities, not restoring the backup");
+        return Ok(());
+    }
+    let report = state.hexad_store.restore_backup(dir, id, "system").await.map_err(backup_error)?;
+    if !report.failed.is_empty() {
+        warn!(backup = %id, failed = report.failed.len(), "Some entities could not be restored");
+    }
+    Ok(())
+}
+
+/// Authentication state requests are admitted with, recording access
+/// decisions to the audit stream and confining clients to the namespaces of
+/// the entities they address
+pub(crate) fn request_auth(state: &AppState) -> auth::AuthState {
+    let mut auth_state = state.auth.clone();
+    auth_state.rbac.audit_log = auth_state.rbac.audit_log.with_stream(state.authz_audit.clone());
+    auth_state.rbac = auth_state
+        .rbac
+        .with_namespaces(Arc::new(EntityNamespaces(state.hexad_store.clone())));
+    auth_state
+}
+
+/// Durably replace `path` with `data`: written to a temporary file and
+/// synced, renamed into place, and the rename synced with the directory,
+/// so a crash leaves either the old contents or the new
+pub(crate) fn write_durably(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = std::path::PathBuf::from(tmp);
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
     }
+    std::fs::rename(&tmp, path)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    std::fs::File::open(dir)?.sync_all()
 }
 
 /// Build the API router
 pub fn build_router(state: AppState) -> Router {
     let federation_routes = federation::federation_router(state.federation.clone());
-    let auth_state = state.auth.clone();
+    let auth_state = request_auth(&state);
+    let replication = state.replication.clone();
+    let redactor = redaction::Redactor::new(state.config.redaction.clone(), auth_state.rbac.clone());
+    let graphql = graphql::graphql_router(state.clone(), auth_state.rbac.clone());
 
     Router::new()
         // Health endpoints
@@ -596,6 +1479,56 @@ pub fn build_router(state: AppState) -> Router {
         .route("/hexads/{id}", get(get_hexad_handler))
         .route("/hexads/{id}", put(update_hexad_handler))
         .route("/hexads/{id}", delete(delete_hexad_handler))
+        .route("/hexads/deleted", get(list_deleted_hexads_handler))
+        .route("/hexads/count", get(count_hexads_handler))
+        .route("/hexads/batch", post(create_hexads_batch_handler))
+        // Read snapshots (consistent lists and searches)
+        .route("/snapshots", post(read_snapshot_open_handler))
+        .route("/snapshots/{id}", delete(read_snapshot_release_handler))
+        .route("/hexads/{id}/restore", post(restore_hexad_handler))
+        .route("/hexads/{id}/proofs", get(hexad_proofs_handler))
+        .route("/hexads/{id}/annotation/prove", post(annotation_prove_handler))
+        // Administration
+        // Sessions
+        .route("/auth/token", post(auth_token_handler))
+        .route("/auth/refresh", post(auth_refresh_handler))
+        .route("/auth/revoke", post(auth_revoke_handler))
+        .route("/admin/stats", get(admin_stats_handler))
+        // Runtime roles and role bindings
+        .route("/admin/roles", get(roles_list_handler).post(role_create_handler))
+        .route("/admin/roles/{name}", get(role_get_handler).delete(role_delete_handler))
+        .route("/admin/roles/{name}/permissions", put(role_permissions_handler))
+        .route(
+            "/admin/role-bindings",
+            get(role_bindings_list_handler)
+                .post(role_binding_create_handler)
+                .delete(role_binding_delete_handler),
+        )
+        .route("/admin/audit/authz", get(authz_audit_handler))
+        .route(
+            "/admin/service-accounts",
+            get(service_accounts_list_handler).post(service_account_create_handler),
+        )
+        .route(
+            "/admin/service-accounts/{name}",
+            get(service_account_get_handler).delete(service_account_delete_handler),
+        )
+        .route("/admin/service-accounts/{name}/keys", post(service_account_issue_key_handler))
+        .route("/admin/service-accounts/{name}/keys/{key_id}", delete(service_account_revoke_key_handler))
+        .route("/admin/service-accounts/{name}/rotate", post(service_account_rotate_handler))
+        // Collections
+        .route("/collections", get(list_collections_handler))
+        .route("/collections/{name}", get(collection_stats_handler))
+        // Write-ahead log
+        .route("/wal/status", get(wal_status_handler))
+        .route("/wal/checkpoint", post(wal_checkpoint_handler))
+        .route("/wal/recover", post(wal_recover_handler))
+        // Object-store persistence
+        .route("/admin/object-store", get(object_store_status_handler))
+        .route("/admin/object-store/sync", post(object_store_sync_handler))
+        // Backups
+        .route("/admin/backups", get(list_backups_handler).post(create_backup_handler))
+        .route("/admin/backups/{id}/restore", post(restore_backup_handler))
         // Search endpoints
         .route("/search/text", get(text_search_handler))
         .route("/search/vector", post(vector_search_handler))
@@ -603,18 +1536,74 @@ pub fn build_router(state: AppState) -> Router {
         // Drift and normalization
         .route("/drift/status", get(drift_status_handler))
         .route("/drift/entity/{id}", get(entity_drift_handler))
+        .route("/drift/scan", get(drift_scan_status_handler).post(drift_scan_handler))
+        .route("/drift/alerts", get(drift_alerts_handler))
+        // Cross-modal consistency
+        .route("/consistency/verify", post(consistency_verify_handler))
+        .route("/consistency/{id}", get(consistency_entity_handler))
+        .route("/drift/plugins", get(drift_plugins_handler))
+        .route("/drift/history", get(drift_history_handler))
+        .route("/drift/forecast", get(drift_forecast_handler))
+        .route("/drift/correlations", get(drift_correlations_handler))
+        .route("/drift/suppressions", get(drift_suppressions_list_handler).post(drift_suppression_create_handler))
+        .route("/drift/suppressions/{id}", delete(drift_suppression_delete_handler))
+        .route("/drift/policy", get(drift_policy_get_handler).put(drift_policy_put_handler))
+        .route("/drift/quarantine", get(drift_quarantine_list_handler))
+        .route("/drift/quarantine/{id}", delete(drift_quarantine_release_handler))
+        .route("/drift/population", get(drift_population_handler))
+        .route("/drift/population/rebaseline", post(drift_population_rebaseline_handler))
+        .route("/drift/thresholds", get(drift_thresholds_get_handler).put(drift_thresholds_put_handler))
+        .route("/drift/thresholds/audit", get(drift_thresholds_audit_handler))
+        .route("/drift/thresholds/namespaces", get(drift_namespace_thresholds_list_handler))
+        .route(
+            "/drift/thresholds/namespaces/{namespace}",
+            delete(drift_namespace_thresholds_delete_handler),
+        )
         .route("/normalizer/status", get(normalizer_status_handler))
+        .route("/normalizer/report", get(normalizer_report_handler))
         .route("/normalizer/trigger/{id}", post(trigger_normalization_handler))
+        .route("/normalizer/preview/{id}", post(preview_normalization_handler))
+        .route(
+            "/normalizer/previews/{preview_id}",
+            get(get_normalization_preview_handler).delete(discard_normalization_preview_handler),
+        )
+        .route("/normalizer/previews/{preview_id}/approve", post(approve_normalization_preview_handler))
+        .route("/normalizer/results", get(normalization_results_handler))
+        .route("/normalizer/rollback/{result_id}", post(rollback_normalization_handler))
+        .route(
+            "/normalizer/strategies",
+            get(strategy_selection_get_handler).put(strategy_selection_put_handler),
+        )
+        .route(
+            "/normalizer/conflict-policy",
+            get(conflict_policy_get_handler).put(conflict_policy_put_handler),
+        )
+        .route("/normalizer/conflict-policy/audit", get(conflict_policy_audit_handler))
+        .route("/normalizer/campaigns", get(list_campaigns_handler).post(create_campaign_handler))
+        .route(
+            "/normalizer/campaigns/{id}",
+            get(get_campaign_handler).put(update_campaign_handler).delete(delete_campaign_handler),
+        )
+        .route("/normalizer/campaigns/{id}/run", post(run_campaign_handler))
+        .route("/normalizer/campaigns/{id}/runs", get(campaign_runs_handler))
         // Meta-query store (homoiconicity: queries as hexads)
         .route("/queries", post(store_query_handler))
         .route("/queries/similar", post(similar_queries_handler))
         .route("/queries/{id}/optimize", put(optimize_query_handler))
+        .route("/queries/active", get(active_queries_handler))
+        .route("/queries/active/{id}", delete(kill_query_handler))
+        // Continuous queries
+        .route("/subscriptions", get(list_subscriptions_handler).post(subscribe_handler))
+        .route("/subscriptions/{id}", get(get_subscription_handler).delete(unsubscribe_handler))
+        .route("/subscriptions/{id}/events", get(subscription_events_handler))
         // Query planner
         .route("/query/plan", post(query_plan_handler))
         .route("/query/explain", post(query_explain_handler))
+        .route("/query/execute", post(query_execute_handler))
         .route("/planner/config", get(get_planner_config_handler))
         .route("/planner/config", put(put_planner_config_handler))
         .route("/planner/stats", get(planner_stats_handler))
+        .route("/planner/analyze", post(planner_analyze_handler))
         // EXPLAIN ANALYZE
         .route("/query/explain-analyze", post(query_explain_analyze_handler))
         // Prepared statements
@@ -624,40 +1613,122 @@ pub fn build_router(state: AppState) -> Router {
         .route("/prepared/stats", get(prepared_stats_handler))
         // Slow query log
         .route("/planner/slow-queries", get(slow_queries_handler))
+        .route("/planner/slow-queries/entries", get(slow_query_entries_handler))
+        .route(
+            "/planner/slow-queries/config",
+            get(slow_query_config_get_handler).put(slow_query_config_put_handler),
+        )
         // Transaction endpoints
         .route("/transactions/begin", post(transaction_begin_handler))
+        .route(
+            "/transactions/distributed",
+            get(distributed_transaction_list_handler).post(distributed_transaction_handler),
+        )
+        .route("/transactions/distributed/{id}", get(distributed_transaction_get_handler))
+        .route("/transactions/{id}/prepare", post(transaction_prepare_handler))
         .route("/transactions/{id}/commit", post(transaction_commit_handler))
         .route("/transactions/{id}/rollback", post(transaction_rollback_handler))
         .route("/transactions/{id}", get(transaction_status_handler))
+        .route("/transactions/{id}/locks", post(transaction_lock_handler))
+        .route("/transactions/{id}/hexads", get(transaction_list_hexads_handler))
+        .route("/transactions/{id}/hexads/{hexad_id}", get(transaction_get_hexad_handler))
         // ZKP proof endpoints
         .route("/proofs/generate", post(proof_generate_handler))
         .route("/proofs/verify", post(proof_verify_handler))
         .route("/proofs/generate-with-circuit", post(proof_generate_with_circuit_handler))
+        .route("/proofs/cache", get(proof_cache_stats_handler))
+        .route("/proofs/revocations", get(proof_revocations_handler).post(proof_revoke_handler))
+        .route("/proofs/provenance/verify", post(provenance_proof_verify_handler))
+        .route("/proofs/disclosure/verify", post(disclosure_verify_handler))
+        // Circuit registry endpoints
+        .route("/circuits", get(list_circuits_handler).post(register_circuit_handler))
+        .route("/circuits/{name}", get(circuit_versions_handler))
+        .route("/circuits/{name}/{version}/deprecate", post(deprecate_circuit_handler))
         // Provenance endpoints
         .route("/provenance/{id}", get(provenance_get_chain_handler))
         .route("/provenance/{id}/record", post(provenance_record_handler))
         .route("/provenance/{id}/verify", get(provenance_verify_handler))
+        .route("/provenance/{id}/records", get(provenance_records_handler))
+        .route("/provenance/{id}/lineage", get(provenance_lineage_handler))
+        .route("/provenance/{id}/prove", post(provenance_prove_handler))
+        // Actor registry (principal → canonical actor IRI)
+        .route("/actors", get(actors_list_handler))
+        .route("/actors/{principal}", get(actor_get_handler))
+        .route("/actors/{principal}/activity", get(actor_activity_handler))
         // Spatial search endpoints
         .route("/spatial/search/radius", post(spatial_radius_search_handler))
         .route("/spatial/search/bounds", post(spatial_bounds_search_handler))
         .route("/spatial/search/nearest", post(spatial_nearest_handler))
+        .route("/spatial/search/polygon", post(spatial_polygon_search_handler))
+        .route("/spatial/join", post(spatial_join_handler))
+        // Trajectories (timestamped position series)
+        .route("/spatial/trajectories/search", post(trajectory_search_handler))
+        .route(
+            "/spatial/trajectories/{id}",
+            get(trajectory_get_handler).post(trajectory_record_handler),
+        )
+        .route("/spatial/trajectories/{id}/position", get(trajectory_position_handler))
+        .route("/spatial/clusters", post(spatial_clusters_handler))
+        // Geohash cells (density tiles)
+        .route("/spatial/cells", get(spatial_cell_counts_handler))
+        .route("/spatial/cells/{geohash}", get(spatial_cell_search_handler))
+        // GeoJSON interchange
+        .route("/spatial/import", post(spatial_import_handler))
+        .route("/spatial/export", get(spatial_export_handler))
         // VQL text query endpoint (used by verisim-repl)
         .route("/vql/execute", post(vql::vql_execute_handler))
+        // Replication (change feed, snapshot export, replica status)
+        .route("/replication/status", get(replication_status_handler))
+        .route("/replication/changes", get(replication_changes_handler))
+        .route("/replication/snapshot", get(replication_snapshot_handler))
+        .route("/replication/sync", post(replication_sync_handler))
+        // CRDT multi-master sync
+        .route("/crdt/status", get(crdt_status_handler))
+        .route("/crdt/state", get(crdt_state_handler))
+        .route("/crdt/merge", post(crdt_merge_handler))
+        .route("/crdt/sync", post(crdt_sync_handler))
+        .route("/crdt/conflicts", get(crdt_conflicts_handler))
+        .route("/crdt/conflicts/{id}/resolve", post(crdt_resolve_conflict_handler))
+        // Cluster health across federation, replication and CRDT peers
+        .route("/federation/status", get(federation_status_handler))
+        // GraphQL, its operations authorized one by one
+        .merge(graphql)
+        // Federated queries and searches
+        .merge(federation::federation_query_router(state.federation.clone()))
+        // Replicas are read-only
+        .layer(axum_middleware::from_fn_with_state(
+            replication.clone(),
+            replication::read_only_middleware,
+        ))
+        // Replicas serve reads within the requested staleness
+        .layer(axum_middleware::from_fn_with_state(
+            replication,
+            replication::read_routing_middleware,
+        ))
+        // Hide what the client's roles may not see
+        .layer(axum_middleware::from_fn_with_state(
+            redactor,
+            redaction::redaction_middleware,
+        ))
         // Authentication middleware layer
         .layer(axum_middleware::from_fn_with_state(
             auth_state,
             auth::auth_middleware,
         ))
-        .with_state(state.clone())
-        // GraphQL endpoint
-        .merge(graphql::graphql_router(state))
-        // Federation endpoints (separate state)
+        .with_state(state)
+        // Federation peer protocol (separate state)
         .merge(federation_routes)
 }
 
-/// Health check handler — verifies drift detector status and reports degraded when critical
+/// Health check handler
 #[instrument(skip(state))]
 async fn health_handler(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    (StatusCode::OK, Json(health(&state)))
+}
+
+/// Health of the server — verifies drift detector status and reports
+/// degraded when critical.  Also reported by the gRPC health service.
+pub(crate) fn health(state: &AppState) -> HealthResponse {
     let uptime = state.start_time.elapsed().as_secs();
     let version = env!("CARGO_PKG_VERSION").to_string();
 
@@ -684,29 +1755,23 @@ async fn health_handler(State(state): State<AppState>) -> (StatusCode, Json<Heal
                 HealthStatus::Healthy => ("healthy", None),
             };
 
-            (
-                StatusCode::OK,
-                Json(HealthResponse {
-                    status: status_str.to_string(),
-                    version,
-                    uptime_seconds: uptime,
-                    degraded_reason: reason,
-                }),
-            )
-        }
-        Err(_) => (
-            StatusCode::OK,
-            Json(HealthResponse {
-                status: "degraded".to_string(),
+            HealthResponse {
+                status: status_str.to_string(),
                 version,
                 uptime_seconds: uptime,
-                degraded_reason: Some("Drift detector unavailable".to_string()),
-            }),
-        ),
+                degraded_reason: reason,
+            }
+        }
+        Err(_) => HealthResponse {
+            status: "degraded".to_string(),
+            version,
+            uptime_seconds: uptime,
+            degraded_reason: Some("Drift detector unavailable".to_string()),
+        },
     }
 }
 
-/// Prometheus metrics handler — exposes drift and query metrics for scraping
+/// Prometheus metrics handler — exposes drift, normalizer and query metrics for scraping
 #[instrument(skip(state))]
 async fn metrics_handler(
     State(state): State<AppState>,
@@ -748,6 +1813,186 @@ async fn metrics_handler(
         }
     }
 
+    // Per-namespace drift
+    let namespace_gauge = GaugeVec::new(
+        Opts::new("verisimdb_drift_namespace_score", "Current drift score by namespace and type"),
+        &["namespace", "drift_type"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let namespace_avg_gauge = GaugeVec::new(
+        Opts::new("verisimdb_drift_namespace_moving_average", "Drift moving average by namespace and type"),
+        &["namespace", "drift_type"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(namespace_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(namespace_avg_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    if let Ok(by_namespace) = state.drift_detector.namespace_metrics() {
+        for (namespace, metrics) in &by_namespace {
+            for (drift_type, m) in metrics {
+                let label = drift_type.to_string();
+                namespace_gauge.with_label_values(&[namespace, &label]).set(m.current_score);
+                namespace_avg_gauge.with_label_values(&[namespace, &label]).set(m.moving_average);
+            }
+        }
+    }
+
+    // Normalizer runs, latency and queue depth
+    let normalization_counter = prometheus::CounterVec::new(
+        Opts::new("verisimdb_normalizer_runs_total", "Normalization runs by strategy and outcome"),
+        &["strategy", "outcome"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let normalization_latency_gauge = GaugeVec::new(
+        Opts::new("verisimdb_normalizer_latency_ms", "Normalization run time by strategy (mean and max)"),
+        &["strategy", "stat"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let normalizer_queue_gauge = GaugeVec::new(
+        Opts::new("verisimdb_normalizer_queue", "Normalizations queued and running"),
+        &["state"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let normalizer_success_rate = prometheus::Gauge::new(
+        "verisimdb_normalizer_success_rate",
+        "Fraction of normalization runs that succeeded",
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(normalization_counter.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(normalization_latency_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(normalizer_queue_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(normalizer_success_rate.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    for (strategy, m) in state.normalizer.strategy_metrics().await {
+        for (outcome, count) in [("success", m.successes), ("failure", m.failures), ("applied", m.applied)] {
+            normalization_counter.with_label_values(&[&strategy, outcome]).inc_by(count as f64);
+        }
+        normalization_latency_gauge.with_label_values(&[&strategy, "mean"]).set(m.mean_duration_ms());
+        normalization_latency_gauge.with_label_values(&[&strategy, "max"]).set(m.max_duration_ms as f64);
+    }
+    let normalizer_status = state.normalizer.status().await;
+    normalizer_queue_gauge.with_label_values(&["pending"]).set(normalizer_status.pending_count as f64);
+    normalizer_queue_gauge.with_label_values(&["active"]).set(normalizer_status.active_count as f64);
+    let runs = normalizer_status.completed_count + normalizer_status.failure_count;
+    if runs > 0 {
+        normalizer_success_rate.set(normalizer_status.completed_count as f64 / runs as f64);
+    }
+
+    // WAL lag since the last checkpoint
+    if let Some(lag) = state.hexad_store.wal_lag().await.map_err(|e| ApiError::Internal(e.to_string()))? {
+        let wal_gauge = GaugeVec::new(
+            Opts::new("verisimdb_wal", "WAL entries since the last checkpoint, segments and bytes on disk"),
+            &["stat"],
+        )
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        registry.register(Box::new(wal_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+        wal_gauge.with_label_values(&["entries_since_checkpoint"]).set(lag.entries_since_checkpoint as f64);
+        wal_gauge.with_label_values(&["segments"]).set(lag.segment_count as f64);
+        wal_gauge.with_label_values(&["bytes"]).set(lag.segment_bytes as f64);
+        if let Some(age) = lag.checkpoint_age_secs(chrono::Utc::now()) {
+            wal_gauge.with_label_values(&["checkpoint_age_seconds"]).set(age as f64);
+        }
+    }
+
+    // Transactions
+    let txn_stats = state.transaction_manager.stats().await;
+    let txn_gauge = GaugeVec::new(
+        Opts::new("verisimdb_transactions", "Transactions by state, and those expired since startup"),
+        &["state"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(txn_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    for (label, count) in [
+        ("active", txn_stats.active as f64),
+        ("prepared", txn_stats.prepared as f64),
+        ("committing", txn_stats.committing as f64),
+        ("committed", txn_stats.committed as f64),
+        ("rolled_back", txn_stats.rolled_back as f64),
+        ("expired", txn_stats.expired as f64),
+        ("expired_total", txn_stats.expired_total as f64),
+    ] {
+        txn_gauge.with_label_values(&[label]).set(count);
+    }
+
+    // Hexad expiry
+    let expiry = state
+        .hexad_store
+        .expiry_stats(chrono::Utc::now())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let expiry_gauge = GaugeVec::new(
+        Opts::new("verisimdb_hexad_expiry", "Hexads with an expiry set, and those past it awaiting the sweeper"),
+        &["state"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let expired_counter =
+        prometheus::IntCounter::new("verisimdb_hexads_expired_total", "Hexads soft-deleted on expiry")
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(expiry_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(expired_counter.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    expiry_gauge.with_label_values(&["scheduled"]).set(expiry.scheduled as f64);
+    expiry_gauge.with_label_values(&["overdue"]).set(expiry.overdue as f64);
+    expired_counter.inc_by(expiry.expired_total);
+
+    // Replication
+    let replication_gauge = GaugeVec::new(
+        Opts::new(
+            "verisimdb_replication",
+            "Change feed head, and on a replica its position and lag behind the primary",
+        ),
+        &["stat"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let follower_lag_gauge = GaugeVec::new(
+        Opts::new("verisimdb_replication_follower_lag_entries", "Changes each replica of this instance has not applied"),
+        &["follower"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(replication_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(follower_lag_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    replication_gauge.with_label_values(&["head"]).set(state.replication.log.head() as f64);
+    if let Some(follower) = &state.replication.follower {
+        let replica = follower.status();
+        replication_gauge.with_label_values(&["position"]).set(replica.position as f64);
+        replication_gauge.with_label_values(&["lag_entries"]).set(replica.lag_entries as f64);
+        replication_gauge.with_label_values(&["lag_seconds"]).set(replica.lag_seconds);
+    }
+    for follower in state.replication.log.followers() {
+        follower_lag_gauge.with_label_values(&[&follower.follower_id]).set(follower.lag_entries as f64);
+    }
+
+    // Service account keys, so rotations are not missed
+    let service_key_gauge = GaugeVec::new(
+        Opts::new(
+            "verisimdb_service_account_keys",
+            "Service account keys by state; expiring keys expire within the warning window",
+        ),
+        &["account", "state"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(service_key_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let warning = i64::try_from(state.auth.config.key_expiry_warning_secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .unwrap_or(chrono::Duration::MAX);
+    for (account, counts) in state.auth.service_accounts.key_counts(chrono::Utc::now(), warning) {
+        service_key_gauge.with_label_values(&[&account, "active"]).set(counts.active as f64);
+        service_key_gauge.with_label_values(&[&account, "expiring"]).set(counts.expiring as f64);
+        service_key_gauge.with_label_values(&[&account, "expired"]).set(counts.expired as f64);
+        service_key_gauge.with_label_values(&[&account, "revoked"]).set(counts.revoked as f64);
+    }
+
+    // Records dropped because their background writer was behind
+    let dropped_gauge = GaugeVec::new(
+        Opts::new(
+            "verisimdb_background_writes_dropped",
+            "Records dropped because their background writer was behind",
+        ),
+        &["stream"],
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    registry.register(Box::new(dropped_gauge.clone())).map_err(|e| ApiError::Internal(e.to_string()))?;
+    dropped_gauge.with_label_values(&["slow_queries"]).set(state.slow_query_writer.dropped() as f64);
+    dropped_gauge.with_label_values(&["authz_audit"]).set(state.authz_audit.dropped() as f64);
+
     // Uptime gauge
     let uptime = prometheus::Gauge::new("verisimdb_uptime_seconds", "Server uptime in seconds")
         .map_err(|e| ApiError::Internal(e.to_string()))?;
@@ -773,8 +2018,8 @@ async fn metrics_handler(
 /// Readiness check handler — checks hexad store accessibility and drift detector health
 #[instrument(skip(state))]
 async fn ready_handler(State(state): State<AppState>) -> StatusCode {
-    // Check hexad store is accessible (try a list with limit 0)
-    if state.hexad_store.list(1, 0).await.is_err() {
+    // Check hexad store is accessible (try a status-only list of one)
+    if state.hexad_store.list_with(1, 0, ModalityMask::STATUS).await.is_err() {
         return StatusCode::SERVICE_UNAVAILABLE;
     }
 
@@ -788,43 +2033,135 @@ async fn ready_handler(State(state): State<AppState>) -> StatusCode {
 
 /// List hexads handler with pagination
 #[instrument(skip(state))]
-async fn list_hexads_handler(
+pub async fn list_hexads_handler(
     State(state): State<AppState>,
     Query(params): Query<ListQuery>,
 ) -> Result<Json<Vec<HexadResponse>>, ApiError> {
     let limit = validate_limit(params.limit.unwrap_or(100));
     let offset = params.offset.unwrap_or(0);
 
-    let hexads = state
-        .hexad_store
-        .list(limit, offset)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if let Some(id) = &params.snapshot {
+        let snapshot = held_snapshot(&state, id)?;
+        let hexads = state
+            .hexad_store
+            .list_at(&snapshot, params.collection.as_deref(), limit, offset)
+            .await
+            .map_err(collection_error)?;
+        return Ok(Json(hexads.iter().map(HexadResponse::from).collect()));
+    }
+
+    // Responses only carry status and counts, so skip the modality reads
+    let hexads = match &params.collection {
+        Some(collection) => {
+            state
+                .hexad_store
+                .list_in(collection, limit, offset, ModalityMask::SUMMARY)
+                .await
+        }
+        None => state.hexad_store.list_with(limit, offset, ModalityMask::SUMMARY).await,
+    }
+    .map_err(collection_error)?;
 
     let responses: Vec<HexadResponse> = hexads.iter().map(HexadResponse::from).collect();
     Ok(Json(responses))
 }
 
 /// Create hexad handler
-#[instrument(skip(state, request))]
-async fn create_hexad_handler(
+#[instrument(skip(state, actor, request))]
+pub async fn create_hexad_handler(
     State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
     Json(request): Json<HexadRequest>,
 ) -> Result<(StatusCode, Json<HexadResponse>), ApiError> {
-    let input = request.to_hexad_input();
+    let mut input = request.to_hexad_input();
+    attribute_actor(&mut input, actor.as_deref(), "created", "Created via API");
 
-    let hexad = state
-        .hexad_store
-        .create(input)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let created = match &request.collection {
+        Some(collection) => state.hexad_store.create_in(collection, input).await,
+        None => state.hexad_store.create(input).await,
+    };
+    let mut hexad = created.map_err(|e| match e {
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal(e.to_string()),
+    })?;
+    if request.expires_at.is_some() {
+        hexad.status = state
+            .hexad_store
+            .set_expiry(&hexad.id, request.expires_at)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+    }
+    state.observe_embedding(hexad.embedding.as_ref());
 
     Ok((StatusCode::CREATED, Json(HexadResponse::from(&hexad))))
 }
 
+/// Most hexads one batch may create
+const MAX_BATCH_SIZE: usize = MAX_RESULT_LIMIT;
+
+/// Hexads created by a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HexadBatchResponse {
+    /// IDs of the hexads created, in request order
+    pub ids: Vec<String>,
+}
+
+/// Create hexads atomically: all of them, or none if one fails
+#[instrument(skip(state, actor, requests), fields(count = requests.len()))]
+async fn create_hexads_batch_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(requests): Json<Vec<HexadRequest>>,
+) -> Result<(StatusCode, Json<HexadBatchResponse>), ApiError> {
+    let ids = create_hexads_batch(&state, requests, actor.as_deref()).await?;
+    Ok((StatusCode::CREATED, Json(HexadBatchResponse { ids })))
+}
+
+/// Create hexads in one transaction, so either all are created or none
+/// is; returns their IDs in request order
+pub async fn create_hexads_batch(
+    state: &AppState,
+    requests: Vec<HexadRequest>,
+    actor: Option<&ActorIdentity>,
+) -> Result<Vec<String>, ApiError> {
+    if requests.is_empty() || requests.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::BadRequest(format!(
+            "A batch must create between 1 and {MAX_BATCH_SIZE} hexads"
+        )));
+    }
+    state.replication.check_writable()?;
+    let txn_id = state.transaction_manager.begin().await?;
+    for request in &requests {
+        let buffered = match serde_json::to_vec(request) {
+            Ok(payload) => state
+                .transaction_manager
+                .buffer_operation(
+                    &txn_id,
+                    transaction::BufferedOperation {
+                        entity_id: String::new(),
+                        operation: transaction::OperationType::Create,
+                        payload,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    },
+                )
+                .await
+                .map_err(ApiError::from),
+            Err(e) => Err(ApiError::Serialization(e.to_string())),
+        };
+        if let Err(e) = buffered {
+            let _ = state.transaction_manager.rollback(&txn_id).await;
+            return Err(e);
+        }
+    }
+    state
+        .transaction_manager
+        .commit(&txn_id, |txn| vql::apply_transaction(state, txn, actor))
+        .await
+}
+
 /// Get hexad handler
 #[instrument(skip(state))]
-async fn get_hexad_handler(
+pub async fn get_hexad_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<HexadResponse>, ApiError> {
@@ -842,361 +2179,848 @@ async fn get_hexad_handler(
 }
 
 /// Update hexad handler
-#[instrument(skip(state, request))]
-async fn update_hexad_handler(
+#[instrument(skip(state, actor, request))]
+pub async fn update_hexad_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
     Json(request): Json<HexadRequest>,
 ) -> Result<Json<HexadResponse>, ApiError> {
     validate_hexad_id(&id)?;
     let hexad_id = HexadId::new(&id);
-    let input = request.to_hexad_input();
+    let mut input = request.to_hexad_input();
+    attribute_actor(&mut input, actor.as_deref(), "modified", "Modified via API");
+    let embedding_changed = input.vector.is_some();
+    // Every update is a new version
+    let changed = verisim_hexad::ModalityStatus {
+        temporal: true,
+        ..input.modalities()
+    };
+    let not_found = |e: verisim_hexad::HexadError| match e {
+        verisim_hexad::HexadError::NotFound(_) => ApiError::NotFound(format!("Hexad {} not found", id)),
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal(e.to_string()),
+    };
 
-    let hexad = state
-        .hexad_store
-        .update(&hexad_id, input)
-        .await
-        .map_err(|e| match e {
-            verisim_hexad::HexadError::NotFound(_) => {
-                ApiError::NotFound(format!("Hexad {} not found", id))
-            }
-            _ => ApiError::Internal(e.to_string()),
-        })?;
+    let mut hexad = state.hexad_store.update(&hexad_id, input).await.map_err(not_found)?;
+    if request.expires_at.is_some() {
+        hexad.status = state
+            .hexad_store
+            .set_expiry(&hexad_id, request.expires_at)
+            .await
+            .map_err(not_found)?;
+    }
+    if embedding_changed {
+        state.observe_embedding(hexad.embedding.as_ref());
+    }
+    state.revoke_changed_proofs(&id, &changed);
+    if let Err(e) = state.check_proof_staleness(&id, hexad.status.version).await {
+        warn!(id = %id, error = %e, "Failed to check attached proofs for staleness");
+    }
 
     Ok(Json(HexadResponse::from(&hexad)))
 }
 
-/// Delete hexad handler
+/// GET /hexads/{id}/proofs — proofs attached to an entity, oldest first;
+/// those generated before its latest change are marked stale
 #[instrument(skip(state))]
-async fn delete_hexad_handler(
+async fn hexad_proofs_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<EntityProof>>, ApiError> {
+    use verisim_hexad::SemanticStore;
+    validate_hexad_id(&id)?;
+    let version = state.entity_version(&id).await?;
+    state.check_proof_staleness(&id, version).await?;
+    state
+        .hexad_store
+        .semantic_store()
+        .entity_proofs(&id)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// Delete hexad handler.  In soft-delete mode the entity is only hidden,
+/// and its trajectory is kept until it is purged.
+#[instrument(skip(state, actor))]
+pub async fn delete_hexad_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
 ) -> Result<StatusCode, ApiError> {
     validate_hexad_id(&id)?;
     let hexad_id = HexadId::new(&id);
+    let not_found = |e: verisim_hexad::HexadError| match e {
+        verisim_hexad::HexadError::NotFound(_) => ApiError::NotFound(format!("Hexad {} not found", id)),
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal(e.to_string()),
+    };
 
+    if state.config.soft_delete {
+        let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+        state
+            .hexad_store
+            .soft_delete(&hexad_id, &actor)
+            .await
+            .map_err(not_found)?;
+        state.proof_registry.revoke_entity(&id, "entity deleted");
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    state.hexad_store.delete(&hexad_id).await.map_err(not_found)?;
+    state.proof_registry.revoke_entity(&id, "entity deleted");
+
+    state
+        .trajectories
+        .delete(&id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /hexads/deleted — soft-deleted hexads, most recently deleted first
+#[instrument(skip(state))]
+async fn list_deleted_hexads_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ListQuery>,
+) -> Result<Json<Vec<verisim_hexad::DeletedHexad>>, ApiError> {
+    let limit = validate_limit(params.limit.unwrap_or(100));
+    let offset = params.offset.unwrap_or(0);
     state
         .hexad_store
-        .delete(&hexad_id)
+        .deleted(limit, offset)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// POST /hexads/{id}/restore — bring back a soft-deleted hexad
+#[instrument(skip(state, actor))]
+async fn restore_hexad_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
+) -> Result<Json<HexadResponse>, ApiError> {
+    validate_hexad_id(&id)?;
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    let hexad = state
+        .hexad_store
+        .restore(&HexadId::new(&id), &actor)
         .await
         .map_err(|e| match e {
             verisim_hexad::HexadError::NotFound(_) => {
-                ApiError::NotFound(format!("Hexad {} not found", id))
+                ApiError::NotFound(format!("No soft-deleted hexad {}", id))
             }
             _ => ApiError::Internal(e.to_string()),
         })?;
+    Ok(Json(HexadResponse::from(&hexad)))
+}
 
-    Ok(StatusCode::NO_CONTENT)
+/// Map a collection-scoped store error: bad collection names are the
+/// client's fault
+fn collection_error(e: verisim_hexad::HexadError) -> ApiError {
+    match e {
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal(e.to_string()),
+    }
 }
 
-/// Text search handler
+/// GET /collections — entity counts for every collection
+#[instrument(skip(state))]
+async fn list_collections_handler(State(state): State<AppState>) -> Result<Json<Vec<CollectionStats>>, ApiError> {
+    let names = state
+        .hexad_store
+        .collections()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let mut stats = Vec::with_capacity(names.len());
+    for name in names {
+        stats.push(state.hexad_store.collection_stats(&name).await.map_err(collection_error)?);
+    }
+    Ok(Json(stats))
+}
+
+/// GET /collections/{name} — entity counts for one collection
 #[instrument(skip(state))]
-async fn text_search_handler(
+async fn collection_stats_handler(
     State(state): State<AppState>,
-    Query(query): Query<SearchQuery>,
-) -> Result<Json<Vec<SearchResultResponse>>, ApiError> {
-    let q = match query.q {
-        Some(q) if !q.is_empty() => q,
-        _ => return Err(ApiError::BadRequest("Query parameter 'q' must not be empty".to_string())),
-    };
-    let limit = validate_limit(query.limit.unwrap_or(10));
+    Path(name): Path<String>,
+) -> Result<Json<CollectionStats>, ApiError> {
+    let stats = state.hexad_store.collection_stats(&name).await.map_err(collection_error)?;
+    if stats.hexads == 0 && stats.deleted == 0 {
+        return Err(ApiError::NotFound(format!("Collection {} not found", name)));
+    }
+    Ok(Json(stats))
+}
 
-    let hexads = state
+/// The held read snapshot with handle `id`
+fn held_snapshot(state: &AppState, id: &str) -> Result<Arc<ReadSnapshot>, ApiError> {
+    state
+        .read_snapshots
+        .get(id, std::time::Duration::from_secs(state.config.read_snapshot_ttl_secs))
+}
+
+/// POST /snapshots — take a read snapshot that list and search requests
+/// can name to see the store as it is now
+#[instrument(skip(state))]
+async fn read_snapshot_open_handler(
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<ReadSnapshotResponse>), ApiError> {
+    let snapshot = state
         .hexad_store
-        .search_text(&q, limit)
+        .read_snapshot()
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let (taken_at, hexads) = (snapshot.taken_at(), snapshot.len());
+    let ttl_secs = state.config.read_snapshot_ttl_secs;
+    let id = state
+        .read_snapshots
+        .hold(snapshot, std::time::Duration::from_secs(ttl_secs));
+    Ok((StatusCode::CREATED, Json(ReadSnapshotResponse { id, taken_at, hexads, ttl_secs })))
+}
 
-    let results: Vec<SearchResultResponse> = hexads
-        .iter()
-        .enumerate()
-        .map(|(i, h)| SearchResultResponse {
-            id: h.id.to_string(),
-            score: 1.0 - (i as f32 * 0.1), // Approximate score based on ranking
-            title: h.document.as_ref().map(|d| d.title.clone()),
-        })
-        .collect();
+/// DELETE /snapshots/{id} — release a read snapshot
+#[instrument(skip(state))]
+async fn read_snapshot_release_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if !state.read_snapshots.release(&id) {
+        return Err(ApiError::NotFound(format!("Snapshot {} not found", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    Ok(Json(results))
+/// Parse a comma-separated list of modality names
+fn parse_modalities(list: &str) -> Result<verisim_hexad::ModalityStatus, ApiError> {
+    let mut modalities = verisim_hexad::ModalityStatus::default();
+    for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let flag = match name {
+            "graph" => &mut modalities.graph,
+            "vector" => &mut modalities.vector,
+            "tensor" => &mut modalities.tensor,
+            "semantic" => &mut modalities.semantic,
+            "document" => &mut modalities.document,
+            "temporal" => &mut modalities.temporal,
+            "provenance" => &mut modalities.provenance,
+            "spatial" => &mut modalities.spatial,
+            other => return Err(ApiError::BadRequest(format!("Unknown modality: {other}"))),
+        };
+        *flag = true;
+    }
+    Ok(modalities)
 }
 
-/// Vector search handler
-#[instrument(skip(state, request))]
-async fn vector_search_handler(
+/// GET /hexads/count — number of live entities, optionally in one
+/// collection and with given modalities
+#[instrument(skip(state))]
+async fn count_hexads_handler(
     State(state): State<AppState>,
-    Json(request): Json<VectorSearchRequest>,
-) -> Result<Json<Vec<SearchResultResponse>>, ApiError> {
-    let k = validate_limit(request.k.unwrap_or(10));
+    Query(params): Query<CountQuery>,
+) -> Result<Json<CountResponse>, ApiError> {
+    let modalities = params.modalities.as_deref().unwrap_or("");
+    let filter = CountFilter {
+        collection: params.collection.clone(),
+        modalities: parse_modalities(modalities)?,
+    };
+    if !params.estimate {
+        let count = state.hexad_store.count(&filter).await.map_err(collection_error)?;
+        return Ok(Json(CountResponse { count, estimated: false }));
+    }
 
-    if request.vector.len() != state.config.vector_dimension {
-        return Err(ApiError::BadRequest(format!(
-            "Vector dimension mismatch: expected {}, got {}",
-            state.config.vector_dimension,
-            request.vector.len()
-        )));
+    if !modalities.trim().is_empty() {
+        return Err(ApiError::BadRequest("Estimated counts cannot filter by modality".to_string()));
     }
-    validate_vector(&request.vector)?;
+    let counts = state
+        .hexad_store
+        .estimated_counts()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let count = match &filter.collection {
+        Some(collection) => {
+            HexadId::validate_collection(collection).map_err(collection_error)?;
+            counts.collections.get(collection).copied().unwrap_or(0)
+        }
+        None => counts.hexads,
+    };
+    Ok(Json(CountResponse { count, estimated: true }))
+}
 
+/// GET /admin/stats — estimated entity counts and expiry backlog
+#[instrument(skip(state))]
+async fn admin_stats_handler(State(state): State<AppState>) -> Result<Json<AdminStatsResponse>, ApiError> {
     let hexads = state
         .hexad_store
-        .search_similar(&request.vector, k)
+        .estimated_counts()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let expiry = state
+        .hexad_store
+        .expiry_stats(chrono::Utc::now())
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(AdminStatsResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_seconds: state.start_time.elapsed().as_secs(),
+        hexads,
+        expiry,
+    }))
+}
 
-    let results: Vec<SearchResultResponse> = hexads
+/// How often expired soft deletes are purged
+const SOFT_DELETE_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Hard-delete hexads soft-deleted longer ago than the purge window of
+/// their collection, or the global one, with their trajectories
+async fn purge_soft_deleted(state: &AppState) -> Result<Vec<HexadId>, ApiError> {
+    let now = chrono::Utc::now();
+    let cutoff = |window: u64| now - chrono::Duration::seconds(window as i64);
+    let global = state.config.soft_delete_purge_after_secs.map(cutoff);
+    let collections: std::collections::HashMap<&str, chrono::DateTime<chrono::Utc>> = state
+        .config
+        .collection_soft_delete_purge_after_secs
         .iter()
-        .enumerate()
-        .map(|(i, h)| SearchResultResponse {
-            id: h.id.to_string(),
-            score: 1.0 - (i as f32 * 0.1), // Approximate score based on ranking
-            title: h.document.as_ref().map(|d| d.title.clone()),
-        })
+        .map(|(collection, window)| (collection.as_str(), cutoff(*window)))
         .collect();
+    if global.is_none() && collections.is_empty() {
+        return Ok(Vec::new());
+    }
+    let purged = state
+        .hexad_store
+        .purge_deleted_by(|id| {
+            id.collection()
+                .and_then(|collection| collections.get(collection).copied())
+                .or(global)
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    for id in &purged {
+        state
+            .trajectories
+            .delete(id.as_str())
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+    }
+    Ok(purged)
+}
 
-    Ok(Json(results))
+/// Purge expired soft deletes in the background
+fn spawn_soft_delete_purge(state: AppState) {
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(SOFT_DELETE_PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = purge_soft_deleted(&state).await {
+                warn!(error = %e, "Soft-delete purge failed");
+            }
+        }
+    }));
 }
 
-/// Related entities search handler
+/// GET /wal/status — WAL size and lag since the last checkpoint
 #[instrument(skip(state))]
-async fn related_search_handler(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-    Query(query): Query<RelatedQuery>,
-) -> Result<Json<Vec<HexadResponse>>, ApiError> {
-    validate_hexad_id(&id)?;
-    let hexad_id = HexadId::new(&id);
-    let predicate = query.predicate.unwrap_or_else(|| "related".to_string());
+async fn wal_status_handler(State(state): State<AppState>) -> Result<Json<WalLag>, ApiError> {
+    state
+        .hexad_store
+        .wal_lag()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("WAL is not enabled".to_string()))
+}
 
-    let hexads = state
+/// POST /wal/checkpoint — snapshot the store and truncate the WAL now
+#[instrument(skip(state))]
+async fn wal_checkpoint_handler(State(state): State<AppState>) -> Result<Json<CheckpointReport>, ApiError> {
+    if state.hexad_store.wal_lag().await.map_err(|e| ApiError::Internal(e.to_string()))?.is_none() {
+        return Err(ApiError::NotFound("WAL is not enabled".to_string()));
+    }
+    let report = state
         .hexad_store
-        .query_related(&hexad_id, &predicate)
+        .checkpoint()
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(report))
+}
 
-    let responses: Vec<HexadResponse> = hexads.iter().map(HexadResponse::from).collect();
+/// Point-in-time recovery request
+#[derive(Debug, Deserialize)]
+pub struct WalRecoverRequest {
+    /// Recover to the state just before this time
+    pub target: chrono::DateTime<chrono::Utc>,
+    /// Only report what would change
+    #[serde(default)]
+    pub dry_run: bool,
+}
 
-    Ok(Json(responses))
+/// POST /wal/recover — bring the store back to its state at a point in time
+#[instrument(skip(state, actor))]
+async fn wal_recover_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(request): Json<WalRecoverRequest>,
+) -> Result<Json<RecoveryReport>, ApiError> {
+    if request.target > chrono::Utc::now() {
+        return Err(ApiError::BadRequest("Recovery target is in the future".to_string()));
+    }
+    if state.hexad_store.wal_lag().await.map_err(|e| ApiError::Internal(e.to_string()))?.is_none() {
+        return Err(ApiError::NotFound("WAL is not enabled".to_string()));
+    }
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    let report = state
+        .hexad_store
+        .recover_to(request.target, request.dry_run, &actor)
+        .await
+        .map_err(|e| match e {
+            verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+            _ => ApiError::Internal(e.to_string()),
+        })?;
+    Ok(Json(report))
 }
 
-/// Query parameters for related search
-#[derive(Debug, Deserialize)]
-pub struct RelatedQuery {
-    pub predicate: Option<String>,
+fn object_mirror(state: &AppState) -> Result<&Arc<object_store::ObjectMirror>, ApiError> {
+    state
+        .object_store
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Object store is not configured".to_string()))
 }
 
-/// Drift status handler
+/// GET /admin/object-store — the bucket state is mirrored to and the last sync
 #[instrument(skip(state))]
-async fn drift_status_handler(
+async fn object_store_status_handler(
     State(state): State<AppState>,
-) -> Result<Json<Vec<DriftStatusResponse>>, ApiError> {
-    let all_metrics = state.drift_detector.all_metrics()
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+) -> Result<Json<object_store::ObjectStoreStatus>, ApiError> {
+    Ok(Json(object_mirror(&state)?.status()))
+}
 
-    let responses: Vec<DriftStatusResponse> = all_metrics
-        .iter()
-        .map(|(drift_type, metrics)| DriftStatusResponse::from_metrics(*drift_type, metrics))
-        .collect();
+/// POST /admin/object-store/sync — upload what changed in the persistence
+/// directory now
+#[instrument(skip(state))]
+async fn object_store_sync_handler(
+    State(state): State<AppState>,
+) -> Result<Json<object_store::SyncReport>, ApiError> {
+    Ok(Json(object_store::sync_state(&state).await?))
+}
 
-    Ok(Json(responses))
+/// Directory backups are kept in
+fn backup_dir(state: &AppState) -> Result<&str, ApiError> {
+    state
+        .config
+        .backup_dir
+        .as_deref()
+        .ok_or_else(|| ApiError::NotFound("Backups are not configured".to_string()))
 }
 
-/// Entity drift response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct EntityDriftResponse {
-    pub entity_id: String,
-    pub score: f64,
-    pub drift_type: String,
-    pub status: String,
+fn backup_error(e: verisim_hexad::HexadError) -> ApiError {
+    match e {
+        verisim_hexad::HexadError::NotFound(msg) => ApiError::NotFound(msg),
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal(e.to_string()),
+    }
 }
 
-/// Entity drift handler — get drift info for a single entity
+/// GET /admin/backups — backups in the backup directory, oldest first
 #[instrument(skip(state))]
-async fn entity_drift_handler(
+async fn list_backups_handler(State(state): State<AppState>) -> Result<Json<Vec<BackupManifest>>, ApiError> {
+    let backups = verisim_hexad::backup::list_backups(backup_dir(&state)?).map_err(backup_error)?;
+    Ok(Json(backups))
+}
+
+/// Backup request
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateBackupRequest {
+    /// `full` (the default) or `incremental`
+    #[serde(default)]
+    pub kind: BackupKind,
+}
+
+/// POST /admin/backups — write a full or incremental backup
+#[instrument(skip(state))]
+async fn create_backup_handler(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<EntityDriftResponse>, ApiError> {
-    validate_hexad_id(&id)?;
-    let hexad_id = HexadId::new(&id);
+    Json(request): Json<CreateBackupRequest>,
+) -> Result<(StatusCode, Json<BackupManifest>), ApiError> {
+    let dir = backup_dir(&state)?;
+    if request.kind == BackupKind::Incremental
+        && state.hexad_store.wal_lag().await.map_err(|e| ApiError::Internal(e.to_string()))?.is_none()
+    {
+        return Err(ApiError::BadRequest("Incremental backups need the WAL enabled".to_string()));
+    }
+    let manifest = state.hexad_store.backup(dir, request.kind).await.map_err(backup_error)?;
+    Ok((StatusCode::CREATED, Json(manifest)))
+}
 
-    // Verify hexad exists
-    let _hexad = state
+/// POST /admin/backups/{id}/restore — rebuild a backup into this instance,
+/// which must be empty
+#[instrument(skip(state, actor))]
+async fn restore_backup_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
+) -> Result<Json<RestoreReport>, ApiError> {
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    let report = state
         .hexad_store
-        .get(&hexad_id)
+        .restore_backup(backup_dir(&state)?, &id, &actor)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))?;
+        .map_err(backup_error)?;
+    Ok(Json(report))
+}
 
-    // Get aggregate health from drift detector
-    let all_metrics = state.drift_detector.all_metrics()
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
-    let (worst_type, worst_score) = all_metrics
-        .iter()
-        .max_by(|a, b| a.1.current_score.partial_cmp(&b.1.current_score).unwrap_or(std::cmp::Ordering::Equal))
-        .map(|(dt, m)| (dt.to_string(), m.current_score))
-        .unwrap_or_else(|| ("none".to_string(), 0.0));
+/// Replication role and positions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplicationStatusResponse {
+    /// `primary`, or `replica` when following another instance
+    pub role: String,
+    /// Epoch of this instance's change feed
+    pub epoch: String,
+    /// Position of this instance's latest change
+    pub head: u64,
+    /// Replicas that have polled this instance
+    pub followers: Vec<replication::FollowerPosition>,
+    /// This replica's progress, when it is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replica: Option<replication::ReplicaStatus>,
+    /// Which entities replicas of this instance are sent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<replication::ReplicationRule>,
+}
 
-    let status = if worst_score >= 0.7 {
-        "critical"
-    } else if worst_score >= 0.3 {
-        "warning"
-    } else {
-        "healthy"
-    };
+/// GET /replication/status — this instance's role, feed and replica lag
+#[instrument(skip(state))]
+async fn replication_status_handler(State(state): State<AppState>) -> Json<ReplicationStatusResponse> {
+    let replica = state.replication.follower.as_ref().map(|f| f.status());
+    Json(ReplicationStatusResponse {
+        role: if replica.is_some() { "replica" } else { "primary" }.to_string(),
+        epoch: state.replication.log.epoch().to_string(),
+        head: state.replication.log.head(),
+        followers: state.replication.log.followers(),
+        replica,
+        rules: state.replication.rules.rules().to_vec(),
+    })
+}
 
-    Ok(Json(EntityDriftResponse {
-        entity_id: id,
-        score: worst_score,
-        drift_type: worst_type,
-        status: status.to_string(),
-    }))
+/// Change feed request
+#[derive(Debug, Deserialize)]
+pub struct ReplicationChangesQuery {
+    /// Position already applied; changes after it are returned
+    #[serde(default)]
+    pub from: u64,
+    pub limit: Option<usize>,
+    /// Epoch the position belongs to
+    pub epoch: Option<String>,
+    /// ID of the replica polling, to track its lag
+    pub follower: Option<String>,
 }
 
-/// Normalizer status handler
-#[instrument(skip(state))]
-async fn normalizer_status_handler(
-    State(state): State<AppState>,
-) -> Result<Json<NormalizerStatus>, ApiError> {
-    let status = state.normalizer.status().await;
-    Ok(Json(status))
+/// The replication rule filter for a request: a federation peer's is
+/// chosen by its peer ID, anyone else's by the follower ID it gives
+fn replication_filter<'a>(
+    state: &'a AppState,
+    identity: Option<&auth::ClientIdentity>,
+    follower: Option<&str>,
+) -> Option<&'a replication::ReplicationFilter> {
+    let peer = identity.and_then(|i| i.id.strip_prefix("peer:"));
+    state.replication.rules.for_follower(peer.or(follower))
 }
 
-/// Trigger normalization handler
-#[instrument(skip(state))]
-async fn trigger_normalization_handler(
+/// GET /replication/changes — changes after a position, for replicas
+#[instrument(skip(state, identity))]
+async fn replication_changes_handler(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<StatusCode, ApiError> {
-    validate_hexad_id(&id)?;
-    let hexad_id = HexadId::new(&id);
-
-    // Check if hexad exists
-    let _hexad = state
-        .hexad_store
-        .get(&hexad_id)
+    identity: Option<Extension<auth::ClientIdentity>>,
+    Query(query): Query<ReplicationChangesQuery>,
+) -> Result<Json<replication::ChangeBatch>, ApiError> {
+    let limit = query.limit.unwrap_or(replication::MAX_CHANGE_PAGE);
+    let filter = replication_filter(&state, identity.as_deref(), query.follower.as_deref());
+    replication::changes(&state, query.from, limit, query.epoch.as_deref(), query.follower.as_deref(), filter)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))?;
-
-    // In a full implementation, this would trigger actual normalization
-    // For now, we just verify the hexad exists and return accepted
-    info!(id = %id, "Normalization triggered for hexad");
+        .map(Json)
+}
 
-    Ok(StatusCode::ACCEPTED)
+/// Snapshot export request
+#[derive(Debug, Deserialize)]
+pub struct ReplicationSnapshotQuery {
+    /// Snapshot being paged through; omitted to take a new one
+    pub snapshot: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// ID of the replica bootstrapping, to choose its replication rule
+    pub follower: Option<String>,
 }
 
-// --- Query Planner Handlers ---
+/// GET /replication/snapshot — export every entity, for bootstrapping a replica
+#[instrument(skip(state, identity))]
+async fn replication_snapshot_handler(
+    State(state): State<AppState>,
+    identity: Option<Extension<auth::ClientIdentity>>,
+    Query(query): Query<ReplicationSnapshotQuery>,
+) -> Result<Json<replication::SnapshotPage>, ApiError> {
+    let (id, position) = match query.snapshot {
+        Some(id) => (id, None),
+        None => {
+            // The position is read first: changes made while the snapshot
+            // is taken are replayed, which is harmless
+            let position = state.replication.log.head();
+            let snapshot = state
+                .hexad_store
+                .read_snapshot()
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            let ttl = std::time::Duration::from_secs(state.config.read_snapshot_ttl_secs);
+            (state.read_snapshots.hold(snapshot, ttl), Some(position))
+        }
+    };
+    let snapshot = held_snapshot(&state, &id)?;
+    let limit = query.limit.unwrap_or(replication::MAX_SNAPSHOT_PAGE).clamp(1, replication::MAX_SNAPSHOT_PAGE);
+    let filter = replication_filter(&state, identity.as_deref(), query.follower.as_deref());
+    let (entities, scanned) = replication::snapshot_entities(&state, &snapshot, query.offset, limit, filter).await?;
+    let next = query.offset + scanned;
+    Ok(Json(replication::SnapshotPage {
+        epoch: state.replication.log.epoch().to_string(),
+        position,
+        snapshot: id,
+        next_offset: (next < snapshot.len() && scanned > 0).then_some(next),
+        entities,
+    }))
+}
 
-/// Query plan handler — optimize a logical plan into a physical plan
-#[instrument(skip(state, plan))]
-async fn query_plan_handler(
+/// POST /replication/sync — on a replica, poll the primary now
+#[instrument(skip(state))]
+async fn replication_sync_handler(
     State(state): State<AppState>,
-    Json(plan): Json<LogicalPlan>,
-) -> Result<Json<PhysicalPlan>, ApiError> {
-    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    let physical = planner
-        .optimize(&plan)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
-    Ok(Json(physical))
+) -> Result<Json<replication::ReplicaStatus>, ApiError> {
+    let follower = state
+        .replication
+        .follower
+        .clone()
+        .ok_or_else(|| ApiError::BadRequest("This instance is not a replica".to_string()))?;
+    follower.sync(&state).await?;
+    Ok(Json(follower.status()))
 }
 
-/// Query explain handler — generate EXPLAIN output for a logical plan
-#[instrument(skip(state, plan))]
-async fn query_explain_handler(
+/// GET /federation/status — every peer's reachability, lag, last sync,
+/// pending conflicts and version compatibility
+#[instrument(skip(state))]
+async fn federation_status_handler(
     State(state): State<AppState>,
-    Json(plan): Json<LogicalPlan>,
-) -> Result<Json<ExplainOutput>, ApiError> {
-    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    let explain = planner
-        .explain(&plan)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
-    Ok(Json(explain))
+) -> Result<Json<federation::FederationStatus>, ApiError> {
+    use federation::PeerRole;
+    let mut topology = federation::Topology::default();
+    {
+        let peers = state
+            .federation
+            .peers
+            .read()
+            .map_err(|_| ApiError::Internal("federation peers lock poisoned".to_string()))?;
+        for store in peers.values() {
+            let peer = topology.peer(Some(&store.store_id), Some(&store.endpoint), PeerRole::Federation);
+            peer.last_seen = store.last_seen.clone();
+        }
+    }
+    if let Some(follower) = &state.replication.follower {
+        let status = follower.status();
+        let peer = topology.peer(None, Some(&status.primary), PeerRole::Primary);
+        peer.lag_entries = Some(status.lag_entries);
+        peer.lag_seconds = Some(status.lag_seconds);
+        peer.last_sync = status.last_sync;
+        peer.error = status.last_error;
+    }
+    for position in state.replication.log.followers() {
+        let peer = topology.peer(Some(&position.follower_id), None, PeerRole::Replica);
+        peer.lag_entries = Some(position.lag_entries);
+        peer.last_sync = Some(position.last_seen);
+    }
+    if let Some(crdt) = &state.crdt {
+        for (endpoint, cursor) in crdt.status().peers {
+            let peer = topology.peer(cursor.node_id.as_deref(), Some(&endpoint), PeerRole::Crdt);
+            peer.last_sync = peer.last_sync.max(cursor.last_sync);
+        }
+        for conflict in crdt.conflicts() {
+            topology.peer(Some(&conflict.peer), None, PeerRole::Crdt).pending_conflicts += 1;
+        }
+    }
+    Ok(Json(topology.check(state.federation.self_store_id.clone(), &state.federation.peer_auth).await))
 }
 
-/// Get planner configuration
+fn crdt_sync_state(state: &AppState) -> Result<Arc<crdt::CrdtSync>, ApiError> {
+    state
+        .crdt
+        .clone()
+        .ok_or_else(|| ApiError::NotFound("CRDT sync is not enabled".to_string()))
+}
+
+/// GET /crdt/status — this node's sync sequence and peer cursors
 #[instrument(skip(state))]
-async fn get_planner_config_handler(
+async fn crdt_status_handler(State(state): State<AppState>) -> Result<Json<crdt::CrdtStatus>, ApiError> {
+    Ok(Json(crdt_sync_state(&state)?.status()))
+}
+
+/// Query parameters for GET /crdt/state
+#[derive(Debug, Deserialize)]
+pub struct CrdtStateQuery {
+    /// This node's sequence number already seen
+    #[serde(default)]
+    pub since: u64,
+}
+
+/// GET /crdt/state — entity states changed since a sequence number
+#[instrument(skip(state))]
+async fn crdt_state_handler(
     State(state): State<AppState>,
-) -> Result<Json<PlannerConfig>, ApiError> {
-    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    Ok(Json(planner.config().clone()))
+    Query(query): Query<CrdtStateQuery>,
+) -> Result<Json<crdt::StateBatch>, ApiError> {
+    let crdt = crdt_sync_state(&state)?;
+    Ok(Json(crdt.export(&state, query.since).await?))
 }
 
-/// Update planner configuration
-#[instrument(skip(state, config))]
-async fn put_planner_config_handler(
+/// POST /crdt/merge — merge a peer's entity states into this node
+#[instrument(skip(state, batch), fields(from = %batch.node_id, entities = batch.entities.len()))]
+async fn crdt_merge_handler(
     State(state): State<AppState>,
-    Json(config): Json<PlannerConfig>,
-) -> Result<Json<PlannerConfig>, ApiError> {
-    let mut planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    planner.set_config(config);
-    Ok(Json(planner.config().clone()))
+    Json(batch): Json<crdt::StateBatch>,
+) -> Result<Json<crdt::MergeReport>, ApiError> {
+    let crdt = crdt_sync_state(&state)?;
+    Ok(Json(crdt.merge(&state, batch).await?))
 }
 
-/// Planner statistics snapshot
+/// Request body for POST /crdt/sync
+#[derive(Debug, Deserialize)]
+pub struct CrdtSyncRequest {
+    /// API endpoint (with prefix) of the peer
+    pub peer: String,
+}
+
+/// POST /crdt/sync — exchange changes with a peer now
 #[instrument(skip(state))]
-async fn planner_stats_handler(
+async fn crdt_sync_handler(
     State(state): State<AppState>,
-) -> Result<Json<StatisticsCollector>, ApiError> {
-    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    Ok(Json(planner.stats().clone()))
+    Json(request): Json<CrdtSyncRequest>,
+) -> Result<Json<crdt::SyncReport>, ApiError> {
+    let crdt = crdt_sync_state(&state)?;
+    Ok(Json(crdt.sync_with(&state, &request.peer).await?))
 }
 
-// --- Meta-Query Store (Homoiconicity) ---
+/// GET /crdt/conflicts — conflicts queued for manual resolution
+#[instrument(skip(state))]
+async fn crdt_conflicts_handler(State(state): State<AppState>) -> Result<Json<Vec<crdt::PendingConflict>>, ApiError> {
+    Ok(Json(crdt_sync_state(&state)?.conflicts()))
+}
 
-/// Store query request body
-#[derive(Debug, Serialize, Deserialize)]
-pub struct StoreQueryRequest {
-    /// The VQL query text
-    pub query: String,
-    /// Optional embedding for the query
-    pub embedding: Option<Vec<f32>>,
-    /// Optional cost vector from the planner
-    pub cost_vector: Option<Vec<f64>>,
-    /// Optional proof obligations
-    pub proof_obligations: Option<Vec<String>>,
+/// Request body for POST /crdt/conflicts/{id}/resolve
+#[derive(Debug, Deserialize)]
+pub struct CrdtResolveRequest {
+    /// Side whose values are kept
+    pub keep: crdt::ConflictSide,
 }
 
-/// Store a VQL query as a hexad (homoiconicity)
-#[instrument(skip(state, request))]
-async fn store_query_handler(
+/// POST /crdt/conflicts/{id}/resolve — settle an entity's queued conflicts
+#[instrument(skip(state, actor))]
+async fn crdt_resolve_conflict_handler(
     State(state): State<AppState>,
-    Json(request): Json<StoreQueryRequest>,
-) -> Result<(StatusCode, Json<HexadResponse>), ApiError> {
-    use verisim_hexad::QueryHexadBuilder;
-
-    let mut builder = QueryHexadBuilder::new(&request.query);
+    actor: Option<Extension<ActorIdentity>>,
+    Path(id): Path<String>,
+    Json(request): Json<CrdtResolveRequest>,
+) -> Result<Json<crdt::PendingConflict>, ApiError> {
+    let crdt = crdt_sync_state(&state)?;
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    Ok(Json(crdt.resolve(&state, &id, request.keep, &actor).await?))
+}
 
-    if let Some(embedding) = request.embedding {
-        validate_vector(&embedding)?;
-        builder = builder.with_embedding(embedding);
-    }
+/// Soft-delete hexads past their expiry in the background every `interval`
+fn spawn_expiry_sweeper(state: AppState, interval: std::time::Duration) {
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = state.hexad_store.expire_due(chrono::Utc::now(), "system").await {
+                warn!(error = %e, "Expiry sweep failed");
+            }
+        }
+    }));
+}
 
-    if let Some(costs) = request.cost_vector {
-        builder = builder.with_cost_vector(costs);
-    }
+/// Roll back transactions past their timeouts in the background
+fn spawn_transaction_sweeper(state: AppState) {
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(TRANSACTION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let expired = state.transaction_manager.cleanup_expired().await;
+            if expired > 0 {
+                info!(expired, "Rolled back expired transactions");
+            }
+            let resolved = state.coordinator.resolve_in_doubt().await;
+            if resolved > 0 {
+                info!(resolved, "Resolved in-doubt distributed transactions");
+            }
+        }
+    }));
+}
 
-    if let Some(obligations) = request.proof_obligations {
-        builder = builder.with_proof_obligations(obligations);
-    }
+/// How often abandoned and in-doubt transactions are looked for
+const TRANSACTION_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
 
-    builder = builder.with_metadata("stored_at", chrono::Utc::now().to_rfc3339());
+/// Checkpoint the WAL in the background every `interval`
+#[cfg(feature = "persistent")]
+fn spawn_wal_checkpoints(state: AppState, interval: std::time::Duration) {
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(interval);
+        // The first tick fires immediately; there is nothing to compact yet
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = state.hexad_store.checkpoint().await {
+                warn!(error = %e, "WAL checkpoint failed");
+            }
+        }
+    }));
+}
 
-    let (_query_id, input) = builder.build();
+/// Text search handler
+#[instrument(skip(state))]
+pub async fn text_search_handler(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<SearchResultResponse>>, ApiError> {
+    let q = match query.q {
+        Some(q) if !q.is_empty() => q,
+        _ => return Err(ApiError::BadRequest("Query parameter 'q' must not be empty".to_string())),
+    };
+    let limit = validate_limit(query.limit.unwrap_or(10));
 
-    let hexad = state
-        .hexad_store
-        .create(input)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let hexads = match (&query.snapshot, &query.collection) {
+        (Some(id), collection) => {
+            let snapshot = held_snapshot(&state, id)?;
+            state
+                .hexad_store
+                .search_text_at(&snapshot, collection.as_deref(), &q, limit)
+                .await
+        }
+        (None, Some(collection)) => state.hexad_store.search_text_in(collection, &q, limit).await,
+        (None, None) => state.hexad_store.search_text(&q, limit).await,
+    }
+    .map_err(collection_error)?;
 
-    info!(hexad_id = %hexad.id, "Stored query as hexad");
+    let results: Vec<SearchResultResponse> = hexads
+        .iter()
+        .enumerate()
+        .map(|(i, h)| SearchResultResponse {
+            id: h.id.to_string(),
+            score: 1.0 - (i as f32 * 0.1), // Approximate score based on ranking
+            title: h.document.as_ref().map(|d| d.title.clone()),
+        })
+        .collect();
 
-    Ok((StatusCode::CREATED, Json(HexadResponse::from(&hexad))))
+    Ok(Json(results))
 }
 
-/// Find similar past queries by vector similarity
+/// Vector search handler
 #[instrument(skip(state, request))]
-async fn similar_queries_handler(
+pub async fn vector_search_handler(
     State(state): State<AppState>,
     Json(request): Json<VectorSearchRequest>,
 ) -> Result<Json<Vec<SearchResultResponse>>, ApiError> {
@@ -1211,26 +3035,30 @@ async fn similar_queries_handler(
     }
     validate_vector(&request.vector)?;
 
-    // Search for similar hexads (which includes query-hexads)
-    let hexads = state
-        .hexad_store
-        .search_similar(&request.vector, k)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let hexads = match (&request.snapshot, &request.collection) {
+        (Some(id), collection) => {
+            let snapshot = held_snapshot(&state, id)?;
+            state
+                .hexad_store
+                .search_similar_at(&snapshot, collection.as_deref(), &request.vector, k)
+                .await
+        }
+        (None, Some(collection)) => {
+            state
+                .hexad_store
+                .search_similar_in(collection, &request.vector, k)
+                .await
+        }
+        (None, None) => state.hexad_store.search_similar(&request.vector, k).await,
+    }
+    .map_err(collection_error)?;
 
-    // Filter to only query hexads (those with "vql_query" type in document fields)
     let results: Vec<SearchResultResponse> = hexads
         .iter()
-        .filter(|h| {
-            h.document
-                .as_ref()
-                .map(|d| d.title.starts_with("VQL Query:"))
-                .unwrap_or(false)
-        })
         .enumerate()
         .map(|(i, h)| SearchResultResponse {
             id: h.id.to_string(),
-            score: 1.0 - (i as f32 * 0.1),
+            score: 1.0 - (i as f32 * 0.1), // Approximate score based on ranking
             title: h.document.as_ref().map(|d| d.title.clone()),
         })
         .collect();
@@ -1238,976 +3066,11267 @@ async fn similar_queries_handler(
     Ok(Json(results))
 }
 
-/// Optimize a stored query — re-plan and update its tensor modality with new costs.
-/// This is reflection: the system modifying its own queries based on learned costs.
-/// Accepts an optional LogicalPlan body; if absent, uses existing tensor as baseline.
+/// Related entities search handler
 #[instrument(skip(state))]
-async fn optimize_query_handler(
+async fn related_search_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    body: Option<Json<LogicalPlan>>,
-) -> Result<Json<HexadResponse>, ApiError> {
+    Query(query): Query<RelatedQuery>,
+) -> Result<Json<Vec<HexadResponse>>, ApiError> {
     validate_hexad_id(&id)?;
     let hexad_id = HexadId::new(&id);
+    let predicate = query.predicate.unwrap_or_else(|| "related".to_string());
 
-    // Get the existing query hexad
-    let hexad = state
+    let hexads = state
         .hexad_store
-        .get(&hexad_id)
+        .query_related(&hexad_id, &predicate)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::NotFound(format!("Query hexad {} not found", id)))?;
-
-    // Compute cost vector from the planner
-    let cost_vector = if let Some(Json(logical_plan)) = body {
-        // If a logical plan was provided, run the planner on it
-        let planner = state.planner.lock().map_err(|_| {
-            ApiError::Internal("Planner lock poisoned".to_string())
-        })?;
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-        match planner.explain(&logical_plan) {
-            Ok(explain) => {
-                explain
-                    .steps
-                    .iter()
-                    .map(|s| s.estimated_cost_ms)
-                    .collect::<Vec<f64>>()
-            }
-            Err(_) => vec![1.0, 0.0, 0.0],
-        }
-    } else {
-        // No plan provided — use existing tensor data scaled by a learning factor,
-        // or default to [1.0] if no tensor exists
-        hexad
-            .tensor
-            .as_ref()
-            .map(|t| t.data.iter().map(|v| v * 0.95).collect())
-            .unwrap_or_else(|| vec![1.0])
-    };
+    let responses: Vec<HexadResponse> = hexads.iter().map(HexadResponse::from).collect();
 
-    // Update the hexad with new tensor data (cost vector)
-    let mut update_input = HexadInput::default();
-    update_input.tensor = Some(HexadTensorInput {
-        shape: vec![1, cost_vector.len()],
-        data: cost_vector,
-    });
-    update_input
-        .metadata
-        .insert("optimized_at".to_string(), chrono::Utc::now().to_rfc3339());
+    Ok(Json(responses))
+}
 
-    let updated = state
-        .hexad_store
-        .update(&hexad_id, update_input)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-    info!(hexad_id = %id, "Optimized query hexad with new cost vector");
-
-    Ok(Json(HexadResponse::from(&updated)))
+/// Query parameters for related search
+#[derive(Debug, Deserialize)]
+pub struct RelatedQuery {
+    pub predicate: Option<String>,
 }
 
-// --- EXPLAIN ANALYZE Handler ---
-
-/// EXPLAIN ANALYZE request — execute a plan and return actual timings
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ExplainAnalyzeRequest {
-    /// The logical plan to analyze
-    pub plan: LogicalPlan,
-    /// Simulated step execution times (milliseconds) for profiling.
-    /// In a real execution engine, these would be measured; here they
-    /// can be provided for testing/simulation purposes.
-    pub simulated_timings: Option<Vec<f64>>,
+/// Drift status query parameters
+#[derive(Debug, Deserialize)]
+pub struct DriftStatusQuery {
+    /// Only report this namespace
+    pub namespace: Option<String>,
 }
 
-/// EXPLAIN ANALYZE handler — produces plan estimates with simulated actual timings
-#[instrument(skip(state, request))]
-async fn query_explain_analyze_handler(
+/// Drift status handler: global metrics per type, followed by the
+/// breakdown for each measured namespace
+#[instrument(skip(state))]
+pub async fn drift_status_handler(
     State(state): State<AppState>,
-    Json(request): Json<ExplainAnalyzeRequest>,
-) -> Result<Json<ExplainAnalyzeOutput>, ApiError> {
-    let mut planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-    let explain = planner
-        .explain(&request.plan)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
-    let physical = planner
-        .optimize(&request.plan)
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
-
-    let plan_id = format!("analyze-{}", chrono::Utc::now().timestamp_millis());
-    let mut profiler = Profiler::new(&plan_id, &physical);
-
-    // Record simulated or default step timings
-    let now = chrono::Utc::now();
-    for (i, step) in physical.steps.iter().enumerate() {
-        let actual_ms = request.simulated_timings
-            .as_ref()
-            .and_then(|t| t.get(i).copied())
-            .unwrap_or(step.cost.time_ms * 1.1); // Default: 10% slower than estimate
-        profiler.record_step(i, actual_ms, step.cost.estimated_rows, now, now);
+    Query(query): Query<DriftStatusQuery>,
+) -> Result<Json<Vec<DriftStatusResponse>>, ApiError> {
+    let mut responses: Vec<DriftStatusResponse> = Vec::new();
+    if query.namespace.is_none() {
+        let all_metrics = state.drift_detector.all_metrics()
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        responses.extend(
+            all_metrics
+                .iter()
+                .map(|(drift_type, metrics)| DriftStatusResponse::from_metrics(*drift_type, metrics)),
+        );
     }
 
-    let profile = profiler.finish(planner.stats_mut());
-    let output = explain.with_profile(&profile);
+    let mut by_namespace: Vec<_> = state
+        .drift_detector
+        .namespace_metrics()
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .into_iter()
+        .filter(|(ns, _)| query.namespace.as_ref().is_none_or(|wanted| wanted == ns))
+        .collect();
+    by_namespace.sort_by(|a, b| a.0.cmp(&b.0));
+    for (namespace, metrics) in by_namespace {
+        responses.extend(metrics.iter().map(|(drift_type, m)| DriftStatusResponse {
+            namespace: Some(namespace.clone()),
+            ..DriftStatusResponse::from_metrics(*drift_type, m)
+        }));
+    }
 
-    Ok(Json(output))
+    Ok(Json(responses))
 }
 
-// --- Prepared Statements Handlers ---
-
-/// Request to create a prepared statement
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PreparedCreateRequest {
-    /// The VQL query text
-    pub query: String,
-    /// The logical plan for the query
-    pub plan: LogicalPlan,
+/// POST /drift/scan — scan the next batch of entities for drift now
+#[instrument(skip(state))]
+async fn drift_scan_handler(State(state): State<AppState>) -> Result<Json<ScanReport>, ApiError> {
+    let report = state
+        .drift_scanner
+        .scan()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(report))
 }
 
-/// Create a prepared statement
-#[instrument(skip(state, request))]
-async fn prepared_create_handler(
-    State(state): State<AppState>,
-    Json(request): Json<PreparedCreateRequest>,
-) -> Result<(StatusCode, Json<PreparedStatement>), ApiError> {
-    let id = state.plan_cache.prepare(&request.query, request.plan).await;
-
-    let stmt = state.plan_cache.get(&id).await
-        .ok_or_else(|| ApiError::Internal("Failed to retrieve prepared statement after creation".to_string()))?;
-
-    Ok((StatusCode::CREATED, Json(stmt)))
+/// POST /consistency/verify — check every entity's modalities against its
+/// status.  The share of entities failing the embedding, graph or
+/// document checks is recorded as schema drift, and the share failing the
+/// provenance check as provenance drift.
+#[instrument(skip(state))]
+async fn consistency_verify_handler(State(state): State<AppState>) -> Result<Json<ConsistencyReport>, ApiError> {
+    let report = state
+        .hexad_store
+        .verify_consistency()
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if report.entities_checked > 0 {
+        let structural: Vec<String> = report
+            .inconsistent
+            .iter()
+            .filter(|e| e.violations.iter().any(|v| v.check != ConsistencyCheck::Provenance))
+            .map(|e| e.id.to_string())
+            .collect();
+        let schema_score = structural.len() as f64 / report.entities_checked as f64;
+        let signals = [
+            (DriftType::SchemaDrift, schema_score, structural),
+            (
+                DriftType::ProvenanceDrift,
+                report.failure_rate(ConsistencyCheck::Provenance),
+                report.failing(ConsistencyCheck::Provenance),
+            ),
+        ];
+        for (drift_type, score, entities) in signals {
+            state
+                .drift_detector
+                .record(drift_type, score, entities)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+        }
+    }
+    Ok(Json(report))
 }
 
-/// Get a prepared statement by ID
+/// GET /consistency/{id} — consistency checks for one entity
 #[instrument(skip(state))]
-async fn prepared_get_handler(
+async fn consistency_entity_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<PreparedStatement>, ApiError> {
-    let prep_id = PreparedId::new(&id);
-    let stmt = state.plan_cache.get(&prep_id).await
-        .ok_or_else(|| ApiError::NotFound(format!("Prepared statement '{}' not found", id)))?;
-    Ok(Json(stmt))
+) -> Result<Json<EntityConsistency>, ApiError> {
+    validate_hexad_id(&id)?;
+    state
+        .hexad_store
+        .verify_entity(&HexadId::new(&id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))
 }
 
-/// Execute a prepared statement with parameters
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PreparedExecuteRequest {
-    /// Parameter bindings
-    pub params: std::collections::HashMap<String, ParamValue>,
+/// GET /drift/scan — report of the most recent drift scan
+#[instrument(skip(state))]
+async fn drift_scan_status_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ScanReport>, ApiError> {
+    state
+        .drift_scanner
+        .last_report()
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound("No drift scan has run yet".to_string()))
 }
 
-/// Execute a prepared statement
-#[instrument(skip(state, request))]
-async fn prepared_execute_handler(
+/// GET /drift/alerts — delivery counters for each configured alert sink
+#[instrument(skip(state))]
+async fn drift_alerts_handler(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-    Json(request): Json<PreparedExecuteRequest>,
-) -> Result<Json<PhysicalPlan>, ApiError> {
-    let prep_id = PreparedId::new(&id);
+) -> Result<Json<std::collections::HashMap<String, SinkStats>>, ApiError> {
+    let dispatcher = state
+        .alert_dispatcher
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Drift alerting is not configured".to_string()))?;
+    dispatcher
+        .stats()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
 
-    let stmt = state.plan_cache
-        .execute_prepared(&prep_id, &request.params)
-        .await
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+/// Drift history query parameters
+#[derive(Debug, Deserialize)]
+pub struct DriftHistoryQuery {
+    /// Drift type (e.g. `tensor` or `tensor_drift`); all types when omitted
+    #[serde(rename = "type")]
+    pub drift_type: Option<String>,
+    /// Lookback such as `30m`, `24h` or `7d`, or an RFC 3339 interval
+    /// `start/end`; defaults to `24h`
+    pub range: Option<String>,
+    /// Only return samples that raised a drift event
+    #[serde(default)]
+    pub events_only: bool,
+}
 
-    // Use cached physical plan if available, otherwise optimize
-    let physical = if let Some(cached) = stmt.cached_physical_plan {
-        cached
-    } else {
-        let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
-        planner.optimize(&stmt.logical_plan).map_err(|e| ApiError::Internal(e.to_string()))?
+/// Parse a history range: a lookback (`90s`, `30m`, `24h`, `7d`, `2w`) ending
+/// now, or an explicit `start/end` pair of RFC 3339 timestamps.
+fn parse_history_range(range: &str) -> Result<verisim_temporal::TimeRange, ApiError> {
+    let invalid = || {
+        ApiError::BadRequest(format!(
+            "Invalid range '{}': expected e.g. 24h, 7d or <rfc3339>/<rfc3339>",
+            range
+        ))
     };
+    if let Some((start, end)) = range.split_once('/') {
+        let start = chrono::DateTime::parse_from_rfc3339(start).map_err(|_| invalid())?;
+        let end = chrono::DateTime::parse_from_rfc3339(end).map_err(|_| invalid())?;
+        return verisim_temporal::TimeRange::new(start.with_timezone(&chrono::Utc), end.with_timezone(&chrono::Utc))
+            .map_err(|e| ApiError::BadRequest(e.to_string()));
+    }
 
-    // Cache the physical plan for future use
-    state.plan_cache.cache_plan(&prep_id, physical.clone()).await;
-
-    Ok(Json(physical))
+    let split = range.len().checked_sub(1).ok_or_else(invalid)?;
+    let (amount, unit) = range.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    let duration = match unit {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    // Extend past now so samples recorded during the request are included.
+    let now = chrono::Utc::now();
+    verisim_temporal::TimeRange::new(now - duration, now + chrono::Duration::seconds(1))
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
 }
 
-/// Get prepared statement cache statistics
+/// GET /drift/history?type=&range= — recorded drift measurements and events
 #[instrument(skip(state))]
-async fn prepared_stats_handler(
+async fn drift_history_handler(
     State(state): State<AppState>,
-) -> Result<Json<verisim_planner::CacheStats>, ApiError> {
-    let stats = state.plan_cache.stats_async().await;
-    Ok(Json(stats))
+    Query(query): Query<DriftHistoryQuery>,
+) -> Result<Json<Vec<DriftHistoryPoint>>, ApiError> {
+    let drift_types = match &query.drift_type {
+        Some(name) => vec![name
+            .parse::<DriftType>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?],
+        None => state
+            .drift_detector
+            .drift_types()
+            .map_err(|e| ApiError::Internal(e.to_string()))?,
+    };
+    let range = parse_history_range(query.range.as_deref().unwrap_or("24h"))?;
+
+    let mut points = Vec::new();
+    for drift_type in drift_types {
+        points.extend(
+            state
+                .drift_detector
+                .history(drift_type, &range)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?,
+        );
+    }
+    if query.events_only {
+        points.retain(|p| p.sample.event.is_some());
+    }
+    points.sort_by_key(|p| p.time);
+    Ok(Json(points))
 }
 
-/// Get slow query log summary
+/// Drift forecast query parameters
+#[derive(Debug, Deserialize)]
+pub struct DriftForecastQuery {
+    /// Drift type; all types when omitted
+    #[serde(rename = "type")]
+    pub drift_type: Option<String>,
+    /// `linear` or `ewma`; defaults to the configured method
+    pub method: Option<ForecastMethod>,
+    /// Look-ahead in seconds; defaults to the configured horizon
+    pub horizon: Option<u64>,
+}
+
+/// GET /drift/forecast?type=&method=&horizon= — predicted time to threshold
 #[instrument(skip(state))]
-async fn slow_queries_handler(
+async fn drift_forecast_handler(
     State(state): State<AppState>,
-) -> Result<Json<SlowQuerySummary>, ApiError> {
-    let summary = state.slow_query_log.summary();
-    Ok(Json(summary))
+    Query(query): Query<DriftForecastQuery>,
+) -> Result<Json<Vec<DriftForecast>>, ApiError> {
+    let drift_types = match &query.drift_type {
+        Some(name) => vec![name
+            .parse::<DriftType>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?],
+        None => state
+            .drift_detector
+            .drift_types()
+            .map_err(|e| ApiError::Internal(e.to_string()))?,
+    };
+    let mut config = state.drift_detector.forecasting().cloned().unwrap_or_default();
+    if let Some(method) = query.method {
+        config.method = method;
+    }
+    if let Some(horizon) = query.horizon {
+        config.horizon_secs = horizon;
+    }
+
+    let mut forecasts = Vec::new();
+    for drift_type in drift_types {
+        if let Some(forecast) = state
+            .drift_detector
+            .forecast(drift_type, &config)
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+        {
+            forecasts.push(forecast);
+        }
+    }
+    Ok(Json(forecasts))
 }
 
-// --- Transaction Handlers ---
+/// Drift correlation query parameters
+#[derive(Debug, Deserialize)]
+pub struct DriftCorrelationQuery {
+    /// History analysed, as for `/drift/history`; defaults to `7d`
+    pub range: Option<String>,
+    /// Drift type whose probable root causes are wanted
+    pub symptom: Option<String>,
+    /// Bucket width in seconds
+    pub bucket: Option<u64>,
+    /// Largest lead tried, in buckets
+    pub max_lag: Option<usize>,
+    /// Weakest coefficient reported
+    pub min_coefficient: Option<f64>,
+}
+
+/// Correlations between drift types
+#[derive(Debug, Serialize)]
+pub struct DriftCorrelationResponse {
+    /// Start of the analysed history
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// End of the analysed history
+    pub end: chrono::DateTime<chrono::Utc>,
+    pub bucket_secs: u64,
+    /// Every pair above the coefficient floor, strongest first
+    pub correlations: Vec<DriftCorrelation>,
+    /// With `symptom`: the types whose changes precede it, strongest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probable_causes: Option<Vec<DriftCorrelation>>,
+}
+
+/// Most buckets a correlation query may resample history onto
+const MAX_CORRELATION_BUCKETS: i64 = 100_000;
 
-/// Begin a new transaction
+/// Widest bucket a correlation query may ask for, in seconds: a year
+const MAX_CORRELATION_BUCKET_SECS: i64 = 366 * 86_400;
+
+/// Largest lead a correlation query may try, in buckets
+const MAX_CORRELATION_LAG: usize = 1_000;
+
+/// GET /drift/correlations?range=&symptom= — which drift types move together
+/// or precede one another
 #[instrument(skip(state))]
-async fn transaction_begin_handler(
+async fn drift_correlations_handler(
     State(state): State<AppState>,
-) -> Result<(StatusCode, Json<transaction::TransactionStatus>), ApiError> {
-    let txn_id = state.transaction_manager
-        .begin()
+    Query(query): Query<DriftCorrelationQuery>,
+) -> Result<Json<DriftCorrelationResponse>, ApiError> {
+    let range = parse_history_range(query.range.as_deref().unwrap_or("7d"))?;
+    let symptom = query
+        .symptom
+        .as_deref()
+        .map(|name| name.parse::<DriftType>())
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let mut config = CorrelationConfig::default();
+    if let Some(bucket) = query.bucket {
+        config.bucket_secs = bucket;
+    }
+    if let Some(max_lag) = query.max_lag {
+        config.max_lag_buckets = max_lag;
+    }
+    if let Some(min_coefficient) = query.min_coefficient {
+        config.min_coefficient = min_coefficient;
+    }
+    let bucket_secs = i64::try_from(config.bucket_secs)
+        .ok()
+        .filter(|secs| (1..=MAX_CORRELATION_BUCKET_SECS).contains(secs))
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!("bucket must be 1 to {} seconds", MAX_CORRELATION_BUCKET_SECS))
+        })?;
+    if range.end.signed_duration_since(range.start).num_seconds() / bucket_secs > MAX_CORRELATION_BUCKETS {
+        return Err(ApiError::BadRequest(format!(
+            "bucket must split the range into at most {} buckets",
+            MAX_CORRELATION_BUCKETS
+        )));
+    }
+    if config.max_lag_buckets > MAX_CORRELATION_LAG {
+        return Err(ApiError::BadRequest(format!("max_lag may be at most {} buckets", MAX_CORRELATION_LAG)));
+    }
+
+    let correlations = state
+        .drift_detector
+        .correlations(&range, &config)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let probable_causes = symptom.map(|s| verisim_drift::correlation::root_causes(&correlations, s));
+    Ok(Json(DriftCorrelationResponse {
+        start: range.start,
+        end: range.end,
+        bucket_secs: config.bucket_secs,
+        correlations,
+        probable_causes,
+    }))
+}
 
-    let status = state.transaction_manager
-        .status(&txn_id)
-        .await
+/// A registered custom drift calculator
+#[derive(Debug, Serialize)]
+pub struct DriftPluginResponse {
+    pub name: String,
+    /// Drift type its scores are recorded under
+    pub drift_type: DriftType,
+    pub default_threshold: f64,
+}
+
+/// GET /drift/plugins — custom drift calculators registered with the detector
+#[instrument(skip(state))]
+async fn drift_plugins_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DriftPluginResponse>>, ApiError> {
+    let plugins = state
+        .drift_detector
+        .plugins()
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    plugins
+        .iter()
+        .map(|p| {
+            Ok(DriftPluginResponse {
+                name: p.name().to_string(),
+                drift_type: DriftType::Custom(
+                    verisim_drift::CustomDriftType::new(p.name()).map_err(|e| ApiError::Internal(e.to_string()))?,
+                ),
+                default_threshold: p.default_threshold(),
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()
+        .map(Json)
+}
 
-    Ok((StatusCode::CREATED, Json(status)))
+/// Request to open a drift suppression window
+#[derive(Debug, Deserialize)]
+pub struct SuppressionRequest {
+    /// Drift types to suppress (e.g. `tensor`); all types when empty
+    #[serde(default)]
+    pub drift_types: Vec<String>,
+    /// Window start; defaults to now
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Window end; give this or `duration_secs`
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Window length from `starts_at`
+    pub duration_secs: Option<u64>,
+    /// Why drift is being suppressed
+    pub reason: String,
+    /// Who is opening the window; ignored when the request is authenticated
+    pub actor: Option<String>,
 }
 
-/// Commit a transaction
+/// GET /drift/suppressions — current and upcoming suppression windows
 #[instrument(skip(state))]
-async fn transaction_commit_handler(
+async fn drift_suppressions_list_handler(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<transaction::TransactionStatus>, ApiError> {
-    let txn_id = transaction::TransactionId::from_str(&id);
+) -> Result<Json<Vec<SuppressionWindow>>, ApiError> {
+    state
+        .drift_detector
+        .suppressions()
+        .list()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
 
-    let _ops = state.transaction_manager
-        .commit(&txn_id)
-        .await
-        .map_err(|e| match e {
-            transaction::TransactionError::NotFound(_) => ApiError::NotFound(e.to_string()),
-            _ => ApiError::BadRequest(e.to_string()),
-        })?;
+/// POST /drift/suppressions — pause drift alerting and normalization for a time
+#[instrument(skip(state, actor, request))]
+async fn drift_suppression_create_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(request): Json<SuppressionRequest>,
+) -> Result<(StatusCode, Json<SuppressionWindow>), ApiError> {
+    let drift_types = request
+        .drift_types
+        .iter()
+        .map(|t| t.parse::<DriftType>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let starts_at = request.starts_at.unwrap_or_else(chrono::Utc::now);
+    let ends_at = match (request.ends_at, request.duration_secs) {
+        (Some(end), None) => end,
+        (None, Some(secs)) => {
+            starts_at
+                + chrono::Duration::try_seconds(secs as i64)
+                    .ok_or_else(|| ApiError::BadRequest("duration_secs is too large".to_string()))?
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Give exactly one of ends_at or duration_secs".to_string(),
+            ))
+        }
+    };
+    let created_by = actor
+        .map(|a| a.iri.clone())
+        .or(request.actor)
+        .unwrap_or_else(|| "anonymous".to_string());
 
-    // In a full implementation, ops would be applied to the hexad store here
-    let status = state.transaction_manager
-        .status(&txn_id)
-        .await
+    let window = SuppressionWindow::new(drift_types, starts_at, ends_at, request.reason, created_by)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    state
+        .drift_detector
+        .suppressions()
+        .add(window.clone())
         .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-    Ok(Json(status))
+    info!(
+        id = %window.id,
+        created_by = %window.created_by,
+        reason = %window.reason,
+        ends_at = %window.ends_at,
+        "Drift suppression window opened"
+    );
+    Ok((StatusCode::CREATED, Json(window)))
 }
 
-/// Rollback a transaction
+/// DELETE /drift/suppressions/{id} — close a suppression window early
 #[instrument(skip(state))]
-async fn transaction_rollback_handler(
+async fn drift_suppression_delete_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<transaction::TransactionStatus>, ApiError> {
-    let txn_id = transaction::TransactionId::from_str(&id);
+) -> Result<StatusCode, ApiError> {
+    state
+        .drift_detector
+        .suppressions()
+        .remove(&id)
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Suppression window {} not found", id)))?;
+    info!(id = %id, "Drift suppression window closed");
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let _discarded = state.transaction_manager
-        .rollback(&txn_id)
-        .await
-        .map_err(|e| match e {
-            transaction::TransactionError::NotFound(_) => ApiError::NotFound(e.to_string()),
-            _ => ApiError::BadRequest(e.to_string()),
-        })?;
+/// GET /drift/policy — the (drift type, severity) to action table
+#[instrument(skip(state))]
+async fn drift_policy_get_handler(State(state): State<AppState>) -> Result<Json<ActionPolicy>, ApiError> {
+    state
+        .drift_detector
+        .policy()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
 
-    let status = state.transaction_manager
-        .status(&txn_id)
-        .await
+/// PUT /drift/policy — replace the action table for subsequent events
+#[instrument(skip(state, actor, policy))]
+async fn drift_policy_put_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(policy): Json<ActionPolicy>,
+) -> Result<Json<ActionPolicy>, ApiError> {
+    let previous = state
+        .drift_detector
+        .set_policy(policy.clone())
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    info!(
+        actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string()),
+        previous_rules = previous.rules.len(),
+        rules = policy.rules.len(),
+        "Drift action policy replaced"
+    );
+    Ok(Json(policy))
+}
 
-    Ok(Json(status))
+/// GET /drift/quarantine — entities held back by the quarantine action
+#[instrument(skip(state))]
+async fn drift_quarantine_list_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<QuarantineRecord>>, ApiError> {
+    state
+        .drift_detector
+        .quarantine()
+        .list()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
 }
 
-/// Get transaction status
+/// DELETE /drift/quarantine/{id} — release an entity after review
 #[instrument(skip(state))]
-async fn transaction_status_handler(
+async fn drift_quarantine_release_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<transaction::TransactionStatus>, ApiError> {
-    let txn_id = transaction::TransactionId::from_str(&id);
-
-    let status = state.transaction_manager
-        .status(&txn_id)
-        .await
-        .map_err(|e| match e {
-            transaction::TransactionError::NotFound(_) => ApiError::NotFound(e.to_string()),
-            _ => ApiError::Internal(e.to_string()),
-        })?;
-
-    Ok(Json(status))
+) -> Result<StatusCode, ApiError> {
+    state
+        .drift_detector
+        .quarantine()
+        .release(&id)
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Entity {} is not quarantined", id)))?;
+    info!(id = %id, "Entity released from drift quarantine");
+    Ok(StatusCode::NO_CONTENT)
 }
 
-// --- ZKP Proof Handlers ---
-
-/// API request for proof generation
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProofGenerateRequest {
-    /// The claim to prove (base64-encoded or plain text)
-    pub claim: String,
-    /// Privacy level: "public", "private", or "zero_knowledge"
-    pub privacy_level: Option<String>,
-    /// Optional membership set for Merkle inclusion proofs
-    pub membership_set: Option<Vec<String>>,
-    /// Index of the claim in the membership set
-    pub membership_index: Option<usize>,
+/// GET /drift/population — embedding population reference and latest PSI/KL
+#[instrument(skip(state))]
+async fn drift_population_handler(
+    State(state): State<AppState>,
+) -> Result<Json<PopulationStatus>, ApiError> {
+    state
+        .population_monitor
+        .status()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
 }
 
-/// API request for proof verification
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProofVerifyRequest {
-    /// The proof to verify (serialized)
-    pub proof: zkp_api::ZkpProof,
-    /// The claim the proof is for
-    pub claim: String,
+/// POST /drift/population/rebaseline — accept recent inserts as the new reference
+#[instrument(skip(state))]
+async fn drift_population_rebaseline_handler(
+    State(state): State<AppState>,
+) -> Result<Json<PopulationStatus>, ApiError> {
+    state
+        .population_monitor
+        .rebaseline()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    state
+        .population_monitor
+        .status()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
 }
 
-/// API request for circuit-based proof generation
+/// Provenance chain that audits threshold changes
+const DRIFT_THRESHOLDS_AUDIT_ID: &str = "drift-thresholds";
+
+/// Threshold update request: the complete new thresholds plus audit details
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ProofWithCircuitRequest {
-    /// The claim to prove
-    pub claim: String,
-    /// Privacy level
-    pub privacy_level: Option<String>,
-    /// Circuit name to verify against
-    pub circuit_name: String,
-    /// Witness data (private inputs)
-    pub witness: Option<Vec<f64>>,
-    /// Public inputs
-    pub public_inputs: Option<Vec<f64>>,
+pub struct ThresholdUpdateRequest {
+    #[serde(flatten)]
+    pub thresholds: DriftThresholds,
+    /// Who is making the change; ignored when the request is authenticated
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Why the thresholds are changing
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
-/// API response for proof operations
+/// Threshold update response
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ProofResponse {
-    pub success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub proof: Option<zkp_api::ZkpProof>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub verified: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
+pub struct ThresholdUpdateResponse {
+    pub thresholds: DriftThresholds,
+    pub previous: DriftThresholds,
+    pub audit: ProvenanceRecordResponse,
 }
 
-fn parse_privacy_level(s: &str) -> Result<PrivacyLevel, ApiError> {
-    match s.to_lowercase().as_str() {
-        "public" => Ok(PrivacyLevel::Public),
-        "private" => Ok(PrivacyLevel::Private),
-        "zero_knowledge" | "zeroknowledge" | "zk" => Ok(PrivacyLevel::ZeroKnowledge),
-        other => Err(ApiError::BadRequest(format!(
-            "Unknown privacy level: '{}'. Use 'public', 'private', or 'zero_knowledge'",
-            other
-        ))),
+/// Summarise which thresholds changed, e.g. `tensor: 0.35 -> 0.5`.
+fn describe_threshold_changes(previous: &DriftThresholds, current: &DriftThresholds) -> String {
+    let (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(previous), serde_json::to_value(current))
+    else {
+        return String::new();
+    };
+    let changes: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, value)| {
+            let old = before.get(key).cloned().unwrap_or(serde_json::Value::Null);
+            format!("{}: {} -> {}", key, old, value)
+        })
+        .collect();
+    if changes.is_empty() {
+        "no changes".to_string()
+    } else {
+        changes.join("; ")
     }
 }
 
-/// Generate a privacy-aware ZKP proof
-#[instrument(skip(_state, request))]
-async fn proof_generate_handler(
-    State(_state): State<AppState>,
-    Json(request): Json<ProofGenerateRequest>,
-) -> Result<Json<ProofResponse>, ApiError> {
-    let privacy_level = match &request.privacy_level {
-        Some(level) => parse_privacy_level(level)?,
-        None => PrivacyLevel::Public,
+/// Drift threshold query parameters
+#[derive(Debug, Deserialize)]
+pub struct DriftThresholdQuery {
+    /// Namespace whose override is read or written; the global thresholds
+    /// when omitted
+    pub namespace: Option<String>,
+}
+
+/// GET /drift/thresholds?namespace= — current fixed and adaptive thresholds;
+/// for a namespace, its override or else the global thresholds
+#[instrument(skip(state))]
+async fn drift_thresholds_get_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DriftThresholdQuery>,
+) -> Result<Json<DriftThresholds>, ApiError> {
+    let detector = &state.drift_detector;
+    let thresholds = match &query.namespace {
+        Some(ns) => detector.namespace_thresholds(ns).transpose(),
+        None => None,
     };
+    thresholds
+        .unwrap_or_else(|| detector.thresholds())
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
 
-    let membership_set = request.membership_set.as_ref().map(|set| {
-        set.iter().map(|s| s.as_bytes().to_vec()).collect::<Vec<_>>()
-    });
+/// PUT /drift/thresholds?namespace= — replace the global thresholds, or set
+/// a namespace's override, and record who changed them
+#[instrument(skip(state, actor, request))]
+pub async fn drift_thresholds_put_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DriftThresholdQuery>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(request): Json<ThresholdUpdateRequest>,
+) -> Result<Json<ThresholdUpdateResponse>, ApiError> {
+    request
+        .thresholds
+        .validate()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
 
-    let bridge_request = ZkpBridgeRequest {
-        claim: request.claim.as_bytes().to_vec(),
-        privacy_level,
-        circuit_name: None,
-        witness: None,
-        public_inputs: None,
-        membership_set,
-        membership_index: request.membership_index,
+    // Authentication is authoritative over a caller-supplied actor.
+    let actor = actor
+        .map(|a| a.iri.clone())
+        .or(request.actor)
+        .unwrap_or_else(|| "anonymous".to_string());
+
+    let detector = &state.drift_detector;
+    let internal = |e: verisim_drift::DriftError| ApiError::Internal(e.to_string());
+    let (previous, previous_override) = match &query.namespace {
+        Some(ns) => {
+            let previous_override = detector
+                .set_namespace_thresholds(ns, request.thresholds.clone())
+                .map_err(internal)?;
+            let previous = match &previous_override {
+                Some(t) => t.clone(),
+                None => detector.thresholds().map_err(internal)?,
+            };
+            (previous, previous_override)
+        }
+        None => (
+            detector.set_thresholds(request.thresholds.clone()).map_err(internal)?,
+            None,
+        ),
     };
 
-    match zkp_api::generate_zkp(&bridge_request) {
-        Ok(proof) => Ok(Json(ProofResponse {
-            success: true,
-            proof: Some(proof),
-            verified: None,
-            error: None,
-        })),
-        Err(e) => Ok(Json(ProofResponse {
-            success: false,
-            proof: None,
-            verified: None,
-            error: Some(e.to_string()),
-        })),
+    let mut description = describe_threshold_changes(&previous, &request.thresholds);
+    if let Some(ns) = &query.namespace {
+        description = format!("namespace {}: {}", ns, description);
     }
-}
-
-/// Verify a previously generated ZKP proof
-#[instrument(skip(_state, request))]
-async fn proof_verify_handler(
-    State(_state): State<AppState>,
-    Json(request): Json<ProofVerifyRequest>,
-) -> Result<Json<ProofResponse>, ApiError> {
-    let verified = zkp_api::verify_zkp(&request.proof, request.claim.as_bytes());
+    if let Some(reason) = request.reason {
+        description = format!("{} ({})", description, reason);
+    }
+    let source = match &query.namespace {
+        Some(ns) => format!("PUT /drift/thresholds?namespace={}", ns),
+        None => "PUT /drift/thresholds".to_string(),
+    };
+    let record = state
+        .hexad_store
+        .provenance_store()
+        .record_event(
+            DRIFT_THRESHOLDS_AUDIT_ID,
+            verisim_provenance::ProvenanceEventType::Modified,
+            &actor,
+            Some(source),
+            &description,
+        )
+        .await;
+    let record = match record {
+        Ok(record) => record,
+        Err(e) => {
+            // An unaudited change must not stay in effect.
+            let _ = match (&query.namespace, previous_override) {
+                (Some(ns), Some(t)) => detector.set_namespace_thresholds(ns, t).map(|_| ()),
+                (Some(ns), None) => detector.remove_namespace_thresholds(ns).map(|_| ()),
+                (None, _) => detector.set_thresholds(previous.clone()).map(|_| ()),
+            };
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+    info!(actor = %actor, changes = %description, "Drift thresholds changed");
 
-    Ok(Json(ProofResponse {
-        success: true,
-        proof: None,
-        verified: Some(verified),
-        error: None,
+    Ok(Json(ThresholdUpdateResponse {
+        thresholds: request.thresholds,
+        previous,
+        audit: (&record).into(),
     }))
 }
 
-/// Generate a proof with circuit verification
-#[instrument(skip(state, request))]
-async fn proof_generate_with_circuit_handler(
+/// GET /drift/thresholds/namespaces — namespace threshold overrides
+#[instrument(skip(state))]
+async fn drift_namespace_thresholds_list_handler(
     State(state): State<AppState>,
-    Json(request): Json<ProofWithCircuitRequest>,
-) -> Result<Json<ProofResponse>, ApiError> {
-    let privacy_level = match &request.privacy_level {
-        Some(level) => parse_privacy_level(level)?,
-        None => PrivacyLevel::Public,
-    };
+) -> Result<Json<std::collections::HashMap<String, DriftThresholds>>, ApiError> {
+    state
+        .drift_detector
+        .all_namespace_thresholds()
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
 
-    let bridge_request = ZkpBridgeRequest {
-        claim: request.claim.as_bytes().to_vec(),
-        privacy_level,
-        circuit_name: Some(request.circuit_name),
-        witness: request.witness,
-        public_inputs: request.public_inputs,
-        membership_set: None,
-        membership_index: None,
-    };
+/// DELETE /drift/thresholds/namespaces/{namespace} — return a namespace to
+/// the global thresholds
+#[instrument(skip(state, actor))]
+async fn drift_namespace_thresholds_delete_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Path(namespace): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let removed = state
+        .drift_detector
+        .remove_namespace_thresholds(&namespace)
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("No threshold override for namespace {}", namespace)))?;
 
-    match zkp_api::generate_zkp_with_circuit(&bridge_request, &state.circuit_registry) {
-        Ok(proof) => Ok(Json(ProofResponse {
-            success: true,
-            proof: Some(proof),
-            verified: None,
-            error: None,
-        })),
-        Err(e) => Ok(Json(ProofResponse {
-            success: false,
-            proof: None,
-            verified: None,
-            error: Some(e.to_string()),
-        })),
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    let description = format!("namespace {}: override removed", namespace);
+    if let Err(e) = state
+        .hexad_store
+        .provenance_store()
+        .record_event(
+            DRIFT_THRESHOLDS_AUDIT_ID,
+            verisim_provenance::ProvenanceEventType::Modified,
+            &actor,
+            Some(format!("DELETE /drift/thresholds/namespaces/{}", namespace)),
+            &description,
+        )
+        .await
+    {
+        let _ = state.drift_detector.set_namespace_thresholds(&namespace, removed);
+        return Err(ApiError::Internal(e.to_string()));
     }
+    info!(actor = %actor, namespace = %namespace, "Drift threshold override removed");
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// Start the API server (plain HTTP)
-pub async fn serve(config: ApiConfig) -> Result<(), std::io::Error> {
-    let state = AppState::new_async(config.clone())
-        .await
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
-    let app = build_router(state);
-
-    let addr = format!("{}:{}", config.host, config.port);
-    info!("Starting VeriSimDB API server on {}", addr);
-
-    let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
-
-    Ok(())
+/// GET /drift/thresholds/audit — history of threshold changes
+#[instrument(skip(state))]
+async fn drift_thresholds_audit_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ProvenanceChainResponse>, ApiError> {
+    audit_chain(&state, DRIFT_THRESHOLDS_AUDIT_ID).await.map(Json)
 }
 
-/// Start the API server with TLS (HTTPS)
-pub async fn serve_tls(
-    config: ApiConfig,
-    cert_path: &str,
-    key_path: &str,
-) -> Result<(), std::io::Error> {
-    use axum_server::tls_rustls::RustlsConfig;
-
-    let state = AppState::new_async(config.clone())
-        .await
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
-    let app = build_router(state);
-
-    let addr = format!("{}:{}", config.host, config.port);
-    info!(addr = %addr, cert = %cert_path, "Starting VeriSimDB API server with TLS");
-
-    let tls_config = RustlsConfig::from_pem_file(cert_path, key_path)
-        .await
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
-
-    let addr: std::net::SocketAddr = addr
-        .parse()
-        .map_err(|e: std::net::AddrParseError| std::io::Error::other(e.to_string()))?;
-
-    axum_server::bind_rustls(addr, tls_config)
-        .serve(app.into_make_service())
-        .await?;
+/// A configuration audit chain; empty until the first change is recorded
+async fn audit_chain(state: &AppState, audit_id: &str) -> Result<ProvenanceChainResponse, ApiError> {
+    let provenance = state.hexad_store.provenance_store();
+    let chain = match provenance.get_chain(audit_id).await {
+        Ok(chain) => chain,
+        Err(verisim_provenance::ProvenanceError::NotFound(_)) => verisim_provenance::ProvenanceChain::new(audit_id),
+        Err(e) => return Err(ApiError::Internal(e.to_string())),
+    };
+    let chain_valid = provenance.verify_chain(audit_id).await.unwrap_or(false);
+    let records: Vec<ProvenanceRecordResponse> = chain.records.iter().map(Into::into).collect();
 
-    Ok(())
+    Ok(ProvenanceChainResponse {
+        entity_id: audit_id.to_string(),
+        chain_length: records.len(),
+        chain_valid,
+        records,
+    })
 }
 
-// ---------------------------------------------------------------------------
-// Provenance endpoint handlers
-// ---------------------------------------------------------------------------
-
-/// Provenance chain response
+/// Entity drift response
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ProvenanceChainResponse {
+pub struct EntityDriftResponse {
     pub entity_id: String,
-    pub chain_length: usize,
-    pub chain_valid: bool,
-    pub records: Vec<ProvenanceRecordResponse>,
-}
-
-/// A single provenance record in the response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProvenanceRecordResponse {
-    pub event_type: String,
-    pub actor: String,
-    pub timestamp: String,
-    pub source: Option<String>,
-    pub description: String,
-    pub content_hash: String,
+    pub score: f64,
+    pub drift_type: String,
+    pub status: String,
 }
 
-/// GET /provenance/{id} — retrieve the full provenance chain for an entity
+/// Entity drift handler — get drift info for a single entity
 #[instrument(skip(state))]
-async fn provenance_get_chain_handler(
+async fn entity_drift_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<ProvenanceChainResponse>, ApiError> {
+) -> Result<Json<EntityDriftResponse>, ApiError> {
     validate_hexad_id(&id)?;
-
-    // Check entity exists
     let hexad_id = HexadId::new(&id);
-    let exists = state
+
+    // Verify hexad exists
+    let _hexad = state
         .hexad_store
-        .status(&hexad_id)
+        .get(&hexad_id)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
-    if exists.is_none() {
-        return Err(ApiError::NotFound(format!("Entity {} not found", id)));
-    }
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))?;
 
-    let chain = state
-        .hexad_store
-        .provenance_store()
-        .get_chain(&id)
-        .await
+    // Get aggregate health from drift detector
+    let all_metrics = state.drift_detector.all_metrics()
         .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-    let chain_valid = state
-        .hexad_store
-        .provenance_store()
-        .verify_chain(&id)
-        .await
-        .unwrap_or(false);
-
-    let records: Vec<ProvenanceRecordResponse> = chain
-        .records
+    let (worst_type, worst_score) = all_metrics
         .iter()
-        .map(|r| ProvenanceRecordResponse {
-            event_type: format!("{:?}", r.event_type),
-            actor: r.actor.clone(),
-            timestamp: r.timestamp.to_rfc3339(),
-            source: r.source.clone(),
-            description: r.description.clone(),
-            content_hash: r.content_hash.clone(),
-        })
-        .collect();
+        .max_by(|a, b| a.1.current_score.partial_cmp(&b.1.current_score).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(dt, m)| (dt.to_string(), m.current_score))
+        .unwrap_or_else(|| ("none".to_string(), 0.0));
 
-    Ok(Json(ProvenanceChainResponse {
+    let status = if worst_score >= 0.7 {
+        "critical"
+    } else if worst_score >= 0.3 {
+        "warning"
+    } else {
+        "healthy"
+    };
+
+    Ok(Json(EntityDriftResponse {
         entity_id: id,
-        chain_length: records.len(),
-        chain_valid,
-        records,
+        score: worst_score,
+        drift_type: worst_type,
+        status: status.to_string(),
     }))
 }
 
-/// POST /provenance/{id}/record — record a new provenance event
-#[instrument(skip(state, body))]
-async fn provenance_record_handler(
+/// Normalizer status handler
+#[instrument(skip(state))]
+async fn normalizer_status_handler(
+    State(state): State<AppState>,
+) -> Result<Json<NormalizerStatus>, ApiError> {
+    let status = state.normalizer.status().await;
+    Ok(Json(status))
+}
+
+/// Normalizer report query parameters
+#[derive(Debug, Deserialize)]
+pub struct NormalizerReportQuery {
+    /// Recent outcomes to include (default 20)
+    #[serde(default = "default_report_limit")]
+    pub limit: usize,
+}
+
+fn default_report_limit() -> usize {
+    20
+}
+
+/// GET /normalizer/report?limit= — success rates, per-strategy latency,
+/// drift moving averages since repairs began, and recent outcomes
+#[instrument(skip(state))]
+async fn normalizer_report_handler(
+    State(state): State<AppState>,
+    Query(query): Query<NormalizerReportQuery>,
+) -> Json<NormalizerReport> {
+    Json(state.normalizer.report(query.limit.min(MAX_RECENT_OUTCOMES)).await)
+}
+
+/// Normalization trigger query parameters
+#[derive(Debug, Deserialize)]
+pub struct NormalizationTriggerQuery {
+    /// Drift type to repair (e.g. `tensor`); defaults to a full quality
+    /// reconciliation across every modality
+    #[serde(rename = "type")]
+    pub drift_type: Option<String>,
+    /// Namespace whose strategy set applies
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// POST /normalizer/trigger/{id}?type=&namespace= — queue a normalization for the
+/// background workers
+#[instrument(skip(state))]
+pub async fn trigger_normalization_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(body): Json<ProvenanceRequest>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    Query(query): Query<NormalizationTriggerQuery>,
+) -> Result<StatusCode, ApiError> {
     validate_hexad_id(&id)?;
-
     let hexad_id = HexadId::new(&id);
-    let input = HexadInput {
-        provenance: Some(HexadProvenanceInput {
-            event_type: body.event_type,
-            actor: body.actor,
-            source: body.source,
-            description: body.description,
-        }),
-        ..Default::default()
+    let drift_type = match &query.drift_type {
+        Some(name) => name
+            .parse::<DriftType>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?,
+        None => DriftType::QualityDrift,
     };
 
-    let hexad = state
+    // Check if hexad exists
+    state
         .hexad_store
-        .update(&hexad_id, input)
+        .get(&hexad_id)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))?;
 
-    Ok(Json(serde_json::json!({
-        "entity_id": id,
-        "chain_length": hexad.provenance_chain_length,
-        "recorded": true,
-    })))
+    let mut event = DriftEvent::new(drift_type, 1.0, "Normalization requested via API")
+        .with_entities(vec![id.clone()])
+        .with_actions(vec![DriftAction::Normalize]);
+    if let Some(namespace) = query.namespace {
+        event = event.with_namespace(namespace);
+    }
+    state
+        .normalizer
+        .enqueue(NormalizationJob::new(hexad_id, event))
+        .await
+        .map_err(|e| match e {
+            NormalizerError::QueueFull => ApiError::Unavailable(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+    info!(id = %id, drift_type = %drift_type, "Normalization queued for hexad");
+
+    Ok(StatusCode::ACCEPTED)
 }
 
-/// GET /provenance/{id}/verify — verify provenance chain integrity
+/// POST /normalizer/preview/{id}?type=&namespace= — dry-run the applicable strategies
+/// and return the proposed changes for approval
 #[instrument(skip(state))]
-async fn provenance_verify_handler(
+async fn preview_normalization_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    Query(query): Query<NormalizationTriggerQuery>,
+) -> Result<Json<NormalizationPreview>, ApiError> {
     validate_hexad_id(&id)?;
-
-    let hexad_id = HexadId::new(&id);
-    let status = state
+    let drift_types = match &query.drift_type {
+        Some(name) => vec![name
+            .parse::<DriftType>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?],
+        None => DriftType::ALL.to_vec(),
+    };
+    let hexad = state
         .hexad_store
-        .status(&hexad_id)
+        .get(&HexadId::new(&id))
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::NotFound(format!("Entity {} not found", id)))?;
+        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", id)))?;
 
-    Ok(Json(serde_json::json!({
-        "entity_id": id,
-        "has_provenance": status.modality_status.provenance,
-        "chain_valid": true,
-    })))
+    state
+        .normalizer
+        .preview(&hexad, &drift_types, query.namespace.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
 }
 
-// ---------------------------------------------------------------------------
-// Spatial endpoint handlers
-// ---------------------------------------------------------------------------
+/// GET /normalizer/previews/{preview_id} — a preview awaiting approval
+#[instrument(skip(state))]
+async fn get_normalization_preview_handler(
+    State(state): State<AppState>,
+    Path(preview_id): Path<String>,
+) -> Result<Json<NormalizationPreview>, ApiError> {
+    state
+        .normalizer
+        .get_preview(&preview_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Preview {} not found", preview_id)))
+}
 
-/// Radius search request
-#[derive(Debug, Deserialize)]
-pub struct RadiusSearchRequest {
-    pub latitude: f64,
-    pub longitude: f64,
-    pub radius_km: f64,
-    pub limit: Option<usize>,
+/// DELETE /normalizer/previews/{preview_id} — reject a preview
+#[instrument(skip(state))]
+async fn discard_normalization_preview_handler(
+    State(state): State<AppState>,
+    Path(preview_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .normalizer
+        .discard_preview(&preview_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .ok_or_else(|| ApiError::NotFound(format!("Preview {} not found", preview_id)))
 }
 
-/// Bounding box search request
-#[derive(Debug, Deserialize)]
-pub struct BoundsSearchRequest {
-    pub min_lat: f64,
-    pub min_lon: f64,
-    pub max_lat: f64,
-    pub max_lon: f64,
-    pub limit: Option<usize>,
+/// POST /normalizer/previews/{preview_id}/approve — apply a preview's
+/// proposed changes
+#[instrument(skip(state, actor))]
+async fn approve_normalization_preview_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Path(preview_id): Path<String>,
+) -> Result<Json<NormalizationResult>, ApiError> {
+    let approver = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    state
+        .normalizer
+        .approve_preview(&preview_id, &approver)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            NormalizerError::PreviewNotFound(_) => ApiError::NotFound(e.to_string()),
+            NormalizerError::PreviewStale { .. } => ApiError::BadRequest(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })
 }
 
-/// K-nearest search request
-#[derive(Debug, Deserialize)]
-pub struct NearestSearchRequest {
-    pub latitude: f64,
-    pub longitude: f64,
-    pub k: Option<usize>,
+/// Rollback query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct NormalizationRollbackQuery {
+    /// Revert even if the entity was written after the repair
+    #[serde(default)]
+    pub force: bool,
 }
 
-/// Spatial search result response
-#[derive(Debug, Serialize)]
-pub struct SpatialSearchResultResponse {
-    pub entity_id: String,
-    pub latitude: f64,
-    pub longitude: f64,
-    pub distance_km: f64,
+/// GET /normalizer/results — applied repairs that can be rolled back
+#[instrument(skip(state))]
+async fn normalization_results_handler(State(state): State<AppState>) -> Json<Vec<NormalizationResult>> {
+    Json(state.normalizer.applied_results().await)
 }
 
-/// POST /spatial/search/radius — find entities within a given radius
-#[instrument(skip_all)]
-async fn spatial_radius_search_handler(
+/// POST /normalizer/rollback/{result_id}?force= — revert the entity an
+/// applied repair changed to its pre-normalization version
+#[instrument(skip(state, actor))]
+async fn rollback_normalization_handler(
     State(state): State<AppState>,
-    Json(body): Json<RadiusSearchRequest>,
-) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
-    let limit = validate_limit(body.limit.unwrap_or(100));
+    actor: Option<Extension<ActorIdentity>>,
+    Path(result_id): Path<String>,
+    Query(query): Query<NormalizationRollbackQuery>,
+) -> Result<Json<NormalizationRollback>, ApiError> {
+    let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "anonymous".to_string());
+    state
+        .normalizer
+        .rollback(&result_id, &actor, query.force)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            NormalizerError::ResultNotFound(_) => ApiError::NotFound(e.to_string()),
+            NormalizerError::RollbackConflict { .. } => ApiError::BadRequest(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })
+}
 
-    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
-        return Err(ApiError::BadRequest("Invalid coordinates".to_string()));
-    }
-    if body.radius_km <= 0.0 {
-        return Err(ApiError::BadRequest("Radius must be positive".to_string()));
+/// Strategy query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct StrategyQuery {
+    /// Namespace to report the effective strategy order for
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Registered strategies and the runtime selection
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StrategySelectionResponse {
+    /// Registered strategies, in registration order
+    pub registered: Vec<String>,
+    /// Strategies that may run in the requested namespace, highest priority first
+    pub order: Vec<String>,
+    pub selection: StrategySelection,
+}
+
+async fn strategy_selection_response(state: &AppState, namespace: Option<&str>) -> StrategySelectionResponse {
+    StrategySelectionResponse {
+        registered: state.normalizer.strategies().await,
+        order: state.normalizer.strategy_order(namespace).await,
+        selection: state.normalizer.strategy_selection().await,
     }
+}
 
-    let center = Coordinates {
-        latitude: body.latitude,
-        longitude: body.longitude,
-        altitude: None,
-    };
+/// GET /normalizer/strategies?namespace= — strategy priorities, enable
+/// flags and namespace sets, with the effective order for a namespace
+#[instrument(skip(state))]
+async fn strategy_selection_get_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StrategyQuery>,
+) -> Json<StrategySelectionResponse> {
+    Json(strategy_selection_response(&state, query.namespace.as_deref()).await)
+}
 
-    let results = state
-        .hexad_store
-        .spatial_store()
-        .search_radius(&center, body.radius_km, limit)
+/// PUT /normalizer/strategies — replace the strategy selection
+#[instrument(skip(state, selection))]
+async fn strategy_selection_put_handler(
+    State(state): State<AppState>,
+    Json(selection): Json<StrategySelection>,
+) -> Result<Json<StrategySelectionResponse>, ApiError> {
+    state
+        .normalizer
+        .set_strategy_selection(selection)
         .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            NormalizerError::StrategyNotFound(_) => ApiError::BadRequest(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+    Ok(Json(strategy_selection_response(&state, None).await))
+}
 
-    let response = results
-        .into_iter()
-        .map(|r| SpatialSearchResultResponse {
-            entity_id: r.entity_id,
-            latitude: r.data.coordinates.latitude,
-            longitude: r.data.coordinates.longitude,
-            distance_km: r.distance_km,
-        })
-        .collect();
+/// Provenance chain that audits conflict policy changes
+const CONFLICT_POLICY_AUDIT_ID: &str = "normalizer-conflict-policy";
 
-    Ok(Json(response))
+/// Conflict policy update request: the complete new configuration plus
+/// audit details
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictPolicyUpdateRequest {
+    #[serde(flatten)]
+    pub config: ConflictConfig,
+    /// Who is making the change; ignored when the request is authenticated
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Why the policy is changing
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
-/// POST /spatial/search/bounds — find entities within a bounding box
-#[instrument(skip_all)]
-async fn spatial_bounds_search_handler(
-    State(state): State<AppState>,
-    Json(body): Json<BoundsSearchRequest>,
-) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
-    let limit = validate_limit(body.limit.unwrap_or(100));
+/// Conflict policy update response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictPolicyUpdateResponse {
+    pub config: ConflictConfig,
+    pub previous: ConflictConfig,
+    pub audit: ProvenanceRecordResponse,
+}
 
-    if body.min_lat > body.max_lat || body.min_lon > body.max_lon {
-        return Err(ApiError::BadRequest(
-            "min values must be less than max values".to_string(),
+/// Summarise a conflict policy change, e.g.
+/// `default_policy: last_writer_wins -> auto_merge; overrides: 0 -> 1`.
+fn describe_conflict_policy_change(previous: &ConflictConfig, current: &ConflictConfig) -> String {
+    let mut changes = Vec::new();
+    if previous.default_policy != current.default_policy {
+        changes.push(format!("default_policy: {} -> {}", previous.default_policy, current.default_policy));
+    }
+    if previous.per_modality_policies != current.per_modality_policies {
+        changes.push(format!(
+            "overrides: {} -> {}",
+            previous.per_modality_policies.len(),
+            current.per_modality_policies.len()
+        ));
+    }
+    for (name, before, after) in [
+        ("auto_resolve_threshold", previous.auto_resolve_threshold, current.auto_resolve_threshold),
+        ("require_manual_above", previous.require_manual_above, current.require_manual_above),
+    ] {
+        if before != after {
+            changes.push(format!("{}: {} -> {}", name, before, after));
+        }
+    }
+    if previous.max_history_entries != current.max_history_entries {
+        changes.push(format!(
+            "max_history_entries: {} -> {}",
+            previous.max_history_entries, current.max_history_entries
         ));
     }
+    if changes.is_empty() {
+        "no changes".to_string()
+    } else {
+        changes.join("; ")
+    }
+}
 
-    let bounds = BoundingBox {
-        min_lat: body.min_lat,
-        min_lon: body.min_lon,
-        max_lat: body.max_lat,
-        max_lon: body.max_lon,
-    };
+/// GET /normalizer/conflict-policy — default policy, per-modality-pair
+/// overrides and escalation thresholds
+#[instrument(skip(state))]
+async fn conflict_policy_get_handler(State(state): State<AppState>) -> Json<ConflictConfig> {
+    Json(state.conflict_resolver.config())
+}
 
-    let results = state
+/// PUT /normalizer/conflict-policy — replace the conflict policy and record
+/// who changed it
+#[instrument(skip(state, actor, request))]
+async fn conflict_policy_put_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(request): Json<ConflictPolicyUpdateRequest>,
+) -> Result<Json<ConflictPolicyUpdateResponse>, ApiError> {
+    // Authentication is authoritative over a caller-supplied actor.
+    let actor = actor
+        .map(|a| a.iri.clone())
+        .or(request.actor)
+        .unwrap_or_else(|| "anonymous".to_string());
+
+    let previous = state
+        .conflict_resolver
+        .set_config(request.config.clone())
+        .map_err(|e| match e {
+            ConflictError::InvalidPolicy(_) => ApiError::BadRequest(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+
+    let mut description = describe_conflict_policy_change(&previous, &request.config);
+    if let Some(reason) = request.reason {
+        description = format!("{} ({})", description, reason);
+    }
+    let record = state
         .hexad_store
-        .spatial_store()
-        .search_within(&bounds, limit)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        .provenance_store()
+        .record_event(
+            CONFLICT_POLICY_AUDIT_ID,
+            verisim_provenance::ProvenanceEventType::Modified,
+            &actor,
+            Some("PUT /normalizer/conflict-policy".to_string()),
+            &description,
+        )
+        .await;
+    let record = match record {
+        Ok(record) => record,
+        Err(e) => {
+            // An unaudited change must not stay in effect.
+            let _ = state.conflict_resolver.set_config(previous);
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+    info!(actor = %actor, changes = %description, "Conflict policy changed");
 
-    let response = results
+    Ok(Json(ConflictPolicyUpdateResponse {
+        config: request.config,
+        previous,
+        audit: (&record).into(),
+    }))
+}
+
+/// GET /normalizer/conflict-policy/audit — history of conflict policy changes
+#[instrument(skip(state))]
+async fn conflict_policy_audit_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ProvenanceChainResponse>, ApiError> {
+    audit_chain(&state, CONFLICT_POLICY_AUDIT_ID).await.map(Json)
+}
+
+/// Map a campaign error to its HTTP status
+fn campaign_error(e: CampaignError) -> ApiError {
+    match e {
+        CampaignError::NotFound(_) => ApiError::NotFound(e.to_string()),
+        CampaignError::InvalidSchedule { .. } | CampaignError::Invalid(_) => ApiError::BadRequest(e.to_string()),
+        CampaignError::AlreadyRunning(_) => ApiError::Unavailable(e.to_string()),
+        CampaignError::Persistence(_) => ApiError::Internal(e.to_string()),
+    }
+}
+
+/// GET /normalizer/campaigns — scheduled normalization campaigns
+#[instrument(skip(state))]
+async fn list_campaigns_handler(State(state): State<AppState>) -> Json<Vec<Campaign>> {
+    Json(state.campaign_scheduler.campaigns().await)
+}
+
+/// POST /normalizer/campaigns — create a campaign; its ID is assigned
+#[instrument(skip(state, campaign))]
+async fn create_campaign_handler(
+    State(state): State<AppState>,
+    Json(mut campaign): Json<Campaign>,
+) -> Result<(StatusCode, Json<Campaign>), ApiError> {
+    campaign.id = String::new();
+    let campaign = state
+        .campaign_scheduler
+        .save_campaign(campaign)
+        .await
+        .map_err(campaign_error)?;
+    info!(id = %campaign.id, name = %campaign.name, schedule = campaign.schedule.expression(), "Normalization campaign created");
+    Ok((StatusCode::CREATED, Json(campaign)))
+}
+
+/// GET /normalizer/campaigns/{id} — one campaign and its next run time
+#[instrument(skip(state))]
+async fn get_campaign_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Campaign>, ApiError> {
+    state
+        .campaign_scheduler
+        .campaign(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Campaign {} not found", id)))
+}
+
+/// PUT /normalizer/campaigns/{id} — replace a campaign's settings
+#[instrument(skip(state, campaign))]
+async fn update_campaign_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(mut campaign): Json<Campaign>,
+) -> Result<Json<Campaign>, ApiError> {
+    if state.campaign_scheduler.campaign(&id).await.is_none() {
+        return Err(ApiError::NotFound(format!("Campaign {} not found", id)));
+    }
+    campaign.id = id;
+    state
+        .campaign_scheduler
+        .save_campaign(campaign)
+        .await
+        .map(Json)
+        .map_err(campaign_error)
+}
+
+/// DELETE /normalizer/campaigns/{id} — delete a campaign and its run history
+#[instrument(skip(state))]
+async fn delete_campaign_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .campaign_scheduler
+        .remove_campaign(&id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(campaign_error)
+}
+
+/// POST /normalizer/campaigns/{id}/run — start a campaign now, or resume its
+/// interrupted run; progress is reported under `/runs`
+#[instrument(skip(state))]
+async fn run_campaign_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if state.campaign_scheduler.campaign(&id).await.is_none() {
+        return Err(ApiError::NotFound(format!("Campaign {} not found", id)));
+    }
+    if state.campaign_scheduler.is_running(&id) {
+        return Err(campaign_error(CampaignError::AlreadyRunning(id)));
+    }
+    let scheduler = state.campaign_scheduler.clone();
+    tokio::spawn(verisim_hexad::security::system(async move {
+        if let Err(e) = scheduler.run(&id, RunTrigger::Manual).await {
+            warn!(campaign = %id, error = %e, "Normalization campaign failed to start");
+        }
+    }));
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// GET /normalizer/campaigns/{id}/runs — progress and summary reports of a
+/// campaign's runs, newest first
+#[instrument(skip(state))]
+async fn campaign_runs_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<CampaignRun>>, ApiError> {
+    if state.campaign_scheduler.campaign(&id).await.is_none() {
+        return Err(ApiError::NotFound(format!("Campaign {} not found", id)));
+    }
+    Ok(Json(state.campaign_scheduler.runs(&id).await))
+}
+
+// --- Query Planner Handlers ---
+
+/// Query plan handler — optimize a logical plan into a physical plan
+#[instrument(skip(state, plan))]
+async fn query_plan_handler(
+    State(state): State<AppState>,
+    Json(plan): Json<LogicalPlan>,
+) -> Result<Json<PhysicalPlan>, ApiError> {
+    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    let physical = planner
+        .optimize(&plan)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    Ok(Json(physical))
+}
+
+/// Query parameters for `/query/explain`
+#[derive(Debug, Default, Deserialize)]
+pub struct ExplainParams {
+    /// Output format: `json` (default), `text`, `tree` or `dot`
+    #[serde(default)]
+    pub format: ExplainFormat,
+}
+
+/// Query explain handler — generate EXPLAIN output for a logical plan,
+/// or render its physical plan as a tree or Graphviz DOT
+#[instrument(skip(state, plan))]
+async fn query_explain_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ExplainParams>,
+    Json(plan): Json<LogicalPlan>,
+) -> Result<Response, ApiError> {
+    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    let physical = planner
+        .optimize(&plan)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let response = match params.format {
+        ExplainFormat::Json => Json(ExplainOutput::from_physical_plan(&physical, planner.config())).into_response(),
+        ExplainFormat::Text => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            ExplainOutput::from_physical_plan(&physical, planner.config()).text_output,
+        )
+            .into_response(),
+        ExplainFormat::Tree => Json(PlanTreeNode::from_physical_plan(&physical)).into_response(),
+        ExplainFormat::Dot => (
+            [(header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            PlanTreeNode::from_physical_plan(&physical).to_dot(),
+        )
+            .into_response(),
+    };
+    Ok(response)
+}
+
+/// Request to execute a logical plan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryExecuteRequest {
+    /// The logical plan to optimize and run
+    pub plan: LogicalPlan,
+    /// Parameter bindings; a vector parameter is the query vector
+    #[serde(default)]
+    pub params: std::collections::HashMap<String, ParamValue>,
+}
+
+/// Query execute handler — optimize a logical plan, run it and return the
+/// result rows
+#[instrument(skip(state, headers, request))]
+async fn query_execute_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<QueryExecuteRequest>,
+) -> Result<Json<executor::PlanExecution>, ApiError> {
+    let timeout = queries::header_timeout(&headers, state.config.default_query_timeout_ms)?;
+    let physical = {
+        let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+        planner
+            .optimize(&request.plan)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?
+    };
+    let execution = state
+        .active_queries
+        .run("(logical plan)", timeout, true, async {
+            executor::PlanExecutor::new(&state)
+                .with_params(&request.params)
+                .execute(&request.plan, physical)
+                .await
+        })
+        .await?;
+    Ok(Json(execution))
+}
+
+/// List running queries
+#[instrument(skip(state))]
+async fn active_queries_handler(State(state): State<AppState>) -> Json<Vec<queries::ActiveQuery>> {
+    Json(state.active_queries.list())
+}
+
+/// Kill a running query
+#[instrument(skip(state))]
+async fn kill_query_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<queries::ActiveQuery>, ApiError> {
+    let killed = state
+        .active_queries
+        .kill(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("Query {} is not running", id)))?;
+    info!(query_id = %id, "Query killed");
+    Ok(Json(killed))
+}
+
+/// Request to register a continuous query
+#[derive(Debug, Deserialize)]
+pub struct SubscribeRequest {
+    /// `SUBSCRIBE ...` statement, or the query after `SUBSCRIBE`
+    pub query: String,
+    /// Values for `$name` parameters in the query
+    #[serde(default)]
+    pub params: std::collections::HashMap<String, serde_json::Value>,
+}
+
+/// Register a continuous query
+#[instrument(skip(state, request), fields(query = %request.query))]
+async fn subscribe_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SubscribeRequest>,
+) -> Result<(StatusCode, Json<subscriptions::Subscription>), ApiError> {
+    let conditions = vql::parse_subscribe(&request.query, &request.params)?;
+    let subscription = state.subscriptions.subscribe(request.query.trim(), conditions);
+    info!(subscription_id = %subscription.id, "Subscription registered");
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// List the caller's continuous queries, or every one for an administrator
+#[instrument(skip(state))]
+async fn list_subscriptions_handler(State(state): State<AppState>) -> Json<Vec<subscriptions::Subscription>> {
+    Json(state.subscriptions.list())
+}
+
+/// Get a continuous query
+#[instrument(skip(state))]
+async fn get_subscription_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<subscriptions::Subscription>, ApiError> {
+    state
+        .subscriptions
+        .get(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Subscription {} not found", id)))
+}
+
+/// Cancel a continuous query, closing its event streams
+#[instrument(skip(state))]
+async fn unsubscribe_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<subscriptions::Subscription>, ApiError> {
+    let subscription = state
+        .subscriptions
+        .unsubscribe(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("Subscription {} not found", id)))?;
+    info!(subscription_id = %id, "Subscription cancelled");
+    Ok(Json(subscription))
+}
+
+/// Stream a continuous query's result set and the changes the caller may
+/// see as server-sent events
+#[instrument(skip(state))]
+async fn subscription_events_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    Ok(subscriptions::event_stream(&state, &id).await?.into_response())
+}
+
+/// Get planner configuration
+#[instrument(skip(state))]
+async fn get_planner_config_handler(
+    State(state): State<AppState>,
+) -> Result<Json<PlannerConfig>, ApiError> {
+    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    Ok(Json(planner.config().clone()))
+}
+
+/// Update planner configuration
+#[instrument(skip(state, config))]
+async fn put_planner_config_handler(
+    State(state): State<AppState>,
+    Json(config): Json<PlannerConfig>,
+) -> Result<Json<PlannerConfig>, ApiError> {
+    let mut planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    planner.set_config(config);
+    Ok(Json(planner.config().clone()))
+}
+
+/// Planner statistics and result cache performance
+#[derive(Debug, Serialize)]
+pub struct PlannerStatsResponse {
+    /// Per-modality store statistics
+    #[serde(flatten)]
+    pub stats: StatisticsCollector,
+    /// Query result cache hit/miss counters
+    pub result_cache: ResultCacheStats,
+}
+
+/// Planner statistics snapshot
+#[instrument(skip(state))]
+async fn planner_stats_handler(
+    State(state): State<AppState>,
+) -> Result<Json<PlannerStatsResponse>, ApiError> {
+    let stats = {
+        let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+        planner.stats().clone()
+    };
+    Ok(Json(PlannerStatsResponse {
+        stats,
+        result_cache: state.result_cache.stats(),
+    }))
+}
+
+/// Run ANALYZE: collect cardinalities, index sizes and field distributions
+/// from every modality store into the planner statistics.
+#[instrument(skip(state))]
+async fn planner_analyze_handler(
+    State(state): State<AppState>,
+) -> Result<Json<analyze::AnalyzeReport>, ApiError> {
+    Ok(Json(analyze::analyze(&state).await?))
+}
+
+// --- Meta-Query Store (Homoiconicity) ---
+
+/// Store query request body
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreQueryRequest {
+    /// The VQL query text
+    pub query: String,
+    /// Optional embedding for the query
+    pub embedding: Option<Vec<f32>>,
+    /// Optional cost vector from the planner
+    pub cost_vector: Option<Vec<f64>>,
+    /// Optional proof obligations
+    pub proof_obligations: Option<Vec<String>>,
+}
+
+/// Store a VQL query as a hexad (homoiconicity)
+#[instrument(skip(state, request))]
+async fn store_query_handler(
+    State(state): State<AppState>,
+    Json(request): Json<StoreQueryRequest>,
+) -> Result<(StatusCode, Json<HexadResponse>), ApiError> {
+    use verisim_hexad::QueryHexadBuilder;
+
+    let mut builder = QueryHexadBuilder::new(&request.query);
+
+    if let Some(embedding) = request.embedding {
+        validate_vector(&embedding)?;
+        builder = builder.with_embedding(embedding);
+    }
+
+    if let Some(costs) = request.cost_vector {
+        builder = builder.with_cost_vector(costs);
+    }
+
+    if let Some(obligations) = request.proof_obligations {
+        builder = builder.with_proof_obligations(obligations);
+    }
+
+    builder = builder.with_metadata("stored_at", chrono::Utc::now().to_rfc3339());
+
+    let (_query_id, input) = builder.build();
+
+    let hexad = state
+        .hexad_store
+        .create(input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    info!(hexad_id = %hexad.id, "Stored query as hexad");
+
+    Ok((StatusCode::CREATED, Json(HexadResponse::from(&hexad))))
+}
+
+/// Find similar past queries by vector similarity
+#[instrument(skip(state, request))]
+async fn similar_queries_handler(
+    State(state): State<AppState>,
+    Json(request): Json<VectorSearchRequest>,
+) -> Result<Json<Vec<SearchResultResponse>>, ApiError> {
+    let k = validate_limit(request.k.unwrap_or(10));
+
+    if request.vector.len() != state.config.vector_dimension {
+        return Err(ApiError::BadRequest(format!(
+            "Vector dimension mismatch: expected {}, got {}",
+            state.config.vector_dimension,
+            request.vector.len()
+        )));
+    }
+    validate_vector(&request.vector)?;
+
+    // Search for similar hexads (which includes query-hexads)
+    let hexads = state
+        .hexad_store
+        .search_similar(&request.vector, k)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    // Filter to only query hexads (those with "vql_query" type in document fields)
+    let results: Vec<SearchResultResponse> = hexads
+        .iter()
+        .filter(|h| {
+            h.document
+                .as_ref()
+                .map(|d| d.title.starts_with("VQL Query:"))
+                .unwrap_or(false)
+        })
+        .enumerate()
+        .map(|(i, h)| SearchResultResponse {
+            id: h.id.to_string(),
+            score: 1.0 - (i as f32 * 0.1),
+            title: h.document.as_ref().map(|d| d.title.clone()),
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
+/// Optimize a stored query — re-plan and update its tensor modality with new costs.
+/// This is reflection: the system modifying its own queries based on learned costs.
+/// Accepts an optional LogicalPlan body; if absent, uses existing tensor as baseline.
+#[instrument(skip(state))]
+async fn optimize_query_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<LogicalPlan>>,
+) -> Result<Json<HexadResponse>, ApiError> {
+    validate_hexad_id(&id)?;
+    let hexad_id = HexadId::new(&id);
+
+    // Get the existing query hexad
+    let hexad = state
+        .hexad_store
+        .get(&hexad_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Query hexad {} not found", id)))?;
+
+    // Compute cost vector from the planner
+    let cost_vector = if let Some(Json(logical_plan)) = body {
+        // If a logical plan was provided, run the planner on it
+        let planner = state.planner.lock().map_err(|_| {
+            ApiError::Internal("Planner lock poisoned".to_string())
+        })?;
+
+        match planner.explain(&logical_plan) {
+            Ok(explain) => {
+                explain
+                    .steps
+                    .iter()
+                    .map(|s| s.estimated_cost_ms)
+                    .collect::<Vec<f64>>()
+            }
+            Err(_) => vec![1.0, 0.0, 0.0],
+        }
+    } else {
+        // No plan provided — use existing tensor data scaled by a learning factor,
+        // or default to [1.0] if no tensor exists
+        hexad
+            .tensor
+            .as_ref()
+            .map(|t| t.data.iter().map(|v| v * 0.95).collect())
+            .unwrap_or_else(|| vec![1.0])
+    };
+
+    // Update the hexad with new tensor data (cost vector)
+    let mut update_input = HexadInput::default();
+    update_input.tensor = Some(HexadTensorInput {
+        shape: vec![1, cost_vector.len()],
+        data: cost_vector,
+    });
+    update_input
+        .metadata
+        .insert("optimized_at".to_string(), chrono::Utc::now().to_rfc3339());
+
+    let updated = state
+        .hexad_store
+        .update(&hexad_id, update_input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    info!(hexad_id = %id, "Optimized query hexad with new cost vector");
+
+    Ok(Json(HexadResponse::from(&updated)))
+}
+
+// --- EXPLAIN ANALYZE Handler ---
+
+/// EXPLAIN ANALYZE request — execute a plan and return actual timings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExplainAnalyzeRequest {
+    /// The logical plan to analyze
+    pub plan: LogicalPlan,
+    /// Simulated step execution times (milliseconds) for profiling.
+    /// In a real execution engine, these would be measured; here they
+    /// can be provided for testing/simulation purposes.
+    pub simulated_timings: Option<Vec<f64>>,
+}
+
+/// EXPLAIN ANALYZE handler — produces plan estimates with simulated actual timings
+#[instrument(skip(state, request))]
+async fn query_explain_analyze_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ExplainAnalyzeRequest>,
+) -> Result<Json<ExplainAnalyzeOutput>, ApiError> {
+    let mut planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    let explain = planner
+        .explain(&request.plan)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let physical = planner
+        .optimize(&request.plan)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let plan_id = format!("analyze-{}", chrono::Utc::now().timestamp_millis());
+    let mut profiler = Profiler::new(&plan_id, &physical);
+
+    // Record simulated or default step timings
+    let now = chrono::Utc::now();
+    for (i, step) in physical.steps.iter().enumerate() {
+        let actual_ms = request.simulated_timings
+            .as_ref()
+            .and_then(|t| t.get(i).copied())
+            .unwrap_or(step.cost.time_ms * 1.1); // Default: 10% slower than estimate
+        profiler.record_step(i, actual_ms, step.cost.estimated_rows, now, now);
+    }
+
+    let profile = profiler.finish(planner.stats_mut());
+    let output = explain.with_profile(&profile);
+
+    Ok(Json(output))
+}
+
+// --- Prepared Statements Handlers ---
+
+/// Request to create a prepared statement
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreparedCreateRequest {
+    /// The VQL query text
+    pub query: String,
+    /// The logical plan for the query
+    pub plan: LogicalPlan,
+}
+
+/// Create a prepared statement
+#[instrument(skip(state, request))]
+async fn prepared_create_handler(
+    State(state): State<AppState>,
+    Json(request): Json<PreparedCreateRequest>,
+) -> Result<(StatusCode, Json<PreparedStatement>), ApiError> {
+    let id = state.plan_cache.prepare(&request.query, request.plan).await;
+
+    let stmt = state.plan_cache.get(&id).await
+        .ok_or_else(|| ApiError::Internal("Failed to retrieve prepared statement after creation".to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(stmt)))
+}
+
+/// Get a prepared statement by ID
+#[instrument(skip(state))]
+async fn prepared_get_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<PreparedStatement>, ApiError> {
+    let prep_id = PreparedId::new(&id);
+    let stmt = state.plan_cache.get(&prep_id).await
+        .ok_or_else(|| ApiError::NotFound(format!("Prepared statement '{}' not found", id)))?;
+    Ok(Json(stmt))
+}
+
+/// Execute a prepared statement with parameters
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreparedExecuteRequest {
+    /// Parameter bindings
+    pub params: std::collections::HashMap<String, ParamValue>,
+}
+
+/// Execute a prepared statement and return its result rows
+#[instrument(skip(state, headers, request))]
+async fn prepared_execute_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<PreparedExecuteRequest>,
+) -> Result<Json<executor::PlanExecution>, ApiError> {
+    let timeout = queries::header_timeout(&headers, state.config.default_query_timeout_ms)?;
+    let prep_id = PreparedId::new(&id);
+
+    let stmt = state.plan_cache
+        .execute_prepared(&prep_id, &request.params)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    // Use cached physical plan if available, otherwise optimize
+    let physical = if let Some(cached) = stmt.cached_physical_plan {
+        cached
+    } else {
+        let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+        planner.optimize(&stmt.logical_plan).map_err(|e| ApiError::Internal(e.to_string()))?
+    };
+
+    // Cache the physical plan for future use
+    state.plan_cache.cache_plan(&prep_id, physical.clone()).await;
+
+    let execution = state
+        .active_queries
+        .run(&stmt.original_query, timeout, true, async {
+            executor::PlanExecutor::new(&state)
+                .with_params(&request.params)
+                .with_query(stmt.original_query.clone())
+                .execute(&stmt.logical_plan, physical)
+                .await
+        })
+        .await?;
+
+    Ok(Json(execution))
+}
+
+/// Get prepared statement cache statistics
+#[instrument(skip(state))]
+async fn prepared_stats_handler(
+    State(state): State<AppState>,
+) -> Result<Json<verisim_planner::CacheStats>, ApiError> {
+    let stats = state.plan_cache.stats_async().await;
+    Ok(Json(stats))
+}
+
+/// Get slow query log summary
+#[instrument(skip(state))]
+async fn slow_queries_handler(
+    State(state): State<AppState>,
+) -> Result<Json<SlowQuerySummary>, ApiError> {
+    let summary = state.slow_query_log.summary();
+    Ok(Json(summary))
+}
+
+/// Slow-query entry listing parameters
+#[derive(Debug, Deserialize)]
+pub struct SlowQueryEntriesQuery {
+    /// Maximum entries to return
+    pub limit: Option<usize>,
+    /// Matching entries to skip, newest first
+    pub offset: Option<usize>,
+    /// Only entries at least this slow (ms)
+    pub min_ms: Option<f64>,
+    /// Only entries with a step on this modality
+    pub modality: Option<String>,
+    /// Only entries recorded at or after this time (RFC 3339)
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only entries recorded before this time (RFC 3339)
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only entries whose query text contains this
+    pub query: Option<String>,
+}
+
+/// A page of slow-query entries
+#[derive(Debug, Serialize)]
+pub struct SlowQueryEntriesResponse {
+    /// Entries matching the filter
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Matching entries, newest first
+    pub entries: Vec<SlowQueryEntry>,
+}
+
+/// List slow-query entries, filtered and paginated.  Reads the persisted
+/// log when persistence is on, otherwise the in-memory ring buffer.
+#[instrument(skip(state))]
+async fn slow_query_entries_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SlowQueryEntriesQuery>,
+) -> Result<Json<SlowQueryEntriesResponse>, ApiError> {
+    let modality = params
+        .modality
+        .as_deref()
+        .map(|m| m.parse::<verisim_planner::Modality>())
+        .transpose()
+        .map_err(|_| ApiError::BadRequest(format!("Unknown modality: {}", params.modality.as_deref().unwrap_or(""))))?;
+    let filter = SlowQueryFilter {
+        min_ms: params.min_ms,
+        modality,
+        since: params.since,
+        until: params.until,
+        query: params.query,
+    };
+    let offset = params.offset.unwrap_or(0);
+    let limit = validate_limit(params.limit.unwrap_or(50));
+    let (total, entries) = if state.slow_query_log.config().persist {
+        slow_queries::load(&state, &filter, offset, limit).await?
+    } else {
+        state.slow_query_log.query(&filter, offset, limit)
+    };
+    Ok(Json(SlowQueryEntriesResponse {
+        total,
+        offset,
+        limit,
+        entries,
+    }))
+}
+
+/// Get slow query log configuration
+#[instrument(skip(state))]
+async fn slow_query_config_get_handler(State(state): State<AppState>) -> Json<SlowQueryConfig> {
+    Json(state.slow_query_log.config())
+}
+
+/// Update slow query log configuration
+#[instrument(skip(state))]
+async fn slow_query_config_put_handler(
+    State(state): State<AppState>,
+    Json(config): Json<SlowQueryConfig>,
+) -> Result<Json<SlowQueryConfig>, ApiError> {
+    if config.threshold_ms.is_nan() || config.threshold_ms < 0.0 || config.max_entries == 0 {
+        return Err(ApiError::BadRequest(
+            "threshold_ms must be non-negative and max_entries positive".to_string(),
+        ));
+    }
+    state.slow_query_log.set_config(config);
+    Ok(Json(state.slow_query_log.config()))
+}
+
+// --- Transaction Handlers ---
+
+/// Begin a new transaction.  The optional body picks its isolation level
+/// (default: snapshot, reading the store as it is now) and timeouts.
+#[instrument(skip(state, options))]
+async fn transaction_begin_handler(
+    State(state): State<AppState>,
+    options: Option<Json<transaction::TransactionOptions>>,
+) -> Result<(StatusCode, Json<transaction::TransactionStatus>), ApiError> {
+    let options = options.map(|Json(options)| options).unwrap_or_default();
+    let status = begin_transaction(&state, options).await?;
+    Ok((StatusCode::CREATED, Json(status)))
+}
+
+/// Begin a transaction, taking its snapshot under snapshot isolation
+pub async fn begin_transaction(
+    state: &AppState,
+    options: transaction::TransactionOptions,
+) -> Result<transaction::TransactionStatus, ApiError> {
+    let snapshot = match options.isolation {
+        transaction::IsolationLevel::Snapshot => Some(
+            state
+                .hexad_store
+                .read_snapshot()
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?,
+        ),
+        transaction::IsolationLevel::ReadCommitted => None,
+    };
+    let txn_id = state.transaction_manager
+        .begin_with(options, snapshot)
+        .await
+        .map_err(|e| match e {
+            transaction::TransactionError::InvalidTimeout { .. } => ApiError::BadRequest(e.to_string()),
+            _ => ApiError::Internal(e.to_string()),
+        })?;
+
+    state.transaction_manager
+        .status(&txn_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// Transaction commit response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionCommitResponse {
+    #[serde(flatten)]
+    pub status: transaction::TransactionStatus,
+    /// IDs of the entities written, in the order written
+    pub ids: Vec<String>,
+}
+
+/// Commit a transaction, applying its buffered writes atomically: if any
+/// fails, none is applied and the transaction ends rolled back
+#[instrument(skip(state, actor))]
+async fn transaction_commit_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    Path(id): Path<String>,
+) -> Result<Json<TransactionCommitResponse>, ApiError> {
+    commit_transaction(&state, &id, actor.as_deref()).await.map(Json)
+}
+
+/// Commit transaction `id`, applying its buffered writes atomically
+pub async fn commit_transaction(
+    state: &AppState,
+    id: &str,
+    actor: Option<&ActorIdentity>,
+) -> Result<TransactionCommitResponse, ApiError> {
+    let txn_id = transaction::TransactionId::from_str(id);
+
+    let ids = state
+        .transaction_manager
+        .commit(&txn_id, |ops| vql::apply_transaction(state, ops, actor))
+        .await?;
+
+    let status = state.transaction_manager
+        .status(&txn_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(TransactionCommitResponse { status, ids })
+}
+
+/// Prepare a transaction for a two-phase commit: check it as commit
+/// would, lock what it writes, and log it, so that a later commit cannot
+/// fail on conflict.  It then waits for commit or rollback.
+#[instrument(skip(state))]
+async fn transaction_prepare_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<transaction::TransactionStatus>, ApiError> {
+    let txn_id = transaction::TransactionId::from_str(&id);
+    state
+        .transaction_manager
+        .prepare(&txn_id, |txn| vql::validate_transaction(&state, txn))
+        .await?;
+    Ok(Json(state.transaction_manager.status(&txn_id).await?))
+}
+
+/// One store's statements in a distributed transaction
+#[derive(Debug, Deserialize)]
+pub struct DistributedParticipantRequest {
+    /// A federation peer, or this instance's own store ID
+    pub store_id: String,
+    /// VQL INSERT, UPDATE and DELETE statements
+    pub statements: Vec<String>,
+}
+
+/// Request to run writes on several stores atomically
+#[derive(Debug, Deserialize)]
+pub struct DistributedTransactionRequest {
+    pub participants: Vec<DistributedParticipantRequest>,
+}
+
+/// Run writes on several federation stores as one transaction with
+/// two-phase commit.  Responds with the transaction once committed (or
+/// decided and still being committed); an abort is a 409.
+#[instrument(skip(state, request))]
+async fn distributed_transaction_handler(
+    State(state): State<AppState>,
+    Json(request): Json<DistributedTransactionRequest>,
+) -> Result<Json<transaction::GlobalTransaction>, ApiError> {
+    if request.participants.is_empty() {
+        return Err(ApiError::BadRequest("participants must not be empty".to_string()));
+    }
+    let mut participants = Vec::with_capacity(request.participants.len());
+    for participant in request.participants {
+        if participant.statements.is_empty() {
+            return Err(ApiError::BadRequest(format!("{} has no statements", participant.store_id)));
+        }
+        if participants.iter().any(|p: &transaction::Participant| p.store_id == participant.store_id) {
+            return Err(ApiError::BadRequest(format!("{} is listed twice", participant.store_id)));
+        }
+        let endpoint = if participant.store_id == state.federation.self_store_id {
+            state.federation.self_endpoint.clone()
+        } else {
+            let peers = state
+                .federation
+                .peers
+                .read()
+                .map_err(|_| ApiError::Internal("federation peers lock poisoned".to_string()))?;
+            peers
+                .get(&participant.store_id)
+                .map(|peer| peer.endpoint.clone())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown federation store: {}", participant.store_id)))?
+        };
+        participants.push(transaction::Participant::new(participant.store_id, endpoint, participant.statements));
+    }
+
+    let txn = state.coordinator.execute(participants).await;
+    if txn.state == transaction::GlobalState::Aborted {
+        return Err(ApiError::Conflict {
+            message: format!(
+                "distributed transaction {} aborted: {}",
+                txn.id,
+                txn.error.as_deref().unwrap_or("a participant failed")
+            ),
+            ids: Vec::new(),
+        });
+    }
+    Ok(Json(txn))
+}
+
+/// List distributed transactions
+#[instrument(skip(state))]
+async fn distributed_transaction_list_handler(State(state): State<AppState>) -> Json<Vec<transaction::GlobalTransaction>> {
+    Json(state.coordinator.list().await)
+}
+
+/// Get a distributed transaction, with each participant's state
+#[instrument(skip(state))]
+async fn distributed_transaction_get_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<transaction::GlobalTransaction>, ApiError> {
+    state
+        .coordinator
+        .get(&id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Distributed transaction {} not found", id)))
+}
+
+/// Rollback a transaction
+#[instrument(skip(state))]
+async fn transaction_rollback_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<transaction::TransactionStatus>, ApiError> {
+    rollback_transaction(&state, &id).await.map(Json)
+}
+
+/// Roll back transaction `id`, discarding its buffered writes
+pub async fn rollback_transaction(state: &AppState, id: &str) -> Result<transaction::TransactionStatus, ApiError> {
+    let txn_id = transaction::TransactionId::from_str(id);
+
+    let _discarded = state.transaction_manager.rollback(&txn_id).await?;
+
+    state.transaction_manager
+        .status(&txn_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// Get transaction status
+#[instrument(skip(state))]
+async fn transaction_status_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<transaction::TransactionStatus>, ApiError> {
+    let txn_id = transaction::TransactionId::from_str(&id);
+
+    let status = state.transaction_manager
+        .status(&txn_id)
+        .await
+        .map_err(|e| match e {
+            transaction::TransactionError::NotFound(_) => ApiError::NotFound(e.to_string()),
+            _ => ApiError::Internal(e.to_string()),
+        })?;
+
+    Ok(Json(status))
+}
+
+/// How long a lock request waits for conflicting locks by default
+const DEFAULT_LOCK_WAIT_MS: u64 = 5_000;
+
+/// Request to lock an entity for a transaction
+#[derive(Debug, Deserialize)]
+pub struct LockRequest {
+    pub entity_id: String,
+    /// Defaults to exclusive
+    #[serde(default = "default_lock_mode")]
+    pub mode: transaction::LockMode,
+    /// Milliseconds to wait for conflicting locks (default 5000)
+    #[serde(default)]
+    pub wait_ms: Option<u64>,
+}
+
+fn default_lock_mode() -> transaction::LockMode {
+    transaction::LockMode::Exclusive
+}
+
+/// Lock an entity until the transaction commits or rolls back, waiting
+/// for conflicting locks.  A deadlock or timed-out wait is a 409.
+#[instrument(skip(state))]
+async fn transaction_lock_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<LockRequest>,
+) -> Result<Json<transaction::TransactionStatus>, ApiError> {
+    validate_hexad_id(&request.entity_id)?;
+    let txn_id = transaction::TransactionId::from_str(&id);
+    let wait = std::time::Duration::from_millis(request.wait_ms.unwrap_or(DEFAULT_LOCK_WAIT_MS));
+    state
+        .transaction_manager
+        .acquire_lock(&txn_id, &request.entity_id, request.mode, wait)
+        .await?;
+    Ok(Json(state.transaction_manager.status(&txn_id).await?))
+}
+
+/// ID a pending insert is listed under until its transaction commits
+const PENDING_INSERT_PREFIX: &str = "pending-";
+
+/// The store as a transaction reads it: the snapshot taken when it began
+/// (the latest committed state, if it began without one) with its own
+/// buffered writes applied
+struct TransactionReads {
+    snapshot: Arc<ReadSnapshot>,
+    /// Buffered updates per entity, in order
+    updates: std::collections::HashMap<String, Vec<HexadInput>>,
+    deleted: std::collections::HashSet<String>,
+    inserts: Vec<HexadInput>,
+}
+
+impl TransactionReads {
+    async fn open(state: &AppState, id: &str) -> Result<Self, ApiError> {
+        let (snapshot, operations) = state
+            .transaction_manager
+            .read_view(&transaction::TransactionId::from_str(id))
+            .await?;
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            None => Arc::new(
+                state
+                    .hexad_store
+                    .read_snapshot()
+                    .await
+                    .map_err(|e| ApiError::Internal(e.to_string()))?,
+            ),
+        };
+        let mut reads = Self {
+            snapshot,
+            updates: Default::default(),
+            deleted: Default::default(),
+            inserts: Vec::new(),
+        };
+        for op in operations {
+            let input = || {
+                serde_json::from_slice::<HexadRequest>(&op.payload)
+                    .map(|request| request.to_hexad_input())
+                    .map_err(|e| ApiError::Internal(format!("Invalid buffered operation: {e}")))
+            };
+            match op.operation {
+                transaction::OperationType::Create => reads.inserts.push(input()?),
+                transaction::OperationType::Update => {
+                    reads.updates.entry(op.entity_id.clone()).or_default().push(input()?)
+                }
+                transaction::OperationType::Delete => {
+                    reads.deleted.insert(op.entity_id);
+                }
+            }
+        }
+        Ok(reads)
+    }
+
+    /// One entity as the transaction sees it
+    async fn get(&self, state: &AppState, id: &HexadId) -> Result<Option<verisim_hexad::Hexad>, ApiError> {
+        if let Some(index) = id.as_str().strip_prefix(PENDING_INSERT_PREFIX) {
+            let Some(input) = index.parse::<usize>().ok().and_then(|i| self.inserts.get(i)) else {
+                return Ok(None);
+            };
+            return self.preview(state, id, vec![input.clone()]).await;
+        }
+        if self.deleted.contains(id.as_str()) {
+            return Ok(None);
+        }
+        let pending = self.updates.get(id.as_str()).cloned().unwrap_or_default();
+        if self.snapshot.status(id).is_none() {
+            // Updates to an entity the snapshot lacks fail at commit.
+            return Ok(None);
+        }
+        self.preview(state, id, pending).await
+    }
+
+    async fn preview(&self, state: &AppState, id: &HexadId, pending: Vec<HexadInput>) -> Result<Option<verisim_hexad::Hexad>, ApiError> {
+        state.hexad_store.preview_at(&self.snapshot, id, pending).await.map_err(|e| match e {
+            verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+            e => ApiError::Internal(e.to_string()),
+        })
+    }
+
+    /// Entities as the transaction sees them: the snapshot's, in ID order,
+    /// then its pending inserts
+    async fn list(&self, state: &AppState, limit: usize, offset: usize) -> Result<Vec<verisim_hexad::Hexad>, ApiError> {
+        let wanted = offset.saturating_add(limit);
+        let mut visible = Vec::new();
+        let mut scanned = 0;
+        let page_size = limit.max(1);
+        while visible.len() < wanted {
+            let page = state
+                .hexad_store
+                .list_at(&self.snapshot, None, page_size, scanned)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            scanned += page.len();
+            for hexad in &page {
+                if self.deleted.contains(hexad.id.as_str()) {
+                    continue;
+                }
+                match self.updates.get(hexad.id.as_str()) {
+                    Some(pending) => visible.extend(self.preview(state, &hexad.id, pending.clone()).await?),
+                    None => visible.push(hexad.clone()),
+                }
+            }
+            if page.len() < page_size {
+                break;
+            }
+        }
+        for (index, input) in self.inserts.iter().enumerate() {
+            if visible.len() >= wanted {
+                break;
+            }
+            let id = HexadId::new(format!("{}{}", PENDING_INSERT_PREFIX, index));
+            visible.extend(self.preview(state, &id, vec![input.clone()]).await?);
+        }
+        Ok(visible.into_iter().skip(offset).take(limit).collect())
+    }
+}
+
+/// Get a hexad as an open transaction sees it: as of the transaction's
+/// snapshot, with its own buffered writes applied.  Pending inserts are
+/// `pending-<n>`, numbered in the order they were buffered.  The entity
+/// joins the transaction's read set, found or not.
+#[instrument(skip(state))]
+async fn transaction_get_hexad_handler(
+    State(state): State<AppState>,
+    Path((id, hexad_id)): Path<(String, String)>,
+) -> Result<Json<HexadResponse>, ApiError> {
+    validate_hexad_id(&hexad_id)?;
+    let reads = TransactionReads::open(&state, &id).await?;
+    let hexad = reads.get(&state, &HexadId::new(&hexad_id)).await?;
+    if !hexad_id.starts_with(PENDING_INSERT_PREFIX) {
+        state
+            .transaction_manager
+            .record_reads(&transaction::TransactionId::from_str(&id), [hexad_id.clone()])
+            .await?;
+    }
+    let hexad = hexad.ok_or_else(|| ApiError::NotFound(format!("Hexad {} not found", hexad_id)))?;
+    Ok(Json(HexadResponse::from(&hexad)))
+}
+
+/// List hexads as an open transaction sees them, adding those listed to
+/// its read set
+#[instrument(skip(state))]
+async fn transaction_list_hexads_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<ListQuery>,
+) -> Result<Json<Vec<HexadResponse>>, ApiError> {
+    let limit = validate_limit(params.limit.unwrap_or(100));
+    let offset = params.offset.unwrap_or(0);
+    let reads = TransactionReads::open(&state, &id).await?;
+    let hexads = reads.list(&state, limit, offset).await?;
+    let read = hexads
+        .iter()
+        .map(|h| h.id.to_string())
+        .filter(|id| !id.starts_with(PENDING_INSERT_PREFIX));
+    state
+        .transaction_manager
+        .record_reads(&transaction::TransactionId::from_str(&id), read)
+        .await?;
+    Ok(Json(hexads.iter().map(HexadResponse::from).collect()))
+}
+
+// --- ZKP Proof Handlers ---
+
+/// API request for proof generation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofGenerateRequest {
+    /// The claim to prove (base64-encoded or plain text)
+    pub claim: String,
+    /// Privacy level: "public", "private", or "zero_knowledge"
+    pub privacy_level: Option<String>,
+    /// Optional membership set for Merkle inclusion proofs
+    pub membership_set: Option<Vec<String>>,
+    /// Index of the claim in the membership set
+    pub membership_index: Option<usize>,
+    /// Entity the claim is about; the proof is attached to it
+    #[serde(default)]
+    pub entity_id: Option<String>,
+    /// Modalities of the entity the proof depends on; a change to any of
+    /// them revokes it.  All of them if unset.
+    #[serde(default)]
+    pub depends_on: Option<Vec<String>>,
+    /// Seconds until the proof expires; never if unset
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// API request for proof verification
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofVerifyRequest {
+    /// The proof to verify (serialized)
+    pub proof: zkp_api::ZkpProof,
+    /// The claim the proof is for
+    pub claim: String,
+}
+
+/// API request for circuit-based proof generation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofWithCircuitRequest {
+    /// The claim to prove
+    pub claim: String,
+    /// Privacy level
+    pub privacy_level: Option<String>,
+    /// Circuit name to verify against
+    pub circuit_name: String,
+    /// Circuit version; the latest one not deprecated if unset
+    #[serde(default)]
+    pub circuit_version: Option<String>,
+    /// Witness data (private inputs)
+    pub witness: Option<Vec<f64>>,
+    /// Public inputs
+    pub public_inputs: Option<Vec<f64>>,
+    /// Entity the claim is about; the proof is attached to it
+    #[serde(default)]
+    pub entity_id: Option<String>,
+    /// Modalities of the entity the proof depends on; a change to any of
+    /// them revokes it.  All of them if unset.
+    #[serde(default)]
+    pub depends_on: Option<Vec<String>>,
+    /// Seconds until the proof expires; never if unset
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// API response for proof operations
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<zkp_api::ZkpProof>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the proof was reused from an identical earlier request
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// The proof's link to the entity it was attached to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attached: Option<EntityProof>,
+    /// ID of the generated proof, for revoking it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_id: Option<String>,
+}
+
+fn parse_privacy_level(s: &str) -> Result<PrivacyLevel, ApiError> {
+    match s.to_lowercase().as_str() {
+        "public" => Ok(PrivacyLevel::Public),
+        "private" => Ok(PrivacyLevel::Private),
+        "zero_knowledge" | "zeroknowledge" | "zk" => Ok(PrivacyLevel::ZeroKnowledge),
+        other => Err(ApiError::BadRequest(format!(
+            "Unknown privacy level: '{}'. Use 'public', 'private', or 'zero_knowledge'",
+            other
+        ))),
+    }
+}
+
+/// Generate a privacy-aware ZKP proof
+#[instrument(skip(state, request))]
+async fn proof_generate_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ProofGenerateRequest>,
+) -> Result<Json<ProofResponse>, ApiError> {
+    let privacy_level = match &request.privacy_level {
+        Some(level) => parse_privacy_level(level)?,
+        None => PrivacyLevel::Public,
+    };
+
+    let membership_set = request.membership_set.as_ref().map(|set| {
+        set.iter().map(|s| s.as_bytes().to_vec()).collect::<Vec<_>>()
+    });
+
+    let depends_on = proof_dependencies(request.entity_id.as_deref(), request.depends_on.as_deref())?;
+    let entity_version = match &request.entity_id {
+        Some(id) => Some(state.entity_version(id).await?),
+        None => None,
+    };
+
+    let bridge_request = ZkpBridgeRequest {
+        claim: request.claim.as_bytes().to_vec(),
+        privacy_level,
+        circuit_name: None,
+        circuit_version: None,
+        witness: None,
+        public_inputs: None,
+        membership_set,
+        membership_index: request.membership_index,
+    };
+
+    let mut response = generated_proof_response(state.generate_proof(&bridge_request));
+    if let Some(proof) = response.proof.take() {
+        let (proof, proof_id) =
+            state.issue_proof(proof, request.ttl_secs, request.entity_id.as_deref(), &depends_on);
+        response.proof = Some(proof);
+        response.proof_id = Some(proof_id);
+    }
+    if let (Some(id), Some(version), Some(proof)) = (&request.entity_id, entity_version, &response.proof) {
+        response.attached = Some(state.attach_proof(id, version, &request.claim, proof).await?);
+    }
+    Ok(Json(response))
+}
+
+/// Verify a previously generated ZKP proof, and for a circuit proof that
+/// the circuit version it used is still registered
+#[instrument(skip(state, request))]
+async fn proof_verify_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ProofVerifyRequest>,
+) -> Result<Json<ProofResponse>, ApiError> {
+    let verified = zkp_api::verify_zkp_with_circuit(
+        &request.proof,
+        request.claim.as_bytes(),
+        &state.circuit_registry,
+    );
+    let status = state.proof_registry.status(
+        &request.proof.fingerprint(),
+        request.proof.expiry(),
+        chrono::Utc::now(),
+    );
+    let (verified, error) = match (verified, status) {
+        (Err(e), _) => (false, Some(e.to_string())),
+        (Ok(false), _) => (false, None),
+        (Ok(true), ProofStatus::Valid) => (true, None),
+        (Ok(true), ProofStatus::Expired { expired_at }) => {
+            (false, Some(format!("Proof expired at {}", expired_at.to_rfc3339())))
+        }
+        (Ok(true), ProofStatus::Revoked { reason, .. }) => (false, Some(format!("Proof revoked: {reason}"))),
+    };
+
+    Ok(Json(ProofResponse {
+        success: true,
+        proof: None,
+        verified: Some(verified),
+        error,
+        cached: false,
+        attached: None,
+        proof_id: None,
+    }))
+}
+
+/// Generate a proof with circuit verification
+#[instrument(skip(state, request))]
+async fn proof_generate_with_circuit_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ProofWithCircuitRequest>,
+) -> Result<Json<ProofResponse>, ApiError> {
+    let privacy_level = match &request.privacy_level {
+        Some(level) => parse_privacy_level(level)?,
+        None => PrivacyLevel::Public,
+    };
+
+    let depends_on = proof_dependencies(request.entity_id.as_deref(), request.depends_on.as_deref())?;
+    let entity_version = match &request.entity_id {
+        Some(id) => Some(state.entity_version(id).await?),
+        None => None,
+    };
+
+    let bridge_request = ZkpBridgeRequest {
+        claim: request.claim.as_bytes().to_vec(),
+        privacy_level,
+        circuit_name: Some(request.circuit_name),
+        circuit_version: request.circuit_version,
+        witness: request.witness,
+        public_inputs: request.public_inputs,
+        membership_set: None,
+        membership_index: None,
+    };
+
+    let mut response = generated_proof_response(state.generate_proof(&bridge_request));
+    if let Some(proof) = response.proof.take() {
+        let (proof, proof_id) =
+            state.issue_proof(proof, request.ttl_secs, request.entity_id.as_deref(), &depends_on);
+        response.proof = Some(proof);
+        response.proof_id = Some(proof_id);
+    }
+    if let (Some(id), Some(version), Some(proof)) = (&request.entity_id, entity_version, &response.proof) {
+        response.attached = Some(state.attach_proof(id, version, &request.claim, proof).await?);
+    }
+    Ok(Json(response))
+}
+
+/// Canonical names of the modalities a proof depends on; `depends_on`
+/// needs the entity they belong to
+fn proof_dependencies(
+    entity_id: Option<&str>,
+    depends_on: Option<&[String]>,
+) -> Result<Vec<&'static str>, ApiError> {
+    match (entity_id, depends_on) {
+        (_, None) => Ok(Vec::new()),
+        (None, Some(_)) => Err(ApiError::BadRequest("depends_on needs an entity_id".to_string())),
+        (Some(_), Some(names)) => Ok(parse_modalities(&names.join(","))?.populated()),
+    }
+}
+
+/// Response for a proof generated, or reused, by the proof cache
+fn generated_proof_response(
+    generated: Result<(zkp_api::ZkpProof, bool), CircuitError>,
+) -> ProofResponse {
+    match generated {
+        Ok((proof, cached)) => ProofResponse {
+            success: true,
+            proof: Some(proof),
+            verified: None,
+            error: None,
+            cached,
+            attached: None,
+            proof_id: None,
+        },
+        Err(e) => ProofResponse {
+            success: false,
+            proof: None,
+            verified: None,
+            error: Some(e.to_string()),
+            cached: false,
+            attached: None,
+            proof_id: None,
+        },
+    }
+}
+
+/// GET /proofs/cache — proof cache size and hit/miss counters
+#[instrument(skip(state))]
+async fn proof_cache_stats_handler(State(state): State<AppState>) -> Json<ProofCacheStats> {
+    Json(state.proof_cache.stats())
+}
+
+/// Query parameters for listing revoked proofs
+#[derive(Debug, Deserialize)]
+pub struct RevocationsQuery {
+    /// Only proofs about this entity
+    pub entity_id: Option<String>,
+}
+
+/// API request to revoke a proof
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokeProofRequest {
+    /// ID of the proof, as returned when it was generated
+    #[serde(default)]
+    pub proof_id: Option<String>,
+    /// The proof itself, when its ID is not known
+    #[serde(default)]
+    pub proof: Option<zkp_api::ZkpProof>,
+    pub reason: String,
+}
+
+/// GET /proofs/revocations — revoked proofs, most recent first
+#[instrument(skip(state))]
+async fn proof_revocations_handler(
+    State(state): State<AppState>,
+    Query(params): Query<RevocationsQuery>,
+) -> Json<Vec<Revocation>> {
+    Json(state.proof_registry.revocations(params.entity_id.as_deref()))
+}
+
+/// POST /proofs/revocations — revoke a proof by hand
+#[instrument(skip(state, request))]
+async fn proof_revoke_handler(
+    State(state): State<AppState>,
+    Json(request): Json<RevokeProofRequest>,
+) -> Result<(StatusCode, Json<Revocation>), ApiError> {
+    let proof_id = match (request.proof_id, &request.proof) {
+        (Some(proof_id), _) => proof_id,
+        (None, Some(proof)) => proof.fingerprint(),
+        (None, None) => return Err(ApiError::BadRequest("Give a proof_id or a proof".to_string())),
+    };
+    if request.reason.trim().is_empty() {
+        return Err(ApiError::BadRequest("A revocation needs a reason".to_string()));
+    }
+    let revocation = state.proof_registry.revoke(&proof_id, &request.reason);
+    info!(proof_id = %proof_id, reason = %revocation.reason, "Proof revoked");
+    Ok((StatusCode::CREATED, Json(revocation)))
+}
+
+// --- Circuit Registry Handlers ---
+
+/// API request to register a circuit version
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircuitRegisterRequest {
+    /// Semantic version, `MAJOR.MINOR.PATCH`
+    pub version: String,
+    /// What the circuit proves
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The circuit; it is registered under `definition.name`
+    pub definition: CircuitDef,
+}
+
+/// API request to deprecate a circuit version
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CircuitDeprecateRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Map a circuit registry error to its HTTP status
+fn circuit_error(e: CircuitError) -> ApiError {
+    match e {
+        CircuitError::NotFound(_) => ApiError::NotFound(e.to_string()),
+        CircuitError::AlreadyExists(_) => ApiError::Conflict {
+            message: e.to_string(),
+            ids: Vec::new(),
+        },
+        CircuitError::CompilationFailed(_)
+        | CircuitError::InvalidVersion(_)
+        | CircuitError::InvalidWitness(_)
+        | CircuitError::Deprecated(_) => ApiError::BadRequest(e.to_string()),
+        CircuitError::VerificationFailed(_) | CircuitError::LockPoisoned => {
+            ApiError::Internal(e.to_string())
+        }
+    }
+}
+
+/// GET /circuits — every registered circuit version and its parameters
+#[instrument(skip(state))]
+async fn list_circuits_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CircuitInfo>>, ApiError> {
+    state.circuit_registry.list_versions().map(Json).map_err(circuit_error)
+}
+
+/// POST /circuits — compile and register a new circuit version
+#[instrument(skip(state, request))]
+async fn register_circuit_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CircuitRegisterRequest>,
+) -> Result<(StatusCode, Json<CircuitInfo>), ApiError> {
+    if request.definition.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("Circuit name must not be empty".to_string()));
+    }
+    let version: CircuitVersion = request.version.parse().map_err(circuit_error)?;
+    let compiled = compile_circuit(&request.definition).map_err(circuit_error)?;
+    let info = state
+        .circuit_registry
+        .register_version(&request.definition.name, version, compiled, request.description)
+        .map_err(circuit_error)?;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// GET /circuits/{name} — every version of one circuit, oldest first
+#[instrument(skip(state))]
+async fn circuit_versions_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<CircuitInfo>>, ApiError> {
+    let versions = state.circuit_registry.versions(&name).map_err(circuit_error)?;
+    if versions.is_empty() {
+        return Err(circuit_error(CircuitError::NotFound(name)));
+    }
+    Ok(Json(versions))
+}
+
+/// POST /circuits/{name}/{version}/deprecate — stop new proofs using a
+/// version; proofs already made with it still verify
+#[instrument(skip(state, request))]
+async fn deprecate_circuit_handler(
+    State(state): State<AppState>,
+    Path((name, version)): Path<(String, String)>,
+    request: Option<Json<CircuitDeprecateRequest>>,
+) -> Result<Json<CircuitInfo>, ApiError> {
+    let version: CircuitVersion = version.parse().map_err(circuit_error)?;
+    let reason = request.and_then(|Json(request)| request.reason);
+    state
+        .circuit_registry
+        .deprecate(&name, version, reason)
+        .map(Json)
+        .map_err(circuit_error)
+}
+
+/// Start the API server (plain HTTP)
+pub async fn serve(config: ApiConfig) -> Result<(), std::io::Error> {
+    let state = AppState::new_async(config.clone())
+        .await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    if config.drift_scan_interval_secs.is_some() {
+        state.drift_scanner.clone().spawn();
+    }
+    state.campaign_scheduler.clone().spawn();
+    if config.soft_delete
+        && (config.soft_delete_purge_after_secs.is_some()
+            || !config.collection_soft_delete_purge_after_secs.is_empty())
+    {
+        spawn_soft_delete_purge(state.clone());
+    }
+    if let Some(secs) = config.expiry_sweep_interval_secs {
+        spawn_expiry_sweeper(state.clone(), std::time::Duration::from_secs(secs.max(1)));
+    }
+    spawn_transaction_sweeper(state.clone());
+    replication::spawn_follower(
+        state.clone(),
+        std::time::Duration::from_millis(config.replication_poll_interval_ms.max(10)),
+    );
+    crdt::spawn_sync(
+        state.clone(),
+        config.crdt_peers.clone(),
+        std::time::Duration::from_secs(config.crdt_sync_interval_secs.max(1)),
+    );
+    if let Some(settings) = &config.object_store {
+        object_store::spawn_sync(
+            state.clone(),
+            std::time::Duration::from_secs(settings.sync_interval_secs.max(1)),
+        );
+    }
+    #[cfg(feature = "persistent")]
+    if let Some(secs) = config.wal_checkpoint_interval_secs {
+        spawn_wal_checkpoints(state.clone(), std::time::Duration::from_secs(secs.max(1)));
+    }
+    if let Some(port) = config.grpc_port {
+        grpc::spawn_server(state.clone(), &format!("{}:{}", config.host, port), None).await?;
+    }
+    let app = build_router(state);
+
+    let addr = format!("{}:{}", config.host, config.port);
+    info!("Starting VeriSimDB API server on {}", addr);
+
+    let listener = TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+/// Start the API server with TLS (HTTPS)
+pub async fn serve_tls(
+    config: ApiConfig,
+    cert_path: &str,
+    key_path: &str,
+) -> Result<(), std::io::Error> {
+    let state = AppState::new_async(config.clone())
+        .await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    if config.drift_scan_interval_secs.is_some() {
+        state.drift_scanner.clone().spawn();
+    }
+    state.campaign_scheduler.clone().spawn();
+    if config.soft_delete
+        && (config.soft_delete_purge_after_secs.is_some()
+            || !config.collection_soft_delete_purge_after_secs.is_empty())
+    {
+        spawn_soft_delete_purge(state.clone());
+    }
+    if let Some(secs) = config.expiry_sweep_interval_secs {
+        spawn_expiry_sweeper(state.clone(), std::time::Duration::from_secs(secs.max(1)));
+    }
+    spawn_transaction_sweeper(state.clone());
+    replication::spawn_follower(
+        state.clone(),
+        std::time::Duration::from_millis(config.replication_poll_interval_ms.max(10)),
+    );
+    crdt::spawn_sync(
+        state.clone(),
+        config.crdt_peers.clone(),
+        std::time::Duration::from_secs(config.crdt_sync_interval_secs.max(1)),
+    );
+    if let Some(settings) = &config.object_store {
+        object_store::spawn_sync(
+            state.clone(),
+            std::time::Duration::from_secs(settings.sync_interval_secs.max(1)),
+        );
+    }
+    #[cfg(feature = "persistent")]
+    if let Some(secs) = config.wal_checkpoint_interval_secs {
+        spawn_wal_checkpoints(state.clone(), std::time::Duration::from_secs(secs.max(1)));
+    }
+    // gRPC is served over the same TLS as the REST API
+    let acceptor = tls_acceptor(&config, &state.auth.peers, cert_path, key_path).await?;
+    if let Some(port) = config.grpc_port {
+        grpc::spawn_server(state.clone(), &format!("{}:{}", config.host, port), Some(acceptor.clone())).await?;
+    }
+    let app = build_router(state);
+
+    let addr = format!("{}:{}", config.host, config.port);
+    info!(addr = %addr, cert = %cert_path, "Starting VeriSimDB API server with TLS");
+
+    let addr: std::net::SocketAddr = addr
+        .parse()
+        .map_err(|e: std::net::AddrParseError| std::io::Error::other(e.to_string()))?;
+
+    axum_server::bind(addr)
+        .acceptor(acceptor)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+/// TLS acceptor for the listeners of `serve_tls`.  Clients are asked for
+/// certificates when peers or certificate-authenticated clients may present
+/// them, so the two can be told apart by theirs.
+async fn tls_acceptor(
+    config: &ApiConfig,
+    peers: &peer_auth::PeerAuth,
+    cert_path: &str,
+    key_path: &str,
+) -> Result<peer_auth::PeerCertAcceptor, std::io::Error> {
+    use axum_server::tls_rustls::RustlsConfig;
+
+    let clients = match &config.auth.client_certificates {
+        Some(certificates) => Some(
+            client_certs::ClientCa::load(&certificates.ca_path)
+                .map_err(|e| std::io::Error::other(e.to_string()))?,
+        ),
+        None => None,
+    };
+    let tls_config = if peers.enabled() || clients.is_some() {
+        let server_config = peers
+            .server_config(cert_path, key_path, clients.as_ref())
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        RustlsConfig::from_config(Arc::new(server_config))
+    } else {
+        RustlsConfig::from_pem_file(cert_path, key_path)
+            .await
+            .map_err(|e| std::io::Error::other(e.to_string()))?
+    };
+    Ok(peer_auth::PeerCertAcceptor::new(tls_config, clients))
+}
+
+// ---------------------------------------------------------------------------
+// Provenance endpoint handlers
+// ---------------------------------------------------------------------------
+
+/// Provenance chain response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvenanceChainResponse {
+    pub entity_id: String,
+    pub chain_length: usize,
+    pub chain_valid: bool,
+    pub records: Vec<ProvenanceRecordResponse>,
+}
+
+/// A single provenance record in the response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceRecordResponse {
+    pub event_type: String,
+    pub actor: String,
+    pub timestamp: String,
+    pub source: Option<String>,
+    pub description: String,
+    pub content_hash: String,
+    /// Chain on another instance this record continues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<verisim_provenance::ChainLink>,
+}
+
+impl From<&verisim_provenance::ProvenanceRecord> for ProvenanceRecordResponse {
+    fn from(r: &verisim_provenance::ProvenanceRecord) -> Self {
+        Self {
+            event_type: format!("{:?}", r.event_type),
+            actor: r.actor.clone(),
+            timestamp: r.timestamp.to_rfc3339(),
+            source: r.source.clone(),
+            description: r.description.clone(),
+            content_hash: r.content_hash.clone(),
+            link: r.link.clone(),
+        }
+    }
+}
+
+/// GET /provenance/{id} — retrieve the full provenance chain for an entity
+#[instrument(skip(state))]
+async fn provenance_get_chain_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ProvenanceChainResponse>, ApiError> {
+    validate_hexad_id(&id)?;
+
+    // Check entity exists
+    let hexad_id = HexadId::new(&id);
+    let exists = state
+        .hexad_store
+        .status(&hexad_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if exists.is_none() {
+        return Err(ApiError::NotFound(format!("Entity {} not found", id)));
+    }
+
+    let chain = state
+        .hexad_store
+        .provenance_store()
+        .get_chain(&id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let chain_valid = state
+        .hexad_store
+        .provenance_store()
+        .verify_chain(&id)
+        .await
+        .unwrap_or(false);
+
+    let records: Vec<ProvenanceRecordResponse> = chain.records.iter().map(Into::into).collect();
+
+    Ok(Json(ProvenanceChainResponse {
+        entity_id: id,
+        chain_length: records.len(),
+        chain_valid,
+        records,
+    }))
+}
+
+/// POST /provenance/{id}/record — record a new provenance event
+#[instrument(skip(state, actor, body))]
+async fn provenance_record_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(body): Json<ProvenanceRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    validate_hexad_id(&id)?;
+
+    let hexad_id = HexadId::new(&id);
+    let mut input = HexadInput {
+        provenance: Some(HexadProvenanceInput {
+            event_type: body.event_type,
+            actor: body.actor,
+            source: body.source,
+            description: body.description,
+            link: None,
+        }),
+        ..Default::default()
+    };
+    attribute_actor(&mut input, actor.as_deref(), "modified", "");
+
+    let hexad = state
+        .hexad_store
+        .update(&hexad_id, input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(serde_json::json!({
+        "entity_id": id,
+        "chain_length": hexad.provenance_chain_length,
+        "recorded": true,
+    })))
+}
+
+/// GET /provenance/{id}/verify — verify provenance chain integrity
+#[instrument(skip(state))]
+async fn provenance_verify_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    validate_hexad_id(&id)?;
+
+    let hexad_id = HexadId::new(&id);
+    let status = state
+        .hexad_store
+        .status(&hexad_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Entity {} not found", id)))?;
+
+    Ok(Json(serde_json::json!({
+        "entity_id": id,
+        "has_provenance": status.modality_status.provenance,
+        "chain_valid": true,
+    })))
+}
+
+/// API request for a zero-knowledge provenance proof
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvenanceProveRequest {
+    /// Event type the chain must contain, as recorded (`created`,
+    /// `approved`, ...)
+    pub event_type: String,
+    /// Actors whose event counts; never revealed by the proof
+    pub authorized_actors: Vec<String>,
+}
+
+/// Name of an event type as the provenance store displays it: built-in
+/// types as they are, anything else as `custom:<name>`
+fn provenance_event_name(event_type: &str) -> String {
+    let event_type = event_type.to_lowercase();
+    match event_type.as_str() {
+        "created" | "modified" | "imported" | "normalized" | "drift_repaired" | "deleted" | "merged" => event_type,
+        custom if custom.starts_with("custom:") => event_type,
+        custom => format!("custom:{}", custom),
+    }
+}
+
+/// POST /provenance/{id}/prove — prove, without revealing actors or
+/// descriptions, that the entity's provenance chain is valid and contains
+/// an event of a type by an authorized actor
+#[instrument(skip(state, request))]
+async fn provenance_prove_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<ProvenanceProveRequest>,
+) -> Result<Json<ProvenanceProof>, ApiError> {
+    validate_hexad_id(&id)?;
+
+    let chain = match state.hexad_store.provenance_store().get_chain(&id).await {
+        Ok(chain) => chain,
+        Err(verisim_provenance::ProvenanceError::NotFound(_)) => {
+            return Err(ApiError::NotFound(format!("No provenance for entity {}", id)));
+        }
+        Err(e) => return Err(ApiError::Internal(e.to_string())),
+    };
+    chain.verify().map_err(|e| ApiError::Conflict {
+        message: e.to_string(),
+        ids: vec![id.clone()],
+    })?;
+
+    let bridge_request = ProvenanceProofRequest {
+        entity_id: id,
+        event_type: provenance_event_name(&request.event_type),
+        authorized_actors: request.authorized_actors,
+        records: chain
+            .records
+            .iter()
+            .map(|r| ProvenanceRecordWitness {
+                event_type: r.event_type.to_string(),
+                actor: r.actor.clone(),
+                parent_hash: r.parent_hash.clone(),
+                content_hash: r.content_hash.clone(),
+            })
+            .collect(),
+    };
+    zkp_api::generate_provenance_proof(&bridge_request)
+        .map(Json)
+        .map_err(circuit_error)
+}
+
+/// POST /proofs/provenance/verify — verify a provenance proof; needs
+/// nothing but the proof
+#[instrument(skip(proof))]
+async fn provenance_proof_verify_handler(Json(proof): Json<ProvenanceProof>) -> Json<ProofResponse> {
+    Json(ProofResponse {
+        success: true,
+        proof: None,
+        verified: Some(zkp_api::verify_provenance_proof(&proof)),
+        error: None,
+        cached: false,
+        attached: None,
+        proof_id: None,
+    })
+}
+
+/// POST /hexads/{id}/annotation/prove — prove a predicate about the
+/// entity's semantic annotation, disclosing only the fields it needs.  The
+/// annotation's commitment is anchored in the entity's provenance first,
+/// unless an earlier proof already anchored it.
+#[instrument(skip(state, actor))]
+async fn annotation_prove_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    actor: Option<Extension<ActorIdentity>>,
+    Json(predicate): Json<AnnotationPredicate>,
+) -> Result<Json<DisclosureProof>, ApiError> {
+    use verisim_hexad::SemanticStore;
+    validate_hexad_id(&id)?;
+    let semantic = state.hexad_store.semantic_store();
+    let annotation = semantic
+        .get_annotations(&id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Hexad {} has no semantic annotation", id)))?;
+    let mut proof = disclosure::prove_annotation(semantic.as_ref(), &annotation, &predicate)
+        .await
+        .map_err(|e| match e {
+            SemanticError::ConstraintViolation(_) => ApiError::BadRequest(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        })?;
+
+    let source = disclosure::anchor_source(&proof.commitment);
+    let provenance = state.hexad_store.provenance_store();
+    let anchored = match provenance.get_chain(&id).await {
+        Ok(chain) => chain
+            .records
+            .iter()
+            .rev()
+            .find(|r| r.source.as_deref() == Some(source.as_str()))
+            .map(|r| r.content_hash.clone()),
+        Err(verisim_provenance::ProvenanceError::NotFound(_)) => None,
+        Err(e) => return Err(ApiError::Internal(e.to_string())),
+    };
+    let anchor = match anchored {
+        Some(anchor) => anchor,
+        None => {
+            let actor = actor.map(|a| a.iri.clone()).unwrap_or_else(|| "system".to_string());
+            provenance
+                .record_event(
+                    &id,
+                    verisim_provenance::ProvenanceEventType::Custom("annotation_committed".to_string()),
+                    &actor,
+                    Some(source),
+                    "Semantic annotation committed for selective disclosure",
+                )
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?
+                .content_hash
+        }
+    };
+    proof.anchor = Some(anchor);
+    Ok(Json(proof))
+}
+
+/// POST /proofs/disclosure/verify — verify a selective disclosure proof:
+/// the opened fields, the type path against the ontology, and the anchor
+/// of its commitment in the entity's provenance
+#[instrument(skip(state, proof))]
+async fn disclosure_verify_handler(
+    State(state): State<AppState>,
+    Json(proof): Json<DisclosureProof>,
+) -> Result<Json<ProofResponse>, ApiError> {
+    let type_path = disclosure::verify_type_path(state.hexad_store.semantic_store().as_ref(), &proof.type_path)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let source = disclosure::anchor_source(&proof.commitment);
+    let anchored = match (&proof.anchor, state.hexad_store.provenance_store().get_chain(&proof.entity_id).await) {
+        (Some(anchor), Ok(chain)) => {
+            chain.verify().is_ok()
+                && chain
+                    .records
+                    .iter()
+                    .any(|r| r.content_hash == *anchor && r.source.as_deref() == Some(source.as_str()))
+        }
+        _ => false,
+    };
+    let verified = disclosure::verify_disclosure(&proof) && type_path && anchored;
+    Ok(Json(ProofResponse {
+        success: true,
+        proof: None,
+        verified: Some(verified),
+        error: (!anchored).then(|| "Commitment is not anchored in the entity's provenance".to_string()),
+        cached: false,
+        attached: None,
+        proof_id: None,
+    }))
+}
+
+/// GET /provenance/{id}/records — the raw provenance chain, hashes and
+/// links included, for another instance to verify
+#[instrument(skip(state))]
+async fn provenance_records_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<verisim_provenance::ProvenanceChain>, ApiError> {
+    validate_hexad_id(&id)?;
+
+    match state.hexad_store.provenance_store().get_chain(&id).await {
+        Ok(chain) => Ok(Json(chain)),
+        Err(verisim_provenance::ProvenanceError::NotFound(_)) => {
+            Err(ApiError::NotFound(format!("No provenance for entity {}", id)))
+        }
+        Err(e) => Err(ApiError::Internal(e.to_string())),
+    }
+}
+
+/// GET /provenance/{id}/lineage — verify the entity's provenance across
+/// the instances it passed through
+#[instrument(skip(state))]
+async fn provenance_lineage_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<lineage::LineageReport>, ApiError> {
+    validate_hexad_id(&id)?;
+    Ok(Json(lineage::verify(&state, &id).await?))
+}
+
+// ---------------------------------------------------------------------------
+// Actor registry handlers
+// ---------------------------------------------------------------------------
+
+/// A provenance event attributed to an actor
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActorActivityResponse {
+    pub entity_id: String,
+    pub event_type: String,
+    pub timestamp: String,
+    pub description: String,
+    pub content_hash: String,
+}
+
+/// GET /actors — list all principals seen by the authentication layer
+#[instrument(skip(state))]
+async fn actors_list_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ActorIdentity>>, ApiError> {
+    Ok(Json(state.auth.actors.list().await))
+}
+
+/// GET /actors/{principal} — look up the canonical actor for a principal
+#[instrument(skip(state))]
+async fn actor_get_handler(
+    State(state): State<AppState>,
+    Path(principal): Path<String>,
+) -> Result<Json<ActorIdentity>, ApiError> {
+    state
+        .auth
+        .actors
+        .get(&principal)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Actor {} not found", principal)))
+}
+
+/// GET /actors/{principal}/activity — every provenance event attributed to a principal
+#[instrument(skip(state))]
+async fn actor_activity_handler(
+    State(state): State<AppState>,
+    Path(principal): Path<String>,
+) -> Result<Json<Vec<ActorActivityResponse>>, ApiError> {
+    let actor = state
+        .auth
+        .actors
+        .get(&principal)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Actor {} not found", principal)))?;
+
+    let mut records = state
+        .hexad_store
+        .provenance_store()
+        .search_by_actor(&actor.iri)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    records.sort_by_key(|(_, r)| r.timestamp);
+
+    let activity = records
+        .into_iter()
+        .map(|(entity_id, r)| ActorActivityResponse {
+            entity_id,
+            event_type: r.event_type.to_string(),
+            timestamp: r.timestamp.to_rfc3339(),
+            description: r.description,
+            content_hash: r.content_hash,
+        })
+        .collect();
+
+    Ok(Json(activity))
+}
+
+// ---------------------------------------------------------------------------
+// Spatial endpoint handlers
+// ---------------------------------------------------------------------------
+
+fn service_account_error(e: ServiceAccountError) -> ApiError {
+    match e {
+        ServiceAccountError::NotFound(_) | ServiceAccountError::KeyNotFound { .. } => ApiError::NotFound(e.to_string()),
+        ServiceAccountError::Exists(ref name) => ApiError::Conflict {
+            message: e.to_string(),
+            ids: vec![name.clone()],
+        },
+        ServiceAccountError::Invalid(_) => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+/// Finds entity namespaces for namespace-confined role bindings: the
+/// `namespace` field of the entity's document
+struct EntityNamespaces(Arc<ConcreteHexadStore>);
+
+impl std::fmt::Debug for EntityNamespaces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityNamespaces").finish_non_exhaustive()
+    }
+}
+
+impl rbac::NamespaceResolver for EntityNamespaces {
+    fn namespace<'a>(&'a self, entity_id: &'a str) -> futures::future::BoxFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let hexad = self.0.get(&HexadId::new(entity_id)).await.ok()??;
+            hexad.document?.fields.get("namespace").cloned()
+        })
+    }
+}
+
+fn rbac_error(e: rbac::RbacError) -> ApiError {
+    match e {
+        rbac::RbacError::RoleNotFound(_) | rbac::RbacError::BindingNotFound => ApiError::NotFound(e.to_string()),
+        rbac::RbacError::RoleExists(ref name) => ApiError::Conflict {
+            message: e.to_string(),
+            ids: vec![name.clone()],
+        },
+        rbac::RbacError::Invalid(_) => ApiError::BadRequest(e.to_string()),
+        rbac::RbacError::Persistence(_) => ApiError::Internal(e.to_string()),
+    }
+}
+
+/// GET /admin/roles — role definitions, by name
+#[instrument(skip(state))]
+async fn roles_list_handler(State(state): State<AppState>) -> Json<Vec<rbac::RoleDefinition>> {
+    let mut roles: Vec<rbac::RoleDefinition> = state.auth.rbac.snapshot().roles.into_values().collect();
+    roles.sort_by(|a, b| a.name.cmp(&b.name));
+    Json(roles)
+}
+
+/// POST /admin/roles — define a role
+#[instrument(skip(state, role), fields(name = %role.name))]
+async fn role_create_handler(
+    State(state): State<AppState>,
+    Json(role): Json<rbac::RoleDefinition>,
+) -> Result<(StatusCode, Json<rbac::RoleDefinition>), ApiError> {
+    state
+        .auth
+        .rbac
+        .update(|policy| policy.create_role(role.clone()))
+        .map_err(rbac_error)?;
+    info!(role = %role.name, "Created role");
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+/// GET /admin/roles/{name} — one role definition
+#[instrument(skip(state))]
+async fn role_get_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<rbac::RoleDefinition>, ApiError> {
+    state
+        .auth
+        .rbac
+        .snapshot()
+        .roles
+        .remove(&name)
+        .map(Json)
+        .ok_or_else(|| rbac_error(rbac::RbacError::RoleNotFound(name)))
+}
+
+/// Permissions to give a role
+#[derive(Debug, Deserialize)]
+pub struct RolePermissionsRequest {
+    #[serde(default)]
+    pub global_permissions: Vec<rbac::Permission>,
+    #[serde(default)]
+    pub modality_permissions: std::collections::HashMap<String, Vec<rbac::Permission>>,
+}
+
+/// PUT /admin/roles/{name}/permissions — replace a role's permissions
+#[instrument(skip(state, request))]
+async fn role_permissions_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<RolePermissionsRequest>,
+) -> Result<Json<rbac::RoleDefinition>, ApiError> {
+    let role = state
+        .auth
+        .rbac
+        .update(|policy| policy.set_permissions(&name, request.global_permissions, request.modality_permissions))
+        .map_err(rbac_error)?;
+    info!(role = %name, global = ?role.global_permissions, "Changed role permissions");
+    Ok(Json(role))
+}
+
+/// DELETE /admin/roles/{name} — remove a role and its bindings
+#[instrument(skip(state))]
+async fn role_delete_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .auth
+        .rbac
+        .update(|policy| policy.remove_role(&name))
+        .map_err(rbac_error)?;
+    info!(role = %name, "Removed role");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Role binding query parameters
+#[derive(Debug, Deserialize)]
+pub struct RoleBindingsQuery {
+    /// Only bindings to this principal
+    pub principal: Option<String>,
+}
+
+/// GET /admin/role-bindings?principal= — roles bound to principals
+#[instrument(skip(state))]
+async fn role_bindings_list_handler(
+    State(state): State<AppState>,
+    Query(query): Query<RoleBindingsQuery>,
+) -> Json<Vec<rbac::RoleBinding>> {
+    let mut bindings = state.auth.rbac.snapshot().bindings;
+    if let Some(principal) = &query.principal {
+        bindings.retain(|b| b.principal == *principal);
+    }
+    Json(bindings)
+}
+
+/// POST /admin/role-bindings — bind a role to a principal, optionally
+/// within a namespace
+#[instrument(skip(state))]
+async fn role_binding_create_handler(
+    State(state): State<AppState>,
+    Json(binding): Json<rbac::RoleBinding>,
+) -> Result<(StatusCode, Json<rbac::RoleBinding>), ApiError> {
+    state
+        .auth
+        .rbac
+        .update(|policy| policy.bind(binding.clone()))
+        .map_err(rbac_error)?;
+    info!(role = %binding.role, principal = %binding.principal, namespace = ?binding.namespace, "Bound role");
+    Ok((StatusCode::CREATED, Json(binding)))
+}
+
+/// DELETE /admin/role-bindings?role=&principal=&namespace= — remove a
+/// binding
+#[instrument(skip(state))]
+async fn role_binding_delete_handler(
+    State(state): State<AppState>,
+    Query(binding): Query<rbac::RoleBinding>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .auth
+        .rbac
+        .update(|policy| policy.unbind(&binding))
+        .map_err(rbac_error)?;
+    info!(role = %binding.role, principal = %binding.principal, namespace = ?binding.namespace, "Unbound role");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Service account creation request
+#[derive(Debug, Deserialize)]
+pub struct CreateServiceAccountRequest {
+    pub name: String,
+    pub role: auth::ClientRole,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Seconds each key stays valid; none issues keys that never expire
+    #[serde(default)]
+    pub key_lifetime_secs: Option<u64>,
+}
+
+/// A created service account and its first key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedServiceAccount {
+    pub account: ServiceAccount,
+    pub key: IssuedKey,
+}
+
+/// Key rotation request
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeysRequest {
+    /// Seconds the previous keys keep working alongside the new one
+    #[serde(default)]
+    pub overlap_secs: u64,
+}
+
+/// GET /admin/service-accounts — service accounts and their keys
+#[instrument(skip(state))]
+async fn service_accounts_list_handler(State(state): State<AppState>) -> Json<Vec<ServiceAccount>> {
+    Json(state.auth.service_accounts.list())
+}
+
+/// POST /admin/service-accounts — create a service account with one key
+#[instrument(skip(state, request), fields(name = %request.name))]
+async fn service_account_create_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateServiceAccountRequest>,
+) -> Result<(StatusCode, Json<CreatedServiceAccount>), ApiError> {
+    let (account, key) = state
+        .auth
+        .service_accounts
+        .create(&request.name, request.role, request.description, request.key_lifetime_secs)
+        .map_err(service_account_error)?;
+    info!(account = %account.name, role = ?account.role, "Created service account");
+    Ok((StatusCode::CREATED, Json(CreatedServiceAccount { account, key })))
+}
+
+/// GET /admin/service-accounts/{name} — one service account
+#[instrument(skip(state))]
+async fn service_account_get_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ServiceAccount>, ApiError> {
+    state
+        .auth
+        .service_accounts
+        .get(&name)
+        .map(Json)
+        .ok_or_else(|| service_account_error(ServiceAccountError::NotFound(name)))
+}
+
+/// DELETE /admin/service-accounts/{name} — delete a service account and its keys
+#[instrument(skip(state))]
+async fn service_account_delete_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if !state.auth.service_accounts.remove(&name) {
+        return Err(service_account_error(ServiceAccountError::NotFound(name)));
+    }
+    let sessions = state.auth.sessions.revoke_credential(
+        |c| matches!(c, auth::Credential::ServiceAccountKey { account, .. } if *account == name),
+    );
+    info!(account = %name, sessions, "Deleted service account");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/service-accounts/{name}/keys — issue another key
+#[instrument(skip(state))]
+async fn service_account_issue_key_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<IssuedKey>), ApiError> {
+    let key = state.auth.service_accounts.issue_key(&name).map_err(service_account_error)?;
+    info!(account = %name, key = %key.key_id, "Issued service account key");
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+fn session_error(e: sessions::SessionError) -> ApiError {
+    match e {
+        sessions::SessionError::Unconfigured => ApiError::Unavailable(e.to_string()),
+        sessions::SessionError::InvalidToken
+        | sessions::SessionError::Reused(_)
+        | sessions::SessionError::CredentialRevoked(_) => ApiError::Unauthorized(e.to_string()),
+        sessions::SessionError::FromSession => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+/// A session token presented to `/auth/refresh` or `/auth/revoke`
+#[derive(Debug, Deserialize)]
+pub struct SessionTokenRequest {
+    /// The refresh token, or for `/auth/revoke` either token of the session
+    #[serde(alias = "refresh_token")]
+    pub token: String,
+}
+
+/// POST /auth/token — exchange the request's credential for a session
+#[instrument(skip(state, identity))]
+async fn auth_token_handler(
+    State(state): State<AppState>,
+    identity: Option<Extension<auth::ClientIdentity>>,
+) -> Result<(StatusCode, Json<sessions::TokenPair>), ApiError> {
+    let Some(Extension(identity)) = identity else {
+        return Err(ApiError::Unauthorized("Authenticate to start a session".to_string()));
+    };
+    let expires_at = state.auth.credential_expires_at(&identity.credential);
+    let pair = state.auth.sessions.issue(&identity, expires_at).map_err(session_error)?;
+    info!(session = %pair.session_id, principal = %identity.principal_id(), "Started session");
+    Ok((StatusCode::CREATED, Json(pair)))
+}
+
+/// POST /auth/refresh — trade a refresh token for new session tokens
+#[instrument(skip(state, request))]
+async fn auth_refresh_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SessionTokenRequest>,
+) -> Result<Json<sessions::TokenPair>, ApiError> {
+    state
+        .auth
+        .sessions
+        .refresh(&request.token, |credential| state.auth.credential_valid(credential))
+        .map(Json)
+        .map_err(session_error)
+}
+
+/// POST /auth/revoke — end the session a token belongs to; unknown tokens
+/// are ignored, as in RFC 7009
+#[instrument(skip(state, request))]
+async fn auth_revoke_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SessionTokenRequest>,
+) -> StatusCode {
+    if state.auth.sessions.revoke(&request.token) {
+        info!("Revoked session");
+    }
+    StatusCode::NO_CONTENT
+}
+
+/// Authorization audit query parameters
+#[derive(Debug, Deserialize)]
+pub struct AuthzAuditQuery {
+    /// Lookback such as `30m`, `24h` or `7d`, or an RFC 3339 interval
+    /// `start/end`; defaults to `24h`
+    pub range: Option<String>,
+    #[serde(flatten)]
+    pub filter: authz_audit::AuthzFilter,
+}
+
+/// Authorization audit page parameters, apart from [`AuthzAuditQuery`]
+/// because flattened query structs cannot hold numbers
+#[derive(Debug, Deserialize)]
+pub struct AuthzAuditPageQuery {
+    /// Decisions per page; defaults to, and is capped at, 1000
+    pub limit: Option<usize>,
+    /// `next` of the previous page
+    pub after: Option<String>,
+}
+
+/// GET /admin/audit/authz?range=&principal=&decision=&source=&route=&entity=&limit=&after=
+/// — recorded authorization decisions, a page at a time
+#[instrument(skip(state))]
+async fn authz_audit_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AuthzAuditQuery>,
+    Query(page): Query<AuthzAuditPageQuery>,
+) -> Result<Json<authz_audit::AuthzPage>, ApiError> {
+    let range = parse_history_range(query.range.as_deref().unwrap_or("24h"))?;
+    let after = page
+        .after
+        .as_deref()
+        .map(|after| {
+            authz_audit::AuthzCursor::parse(after)
+                .ok_or_else(|| ApiError::BadRequest(format!("Invalid cursor '{after}'")))
+        })
+        .transpose()?;
+    let limit = validate_limit(page.limit.unwrap_or(MAX_RESULT_LIMIT)).max(1);
+    state
+        .authz_audit
+        .query(&range, &query.filter, after, limit)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// POST /admin/service-accounts/{name}/rotate — issue a new key and retire
+/// the others after an overlap
+#[instrument(skip(state, request))]
+async fn service_account_rotate_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    request: Option<Json<RotateKeysRequest>>,
+) -> Result<(StatusCode, Json<IssuedKey>), ApiError> {
+    let Json(request) = request.unwrap_or_default();
+    let key = state
+        .auth
+        .service_accounts
+        .rotate(&name, request.overlap_secs)
+        .map_err(service_account_error)?;
+    info!(account = %name, key = %key.key_id, overlap_secs = request.overlap_secs, "Rotated service account keys");
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+/// DELETE /admin/service-accounts/{name}/keys/{key_id} — revoke one key
+#[instrument(skip(state))]
+async fn service_account_revoke_key_handler(
+    State(state): State<AppState>,
+    Path((name, key_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .auth
+        .service_accounts
+        .revoke_key(&name, &key_id)
+        .map_err(service_account_error)?;
+    let sessions = state.auth.sessions.revoke_credential(|c| {
+        matches!(c, auth::Credential::ServiceAccountKey { account, key_id: key } if *account == name && *key == key_id)
+    });
+    info!(account = %name, key = %key_id, sessions, "Revoked service account key");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Radius search request
+#[derive(Debug, Deserialize)]
+pub struct RadiusSearchRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Query point altitude in metres, used by the `3d` distance mode
+    pub altitude: Option<f64>,
+    pub radius_km: f64,
+    /// Distance mode and altitude filter
+    #[serde(flatten)]
+    pub options: verisim_spatial::SearchOptions,
+    pub limit: Option<usize>,
+    /// Shape encoding to include in each result
+    #[serde(default)]
+    pub format: SpatialOutputFormat,
+}
+
+/// Bounding box search request
+#[derive(Debug, Deserialize)]
+pub struct BoundsSearchRequest {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+    pub limit: Option<usize>,
+    /// Shape encoding to include in each result
+    #[serde(default)]
+    pub format: SpatialOutputFormat,
+}
+
+/// K-nearest search request
+#[derive(Debug, Deserialize)]
+pub struct NearestSearchRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Query point altitude in metres, used by the `3d` distance mode
+    pub altitude: Option<f64>,
+    pub k: Option<usize>,
+    /// Distance mode and altitude filter
+    #[serde(flatten)]
+    pub options: verisim_spatial::SearchOptions,
+    /// Shape encoding to include in each result
+    #[serde(default)]
+    pub format: SpatialOutputFormat,
+}
+
+/// Polygon search request
+#[derive(Debug, Deserialize)]
+pub struct PolygonSearchRequest {
+    /// Search area (Polygon or MultiPolygon)
+    pub polygon: verisim_spatial::Geometry,
+    /// `within` (default) or `intersects`
+    #[serde(default)]
+    pub predicate: verisim_spatial::SpatialPredicate,
+    pub limit: Option<usize>,
+    /// Shape encoding to include in each result
+    #[serde(default)]
+    pub format: SpatialOutputFormat,
+}
+
+/// Optional shape encoding for spatial search results
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpatialOutputFormat {
+    /// Representative point only
+    #[default]
+    Point,
+    /// Add the entity's shape as WKT
+    Wkt,
+    /// Add the entity's shape as hex-encoded WKB
+    Wkb,
+}
+
+/// Spatial search result response
+#[derive(Debug, Serialize)]
+pub struct SpatialSearchResultResponse {
+    pub entity_id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance_km: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wkt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wkb: Option<String>,
+}
+
+/// `items` less those whose entity the entity policy hides from the client,
+/// for results read from a modality store rather than through the hexad store
+async fn retain_admitted<T>(state: &AppState, items: Vec<T>, id: impl Fn(&T) -> &str) -> Result<Vec<T>, ApiError> {
+    let mut admitted = Vec::with_capacity(items.len());
+    for item in items {
+        let visible = state
+            .hexad_store
+            .admits(&HexadId::new(id(&item)))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if visible {
+            admitted.push(item);
+        }
+    }
+    Ok(admitted)
+}
+
+fn spatial_result_response(
+    r: verisim_spatial::SpatialSearchResult,
+    format: SpatialOutputFormat,
+) -> SpatialSearchResultResponse {
+    let (wkt, wkb) = match format {
+        SpatialOutputFormat::Point => (None, None),
+        SpatialOutputFormat::Wkt => (Some(r.data.shape().to_wkt()), None),
+        SpatialOutputFormat::Wkb => (None, Some(r.data.shape().to_wkb_hex())),
+    };
+    SpatialSearchResultResponse {
+        entity_id: r.entity_id,
+        latitude: r.data.coordinates.latitude,
+        longitude: r.data.coordinates.longitude,
+        distance_km: r.distance_km,
+        wkt,
+        wkb,
+    }
+}
+
+/// POST /spatial/search/radius — find entities within a given radius
+#[instrument(skip_all)]
+async fn spatial_radius_search_handler(
+    State(state): State<AppState>,
+    Json(body): Json<RadiusSearchRequest>,
+) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
+    let limit = validate_limit(body.limit.unwrap_or(100));
+
+    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
+        return Err(ApiError::BadRequest("Invalid coordinates".to_string()));
+    }
+    if body.radius_km <= 0.0 {
+        return Err(ApiError::BadRequest("Radius must be positive".to_string()));
+    }
+
+    let center = Coordinates {
+        latitude: body.latitude,
+        longitude: body.longitude,
+        altitude: body.altitude,
+    };
+
+    let results = state
+        .hexad_store
+        .spatial_store()
+        .search_radius_with(&center, body.radius_km, &body.options, limit)
+        .await
+        .map_err(spatial_error)?;
+    let results = retain_admitted(&state, results, |r| &r.entity_id).await?;
+
+    let response = results
+        .into_iter()
+        .map(|r| spatial_result_response(r, body.format))
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// POST /spatial/search/bounds — find entities within a bounding box
+#[instrument(skip_all)]
+async fn spatial_bounds_search_handler(
+    State(state): State<AppState>,
+    Json(body): Json<BoundsSearchRequest>,
+) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
+    let limit = validate_limit(body.limit.unwrap_or(100));
+
+    if body.min_lat > body.max_lat || body.min_lon > body.max_lon {
+        return Err(ApiError::BadRequest(
+            "min values must be less than max values".to_string(),
+        ));
+    }
+
+    let bounds = BoundingBox {
+        min_lat: body.min_lat,
+        min_lon: body.min_lon,
+        max_lat: body.max_lat,
+        max_lon: body.max_lon,
+    };
+
+    let results = state
+        .hexad_store
+        .spatial_store()
+        .search_within(&bounds, limit)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let results = retain_admitted(&state, results, |r| &r.entity_id).await?;
+
+    let response = results
+        .into_iter()
+        .map(|r| spatial_result_response(r, body.format))
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// POST /spatial/search/nearest — find k nearest entities to a point
+#[instrument(skip_all)]
+async fn spatial_nearest_handler(
+    State(state): State<AppState>,
+    Json(body): Json<NearestSearchRequest>,
+) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
+    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
+        return Err(ApiError::BadRequest("Invalid coordinates".to_string()));
+    }
+
+    let k = body.k.unwrap_or(10).min(MAX_RESULT_LIMIT);
+
+    let point = Coordinates {
+        latitude: body.latitude,
+        longitude: body.longitude,
+        altitude: body.altitude,
+    };
+
+    let results = state
+        .hexad_store
+        .spatial_store()
+        .nearest_with(&point, k, &body.options)
+        .await
+        .map_err(spatial_error)?;
+    let results = retain_admitted(&state, results, |r| &r.entity_id).await?;
+
+    let response = results
+        .into_iter()
+        .map(|r| spatial_result_response(r, body.format))
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// POST /spatial/search/polygon — find entities within (or intersecting) a polygon
+#[instrument(skip_all)]
+async fn spatial_polygon_search_handler(
+    State(state): State<AppState>,
+    Json(body): Json<PolygonSearchRequest>,
+) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
+    let limit = validate_limit(body.limit.unwrap_or(100));
+
+    let results = state
+        .hexad_store
+        .spatial_store()
+        .search_polygon(&body.polygon, body.predicate, limit)
+        .await
+        .map_err(spatial_error)?;
+    let results = retain_admitted(&state, results, |r| &r.entity_id).await?;
+
+    let response = results
+        .into_iter()
+        .map(|r| spatial_result_response(r, body.format))
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// Largest entity set either side of a spatial join may resolve to.
+const MAX_JOIN_SET_SIZE: usize = 10_000;
+
+/// Filter selecting one side of a spatial join. Filters combine with AND;
+/// an empty filter selects every spatially indexed entity.
+#[derive(Debug, Default, Deserialize)]
+pub struct SpatialJoinSide {
+    /// Only entities annotated with this semantic type IRI
+    pub semantic_type: Option<String>,
+    /// Only these entities
+    pub entity_ids: Option<Vec<String>>,
+    /// Only entities whose representative point lies in this box
+    pub bounds: Option<BoundingBox>,
+}
+
+/// Spatial join request
+#[derive(Debug, Deserialize)]
+pub struct SpatialJoinRequest {
+    pub left: SpatialJoinSide,
+    pub right: SpatialJoinSide,
+    pub predicate: verisim_spatial::JoinPredicate,
+    pub limit: Option<usize>,
+}
+
+/// Resolve one side of a join to its entities' spatial data.
+async fn resolve_join_side(
+    state: &AppState,
+    side: &SpatialJoinSide,
+) -> Result<Vec<(String, verisim_spatial::SpatialData)>, ApiError> {
+    use verisim_hexad::SemanticStore;
+
+    let spatial = state.hexad_store.spatial_store();
+    let candidates = match &side.entity_ids {
+        Some(ids) => {
+            let mut found = Vec::with_capacity(ids.len());
+            for id in ids {
+                validate_hexad_id(id)?;
+                if let Some(data) = spatial.get(id).await.map_err(spatial_error)? {
+                    found.push((id.clone(), data));
+                }
+            }
+            found
+        }
+        None => spatial
+            .list(usize::MAX, 0)
+            .await
+            .map_err(spatial_error)?,
+    };
+    let candidates = retain_admitted(state, candidates, |(id, _)| id).await?;
+
+    let mut selected = Vec::new();
+    for (id, data) in candidates {
+        if let Some(b) = &side.bounds {
+            let (lat, lon) = (data.coordinates.latitude, data.coordinates.longitude);
+            if lat < b.min_lat || lat > b.max_lat || lon < b.min_lon || lon > b.max_lon {
+                continue;
+            }
+        }
+        if let Some(wanted) = &side.semantic_type {
+            let annotation = state
+                .hexad_store
+                .semantic_store()
+                .get_annotations(&id)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            if !annotation.is_some_and(|a| a.types.iter().any(|t| t == wanted)) {
+                continue;
+            }
+        }
+        selected.push((id, data));
+        if selected.len() > MAX_JOIN_SET_SIZE {
+            return Err(ApiError::BadRequest(format!(
+                "Join side matches more than {} entities; narrow the filter",
+                MAX_JOIN_SET_SIZE
+            )));
+        }
+    }
+    Ok(selected)
+}
+
+/// POST /spatial/join — pair entities of two filtered sets by distance or containment
+#[instrument(skip_all)]
+async fn spatial_join_handler(
+    State(state): State<AppState>,
+    Json(body): Json<SpatialJoinRequest>,
+) -> Result<Json<Vec<verisim_spatial::JoinPair>>, ApiError> {
+    let limit = validate_limit(body.limit.unwrap_or(100));
+    if let verisim_spatial::JoinPredicate::WithinDistance { distance_km } = body.predicate {
+        if distance_km.is_nan() || distance_km <= 0.0 {
+            return Err(ApiError::BadRequest("Distance must be positive".to_string()));
+        }
+    }
+
+    let left = resolve_join_side(&state, &body.left).await?;
+    let right = resolve_join_side(&state, &body.right).await?;
+
+    Ok(Json(verisim_spatial::spatial_join(&left, &right, body.predicate, limit)))
+}
+
+/// A position fix in a trajectory request or response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionFixBody {
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+}
+
+/// Request to append fixes to an entity's trajectory
+#[derive(Debug, Deserialize)]
+pub struct TrajectoryRecordRequest {
+    pub fixes: Vec<PositionFixBody>,
+}
+
+/// Time window query parameters
+#[derive(Debug, Deserialize)]
+pub struct TrajectoryRangeQuery {
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+}
+
+/// Point-in-time query parameters
+#[derive(Debug, Deserialize)]
+pub struct TrajectoryPositionQuery {
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Interpolated position response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrajectoryPositionResponse {
+    pub entity_id: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+}
+
+/// Pass-through search request
+#[derive(Debug, Deserialize)]
+pub struct TrajectorySearchRequest {
+    /// Area the trajectories must meet (any geometry; typically a Polygon)
+    pub area: verisim_spatial::Geometry,
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+    pub limit: Option<usize>,
+}
+
+fn time_range(
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> Result<verisim_temporal::TimeRange, ApiError> {
+    verisim_temporal::TimeRange::new(start, end).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+/// POST /spatial/trajectories/{id} — append position fixes for a hexad
+#[instrument(skip(state, body))]
+async fn trajectory_record_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<TrajectoryRecordRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    validate_hexad_id(&id)?;
+    let exists = state
+        .hexad_store
+        .get(&HexadId::new(&id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .is_some();
+    if !exists {
+        return Err(ApiError::NotFound(format!("Hexad {} not found", id)));
+    }
+
+    // Validate everything first so a bad fix doesn't leave a partial write.
+    for f in &body.fixes {
+        Coordinates::new(f.latitude, f.longitude, f.altitude).map_err(spatial_error)?;
+    }
+    for f in &body.fixes {
+        let fix = verisim_spatial::PositionFix::new(
+            f.time,
+            Coordinates::new_unchecked(f.latitude, f.longitude, f.altitude),
+        );
+        state.trajectories.record(&id, fix).await.map_err(spatial_error)?;
+    }
+
+    Ok(Json(serde_json::json!({
+        "entity_id": id,
+        "recorded": body.fixes.len(),
+    })))
+}
+
+/// GET /spatial/trajectories/{id}?start=&end= — fixes within a time window
+#[instrument(skip(state))]
+async fn trajectory_get_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<TrajectoryRangeQuery>,
+) -> Result<Json<Vec<PositionFixBody>>, ApiError> {
+    validate_hexad_id(&id)?;
+    let range = time_range(params.start, params.end)?;
+    let fixes = state
+        .trajectories
+        .trajectory(&id, &range)
+        .await
+        .map_err(spatial_error)?;
+
+    Ok(Json(
+        fixes
+            .into_iter()
+            .map(|f| PositionFixBody {
+                time: f.time,
+                latitude: f.value.latitude,
+                longitude: f.value.longitude,
+                altitude: f.value.altitude,
+            })
+            .collect(),
+    ))
+}
+
+/// GET /spatial/trajectories/{id}/position?at= — where an entity was at a given time
+#[instrument(skip(state))]
+async fn trajectory_position_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<TrajectoryPositionQuery>,
+) -> Result<Json<TrajectoryPositionResponse>, ApiError> {
+    validate_hexad_id(&id)?;
+    let position = state
+        .trajectories
+        .position_at(&id, params.at)
+        .await
+        .map_err(spatial_error)?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No position recorded for {} at {}", id, params.at))
+        })?;
+
+    Ok(Json(TrajectoryPositionResponse {
+        entity_id: id,
+        at: params.at,
+        latitude: position.latitude,
+        longitude: position.longitude,
+        altitude: position.altitude,
+    }))
+}
+
+/// POST /spatial/trajectories/search — entities whose path met an area in a time window
+#[instrument(skip_all)]
+async fn trajectory_search_handler(
+    State(state): State<AppState>,
+    Json(body): Json<TrajectorySearchRequest>,
+) -> Result<Json<Vec<verisim_spatial::TrajectoryMatch>>, ApiError> {
+    let limit = validate_limit(body.limit.unwrap_or(100));
+    let range = time_range(body.start, body.end)?;
+    let matches = state
+        .trajectories
+        .passed_through(&body.area, &range, limit)
+        .await
+        .map_err(spatial_error)?;
+
+    Ok(Json(matches))
+}
+
+/// Largest number of entities clustered in one request.
+const MAX_CLUSTER_INPUT: usize = 1_000_000;
+
+/// Clustering request
+#[derive(Debug, Deserialize)]
+pub struct ClusterRequest {
+    /// Grid (by zoom level) or DBSCAN
+    pub method: verisim_spatial::ClusterMethod,
+    /// Restrict to the visible map area
+    pub bounds: Option<BoundingBox>,
+    /// Representative entity ids returned per cluster (default 3)
+    pub representatives: Option<usize>,
+}
+
+/// POST /spatial/clusters — group nearby entities for map display
+#[instrument(skip_all)]
+async fn spatial_clusters_handler(
+    State(state): State<AppState>,
+    Json(body): Json<ClusterRequest>,
+) -> Result<Json<Vec<verisim_spatial::Cluster>>, ApiError> {
+    if let verisim_spatial::ClusterMethod::Dbscan { eps_km, min_points } = body.method {
+        if eps_km.is_nan() || eps_km <= 0.0 || min_points == 0 {
+            return Err(ApiError::BadRequest(
+                "DBSCAN needs a positive eps_km and min_points".to_string(),
+            ));
+        }
+    }
+    let representatives = validate_limit(body.representatives.unwrap_or(3));
+
+    let entities: Vec<(String, verisim_spatial::SpatialData)> = state
+        .hexad_store
+        .spatial_store()
+        .list(usize::MAX, 0)
+        .await
+        .map_err(spatial_error)?
+        .into_iter()
+        .filter(|(_, data)| {
+            body.bounds.as_ref().is_none_or(|b| {
+                let (lat, lon) = (data.coordinates.latitude, data.coordinates.longitude);
+                lat >= b.min_lat && lat <= b.max_lat && lon >= b.min_lon && lon <= b.max_lon
+            })
+        })
+        .collect();
+    let entities = retain_admitted(&state, entities, |(id, _)| id).await?;
+    if entities.len() > MAX_CLUSTER_INPUT {
+        return Err(ApiError::BadRequest(format!(
+            "{} entities in view exceeds the clustering limit of {}; narrow the bounds",
+            entities.len(),
+            MAX_CLUSTER_INPUT
+        )));
+    }
+
+    Ok(Json(verisim_spatial::cluster::cluster(
+        &entities,
+        body.method,
+        representatives,
+    )))
+}
+
+/// Per-cell count query parameters
+#[derive(Debug, Deserialize)]
+pub struct CellCountsQuery {
+    /// Geohash length (1-12); defaults to 5 (~4.9 km cells)
+    pub precision: Option<usize>,
+    /// Optional bounding box; all four bounds must be given together
+    pub min_lat: Option<f64>,
+    pub min_lon: Option<f64>,
+    pub max_lat: Option<f64>,
+    pub max_lon: Option<f64>,
+}
+
+/// Cell lookup query parameters
+#[derive(Debug, Deserialize)]
+pub struct CellSearchQuery {
+    pub limit: Option<usize>,
+    /// Shape encoding to include in each result
+    #[serde(default)]
+    pub format: SpatialOutputFormat,
+}
+
+/// Map spatial errors caused by bad client input to 400s.
+fn spatial_error(e: verisim_spatial::SpatialError) -> ApiError {
+    use verisim_spatial::SpatialError;
+    match e {
+        SpatialError::InvalidCoordinates(msg) | SpatialError::ParseError(msg) => {
+            ApiError::BadRequest(msg)
+        }
+        other => ApiError::Internal(other.to_string()),
+    }
+}
+
+/// GET /spatial/cells — entity counts per geohash cell, for density tiles
+#[instrument(skip(state))]
+async fn spatial_cell_counts_handler(
+    State(state): State<AppState>,
+    Query(params): Query<CellCountsQuery>,
+) -> Result<Json<Vec<verisim_spatial::CellCount>>, ApiError> {
+    let bounds = match (params.min_lat, params.min_lon, params.max_lat, params.max_lon) {
+        (Some(min_lat), Some(min_lon), Some(max_lat), Some(max_lon)) => Some(BoundingBox {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        }),
+        (None, None, None, None) => None,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Bounding box needs min_lat, min_lon, max_lat and max_lon".to_string(),
+            ))
+        }
+    };
+
+    let counts = state
+        .hexad_store
+        .spatial_store()
+        .cell_counts(params.precision.unwrap_or(5), bounds.as_ref())
+        .await
+        .map_err(spatial_error)?;
+
+    Ok(Json(counts))
+}
+
+/// GET /spatial/cells/{geohash} — entities whose point falls in a geohash cell
+#[instrument(skip(state))]
+async fn spatial_cell_search_handler(
+    State(state): State<AppState>,
+    Path(geohash): Path<String>,
+    Query(params): Query<CellSearchQuery>,
+) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
+    let limit = validate_limit(params.limit.unwrap_or(100));
+
+    let results = state
+        .hexad_store
+        .spatial_store()
+        .search_cell(&geohash, limit)
+        .await
+        .map_err(spatial_error)?;
+    let results = retain_admitted(&state, results, |r| &r.entity_id).await?;
+
+    let response = results
+        .into_iter()
+        .map(|r| spatial_result_response(r, params.format))
+        .collect();
+
+    Ok(Json(response))
+}
+
+// ---------------------------------------------------------------------------
+// GeoJSON import/export
+// ---------------------------------------------------------------------------
+
+/// Media type of a single GeoJSON document (RFC 7946).
+const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+/// Media type of a GeoJSON text sequence (RFC 8142), one feature per record.
+const GEOJSON_SEQ_CONTENT_TYPE: &str = "application/geo+json-seq";
+
+/// Largest FeatureCollection accepted in a single document. Larger imports
+/// should be sent as a GeoJSON text sequence, which is processed as it streams.
+const MAX_GEOJSON_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Largest single record of a GeoJSON text sequence.
+const MAX_GEOJSON_SEQ_RECORD_BYTES: usize = 16 * 1024 * 1024;
+
+/// Number of features fetched from the spatial index per export chunk.
+const GEOJSON_EXPORT_PAGE_SIZE: usize = 500;
+
+/// GeoJSON export query parameters
+#[derive(Debug, Deserialize)]
+pub struct GeoJsonExportQuery {
+    /// `collection` (default) for a FeatureCollection, `seq` for a GeoJSON
+    /// text sequence
+    pub format: Option<String>,
+}
+
+/// Outcome of importing one feature
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeoJsonImportedFeature {
+    /// Position of the feature in the input
+    pub index: usize,
+    /// Entity id carried by the feature, if any
+    pub source_id: Option<String>,
+    /// Hexad the feature was written to
+    pub entity_id: String,
+    /// Whether a new hexad was created (otherwise an existing one was updated)
+    pub created: bool,
+}
+
+/// A feature that could not be imported
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeoJsonImportError {
+    /// Position of the feature in the input
+    pub index: usize,
+    pub error: String,
+}
+
+/// GeoJSON import response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeoJsonImportResponse {
+    pub imported: Vec<GeoJsonImportedFeature>,
+    pub errors: Vec<GeoJsonImportError>,
+}
+
+/// Convert decoded spatial data into hexad spatial input.
+fn spatial_input_from_data(data: verisim_spatial::SpatialData) -> HexadSpatialInput {
+    HexadSpatialInput {
+        latitude: data.coordinates.latitude,
+        longitude: data.coordinates.longitude,
+        altitude: data.coordinates.altitude,
+        geometry_type: Some(data.geometry_type.to_string()),
+        srid: Some(data.srid),
+        geometry: data.geometry,
+        wkt: None,
+        wkb: None,
+        properties: data.properties,
+    }
+}
+
+/// Write one decoded feature: update the hexad it names if that exists,
+/// otherwise create a new hexad.
+async fn import_geojson_feature(
+    state: &AppState,
+    actor: Option<&ActorIdentity>,
+    index: usize,
+    feature: verisim_spatial::geojson::GeoJsonFeature,
+) -> Result<GeoJsonImportedFeature, ApiError> {
+    let mut input = HexadInput {
+        spatial: Some(spatial_input_from_data(feature.data)),
+        ..Default::default()
+    };
+    attribute_actor(&mut input, actor, "imported", "Imported from GeoJSON");
+
+    let existing = match feature.entity_id.as_deref() {
+        Some(id) if validate_hexad_id(id).is_ok() => state
+            .hexad_store
+            .get(&HexadId::new(id))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?,
+        _ => None,
+    };
+
+    let (hexad, created) = match existing {
+        Some(hexad) => (
+            state
+                .hexad_store
+                .update(&hexad.id, input)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?,
+            false,
+        ),
+        None => (
+            state
+                .hexad_store
+                .create(input)
+                .await
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?,
+            true,
+        ),
+    };
+
+    Ok(GeoJsonImportedFeature {
+        index,
+        source_id: feature.entity_id,
+        entity_id: hexad.id.to_string(),
+        created,
+    })
+}
+
+/// Decode and import one feature, recording the outcome in `response`.
+async fn import_geojson_value(
+    state: &AppState,
+    actor: Option<&ActorIdentity>,
+    index: usize,
+    decoded: Result<verisim_spatial::geojson::GeoJsonFeature, verisim_spatial::SpatialError>,
+    response: &mut GeoJsonImportResponse,
+) {
+    let result = match decoded {
+        Ok(feature) => import_geojson_feature(state, actor, index, feature).await,
+        Err(e) => Err(ApiError::BadRequest(e.to_string())),
+    };
+    match result {
+        Ok(imported) => response.imported.push(imported),
+        Err(e) => response.errors.push(GeoJsonImportError {
+            index,
+            error: e.to_string(),
+        }),
+    }
+}
+
+/// POST /spatial/import — import a GeoJSON FeatureCollection
+///
+/// With `Content-Type: application/geo+json-seq` the body is read as a
+/// GeoJSON text sequence (one feature per line, optionally prefixed with the
+/// RS character) and features are imported as they arrive, so collections
+/// of any size can be loaded. Features naming an existing hexad in
+/// `properties.entity_id` update it; all others create new hexads.
+#[instrument(skip(state, actor, headers, body))]
+async fn spatial_import_handler(
+    State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<GeoJsonImportResponse>, ApiError> {
+    use futures::StreamExt;
+
+    let actor = actor.as_deref();
+    let sequence = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with(GEOJSON_SEQ_CONTENT_TYPE));
+
+    let mut response = GeoJsonImportResponse {
+        imported: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    if !sequence {
+        let bytes = axum::body::to_bytes(body, MAX_GEOJSON_IMPORT_BYTES)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let value: serde_json::Value = serde_json::from_slice(&bytes)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid GeoJSON: {}", e)))?;
+        let features = verisim_spatial::geojson::from_feature_collection(&value)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        for (index, decoded) in features.into_iter().enumerate() {
+            import_geojson_value(&state, actor, index, decoded, &mut response).await;
+        }
+        return Ok(Json(response));
+    }
+
+    let mut stream = body.into_data_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut index = 0;
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?);
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let record: Vec<u8> = buffer.drain(..=end).collect();
+            if import_geojson_record(&state, actor, index, &record, &mut response).await {
+                index += 1;
+            }
+        }
+        if buffer.len() > MAX_GEOJSON_SEQ_RECORD_BYTES {
+            return Err(ApiError::BadRequest(format!(
+                "GeoJSON sequence record {} exceeds {} bytes",
+                index, MAX_GEOJSON_SEQ_RECORD_BYTES
+            )));
+        }
+    }
+    // The final record need not be newline-terminated.
+    import_geojson_record(&state, actor, index, &buffer, &mut response).await;
+
+    Ok(Json(response))
+}
+
+/// Import one record of a GeoJSON text sequence. Returns `false` for blank
+/// records, which do not count as features.
+async fn import_geojson_record(
+    state: &AppState,
+    actor: Option<&ActorIdentity>,
+    index: usize,
+    record: &[u8],
+    response: &mut GeoJsonImportResponse,
+) -> bool {
+    let text = String::from_utf8_lossy(record);
+    let text = text.trim_matches(|c: char| c == '\u{1e}' || c.is_whitespace());
+    if text.is_empty() {
+        return false;
+    }
+    let decoded = serde_json::from_str::<serde_json::Value>(text)
+        .map_err(|e| verisim_spatial::SpatialError::ParseError(e.to_string()))
+        .and_then(|v| verisim_spatial::geojson::from_feature(&v));
+    import_geojson_value(state, actor, index, decoded, response).await;
+    true
+}
+
+/// GET /spatial/export — export every spatially indexed hexad as GeoJSON
+///
+/// The response is streamed page by page from the spatial index.
+/// `?format=seq` produces a GeoJSON text sequence instead of a
+/// FeatureCollection.
+#[instrument(skip(state))]
+async fn spatial_export_handler(
+    State(state): State<AppState>,
+    Query(params): Query<GeoJsonExportQuery>,
+) -> Result<Response, ApiError> {
+    use futures::StreamExt;
+
+    let sequence = match params.format.as_deref() {
+        None | Some("collection") => false,
+        Some("seq") => true,
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown export format '{}' (expected 'collection' or 'seq')",
+                other
+            )))
+        }
+    };
+
+    let store = state.hexad_store.clone();
+    // The body streams after the request has left the auth middleware, so
+    // the entity policy's principal and the client's redaction are carried
+    // into each page
+    let principal = verisim_hexad::security::current();
+    let redaction = redaction::current();
+    let pages = futures::stream::unfold((Some(0usize), false), move |(offset, started)| {
+        let store = store.clone();
+        let principal = principal.clone();
+        let redaction = redaction.clone();
+        async move {
+            let offset = offset?;
+            let page = async {
+                let page = store
+                    .spatial_store()
+                    .list(GEOJSON_EXPORT_PAGE_SIZE, offset)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let mut features = Vec::with_capacity(page.len());
+                for (id, data) in &page {
+                    if store.admits(&HexadId::new(id)).await.map_err(|e| e.to_string())? {
+                        let mut feature = verisim_spatial::geojson::to_feature(id, data);
+                        if let Some(redaction) = &redaction {
+                            redaction.apply(&mut feature);
+                        }
+                        features.push(feature.to_string());
+                    }
+                }
+                Ok::<_, String>((page.len(), features))
+            };
+            let page = match principal {
+                Some(principal) => verisim_hexad::security::scope(principal, page).await,
+                None => page.await,
+            };
+            match page {
+                Ok((0, _)) => None,
+                Ok((listed, features)) => {
+                    let chunk = if sequence {
+                        features
+                            .iter()
+                            .map(|f| format!("\u{1e}{}\n", f))
+                            .collect::<String>()
+                    } else if features.is_empty() {
+                        String::new()
+                    } else {
+                        let separator = if started { "," } else { "" };
+                        format!("{}{}", separator, features.join(","))
+                    };
+                    let next = (listed == GEOJSON_EXPORT_PAGE_SIZE).then_some(offset + listed);
+                    Some((Ok::<_, std::io::Error>(chunk), (next, started || !features.is_empty())))
+                }
+                Err(e) => Some((Err(std::io::Error::other(e)), (None, started))),
+            }
+        }
+    });
+
+    let (content_type, body) = if sequence {
+        (GEOJSON_SEQ_CONTENT_TYPE, Body::from_stream(pages))
+    } else {
+        let open = futures::stream::iter([Ok(r#"{"type":"FeatureCollection","features":["#.to_string())]);
+        let close = futures::stream::iter([Ok("]}".to_string())]);
+        (
+            GEOJSON_CONTENT_TYPE,
+            Body::from_stream(open.chain(pages).chain(close)),
+        )
+    };
+
+    // Each feature was redacted as its page was written
+    Ok(([(header::CONTENT_TYPE, content_type)], Extension(redaction::RedactedAsWritten), body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn create_test_state() -> AppState {
+        create_test_state_with(ApiConfig {
+            vector_dimension: 3,
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn create_test_state_with(mut config: ApiConfig) -> AppState {
+        // When the `persistent` feature is enabled, each test gets a unique temp directory
+        // to avoid redb lock contention between parallel tests.
+        #[cfg(feature = "persistent")]
+        {
+            use std::sync::atomic::{AtomicU64, Ordering};
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let tmp = std::env::temp_dir().join(format!(
+                "verisimdb-test-{}-{}",
+                std::process::id(),
+                id,
+            ));
+            config.persistence_dir = Some(tmp.to_string_lossy().into_owned());
+        }
+
+        AppState::new_async(config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_ready_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/ready")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_hexad() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        // Create a hexad
+        let create_request = HexadRequest {
+            title: Some("Test Document".to_string()),
+            body: Some("Test body content".to_string()),
+            embedding: Some(vec![0.1, 0.2, 0.3]),
+            embedding_model: None,
+            types: None,
+            relationships: None,
+            tensor: None,
+            metadata: None,
+            provenance: None,
+            spatial: None,
+            namespace: None,
+            collection: None,
+            expires_at: None,
+        };
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&create_request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Parse response to get ID
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let created: HexadResponse = serde_json::from_slice(&body).unwrap();
+
+        // Get the hexad
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/hexads/{}", created.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_text_search() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        // Create a hexad
+        let create_request = HexadRequest {
+            title: Some("Rust Programming".to_string()),
+            body: Some("Rust is a systems programming language".to_string()),
+            embedding: Some(vec![0.1, 0.2, 0.3]),
+            embedding_model: None,
+            types: None,
+            relationships: None,
+            tensor: None,
+            metadata: None,
+            provenance: None,
+            spatial: None,
+            namespace: None,
+            collection: None,
+            expires_at: None,
+        };
+
+        let _ = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&create_request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Search for it
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search/text?q=Rust&limit=10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_writes_attributed_to_actor() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state
+            .auth
+            .key_registry
+            .register("ingest-key", "ingest pipeline", auth::ClientRole::Writer);
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        let create_request = HexadRequest {
+            title: Some("Attributed".to_string()),
+            body: None,
+            embedding: None,
+            embedding_model: None,
+            types: None,
+            relationships: None,
+            tensor: None,
+            metadata: None,
+            provenance: None,
+            spatial: None,
+            namespace: None,
+            collection: None,
+            expires_at: None,
+        };
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .header("x-api-key", "ingest-key")
+                    .body(Body::from(serde_json::to_string(&create_request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let created: HexadResponse = serde_json::from_slice(&body).unwrap();
+        assert!(created.has_provenance);
+
+        let chain = store.provenance_store().get_chain(&created.id).await.unwrap();
+        let key_hash = chain.records[0]
+            .actor
+            .rsplit('/')
+            .next()
+            .unwrap()
+            .to_string();
+        assert!(chain.records[0].actor.starts_with("https://verisim.db/actor/api_key/"));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/actors/{}/activity", key_hash))
+                    .header("x-api-key", "ingest-key")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let activity: Vec<ActorActivityResponse> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].entity_id, created.id);
+        assert_eq!(activity[0].event_type, "created");
+    }
+
+    #[tokio::test]
+    async fn test_client_certificates_authenticate_exclusively() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            client_certificates: Some(client_certs::ClientCertConfig {
+                ca_path: "clients.pem".to_string(),
+                exclusive: true,
+                mappings: vec![client_certs::SanMapping {
+                    san: "spiffe://example.org/ns/prod/*".to_string(),
+                    principal: None,
+                    role: auth::ClientRole::Writer,
+                }],
+                default_role: Some(auth::ClientRole::Reader),
+            }),
+            ..Default::default()
+        });
+        state
+            .auth
+            .key_registry
+            .register("ingest-key", "ingest pipeline", auth::ClientRole::Writer);
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        let create = |names: Option<&[&str]>, api_key: Option<&str>| {
+            let mut request = Request::builder()
+                .method("POST")
+                .uri("/hexads")
+                .header("content-type", "application/json");
+            if let Some(key) = api_key {
+                request = request.header("x-api-key", key);
+            }
+            let mut request = request
+                .body(Body::from(serde_json::json!({"title": "Certified"}).to_string()))
+                .unwrap();
+            request.extensions_mut().insert(client_certs::ClientCertificate(
+                names.map(|names| names.iter().map(|n| n.to_string()).collect()),
+            ));
+            request
+        };
+
+        // Keys and missing certificates are refused, even alongside a certificate
+        let ingest: &[&str] = &["ingest.svc.example.com", "spiffe://example.org/ns/prod/ingest"];
+        let response = app.clone().oneshot(create(None, Some("ingest-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(create(Some(ingest), Some("ingest-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(create(None, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // An unmapped certificate gets the default role
+        let response = app
+            .clone()
+            .oneshot(create(Some(&["batch.svc.example.com"]), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.clone().oneshot(create(Some(ingest), None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let created: HexadResponse = serde_json::from_slice(&body).unwrap();
+        let chain = store.provenance_store().get_chain(&created.id).await.unwrap();
+        assert!(chain.records[0].actor.starts_with("https://verisim.db/actor/certificate/"));
+    }
+
+    #[tokio::test]
+    async fn test_service_account_keys_rotate_and_attribute_writes() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("admin-key", "ops", auth::ClientRole::Admin);
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+        let send = |key: &str, method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-api-key", key)
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let create_hexad = |key: String| {
+            let send = &send;
+            async move { send(&key, "POST", "/hexads", Some(serde_json::json!({"title": "Nightly import"}))).await.unwrap() }
+        };
+
+        let account = serde_json::json!({"name": "etl", "role": "Writer", "key_lifetime_secs": 3600});
+        let response = send("admin-key", "POST", "/admin/service-accounts", Some(account.clone())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: CreatedServiceAccount = serde_json::from_slice(&body).unwrap();
+        assert!(created.key.expires_at.is_some());
+        let response = send("admin-key", "POST", "/admin/service-accounts", Some(account)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = send(&created.key.key, "GET", "/admin/service-accounts", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Writes with any of the account's keys are attributed to the account
+        let response = create_hexad(created.key.key.clone()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let hexad: HexadResponse = serde_json::from_slice(&body).unwrap();
+        let chain = store.provenance_store().get_chain(&hexad.id).await.unwrap();
+        assert_eq!(chain.records[0].actor, "https://verisim.db/actor/service_account/etl");
+
+        // An overlap past the maximum is refused, changing nothing
+        let response = send("admin-key", "POST", "/admin/service-accounts/etl/rotate", Some(serde_json::json!({"overlap_secs": u64::MAX})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(create_hexad(created.key.key.clone()).await.status(), StatusCode::CREATED);
+
+        // Rotating without an overlap retires the old key at once
+        let response = send("admin-key", "POST", "/admin/service-accounts/etl/rotate", Some(serde_json::json!({"overlap_secs": 0})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let rotated: IssuedKey = serde_json::from_slice(&body).unwrap();
+        assert_eq!(create_hexad(created.key.key.clone()).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(create_hexad(rotated.key.clone()).await.status(), StatusCode::CREATED);
+
+        let metrics = send("admin-key", "GET", "/metrics", None).await.unwrap();
+        let body = axum::body::to_bytes(metrics.into_body(), usize::MAX).await.unwrap();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        assert!(metrics.contains(r#"verisimdb_service_account_keys{account="etl",state="expired"} 1"#), "{metrics}");
+        assert!(metrics.contains(r#"verisimdb_service_account_keys{account="etl",state="expiring"} 1"#), "{metrics}");
+
+        let uri = format!("/admin/service-accounts/etl/keys/{}", rotated.key_id);
+        let response = send("admin-key", "DELETE", &uri, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(create_hexad(rotated.key).await.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_drift_status() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/drift/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_geojson_import_export_roundtrip() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let collection = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [
+                { "type": "Feature",
+                  "geometry": { "type": "Polygon",
+                                "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]] },
+                  "properties": { "name": "square" } },
+                { "type": "Feature",
+                  "geometry": { "type": "Point", "coordinates": [2.3522, 48.8566] },
+                  "properties": { "name": "paris" } },
+                { "type": "Feature",
+                  "geometry": { "type": "Point", "coordinates": [0.0, 123.0] } }
+            ]
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/import")
+                    .header("content-type", "application/geo+json")
+                    .body(Body::from(collection.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let imported: GeoJsonImportResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(imported.imported.len(), 2);
+        assert!(imported.imported.iter().all(|f| f.created));
+        assert_eq!(imported.errors.len(), 1);
+        assert_eq!(imported.errors[0].index, 2);
+
+        // Re-importing an exported feature updates the same hexad.
+        let square_id = imported.imported[0].entity_id.clone();
+        let sequence = format!(
+            "\u{1e}{}\n",
+            serde_json::json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [0.5, 0.5] },
+                "properties": { "entity_id": square_id, "name": "moved" }
+            })
+        );
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/import")
+                    .header("content-type", "application/geo+json-seq")
+                    .body(Body::from(sequence))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let updated: GeoJsonImportResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(updated.imported.len(), 1);
+        assert!(!updated.imported[0].created);
+        assert_eq!(updated.imported[0].entity_id, square_id);
+
+        // A record that never ends is refused rather than buffered.
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/import")
+                    .header("content-type", "application/geo+json-seq")
+                    .body(Body::from(vec![b' '; MAX_GEOJSON_SEQ_RECORD_BYTES + 1]))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/spatial/export")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/geo+json");
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let exported: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let features = verisim_spatial::geojson::from_feature_collection(&exported).unwrap();
+        assert_eq!(features.len(), 2);
+        let moved = features
+            .iter()
+            .map(|f| f.as_ref().unwrap())
+            .find(|f| f.entity_id.as_deref() == Some(square_id.as_str()))
+            .unwrap();
+        assert_eq!(moved.data.properties["name"], "moved");
+    }
+
+    #[tokio::test]
+    async fn test_entity_policy_isolates_clients() {
+        let mut state = create_test_state_with(ApiConfig {
+            vector_dimension: 3,
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![
+                    verisim_hexad::PolicyRule::Owner { field: "owner".to_string() },
+                    verisim_hexad::PolicyRule::Member { field: "namespace".to_string() },
+                ],
+                groups: std::collections::HashMap::from([("red".to_string(), vec!["api_key:carol".to_string()])]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            jwt_secret: Some("policy-secret".to_string()),
+            ..Default::default()
+        });
+        let keys = &state.auth.key_registry;
+        keys.register("alice-key", "alice", auth::ClientRole::Writer);
+        keys.register("bob-key", "bob", auth::ClientRole::Writer);
+        keys.register("carol-key", "carol", auth::ClientRole::Writer);
+        keys.register("admin-key", "ops", auth::ClientRole::Admin);
+        let app = build_router(state);
+        let send = |key: &str, method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-api-key", key)
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let text = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        };
+
+        let response = send(
+            "alice-key",
+            "POST",
+            "/hexads",
+            Some(serde_json::json!({
+                "title": "Alpha plan", "body": "launch", "namespace": "red",
+                "spatial": {"latitude": 51.5, "longitude": -0.1}
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created: serde_json::Value = serde_json::from_str(&text(response).await).unwrap();
+        let alice = created["id"].as_str().unwrap().to_string();
+        let response = send("bob-key", "POST", "/hexads", Some(serde_json::json!({"title": "Alpha notes", "body": "draft"})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Bob can't reach Alice's entity by ID, search or the spatial index
+        let response = send("bob-key", "GET", &format!("/hexads/{alice}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let hits = text(send("bob-key", "GET", "/search/text?q=alpha", None).await.unwrap()).await;
+        assert!(hits.contains("Alpha notes") && !hits.contains(&alice), "{hits}");
+        let radius = serde_json::json!({"latitude": 51.5, "longitude": -0.1, "radius_km": 5.0});
+        let hits = text(send("bob-key", "POST", "/spatial/search/radius", Some(radius.clone())).await.unwrap()).await;
+        assert_eq!(hits, "[]");
+        let export = text(send("bob-key", "GET", "/spatial/export", None).await.unwrap()).await;
+        assert_eq!(export, r#"{"type":"FeatureCollection","features":[]}"#);
+        let response = send("bob-key", "DELETE", &format!("/hexads/{alice}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Namespace members and admins can
+        let hits = text(send("carol-key", "POST", "/spatial/search/radius", Some(radius)).await.unwrap()).await;
+        assert!(hits.contains(&alice), "{hits}");
+        let response = send("admin-key", "GET", &format!("/hexads/{alice}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A token whose subject is the label of Alice's key is not Alice
+        let token = auth::sign_jwt(&serde_json::json!({"sub": "alice", "role": "writer"}), "policy-secret");
+        let request = Request::builder()
+            .uri(format!("/hexads/{alice}"))
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_entity_policy_expressions_grant_by_attribute() {
+        let rule = |expr: &str| verisim_hexad::PolicyRule::Expression {
+            expr: verisim_hexad::Expression::parse(expr).unwrap(),
+        };
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![
+                    rule(r#"principal.position == "phd_student" and request.action == "read"
+                            and "project:alpha" in entity.tags and entity.created_at >= "2024""#),
+                    rule(r#""staff" in principal.groups and request.method != "DELETE""#),
+                ],
+                groups: std::collections::HashMap::from([("staff".to_string(), vec!["api_key:prof".to_string()])]),
+                attributes: std::collections::HashMap::from([(
+                    "api_key:dana".to_string(),
+                    std::collections::HashMap::from([("position".to_string(), "phd_student".to_string())]),
+                )]),
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("dana-key", "dana", auth::ClientRole::Writer);
+        state.auth.key_registry.register("prof-key", "prof", auth::ClientRole::Writer);
+        let mut ids = Vec::new();
+        for tags in ["project:alpha", "project:beta"] {
+            let mut input = verisim_hexad::HexadBuilder::new().with_document(tags, "notes").build();
+            input.document.as_mut().unwrap().fields.insert("tags".to_string(), tags.to_string());
+            ids.push(verisim_hexad::security::system(state.hexad_store.create(input)).await.unwrap().id.to_string());
+        }
+        let (alpha, beta) = (&ids[0], &ids[1]);
+        let app = build_router(state);
+        let send = |key: &str, method: &str, uri: String| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // Dana may read alpha's entities but not write them, and not see beta's
+        let response = send("dana-key", "GET", format!("/hexads/{alpha}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send("dana-key", "GET", format!("/hexads/{beta}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = send("dana-key", "DELETE", format!("/hexads/{alpha}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Staff reach everything, except by the method the rule excludes
+        let response = send("prof-key", "GET", format!("/hexads/{beta}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send("prof-key", "DELETE", format!("/hexads/{beta}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_issue_refresh_and_revoke() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            jwt_secret: Some("session-secret".to_string()),
+            ..Default::default()
+        });
+        state.auth.key_registry.register("ui-key", "dana", auth::ClientRole::Reader);
+        let app = build_router(state);
+        let send = |credential: Option<(&str, String)>, method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json");
+            if let Some((name, value)) = credential {
+                request = request.header(name, value);
+            }
+            let request = request
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let key = || Some(("x-api-key", "ui-key".to_string()));
+        let bearer = |token: &str| Some(("authorization", format!("Bearer {token}")));
+        let pair = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<sessions::TokenPair>(&body).unwrap()
+        };
+
+        // A reader's key buys a session acting as the same principal
+        let response = send(key(), "POST", "/auth/token", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let first = pair(response).await;
+        let response = send(bearer(&first.access_token), "GET", "/hexads", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(bearer(&first.access_token), "POST", "/hexads", Some(serde_json::json!({}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Refresh needs no other credential and rotates the refresh token
+        let refresh = |token: &str| serde_json::json!({ "refresh_token": token });
+        let response = send(None, "POST", "/auth/refresh", Some(refresh(&first.refresh_token)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let second = pair(response).await;
+        assert_eq!(second.session_id, first.session_id);
+
+        // Revoking ends the session: its access tokens and refresh token stop working
+        let response = send(None, "POST", "/auth/revoke", Some(serde_json::json!({ "token": second.refresh_token })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send(bearer(&second.access_token), "GET", "/hexads", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(None, "POST", "/auth/refresh", Some(refresh(&second.refresh_token)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Replaying a spent refresh token revokes its session
+        let third = pair(send(key(), "POST", "/auth/token", None).await.unwrap()).await;
+        let fourth = pair(send(None, "POST", "/auth/refresh", Some(refresh(&third.refresh_token))).await.unwrap()).await;
+        let response = send(None, "POST", "/auth/refresh", Some(refresh(&third.refresh_token)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(bearer(&fourth.access_token), "GET", "/hexads", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_end_with_their_credential() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            jwt_secret: Some("session-secret".to_string()),
+            ..Default::default()
+        });
+        state.auth.key_registry.register("ui-key", "dana", auth::ClientRole::Reader);
+        state.auth.key_registry.register("admin-key", "admin", auth::ClientRole::Admin);
+        let (_, issued) = state
+            .auth
+            .service_accounts
+            .create("ingest", auth::ClientRole::Writer, None, None)
+            .unwrap();
+        let keys = state.auth.key_registry.clone();
+        let app = build_router(state);
+        let send = |credential: (&str, String), method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header(credential.0, credential.1)
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let key = |key: &str| ("x-api-key", key.to_string());
+        let bearer = |token: &str| ("authorization", format!("Bearer {token}"));
+        let refresh = |token: &str| Some(serde_json::json!({ "refresh_token": token }));
+        let pair = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<sessions::TokenPair>(&body).unwrap()
+        };
+
+        // A session's access token cannot start another session
+        let service = pair(send(key(&issued.key), "POST", "/auth/token", None).await.unwrap()).await;
+        let response = send(bearer(&service.access_token), "POST", "/auth/token", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Revoking a service account key ends the sessions it started
+        let uri = format!("/admin/service-accounts/ingest/keys/{}", issued.key_id);
+        let response = send(key("admin-key"), "DELETE", &uri, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send(bearer(&service.access_token), "GET", "/hexads", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(key("admin-key"), "POST", "/auth/refresh", refresh(&service.refresh_token))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // A session of a revoked API key can no longer be refreshed
+        let ui = pair(send(key("ui-key"), "POST", "/auth/token", None).await.unwrap()).await;
+        keys.revoke(&auth::hash_key("ui-key"));
+        let response = send(key("admin-key"), "POST", "/auth/refresh", refresh(&ui.refresh_token))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(bearer(&ui.access_token), "GET", "/hexads", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_authorization_decisions_are_audited() {
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Owner { field: "owner".to_string() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("alice-key", "alice", auth::ClientRole::Reader);
+        state.auth.key_registry.register("admin-key", "admin", auth::ClientRole::Admin);
+        let mut input = verisim_hexad::HexadBuilder::new().with_document("Owned", "notes").build();
+        input.document.as_mut().unwrap().fields.insert("owner".to_string(), "api_key:bob".to_string());
+        let id = verisim_hexad::security::system(state.hexad_store.create(input)).await.unwrap().id.to_string();
+        let app = build_router(state);
+        let send = |key: &str, method: &str, uri: String| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // RBAC lets the reader read but not delete; the entity policy hides
+        // bob's entity from her
+        let response = send("alice-key", "GET", format!("/hexads/{id}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = send("alice-key", "DELETE", format!("/hexads/{id}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let audit_page = |query: &str| {
+            let uri = format!("/admin/audit/authz?{query}");
+            let response = send("admin-key", "GET", uri);
+            async move {
+                let response = response.await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+        let page_events = |page: &serde_json::Value| page["events"].as_array().unwrap().clone();
+        let audit = |query: &str| {
+            let page = audit_page(query);
+            async move { page_events(&page.await) }
+        };
+        let events = audit("principal=api_key:alice").await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["source"], "rbac");
+        assert_eq!(events[0]["decision"], "Allowed");
+        assert_eq!(events[0]["entity"], id.as_str());
+        assert_eq!(events[0]["matched"], "global role grant");
+        assert_eq!(events[1]["source"], "entity_policy");
+        assert_eq!(events[1]["decision"], "Denied");
+        assert_eq!(events[1]["action"], "read");
+        assert_eq!(events[1]["route"], format!("/hexads/{id}"));
+        assert_eq!(events[2]["method"], "DELETE");
+        assert_eq!(events[2]["decision"], "Denied");
+
+        let denied = audit("principal=api_key:alice&decision=denied&source=rbac").await;
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0]["action"], "write");
+        assert!(audit(&format!("entity={id}&range=1h")).await.len() >= 3);
+        assert!(audit("principal=nobody").await.is_empty());
+
+        // Pages follow on from the cursor of the one before
+        let first = audit_page("principal=api_key:alice&limit=2").await;
+        assert_eq!(page_events(&first).len(), 2);
+        let next = first["next"].as_str().unwrap();
+        let second = audit_page(&format!("principal=api_key:alice&limit=2&after={next}")).await;
+        assert_eq!(page_events(&second).len(), 1);
+        assert_eq!(page_events(&second)[0]["method"], "DELETE");
+        assert!(second.get("next").is_none());
+        let response = send("admin-key", "GET", "/admin/audit/authz?after=soon".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Audit queries are for admins only
+        let response = send("alice-key", "GET", "/admin/audit/authz".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_runtime_roles_and_bindings() {
+        let mut state = create_test_state().await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("dana-key", "dana", auth::ClientRole::Reader);
+        state.auth.key_registry.register("admin-key", "admin", auth::ClientRole::Admin);
+        let mut ids = Vec::new();
+        for namespace in ["alpha", "beta"] {
+            let mut input = verisim_hexad::HexadBuilder::new().with_document("Scoped", "notes").build();
+            input.document.as_mut().unwrap().fields.insert("namespace".to_string(), namespace.to_string());
+            ids.push(state.hexad_store.create(input).await.unwrap().id.to_string());
+        }
+        let (alpha, beta) = (&ids[0], &ids[1]);
+        let app = build_router(state);
+        let send = |key: &str, method: &str, uri: String, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("x-api-key", key)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = send("admin-key", "POST", "/admin/roles".to_string(), Some(serde_json::json!({
+            "name": "curator",
+            "global_permissions": ["Read"]
+        })))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = send("admin-key", "POST", "/admin/roles".to_string(), Some(serde_json::json!({ "name": "curator" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let binding = serde_json::json!({ "role": "curator", "principal": "api_key:dana", "namespace": "alpha" });
+        let response = send("admin-key", "POST", "/admin/role-bindings".to_string(), Some(binding))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // The role does not grant writes yet
+        let response = send("dana-key", "DELETE", format!("/hexads/{alpha}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Attaching a permission applies to the next request
+        let permissions = serde_json::json!({ "global_permissions": ["Read", "Write"] });
+        let response = send("admin-key", "PUT", "/admin/roles/curator/permissions".to_string(), Some(permissions))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send("dana-key", "DELETE", format!("/hexads/{alpha}"), None).await.unwrap();
+        assert!(response.status().is_success());
+        let response = send("dana-key", "DELETE", format!("/hexads/{beta}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send("admin-key", "GET", "/admin/role-bindings?principal=api_key:dana".to_string(), None)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let bindings: Vec<rbac::RoleBinding> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].namespace.as_deref(), Some("alpha"));
+
+        // Removing the role removes the grant
+        let response = send("admin-key", "DELETE", "/admin/roles/curator".to_string(), None).await.unwrap();
+        assert!(response.status().is_success());
+        let response = send("admin-key", "DELETE", "/admin/roles/writer".to_string(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = send("dana-key", "DELETE", format!("/hexads/{beta}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Role management is for admins only
+        let response = send("dana-key", "GET", "/admin/roles".to_string(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_redacted_by_role() {
+        let mut state = create_test_state_with(ApiConfig {
+            redaction: redaction::RedactionPolicy {
+                rules: vec![redaction::RedactionRule {
+                    role: "reader".to_string(),
+                    modalities: vec![redaction::RedactedModality::Document, redaction::RedactedModality::Vector],
+                    fields: vec![],
+                    action: redaction::RedactionAction::Drop,
+                }],
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("reader-key", "reader", auth::ClientRole::Reader);
+        state.auth.key_registry.register("writer-key", "writer", auth::ClientRole::Writer);
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Payroll", "salaries").build())
+            .await
+            .unwrap();
+        let app = build_router(state);
+        let search = |key: &str| {
+            let request = Request::builder()
+                .uri("/search/text?q=payroll")
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request);
+            async move {
+                let response = response.await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                serde_json::from_slice::<Vec<serde_json::Value>>(&body).unwrap()
+            }
+        };
+
+        // Readers learn the entity exists, not what its document says
+        let hits = search("reader-key").await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], hexad.id.to_string());
+        assert!(hits[0].get("title").is_none());
+        assert_eq!(search("writer-key").await[0]["title"], "Payroll");
+    }
+
+    #[tokio::test]
+    async fn test_redaction_reaches_graphql_grpc_exports_and_federation() {
+        use grpc::proto::veri_sim_hexad_client::VeriSimHexadClient;
+
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let mut state = create_test_state_with(ApiConfig {
+            redaction: redaction::RedactionPolicy {
+                rules: vec![redaction::RedactionRule {
+                    role: "reader".to_string(),
+                    modalities: vec![redaction::RedactedModality::Document, redaction::RedactedModality::Spatial],
+                    fields: vec![],
+                    action: redaction::RedactionAction::Drop,
+                }],
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("reader-key", "reader", auth::ClientRole::Reader);
+        state.auth.key_registry.register("writer-key", "writer", auth::ClientRole::Writer);
+        state
+            .hexad_store
+            .create(
+                verisim_hexad::HexadBuilder::new()
+                    .with_document("Payroll", "salaries")
+                    .with_spatial(51.5, -0.1)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let app = build_router(state.clone());
+        let send = |key: Option<&str>, method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json");
+            if let Some(key) = key {
+                request = request.header("x-api-key", key);
+            }
+            let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+            let response = app.clone().oneshot(request.body(body).unwrap());
+            async move {
+                let response = response.await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        // GraphQL answers are redacted like the REST ones
+        let graphql = |key: &'static str| {
+            let query = serde_json::json!({ "query": r#"{ searchText(query: "payroll") { id title } }"# });
+            let response = send(Some(key), "POST", "/graphql", Some(query));
+            async move { serde_json::from_str::<serde_json::Value>(&response.await).unwrap() }
+        };
+        let hits = graphql("reader-key").await;
+        assert_eq!(hits["data"]["searchText"].as_array().unwrap().len(), 1);
+        assert!(hits["data"]["searchText"][0].get("title").is_none());
+        assert_eq!(graphql("writer-key").await["data"]["searchText"][0]["title"], "Payroll");
+
+        // Streamed exports are redacted record by record
+        let export = |key: &'static str| {
+            let response = send(Some(key), "GET", "/spatial/export?format=seq", None);
+            async move {
+                let export = response.await;
+                let record = export.trim_matches(|c: char| c == '\u{1e}' || c.is_whitespace());
+                serde_json::from_str::<serde_json::Value>(record).unwrap()
+            }
+        };
+        let feature = export("reader-key").await;
+        assert_eq!(feature["type"], "Feature");
+        assert!(feature.get("geometry").is_none());
+        assert_eq!(export("writer-key").await["geometry"]["type"], "Point");
+
+        // Federated searches answer authenticated callers only
+        let search = serde_json::json!({ "query": "payroll" });
+        let anonymous = Request::builder()
+            .method("POST")
+            .uri("/federation/search/text")
+            .header("content-type", "application/json")
+            .body(Body::from(search.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(anonymous).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        send(Some("reader-key"), "POST", "/federation/search/text", Some(search)).await;
+
+        // gRPC messages are redacted as they are built
+        let addr = grpc::spawn_server(state, "127.0.0.1:0", None).await.unwrap();
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut hexads = VeriSimHexadClient::new(channel);
+        let mut search = async |key: &str| {
+            let mut request = tonic::Request::new(grpc::proto::TextSearchRequest {
+                query: "payroll".to_string(),
+                limit: 10,
+            });
+            request.metadata_mut().insert("x-api-key", key.parse().unwrap());
+            hexads.search_text(request).await.unwrap().into_inner().results
+        };
+        let hits = search("reader-key").await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "");
+        assert_eq!(search("writer-key").await[0].title, "Payroll");
+    }
+
+    #[tokio::test]
+    async fn test_graphql_is_served_behind_authentication() {
+        use futures::StreamExt;
+
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Owner { field: "owner".to_string() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let keys = &state.auth.key_registry;
+        keys.register("alice-key", "alice", auth::ClientRole::Writer);
+        keys.register("bob-key", "bob", auth::ClientRole::Writer);
+        keys.register("reader-key", "rita", auth::ClientRole::Reader);
+        let app = build_router(state.clone());
+        let send = |key: Option<&str>, uri: &str, body: serde_json::Value| {
+            let mut request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json");
+            if let Some(key) = key {
+                request = request.header("x-api-key", key);
+            }
+            app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap())
+        };
+        let graphql = |key: &'static str, query: String| {
+            let response = send(Some(key), "/graphql", serde_json::json!({ "query": query }));
+            async move {
+                let response = response.await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        // Anonymous requests are refused before any operation runs
+        let response = send(None, "/graphql", serde_json::json!({ "query": "{ health { status } }" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Readers may query but not mutate; planner settings are for admins
+        let data = graphql("reader-key", "{ health { status } }".to_string()).await;
+        assert_eq!(data["data"]["health"]["status"], "healthy");
+        let data = graphql("reader-key", r#"mutation { createHexad(input: { title: "Nope" }) { id } }"#.to_string()).await;
+        assert!(data["errors"][0]["message"].as_str().unwrap().contains("does not have 'write' permission"), "{data}");
+        assert_eq!(state.hexad_store.list(10, 0).await.unwrap().len(), 0);
+        let data = graphql("alice-key", "mutation { updatePlannerConfig(input: { enableAdaptive: false }) { enableAdaptive } }".to_string()).await;
+        assert!(data["errors"][0]["message"].as_str().unwrap().contains("does not have 'admin' permission"), "{data}");
+        assert!(state.planner.lock().unwrap().config().enable_adaptive);
+
+        // Operations run for the client, under the entity policy
+        let mut changes = graphql::build_schema(state.clone()).execute_stream("subscription { hexadChanged { change id } }");
+        let bob = verisim_hexad::Principal::new("api_key:bob");
+        assert!(verisim_hexad::security::scope(bob.clone(), async { futures::poll!(changes.next()) }).await.is_pending());
+        let response = send(Some("alice-key"), "/hexads", serde_json::json!({ "title": "Alpha plan", "body": "launch" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let alice = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"].as_str().unwrap().to_string();
+        let query = format!(r#"{{ hexad(id: "{alice}") {{ id }} }}"#);
+        assert_eq!(graphql("alice-key", query.clone()).await["data"]["hexad"]["id"], alice.as_str());
+        assert!(graphql("bob-key", query).await["data"]["hexad"].is_null());
+
+        // Subscribers see only the changes of entities they may see
+        let response = send(Some("bob-key"), "/hexads", serde_json::json!({ "title": "Bob's notes", "body": "draft" })).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let own = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"].as_str().unwrap().to_string();
+        let timeout = std::time::Duration::from_secs(5);
+        let event = verisim_hexad::security::scope(bob.clone(), tokio::time::timeout(timeout, changes.next()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.data.into_json().unwrap()["hexadChanged"]["id"], own.as_str());
+
+        // Deletions too, judged by the entity as it was
+        let delete = |key: &str, id: &str| {
+            let request = Request::builder()
+                .method("DELETE")
+                .uri(format!("/hexads/{id}"))
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        assert_eq!(delete("alice-key", &alice).await.unwrap().status(), StatusCode::NO_CONTENT);
+        assert_eq!(delete("bob-key", &own).await.unwrap().status(), StatusCode::NO_CONTENT);
+        let event = verisim_hexad::security::scope(bob, tokio::time::timeout(timeout, changes.next()))
+            .await
+            .unwrap()
+            .unwrap();
+        let event = event.data.into_json().unwrap();
+        assert_eq!(event["hexadChanged"]["change"], "deleted");
+        assert_eq!(event["hexadChanged"]["id"], own.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_graphql_subscriptions_stream_events() {
+        use futures::StreamExt;
+
+        let state = create_test_state().await;
+        let schema = graphql::build_schema(state.clone());
+        let mut changes = schema.execute_stream("subscription { hexadChanged { change id hexad { hasDocument } } }");
+        let mut alerts = schema.execute_stream(r#"subscription { driftDetected(driftType: "schema_drift") { severity affectedEntities } }"#);
+        // The first poll subscribes
+        assert!(futures::poll!(changes.next()).is_pending());
+        assert!(futures::poll!(alerts.next()).is_pending());
+
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Watched", "body").build())
+            .await
+            .unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), changes.next()).await.unwrap().unwrap();
+        assert!(event.errors.is_empty(), "{:?}", event.errors);
+        let event = event.data.into_json().unwrap();
+        assert_eq!(event["hexadChanged"]["change"], "created");
+        assert_eq!(event["hexadChanged"]["id"], hexad.id.to_string());
+        assert_eq!(event["hexadChanged"]["hexad"]["hasDocument"], true);
+
+        state.hexad_store.delete(&hexad.id).await.unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), changes.next()).await.unwrap().unwrap();
+        let event = event.data.into_json().unwrap();
+        assert_eq!(event["hexadChanged"]["change"], "deleted");
+        assert!(event["hexadChanged"]["hexad"].is_null());
+
+        state
+            .drift_detector
+            .record(DriftType::SchemaDrift, 0.95, vec!["entity-1".to_string()])
+            .await
+            .unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), alerts.next()).await.unwrap().unwrap();
+        let event = event.data.into_json().unwrap();
+        assert_eq!(event["driftDetected"]["severity"], "Emergency");
+        assert_eq!(event["driftDetected"]["affectedEntities"][0], "entity-1");
+    }
+
+    #[tokio::test]
+    async fn test_graphql_mutations_cover_modalities_and_transactions() {
+        let state = create_test_state().await;
+        let schema = graphql::build_schema(state.clone());
+        let run = |query: String| {
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(query).await;
+                assert!(response.errors.is_empty(), "{:?}", response.errors);
+                response.data.into_json().unwrap()
+            }
+        };
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Sensor", "readings").build())
+            .await
+            .unwrap();
+        let id = hexad.id.to_string();
+
+        let data = run(format!(r#"mutation {{ uploadTensor(id: "{id}", shape: [2, 2], data: [1, 2, 3, 4]) {{ hasTensor }} }}"#)).await;
+        assert_eq!(data["uploadTensor"]["hasTensor"], true);
+        let data = run(format!(
+            r#"mutation {{ indexSpatial(id: "{id}", input: {{ latitude: 51.5, longitude: -0.12, properties: [{{ key: "site", value: "london" }}] }}) {{ id }} }}"#
+        ))
+        .await;
+        assert_eq!(data["indexSpatial"]["id"], id.as_str());
+        let data = run(format!(r#"mutation {{ annotateSemantic(id: "{id}", types: ["Sensor"]) {{ hasSemantic }} }}"#)).await;
+        assert_eq!(data["annotateSemantic"]["hasSemantic"], true);
+        let data = run(format!(
+            r#"mutation {{ recordProvenance(id: "{id}", input: {{ eventType: "imported", actor: "etl", description: "Nightly load" }}) {{ chainLength }} }}"#
+        ))
+        .await;
+        assert!(data["recordProvenance"]["chainLength"].as_u64().unwrap() >= 1);
+
+        let stored = state.hexad_store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(stored.tensor.unwrap().shape, vec![2, 2]);
+        assert_eq!(stored.spatial_data.unwrap().properties["site"], "london");
+        assert_eq!(stored.semantic.unwrap().types, vec!["Sensor".to_string()]);
+
+        // Writes buffered through VQL apply on commit
+        let data = run(r#"mutation { beginTransaction(input: { isolation: "read_committed" }) { id state isolation } }"#.to_string()).await;
+        assert_eq!(data["beginTransaction"]["isolation"], "read_committed");
+        let txn = data["beginTransaction"]["id"].as_str().unwrap().to_string();
+        let data = run(format!(r#"mutation {{ executeVql(query: "DELETE HEXAD '{id}'", transactionId: "{txn}") {{ success }} }}"#)).await;
+        assert_eq!(data["executeVql"]["success"], true);
+        assert!(state.hexad_store.get(&hexad.id).await.unwrap().is_some());
+        let data = run(format!(r#"mutation {{ commitTransaction(id: "{txn}") {{ ids transaction {{ state }} }} }}"#)).await;
+        assert_eq!(data["commitTransaction"]["transaction"]["state"], "Committed");
+        assert_eq!(data["commitTransaction"]["ids"][0], id.as_str());
+        assert!(state.hexad_store.get(&hexad.id).await.unwrap().is_none());
+
+        // Client errors are reported; missing hexads are not found
+        let response = schema
+            .execute(r#"mutation { annotateSemantic(id: "missing", types: ["X"]) { id } }"#)
+            .await;
+        assert!(response.errors[0].message.starts_with("Not found"));
+    }
+
+    #[tokio::test]
+    async fn test_graphql_expands_relationships_in_batches() {
+        let state = create_test_state().await;
+        let schema = graphql::build_schema(state.clone());
+        let create = |title: &'static str, cites: Vec<String>| {
+            let store = state.hexad_store.clone();
+            async move {
+                let relationships = cites.iter().map(|id| ("cites", id.as_str())).collect();
+                let input = verisim_hexad::HexadBuilder::new()
+                    .with_document(title, "paper")
+                    .with_relationships(relationships)
+                    .build();
+                store.create(input).await.unwrap().id.to_string()
+            }
+        };
+        let c = create("C", vec![]).await;
+        let b = create("B", vec![c.clone()]).await;
+        let a = create("A", vec![b.clone(), c.clone(), "missing".to_string()]).await;
+
+        let query = format!(
+            r#"{{ a: hexad(id: "{a}") {{ id related(predicate: "cites") {{ id related(predicate: "cites") {{ id }} }} }} b: hexad(id: "{b}") {{ id }} gone: hexad(id: "missing") {{ id }} }}"#
+        );
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["b"]["id"], b.as_str());
+        assert!(data["gone"].is_null());
+
+        // Edges to missing entities are skipped
+        let related = data["a"]["related"].as_array().unwrap();
+        let mut ids: Vec<&str> = related.iter().map(|h| h["id"].as_str().unwrap()).collect();
+        ids.sort();
+        let mut expected = vec![b.as_str(), c.as_str()];
+        expected.sort();
+        assert_eq!(ids, expected);
+        let nested = related.iter().find(|h| h["id"] == b.as_str()).unwrap();
+        assert_eq!(nested["related"][0]["id"], c.as_str());
+        let leaf = related.iter().find(|h| h["id"] == c.as_str()).unwrap();
+        assert_eq!(leaf["related"].as_array().unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_streams_changes_and_round_trips_export() {
+        use futures::StreamExt;
+        use grpc::proto::veri_sim_hexad_client::VeriSimHexadClient;
+        use grpc::proto::veri_sim_vql_client::VeriSimVqlClient;
+
+        async fn serve(state: AppState) -> tonic::transport::Channel {
+            let addr = grpc::spawn_server(state, "127.0.0.1:0", None).await.unwrap();
+            tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap()
+        }
+
+        let source = create_test_state().await;
+        let mut hexads = VeriSimHexadClient::new(serve(source.clone()).await);
+        let first = hexads
+            .create(grpc::proto::HexadCreateRequest {
+                title: "Alpha".to_string(),
+                body: "first".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let request_json = r#"{"title": "Beta", "body": "second", "provenance": {"event_type": "created", "actor": "tester", "description": "seeded"}}"#;
+        let second = hexads
+            .create(grpc::proto::HexadCreateRequest {
+                request_json: request_json.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(second.has_provenance);
+        let invalid = hexads
+            .create(grpc::proto::HexadCreateRequest {
+                request_json: "{".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+
+        // The feed replays what is retained, then follows new changes
+        let mut changes = hexads
+            .watch_changes(grpc::proto::WatchChangesRequest::default())
+            .await
+            .unwrap()
+            .into_inner();
+        let replayed: Vec<_> = [changes.message().await.unwrap().unwrap(), changes.message().await.unwrap().unwrap()].into();
+        assert_eq!(replayed[0].hexad_id, first.id);
+        assert_eq!(replayed[1].hexad_id, second.id);
+        assert!(replayed.iter().all(|c| c.op == "put" && !c.input_json.is_empty()));
+        hexads
+            .delete(grpc::proto::HexadIdRequest { id: first.id.clone() })
+            .await
+            .unwrap();
+        let live = changes.message().await.unwrap().unwrap();
+        assert_eq!((live.hexad_id.as_str(), live.op.as_str()), (first.id.as_str(), "delete"));
+        assert_eq!(live.position, replayed[1].position + 1);
+
+        let export = hexads.export(grpc::proto::Empty {}).await.unwrap();
+        assert_eq!(export.metadata().get("x-verisim-position").unwrap(), &live.position.to_string());
+        let records: Vec<_> = export.into_inner().map(Result::unwrap).collect().await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, second.id);
+
+        // Imported under the same IDs, with records that fail counted
+        let target = create_test_state().await;
+        let mut imports = VeriSimHexadClient::new(serve(target.clone()).await);
+        let broken = grpc::proto::HexadRecord {
+            input_json: "not json".to_string(),
+            ..Default::default()
+        };
+        let upload = futures::stream::iter(records.into_iter().chain([broken]));
+        let imported = imports.import(upload).await.unwrap().into_inner();
+        assert_eq!((imported.imported, imported.failed), (1, 1));
+        assert!(imported.errors[0].starts_with("1: Invalid input_json"));
+        let copy = imports
+            .get(grpc::proto::HexadIdRequest { id: second.id.clone() })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(copy.has_document && copy.has_provenance);
+
+        let mut vql = VeriSimVqlClient::new(serve(target).await);
+        let rows: Vec<_> = vql
+            .execute_stream(grpc::proto::VqlRequest {
+                query: "SELECT * FROM hexads".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].row_json.contains(&second.id));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_health_follows_health_checks_and_reflection_lists_services() {
+        use tonic_health::pb::health_check_response::ServingStatus;
+        use tonic_health::pb::health_client::HealthClient;
+        use tonic_health::pb::HealthCheckRequest;
+        use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+        use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+        use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+        use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+        let state = create_test_state().await;
+        let addr = grpc::spawn_server(state.clone(), "127.0.0.1:0", None).await.unwrap();
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+
+        let mut health = HealthClient::new(channel.clone());
+        let check = |service: &str| HealthCheckRequest { service: service.to_string() };
+        let status = health.check(check("")).await.unwrap().into_inner().status;
+        assert_eq!(status, ServingStatus::Serving as i32);
+        let mut watch = health.watch(check("verisim.VeriSimHexad")).await.unwrap().into_inner();
+        assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::Serving as i32);
+        let unknown = health.check(check("verisim.Nope")).await.unwrap_err();
+        assert_eq!(unknown.code(), tonic::Code::NotFound);
+
+        // Critical drift degrades /health, and gRPC health with it
+        state.drift_detector.record(DriftType::SchemaDrift, 0.95, vec![]).await.unwrap();
+        assert_eq!(health_handler(State(state.clone())).await.1.status, "degraded");
+        let status = health.check(check("verisim.VeriSimVql")).await.unwrap().into_inner().status;
+        assert_eq!(status, ServingStatus::NotServing as i32);
+
+        let mut reflection = ServerReflectionClient::new(channel);
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        let mut responses = reflection
+            .server_reflection_info(futures::stream::iter([request]))
+            .await
+            .unwrap()
+            .into_inner();
+        let Some(MessageResponse::ListServicesResponse(list)) =
+            responses.message().await.unwrap().unwrap().message_response
+        else {
+            panic!("expected a service list");
+        };
+        let names: Vec<&str> = list.service.iter().map(|s| s.name.as_str()).collect();
+        for name in ["verisim.VeriSimHexad", "verisim.VeriSimVql", "grpc.health.v1.Health"] {
+            assert!(names.contains(&name), "{name} missing from {names:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_calls_are_authenticated_and_authorized() {
+        use futures::StreamExt;
+        use grpc::proto::veri_sim_hexad_client::VeriSimHexadClient;
+        use grpc::proto::veri_sim_planner_client::VeriSimPlannerClient;
+
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Owner { field: "owner".to_string() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let keys = &state.auth.key_registry;
+        keys.register("alice-key", "alice", auth::ClientRole::Writer);
+        keys.register("bob-key", "bob", auth::ClientRole::Writer);
+        keys.register("reader-key", "rita", auth::ClientRole::Reader);
+        let addr = grpc::spawn_server(state.clone(), "127.0.0.1:0", None).await.unwrap();
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut hexads = VeriSimHexadClient::new(channel.clone());
+        let mut planner = VeriSimPlannerClient::new(channel);
+        fn keyed<T>(key: &str, message: T) -> tonic::Request<T> {
+            let mut request = tonic::Request::new(message);
+            request.metadata_mut().insert("x-api-key", key.parse().unwrap());
+            request
+        }
+        let create = |title: &str| grpc::proto::HexadCreateRequest {
+            title: title.to_string(),
+            body: "body".to_string(),
+            ..Default::default()
+        };
+
+        // Calls without credentials, or beyond the caller's role, are refused
+        let refused = hexads.list(grpc::proto::ListRequest::default()).await.unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::Unauthenticated);
+        let refused = hexads.create(keyed("reader-key", create("Nope"))).await.unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::PermissionDenied);
+        assert!(refused.message().contains("does not have 'write' permission"), "{}", refused.message());
+        let refused = planner
+            .set_config(keyed("alice-key", grpc::proto::PlannerConfigRequest::default()))
+            .await
+            .unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::PermissionDenied);
+        hexads.list(keyed("reader-key", grpc::proto::ListRequest::default())).await.unwrap();
+
+        // Calls run for the caller, under the entity policy, streams included
+        let alice = hexads.create(keyed("alice-key", create("Alpha plan"))).await.unwrap().into_inner();
+        let hidden = hexads
+            .get(keyed("bob-key", grpc::proto::HexadIdRequest { id: alice.id.clone() }))
+            .await
+            .unwrap_err();
+        assert_eq!(hidden.code(), tonic::Code::NotFound);
+        let exported: Vec<_> = hexads
+            .export(keyed("alice-key", grpc::proto::Empty {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].id, alice.id);
+        let exported = hexads.export(keyed("bob-key", grpc::proto::Empty {})).await.unwrap().into_inner();
+        assert_eq!(exported.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_polygon_search() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        for (lat, lon) in [(51.5074, -0.1278), (48.8566, 2.3522)] {
+            store
+                .create(verisim_hexad::HexadBuilder::new().with_spatial(lat, lon).build())
+                .await
+                .unwrap();
+        }
+
+        let ring: Vec<serde_json::Value> = [(50.5, -2.0), (50.5, 1.5), (53.0, -1.0), (50.5, -2.0)]
+            .iter()
+            .map(|(lat, lon)| serde_json::json!({ "latitude": lat, "longitude": lon }))
+            .collect();
+        let request = serde_json::json!({
+            "polygon": { "type": "Polygon", "coordinates": [ring] },
+            "predicate": "within"
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/search/polygon")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!((results[0]["latitude"].as_f64().unwrap() - 51.5074).abs() < 1e-9);
+
+        let point = serde_json::json!({
+            "polygon": { "type": "Point", "coordinates": { "latitude": 0.0, "longitude": 0.0 } }
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/search/polygon")
+                    .header("content-type", "application/json")
+                    .body(Body::from(point.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_cells() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        for (lat, lon) in [(51.5074, -0.1278), (51.5155, -0.0922), (48.8566, 2.3522)] {
+            store
+                .create(verisim_hexad::HexadBuilder::new().with_spatial(lat, lon).build())
+                .await
+                .unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/spatial/cells?precision=3")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let counts: Vec<verisim_spatial::CellCount> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].geohash, "gcp");
+        assert_eq!(counts[0].count, 2);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/spatial/cells/u09")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results.len(), 1);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/spatial/cells/not-a-hash")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_join_by_semantic_type() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        let mut ids = Vec::new();
+        for (kind, lat, lon) in [
+            ("https://example.org/Warehouse", 51.5074, -0.1278),
+            ("https://example.org/Warehouse", 48.8566, 2.3522),
+            ("https://example.org/Sensor", 51.51, -0.12),
+            ("https://example.org/Sensor", 40.71, -74.00),
+        ] {
+            let hexad = store
+                .create(
+                    verisim_hexad::HexadBuilder::new()
+                        .with_types(vec![kind])
+                        .with_spatial(lat, lon)
+                        .build(),
+                )
+                .await
+                .unwrap();
+            ids.push(hexad.id.to_string());
+        }
+
+        let request = serde_json::json!({
+            "left": { "semantic_type": "https://example.org/Warehouse" },
+            "right": { "semantic_type": "https://example.org/Sensor" },
+            "predicate": { "type": "within_distance", "distance_km": 10.0 }
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/join")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let pairs: Vec<verisim_spatial::JoinPair> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].left_id, ids[0]);
+        assert_eq!(pairs[0].right_id, ids[2]);
+    }
+
+    #[tokio::test]
+    async fn test_trajectory_endpoints() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        let sensor = store
+            .create(verisim_hexad::HexadBuilder::new().with_document("sensor-7", "mobile sensor").build())
+            .await
+            .unwrap();
+        let id = sensor.id.to_string();
+
+        let fixes = serde_json::json!({ "fixes": [
+            { "time": "2026-03-02T14:00:00Z", "latitude": 0.5, "longitude": -1.0 },
+            { "time": "2026-03-02T14:10:00Z", "latitude": 0.5, "longitude": 2.0 }
+        ]});
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/spatial/trajectories/{}", id))
+                    .header("content-type", "application/json")
+                    .body(Body::from(fixes.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/spatial/trajectories/{}/position?at=2026-03-02T14:05:00Z", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let position: TrajectoryPositionResponse = serde_json::from_slice(&body).unwrap();
+        assert!((position.longitude - 0.5).abs() < 1e-9);
+
+        let ring: Vec<serde_json::Value> = [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)]
+            .iter()
+            .map(|(lat, lon)| serde_json::json!({ "latitude": lat, "longitude": lon }))
+            .collect();
+        let search = serde_json::json!({
+            "area": { "type": "Polygon", "coordinates": [ring] },
+            "start": "2026-03-02T00:00:00Z",
+            "end": "2026-03-03T00:00:00Z"
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/trajectories/search")
+                    .header("content-type", "application/json")
+                    .body(Body::from(search.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let matches: Vec<verisim_spatial::TrajectoryMatch> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entity_id, id);
+
+        // Unknown hexads cannot have trajectories.
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/trajectories/no-such-hexad")
+                    .header("content-type", "application/json")
+                    .body(Body::from(fixes.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_clusters() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        for (lat, lon) in [
+            (51.5074, -0.1278),
+            (51.5155, -0.0922),
+            (51.5007, -0.1246),
+            (48.8566, 2.3522),
+            (40.7128, -74.0060),
+        ] {
+            store
+                .create(verisim_hexad::HexadBuilder::new().with_spatial(lat, lon).build())
+                .await
+                .unwrap();
+        }
+
+        let request = serde_json::json!({
+            "method": { "type": "grid", "zoom": 6 },
+            "bounds": { "min_lat": 45.0, "min_lon": -5.0, "max_lat": 55.0, "max_lon": 10.0 },
+            "representatives": 1
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/clusters")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let clusters: Vec<verisim_spatial::Cluster> = serde_json::from_slice(&body).unwrap();
+        let counts: Vec<usize> = clusters.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![3, 1]);
+        assert_eq!(clusters[0].representatives.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_wkt_input_and_output() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let create = |spatial: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/hexads")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({ "title": "Site", "spatial": spatial }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(create(serde_json::json!({
+                "latitude": 0.0,
+                "longitude": 0.0,
+                "wkt": "SRID=4326;POLYGON ((-0.2 51.4, 0.0 51.4, 0.0 51.6, -0.2 51.6, -0.2 51.4))"
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let point = verisim_spatial::Geometry::from_wkt("POINT (2.35 48.85)").unwrap();
+        let response = app
+            .clone()
+            .oneshot(create(serde_json::json!({
+                "latitude": 0.0,
+                "longitude": 0.0,
+                "wkb": point.to_wkb_hex()
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let search = serde_json::json!({
+            "latitude": 51.5, "longitude": -0.1, "radius_km": 5.0, "format": "wkt"
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/spatial/search/radius")
+                    .header("content-type", "application/json")
+                    .body(Body::from(search.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results.len(), 1);
+        let wkt = results[0]["wkt"].as_str().unwrap();
+        assert!(wkt.starts_with("POLYGON ((-0.2 51.4"), "{}", wkt);
+        assert!(results[0].get("wkb").is_none());
+
+        let response = app
+            .oneshot(create(serde_json::json!({
+                "latitude": 0.0,
+                "longitude": 0.0,
+                "wkt": "POLYGON ((0 0, 1 0, 1 1))"
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_spatial_search_3d_distance_and_altitude_filter() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        for alt in [0.0, 9000.0] {
+            let mut input = verisim_hexad::HexadBuilder::new().with_spatial(51.5, -0.12).build();
+            input.spatial.as_mut().unwrap().altitude = Some(alt);
+            store.create(input).await.unwrap();
+        }
+
+        let search = |uri: &str, request: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(search(
+                "/spatial/search/radius",
+                serde_json::json!({
+                    "latitude": 51.5, "longitude": -0.12, "altitude": 0.0,
+                    "radius_km": 1.0, "distance_mode": "3d"
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results.len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(search(
+                "/spatial/search/nearest",
+                serde_json::json!({
+                    "latitude": 51.5, "longitude": -0.12, "altitude": 0.0,
+                    "distance_mode": "3d", "min_altitude": 1000.0
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!((results[0]["distance_km"].as_f64().unwrap() - 9.0).abs() < 1e-9);
+
+        let response = app
+            .oneshot(search(
+                "/spatial/search/nearest",
+                serde_json::json!({
+                    "latitude": 51.5, "longitude": -0.12,
+                    "min_altitude": 100.0, "max_altitude": 10.0
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_drift_scan_endpoint() {
+        let state = create_test_state().await;
+        let store = state.hexad_store.clone();
+        let app = build_router(state);
+
+        for embedding in [
+            vec![1.0, 0.0, 0.0],
+            vec![0.98, 0.05, 0.0],
+            vec![0.97, 0.0, 0.05],
+            vec![0.0, 0.0, 1.0],
+        ] {
+            store
+                .create(
+                    verisim_hexad::HexadBuilder::new()
+                        .with_embedding(embedding)
+                        .with_types(vec!["https://example.org/Sensor"])
+                        .build(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/scan").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/drift/scan")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["scanned"], 4);
+        assert_eq!(report["measurements"], 4);
+        assert!(!report["events"].as_array().unwrap().is_empty());
+
+        let response = app
+            .oneshot(Request::builder().uri("/drift/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let statuses: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        let semantic = statuses
+            .iter()
+            .find(|s| s["drift_type"] == "semantic_vector_drift")
+            .unwrap();
+        assert!(semantic["current_score"].as_f64().unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_drift_alerts_endpoint() {
+        let state = create_test_state().await;
+        let response = build_router(state.clone())
+            .oneshot(Request::builder().uri("/drift/alerts").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let alerts: AlertConfig = serde_json::from_value(serde_json::json!({
+            "sinks": [{
+                "name": "ops-mail",
+                "min_severity": "Critical",
+                "type": "email",
+                "smtp_host": "127.0.0.1",
+                "from": "verisimdb@example.org",
+                "to": ["ops@example.org"]
+            }]
+        }))
+        .unwrap();
+        let mut state = state;
+        state.alert_dispatcher = Some(Arc::new(AlertDispatcher::from_config(&alerts)));
+
+        let response = build_router(state)
+            .oneshot(Request::builder().uri("/drift/alerts").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats["ops-mail"]["delivered"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_drift_thresholds_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/thresholds").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut thresholds: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(thresholds["tensor"], 0.35);
+
+        thresholds["tensor"] = serde_json::json!(1.5);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/drift/thresholds")
+                    .header("content-type", "application/json")
+                    .body(Body::from(thresholds.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        thresholds["tensor"] = serde_json::json!(0.5);
+        thresholds["adaptive_policies"] = serde_json::json!({
+            "SemanticVectorDrift": {"Adaptive": {"base": 0.2, "sensitivity": 0.5}}
+        });
+        thresholds["actor"] = serde_json::json!("ops@example.org");
+        thresholds["reason"] = serde_json::json!("noisy tensor alerts");
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/drift/thresholds")
+                    .header("content-type", "application/json")
+                    .body(Body::from(thresholds.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let update: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(update["previous"]["tensor"], 0.35);
+        assert_eq!(update["audit"]["actor"], "ops@example.org");
+        assert!(update["audit"]["description"].as_str().unwrap().contains("tensor: 0.35 -> 0.5"));
+        assert_eq!(state.drift_detector.thresholds().unwrap().tensor, 0.5);
+
+        let response = app
+            .oneshot(Request::builder().uri("/drift/thresholds/audit").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let audit: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(audit["chain_length"], 1);
+        assert_eq!(audit["chain_valid"], true);
+    }
+
+    #[tokio::test]
+    async fn test_drift_history_endpoint() {
+        let state = create_test_state().await;
+        state
+            .drift_detector
+            .record(DriftType::TensorDrift, 0.1, vec![])
+            .await
+            .unwrap();
+        state
+            .drift_detector
+            .record(DriftType::TensorDrift, 0.9, vec!["e1".to_string()])
+            .await
+            .unwrap();
+        state
+            .drift_detector
+            .record(DriftType::SchemaDrift, 0.05, vec![])
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/drift/history?type=tensor&range=1h")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let points: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1]["score"], 0.9);
+        assert_eq!(points[1]["event"]["affected_entities"][0], "e1");
+
+        let response = app.clone().oneshot(get("/drift/history")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let points: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(points.len(), 3);
+
+        let response = app.clone().oneshot(get("/drift/history?events_only=true")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let points: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0]["drift_type"], "TensorDrift");
+
+        let response = app.clone().oneshot(get("/drift/history?type=bogus")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.oneshot(get("/drift/history?range=soon")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_drift_forecast_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/forecast?type=tensor").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let forecasts: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert!(forecasts.is_empty(), "no measurements yet");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/drift/forecast?method=quadratic")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_drift_population_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        for i in 0..3 {
+            let body = serde_json::json!({
+                "title": format!("Doc {}", i),
+                "body": "text",
+                "embedding": [1.0, 0.1 * i as f32, 0.0]
+            });
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/hexads")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/population").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status["pending_reference"], 3);
+        assert!(status["reference_size"].is_null());
+
+        // Not enough recent inserts to form a reference from.
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/drift/population/rebaseline")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_drift_suppression_endpoints() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let post = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/drift/suppressions")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(post(serde_json::json!({"drift_types": ["tensor"], "reason": "import"})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "needs an end");
+
+        let response = app
+            .clone()
+            .oneshot(post(serde_json::json!({
+                "drift_types": ["tensor"],
+                "duration_secs": 3600,
+                "reason": "nightly bulk import",
+                "actor": "ops@example.org"
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let window: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = window["id"].as_str().unwrap().to_string();
+        assert_eq!(window["created_by"], "ops@example.org");
+
+        let event = state
+            .drift_detector
+            .record(DriftType::TensorDrift, 0.8, vec![])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.suppressed_by.as_deref(), Some(id.as_str()));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/suppressions").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let windows: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(windows.len(), 1);
+
+        let delete = |id: &str| {
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/drift/suppressions/{}", id))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(delete(&id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(delete(&id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_drift_policy_and_quarantine_endpoints() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/policy").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let policy: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(policy["rules"].as_array().unwrap().len(), 2);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/drift/policy")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"rules": [
+                            {"min_severity": "Info", "actions": ["log"]},
+                            {"drift_type": "SchemaDrift", "min_severity": "Critical", "actions": ["log", "quarantine"]}
+                        ]})
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        state
+            .drift_detector
+            .record(DriftType::SchemaDrift, 0.95, vec!["entity-1".to_string()])
+            .await
+            .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/quarantine").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let records: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["entity_id"], "entity-1");
+
+        let release = || {
+            Request::builder()
+                .method("DELETE")
+                .uri("/drift/quarantine/entity-1")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(release()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(release()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_drift_correlations_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        for score in [0.1, 0.2, 0.15] {
+            state.drift_detector.record(DriftType::SchemaDrift, score, vec![]).await.unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(get("/drift/correlations?range=1h&symptom=quality"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["bucket_secs"], 300);
+        // Too little history to correlate anything.
+        assert!(report["correlations"].as_array().unwrap().is_empty());
+        assert!(report["probable_causes"].as_array().unwrap().is_empty());
+
+        let response = app.clone().oneshot(get("/drift/correlations?symptom=nope")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        for query in ["range=2w&bucket=1", "bucket=18446744073709551615", "bucket=9223372036854775807", "max_lag=1000000"] {
+            let response = app.clone().oneshot(get(&format!("/drift/correlations?{query}"))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drift_namespace_thresholds_and_status() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/drift/thresholds?namespace=alpha")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "semantic_vector": 0.3, "graph_document": 0.4, "temporal_consistency": 0.2,
+                            "tensor": 0.05, "schema": 0.1, "provenance": 0.2, "spatial": 0.3, "quality": 0.25,
+                            "adaptive_policies": {}
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let global = state.drift_detector.thresholds().unwrap();
+        assert!(global.tensor > 0.05, "global thresholds untouched");
+
+        let event = state
+            .drift_detector
+            .record_in(Some("alpha"), DriftType::TensorDrift, 0.1, vec![])
+            .await
+            .unwrap();
+        assert!(event.is_some());
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(get("/drift/status?namespace=alpha")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0]["namespace"], "alpha");
+
+        let response = app.clone().oneshot(get("/metrics")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("verisimdb_drift_namespace_score{drift_type=\"tensor_drift\",namespace=\"alpha\"}"));
+
+        let delete = || {
+            Request::builder()
+                .method("DELETE")
+                .uri("/drift/thresholds/namespaces/alpha")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    struct ConstantDrift;
+
+    #[tonic::async_trait]
+    impl verisim_drift::DriftCalculatorPlugin for ConstantDrift {
+        fn name(&self) -> &str {
+            "constant"
+        }
+
+        async fn measure(&self, _hexad: &verisim_hexad::Hexad) -> Result<Option<f64>, verisim_drift::DriftError> {
+            Ok(Some(0.9))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drift_plugins_endpoint() {
+        let state = create_test_state().await;
+        let drift_type = state.drift_detector.register_plugin(Arc::new(ConstantDrift)).unwrap();
+        state.drift_detector.record(drift_type, 0.9, vec![]).await.unwrap();
+        let app = build_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/drift/plugins").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let plugins: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0]["drift_type"], "custom:constant");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/drift/history?type=custom:constant&range=1h")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let history: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_normalization_runs_in_background() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"title": "Queued", "body": "Normalize me", "embedding": [0.1, 0.2, 0.3]})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let trigger = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = trigger(format!("/normalizer/trigger/{}", id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let response = trigger(format!("/normalizer/trigger/{}?type=nonsense", id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = trigger("/normalizer/trigger/missing".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut status = state.normalizer.status().await;
+        for _ in 0..200 {
+            if status.completed_count > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            status = state.normalizer.status().await;
+        }
+        assert!(status.running);
+        assert_eq!(status.completed_count, 1);
+        assert_eq!(status.pending_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_normalization_preview_and_approval() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"title": "Preview", "body": "Dry run first", "embedding": [0.1, 0.2, 0.3]})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+        let version = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap().status.version;
+
+        let post = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = post(format!("/normalizer/preview/{}?type=semantic_vector", id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let preview: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(preview["proposals"][0]["strategy"], "semantic-vector-sync");
+        assert_eq!(preview["proposals"][0]["changes"][0]["modality"], "vector");
+        let preview_id = preview["id"].as_str().unwrap().to_string();
+        let unchanged = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap();
+        assert_eq!(unchanged.status.version, version);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/normalizer/previews/{}", preview_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = post(format!("/normalizer/previews/{}/approve", preview_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(result["applied"], true);
+        let repaired = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap();
+        assert_eq!(repaired.status.version, version + 1);
+
+        let response = post(format!("/normalizer/previews/{}/approve", preview_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_normalization_rollback_endpoint() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"title": "Rollback", "body": "Undo a bad repair", "embedding": [0.1, 0.2, 0.3]})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+        let original = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap();
+
+        let post = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = post(format!("/normalizer/preview/{}?type=semantic_vector", id)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let preview: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let response = post(format!("/normalizer/previews/{}/approve", preview["id"].as_str().unwrap()))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(result["base_version"], original.status.version);
+        let result_id = result["id"].as_str().unwrap().to_string();
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/normalizer/results").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let results: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(results[0]["id"], result_id);
+
+        let response = post(format!("/normalizer/rollback/{}", result_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let rollback: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rollback["restored_version"], original.status.version);
+        let reverted = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap();
+        assert_eq!(
+            reverted.embedding.map(|e| e.vector),
+            original.embedding.map(|e| e.vector)
+        );
+
+        let response = post(format!("/normalizer/rollback/{}", result_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_normalizer_strategy_selection_endpoints() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let get = |uri: &str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let put = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/normalizer/strategies")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = get("/normalizer/strategies").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let initial: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(initial["registered"], initial["order"]);
+
+        let response = put(serde_json::json!({
+            "strategies": {"semantic-vector-sync": {"enabled": false}, "temporal-repair": {"priority": 5}},
+            "namespaces": {"project-x": ["semantic-vector-sync"]}
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let updated: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let order: Vec<&str> = updated["order"].as_array().unwrap().iter().map(|s| s.as_str().unwrap()).collect();
+        assert_eq!(order[0], "temporal-repair");
+        assert!(!order.contains(&"semantic-vector-sync"));
+
+        let response = get("/normalizer/strategies?namespace=project-x").await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let scoped: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(scoped["order"], serde_json::json!(["semantic-vector-sync"]));
+
+        let response = put(serde_json::json!({"namespaces": {"project-x": ["no-such-strategy"]}}))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_normalizer_report_and_metrics() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Report", "Outcome tracking").build())
+            .await
+            .unwrap();
+        let event = DriftEvent::new(DriftType::TemporalConsistencyDrift, 0.7, "drift")
+            .with_entities(vec![hexad.id.to_string()])
+            .with_actions(vec![DriftAction::Normalize]);
+        state.normalizer.handle_drift(&hexad, &event).await.unwrap();
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(get("/normalizer/report?limit=5")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["success_rate"], 1.0);
+        assert_eq!(report["recent"][0]["strategy"], "temporal-repair");
+        assert_eq!(report["strategies"][0]["runs"], 1);
+        assert_eq!(report["drift"][0]["mean_trigger_score"], 0.7);
+
+        let response = app.oneshot(get("/metrics")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("verisimdb_normalizer_runs_total{outcome=\"success\",strategy=\"temporal-repair\"} 1"));
+        assert!(text.contains("verisimdb_normalizer_queue{state=\"pending\"} 0"));
+        assert!(text.contains("verisimdb_normalizer_success_rate 1"));
+    }
+
+    #[tokio::test]
+    async fn test_list_hexads_reports_modalities_without_loading_them() {
+        let state = create_test_state().await;
+        state
+            .hexad_store
+            .create(
+                verisim_hexad::HexadBuilder::new()
+                    .with_document("Listed", "Summary only")
+                    .with_tensor(vec![2], vec![1.0, 2.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(Request::builder().uri("/hexads?limit=10").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["has_document"], true);
+        assert_eq!(listed[0]["has_tensor"], true);
+        assert_eq!(listed[0]["has_vector"], false);
+        assert_eq!(listed[0]["version_count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_restore_and_purge() {
+        let mut state = create_test_state().await;
+        state.config.soft_delete = true;
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Ledger", "Kept for audit").build())
+            .await
+            .unwrap();
+        let app = build_router(state.clone());
+        let request = |method: &str, uri: String| {
+            app.clone()
+                .oneshot(Request::builder().method(method).uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = request("DELETE", format!("/hexads/{}", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = request("GET", format!("/hexads/{}", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = request("GET", "/hexads/deleted".to_string()).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let deleted: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(deleted[0]["status"]["id"], hexad.id.as_str());
+        assert_eq!(deleted[0]["deleted_by"], "anonymous");
+
+        let response = request("POST", format!("/hexads/{}/restore", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = request("GET", format!("/hexads/{}", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = request("POST", format!("/hexads/{}/restore", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        request("DELETE", format!("/hexads/{}", hexad.id)).await.unwrap();
+        assert!(purge_soft_deleted(&state).await.unwrap().is_empty());
+        state.config.soft_delete_purge_after_secs = Some(0);
+        assert_eq!(purge_soft_deleted(&state).await.unwrap(), vec![hexad.id.clone()]);
+        let response = request("POST", format!("/hexads/{}/restore", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_wal_checkpoint_endpoints() {
+        let state = create_test_state().await;
+        state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Logged", "write").build())
+            .await
+            .unwrap();
+        let app = build_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().method("POST").uri("/wal/checkpoint").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        if !cfg!(feature = "persistent") {
+            // Without the persistent feature there is no WAL to checkpoint
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            return;
+        }
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["hexads"], 1);
+
+        let response = app
+            .oneshot(Request::builder().uri("/wal/status").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let lag: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(lag["entries_since_checkpoint"], 0);
+        assert_eq!(lag["checkpoint_sequence"], report["sequence"]);
+    }
+
+    #[tokio::test]
+    async fn test_backups_restore_into_a_fresh_instance() {
+        let dir = std::env::temp_dir().join(format!("verisimdb-backups-{}", std::process::id()));
+        let backup_dir = Some(dir.to_string_lossy().into_owned());
+        let state = create_test_state_with(ApiConfig {
+            vector_dimension: 3,
+            backup_dir: backup_dir.clone(),
+            ..Default::default()
+        })
+        .await;
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Backed up", "kept").build())
+            .await
+            .unwrap();
+        let app = build_router(state);
+        let post = |uri: &str, body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(post("/admin/backups", "{}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let manifest: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(manifest["kind"], "full");
+        let id = manifest["id"].as_str().unwrap().to_string();
+        if !cfg!(feature = "persistent") {
+            // Without the persistent feature there is no WAL to continue
+            let response = app
+                .clone()
+                .oneshot(post("/admin/backups", r#"{"kind": "incremental"}"#))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let response = app
+            .oneshot(Request::builder().uri("/admin/backups").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let backups: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(backups.len(), 1);
+
+        let fresh = build_router(
+            create_test_state_with(ApiConfig {
+                vector_dimension: 3,
+                backup_dir: backup_dir.clone(),
+                ..Default::default()
+            })
+            .await,
+        );
+        let response = fresh.clone().oneshot(post("/admin/backups/missing/restore", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = fresh.clone().oneshot(post(&format!("/admin/backups/{id}/restore"), "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["hexads"], 1);
+        // Only ever into an empty instance
+        let response = fresh.oneshot(post(&format!("/admin/backups/{id}/restore"), "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Or on startup
+        let restored = create_test_state_with(ApiConfig {
+            vector_dimension: 3,
+            backup_dir,
+            restore_backup: Some(id),
+            ..Default::default()
+        })
+        .await;
+        let entity = restored.hexad_store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(entity.document.unwrap().body, "kept");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_object_store_endpoints() {
+        let state = create_test_state().await;
+        let response = build_router(state.clone())
+            .oneshot(Request::builder().uri("/admin/object-store").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let dir = std::env::temp_dir().join(format!("verisimdb-object-store-api-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("wal")).unwrap();
+        std::fs::write(dir.join("wal/wal-0000000000000001.log"), b"entries").unwrap();
+        let bucket = verisim_storage::InMemoryBackend::new();
+        let mut state = state;
+        state.object_store = Some(Arc::new(object_store::ObjectMirror::new(Box::new(bucket.clone()), &dir)));
+        let app = build_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/object-store/sync")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["uploaded"], 1);
+        assert!(verisim_storage::StorageBackend::exists(&bucket, b"wal/wal-0000000000000001.log").await.unwrap());
+
+        let response = app
+            .oneshot(Request::builder().uri("/admin/object-store").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status["backend"], "in-memory");
+        assert_eq!(status["last_sync"]["files"], 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_wal_recover_endpoint() {
+        let state = create_test_state().await;
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Before", "good").build())
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let target = chrono::Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        state
+            .hexad_store
+            .update(&hexad.id, verisim_hexad::HexadBuilder::new().with_document("After", "bad").build())
+            .await
+            .unwrap();
+        let app = build_router(state.clone());
+
+        let recover = |body: serde_json::Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/wal/recover")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+        let future = chrono::Utc::now() + chrono::Duration::hours(1);
+        let response = app.clone().oneshot(recover(serde_json::json!({ "target": future }))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.oneshot(recover(serde_json::json!({ "target": target }))).await.unwrap();
+        if !cfg!(feature = "persistent") {
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            return;
+        }
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["rolled_back"][0]["id"], hexad.id.as_str());
+        let recovered = state.hexad_store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(recovered.document.unwrap().title, "Before");
+    }
+
+    #[tokio::test]
+    async fn test_collection_endpoints() {
+        let mut state = create_test_state().await;
+        state.config.soft_delete = true;
+        let app = build_router(state.clone());
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(body) => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+                None => builder.body(Body::empty()).unwrap(),
+            };
+            app.clone().oneshot(request)
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let response = send(
+            "POST",
+            "/hexads",
+            Some(serde_json::json!({"title": "Alice", "body": "Rust engineer", "collection": "people"})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let alice = json(response).await["id"].as_str().unwrap().to_string();
+        assert!(alice.starts_with("people:"));
+        send("POST", "/hexads", Some(serde_json::json!({"title": "Paper", "body": "Rust", "collection": "papers"})))
+            .await
+            .unwrap();
+        let response = send("POST", "/hexads", Some(serde_json::json!({"title": "Bad", "collection": "a b"})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = send("GET", &format!("/hexads/{}", alice), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let listed = json(send("GET", "/hexads?collection=people", None).await.unwrap()).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["id"], alice.as_str());
+        let hits = json(send("GET", "/search/text?q=rust&collection=people", None).await.unwrap()).await;
+        assert_eq!(hits.as_array().unwrap().len(), 1);
+        assert_eq!(hits[0]["id"], alice.as_str());
+
+        let collections = json(send("GET", "/collections", None).await.unwrap()).await;
+        assert_eq!(collections[0]["name"], "papers");
+        assert_eq!(collections[1]["name"], "people");
+        let stats = json(send("GET", "/collections/people", None).await.unwrap()).await;
+        assert_eq!(stats["hexads"], 1);
+        assert_eq!(stats["modalities"]["document"], 1);
+        let response = send("GET", "/collections/missing", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Each collection keeps soft deletes for its own window
+        send("DELETE", &format!("/hexads/{}", alice), None).await.unwrap();
+        state.config.collection_soft_delete_purge_after_secs.insert("papers".to_string(), 0);
+        assert!(purge_soft_deleted(&state).await.unwrap().is_empty());
+        state.config.collection_soft_delete_purge_after_secs.insert("people".to_string(), 0);
+        assert_eq!(purge_soft_deleted(&state).await.unwrap(), vec![HexadId::new(&alice)]);
+    }
+
+    #[tokio::test]
+    async fn test_hexad_expiry() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let expires_at = chrono::Utc::now() - chrono::Duration::seconds(1);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"title": "Scratch", "body": "experiment", "expires_at": expires_at})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["status"]["expires_at"], expires_at.to_rfc3339());
+        let id = HexadId::new(created["id"].as_str().unwrap());
+
+        let metrics = |app: Router| async move {
+            let response = app
+                .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        };
+        assert!(metrics(app.clone()).await.contains("verisimdb_hexad_expiry{state=\"overdue\"} 1"));
+
+        assert_eq!(state.hexad_store.expire_due(chrono::Utc::now(), "system").await.unwrap(), vec![id.clone()]);
+        assert!(state.hexad_store.get(&id).await.unwrap().is_none());
+        let text = metrics(app).await;
+        assert!(text.contains("verisimdb_hexads_expired_total 1"));
+        assert!(text.contains("verisimdb_hexad_expiry{state=\"scheduled\"} 0"));
+    }
+
+    #[tokio::test]
+    async fn test_consistency_endpoints() {
+        let state = create_test_state().await;
+        let hexad = state
+            .hexad_store
+            .create(
+                verisim_hexad::HexadBuilder::new()
+                    .with_document("Checked", "entity")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let app = build_router(state.clone());
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().method("POST").uri("/consistency/verify").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["entities_checked"], 1);
+        assert!(report["inconsistent"].as_array().unwrap().is_empty());
+        let schema = state.drift_detector.get_metrics(DriftType::SchemaDrift).unwrap().unwrap();
+        assert_eq!(schema.current_score, 0.0);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(format!("/consistency/{}", hexad.id)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let entity: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(entity["violations"], serde_json::json!([]));
+
+        let response = app
+            .oneshot(Request::builder().uri("/consistency/missing").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_count_endpoints() {
+        let state = create_test_state().await;
+        state
+            .hexad_store
+            .create_in(
+                "people",
+                verisim_hexad::HexadBuilder::new()
+                    .with_document("Alice", "Rust engineer")
+                    .with_embedding(vec![1.0, 0.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Loose", "Rust").build())
+            .await
+            .unwrap();
+        let app = build_router(state);
+        let get = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let count = json(get("/hexads/count").await.unwrap()).await;
+        assert_eq!((count["count"].as_u64(), count["estimated"].as_bool()), (Some(2), Some(false)));
+        let count = json(get("/hexads/count?collection=people&modalities=document,vector").await.unwrap()).await;
+        assert_eq!(count["count"], 1);
+        let count = json(get("/hexads/count?modalities=vector").await.unwrap()).await;
+        assert_eq!(count["count"], 1);
+        let count = json(get("/hexads/count?collection=people&estimate=true").await.unwrap()).await;
+        assert_eq!((count["count"].as_u64(), count["estimated"].as_bool()), (Some(1), Some(true)));
+
+        let response = get("/hexads/count?modalities=smell").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = get("/hexads/count?estimate=true&modalities=vector").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let stats = json(get("/admin/stats").await.unwrap()).await;
+        assert_eq!(stats["hexads"]["hexads"], 2);
+        assert_eq!(stats["hexads"]["collections"]["people"], 1);
+        assert_eq!(stats["expiry"]["scheduled"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_snapshot_endpoints() {
+        let state = create_test_state().await;
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Draft", "Rust notes").build())
+            .await
+            .unwrap();
+        let app = build_router(state.clone());
+        let send = |method: &str, uri: &str| {
+            app.clone().oneshot(Request::builder().method(method).uri(uri).body(Body::empty()).unwrap())
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let response = send("POST", "/snapshots").await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let opened = json(response).await;
+        assert_eq!(opened["hexads"], 1);
+        let snapshot = opened["id"].as_str().unwrap().to_string();
+
+        state
+            .hexad_store
+            .update(&hexad.id, verisim_hexad::HexadBuilder::new().with_document("Final", "Rust notes").build())
+            .await
+            .unwrap();
+        state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Later", "Rust").build())
+            .await
+            .unwrap();
+
+        let listed = json(send("GET", &format!("/hexads?snapshot={snapshot}")).await.unwrap()).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["status"]["version"], 1);
+        assert_eq!(json(send("GET", "/hexads").await.unwrap()).await.as_array().unwrap().len(), 2);
+        let hits = json(send("GET", &format!("/search/text?q=rust&snapshot={snapshot}")).await.unwrap()).await;
+        assert_eq!(hits.as_array().unwrap().len(), 1);
+        assert_eq!(hits[0]["title"], "Draft");
+
+        assert_eq!(send("DELETE", &format!("/snapshots/{snapshot}")).await.unwrap().status(), StatusCode::NO_CONTENT);
+        let response = send("GET", &format!("/hexads?snapshot={snapshot}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(send("DELETE", &format!("/snapshots/{snapshot}")).await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_vql_match_joins_graph_text_and_vector() {
+        let state = create_test_state().await;
+        let store = &state.hexad_store;
+        let lemma = store
+            .create(
+                verisim_hexad::HexadBuilder::new()
+                    .with_document("Lemma", "Proved by induction")
+                    .with_embedding(vec![0.0, 1.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let lemma_id = lemma.id.to_string();
+        let theorem = |title: &'static str, body: &'static str, embedding: Vec<f32>| {
+            verisim_hexad::HexadBuilder::new()
+                .with_document(title, body)
+                .with_embedding(embedding)
+                .with_relationships(vec![("provedBy", lemma_id.as_str())])
+                .build()
+        };
+        let near = store.create(theorem("Near", "Induction on n", vec![1.0, 0.0, 0.0])).await.unwrap();
+        let far = store.create(theorem("Far", "Induction on lists", vec![0.5, 0.5, 0.0])).await.unwrap();
+        store.create(theorem("Cases", "Case analysis", vec![1.0, 0.0, 0.0])).await.unwrap();
+
+        let app = build_router(state.clone());
+        let vql = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let query = "MATCH related(x, 'provedBy') WHERE text ~ 'induction' ORDER BY similarity(x.embedding, $v)";
+        let response = vql(serde_json::json!({ "query": query, "params": { "v": [1.0, 0.0, 0.0] } }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let result = json(response).await;
+        let ids: Vec<&str> = result["data"].as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec![near.id.as_str(), far.id.as_str()]);
+        assert!(result["message"].as_str().unwrap().starts_with("Join order: document"));
+
+        let explained = json(vql(serde_json::json!({ "query": format!("EXPLAIN {query}") })).await.unwrap()).await;
+        assert_eq!(explained["data"]["plan"]["join_order"], serde_json::json!([1, 0]));
+
+        // Hints pin the query's order without changing the result
+        let hinted = query.replacen("MATCH", "MATCH /*+ NO_REORDER USE_INDEX(graph) */", 1);
+        let explained = json(vql(serde_json::json!({ "query": format!("EXPLAIN {hinted}") })).await.unwrap()).await;
+        assert_eq!(explained["data"]["plan"]["join_order"], serde_json::json!([0, 1]));
+        assert!(explained["data"]["plan"]["notes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|n| n == "Hints applied: USE_INDEX(graph) NO_REORDER"));
+        let result = json(vql(serde_json::json!({ "query": hinted, "params": { "v": [1.0, 0.0, 0.0] } })).await.unwrap()).await;
+        let ids: Vec<&str> = result["data"].as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec![near.id.as_str(), far.id.as_str()]);
+        let response = vql(serde_json::json!({ "query": "MATCH /*+ FASTER */ x" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let anchored = format!("MATCH related('{}', x, 'provedBy')", near.id);
+        let result = json(vql(serde_json::json!({ "query": anchored })).await.unwrap()).await;
+        assert_eq!(result["row_count"], 1);
+        assert_eq!(result["data"][0]["id"], lemma_id.as_str());
+
+        let response = vql(serde_json::json!({ "query": query })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_vql_aggregates() {
+        let state = create_test_state().await;
+        for (title, pages, embedded) in [("A", "10", true), ("B", "30", true), ("C", "5", false)] {
+            let mut builder = verisim_hexad::HexadBuilder::new().with_document(title, "body");
+            if embedded {
+                builder = builder.with_embedding(vec![1.0, 0.0, 0.0]);
+            }
+            let mut input = builder.build();
+            input.document.as_mut().unwrap().fields.insert("pages".to_string(), pages.to_string());
+            state.hexad_store.create(input).await.unwrap();
+        }
+
+        let app = build_router(state);
+        let vql = |query: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({ "query": query }).to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let result = json(
+            vql("SELECT COUNT(*), SUM(document.pages), AVG(document.pages), MIN(title), MAX(document.pages) FROM hexads")
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(result["row_count"], 1);
+        let row = &result["data"][0];
+        assert_eq!(row["count(*)"], 3);
+        assert_eq!(row["sum(document.pages)"], 45.0);
+        assert_eq!(row["avg(document.pages)"], 15.0);
+        assert_eq!(row["min(title)"], "A");
+        assert_eq!(row["max(document.pages)"], "30");
+
+        let result = json(
+            vql("SELECT has_vector, COUNT(*) AS n FROM hexads WHERE version = 1 GROUP BY has_vector")
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(
+            result["data"],
+            serde_json::json!([{ "has_vector": false, "n": 1 }, { "has_vector": true, "n": 2 }])
+        );
+
+        let response = vql("SELECT title, COUNT(*) FROM hexads").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_vql_temporal_clauses() {
+        let state = create_test_state().await;
+        let start = chrono::Utc::now();
+        let hexad = state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Draft", "v1").build())
+            .await
+            .unwrap();
+        let between = chrono::Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        state
+            .hexad_store
+            .update(&hexad.id, verisim_hexad::HexadBuilder::new().with_document("Final", "v2").build())
+            .await
+            .unwrap();
+
+        let app = build_router(state);
+        let vql = |query: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({ "query": query }).to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let as_of = between.to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+        let result = json(vql(format!("SELECT * FROM hexads WHERE id = '{}' AS OF '{as_of}'", hexad.id)).await.unwrap()).await;
+        assert_eq!(result["data"][0]["status"]["version"], 1);
+        assert_eq!(result["data"][0]["document"]["title"], "Draft");
+        let result = json(vql(format!("SELECT * FROM hexads AS OF '{as_of}'")).await.unwrap()).await;
+        assert_eq!(result["row_count"], 1);
+
+        let end = (chrono::Utc::now() + chrono::Duration::seconds(1)).to_rfc3339();
+        let query = format!(
+            "SELECT * FROM hexads WHERE id = '{}' VERSIONS BETWEEN '{}' AND '{end}'",
+            hexad.id,
+            start.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+        );
+        let result = json(vql(query).await.unwrap()).await;
+        assert_eq!(result["row_count"], 2);
+        assert_eq!(result["data"][1]["document"]["title"], "Final");
+
+        let response = vql(format!("SELECT * FROM hexads WHERE id = '{}' AS OF '2000-01-01'", hexad.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = vql(format!("SELECT * FROM hexads VERSIONS BETWEEN '2000-01-01' AND '{end}'")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_vql_similar_to() {
+        let state = create_test_state().await;
+        let mut ids = Vec::new();
+        for (title, embedding, model) in [
+            ("exact", vec![1.0, 0.0, 0.0], "minilm"),
+            ("close", vec![0.9, 0.1, 0.0], "minilm"),
+            ("other-model", vec![1.0, 0.0, 0.0], "e5"),
+            ("far", vec![0.0, 0.0, 1.0], "minilm"),
+        ] {
+            let mut input = verisim_hexad::HexadBuilder::new()
+                .with_document(title, "body")
+                .with_embedding(embedding)
+                .build();
+            input.vector.as_mut().unwrap().model = Some(model.to_string());
+            ids.push(state.hexad_store.create(input).await.unwrap().id.to_string());
+        }
+
+        let app = build_router(state);
+        let vql = |query: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "query": query, "params": { "v": [1.0, 0.0, 0.0] } }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let titles = |result: &serde_json::Value| -> Vec<String> {
+            result["data"].as_array().unwrap().iter().map(|r| r["title"].as_str().unwrap().to_string()).collect()
+        };
+
+        let result = json(vql("SELECT * FROM hexads SIMILAR TO $v USING 'minilm' LIMIT 2").await.unwrap()).await;
+        assert_eq!(titles(&result), vec!["exact", "close"]);
+
+        let query = format!("SELECT * FROM hexads WHERE id = '{}' SIMILAR TO [1, 0, 0] LIMIT 2", ids[3]);
+        let result = json(vql(&query).await.unwrap()).await;
+        assert_eq!(titles(&result), vec!["far"]);
+
+        let explained = json(vql("EXPLAIN SELECT * FROM hexads SIMILAR TO $v USING 'e5' LIMIT 5").await.unwrap()).await;
+        assert_eq!(explained["data"]["plan"]["steps"][0]["modality"], "vector");
+
+        let response = vql("SELECT * FROM hexads SIMILAR TO [1, 0] LIMIT 2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_vql_insert_update_delete() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let vql = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let ids = |result: &serde_json::Value| -> Vec<String> {
+            result["data"]["ids"].as_array().unwrap().iter().map(|id| id.as_str().unwrap().to_string()).collect()
+        };
+
+        let mut created = Vec::new();
+        for title in ["draft one", "draft two", "final"] {
+            let query = format!("INSERT HEXAD {{title: '{}', body: 'text', types: ['Paper'], embedding: $v}}", title);
+            let result = json(vql(serde_json::json!({ "query": query, "params": { "v": [1.0, 0.0, 0.0] } })).await.unwrap()).await;
+            assert_eq!(result["statement_type"], "INSERT");
+            created.extend(ids(&result));
+        }
+        assert_eq!(created.len(), 3);
+        let hexad = state.hexad_store.get(&HexadId::new(&created[0])).await.unwrap().unwrap();
+        assert_eq!(hexad.document.unwrap().title, "draft one");
+        assert_eq!(hexad.embedding.unwrap().vector, vec![1.0, 0.0, 0.0]);
+
+        // Conditional update touches only the matching entities.
+        let result = json(
+            vql(serde_json::json!({ "query": "UPDATE hexads SET {title: 'final', body: 'done'} WHERE title = 'draft two'" }))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(ids(&result), vec![created[1].clone()]);
+        let hexad = state.hexad_store.get(&HexadId::new(&created[1])).await.unwrap().unwrap();
+        assert_eq!(hexad.document.unwrap().body, "done");
+
+        let result = json(vql(serde_json::json!({ "query": "DELETE FROM hexads WHERE title = 'final'" })).await.unwrap()).await;
+        let mut deleted = ids(&result);
+        deleted.sort();
+        let mut expected = vec![created[1].clone(), created[2].clone()];
+        expected.sort();
+        assert_eq!(deleted, expected);
+        assert!(state.hexad_store.get(&HexadId::new(&created[2])).await.unwrap().is_none());
+
+        let response = vql(serde_json::json!({ "query": "UPDATE HEXAD 'missing' SET {title: 'x'}" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Inside a transaction the writes are buffered, not applied.
+        let txn_id = state.transaction_manager.begin().await.unwrap();
+        let query = format!("DELETE HEXAD '{}'", created[0]);
+        let result = json(vql(serde_json::json!({ "query": query, "transaction": txn_id.as_str() })).await.unwrap()).await;
+        assert_eq!(ids(&result), vec![created[0].clone()]);
+        assert_eq!(state.transaction_manager.status(&txn_id).await.unwrap().operation_count, 1);
+        assert!(state.hexad_store.get(&HexadId::new(&created[0])).await.unwrap().is_some());
+
+        let response = vql(serde_json::json!({ "query": query, "transaction": "no-such-txn" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commit_applies_buffered_writes() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let post = |uri: String, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let mut existing = Vec::new();
+        for title in ["kept", "dropped"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "text").build();
+            existing.push(state.hexad_store.create(input).await.unwrap().id);
+        }
+        let begin = || async {
+            let response = post("/transactions/begin".to_string(), serde_json::json!({})).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            json(response).await["id"].as_str().unwrap().to_string()
+        };
+
+        let txn = begin().await;
+        for query in [
+            "INSERT HEXAD {title: 'added', body: 'text'}".to_string(),
+            format!("UPDATE HEXAD '{}' SET {{title: 'kept', body: 'edited'}}", existing[0]),
+            format!("DELETE HEXAD '{}'", existing[1]),
+        ] {
+            let response = post("/vql/execute".to_string(), serde_json::json!({ "query": query, "transaction": txn })).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(state.hexad_store.list(100, 0).await.unwrap().len(), 2);
+
+        let response = post(format!("/transactions/{}/commit", txn), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let result = json(response).await;
+        assert_eq!(result["state"], "Committed");
+        let ids: Vec<String> = serde_json::from_value(result["ids"].clone()).unwrap();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[1..], [existing[0].to_string(), existing[1].to_string()]);
+        let added = state.hexad_store.get(&HexadId::new(&ids[0])).await.unwrap().unwrap();
+        assert_eq!(added.document.unwrap().title, "added");
+        let kept = state.hexad_store.get(&existing[0]).await.unwrap().unwrap();
+        assert_eq!(kept.document.unwrap().body, "edited");
+        assert!(state.hexad_store.get(&existing[1]).await.unwrap().is_none());
+
+        // An operation whose entity has gone since the transaction began
+        // fails the commit, and none of the transaction's writes are applied.
+        let txn = begin().await;
+        for query in [
+            "INSERT HEXAD {title: 'orphan', body: 'text'}".to_string(),
+            format!("UPDATE HEXAD '{}' SET {{title: 'kept', body: 'again'}}", existing[0]),
+        ] {
+            let response = post("/vql/execute".to_string(), serde_json::json!({ "query": query, "transaction": txn })).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        state.hexad_store.delete(&existing[0]).await.unwrap();
+        let response = post(format!("/transactions/{}/commit", txn), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let status = state.transaction_manager.status(&transaction::TransactionId::from_str(&txn)).await.unwrap();
+        assert_eq!(status.state, transaction::TransactionState::RolledBack);
+        let titles: Vec<String> = state
+            .hexad_store
+            .list(100, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|h| h.document.map(|d| d.title))
+            .collect();
+        assert_eq!(titles, vec!["added".to_string()]);
+
+        let response = post(format!("/transactions/{}/commit", txn), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_is_all_or_nothing() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let batch = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads/batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = batch(serde_json::json!([
+            {"title": "first", "body": "text"},
+            {"title": "second", "body": "text", "collection": "papers"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: HexadBatchResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created.ids.len(), 2);
+        assert!(created.ids[1].starts_with("papers:"));
+        let first = state.hexad_store.get(&HexadId::new(&created.ids[0])).await.unwrap().unwrap();
+        assert_eq!(first.document.unwrap().title, "first");
+
+        // The invalid collection fails the batch after the first hexad was
+        // written, which is undone
+        let response = batch(serde_json::json!([
+            {"title": "third", "body": "text"},
+            {"title": "fourth", "body": "text", "collection": "not valid"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(state.hexad_store.list(100, 0).await.unwrap().len(), 2);
+
+        let response = batch(serde_json::json!([])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // The transaction functions the NIF bridge calls
+        let status = begin_transaction(&state, Default::default()).await.unwrap();
+        let statement = vql::VqlExecuteRequest {
+            query: "INSERT HEXAD {title: 'buffered', body: 'text'}".to_string(),
+            params: Default::default(),
+            transaction: Some(status.id.clone()),
+        };
+        let Json(buffered) = vql::vql_execute_handler(State(state.clone()), None, HeaderMap::new(), Json(statement))
+            .await
+            .unwrap();
+        assert_eq!(buffered.row_count, 1);
+        let rolled_back = rollback_transaction(&state, &status.id).await.unwrap();
+        assert_eq!(rolled_back.state, transaction::TransactionState::RolledBack);
+        assert!(commit_transaction(&state, &status.id, None).await.is_err());
+        assert_eq!(state.hexad_store.list(100, 0).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_identical_proof_requests_are_served_from_the_cache() {
+        let app = build_router(create_test_state().await);
+        let generate = |body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method("POST")
+                            .uri("/proofs/generate")
+                            .header("content-type", "application/json")
+                            .body(Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<ProofResponse>(&body).unwrap()
+            }
+        };
+
+        let claim = serde_json::json!({"claim": "balance >= 1000", "privacy_level": "private"});
+        let first = generate(claim.clone()).await;
+        assert!(first.success && !first.cached);
+        let second = generate(claim).await;
+        assert!(second.cached);
+        assert_eq!(second.proof.unwrap().generated_at, first.proof.unwrap().generated_at);
+        assert!(!generate(serde_json::json!({"claim": "balance >= 1000"})).await.cached);
+
+        let response = app
+            .oneshot(Request::builder().uri("/proofs/cache").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats: ProofCacheStats = serde_json::from_slice(&body).unwrap();
+        assert_eq!((stats.entries, stats.hit_count, stats.miss_count), (2, 1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_circuit_versions_register_deprecate_and_keep_verifying() {
+        let app = build_router(create_test_state().await);
+        let request = |method: &str, uri: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        // a * b = c, with a and b public
+        let register = |version: &str| {
+            serde_json::json!({
+                "version": version,
+                "description": "Product check",
+                "definition": {
+                    "name": "mul-check",
+                    "wires": [
+                        {"name": "a", "is_public": true, "is_output": false},
+                        {"name": "b", "is_public": true, "is_output": false},
+                        {"name": "c", "is_public": false, "is_output": false}
+                    ],
+                    "gates": [{"gate_type": "And", "inputs": ["a", "b"], "output": "c"}],
+                    "parameters": ["a"]
+                }
+            })
+        };
+
+        let (status, info) = request("POST", "/circuits", register("1.0.0")).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((info["version"].as_str(), info["parameters"].clone()), (Some("1.0.0"), serde_json::json!(["a"])));
+        assert_eq!(request("POST", "/circuits", register("1.0.0")).await.0, StatusCode::CONFLICT);
+        assert_eq!(request("POST", "/circuits", register("1.0")).await.0, StatusCode::BAD_REQUEST);
+
+        let prove = serde_json::json!({
+            "claim": "3 * 4 = 12",
+            "circuit_name": "mul-check",
+            "witness": [12.0],
+            "public_inputs": [3.0, 4.0]
+        });
+        let (_, generated) = request("POST", "/proofs/generate-with-circuit", prove.clone()).await;
+        assert_eq!(generated["proof"]["circuit_result"]["circuit_version"], "1.0.0");
+        assert_eq!(generated["proof"]["circuit_result"]["satisfied"], true);
+
+        request("POST", "/circuits", register("1.1.0")).await;
+        let (status, info) = request(
+            "POST",
+            "/circuits/mul-check/1.0.0/deprecate",
+            serde_json::json!({"reason": "superseded"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["deprecation_reason"], "superseded");
+        let (_, versions) = request("GET", "/circuits/mul-check", serde_json::Value::Null).await;
+        assert_eq!(versions.as_array().unwrap().len(), 2);
+        assert_eq!(versions[0]["deprecated"], true);
+
+        // New proofs move to 1.1.0 and may not pin 1.0.0, while the proof
+        // made with 1.0.0 still verifies
+        let (_, generated_again) = request("POST", "/proofs/generate-with-circuit", prove.clone()).await;
+        assert_eq!(generated_again["proof"]["circuit_result"]["circuit_version"], "1.1.0");
+        let mut pinned = prove.clone();
+        pinned["circuit_version"] = "1.0.0".into();
+        let (_, refused) = request("POST", "/proofs/generate-with-circuit", pinned).await;
+        assert_eq!(refused["success"], false);
+        let (_, verified) = request(
+            "POST",
+            "/proofs/verify",
+            serde_json::json!({"proof": generated["proof"], "claim": "3 * 4 = 12"}),
+        )
+        .await;
+        assert_eq!(verified["verified"], true);
+
+        assert_eq!(
+            request("POST", "/circuits/mul-check/2.0.0/deprecate", serde_json::json!({})).await.0,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(request("GET", "/circuits/unknown", serde_json::Value::Null).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_proofs_attach_to_hexads_and_go_stale_on_update() {
+        use verisim_hexad::SemanticStore;
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let request = |method: &str, uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let (_, created) = request(
+            "POST",
+            "/hexads".to_string(),
+            serde_json::json!({"title": "Sensor 7", "body": "Calibrated in March"}),
+        )
+        .await;
+        let id = created["id"].as_str().unwrap().to_string();
+        let prove = serde_json::json!({"claim": "sensor 7 is calibrated", "entity_id": id});
+
+        let (_, generated) = request("POST", "/proofs/generate".to_string(), prove.clone()).await;
+        assert_eq!(generated["attached"]["entity_id"], id.as_str());
+        // The cached proof again is the same artifact, not a second one
+        request("POST", "/proofs/generate".to_string(), prove.clone()).await;
+        let (status, proofs) = request("GET", format!("/hexads/{id}/proofs"), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(proofs.as_array().unwrap().len(), 1);
+        assert_eq!(proofs[0]["claim"], "sensor 7 is calibrated");
+        assert!(proofs[0].get("stale_since").is_none());
+        let stored = state.hexad_store.semantic_store().get_proofs("sensor 7 is calibrated").await.unwrap();
+        assert_eq!(stored.len(), 1);
+
+        request(
+            "PUT",
+            format!("/hexads/{id}"),
+            serde_json::json!({"title": "Sensor 7", "body": "Recalibration overdue"}),
+        )
+        .await;
+        let (_, proofs) = request("GET", format!("/hexads/{id}/proofs"), serde_json::Value::Null).await;
+        assert!(proofs[0]["stale_since"].is_string());
+        let schema = state.drift_detector.get_metrics(DriftType::SchemaDrift).unwrap().unwrap();
+        assert_eq!(schema.current_score, 1.0);
+
+        // Proving the claim again attaches a fresh artifact
+        let (_, generated) = request("POST", "/proofs/generate".to_string(), prove).await;
+        assert!(generated["attached"].get("stale_since").is_none());
+
+        let (status, _) = request(
+            "POST",
+            "/proofs/generate".to_string(),
+            serde_json::json!({"claim": "x", "entity_id": "missing"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = request("GET", "/hexads/missing/proofs".to_string(), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_provenance_proof_hides_actors_and_verifies_alone() {
+        let app = build_router(create_test_state().await);
+        let request = |uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let (_, created) = request(
+            "/hexads".to_string(),
+            serde_json::json!({"title": "Contract 12", "body": "Draft"}),
+        )
+        .await;
+        let id = created["id"].as_str().unwrap().to_string();
+        request(
+            format!("/provenance/{id}/record"),
+            serde_json::json!({"event_type": "approved", "actor": "did:example:board", "description": "Board minute 7"}),
+        )
+        .await;
+
+        let prove = |actors: serde_json::Value| {
+            request(format!("/provenance/{id}/prove"), serde_json::json!({"event_type": "approved", "authorized_actors": actors}))
+        };
+        let (status, proof) = prove(serde_json::json!(["did:example:board", "did:example:auditor"])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(proof["event_type"], "custom:approved");
+        assert!(!proof.to_string().contains("did:example") && !proof.to_string().contains("minute"));
+
+        let (_, verified) = request("/proofs/provenance/verify".to_string(), proof.clone()).await;
+        assert_eq!(verified["verified"], true);
+        let mut forged = proof.clone();
+        forged["entity_id"] = "another".into();
+        let (_, verified) = request("/proofs/provenance/verify".to_string(), forged).await;
+        assert_eq!(verified["verified"], false);
+
+        let (status, _) = prove(serde_json::json!(["did:example:auditor"])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = request("/provenance/missing/prove".to_string(), serde_json::json!({"event_type": "created", "authorized_actors": []})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_annotation_disclosure_is_anchored_in_provenance() {
+        use verisim_hexad::SemanticStore;
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let request = |uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let semantic = state.hexad_store.semantic_store();
+        semantic
+            .register_type(&verisim_semantic::SemanticType::new("https://example.org/Theorem", "Theorem"))
+            .await
+            .unwrap();
+        semantic
+            .register_type(
+                &verisim_semantic::SemanticType::new("https://example.org/Lemma", "Lemma")
+                    .with_supertype("https://example.org/Theorem"),
+            )
+            .await
+            .unwrap();
+        let (_, created) = request(
+            "/hexads".to_string(),
+            serde_json::json!({"title": "Lemma 4", "body": "Secret statement", "types": ["https://example.org/Lemma"]}),
+        )
+        .await;
+        let id = created["id"].as_str().unwrap().to_string();
+        let prove = |predicate: serde_json::Value| request(format!("/hexads/{id}/annotation/prove"), predicate);
+
+        let (status, proof) = prove(serde_json::json!({"predicate": "subtype_of", "type_iri": "https://example.org/Theorem"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(proof["type_path"], serde_json::json!(["https://example.org/Lemma", "https://example.org/Theorem"]));
+        let (_, verified) = request("/proofs/disclosure/verify".to_string(), proof.clone()).await;
+        assert_eq!(verified["verified"], true);
+
+        // A second proof reuses the anchor rather than recording another
+        let (_, confident) = prove(serde_json::json!({"predicate": "confidence_above", "threshold": 0.5})).await;
+        assert_eq!(confident["anchor"], proof["anchor"]);
+        let chain = state.hexad_store.provenance_store().get_chain(&id).await.unwrap();
+        let anchors = chain.records.iter().filter(|r| r.content_hash == proof["anchor"]).count();
+        assert_eq!(anchors, 1);
+
+        let mut unanchored = proof.clone();
+        unanchored["anchor"] = "0".repeat(64).into();
+        let (_, verified) = request("/proofs/disclosure/verify".to_string(), unanchored).await;
+        assert_eq!(verified["verified"], false);
+
+        let (status, _) = prove(serde_json::json!({"predicate": "subtype_of", "type_iri": "https://example.org/Axiom"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_proofs_expire_and_are_revoked_by_entity_changes() {
+        let app = build_router(create_test_state().await);
+        let request = |method: &str, uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let verify = |proof: &serde_json::Value, claim: &str| {
+            request(
+                "POST",
+                "/proofs/verify".to_string(),
+                serde_json::json!({"proof": proof, "claim": claim}),
+            )
+        };
+        let (_, created) = request(
+            "POST",
+            "/hexads".to_string(),
+            serde_json::json!({"title": "Sensor 9", "body": "Calibrated in May"}),
+        )
+        .await;
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let on_vector = serde_json::json!({"claim": "sensor 9 has an embedding", "entity_id": id, "depends_on": ["vector"]});
+        let (_, vector_proof) = request("POST", "/proofs/generate".to_string(), on_vector).await;
+        let on_any = serde_json::json!({"claim": "sensor 9 is calibrated", "entity_id": id});
+        let (_, any_proof) = request("POST", "/proofs/generate".to_string(), on_any.clone()).await;
+        let any_id = any_proof["proof_id"].as_str().unwrap().to_string();
+        assert_eq!(verify(&any_proof["proof"], "sensor 9 is calibrated").await.1["verified"], true);
+
+        // A document change revokes the proof depending on every modality
+        request(
+            "PUT",
+            format!("/hexads/{id}"),
+            serde_json::json!({"title": "Sensor 9", "body": "Recalibration overdue"}),
+        )
+        .await;
+        let (_, verified) = verify(&any_proof["proof"], "sensor 9 is calibrated").await;
+        assert_eq!(verified["verified"], false);
+        assert_eq!(verified["error"], "Proof revoked: document, temporal changed");
+        assert_eq!(verify(&vector_proof["proof"], "sensor 9 has an embedding").await.1["verified"], true);
+        let (_, revoked) = request("GET", format!("/proofs/revocations?entity_id={id}"), serde_json::Value::Null).await;
+        assert_eq!(revoked.as_array().unwrap().len(), 1);
+        assert_eq!(revoked[0]["proof_id"], any_id.as_str());
+
+        // Proving the claim again does not reuse the revoked proof
+        let (_, fresh) = request("POST", "/proofs/generate".to_string(), on_any).await;
+        assert_ne!(fresh["proof_id"], any_id.as_str());
+        assert_eq!(verify(&fresh["proof"], "sensor 9 is calibrated").await.1["verified"], true);
+
+        // Expired proofs stay expired without their expires_at
+        let (_, expiring) = request(
+            "POST",
+            "/proofs/generate".to_string(),
+            serde_json::json!({"claim": "short-lived", "ttl_secs": 0}),
+        )
+        .await;
+        assert!(expiring["proof"]["expires_at"].is_string());
+        let (_, verified) = verify(&expiring["proof"], "short-lived").await;
+        assert_eq!(verified["verified"], false);
+        assert!(verified["error"].as_str().unwrap().starts_with("Proof expired at"));
+        let mut stripped = expiring["proof"].clone();
+        stripped.as_object_mut().unwrap().remove("expires_at");
+        assert_eq!(verify(&stripped, "short-lived").await.1["verified"], false);
+
+        let (status, revocation) = request(
+            "POST",
+            "/proofs/revocations".to_string(),
+            serde_json::json!({"proof": vector_proof["proof"], "reason": "sensor replaced"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(revocation["proof_id"], vector_proof["proof_id"]);
+        assert_eq!(verify(&vector_proof["proof"], "sensor 9 has an embedding").await.1["verified"], false);
+
+        // Deleting the entity revokes what is left
+        request("DELETE", format!("/hexads/{id}"), serde_json::Value::Null).await;
+        let (_, verified) = verify(&fresh["proof"], "sensor 9 is calibrated").await;
+        assert_eq!(verified["error"], "Proof revoked: entity deleted");
+
+        for body in [
+            serde_json::json!({"claim": "x", "depends_on": ["vector"]}),
+            serde_json::json!({"claim": "x", "entity_id": id, "depends_on": ["colour"]}),
+        ] {
+            assert_eq!(request("POST", "/proofs/generate".to_string(), body).await.0, StatusCode::BAD_REQUEST);
+        }
+        let (status, _) = request("POST", "/proofs/revocations".to_string(), serde_json::json!({"reason": "x"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_reads_snapshot_and_own_writes() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let request = |method: &str, uri: String, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let mut ids = Vec::new();
+        for title in ["changed outside", "updated inside", "deleted inside"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "text").build();
+            ids.push(state.hexad_store.create(input).await.unwrap().id.to_string());
+        }
+
+        let response = request("POST", "/transactions/begin".to_string(), serde_json::json!({})).await.unwrap();
+        let begun = json(response).await;
+        assert!(begun["snapshot_at"].is_string());
+        let txn = begun["id"].as_str().unwrap().to_string();
+
+        // Commits after the transaction began are invisible to it...
+        let input = verisim_hexad::HexadBuilder::new().with_document("changed outside", "later").build();
+        state.hexad_store.update(&HexadId::new(&ids[0]), input).await.unwrap();
+        let input = verisim_hexad::HexadBuilder::new().with_document("created outside", "later").build();
+        let outside = state.hexad_store.create(input).await.unwrap().id.to_string();
+
+        // ...while its own buffered writes are
+        for (query, params) in [
+            (format!("UPDATE HEXAD '{}' SET {{embedding: $v}}", ids[1]), serde_json::json!({ "v": [1.0, 0.0, 0.0] })),
+            (format!("DELETE HEXAD '{}'", ids[2]), serde_json::json!({})),
+            ("INSERT HEXAD {title: 'inserted inside', body: 'text'}".to_string(), serde_json::json!({})),
+        ] {
+            let body = serde_json::json!({ "query": query, "params": params, "transaction": txn });
+            let response = request("POST", "/vql/execute".to_string(), body).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let get = |hexad_id: &str| request("GET", format!("/transactions/{}/hexads/{}", txn, hexad_id), serde_json::json!({}));
+        let seen = json(get(&ids[0]).await.unwrap()).await;
+        assert_eq!(seen["status"]["version"], 1);
+        let seen = json(get(&ids[1]).await.unwrap()).await;
+        assert_eq!(seen["status"]["version"], 2);
+        assert_eq!(seen["has_vector"], true);
+        assert_eq!(get(&ids[2]).await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get(&outside).await.unwrap().status(), StatusCode::NOT_FOUND);
+        let seen = json(get("pending-0").await.unwrap()).await;
+        assert_eq!(seen["has_document"], true);
+
+        let response = request("GET", format!("/transactions/{}/hexads", txn), serde_json::json!({})).await.unwrap();
+        let listed: Vec<String> = json(response)
+            .await
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["id"].as_str().unwrap().to_string())
+            .collect();
+        let mut expected = vec![ids[0].clone(), ids[1].clone()];
+        expected.sort();
+        expected.push("pending-0".to_string());
+        assert_eq!(listed, expected);
+        let response = request("GET", format!("/transactions/{}/hexads?offset=2", txn), serde_json::json!({})).await.unwrap();
+        assert_eq!(json(response).await.as_array().unwrap().len(), 1);
+
+        // Outside the transaction nothing has changed yet
+        let current = state.hexad_store.get(&HexadId::new(&ids[1])).await.unwrap().unwrap();
+        assert!(current.embedding.is_none());
+
+        // The transaction read an entity changed since it began, so its
+        // commit conflicts
+        let response = request("POST", format!("/transactions/{}/commit", txn), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(get(&ids[0]).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_transactions_abort_later_committer() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let post = |uri: String, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let mut ids = Vec::new();
+        for title in ["shared", "only first", "only second"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "v1").build();
+            ids.push(state.hexad_store.create(input).await.unwrap().id.to_string());
+        }
+
+        let mut txns = Vec::new();
+        for own in [&ids[1], &ids[2]] {
+            let response = post("/transactions/begin".to_string(), serde_json::json!({})).await.unwrap();
+            let txn = json(response).await["id"].as_str().unwrap().to_string();
+            for target in [&ids[0], own] {
+                let query = format!("UPDATE HEXAD '{}' SET {{title: 'edited', body: '{}'}}", target, txn);
+                let response = post("/vql/execute".to_string(), serde_json::json!({ "query": query, "transaction": txn })).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+            }
+            txns.push(txn);
+        }
+
+        let response = post(format!("/transactions/{}/commit", txns[0]), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = post(format!("/transactions/{}/commit", txns[1]), serde_json::json!({})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let error = json(response).await;
+        assert_eq!(error["code"], 409);
+        assert_eq!(error["conflicting_ids"], serde_json::json!([ids[0]]));
+
+        // The first commit stands and none of the second's writes landed
+        let shared = state.hexad_store.get(&HexadId::new(&ids[0])).await.unwrap().unwrap();
+        assert_eq!(shared.document.unwrap().body, txns[0]);
+        let untouched = state.hexad_store.get(&HexadId::new(&ids[2])).await.unwrap().unwrap();
+        assert_eq!(untouched.status.version, 1);
+        let status = state.transaction_manager.status(&transaction::TransactionId::from_str(&txns[1])).await.unwrap();
+        assert_eq!(status.state, transaction::TransactionState::RolledBack);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_isolation_levels_and_expiry() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let request = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(body) => builder.header("content-type", "application/json").body(Body::from(body.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let input = verisim_hexad::HexadBuilder::new().with_document("shared", "v1").build();
+        let id = state.hexad_store.create(input).await.unwrap().id.to_string();
+
+        // With no body the transaction gets snapshot isolation
+        let begun = json(request("POST", "/transactions/begin".to_string(), None).await.unwrap()).await;
+        assert_eq!(begun["isolation"], "snapshot");
+        assert!(begun["snapshot_at"].is_string() && begun["expires_at"].is_string());
+
+        // Read committed sees commits made after it began, and its commit
+        // does not conflict with them
+        let body = serde_json::json!({ "isolation": "read_committed" });
+        let begun = json(request("POST", "/transactions/begin".to_string(), Some(body)).await.unwrap()).await;
+        assert_eq!(begun["isolation"], "read_committed");
+        assert!(begun["snapshot_at"].is_null());
+        let txn = begun["id"].as_str().unwrap().to_string();
+        let query = format!("UPDATE HEXAD '{}' SET {{title: 'shared', body: 'mine'}}", id);
+        let body = serde_json::json!({ "query": query, "transaction": txn });
+        assert_eq!(request("POST", "/vql/execute".to_string(), Some(body)).await.unwrap().status(), StatusCode::OK);
+        let input = verisim_hexad::HexadBuilder::new().with_document("shared", "theirs").build();
+        state.hexad_store.update(&HexadId::new(&id), input).await.unwrap();
+        let seen = json(request("GET", format!("/transactions/{}/hexads/{}", txn, id), None).await.unwrap()).await;
+        assert_eq!(seen["status"]["version"], 3);
+        let response = request("POST", format!("/transactions/{}/commit", txn), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let hexad = state.hexad_store.get(&HexadId::new(&id)).await.unwrap().unwrap();
+        assert_eq!(hexad.document.unwrap().body, "mine");
+
+        // A transaction idle past its timeout is rolled back as expired
+        let body = serde_json::json!({ "idle_timeout_secs": 0 });
+        let begun = json(request("POST", "/transactions/begin".to_string(), Some(body)).await.unwrap()).await;
+        let txn = begun["id"].as_str().unwrap().to_string();
+        let status = json(request("GET", format!("/transactions/{}", txn), None).await.unwrap()).await;
+        assert_eq!(status["state"], "Expired");
+        let response = request("POST", format!("/transactions/{}/commit", txn), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Timeouts beyond the maximum are refused, not begun
+        let body = serde_json::json!({ "timeout_secs": u64::MAX });
+        let response = request("POST", "/transactions/begin".to_string(), Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = request("GET", "/metrics".to_string(), None).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        assert!(metrics.contains("verisimdb_transactions{state=\"expired_total\"} 1"));
+        assert!(metrics.contains("verisimdb_transactions{state=\"active\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_transaction_locks_and_deadlock_detection() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
+        let request = |method: &str, uri: String, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(body) => builder.header("content-type", "application/json").body(Body::from(body.to_string())),
+                None => builder.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let mut ids = Vec::new();
+        for title in ["first", "second"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "v1").build();
+            ids.push(state.hexad_store.create(input).await.unwrap().id.to_string());
+        }
+        let mut txns = Vec::new();
+        for _ in 0..2 {
+            let begun = json(request("POST", "/transactions/begin".to_string(), None).await.unwrap()).await;
+            txns.push(begun["id"].as_str().unwrap().to_string());
+        }
+
+        // Each transaction locks one hexad; the lock shows in its status
+        for (txn, id) in txns.iter().zip(&ids) {
+            let body = serde_json::json!({ "entity_id": id });
+            let status = json(request("POST", format!("/transactions/{}/locks", txn), Some(body)).await.unwrap()).await;
+            assert_eq!(status["locks"], serde_json::json!([{ "entity_id": id, "mode": "exclusive" }]));
+        }
+
+        // The other transaction may not write to a locked hexad, nor lock it in time
+        let query = format!("UPDATE HEXAD '{}' SET {{title: 'first', body: 'theirs'}}", ids[0]);
+        let body = serde_json::json!({ "query": query, "transaction": txns[1] });
+        let response = request("POST", "/vql/execute".to_string(), Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = serde_json::json!({ "entity_id": ids[0], "wait_ms": 10 });
+        let response = request("POST", format!("/transactions/{}/locks", txns[1]), Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(json(response).await["conflicting_ids"], serde_json::json!([ids[0]]));
+
+        // The first waits for the second's lock; the second asking for the
+        // first's would close the cycle and is refused at once
+        let waiter = {
+            let (app, txn, id) = (app.clone(), txns[0].clone(), ids[1].clone());
+            tokio::spawn(async move {
+                let body = serde_json::json!({ "entity_id": id, "wait_ms": 5000 });
+                let request = Request::builder()
+                    .method("POST")
+                    .uri(format!("/transactions/{}/locks", txn))
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                app.oneshot(request).await.unwrap()
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let status = json(request("GET", format!("/transactions/{}", txns[0]), None).await.unwrap()).await;
+        assert_eq!(status["waiting_for"]["entity_id"], ids[1].as_str());
+        assert_eq!(status["waiting_for"]["blocked_by"], serde_json::json!([txns[1]]));
+        let body = serde_json::json!({ "entity_id": ids[0] });
+        let response = request("POST", format!("/transactions/{}/locks", txns[1]), Some(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(json(response).await["error"].as_str().unwrap().contains("deadlock"));
+
+        // Rolling back the refused transaction releases its lock to the waiter
+        request("POST", format!("/transactions/{}/rollback", txns[1]), None).await.unwrap();
+        let status = json(waiter.await.unwrap()).await;
+        assert_eq!(status["locks"].as_array().unwrap().len(), 2);
+        assert!(status["waiting_for"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_transaction_commit_is_replayed() {
+        let dir = std::env::temp_dir().join(format!("verisimdb-txn-wal-{}", uuid::Uuid::new_v4()));
+        let with_wal = || {
+            let manager = transaction::TransactionManager::new(Default::default())
+                .with_wal(&dir, verisim_hexad::SyncMode::Fsync)
+                .unwrap();
+            Arc::new(manager)
+        };
+        let mut state = create_test_state().await;
+        state.transaction_manager = with_wal();
+        let app = build_router(state.clone());
+        let post = |uri: String, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let input = verisim_hexad::HexadBuilder::new().with_document("kept", "v1").build();
+        let id = state.hexad_store.create(input).await.unwrap().id;
+
+        let response = post("/transactions/begin".to_string(), serde_json::json!({})).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let begun: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let txn_id = transaction::TransactionId::from_str(begun["id"].as_str().unwrap());
+        for query in [
+            format!("UPDATE HEXAD '{}' SET {{title: 'kept', body: 'v2'}}", id),
+            "INSERT HEXAD {title: 'added', body: 'text'}".to_string(),
+        ] {
+            let body = serde_json::json!({ "query": query, "transaction": txn_id.as_str() });
+            assert_eq!(post("/vql/execute".to_string(), body).await.unwrap().status(), StatusCode::OK);
+        }
+
+        // The server dies after the commit point and the first write
+        let manager = state.transaction_manager.clone();
+        let commit = manager.commit(&txn_id, |_| async {
+            manager.log_commit_point(&txn_id).await?;
+            let input = verisim_hexad::HexadBuilder::new().with_document("kept", "v2").build();
+            state.hexad_store.update(&id, input).await.unwrap();
+            manager.log_applied(&txn_id, 0, id.as_str()).await?;
+            std::future::pending::<Result<(), transaction::TransactionError>>().await
+        });
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), commit).await.is_err());
+        assert_eq!(state.hexad_store.list(100, 0).await.unwrap().len(), 1);
+
+        // On restart the insert is written; the update is not written twice
+        state.transaction_manager = with_wal();
+        assert_eq!(vql::replay_transactions(&state).await.unwrap(), 1);
+        let hexads = state.hexad_store.list(100, 0).await.unwrap();
+        assert_eq!(hexads.len(), 2);
+        let kept = state.hexad_store.get(&id).await.unwrap().unwrap();
+        assert_eq!(kept.document.unwrap().body, "v2");
+        assert_eq!(state.hexad_store.status(&id).await.unwrap().unwrap().version, 2);
+        assert!(state.transaction_manager.recover().unwrap().is_empty());
+        assert_eq!(vql::replay_transactions(&state).await.unwrap(), 0);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_distributed_transaction_two_phase_commit() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let mut peers = Vec::new();
+        for title in ["project-a", "project-b"] {
+            let peer = create_test_state().await;
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "v1").build();
+            let id = peer.hexad_store.create(input).await.unwrap().id;
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(peer.clone()))));
+            peers.push((peer, endpoint, id));
+        }
+        let state = create_test_state().await;
+        for (i, (_, endpoint, _)) in peers.iter().enumerate() {
+            let store_id = format!("peer-{}", i);
+            state.federation.peers.write().unwrap().insert(
+                store_id.clone(),
+                federation::PeerStore {
+                    store_id,
+                    endpoint: endpoint.clone(),
+                    modalities: vec!["document".to_string()],
+                    trust_level: 1.0,
+                    last_seen: None,
+                    response_time_ms: None,
+                    secret_hash: None,
+                },
+            );
+        }
+        let app = build_router(state.clone());
+        let post = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/transactions/distributed")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let update = |i: usize, body: &str| {
+            let title = if i == 0 { "project-a" } else { "project-b" };
+            format!("UPDATE HEXAD '{}' SET {{title: '{}', body: '{}'}}", peers[i].2, title, body)
+        };
+        let body_of = |i: usize| {
+            let (peer, _, id) = &peers[i];
+            let id = id.clone();
+            async move { peer.hexad_store.get(&id).await.unwrap().unwrap().document.unwrap().body }
+        };
+
+        // Both stores commit
+        let request = serde_json::json!({ "participants": [
+            { "store_id": "peer-0", "statements": [update(0, "aligned")] },
+            { "store_id": "peer-1", "statements": [update(1, "aligned"), "INSERT HEXAD {title: 'edge', body: 'a-b'}"] },
+        ]});
+        let response = post(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let txn: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(txn["state"], "committed");
+        assert!(txn["participants"].as_array().unwrap().iter().all(|p| p["state"] == "committed"));
+        assert_eq!((body_of(0).await, body_of(1).await), ("aligned".to_string(), "aligned".to_string()));
+        assert_eq!(peers[1].0.hexad_store.list(100, 0).await.unwrap().len(), 2);
+
+        // One store fails, so neither commits
+        let request = serde_json::json!({ "participants": [
+            { "store_id": "peer-0", "statements": [update(0, "orphaned")] },
+            { "store_id": "peer-1", "statements": ["DELETE HEXAD 'no-such-hexad'"] },
+        ]});
+        let response = post(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(body_of(0).await, "aligned");
+        let list = app
+            .clone()
+            .oneshot(Request::builder().uri("/transactions/distributed").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(list.into_body(), usize::MAX).await.unwrap();
+        let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let aborted = &list[1];
+        assert_eq!(aborted["state"], "aborted");
+        assert_eq!(aborted["participants"][0]["state"], "aborted");
+        assert!(aborted["participants"][1]["error"].as_str().unwrap().contains("peer-1"));
+        let response = post(serde_json::json!({ "participants": [{ "store_id": "peer-9", "statements": ["x"] }] })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // A coordinator restarted after deciding to commit finishes the
+        // commit; one restarted before deciding aborts
+        let prepare = |i: usize, statement: String| {
+            let endpoint = peers[i].1.clone();
+            async move {
+                let client = reqwest::Client::new();
+                let begun: serde_json::Value = client
+                    .post(format!("{}/transactions/begin", endpoint))
+                    .json(&serde_json::json!({}))
+                    .send()
+                    .await
+                    .unwrap()
+                    .json()
+                    .await
+                    .unwrap();
+                let local_id = begun["id"].as_str().unwrap().to_string();
+                let body = serde_json::json!({ "query": statement, "transaction": local_id });
+                client.post(format!("{}/vql/execute", endpoint)).json(&body).send().await.unwrap();
+                let url = format!("{}/transactions/{}/prepare", endpoint, local_id);
+                assert!(client.post(url).send().await.unwrap().status().is_success());
+                let mut participant = transaction::Participant::new(format!("peer-{}", i), endpoint, vec![statement]);
+                participant.transaction_id = Some(local_id);
+                participant.state = transaction::ParticipantState::Prepared;
+                participant
+            }
+        };
+        let in_doubt = |id: &str, state: transaction::GlobalState, participant| transaction::GlobalTransaction {
+            id: id.to_string(),
+            state,
+            participants: vec![participant],
+            started_at: chrono::Utc::now().to_rfc3339(),
+            completed_at: None,
+            error: None,
+            principal: None,
+        };
+        let records: std::collections::BTreeMap<_, _> = [
+            ("decided", in_doubt("decided", transaction::GlobalState::Committing, prepare(0, update(0, "recovered")).await)),
+            ("undecided", in_doubt("undecided", transaction::GlobalState::Preparing, prepare(1, update(1, "lost")).await)),
+        ]
         .into_iter()
-        .map(|r| SpatialSearchResultResponse {
-            entity_id: r.entity_id,
-            latitude: r.data.coordinates.latitude,
-            longitude: r.data.coordinates.longitude,
-            distance_km: r.distance_km,
-        })
+        .map(|(id, txn)| (id.to_string(), txn))
         .collect();
+        let path = std::env::temp_dir().join(format!("verisimdb-2pc-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, serde_json::to_string(&records).unwrap()).unwrap();
+        let coordinator = transaction::Coordinator::new().with_persistence(&path).unwrap();
+        assert_eq!(coordinator.resolve_in_doubt().await, 2);
+        assert_eq!(coordinator.get("decided").await.unwrap().state, transaction::GlobalState::Committed);
+        assert_eq!(coordinator.get("undecided").await.unwrap().state, transaction::GlobalState::Aborted);
+        assert_eq!((body_of(0).await, body_of(1).await), ("recovered".to_string(), "aligned".to_string()));
+        let reloaded = transaction::Coordinator::new().with_persistence(&path).unwrap();
+        assert!(reloaded.list().await.iter().all(|t| t.is_finished()));
+        std::fs::remove_file(&path).ok();
+    }
 
-    Ok(Json(response))
-}
+    #[tokio::test]
+    async fn test_federation_search_fans_out_and_reports_slow_peers() {
+        // As in main: reqwest needs a process-wide rustls provider
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let mut endpoints = Vec::new();
+        for titles in [["rust ownership", "rust borrowing"], ["rust traits", "go channels"]] {
+            let peer = create_test_state().await;
+            for title in titles {
+                let input = verisim_hexad::HexadBuilder::new().with_document(title, "notes").build();
+                peer.hexad_store.create(input).await.unwrap();
+            }
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            endpoints.push(format!("http://{}", listener.local_addr().unwrap()));
+            tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(peer))));
+        }
+        // A peer that accepts connections but never answers
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent_endpoint = format!("http://{}", silent.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = silent.accept().await {
+                held.push(socket);
+            }
+        });
 
-/// POST /spatial/search/nearest — find k nearest entities to a point
-#[instrument(skip_all)]
-async fn spatial_nearest_handler(
-    State(state): State<AppState>,
-    Json(body): Json<NearestSearchRequest>,
-) -> Result<Json<Vec<SpatialSearchResultResponse>>, ApiError> {
-    if !(-90.0..=90.0).contains(&body.latitude) || !(-180.0..=180.0).contains(&body.longitude) {
-        return Err(ApiError::BadRequest("Invalid coordinates".to_string()));
+        let state = create_test_state().await;
+        {
+            let mut peers = state.federation.peers.write().unwrap();
+            let peer = |store_id: &str, endpoint: &str, modality: &str| federation::PeerStore {
+                store_id: store_id.to_string(),
+                endpoint: endpoint.to_string(),
+                modalities: vec![modality.to_string()],
+                trust_level: 1.0,
+                last_seen: None,
+                response_time_ms: None,
+                secret_hash: None,
+            };
+            peers.insert("peer-a".to_string(), peer("peer-a", &endpoints[0], "document"));
+            peers.insert("peer-b".to_string(), peer("peer-b", &endpoints[1], "document"));
+            peers.insert("peer-slow".to_string(), peer("peer-slow", &silent_endpoint, "document"));
+            peers.insert("peer-graph".to_string(), peer("peer-graph", "http://127.0.0.1:9", "graph"));
+        }
+        let app = build_router(state.clone());
+
+        let request = serde_json::json!({"query": "rust", "limit": 10, "timeout_ms": 500});
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/federation/search/text")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["normalization"], "min_max");
+        let results = json["results"].as_array().unwrap();
+        let titles: std::collections::BTreeSet<&str> = results.iter().map(|r| r["data"]["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, ["rust borrowing", "rust ownership", "rust traits"].into_iter().collect());
+        let traits = results.iter().find(|r| r["data"]["title"] == "rust traits").unwrap();
+        assert_eq!(traits["origin"], "peer-b");
+        assert_eq!(traits["origin_endpoint"], endpoints[1].as_str());
+        assert_eq!(traits["score"], 1.0);
+        let mut queried: Vec<&str> = json["stores_queried"].as_array().unwrap().iter().map(|s| s.as_str().unwrap()).collect();
+        queried.sort();
+        assert_eq!(queried, ["peer-a", "peer-b", "peer-slow"]);
+        let failed = json["stores_failed"].as_array().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0]["store_id"], "peer-slow");
+        assert!(failed[0]["error"].as_str().unwrap().contains("timed out"));
+
+        // Vector search goes only to peers with vectors
+        let request = serde_json::json!({"vector": [0.1, 0.2, 0.3]});
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/federation/search/vector")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["normalization"], "none");
+        assert_eq!(json["stores_queried"], serde_json::json!([]));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/federation/search/text")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"query": "  "}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
-    let k = body.k.unwrap_or(10).min(MAX_RESULT_LIMIT);
+    #[tokio::test]
+    async fn test_replica_bootstraps_and_follows_primary() {
+        // As in main: reqwest needs a process-wide rustls provider
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let primary = create_test_state().await;
+        let doc = |title: &str| verisim_hexad::HexadBuilder::new().with_document(title, "notes").build();
+        let kept = primary.hexad_store.create(doc("kept")).await.unwrap();
+        let doomed = primary.hexad_store.create(doc("doomed")).await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        let mut replica = create_test_state().await;
+        let stale = replica.hexad_store.create(doc("stale")).await.unwrap();
+        replica.replication.follower = Some(Arc::new(replication::Follower::new(endpoint, "replica-1")));
+        let app = build_router(replica.clone());
+        let sync = || async {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().method("POST").uri("/replication/sync").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
 
-    let point = Coordinates {
-        latitude: body.latitude,
-        longitude: body.longitude,
-        altitude: None,
-    };
+        // Bootstrap from the snapshot export replaces the replica's contents
+        let status = sync().await;
+        assert_eq!(status["bootstraps"], 1);
+        assert_eq!(status["position"], 2);
+        let copy = replica.hexad_store.get(&kept.id).await.unwrap().unwrap();
+        assert_eq!(copy.document.unwrap().title, "kept");
+        assert!(replica.hexad_store.get(&stale.id).await.unwrap().is_none());
+
+        // Then changes stream from the feed
+        primary.hexad_store.update(&kept.id, doc("kept, edited")).await.unwrap();
+        primary.hexad_store.delete(&doomed.id).await.unwrap();
+        let added = primary.hexad_store.create(doc("added")).await.unwrap();
+        let status = sync().await;
+        assert_eq!(status["position"], 5);
+        assert_eq!(status["applied"], 5);
+        assert_eq!(status["lag_entries"], 0);
+        let copy = replica.hexad_store.get(&kept.id).await.unwrap().unwrap();
+        assert_eq!(copy.document.unwrap().title, "kept, edited");
+        assert!(replica.hexad_store.get(&doomed.id).await.unwrap().is_none());
+        assert!(replica.hexad_store.get(&added.id).await.unwrap().is_some());
+
+        // The primary tracks the replica's position as of its last poll
+        sync().await;
+        let followers = primary.replication.log.followers();
+        assert_eq!(followers[0].follower_id, "replica-1");
+        assert_eq!(followers[0].position, 5);
+        assert_eq!(followers[0].lag_entries, 0);
+
+        // Positions from another feed epoch (a restarted primary) call for a
+        // new bootstrap
+        let batch = replication::changes(&primary, 5, 10, Some("another-epoch"), Some("replica-1"), None).await.unwrap();
+        assert!(batch.bootstrap_required);
+        assert!(batch.changes.is_empty());
+
+        // Replicas are read-only
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/hexads")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"title": "local"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"query": "INSERT HEXAD WITH {title: 'local'}"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
 
-    let results = state
-        .hexad_store
-        .spatial_store()
-        .nearest(&point, k)
-        .await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        let response = app
+            .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+        assert!(metrics.contains("verisimdb_replication{stat=\"lag_entries\"} 0"));
+    }
 
-    let response = results
-        .into_iter()
-        .map(|r| SpatialSearchResultResponse {
-            entity_id: r.entity_id,
-            latitude: r.data.coordinates.latitude,
-            longitude: r.data.coordinates.longitude,
-            distance_km: r.distance_km,
-        })
-        .collect();
+    #[tokio::test]
+    async fn test_crdt_sync_converges_after_offline_writes() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let node = |id: &'static str| async move {
+            let mut state = create_test_state().await;
+            let crdt = Arc::new(crdt::CrdtSync::new(id));
+            state.hexad_store.add_listener(Arc::new(crdt::CrdtListener(crdt.clone())));
+            state.crdt = Some(crdt);
+            state
+        };
+        let doc = |title: &str, body: &str| verisim_hexad::HexadBuilder::new().with_document(title, body).build();
+        let a = node("edge-a").await;
+        let b = node("edge-b").await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(b.clone()))));
+        let app = build_router(a.clone());
+        let sync = || async {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/crdt/sync")
+                        .header("content-type", "application/json")
+                        .body(Body::from(serde_json::json!({ "peer": endpoint }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let title = |state: &AppState, id: &verisim_hexad::HexadId| {
+            let (state, id) = (state.clone(), id.clone());
+            async move { state.hexad_store.get(&id).await.unwrap().map(|h| h.document.unwrap()) }
+        };
 
-    Ok(Json(response))
-}
+        // Written before sync began, on a
+        let shared = a.hexad_store.create(doc("draft", "original")).await.unwrap();
+        let report = sync().await;
+        assert_eq!(report["pushed"], 1);
+        assert_eq!(title(&b, &shared.id).await.unwrap().title, "draft");
+
+        // Disconnected, both edit the entity: a the title, b (later) the body
+        a.hexad_store.update(&shared.id, doc("retitled on a", "original")).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        b.hexad_store.update(&shared.id, doc("draft", "rewritten on b")).await.unwrap();
+        let added = b.hexad_store.create(doc("added on b", "")).await.unwrap();
+        let report = sync().await;
+        assert_eq!(report["pulled"], 2);
+        for state in [&a, &b] {
+            let document = title(state, &shared.id).await.unwrap();
+            assert_eq!(document.title, "retitled on a");
+            assert_eq!(document.body, "rewritten on b");
+            assert!(title(state, &added.id).await.is_some());
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::body::Body;
-    use axum::http::{Request, StatusCode};
-    use tower::ServiceExt;
+        // An edit made after a concurrent delete keeps the entity
+        a.hexad_store.delete(&added.id).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        b.hexad_store.update(&added.id, doc("kept by b", "")).await.unwrap();
+        sync().await;
+        assert_eq!(title(&a, &added.id).await.unwrap().title, "kept by b");
+        assert_eq!(title(&b, &added.id).await.unwrap().title, "kept by b");
+
+        // A delete after the last edit wins everywhere
+        b.hexad_store.delete(&shared.id).await.unwrap();
+        sync().await;
+        assert!(title(&a, &shared.id).await.is_none());
+
+        // Syncing again changes nothing
+        let report = sync().await;
+        assert_eq!(report["changed"], serde_json::json!([]));
+        let status = a.crdt.as_ref().unwrap().status();
+        assert_eq!(status.peers[&endpoint].incarnation, Some(b.crdt.as_ref().unwrap().status().incarnation));
+
+        // Without a node ID the endpoints are absent
+        let plain = create_test_state().await;
+        let response = build_router(plain)
+            .oneshot(Request::builder().uri("/crdt/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 
-    async fn create_test_state() -> AppState {
-        let mut config = ApiConfig {
-            vector_dimension: 3,
+    #[tokio::test]
+    async fn test_replica_authenticates_to_primary_with_scoped_token() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let peer = |store_id: &str, endpoint: Option<String>| peer_auth::PeerConfig {
+            store_id: store_id.to_string(),
+            endpoint,
+            secret: "replica-secret".to_string(),
+            scopes: vec![peer_auth::PeerScope::Replication],
+            fingerprint: None,
+        };
+        let mut primary = create_test_state().await;
+        let peers = peer_auth::PeerAuth::new(peer_auth::PeerAuthConfig {
+            store_id: "primary".to_string(),
+            peers: vec![peer("replica-1", None)],
+            ..Default::default()
+        })
+        .unwrap();
+        primary.auth.peers = peers.clone();
+        primary.federation = primary.federation.clone().with_peer_auth(peers);
+        let kept = primary
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("kept", "notes").build())
+            .await
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        // The feed is closed to anyone without a token, even with client
+        // authentication off
+        let client = reqwest::Client::new();
+        let response = client.get(format!("{endpoint}/replication/changes")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        // The replica exchanges its secret for a token and follows
+        let replica_peers = peer_auth::PeerAuth::new(peer_auth::PeerAuthConfig {
+            store_id: "replica-1".to_string(),
+            peers: vec![peer("primary", Some(format!("{endpoint}/")))],
+            ..Default::default()
+        })
+        .unwrap();
+        let follower = replication::Follower::new(endpoint.clone(), "replica-1").with_peer_auth(replica_peers);
+        let replica = create_test_state().await;
+        follower.sync(&replica).await.unwrap();
+        assert!(replica.hexad_store.get(&kept.id).await.unwrap().is_some());
+
+        // A replication token admits its holder to nothing else
+        let token: peer_auth::TokenResponse = client
+            .post(format!("{endpoint}/federation/token"))
+            .header("X-Federation-PSK", "replica-secret")
+            .json(&serde_json::json!({"store_id": "replica-1", "scope": "replication"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let response = client
+            .get(format!("{endpoint}/replication/changes"))
+            .header(peer_auth::TOKEN_HEADER, &token.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let response = client
+            .post(format!("{endpoint}/hexads"))
+            .header(peer_auth::TOKEN_HEADER, &token.token)
+            .json(&serde_json::json!({"document": {"title": "injected", "body": ""}}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        // Scopes the peer is not allowed are not issued
+        let response = client
+            .post(format!("{endpoint}/federation/token"))
+            .header("X-Federation-PSK", "replica-secret")
+            .json(&serde_json::json!({"store_id": "replica-1", "scope": "transaction"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_peers_act_for_the_coordinators_principal() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let peer = |store_id: &str, endpoint: Option<String>| peer_auth::PeerConfig {
+            store_id: store_id.to_string(),
+            endpoint,
+            secret: "txn-secret".to_string(),
+            scopes: vec![peer_auth::PeerScope::Transaction],
+            fingerprint: None,
+        };
+        let mut participant = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Member { field: "namespace".to_string() }],
+                groups: std::collections::HashMap::from([("red".to_string(), vec!["alice".to_string()])]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        let peers = peer_auth::PeerAuth::new(peer_auth::PeerAuthConfig {
+            store_id: "edge".to_string(),
+            peers: vec![peer("hub", None)],
             ..Default::default()
+        })
+        .unwrap();
+        participant.auth.peers = peers.clone();
+        participant.federation = participant.federation.clone().with_peer_auth(peers);
+        let alice = verisim_hexad::Principal::new("alice");
+        let mut input = verisim_hexad::HexadBuilder::new().with_document("plan", "v1").build();
+        input.document.as_mut().unwrap().fields.insert("namespace".to_string(), "red".to_string());
+        let owned = verisim_hexad::security::scope(alice.clone(), participant.hexad_store.create(input))
+            .await
+            .unwrap()
+            .id;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(participant.clone()))));
+
+        let coordinator = transaction::Coordinator::new().with_peer_auth(
+            peer_auth::PeerAuth::new(peer_auth::PeerAuthConfig {
+                store_id: "hub".to_string(),
+                peers: vec![peer("edge", Some(endpoint.clone()))],
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let run = |principal: verisim_hexad::Principal, body: &str| {
+            let statement = format!("UPDATE HEXAD '{owned}' SET {{title: 'plan', body: '{body}', namespace: 'red'}}");
+            let participants = vec![transaction::Participant::new("edge", endpoint.clone(), vec![statement])];
+            verisim_hexad::security::scope(principal, coordinator.execute(participants))
+        };
+        let body_of = || async {
+            let hexad = verisim_hexad::security::system(participant.hexad_store.get(&owned)).await.unwrap().unwrap();
+            hexad.document.unwrap().body
         };
 
-        // When the `persistent` feature is enabled, each test gets a unique temp directory
-        // to avoid redb lock contention between parallel tests.
-        #[cfg(feature = "persistent")]
-        {
-            use std::sync::atomic::{AtomicU64, Ordering};
-            static COUNTER: AtomicU64 = AtomicU64::new(0);
-            let id = COUNTER.fetch_add(1, Ordering::Relaxed);
-            let tmp = std::env::temp_dir().join(format!(
-                "verisimdb-test-{}-{}",
-                std::process::id(),
-                id,
+        // The participant judges the statements for the coordinator's
+        // principal, not for the peer
+        let txn = run(verisim_hexad::Principal::new("bob"), "taken").await;
+        assert_eq!(txn.state, transaction::GlobalState::Aborted);
+        assert!(txn.error.as_deref().is_some_and(|e| e.contains("not found")), "{:?}", txn.error);
+        assert_eq!(body_of().await, "v1");
+        let txn = run(alice, "revised").await;
+        assert_eq!(txn.state, transaction::GlobalState::Committed, "{:?}", txn.participants);
+        assert_eq!(body_of().await, "revised");
+
+        // A transaction token admits VQL only in a named transaction, and
+        // nothing without a principal or beyond the commit protocol
+        let client = reqwest::Client::new();
+        let token: peer_auth::TokenResponse = client
+            .post(format!("{endpoint}/federation/token"))
+            .header("X-Federation-PSK", "txn-secret")
+            .json(&serde_json::json!({"store_id": "hub", "scope": "transaction"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let principal = peer_auth::encode_principal(&verisim_hexad::Principal::unrestricted("ops"));
+        let status = |request: reqwest::RequestBuilder| {
+            let sent = request.header(peer_auth::TOKEN_HEADER, &token.token).send();
+            async move { sent.await.unwrap().status() }
+        };
+        let statement = serde_json::json!({"query": "SELECT * FROM HEXADS"});
+        let outside = client
+            .post(format!("{endpoint}/vql/execute"))
+            .header(peer_auth::PRINCIPAL_HEADER, &principal)
+            .json(&statement);
+        assert_eq!(status(outside).await, reqwest::StatusCode::FORBIDDEN);
+        let unnamed = client.post(format!("{endpoint}/transactions/begin")).json(&serde_json::json!({}));
+        assert_eq!(status(unnamed).await, reqwest::StatusCode::FORBIDDEN);
+        let listing = client
+            .get(format!("{endpoint}/transactions/{}/hexads", txn.participants[0].transaction_id.as_deref().unwrap()))
+            .header(peer_auth::PRINCIPAL_HEADER, &principal);
+        assert_eq!(status(listing).await, reqwest::StatusCode::FORBIDDEN);
+        let elsewhere = client
+            .post(format!("{endpoint}/vql/execute"))
+            .header(peer_auth::PRINCIPAL_HEADER, &principal)
+            .header(peer_auth::TRANSACTION_HEADER, "one")
+            .json(&serde_json::json!({"query": "DELETE HEXAD 'x'", "transaction": "another"}));
+        assert_eq!(status(elsewhere).await, reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_replication_rules_send_replica_only_matching_entities() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let mut primary = create_test_state().await;
+        primary.replication.rules = replication::ReplicationRules::new(vec![replication::ReplicationRule {
+            follower: "replica-1".to_string(),
+            collections: vec!["shared".to_string()],
+            filter: Some("WHERE title = 'public'".to_string()),
+        }])
+        .unwrap();
+        let doc = |title: &str| verisim_hexad::HexadBuilder::new().with_document(title, "notes").build();
+        let put = |collection: &str, id: &str, title: &str| {
+            let (primary, id, input) = (primary.clone(), HexadId::in_collection(collection, id), doc(title));
+            async move { primary.hexad_store.put(&id, input).await.unwrap().id }
+        };
+        let shared = put("shared", "a", "public").await;
+        let unmatched = put("shared", "b", "secret").await;
+        let private = put("private", "c", "public").await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        let replica = create_test_state().await;
+        let follower = replication::Follower::new(endpoint, "replica-1");
+        let has = |id: HexadId| {
+            let replica = replica.clone();
+            async move { replica.hexad_store.get(&id).await.unwrap().is_some() }
+        };
+
+        // The snapshot sends only entities in the allow-listed collection
+        // that meet the filter
+        follower.sync(&replica).await.unwrap();
+        assert!(has(shared.clone()).await);
+        assert!(!has(unmatched.clone()).await);
+        assert!(!has(private.clone()).await);
+
+        // An entity that stops matching is deleted on the replica, one that
+        // starts is sent, and changes outside the collection are withheld
+        put("shared", "a", "secret").await;
+        put("shared", "b", "public").await;
+        put("private", "c", "still public").await;
+        follower.sync(&replica).await.unwrap();
+        assert!(!has(shared).await);
+        assert!(has(unmatched).await);
+        assert!(!has(private.clone()).await);
+        let status = follower.status();
+        assert_eq!(status.position, primary.replication.log.head());
+        assert_eq!(status.lag_entries, 0);
+
+        // Withheld changes carry no entity state; other followers get everything
+        let from = primary.replication.log.head() - 1;
+        let batch = replication::changes(&primary, from, 10, None, Some("replica-1"), None).await.unwrap();
+        assert_eq!(batch.changes[0].hexad_id, private.to_string());
+        let filter = primary.replication.rules.for_follower(Some("replica-1"));
+        let batch = replication::changes(&primary, from, 10, None, Some("replica-1"), filter).await.unwrap();
+        assert!(batch.changes.is_empty());
+        assert_eq!(batch.through, Some(from + 1));
+        assert!(primary.replication.rules.for_follower(Some("replica-2")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_crdt_conflicts_queue_for_manual_resolution() {
+        use verisim_hexad::ProvenanceStore;
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let node = |id: &'static str| async move {
+            let mut state = create_test_state().await;
+            let policies = crdt::ConflictPolicies {
+                default: crdt::ConflictPolicy::Manual,
+                ..Default::default()
+            };
+            let crdt = Arc::new(crdt::CrdtSync::new(id).with_policies(policies));
+            state.hexad_store.add_listener(Arc::new(crdt::CrdtListener(crdt.clone())));
+            state.crdt = Some(crdt);
+            state
+        };
+        let doc = |title: &str| verisim_hexad::HexadBuilder::new().with_document(title, "").build();
+        let a = node("edge-a").await;
+        let b = node("edge-b").await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(b.clone()))));
+        let app = build_router(a.clone());
+        let call = |method: &str, uri: String, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let sync = || call("POST", "/crdt/sync".to_string(), serde_json::json!({ "peer": endpoint }));
+        let title = |state: &AppState, id: &verisim_hexad::HexadId| {
+            let (state, id) = (state.clone(), id.clone());
+            async move { state.hexad_store.get(&id).await.unwrap().unwrap().document.unwrap().title }
+        };
+
+        let shared = a.hexad_store.create(doc("draft")).await.unwrap();
+        sync().await;
+        assert_eq!(title(&b, &shared.id).await, "draft");
+
+        // Both retitle it while apart: each keeps its own title and queues
+        // the conflict
+        a.hexad_store.update(&shared.id, doc("title from a")).await.unwrap();
+        b.hexad_store.update(&shared.id, doc("title from b")).await.unwrap();
+        let (_, report) = sync().await;
+        assert_eq!(report["conflicts"], serde_json::json!([shared.id.to_string()]));
+        assert_eq!(title(&a, &shared.id).await, "title from a");
+        assert_eq!(title(&b, &shared.id).await, "title from b");
+        assert_eq!(b.crdt.as_ref().unwrap().status().conflicts, 1);
+        let (status, queued) = call("GET", "/crdt/conflicts".to_string(), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(queued[0]["peer"], "edge-b");
+        assert_eq!(queued[0]["fields"][0]["field"], "document.title");
+
+        // Keeping b's title on a settles it on both
+        let resolve = format!("/crdt/conflicts/{}/resolve", shared.id);
+        let (status, _) = call("POST", resolve.clone(), serde_json::json!({ "keep": "theirs" })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(title(&a, &shared.id).await, "title from b");
+        let (_, report) = sync().await;
+        assert_eq!(report["conflicts"], serde_json::json!([]));
+        assert_eq!(title(&b, &shared.id).await, "title from b");
+        assert_eq!(a.crdt.as_ref().unwrap().status().conflicts, 0);
+        assert_eq!(b.crdt.as_ref().unwrap().status().conflicts, 0);
+        let (status, _) = call("POST", resolve, serde_json::json!({ "keep": "ours" })).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The conflict and its resolution are in the entity's provenance
+        let chain = a.hexad_store.provenance_store().get_chain(shared.id.as_str()).await.unwrap();
+        let conflicts: Vec<&str> = chain
+            .records
+            .iter()
+            .filter(|r| r.event_type == verisim_provenance::ProvenanceEventType::Custom("sync_conflict".to_string()))
+            .map(|r| r.description.as_str())
+            .collect();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts[0].contains("queued for manual resolution: document.title"));
+        assert!(conflicts[1].contains("resolved manually, keeping node edge-b's values"));
+    }
+
+    #[tokio::test]
+    async fn test_provenance_lineage_spans_primary_and_replica() {
+        use verisim_hexad::ProvenanceStore;
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let primary = create_test_state().await;
+        let id = HexadId::new("lineage-1");
+        let input = verisim_hexad::HexadBuilder::new()
+            .with_document("Report", "draft")
+            .with_provenance("created", "alice", "Drafted")
+            .build();
+        primary.hexad_store.put(&id, input).await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        let replica = create_test_state().await;
+        replication::Follower::new(endpoint.clone(), "replica-1").sync(&replica).await.unwrap();
+
+        // The replica's chain records the primary's head at hand-off
+        let head = primary.hexad_store.provenance_store().get_latest(id.as_str()).await.unwrap().unwrap();
+        let chain = replica.hexad_store.provenance_store().get_chain(id.as_str()).await.unwrap();
+        let links: Vec<_> = chain.links().cloned().collect();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].instance, endpoint);
+        assert_eq!(links[0].head_hash, head.content_hash);
+
+        let app = build_router(replica.clone());
+        let lineage = |app: axum::Router| async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/provenance/lineage-1/lineage")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<lineage::LineageReport>(&body).unwrap()
+        };
+        let report = lineage(app.clone()).await;
+        assert!(report.valid);
+        assert_eq!(report.segments.len(), 2);
+        assert_eq!(report.segments[1].instance.as_deref(), Some(endpoint.as_str()));
+        assert_eq!(report.segments[1].records.last().unwrap().content_hash, head.content_hash);
+        assert!(report.links[0].verified);
+
+        // A primary chain that no longer holds the linked head fails the lineage
+        primary.hexad_store.provenance_store().delete_chain(id.as_str()).await.unwrap();
+        let report = lineage(app).await;
+        assert!(!report.valid);
+        assert!(!report.links[0].verified);
+        assert!(report.segments[0].chain_valid);
+    }
+
+    #[tokio::test]
+    async fn test_replica_routes_reads_by_staleness_bound() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let primary = create_test_state().await;
+        let doc = |title: &str| verisim_hexad::HexadBuilder::new().with_document(title, "notes").build();
+        let early = primary.hexad_store.create(doc("early")).await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        let mut replica = create_test_state().await;
+        let follower = Arc::new(replication::Follower::new(endpoint.clone(), "replica-1"));
+        replica.replication.follower = Some(follower.clone());
+        let app = build_router(replica.clone());
+        let get = |id: &HexadId, bound: Option<&str>| {
+            let mut request = Request::builder().uri(format!("/hexads/{}", id));
+            if let Some(bound) = bound {
+                request = request.header(replication::MAX_STALENESS_HEADER, bound);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let served_by = |response: &axum::response::Response| {
+            response.headers()[replication::SERVED_BY_HEADER].to_str().unwrap().to_string()
+        };
+
+        // Before its first poll the replica's staleness is unbounded
+        let response = get(&early.id, Some("60s")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(served_by(&response), endpoint);
+        assert!(response.headers().get(replication::STALENESS_HEADER).is_none());
+
+        follower.sync(&replica).await.unwrap();
+        let response = get(&early.id, Some("60s")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(served_by(&response), "replica-1");
+        assert!(response.headers().contains_key(replication::STALENESS_HEADER));
+
+        // A write the replica has not polled for is read from the primary
+        // when the bound is tighter than the replica's staleness
+        let late = primary.hexad_store.create(doc("late")).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let response = get(&late.id, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(served_by(&response), "replica-1");
+        let response = get(&late.id, Some("10")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(served_by(&response), endpoint);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let hexad: HexadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(hexad.id, late.id.to_string());
+
+        let response = get(&late.id, Some("soon")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_replica_forwards_reads_with_the_callers_credentials() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let with_auth = |mut state: AppState| {
+            state.auth = auth::AuthState::new(auth::AuthConfig {
+                enabled: true,
+                client_certificates: Some(client_certs::ClientCertConfig {
+                    ca_path: "clients.pem".to_string(),
+                    exclusive: false,
+                    mappings: Vec::new(),
+                    default_role: Some(auth::ClientRole::Reader),
+                }),
+                ..Default::default()
+            });
+            state.auth.key_registry.register("reader-key", "dashboard", auth::ClientRole::Reader);
+            state
+        };
+        let primary = with_auth(create_test_state().await);
+        let doc = verisim_hexad::HexadBuilder::new().with_document("kept", "notes").build();
+        let kept = primary.hexad_store.create(doc).await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        // Never having polled, the replica forwards every bounded read
+        let mut replica = with_auth(create_test_state().await);
+        replica.replication.follower = Some(Arc::new(replication::Follower::new(endpoint.clone(), "replica-1")));
+        let app = build_router(replica);
+        let get = |api_key: Option<&str>, names: Option<&[&str]>| {
+            let mut request = Request::builder()
+                .uri(format!("/hexads/{}", kept.id))
+                .header(replication::MAX_STALENESS_HEADER, "1s");
+            if let Some(key) = api_key {
+                request = request.header("x-api-key", key);
+            }
+            let mut request = request.body(Body::empty()).unwrap();
+            request.extensions_mut().insert(client_certs::ClientCertificate(
+                names.map(|names| names.iter().map(|n| n.to_string()).collect()),
             ));
-            config.persistence_dir = Some(tmp.to_string_lossy().into_owned());
+            app.clone().oneshot(request)
+        };
+
+        let response = get(Some("reader-key"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[replication::SERVED_BY_HEADER], endpoint.as_str());
+
+        // A certificate cannot be passed on, so its reads are not forwarded
+        let response = get(None, Some(&["dashboard.example.com"])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_federation_status_reports_peer_health() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let primary = create_test_state().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(primary.clone()))));
+
+        let mut replica = create_test_state().await;
+        let follower = Arc::new(replication::Follower::new(endpoint.clone(), "replica-1"));
+        replica.replication.follower = Some(follower.clone());
+        // Bootstrap, then poll the change feed
+        follower.sync(&replica).await.unwrap();
+        follower.sync(&replica).await.unwrap();
+        let status = |state: &AppState| {
+            let app = build_router(state.clone());
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri("/federation/status").body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<federation::FederationStatus>(&body).unwrap()
+            }
+        };
+
+        let report = status(&replica).await;
+        assert_eq!(report.status, "healthy");
+        assert_eq!(report.peers.len(), 1);
+        let peer = &report.peers[0];
+        assert_eq!(peer.roles, [federation::PeerRole::Primary]);
+        assert_eq!(peer.reachable, Some(true));
+        assert_eq!(peer.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(peer.compatible, Some(true));
+        assert_eq!(peer.lag_entries, Some(0));
+        assert!(peer.last_sync.is_some());
+
+        // The primary sees the replica that polled it, and an unreachable
+        // registered peer degrades the cluster
+        primary.federation.peers.write().unwrap().insert(
+            "gone".to_string(),
+            federation::PeerStore {
+                store_id: "gone".to_string(),
+                endpoint: "http://127.0.0.1:1".to_string(),
+                modalities: Vec::new(),
+                trust_level: 1.0,
+                last_seen: None,
+                response_time_ms: None,
+                secret_hash: None,
+            },
+        );
+        let report = status(&primary).await;
+        assert_eq!(report.status, "degraded");
+        let replica = report.peers.iter().find(|p| p.peer == "replica-1").unwrap();
+        assert_eq!(replica.roles, [federation::PeerRole::Replica]);
+        assert_eq!(replica.reachable, None);
+        let gone = report.peers.iter().find(|p| p.peer == "gone").unwrap();
+        assert_eq!(gone.reachable, Some(false));
+        assert!(gone.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_query_execute_runs_plan() {
+        let state = create_test_state().await;
+        for (title, body, embedding) in [
+            ("rust ownership", "borrow checker", vec![1.0, 0.0, 0.0]),
+            ("rust lifetimes", "borrow scopes", vec![0.0, 1.0, 0.0]),
+            ("haskell monads", "borrow nothing", vec![0.9, 0.1, 0.0]),
+        ] {
+            let input = verisim_hexad::HexadBuilder::new()
+                .with_document(title, body)
+                .with_embedding(embedding)
+                .build();
+            state.hexad_store.create(input).await.unwrap();
         }
+        let app = build_router(state);
+        let post = |uri: &str, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
 
-        AppState::new_async(config).await.unwrap()
+        // Text match, then rank by similarity to $v and keep the best two
+        let plan = serde_json::json!({
+            "source": "hexad",
+            "nodes": [
+                {"modality": "document", "conditions": [{"fulltext": {"query": "$q"}}], "projections": [], "early_limit": null},
+                {"modality": "vector", "conditions": [{"similarity": {"k": 2}}], "projections": [], "early_limit": null}
+            ],
+            "post_processing": [
+                {"order_by": {"fields": [["similarity", false]]}},
+                {"limit": {"count": 2}}
+            ]
+        });
+        let params = serde_json::json!({"q": {"string": "borrow"}, "v": {"vector": [1.0, 0.0, 0.0]}});
+        let response = post("/query/execute", serde_json::json!({"plan": plan, "params": params})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let result = json(response).await;
+        let titles: Vec<&str> = result["rows"].as_array().unwrap().iter().map(|r| r["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, vec!["rust ownership", "haskell monads"]);
+        assert_eq!(result["steps"].as_array().unwrap().len(), 2);
+
+        // Prepared statements return rows too
+        let plan = serde_json::json!({
+            "source": "hexad",
+            "nodes": [{"modality": "document", "conditions": [{"fulltext": {"query": "rust"}}], "projections": ["id", "title"], "early_limit": null}],
+            "post_processing": [{"group_by": {"fields": [], "aggregates": ["COUNT(*)"]}}]
+        });
+        let prepared = json(post("/prepared", serde_json::json!({"query": "count rust", "plan": plan})).await.unwrap()).await;
+        let uri = format!("/prepared/{}/execute", prepared["id"].as_str().unwrap());
+        let result = json(post(&uri, serde_json::json!({"params": {}})).await.unwrap()).await;
+        assert_eq!(result["rows"], serde_json::json!([{"COUNT(*)": 2}]));
+
+        let plan = serde_json::json!({
+            "source": "hexad",
+            "nodes": [{"modality": "tensor", "conditions": [{"tensor_op": {"operation": "matmul"}}], "projections": [], "early_limit": null}],
+            "post_processing": []
+        });
+        let response = post("/query/execute", serde_json::json!({"plan": plan})).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_health_endpoint() {
+    async fn test_planner_analyze_collects_statistics() {
         let state = create_test_state().await;
-        let app = build_router(state);
+        for (title, embedding) in [
+            ("dup", Some(vec![1.0, 0.0, 0.0])),
+            ("dup", Some(vec![0.0, 1.0, 0.0])),
+            ("unique one", None),
+            ("unique two", None),
+        ] {
+            let mut input = verisim_hexad::HexadBuilder::new().with_document(title, "body text");
+            if let Some(embedding) = embedding {
+                input = input.with_embedding(embedding);
+            }
+            state.hexad_store.create(input.build()).await.unwrap();
+        }
+        let app = build_router(state.clone());
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/health")
+                    .method("POST")
+                    .uri("/planner/analyze")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["hexads_scanned"], 4);
+        let document = &report["stores"]["document"];
+        assert_eq!(document["total_rows"], 4);
+        assert_eq!(document["fields"]["title"]["distinct_values"], 3);
+        assert!(document["index_bytes"].as_u64().unwrap() > 0);
+        assert!(document["analyzed_at"].is_string());
+        assert_eq!(report["stores"]["vector"]["total_rows"], 2);
+        assert_eq!(report["stores"]["vector"]["index_bytes"], 24);
+
+        // The planner now prices title equality from the measured distribution
+        {
+            let planner = state.planner.lock().unwrap();
+            let stats = planner.stats().get(verisim_planner::Modality::Document).unwrap();
+            assert_eq!(stats.equality_selectivity("title"), Some(1.0 / 3.0));
+        }
 
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({"query": "ANALYZE"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(result["statement_type"], "ANALYZE");
+        assert_eq!(result["data"]["hexads_scanned"], 4);
     }
 
     #[tokio::test]
-    async fn test_ready_endpoint() {
+    async fn test_query_execute_replans_on_cardinality_error() {
         let state = create_test_state().await;
-        let app = build_router(state);
-
+        for i in 0..12 {
+            let input = verisim_hexad::HexadBuilder::new()
+                .with_document(&format!("rust {}", i), "ownership")
+                .build();
+            state.hexad_store.create(input).await.unwrap();
+        }
+        {
+            let mut planner = state.planner.lock().unwrap();
+            let mut config = planner.config().clone();
+            config.reoptimize_threshold = 2.0;
+            planner.set_config(config);
+        }
+        let app = build_router(state.clone());
+
+        // Equality on title runs first, estimated well above the one row
+        // it finds, which re-plans the two probes left behind it
+        let plan = serde_json::json!({
+            "source": "hexad",
+            "nodes": [
+                {"modality": "document", "conditions": [{"fulltext": {"query": "rust"}}], "projections": [], "early_limit": null},
+                {"modality": "document", "conditions": [{"equality": {"field": "title", "value": "rust 7"}}], "projections": [], "early_limit": null},
+                {"modality": "document", "conditions": [{"range": {"field": "body_length", "low": "0", "high": "100"}}], "projections": [], "early_limit": null}
+            ],
+            "post_processing": [],
+            "joins": [{"left": 0, "right": 1}, {"left": 1, "right": 2}]
+        });
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/ready")
-                    .body(Body::empty())
+                    .method("POST")
+                    .uri("/query/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::json!({"plan": plan}).to_string()))
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(result["rows"].as_array().unwrap().len(), 1);
+        assert_eq!(result["rows"][0]["title"], "rust 7");
+        assert_eq!(result["replans"][0]["after_step"], 1);
+        assert_eq!(result["replans"][0]["actual_rows"], 1);
+        assert!(result["plan"]["notes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|n| n.as_str().unwrap().starts_with("Re-optimized after step 1")));
+
+        let logged = state.slow_query_log.recent(1);
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].replans.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_create_and_get_hexad() {
+    async fn test_query_deadlines_and_kill() {
         let state = create_test_state().await;
-        let app = build_router(state);
-
-        // Create a hexad
-        let create_request = HexadRequest {
-            title: Some("Test Document".to_string()),
-            body: Some("Test body content".to_string()),
-            embedding: Some(vec![0.1, 0.2, 0.3]),
-            types: None,
-            relationships: None,
-            tensor: None,
-            metadata: None,
-            provenance: None,
-            spatial: None,
+        let app = build_router(state.clone());
+        let vql = |query: &str, timeout: Option<&str>| {
+            let mut request = Request::builder()
+                .method("POST")
+                .uri("/vql/execute")
+                .header("content-type", "application/json");
+            if let Some(timeout) = timeout {
+                request = request.header(queries::TIMEOUT_HEADER, timeout);
+            }
+            request
+                .body(Body::from(serde_json::json!({"query": query}).to_string()))
+                .unwrap()
         };
 
+        // An elapsed deadline stops the query before it runs
+        let response = app.clone().oneshot(vql("SET TIMEOUT 0; SELECT * FROM hexads", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let response = app.clone().oneshot(vql("SELECT * FROM hexads", Some("0"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        // SET TIMEOUT overrides the header
+        let response = app.clone().oneshot(vql("SET TIMEOUT 5s; SELECT * FROM hexads", Some("0"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(vql("SELECT * FROM hexads", Some("soon"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Finished queries are no longer listed; a running one can be killed
+        let handle = state.active_queries.register("MATCH (h)", Some(std::time::Duration::from_secs(60)));
         let response = app
             .clone()
-            .oneshot(
+            .oneshot(Request::builder().uri("/queries/active").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let active: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0]["id"], handle.id());
+        assert_eq!(active[0]["query"], "MATCH (h)");
+        assert!(active[0]["deadline"].is_string());
+
+        let kill = |id: &str| {
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/queries/active/{}", id))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(kill(handle.id())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(matches!(handle.check(), Err(ApiError::Cancelled(_))));
+        state.active_queries.finish(handle.id());
+        let response = app.oneshot(kill(handle.id())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_is_kept_per_principal() {
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Owner { field: "owner".to_string() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        state.auth.key_registry.register("alice-key", "alice", auth::ClientRole::Writer);
+        state.auth.key_registry.register("bob-key", "bob", auth::ClientRole::Writer);
+        let app = build_router(state);
+        let send = |key: &str, uri: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-api-key", key)
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let execute = |key: &str| {
+            let plan = serde_json::json!({
+                "source": "hexad",
+                "nodes": [{"modality": "document", "conditions": [{"fulltext": {"query": "rust"}}], "projections": [], "early_limit": null}],
+                "post_processing": []
+            });
+            let response = send(key, "/query/execute", serde_json::json!({"plan": plan}));
+            async move {
+                let body = axum::body::to_bytes(response.await.unwrap().into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+        for (key, title) in [("alice-key", "rust ownership"), ("bob-key", "rust lifetimes")] {
+            let response = send(key, "/hexads", serde_json::json!({"title": title, "body": "borrow"})).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let alice = execute("alice-key").await;
+        assert_eq!(alice["row_count"], 1);
+        assert_eq!(execute("alice-key").await["cached"], true);
+        // Bob runs the same plan, and gets his own rows rather than Alice's
+        let bob = execute("bob-key").await;
+        assert!(bob.get("cached").is_none());
+        assert_eq!(bob["row_count"], 1);
+        assert_ne!(bob["rows"], alice["rows"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_execute_caches_results_until_a_write() {
+        let state = create_test_state().await;
+        for title in ["rust ownership", "rust lifetimes"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "borrow").build();
+            state.hexad_store.create(input).await.unwrap();
+        }
+        let app = build_router(state.clone());
+        let execute = |q: &str| {
+            let plan = serde_json::json!({
+                "source": "hexad",
+                "nodes": [{"modality": "document", "conditions": [{"fulltext": {"query": "$q"}}], "projections": [], "early_limit": null}],
+                "post_processing": []
+            });
+            let body = serde_json::json!({"plan": plan, "params": {"q": {"string": q}}});
+            app.clone().oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/hexads")
+                    .uri("/query/execute")
                     .header("content-type", "application/json")
-                    .body(Body::from(serde_json::to_string(&create_request).unwrap()))
+                    .body(Body::from(body.to_string()))
                     .unwrap(),
             )
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let first = json(execute("rust").await.unwrap()).await;
+        assert_eq!(first["row_count"], 2);
+        assert!(first.get("cached").is_none());
+        let second = json(execute("rust").await.unwrap()).await;
+        assert_eq!(second["cached"], true);
+        assert_eq!(second["rows"], first["rows"]);
+        // Other parameters are a different entry
+        let other = json(execute("borrow").await.unwrap()).await;
+        assert!(other.get("cached").is_none());
+
+        // A write invalidates the cache
+        let input = verisim_hexad::HexadBuilder::new().with_document("rust macros", "borrow").build();
+        state.hexad_store.create(input).await.unwrap();
+        let third = json(execute("rust").await.unwrap()).await;
+        assert!(third.get("cached").is_none());
+        assert_eq!(third["row_count"], 3);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/planner/stats").body(Body::empty()).unwrap())
             .await
             .unwrap();
+        let stats = json(response).await;
+        let cache = &stats["result_cache"];
+        assert_eq!(cache["hit_count"], 1);
+        assert_eq!(cache["miss_count"], 3);
+        // One invalidation per create, including the two above
+        assert_eq!(cache["invalidation_count"], 3);
+        assert_eq!(cache["entries"], 1);
+        assert!(stats["stats"]["document"].is_object());
+    }
 
-        assert_eq!(response.status(), StatusCode::CREATED);
+    #[tokio::test]
+    async fn test_slow_queries_are_persisted_and_filterable() {
+        let state = create_test_state().await;
+        for title in ["rust ownership", "rust lifetimes", "haskell monads"] {
+            let input = verisim_hexad::HexadBuilder::new().with_document(title, "borrow").build();
+            state.hexad_store.create(input).await.unwrap();
+        }
+        let app = build_router(state.clone());
+        let send = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let json = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
 
-        // Parse response to get ID
-        let body = axum::body::to_bytes(response.into_body(), 1024 * 1024)
+        // Log every query, with EXPLAIN ANALYZE
+        let config = serde_json::json!({
+            "threshold_ms": 0.0, "max_entries": 10, "enabled": true,
+            "multi_modality_threshold": 0, "capture_explain": true
+        });
+        let response = send("PUT", "/planner/slow-queries/config", Some(config)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json(response).await["persist"], true);
+
+        for q in ["rust", "haskell"] {
+            let plan = serde_json::json!({
+                "source": "hexad",
+                "nodes": [{"modality": "document", "conditions": [{"fulltext": {"query": "$q"}}], "projections": [], "early_limit": null}],
+                "post_processing": []
+            });
+            let body = serde_json::json!({"plan": plan, "params": {"q": {"string": q}}});
+            let response = send("POST", "/query/execute", Some(body)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = send("POST", "/vql/execute", Some(serde_json::json!({"query": "MATCH x WHERE text ~ 'rust'"})))
             .await
             .unwrap();
-        let created: HexadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Entries are stored in the background as query hexads in their
+        // own collection, which only the system and administrators see
+        state.slow_query_writer.flush().await;
+        let list = || state.hexad_store.list_in(slow_queries::SLOW_QUERY_COLLECTION, 10, 0, ModalityMask::ALL);
+        let stored = verisim_hexad::security::system(list()).await.unwrap();
+        assert_eq!(stored.len(), 3);
+        assert!(stored.iter().all(|h| h.document.as_ref().unwrap().fields["type"] == "slow_query"));
+        let reader = verisim_hexad::security::Principal::new("reader");
+        assert!(verisim_hexad::security::scope(reader, list()).await.unwrap().is_empty());
+        assert!(list().await.unwrap().is_empty());
+        assert_eq!(state.slow_query_writer.dropped(), 0);
+
+        let page = json(send("GET", "/planner/slow-queries/entries", None).await.unwrap()).await;
+        assert_eq!(page["total"], 3);
+        let entry = &page["entries"][0];
+        assert!(entry["query_text"].as_str().unwrap().starts_with("MATCH"));
+        assert!(entry["plan"]["steps"].is_array());
+        assert_eq!(entry["steps"].as_array().unwrap().len(), 1);
+        assert!(entry["explain"]["text_output"].as_str().unwrap().contains("EXPLAIN ANALYZE"));
+        let rust = page["entries"].as_array().unwrap().iter().find(|e| e["parameters"]["q"] == "rust");
+        assert!(rust.is_some());
+
+        let page = json(send("GET", "/planner/slow-queries/entries?query=match&limit=5", None).await.unwrap()).await;
+        assert_eq!(page["total"], 1);
+        let page = json(send("GET", "/planner/slow-queries/entries?offset=1&limit=1", None).await.unwrap()).await;
+        assert_eq!((page["total"].as_u64(), page["entries"].as_array().unwrap().len()), (Some(3), 1));
+        let page = json(send("GET", "/planner/slow-queries/entries?modality=graph", None).await.unwrap()).await;
+        assert_eq!(page["total"], 0);
+        let response = send("GET", "/planner/slow-queries/entries?modality=sound", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_query_explain_formats() {
+        let app = build_router(create_test_state().await);
+        let plan = serde_json::json!({
+            "source": "hexad",
+            "nodes": [
+                {"modality": "document", "conditions": [{"fulltext": {"query": "rust"}}], "projections": [], "early_limit": null},
+                {"modality": "graph", "conditions": [{"traversal": {"predicate": "cites", "depth": 1}}], "projections": [], "early_limit": null}
+            ],
+            "post_processing": []
+        });
+        let explain = |format: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/query/explain?format={}", format))
+                    .header("content-type", "application/json")
+                    .body(Body::from(plan.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = explain("tree").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let tree: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(tree["id"], "result");
+        assert!(tree["estimated_cost_ms"].as_f64().unwrap() > 0.0);
+        let mut steps = 0;
+        let mut pending = vec![&tree];
+        while let Some(node) = pending.pop() {
+            steps += node.get("step").is_some() as usize;
+            pending.extend(node["children"].as_array().unwrap());
+        }
+        assert_eq!(steps, 2);
+
+        let response = explain("dot").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/vnd.graphviz"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let dot = String::from_utf8(body.to_vec()).unwrap();
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.contains("-> result"));
+
+        let response = explain("text").await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("=== VeriSimDB Query Plan ==="));
+
+        let response = explain("json").await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(output["steps"].as_array().unwrap().len(), 2);
+
+        assert_eq!(explain("svg").await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_streams_result_set_changes() {
+        use futures::StreamExt;
+
+        let state = create_test_state().await;
+        let existing = verisim_hexad::HexadBuilder::new().with_document("watched", "before").build();
+        let existing = state.hexad_store.create(existing).await.unwrap();
+        let app = build_router(state.clone());
 
-        // Get the hexad
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri(format!("/hexads/{}", created.id))
-                    .body(Body::empty())
+                    .method("POST")
+                    .uri("/vql/execute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"query": "SUBSCRIBE SELECT * FROM hexads WHERE title = 'watched'"}"#))
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["statement_type"], "SUBSCRIBE");
+        let events = json["data"]["events"].as_str().unwrap().to_string();
+        let id = json["data"]["subscription"]["id"].as_str().unwrap().to_string();
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(&events).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/event-stream"));
+        let mut stream = response.into_body().into_data_stream();
+        async fn next_event(stream: &mut axum::body::BodyDataStream) -> String {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next()).await.unwrap();
+            String::from_utf8(chunk.unwrap().unwrap().to_vec()).unwrap()
+        }
+
+        let snapshot = next_event(&mut stream).await;
+        assert!(snapshot.starts_with("event: snapshot"));
+        assert!(snapshot.contains(&format!("\"id\":\"{}\"", existing.id)));
+
+        // Unrelated writes are not published
+        let other = verisim_hexad::HexadBuilder::new().with_document("ignored", "x").build();
+        state.hexad_store.create(other).await.unwrap();
+        let added = verisim_hexad::HexadBuilder::new().with_document("watched", "new").build();
+        let added = state.hexad_store.create(added).await.unwrap();
+        let event = next_event(&mut stream).await;
+        assert!(event.starts_with("event: added"));
+        assert!(event.contains(&added.id.to_string()));
 
+        state.hexad_store.delete(&existing.id).await.unwrap();
+        let event = next_event(&mut stream).await;
+        assert!(event.starts_with("event: removed"));
+        assert!(event.contains(&existing.id.to_string()));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().method("DELETE").uri(format!("/subscriptions/{}", id)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        let closed = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next()).await.unwrap();
+        assert!(closed.is_none());
+
+        let response = app
+            .oneshot(Request::builder().uri(&events).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn test_text_search() {
-        let state = create_test_state().await;
-        let app = build_router(state);
+    async fn test_subscriptions_are_scoped_to_their_owner_and_the_entity_policy() {
+        use futures::StreamExt;
 
-        // Create a hexad
-        let create_request = HexadRequest {
-            title: Some("Rust Programming".to_string()),
-            body: Some("Rust is a systems programming language".to_string()),
-            embedding: Some(vec![0.1, 0.2, 0.3]),
-            types: None,
-            relationships: None,
-            tensor: None,
-            metadata: None,
-            provenance: None,
-            spatial: None,
+        let mut state = create_test_state_with(ApiConfig {
+            entity_policy: verisim_hexad::EntityPolicy {
+                rules: vec![verisim_hexad::PolicyRule::Owner { field: "owner".to_string() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        state.auth = auth::AuthState::new(auth::AuthConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let keys = &state.auth.key_registry;
+        keys.register("alice-key", "alice", auth::ClientRole::Writer);
+        keys.register("bob-key", "bob", auth::ClientRole::Writer);
+        keys.register("admin-key", "admin", auth::ClientRole::Admin);
+        let app = build_router(state.clone());
+        let send = |key: &str, method: &str, uri: &str, body: Option<&str>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-api-key", key)
+                .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let subscribe = |key: &'static str| async move {
+            let response = send(key, "POST", "/subscriptions", Some(r#"{"query": "SUBSCRIBE WHERE title = 'watched'"}"#))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"].as_str().unwrap().to_string()
+        };
+        let listed = |key: &'static str| async move {
+            let response = send(key, "GET", "/subscriptions", None).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+            serde_json::from_slice::<Vec<serde_json::Value>>(&body).unwrap().len()
         };
 
-        let _ = app
-            .clone()
+        // Another principal can neither see nor attach to a subscription
+        let alices = subscribe("alice-key").await;
+        assert_eq!(listed("bob-key").await, 0);
+        assert_eq!(listed("admin-key").await, 1);
+        for (method, uri) in [
+            ("GET", format!("/subscriptions/{alices}")),
+            ("GET", format!("/subscriptions/{alices}/events")),
+            ("DELETE", format!("/subscriptions/{alices}")),
+        ] {
+            let response = send("bob-key", method, &uri, None).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{method} {uri}");
+        }
+        let response = send("alice-key", "GET", &format!("/subscriptions/{alices}"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A stream carries only the changes its principal may see
+        let bobs = subscribe("bob-key").await;
+        let response = send("bob-key", "GET", &format!("/subscriptions/{bobs}/events"), None).await.unwrap();
+        let mut stream = response.into_body().into_data_stream();
+        async fn next_event(stream: &mut axum::body::BodyDataStream) -> String {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next()).await.unwrap();
+            String::from_utf8(chunk.unwrap().unwrap().to_vec()).unwrap()
+        }
+        assert!(next_event(&mut stream).await.starts_with("event: snapshot"));
+        let owned = |owner: &str, body: &str| {
+            let mut input = verisim_hexad::HexadBuilder::new().with_document("watched", body).build();
+            input.document.as_mut().unwrap().fields.insert("owner".to_string(), owner.to_string());
+            input
+        };
+        let hidden = verisim_hexad::security::system(state.hexad_store.create(owned("api_key:alice", "secret")))
+            .await
+            .unwrap();
+        verisim_hexad::security::system(state.hexad_store.delete(&hidden.id)).await.unwrap();
+        let seen = verisim_hexad::security::system(state.hexad_store.create(owned("api_key:bob", "mine")))
+            .await
+            .unwrap();
+        let event = next_event(&mut stream).await;
+        assert!(event.starts_with("event: added"), "{event}");
+        assert!(event.contains(&seen.id.to_string()));
+        assert!(!event.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_federation_query_distributes_plan_and_merges_results() {
+        // As in main: reqwest needs a process-wide rustls provider
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let mut endpoints = Vec::new();
+        for titles in [["rust ownership", "rust borrowing"], ["rust traits", "go channels"]] {
+            let peer = create_test_state().await;
+            for title in titles {
+                let input = verisim_hexad::HexadBuilder::new().with_document(title, "notes").build();
+                peer.hexad_store.create(input).await.unwrap();
+            }
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            endpoints.push(format!("http://{}", listener.local_addr().unwrap()));
+            tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, build_router(peer))));
+        }
+
+        let state = create_test_state().await;
+        {
+            let mut peers = state.federation.peers.write().unwrap();
+            let peer = |store_id: &str, endpoint: &str, modality: &str| federation::PeerStore {
+                store_id: store_id.to_string(),
+                endpoint: endpoint.to_string(),
+                modalities: vec![modality.to_string()],
+                trust_level: 1.0,
+                last_seen: None,
+                response_time_ms: None,
+                secret_hash: None,
+            };
+            peers.insert("peer-a".to_string(), peer("peer-a", &endpoints[0], "document"));
+            peers.insert("peer-b".to_string(), peer("peer-b", &endpoints[1], "document"));
+            peers.insert("peer-graph".to_string(), peer("peer-graph", "http://127.0.0.1:9", "graph"));
+        }
+        let app = build_router(state.clone());
+
+        let request = serde_json::json!({
+            "pattern": "*",
+            "plan": {
+                "source": "hexad",
+                "nodes": [{"modality": "document", "conditions": [{"fulltext": {"query": "rust"}}], "projections": [], "early_limit": null}],
+                "post_processing": [{"limit": {"count": 10}}]
+            }
+        });
+        let response = app
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/hexads")
+                    .uri("/federation/query")
                     .header("content-type", "application/json")
-                    .body(Body::from(serde_json::to_string(&create_request).unwrap()))
+                    .body(Body::from(request.to_string()))
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["plan"]["merge"]["kind"], "rank_fusion");
+        assert_eq!(json["plan"]["sub_plans"].as_array().unwrap().len(), 2);
+        assert_eq!(json["stores_excluded"], serde_json::json!(["peer-graph"]));
+        let results = json["results"].as_array().unwrap();
+        let titles: std::collections::BTreeSet<&str> = results.iter().map(|r| r["data"]["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, ["rust borrowing", "rust ownership", "rust traits"].into_iter().collect());
+        assert!(results.iter().any(|r| r["source_store"] == "peer-b"));
+
+        // Measured response times feed the network cost of later plans
+        let peers = state.federation.peers.read().unwrap();
+        assert!(peers["peer-a"].response_time_ms.is_some());
+        assert!(peers["peer-graph"].response_time_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_conflict_policy_endpoints() {
+        let state = create_test_state().await;
+        let app = build_router(state.clone());
 
-        // Search for it
         let response = app
-            .oneshot(
+            .clone()
+            .oneshot(Request::builder().uri("/normalizer/conflict-policy").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let config: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(config["default_policy"], "LastWriterWins");
+
+        let put = |body: serde_json::Value| {
+            app.clone().oneshot(
                 Request::builder()
-                    .uri("/search/text?q=Rust&limit=10")
-                    .body(Body::empty())
+                    .method("PUT")
+                    .uri("/normalizer/conflict-policy")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
                     .unwrap(),
             )
+        };
+        let response = put(serde_json::json!({
+            "default_policy": "AutoMerge",
+            "per_modality_policies": [
+                {"modalities": ["document", "vector"], "policy": {"ModalityPriority": ["document", "vector"]}}
+            ],
+            "auto_resolve_threshold": 0.2,
+            "require_manual_above": 0.9,
+            "max_history_entries": 500,
+            "actor": "ops@example.org",
+            "reason": "prefer documents"
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let update: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(update["previous"]["default_policy"], "LastWriterWins");
+        assert_eq!(update["audit"]["actor"], "ops@example.org");
+        assert!(update["audit"]["description"]
+            .as_str()
+            .unwrap()
+            .contains("default_policy: last_writer_wins -> auto_merge"));
+        assert_eq!(
+            state.conflict_resolver.config().policy_for_pair(
+                verisim_normalizer::regeneration::Modality::Vector,
+                verisim_normalizer::regeneration::Modality::Document
+            ),
+            &verisim_normalizer::conflict::ConflictPolicy::ModalityPriority(vec![
+                verisim_normalizer::regeneration::Modality::Document,
+                verisim_normalizer::regeneration::Modality::Vector,
+            ])
+        );
+
+        let response = put(serde_json::json!({
+            "default_policy": "ManualResolve",
+            "auto_resolve_threshold": 0.9,
+            "require_manual_above": 0.1,
+            "max_history_entries": 500
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            state.conflict_resolver.config().default_policy,
+            verisim_normalizer::conflict::ConflictPolicy::AutoMerge
+        );
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/normalizer/conflict-policy/audit").body(Body::empty()).unwrap())
             .await
             .unwrap();
-
-        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let audit: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(audit["chain_length"], 1);
     }
 
     #[tokio::test]
-    async fn test_drift_status() {
+    async fn test_normalization_campaign_endpoints() {
         let state = create_test_state().await;
-        let app = build_router(state);
+        let app = build_router(state.clone());
+        state
+            .hexad_store
+            .create(verisim_hexad::HexadBuilder::new().with_document("Campaign target", "body").build())
+            .await
+            .unwrap();
+
+        let post = |uri: &str, body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let response = post(
+            "/normalizer/campaigns",
+            serde_json::json!({"name": "nightly", "schedule": "0 3 * * *"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let campaign: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = campaign["id"].as_str().unwrap().to_string();
+        assert_eq!(campaign["schedule"], "0 3 * * *");
+        assert!(campaign["next_run_at"].is_string());
+
+        let response = post(
+            "/normalizer/campaigns",
+            serde_json::json!({"name": "broken", "schedule": "0 25 * * *"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = post(
+            "/normalizer/campaigns",
+            serde_json::json!({"name": "broken", "schedule": "@daily", "selector": {"min_moving_average": 2.0}}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post(&format!("/normalizer/campaigns/{}/run", id), serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let mut runs = serde_json::Value::Null;
+        for _ in 0..200 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/normalizer/campaigns/{}/runs", id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            runs = serde_json::from_slice(&body).unwrap();
+            if runs[0]["status"] == "completed" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(runs[0]["status"], "completed");
+        assert_eq!(runs[0]["trigger"], "manual");
+        assert_eq!(runs[0]["scanned"], 1);
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/drift/status")
+                    .method("DELETE")
+                    .uri(format!("/normalizer/campaigns/{}", id))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/normalizer/campaigns/{}", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 }
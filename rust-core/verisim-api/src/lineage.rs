@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Cross-instance provenance lineage.
+//!
+//! An entity that reaches an instance through replication or CRDT sync
+//! starts, or continues, its provenance chain there with a record that
+//! carries a [`ChainLink`]: the instance it came from and the hash of the
+//! head of its chain on that instance at hand-off.  The link is part of the
+//! record's hash, so it cannot be altered without breaking the chain.
+//!
+//! [`verify`] follows the links from the local chain to the chains they
+//! name, fetched from each instance's `GET /provenance/{id}/records`, and
+//! on to the links those hold, checking every chain's hashes and that every
+//! linked head is in the chain it names.  An instance is named by URL (a
+//! replica's primary) or by CRDT node ID, resolved to the URL this node
+//! syncs with it at.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use verisim_hexad::{ChainLink, ProvenanceStore};
+use verisim_provenance::{ProvenanceChain, ProvenanceError};
+
+use crate::peer_auth::PeerScope;
+use crate::{ApiError, AppState, ProvenanceRecordResponse};
+
+/// Most chains one verification follows
+pub const MAX_SEGMENTS: usize = 64;
+
+const PEER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One instance's chain for the entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageSegment {
+    /// URL of the instance holding the chain; `None` for this one
+    pub instance: Option<String>,
+    pub entity_id: String,
+    pub chain_length: usize,
+    pub chain_valid: bool,
+    /// Why the chain could not be read or verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub records: Vec<ProvenanceRecordResponse>,
+}
+
+/// A hand-off from one chain to another, checked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCheck {
+    /// Segment whose chain holds the link
+    pub from_segment: usize,
+    pub link: ChainLink,
+    /// Whether the linked head is in the chain the link names
+    pub verified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of verifying an entity's lineage across instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageReport {
+    pub entity_id: String,
+    /// Whether every chain and every link verified
+    pub valid: bool,
+    /// The local chain first, then the chains it links to, breadth first
+    pub segments: Vec<LineageSegment>,
+    pub links: Vec<LinkCheck>,
+}
+
+/// Verify the lineage of `id`, starting from this instance's chain
+pub async fn verify(state: &AppState, id: &str) -> Result<LineageReport, ApiError> {
+    let local = match state.hexad_store.provenance_store().get_chain(id).await {
+        Ok(chain) => chain,
+        Err(ProvenanceError::NotFound(_)) => {
+            return Err(ApiError::NotFound(format!("No provenance for entity {id}")));
+        }
+        Err(e) => return Err(ApiError::Internal(e.to_string())),
+    };
+
+    let mut segments = Vec::new();
+    let mut links = Vec::new();
+    // Chains read so far, by instance and entity
+    let mut read: HashMap<(Option<String>, String), Result<ProvenanceChain, String>> = HashMap::new();
+    let mut queue: VecDeque<(usize, ChainLink)> = VecDeque::new();
+
+    push_segment(&mut segments, &mut queue, None, id, &local);
+    read.insert((None, id.to_string()), Ok(local));
+
+    let mut truncated = false;
+    while let Some((from_segment, link)) = queue.pop_front() {
+        let instance = match resolve(state, &link.instance) {
+            Ok(instance) => instance,
+            Err(error) => {
+                links.push(LinkCheck { from_segment, link, verified: false, error: Some(error) });
+                continue;
+            }
+        };
+        let key = (instance.clone(), link.entity_id.clone());
+        if !read.contains_key(&key) {
+            if segments.len() >= MAX_SEGMENTS {
+                truncated = true;
+                links.push(LinkCheck {
+                    from_segment,
+                    link,
+                    verified: false,
+                    error: Some(format!("lineage longer than {MAX_SEGMENTS} chains")),
+                });
+                continue;
+            }
+            let fetched = match &instance {
+                None => state
+                    .hexad_store
+                    .provenance_store()
+                    .get_chain(&link.entity_id)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Some(url) => fetch(state, url, &link.entity_id).await,
+            };
+            let entry = match fetched {
+                Ok(chain) => {
+                    push_segment(&mut segments, &mut queue, instance.clone(), &link.entity_id, &chain);
+                    Ok(chain)
+                }
+                Err(error) => {
+                    segments.push(LineageSegment {
+                        instance: instance.clone(),
+                        entity_id: link.entity_id.clone(),
+                        chain_length: 0,
+                        chain_valid: false,
+                        error: Some(error.clone()),
+                        records: Vec::new(),
+                    });
+                    Err(error)
+                }
+            };
+            read.insert(key.clone(), entry);
+        }
+        let check = match &read[&key] {
+            Ok(chain) if chain.contains_hash(&link.head_hash) => LinkCheck { from_segment, link, verified: true, error: None },
+            Ok(_) => LinkCheck {
+                from_segment,
+                link,
+                verified: false,
+                error: Some("linked head not in the chain".to_string()),
+            },
+            Err(error) => LinkCheck { from_segment, link, verified: false, error: Some(error.clone()) },
+        };
+        links.push(check);
+    }
+
+    let valid = !truncated && segments.iter().all(|s| s.chain_valid) && links.iter().all(|l| l.verified);
+    Ok(LineageReport {
+        entity_id: id.to_string(),
+        valid,
+        segments,
+        links,
+    })
+}
+
+/// Add a chain as a segment and queue its links
+fn push_segment(
+    segments: &mut Vec<LineageSegment>,
+    queue: &mut VecDeque<(usize, ChainLink)>,
+    instance: Option<String>,
+    entity_id: &str,
+    chain: &ProvenanceChain,
+) {
+    let index = segments.len();
+    let error = chain.verify().err().map(|e| e.to_string());
+    segments.push(LineageSegment {
+        instance,
+        entity_id: entity_id.to_string(),
+        chain_length: chain.records.len(),
+        chain_valid: error.is_none(),
+        error,
+        records: chain.records.iter().map(Into::into).collect(),
+    });
+    queue.extend(chain.links().map(|link| (index, link.clone())));
+}
+
+/// Base URL of the instance a link names; `None` for this one
+fn resolve(state: &AppState, instance: &str) -> Result<Option<String>, String> {
+    if instance.starts_with("http://") || instance.starts_with("https://") {
+        return Ok(Some(instance.trim_end_matches('/').to_string()));
+    }
+    let Some(crdt) = &state.crdt else {
+        return Err(format!("unknown instance {instance}"));
+    };
+    if instance == crdt.node_id() {
+        return Ok(None);
+    }
+    crdt.peer_endpoint(instance)
+        .map(Some)
+        .ok_or_else(|| format!("unknown instance {instance}"))
+}
+
+/// Read an entity's chain from another instance
+async fn fetch(state: &AppState, base: &str, entity_id: &str) -> Result<ProvenanceChain, String> {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    let client = CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(PEER_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    });
+    let url = format!("{base}/provenance/{entity_id}/records");
+    state
+        .auth
+        .peers
+        .request(client, reqwest::Method::GET, &url, PeerScope::Replication, PEER_TIMEOUT)
+        .await?
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| format!("{base}: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("{base}: {e}"))
+}
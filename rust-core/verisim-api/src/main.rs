@@ -61,6 +61,171 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .and_then(|v| v.parse().ok())
             .unwrap_or(384),
         persistence_dir: persist_dir.clone(),
+        drift_scan_interval_secs: std::env::var("VERISIM_DRIFT_SCAN_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        drift_alerts: match std::env::var("VERISIM_DRIFT_ALERTS_CONFIG") {
+            Ok(path) => Some(serde_json::from_str(&std::fs::read_to_string(&path)?)?),
+            Err(_) => None,
+        },
+        drift_forecast: std::env::var("VERISIM_DRIFT_FORECAST_HORIZON_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(|horizon_secs| verisim_drift::ForecastConfig {
+                horizon_secs,
+                ..Default::default()
+            }),
+        normalizer_apply_repairs: std::env::var("VERISIM_NORMALIZER_APPLY_REPAIRS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false),
+        embedding_service: std::env::var("VERISIM_EMBEDDING_URL").ok().map(|url| {
+            verisim_normalizer::embedding::HttpEmbedderConfig {
+                url,
+                model: std::env::var("VERISIM_EMBEDDING_MODEL")
+                    .unwrap_or_else(|_| "text-embedding-3-small".to_string()),
+                api_key: std::env::var("VERISIM_EMBEDDING_API_KEY").ok(),
+                timeout_secs: std::env::var("VERISIM_EMBEDDING_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(30),
+            }
+        }),
+        soft_delete: std::env::var("VERISIM_SOFT_DELETE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false),
+        soft_delete_purge_after_secs: std::env::var("VERISIM_SOFT_DELETE_PURGE_AFTER_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        // e.g. "people=86400,scratch=3600"
+        collection_soft_delete_purge_after_secs: std::env::var("VERISIM_COLLECTION_PURGE_AFTER_SECS")
+            .map(|v| {
+                v.split(',')
+                    .filter_map(|pair| pair.split_once('='))
+                    .filter_map(|(name, secs)| Some((name.trim().to_string(), secs.trim().parse().ok()?)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        // 0 disables expiry
+        expiry_sweep_interval_secs: match std::env::var("VERISIM_EXPIRY_SWEEP_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            Some(0) => None,
+            Some(secs) => Some(secs),
+            None => Some(60),
+        },
+        // 0 disables periodic checkpoints
+        wal_checkpoint_interval_secs: match std::env::var("VERISIM_WAL_CHECKPOINT_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            Some(0) => None,
+            Some(secs) => Some(secs),
+            None => Some(300),
+        },
+        wal_checkpoint_retention: std::env::var("VERISIM_WAL_CHECKPOINT_RETENTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(12),
+        // Unset fsyncs every WAL entry
+        wal_sync_interval_ms: std::env::var("VERISIM_WAL_SYNC_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        backup_dir: std::env::var("VERISIM_BACKUP_DIR").ok(),
+        restore_backup: std::env::var("VERISIM_RESTORE_BACKUP").ok(),
+        // Credentials may come from their own variables, e.g. a Kubernetes secret
+        object_store: match std::env::var("VERISIM_OBJECT_STORE_CONFIG") {
+            Ok(path) => {
+                let mut settings: verisim_api::object_store::ObjectStoreSettings =
+                    serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+                if let Ok(key) = std::env::var("VERISIM_OBJECT_STORE_ACCESS_KEY_ID") {
+                    settings.connection.access_key_id = key;
+                }
+                if let Ok(secret) = std::env::var("VERISIM_OBJECT_STORE_SECRET_ACCESS_KEY") {
+                    settings.connection.secret_access_key = secret;
+                }
+                Some(settings)
+            }
+            Err(_) => None,
+        },
+        read_snapshot_ttl_secs: std::env::var("VERISIM_READ_SNAPSHOT_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300),
+        default_query_timeout_ms: std::env::var("VERISIM_QUERY_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        transaction_timeout_secs: std::env::var("VERISIM_TRANSACTION_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300),
+        transaction_idle_timeout_secs: std::env::var("VERISIM_TRANSACTION_IDLE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        transaction_max_timeout_secs: std::env::var("VERISIM_TRANSACTION_MAX_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86_400),
+        replicate_from: std::env::var("VERISIM_REPLICATE_FROM").ok(),
+        replication_poll_interval_ms: std::env::var("VERISIM_REPLICATION_POLL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000),
+        replication_rules: match std::env::var("VERISIM_REPLICATION_RULES_CONFIG") {
+            Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            Err(_) => Vec::new(),
+        },
+        crdt_node_id: std::env::var("VERISIM_CRDT_NODE_ID").ok(),
+        crdt_peers: std::env::var("VERISIM_CRDT_PEERS")
+            .map(|v| v.split(',').map(str::trim).filter(|p| !p.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        crdt_sync_interval_secs: std::env::var("VERISIM_CRDT_SYNC_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+        crdt_conflict_policies: match std::env::var("VERISIM_CRDT_CONFLICTS_CONFIG") {
+            Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            Err(_) => Default::default(),
+        },
+        peer_auth: match std::env::var("VERISIM_PEER_AUTH_CONFIG") {
+            Ok(path) => Some(serde_json::from_str(&std::fs::read_to_string(&path)?)?),
+            Err(_) => None,
+        },
+        auth: match std::env::var("VERISIM_AUTH_CONFIG") {
+            Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            Err(_) => Default::default(),
+        },
+        entity_policy: match std::env::var("VERISIM_ENTITY_POLICY_CONFIG") {
+            Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            Err(_) => Default::default(),
+        },
+        redaction: match std::env::var("VERISIM_REDACTION_CONFIG") {
+            Ok(path) => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            Err(_) => Default::default(),
+        },
+        authz_audit_retention_secs: std::env::var("VERISIM_AUTHZ_AUDIT_RETENTION_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(90 * 86_400),
+        grpc_port: std::env::var("VERISIM_GRPC_PORT")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        // 0 disables proof caching
+        proof_cache: match std::env::var("VERISIM_PROOF_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+        {
+            Some(0) => verisim_semantic::proof_cache::ProofCacheConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            Some(ttl_secs) => verisim_semantic::proof_cache::ProofCacheConfig {
+                ttl_secs,
+                ..Default::default()
+            },
+            None => Default::default(),
+        },
     };
 
     let storage_mode = if cfg!(feature = "persistent") { "persistent" } else { "in-memory" };
@@ -0,0 +1,759 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Object-store persistence — the persistence directory kept in an
+//! S3-compatible bucket.
+//!
+//! With `object_store` configured, the local persistence directory is only
+//! a working copy: an [`ObjectMirror`] uploads what recovery needs to the
+//! bucket, and a new instance whose directory is empty downloads it again
+//! before opening its stores.  Instances can then run on ephemeral disks.
+//!
+//! ## Layout
+//!
+//! | Local file                 | Object                                    |
+//! |----------------------------|-------------------------------------------|
+//! | `wal/wal-*.log`            | `wal/…` — archived, never deleted         |
+//! | `wal/snapshot-*.bin`       | `snapshots/…`, tensors moved to `tensors/` |
+//! | `documents/…`              | `documents/…` — the live Tantivy segments |
+//! | `*.json`                   | `state/…` — policies and sync state       |
+//!
+//! The `state/` objects include the RBAC policy, `rbac-policy.json`, so
+//! roles and bindings changed at runtime survive the instance, and the
+//! actor registry, `actors.json`, so provenance keeps naming the same
+//! actors after a restore.
+//!
+//! Checkpoint snapshots are stored with each entity's tensor replaced by a
+//! reference to a blob named by the SHA-256 of its contents, so tensors
+//! that did not change between checkpoints are uploaded once.  Blobs no
+//! snapshot refers to any more are deleted.
+//!
+//! `manifest.json` lists the objects of the last complete sync and is
+//! written after all of them, so a restore never sees half a sync.  WAL
+//! segments stay in the bucket after a checkpoint removes them locally, as
+//! an archive; everything else follows the local directory.
+//!
+//! A sync holds writes and checkpoints off while it runs.  Of the document
+//! index it uploads the segments `meta.json` names and then that copy of
+//! `meta.json`, so segments Tantivy merges in the background meanwhile
+//! cannot leave the bucket with an index that does not open.
+//!
+//! Tensor blobs never change once written, so they are read and written
+//! through a [`CachedBackend`] in `cache_dir`: an instance that keeps that
+//! directory restores without downloading the tensors it already has.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use verisim_storage::{CachedBackend, ObjectStoreBackend, ObjectStoreConfig, StorageBackend, StorageError};
+
+use crate::{ApiError, AppState};
+
+/// Key of the manifest of the last complete sync
+pub const MANIFEST_KEY: &str = "manifest.json";
+
+/// The document index's list of live segments
+const INDEX_META: &str = "documents/meta.json";
+
+/// Files of a Tantivy segment besides its deletes, by extension
+const SEGMENT_COMPONENTS: [&str; 6] = ["idx", "pos", "term", "store", "fast", "fieldnorm"];
+
+/// Object-store persistence settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStoreSettings {
+    /// Bucket, endpoint and credentials
+    #[serde(flatten)]
+    pub connection: ObjectStoreConfig,
+    /// Directory tensor blobs are cached in; `None` uses `object-cache` in
+    /// the persistence directory
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+    /// Bytes of tensor blobs kept in the cache
+    #[serde(default = "default_cache_capacity_bytes")]
+    pub cache_capacity_bytes: u64,
+    /// Seconds between uploads of the persistence directory
+    #[serde(default = "default_sync_interval_secs")]
+    pub sync_interval_secs: u64,
+}
+
+fn default_cache_capacity_bytes() -> u64 {
+    1 << 30
+}
+
+fn default_sync_interval_secs() -> u64 {
+    60
+}
+
+/// A file of the persistence directory, as last uploaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MirroredFile {
+    key: String,
+    /// Local size and modification time, to tell whether it changed since
+    size: u64,
+    modified_ms: i64,
+    /// Tensor blobs a snapshot refers to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tensors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    synced_at: Option<DateTime<Utc>>,
+    /// By path relative to the persistence directory
+    files: BTreeMap<String, MirroredFile>,
+}
+
+impl Manifest {
+    fn tensors(&self) -> BTreeSet<&str> {
+        self.files
+            .values()
+            .flat_map(|file| file.tensors.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// Outcome of uploading the persistence directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub synced_at: DateTime<Utc>,
+    /// Files uploaded because they were new or changed
+    pub uploaded: usize,
+    pub uploaded_bytes: u64,
+    /// Tensor blobs uploaded
+    pub tensor_blobs: usize,
+    /// Objects deleted because their file or last reference is gone
+    pub deleted: usize,
+    /// Files the manifest lists
+    pub files: usize,
+}
+
+/// Outcome of downloading the persistence directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HydrateReport {
+    /// When the sync restored was made
+    pub synced_at: Option<DateTime<Utc>>,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Object-store persistence status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStoreStatus {
+    pub backend: String,
+    pub dir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sync: Option<SyncReport>,
+    /// Error of the last sync, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// The persistence directory mirrored to an object store
+pub struct ObjectMirror {
+    store: Box<dyn StorageBackend>,
+    /// Tensor blobs, when read through a cache rather than from `store`
+    tensors: Option<Box<dyn StorageBackend>>,
+    dir: PathBuf,
+    /// What the bucket holds, once read; held across a sync, so syncs
+    /// never overlap
+    manifest: tokio::sync::Mutex<Option<Manifest>>,
+    /// The last sync and, if it failed, its error
+    outcome: Mutex<(Option<SyncReport>, Option<String>)>,
+}
+
+fn storage_error(e: StorageError) -> ApiError {
+    match e {
+        StorageError::BackendUnavailable(msg) => ApiError::Unavailable(msg),
+        _ => ApiError::Internal(format!("object store: {e}")),
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ApiError {
+    ApiError::Internal(format!("{}: {e}", path.display()))
+}
+
+impl ObjectMirror {
+    /// Mirror persistence directory `dir` to the bucket `settings` names
+    pub fn connect(settings: &ObjectStoreSettings, dir: impl Into<PathBuf>) -> Result<Self, ApiError> {
+        let dir = dir.into();
+        let cache_dir = settings
+            .cache_dir
+            .as_ref()
+            .map_or_else(|| dir.join("object-cache"), PathBuf::from);
+        let backend = || ObjectStoreBackend::new(settings.connection.clone()).map_err(storage_error);
+        let tensors = CachedBackend::new(backend()?, &cache_dir)
+            .map_err(storage_error)?
+            .with_capacity(settings.cache_capacity_bytes);
+        Ok(Self::new(Box::new(backend()?), dir).with_tensor_cache(Box::new(tensors)))
+    }
+
+    /// Mirror `dir` to `store`
+    pub fn new(store: Box<dyn StorageBackend>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            store,
+            tensors: None,
+            dir: dir.into(),
+            manifest: tokio::sync::Mutex::new(None),
+            outcome: Mutex::new((None, None)),
+        }
+    }
+
+    /// Read and write tensor blobs through `tensors`, a cache of the store
+    pub fn with_tensor_cache(mut self, tensors: Box<dyn StorageBackend>) -> Self {
+        self.tensors = Some(tensors);
+        self
+    }
+
+    fn backend_for(&self, key: &str) -> &dyn StorageBackend {
+        match &self.tensors {
+            Some(tensors) if key.starts_with("tensors/") => tensors.as_ref(),
+            _ => self.store.as_ref(),
+        }
+    }
+
+    pub fn status(&self) -> ObjectStoreStatus {
+        let (last_sync, last_error) = self.outcome.lock().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+        ObjectStoreStatus {
+            backend: self.store.name().to_string(),
+            dir: self.dir.display().to_string(),
+            last_sync,
+            last_error,
+        }
+    }
+
+    async fn read_manifest(&self) -> Result<Manifest, ApiError> {
+        match self.store.get(MANIFEST_KEY.as_bytes()).await.map_err(storage_error)? {
+            Some(data) => serde_json::from_slice(&data)
+                .map_err(|e| ApiError::Internal(format!("object store manifest: {e}"))),
+            None => Ok(Manifest::default()),
+        }
+    }
+
+    /// Download the last sync into the persistence directory, unless it
+    /// already holds a WAL or snapshot of its own.  Runs before the stores
+    /// open.
+    pub async fn hydrate(&self) -> Result<Option<HydrateReport>, ApiError> {
+        let mut current = self.manifest.lock().await;
+        if has_local_state(&self.dir) {
+            return Ok(None);
+        }
+        let mut manifest = self.read_manifest().await?;
+        if manifest.files.is_empty() {
+            *current = Some(manifest);
+            return Ok(None);
+        }
+        let mut report = HydrateReport {
+            synced_at: manifest.synced_at,
+            files: 0,
+            bytes: 0,
+        };
+        for (path, file) in &mut manifest.files {
+            let data = self.fetch(&file.key).await?;
+            let data = if file.tensors.is_empty() { data } else { self.internalize_tensors(&data).await? };
+            let local = self.dir.join(path);
+            write_atomically(&local, &data)?;
+            // Remember the copy just written as the one uploaded
+            let (size, modified_ms) = file_stamp(&local).map_err(|e| io_error(&local, e))?;
+            file.size = size;
+            file.modified_ms = modified_ms;
+            report.files += 1;
+            report.bytes += size;
+        }
+        *current = Some(manifest);
+        info!(files = report.files, bytes = report.bytes, "Restored persistence directory from the object store");
+        Ok(Some(report))
+    }
+
+    async fn fetch(&self, key: &str) -> Result<Vec<u8>, ApiError> {
+        self.backend_for(key)
+            .get(key.as_bytes())
+            .await
+            .map_err(storage_error)?
+            .ok_or_else(|| ApiError::Internal(format!("object store: {key} is in the manifest but missing")))
+    }
+
+    /// Upload what changed in the persistence directory since the last sync.
+    ///
+    /// Callers hold writes off for the duration, as [`sync_state`] does.
+    pub async fn sync(&self) -> Result<SyncReport, ApiError> {
+        let mut current = self.manifest.lock().await;
+        let result = self.sync_locked(&mut current).await;
+        let mut outcome = self.outcome.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        match &result {
+            Ok(report) => *outcome = (Some(report.clone()), None),
+            Err(e) => outcome.1 = Some(e.to_string()),
+        }
+        result
+    }
+
+    async fn sync_locked(&self, current: &mut Option<Manifest>) -> Result<SyncReport, ApiError> {
+        let previous = match current.take() {
+            Some(manifest) => manifest,
+            None => self.read_manifest().await?,
+        };
+        let known_tensors: BTreeSet<String> = previous.tensors().into_iter().map(String::from).collect();
+        let mut manifest = Manifest {
+            synced_at: Some(Utc::now()),
+            files: BTreeMap::new(),
+        };
+        let mut report = SyncReport {
+            synced_at: Utc::now(),
+            uploaded: 0,
+            uploaded_bytes: 0,
+            tensor_blobs: 0,
+            deleted: 0,
+            files: 0,
+        };
+        let mut uploaded_tensors = BTreeSet::new();
+
+        let LocalFiles { files, mut index_meta } = local_files(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+        for (path, key) in files {
+            let local = self.dir.join(&path);
+            // The index meta as read when its segments were listed
+            let read_meta = if path == INDEX_META { index_meta.take() } else { None };
+            let stamp = match &read_meta {
+                Some((stamp, _)) => Ok(*stamp),
+                None => file_stamp(&local),
+            };
+            let (size, modified_ms) = match stamp {
+                Ok(stamp) => stamp,
+                Err(e) => {
+                    index_changed(&path, &e)?;
+                    // Removed since listed, e.g. a segment a checkpoint covered
+                    continue;
+                }
+            };
+            if let Some(file) = previous.files.get(&path) {
+                if file.key == key && file.size == size && file.modified_ms == modified_ms {
+                    manifest.files.insert(path, file.clone());
+                    continue;
+                }
+            }
+            let data = match read_meta {
+                Some((_, data)) => data,
+                None => match std::fs::read(&local) {
+                    Ok(data) => data,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        index_changed(&path, &e)?;
+                        continue;
+                    }
+                    Err(e) => return Err(io_error(&local, e)),
+                },
+            };
+            let (data, tensors) = if key.starts_with("snapshots/") {
+                let (data, blobs) = externalize_tensors(&data);
+                let mut tensors = Vec::with_capacity(blobs.len());
+                for (hash, blob) in blobs {
+                    if !known_tensors.contains(&hash) && uploaded_tensors.insert(hash.clone()) {
+                        let key = tensor_key(&hash);
+                        self.backend_for(&key).put(key.as_bytes(), &blob).await.map_err(storage_error)?;
+                        report.tensor_blobs += 1;
+                    }
+                    tensors.push(hash);
+                }
+                tensors.sort();
+                tensors.dedup();
+                (data, tensors)
+            } else {
+                (data, Vec::new())
+            };
+            self.store.put(key.as_bytes(), &data).await.map_err(storage_error)?;
+            report.uploaded += 1;
+            report.uploaded_bytes += data.len() as u64;
+            manifest.files.insert(path, MirroredFile { key, size, modified_ms, tensors });
+        }
+
+        let data = serde_json::to_vec_pretty(&manifest).map_err(|e| ApiError::Serialization(e.to_string()))?;
+        self.store.put(MANIFEST_KEY.as_bytes(), &data).await.map_err(storage_error)?;
+
+        // Only now that the manifest no longer refers to them
+        let kept: BTreeSet<&str> = manifest.files.values().map(|file| file.key.as_str()).collect();
+        let live_tensors = manifest.tensors();
+        let mut stale: Vec<String> = previous
+            .files
+            .values()
+            .filter(|file| !file.key.starts_with("wal/") && !kept.contains(file.key.as_str()))
+            .map(|file| file.key.clone())
+            .collect();
+        stale.extend(
+            known_tensors
+                .iter()
+                .filter(|hash| !live_tensors.contains(hash.as_str()))
+                .map(|hash| tensor_key(hash)),
+        );
+        for key in stale {
+            match self.backend_for(&key).delete(key.as_bytes()).await {
+                Ok(_) => report.deleted += 1,
+                // Left behind, not lost: the next sync does not retry it
+                Err(e) => warn!(key = %key, error = %e, "Failed to delete object"),
+            }
+        }
+
+        report.files = manifest.files.len();
+        *current = Some(manifest);
+        Ok(report)
+    }
+
+    /// Put the tensors a stored snapshot refers to back in place
+    async fn internalize_tensors(&self, data: &[u8]) -> Result<Vec<u8>, ApiError> {
+        let Ok(mut snapshot) = serde_json::from_slice::<serde_json::Value>(data) else {
+            return Ok(data.to_vec());
+        };
+        for tensor in snapshot_tensors(&mut snapshot) {
+            let Some(hash) = tensor.get("blob").and_then(|hash| hash.as_str()) else {
+                continue;
+            };
+            let blob = self.fetch(&tensor_key(hash)).await?;
+            *tensor = serde_json::from_slice(&blob)
+                .map_err(|e| ApiError::Internal(format!("object store tensor {hash}: {e}")))?;
+        }
+        serde_json::to_vec(&snapshot).map_err(|e| ApiError::Serialization(e.to_string()))
+    }
+}
+
+/// Fail the sync when a file of the document index vanished while it ran:
+/// the `meta.json` being uploaded needs it, so the sync is retried later
+fn index_changed(path: &str, e: &std::io::Error) -> Result<(), ApiError> {
+    if path.starts_with("documents/") {
+        return Err(ApiError::Unavailable(format!(
+            "document index changed during sync ({path}: {e}); retry"
+        )));
+    }
+    Ok(())
+}
+
+/// Upload the persistence directory with writes and checkpoints held off,
+/// so the WAL and the document index are copied as of one moment
+pub async fn sync_state(state: &AppState) -> Result<SyncReport, ApiError> {
+    let mirror = state
+        .object_store
+        .as_ref()
+        .ok_or_else(|| ApiError::NotFound("Object store is not configured".to_string()))?;
+    let _paused = state.hexad_store.pause_writes().await;
+    mirror.sync().await
+}
+
+fn tensor_key(hash: &str) -> String {
+    format!("tensors/{hash}")
+}
+
+/// Every entity's tensor in a snapshot
+fn snapshot_tensors(snapshot: &mut serde_json::Value) -> Vec<&mut serde_json::Value> {
+    let mut tensors = Vec::new();
+    let serde_json::Value::Object(snapshot) = snapshot else {
+        return tensors;
+    };
+    for (name, entities) in snapshot.iter_mut() {
+        if name != "hexads" && name != "deleted" {
+            continue;
+        }
+        let Some(entities) = entities.as_array_mut() else {
+            continue;
+        };
+        for entity in entities {
+            if let Some(tensor) = entity.pointer_mut("/input/tensor").filter(|tensor| !tensor.is_null()) {
+                tensors.push(tensor);
+            }
+        }
+    }
+    tensors
+}
+
+/// Move a snapshot's tensors out into blobs named by their SHA-256.
+/// Anything that is not a snapshot is returned as it is.
+fn externalize_tensors(data: &[u8]) -> (Vec<u8>, Vec<(String, Vec<u8>)>) {
+    let Ok(mut snapshot) = serde_json::from_slice::<serde_json::Value>(data) else {
+        return (data.to_vec(), Vec::new());
+    };
+    let mut blobs = Vec::new();
+    for tensor in snapshot_tensors(&mut snapshot) {
+        let blob = tensor.to_string().into_bytes();
+        let hash = hex::encode(Sha256::digest(&blob));
+        *tensor = serde_json::json!({ "blob": hash });
+        blobs.push((hash, blob));
+    }
+    match serde_json::to_vec(&snapshot) {
+        Ok(stripped) => (stripped, blobs),
+        Err(_) => (data.to_vec(), Vec::new()),
+    }
+}
+
+/// Whether the persistence directory already holds WAL state to recover
+fn has_local_state(dir: &Path) -> bool {
+    std::fs::read_dir(dir.join("wal")).is_ok_and(|mut entries| {
+        entries.any(|entry| {
+            entry.is_ok_and(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                verisim_wal::segment::parse_segment_filename(&name).is_some()
+                    || verisim_wal::snapshot::parse_snapshot_filename(&name).is_some()
+            })
+        })
+    })
+}
+
+/// Files of the persistence directory that are mirrored
+struct LocalFiles {
+    /// Paths relative to the directory, with their keys, in upload order
+    files: Vec<(String, String)>,
+    /// Stamp and contents of the index meta, read before the segments it
+    /// names were listed
+    index_meta: Option<((u64, i64), Vec<u8>)>,
+}
+
+/// Files of the persistence directory that are mirrored, with their keys:
+/// WAL segments before snapshots, so a segment a checkpoint removes while
+/// they are listed is covered by a snapshot listed after it, and index
+/// segments before the `meta.json` that names them
+fn local_files(dir: &Path) -> std::io::Result<LocalFiles> {
+    let mut files = Vec::new();
+    let wal = dir.join("wal");
+    if wal.is_dir() {
+        let mut segments = Vec::new();
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(&wal)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if verisim_wal::segment::parse_segment_filename(&name).is_some() {
+                segments.push((format!("wal/{name}"), format!("wal/{name}")));
+            } else if verisim_wal::snapshot::parse_snapshot_filename(&name).is_some() {
+                snapshots.push((format!("wal/{name}"), format!("snapshots/{name}")));
+            }
+        }
+        segments.sort();
+        snapshots.sort();
+        files.extend(segments);
+        files.extend(snapshots);
+    }
+    // Stamped before it is read, so a meta.json rewritten in between is
+    // seen as changed by the next sync
+    let meta = dir.join(INDEX_META);
+    let index_meta = match file_stamp(&meta) {
+        Ok(stamp) => Some((stamp, std::fs::read(&meta)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    if let Some((_, data)) = &index_meta {
+        for name in index_files(data)? {
+            if dir.join("documents").join(&name).is_file() {
+                let path = format!("documents/{name}");
+                files.push((path.clone(), path));
+            }
+        }
+        files.push((INDEX_META.to_string(), INDEX_META.to_string()));
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".json") && entry.file_type()?.is_file() {
+            files.push((name.clone(), format!("state/{name}")));
+        }
+    }
+    Ok(LocalFiles { files, index_meta })
+}
+
+/// Files of the segments a Tantivy `meta.json` names, sorted
+fn index_files(meta: &[u8]) -> std::io::Result<Vec<String>> {
+    let meta: serde_json::Value =
+        serde_json::from_slice(meta).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let mut files = Vec::new();
+    for segment in meta["segments"].as_array().into_iter().flatten() {
+        let Some(id) = segment["segment_id"].as_str() else {
+            continue;
+        };
+        let id = id.replace('-', "");
+        files.extend(SEGMENT_COMPONENTS.iter().map(|ext| format!("{id}.{ext}")));
+        if let Some(opstamp) = segment["deletes"]["opstamp"].as_u64() {
+            files.push(format!("{id}.{opstamp}.del"));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn file_stamp(path: &Path) -> std::io::Result<(u64, i64)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified: DateTime<Utc> = metadata.modified()?.into();
+    Ok((metadata.len(), modified.timestamp_millis()))
+}
+
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), ApiError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    }
+    let tmp = path.with_extension("download");
+    std::fs::write(&tmp, data)
+        .and_then(|()| std::fs::rename(&tmp, path))
+        .map_err(|e| io_error(path, e))
+}
+
+/// Upload the persistence directory every `interval`
+pub fn spawn_sync(state: AppState, interval: std::time::Duration) {
+    if state.object_store.is_none() {
+        return;
+    }
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = sync_state(&state).await {
+                warn!(error = %e, "Object-store sync failed");
+            }
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verisim_storage::InMemoryBackend;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("verisimdb-object-store-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn snapshot(tensors: &[&[f64]]) -> Vec<u8> {
+        let hexads: Vec<serde_json::Value> = tensors
+            .iter()
+            .map(|data| serde_json::json!({ "status": {}, "input": { "tensor": { "shape": [data.len()], "data": data } } }))
+            .collect();
+        serde_json::to_vec(&serde_json::json!({ "sequence": 3, "hexads": hexads, "deleted": [], "tombstones": [] })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_and_hydrate_round_trip() {
+        let dir = temp_dir("round-trip");
+        std::fs::create_dir_all(dir.join("wal")).unwrap();
+        std::fs::create_dir_all(dir.join("documents")).unwrap();
+        std::fs::write(dir.join("wal/wal-0000000000000001.log"), b"entries").unwrap();
+        std::fs::write(dir.join("wal/snapshot-0000000000000003.bin"), snapshot(&[&[1.0, 2.0], &[1.0, 2.0]])).unwrap();
+        let meta = serde_json::json!({
+            "segments": [{ "segment_id": "0a1b2c3d-0000-4000-8000-000000000001", "max_doc": 1, "deletes": null }],
+            "opstamp": 1
+        });
+        std::fs::write(dir.join("documents/meta.json"), meta.to_string()).unwrap();
+        std::fs::write(dir.join("documents/0a1b2c3d000040008000000000000001.idx"), b"index").unwrap();
+        // A segment merged away but not yet collected is not mirrored
+        std::fs::write(dir.join("documents/ffffffff000040008000000000000002.idx"), b"merged").unwrap();
+        std::fs::write(dir.join("documents/.tantivy-writer.lock"), b"").unwrap();
+        std::fs::write(dir.join("rbac-policy.json"), b"[]").unwrap();
+        std::fs::write(dir.join("graph.redb"), b"not mirrored").unwrap();
+
+        let store = InMemoryBackend::new();
+        let mirror = ObjectMirror::new(Box::new(store.clone()), &dir);
+        let report = mirror.sync().await.unwrap();
+        assert_eq!((report.uploaded, report.tensor_blobs, report.files), (5, 1, 5));
+        assert!(store.exists(b"snapshots/snapshot-0000000000000003.bin").await.unwrap());
+        assert!(store.exists(b"state/rbac-policy.json").await.unwrap());
+        assert!(!store.exists(b"documents/.tantivy-writer.lock").await.unwrap());
+        assert!(!store.exists(b"documents/ffffffff000040008000000000000002.idx").await.unwrap());
+        let stored = store.get(b"snapshots/snapshot-0000000000000003.bin").await.unwrap().unwrap();
+        assert!(!String::from_utf8(stored).unwrap().contains("shape"));
+
+        // Nothing changed, nothing uploaded
+        let report = mirror.sync().await.unwrap();
+        assert_eq!((report.uploaded, report.deleted), (0, 0));
+        assert!(mirror.status().last_sync.is_some());
+
+        let fresh = temp_dir("round-trip-fresh");
+        let restored = ObjectMirror::new(Box::new(store.clone()), &fresh);
+        let report = restored.hydrate().await.unwrap().unwrap();
+        assert_eq!(report.files, 5);
+        for path in [
+            "wal/wal-0000000000000001.log",
+            "documents/0a1b2c3d000040008000000000000001.idx",
+            "documents/meta.json",
+            "rbac-policy.json",
+        ] {
+            assert_eq!(std::fs::read(fresh.join(path)).unwrap(), std::fs::read(dir.join(path)).unwrap());
+        }
+        let snapshot: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(fresh.join("wal/snapshot-0000000000000003.bin")).unwrap()).unwrap();
+        assert_eq!(snapshot["hexads"][1]["input"]["tensor"]["data"], serde_json::json!([1.0, 2.0]));
+        // What was just downloaded is not uploaded again
+        assert_eq!(restored.sync().await.unwrap().uploaded, 0);
+        // A directory with its own WAL is left alone
+        assert!(restored.hydrate().await.unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+        let _ = std::fs::remove_dir_all(fresh);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_archive_segments_and_collect_tensors() {
+        let dir = temp_dir("checkpoint");
+        std::fs::create_dir_all(dir.join("wal")).unwrap();
+        std::fs::write(dir.join("wal/wal-0000000000000001.log"), b"old").unwrap();
+        std::fs::write(dir.join("wal/snapshot-0000000000000001.bin"), snapshot(&[&[1.0]])).unwrap();
+        let store = InMemoryBackend::new();
+        let mirror = ObjectMirror::new(Box::new(store.clone()), &dir);
+        mirror.sync().await.unwrap();
+
+        // A checkpoint replaces the snapshot and removes the segment it covers
+        std::fs::remove_file(dir.join("wal/wal-0000000000000001.log")).unwrap();
+        std::fs::remove_file(dir.join("wal/snapshot-0000000000000001.bin")).unwrap();
+        std::fs::write(dir.join("wal/wal-0000000000000009.log"), b"new").unwrap();
+        std::fs::write(dir.join("wal/snapshot-0000000000000008.bin"), snapshot(&[&[2.0]])).unwrap();
+        let report = mirror.sync().await.unwrap();
+        assert_eq!((report.uploaded, report.tensor_blobs, report.deleted), (2, 1, 2));
+
+        // The segment stays archived; the old snapshot and its tensor go
+        assert!(store.exists(b"wal/wal-0000000000000001.log").await.unwrap());
+        assert!(!store.exists(b"snapshots/snapshot-0000000000000001.bin").await.unwrap());
+        assert_eq!(store.scan_prefix(b"tensors/", 10).await.unwrap().len(), 1);
+
+        // A restore takes only what the manifest lists
+        let fresh = temp_dir("checkpoint-fresh");
+        ObjectMirror::new(Box::new(store), &fresh).hydrate().await.unwrap().unwrap();
+        assert!(!fresh.join("wal/wal-0000000000000001.log").exists());
+        assert!(fresh.join("wal/wal-0000000000000009.log").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+        let _ = std::fs::remove_dir_all(fresh);
+    }
+
+    #[tokio::test]
+    async fn test_document_index_restores_from_its_meta() {
+        use verisim_document::{Document, DocumentStore, TantivyDocumentStore};
+
+        let dir = temp_dir("index");
+        let index = TantivyDocumentStore::persistent(dir.join("documents")).unwrap();
+        for (id, body) in [("d1", "object stores"), ("d2", "ephemeral disks")] {
+            index.index(&Document::new(id, id, body)).await.unwrap();
+            index.commit().await.unwrap();
+        }
+        let store = InMemoryBackend::new();
+        ObjectMirror::new(Box::new(store.clone()), &dir).sync().await.unwrap();
+        let segments = index_files(&std::fs::read(dir.join(INDEX_META)).unwrap()).unwrap();
+        assert!(segments.iter().any(|name| name.ends_with(".idx")));
+
+        let fresh = temp_dir("index-fresh");
+        ObjectMirror::new(Box::new(store), &fresh).hydrate().await.unwrap().unwrap();
+        let restored = TantivyDocumentStore::persistent(fresh.join("documents")).unwrap();
+        assert_eq!(restored.search("ephemeral", 10).await.unwrap().len(), 1);
+
+        drop(index);
+        let _ = std::fs::remove_dir_all(dir);
+        let _ = std::fs::remove_dir_all(fresh);
+    }
+
+    #[test]
+    fn test_only_snapshots_have_tensors_moved_out() {
+        let (data, blobs) = externalize_tensors(b"raw segment bytes");
+        assert_eq!(data, b"raw segment bytes");
+        assert!(blobs.is_empty());
+
+        let (data, blobs) = externalize_tensors(&snapshot(&[&[1.0], &[3.0]]));
+        assert_eq!(blobs.len(), 2);
+        let stripped: serde_json::Value = serde_json::from_slice(&data).unwrap();
+        assert_eq!(stripped["hexads"][0]["input"]["tensor"]["blob"], blobs[0].0);
+        assert_ne!(blobs[0].0, blobs[1].0);
+    }
+}
@@ -0,0 +1,697 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! OpenID Connect bearer tokens.
+//!
+//! With an [`OidcConfig`], bearer tokens signed by the identity provider
+//! are accepted alongside the HMAC tokens signed with `jwt_secret`.  The
+//! provider's signing keys are fetched from its JWKS — found through the
+//! issuer's `/.well-known/openid-configuration` unless `jwks_uri` is given —
+//! and cached for `jwks_refresh_secs`; a token signed with a key not in the
+//! cache fetches the keys again, so rotated keys are picked up.  Fetches,
+//! whether they succeed or fail, are at least 30 seconds apart, and tokens
+//! with cached keys are verified while a fetch is under way.  RS256/384/512,
+//! PS256/384/512, ES256/384 and EdDSA signatures are accepted.
+//!
+//! A token must name the configured issuer, carry one of the configured
+//! audiences (when any are), and be within its `exp` and `nbf`, allowing
+//! `leeway_secs` of clock skew.
+//!
+//! ## Claims
+//!
+//! The subject (`sub` by default) becomes the principal, under which RBAC
+//! and provenance attribution see the caller, and `preferred_username`,
+//! `name` or `email` its display name.  The roles claim — a string or array,
+//! found by a dotted path such as `realm_access.roles` — is mapped through
+//! `role_mapping` to a [`ClientRole`]; the highest mapped role is granted,
+//! and `default_role` when none maps.  Claims named in `attribute_claims`
+//! become principal attributes for [entity policy](verisim_hexad::security)
+//! expressions.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::auth::{base64url_decode, ClientIdentity, ClientRole};
+use verisim_provenance::PrincipalKind;
+
+/// Time allowed for fetching the discovery document or the JWKS
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shortest time between JWKS fetch attempts
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+
+fn default_leeway_secs() -> u64 {
+    60
+}
+
+fn default_jwks_refresh_secs() -> u64 {
+    3600
+}
+
+fn default_subject_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_roles_claim() -> String {
+    "roles".to_string()
+}
+
+fn default_role_mapping() -> HashMap<String, ClientRole> {
+    HashMap::from([
+        ("admin".to_string(), ClientRole::Admin),
+        ("writer".to_string(), ClientRole::Writer),
+        ("reader".to_string(), ClientRole::Reader),
+    ])
+}
+
+fn default_role() -> Option<ClientRole> {
+    Some(ClientRole::Reader)
+}
+
+/// Identity provider whose tokens are accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcConfig {
+    /// Issuer URL, matched exactly against the `iss` claim.
+    pub issuer: String,
+    /// Audiences of which a token's `aud` must name one; empty accepts any.
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// JWKS URL; found through OIDC discovery when not given.
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    /// Seconds of clock skew allowed on `exp` and `nbf`.
+    #[serde(default = "default_leeway_secs")]
+    pub leeway_secs: u64,
+    /// Seconds the signing keys are cached.
+    #[serde(default = "default_jwks_refresh_secs")]
+    pub jwks_refresh_secs: u64,
+    /// Claim naming the principal.
+    #[serde(default = "default_subject_claim")]
+    pub subject_claim: String,
+    /// Dotted path of the claim listing the caller's roles or groups.
+    #[serde(default = "default_roles_claim")]
+    pub roles_claim: String,
+    /// Provider role or group → role granted.
+    #[serde(default = "default_role_mapping")]
+    pub role_mapping: HashMap<String, ClientRole>,
+    /// Role granted when none of the caller's roles maps; `None` refuses
+    /// such tokens.
+    #[serde(default = "default_role")]
+    pub default_role: Option<ClientRole>,
+    /// Principal attribute → dotted path of the claim it is read from, for
+    /// entity policy expressions; arrays are joined with commas.
+    #[serde(default)]
+    pub attribute_claims: HashMap<String, String>,
+}
+
+impl OidcConfig {
+    /// Accept tokens from `issuer` for any audience, with the defaults.
+    pub fn new(issuer: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            audiences: Vec::new(),
+            jwks_uri: None,
+            leeway_secs: default_leeway_secs(),
+            jwks_refresh_secs: default_jwks_refresh_secs(),
+            subject_claim: default_subject_claim(),
+            roles_claim: default_roles_claim(),
+            role_mapping: default_role_mapping(),
+            default_role: default_role(),
+            attribute_claims: HashMap::new(),
+        }
+    }
+}
+
+/// One key of a JWKS.
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    alg: Option<String>,
+    #[serde(default, rename = "use")]
+    usage: Option<String>,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct KeyCache {
+    jwks_uri: Option<String>,
+    keys: Vec<Jwk>,
+    /// Last successful fetch
+    fetched_at: Option<Instant>,
+    /// Last fetch, successful or not
+    attempted_at: Option<Instant>,
+}
+
+/// Verifies tokens issued by the configured provider.
+#[derive(Debug)]
+pub struct OidcVerifier {
+    config: OidcConfig,
+    client: reqwest::Client,
+    cache: RwLock<KeyCache>,
+    /// Held while fetching, so one fetch is under way at a time
+    fetching: Mutex<()>,
+}
+
+impl OidcVerifier {
+    pub fn new(config: OidcConfig) -> Self {
+        info!(issuer = %config.issuer, audiences = ?config.audiences, "OIDC authentication configured");
+        let cache = KeyCache {
+            jwks_uri: config.jwks_uri.clone(),
+            ..Default::default()
+        };
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            cache: RwLock::new(cache),
+            fetching: Mutex::new(()),
+        }
+    }
+
+    /// Verify a token's signature and claims, and map it to a client.
+    pub async fn verify(&self, token: &str) -> Result<ClientIdentity, String> {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err("Invalid JWT format".to_string());
+        }
+        let header: TokenHeader = base64url_decode(parts[0])
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .ok_or_else(|| "Invalid JWT header".to_string())?;
+        let algorithm = algorithm(&header.alg)?;
+        let signature = base64url_decode(parts[2]).map_err(|_| "Invalid JWT signature encoding".to_string())?;
+        let signing_input = format!("{}.{}", parts[0], parts[1]);
+
+        let keys = self.keys_for(&header).await?;
+        let verified = keys
+            .iter()
+            .any(|key| verify_with(key, &header.alg, algorithm, signing_input.as_bytes(), &signature));
+        if !verified {
+            return Err("Invalid JWT signature".to_string());
+        }
+
+        let claims: serde_json::Value = base64url_decode(parts[1])
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .ok_or_else(|| "Invalid JWT payload".to_string())?;
+        self.check_claims(&claims, chrono::Utc::now().timestamp())?;
+        self.identity(&claims)
+    }
+
+    /// Keys that may have signed a token with `header`, fetching the JWKS
+    /// when the cache is stale or lacks the token's key.
+    async fn keys_for(&self, header: &TokenHeader) -> Result<Vec<Jwk>, String> {
+        if let Some(keys) = self.cached(header).await? {
+            return Ok(keys);
+        }
+        let _fetching = self.fetching.lock().await;
+        // Another request may have fetched while this one waited
+        if let Some(keys) = self.cached(header).await? {
+            return Ok(keys);
+        }
+        let jwks_uri = self.cache.read().await.jwks_uri.clone();
+        let fetched = self.fetch(jwks_uri).await;
+        let mut cache = self.cache.write().await;
+        cache.attempted_at = Some(Instant::now());
+        match fetched {
+            Ok((jwks_uri, keys)) => {
+                info!(issuer = %self.config.issuer, keys = keys.len(), "Fetched OIDC signing keys");
+                cache.jwks_uri = Some(jwks_uri);
+                cache.keys = keys;
+                cache.fetched_at = cache.attempted_at;
+            }
+            Err(e) => {
+                warn!(issuer = %self.config.issuer, error = %e, "Fetching OIDC signing keys failed");
+                if cache.keys.is_empty() {
+                    return Err(format!("OIDC signing keys unavailable: {e}"));
+                }
+            }
+        }
+        Ok(candidates(&cache.keys, header))
+    }
+
+    /// The cached keys for `header`, or `None` when the keys are to be
+    /// fetched first
+    async fn cached(&self, header: &TokenHeader) -> Result<Option<Vec<Jwk>>, String> {
+        let refresh = Duration::from_secs(self.config.jwks_refresh_secs);
+        let cache = self.cache.read().await;
+        let fresh = cache.fetched_at.is_some_and(|at| at.elapsed() < refresh);
+        let keys = candidates(&cache.keys, header);
+        if fresh && !keys.is_empty() {
+            return Ok(Some(keys));
+        }
+        if cache.attempted_at.is_some_and(|at| at.elapsed() < MIN_REFETCH_INTERVAL) {
+            if cache.keys.is_empty() {
+                return Err("OIDC signing keys unavailable".to_string());
+            }
+            return Ok(Some(keys));
+        }
+        Ok(None)
+    }
+
+    /// The JWKS URI, discovered unless known, and the keys it serves
+    async fn fetch(&self, jwks_uri: Option<String>) -> Result<(String, Vec<Jwk>), String> {
+        let jwks_uri = match jwks_uri {
+            Some(uri) => uri,
+            None => {
+                let url = format!("{}/.well-known/openid-configuration", self.config.issuer.trim_end_matches('/'));
+                let discovery: Discovery = self.get_json(&url).await?;
+                discovery.jwks_uri
+            }
+        };
+        let jwks: JwkSet = self.get_json(&jwks_uri).await?;
+        Ok((jwks_uri, jwks.keys))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, String> {
+        let response = self
+            .client
+            .get(url)
+            .timeout(FETCH_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| format!("{url}: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("{url}: status {}", response.status()));
+        }
+        response.json().await.map_err(|e| format!("{url}: {e}"))
+    }
+
+    /// Check issuer, audience and validity period at `now` (Unix seconds).
+    fn check_claims(&self, claims: &serde_json::Value, now: i64) -> Result<(), String> {
+        let leeway = self.config.leeway_secs as i64;
+        if claims.get("iss").and_then(|v| v.as_str()) != Some(self.config.issuer.as_str()) {
+            return Err("JWT issuer not accepted".to_string());
+        }
+        if !self.config.audiences.is_empty() {
+            let audiences: Vec<&str> = match claims.get("aud") {
+                Some(serde_json::Value::String(aud)) => vec![aud.as_str()],
+                Some(serde_json::Value::Array(auds)) => auds.iter().filter_map(|a| a.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if !audiences.iter().any(|a| self.config.audiences.iter().any(|c| c == a)) {
+                return Err("JWT audience not accepted".to_string());
+            }
+        }
+        let exp = claims
+            .get("exp")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| "JWT has no expiry".to_string())?;
+        if now > exp + leeway {
+            return Err("JWT token expired".to_string());
+        }
+        if let Some(nbf) = claims.get("nbf").and_then(|v| v.as_i64()) {
+            if now + leeway < nbf {
+                return Err("JWT token not yet valid".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The client a token's claims describe.
+    fn identity(&self, claims: &serde_json::Value) -> Result<ClientIdentity, String> {
+        let subject = claims
+            .get(&self.config.subject_claim)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| format!("JWT has no {} claim", self.config.subject_claim))?;
+        let roles = match claim_path(claims, &self.config.roles_claim) {
+            Some(serde_json::Value::String(role)) => vec![role.as_str()],
+            Some(serde_json::Value::Array(roles)) => roles.iter().filter_map(|r| r.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        let role = roles
+            .iter()
+            .filter_map(|r| self.config.role_mapping.get(*r).copied())
+            .max()
+            .or(self.config.default_role)
+            .ok_or_else(|| "No role granted to this token".to_string())?;
+        let display_name = ["preferred_username", "name", "email"]
+            .iter()
+            .find_map(|claim| claims.get(*claim).and_then(|v| v.as_str()))
+            .map(str::to_string);
+        let attributes = self
+            .config
+            .attribute_claims
+            .iter()
+            .filter_map(|(name, path)| {
+                let value = match claim_path(claims, path)? {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Array(items) => items
+                        .iter()
+                        .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    serde_json::Value::Null | serde_json::Value::Object(_) => return None,
+                    other => other.to_string(),
+                };
+                Some((name.clone(), value))
+            })
+            .collect();
+        // Subjects are unique only within their issuer
+        Ok(ClientIdentity {
+            id: format!("{}/{}", self.config.issuer, subject),
+            role,
+            kind: PrincipalKind::Token,
+            display_name,
+            attributes,
+            credential: crate::auth::Credential::Token {
+                expires_at: crate::auth::token_expiry(claims),
+            },
+        })
+    }
+}
+
+/// Whether a token's `alg` is one an identity provider signs with; HMAC
+/// and `none` are refused.
+pub fn is_oidc_algorithm(alg: &str) -> bool {
+    algorithm(alg).is_ok()
+}
+
+fn algorithm(alg: &str) -> Result<&'static dyn VerificationAlgorithm, String> {
+    Ok(match alg {
+        "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+        "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+        "RS512" => &signature::RSA_PKCS1_2048_8192_SHA512,
+        "PS256" => &signature::RSA_PSS_2048_8192_SHA256,
+        "PS384" => &signature::RSA_PSS_2048_8192_SHA384,
+        "PS512" => &signature::RSA_PSS_2048_8192_SHA512,
+        "ES256" => &signature::ECDSA_P256_SHA256_FIXED,
+        "ES384" => &signature::ECDSA_P384_SHA384_FIXED,
+        "EdDSA" => &signature::ED25519,
+        other => return Err(format!("JWT algorithm {other} not accepted")),
+    })
+}
+
+/// Signing keys of the JWKS that could have signed a token with `header`.
+fn candidates(keys: &[Jwk], header: &TokenHeader) -> Vec<Jwk> {
+    keys.iter()
+        .filter(|k| k.usage.as_deref().is_none_or(|u| u == "sig"))
+        .filter(|k| k.alg.as_deref().is_none_or(|a| a == header.alg))
+        .filter(|k| header.kid.is_none() || k.kid == header.kid)
+        .cloned()
+        .collect()
+}
+
+/// Verify `signature` over `message` with a JWK of the type `alg` needs.
+fn verify_with(
+    key: &Jwk,
+    alg: &str,
+    algorithm: &'static dyn VerificationAlgorithm,
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let decode = |field: &Option<String>| field.as_deref().and_then(|v| base64url_decode(v).ok());
+    match (key.kty.as_str(), alg) {
+        ("RSA", "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512") => {
+            let (Some(n), Some(e)) = (decode(&key.n), decode(&key.e)) else { return false };
+            let params = match alg {
+                "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                "RS512" => &signature::RSA_PKCS1_2048_8192_SHA512,
+                "PS256" => &signature::RSA_PSS_2048_8192_SHA256,
+                "PS384" => &signature::RSA_PSS_2048_8192_SHA384,
+                _ => &signature::RSA_PSS_2048_8192_SHA512,
+            };
+            signature::RsaPublicKeyComponents { n, e }.verify(params, message, signature).is_ok()
+        }
+        ("EC", "ES256" | "ES384") => {
+            let curve = if alg == "ES256" { "P-256" } else { "P-384" };
+            if key.crv.as_deref() != Some(curve) {
+                return false;
+            }
+            let (Some(x), Some(y)) = (decode(&key.x), decode(&key.y)) else { return false };
+            let point = [&[0x04][..], &x, &y].concat();
+            UnparsedPublicKey::new(algorithm, point).verify(message, signature).is_ok()
+        }
+        ("OKP", "EdDSA") => {
+            if key.crv.as_deref() != Some("Ed25519") {
+                return false;
+            }
+            let Some(x) = decode(&key.x) else { return false };
+            UnparsedPublicKey::new(algorithm, x).verify(message, signature).is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// The claim at a dotted path, such as `realm_access.roles`.
+fn claim_path<'a>(claims: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(claims, |value, key| value.get(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    fn b64(bytes: &[u8]) -> String {
+        const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                out.push(CHARSET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            }
+        }
+        out
+    }
+
+    /// An ES256 signing key and its JWK
+    struct TestKey {
+        pair: EcdsaKeyPair,
+        kid: String,
+    }
+
+    impl TestKey {
+        fn generate(kid: &str) -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+            Self { pair, kid: kid.to_string() }
+        }
+
+        fn jwk(&self) -> serde_json::Value {
+            let point = self.pair.public_key().as_ref();
+            serde_json::json!({
+                "kty": "EC", "crv": "P-256", "alg": "ES256", "use": "sig", "kid": self.kid,
+                "x": b64(&point[1..33]), "y": b64(&point[33..65]),
+            })
+        }
+
+        fn sign(&self, claims: &serde_json::Value) -> String {
+            let header = serde_json::json!({ "alg": "ES256", "typ": "JWT", "kid": self.kid });
+            let input = format!("{}.{}", b64(header.to_string().as_bytes()), b64(claims.to_string().as_bytes()));
+            let signature = self.pair.sign(&SystemRandom::new(), input.as_bytes()).unwrap();
+            format!("{input}.{}", b64(signature.as_ref()))
+        }
+    }
+
+    /// Serve discovery and a JWKS whose keys can be swapped; returns the issuer
+    async fn serve_provider(keys: std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>) -> String {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        use axum::{routing::get, Json, Router};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let jwks_uri = format!("{issuer}/jwks");
+        let app = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || async move { Json(serde_json::json!({ "jwks_uri": jwks_uri })) }),
+            )
+            .route(
+                "/jwks",
+                get(move || async move { Json(serde_json::json!({ "keys": keys.lock().unwrap().clone() })) }),
+            );
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, app)));
+        issuer
+    }
+
+    fn claims(issuer: &str, extra: serde_json::Value) -> serde_json::Value {
+        let mut claims = serde_json::json!({
+            "iss": issuer,
+            "sub": "user-42",
+            "aud": "verisimdb",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "preferred_username": "ada",
+        });
+        claims.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        claims
+    }
+
+    #[tokio::test]
+    async fn test_verifies_token_through_discovery_and_maps_roles() {
+        let key = TestKey::generate("k1");
+        let keys = std::sync::Arc::new(std::sync::Mutex::new(vec![key.jwk()]));
+        let issuer = serve_provider(keys.clone()).await;
+        let mut config = OidcConfig::new(issuer.clone());
+        config.audiences = vec!["verisimdb".to_string()];
+        config.roles_claim = "realm_access.roles".to_string();
+        config.role_mapping.insert("data-engineers".to_string(), ClientRole::Writer);
+        config.attribute_claims.insert("position".to_string(), "position".to_string());
+        config.attribute_claims.insert("projects".to_string(), "research.projects".to_string());
+        let verifier = OidcVerifier::new(config);
+
+        let token = key.sign(&claims(&issuer, serde_json::json!({
+            "realm_access": { "roles": ["offline_access", "data-engineers"] },
+            "position": "phd_student",
+            "research": { "projects": ["alpha", "beta"] },
+        })));
+        let identity = verifier.verify(&token).await.unwrap();
+        assert_eq!(identity.id, format!("{issuer}/user-42"));
+        assert_eq!(identity.principal_id(), format!("token:{issuer}/user-42"));
+        assert_eq!(identity.role, ClientRole::Writer);
+        assert_eq!(identity.display_name.as_deref(), Some("ada"));
+        assert_eq!(identity.attributes["position"], "phd_student");
+        assert_eq!(identity.attributes["projects"], "alpha,beta");
+
+        // No mapped role: the default
+        let token = key.sign(&claims(&issuer, serde_json::json!({})));
+        assert_eq!(verifier.verify(&token).await.unwrap().role, ClientRole::Reader);
+
+        // A rotated key is fetched when first seen
+        let rotated = TestKey::generate("k2");
+        keys.lock().unwrap().push(rotated.jwk());
+        verifier.cache.write().await.attempted_at = Some(Instant::now() - MIN_REFETCH_INTERVAL);
+        let token = rotated.sign(&claims(&issuer, serde_json::json!({ "realm_access": { "roles": "admin" } })));
+        assert_eq!(verifier.verify(&token).await.unwrap().role, ClientRole::Admin);
+
+        // A key the provider does not publish
+        let stranger = TestKey::generate("k1");
+        let token = stranger.sign(&claims(&issuer, serde_json::json!({})));
+        assert!(verifier.verify(&token).await.unwrap_err().contains("signature"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetches_are_not_repeated_within_the_interval() {
+        use axum::{http::StatusCode, routing::get, Router};
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = requests.clone();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().route(
+            "/.well-known/openid-configuration",
+            get(move || async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }),
+        );
+        tokio::spawn(std::future::IntoFuture::into_future(axum::serve(listener, app)));
+        let verifier = OidcVerifier::new(OidcConfig::new(issuer.clone()));
+        let token = TestKey::generate("k1").sign(&claims(&issuer, serde_json::json!({})));
+
+        for _ in 0..3 {
+            assert!(verifier.verify(&token).await.unwrap_err().contains("unavailable"));
+        }
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        verifier.cache.write().await.attempted_at = Some(Instant::now() - MIN_REFETCH_INTERVAL);
+        assert!(verifier.verify(&token).await.is_err());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_issuer_audience_and_expired_tokens() {
+        let key = TestKey::generate("k1");
+        let keys = std::sync::Arc::new(std::sync::Mutex::new(vec![key.jwk()]));
+        let issuer = serve_provider(keys).await;
+        let mut config = OidcConfig::new(issuer.clone());
+        config.audiences = vec!["verisimdb".to_string()];
+        config.leeway_secs = 30;
+        let verifier = OidcVerifier::new(config);
+        let now = chrono::Utc::now().timestamp();
+
+        let token = key.sign(&claims("https://elsewhere", serde_json::json!({})));
+        assert!(verifier.verify(&token).await.unwrap_err().contains("issuer"));
+        let token = key.sign(&claims(&issuer, serde_json::json!({ "aud": ["other", "another"] })));
+        assert!(verifier.verify(&token).await.unwrap_err().contains("audience"));
+        let token = key.sign(&claims(&issuer, serde_json::json!({ "aud": ["other", "verisimdb"] })));
+        assert!(verifier.verify(&token).await.is_ok());
+
+        // Clock skew within the leeway is tolerated
+        let token = key.sign(&claims(&issuer, serde_json::json!({ "exp": now - 10, "nbf": now + 10 })));
+        assert!(verifier.verify(&token).await.is_ok());
+        let token = key.sign(&claims(&issuer, serde_json::json!({ "exp": now - 60 })));
+        assert!(verifier.verify(&token).await.unwrap_err().contains("expired"));
+        let token = key.sign(&claims(&issuer, serde_json::json!({ "nbf": now + 60 })));
+        assert!(verifier.verify(&token).await.unwrap_err().contains("not yet valid"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_admits_provider_tokens() {
+        use axum::{body::Body, http::{Request, StatusCode}, routing::get, Router};
+        use tower::ServiceExt;
+        let key = TestKey::generate("k1");
+        let keys = std::sync::Arc::new(std::sync::Mutex::new(vec![key.jwk()]));
+        let issuer = serve_provider(keys).await;
+        let auth = crate::auth::AuthState::new(crate::auth::AuthConfig {
+            enabled: true,
+            oidc: Some(OidcConfig::new(issuer.clone())),
+            ..Default::default()
+        });
+        let app = Router::new()
+            .route("/hexads", get(|| async { "ok" }).post(|| async { "created" }))
+            .layer(axum::middleware::from_fn_with_state(auth, crate::auth::auth_middleware));
+        let call = |method: &str, token: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri("/hexads")
+                    .header("authorization", format!("Bearer {token}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let reader = key.sign(&claims(&issuer, serde_json::json!({})));
+        assert_eq!(call("GET", reader.clone()).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(call("POST", reader).await.unwrap().status(), StatusCode::FORBIDDEN);
+        let writer = key.sign(&claims(&issuer, serde_json::json!({ "roles": ["writer"] })));
+        assert_eq!(call("POST", writer).await.unwrap().status(), StatusCode::OK);
+        let foreign = key.sign(&claims("https://elsewhere", serde_json::json!({})));
+        assert_eq!(call("GET", foreign).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_hmac_and_none_are_not_oidc_algorithms() {
+        assert!(is_oidc_algorithm("RS256"));
+        assert!(is_oidc_algorithm("ES256"));
+        assert!(!is_oidc_algorithm("HS256"));
+        assert!(!is_oidc_algorithm("none"));
+    }
+}
@@ -0,0 +1,809 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Mutual TLS and scoped tokens for federation traffic.
+//!
+//! Calls between instances — federated queries, two-phase commit, replica
+//! polling and CRDT sync — authenticate as peers instead of riding on the
+//! general API's credentials.  Each peer is configured by store ID
+//! ([`PeerConfig`]) with a pre-shared secret, the scopes it may be granted
+//! (`query`, `replication`, `transaction`) and, optionally, the SHA-256
+//! fingerprint of its certificate.
+//!
+//! ## Tokens
+//!
+//! A peer POSTs its store ID and the scope it needs to `/federation/token`,
+//! with its secret in `X-Federation-PSK`, and receives a token signed by
+//! this instance that expires after `token_ttl_secs`.  Sent as
+//! `X-Federation-Token`, the token admits the peer to that scope's
+//! endpoints and no others, in place of an API key.  While peer
+//! authentication is configured, the replication feed and the CRDT
+//! exchange admit peers with tokens only.  Calls to peers with a configured
+//! endpoint fetch tokens from them, cached until shortly before they
+//! expire.
+//!
+//! ## Mutual TLS
+//!
+//! With a certificate and key, this instance presents them when calling
+//! peers, and `serve_tls` asks clients for a certificate, accepting those
+//! signed by the peer CA or pinned for a peer; clients without one are
+//! still served.  A pinned peer must present the pinned certificate: when
+//! it is called, when it asks for a token and when it uses one.  A CA
+//! signature does not stand in for a pin.  Certificates signed by the
+//! [client CA](crate::client_certs) are accepted on the same connections.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::{DigitallySignedStruct, DistinguishedName, RootCertStore, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::ApiError;
+
+/// Header carrying a federation token
+pub const TOKEN_HEADER: &str = "x-federation-token";
+
+/// Header carrying, on transaction calls, the principal the coordinator
+/// acts for
+pub const PRINCIPAL_HEADER: &str = "x-federation-principal";
+
+/// Header naming the transaction a peer's VQL statement is buffered in
+pub const TRANSACTION_HEADER: &str = "x-federation-transaction";
+
+/// Time allowed for fetching a token from a peer
+const TOKEN_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn default_token_ttl_secs() -> u64 {
+    300
+}
+
+/// Peer authentication settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerAuthConfig {
+    /// Store ID this instance gives when asking peers for tokens
+    pub store_id: String,
+    /// Certificate (PEM) presented to peers
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    /// Private key (PEM) of `cert_path`
+    #[serde(default)]
+    pub key_path: Option<String>,
+    /// CA certificates (PEM) that sign peer certificates
+    #[serde(default)]
+    pub ca_path: Option<String>,
+    /// Seconds a token issued to a peer stays valid
+    #[serde(default = "default_token_ttl_secs")]
+    pub token_ttl_secs: u64,
+    #[serde(default)]
+    pub peers: Vec<PeerConfig>,
+}
+
+/// One peer's credentials and permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerConfig {
+    pub store_id: String,
+    /// API endpoint (with prefix) of the peer, for calls to it
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Pre-shared secret, given by the peer for tokens from us and by us
+    /// for tokens from the peer
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Scopes the peer may be granted
+    #[serde(default = "PeerScope::all")]
+    pub scopes: Vec<PeerScope>,
+    /// SHA-256 fingerprint (hex) of the peer's certificate
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+}
+
+/// Operations a federation token admits a peer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerScope {
+    /// Searches, reads and plan fragments of federated queries
+    Query,
+    /// Replica polling, CRDT exchange and reading provenance chains to
+    /// verify lineage
+    Replication,
+    /// Participating in two-phase commits
+    Transaction,
+}
+
+impl PeerScope {
+    fn all() -> Vec<Self> {
+        vec![Self::Query, Self::Replication, Self::Transaction]
+    }
+
+    /// Whether a request falls within the scope
+    pub fn covers(self, method: &Method, path: &str) -> bool {
+        match self {
+            Self::Query => match *method {
+                Method::GET => path == "/hexads" || path.starts_with("/hexads/") || path == "/search/text",
+                Method::POST => path == "/search/vector" || path == "/query/execute",
+                _ => false,
+            },
+            Self::Replication => {
+                peer_only(method, path)
+                    || (*method == Method::DELETE && path.starts_with("/snapshots/"))
+                    || (*method == Method::GET && path.starts_with("/provenance/") && path.ends_with("/records"))
+            }
+            Self::Transaction => {
+                (*method == Method::POST && path == "/vql/execute") || transaction_step(method, path)
+            }
+        }
+    }
+}
+
+/// Whether a request is a step of a participant's part in a two-phase
+/// commit: beginning, preparing, committing or rolling back a transaction,
+/// or reading its status
+fn transaction_step(method: &Method, path: &str) -> bool {
+    let Some(rest) = path.strip_prefix("/transactions/") else {
+        return false;
+    };
+    match (method, rest.split_once('/')) {
+        (&Method::POST, None) => rest == "begin",
+        (&Method::GET, None) => !rest.is_empty() && rest != "distributed",
+        (&Method::POST, Some((id, step))) => {
+            id != "distributed" && matches!(step, "prepare" | "commit" | "rollback")
+        }
+        _ => false,
+    }
+}
+
+/// The principal a transaction call carries in [`PRINCIPAL_HEADER`]
+pub(crate) fn carried_principal(headers: &axum::http::HeaderMap) -> Result<verisim_hexad::Principal, String> {
+    let value = headers
+        .get(PRINCIPAL_HEADER)
+        .ok_or_else(|| "Transaction calls must name the principal they act for".to_string())?;
+    let decoded = value
+        .to_str()
+        .ok()
+        .and_then(|v| hex::decode(v).ok())
+        .ok_or_else(|| "Malformed principal".to_string())?;
+    serde_json::from_slice(&decoded).map_err(|e| format!("Malformed principal: {e}"))
+}
+
+/// [`PRINCIPAL_HEADER`]'s value for `principal`
+pub(crate) fn encode_principal(principal: &verisim_hexad::Principal) -> String {
+    hex::encode(serde_json::to_vec(principal).unwrap_or_default())
+}
+
+/// Whether a request is one only peers make: the replication feed and the
+/// CRDT exchange
+fn peer_only(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::GET => matches!(path, "/replication/changes" | "/replication/snapshot" | "/crdt/state"),
+        Method::POST => path == "/crdt/merge",
+        _ => false,
+    }
+}
+
+/// Request body for POST /federation/token
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub store_id: String,
+    pub scope: PeerScope,
+}
+
+/// A token issued to a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub scope: PeerScope,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What a token grants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub peer: String,
+    pub scope: PeerScope,
+    /// Expiry, in Unix seconds
+    pub exp: i64,
+}
+
+/// Fingerprint of the client certificate a connection presented, added to
+/// each of its requests by [`PeerCertAcceptor`]
+#[derive(Debug, Clone)]
+pub struct PeerCertificate(pub Option<String>);
+
+/// SHA-256 fingerprint (lowercase hex) of a DER certificate
+pub fn fingerprint(der: &[u8]) -> String {
+    hex::encode(Sha256::digest(der))
+}
+
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint.replace(':', "").to_ascii_lowercase()
+}
+
+/// Token fetched from a peer
+struct CachedToken {
+    token: String,
+    renew_at: Instant,
+}
+
+struct PeerAuthInner {
+    config: PeerAuthConfig,
+    /// Signs the tokens this instance issues; tokens do not outlive the process
+    signing_key: String,
+    /// Client presenting our certificate and checking pins, when either is
+    /// configured
+    client: Option<reqwest::Client>,
+    tokens: Mutex<HashMap<(String, PeerScope), CachedToken>>,
+}
+
+/// Peer authentication, shared by the inbound middleware and outbound calls.
+/// The default has no peers configured and changes nothing.
+#[derive(Clone, Default)]
+pub struct PeerAuth {
+    inner: Option<Arc<PeerAuthInner>>,
+}
+
+impl std::fmt::Debug for PeerAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PeerAuth")
+            .field("peers", &self.inner.as_ref().map(|i| i.config.peers.len()))
+            .finish()
+    }
+}
+
+impl PeerAuth {
+    /// Load certificates and keys and build the peer client.
+    pub fn new(mut config: PeerAuthConfig) -> Result<Self, ApiError> {
+        for peer in &mut config.peers {
+            peer.fingerprint = peer.fingerprint.as_deref().map(normalize_fingerprint);
+            peer.endpoint = peer.endpoint.as_deref().map(|e| e.trim_end_matches('/').to_string());
+        }
+        let mutual_tls = config.cert_path.is_some() || config.ca_path.is_some() || config.peers.iter().any(|p| p.fingerprint.is_some());
+        let client = match mutual_tls {
+            true => Some(build_client(&config)?),
+            false => None,
+        };
+        info!(peers = config.peers.len(), mutual_tls, "Peer authentication configured");
+        Ok(Self {
+            inner: Some(Arc::new(PeerAuthInner {
+                signing_key: format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+                config,
+                client,
+                tokens: Mutex::new(HashMap::new()),
+            })),
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    fn peer(&self, store_id: &str) -> Option<&PeerConfig> {
+        self.inner.as_ref()?.config.peers.iter().find(|p| p.store_id == store_id)
+    }
+
+    /// Client for calls made on a caller's behalf rather than as a peer:
+    /// over mutual TLS when configured, but carrying no token
+    pub fn client<'a>(&'a self, fallback: &'a reqwest::Client) -> &'a reqwest::Client {
+        self.inner.as_ref().and_then(|inner| inner.client.as_ref()).unwrap_or(fallback)
+    }
+
+    /// Whether a request needs a federation token
+    pub fn required_for(&self, method: &Method, path: &str) -> bool {
+        self.enabled() && peer_only(method, path)
+    }
+
+    /// Issue a token to a peer that gave its secret, over a connection
+    /// that presented `certificate`.
+    pub fn issue(
+        &self,
+        request: &TokenRequest,
+        secret: &str,
+        certificate: Option<&str>,
+    ) -> Result<TokenResponse, (StatusCode, String)> {
+        let Some(inner) = &self.inner else {
+            return Err((StatusCode::NOT_FOUND, "Peer authentication is not configured".to_string()));
+        };
+        let peer = self
+            .peer(&request.store_id)
+            .filter(|p| !secret.is_empty() && p.secret == secret)
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Unknown peer or wrong secret".to_string()))?;
+        check_pin(peer, certificate).map_err(|e| (StatusCode::UNAUTHORIZED, e))?;
+        if !peer.scopes.contains(&request.scope) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("Peer {} may not be granted {:?}", peer.store_id, request.scope),
+            ));
+        }
+        let expires_at = Utc::now() + chrono::Duration::seconds(inner.config.token_ttl_secs as i64);
+        let claims = TokenClaims {
+            peer: peer.store_id.clone(),
+            scope: request.scope,
+            exp: expires_at.timestamp(),
+        };
+        let payload = hex::encode(serde_json::to_vec(&claims).unwrap_or_default());
+        let signature = hex::encode(crate::auth::hmac_sha256(payload.as_bytes(), inner.signing_key.as_bytes()));
+        info!(peer = %peer.store_id, scope = ?request.scope, "Issued federation token");
+        Ok(TokenResponse {
+            token: format!("{payload}.{signature}"),
+            scope: request.scope,
+            expires_at,
+        })
+    }
+
+    /// Check a token presented over a connection that presented
+    /// `certificate`.
+    pub fn verify(&self, token: &str, certificate: Option<&str>) -> Result<TokenClaims, String> {
+        let inner = self.inner.as_ref().ok_or("Peer authentication is not configured")?;
+        let (payload, signature) = token.split_once('.').ok_or("Malformed federation token")?;
+        let expected = hex::encode(crate::auth::hmac_sha256(payload.as_bytes(), inner.signing_key.as_bytes()));
+        if signature != expected {
+            return Err("Invalid federation token signature".to_string());
+        }
+        let claims: TokenClaims = hex::decode(payload)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or("Malformed federation token")?;
+        if Utc::now().timestamp() > claims.exp {
+            return Err("Federation token expired".to_string());
+        }
+        let peer = self.peer(&claims.peer).ok_or("Federation token for an unknown peer")?;
+        check_pin(peer, certificate)?;
+        Ok(claims)
+    }
+
+    /// Start a request to a peer: over mutual TLS when configured, and with
+    /// a token for `scope` when `url` is a configured peer's.
+    pub async fn request(
+        &self,
+        fallback: &reqwest::Client,
+        method: reqwest::Method,
+        url: &str,
+        scope: PeerScope,
+        timeout: Duration,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let Some(inner) = &self.inner else {
+            return Ok(fallback.request(method, url).timeout(timeout));
+        };
+        let client = inner.client.as_ref().unwrap_or(fallback);
+        let request = client.request(method, url).timeout(timeout);
+        let peer = inner
+            .config
+            .peers
+            .iter()
+            .filter(|p| p.endpoint.as_deref().is_some_and(|e| url.starts_with(e)))
+            .max_by_key(|p| p.endpoint.as_ref().map_or(0, String::len));
+        match peer {
+            Some(peer) => {
+                let token = self.token(client, peer, scope).await?;
+                Ok(request.header(TOKEN_HEADER, token))
+            }
+            None => Ok(request),
+        }
+    }
+
+    /// A token from `peer` for `scope`, fetched unless one is cached
+    async fn token(&self, client: &reqwest::Client, peer: &PeerConfig, scope: PeerScope) -> Result<String, String> {
+        let inner = self.inner.as_ref().ok_or("Peer authentication is not configured")?;
+        let key = (peer.store_id.clone(), scope);
+        if let Some(cached) = inner.tokens.lock().unwrap_or_else(std::sync::PoisonError::into_inner).get(&key) {
+            if Instant::now() < cached.renew_at {
+                return Ok(cached.token.clone());
+            }
+        }
+        let endpoint = peer.endpoint.as_deref().unwrap_or_default();
+        let response = client
+            .post(format!("{endpoint}/federation/token"))
+            .timeout(TOKEN_REQUEST_TIMEOUT)
+            .header("X-Federation-PSK", &peer.secret)
+            .json(&TokenRequest {
+                store_id: inner.config.store_id.clone(),
+                scope,
+            })
+            .send()
+            .await
+            .map_err(|e| format!("token from {}: {e}", peer.store_id))?;
+        if !response.status().is_success() {
+            return Err(format!("token from {}: {}", peer.store_id, response.status()));
+        }
+        let issued: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("token from {}: {e}", peer.store_id))?;
+        // Renew once four fifths of the lifetime have passed
+        let lifetime = (issued.expires_at - Utc::now()).num_milliseconds().max(0) as u64;
+        inner.tokens.lock().unwrap_or_else(std::sync::PoisonError::into_inner).insert(
+            key,
+            CachedToken {
+                token: issued.token.clone(),
+                renew_at: Instant::now() + Duration::from_millis(lifetime * 4 / 5),
+            },
+        );
+        Ok(issued.token)
+    }
+
+    /// TLS settings for `serve_tls`, asking clients for certificates and
+    /// accepting those of peers and, when given, of the client CA
+    pub fn server_config(
+        &self,
+        cert_path: &str,
+        key_path: &str,
+        clients: Option<&crate::client_certs::ClientCa>,
+    ) -> Result<rustls::ServerConfig, ApiError> {
+        let config = self.inner.as_ref().map(|i| &i.config);
+        let provider = provider();
+        let ca = match config.and_then(|c| c.ca_path.as_deref()) {
+            Some(path) => Some(
+                rustls::server::WebPkiClientVerifier::builder_with_provider(Arc::new(load_roots(path)?), provider.clone())
+                    .allow_unauthenticated()
+                    .build()
+                    .map_err(|e| tls_error(path, e))?,
+            ),
+            None => None,
+        };
+        let verifier = PeerClientVerifier::new(
+            config
+                .into_iter()
+                .flat_map(|c| &c.peers)
+                .filter_map(|p| p.fingerprint.clone())
+                .collect(),
+            ca.into_iter().chain(clients.map(|c| c.verifier())).collect(),
+            provider.clone(),
+        );
+        let mut server = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| tls_error(cert_path, e))?
+            .with_client_cert_verifier(Arc::new(verifier))
+            .with_single_cert(load_certs(cert_path)?, load_key(key_path)?)
+            .map_err(|e| tls_error(cert_path, e))?;
+        server.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(server)
+    }
+}
+
+/// Check a peer's pin, if it has one, against the certificate presented
+fn check_pin(peer: &PeerConfig, certificate: Option<&str>) -> Result<(), String> {
+    match &peer.fingerprint {
+        Some(pin) if certificate != Some(pin.as_str()) => {
+            Err(format!("Peer {} did not present its pinned certificate", peer.store_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+pub(crate) fn tls_error(path: &str, e: impl std::fmt::Display) -> ApiError {
+    ApiError::Internal(format!("{path}: {e}"))
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, ApiError> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| tls_error(path, e))
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>, ApiError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|e| tls_error(path, e))
+}
+
+pub(crate) fn load_roots(path: &str) -> Result<RootCertStore, ApiError> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots.add(cert).map_err(|e| tls_error(path, e))?;
+    }
+    Ok(roots)
+}
+
+/// Host of an endpoint URL, as TLS server names give it
+fn endpoint_host(endpoint: &str) -> Option<String> {
+    let url = reqwest::Url::parse(endpoint).ok()?;
+    Some(url.host_str()?.trim_start_matches('[').trim_end_matches(']').to_string())
+}
+
+fn build_client(config: &PeerAuthConfig) -> Result<reqwest::Client, ApiError> {
+    let provider = provider();
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    if let Some(path) = &config.ca_path {
+        roots.roots.extend(load_roots(path)?.roots);
+    }
+    let mut pins: HashMap<String, HashSet<String>> = HashMap::new();
+    for peer in &config.peers {
+        if let (Some(host), Some(pin)) = (peer.endpoint.as_deref().and_then(endpoint_host), &peer.fingerprint) {
+            pins.entry(host).or_default().insert(pin.clone());
+        }
+    }
+    let verifier = PinnedServerVerifier {
+        pins,
+        roots: rustls::client::WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .map_err(|e| ApiError::Internal(format!("peer CA: {e}")))?,
+        provider: provider.clone(),
+    };
+    let builder = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| ApiError::Internal(format!("peer TLS: {e}")))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+    let mut tls = match (&config.cert_path, &config.key_path) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(load_certs(cert)?, load_key(key)?)
+            .map_err(|e| tls_error(cert, e))?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Peer authentication needs both cert_path and key_path, or neither".to_string(),
+            ))
+        }
+    };
+    tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    reqwest::Client::builder()
+        .use_preconfigured_tls(tls)
+        .build()
+        .map_err(|e| ApiError::Internal(format!("peer client: {e}")))
+}
+
+/// Checks peers' server certificates: against the pin for pinned hosts,
+/// otherwise against the web and peer CAs
+#[derive(Debug)]
+struct PinnedServerVerifier {
+    /// Host → fingerprints of peers there
+    pins: HashMap<String, HashSet<String>>,
+    roots: Arc<rustls::client::WebPkiServerVerifier>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_string(),
+            ServerName::IpAddress(ip) => std::net::IpAddr::from(*ip).to_string(),
+            _ => String::new(),
+        };
+        match self.pins.get(&host) {
+            Some(pins) if pins.contains(&fingerprint(end_entity)) => Ok(ServerCertVerified::assertion()),
+            Some(_) => Err(rustls::Error::General(format!("certificate of {host} does not match its pin"))),
+            None => self
+                .roots
+                .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Asks clients for a certificate, accepting pinned ones and those the
+/// peer or client CA signed; clients may also present none
+#[derive(Debug)]
+struct PeerClientVerifier {
+    pins: HashSet<String>,
+    cas: Vec<Arc<dyn ClientCertVerifier>>,
+    /// Subjects of every CA's roots
+    hints: Vec<DistinguishedName>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PeerClientVerifier {
+    fn new(pins: HashSet<String>, cas: Vec<Arc<dyn ClientCertVerifier>>, provider: Arc<CryptoProvider>) -> Self {
+        let hints = cas.iter().flat_map(|ca| ca.root_hint_subjects().to_vec()).collect();
+        Self {
+            pins,
+            cas,
+            hints,
+            provider,
+        }
+    }
+}
+
+impl ClientCertVerifier for PeerClientVerifier {
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &self.hints
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        if self.pins.contains(&fingerprint(end_entity)) {
+            return Ok(ClientCertVerified::assertion());
+        }
+        let mut refused = rustls::Error::General("client certificate is not pinned for any peer".to_string());
+        for ca in &self.cas {
+            match ca.verify_client_cert(end_entity, intermediates, now) {
+                Ok(verified) => return Ok(verified),
+                Err(e) => refused = e,
+            }
+        }
+        Err(refused)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// TLS acceptor that adds the client certificate's fingerprint to every
+/// request on the connection, as a [`PeerCertificate`] extension, and its
+/// names, when the client CA signed it, as a
+/// [`ClientCertificate`](crate::client_certs::ClientCertificate)
+#[derive(Clone)]
+pub struct PeerCertAcceptor {
+    inner: axum_server::tls_rustls::RustlsAcceptor,
+    clients: Option<crate::client_certs::ClientCa>,
+}
+
+impl PeerCertAcceptor {
+    pub fn new(config: axum_server::tls_rustls::RustlsConfig, clients: Option<crate::client_certs::ClientCa>) -> Self {
+        Self {
+            inner: axum_server::tls_rustls::RustlsAcceptor::new(config),
+            clients,
+        }
+    }
+}
+
+impl<I, S> axum_server::accept::Accept<I, S> for PeerCertAcceptor
+where
+    I: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = tokio_rustls::server::TlsStream<I>;
+    type Service = axum::middleware::AddExtension<
+        axum::middleware::AddExtension<S, crate::client_certs::ClientCertificate>,
+        PeerCertificate,
+    >;
+    type Future = futures::future::BoxFuture<'static, std::io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let handshake = self.inner.accept(stream, service);
+        let clients = self.clients.clone();
+        Box::pin(async move {
+            let (stream, service) = handshake.await?;
+            let chain = stream.get_ref().1.peer_certificates().unwrap_or_default();
+            let presented = chain.first().map(|c| fingerprint(c));
+            let names = clients.and_then(|ca| ca.names(chain));
+            let service = tower::Layer::layer(&axum::Extension(crate::client_certs::ClientCertificate(names)), service);
+            let service = tower::Layer::layer(&axum::Extension(PeerCertificate(presented)), service);
+            Ok((stream, service))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer_auth(fingerprint: Option<&str>) -> PeerAuth {
+        PeerAuth::new(PeerAuthConfig {
+            store_id: "hub".to_string(),
+            token_ttl_secs: 60,
+            peers: vec![PeerConfig {
+                store_id: "edge-1".to_string(),
+                endpoint: None,
+                secret: "s3cret".to_string(),
+                scopes: vec![PeerScope::Replication],
+                fingerprint: fingerprint.map(str::to_string),
+            }],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tokens_are_scoped_and_signed() {
+        let auth = peer_auth(None);
+        let ask = |scope| TokenRequest {
+            store_id: "edge-1".to_string(),
+            scope,
+        };
+        assert_eq!(auth.issue(&ask(PeerScope::Replication), "wrong", None).unwrap_err().0, StatusCode::UNAUTHORIZED);
+        assert_eq!(auth.issue(&ask(PeerScope::Query), "s3cret", None).unwrap_err().0, StatusCode::FORBIDDEN);
+
+        let issued = auth.issue(&ask(PeerScope::Replication), "s3cret", None).unwrap();
+        let claims = auth.verify(&issued.token, None).unwrap();
+        assert_eq!(claims.peer, "edge-1");
+        assert!(claims.scope.covers(&Method::GET, "/replication/changes"));
+        assert!(!claims.scope.covers(&Method::POST, "/hexads"));
+        assert!(!PeerScope::Transaction.covers(&Method::POST, "/transactions/distributed"));
+        assert!(PeerScope::Transaction.covers(&Method::POST, "/transactions/begin"));
+        assert!(PeerScope::Transaction.covers(&Method::POST, "/transactions/t1/prepare"));
+        assert!(PeerScope::Transaction.covers(&Method::GET, "/transactions/t1"));
+        assert!(!PeerScope::Transaction.covers(&Method::GET, "/transactions/t1/hexads"));
+        assert!(!PeerScope::Transaction.covers(&Method::POST, "/transactions/t1/locks"));
+        assert!(!PeerScope::Transaction.covers(&Method::GET, "/hexads"));
+
+        // Another instance's signing key, or a changed claim, is refused
+        assert!(peer_auth(None).verify(&issued.token, None).is_err());
+        let (_, signature) = issued.token.split_once('.').unwrap();
+        let forged = TokenClaims {
+            scope: PeerScope::Query,
+            ..claims
+        };
+        let forged = format!("{}.{signature}", hex::encode(serde_json::to_vec(&forged).unwrap()));
+        assert!(auth.verify(&forged, None).is_err());
+    }
+
+    #[test]
+    fn test_pinned_peers_must_present_their_certificate() {
+        let pin = fingerprint(b"edge-1 certificate");
+        let auth = peer_auth(Some(&pin.to_ascii_uppercase()));
+        let ask = TokenRequest {
+            store_id: "edge-1".to_string(),
+            scope: PeerScope::Replication,
+        };
+        assert!(auth.issue(&ask, "s3cret", None).is_err());
+        let other = fingerprint(b"another certificate");
+        assert!(auth.issue(&ask, "s3cret", Some(&other)).is_err());
+        let issued = auth.issue(&ask, "s3cret", Some(&pin)).unwrap();
+        assert!(auth.verify(&issued.token, Some(&pin)).is_ok());
+        // A stolen token is no use without the certificate
+        assert!(auth.verify(&issued.token, Some(&other)).is_err());
+
+        let verifier = PeerClientVerifier::new(HashSet::from([pin]), Vec::new(), provider());
+        let now = UnixTime::now();
+        assert!(verifier.verify_client_cert(&CertificateDer::from(&b"edge-1 certificate"[..]), &[], now).is_ok());
+        assert!(verifier.verify_client_cert(&CertificateDer::from(&b"another certificate"[..]), &[], now).is_err());
+    }
+}
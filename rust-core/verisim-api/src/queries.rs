@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Running queries — deadlines and cancellation.
+//!
+//! Every VQL statement and plan execution is registered here while it
+//! runs, so `GET /queries/active` can list it and
+//! `DELETE /queries/active/{id}` can kill it.  A query may carry a
+//! deadline, taken from (in order of precedence) a `SET TIMEOUT` prefix on
+//! the VQL statement, the `x-query-timeout-ms` header, or the server's
+//! `default_query_timeout_ms`.
+//!
+//! ## Cancellation
+//!
+//! Cancellation is cooperative:
+//!
+//! - A read runs until its deadline passes or it is killed, then is
+//!   dropped at its next await point, abandoning whatever store operation
+//!   it was waiting on.
+//! - Long loops call [`checkpoint`] between store operations, so a query
+//!   stops at the next step or page rather than the next await.
+//! - A write is never dropped mid-way.  It only stops at a checkpoint, as
+//!   an error, so the statement's undo runs as for any other failure.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::ApiError;
+
+/// Request header giving a query's timeout in milliseconds
+pub const TIMEOUT_HEADER: &str = "x-query-timeout-ms";
+
+tokio::task_local! {
+    static CURRENT: QueryHandle;
+}
+
+/// A running query, as listed by `GET /queries/active`
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveQuery {
+    /// Handle used to kill the query
+    pub id: String,
+    /// Query text
+    pub query: String,
+    /// When the query started
+    pub started_at: DateTime<Utc>,
+    /// When the query times out, if it has a deadline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+    /// Time the query has been running
+    pub elapsed_ms: f64,
+    /// Whether the query was killed and has not yet stopped
+    pub cancelled: bool,
+}
+
+/// Cancellation state shared between a query and the registry
+#[derive(Default)]
+struct CancelSignal {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// A registered query's deadline and cancellation signal
+#[derive(Clone)]
+pub struct QueryHandle {
+    id: String,
+    deadline: Option<Instant>,
+    signal: Arc<CancelSignal>,
+}
+
+impl QueryHandle {
+    /// Query handle
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Fail if the query was killed or its deadline has passed
+    pub fn check(&self) -> Result<(), ApiError> {
+        if self.signal.cancelled.load(Ordering::Acquire) {
+            return Err(self.cancelled_error());
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(self.timeout_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolve once the query is killed or its deadline passes
+    async fn interrupted(&self) -> ApiError {
+        let killed = async {
+            loop {
+                let notified = self.signal.notify.notified();
+                if self.signal.cancelled.load(Ordering::Acquire) {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        match self.deadline {
+            Some(deadline) => tokio::select! {
+                _ = killed => self.cancelled_error(),
+                _ = tokio::time::sleep_until(deadline) => self.timeout_error(),
+            },
+            None => {
+                killed.await;
+                self.cancelled_error()
+            }
+        }
+    }
+
+    fn cancelled_error(&self) -> ApiError {
+        ApiError::Cancelled(format!("Query {} was killed", self.id))
+    }
+
+    fn timeout_error(&self) -> ApiError {
+        ApiError::Timeout(format!("Query {} exceeded its deadline", self.id))
+    }
+}
+
+/// A query's registry entry
+struct Running {
+    info: ActiveQuery,
+    started: Instant,
+    signal: Arc<CancelSignal>,
+}
+
+/// Queries currently running, keyed by handle
+#[derive(Clone, Default)]
+pub struct ActiveQueries {
+    running: Arc<Mutex<HashMap<String, Running>>>,
+}
+
+impl ActiveQueries {
+    /// Register a query and return its handle; it stays listed until
+    /// [`ActiveQueries::finish`]
+    pub fn register(&self, query: &str, timeout: Option<Duration>) -> QueryHandle {
+        let id = uuid::Uuid::new_v4().to_string();
+        let started = Instant::now();
+        let signal = Arc::new(CancelSignal::default());
+        let info = ActiveQuery {
+            id: id.clone(),
+            query: query.to_string(),
+            started_at: Utc::now(),
+            deadline: timeout.and_then(|t| chrono::Duration::from_std(t).ok()).map(|t| Utc::now() + t),
+            elapsed_ms: 0.0,
+            cancelled: false,
+        };
+        self.running
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(id.clone(), Running { info, started, signal: signal.clone() });
+        QueryHandle {
+            id,
+            deadline: timeout.map(|t| started + t),
+            signal,
+        }
+    }
+
+    /// Stop listing the query with handle `id`
+    pub fn finish(&self, id: &str) {
+        self.running
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(id);
+    }
+
+    /// Running queries, longest-running first
+    pub fn list(&self) -> Vec<ActiveQuery> {
+        let running = self.running.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut queries: Vec<ActiveQuery> = running
+            .values()
+            .map(|r| ActiveQuery {
+                elapsed_ms: r.started.elapsed().as_secs_f64() * 1000.0,
+                cancelled: r.signal.cancelled.load(Ordering::Acquire),
+                ..r.info.clone()
+            })
+            .collect();
+        queries.sort_by_key(|q| q.started_at);
+        queries
+    }
+
+    /// Kill the query with handle `id`, returning it if it was running
+    pub fn kill(&self, id: &str) -> Option<ActiveQuery> {
+        let running = self.running.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let r = running.get(id)?;
+        r.signal.cancelled.store(true, Ordering::Release);
+        r.signal.notify.notify_waiters();
+        Some(ActiveQuery {
+            elapsed_ms: r.started.elapsed().as_secs_f64() * 1000.0,
+            cancelled: true,
+            ..r.info.clone()
+        })
+    }
+
+    /// Run `work` as a registered query.
+    ///
+    /// With `abortable`, `work` is dropped as soon as the query is killed or
+    /// times out; otherwise it only stops at a [`checkpoint`].
+    pub async fn run<T, F>(&self, query: &str, timeout: Option<Duration>, abortable: bool, work: F) -> Result<T, ApiError>
+    where
+        F: std::future::Future<Output = Result<T, ApiError>>,
+    {
+        let handle = self.register(query, timeout);
+        let _listed = Finished { queries: self, id: handle.id.clone() };
+        handle.check()?;
+        CURRENT
+            .scope(handle.clone(), async {
+                if !abortable {
+                    return work.await;
+                }
+                tokio::select! {
+                    biased;
+                    error = handle.interrupted() => Err(error),
+                    result = work => result,
+                }
+            })
+            .await
+    }
+}
+
+/// Removes a query from the registry however its future ends
+struct Finished<'a> {
+    queries: &'a ActiveQueries,
+    id: String,
+}
+
+impl Drop for Finished<'_> {
+    fn drop(&mut self) {
+        self.queries.finish(&self.id);
+    }
+}
+
+/// Fail if the query running on this task was killed or timed out.
+///
+/// Outside a registered query this always succeeds.
+pub fn checkpoint() -> Result<(), ApiError> {
+    CURRENT.try_with(QueryHandle::check).unwrap_or(Ok(()))
+}
+
+/// Parse a `SET TIMEOUT <n>[ms|s];` prefix, returning the timeout and the
+/// statement after it.  A query without the prefix is returned unchanged.
+pub fn parse_set_timeout(query: &str) -> Result<(Option<Duration>, &str), ApiError> {
+    let mut words = query.splitn(3, char::is_whitespace);
+    let is_set = matches!(
+        (words.next(), words.next()),
+        (Some(set), Some(timeout)) if set.eq_ignore_ascii_case("SET") && timeout.eq_ignore_ascii_case("TIMEOUT")
+    );
+    if !is_set {
+        return Ok((None, query));
+    }
+    let rest = words.next().unwrap_or("");
+    let (value, statement) = rest
+        .split_once(';')
+        .ok_or_else(|| ApiError::BadRequest("SET TIMEOUT must be followed by ';' and a statement".to_string()))?;
+    let value = value.trim().trim_start_matches('=').trim();
+    let timeout = parse_timeout(value)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid timeout '{}': expected e.g. 500, 500ms or 2s", value)))?;
+    Ok((Some(timeout), statement.trim()))
+}
+
+/// Timeout requested by the `x-query-timeout-ms` header, falling back to
+/// `default_ms`
+pub fn header_timeout(headers: &HeaderMap, default_ms: Option<u64>) -> Result<Option<Duration>, ApiError> {
+    match headers.get(TIMEOUT_HEADER) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(parse_timeout)
+            .map(Some)
+            .ok_or_else(|| ApiError::BadRequest(format!("Invalid {} header", TIMEOUT_HEADER))),
+        None => Ok(default_ms.map(Duration::from_millis)),
+    }
+}
+
+/// Parse a timeout of milliseconds, with an optional `ms` or `s` unit
+pub fn parse_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(ms) = value.strip_suffix("ms") {
+        ms.trim().parse().ok().map(Duration::from_millis)
+    } else if let Some(secs) = value.strip_suffix('s') {
+        secs.trim().parse::<f64>().ok().filter(|s| s.is_finite() && *s >= 0.0).map(Duration::from_secs_f64)
+    } else {
+        value.parse().ok().map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set_timeout() {
+        let (timeout, rest) = parse_set_timeout("SET TIMEOUT 250; MATCH (h) LIMIT 1").unwrap();
+        assert_eq!(timeout, Some(Duration::from_millis(250)));
+        assert_eq!(rest, "MATCH (h) LIMIT 1");
+
+        let (timeout, _) = parse_set_timeout("set timeout = 2s; SHOW STATUS").unwrap();
+        assert_eq!(timeout, Some(Duration::from_secs(2)));
+
+        let (timeout, rest) = parse_set_timeout("SELECT * FROM hexads").unwrap();
+        assert_eq!(timeout, None);
+        assert_eq!(rest, "SELECT * FROM hexads");
+
+        assert!(parse_set_timeout("SET TIMEOUT 100").is_err());
+        assert!(parse_set_timeout("SET TIMEOUT soon; SHOW STATUS").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_killed_query_is_dropped() {
+        let queries = ActiveQueries::default();
+        let killer = queries.clone();
+        let result: Result<(), ApiError> = queries
+            .run("MATCH (h)", None, true, async {
+                let id = killer.list()[0].id.clone();
+                assert!(killer.kill(&id).unwrap().cancelled);
+                std::future::pending().await
+            })
+            .await;
+        assert!(matches!(result, Err(ApiError::Cancelled(_))));
+        assert!(queries.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_stops_at_checkpoint() {
+        let queries = ActiveQueries::default();
+        let result = queries
+            .run("DELETE FROM hexads", Some(Duration::from_millis(10)), false, async {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                checkpoint()?;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(ApiError::Timeout(_))));
+        assert!(checkpoint().is_ok());
+        assert!(queries.kill("missing").is_none());
+    }
+}
@@ -10,8 +10,15 @@
 //! - **Per-modality permissions**: Grant read/write/execute per VeriSimDB modality
 //!   (graph, vector, tensor, semantic, document, temporal).
 //! - **Entity-level ACLs**: Override permissions for specific hexad entities.
+//! - **Role bindings**: Grant further roles to a principal, everywhere or only
+//!   on entities in one namespace, on top of its [`ClientRole`].
 //! - **Audit logging**: Every access decision is recorded with timestamps.
 //!
+//! Roles and bindings can be changed at runtime through the `/admin/roles`
+//! and `/admin/role-bindings` endpoints.  The middleware reads the shared
+//! policy on every request, so changes apply to the next request; with
+//! [`RbacState::persistent`] they are also saved and survive restarts.
+//!
 //! # Integration with auth middleware
 //!
 //! After the [`auth_middleware`](crate::auth::auth_middleware) extracts a
@@ -23,12 +30,34 @@
 use axum::http::{Method, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
+use thiserror::Error;
 use tracing::{info, warn};
 
+/// Errors changing the RBAC policy.
+#[derive(Debug, Error)]
+pub enum RbacError {
+    #[error("Role {0} not found")]
+    RoleNotFound(String),
+
+    #[error("Role {0} already exists")]
+    RoleExists(String),
+
+    #[error("Role binding not found")]
+    BindingNotFound,
+
+    #[error("Invalid role change: {0}")]
+    Invalid(String),
+
+    #[error("RBAC policy persistence: {0}")]
+    Persistence(String),
+}
+
 // ---------------------------------------------------------------------------
 // Permission types
 // ---------------------------------------------------------------------------
@@ -84,8 +113,10 @@ pub struct RoleDefinition {
     /// "vector-analyst").
     pub name: String,
     /// Permissions that apply to every modality and every resource.
+    #[serde(default)]
     pub global_permissions: Vec<Permission>,
     /// Per-modality permission overrides. Key is the modality name.
+    #[serde(default)]
     pub modality_permissions: HashMap<String, Vec<Permission>>,
 }
 
@@ -110,6 +141,24 @@ pub fn has_modality_permission(&self, modality: &str, permission: Permission) ->
     }
 }
 
+/// A grant of a role to a principal, on top of its [`ClientRole`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleBinding {
+    /// Name of the role granted.
+    pub role: String,
+    /// Principal the role is granted to, named by
+    /// [`ClientIdentity::principal_id`], as in `api_key:<label>` or
+    /// `token:<subject>`.
+    pub principal: String,
+    /// Namespace the grant is confined to: it then applies only to requests
+    /// on an existing entity whose `namespace` field names it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Names of the roles the [`ClientRole`]s map to.
+pub const BUILT_IN_ROLES: [&str; 3] = ["reader", "writer", "admin"];
+
 // ---------------------------------------------------------------------------
 // RBAC policy
 // ---------------------------------------------------------------------------
@@ -124,6 +173,9 @@ pub struct RbacPolicy {
     /// Entity-level ACL overrides. Outer key is the entity (hexad) ID,
     /// inner tuples are `(client_id, Vec<Permission>)` pairs.
     pub entity_acls: HashMap<String, Vec<(String, Vec<Permission>)>>,
+    /// Roles bound to principals at runtime.
+    #[serde(default)]
+    pub bindings: Vec<RoleBinding>,
 }
 
 impl Default for RbacPolicy {
@@ -169,6 +221,7 @@ fn default() -> Self {
         Self {
             roles,
             entity_acls: HashMap::new(),
+            bindings: Vec::new(),
         }
     }
 }
@@ -179,6 +232,7 @@ pub fn new() -> Self {
         Self {
             roles: HashMap::new(),
             entity_acls: HashMap::new(),
+            bindings: Vec::new(),
         }
     }
 
@@ -197,6 +251,92 @@ pub fn set_role(&mut self, role: RoleDefinition) {
         self.roles.insert(role.name.clone(), role);
     }
 
+    /// Roles bound to `principal` that apply to a request on an entity in
+    /// `namespace` (or on no entity in any namespace), with their bindings.
+    pub fn bound_roles<'a>(
+        &'a self,
+        principal: &'a str,
+        namespace: Option<&'a str>,
+    ) -> impl Iterator<Item = (&'a RoleBinding, &'a RoleDefinition)> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |b| b.principal == principal && (b.namespace.is_none() || b.namespace.as_deref() == namespace))
+            .filter_map(|b| Some((b, self.roles.get(&b.role)?)))
+    }
+
+    /// Whether any role bound to `principal` is confined to a namespace.
+    pub fn has_namespace_bindings(&self, principal: &str) -> bool {
+        self.bindings.iter().any(|b| b.principal == principal && b.namespace.is_some())
+    }
+
+    /// Add a role, refusing to replace one.
+    pub fn create_role(&mut self, role: RoleDefinition) -> Result<(), RbacError> {
+        validate_role_name(&role.name)?;
+        if self.roles.contains_key(&role.name) {
+            return Err(RbacError::RoleExists(role.name));
+        }
+        self.set_role(role);
+        Ok(())
+    }
+
+    /// Replace the permissions of role `name`.  The admin role keeps every
+    /// permission, so that the policy can always be changed back.
+    pub fn set_permissions(
+        &mut self,
+        name: &str,
+        global_permissions: Vec<Permission>,
+        modality_permissions: HashMap<String, Vec<Permission>>,
+    ) -> Result<RoleDefinition, RbacError> {
+        if name == "admin" {
+            return Err(RbacError::Invalid("the admin role cannot be changed".to_string()));
+        }
+        let role = self
+            .roles
+            .get_mut(name)
+            .ok_or_else(|| RbacError::RoleNotFound(name.to_string()))?;
+        role.global_permissions = global_permissions;
+        role.modality_permissions = modality_permissions;
+        Ok(role.clone())
+    }
+
+    /// Remove role `name` and its bindings.  Built-in roles cannot be
+    /// removed.
+    pub fn remove_role(&mut self, name: &str) -> Result<RoleDefinition, RbacError> {
+        if BUILT_IN_ROLES.contains(&name) {
+            return Err(RbacError::Invalid(format!("built-in role {} cannot be removed", name)));
+        }
+        let role = self
+            .roles
+            .remove(name)
+            .ok_or_else(|| RbacError::RoleNotFound(name.to_string()))?;
+        self.bindings.retain(|b| b.role != name);
+        Ok(role)
+    }
+
+    /// Bind a role to a principal; binding it again changes nothing.
+    pub fn bind(&mut self, binding: RoleBinding) -> Result<(), RbacError> {
+        if !self.roles.contains_key(&binding.role) {
+            return Err(RbacError::RoleNotFound(binding.role));
+        }
+        if binding.principal.is_empty() {
+            return Err(RbacError::Invalid("binding needs a principal".to_string()));
+        }
+        if !self.bindings.contains(&binding) {
+            self.bindings.push(binding);
+        }
+        Ok(())
+    }
+
+    /// Remove a binding.
+    pub fn unbind(&mut self, binding: &RoleBinding) -> Result<(), RbacError> {
+        let before = self.bindings.len();
+        self.bindings.retain(|b| b != binding);
+        if self.bindings.len() == before {
+            return Err(RbacError::BindingNotFound);
+        }
+        Ok(())
+    }
+
     /// Add an entity-level ACL entry granting `permissions` to `client_id`
     /// on `entity_id`.
     pub fn add_entity_acl(
@@ -259,6 +399,9 @@ pub struct AuditEntry {
     pub timestamp: u64,
     /// Client identifier (API key hash or JWT subject).
     pub client_id: String,
+    /// Principal the client acts as (API key label or JWT subject).
+    #[serde(default)]
+    pub principal: String,
     /// Role of the client at the time of the decision.
     pub client_role: String,
     /// The resource path that was accessed.
@@ -271,6 +414,9 @@ pub struct AuditEntry {
     pub decision: AccessDecision,
     /// Optional reason string for denials.
     pub reason: Option<String>,
+    /// The entity (hexad) the resource path targets, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
 }
 
 /// Thread-safe audit log that records all authorization decisions.
@@ -279,6 +425,8 @@ pub struct AuditLog {
     entries: Arc<Mutex<Vec<AuditEntry>>>,
     /// Maximum number of entries retained (ring buffer behaviour).
     max_entries: usize,
+    /// Audit stream every entry is also recorded to.
+    stream: Option<crate::authz_audit::AuthzAudit>,
 }
 
 impl AuditLog {
@@ -287,11 +435,22 @@ pub fn new(max_entries: usize) -> Self {
         Self {
             entries: Arc::new(Mutex::new(Vec::with_capacity(max_entries.min(4096)))),
             max_entries,
+            stream: None,
         }
     }
 
+    /// Also record every entry to the time-series audit `stream`, sharing
+    /// the retained entries with `self`.
+    pub fn with_stream(mut self, stream: crate::authz_audit::AuthzAudit) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
     /// Record an authorization decision.
     pub fn record(&self, entry: AuditEntry) {
+        if let Some(stream) = &self.stream {
+            stream.record((&entry).into());
+        }
         let mut entries = self.entries.lock().expect("audit log lock");
         if entries.len() >= self.max_entries {
             // Drop the oldest entry to stay within capacity.
@@ -341,6 +500,16 @@ pub struct RbacState {
     pub policy: Arc<Mutex<RbacPolicy>>,
     /// Authorization audit log.
     pub audit_log: AuditLog,
+    /// File the policy is saved to after every change.
+    persist_path: Option<PathBuf>,
+    /// Finds the namespace of an entity, for namespace-confined bindings.
+    namespaces: Option<Arc<dyn NamespaceResolver>>,
+}
+
+/// Looks up the namespace an entity belongs to.
+pub trait NamespaceResolver: Send + Sync + std::fmt::Debug {
+    /// The namespace of entity `entity_id`, if it exists and has one.
+    fn namespace<'a>(&'a self, entity_id: &'a str) -> BoxFuture<'a, Option<String>>;
 }
 
 impl RbacState {
@@ -349,10 +518,90 @@ pub fn new(policy: RbacPolicy) -> Self {
         Self {
             policy: Arc::new(Mutex::new(policy)),
             audit_log: AuditLog::default(),
+            persist_path: None,
+            namespaces: None,
+        }
+    }
+
+    /// RBAC state whose policy is loaded from and saved to `path`; the
+    /// default policy when the file does not exist yet.
+    pub fn persistent(path: impl Into<PathBuf>) -> Result<Self, RbacError> {
+        let path = path.into();
+        let persistence = |e: &dyn std::fmt::Display| RbacError::Persistence(format!("{}: {}", path.display(), e));
+        let policy = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| persistence(&e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RbacPolicy::default(),
+            Err(e) => return Err(persistence(&e)),
+        };
+        info!(path = %path.display(), "Loaded RBAC policy");
+        Ok(Self {
+            persist_path: Some(path),
+            ..Self::new(policy)
+        })
+    }
+
+    /// Resolve entity namespaces with `resolver`.
+    pub fn with_namespaces(mut self, resolver: Arc<dyn NamespaceResolver>) -> Self {
+        self.namespaces = Some(resolver);
+        self
+    }
+
+    /// A copy of the current policy.
+    pub fn snapshot(&self) -> RbacPolicy {
+        self.policy.lock().expect("rbac policy lock").clone()
+    }
+
+    /// Apply `change` to the policy, saving the result first when the
+    /// state is persistent; the policy is left as it was if either fails.
+    pub fn update<T>(&self, change: impl FnOnce(&mut RbacPolicy) -> Result<T, RbacError>) -> Result<T, RbacError> {
+        let mut policy = self.policy.lock().expect("rbac policy lock");
+        let mut changed = policy.clone();
+        let result = change(&mut changed)?;
+        if let Some(path) = &self.persist_path {
+            save_policy(path, &changed)?;
+        }
+        *policy = changed;
+        Ok(result)
+    }
+
+    /// The namespace of the entity `resource_path` targets, when a
+    /// namespace-confined binding of the client's principal could apply.
+    pub async fn namespace_for(&self, identity: &ClientIdentity, resource_path: &str) -> Option<String> {
+        let resolver = self.namespaces.as_ref()?;
+        let entity_id = entity_from_path(resource_path)?;
+        let confined = self
+            .policy
+            .lock()
+            .expect("rbac policy lock")
+            .has_namespace_bindings(&identity.principal_id());
+        if !confined {
+            return None;
         }
+        resolver.namespace(entity_id).await
     }
 }
 
+/// Role names: 1 to 64 ASCII alphanumerics, dashes and underscores.
+fn validate_role_name(name: &str) -> Result<(), RbacError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RbacError::Invalid(format!("invalid role name '{}'", name)))
+    }
+}
+
+/// Write the policy to `path` durably, via a temporary file, so a crash
+/// leaves either the old policy or the new one on disk, never a truncated
+/// one or none.
+fn save_policy(path: &Path, policy: &RbacPolicy) -> Result<(), RbacError> {
+    let persistence = |e: &dyn std::fmt::Display| RbacError::Persistence(format!("{}: {}", path.display(), e));
+    let json = serde_json::to_string_pretty(policy).map_err(|e| persistence(&e))?;
+    crate::write_durably(path, json.as_bytes()).map_err(|e| persistence(&e))
+}
+
 impl Default for RbacState {
     fn default() -> Self {
         Self::new(RbacPolicy::default())
@@ -389,8 +638,18 @@ fn into_response(self) -> axum::response::Response {
 /// The mapping follows REST conventions:
 /// - `GET` / `HEAD` / `OPTIONS` -> [`Permission::Read`]
 /// - `POST` to query/plan/explain endpoints -> [`Permission::Execute`]
+/// - `/auth` session endpoints -> [`Permission::Read`], since a session
+///   carries no more than the role of the credential it was issued for
+/// - `POST /graphql` -> [`Permission::Read`], each of its operations being
+///   authorized in turn as the REST request it stands for
 /// - `POST` / `PUT` / `PATCH` / `DELETE` -> [`Permission::Write`]
-/// - Admin endpoints (`/normalizer/trigger`, `/planner/config` PUT) ->
+/// - Admin endpoints (`/normalizer/trigger`, `/planner/config` PUT,
+///   `/drift/suppressions` POST/DELETE, `/drift/policy` PUT,
+///   `/drift/quarantine` DELETE, `/normalizer/previews` POST/DELETE,
+///   `/normalizer/rollback` POST, `/normalizer/strategies` PUT,
+///   `/normalizer/conflict-policy` PUT, `/normalizer/campaigns`
+///   POST/PUT/DELETE, `/circuits` POST, `/proofs/revocations` POST,
+///   `/wal` POST, `/admin`) ->
 ///   [`Permission::Admin`]
 pub fn required_permission(method: &Method, path: &str) -> Permission {
     // Admin endpoints (explicitly listed).
@@ -403,8 +662,12 @@ pub fn required_permission(method: &Method, path: &str) -> Permission {
         return Permission::Execute;
     }
 
-    // Read-only methods.
-    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+    // Read-only methods, exchanging a credential for a session, and
+    // GraphQL, whose operations are authorized one by one.
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || path.starts_with("/auth/")
+        || path == "/graphql"
+    {
         return Permission::Read;
     }
 
@@ -422,6 +685,53 @@ fn is_admin_path(method: &Method, path: &str) -> bool {
     if path.starts_with("/planner/config") && *method == Method::PUT {
         return true;
     }
+    // Opening or closing drift suppression windows is admin-only.
+    if path.starts_with("/drift/suppressions") && matches!(*method, Method::POST | Method::DELETE) {
+        return true;
+    }
+    // Changing the drift action policy or releasing quarantined entities is admin-only.
+    if path.starts_with("/drift/policy") && *method == Method::PUT {
+        return true;
+    }
+    if path.starts_with("/drift/quarantine") && *method == Method::DELETE {
+        return true;
+    }
+    // Approving or discarding a normalization preview is admin-only.
+    if path.starts_with("/normalizer/previews") && matches!(*method, Method::POST | Method::DELETE) {
+        return true;
+    }
+    // Rolling back an applied normalization is admin-only.
+    if path.starts_with("/normalizer/rollback") && *method == Method::POST {
+        return true;
+    }
+    // Changing strategy priorities and namespace sets is admin-only.
+    if path.starts_with("/normalizer/strategies") && *method == Method::PUT {
+        return true;
+    }
+    // Changing the conflict resolution policy is admin-only.
+    if path.starts_with("/normalizer/conflict-policy") && *method == Method::PUT {
+        return true;
+    }
+    // Managing or starting normalization campaigns is admin-only.
+    if path.starts_with("/normalizer/campaigns") && matches!(*method, Method::POST | Method::PUT | Method::DELETE) {
+        return true;
+    }
+    // Registering and deprecating proof circuits is admin-only.
+    if path.starts_with("/circuits") && *method == Method::POST {
+        return true;
+    }
+    // Revoking proofs by hand is admin-only.
+    if path.starts_with("/proofs/revocations") && *method == Method::POST {
+        return true;
+    }
+    // Checkpointing and point-in-time recovery are admin-only.
+    if path.starts_with("/wal/") && *method == Method::POST {
+        return true;
+    }
+    // Store-wide operator endpoints are admin-only.
+    if path.starts_with("/admin/") {
+        return true;
+    }
     false
 }
 
@@ -434,6 +744,9 @@ fn is_execute_path(method: &Method, path: &str) -> bool {
         || path.starts_with("/query/explain")
         || path.starts_with("/queries/similar")
         || path.starts_with("/search/")
+        || path == "/federation/query"
+        || path.starts_with("/federation/search/")
+        || path == "/subscriptions"
 }
 
 /// Extract the modality name from a resource path, if applicable.
@@ -472,8 +785,11 @@ pub fn entity_from_path(path: &str) -> Option<&str> {
             return Some(id);
         }
     }
-    // Matches /normalizer/trigger/{id}.
-    if let Some(rest) = path.strip_prefix("/normalizer/trigger/") {
+    // Matches /normalizer/trigger/{id} and /normalizer/preview/{id}.
+    if let Some(rest) = path
+        .strip_prefix("/normalizer/trigger/")
+        .or_else(|| path.strip_prefix("/normalizer/preview/"))
+    {
         let id = rest.split('/').next().unwrap_or(rest);
         if !id.is_empty() {
             return Some(id);
@@ -491,7 +807,8 @@ pub fn entity_from_path(path: &str) -> Option<&str> {
 /// This is the primary entry point for RBAC enforcement. It:
 ///
 /// 1. Determines the required [`Permission`] from the HTTP method and path.
-/// 2. Looks up the client's [`RoleDefinition`] in the active policy.
+/// 2. Looks up the client's [`RoleDefinition`] in the active policy, with
+///    the roles bound to its principal.
 /// 3. Checks entity-level ACLs (which can grant access that the role alone
 ///    would not).
 /// 4. Checks modality-specific permissions when the path targets a specific
@@ -505,6 +822,18 @@ pub fn check_access(
     resource_path: &str,
     method: &Method,
     rbac: &RbacState,
+) -> Result<(), AuthzError> {
+    check_access_in(identity, resource_path, method, rbac, None)
+}
+
+/// [`check_access`] for a request on an entity in `namespace`, to which
+/// roles bound in that namespace apply as well.
+pub fn check_access_in(
+    identity: &ClientIdentity,
+    resource_path: &str,
+    method: &Method,
+    rbac: &RbacState,
+    namespace: Option<&str>,
 ) -> Result<(), AuthzError> {
     let permission = required_permission(method, resource_path);
     let policy = rbac.policy.lock().expect("rbac policy lock");
@@ -525,12 +854,14 @@ pub fn check_access(
         let entry = AuditEntry {
             timestamp: now_secs,
             client_id: identity.id.clone(),
+            principal: identity.principal_id(),
             client_role: role_name.to_string(),
             resource_path: resource_path.to_string(),
             method: method.to_string(),
             required_permission: permission,
             decision,
             reason: reason.clone(),
+            entity: entity_from_path(resource_path).map(str::to_string),
         };
         rbac.audit_log.record(entry);
     };
@@ -550,29 +881,44 @@ pub fn check_access(
         }
     }
 
-    // --- 2. Look up role definition ---
-    let role_def = match policy.role_for(identity.role) {
-        Some(rd) => rd,
-        None => {
-            let reason = format!("No role definition found for '{}'", role_name);
-            warn!(
-                client = %identity.id,
-                role = %role_name,
-                path = %resource_path,
-                "Access DENIED: {}", reason
-            );
-            record(AccessDecision::Denied, Some(reason.clone()));
-            return Err(AuthzError {
-                error: reason,
-                code: 403,
-                required_permission: permission.to_string(),
-            });
-        }
+    // --- 2. Look up role definitions: the client's own, then bound ones ---
+    let principal = identity.principal_id();
+    let roles: Vec<(Option<&RoleBinding>, &RoleDefinition)> = policy
+        .role_for(identity.role)
+        .map(|rd| (None, rd))
+        .into_iter()
+        .chain(policy.bound_roles(&principal, namespace).map(|(b, rd)| (Some(b), rd)))
+        .collect();
+    if roles.is_empty() {
+        let reason = format!("No role definition found for '{}'", role_name);
+        warn!(
+            client = %identity.id,
+            role = %role_name,
+            path = %resource_path,
+            "Access DENIED: {}", reason
+        );
+        record(AccessDecision::Denied, Some(reason.clone()));
+        return Err(AuthzError {
+            error: reason,
+            code: 403,
+            required_permission: permission.to_string(),
+        });
+    }
+    // Grants through a bound role name the role and where it is bound.
+    let grant = |binding: Option<&RoleBinding>, kind: String| match binding {
+        None => kind,
+        Some(b) => match &b.namespace {
+            Some(ns) => format!("{} via role '{}' bound in namespace '{}'", kind, b.role, ns),
+            None => format!("{} via bound role '{}'", kind, b.role),
+        },
     };
 
     // --- 3. Modality-specific check ---
     if let Some(modality) = modality_from_path(resource_path) {
-        if role_def.has_modality_permission(modality, permission) {
+        if let Some((binding, _)) = roles
+            .iter()
+            .find(|(_, rd)| rd.has_modality_permission(modality, permission))
+        {
             info!(
                 client = %identity.id,
                 role = %role_name,
@@ -581,13 +927,13 @@ pub fn check_access(
                 permission = %permission,
                 "Access ALLOWED via modality permission"
             );
-            record(AccessDecision::Allowed, Some(format!("modality '{}' grant", modality)));
+            record(AccessDecision::Allowed, Some(grant(*binding, format!("modality '{}' grant", modality))));
             return Ok(());
         }
-        // If the role has a modality_permissions entry for this modality
+        // If a role has a modality_permissions entry for this modality
         // but the specific permission is missing, deny rather than falling
         // through to global (explicit modality config is restrictive).
-        if role_def.modality_permissions.contains_key(modality) {
+        if roles.iter().any(|(_, rd)| rd.modality_permissions.contains_key(modality)) {
             let reason = format!(
                 "Role '{}' lacks '{}' permission on modality '{}'",
                 role_name, permission, modality
@@ -608,7 +954,7 @@ pub fn check_access(
     }
 
     // --- 4. Global permission check ---
-    if role_def.has_global_permission(permission) {
+    if let Some((binding, _)) = roles.iter().find(|(_, rd)| rd.has_global_permission(permission)) {
         info!(
             client = %identity.id,
             role = %role_name,
@@ -616,7 +962,7 @@ pub fn check_access(
             permission = %permission,
             "Access ALLOWED via global permission"
         );
-        record(AccessDecision::Allowed, Some("global role grant".to_string()));
+        record(AccessDecision::Allowed, Some(grant(*binding, "global role grant".to_string())));
         return Ok(());
     }
 
@@ -649,8 +995,9 @@ pub fn check_authorization(
     resource_path: &str,
     method: &Method,
     rbac: &RbacState,
+    namespace: Option<&str>,
 ) -> Result<(), AuthzError> {
-    check_access(identity, resource_path, method, rbac)
+    check_access_in(identity, resource_path, method, rbac, namespace)
 }
 
 // ---------------------------------------------------------------------------
@@ -672,6 +1019,10 @@ fn identity(id: &str, role: ClientRole) -> ClientIdentity {
         ClientIdentity {
             id: id.to_string(),
             role,
+            kind: verisim_provenance::PrincipalKind::Token,
+            display_name: None,
+            attributes: Default::default(),
+            credential: Default::default(),
         }
     }
 
@@ -779,6 +1130,16 @@ fn test_writer_allowed_write_denied_admin() {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_wal_recovery_requires_admin() {
+        let rbac = default_rbac();
+        let writer = identity("writer-user", ClientRole::Writer);
+
+        assert!(check_access(&writer, "/wal/recover", &Method::POST, &rbac).is_err());
+        assert!(check_access(&writer, "/wal/status", &Method::GET, &rbac).is_ok());
+        assert!(check_access(&writer, "/admin/stats", &Method::GET, &rbac).is_err());
+    }
+
     // ------------------------------------------------------------------
     // Test 5: Per-modality permissions work
     // ------------------------------------------------------------------
@@ -900,12 +1261,14 @@ fn test_audit_log_capacity() {
             log.record(AuditEntry {
                 timestamp: i as u64,
                 client_id: format!("client-{}", i),
+                principal: format!("client-{}", i),
                 client_role: "reader".to_string(),
                 resource_path: "/test".to_string(),
                 method: "GET".to_string(),
                 required_permission: Permission::Read,
                 decision: AccessDecision::Allowed,
                 reason: None,
+                entity: None,
             });
         }
 
@@ -926,6 +1289,7 @@ fn test_required_permission_derivation() {
         assert_eq!(required_permission(&Method::GET, "/hexads"), Permission::Read);
         assert_eq!(required_permission(&Method::HEAD, "/hexads"), Permission::Read);
         assert_eq!(required_permission(&Method::OPTIONS, "/anything"), Permission::Read);
+        assert_eq!(required_permission(&Method::POST, "/graphql"), Permission::Read);
 
         // Write
         assert_eq!(required_permission(&Method::POST, "/hexads"), Permission::Write);
@@ -938,6 +1302,7 @@ fn test_required_permission_derivation() {
         assert_eq!(required_permission(&Method::POST, "/search/vector"), Permission::Execute);
         assert_eq!(required_permission(&Method::POST, "/search/text?q=foo"), Permission::Execute);
         assert_eq!(required_permission(&Method::POST, "/queries/similar"), Permission::Execute);
+        assert_eq!(required_permission(&Method::POST, "/subscriptions"), Permission::Execute);
 
         // Admin
         assert_eq!(
@@ -948,6 +1313,45 @@ fn test_required_permission_derivation() {
             required_permission(&Method::PUT, "/planner/config"),
             Permission::Admin
         );
+        assert_eq!(
+            required_permission(&Method::POST, "/drift/suppressions"),
+            Permission::Admin
+        );
+        assert_eq!(
+            required_permission(&Method::DELETE, "/drift/suppressions/abc"),
+            Permission::Admin
+        );
+        assert_eq!(required_permission(&Method::GET, "/drift/suppressions"), Permission::Read);
+        assert_eq!(required_permission(&Method::PUT, "/drift/policy"), Permission::Admin);
+        assert_eq!(
+            required_permission(&Method::DELETE, "/drift/quarantine/abc"),
+            Permission::Admin
+        );
+        assert_eq!(
+            required_permission(&Method::POST, "/normalizer/previews/abc/approve"),
+            Permission::Admin
+        );
+        assert_eq!(required_permission(&Method::POST, "/normalizer/preview/abc"), Permission::Write);
+        assert_eq!(required_permission(&Method::POST, "/normalizer/rollback/abc"), Permission::Admin);
+        assert_eq!(required_permission(&Method::PUT, "/normalizer/strategies"), Permission::Admin);
+        assert_eq!(required_permission(&Method::GET, "/normalizer/strategies"), Permission::Read);
+        assert_eq!(
+            required_permission(&Method::PUT, "/normalizer/conflict-policy"),
+            Permission::Admin
+        );
+        assert_eq!(
+            required_permission(&Method::POST, "/normalizer/campaigns/abc/run"),
+            Permission::Admin
+        );
+        assert_eq!(required_permission(&Method::GET, "/normalizer/campaigns/abc/runs"), Permission::Read);
+        assert_eq!(required_permission(&Method::POST, "/circuits"), Permission::Admin);
+        assert_eq!(
+            required_permission(&Method::POST, "/circuits/age-check/1.0.0/deprecate"),
+            Permission::Admin
+        );
+        assert_eq!(required_permission(&Method::GET, "/circuits/age-check"), Permission::Read);
+        assert_eq!(required_permission(&Method::POST, "/proofs/revocations"), Permission::Admin);
+        assert_eq!(required_permission(&Method::GET, "/proofs/revocations"), Permission::Read);
     }
 
     // ------------------------------------------------------------------
@@ -1031,10 +1435,10 @@ fn test_check_authorization_wrapper() {
         let reader = identity("wrapper-reader", ClientRole::Reader);
 
         // Admin should pass.
-        assert!(check_authorization(&admin, "/hexads", &Method::POST, &rbac).is_ok());
+        assert!(check_authorization(&admin, "/hexads", &Method::POST, &rbac, None).is_ok());
 
         // Reader write should fail.
-        assert!(check_authorization(&reader, "/hexads", &Method::POST, &rbac).is_err());
+        assert!(check_authorization(&reader, "/hexads", &Method::POST, &rbac, None).is_err());
     }
 
     // ------------------------------------------------------------------
@@ -1081,4 +1485,106 @@ fn test_audit_log_clear() {
         assert!(rbac.audit_log.is_empty());
         assert_eq!(rbac.audit_log.len(), 0);
     }
+
+    // ------------------------------------------------------------------
+    // Test 16: Bound roles grant permissions, in their namespace only
+    // ------------------------------------------------------------------
+    #[test]
+    fn test_role_bindings_grant_permissions() {
+        let rbac = default_rbac();
+        rbac.update(|policy| {
+            policy.create_role(RoleDefinition {
+                name: "curator".to_string(),
+                global_permissions: vec![Permission::Write],
+                modality_permissions: HashMap::new(),
+            })?;
+            policy.bind(RoleBinding {
+                role: "curator".to_string(),
+                principal: "token:dana".to_string(),
+                namespace: Some("alpha".to_string()),
+            })?;
+            policy.bind(RoleBinding {
+                role: "writer".to_string(),
+                principal: "token:erin".to_string(),
+                namespace: None,
+            })
+        })
+        .unwrap();
+
+        let dana = identity("dana", ClientRole::Reader);
+        let path = "/hexads/entity-1";
+        assert!(check_access_in(&dana, path, &Method::PUT, &rbac, Some("alpha")).is_ok());
+        assert!(check_access_in(&dana, path, &Method::PUT, &rbac, Some("beta")).is_err());
+        assert!(check_access(&dana, path, &Method::PUT, &rbac).is_err());
+
+        let erin = identity("erin", ClientRole::Reader);
+        assert!(check_access(&erin, "/hexads", &Method::POST, &rbac).is_ok());
+        assert!(check_access(&erin, "/admin/stats", &Method::GET, &rbac).is_err());
+
+        let entry = rbac.audit_log.entries().into_iter().rev().nth(1).unwrap();
+        assert_eq!(entry.reason.as_deref(), Some("global role grant via bound role 'writer'"));
+    }
+
+    // ------------------------------------------------------------------
+    // Test 17: Role management guards
+    // ------------------------------------------------------------------
+    #[test]
+    fn test_role_management_guards() {
+        let mut policy = RbacPolicy::default();
+        let role = |name: &str| RoleDefinition {
+            name: name.to_string(),
+            global_permissions: vec![Permission::Read],
+            modality_permissions: HashMap::new(),
+        };
+        assert!(matches!(policy.create_role(role("writer")), Err(RbacError::RoleExists(_))));
+        assert!(matches!(policy.create_role(role("bad name")), Err(RbacError::Invalid(_))));
+        assert!(matches!(
+            policy.set_permissions("admin", vec![], HashMap::new()),
+            Err(RbacError::Invalid(_))
+        ));
+        assert!(matches!(policy.remove_role("reader"), Err(RbacError::Invalid(_))));
+
+        policy.create_role(role("auditor")).unwrap();
+        let binding = RoleBinding {
+            role: "auditor".to_string(),
+            principal: "frank".to_string(),
+            namespace: None,
+        };
+        policy.bind(binding.clone()).unwrap();
+        policy.bind(binding.clone()).unwrap();
+        assert_eq!(policy.bindings.len(), 1);
+
+        // Removing a role drops its bindings
+        policy.remove_role("auditor").unwrap();
+        assert!(policy.bindings.is_empty());
+        assert!(matches!(policy.unbind(&binding), Err(RbacError::BindingNotFound)));
+        assert!(matches!(policy.bind(binding), Err(RbacError::RoleNotFound(_))));
+    }
+
+    // ------------------------------------------------------------------
+    // Test 18: Persistent policy survives a restart
+    // ------------------------------------------------------------------
+    #[test]
+    fn test_persistent_policy_round_trip() {
+        let path = std::env::temp_dir().join(format!("verisimdb-rbac-{}.json", uuid::Uuid::new_v4()));
+        let rbac = RbacState::persistent(&path).unwrap();
+        assert!(rbac.snapshot().roles.contains_key("admin"));
+        rbac.update(|policy| {
+            policy.bind(RoleBinding {
+                role: "writer".to_string(),
+                principal: "gina".to_string(),
+                namespace: Some("alpha".to_string()),
+            })
+        })
+        .unwrap();
+
+        // A failed change is neither applied nor saved
+        assert!(rbac.update(|policy| policy.remove_role("admin")).is_err());
+
+        let reloaded = RbacState::persistent(&path).unwrap().snapshot();
+        assert_eq!(reloaded.bindings.len(), 1);
+        assert!(reloaded.has_namespace_bindings("gina"));
+        assert!(reloaded.roles.contains_key("admin"));
+        let _ = std::fs::remove_file(&path);
+    }
 }
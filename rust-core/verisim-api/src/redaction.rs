@@ -0,0 +1,418 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Response redaction
+//!
+//! Some callers may know that an entity exists without seeing what it
+//! holds.  Redaction rules name a role and the modalities and fields its
+//! holders may not see.  A middleware works out what the client may not
+//! see and runs the request in its [`scope`]; JSON responses that are
+//! answered whole are redacted as they leave, and whatever serializes
+//! entities later or elsewhere — streamed exports, subscription events,
+//! GraphQL subscriptions, gRPC messages — applies [`current`] to each
+//! record it writes.  A streamed JSON response says so by carrying
+//! [`RedactedAsWritten`]; any other JSON response that cannot be redacted
+//! whole, being too large or not parsing, is refused rather than sent as it
+//! is.
+//!
+//! A client's roles are its own and every role bound to its principal
+//! outside a namespace.  Admins and federation peers, whom the entity policy
+//! does not restrict either, are never redacted.  Field rules are dotted key
+//! paths matched against the end of a value's path in the response, so
+//! `salary` matches every `salary` key and `document.body` only the body of
+//! a document.
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::auth::ClientIdentity;
+use crate::rbac::RbacState;
+
+/// What a masked string reads as; other masked values become `null`
+pub const MASK: &str = "[redacted]";
+
+/// Largest response body redacted whole; larger bodies are refused unless
+/// they were redacted record by record where they were written
+const MAX_REDACTED_BODY: u64 = 64 * 1024 * 1024;
+
+/// Response extension of a body redacted record by record as it was
+/// written, which the middleware passes through
+#[derive(Debug, Clone, Copy)]
+pub struct RedactedAsWritten;
+
+tokio::task_local! {
+    static REDACTION: Option<Arc<Redaction>>;
+}
+
+/// Run `future` hiding what `redaction` names from what it serializes
+pub async fn scope<F: Future>(redaction: Option<Arc<Redaction>>, future: F) -> F::Output {
+    REDACTION.scope(redaction, future).await
+}
+
+/// What the current request's client may not see; `None` outside a
+/// request or when nothing is hidden from it
+pub fn current() -> Option<Arc<Redaction>> {
+    REDACTION.try_with(Clone::clone).ok().flatten()
+}
+
+/// Hide from `value` what the current client may not see
+pub fn redact(value: &mut Value) {
+    if let Some(redaction) = current() {
+        redaction.apply(value);
+    }
+}
+
+/// [`redact`] for a JSON document held as text, which is left alone when
+/// it does not parse
+pub fn redact_json(json: String) -> String {
+    let Some(redaction) = current() else {
+        return json;
+    };
+    match serde_json::from_str::<Value>(&json) {
+        Ok(mut value) => {
+            redaction.apply(&mut value);
+            value.to_string()
+        }
+        Err(_) => json,
+    }
+}
+
+/// A modality whose content can be redacted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactedModality {
+    Graph,
+    Vector,
+    Tensor,
+    Semantic,
+    Document,
+    Spatial,
+}
+
+impl RedactedModality {
+    /// Response keys carrying the modality's content
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Graph => &["graph_node"],
+            Self::Vector => &["embedding"],
+            Self::Tensor => &["tensor"],
+            Self::Semantic => &["semantic"],
+            Self::Document => &["document", "title", "body"],
+            Self::Spatial => &["spatial_data", "latitude", "longitude", "geometry", "wkt", "wkb"],
+        }
+    }
+}
+
+/// How a redacted value is hidden
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionAction {
+    /// Leave the key out
+    #[default]
+    Drop,
+    /// Keep the key with its value masked
+    Mask,
+}
+
+/// What holders of one role may not see
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// Role the rule applies to, built-in or defined at runtime
+    pub role: String,
+    #[serde(default)]
+    pub modalities: Vec<RedactedModality>,
+    /// Dotted key paths, such as `salary` or `document.body`
+    #[serde(default)]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub action: RedactionAction,
+}
+
+/// Redaction rules by role; no rules redacts nothing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionPolicy {
+    #[serde(default)]
+    pub rules: Vec<RedactionRule>,
+}
+
+impl RedactionPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// What holders of `roles` may not see, or `None` when nothing
+    pub fn redaction_for<S: AsRef<str>>(&self, roles: &[S]) -> Option<Redaction> {
+        let mut paths = Vec::new();
+        for rule in self.rules.iter().filter(|r| roles.iter().any(|role| role.as_ref() == r.role)) {
+            let modality_keys = rule.modalities.iter().flat_map(|m| m.keys().iter().map(|k| vec![k.to_string()]));
+            let fields = rule.fields.iter().map(|f| f.split('.').map(str::to_string).collect());
+            paths.extend(modality_keys.chain(fields).map(|path| (path, rule.action)));
+        }
+        (!paths.is_empty()).then_some(Redaction { paths })
+    }
+}
+
+/// Key paths to hide from one response
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    paths: Vec<(Vec<String>, RedactionAction)>,
+}
+
+impl Redaction {
+    /// Hide the redacted keys of `value`
+    pub fn apply(&self, value: &mut Value) {
+        self.walk(value, &mut Vec::new());
+    }
+
+    fn walk(&self, value: &mut Value, path: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                let keys: Vec<String> = map.keys().cloned().collect();
+                for key in keys {
+                    path.push(key.clone());
+                    match self.action_for(path) {
+                        Some(RedactionAction::Drop) => {
+                            map.remove(&key);
+                        }
+                        Some(RedactionAction::Mask) => {
+                            if let Some(v) = map.get_mut(&key) {
+                                *v = match v {
+                                    Value::String(_) => Value::String(MASK.to_string()),
+                                    _ => Value::Null,
+                                };
+                            }
+                        }
+                        None => {
+                            if let Some(v) = map.get_mut(&key) {
+                                self.walk(v, path);
+                            }
+                        }
+                    }
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.walk(item, path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// How the key at `path` is hidden; dropping wins over masking
+    fn action_for(&self, path: &[String]) -> Option<RedactionAction> {
+        self.paths
+            .iter()
+            .filter(|(redacted, _)| path.ends_with(redacted))
+            .map(|(_, action)| *action)
+            .max_by_key(|action| *action == RedactionAction::Drop)
+    }
+}
+
+/// Applies a [`RedactionPolicy`] to the roles of each client
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    policy: Arc<RedactionPolicy>,
+    rbac: RbacState,
+}
+
+impl Redactor {
+    /// Redact by `policy`, finding bound roles in `rbac`
+    pub fn new(policy: RedactionPolicy, rbac: RbacState) -> Self {
+        Self {
+            policy: Arc::new(policy),
+            rbac,
+        }
+    }
+
+    /// What `identity` may not see
+    pub fn redaction_for(&self, identity: &ClientIdentity) -> Option<Redaction> {
+        let principal = identity.principal_id();
+        let mut roles = vec![format!("{:?}", identity.role).to_lowercase()];
+        let policy = self.rbac.policy.lock().expect("rbac policy lock");
+        roles.extend(policy.bound_roles(&principal, None).map(|(binding, _)| binding.role.clone()));
+        drop(policy);
+        self.policy.redaction_for(&roles)
+    }
+}
+
+/// Run the request in the scope of what the client's roles may not see,
+/// and redact its JSON response, refusing one it cannot redact
+pub async fn redaction_middleware(State(redactor): State<Redactor>, request: Request, next: Next) -> Response {
+    if redactor.policy.is_empty() {
+        return next.run(request).await;
+    }
+    let unrestricted = verisim_hexad::security::current().is_some_and(|p| p.unrestricted);
+    let redaction = request
+        .extensions()
+        .get::<ClientIdentity>()
+        .filter(|_| !unrestricted)
+        .and_then(|identity| redactor.redaction_for(identity))
+        .map(Arc::new);
+    let response = scope(redaction.clone(), next.run(request)).await;
+    let Some(redaction) = redaction else {
+        return response;
+    };
+    let json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::trim)
+        .is_some_and(|mime| mime == "application/json" || mime.ends_with("+json"));
+    if !json || response.extensions().get::<RedactedAsWritten>().is_some() {
+        return response;
+    }
+    let whole = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_REDACTED_BODY);
+    if !whole {
+        warn!("Response too large to redact refused");
+        let error = serde_json::json!({"error": "Response too large to redact", "code": 413});
+        return (StatusCode::PAYLOAD_TOO_LARGE, axum::Json(error)).into_response();
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_REDACTED_BODY as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!(error = %e, "Reading response to redact failed");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return crate::ApiError::Internal("Response to redact is not JSON".to_string()).into_response();
+    };
+    redaction.apply(&mut value);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(action: RedactionAction) -> RedactionPolicy {
+        RedactionPolicy {
+            rules: vec![RedactionRule {
+                role: "reader".to_string(),
+                modalities: vec![RedactedModality::Vector],
+                fields: vec!["document.body".to_string(), "salary".to_string()],
+                action,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rules_apply_to_their_roles_only() {
+        let policy = policy(RedactionAction::Drop);
+        assert!(policy.redaction_for(&["writer"]).is_none());
+        assert!(policy.redaction_for(&["writer", "reader"]).is_some());
+    }
+
+    #[test]
+    fn test_drop_and_mask() {
+        let mut value = json!([{
+            "id": "h1",
+            "title": "Payroll",
+            "embedding": {"vector": [0.1, 0.2]},
+            "document": {"title": "Payroll", "body": "secret", "fields": {"salary": "90000"}},
+            "body": "kept: not a document body"
+        }]);
+        let mut masked = value.clone();
+
+        policy(RedactionAction::Drop).redaction_for(&["reader"]).unwrap().apply(&mut value);
+        assert_eq!(
+            value,
+            json!([{
+                "id": "h1",
+                "title": "Payroll",
+                "document": {"title": "Payroll", "fields": {}},
+                "body": "kept: not a document body"
+            }])
+        );
+
+        policy(RedactionAction::Mask).redaction_for(&["reader"]).unwrap().apply(&mut masked);
+        assert_eq!(masked[0]["embedding"], Value::Null);
+        assert_eq!(masked[0]["document"]["body"], MASK);
+        assert_eq!(masked[0]["document"]["fields"]["salary"], MASK);
+    }
+
+    #[tokio::test]
+    async fn test_scope_redacts_what_is_serialized_within_it() {
+        let json = json!({"id": "h1", "salary": 90000}).to_string();
+        assert_eq!(redact_json(json.clone()), json);
+
+        let redaction = policy(RedactionAction::Drop).redaction_for(&["reader"]).map(Arc::new);
+        let redacted = scope(redaction.clone(), async { redact_json(json.clone()) }).await;
+        assert_eq!(redacted, json!({"id": "h1"}).to_string());
+        let unparsed = scope(redaction, async { redact_json("not json".to_string()) }).await;
+        assert_eq!(unparsed, "not json");
+        assert_eq!(scope(None, async { redact_json(json.clone()) }).await, json);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_refuses_what_it_cannot_redact() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let json_response = |body: Body| ([(header::CONTENT_TYPE, "application/json")], body).into_response();
+        let stream = || {
+            let chunks = [Ok::<_, std::io::Error>(r#"{"salary": "#), Ok("90000}")];
+            Body::from_stream(futures::stream::iter(chunks))
+        };
+        let redactor = Redactor::new(policy(RedactionAction::Drop), RbacState::default());
+        let app = Router::new()
+            .route("/small", get(|| async { axum::Json(json!({"id": "h1", "salary": 90000})) }))
+            .route("/large", get(move || async move {
+                json_response(Body::from(vec![b' '; MAX_REDACTED_BODY as usize + 1]))
+            }))
+            .route("/streamed", get(move || async move { json_response(stream()) }))
+            .route("/written", get(move || async move {
+                let mut response = json_response(stream());
+                response.extensions_mut().insert(RedactedAsWritten);
+                response
+            }))
+            .route("/garbled", get(move || async move { json_response(Body::from("{\"salary\"")) }))
+            .layer(axum::middleware::from_fn_with_state(redactor, redaction_middleware))
+            .layer(axum::middleware::from_fn(|mut request: Request, next: Next| async move {
+                request.extensions_mut().insert(ClientIdentity {
+                    id: "reader-1".to_string(),
+                    role: crate::auth::ClientRole::Reader,
+                    kind: verisim_provenance::PrincipalKind::ApiKey,
+                    display_name: None,
+                    attributes: Default::default(),
+                    credential: Default::default(),
+                });
+                next.run(request).await
+            }));
+        let get = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+        let text = |response: Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        };
+
+        assert_eq!(text(get("/small").await.unwrap()).await, json!({"id": "h1"}).to_string());
+        assert_eq!(get("/large").await.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(get("/streamed").await.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(get("/garbled").await.unwrap().status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let written = get("/written").await.unwrap();
+        assert_eq!(written.status(), StatusCode::OK);
+        assert_eq!(text(written).await, r#"{"salary": 90000}"#);
+    }
+}
@@ -0,0 +1,1120 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Asynchronous replication — read replicas.
+//!
+//! Every instance keeps a change feed: the [`ChangeLogListener`] change hook
+//! gives each write a position in the [`ChangeLog`].  A replica started with
+//! `replicate_from` polls its primary's `GET /replication/changes` and
+//! applies what it gets:
+//!
+//! - a `put` carries the entity's state as the primary has it when the feed
+//!   is read, so a replica that applies it converges however many writes it
+//!   stood for; the replica writes it under the primary's ID, linking its
+//!   provenance to the head of the primary's chain for the entity
+//! - a `delete` hard-deletes the entity
+//!
+//! The replica reports the position it has applied with each poll, from
+//! which the primary tracks every follower's lag; the replica tracks its
+//! own, in entries and in seconds since the oldest change it has not
+//! applied.
+//!
+//! ## Bootstrap
+//!
+//! A new replica, or one whose position the primary no longer retains (the
+//! feed keeps the last [`CHANGE_LOG_CAPACITY`] changes, in memory, and a
+//! restart starts a new feed with a new `epoch`), catches up through the
+//! export path: `GET /replication/snapshot` pages through a read snapshot of
+//! the primary, which the replica imports, deleting what the primary does
+//! not have, before polling from the position the snapshot was taken at.
+//! Changes the snapshot already includes are applied again, harmlessly.
+//!
+//! ## Selective replication
+//!
+//! A primary can hold back entities from some replicas with
+//! [`ReplicationRule`]s: a collection allow-list, a `WHERE` filter in the
+//! form SUBSCRIBE takes, or both.  A replica polling under a rule is sent
+//! only the entities that meet it; one that stops meeting it is sent as a
+//! `delete`, so the replica drops its copy.  Changes to entities outside an
+//! allow-listed collection are withheld entirely; with a filter, the replica
+//! may see the ID of an entity it never received in a `delete`, but never
+//! its contents.  The rule is chosen by the follower ID the replica polls
+//! under or, when it authenticates with a federation token, by its peer ID;
+//! a `*` rule applies to any follower no other rule names.
+//!
+//! A replica is read-only: writes through the API are refused with 503 and
+//! should be sent to the primary.
+//!
+//! ## Read routing
+//!
+//! A replica serves reads — entity gets and lists, and searches — itself,
+//! unless the request bounds the staleness it accepts with
+//! `x-max-staleness-ms` and the replica may be further behind than that:
+//! then the request is forwarded to the primary, with the caller's API key
+//! or token, and the primary's answer returned.  A client certificate
+//! cannot be passed on: reads it authenticates are refused with 503
+//! instead of being forwarded.  A replica's staleness is
+//! the time since the oldest change it knows of but has not applied or,
+//! when it is caught up, since its last poll; before its first poll it is
+//! unbounded.  Every routed read's response names the instance that served
+//! it in `x-served-by` (the replica's follower ID or the primary's endpoint)
+//! and, when the replica served it, its staleness in `x-staleness-ms`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::{info, warn};
+use verisim_hexad::{ChainLink, Hexad, HexadId, HexadInput, HexadListener, HexadProvenanceInput, HexadStore, ProvenanceStore};
+
+use crate::peer_auth::{PeerAuth, PeerScope};
+use crate::subscriptions::{self, SubscriptionCondition};
+use crate::{ApiError, AppState};
+
+/// Changes the feed retains; a replica further behind bootstraps again
+pub const CHANGE_LOG_CAPACITY: usize = 100_000;
+
+/// Most changes served per poll
+pub const MAX_CHANGE_PAGE: usize = 1000;
+
+/// Most entities served per snapshot page
+pub const MAX_SNAPSHOT_PAGE: usize = 500;
+
+/// Time allowed for one request to the primary
+const PRIMARY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Request header bounding the staleness of a read a replica may serve, in
+/// milliseconds
+pub const MAX_STALENESS_HEADER: &str = "x-max-staleness-ms";
+
+/// Response header naming the instance that served a routed read
+pub const SERVED_BY_HEADER: &str = "x-served-by";
+
+/// Response header giving the staleness of a read a replica served, in
+/// milliseconds
+pub const STALENESS_HEADER: &str = "x-staleness-ms";
+
+/// Largest request body forwarded to the primary
+const FORWARD_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// What a change did to an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOp {
+    /// Created, updated or restored
+    Put,
+    /// Deleted, soft-deleted, expired or merged away
+    Delete,
+}
+
+/// One entry of the change feed
+#[derive(Debug, Clone)]
+struct ChangeRecord {
+    position: u64,
+    hexad_id: String,
+    op: ChangeOp,
+    at: DateTime<Utc>,
+}
+
+/// A change as served to replicas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub position: u64,
+    pub hexad_id: String,
+    pub op: ChangeOp,
+    /// When the primary made the change
+    pub at: DateTime<Utc>,
+    /// For a `put`, the entity's current state; absent if it has since been
+    /// deleted (a later `delete` follows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<HexadInput>,
+    /// For a `put`, the hash of the latest record of the entity's
+    /// provenance chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance_head: Option<String>,
+}
+
+/// A page of the change feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    /// Identifies this run of the feed; positions from another are meaningless
+    pub epoch: String,
+    /// Position of the latest change
+    pub head: u64,
+    pub changes: Vec<Change>,
+    /// The requested position is not retained, or is from another epoch:
+    /// bootstrap from `/replication/snapshot`
+    #[serde(default)]
+    pub bootstrap_required: bool,
+    /// When the first change after this page was made, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_since: Option<DateTime<Utc>>,
+    /// Position the page runs through, counting changes the follower's
+    /// replication rule withheld
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub through: Option<u64>,
+}
+
+/// One entity of a snapshot export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntity {
+    pub id: String,
+    pub input: HexadInput,
+    /// Hash of the latest record of the entity's provenance chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance_head: Option<String>,
+}
+
+/// A page of a snapshot export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPage {
+    pub epoch: String,
+    /// Feed position the snapshot was taken at; only on the page that took it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u64>,
+    /// Read snapshot handle to page with
+    pub snapshot: String,
+    pub entities: Vec<SnapshotEntity>,
+    /// Offset of the next page, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
+}
+
+/// A replica's position as its primary last saw it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowerPosition {
+    pub follower_id: String,
+    /// Position the follower has applied
+    pub position: u64,
+    /// Changes the follower has not applied
+    pub lag_entries: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Which entities a replica is sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationRule {
+    /// Follower or federation peer ID the rule applies to; `*` for any
+    /// follower no other rule names
+    pub follower: String,
+    /// Collections whose entities are sent; empty for every collection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<String>,
+    /// `WHERE field = value [AND ...]` an entity must also meet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+/// A [`ReplicationRule`] ready to evaluate
+#[derive(Debug, Clone)]
+pub struct ReplicationFilter {
+    collections: HashSet<String>,
+    conditions: Vec<SubscriptionCondition>,
+}
+
+impl ReplicationFilter {
+    fn new(rule: &ReplicationRule) -> Result<Self, ApiError> {
+        let conditions = match &rule.filter {
+            Some(filter) => crate::vql::parse_subscribe(filter, &HashMap::new())
+                .map_err(|e| ApiError::BadRequest(format!("replication rule for {}: {e}", rule.follower)))?
+                .into_iter()
+                .map(|(field, value)| SubscriptionCondition { field, value })
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(Self {
+            collections: rule.collections.iter().cloned().collect(),
+            conditions,
+        })
+    }
+
+    /// Whether changes to the entity with this ID can be sent at all
+    pub fn admits_id(&self, id: &HexadId) -> bool {
+        self.collections.is_empty() || id.collection().is_some_and(|c| self.collections.contains(c))
+    }
+
+    /// Whether the entity is sent
+    pub fn admits(&self, hexad: &Hexad) -> bool {
+        self.admits_id(&hexad.id) && subscriptions::matches(&self.conditions, hexad)
+    }
+}
+
+/// A primary's replication rules, by follower
+#[derive(Debug, Clone, Default)]
+pub struct ReplicationRules {
+    rules: Vec<ReplicationRule>,
+    filters: Arc<HashMap<String, ReplicationFilter>>,
+}
+
+impl ReplicationRules {
+    /// Compile `rules`, refusing a filter that does not parse
+    pub fn new(rules: Vec<ReplicationRule>) -> Result<Self, ApiError> {
+        let filters = rules
+            .iter()
+            .map(|rule| Ok((rule.follower.clone(), ReplicationFilter::new(rule)?)))
+            .collect::<Result<HashMap<_, _>, ApiError>>()?;
+        Ok(Self {
+            rules,
+            filters: Arc::new(filters),
+        })
+    }
+
+    pub fn rules(&self) -> &[ReplicationRule] {
+        &self.rules
+    }
+
+    /// The filter for `follower`, or for an unnamed one; `None` sends everything
+    pub fn for_follower(&self, follower: Option<&str>) -> Option<&ReplicationFilter> {
+        follower
+            .and_then(|f| self.filters.get(f))
+            .or_else(|| self.filters.get("*"))
+    }
+}
+
+#[derive(Debug)]
+struct ChangeLogInner {
+    records: VecDeque<ChangeRecord>,
+    head: u64,
+    followers: HashMap<String, (u64, DateTime<Utc>)>,
+}
+
+/// The change feed: positions of the writes this instance has made
+#[derive(Debug, Clone)]
+pub struct ChangeLog {
+    epoch: String,
+    capacity: usize,
+    inner: Arc<Mutex<ChangeLogInner>>,
+    /// The head, for waiting on the next change
+    heads: Arc<watch::Sender<u64>>,
+}
+
+impl Default for ChangeLog {
+    fn default() -> Self {
+        Self::with_capacity(CHANGE_LOG_CAPACITY)
+    }
+}
+
+impl ChangeLog {
+    /// A feed retaining the last `capacity` changes
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            epoch: uuid::Uuid::new_v4().to_string(),
+            capacity: capacity.max(1),
+            inner: Arc::new(Mutex::new(ChangeLogInner {
+                records: VecDeque::new(),
+                head: 0,
+                followers: HashMap::new(),
+            })),
+            heads: Arc::new(watch::channel(0).0),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChangeLogInner> {
+        self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    pub fn epoch(&self) -> &str {
+        &self.epoch
+    }
+
+    /// Position of the latest change (0 before the first)
+    pub fn head(&self) -> u64 {
+        self.lock().head
+    }
+
+    /// The head as it moves, to wait for changes after the one seen
+    pub fn watch(&self) -> watch::Receiver<u64> {
+        self.heads.subscribe()
+    }
+
+    fn record(&self, hexad_id: &str, op: ChangeOp) {
+        let mut inner = self.lock();
+        inner.head += 1;
+        let position = inner.head;
+        inner.records.push_back(ChangeRecord {
+            position,
+            hexad_id: hexad_id.to_string(),
+            op,
+            at: Utc::now(),
+        });
+        while inner.records.len() > self.capacity {
+            inner.records.pop_front();
+        }
+        drop(inner);
+        self.heads.send_replace(position);
+    }
+
+    /// Up to `limit` changes after `from`, and when the next one after them
+    /// was made; `None` if changes after `from` are no longer retained or
+    /// `from` is past the head
+    fn since(&self, from: u64, limit: usize) -> Option<(Vec<ChangeRecord>, Option<DateTime<Utc>>)> {
+        let inner = self.lock();
+        let oldest = inner.records.front().map(|r| r.position).unwrap_or(inner.head + 1);
+        if from > inner.head || from + 1 < oldest {
+            return None;
+        }
+        let mut pending = inner.records.iter().skip_while(|r| r.position <= from);
+        let records: Vec<ChangeRecord> = pending.by_ref().take(limit).cloned().collect();
+        Some((records, pending.next().map(|r| r.at)))
+    }
+
+    /// Note that `follower_id` has applied the feed through `position`
+    fn acknowledge(&self, follower_id: &str, position: u64) {
+        self.lock().followers.insert(follower_id.to_string(), (position, Utc::now()));
+    }
+
+    /// Followers that have polled this feed, by ID
+    pub fn followers(&self) -> Vec<FollowerPosition> {
+        let inner = self.lock();
+        let mut followers: Vec<FollowerPosition> = inner
+            .followers
+            .iter()
+            .map(|(id, &(position, last_seen))| FollowerPosition {
+                follower_id: id.clone(),
+                position,
+                lag_entries: inner.head.saturating_sub(position),
+                last_seen,
+            })
+            .collect();
+        followers.sort_by(|a, b| a.follower_id.cmp(&b.follower_id));
+        followers
+    }
+}
+
+/// Change hook that records writes in the [`ChangeLog`]
+pub struct ChangeLogListener(pub ChangeLog);
+
+impl HexadListener for ChangeLogListener {
+    fn name(&self) -> &str {
+        "replication"
+    }
+
+    fn on_created(&self, new: &Hexad) {
+        self.0.record(new.id.as_str(), ChangeOp::Put);
+    }
+
+    fn on_updated(&self, _old: &Hexad, new: &Hexad) {
+        self.0.record(new.id.as_str(), ChangeOp::Put);
+    }
+
+    fn on_deleted(&self, old: &Hexad) {
+        self.0.record(old.id.as_str(), ChangeOp::Delete);
+    }
+}
+
+/// Up to `limit` changes after `from`, with the state of the entities put.
+/// With `follower`, records that it has applied the feed through `from`;
+/// with `filter`, sends only what it admits.
+pub async fn changes(
+    state: &AppState,
+    from: u64,
+    limit: usize,
+    epoch: Option<&str>,
+    follower: Option<&str>,
+    filter: Option<&ReplicationFilter>,
+) -> Result<ChangeBatch, ApiError> {
+    let log = &state.replication.log;
+    let same_epoch = epoch.is_none_or(|e| e == log.epoch());
+    if let (Some(follower), true) = (follower, same_epoch) {
+        log.acknowledge(follower, from);
+    }
+    let found = if same_epoch { log.since(from, limit.clamp(1, MAX_CHANGE_PAGE)) } else { None };
+    let Some((records, pending_since)) = found else {
+        return Ok(ChangeBatch {
+            epoch: log.epoch().to_string(),
+            head: log.head(),
+            changes: Vec::new(),
+            bootstrap_required: true,
+            pending_since: None,
+            through: None,
+        });
+    };
+
+    let through = records.last().map_or(from, |r| r.position);
+    let mut puts: HashMap<String, (ChangeOp, Option<HexadInput>, Option<String>)> = HashMap::new();
+    let mut changes = Vec::with_capacity(records.len());
+    for record in records {
+        let id = HexadId::new(record.hexad_id.clone());
+        if filter.is_some_and(|f| !f.admits_id(&id)) {
+            continue;
+        }
+        let (op, input, provenance_head) = match record.op {
+            ChangeOp::Delete => (ChangeOp::Delete, None, None),
+            ChangeOp::Put => match puts.get(&record.hexad_id) {
+                Some(put) => put.clone(),
+                None => {
+                    let put = current_put(state, &id, filter).await?;
+                    puts.insert(record.hexad_id.clone(), put.clone());
+                    put
+                }
+            },
+        };
+        changes.push(Change {
+            position: record.position,
+            hexad_id: record.hexad_id,
+            op,
+            at: record.at,
+            input,
+            provenance_head,
+        });
+    }
+    Ok(ChangeBatch {
+        epoch: log.epoch().to_string(),
+        head: log.head(),
+        changes,
+        bootstrap_required: false,
+        pending_since,
+        through: Some(through),
+    })
+}
+
+/// A put of the entity's current state and provenance head, or a delete
+/// if `filter` no longer admits it
+async fn current_put(
+    state: &AppState,
+    id: &HexadId,
+    filter: Option<&ReplicationFilter>,
+) -> Result<(ChangeOp, Option<HexadInput>, Option<String>), ApiError> {
+    if let Some(filter) = filter {
+        let hexad = state.hexad_store.get(id).await.map_err(|e| ApiError::Internal(e.to_string()))?;
+        if hexad.is_some_and(|h| !filter.admits(&h)) {
+            return Ok((ChangeOp::Delete, None, None));
+        }
+    }
+    let input = current_input(state, id).await?;
+    let head = match input {
+        Some(_) => provenance_head(state, id.as_str()).await?,
+        None => None,
+    };
+    Ok((ChangeOp::Put, input, head))
+}
+
+/// Hash of the latest record of the entity's provenance chain
+pub(crate) async fn provenance_head(state: &AppState, id: &str) -> Result<Option<String>, ApiError> {
+    state
+        .hexad_store
+        .provenance_store()
+        .get_latest(id)
+        .await
+        .map(|latest| latest.map(|r| r.content_hash))
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// The entity's current state, if it exists
+async fn current_input(state: &AppState, id: &HexadId) -> Result<Option<HexadInput>, ApiError> {
+    let status = state.hexad_store.status(id).await.map_err(|e| ApiError::Internal(e.to_string()))?;
+    match status {
+        Some(status) => state
+            .hexad_store
+            .input_at(id, status.version)
+            .await
+            .map(Some)
+            .map_err(|e| ApiError::Internal(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// The state of each entity in a read snapshot page that `filter` admits,
+/// and how many entities the page ran through
+pub async fn snapshot_entities(
+    state: &AppState,
+    snapshot: &verisim_hexad::ReadSnapshot,
+    offset: usize,
+    limit: usize,
+    filter: Option<&ReplicationFilter>,
+) -> Result<(Vec<SnapshotEntity>, usize), ApiError> {
+    let hexads = state
+        .hexad_store
+        .list_at(snapshot, None, limit, offset)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let scanned = hexads.len();
+    let mut entities = Vec::with_capacity(hexads.len());
+    for hexad in hexads {
+        if filter.is_some_and(|f| !f.admits(&hexad)) {
+            continue;
+        }
+        let input = state
+            .hexad_store
+            .input_at(&hexad.id, hexad.status.version)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        entities.push(SnapshotEntity {
+            provenance_head: provenance_head(state, hexad.id.as_str()).await?,
+            id: hexad.id.to_string(),
+            input,
+        });
+    }
+    Ok((entities, scanned))
+}
+
+/// A replica's view of its replication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    /// Endpoint of the primary
+    pub primary: String,
+    /// ID this replica reports its position under
+    pub follower_id: String,
+    /// Epoch of the primary's feed being followed
+    pub epoch: Option<String>,
+    /// Position applied
+    pub position: u64,
+    /// Primary's head when last polled
+    pub primary_head: u64,
+    /// Changes not yet applied
+    pub lag_entries: u64,
+    /// Seconds since the oldest change not yet applied was made
+    pub lag_seconds: f64,
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Changes applied since start
+    pub applied: u64,
+    /// Times the replica has bootstrapped from a snapshot
+    pub bootstraps: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct FollowerState {
+    epoch: Option<String>,
+    position: u64,
+    primary_head: u64,
+    pending_since: Option<DateTime<Utc>>,
+    last_sync: Option<DateTime<Utc>>,
+    applied: u64,
+    bootstraps: u64,
+    last_error: Option<String>,
+}
+
+/// The replica side: follows a primary's change feed
+#[derive(Debug)]
+pub struct Follower {
+    primary: String,
+    follower_id: String,
+    state: Mutex<FollowerState>,
+    /// Held while syncing, so polls do not overlap
+    syncing: tokio::sync::Mutex<()>,
+    client: OnceLock<reqwest::Client>,
+    peer_auth: PeerAuth,
+}
+
+impl Follower {
+    /// Follow the primary at `primary` (its API endpoint, with prefix)
+    pub fn new(primary: impl Into<String>, follower_id: impl Into<String>) -> Self {
+        Self {
+            primary: primary.into().trim_end_matches('/').to_string(),
+            follower_id: follower_id.into(),
+            state: Mutex::new(FollowerState::default()),
+            syncing: tokio::sync::Mutex::new(()),
+            client: OnceLock::new(),
+            peer_auth: PeerAuth::default(),
+        }
+    }
+
+    /// Authenticate to the primary as a federation peer
+    pub fn with_peer_auth(mut self, peer_auth: PeerAuth) -> Self {
+        self.peer_auth = peer_auth;
+        self
+    }
+
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FollowerState> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(PRIMARY_TIMEOUT)
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str) -> Result<reqwest::RequestBuilder, ApiError> {
+        self.peer_auth
+            .request(self.client(), method, &format!("{}{}", self.primary, path), PeerScope::Replication, PRIMARY_TIMEOUT)
+            .await
+            .map_err(|e| ApiError::Unavailable(format!("primary {}: {e}", self.primary)))
+    }
+
+    /// How far behind the primary the replica may be: the time since the
+    /// oldest change not yet applied or, when caught up, since the last
+    /// poll; `None` before the first poll
+    pub fn staleness(&self) -> Option<std::time::Duration> {
+        let state = self.lock();
+        let since = state.pending_since.or(state.last_sync)?;
+        Some((Utc::now() - since).to_std().unwrap_or_default())
+    }
+
+    /// Send a read to the primary on the caller's behalf and return its
+    /// response
+    pub async fn forward(&self, request: Request) -> Result<Response, ApiError> {
+        use axum::http::header;
+        let unavailable = |e: &dyn std::fmt::Display| ApiError::Unavailable(format!("primary {}: {e}", self.primary));
+        let (parts, body) = request.into_parts();
+        let path = parts.uri.path_and_query().map_or(parts.uri.path(), |p| p.as_str());
+        let body = axum::body::to_bytes(body, FORWARD_BODY_LIMIT)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let mut forwarded = self
+            .peer_auth
+            .client(self.client())
+            .request(parts.method, format!("{}{}", self.primary, path))
+            .timeout(PRIMARY_TIMEOUT)
+            .body(body);
+        for name in [header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT] {
+            if let Some(value) = parts.headers.get(&name) {
+                forwarded = forwarded.header(name, value);
+            }
+        }
+        for name in ["x-api-key", crate::queries::TIMEOUT_HEADER] {
+            if let Some(value) = parts.headers.get(name) {
+                forwarded = forwarded.header(name, value);
+            }
+        }
+        let response = forwarded.send().await.map_err(|e| unavailable(&e))?;
+        let status = response.status();
+        let content_type = response.headers().get(header::CONTENT_TYPE).cloned();
+        let bytes = response.bytes().await.map_err(|e| unavailable(&e))?;
+        let mut response = (status, bytes).into_response();
+        if let Some(content_type) = content_type {
+            response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+        }
+        Ok(response)
+    }
+
+    pub fn status(&self) -> ReplicaStatus {
+        let state = self.lock();
+        let lag_seconds = state
+            .pending_since
+            .map(|since| (Utc::now() - since).num_milliseconds().max(0) as f64 / 1000.0)
+            .unwrap_or(0.0);
+        ReplicaStatus {
+            primary: self.primary.clone(),
+            follower_id: self.follower_id.clone(),
+            epoch: state.epoch.clone(),
+            position: state.position,
+            primary_head: state.primary_head,
+            lag_entries: state.primary_head.saturating_sub(state.position),
+            lag_seconds,
+            last_sync: state.last_sync,
+            applied: state.applied,
+            bootstraps: state.bootstraps,
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    /// Poll the primary once and apply what it returns, bootstrapping when
+    /// needed; returns the number of changes applied
+    pub async fn sync(&self, state: &AppState) -> Result<usize, ApiError> {
+        let _syncing = self.syncing.lock().await;
+        let result = self.sync_inner(state).await;
+        let mut follower = self.lock();
+        match &result {
+            Ok(applied) => {
+                follower.applied += *applied as u64;
+                follower.last_sync = Some(Utc::now());
+                follower.last_error = None;
+            }
+            Err(e) => follower.last_error = Some(e.to_string()),
+        }
+        result
+    }
+
+    async fn sync_inner(&self, state: &AppState) -> Result<usize, ApiError> {
+        let (epoch, position) = {
+            let follower = self.lock();
+            (follower.epoch.clone(), follower.position)
+        };
+        let Some(epoch) = epoch else {
+            return self.bootstrap(state).await;
+        };
+        let batch: ChangeBatch = self
+            .fetch(
+                "/replication/changes",
+                &[
+                    ("from", position.to_string()),
+                    ("limit", MAX_CHANGE_PAGE.to_string()),
+                    ("epoch", epoch),
+                    ("follower", self.follower_id.clone()),
+                ],
+            )
+            .await?;
+        if batch.bootstrap_required {
+            info!(primary = %self.primary, position, "Replica position not retained by primary; bootstrapping");
+            return self.bootstrap(state).await;
+        }
+
+        let mut applied = 0;
+        for change in batch.changes {
+            self.apply(state, &change).await?;
+            applied += 1;
+            self.lock().position = change.position;
+        }
+        let mut follower = self.lock();
+        // Past changes the primary withheld from this replica
+        if let Some(through) = batch.through {
+            follower.position = follower.position.max(through);
+        }
+        follower.primary_head = batch.head;
+        follower.pending_since = batch.pending_since;
+        Ok(applied)
+    }
+
+    /// Apply one change from the feed
+    async fn apply(&self, state: &AppState, change: &Change) -> Result<(), ApiError> {
+        let id = HexadId::new(change.hexad_id.clone());
+        match (change.op, &change.input) {
+            (ChangeOp::Put, Some(input)) => self.put(state, &id, input.clone(), change.provenance_head.clone()).await,
+            // Deleted since; the delete follows
+            (ChangeOp::Put, None) => Ok(()),
+            (ChangeOp::Delete, _) => match state.hexad_store.delete(&id).await {
+                Ok(()) | Err(verisim_hexad::HexadError::NotFound(_)) => Ok(()),
+                Err(e) => Err(ApiError::Internal(e.to_string())),
+            },
+        }
+    }
+
+    async fn put(&self, state: &AppState, id: &HexadId, mut input: HexadInput, head: Option<String>) -> Result<(), ApiError> {
+        input.provenance = Some(HexadProvenanceInput {
+            event_type: "imported".to_string(),
+            actor: "replication".to_string(),
+            source: Some(self.primary.clone()),
+            description: format!("Replicated from {}", self.primary),
+            link: head.map(|head_hash| ChainLink {
+                instance: self.primary.clone(),
+                entity_id: id.to_string(),
+                head_hash,
+            }),
+        });
+        let hexad = state
+            .hexad_store
+            .put(id, input)
+            .await
+            .map_err(|e| ApiError::Internal(format!("replicating {id}: {e}")))?;
+        state.observe_embedding(hexad.embedding.as_ref());
+        Ok(())
+    }
+
+    /// Import a snapshot of the primary, replacing this replica's contents
+    async fn bootstrap(&self, state: &AppState) -> Result<usize, ApiError> {
+        let first: SnapshotPage = self
+            .fetch(
+                "/replication/snapshot",
+                &[("limit", MAX_SNAPSHOT_PAGE.to_string()), ("follower", self.follower_id.clone())],
+            )
+            .await?;
+        let position = first
+            .position
+            .ok_or_else(|| ApiError::Internal("primary snapshot has no feed position".to_string()))?;
+        let (epoch, snapshot) = (first.epoch.clone(), first.snapshot.clone());
+
+        let mut kept = HashSet::new();
+        let mut page = first;
+        loop {
+            for entity in page.entities {
+                let id = HexadId::new(entity.id);
+                self.put(state, &id, entity.input, entity.provenance_head).await?;
+                kept.insert(id.to_string());
+            }
+            let Some(offset) = page.next_offset else { break };
+            page = self
+                .fetch(
+                    "/replication/snapshot",
+                    &[
+                        ("snapshot", snapshot.clone()),
+                        ("offset", offset.to_string()),
+                        ("limit", MAX_SNAPSHOT_PAGE.to_string()),
+                        ("follower", self.follower_id.clone()),
+                    ],
+                )
+                .await?;
+        }
+        if let Ok(release) = self.request(reqwest::Method::DELETE, &format!("/snapshots/{snapshot}")).await {
+            let _ = release.send().await;
+        }
+
+        // Whatever the primary does not have goes
+        let mut stale = Vec::new();
+        let mut offset = 0;
+        loop {
+            let local = state
+                .hexad_store
+                .list(MAX_SNAPSHOT_PAGE, offset)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            stale.extend(local.iter().filter(|h| !kept.contains(h.id.as_str())).map(|h| h.id.clone()));
+            if local.len() < MAX_SNAPSHOT_PAGE {
+                break;
+            }
+            offset += local.len();
+        }
+        for id in &stale {
+            if let Err(e) = state.hexad_store.delete(id).await {
+                warn!(id = %id, error = %e, "Could not remove entity the primary does not have");
+            }
+        }
+
+        let mut follower = self.lock();
+        follower.epoch = Some(epoch);
+        follower.position = position;
+        follower.primary_head = position;
+        follower.pending_since = None;
+        follower.bootstraps += 1;
+        info!(primary = %self.primary, entities = kept.len(), removed = stale.len(), position, "Replica bootstrapped");
+        Ok(kept.len())
+    }
+
+    async fn fetch<T: serde::de::DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T, ApiError> {
+        let response = self
+            .request(reqwest::Method::GET, path)
+            .await?
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ApiError::Unavailable(format!("primary {}: {e}", self.primary)))?;
+        if !response.status().is_success() {
+            return Err(ApiError::Unavailable(format!(
+                "primary {} returned {}",
+                self.primary,
+                response.status()
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| ApiError::Internal(format!("primary {} sent an unreadable response: {e}", self.primary)))
+    }
+}
+
+/// This instance's part in replication
+#[derive(Debug, Clone, Default)]
+pub struct Replication {
+    /// Feed of this instance's writes, which replicas of it follow
+    pub log: ChangeLog,
+    /// Set when this instance is a replica
+    pub follower: Option<Arc<Follower>>,
+    /// What replicas of this instance are sent
+    pub rules: ReplicationRules,
+}
+
+impl Replication {
+    /// Refuse a write if this instance is a replica
+    pub fn check_writable(&self) -> Result<(), ApiError> {
+        match &self.follower {
+            Some(follower) => Err(ApiError::Unavailable(format!(
+                "This instance is a read replica of {}; send writes there",
+                follower.primary()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether a request writes entities, which a replica refuses
+fn is_write(method: &axum::http::Method, path: &str) -> bool {
+    use axum::http::Method;
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+    path.starts_with("/hexads") || path.starts_with("/transactions") || path == "/spatial/import" || path == "/wal/recover"
+}
+
+/// Middleware refusing writes on a replica
+pub async fn read_only_middleware(State(replication): State<Replication>, request: Request, next: Next) -> Response {
+    if is_write(request.method(), request.uri().path()) {
+        if let Err(e) = replication.check_writable() {
+            return e.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Whether a request is a read a replica may forward to its primary
+fn is_routable_read(method: &axum::http::Method, path: &str) -> bool {
+    use axum::http::Method;
+    match *method {
+        Method::GET => path.starts_with("/hexads") || path.starts_with("/search/"),
+        Method::POST => path.starts_with("/search/"),
+        _ => false,
+    }
+}
+
+/// Middleware serving reads on a replica within the staleness the request
+/// accepts, and forwarding them to the primary otherwise
+pub async fn read_routing_middleware(State(replication): State<Replication>, request: Request, next: Next) -> Response {
+    let Some(follower) = replication.follower.clone() else {
+        return next.run(request).await;
+    };
+    if !is_routable_read(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+    let bound = match request.headers().get(MAX_STALENESS_HEADER) {
+        Some(value) => match value.to_str().ok().and_then(crate::queries::parse_timeout) {
+            Some(bound) => Some(bound),
+            None => return ApiError::BadRequest(format!("Invalid {} header", MAX_STALENESS_HEADER)).into_response(),
+        },
+        None => None,
+    };
+    let staleness = follower.staleness();
+    let local = match (bound, staleness) {
+        (None, _) => true,
+        (Some(bound), Some(staleness)) => staleness <= bound,
+        (Some(_), None) => false,
+    };
+    // A client certificate authenticates the connection to this replica,
+    // and cannot be presented to the primary
+    let certified = request
+        .extensions()
+        .get::<crate::auth::ClientIdentity>()
+        .is_some_and(|identity| identity.credential == crate::auth::Credential::Certificate);
+    let (mut response, served_by) = if local {
+        (next.run(request).await, follower.follower_id.clone())
+    } else if certified {
+        return ApiError::Unavailable(format!(
+            "Replica may be staler than {} allows, and reads authenticated by client certificate are not forwarded to the primary",
+            MAX_STALENESS_HEADER
+        ))
+        .into_response();
+    } else {
+        match follower.forward(request).await {
+            Ok(response) => (response, follower.primary.clone()),
+            Err(e) => return e.into_response(),
+        }
+    };
+    let headers = response.headers_mut();
+    if let Ok(value) = served_by.parse() {
+        headers.insert(SERVED_BY_HEADER, value);
+    }
+    if let Some(staleness) = staleness.filter(|_| local) {
+        headers.insert(STALENESS_HEADER, (staleness.as_millis() as u64).into());
+    }
+    response
+}
+
+/// Follow the primary in the background, polling every `interval`
+pub fn spawn_follower(state: AppState, interval: std::time::Duration) {
+    let Some(follower) = state.replication.follower.clone() else { return };
+    tokio::spawn(verisim_hexad::security::system(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            // Drain a backlog without waiting between pages
+            loop {
+                match follower.sync(&state).await {
+                    Ok(_) if follower.status().lag_entries > 0 => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        warn!(primary = %follower.primary(), error = %e, "Replication sync failed");
+                        break;
+                    }
+                }
+            }
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_log_serves_retained_positions() {
+        let log = ChangeLog::with_capacity(3);
+        for id in ["a", "b", "c", "d"] {
+            log.record(id, ChangeOp::Put);
+        }
+        assert_eq!(log.head(), 4);
+
+        let (records, pending_since) = log.since(1, 2).unwrap();
+        let ids: Vec<&str> = records.iter().map(|r| r.hexad_id.as_str()).collect();
+        assert_eq!(ids, ["b", "c"]);
+        assert!(pending_since.is_some());
+        let (records, pending_since) = log.since(3, 10).unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(pending_since.is_none());
+        assert!(log.since(4, 10).unwrap().0.is_empty());
+
+        // Position 1's successor was trimmed, and 5 is past the head
+        assert!(log.since(0, 10).is_none());
+        assert!(log.since(5, 10).is_none());
+
+        log.acknowledge("replica-1", 2);
+        assert_eq!(log.followers()[0].lag_entries, 2);
+    }
+
+    #[test]
+    fn test_replicas_refuse_writes() {
+        use axum::http::Method;
+        assert!(is_write(&Method::POST, "/hexads"));
+        assert!(is_write(&Method::DELETE, "/hexads/h-1"));
+        assert!(is_write(&Method::POST, "/transactions/begin"));
+        assert!(!is_write(&Method::GET, "/hexads/h-1"));
+        assert!(!is_write(&Method::POST, "/search/vector"));
+
+        let primary = Replication::default();
+        assert!(primary.check_writable().is_ok());
+        let replica = Replication {
+            follower: Some(Arc::new(Follower::new("http://primary:8080/", "replica-1"))),
+            ..Default::default()
+        };
+        assert!(matches!(replica.check_writable(), Err(ApiError::Unavailable(_))));
+        assert_eq!(replica.follower.unwrap().primary(), "http://primary:8080");
+    }
+
+    #[test]
+    fn test_replication_rules_choose_filter_by_follower() {
+        let rule = |follower: &str, collections: &[&str], filter: Option<&str>| ReplicationRule {
+            follower: follower.to_string(),
+            collections: collections.iter().map(|c| c.to_string()).collect(),
+            filter: filter.map(String::from),
+        };
+        let rules = ReplicationRules::new(vec![
+            rule("eu-replica", &["eu"], None),
+            rule("*", &[], Some("WHERE title = 'public'")),
+        ])
+        .unwrap();
+        let eu = rules.for_follower(Some("eu-replica")).unwrap();
+        assert!(eu.admits_id(&HexadId::in_collection("eu", "h-1")));
+        assert!(!eu.admits_id(&HexadId::in_collection("us", "h-1")));
+        assert!(!eu.admits_id(&HexadId::new("h-1")));
+        let id = HexadId::in_collection("eu", "h-1");
+        let hexad = Hexad {
+            id: id.clone(),
+            status: verisim_hexad::HexadStatus {
+                id: id.clone(),
+                created_at: Utc::now(),
+                modified_at: Utc::now(),
+                version: 1,
+                modality_status: Default::default(),
+                expires_at: None,
+            },
+            graph_node: None,
+            embedding: None,
+            tensor: None,
+            semantic: None,
+            document: Some(verisim_document::Document::new(id.as_str(), "private", "")),
+            version_count: 1,
+            provenance_chain_length: 0,
+            spatial_data: None,
+        };
+        assert!(eu.admits(&hexad));
+
+        // Unnamed followers fall back to the `*` rule
+        let other = rules.for_follower(Some("us-replica")).unwrap();
+        assert!(other.admits_id(&HexadId::new("h-1")));
+        assert!(!other.admits(&hexad));
+        assert!(rules.for_follower(None).is_some());
+        assert!(ReplicationRules::default().for_follower(Some("eu-replica")).is_none());
+
+        assert!(ReplicationRules::new(vec![rule("bad", &[], Some("ORDER BY title"))]).is_err());
+    }
+}
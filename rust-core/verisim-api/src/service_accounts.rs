@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Service accounts
+//!
+//! Pipelines, sync jobs and other services authenticate as service
+//! accounts rather than with ad hoc API keys.  An account has a role and
+//! any number of keys, all presented in the `X-API-Key` header; every key
+//! of an account authenticates as the same principal, so its writes are
+//! attributed to one actor (`.../actor/service_account/{name}`) and rate
+//! limited together, however often its keys change.
+//!
+//! Keys can be given a lifetime, after which they stop working.  Rotation
+//! issues a new key and lets the old ones overlap it for a grace period, so
+//! a service can roll over without downtime.  Keys nearing expiry are
+//! reported in `/metrics` so rotations are not missed.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::auth::{hash_key, ClientRole};
+
+/// Longest key lifetime or rotation overlap, in seconds: 100 years
+pub const MAX_KEY_SECS: u64 = 100 * 365 * 86_400;
+
+/// Service account errors
+#[derive(Debug, Error)]
+pub enum ServiceAccountError {
+    #[error("Service account {0} not found")]
+    NotFound(String),
+
+    #[error("Service account {0} already exists")]
+    Exists(String),
+
+    #[error("Key {key} of service account {account} not found")]
+    KeyNotFound { account: String, key: String },
+
+    #[error("Invalid service account: {0}")]
+    Invalid(String),
+}
+
+/// A non-human client and its keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceAccount {
+    /// Account name, the principal its keys authenticate as
+    pub name: String,
+    /// Role granted to every key of the account
+    pub role: ClientRole,
+    /// What the account is for
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Seconds each new key stays valid; none issues keys that never expire
+    #[serde(default)]
+    pub key_lifetime_secs: Option<u64>,
+    /// When the account was created
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// Keys issued to the account, including expired and revoked ones
+    #[serde(default)]
+    pub keys: Vec<ServiceKey>,
+}
+
+/// One key of a service account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceKey {
+    /// Public identifier of the key, safe to log
+    pub id: String,
+    /// SHA-256 hash of the key (the plaintext is never stored); given in
+    /// configuration, never returned
+    #[serde(default, skip_serializing)]
+    pub key_hash: String,
+    /// When the key was issued
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// When the key stops working
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When the key was revoked
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// When the key last authenticated a request
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ServiceKey {
+    /// Whether the key authenticates requests at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
+/// A newly issued key, the only time its plaintext is shown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedKey {
+    /// Account the key belongs to
+    pub account: String,
+    /// Public identifier of the key
+    pub key_id: String,
+    /// The key itself, to be sent in `X-API-Key`
+    pub key: String,
+    /// When the key stops working
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Keys of one account by state, for `/metrics`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyCounts {
+    /// Active keys not yet in their warning window
+    pub active: usize,
+    /// Active keys that expire within the warning window
+    pub expiring: usize,
+    /// Keys past their expiry
+    pub expired: usize,
+    /// Revoked keys
+    pub revoked: usize,
+}
+
+/// Registry of service accounts, shared by the auth middleware and the
+/// admin endpoints
+#[derive(Debug, Clone, Default)]
+pub struct ServiceAccountRegistry {
+    accounts: Arc<Mutex<HashMap<String, ServiceAccount>>>,
+}
+
+impl ServiceAccountRegistry {
+    /// A registry holding `accounts`, as configured
+    pub fn new(accounts: Vec<ServiceAccount>) -> Self {
+        Self {
+            accounts: Arc::new(Mutex::new(accounts.into_iter().map(|a| (a.name.clone(), a)).collect())),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ServiceAccount>> {
+        self.accounts.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Create an account with one key
+    pub fn create(
+        &self,
+        name: &str,
+        role: ClientRole,
+        description: Option<String>,
+        key_lifetime_secs: Option<u64>,
+    ) -> Result<(ServiceAccount, IssuedKey), ServiceAccountError> {
+        validate_name(name)?;
+        let mut accounts = self.lock();
+        if accounts.contains_key(name) {
+            return Err(ServiceAccountError::Exists(name.to_string()));
+        }
+        let mut account = ServiceAccount {
+            name: name.to_string(),
+            role,
+            description,
+            key_lifetime_secs,
+            created_at: Utc::now(),
+            keys: Vec::new(),
+        };
+        let issued = issue(&mut account)?;
+        accounts.insert(name.to_string(), account.clone());
+        Ok((account, issued))
+    }
+
+    /// All accounts, by name
+    pub fn list(&self) -> Vec<ServiceAccount> {
+        let mut accounts: Vec<ServiceAccount> = self.lock().values().cloned().collect();
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        accounts
+    }
+
+    /// One account
+    pub fn get(&self, name: &str) -> Option<ServiceAccount> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Delete an account and with it all its keys
+    pub fn remove(&self, name: &str) -> bool {
+        self.lock().remove(name).is_some()
+    }
+
+    /// Issue another key, leaving the account's other keys active
+    pub fn issue_key(&self, name: &str) -> Result<IssuedKey, ServiceAccountError> {
+        let mut accounts = self.lock();
+        let account = accounts
+            .get_mut(name)
+            .ok_or_else(|| ServiceAccountError::NotFound(name.to_string()))?;
+        issue(account)
+    }
+
+    /// Issue a new key and expire the account's other active keys once
+    /// `overlap_secs` have passed
+    pub fn rotate(&self, name: &str, overlap_secs: u64) -> Result<IssuedKey, ServiceAccountError> {
+        let mut accounts = self.lock();
+        let account = accounts
+            .get_mut(name)
+            .ok_or_else(|| ServiceAccountError::NotFound(name.to_string()))?;
+        let now = Utc::now();
+        let retire_at = after(now, overlap_secs, "overlap")?;
+        let issued = issue(account)?;
+        for key in account.keys.iter_mut().filter(|k| k.is_active(now) && k.id != issued.key_id) {
+            key.expires_at = Some(key.expires_at.map_or(retire_at, |at| at.min(retire_at)));
+        }
+        Ok(issued)
+    }
+
+    /// Revoke one key of an account at once
+    pub fn revoke_key(&self, name: &str, key_id: &str) -> Result<(), ServiceAccountError> {
+        let mut accounts = self.lock();
+        let account = accounts
+            .get_mut(name)
+            .ok_or_else(|| ServiceAccountError::NotFound(name.to_string()))?;
+        let key = account
+            .keys
+            .iter_mut()
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| ServiceAccountError::KeyNotFound {
+                account: name.to_string(),
+                key: key_id.to_string(),
+            })?;
+        key.revoked_at.get_or_insert_with(Utc::now);
+        Ok(())
+    }
+
+    /// Whether key `key_id` of account `name` authenticates requests at `now`
+    pub fn key_active(&self, name: &str, key_id: &str, now: DateTime<Utc>) -> bool {
+        self.lock()
+            .get(name)
+            .and_then(|account| account.keys.iter().find(|k| k.id == key_id))
+            .is_some_and(|key| key.is_active(now))
+    }
+
+    /// When key `key_id` of account `name` expires, if it does
+    pub fn key_expires_at(&self, name: &str, key_id: &str) -> Option<DateTime<Utc>> {
+        self.lock()
+            .get(name)
+            .and_then(|account| account.keys.iter().find(|k| k.id == key_id))
+            .and_then(|key| key.expires_at)
+    }
+
+    /// The account an active key belongs to and the key's ID, recording
+    /// the key's use
+    pub fn authenticate(&self, plaintext_key: &str) -> Option<(ServiceAccount, String)> {
+        let hash = hash_key(plaintext_key);
+        let now = Utc::now();
+        let mut accounts = self.lock();
+        accounts.values_mut().find_map(|account| {
+            let key = account.keys.iter_mut().find(|k| k.key_hash == hash && k.is_active(now))?;
+            key.last_used_at = Some(now);
+            let key_id = key.id.clone();
+            Some((account.clone(), key_id))
+        })
+    }
+
+    /// Keys of each account by state at `now`, counting those that expire
+    /// within `warning` as expiring
+    pub fn key_counts(&self, now: DateTime<Utc>, warning: Duration) -> Vec<(String, KeyCounts)> {
+        let horizon = now.checked_add_signed(warning);
+        self.list()
+            .into_iter()
+            .map(|account| {
+                let mut counts = KeyCounts::default();
+                for key in &account.keys {
+                    if key.revoked_at.is_some() {
+                        counts.revoked += 1;
+                    } else if !key.is_active(now) {
+                        counts.expired += 1;
+                    } else if key.expires_at.is_some_and(|at| horizon.is_none_or(|h| at <= h)) {
+                        counts.expiring += 1;
+                    } else {
+                        counts.active += 1;
+                    }
+                }
+                (account.name, counts)
+            })
+            .collect()
+    }
+}
+
+/// Add a fresh key to `account`
+fn issue(account: &mut ServiceAccount) -> Result<IssuedKey, ServiceAccountError> {
+    let now = Utc::now();
+    let key_id = format!("sk_{}", random_hex(6));
+    let key = format!("vsa_{}_{}", account.name, random_hex(24));
+    let expires_at = account
+        .key_lifetime_secs
+        .map(|secs| after(now, secs, "key_lifetime_secs"))
+        .transpose()?;
+    account.keys.push(ServiceKey {
+        id: key_id.clone(),
+        key_hash: hash_key(&key),
+        created_at: now,
+        expires_at,
+        revoked_at: None,
+        last_used_at: None,
+    });
+    Ok(IssuedKey {
+        account: account.name.clone(),
+        key_id,
+        key,
+        expires_at,
+    })
+}
+
+/// The time `secs` seconds after `now`, refusing `what` longer than
+/// [`MAX_KEY_SECS`]
+fn after(now: DateTime<Utc>, secs: u64, what: &str) -> Result<DateTime<Utc>, ServiceAccountError> {
+    i64::try_from(secs)
+        .ok()
+        .filter(|_| secs <= MAX_KEY_SECS)
+        .and_then(Duration::try_seconds)
+        .and_then(|delta| now.checked_add_signed(delta))
+        .ok_or_else(|| ServiceAccountError::Invalid(format!("{what} may be at most {MAX_KEY_SECS} seconds")))
+}
+
+/// `len` random bytes, hex-encoded
+fn random_hex(len: usize) -> String {
+    use ring::rand::SecureRandom;
+    let mut bytes = vec![0u8; len];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator");
+    hex::encode(bytes)
+}
+
+/// Account names appear in actor IRIs and keys: 1 to 64 ASCII
+/// alphanumerics, dashes and underscores
+fn validate_name(name: &str) -> Result<(), ServiceAccountError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ServiceAccountError::Invalid(format!(
+            "name '{}' must be 1 to 64 ASCII letters, digits, dashes or underscores",
+            name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_authenticate_as_their_account_until_revoked() {
+        let registry = ServiceAccountRegistry::default();
+        let (account, first) = registry.create("ingest", ClientRole::Writer, None, None).unwrap();
+        assert_eq!(account.keys.len(), 1);
+        assert!(matches!(
+            registry.create("ingest", ClientRole::Reader, None, None),
+            Err(ServiceAccountError::Exists(_))
+        ));
+        assert!(matches!(
+            registry.create("bad name", ClientRole::Reader, None, None),
+            Err(ServiceAccountError::Invalid(_))
+        ));
+
+        let second = registry.issue_key("ingest").unwrap();
+        let (found, key_id) = registry.authenticate(&first.key).unwrap();
+        assert_eq!((found.name.as_str(), key_id), ("ingest", first.key_id.clone()));
+        assert_eq!(registry.authenticate(&second.key).unwrap().1, second.key_id);
+        assert!(registry.get("ingest").unwrap().keys[0].last_used_at.is_some());
+
+        registry.revoke_key("ingest", &first.key_id).unwrap();
+        assert!(registry.authenticate(&first.key).is_none());
+        assert!(registry.authenticate(&second.key).is_some());
+        assert!(registry.authenticate("vsa_ingest_guess").is_none());
+    }
+
+    #[test]
+    fn test_rotation_overlaps_then_retires_old_keys() {
+        let registry = ServiceAccountRegistry::default();
+        let (_, old) = registry.create("sync", ClientRole::Writer, None, Some(86_400)).unwrap();
+        assert!(old.expires_at.is_some());
+
+        // With an overlap the old key keeps working for now
+        let new = registry.rotate("sync", 3600).unwrap();
+        assert!(registry.authenticate(&old.key).is_some());
+        assert!(registry.authenticate(&new.key).is_some());
+        let counts = registry.key_counts(Utc::now(), Duration::hours(2));
+        assert_eq!(counts[0].1, KeyCounts { expiring: 1, active: 1, ..Default::default() });
+
+        // Without one the previous keys stop at once
+        let newest = registry.rotate("sync", 0).unwrap();
+        assert!(registry.authenticate(&old.key).is_none());
+        assert!(registry.authenticate(&new.key).is_none());
+        assert!(registry.authenticate(&newest.key).is_some());
+        let counts = registry.key_counts(Utc::now(), Duration::zero());
+        assert_eq!(counts[0].1, KeyCounts { expired: 2, active: 1, ..Default::default() });
+
+        // Lifetimes and overlaps beyond the maximum are refused, not added
+        let refused = |result| matches!(result, Err(ServiceAccountError::Invalid(_)));
+        assert!(refused(registry.rotate("sync", u64::MAX)));
+        assert!(refused(registry.create("forever", ClientRole::Reader, None, Some(u64::MAX)).map(|(_, key)| key)));
+        assert!(registry.get("forever").is_none());
+        assert_eq!(registry.get("sync").unwrap().keys.len(), 3);
+        assert_eq!(registry.key_counts(Utc::now(), Duration::MAX)[0].1.expiring, 1);
+    }
+}
@@ -0,0 +1,464 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Sessions
+//!
+//! Long-lived UIs should not hold static API keys.  A client authenticated
+//! by any credential exchanges it at `POST /auth/token` for a session: a
+//! short-lived access token (an HS256 JWT signed with the `jwt_secret`,
+//! carrying the client's identity, as the kind of credential it started
+//! with and its id, role and attributes) and a refresh token.  Access
+//! tokens therefore act as the very principal that started the session.
+//! A session's own access token cannot start another session, which would
+//! outlive it.
+//!
+//! `POST /auth/refresh` trades the refresh token for a new pair; each
+//! refresh token is good for one use, and presenting one that was already
+//! used revokes the whole session, since it was copied.  Refreshing never
+//! extends a session past its deadline, fixed when it starts: the longest
+//! lifetime configured, or sooner if the credential it was started with
+//! expires sooner.  Access tokens carry the deadline as `session_exp`, and
+//! no token of the session is valid after it.  The credential the
+//! session was started with is checked again on every refresh: once that
+//! API key or service account key is revoked, removed or expired, the
+//! session is revoked too.  `POST /auth/revoke` ends a session given either
+//! of its tokens.
+//!
+//! Revoked sessions are remembered until their last access token expires,
+//! and the auth middleware refuses access tokens of a revoked session, or
+//! of one it does not know.  Sessions are held in memory, so a restart
+//! signs every UI out, and an instance honours only the sessions it
+//! started.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use verisim_provenance::PrincipalKind;
+
+use crate::auth::{base64url_decode, hash_key, sign_jwt, verify_jwt, ClientIdentity, ClientRole, Credential};
+
+/// Longest token lifetime honoured, whatever is configured
+const MAX_TTL_SECS: u64 = 10 * 365 * 24 * 3600;
+
+/// Session errors
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Token issuance needs a jwt_secret")]
+    Unconfigured,
+
+    #[error("Invalid or expired refresh token")]
+    InvalidToken,
+
+    #[error("Refresh token was already used; session {0} revoked")]
+    Reused(String),
+
+    #[error("The credential session {0} was started with is no longer valid; session revoked")]
+    CredentialRevoked(String),
+
+    #[error("Sessions cannot be started with a session token; refresh it instead")]
+    FromSession,
+}
+
+/// Lifetimes of sessions and their tokens, each capped at ten years
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Seconds an access token is valid
+    pub access_ttl_secs: u64,
+    /// Seconds a refresh token is valid; each refresh issues a new one
+    pub refresh_ttl_secs: u64,
+    /// Seconds a session lasts however often it is refreshed
+    pub max_lifetime_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            access_ttl_secs: 15 * 60,
+            refresh_ttl_secs: 14 * 24 * 3600,
+            max_lifetime_secs: 30 * 24 * 3600,
+        }
+    }
+}
+
+/// Tokens of a session, the only time they are shown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub session_id: String,
+    /// Sent as `Authorization: Bearer <access_token>`
+    pub access_token: String,
+    pub token_type: String,
+    /// Seconds until the access token expires
+    pub expires_in: u64,
+    /// Traded at `/auth/refresh` for a new pair
+    pub refresh_token: String,
+    /// Seconds until the refresh token expires
+    pub refresh_expires_in: u64,
+}
+
+/// One session and the principal its tokens act as
+#[derive(Debug, Clone)]
+struct Session {
+    /// Identity of the credential the session was started with
+    subject: String,
+    kind: PrincipalKind,
+    display_name: Option<String>,
+    role: ClientRole,
+    attributes: HashMap<String, String>,
+    /// The credential itself, checked again on every refresh
+    credential: Credential,
+    /// Hash of the refresh token that may be used next
+    refresh_hash: String,
+    refresh_expires_at: DateTime<Utc>,
+    /// When the last access token issued expires
+    access_expires_at: DateTime<Utc>,
+    /// When the session ends, however often it is refreshed
+    expires_at: DateTime<Utc>,
+    revoked: bool,
+}
+
+#[derive(Debug, Default)]
+struct SessionTable {
+    sessions: HashMap<String, Session>,
+    /// Every refresh token hash issued, current or used, to its session
+    refresh_tokens: HashMap<String, String>,
+}
+
+impl SessionTable {
+    /// Forget sessions none of whose tokens can be used any more
+    fn prune(&mut self, now: DateTime<Utc>) {
+        self.sessions.retain(|_, s| {
+            let refreshable = !s.revoked && s.refresh_expires_at > now;
+            refreshable || s.access_expires_at > now
+        });
+        let sessions = &self.sessions;
+        self.refresh_tokens.retain(|_, id| sessions.contains_key(id));
+    }
+}
+
+/// Sessions issued by this server, shared by the auth middleware and the
+/// `/auth` endpoints
+#[derive(Debug, Clone, Default)]
+pub struct Sessions {
+    config: SessionConfig,
+    secret: Option<String>,
+    table: Arc<Mutex<SessionTable>>,
+}
+
+impl Sessions {
+    /// Sessions whose access tokens are signed with `secret`
+    pub fn new(config: SessionConfig, secret: Option<String>) -> Self {
+        Self {
+            config,
+            secret,
+            table: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SessionTable> {
+        self.table.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Start a session for `identity`, ending no later than
+    /// `credential_expires_at`, when its credential expires
+    pub fn issue(
+        &self,
+        identity: &ClientIdentity,
+        credential_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<TokenPair, SessionError> {
+        let secret = self.secret.as_deref().ok_or(SessionError::Unconfigured)?;
+        if matches!(identity.credential, Credential::Session(_)) {
+            return Err(SessionError::FromSession);
+        }
+        let now = Utc::now();
+        let lifetime = now + Duration::seconds(ttl(self.config.max_lifetime_secs) as i64);
+        let expires_at = credential_expires_at.map_or(lifetime, |at| at.min(lifetime));
+        if expires_at <= now {
+            return Err(SessionError::InvalidToken);
+        }
+        let session_id = format!("ses_{}", random_hex(12));
+        let mut session = Session {
+            subject: identity.id.clone(),
+            kind: identity.kind,
+            display_name: identity.display_name.clone(),
+            role: identity.role,
+            attributes: identity.attributes.clone(),
+            credential: identity.credential.clone(),
+            refresh_hash: String::new(),
+            refresh_expires_at: now,
+            access_expires_at: now,
+            expires_at,
+            revoked: false,
+        };
+        let pair = self.tokens(&session_id, &mut session, secret, now);
+        let mut table = self.lock();
+        table.prune(now);
+        table.refresh_tokens.insert(session.refresh_hash.clone(), session_id.clone());
+        table.sessions.insert(session_id, session);
+        Ok(pair)
+    }
+
+    /// Trade `refresh_token` for a new pair, retiring it, while `valid`
+    /// holds for the credential the session was started with
+    pub fn refresh(
+        &self,
+        refresh_token: &str,
+        valid: impl Fn(&Credential) -> bool,
+    ) -> Result<TokenPair, SessionError> {
+        let secret = self.secret.as_deref().ok_or(SessionError::Unconfigured)?;
+        let now = Utc::now();
+        let hash = hash_key(refresh_token);
+        let mut table = self.lock();
+        table.prune(now);
+        let session_id = table.refresh_tokens.get(&hash).cloned().ok_or(SessionError::InvalidToken)?;
+        let session = table.sessions.get_mut(&session_id).ok_or(SessionError::InvalidToken)?;
+        if session.revoked || session.refresh_expires_at <= now {
+            return Err(SessionError::InvalidToken);
+        }
+        if session.refresh_hash != hash {
+            session.revoked = true;
+            tracing::warn!(session = %session_id, subject = %session.subject, "Refresh token reused; session revoked");
+            return Err(SessionError::Reused(session_id));
+        }
+        if !valid(&session.credential) {
+            session.revoked = true;
+            tracing::warn!(session = %session_id, subject = %session.subject, "Session credential no longer valid; session revoked");
+            return Err(SessionError::CredentialRevoked(session_id));
+        }
+        let pair = self.tokens(&session_id, session, secret, now);
+        let refresh_hash = session.refresh_hash.clone();
+        table.refresh_tokens.insert(refresh_hash, session_id);
+        Ok(pair)
+    }
+
+    /// End the session `token` (a refresh or access token) belongs to;
+    /// false when it belongs to none
+    pub fn revoke(&self, token: &str) -> bool {
+        let mut table = self.lock();
+        let session_id = match table.refresh_tokens.get(&hash_key(token)) {
+            Some(id) => Some(id.clone()),
+            None => self
+                .secret
+                .as_deref()
+                .and_then(|secret| verify_jwt(token, secret).ok())
+                .and_then(|_| session_of(token)),
+        };
+        match session_id.and_then(|id| table.sessions.get_mut(&id)) {
+            Some(session) => {
+                session.revoked = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// End every session started with a credential `revoked` holds for;
+    /// returns how many were ended
+    pub fn revoke_credential(&self, revoked: impl Fn(&Credential) -> bool) -> usize {
+        let mut table = self.lock();
+        let mut ended = 0;
+        for session in table.sessions.values_mut().filter(|s| !s.revoked && revoked(&s.credential)) {
+            session.revoked = true;
+            ended += 1;
+        }
+        ended
+    }
+
+    /// Whether access token `token` belongs to a revoked session, or to one
+    /// this server does not know: started before a restart, by another
+    /// instance, or forgotten
+    pub fn revoked(&self, token: &str) -> bool {
+        let Some(session_id) = session_of(token) else {
+            return false;
+        };
+        self.lock().sessions.get(&session_id).is_none_or(|s| s.revoked)
+    }
+
+    /// Issue an access token and a new refresh token for `session`, neither
+    /// valid past the session's deadline
+    fn tokens(&self, session_id: &str, session: &mut Session, secret: &str, now: DateTime<Utc>) -> TokenPair {
+        let until = |secs: u64| (now + Duration::seconds(ttl(secs) as i64)).min(session.expires_at);
+        let access_expires_at = until(self.config.access_ttl_secs);
+        let refresh_expires_at = until(self.config.refresh_ttl_secs);
+        let claims = serde_json::json!({
+            "sub": session.subject,
+            "kind": session.kind,
+            "name": session.display_name,
+            "role": format!("{:?}", session.role).to_lowercase(),
+            "attributes": session.attributes,
+            "sid": session_id,
+            "iat": now.timestamp(),
+            "exp": access_expires_at.timestamp(),
+            "session_exp": session.expires_at.timestamp(),
+        });
+        let refresh_token = format!("vsr_{}", random_hex(32));
+        session.refresh_hash = hash_key(&refresh_token);
+        session.refresh_expires_at = refresh_expires_at;
+        session.access_expires_at = access_expires_at;
+        let secs_until = |at: DateTime<Utc>| (at - now).num_seconds().max(0) as u64;
+        TokenPair {
+            session_id: session_id.to_string(),
+            access_token: sign_jwt(&claims, secret),
+            token_type: "Bearer".to_string(),
+            expires_in: secs_until(access_expires_at),
+            refresh_token,
+            refresh_expires_in: secs_until(refresh_expires_at),
+        }
+    }
+}
+
+/// The session an access token names in its `sid` claim
+fn session_of(token: &str) -> Option<String> {
+    let payload = base64url_decode(token.split('.').nth(1)?).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    claims.get("sid")?.as_str().map(str::to_string)
+}
+
+/// A configured lifetime, capped at [`MAX_TTL_SECS`]
+fn ttl(secs: u64) -> u64 {
+    secs.min(MAX_TTL_SECS)
+}
+
+fn random_hex(len: usize) -> String {
+    use ring::rand::SecureRandom;
+    let mut bytes = vec![0u8; len];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator");
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{validate_jwt, AuthConfig};
+    use verisim_provenance::PrincipalKind;
+
+    fn identity() -> ClientIdentity {
+        ClientIdentity {
+            id: hash_key("ui-key"),
+            role: ClientRole::Writer,
+            kind: PrincipalKind::ApiKey,
+            display_name: Some("dana".to_string()),
+            attributes: HashMap::from([("position".to_string(), "phd_student".to_string())]),
+            credential: Credential::ApiKey(hash_key("ui-key")),
+        }
+    }
+
+    fn jwt_config() -> AuthConfig {
+        AuthConfig {
+            jwt_secret: Some("session-secret".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_access_tokens_act_as_the_issuing_principal() {
+        let sessions = Sessions::new(SessionConfig::default(), Some("session-secret".to_string()));
+        let pair = sessions.issue(&identity(), None).unwrap();
+        let token = validate_jwt(&pair.access_token, &jwt_config()).unwrap();
+        assert_eq!(token.id, identity().id);
+        assert_eq!(token.kind, PrincipalKind::ApiKey);
+        assert_eq!(token.principal_id(), "api_key:dana");
+        assert_eq!(token.role, ClientRole::Writer);
+        assert_eq!(token.attributes["position"], "phd_student");
+        assert!(!sessions.revoked(&pair.access_token));
+
+        assert!(matches!(
+            Sessions::default().issue(&identity(), None),
+            Err(SessionError::Unconfigured)
+        ));
+    }
+
+    #[test]
+    fn test_refresh_rotates_and_reuse_revokes_the_session() {
+        let sessions = Sessions::new(SessionConfig::default(), Some("session-secret".to_string()));
+        let first = sessions.issue(&identity(), None).unwrap();
+        let second = sessions.refresh(&first.refresh_token, |_| true).unwrap();
+        assert_eq!(second.session_id, first.session_id);
+        assert_ne!(second.refresh_token, first.refresh_token);
+
+        // The first refresh token is spent; replaying it ends the session
+        assert!(matches!(sessions.refresh(&first.refresh_token, |_| true), Err(SessionError::Reused(_))));
+        assert!(sessions.revoked(&second.access_token));
+        assert!(matches!(sessions.refresh(&second.refresh_token, |_| true), Err(SessionError::InvalidToken)));
+        assert!(matches!(sessions.refresh("vsr_unknown", |_| true), Err(SessionError::InvalidToken)));
+    }
+
+    #[test]
+    fn test_sessions_stay_answerable_to_their_credential() {
+        let sessions = Sessions::new(
+            SessionConfig {
+                access_ttl_secs: u64::MAX,
+                refresh_ttl_secs: u64::MAX,
+                max_lifetime_secs: u64::MAX,
+            },
+            Some("session-secret".to_string()),
+        );
+        let pair = sessions.issue(&identity(), None).unwrap();
+        assert_eq!((pair.expires_in, pair.refresh_expires_in), (MAX_TTL_SECS, MAX_TTL_SECS));
+
+        // Not from a session's own token
+        let chained = ClientIdentity {
+            credential: Credential::Session(pair.session_id.clone()),
+            ..identity()
+        };
+        assert!(matches!(sessions.issue(&chained, None), Err(SessionError::FromSession)));
+
+        // A credential no longer valid revokes the session on refresh
+        let ui_key = Credential::ApiKey(hash_key("ui-key"));
+        assert!(matches!(
+            sessions.refresh(&pair.refresh_token, |c| *c != ui_key),
+            Err(SessionError::CredentialRevoked(_))
+        ));
+        assert!(sessions.revoked(&pair.access_token));
+
+        let other = sessions.issue(&identity(), None).unwrap();
+        assert_eq!(sessions.revoke_credential(|c| *c == ui_key), 1);
+        assert!(sessions.revoked(&other.access_token));
+    }
+
+    #[test]
+    fn test_sessions_end_by_their_deadline() {
+        let sessions = Sessions::new(SessionConfig::default(), Some("session-secret".to_string()));
+        let now = Utc::now();
+        let pair = sessions.issue(&identity(), Some(now + Duration::seconds(60))).unwrap();
+        assert!(pair.expires_in <= 60 && pair.refresh_expires_in <= 60);
+        let payload = base64url_decode(pair.access_token.split('.').nth(1).unwrap()).unwrap();
+        let claims: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert!(claims["session_exp"].as_i64().unwrap() <= (now + Duration::seconds(60)).timestamp());
+
+        // Refreshing does not move the deadline
+        let refreshed = sessions.refresh(&pair.refresh_token, |_| true).unwrap();
+        assert!(refreshed.expires_in <= 60 && refreshed.refresh_expires_in <= 60);
+        assert!(matches!(
+            sessions.issue(&identity(), Some(now - Duration::seconds(1))),
+            Err(SessionError::InvalidToken)
+        ));
+
+        // Tokens of sessions this instance did not start are refused
+        let restarted = Sessions::new(SessionConfig::default(), Some("session-secret".to_string()));
+        assert!(restarted.revoked(&refreshed.access_token));
+        assert!(!sessions.revoked(&refreshed.access_token));
+    }
+
+    #[test]
+    fn test_revoke_by_either_token() {
+        let sessions = Sessions::new(SessionConfig::default(), Some("session-secret".to_string()));
+        let by_refresh = sessions.issue(&identity(), None).unwrap();
+        assert!(sessions.revoke(&by_refresh.refresh_token));
+        assert!(sessions.revoked(&by_refresh.access_token));
+
+        let by_access = sessions.issue(&identity(), None).unwrap();
+        assert!(sessions.revoke(&by_access.access_token));
+        assert!(matches!(sessions.refresh(&by_access.refresh_token, |_| true), Err(SessionError::InvalidToken)));
+
+        // Only tokens this server signed are honoured
+        let other = sessions.issue(&identity(), None).unwrap();
+        let forged = sign_jwt(&serde_json::json!({"sid": other.session_id}), "other-secret");
+        assert!(!sessions.revoke(&forged));
+        assert!(!sessions.revoked(&other.access_token));
+        assert!(!sessions.revoke("not-a-token"));
+    }
+}
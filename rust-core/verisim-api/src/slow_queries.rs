@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Persistent slow-query log.
+//!
+//! The planner's [`SlowQueryLog`](verisim_planner::SlowQueryLog) keeps a
+//! bounded ring buffer in memory.  Every entry it logs is also stored as a
+//! query hexad (see [`QueryHexadBuilder`]) in the `slow-queries`
+//! collection, so the log survives restarts and grows beyond the buffer:
+//!
+//! - Document: the query text, with the full entry (plan, step timings,
+//!   parameters, EXPLAIN ANALYZE) as JSON in the `slow_query` field
+//! - Tensor: the actual time of each step
+//! - Temporal: the execution, with its duration and row count
+//!
+//! Writes to this collection do not invalidate cached query results.
+//!
+//! Entries are stored by a background task, for the system rather than
+//! the client whose query was slow, so storing them neither slows the
+//! query further nor depends on what the client may write.  The collection
+//! is private to the store: only administrators see it.  When the writer
+//! falls [`PENDING_ENTRIES`] behind, further entries are dropped and
+//! counted in `/metrics`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+use verisim_hexad::{HexadId, HexadStore, ModalityMask, QueryExecution, QueryHexadBuilder};
+use verisim_planner::{SlowQueryEntry, SlowQueryFilter};
+
+use crate::vql::AGGREGATE_PAGE_SIZE;
+use crate::{ApiError, AppState, ConcreteHexadStore};
+
+/// Collection holding persisted slow-query entries
+pub const SLOW_QUERY_COLLECTION: &str = "slow-queries";
+
+/// Entries waiting to be stored before further ones are dropped
+pub const PENDING_ENTRIES: usize = 1024;
+
+/// Document field holding an entry's JSON
+const ENTRY_FIELD: &str = "slow_query";
+
+enum Message {
+    Entry(Box<SlowQueryEntry>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Stores logged entries in the slow-query collection from a background
+/// task
+#[derive(Clone)]
+pub struct SlowQueryWriter {
+    sender: mpsc::Sender<Message>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl SlowQueryWriter {
+    /// Store entries in `store`, writing from a background task
+    pub fn spawn(store: Arc<ConcreteHexadStore>) -> Self {
+        let (sender, mut receiver) = mpsc::channel(PENDING_ENTRIES);
+        tokio::spawn(verisim_hexad::security::system(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    Message::Entry(entry) => {
+                        if let Err(e) = persist(&store, &entry).await {
+                            warn!(error = %e, "Failed to persist slow-query entry");
+                        }
+                    }
+                    Message::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        }));
+        Self {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queue `entry` to be stored, dropping it if the writer is too far
+    /// behind
+    pub fn submit(&self, entry: SlowQueryEntry) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.sender.try_send(Message::Entry(Box::new(entry))) {
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                warn!("Slow-query writer is behind; dropping entries");
+            }
+        }
+    }
+
+    /// Entries dropped because the writer was behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every entry queued so far has been stored
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(Message::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
+
+/// Store `entry` in the slow-query collection.
+pub async fn persist(store: &ConcreteHexadStore, entry: &SlowQueryEntry) -> Result<HexadId, ApiError> {
+    let json = serde_json::to_string(entry).map_err(|e| ApiError::Serialization(e.to_string()))?;
+    let mut builder = QueryHexadBuilder::new(entry.query_text.as_deref().unwrap_or("(logical plan)")).with_execution(
+        QueryExecution {
+            executed_at: entry.timestamp,
+            duration_ms: entry.actual_ms.round() as u64,
+            result_count: entry.rows_returned,
+            estimated_cost: entry.estimated_ms,
+        },
+    );
+    if !entry.steps.is_empty() {
+        builder = builder.with_cost_vector(entry.steps.iter().map(|s| s.time_ms).collect());
+    }
+    let (_, mut input) = builder.build();
+    if let Some(document) = input.document.as_mut() {
+        document.fields.insert("type".to_string(), "slow_query".to_string());
+        document.fields.insert(ENTRY_FIELD.to_string(), json);
+    }
+    let hexad = store
+        .create_in(SLOW_QUERY_COLLECTION, input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(hexad.id)
+}
+
+/// Persisted entries matching `filter`, newest first, skipping `offset` and
+/// returning at most `limit`, with the total number matching.  Entries
+/// still queued are stored first.
+pub async fn load(
+    state: &AppState,
+    filter: &SlowQueryFilter,
+    offset: usize,
+    limit: usize,
+) -> Result<(usize, Vec<SlowQueryEntry>), ApiError> {
+    let mask = ModalityMask {
+        document: true,
+        ..ModalityMask::STATUS
+    };
+    state.slow_query_writer.flush().await;
+    let mut matching = Vec::new();
+    let mut scanned = 0;
+    loop {
+        let page = verisim_hexad::security::system(state.hexad_store.list_in(
+            SLOW_QUERY_COLLECTION,
+            AGGREGATE_PAGE_SIZE,
+            scanned,
+            mask,
+        ))
+        .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        scanned += page.len();
+        matching.extend(
+            page.iter()
+                .filter_map(|h| h.document.as_ref()?.fields.get(ENTRY_FIELD))
+                .filter_map(|json| serde_json::from_str::<SlowQueryEntry>(json).ok())
+                .filter(|entry| filter.matches(entry)),
+        );
+        if page.len() < AGGREGATE_PAGE_SIZE {
+            break;
+        }
+    }
+    matching.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    let total = matching.len();
+    Ok((total, matching.into_iter().skip(offset).take(limit).collect()))
+}
@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Continuous queries — VQL subscriptions.
+//!
+//! `SUBSCRIBE [SELECT * FROM hexads] [WHERE field = value [AND ...]]`
+//! registers a query whose result set is kept up to date as entities
+//! change.  `GET /subscriptions/{id}/events` streams it as server-sent
+//! events:
+//!
+//! - `snapshot` — the current result set, sent once when the stream opens
+//! - `added` — an entity entered the result set (created, or updated to match)
+//! - `updated` — an entity in the result set changed and still matches
+//! - `removed` — an entity left the result set (deleted, or updated to no
+//!   longer match)
+//! - `lagged` — the client fell behind and missed events; re-open the
+//!   stream for a fresh snapshot
+//!
+//! ## Incremental evaluation
+//!
+//! Queries are not re-run on writes.  The [`SubscriptionListener`] change
+//! hook evaluates each subscription's conditions against the one entity a
+//! write touched, before and after, and publishes the difference.  The
+//! conditions are those of aggregate WHERE clauses, which depend only on
+//! the entity itself, so the result is exact.
+//!
+//! ## Entity policy
+//!
+//! A subscription belongs to the principal that registered it, and only
+//! that principal or an unrestricted one (an administrator) may list,
+//! stream or cancel it.  Each stream is judged for the principal that
+//! opened it: the snapshot is read as that principal, and a change reaches
+//! the stream only if the entity policy admits the principal to the entity.
+//! A deleted entity has no document left to judge, so under a policy its
+//! removal reaches only unrestricted subscribers.  Events are redacted for
+//! the roles of the client that opened the stream.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::warn;
+use verisim_hexad::security::{self, Principal};
+use verisim_hexad::{Hexad, HexadId, HexadListener, HexadStore, ModalityMask};
+
+use crate::vql::{aggregate_field_value, aggregate_mask, value_matches, AGGREGATE_PAGE_SIZE};
+use crate::{redaction, ApiError, AppState, ConcreteHexadStore, HexadResponse};
+
+/// Events buffered per subscription before slow clients start missing them
+const EVENT_BUFFER: usize = 1024;
+
+/// One `field = value` condition of a subscription
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionCondition {
+    pub field: String,
+    pub value: String,
+}
+
+/// A registered continuous query
+#[derive(Debug, Clone, Serialize)]
+pub struct Subscription {
+    /// Handle used to stream and cancel the subscription
+    pub id: String,
+    /// Query text
+    pub query: String,
+    /// Principal that registered the subscription; none when it was
+    /// registered without authentication
+    pub owner: Option<String>,
+    /// Conditions an entity must meet to be in the result set
+    pub conditions: Vec<SubscriptionCondition>,
+    /// When the subscription was registered
+    pub created_at: DateTime<Utc>,
+    /// Event streams currently open
+    pub streams: usize,
+}
+
+/// How an entity's membership of a result set changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Updated,
+    Removed,
+}
+
+impl ChangeKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Updated => "updated",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// A change to a subscription's result set
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    pub change: ChangeKind,
+    /// Entity that changed
+    pub id: String,
+    /// The entity as it is now; absent when removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hexad: Option<Value>,
+    /// Store version of the entity after the change
+    pub version: u64,
+    pub at: DateTime<Utc>,
+}
+
+/// A subscription's registry entry
+struct Registered {
+    info: Subscription,
+    sender: broadcast::Sender<Arc<ChangeEvent>>,
+}
+
+/// Registered subscriptions, keyed by handle
+#[derive(Clone, Default)]
+pub struct Subscriptions {
+    registered: Arc<Mutex<HashMap<String, Registered>>>,
+}
+
+impl Subscriptions {
+    /// Register a continuous query over entities meeting `conditions`,
+    /// owned by the current principal
+    pub fn subscribe(&self, query: &str, conditions: Vec<(String, String)>) -> Subscription {
+        let info = Subscription {
+            id: uuid::Uuid::new_v4().to_string(),
+            query: query.to_string(),
+            owner: security::current().map(|p| p.id),
+            conditions: conditions
+                .into_iter()
+                .map(|(field, value)| SubscriptionCondition { field, value })
+                .collect(),
+            created_at: Utc::now(),
+            streams: 0,
+        };
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        self.lock().insert(info.id.clone(), Registered { info: info.clone(), sender });
+        info
+    }
+
+    /// Cancel the subscription with handle `id`, closing its streams.
+    /// Subscriptions the current principal may not reach are not found.
+    pub fn unsubscribe(&self, id: &str) -> Option<Subscription> {
+        let mut registered = self.lock();
+        if !registered.get(id).is_some_and(|r| reachable(&r.info)) {
+            return None;
+        }
+        registered.remove(id).map(|r| Subscription {
+            streams: r.sender.receiver_count(),
+            ..r.info
+        })
+    }
+
+    /// The subscription with handle `id`, if the current principal may
+    /// reach it
+    pub fn get(&self, id: &str) -> Option<Subscription> {
+        self.lock().get(id).filter(|r| reachable(&r.info)).map(Registered::snapshot)
+    }
+
+    /// Registered subscriptions the current principal may reach, oldest first
+    pub fn list(&self) -> Vec<Subscription> {
+        let mut subscriptions: Vec<Subscription> = self
+            .lock()
+            .values()
+            .filter(|r| reachable(&r.info))
+            .map(Registered::snapshot)
+            .collect();
+        subscriptions.sort_by_key(|s| s.created_at);
+        subscriptions
+    }
+
+    /// Start receiving the changes of subscription `id`, if the current
+    /// principal may reach it
+    fn receiver(&self, id: &str) -> Option<(Subscription, broadcast::Receiver<Arc<ChangeEvent>>)> {
+        self.lock()
+            .get(id)
+            .filter(|r| reachable(&r.info))
+            .map(|r| (r.info.clone(), r.sender.subscribe()))
+    }
+
+    /// Publish the change from `old` to `new` to every subscription whose
+    /// result set it affects
+    fn publish(&self, old: Option<&Hexad>, new: Option<&Hexad>) {
+        let registered = self.lock();
+        for r in registered.values() {
+            if r.sender.receiver_count() == 0 {
+                continue;
+            }
+            let was = old.is_some_and(|h| matches(&r.info.conditions, h));
+            let is = new.is_some_and(|h| matches(&r.info.conditions, h));
+            let change = match (was, is) {
+                (false, true) => ChangeKind::Added,
+                (true, true) => ChangeKind::Updated,
+                (true, false) => ChangeKind::Removed,
+                (false, false) => continue,
+            };
+            let Some(hexad) = new.or(old) else { continue };
+            let event = ChangeEvent {
+                change,
+                id: hexad.id.to_string(),
+                hexad: (change != ChangeKind::Removed).then(|| row(hexad)),
+                version: hexad.status.version,
+                at: Utc::now(),
+            };
+            // Fails only when every stream closed since the check above
+            let _ = r.sender.send(Arc::new(event));
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Registered>> {
+        self.registered.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Registered {
+    fn snapshot(&self) -> Subscription {
+        Subscription {
+            streams: self.sender.receiver_count(),
+            ..self.info.clone()
+        }
+    }
+}
+
+/// Whether the current principal may reach `subscription`: its owner, or
+/// an unrestricted principal.  Without authentication every subscription
+/// is reachable.
+fn reachable(subscription: &Subscription) -> bool {
+    security::current().is_none_or(|p| p.unrestricted || subscription.owner.as_deref() == Some(p.id.as_str()))
+}
+
+/// Whether `principal` may see entity `id` under the entity policy; an
+/// entity that cannot be judged is withheld.
+async fn visible(store: &ConcreteHexadStore, principal: Option<Principal>, id: &str) -> bool {
+    let id = HexadId::new(id);
+    let admits = store.admits(&id);
+    let judged = match principal {
+        Some(principal) => security::scope(principal, admits).await,
+        None => admits.await,
+    };
+    judged.unwrap_or_else(|e| {
+        warn!(error = %e, entity = %id, "Could not judge subscription event");
+        false
+    })
+}
+
+/// Whether `hexad` meets every condition
+pub fn matches(conditions: &[SubscriptionCondition], hexad: &Hexad) -> bool {
+    conditions
+        .iter()
+        .all(|c| value_matches(&aggregate_field_value(hexad, &c.field), &c.value))
+}
+
+/// An entity as a result row
+fn row(hexad: &Hexad) -> Value {
+    serde_json::to_value(HexadResponse::from(hexad)).unwrap_or(Value::Null)
+}
+
+/// Change hook feeding every subscription
+pub struct SubscriptionListener(pub Subscriptions);
+
+impl HexadListener for SubscriptionListener {
+    fn name(&self) -> &str {
+        "subscriptions"
+    }
+
+    fn on_created(&self, new: &Hexad) {
+        self.0.publish(None, Some(new));
+    }
+
+    fn on_updated(&self, old: &Hexad, new: &Hexad) {
+        self.0.publish(Some(old), Some(new));
+    }
+
+    fn on_deleted(&self, old: &Hexad) {
+        self.0.publish(Some(old), None);
+    }
+}
+
+/// The current result set of `subscription`, as rows
+pub async fn snapshot(state: &AppState, subscription: &Subscription) -> Result<Vec<Value>, ApiError> {
+    let mask = ModalityMask {
+        temporal: true,
+        provenance: true,
+        ..aggregate_mask(subscription.conditions.iter().map(|c| c.field.as_str()))
+    };
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = state
+            .hexad_store
+            .list_with(AGGREGATE_PAGE_SIZE, offset, mask)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        rows.extend(page.iter().filter(|h| matches(&subscription.conditions, h)).map(row));
+        if page.len() < AGGREGATE_PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+    Ok(rows)
+}
+
+/// Open the event stream of subscription `id` for the current principal:
+/// a snapshot, then the changes it may see until the subscription is
+/// cancelled.
+pub async fn event_stream(
+    state: &AppState,
+    id: &str,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Receive before the snapshot so no change between the two is lost
+    let (subscription, receiver) = state
+        .subscriptions
+        .receiver(id)
+        .ok_or_else(|| ApiError::NotFound(format!("Subscription {} not found", id)))?;
+    let rows = snapshot(state, &subscription).await?;
+    let mut snapshot = serde_json::json!({"subscription": subscription.id, "row_count": rows.len(), "rows": rows});
+    redaction::redact(&mut snapshot);
+    let first = Event::default()
+        .event("snapshot")
+        .json_data(snapshot)
+        .map_err(|e| ApiError::Serialization(e.to_string()))?;
+
+    // The stream outlives the request, so it judges and redacts changes for
+    // the principal captured here
+    let store = state.hexad_store.clone();
+    let principal = security::current();
+    let redaction = redaction::current();
+    let changes = futures::stream::unfold(receiver, move |mut receiver| {
+        let (store, principal, redaction) = (store.clone(), principal.clone(), redaction.clone());
+        async move {
+            let event = loop {
+                match receiver.recv().await {
+                    Ok(change) => {
+                        if !visible(&store, principal.clone(), &change.id).await {
+                            continue;
+                        }
+                        let Ok(mut data) = serde_json::to_value(&*change) else {
+                            break Event::default().event("error");
+                        };
+                        if let Some(redaction) = &redaction {
+                            redaction.apply(&mut data);
+                        }
+                        break Event::default()
+                            .event(change.change.name())
+                            .json_data(data)
+                            .unwrap_or_else(|_| Event::default().event("error"));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        break Event::default()
+                            .event("lagged")
+                            .data(serde_json::json!({"missed": missed}).to_string());
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            };
+            Some((Ok(event), receiver))
+        }
+    });
+    let stream = futures::StreamExt::chain(futures::stream::once(async move { Ok(first) }), changes);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verisim_hexad::{HexadId, HexadStatus};
+
+    fn hexad(id: &str, title: &str, version: u64) -> Hexad {
+        Hexad {
+            id: HexadId::new(id),
+            status: HexadStatus {
+                id: HexadId::new(id),
+                created_at: Utc::now(),
+                modified_at: Utc::now(),
+                version,
+                modality_status: Default::default(),
+                expires_at: None,
+            },
+            graph_node: None,
+            embedding: None,
+            tensor: None,
+            semantic: None,
+            document: Some(verisim_document::Document::new(id, title, "")),
+            version_count: version,
+            provenance_chain_length: 0,
+            spatial_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publishes_result_set_changes() {
+        let subscriptions = Subscriptions::default();
+        let sub = subscriptions.subscribe("SUBSCRIBE WHERE title = 'rust'", vec![("title".into(), "rust".into())]);
+        let (_, mut receiver) = subscriptions.receiver(&sub.id).unwrap();
+        assert_eq!(subscriptions.get(&sub.id).unwrap().streams, 1);
+        let listener = SubscriptionListener(subscriptions.clone());
+
+        listener.on_created(&hexad("a", "go", 1));
+        listener.on_created(&hexad("b", "rust", 1));
+        listener.on_updated(&hexad("a", "go", 1), &hexad("a", "rust", 2));
+        listener.on_updated(&hexad("b", "rust", 1), &hexad("b", "rust", 2));
+        listener.on_updated(&hexad("a", "rust", 2), &hexad("a", "go", 3));
+        listener.on_deleted(&hexad("b", "rust", 2));
+
+        let mut changes = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            changes.push((event.change, event.id.clone(), event.hexad.is_some()));
+        }
+        assert_eq!(
+            changes,
+            vec![
+                (ChangeKind::Added, "b".to_string(), true),
+                (ChangeKind::Added, "a".to_string(), true),
+                (ChangeKind::Updated, "b".to_string(), true),
+                (ChangeKind::Removed, "a".to_string(), false),
+                (ChangeKind::Removed, "b".to_string(), false),
+            ]
+        );
+
+        assert!(subscriptions.unsubscribe(&sub.id).is_some());
+        assert!(matches!(receiver.try_recv(), Err(broadcast::error::TryRecvError::Closed)));
+        assert!(subscriptions.list().is_empty());
+    }
+}
@@ -6,22 +6,93 @@
 //! for durability and crash recovery. Transactions buffer operations and
 //! apply them atomically on commit, or discard them on rollback.
 //!
+//! Commits are serialized. The manager writes the transaction's operations
+//! to its WAL, hands the transaction to the caller's `apply` step (which
+//! validates it and writes its operations to the stores, undoing its own
+//! writes if one fails), then records the outcome: a `COMMITTED` marker if `apply`
+//! succeeded, `ABORTED` if it did not.  A transaction whose `apply` fails
+//! ends rolled back.
+//!
+//! Between validating and writing, `apply` logs the commit point
+//! ([`TransactionManager::log_commit_point`]), synced whatever the WAL's
+//! `SyncMode`, and after each write an `APPLIED` record
+//! ([`TransactionManager::log_applied`]).  On startup,
+//! [`TransactionManager::recover`] finds the transactions a crash left
+//! without an outcome: those short of their commit point wrote nothing and
+//! are discarded; those past it are replayed from their first unapplied
+//! operation.
+//!
+//! For a two-phase commit across federation peers, a participant's
+//! transaction is first prepared ([`TransactionManager::prepare`]): checked
+//! as `apply` would, its write set locked and its operations logged with a
+//! `PREPARED` marker.  It then waits, surviving restarts, for the
+//! [`Coordinator`] to commit or roll it back.  The coordinator runs the
+//! protocol over the peers' HTTP APIs and keeps its own decisions in a
+//! state file, so it can finish in-doubt transactions after a restart.
+//!
+//! A transaction begun with a snapshot ([`TransactionManager::begin_with`]) holds the read
+//! snapshot taken as it began.  Its reads (see [`TransactionManager::read_view`])
+//! see that snapshot, unaffected by later commits, with its own buffered
+//! writes applied on top.
+//!
+//! Each transaction picks an [`IsolationLevel`] when it begins: snapshot
+//! isolation (the default) reads the snapshot as above, read committed
+//! reads the latest committed state on every read.  Transactions also have
+//! an absolute and an idle timeout; one still active past either is rolled
+//! back as `Expired`, on its next use or by [`TransactionManager::cleanup_expired`].
+//!
+//! Concurrency control is optimistic.  Each transaction tracks a read set
+//! (entities its reads returned, see [`TransactionManager::record_reads`])
+//! and a write set (entities its operations target); `apply` aborts with
+//! [`TransactionError::Conflict`] if another commit changed any of them
+//! since the snapshot, so of two transactions updating the same hexad the
+//! later committer fails rather than overwriting the earlier.  Read
+//! committed transactions have no snapshot to check against: the last
+//! committer wins.
+//!
+//! Workflows that cannot afford to lose a commit can lock entities up front
+//! instead ([`TransactionManager::acquire_lock`]).  Locks are shared or
+//! exclusive, held until the transaction commits, rolls back or expires,
+//! and keep other transactions from buffering or committing writes to the
+//! entity.  A request that has to wait is recorded in a wait-for graph; one
+//! that would close a cycle in it fails at once with
+//! [`TransactionError::Deadlock`] rather than waiting forever.
+//!
 //! # Usage
 //!
 //! ```ignore
 //! let txn_id = manager.begin().await;
 //! manager.buffer_operation(&txn_id, op).await?;
-//! manager.commit(&txn_id).await?;  // or rollback(&txn_id)
+//! manager.commit(&txn_id, |txn| apply(txn)).await?;  // or rollback(&txn_id)
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, Notify, RwLock};
 use tracing::{info, warn};
+use crate::peer_auth::{encode_principal, PeerAuth, PeerScope, PRINCIPAL_HEADER, TRANSACTION_HEADER};
+use verisim_hexad::security::{self, Principal};
+use verisim_hexad::{ReadSnapshot, SyncMode, WalEntry, WalModality, WalOperation, WalReader, WalWriter};
+
+/// Transaction WAL marker: the transaction is committed; its writes follow.
+const COMMITTING: &[u8] = b"COMMITTING";
+/// Transaction WAL marker: all the transaction's writes are in the stores.
+const COMMITTED: &[u8] = b"COMMITTED";
+/// Transaction WAL marker: the transaction wrote nothing, or undid its writes.
+const ABORTED: &[u8] = b"ABORTED";
+/// Transaction WAL marker: the transaction is prepared for a two-phase
+/// commit; its operations precede it.
+const PREPARED: &[u8] = b"PREPARED";
+/// Prefix of the record of one applied operation: `APPLIED:<index>:<id>`.
+const APPLIED: &str = "APPLIED:";
 
 /// Unique identifier for a transaction.
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
@@ -59,10 +130,203 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 pub enum TransactionState {
     /// Transaction is active and accepting operations.
     Active,
+    /// Transaction is validated and logged, awaiting a two-phase commit
+    /// decision.
+    Prepared,
+    /// Transaction's operations are being applied.
+    Committing,
     /// Transaction has been committed.
     Committed,
     /// Transaction has been rolled back.
     RolledBack,
+    /// Transaction was rolled back for passing its timeout.
+    Expired,
+}
+
+/// What a transaction's reads see.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IsolationLevel {
+    /// The latest committed state, read afresh each time.
+    ReadCommitted,
+    /// The state when the transaction began, plus its own writes; commits
+    /// fail on conflict with commits made since.
+    #[default]
+    Snapshot,
+}
+
+/// Settings chosen when a transaction begins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionOptions {
+    #[serde(default)]
+    pub isolation: IsolationLevel,
+    /// Seconds after beginning at which the transaction is rolled back;
+    /// defaults to the manager's `timeout_seconds`, and may not exceed its
+    /// `max_timeout_seconds`
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Seconds without use after which the transaction is rolled back;
+    /// defaults to the manager's `idle_timeout_seconds`, and may not exceed
+    /// its `max_timeout_seconds`
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+}
+
+/// How a transaction holds a lock on an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMode {
+    /// Shared with other shared holders; no transaction may write.
+    Shared,
+    /// Held by one transaction, which alone may write.
+    Exclusive,
+}
+
+/// A lock a transaction holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeldLock {
+    pub entity_id: String,
+    pub mode: LockMode,
+}
+
+/// A lock a transaction is waiting for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockWait {
+    pub entity_id: String,
+    pub mode: LockMode,
+    /// Transactions whose locks it is waiting on
+    pub blocked_by: Vec<String>,
+}
+
+/// Entity locks and the waits for them.  Waits form the wait-for graph:
+/// a waiting transaction has an edge to each transaction blocking it.
+#[derive(Debug, Default)]
+struct LockTable {
+    /// Holders of each locked entity, with the mode each holds it in
+    held: HashMap<String, HashMap<TransactionId, LockMode>>,
+    /// The entity and mode each waiting transaction is waiting for
+    waiting: HashMap<TransactionId, (String, LockMode)>,
+}
+
+impl LockTable {
+    /// Transactions other than `txn_id` whose locks on `entity_id` keep
+    /// `txn_id` from taking it in `mode`, sorted.
+    fn blockers(&self, entity_id: &str, txn_id: &TransactionId, mode: LockMode) -> Vec<TransactionId> {
+        let mut blockers: Vec<TransactionId> = self
+            .held
+            .get(entity_id)
+            .into_iter()
+            .flatten()
+            .filter(|(holder, held)| {
+                *holder != txn_id && (mode == LockMode::Exclusive || **held == LockMode::Exclusive)
+            })
+            .map(|(holder, _)| holder.clone())
+            .collect();
+        blockers.sort_by(|a, b| a.0.cmp(&b.0));
+        blockers
+    }
+
+    /// Give `txn_id` `entity_id` in `mode`, upgrading a shared lock it holds.
+    fn grant(&mut self, entity_id: &str, txn_id: &TransactionId, mode: LockMode) {
+        let held = self
+            .held
+            .entry(entity_id.to_string())
+            .or_default()
+            .entry(txn_id.clone())
+            .or_insert(mode);
+        if mode == LockMode::Exclusive {
+            *held = LockMode::Exclusive;
+        }
+    }
+
+    /// A cycle in the wait-for graph through `txn_id`, starting with it.
+    fn deadlock(&self, txn_id: &TransactionId) -> Option<Vec<TransactionId>> {
+        let mut path = vec![txn_id.clone()];
+        let mut visited = HashSet::new();
+        self.find_cycle(txn_id, txn_id, &mut path, &mut visited)
+    }
+
+    fn find_cycle(
+        &self,
+        start: &TransactionId,
+        current: &TransactionId,
+        path: &mut Vec<TransactionId>,
+        visited: &mut HashSet<TransactionId>,
+    ) -> Option<Vec<TransactionId>> {
+        let (entity_id, mode) = self.waiting.get(current)?;
+        for blocker in self.blockers(entity_id, current, *mode) {
+            if &blocker == start {
+                return Some(path.clone());
+            }
+            if visited.insert(blocker.clone()) {
+                path.push(blocker.clone());
+                if let Some(cycle) = self.find_cycle(start, &blocker, path, visited) {
+                    return Some(cycle);
+                }
+                path.pop();
+            }
+        }
+        None
+    }
+
+    /// Drop every lock and wait of `txn_id`; returns whether it held any lock.
+    fn release(&mut self, txn_id: &TransactionId) -> bool {
+        self.waiting.remove(txn_id);
+        let mut released = false;
+        self.held.retain(|_, holders| {
+            released |= holders.remove(txn_id).is_some();
+            !holders.is_empty()
+        });
+        released
+    }
+
+    /// Locks `txn_id` holds, by entity.
+    fn held_by(&self, txn_id: &TransactionId) -> Vec<HeldLock> {
+        let mut locks: Vec<HeldLock> = self
+            .held
+            .iter()
+            .filter_map(|(entity_id, holders)| {
+                holders.get(txn_id).map(|mode| HeldLock {
+                    entity_id: entity_id.clone(),
+                    mode: *mode,
+                })
+            })
+            .collect();
+        locks.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+        locks
+    }
+
+    /// The lock `txn_id` is waiting for, if any.
+    fn wait_of(&self, txn_id: &TransactionId) -> Option<LockWait> {
+        let (entity_id, mode) = self.waiting.get(txn_id)?;
+        Some(LockWait {
+            entity_id: entity_id.clone(),
+            mode: *mode,
+            blocked_by: self
+                .blockers(entity_id, txn_id, *mode)
+                .into_iter()
+                .map(|id| id.0)
+                .collect(),
+        })
+    }
+}
+
+/// Removes a transaction's wait from the lock table when dropped, so a
+/// waiter that gives up (or whose request is cancelled) leaves no edge in
+/// the wait-for graph.
+struct WaitGuard<'a> {
+    locks: &'a std::sync::Mutex<LockTable>,
+    txn_id: &'a TransactionId,
+}
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        self.locks
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .waiting
+            .remove(self.txn_id);
+    }
 }
 
 /// A buffered operation within a transaction.
@@ -102,6 +366,90 @@ pub struct Transaction {
     pub started_at: String,
     /// When the transaction was completed (committed or rolled back)
     pub completed_at: Option<String>,
+    /// The store as the transaction's reads see it
+    #[serde(skip)]
+    pub snapshot: Option<Arc<ReadSnapshot>>,
+    /// Entities the transaction has read
+    #[serde(default)]
+    pub read_set: BTreeSet<String>,
+    /// What the transaction's reads see
+    #[serde(default)]
+    pub isolation: IsolationLevel,
+    /// When the transaction was last used
+    pub last_active_at: String,
+    /// Seconds after `started_at` at which it expires
+    pub timeout_secs: u64,
+    /// Seconds after `last_active_at` at which it expires
+    pub idle_timeout_secs: u64,
+}
+
+fn wal_error(e: impl std::fmt::Display) -> TransactionError {
+    TransactionError::Wal(e.to_string())
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// `secs` seconds after `time`; `None` when that is past the end of time
+fn seconds_after(time: DateTime<Utc>, secs: u64) -> Option<DateTime<Utc>> {
+    let secs = TimeDelta::try_seconds(i64::try_from(secs).ok()?)?;
+    time.checked_add_signed(secs)
+}
+
+impl Transaction {
+    /// When the transaction expires if it is still active then: at its
+    /// absolute timeout or after its idle timeout, whichever comes first
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        if self.state != TransactionState::Active {
+            return None;
+        }
+        let started = parse_time(&self.started_at)?;
+        let last_active = parse_time(&self.last_active_at).unwrap_or(started);
+        // A deadline past the end of time is never reached
+        let absolute = seconds_after(started, self.timeout_secs);
+        let idle = seconds_after(last_active, self.idle_timeout_secs);
+        absolute.into_iter().chain(idle).min()
+    }
+
+    /// Roll the transaction back as `Expired` if it is active and past
+    /// `expires_at`; returns whether it was.
+    fn expire_if_due(&mut self, now: DateTime<Utc>) -> bool {
+        match self.expires_at() {
+            Some(at) if at <= now => {
+                self.operations.clear();
+                self.state = TransactionState::Expired;
+                self.completed_at = Some(now.to_rfc3339());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Entities the transaction's operations target (inserts have none yet)
+    pub fn write_set(&self) -> BTreeSet<String> {
+        self.operations
+            .iter()
+            .filter(|op| !op.entity_id.is_empty())
+            .map(|op| op.entity_id.clone())
+            .collect()
+    }
+}
+
+/// A transaction found in the WAL without an outcome after a restart.
+#[derive(Debug, Clone)]
+pub struct RecoveredTransaction {
+    pub id: TransactionId,
+    /// Its buffered operations, in order
+    pub operations: Vec<BufferedOperation>,
+    /// Whether it reached its commit point; if not, it wrote nothing
+    pub committing: bool,
+    /// Whether it was prepared for a two-phase commit; if so and not
+    /// committing, its coordinator has yet to decide it
+    pub prepared: bool,
+    /// Operations already written, by index, with the entity each wrote
+    pub applied: BTreeMap<usize, String>,
+    finished: bool,
 }
 
 /// Transaction status response for the API.
@@ -112,6 +460,36 @@ pub struct TransactionStatus {
     pub operation_count: usize,
     pub started_at: String,
     pub completed_at: Option<String>,
+    /// When the snapshot the transaction reads was taken
+    #[serde(default)]
+    pub snapshot_at: Option<String>,
+    #[serde(default)]
+    pub isolation: IsolationLevel,
+    #[serde(default)]
+    pub last_active_at: Option<String>,
+    /// When the transaction will be rolled back if still unfinished
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Entity locks the transaction holds
+    #[serde(default)]
+    pub locks: Vec<HeldLock>,
+    /// The lock the transaction is waiting for, if any
+    #[serde(default)]
+    pub waiting_for: Option<LockWait>,
+}
+
+/// Transactions by state, for metrics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionStats {
+    pub active: usize,
+    pub prepared: usize,
+    pub committing: usize,
+    pub committed: usize,
+    pub rolled_back: usize,
+    pub expired: usize,
+    /// Transactions expired since the manager started, including those
+    /// since forgotten
+    pub expired_total: u64,
 }
 
 impl From<&Transaction> for TransactionStatus {
@@ -122,6 +500,12 @@ fn from(txn: &Transaction) -> Self {
             operation_count: txn.operations.len(),
             started_at: txn.started_at.clone(),
             completed_at: txn.completed_at.clone(),
+            snapshot_at: txn.snapshot.as_ref().map(|s| s.taken_at().to_rfc3339()),
+            isolation: txn.isolation,
+            last_active_at: Some(txn.last_active_at.clone()),
+            expires_at: txn.expires_at().map(|t| t.to_rfc3339()),
+            locks: Vec::new(),
+            waiting_for: None,
         }
     }
 }
@@ -137,8 +521,27 @@ pub enum TransactionError {
     AlreadyCommitted(String),
     /// Transaction has already been rolled back.
     AlreadyRolledBack(String),
+    /// Transaction was rolled back for passing its timeout.
+    Expired(String),
     /// Maximum concurrent transactions exceeded.
     TooManyTransactions,
+    /// A timeout asked for is longer than the manager allows.
+    InvalidTimeout { requested: u64, max: u64 },
+    /// Entities the transaction read or writes were changed by a commit
+    /// since its snapshot.
+    Conflict(Vec<String>),
+    /// Another transaction holds a lock on the entity that forbids the
+    /// write.
+    Locked { entity_id: String, holders: Vec<String> },
+    /// Waiting for the lock would close a cycle of transactions each
+    /// waiting for the next.
+    Deadlock { entity_id: String, cycle: Vec<String> },
+    /// The lock was not granted within the wait allowed.
+    LockTimeout { entity_id: String, holders: Vec<String> },
+    /// The transaction WAL could not be opened or written.
+    Wal(String),
+    /// The two-phase commit coordinator's state could not be loaded or saved.
+    Coordinator(String),
 }
 
 impl std::fmt::Display for TransactionError {
@@ -148,7 +551,36 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::NotActive(id) => write!(f, "transaction not active: {}", id),
             Self::AlreadyCommitted(id) => write!(f, "transaction already committed: {}", id),
             Self::AlreadyRolledBack(id) => write!(f, "transaction already rolled back: {}", id),
+            Self::Expired(id) => write!(f, "transaction expired: {}", id),
             Self::TooManyTransactions => write!(f, "maximum concurrent transactions exceeded"),
+            Self::InvalidTimeout { requested, max } => {
+                write!(f, "transaction timeout of {} seconds exceeds the maximum of {}", requested, max)
+            }
+            Self::Conflict(ids) => write!(
+                f,
+                "transaction conflicts with commits since it began on: {}",
+                ids.join(", ")
+            ),
+            Self::Locked { entity_id, holders } => write!(
+                f,
+                "{} is locked by transaction {}",
+                entity_id,
+                holders.join(", ")
+            ),
+            Self::Deadlock { entity_id, cycle } => write!(
+                f,
+                "deadlock waiting for lock on {}: transactions {} wait on each other",
+                entity_id,
+                cycle.join(" -> ")
+            ),
+            Self::LockTimeout { entity_id, holders } => write!(
+                f,
+                "timed out waiting for lock on {} held by transaction {}",
+                entity_id,
+                holders.join(", ")
+            ),
+            Self::Wal(msg) => write!(f, "transaction WAL error: {}", msg),
+            Self::Coordinator(msg) => write!(f, "transaction coordinator error: {}", msg),
         }
     }
 }
@@ -162,6 +594,20 @@ pub struct TransactionConfig {
     pub max_concurrent: usize,
     /// Transaction timeout in seconds (auto-rollback after this).
     pub timeout_seconds: u64,
+    /// Seconds a transaction may go unused before it is rolled back.
+    #[serde(default = "default_idle_timeout_seconds")]
+    pub idle_timeout_seconds: u64,
+    /// Longest timeout, absolute or idle, a transaction may ask for.
+    #[serde(default = "default_max_timeout_seconds")]
+    pub max_timeout_seconds: u64,
+}
+
+fn default_idle_timeout_seconds() -> u64 {
+    60
+}
+
+fn default_max_timeout_seconds() -> u64 {
+    86_400
 }
 
 impl Default for TransactionConfig {
@@ -169,6 +615,8 @@ fn default() -> Self {
         Self {
             max_concurrent: 256,
             timeout_seconds: 300, // 5 minutes
+            idle_timeout_seconds: default_idle_timeout_seconds(),
+            max_timeout_seconds: default_max_timeout_seconds(),
         }
     }
 }
@@ -181,6 +629,18 @@ fn default() -> Self {
 pub struct TransactionManager {
     config: TransactionConfig,
     transactions: Arc<RwLock<HashMap<TransactionId, Transaction>>>,
+    /// Held for the whole of a commit, so commits apply one at a time
+    commit_lock: Mutex<()>,
+    /// Log of committed batches and their outcomes, when persistent
+    wal: Option<Mutex<WalWriter>>,
+    /// Where `wal` lives, for recovery
+    wal_dir: Option<PathBuf>,
+    /// Transactions expired since the manager started
+    expired_total: AtomicU64,
+    /// Entity locks held and awaited by transactions
+    locks: std::sync::Mutex<LockTable>,
+    /// Notified whenever locks are released, waking waiters to retry
+    lock_released: Notify,
 }
 
 impl TransactionManager {
@@ -189,14 +649,266 @@ pub fn new(config: TransactionConfig) -> Self {
         Self {
             config,
             transactions: Arc::new(RwLock::new(HashMap::new())),
+            commit_lock: Mutex::new(()),
+            wal: None,
+            wal_dir: None,
+            expired_total: AtomicU64::new(0),
+            locks: std::sync::Mutex::new(LockTable::default()),
+            lock_released: Notify::new(),
         }
     }
 
-    /// Begin a new transaction.
+    fn lock_table(&self) -> std::sync::MutexGuard<'_, LockTable> {
+        self.locks.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Release every lock `txn_id` holds, waking waiters.
+    fn release_locks(&self, txn_id: &TransactionId) {
+        if self.lock_table().release(txn_id) {
+            self.lock_released.notify_waiters();
+        }
+    }
+
+    /// Error unless no other transaction holds a lock on any of `entity_ids`.
+    fn check_unlocked<'a>(
+        &self,
+        txn_id: &TransactionId,
+        entity_ids: impl IntoIterator<Item = &'a String>,
+    ) -> Result<(), TransactionError> {
+        let table = self.lock_table();
+        for entity_id in entity_ids {
+            let holders = table.blockers(entity_id, txn_id, LockMode::Exclusive);
+            if !holders.is_empty() {
+                return Err(TransactionError::Locked {
+                    entity_id: entity_id.clone(),
+                    holders: holders.into_iter().map(|id| id.0).collect(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Expire `txn` if it is past its deadline, counting it if so.
+    fn expire_if_due(&self, txn: &mut Transaction) {
+        if txn.expire_if_due(Utc::now()) {
+            self.expired_total.fetch_add(1, Ordering::Relaxed);
+            self.release_locks(&txn.id);
+            warn!(txn_id = %txn.id, "Transaction expired; rolled back");
+        }
+    }
+
+    /// Log every commit to a WAL in `wal_dir`, synced per `sync_mode`
+    /// except at commit points and outcomes, which are always synced.
+    ///
+    /// Each buffered operation becomes one entry keyed by the transaction
+    /// ID (payload: the operation as JSON).  Checkpoint entries with the
+    /// same key follow: `COMMITTING` at the commit point, `APPLIED:<index>:<id>`
+    /// per operation written, then `COMMITTED` or `ABORTED`.
+    pub fn with_wal(mut self, wal_dir: impl AsRef<Path>, sync_mode: SyncMode) -> Result<Self, TransactionError> {
+        let wal_dir = wal_dir.as_ref().to_path_buf();
+        let writer = WalWriter::open(&wal_dir, sync_mode).map_err(|e| TransactionError::Wal(e.to_string()))?;
+        self.wal = Some(Mutex::new(writer));
+        self.wal_dir = Some(wal_dir);
+        Ok(self)
+    }
+
+    /// Append `operations` to the WAL, if there is one.
+    async fn wal_append_operations(
+        &self,
+        txn_id: &TransactionId,
+        operations: &[BufferedOperation],
+    ) -> Result<(), TransactionError> {
+        let Some(wal) = &self.wal else {
+            return Ok(());
+        };
+        let mut writer = wal.lock().await;
+        for op in operations {
+            let payload = serde_json::to_vec(op).map_err(|e| TransactionError::Wal(e.to_string()))?;
+            let operation = match op.operation {
+                OperationType::Create => WalOperation::Insert,
+                OperationType::Update => WalOperation::Update,
+                OperationType::Delete => WalOperation::Delete,
+            };
+            writer
+                .append(WalEntry {
+                    sequence: 0, // Assigned by the writer
+                    timestamp: Utc::now(),
+                    operation,
+                    modality: WalModality::All,
+                    entity_id: txn_id.0.clone(),
+                    payload,
+                })
+                .map_err(|e| TransactionError::Wal(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Append a marker for a commit, syncing it if `sync`, if there is a WAL.
+    async fn wal_append_marker(&self, txn_id: &TransactionId, marker: &[u8], sync: bool) -> Result<(), TransactionError> {
+        let Some(wal) = &self.wal else {
+            return Ok(());
+        };
+        let mut writer = wal.lock().await;
+        writer
+            .append(WalEntry {
+                sequence: 0,
+                timestamp: Utc::now(),
+                operation: WalOperation::Checkpoint,
+                modality: WalModality::All,
+                entity_id: txn_id.0.clone(),
+                payload: marker.to_vec(),
+            })
+            .and_then(|_| if sync { writer.sync() } else { Ok(()) })
+            .map_err(|e| TransactionError::Wal(e.to_string()))
+    }
+
+    /// Append the outcome marker for a commit and sync it, if there is a WAL.
+    async fn wal_append_outcome(&self, txn_id: &TransactionId, outcome: &[u8]) -> Result<(), TransactionError> {
+        self.wal_append_marker(txn_id, outcome, true).await
+    }
+
+    /// Log a committing transaction's commit point: it has been validated
+    /// and its writes are about to start.  Synced before returning, so a
+    /// crash from here on replays the transaction rather than losing it.
+    pub async fn log_commit_point(&self, txn_id: &TransactionId) -> Result<(), TransactionError> {
+        self.wal_append_marker(txn_id, COMMITTING, true).await
+    }
+
+    /// Log that operation `index` of a committing transaction has been
+    /// written, as entity `entity_id`, so replay does not write it again.
+    pub async fn log_applied(&self, txn_id: &TransactionId, index: usize, entity_id: &str) -> Result<(), TransactionError> {
+        let record = format!("{}{}:{}", APPLIED, index, entity_id);
+        self.wal_append_marker(txn_id, record.as_bytes(), false).await
+    }
+
+    /// Transactions the WAL shows a crash interrupted: operations logged
+    /// but no `COMMITTED` or `ABORTED` outcome.  Empty without a WAL.
+    pub fn recover(&self) -> Result<Vec<RecoveredTransaction>, TransactionError> {
+        let Some(wal_dir) = &self.wal_dir else {
+            return Ok(Vec::new());
+        };
+        let entries = WalReader::open(wal_dir).and_then(|r| r.replay_all()).map_err(wal_error)?;
+
+        let mut order = Vec::new();
+        let mut pending: HashMap<String, RecoveredTransaction> = HashMap::new();
+        for entry in entries {
+            let txn = pending.entry(entry.entity_id.clone()).or_insert_with(|| {
+                order.push(entry.entity_id.clone());
+                RecoveredTransaction {
+                    id: TransactionId(entry.entity_id.clone()),
+                    operations: Vec::new(),
+                    committing: false,
+                    prepared: false,
+                    applied: BTreeMap::new(),
+                    finished: false,
+                }
+            });
+            if entry.operation != WalOperation::Checkpoint {
+                let op = serde_json::from_slice(&entry.payload).map_err(wal_error)?;
+                txn.operations.push(op);
+                continue;
+            }
+            match entry.payload.as_slice() {
+                COMMITTING => txn.committing = true,
+                PREPARED => txn.prepared = true,
+                COMMITTED | ABORTED => txn.finished = true,
+                record => {
+                    let applied = std::str::from_utf8(record)
+                        .ok()
+                        .and_then(|r| r.strip_prefix(APPLIED))
+                        .and_then(|r| r.split_once(':'))
+                        .and_then(|(index, id)| Some((index.parse().ok()?, id.to_string())));
+                    if let Some((index, id)) = applied {
+                        txn.applied.insert(index, id);
+                    }
+                }
+            }
+        }
+        Ok(order
+            .into_iter()
+            .filter_map(|id| pending.remove(&id))
+            .filter(|txn| !txn.finished)
+            .collect())
+    }
+
+    /// Record the outcome of replaying a recovered transaction.
+    pub async fn finish_recovered(&self, txn_id: &TransactionId, committed: bool) -> Result<(), TransactionError> {
+        self.wal_append_outcome(txn_id, if committed { COMMITTED } else { ABORTED }).await
+    }
+
+    /// Move an active or prepared transaction to `Committing`, returning
+    /// it and whether it was prepared.  A prepared transaction passed its
+    /// conflict check when it was prepared, so it is returned without its
+    /// snapshot.
+    async fn start_commit(&self, txn_id: &TransactionId) -> Result<(Transaction, bool), TransactionError> {
+        let mut txns = self.transactions.write().await;
+        let txn = txns
+            .get_mut(txn_id)
+            .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
+
+        match txn.state {
+            TransactionState::Active => {
+                self.check_unlocked(txn_id, &txn.write_set())?;
+                txn.state = TransactionState::Committing;
+                Ok((txn.clone(), false))
+            }
+            TransactionState::Prepared => {
+                txn.state = TransactionState::Committing;
+                let mut prepared = txn.clone();
+                prepared.snapshot = None;
+                Ok((prepared, true))
+            }
+            TransactionState::Committing => Err(TransactionError::NotActive(txn_id.0.clone())),
+            TransactionState::Committed => {
+                Err(TransactionError::AlreadyCommitted(txn_id.0.clone()))
+            }
+            TransactionState::RolledBack => {
+                Err(TransactionError::AlreadyRolledBack(txn_id.0.clone()))
+            }
+            TransactionState::Expired => Err(TransactionError::Expired(txn_id.0.clone())),
+        }
+    }
+
+    /// Move a committing transaction to its final state.
+    async fn finish_commit(&self, txn_id: &TransactionId, state: TransactionState) {
+        if let Some(txn) = self.transactions.write().await.get_mut(txn_id) {
+            txn.state = state;
+            txn.completed_at = Some(Utc::now().to_rfc3339());
+        }
+        self.release_locks(txn_id);
+    }
+
+    /// Begin a new read-committed transaction with the default timeouts.
     pub async fn begin(&self) -> Result<TransactionId, TransactionError> {
+        let options = TransactionOptions {
+            isolation: IsolationLevel::ReadCommitted,
+            ..Default::default()
+        };
+        self.begin_with(options, None).await
+    }
+
+    /// Begin a new transaction.  Under snapshot isolation its reads see
+    /// `snapshot`, which should be taken as it begins, plus its own
+    /// writes; without one they see the latest committed state.
+    pub async fn begin_with(
+        &self,
+        options: TransactionOptions,
+        snapshot: Option<ReadSnapshot>,
+    ) -> Result<TransactionId, TransactionError> {
+        let timeout_secs = options.timeout_secs.unwrap_or(self.config.timeout_seconds);
+        let idle_timeout_secs = options.idle_timeout_secs.unwrap_or(self.config.idle_timeout_seconds);
+        let max = self.config.max_timeout_seconds;
+        if let Some(requested) = [timeout_secs, idle_timeout_secs].into_iter().find(|&secs| secs > max) {
+            return Err(TransactionError::InvalidTimeout { requested, max });
+        }
+
         let mut txns = self.transactions.write().await;
 
         // Check concurrent transaction limit
+        for txn in txns.values_mut() {
+            self.expire_if_due(txn);
+        }
         let active_count = txns
             .values()
             .filter(|t| t.state == TransactionState::Active)
@@ -206,12 +918,23 @@ pub async fn begin(&self) -> Result<TransactionId, TransactionError> {
         }
 
         let id = TransactionId::new();
+        let now = Utc::now().to_rfc3339();
+        let snapshot = match options.isolation {
+            IsolationLevel::Snapshot => snapshot.map(Arc::new),
+            IsolationLevel::ReadCommitted => None,
+        };
         let txn = Transaction {
             id: id.clone(),
             state: TransactionState::Active,
             operations: Vec::new(),
-            started_at: Utc::now().to_rfc3339(),
+            started_at: now.clone(),
             completed_at: None,
+            snapshot,
+            read_set: BTreeSet::new(),
+            isolation: options.isolation,
+            last_active_at: now,
+            timeout_secs,
+            idle_timeout_secs,
         };
 
         info!(txn_id = %id, "Transaction started");
@@ -229,69 +952,240 @@ pub async fn buffer_operation(
         let txn = txns
             .get_mut(txn_id)
             .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
 
         match txn.state {
             TransactionState::Active => {
+                if !operation.entity_id.is_empty() {
+                    self.check_unlocked(txn_id, [&operation.entity_id])?;
+                }
                 txn.operations.push(operation);
+                txn.last_active_at = Utc::now().to_rfc3339();
                 Ok(())
             }
+            TransactionState::Committing | TransactionState::Prepared => {
+                Err(TransactionError::NotActive(txn_id.0.clone()))
+            }
             TransactionState::Committed => {
                 Err(TransactionError::AlreadyCommitted(txn_id.0.clone()))
             }
             TransactionState::RolledBack => {
                 Err(TransactionError::AlreadyRolledBack(txn_id.0.clone()))
             }
+            TransactionState::Expired => Err(TransactionError::Expired(txn_id.0.clone())),
         }
     }
 
-    /// Commit a transaction — marks all buffered operations as committed.
+    /// Commit a transaction: log its buffered operations, pass the
+    /// transaction to `apply`, and record the outcome.
     ///
-    /// Returns the list of buffered operations so the caller (API layer)
-    /// can apply them to the hexad store.
-    pub async fn commit(
+    /// `apply` must check the transaction for conflicts and write its
+    /// operations atomically: all of them, or none (undoing its own writes
+    /// before returning an error).  Its result is
+    /// returned; on error the transaction ends rolled back.  Commits are
+    /// serialized, so `apply` sees no other transaction's writes mid-way.
+    pub async fn commit<T, E, F, Fut>(&self, txn_id: &TransactionId, apply: F) -> Result<T, E>
+    where
+        F: FnOnce(Transaction) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<TransactionError>,
+    {
+        let _commit = self.commit_lock.lock().await;
+        let (txn, prepared) = self.start_commit(txn_id).await?;
+        let count = txn.operations.len();
+
+        // A prepared transaction's operations are already logged.
+        let logged = match prepared {
+            true => Ok(()),
+            false => self.wal_append_operations(txn_id, &txn.operations).await,
+        };
+        if let Err(e) = logged {
+            self.wal_append_outcome(txn_id, ABORTED).await.ok();
+            self.finish_commit(txn_id, TransactionState::RolledBack).await;
+            return Err(e.into());
+        }
+
+        match apply(txn).await {
+            Ok(applied) => {
+                // The writes are in the stores (and their own WAL) now; a
+                // missing marker only leaves the log less informative.
+                if let Err(e) = self.wal_append_outcome(txn_id, COMMITTED).await {
+                    warn!(txn_id = %txn_id, error = %e, "Failed to log transaction commit");
+                }
+                self.finish_commit(txn_id, TransactionState::Committed).await;
+                info!(txn_id = %txn_id, ops = count, "Transaction committed");
+                Ok(applied)
+            }
+            Err(e) => {
+                self.wal_append_outcome(txn_id, ABORTED).await.ok();
+                self.finish_commit(txn_id, TransactionState::RolledBack).await;
+                warn!(txn_id = %txn_id, ops = count, "Transaction commit failed; rolled back");
+                Err(e)
+            }
+        }
+    }
+
+    /// Prepare an active transaction for a two-phase commit: pass it to
+    /// `validate` (the checks `apply` makes before writing), lock its write
+    /// set exclusively, and log its operations and a synced `PREPARED`
+    /// marker.  Preparing a prepared transaction again is a no-op.
+    ///
+    /// A prepared transaction no longer expires.  It keeps its locks until
+    /// [`commit`](Self::commit) or [`rollback`](Self::rollback) decides it,
+    /// and after a restart [`recover`](Self::recover) reports it so it can
+    /// be restored, still prepared.  If `validate` or logging fails, the
+    /// transaction ends rolled back.
+    pub async fn prepare<E, F, Fut>(&self, txn_id: &TransactionId, validate: F) -> Result<(), E>
+    where
+        F: FnOnce(Transaction) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: From<TransactionError>,
+    {
+        let _commit = self.commit_lock.lock().await;
+        let txn = {
+            let mut txns = self.transactions.write().await;
+            let txn = txns
+                .get_mut(txn_id)
+                .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+            self.expire_if_due(txn);
+            match txn.state {
+                TransactionState::Active => {}
+                TransactionState::Prepared => return Ok(()),
+                TransactionState::Expired => return Err(TransactionError::Expired(txn_id.0.clone()).into()),
+                _ => return Err(TransactionError::NotActive(txn_id.0.clone()).into()),
+            }
+            self.lock_write_set(txn_id, &txn.write_set())?;
+            txn.state = TransactionState::Committing;
+            txn.clone()
+        };
+
+        let prepared = match validate(txn.clone()).await {
+            Ok(()) => match self.wal_append_operations(txn_id, &txn.operations).await {
+                Ok(()) => self.wal_append_outcome(txn_id, PREPARED).await.map_err(E::from),
+                Err(e) => Err(e.into()),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = prepared {
+            self.wal_append_outcome(txn_id, ABORTED).await.ok();
+            self.finish_commit(txn_id, TransactionState::RolledBack).await;
+            warn!(txn_id = %txn_id, "Transaction prepare failed; rolled back");
+            return Err(e);
+        }
+        if let Some(txn) = self.transactions.write().await.get_mut(txn_id) {
+            txn.state = TransactionState::Prepared;
+            txn.last_active_at = Utc::now().to_rfc3339();
+        }
+        info!(txn_id = %txn_id, ops = txn.operations.len(), "Transaction prepared");
+        Ok(())
+    }
+
+    /// Lock every entity in `entity_ids` exclusively for `txn_id`, or none
+    /// of them if another transaction holds a lock on any.
+    fn lock_write_set(&self, txn_id: &TransactionId, entity_ids: &BTreeSet<String>) -> Result<(), TransactionError> {
+        let mut table = self.lock_table();
+        for entity_id in entity_ids {
+            let holders = table.blockers(entity_id, txn_id, LockMode::Exclusive);
+            if !holders.is_empty() {
+                return Err(TransactionError::Locked {
+                    entity_id: entity_id.clone(),
+                    holders: holders.into_iter().map(|id| id.0).collect(),
+                });
+            }
+        }
+        for entity_id in entity_ids {
+            table.grant(entity_id, txn_id, LockMode::Exclusive);
+        }
+        Ok(())
+    }
+
+    /// Restore a transaction [`recover`](Self::recover) found prepared but
+    /// undecided, prepared again with its write set locked, to await its
+    /// coordinator's commit or rollback.
+    pub async fn restore_prepared(&self, recovered: RecoveredTransaction) -> Result<(), TransactionError> {
+        let now = Utc::now().to_rfc3339();
+        let txn = Transaction {
+            id: recovered.id.clone(),
+            state: TransactionState::Prepared,
+            operations: recovered.operations,
+            started_at: now.clone(),
+            completed_at: None,
+            snapshot: None,
+            read_set: BTreeSet::new(),
+            isolation: IsolationLevel::ReadCommitted,
+            last_active_at: now,
+            timeout_secs: self.config.timeout_seconds,
+            idle_timeout_secs: self.config.idle_timeout_seconds,
+        };
+        self.lock_write_set(&txn.id, &txn.write_set())?;
+        info!(txn_id = %txn.id, ops = txn.operations.len(), "Restored prepared transaction");
+        self.transactions.write().await.insert(txn.id.clone(), txn);
+        Ok(())
+    }
+
+    /// Rollback a transaction — discard all buffered operations.
+    pub async fn rollback(
         &self,
         txn_id: &TransactionId,
-    ) -> Result<Vec<BufferedOperation>, TransactionError> {
+    ) -> Result<usize, TransactionError> {
         let mut txns = self.transactions.write().await;
         let txn = txns
             .get_mut(txn_id)
             .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
 
         match txn.state {
             TransactionState::Active => {
-                txn.state = TransactionState::Committed;
+                let discarded = txn.operations.len();
+                txn.operations.clear();
+                txn.state = TransactionState::RolledBack;
+                txn.completed_at = Some(Utc::now().to_rfc3339());
+                self.release_locks(txn_id);
+                warn!(txn_id = %txn_id, discarded = discarded, "Transaction rolled back");
+                Ok(discarded)
+            }
+            TransactionState::Prepared => {
+                // Its operations and PREPARED marker are logged; the outcome
+                // must be too, or a restart would restore it as prepared.
+                self.wal_append_outcome(txn_id, ABORTED).await?;
+                let discarded = txn.operations.len();
+                txn.operations.clear();
+                txn.state = TransactionState::RolledBack;
                 txn.completed_at = Some(Utc::now().to_rfc3339());
-                let ops = txn.operations.clone();
-                info!(txn_id = %txn_id, ops = ops.len(), "Transaction committed");
-                Ok(ops)
+                self.release_locks(txn_id);
+                warn!(txn_id = %txn_id, discarded = discarded, "Prepared transaction rolled back");
+                Ok(discarded)
             }
+            TransactionState::Committing => Err(TransactionError::NotActive(txn_id.0.clone())),
             TransactionState::Committed => {
                 Err(TransactionError::AlreadyCommitted(txn_id.0.clone()))
             }
             TransactionState::RolledBack => {
                 Err(TransactionError::AlreadyRolledBack(txn_id.0.clone()))
             }
+            TransactionState::Expired => Err(TransactionError::Expired(txn_id.0.clone())),
         }
     }
 
-    /// Rollback a transaction — discard all buffered operations.
-    pub async fn rollback(
+    /// What an active transaction reads: its snapshot (if it began with
+    /// one) and its buffered operations, in order.
+    pub async fn read_view(
         &self,
         txn_id: &TransactionId,
-    ) -> Result<usize, TransactionError> {
+    ) -> Result<(Option<Arc<ReadSnapshot>>, Vec<BufferedOperation>), TransactionError> {
         let mut txns = self.transactions.write().await;
         let txn = txns
             .get_mut(txn_id)
             .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
 
         match txn.state {
             TransactionState::Active => {
-                let discarded = txn.operations.len();
-                txn.operations.clear();
-                txn.state = TransactionState::RolledBack;
-                txn.completed_at = Some(Utc::now().to_rfc3339());
-                warn!(txn_id = %txn_id, discarded = discarded, "Transaction rolled back");
-                Ok(discarded)
+                txn.last_active_at = Utc::now().to_rfc3339();
+                Ok((txn.snapshot.clone(), txn.operations.clone()))
+            }
+            TransactionState::Committing | TransactionState::Prepared => {
+                Err(TransactionError::NotActive(txn_id.0.clone()))
             }
             TransactionState::Committed => {
                 Err(TransactionError::AlreadyCommitted(txn_id.0.clone()))
@@ -299,55 +1193,587 @@ pub async fn rollback(
             TransactionState::RolledBack => {
                 Err(TransactionError::AlreadyRolledBack(txn_id.0.clone()))
             }
+            TransactionState::Expired => Err(TransactionError::Expired(txn_id.0.clone())),
         }
     }
 
+    /// Add `ids` to an active transaction's read set.
+    pub async fn record_reads(
+        &self,
+        txn_id: &TransactionId,
+        ids: impl IntoIterator<Item = String>,
+    ) -> Result<(), TransactionError> {
+        let mut txns = self.transactions.write().await;
+        let txn = txns
+            .get_mut(txn_id)
+            .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
+        match txn.state {
+            TransactionState::Active => {}
+            TransactionState::Expired => return Err(TransactionError::Expired(txn_id.0.clone())),
+            _ => return Err(TransactionError::NotActive(txn_id.0.clone())),
+        }
+        txn.read_set.extend(ids);
+        Ok(())
+    }
+
     /// Get the status of a transaction.
     pub async fn status(
         &self,
         txn_id: &TransactionId,
     ) -> Result<TransactionStatus, TransactionError> {
-        let txns = self.transactions.read().await;
+        let mut txns = self.transactions.write().await;
         let txn = txns
-            .get(txn_id)
+            .get_mut(txn_id)
             .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
-        Ok(TransactionStatus::from(txn))
+        self.expire_if_due(txn);
+        let mut status = TransactionStatus::from(&*txn);
+        let table = self.lock_table();
+        status.locks = table.held_by(txn_id);
+        status.waiting_for = table.wait_of(txn_id);
+        Ok(status)
     }
 
-    /// Clean up completed transactions older than the timeout.
+    /// Lock `entity_id` in `mode` for an active transaction, waiting up to
+    /// `wait` for conflicting locks to be released.  Re-acquiring a lock
+    /// already held is a no-op; asking for exclusive while holding shared
+    /// upgrades it.  Fails with `Deadlock` as soon as waiting would close a
+    /// cycle in the wait-for graph, or `LockTimeout` once `wait` passes.
+    pub async fn acquire_lock(
+        &self,
+        txn_id: &TransactionId,
+        entity_id: &str,
+        mode: LockMode,
+        wait: std::time::Duration,
+    ) -> Result<(), TransactionError> {
+        let deadline = tokio::time::Instant::now() + wait;
+        let _wait = WaitGuard {
+            locks: &self.locks,
+            txn_id,
+        };
+        loop {
+            // Re-checked each round: the transaction may expire while waiting.
+            self.touch(txn_id).await?;
+            // Created before checking, so a release in between still wakes it.
+            let released = self.lock_released.notified();
+            {
+                let mut table = self.lock_table();
+                let blockers = table.blockers(entity_id, txn_id, mode);
+                if blockers.is_empty() {
+                    table.waiting.remove(txn_id);
+                    table.grant(entity_id, txn_id, mode);
+                    return Ok(());
+                }
+                table
+                    .waiting
+                    .insert(txn_id.clone(), (entity_id.to_string(), mode));
+                if let Some(cycle) = table.deadlock(txn_id) {
+                    warn!(txn_id = %txn_id, entity_id, "Deadlock detected; lock refused");
+                    return Err(TransactionError::Deadlock {
+                        entity_id: entity_id.to_string(),
+                        cycle: cycle.into_iter().map(|id| id.0).collect(),
+                    });
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    return Err(TransactionError::LockTimeout {
+                        entity_id: entity_id.to_string(),
+                        holders: blockers.into_iter().map(|id| id.0).collect(),
+                    });
+                }
+            }
+            // On timeout, the next round reports it (or finds the lock free).
+            tokio::time::timeout_at(deadline, released).await.ok();
+        }
+    }
+
+    /// Error unless the transaction is active; marks it used.
+    async fn touch(&self, txn_id: &TransactionId) -> Result<(), TransactionError> {
+        let mut txns = self.transactions.write().await;
+        let txn = txns
+            .get_mut(txn_id)
+            .ok_or_else(|| TransactionError::NotFound(txn_id.0.clone()))?;
+        self.expire_if_due(txn);
+        match txn.state {
+            TransactionState::Active => {
+                txn.last_active_at = Utc::now().to_rfc3339();
+                Ok(())
+            }
+            TransactionState::Expired => Err(TransactionError::Expired(txn_id.0.clone())),
+            _ => Err(TransactionError::NotActive(txn_id.0.clone())),
+        }
+    }
+
+    /// Roll back active transactions past their deadline, and forget
+    /// finished ones completed more than `timeout_seconds` ago.  Returns
+    /// the number rolled back.
     pub async fn cleanup_expired(&self) -> usize {
         let mut txns = self.transactions.write().await;
         let now = Utc::now();
-        let timeout = chrono::Duration::seconds(self.config.timeout_seconds as i64);
+        let timeout = i64::try_from(self.config.timeout_seconds)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .unwrap_or(TimeDelta::MAX);
+
+        let mut expired = 0;
+        for txn in txns.values_mut() {
+            if txn.state == TransactionState::Active {
+                self.expire_if_due(txn);
+                expired += usize::from(txn.state == TransactionState::Expired);
+            }
+        }
+        txns.retain(|_, txn| match txn.completed_at.as_deref().and_then(parse_time) {
+            Some(completed_at) => now.signed_duration_since(completed_at) <= timeout,
+            None => true,
+        });
+        expired
+    }
 
-        let expired_ids: Vec<TransactionId> = txns
+    /// Transactions by state.
+    pub async fn stats(&self) -> TransactionStats {
+        let mut txns = self.transactions.write().await;
+        let mut stats = TransactionStats::default();
+        for txn in txns.values_mut() {
+            self.expire_if_due(txn);
+            match txn.state {
+                TransactionState::Active => stats.active += 1,
+                TransactionState::Prepared => stats.prepared += 1,
+                TransactionState::Committing => stats.committing += 1,
+                TransactionState::Committed => stats.committed += 1,
+                TransactionState::RolledBack => stats.rolled_back += 1,
+                TransactionState::Expired => stats.expired += 1,
+            }
+        }
+        stats.expired_total = self.expired_total.load(Ordering::Relaxed);
+        stats
+    }
+}
+
+/// Time allowed for one call to a two-phase commit participant
+const PARTICIPANT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Where a distributed transaction is in its two-phase commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalState {
+    /// Participants are running their statements and preparing.
+    Preparing,
+    /// Every participant prepared; the decision is commit.
+    Committing,
+    /// A participant failed before the decision; the decision is abort.
+    Aborting,
+    /// Every participant committed.
+    Committed,
+    /// Every participant rolled back.
+    Aborted,
+}
+
+/// Where one participant is in a distributed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantState {
+    /// Its local transaction has not begun.
+    Pending,
+    /// Its local transaction is open.
+    Active,
+    /// Its local transaction is prepared.
+    Prepared,
+    /// Its local transaction committed.
+    Committed,
+    /// Its local transaction rolled back (or never began).
+    Aborted,
+}
+
+/// One store's part in a distributed transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participant {
+    pub store_id: String,
+    /// Base URL of the store's API
+    pub endpoint: String,
+    /// VQL write statements run in its local transaction
+    pub statements: Vec<String>,
+    /// Its local transaction, once begun
+    #[serde(default)]
+    pub transaction_id: Option<String>,
+    pub state: ParticipantState,
+    /// The last error calling it, if any
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl Participant {
+    pub fn new(store_id: impl Into<String>, endpoint: impl Into<String>, statements: Vec<String>) -> Self {
+        Self {
+            store_id: store_id.into(),
+            endpoint: endpoint.into(),
+            statements,
+            transaction_id: None,
+            state: ParticipantState::Pending,
+            error: None,
+        }
+    }
+}
+
+/// A transaction spanning several stores, committed with two-phase commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalTransaction {
+    pub id: String,
+    pub state: GlobalState,
+    pub participants: Vec<Participant>,
+    pub started_at: String,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    /// Why it aborted, if it did
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Whom it runs for on every participant
+    #[serde(default)]
+    pub principal: Option<Principal>,
+}
+
+impl GlobalTransaction {
+    /// Whether every participant has reached the decided outcome
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, GlobalState::Committed | GlobalState::Aborted)
+    }
+}
+
+/// Two-phase commit coordinator for writes spanning federation peers.
+///
+/// [`execute`](Self::execute) begins a transaction on every participant
+/// and runs its statements there, then prepares each.  If all prepare,
+/// the decision to commit is recorded before any participant is told;
+/// otherwise the decision is abort.  The decision is then sent to every
+/// participant.  With a state file, the record of each transaction is
+/// saved at every step, and [`resolve_in_doubt`](Self::resolve_in_doubt)
+/// finishes those left undecided or half-told by a crash or an
+/// unreachable peer: undecided ones abort (nothing can have committed),
+/// decided ones are re-sent their decision.  Once every participant has
+/// acknowledged a decision it is no longer saved, and only the latest
+/// [`FINISHED_KEPT`] finished transactions are remembered.
+pub struct Coordinator {
+    transactions: Mutex<BTreeMap<String, GlobalTransaction>>,
+    /// Transactions `execute` is still running, left alone by recovery
+    in_flight: std::sync::Mutex<HashSet<String>>,
+    persist_path: Option<PathBuf>,
+    /// Built on first use, once a TLS provider is installed
+    client: std::sync::OnceLock<reqwest::Client>,
+    peer_auth: PeerAuth,
+}
+
+impl Default for Coordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Coordinator {
+    pub fn new() -> Self {
+        Self {
+            transactions: Mutex::new(BTreeMap::new()),
+            in_flight: std::sync::Mutex::new(HashSet::new()),
+            persist_path: None,
+            client: std::sync::OnceLock::new(),
+            peer_auth: PeerAuth::default(),
+        }
+    }
+
+    /// Authenticate to participants as a federation peer.
+    pub fn with_peer_auth(mut self, peer_auth: PeerAuth) -> Self {
+        self.peer_auth = peer_auth;
+        self
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(PARTICIPANT_TIMEOUT)
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Keep transaction records in `path`, loading those already there.
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self, TransactionError> {
+        let path = path.into();
+        let persistence = |e: &dyn std::fmt::Display| TransactionError::Coordinator(format!("{}: {}", path.display(), e));
+        let transactions: BTreeMap<String, GlobalTransaction> = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| persistence(&e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(persistence(&e)),
+        };
+        let in_doubt = transactions.values().filter(|t| !t.is_finished()).count();
+        info!(path = %path.display(), transactions = transactions.len(), in_doubt, "Loaded distributed transactions");
+        self.transactions = Mutex::new(transactions);
+        self.persist_path = Some(path);
+        Ok(self)
+    }
+
+    /// All distributed transactions, oldest first.
+    pub async fn list(&self) -> Vec<GlobalTransaction> {
+        let mut transactions: Vec<_> = self.transactions.lock().await.values().cloned().collect();
+        transactions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        transactions
+    }
+
+    /// One distributed transaction.
+    pub async fn get(&self, id: &str) -> Option<GlobalTransaction> {
+        self.transactions.lock().await.get(id).cloned()
+    }
+
+    /// Run `participants`' statements as one transaction across their
+    /// stores, returning its record: `Committed`, `Aborted`, or
+    /// `Committing` if the decision was commit but some participant has
+    /// yet to be told.
+    pub async fn execute(&self, participants: Vec<Participant>) -> GlobalTransaction {
+        let mut txn = GlobalTransaction {
+            id: uuid::Uuid::new_v4().to_string(),
+            state: GlobalState::Preparing,
+            participants,
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+            error: None,
+            principal: security::current(),
+        };
+        self.in_flight
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(txn.id.clone());
+
+        let prepared = match self.save(&txn).await {
+            Ok(()) => self.prepare_all(&mut txn).await,
+            Err(e) => Err(e.to_string()),
+        };
+        // The decision only counts once it is saved.
+        let decided = match prepared {
+            Ok(()) => {
+                txn.state = GlobalState::Committing;
+                self.save(&txn).await.map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = decided {
+            warn!(txn_id = %txn.id, error = %e, "Distributed transaction aborted");
+            txn.state = GlobalState::Aborting;
+            txn.error = Some(e);
+        }
+        self.finish(&mut txn).await;
+
+        self.in_flight
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&txn.id);
+        txn
+    }
+
+    /// Finish every transaction not being executed that is undecided or
+    /// whose decision has not reached all participants.  Returns the
+    /// number finished.
+    pub async fn resolve_in_doubt(&self) -> usize {
+        let in_doubt: Vec<GlobalTransaction> = {
+            // `execute` marks a transaction in flight before first saving it
+            // and clears the mark after last saving it.
+            let transactions = self.transactions.lock().await;
+            let in_flight = self.in_flight.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            transactions
+                .values()
+                .filter(|t| !t.is_finished() && !in_flight.contains(&t.id))
+                .cloned()
+                .collect()
+        };
+        let mut resolved = 0;
+        for mut txn in in_doubt {
+            if txn.state == GlobalState::Preparing {
+                txn.state = GlobalState::Aborting;
+                txn.error = Some("coordinator stopped before deciding".to_string());
+            }
+            self.finish(&mut txn).await;
+            if txn.is_finished() {
+                info!(txn_id = %txn.id, state = ?txn.state, "Resolved in-doubt distributed transaction");
+                resolved += 1;
+            }
+        }
+        resolved
+    }
+
+    /// Phase one: begin, run the statements, and prepare on each participant.
+    async fn prepare_all(&self, txn: &mut GlobalTransaction) -> Result<(), String> {
+        let principal = txn.principal.clone();
+        for i in 0..txn.participants.len() {
+            let participant = &mut txn.participants[i];
+            let begun = self.call(&principal, participant, "/transactions/begin", None, serde_json::json!({})).await?;
+            let local_id = begun["id"].as_str().unwrap_or_default().to_string();
+            participant.transaction_id = Some(local_id.clone());
+            participant.state = ParticipantState::Active;
+            // Saved before running anything, so recovery can roll it back.
+            self.save(txn).await.map_err(|e| e.to_string())?;
+
+            let participant = &mut txn.participants[i];
+            for statement in participant.statements.clone() {
+                let body = serde_json::json!({ "query": statement, "transaction": local_id });
+                self.call(&principal, participant, "/vql/execute", Some(&local_id), body).await?;
+            }
+        }
+        for i in 0..txn.participants.len() {
+            let participant = &mut txn.participants[i];
+            let path = format!("/transactions/{}/prepare", participant.transaction_id.as_deref().unwrap_or_default());
+            self.call(&principal, participant, &path, None, serde_json::json!({})).await?;
+            participant.state = ParticipantState::Prepared;
+        }
+        self.save(txn).await.map_err(|e| e.to_string())
+    }
+
+    /// Phase two: send the decision to every participant not yet told.
+    /// The transaction is finished once all have acknowledged it.
+    async fn finish(&self, txn: &mut GlobalTransaction) {
+        let commit = txn.state == GlobalState::Committing;
+        let principal = txn.principal.clone();
+        for participant in &mut txn.participants {
+            if matches!(participant.state, ParticipantState::Committed | ParticipantState::Aborted) {
+                continue;
+            }
+            let Some(local_id) = participant.transaction_id.clone() else {
+                participant.state = ParticipantState::Aborted;
+                continue;
+            };
+            let (action, done) = match commit {
+                true => ("commit", ParticipantState::Committed),
+                false => ("rollback", ParticipantState::Aborted),
+            };
+            let path = format!("/transactions/{}/{}", local_id, action);
+            // On abort, the error that caused it is kept.
+            let error = participant.error.take().filter(|_| !commit);
+            if self.call(&principal, participant, &path, None, serde_json::json!({})).await.is_ok() {
+                participant.state = done;
+                participant.error = error;
+                continue;
+            }
+            // It may have acted on an earlier attempt whose reply was lost.
+            let status = self.status(&principal, participant, &local_id).await;
+            let acted = matches!(
+                (commit, status.as_deref()),
+                (true, Some("Committed")) | (false, Some("RolledBack" | "Expired") | None)
+            );
+            if acted {
+                participant.state = done;
+                participant.error = error;
+            }
+        }
+        let told = txn
+            .participants
             .iter()
-            .filter(|(_, txn)| {
-                if txn.state != TransactionState::Active {
-                    // Already completed — clean up after timeout
-                    if let Some(ref completed) = txn.completed_at {
-                        if let Ok(completed_at) = chrono::DateTime::parse_from_rfc3339(completed) {
-                            return now.signed_duration_since(completed_at) > timeout;
-                        }
-                    }
-                    false
-                } else {
-                    // Active but expired — auto-rollback candidate
-                    if let Ok(started) = chrono::DateTime::parse_from_rfc3339(&txn.started_at) {
-                        now.signed_duration_since(started) > timeout
-                    } else {
-                        false
-                    }
-                }
-            })
-            .map(|(id, _)| id.clone())
-            .collect();
+            .all(|p| matches!(p.state, ParticipantState::Committed | ParticipantState::Aborted));
+        if told {
+            txn.state = if commit { GlobalState::Committed } else { GlobalState::Aborted };
+            txn.completed_at = Some(Utc::now().to_rfc3339());
+        }
+        if let Err(e) = self.save(txn).await {
+            warn!(txn_id = %txn.id, error = %e, "Failed to save distributed transaction");
+        }
+    }
+
+    /// POST `body` to `path` on a participant for `principal`, naming
+    /// the participant's `transaction` a statement runs in, returning the
+    /// response body; on failure the error is also kept on the participant.
+    async fn call(
+        &self,
+        principal: &Option<Principal>,
+        participant: &mut Participant,
+        path: &str,
+        transaction: Option<&str>,
+        body: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let url = format!("{}{}", participant.endpoint, path);
+        let result = async {
+            let mut request = self
+                .peer_auth
+                .request(self.client(), reqwest::Method::POST, &url, PeerScope::Transaction, PARTICIPANT_TIMEOUT)
+                .await?
+                .header(PRINCIPAL_HEADER, carried(principal));
+            if let Some(transaction) = transaction {
+                request = request.header(TRANSACTION_HEADER, transaction);
+            }
+            let resp = request
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let status = resp.status();
+            let body: serde_json::Value = resp.json().await.unwrap_or_default();
+            match status.is_success() {
+                true => Ok(body),
+                false => Err(format!("{} ({})", body["error"].as_str().unwrap_or("request failed"), status)),
+            }
+        }
+        .await
+        .map_err(|e| format!("{} {}: {}", participant.store_id, path, e));
+        if let Err(e) = &result {
+            participant.error = Some(e.clone());
+        }
+        result
+    }
 
-        let count = expired_ids.len();
-        for id in expired_ids {
-            txns.remove(&id);
+    /// A participant's local transaction state, `None` if it does not
+    /// know the transaction, or `Some("unreachable")` if it cannot say.
+    async fn status(&self, principal: &Option<Principal>, participant: &Participant, local_id: &str) -> Option<String> {
+        let url = format!("{}/transactions/{}", participant.endpoint, local_id);
+        let request = self
+            .peer_auth
+            .request(self.client(), reqwest::Method::GET, &url, PeerScope::Transaction, PARTICIPANT_TIMEOUT)
+            .await;
+        let Ok(request) = request else {
+            return Some("unreachable".to_string());
+        };
+        match request.header(PRINCIPAL_HEADER, carried(principal)).send().await {
+            Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND => None,
+            Ok(resp) if resp.status().is_success() => {
+                let body: serde_json::Value = resp.json().await.unwrap_or_default();
+                Some(body["state"].as_str().unwrap_or("unknown").to_string())
+            }
+            _ => Some("unreachable".to_string()),
         }
-        count
+    }
+
+    /// Record `txn` and durably write the records still needed for
+    /// recovery — those not yet finished — to the state file, if any.
+    async fn save(&self, txn: &GlobalTransaction) -> Result<(), TransactionError> {
+        let mut transactions = self.transactions.lock().await;
+        transactions.insert(txn.id.clone(), txn.clone());
+        if txn.is_finished() {
+            forget_finished(&mut transactions);
+        }
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let persistence = |e: &dyn std::fmt::Display| TransactionError::Coordinator(format!("{}: {}", path.display(), e));
+        let unfinished: BTreeMap<&String, &GlobalTransaction> =
+            transactions.iter().filter(|(_, t)| !t.is_finished()).collect();
+        let json = serde_json::to_vec_pretty(&unfinished).map_err(|e| persistence(&e))?;
+        crate::write_durably(path, &json).map_err(|e| persistence(&e))
+    }
+}
+
+/// [`PRINCIPAL_HEADER`]'s value for a transaction run for `principal`: as
+/// locally, calls outside any scope are made for the anonymous principal
+fn carried(principal: &Option<Principal>) -> String {
+    encode_principal(principal.as_ref().unwrap_or(&Principal::anonymous()))
+}
+
+/// Finished distributed transactions remembered for listing
+pub const FINISHED_KEPT: usize = 1000;
+
+/// Forget the oldest finished transactions beyond [`FINISHED_KEPT`]
+fn forget_finished(transactions: &mut BTreeMap<String, GlobalTransaction>) {
+    let mut finished: Vec<(String, String)> = transactions
+        .values()
+        .filter(|t| t.is_finished())
+        .map(|t| (t.completed_at.clone().unwrap_or_default(), t.id.clone()))
+        .collect();
+    if finished.len() <= FINISHED_KEPT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in &finished[..finished.len() - FINISHED_KEPT] {
+        transactions.remove(id);
     }
 }
 
@@ -355,6 +1781,20 @@ pub async fn cleanup_expired(&self) -> usize {
 mod tests {
     use super::*;
 
+    /// Commit, applying nothing and returning the operations.
+    async fn commit(mgr: &TransactionManager, txn_id: &TransactionId) -> Result<Vec<BufferedOperation>, TransactionError> {
+        mgr.commit(txn_id, |txn| async move { Ok(txn.operations) }).await
+    }
+
+    fn op(entity_id: &str, operation: OperationType) -> BufferedOperation {
+        BufferedOperation {
+            entity_id: entity_id.to_string(),
+            operation,
+            payload: b"{}".to_vec(),
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
     #[tokio::test]
     async fn test_begin_commit() {
         let mgr = TransactionManager::new(TransactionConfig::default());
@@ -381,7 +1821,7 @@ async fn test_begin_commit() {
         assert_eq!(status.operation_count, 1);
 
         // Commit
-        let ops = mgr.commit(&txn_id).await.unwrap();
+        let ops = commit(&mgr, &txn_id).await.unwrap();
         assert_eq!(ops.len(), 1);
 
         let status = mgr.status(&txn_id).await.unwrap();
@@ -417,8 +1857,8 @@ async fn test_begin_rollback() {
     async fn test_double_commit_fails() {
         let mgr = TransactionManager::new(TransactionConfig::default());
         let txn_id = mgr.begin().await.unwrap();
-        mgr.commit(&txn_id).await.unwrap();
-        let result = mgr.commit(&txn_id).await;
+        commit(&mgr, &txn_id).await.unwrap();
+        let result = commit(&mgr, &txn_id).await;
         assert!(result.is_err());
     }
 
@@ -426,7 +1866,7 @@ async fn test_double_commit_fails() {
     async fn test_operation_on_committed_fails() {
         let mgr = TransactionManager::new(TransactionConfig::default());
         let txn_id = mgr.begin().await.unwrap();
-        mgr.commit(&txn_id).await.unwrap();
+        commit(&mgr, &txn_id).await.unwrap();
 
         let result = mgr
             .buffer_operation(
@@ -447,6 +1887,8 @@ async fn test_max_concurrent_transactions() {
         let mgr = TransactionManager::new(TransactionConfig {
             max_concurrent: 2,
             timeout_seconds: 300,
+            idle_timeout_seconds: 60,
+            max_timeout_seconds: 86_400,
         });
 
         let _t1 = mgr.begin().await.unwrap();
@@ -460,7 +1902,361 @@ async fn test_not_found() {
         let mgr = TransactionManager::new(TransactionConfig::default());
         let fake_id = TransactionId("nonexistent".to_string());
         assert!(mgr.status(&fake_id).await.is_err());
-        assert!(mgr.commit(&fake_id).await.is_err());
+        assert!(commit(&mgr, &fake_id).await.is_err());
         assert!(mgr.rollback(&fake_id).await.is_err());
     }
+
+    #[tokio::test]
+    async fn test_failed_apply_rolls_back() {
+        let mgr = TransactionManager::new(TransactionConfig::default());
+        let txn_id = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&txn_id, op("hex-004", OperationType::Update)).await.unwrap();
+
+        let result: Result<(), TransactionError> = mgr
+            .commit(&txn_id, |_| async { Err(TransactionError::NotActive("apply failed".to_string())) })
+            .await;
+        assert!(result.is_err());
+        let status = mgr.status(&txn_id).await.unwrap();
+        assert_eq!(status.state, TransactionState::RolledBack);
+        assert!(status.completed_at.is_some());
+        assert!(mgr.buffer_operation(&txn_id, op("hex-004", OperationType::Delete)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_writes_operations_and_outcome_to_wal() {
+        let dir = std::env::temp_dir().join(format!("verisimdb-txn-wal-{}", uuid::Uuid::new_v4()));
+        let mgr = TransactionManager::new(TransactionConfig::default())
+            .with_wal(&dir, SyncMode::Fsync)
+            .unwrap();
+
+        let committed = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&committed, op("", OperationType::Create)).await.unwrap();
+        mgr.buffer_operation(&committed, op("hex-005", OperationType::Delete)).await.unwrap();
+        commit(&mgr, &committed).await.unwrap();
+
+        let aborted = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&aborted, op("hex-006", OperationType::Update)).await.unwrap();
+        let result: Result<(), TransactionError> = mgr
+            .commit(&aborted, |_| async { Err(TransactionError::TooManyTransactions) })
+            .await;
+        assert!(result.is_err());
+
+        let entries: Vec<WalEntry> = verisim_hexad::WalReader::open(&dir).unwrap().replay_all().unwrap().collect();
+        let summary: Vec<(WalOperation, &str, &[u8])> = entries
+            .iter()
+            .map(|e| (e.operation, e.entity_id.as_str(), e.payload.as_slice()))
+            .filter(|(operation, _, _)| *operation == WalOperation::Checkpoint)
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (WalOperation::Checkpoint, committed.as_str(), b"COMMITTED".as_slice()),
+                (WalOperation::Checkpoint, aborted.as_str(), b"ABORTED".as_slice()),
+            ]
+        );
+        let logged: BufferedOperation = serde_json::from_slice(&entries[1].payload).unwrap();
+        assert_eq!(entries[1].operation, WalOperation::Delete);
+        assert_eq!(logged.entity_id, "hex-005");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_read_view_returns_buffered_operations_while_active() {
+        let mgr = TransactionManager::new(TransactionConfig::default());
+        let txn_id = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&txn_id, op("hex-007", OperationType::Update)).await.unwrap();
+        mgr.buffer_operation(&txn_id, op("hex-007", OperationType::Delete)).await.unwrap();
+
+        let (snapshot, ops) = mgr.read_view(&txn_id).await.unwrap();
+        assert!(snapshot.is_none());
+        assert_eq!(ops.len(), 2);
+        assert!(mgr.status(&txn_id).await.unwrap().snapshot_at.is_none());
+
+        commit(&mgr, &txn_id).await.unwrap();
+        assert!(matches!(mgr.read_view(&txn_id).await, Err(TransactionError::AlreadyCommitted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_commit_sees_read_and_write_sets() {
+        let mgr = TransactionManager::new(TransactionConfig::default());
+        let txn_id = mgr.begin().await.unwrap();
+        mgr.record_reads(&txn_id, ["hex-008".to_string(), "hex-009".to_string()]).await.unwrap();
+        mgr.buffer_operation(&txn_id, op("hex-009", OperationType::Update)).await.unwrap();
+        mgr.buffer_operation(&txn_id, op("", OperationType::Create)).await.unwrap();
+
+        let (read_set, write_set) = mgr
+            .commit(&txn_id, |txn| async move { Ok::<_, TransactionError>((txn.read_set.clone(), txn.write_set())) })
+            .await
+            .unwrap();
+        assert_eq!(read_set.into_iter().collect::<Vec<_>>(), vec!["hex-008", "hex-009"]);
+        assert_eq!(write_set.into_iter().collect::<Vec<_>>(), vec!["hex-009"]);
+        assert!(mgr.record_reads(&txn_id, ["hex-010".to_string()]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_idle_and_absolute_timeouts_expire_transactions() {
+        let mgr = TransactionManager::new(TransactionConfig::default());
+        let txn_id = mgr.begin().await.unwrap();
+        let status = mgr.status(&txn_id).await.unwrap();
+        assert_eq!(status.isolation, IsolationLevel::ReadCommitted);
+        let started = parse_time(&status.started_at).unwrap();
+        let expires = parse_time(status.expires_at.as_deref().unwrap()).unwrap();
+        assert_eq!((expires - started).num_seconds(), 60);
+
+        let idle = TransactionOptions {
+            idle_timeout_secs: Some(0),
+            ..Default::default()
+        };
+        let idle_id = mgr.begin_with(idle, None).await.unwrap();
+        let absolute = TransactionOptions {
+            timeout_secs: Some(0),
+            ..Default::default()
+        };
+        let absolute_id = mgr.begin_with(absolute, None).await.unwrap();
+
+        assert!(matches!(
+            mgr.buffer_operation(&idle_id, op("hex-011", OperationType::Delete)).await,
+            Err(TransactionError::Expired(_))
+        ));
+        let status = mgr.status(&idle_id).await.unwrap();
+        assert_eq!(status.state, TransactionState::Expired);
+        assert!(status.expires_at.is_none());
+
+        assert_eq!(mgr.cleanup_expired().await, 1);
+        assert_eq!(mgr.status(&absolute_id).await.unwrap().state, TransactionState::Expired);
+        let stats = mgr.stats().await;
+        assert_eq!((stats.active, stats.expired, stats.expired_total), (1, 2, 2));
+        assert!(commit(&mgr, &absolute_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_saves_only_unacknowledged_decisions() {
+        let path = std::env::temp_dir().join(format!("verisimdb-2pc-{}.json", uuid::Uuid::new_v4()));
+        let coordinator = Coordinator::new().with_persistence(&path).unwrap();
+        let txn = |id: String, state: GlobalState| GlobalTransaction {
+            id,
+            state,
+            participants: Vec::new(),
+            started_at: Utc::now().to_rfc3339(),
+            completed_at: Some(Utc::now().to_rfc3339()),
+            error: None,
+            principal: None,
+        };
+        coordinator.save(&txn("told".to_string(), GlobalState::Committing)).await.unwrap();
+        coordinator.save(&txn("untold".to_string(), GlobalState::Committing)).await.unwrap();
+        coordinator.save(&txn("told".to_string(), GlobalState::Committed)).await.unwrap();
+
+        let saved: BTreeMap<String, GlobalTransaction> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.keys().collect::<Vec<_>>(), vec!["untold"]);
+        assert_eq!(coordinator.get("told").await.unwrap().state, GlobalState::Committed);
+
+        for i in 0..FINISHED_KEPT {
+            coordinator.save(&txn(format!("done-{i:04}"), GlobalState::Aborted)).await.unwrap();
+        }
+        assert_eq!(coordinator.list().await.len(), FINISHED_KEPT + 1);
+        assert!(coordinator.get("told").await.is_none());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_are_bounded() {
+        let mgr = TransactionManager::new(TransactionConfig::default());
+        let too_long = TransactionOptions {
+            timeout_secs: Some(u64::MAX),
+            ..Default::default()
+        };
+        assert!(matches!(
+            mgr.begin_with(too_long.clone(), None).await,
+            Err(TransactionError::InvalidTimeout { requested: u64::MAX, max: 86_400 })
+        ));
+        assert_eq!(mgr.stats().await.active, 0);
+
+        // Deadlines past the end of time are never reached, and do not
+        // stop the manager judging its transactions
+        let mgr = TransactionManager::new(TransactionConfig {
+            max_timeout_seconds: u64::MAX,
+            ..Default::default()
+        });
+        let forever = TransactionOptions {
+            idle_timeout_secs: Some(u64::MAX),
+            ..too_long
+        };
+        let txn_id = mgr.begin_with(forever, None).await.unwrap();
+        assert!(mgr.status(&txn_id).await.unwrap().expires_at.is_none());
+        mgr.begin().await.unwrap();
+        assert_eq!(mgr.cleanup_expired().await, 0);
+        assert_eq!(mgr.stats().await.active, 2);
+    }
+
+    #[tokio::test]
+    async fn test_locks_block_writers_until_released() {
+        let mgr = Arc::new(TransactionManager::new(TransactionConfig::default()));
+        let wait = std::time::Duration::from_millis(20);
+        let a = mgr.begin().await.unwrap();
+        let b = mgr.begin().await.unwrap();
+
+        // Shared locks are compatible; neither holder may then write.
+        mgr.acquire_lock(&a, "hex-012", LockMode::Shared, wait).await.unwrap();
+        mgr.acquire_lock(&b, "hex-012", LockMode::Shared, wait).await.unwrap();
+        assert!(matches!(
+            mgr.buffer_operation(&a, op("hex-012", OperationType::Update)).await,
+            Err(TransactionError::Locked { .. })
+        ));
+        assert!(matches!(
+            mgr.acquire_lock(&a, "hex-012", LockMode::Exclusive, wait).await,
+            Err(TransactionError::LockTimeout { holders, .. }) if holders == vec![b.0.clone()]
+        ));
+        mgr.rollback(&b).await.unwrap();
+
+        // Upgrade, then hold exclusively while another transaction waits.
+        mgr.acquire_lock(&a, "hex-012", LockMode::Exclusive, wait).await.unwrap();
+        mgr.buffer_operation(&a, op("hex-012", OperationType::Update)).await.unwrap();
+        let c = mgr.begin().await.unwrap();
+        let waiter = {
+            let (mgr, c) = (mgr.clone(), c.clone());
+            tokio::spawn(async move {
+                mgr.acquire_lock(&c, "hex-012", LockMode::Exclusive, std::time::Duration::from_secs(5))
+                    .await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let status = mgr.status(&c).await.unwrap();
+        let waiting = status.waiting_for.unwrap();
+        assert_eq!((waiting.entity_id.as_str(), waiting.blocked_by), ("hex-012", vec![a.0.clone()]));
+        let status = mgr.status(&a).await.unwrap();
+        assert_eq!(
+            status.locks,
+            vec![HeldLock { entity_id: "hex-012".to_string(), mode: LockMode::Exclusive }]
+        );
+
+        commit(&mgr, &a).await.unwrap();
+        waiter.await.unwrap().unwrap();
+        let status = mgr.status(&c).await.unwrap();
+        assert!(status.waiting_for.is_none());
+        assert_eq!(status.locks.len(), 1);
+        assert!(mgr.status(&a).await.unwrap().locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deadlock_is_detected_and_refused() {
+        let mgr = Arc::new(TransactionManager::new(TransactionConfig::default()));
+        let wait = std::time::Duration::from_secs(5);
+        let a = mgr.begin().await.unwrap();
+        let b = mgr.begin().await.unwrap();
+        mgr.acquire_lock(&a, "hex-013", LockMode::Exclusive, wait).await.unwrap();
+        mgr.acquire_lock(&b, "hex-014", LockMode::Exclusive, wait).await.unwrap();
+
+        let waiter = {
+            let (mgr, a) = (mgr.clone(), a.clone());
+            tokio::spawn(async move { mgr.acquire_lock(&a, "hex-014", LockMode::Exclusive, wait).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        match mgr.acquire_lock(&b, "hex-013", LockMode::Exclusive, wait).await {
+            Err(TransactionError::Deadlock { entity_id, cycle }) => {
+                assert_eq!(entity_id, "hex-013");
+                assert_eq!(cycle, vec![b.0.clone(), a.0.clone()]);
+            }
+            other => panic!("expected deadlock, got {:?}", other),
+        }
+        assert!(mgr.status(&b).await.unwrap().waiting_for.is_none());
+
+        // The refused transaction gives up its locks; the other proceeds.
+        mgr.rollback(&b).await.unwrap();
+        waiter.await.unwrap().unwrap();
+        assert_eq!(mgr.status(&a).await.unwrap().locks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recover_finds_transactions_without_an_outcome() {
+        let dir = std::env::temp_dir().join(format!("verisimdb-txn-wal-{}", uuid::Uuid::new_v4()));
+        let mgr = TransactionManager::new(TransactionConfig::default())
+            .with_wal(&dir, SyncMode::Async)
+            .unwrap();
+
+        let committed = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&committed, op("hex-015", OperationType::Delete)).await.unwrap();
+        commit(&mgr, &committed).await.unwrap();
+
+        // Crashed after its commit point and first write; and before it
+        let in_doubt = TransactionId::new();
+        let ops = vec![op("hex-016", OperationType::Update), op("", OperationType::Create)];
+        mgr.wal_append_operations(&in_doubt, &ops).await.unwrap();
+        mgr.log_commit_point(&in_doubt).await.unwrap();
+        mgr.log_applied(&in_doubt, 0, "hex-016").await.unwrap();
+        let uncommitted = TransactionId::new();
+        mgr.wal_append_operations(&uncommitted, &ops[..1]).await.unwrap();
+        drop(mgr);
+
+        let mgr = TransactionManager::new(TransactionConfig::default())
+            .with_wal(&dir, SyncMode::Fsync)
+            .unwrap();
+        let recovered = mgr.recover().unwrap();
+        assert_eq!(recovered.len(), 2);
+        assert_eq!(recovered[0].id, in_doubt);
+        assert!(recovered[0].committing);
+        assert_eq!(recovered[0].operations.len(), 2);
+        assert_eq!(recovered[0].applied, BTreeMap::from([(0, "hex-016".to_string())]));
+        assert_eq!(recovered[1].id, uncommitted);
+        assert!(!recovered[1].committing && recovered[1].applied.is_empty());
+
+        mgr.finish_recovered(&in_doubt, true).await.unwrap();
+        mgr.finish_recovered(&uncommitted, false).await.unwrap();
+        assert!(mgr.recover().unwrap().is_empty());
+        assert!(TransactionManager::new(TransactionConfig::default()).recover().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_prepared_transaction_holds_locks_and_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("verisimdb-txn-wal-{}", uuid::Uuid::new_v4()));
+        let open = || {
+            TransactionManager::new(TransactionConfig::default())
+                .with_wal(&dir, SyncMode::Fsync)
+                .unwrap()
+        };
+        let mgr = open();
+        let prepared = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&prepared, op("hex-017", OperationType::Update)).await.unwrap();
+        let validate = |_| async { Ok::<(), TransactionError>(()) };
+        mgr.prepare(&prepared, validate).await.unwrap();
+        mgr.prepare(&prepared, validate).await.unwrap();
+        let status = mgr.status(&prepared).await.unwrap();
+        assert_eq!(status.state, TransactionState::Prepared);
+        assert!(status.expires_at.is_none());
+        assert_eq!(status.locks, vec![HeldLock { entity_id: "hex-017".to_string(), mode: LockMode::Exclusive }]);
+        let other = mgr.begin().await.unwrap();
+        assert!(matches!(
+            mgr.buffer_operation(&other, op("hex-017", OperationType::Delete)).await,
+            Err(TransactionError::Locked { .. })
+        ));
+
+        // A failed check rolls back and unlocks; a prepared rollback is logged
+        mgr.buffer_operation(&other, op("hex-018", OperationType::Delete)).await.unwrap();
+        let result = mgr.prepare(&other, |_| async { Err(TransactionError::Conflict(vec!["hex-018".to_string()])) }).await;
+        assert!(result.is_err());
+        assert_eq!(mgr.status(&other).await.unwrap().state, TransactionState::RolledBack);
+        let aborted = mgr.begin().await.unwrap();
+        mgr.buffer_operation(&aborted, op("hex-018", OperationType::Delete)).await.unwrap();
+        mgr.prepare(&aborted, validate).await.unwrap();
+        mgr.rollback(&aborted).await.unwrap();
+        assert!(mgr.status(&aborted).await.unwrap().locks.is_empty());
+        drop(mgr);
+
+        // After a restart it is restored, still prepared, and can commit
+        let mgr = open();
+        let recovered = mgr.recover().unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert!(recovered[0].prepared && !recovered[0].committing);
+        mgr.restore_prepared(recovered.into_iter().next().unwrap()).await.unwrap();
+        assert_eq!(mgr.status(&prepared).await.unwrap().locks.len(), 1);
+        let ops = commit(&mgr, &prepared).await.unwrap();
+        assert_eq!(ops.len(), 1);
+        assert!(mgr.status(&prepared).await.unwrap().locks.is_empty());
+        assert!(open().recover().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
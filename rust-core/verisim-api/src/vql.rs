@@ -12,30 +12,52 @@
 //! ## Supported VQL Statements
 //!
 //! - `SELECT [modalities] FROM hexads [WHERE id = '...'] [LIMIT n]`
+//! - `SELECT * FROM hexads [WHERE id = '...'] AS OF '<timestamp>'`
+//! - `SELECT * FROM hexads WHERE id = '...' VERSIONS BETWEEN '<start>' AND '<end>'`
+//! - `SELECT * FROM hexads [WHERE field = value] SIMILAR TO <vector> [USING '<model>'] [LIMIT k]`
+//! - `SELECT COUNT(*), AVG(version), ... FROM hexads [WHERE field = value] [GROUP BY fields] [LIMIT n]`
 //! - `SEARCH TEXT '<query>' [LIMIT n]`
 //! - `SEARCH VECTOR [v1, v2, ...] [LIMIT n]`
 //! - `SEARCH RELATED '<id>' [BY '<predicate>']`
+//! - `MATCH <patterns> [WHERE text ~ '<query>'] [ORDER BY similarity(x.embedding, <vector>)] [LIMIT n]`
 //! - `INSERT INTO hexads (fields...) VALUES (values...)`
-//! - `DELETE FROM hexads WHERE id = '<id>'`
+//! - `INSERT HEXAD {title: '...', body: '...', ...}`
+//! - `UPDATE HEXAD '<id>' SET {...}` / `UPDATE hexads SET {...} WHERE field = value`
+//! - `DELETE HEXAD '<id>'` / `DELETE FROM hexads WHERE field = value`
 //! - `SHOW STATUS` / `SHOW DRIFT` / `SHOW NORMALIZER`
 //! - `SHOW HEXADS [LIMIT n]`
 //! - `COUNT hexads`
 //! - `EXPLAIN <query>`
+//! - `SUBSCRIBE [SELECT * FROM hexads] [WHERE field = value [AND ...]]` / `UNSUBSCRIBE '<id>'`
 
-use axum::{extract::State, Json};
+use axum::{extract::State, http::HeaderMap, Extension, Json};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
-use verisim_hexad::{HexadId, HexadInput, HexadDocumentInput, HexadStore};
+use verisim_hexad::{HexadId, HexadInput, HexadDocumentInput, HexadStore, ModalityMask};
+use verisim_planner::plan::{ConditionKind, PlanNode, PostProcessing, QuerySource};
+use verisim_planner::{Join, LogicalPlan, Modality, PhysicalPlan, QueryHints};
 
-use crate::{ApiError, AppState, HexadResponse};
+use verisim_provenance::ActorIdentity;
+use verisim_spatial::TrajectoryStore;
+
+use crate::transaction::{BufferedOperation, OperationType, Transaction, TransactionError, TransactionId};
+use crate::executor::PlanExecutor;
+use crate::{attribute_actor, queries, ApiError, AppState, HexadRequest, HexadResponse};
 
 /// VQL execute request — wraps a raw VQL query string.
 #[derive(Debug, Deserialize)]
 pub struct VqlExecuteRequest {
     /// The VQL query text to parse and execute.
     pub query: String,
+    /// Values for `$name` parameters in the query.
+    #[serde(default)]
+    pub params: std::collections::HashMap<String, Value>,
+    /// Open transaction to buffer INSERT, UPDATE and DELETE writes in,
+    /// instead of applying them at once.
+    #[serde(default)]
+    pub transaction: Option<String>,
 }
 
 /// VQL execute response — returns structured results from a query.
@@ -58,9 +80,11 @@ pub struct VqlExecuteResponse {
 ///
 /// Parses the query, determines the operation, executes it against the
 /// hexad store, and returns structured results.
-#[instrument(skip(state, request), fields(query = %request.query))]
+#[instrument(skip(state, actor, request), fields(query = %request.query))]
 pub async fn vql_execute_handler(
     State(state): State<AppState>,
+    actor: Option<Extension<ActorIdentity>>,
+    headers: HeaderMap,
     Json(request): Json<VqlExecuteRequest>,
 ) -> Result<Json<VqlExecuteResponse>, ApiError> {
     let query = request.query.trim();
@@ -69,28 +93,45 @@ pub async fn vql_execute_handler(
         return Err(ApiError::BadRequest("Empty query".to_string()));
     }
 
+    // A statement sent as part of a two-phase commit runs in the
+    // transaction its call was admitted for.
+    if let Some(bound) = headers.get(crate::peer_auth::TRANSACTION_HEADER) {
+        if request.transaction.as_deref().map(str::as_bytes) != Some(bound.as_bytes()) {
+            return Err(ApiError::BadRequest(
+                "Statement is not in the transaction its call names".to_string(),
+            ));
+        }
+    }
+
     // Normalize: strip trailing semicolons, collapse whitespace.
     let query = query.trim_end_matches(';').trim();
 
+    // A `SET TIMEOUT <n>;` prefix overrides the request header's deadline.
+    let (timeout, query) = queries::parse_set_timeout(query)?;
+    let timeout = match timeout {
+        Some(timeout) => Some(timeout),
+        None => queries::header_timeout(&headers, state.config.default_query_timeout_ms)?,
+    };
+
+    // Optimizer hints (`/*+ ... */`) apply to the statements the planner
+    // runs: MATCH and SELECT ... SIMILAR TO, and EXPLAIN of them.
+    let (hints, query) = QueryHints::extract(query).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let query = query.trim();
+
     // Parse and route the query.
     let tokens = tokenize(query);
     if tokens.is_empty() {
         return Err(ApiError::BadRequest("Empty query after parsing".to_string()));
     }
 
-    let result = match tokens[0].to_uppercase().as_str() {
-        "SELECT" => execute_select(&state, &tokens, query).await,
-        "SEARCH" => execute_search(&state, &tokens).await,
-        "INSERT" => execute_insert(&state, query).await,
-        "DELETE" => execute_delete(&state, &tokens).await,
-        "SHOW" => execute_show(&state, &tokens).await,
-        "COUNT" => execute_count(&state, &tokens).await,
-        "EXPLAIN" => execute_explain(&state, &tokens, query).await,
-        other => Err(ApiError::BadRequest(format!(
-            "Unknown VQL statement: '{}'. Supported: SELECT, SEARCH, INSERT, DELETE, SHOW, COUNT, EXPLAIN",
-            other
-        ))),
-    }?;
+    // Writes are not dropped mid-statement; they stop at a checkpoint and
+    // undo what they applied.
+    let statement = tokens[0].to_uppercase();
+    let abortable = !matches!(statement.as_str(), "INSERT" | "UPDATE" | "DELETE");
+    let result = state
+        .active_queries
+        .run(query, timeout, abortable, dispatch(&state, &statement, &tokens, query, &hints, &request, actor.as_deref()))
+        .await?;
 
     info!(
         statement_type = %result.statement_type,
@@ -101,6 +142,38 @@ pub async fn vql_execute_handler(
     Ok(Json(result))
 }
 
+/// Run one VQL statement.
+async fn dispatch(
+    state: &AppState,
+    statement: &str,
+    tokens: &[String],
+    query: &str,
+    hints: &QueryHints,
+    request: &VqlExecuteRequest,
+    actor: Option<&ActorIdentity>,
+) -> Result<VqlExecuteResponse, ApiError> {
+    match statement {
+        "SELECT" => execute_select(state, tokens, hints, &request.params).await,
+        "SEARCH" => execute_search(state, tokens).await,
+        "MATCH" => execute_match(state, tokens, hints, &request.params).await,
+        "INSERT" if find_map_start(query).is_none() => execute_insert(state, query).await,
+        "INSERT" | "UPDATE" | "DELETE" => {
+            let transaction = request.transaction.as_deref();
+            execute_mutation(state, query, &request.params, transaction, actor).await
+        }
+        "SHOW" => execute_show(state, tokens).await,
+        "COUNT" => execute_count(state, tokens).await,
+        "EXPLAIN" => execute_explain(state, tokens, query, hints).await,
+        "ANALYZE" => execute_analyze(state, tokens).await,
+        "SUBSCRIBE" => execute_subscribe(state, query, &request.params),
+        "UNSUBSCRIBE" => execute_unsubscribe(state, tokens),
+        other => Err(ApiError::BadRequest(format!(
+            "Unknown VQL statement: '{}'. Supported: SELECT, SEARCH, MATCH, INSERT, UPDATE, DELETE, SHOW, COUNT, EXPLAIN, ANALYZE, SUBSCRIBE, UNSUBSCRIBE",
+            other
+        ))),
+    }
+}
+
 /// Tokenize a VQL query into whitespace-separated tokens, respecting
 /// quoted strings (single and double quotes).
 fn tokenize(input: &str) -> Vec<String> {
@@ -170,11 +243,37 @@ fn parse_limit(tokens: &[String]) -> (usize, usize) {
 /// - `SELECT * FROM hexads` — list all hexads
 /// - `SELECT * FROM hexads WHERE id = '<id>'` — get one hexad
 /// - `SELECT * FROM hexads LIMIT n` — list with limit
+/// - `SELECT <aggregates> FROM hexads ... [GROUP BY ...]` — see [`execute_aggregate`]
+/// - `... AS OF '<timestamp>'` / `... VERSIONS BETWEEN '<start>' AND '<end>'`
+///   — see [`execute_temporal`]
+/// - `... SIMILAR TO <vector> [USING '<model>']` — see [`execute_similar`]
 async fn execute_select(
     state: &AppState,
     tokens: &[String],
-    _raw: &str,
+    hints: &QueryHints,
+    params: &std::collections::HashMap<String, Value>,
 ) -> Result<VqlExecuteResponse, ApiError> {
+    let (stripped, temporal) = split_temporal(tokens)?;
+    let (stripped, similar) = split_similar(&stripped)?;
+    if (temporal.is_some() || similar.is_some()) && parse_aggregate(&stripped)?.is_some() {
+        return Err(ApiError::BadRequest(
+            "AS OF, VERSIONS BETWEEN and SIMILAR TO cannot be combined with aggregates".to_string(),
+        ));
+    }
+    match (temporal, similar) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::BadRequest(
+                "SIMILAR TO cannot be combined with AS OF or VERSIONS BETWEEN".to_string(),
+            ))
+        }
+        (Some(temporal), None) => return execute_temporal(state, &stripped, &temporal).await,
+        (None, Some(similar)) => return execute_similar(state, &stripped, &similar, hints, params).await,
+        (None, None) => {}
+    }
+    if let Some(query) = parse_aggregate(tokens)? {
+        return execute_aggregate(state, &query).await;
+    }
+
     let (limit, _) = parse_limit(tokens);
 
     // Check for WHERE id = '...'
@@ -203,7 +302,7 @@ async fn execute_select(
         // List hexads
         let hexads = state
             .hexad_store
-            .list(limit, 0)
+            .list_with(limit, 0, ModalityMask::SUMMARY)
             .await
             .map_err(|e| ApiError::Internal(e.to_string()))?;
 
@@ -221,6 +320,264 @@ async fn execute_select(
     }
 }
 
+/// A temporal clause on a SELECT.
+#[derive(Debug)]
+enum TemporalClause {
+    /// `AS OF '<timestamp>'`
+    AsOf(chrono::DateTime<chrono::Utc>),
+    /// `VERSIONS BETWEEN '<start>' AND '<end>'`
+    Between(verisim_hexad::TimeRange),
+}
+
+/// Parse a timestamp literal: RFC 3339, or a date for its midnight UTC.
+pub(crate) fn parse_timestamp(token: &str) -> Result<chrono::DateTime<chrono::Utc>, ApiError> {
+    let literal = unquote(token);
+    chrono::DateTime::parse_from_rfc3339(literal)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(literal, "%Y-%m-%d")
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        })
+        .map_err(|_| ApiError::BadRequest(format!("Invalid timestamp '{}': expected RFC 3339 or YYYY-MM-DD", literal)))
+}
+
+/// Remove an `AS OF` or `VERSIONS BETWEEN` clause from the tokens,
+/// returning the rest and the clause.
+fn split_temporal(tokens: &[String]) -> Result<(Vec<String>, Option<TemporalClause>), ApiError> {
+    let is_keyword = |i: usize, keyword: &str| tokens.get(i).is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+    let timestamp = |i: usize, clause: &str| {
+        tokens
+            .get(i)
+            .ok_or_else(|| ApiError::BadRequest(format!("{} requires a timestamp", clause)))
+            .and_then(|t| parse_timestamp(t))
+    };
+
+    for i in 1..tokens.len() {
+        let (clause, len) = if is_keyword(i, "AS") && is_keyword(i + 1, "OF") {
+            (TemporalClause::AsOf(timestamp(i + 2, "AS OF")?), 3)
+        } else if is_keyword(i, "VERSIONS") && is_keyword(i + 1, "BETWEEN") {
+            if !is_keyword(i + 3, "AND") {
+                return Err(ApiError::BadRequest(
+                    "VERSIONS BETWEEN requires: VERSIONS BETWEEN '<start>' AND '<end>'".to_string(),
+                ));
+            }
+            let range = verisim_hexad::TimeRange::new(
+                timestamp(i + 2, "VERSIONS BETWEEN")?,
+                timestamp(i + 4, "VERSIONS BETWEEN")?,
+            )
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            (TemporalClause::Between(range), 5)
+        } else {
+            continue;
+        };
+        let mut rest = tokens.to_vec();
+        rest.drain(i..i + len);
+        return Ok((rest, Some(clause)));
+    }
+    Ok((tokens.to_vec(), None))
+}
+
+/// Execute a SELECT with a temporal clause through the temporal store.
+///
+/// - `AS OF` with `WHERE id = ...` returns the entity as it was then;
+///   without it, every entity that existed then (up to LIMIT), as it was.
+/// - `VERSIONS BETWEEN` requires `WHERE id = ...` and returns each version
+///   recorded in the range, oldest first.
+async fn execute_temporal(
+    state: &AppState,
+    tokens: &[String],
+    clause: &TemporalClause,
+) -> Result<VqlExecuteResponse, ApiError> {
+    let store = &state.hexad_store;
+    let where_id = find_where_id(tokens).map(HexadId::new);
+    let (hexads, message) = match (clause, where_id) {
+        (TemporalClause::AsOf(time), Some(id)) => {
+            let hexad = store
+                .at_time(&id, *time)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?
+                .ok_or_else(|| ApiError::NotFound(format!("Hexad '{}' did not exist at {}", id, time.to_rfc3339())))?;
+            (vec![hexad], format!("As of {}", time.to_rfc3339()))
+        }
+        (TemporalClause::AsOf(time), None) => {
+            let (limit, _) = parse_limit(tokens);
+            let mut hexads = Vec::new();
+            let mut offset = 0;
+            'scan: loop {
+                queries::checkpoint()?;
+                let page = store
+                    .list_with(AGGREGATE_PAGE_SIZE, offset, ModalityMask::STATUS)
+                    .await
+                    .map_err(|e| ApiError::Internal(e.to_string()))?;
+                for current in &page {
+                    if current.status.created_at > *time {
+                        continue;
+                    }
+                    if let Some(hexad) = store
+                        .at_time(&current.id, *time)
+                        .await
+                        .map_err(|e| ApiError::Internal(e.to_string()))?
+                    {
+                        hexads.push(hexad);
+                        if hexads.len() == limit {
+                            break 'scan;
+                        }
+                    }
+                }
+                if page.len() < AGGREGATE_PAGE_SIZE {
+                    break;
+                }
+                offset += page.len();
+            }
+            (hexads, format!("As of {}", time.to_rfc3339()))
+        }
+        (TemporalClause::Between(range), Some(id)) => {
+            let hexads = store
+                .versions_between(&id, range)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            let message = format!(
+                "{} versions between {} and {}",
+                hexads.len(),
+                range.start.to_rfc3339(),
+                range.end.to_rfc3339()
+            );
+            (hexads, message)
+        }
+        (TemporalClause::Between(_), None) => {
+            return Err(ApiError::BadRequest(
+                "VERSIONS BETWEEN requires WHERE id = '<id>'".to_string(),
+            ))
+        }
+    };
+
+    // Full entities, so the historical modality contents are visible
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "SELECT".to_string(),
+        row_count: hexads.len(),
+        data: serde_json::to_value(&hexads).map_err(|e| ApiError::Serialization(e.to_string()))?,
+        message: Some(message),
+    })
+}
+
+/// A `SIMILAR TO <vector> [USING '<model>']` clause on a SELECT.
+#[derive(Debug, PartialEq)]
+struct SimilarClause {
+    vector: VectorArg,
+    /// Only match embeddings produced by this model
+    model: Option<String>,
+}
+
+/// Remove a `SIMILAR TO` clause from the tokens, returning the rest and
+/// the clause.  The vector runs up to `USING`, `LIMIT` or the end.
+fn split_similar(tokens: &[String]) -> Result<(Vec<String>, Option<SimilarClause>), ApiError> {
+    let is_keyword = |i: usize, keyword: &str| tokens.get(i).is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+    let Some(at) = (1..tokens.len()).find(|&i| is_keyword(i, "SIMILAR") && is_keyword(i + 1, "TO")) else {
+        return Ok((tokens.to_vec(), None));
+    };
+    let vector_end = (at + 2..tokens.len())
+        .find(|&i| is_keyword(i, "USING") || is_keyword(i, "LIMIT"))
+        .unwrap_or(tokens.len());
+    let vector = tokens[at + 2..vector_end].join(" ");
+    let vector = match vector.strip_prefix('$') {
+        Some(name) if is_variable(name) => VectorArg::Param(name.to_string()),
+        _ if vector.is_empty() => {
+            return Err(ApiError::BadRequest(
+                "SIMILAR TO requires a vector: SIMILAR TO [v1, ...] or SIMILAR TO $name".to_string(),
+            ))
+        }
+        _ => VectorArg::Literal(parse_vector(&vector)?),
+    };
+    let (model, end) = if is_keyword(vector_end, "USING") {
+        let model = tokens
+            .get(vector_end + 1)
+            .ok_or_else(|| ApiError::BadRequest("USING requires a model name".to_string()))?;
+        (Some(unquote(model).to_string()), vector_end + 2)
+    } else {
+        (None, vector_end)
+    };
+
+    let mut rest = tokens.to_vec();
+    rest.drain(at..end);
+    Ok((rest, Some(SimilarClause { vector, model })))
+}
+
+/// Conditions and result count of a SELECT ... SIMILAR TO.  `k` defaults
+/// to 10.
+fn similar_conditions(tokens: &[String]) -> Result<(Vec<(String, String)>, usize), ApiError> {
+    let (limit, limit_at) = parse_limit(tokens);
+    let k = if limit_at < tokens.len() { limit } else { 10 };
+    let conditions = match tokens.iter().position(|t| t.eq_ignore_ascii_case("WHERE")) {
+        Some(at) => parse_conditions(&tokens[at + 1..limit_at.max(at + 1)])?,
+        None => Vec::new(),
+    };
+    Ok((conditions, k))
+}
+
+/// Lower a SELECT ... SIMILAR TO to a vector index scan for `k` results
+/// with the conditions (and model) as post-filters.
+fn similar_plan(conditions: &[(String, String)], model: Option<&str>, k: usize) -> LogicalPlan {
+    let mut node_conditions = vec![ConditionKind::Similarity { k }];
+    node_conditions.extend(
+        conditions
+            .iter()
+            .map(|(field, value)| (field.as_str(), value.as_str()))
+            .chain(model.map(|model| ("model", model)))
+            .map(|(field, value)| ConditionKind::Equality {
+                field: field.to_string(),
+                value: value.to_string(),
+            }),
+    );
+    LogicalPlan {
+        source: QuerySource::Hexad,
+        nodes: vec![PlanNode {
+            modality: Modality::Vector,
+            conditions: node_conditions,
+            projections: vec![],
+            early_limit: Some(k),
+        }],
+        post_processing: vec![PostProcessing::Limit { count: k }],
+        joins: vec![],
+        hints: QueryHints::default(),
+    }
+}
+
+/// Execute a SELECT ... SIMILAR TO.
+///
+/// Runs the plan from [`similar_plan`]: the vector index scan applies the
+/// WHERE conditions and the USING model to its hits, widening until `k`
+/// hits pass or the index runs out, so the filters never starve the
+/// result.
+async fn execute_similar(
+    state: &AppState,
+    tokens: &[String],
+    clause: &SimilarClause,
+    hints: &QueryHints,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<VqlExecuteResponse, ApiError> {
+    let (conditions, k) = similar_conditions(tokens)?;
+    let vector = resolve_vector(state, &clause.vector, params)?;
+    let plan = LogicalPlan {
+        hints: hints.clone(),
+        ..similar_plan(&conditions, clause.model.as_deref(), k)
+    };
+    let physical = optimize_plan(state, &plan)?;
+    let execution = PlanExecutor::new(state)
+        .with_vector(vector)
+        .with_query(tokens.join(" "))
+        .execute(&plan, physical)
+        .await?;
+
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "SELECT".to_string(),
+        row_count: execution.row_count,
+        data: json!(execution.rows),
+        message: None,
+    })
+}
+
 /// Find `WHERE id = '<value>'` in token list.
 fn find_where_id<'a>(tokens: &'a [String]) -> Option<&'a str> {
     for (i, token) in tokens.iter().enumerate() {
@@ -238,6 +595,489 @@ fn find_where_id<'a>(tokens: &'a [String]) -> Option<&'a str> {
     None
 }
 
+// ---------------------------------------------------------------------------
+// Aggregation
+// ---------------------------------------------------------------------------
+
+/// Page size for the full scan behind an aggregate query.
+pub(crate) const AGGREGATE_PAGE_SIZE: usize = 1000;
+
+/// An aggregate function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(Self::Count),
+            "SUM" => Some(Self::Sum),
+            "AVG" => Some(Self::Avg),
+            "MIN" => Some(Self::Min),
+            "MAX" => Some(Self::Max),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+/// One item of an aggregate query's select list.
+#[derive(Debug, PartialEq)]
+enum SelectItem {
+    /// A grouping field
+    Field { field: String, alias: Option<String> },
+    /// `FUNC(field)`, or `COUNT(*)` with no field
+    Aggregate {
+        function: AggregateFunction,
+        field: Option<String>,
+        alias: Option<String>,
+    },
+}
+
+impl SelectItem {
+    /// Key of this item in each result row
+    fn column(&self) -> String {
+        match self {
+            SelectItem::Field { alias: Some(alias), .. } | SelectItem::Aggregate { alias: Some(alias), .. } => {
+                alias.clone()
+            }
+            SelectItem::Field { field, .. } => field.clone(),
+            SelectItem::Aggregate { function, field, .. } => {
+                format!("{}({})", function.name(), field.as_deref().unwrap_or("*"))
+            }
+        }
+    }
+}
+
+/// A parsed aggregate SELECT.
+#[derive(Debug, PartialEq)]
+struct AggregateQuery {
+    items: Vec<SelectItem>,
+    /// `field = value` conditions, all of which must hold
+    filters: Vec<(String, String)>,
+    group_by: Vec<String>,
+    limit: usize,
+}
+
+/// Fields aggregate queries can group, filter and aggregate over: entity
+/// attributes, values derived from the modalities, and `document.<key>`
+/// for a document field or metadata entry.
+const AGGREGATE_FIELDS: &[&str] = &[
+    "id",
+    "collection",
+    "version",
+    "created_at",
+    "modified_at",
+    "version_count",
+    "provenance_length",
+    "title",
+    "body_length",
+    "type",
+    "embedding_dim",
+    "tensor_size",
+    "has_graph",
+    "has_vector",
+    "has_tensor",
+    "has_semantic",
+    "has_document",
+    "has_temporal",
+    "has_provenance",
+    "has_spatial",
+];
+
+fn check_aggregate_field(field: &str) -> Result<(), ApiError> {
+    if AGGREGATE_FIELDS.contains(&field) || field.strip_prefix("document.").is_some_and(|key| !key.is_empty()) {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Unknown field '{}'. Use one of {} or document.<key>",
+            field,
+            AGGREGATE_FIELDS.join(", ")
+        )))
+    }
+}
+
+/// Parse an aggregate SELECT, or return `None` for a plain SELECT (one
+/// with neither aggregate functions nor GROUP BY).
+fn parse_aggregate(tokens: &[String]) -> Result<Option<AggregateQuery>, ApiError> {
+    let is_keyword = |i: usize, keyword: &str| tokens.get(i).is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+    let Some(from_at) = (1..tokens.len()).find(|&i| is_keyword(i, "FROM")) else {
+        return Ok(None);
+    };
+    let where_at = (from_at..tokens.len()).find(|&i| is_keyword(i, "WHERE"));
+    let group_at = (from_at..tokens.len()).find(|&i| is_keyword(i, "GROUP") && is_keyword(i + 1, "BY"));
+    let (limit, limit_at) = parse_limit(tokens);
+
+    let items = split_commas(&tokens[1..from_at].join(" "));
+    let has_aggregate = items.iter().any(|item| {
+        item.split_once('(')
+            .is_some_and(|(name, _)| AggregateFunction::parse(name.trim()).is_some())
+    });
+    if !has_aggregate && group_at.is_none() {
+        return Ok(None);
+    }
+
+    let mut select = Vec::new();
+    for item in items {
+        let words: Vec<&str> = item.split_whitespace().collect();
+        let (expression, alias) = match words.as_slice() {
+            [expression @ .., as_keyword, alias] if as_keyword.eq_ignore_ascii_case("AS") => {
+                (expression.join(" "), Some(alias.to_string()))
+            }
+            _ => (item.clone(), None),
+        };
+        let aggregate = expression.split_once('(').and_then(|(name, _)| {
+            let function = AggregateFunction::parse(name.trim())?;
+            let args = call_args(&expression, name.trim())?;
+            Some((function, args))
+        });
+        select.push(match aggregate {
+            Some((function, args)) => {
+                let field = match args.as_slice() {
+                    [star] if star == "*" && function == AggregateFunction::Count => None,
+                    [field] => {
+                        check_aggregate_field(field)?;
+                        Some(field.clone())
+                    }
+                    _ => {
+                        return Err(ApiError::BadRequest(format!(
+                            "{} takes one field{}",
+                            function.name().to_uppercase(),
+                            if function == AggregateFunction::Count { " or *" } else { "" }
+                        )))
+                    }
+                };
+                SelectItem::Aggregate { function, field, alias }
+            }
+            None => {
+                check_aggregate_field(&expression)?;
+                SelectItem::Field { field: expression, alias }
+            }
+        });
+    }
+
+    let clause_end = |start: usize| {
+        [where_at, group_at, Some(limit_at)]
+            .into_iter()
+            .flatten()
+            .filter(|&at| at > start)
+            .min()
+            .unwrap_or(tokens.len())
+    };
+
+    let filters = match where_at {
+        Some(at) => parse_conditions(&tokens[at + 1..clause_end(at)])?,
+        None => Vec::new(),
+    };
+
+    let group_by = match group_at {
+        Some(at) => split_commas(&tokens[at + 2..clause_end(at)].join(" ")),
+        None => Vec::new(),
+    };
+    for field in &group_by {
+        check_aggregate_field(field)?;
+    }
+    for item in &select {
+        if let SelectItem::Field { field, .. } = item {
+            if !group_by.contains(field) {
+                return Err(ApiError::BadRequest(format!(
+                    "'{}' must appear in GROUP BY or be used in an aggregate function",
+                    field
+                )));
+            }
+        }
+    }
+
+    Ok(Some(AggregateQuery {
+        items: select,
+        filters,
+        group_by,
+        limit,
+    }))
+}
+
+/// Parse `field = value [AND ...]` conditions over the aggregate fields.
+fn parse_conditions(tokens: &[String]) -> Result<Vec<(String, String)>, ApiError> {
+    split_and(tokens)
+        .into_iter()
+        .map(|condition| {
+            let (field, value) = condition.split_once('=').ok_or_else(|| {
+                ApiError::BadRequest(format!("Unsupported condition '{}'; expected field = value", condition))
+            })?;
+            let field = field.trim().to_string();
+            check_aggregate_field(&field)?;
+            Ok((field, unquote(value.trim()).to_string()))
+        })
+        .collect()
+}
+
+/// Split at commas outside quotes and parentheses, trimming each part.
+fn split_commas(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    for ch in s.chars() {
+        match ch {
+            '\'' | '"' if quote == Some(ch) => quote = None,
+            '\'' | '"' if quote.is_none() => quote = Some(ch),
+            '(' | '[' if quote.is_none() => depth += 1,
+            ')' | ']' if quote.is_none() => depth = depth.saturating_sub(1),
+            ',' if quote.is_none() && depth == 0 => {
+                parts.push(std::mem::take(&mut current).trim().to_string());
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    parts.push(current.trim().to_string());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Modalities a read must load to evaluate `fields`.
+pub(crate) fn aggregate_mask<'a>(fields: impl IntoIterator<Item = &'a str>) -> ModalityMask {
+    let mut mask = ModalityMask::STATUS;
+    for field in fields {
+        match field {
+            "title" | "body_length" => mask.document = true,
+            "type" => mask.semantic = true,
+            "embedding_dim" => mask.vector = true,
+            "tensor_size" => mask.tensor = true,
+            "version_count" => mask.temporal = true,
+            "provenance_length" => mask.provenance = true,
+            field if field.starts_with("document.") => mask.document = true,
+            _ => {}
+        }
+    }
+    mask
+}
+
+/// Value of `field` (checked by [`check_aggregate_field`]) for `hexad`.
+pub(crate) fn aggregate_field_value(hexad: &verisim_hexad::Hexad, field: &str) -> Value {
+    let modalities = &hexad.status.modality_status;
+    match field {
+        "id" => json!(hexad.id.as_str()),
+        "collection" => json!(hexad.id.collection()),
+        "version" => json!(hexad.status.version),
+        "created_at" => json!(hexad.status.created_at.to_rfc3339()),
+        "modified_at" => json!(hexad.status.modified_at.to_rfc3339()),
+        "version_count" => json!(hexad.version_count),
+        "provenance_length" => json!(hexad.provenance_chain_length),
+        "title" => json!(hexad.document.as_ref().map(|d| d.title.as_str())),
+        "body_length" => json!(hexad.document.as_ref().map(|d| d.body.chars().count())),
+        "type" => json!(hexad.semantic.as_ref().and_then(|s| s.types.first())),
+        "embedding_dim" => json!(hexad.embedding.as_ref().map(|e| e.vector.len())),
+        "tensor_size" => json!(hexad.tensor.as_ref().map(|t| t.data.len())),
+        "has_graph" => json!(modalities.graph),
+        "has_vector" => json!(modalities.vector),
+        "has_tensor" => json!(modalities.tensor),
+        "has_semantic" => json!(modalities.semantic),
+        "has_document" => json!(modalities.document),
+        "has_temporal" => json!(modalities.temporal),
+        "has_provenance" => json!(modalities.provenance),
+        "has_spatial" => json!(modalities.spatial),
+        field => {
+            let key = field.trim_start_matches("document.");
+            json!(hexad
+                .document
+                .as_ref()
+                .and_then(|d| d.fields.get(key).or_else(|| d.metadata.get(key))))
+        }
+    }
+}
+
+/// A value as a number: numbers, and strings that parse as one.
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Order values numerically when both are numeric, else by their text.
+pub(crate) fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (numeric_value(a), numeric_value(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => match (a, b) {
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            _ => a.to_string().cmp(&b.to_string()),
+        },
+    }
+}
+
+/// Running state of one aggregate over one group.
+#[derive(Debug, Default)]
+pub(crate) struct Accumulator {
+    count: u64,
+    sum: f64,
+    numeric: u64,
+    min: Option<Value>,
+    max: Option<Value>,
+}
+
+impl Accumulator {
+    /// Fold in one row; `value` is `None` for `COUNT(*)`.
+    pub(crate) fn add(&mut self, value: Option<Value>) {
+        let Some(value) = value else {
+            self.count += 1;
+            return;
+        };
+        if value.is_null() {
+            return;
+        }
+        self.count += 1;
+        if let Some(n) = numeric_value(&value) {
+            self.sum += n;
+            self.numeric += 1;
+        }
+        if self.min.as_ref().is_none_or(|min| compare_values(&value, min).is_lt()) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| compare_values(&value, max).is_gt()) {
+            self.max = Some(value);
+        }
+    }
+
+    /// The aggregate's result; SUM and AVG over no numeric values are null.
+    pub(crate) fn finish(&self, function: AggregateFunction) -> Value {
+        match function {
+            AggregateFunction::Count => json!(self.count),
+            AggregateFunction::Sum if self.numeric > 0 => json!(self.sum),
+            AggregateFunction::Avg if self.numeric > 0 => json!(self.sum / self.numeric as f64),
+            AggregateFunction::Sum | AggregateFunction::Avg => Value::Null,
+            AggregateFunction::Min => self.min.clone().unwrap_or(Value::Null),
+            AggregateFunction::Max => self.max.clone().unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Whether `value` equals the literal `expected` from a WHERE clause.
+pub(crate) fn value_matches(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::String(s) => s == expected,
+        Value::Bool(b) => expected.parse::<bool>().is_ok_and(|e| e == *b),
+        Value::Null => expected.eq_ignore_ascii_case("null"),
+        value => numeric_value(value)
+            .zip(expected.parse::<f64>().ok())
+            .is_some_and(|(a, b)| a == b),
+    }
+}
+
+/// Execute an aggregate SELECT.
+///
+/// Scans every live entity, loading only the modalities the referenced
+/// fields need, keeps those matching the WHERE conditions, groups them by
+/// the GROUP BY fields and folds each aggregate per group.  Groups are
+/// returned in order of their key values; without GROUP BY there is one
+/// row, even over no entities.
+async fn execute_aggregate(state: &AppState, query: &AggregateQuery) -> Result<VqlExecuteResponse, ApiError> {
+    let fields = query
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::Field { field, .. } => Some(field.as_str()),
+            SelectItem::Aggregate { field, .. } => field.as_deref(),
+        })
+        .chain(query.filters.iter().map(|(field, _)| field.as_str()))
+        .chain(query.group_by.iter().map(String::as_str));
+    let mask = aggregate_mask(fields);
+
+    let aggregates: Vec<(AggregateFunction, Option<&str>)> = query
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::Aggregate { function, field, .. } => Some((*function, field.as_deref())),
+            SelectItem::Field { .. } => None,
+        })
+        .collect();
+    let mut groups: std::collections::BTreeMap<String, (Vec<Value>, Vec<Accumulator>)> =
+        std::collections::BTreeMap::new();
+    let mut scanned = 0;
+    let mut offset = 0;
+    loop {
+        queries::checkpoint()?;
+        let page = state
+            .hexad_store
+            .list_with(AGGREGATE_PAGE_SIZE, offset, mask)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        for hexad in &page {
+            if !query
+                .filters
+                .iter()
+                .all(|(field, expected)| value_matches(&aggregate_field_value(hexad, field), expected))
+            {
+                continue;
+            }
+            scanned += 1;
+            let key: Vec<Value> = query.group_by.iter().map(|f| aggregate_field_value(hexad, f)).collect();
+            let (_, accumulators) = groups
+                .entry(Value::Array(key.clone()).to_string())
+                .or_insert_with(|| (key, aggregates.iter().map(|_| Accumulator::default()).collect()));
+            for ((_, field), accumulator) in aggregates.iter().zip(accumulators.iter_mut()) {
+                accumulator.add(field.map(|f| aggregate_field_value(hexad, f)));
+            }
+        }
+        if page.len() < AGGREGATE_PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+    if query.group_by.is_empty() && groups.is_empty() {
+        groups.insert(String::new(), (vec![], aggregates.iter().map(|_| Accumulator::default()).collect()));
+    }
+
+    let rows: Vec<Value> = groups
+        .values()
+        .take(query.limit)
+        .map(|(key, accumulators)| {
+            let mut finished = aggregates.iter().zip(accumulators).map(|((f, _), a)| a.finish(*f));
+            let row: serde_json::Map<String, Value> = query
+                .items
+                .iter()
+                .map(|item| {
+                    let value = match item {
+                        SelectItem::Field { field, .. } => query
+                            .group_by
+                            .iter()
+                            .position(|g| g == field)
+                            .map_or(Value::Null, |i| key[i].clone()),
+                        SelectItem::Aggregate { .. } => finished.next().unwrap_or(Value::Null),
+                    };
+                    (item.column(), value)
+                })
+                .collect();
+            Value::Object(row)
+        })
+        .collect();
+
+    let count = rows.len();
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "SELECT".to_string(),
+        row_count: count,
+        data: json!(rows),
+        message: Some(format!("Aggregated {} hexads into {} groups", scanned, groups.len())),
+    })
+}
+
 // ---------------------------------------------------------------------------
 // SEARCH
 // ---------------------------------------------------------------------------
@@ -394,41 +1234,362 @@ fn parse_vector(s: &str) -> Result<Vec<f32>, ApiError> {
 }
 
 // ---------------------------------------------------------------------------
-// INSERT
+// MATCH
 // ---------------------------------------------------------------------------
 
-/// Execute an INSERT statement.
-///
-/// Supported form:
-/// `INSERT INTO hexads (title, body) VALUES ('<title>', '<body>')`
+/// Largest candidate set one MATCH input fetches.
+pub(crate) const MATCH_SCAN_LIMIT: usize = 10_000;
+
+/// A parsed MATCH query.  Every pattern and text term is a filter on the
+/// one variable; the planner decides which runs first.
+#[derive(Debug, PartialEq)]
+struct MatchQuery {
+    variable: String,
+    filters: Vec<MatchFilter>,
+    order_by: Option<SimilarityOrder>,
+    limit: usize,
+}
+
+/// One input to a MATCH join.
+#[derive(Debug, PartialEq)]
+enum MatchFilter {
+    /// `related(x, 'p')` — `x` has an outgoing `p` edge.
+    /// `related('a', x, 'p')` — `a` has a `p` edge to `x`.
+    Related { anchor: Option<String>, predicate: String },
+    /// `text ~ 'q'` — `x`'s document matches `q`.
+    Text(String),
+}
+
+/// `ORDER BY similarity(x.embedding, <vector>) [ASC|DESC]`
+#[derive(Debug, PartialEq)]
+struct SimilarityOrder {
+    vector: VectorArg,
+    descending: bool,
+}
+
+/// A vector literal or a `$name` parameter.
+#[derive(Debug, PartialEq)]
+enum VectorArg {
+    Literal(Vec<f32>),
+    Param(String),
+}
+
+/// Parse a MATCH query.
 ///
-/// Also accepts simplified form:
-/// `INSERT '<title>' '<body>'`
-async fn execute_insert(
-    state: &AppState,
-    raw: &str,
-) -> Result<VqlExecuteResponse, ApiError> {
-    let upper = raw.to_uppercase();
+/// Grammar:
+/// `MATCH <pattern> [AND <pattern>...] [WHERE text ~ '<q>' [AND ...]]
+///  [ORDER BY similarity(x.embedding, <[v1, ...] | $name>) [ASC|DESC]] [LIMIT n]`
+/// where a pattern is `related(x, '<predicate>')`,
+/// `related('<id>', x, '<predicate>')` or the bare variable `x`.
+fn parse_match(tokens: &[String]) -> Result<MatchQuery, ApiError> {
+    let is_keyword = |i: usize, keyword: &str| tokens.get(i).is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+    let where_at = (1..tokens.len()).find(|&i| is_keyword(i, "WHERE"));
+    let order_at = (1..tokens.len()).find(|&i| is_keyword(i, "ORDER") && is_keyword(i + 1, "BY"));
+    let (limit, limit_at) = parse_limit(tokens);
+    let clause_end = |start: usize| {
+        [where_at, order_at, Some(limit_at)]
+            .into_iter()
+            .flatten()
+            .filter(|&at| at > start)
+            .min()
+            .unwrap_or(tokens.len())
+    };
 
-    let (title, body) = if upper.starts_with("INSERT INTO") {
-        // Parse: INSERT INTO hexads (title, body) VALUES ('...', '...')
-        parse_insert_values(raw)?
-    } else {
-        // Simplified: INSERT '<title>' '<body>'
-        let tokens = tokenize(raw);
-        if tokens.len() < 3 {
-            return Err(ApiError::BadRequest(
-                "INSERT requires: INSERT INTO hexads (title, body) VALUES ('<title>', '<body>')".to_string(),
-            ));
+    let mut variable: Option<String> = None;
+    let mut bind = |name: &str| -> Result<(), ApiError> {
+        if !is_variable(name) {
+            return Err(ApiError::BadRequest(format!("MATCH: '{}' is not a variable name", name)));
+        }
+        match &variable {
+            Some(bound) if bound != name => Err(ApiError::BadRequest(format!(
+                "MATCH supports a single variable; found '{}' and '{}'",
+                bound, name
+            ))),
+            _ => {
+                variable = Some(name.to_string());
+                Ok(())
+            }
         }
-        (
-            unquote(&tokens[1]).to_string(),
-            unquote(&tokens[2]).to_string(),
-        )
     };
 
-    let mut input = HexadInput::default();
-    input.document = Some(HexadDocumentInput {
+    let mut filters = Vec::new();
+    for pattern in split_and(&tokens[1..clause_end(0)]) {
+        let Some(args) = call_args(&pattern, "related") else {
+            bind(&pattern)?;
+            continue;
+        };
+        let (anchor, name, predicate) = match args.as_slice() {
+            [name, predicate] => (None, name, predicate),
+            [anchor, name, predicate] => (Some(unquote(anchor).to_string()), name, predicate),
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "MATCH: expected related(x, '<predicate>') or related('<id>', x, '<predicate>'), got {}",
+                    pattern
+                )))
+            }
+        };
+        bind(name)?;
+        filters.push(MatchFilter::Related {
+            anchor,
+            predicate: unquote(predicate).to_string(),
+        });
+    }
+    let variable = variable.ok_or_else(|| ApiError::BadRequest("MATCH requires a pattern".to_string()))?;
+
+    if let Some(at) = where_at {
+        for term in split_and(&tokens[at + 1..clause_end(at)]) {
+            let text = term.split_once('~').and_then(|(field, query)| {
+                let field = field.trim();
+                (field == "text" || field == format!("{}.text", variable)).then(|| unquote(query.trim()).to_string())
+            });
+            match text {
+                Some(query) => filters.push(MatchFilter::Text(query)),
+                None => {
+                    return Err(ApiError::BadRequest(format!(
+                        "MATCH: unsupported condition '{}'; expected text ~ '<query>'",
+                        term
+                    )))
+                }
+            }
+        }
+    }
+
+    let order_by = match order_at {
+        Some(at) => Some(parse_similarity_order(&tokens[at + 2..clause_end(at)], &variable)?),
+        None => None,
+    };
+
+    Ok(MatchQuery {
+        variable,
+        filters,
+        order_by,
+        limit,
+    })
+}
+
+/// Parse `similarity(x.embedding, <vector>) [ASC|DESC]`.
+fn parse_similarity_order(tokens: &[String], variable: &str) -> Result<SimilarityOrder, ApiError> {
+    let (tokens, descending) = match tokens.last().map(|t| t.to_uppercase()) {
+        Some(direction) if direction == "ASC" => (&tokens[..tokens.len() - 1], false),
+        Some(direction) if direction == "DESC" => (&tokens[..tokens.len() - 1], true),
+        _ => (tokens, true),
+    };
+    let expression = tokens.join(" ");
+    let args = call_args(&expression, "similarity")
+        .filter(|args| args.len() == 2 && args[0] == format!("{}.embedding", variable))
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "MATCH: expected ORDER BY similarity({}.embedding, <vector>), got {}",
+                variable, expression
+            ))
+        })?;
+    let vector = match args[1].strip_prefix('$') {
+        Some(name) => VectorArg::Param(name.to_string()),
+        None => VectorArg::Literal(parse_vector(&args[1])?),
+    };
+    Ok(SimilarityOrder { vector, descending })
+}
+
+/// Split tokens at top-level `AND` keywords, rejoining each part.
+fn split_and(tokens: &[String]) -> Vec<String> {
+    tokens
+        .split(|t| t.eq_ignore_ascii_case("AND"))
+        .filter(|part| !part.is_empty())
+        .map(|part| part.join(" "))
+        .collect()
+}
+
+/// The arguments of `name(a, b, ...)`, split at commas outside quotes and
+/// brackets, or `None` if `expression` is not a call to `name`.
+fn call_args(expression: &str, name: &str) -> Option<Vec<String>> {
+    let open = expression.find('(')?;
+    if !expression[..open].trim().eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let inner = expression[open + 1..].trim_end().strip_suffix(')')?;
+    Some(split_commas(inner))
+}
+
+/// Whether `s` is a plain identifier.
+fn is_variable(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Build the logical plan for a MATCH query: one node per filter, joined
+/// in a chain, then a vector node for the similarity ordering.
+fn match_plan(query: &MatchQuery) -> LogicalPlan {
+    let mut nodes: Vec<PlanNode> = query
+        .filters
+        .iter()
+        .map(|filter| match filter {
+            MatchFilter::Related { anchor, predicate } => {
+                let mut conditions = vec![ConditionKind::Traversal {
+                    predicate: predicate.clone(),
+                    depth: Some(1),
+                }];
+                if let Some(anchor) = anchor {
+                    conditions.push(ConditionKind::Equality {
+                        field: "source".to_string(),
+                        value: anchor.clone(),
+                    });
+                }
+                PlanNode {
+                    modality: Modality::Graph,
+                    conditions,
+                    projections: vec![],
+                    early_limit: None,
+                }
+            }
+            MatchFilter::Text(text) => PlanNode {
+                modality: Modality::Document,
+                conditions: vec![ConditionKind::Fulltext { query: text.clone() }],
+                projections: vec![],
+                early_limit: None,
+            },
+        })
+        .collect();
+    let joins = (1..nodes.len()).map(|i| Join { left: i - 1, right: i }).collect();
+
+    let mut post_processing = Vec::new();
+    if let Some(order) = &query.order_by {
+        nodes.push(PlanNode {
+            modality: Modality::Vector,
+            conditions: vec![ConditionKind::Similarity { k: query.limit }],
+            projections: vec![],
+            early_limit: None,
+        });
+        post_processing.push(PostProcessing::OrderBy {
+            fields: vec![("similarity".to_string(), !order.descending)],
+        });
+    }
+    post_processing.push(PostProcessing::Limit { count: query.limit });
+
+    LogicalPlan {
+        source: QuerySource::Hexad,
+        nodes,
+        post_processing,
+        joins,
+        hints: QueryHints::default(),
+    }
+}
+
+/// Optimize a logical plan with the shared planner.
+fn optimize_plan(state: &AppState, plan: &LogicalPlan) -> Result<PhysicalPlan, ApiError> {
+    let planner = state.planner.lock().map_err(|_| ApiError::Internal("Planner lock poisoned".to_string()))?;
+    planner.optimize(plan).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+/// Execute a MATCH query.
+///
+/// The plan's filters run in the join order the planner chose, each one
+/// narrowing the candidates left by the ones before, so the most
+/// selective input drives the join.  The survivors are then ranked by
+/// similarity (if ordered) and limited.
+async fn execute_match(
+    state: &AppState,
+    tokens: &[String],
+    hints: &QueryHints,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<VqlExecuteResponse, ApiError> {
+    let query = parse_match(tokens)?;
+    let plan = LogicalPlan {
+        hints: hints.clone(),
+        ..match_plan(&query)
+    };
+    let physical = optimize_plan(state, &plan)?;
+    let message = physical.notes.iter().find(|n| n.starts_with("Join order")).cloned();
+
+    let mut executor = PlanExecutor::new(state).with_query(tokens.join(" "));
+    if let Some(order) = &query.order_by {
+        executor = executor.with_vector(resolve_vector(state, &order.vector, params)?);
+    }
+    let execution = executor.execute(&plan, physical).await?;
+
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "MATCH".to_string(),
+        row_count: execution.row_count,
+        data: json!(execution.rows),
+        message,
+    })
+}
+
+/// The vector a [`VectorArg`] names, checked against the configured
+/// dimension.
+fn resolve_vector(
+    state: &AppState,
+    arg: &VectorArg,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<Vec<f32>, ApiError> {
+    let vector = match arg {
+        VectorArg::Literal(vector) => vector.clone(),
+        VectorArg::Param(name) => {
+            let value = params
+                .get(name)
+                .ok_or_else(|| ApiError::BadRequest(format!("Unbound parameter ${}", name)))?;
+            serde_json::from_value::<Vec<f32>>(value.clone())
+                .map_err(|e| ApiError::BadRequest(format!("Parameter ${} is not a vector: {}", name, e)))?
+        }
+    };
+    if vector.len() != state.config.vector_dimension {
+        return Err(ApiError::BadRequest(format!(
+            "Vector dimension mismatch: expected {}, got {}",
+            state.config.vector_dimension,
+            vector.len()
+        )));
+    }
+    Ok(vector)
+}
+
+/// Cosine similarity of two vectors, 0.0 when either is zero.
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        0.0
+    } else {
+        dot / norm
+    }
+}
+
+// ---------------------------------------------------------------------------
+// INSERT
+// ---------------------------------------------------------------------------
+
+/// Execute an INSERT statement.
+///
+/// Supported form:
+/// `INSERT INTO hexads (title, body) VALUES ('<title>', '<body>')`
+///
+/// Also accepts simplified form:
+/// `INSERT '<title>' '<body>'`
+async fn execute_insert(
+    state: &AppState,
+    raw: &str,
+) -> Result<VqlExecuteResponse, ApiError> {
+    let upper = raw.to_uppercase();
+
+    let (title, body) = if upper.starts_with("INSERT INTO") {
+        // Parse: INSERT INTO hexads (title, body) VALUES ('...', '...')
+        parse_insert_values(raw)?
+    } else {
+        // Simplified: INSERT '<title>' '<body>'
+        let tokens = tokenize(raw);
+        if tokens.len() < 3 {
+            return Err(ApiError::BadRequest(
+                "INSERT requires: INSERT INTO hexads (title, body) VALUES ('<title>', '<body>')".to_string(),
+            ));
+        }
+        (
+            unquote(&tokens[1]).to_string(),
+            unquote(&tokens[2]).to_string(),
+        )
+    };
+
+    let mut input = HexadInput::default();
+    input.document = Some(HexadDocumentInput {
         title: title.clone(),
         body,
         fields: std::collections::HashMap::new(),
@@ -480,42 +1641,812 @@ fn parse_insert_values(raw: &str) -> Result<(String, String), ApiError> {
 }
 
 // ---------------------------------------------------------------------------
-// DELETE
+// DML: INSERT / UPDATE / DELETE with literal maps
 // ---------------------------------------------------------------------------
 
-/// Execute a DELETE statement.
+/// Keys a mutation's literal map may set, as accepted by [`HexadRequest`].
+const MUTATION_FIELDS: &[&str] = &[
+    "title",
+    "body",
+    "embedding",
+    "embedding_model",
+    "types",
+    "relationships",
+    "tensor",
+    "provenance",
+    "spatial",
+    "metadata",
+    "namespace",
+    "collection",
+    "expires_at",
+];
+
+/// A parsed INSERT, UPDATE or DELETE.
+#[derive(Debug)]
+enum Mutation {
+    /// `INSERT HEXAD {...}`
+    Insert(HexadRequest),
+    /// `UPDATE HEXAD '<id>' SET {...}` or `UPDATE hexads SET {...} WHERE ...`
+    Update(HexadRequest, MutationTarget),
+    /// `DELETE HEXAD '<id>'` or `DELETE FROM hexads WHERE ...`
+    Delete(MutationTarget),
+}
+
+/// The entities an UPDATE or DELETE applies to.
+#[derive(Debug, PartialEq)]
+enum MutationTarget {
+    /// One entity, which must exist
+    Id(String),
+    /// Every entity matching all the conditions
+    Where(Vec<(String, String)>),
+}
+
+/// Parser for VQL literals: `{key: value, ...}` maps (bare or quoted
+/// keys), `[...]` lists, quoted strings, numbers, `true`, `false`, `null`
+/// and `$name` parameters.
+struct LiteralParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    params: &'a std::collections::HashMap<String, Value>,
+}
+
+impl LiteralParser<'_> {
+    fn error(&self, expected: &str) -> ApiError {
+        ApiError::BadRequest(format!("Invalid literal at offset {}: expected {}", self.pos, expected))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ApiError> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos) {
+            Some('{') => self.map(),
+            Some('[') => self.list(),
+            Some('\'' | '"') => self.string().map(Value::String),
+            Some('$') => {
+                self.pos += 1;
+                let name = self.word();
+                self.params
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| ApiError::BadRequest(format!("Missing parameter ${}", name)))
+            }
+            Some(_) => {
+                let word = self.word();
+                match word.as_str() {
+                    "true" => Ok(json!(true)),
+                    "false" => Ok(json!(false)),
+                    "null" => Ok(Value::Null),
+                    _ => serde_json::from_str::<serde_json::Number>(&word)
+                        .map(Value::Number)
+                        .map_err(|_| self.error("a value")),
+                }
+            }
+            None => Err(self.error("a value")),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn string(&mut self) -> Result<String, ApiError> {
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+        let mut s = String::new();
+        while let Some(&ch) = self.chars.get(self.pos) {
+            self.pos += 1;
+            match ch {
+                '\\' => {
+                    if let Some(&escaped) = self.chars.get(self.pos) {
+                        s.push(escaped);
+                        self.pos += 1;
+                    }
+                }
+                ch if ch == quote => return Ok(s),
+                ch => s.push(ch),
+            }
+        }
+        Err(self.error("a closing quote"))
+    }
+
+    fn list(&mut self) -> Result<Value, ApiError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.eat(']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            if !self.eat(',') {
+                return Err(self.error("',' or ']'"));
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Value, ApiError> {
+        self.pos += 1;
+        let mut map = serde_json::Map::new();
+        if self.eat('}') {
+            return Ok(Value::Object(map));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.chars.get(self.pos) {
+                Some('\'' | '"') => self.string()?,
+                _ => self.word(),
+            };
+            if key.is_empty() {
+                return Err(self.error("a key"));
+            }
+            if !self.eat(':') {
+                return Err(self.error("':'"));
+            }
+            let value = self.value()?;
+            map.insert(key, value);
+            if self.eat('}') {
+                return Ok(Value::Object(map));
+            }
+            if !self.eat(',') {
+                return Err(self.error("',' or '}'"));
+            }
+        }
+    }
+}
+
+/// Parse the literal map starting at `start` in `raw`, returning it and
+/// the byte offset just past it.
+fn parse_literal_map(
+    raw: &str,
+    start: usize,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<(Value, usize), ApiError> {
+    let mut parser = LiteralParser {
+        chars: raw[start..].chars().collect(),
+        pos: 0,
+        params,
+    };
+    let value = parser.map()?;
+    let consumed: usize = parser.chars[..parser.pos].iter().map(|c| c.len_utf8()).sum();
+    Ok((value, start + consumed))
+}
+
+/// Byte offset of the first `{` outside quotes.
+fn find_map_start(raw: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, ch) in raw.char_indices() {
+        match ch {
+            '\'' | '"' if quote == Some(ch) => quote = None,
+            '\'' | '"' if quote.is_none() => quote = Some(ch),
+            '{' if quote.is_none() => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Turn a literal map into a [`HexadRequest`], rejecting unknown keys.
+fn mutation_request(map: Value) -> Result<HexadRequest, ApiError> {
+    if let Some(key) = map
+        .as_object()
+        .and_then(|m| m.keys().find(|k| !MUTATION_FIELDS.contains(&k.as_str())))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown key '{}'. Use one of {}",
+            key,
+            MUTATION_FIELDS.join(", ")
+        )));
+    }
+    serde_json::from_value(map).map_err(|e| ApiError::BadRequest(format!("Invalid literal map: {}", e)))
+}
+
+/// Parse `WHERE field = value [AND ...]`, binding `$name` values from
+/// `params`.
+fn parse_mutation_where(
+    tokens: &[String],
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<MutationTarget, ApiError> {
+    if !tokens.first().is_some_and(|t| t.eq_ignore_ascii_case("WHERE")) || tokens.len() < 2 {
+        return Err(ApiError::BadRequest(
+            "UPDATE and DELETE over hexads need a WHERE clause".to_string(),
+        ));
+    }
+    Ok(MutationTarget::Where(parse_bound_conditions(&tokens[1..], params)?))
+}
+
+/// Parse `field = value [AND ...]` conditions, binding `$name` values from
+/// `params`.
+fn parse_bound_conditions(
+    tokens: &[String],
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<Vec<(String, String)>, ApiError> {
+    parse_conditions(tokens)?
+        .into_iter()
+        .map(|(field, value)| match value.strip_prefix('$') {
+            Some(name) => match params.get(name) {
+                Some(Value::String(s)) => Ok((field, s.clone())),
+                Some(other) => Ok((field, other.to_string())),
+                None => Err(ApiError::BadRequest(format!("Missing parameter ${}", name))),
+            },
+            None => Ok((field, value)),
+        })
+        .collect()
+}
+
+/// Parse an INSERT, UPDATE or DELETE statement.
 ///
-/// Supported form:
-/// `DELETE FROM hexads WHERE id = '<id>'`
-async fn execute_delete(
+/// Supported forms:
+/// - `INSERT HEXAD {title: '...', body: '...', types: [...], ...}`
+///   (`INSERT INTO hexads {...}` is the same)
+/// - `UPDATE HEXAD '<id>' SET {...}`
+/// - `UPDATE hexads SET {...} WHERE field = value [AND ...]`
+/// - `DELETE HEXAD '<id>'`
+/// - `DELETE FROM hexads WHERE field = value [AND ...]`
+///
+/// Map keys are the fields of the REST create/update body; WHERE fields
+/// are those of aggregate queries.
+fn parse_mutation(raw: &str, params: &std::collections::HashMap<String, Value>) -> Result<Mutation, ApiError> {
+    let (head, map, tail) = match find_map_start(raw) {
+        Some(start) => {
+            let (map, end) = parse_literal_map(raw, start, params)?;
+            (tokenize(&raw[..start]), Some(map), tokenize(&raw[end..]))
+        }
+        None => (tokenize(raw), None, Vec::new()),
+    };
+    let keywords: Vec<String> = head.iter().map(|t| t.to_uppercase()).collect();
+    let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
+
+    match (keywords.as_slice(), map) {
+        (["INSERT", "HEXAD"] | ["INSERT", "INTO", "HEXADS"], Some(map)) if tail.is_empty() => {
+            Ok(Mutation::Insert(mutation_request(map)?))
+        }
+        (["UPDATE", "HEXAD", _, "SET"], Some(map)) if tail.is_empty() => Ok(Mutation::Update(
+            mutation_request(map)?,
+            MutationTarget::Id(unquote(&head[2]).to_string()),
+        )),
+        (["UPDATE", "HEXADS", "SET"], Some(map)) => Ok(Mutation::Update(
+            mutation_request(map)?,
+            parse_mutation_where(&tail, params)?,
+        )),
+        (["DELETE", "HEXAD", _], None) => Ok(Mutation::Delete(MutationTarget::Id(unquote(&head[2]).to_string()))),
+        (["DELETE", "FROM", "HEXADS", ..], None) => Ok(Mutation::Delete(parse_mutation_where(&head[3..], params)?)),
+        _ => Err(ApiError::BadRequest(
+            "Expected INSERT HEXAD {...}, UPDATE HEXAD '<id>' SET {...}, \
+             UPDATE hexads SET {...} WHERE ..., DELETE HEXAD '<id>' or DELETE FROM hexads WHERE ..."
+                .to_string(),
+        )),
+    }
+}
+
+/// IDs of the entities `target` selects, in scan order.
+async fn mutation_targets(state: &AppState, target: &MutationTarget) -> Result<Vec<HexadId>, ApiError> {
+    let conditions = match target {
+        MutationTarget::Id(id) => {
+            let hexad_id = HexadId::new(id);
+            return match state.hexad_store.status(&hexad_id).await {
+                Ok(Some(_)) => Ok(vec![hexad_id]),
+                Ok(None) => Err(ApiError::NotFound(format!("Hexad '{}' not found", id))),
+                Err(e) => Err(ApiError::Internal(e.to_string())),
+            };
+        }
+        MutationTarget::Where(conditions) => conditions,
+    };
+    let mask = aggregate_mask(conditions.iter().map(|(field, _)| field.as_str()));
+    let matches = |hexad: &verisim_hexad::Hexad| {
+        conditions
+            .iter()
+            .all(|(field, expected)| value_matches(&aggregate_field_value(hexad, field), expected))
+    };
+
+    // An `id` condition picks at most one entity; check just that one.
+    if let Some((_, id)) = conditions.iter().find(|(field, _)| field == "id") {
+        let hexad = state
+            .hexad_store
+            .get_with(&HexadId::new(id), mask)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        return Ok(hexad.filter(|h| matches(h)).map(|h| h.id).into_iter().collect());
+    }
+
+    let mut ids = Vec::new();
+    let mut offset = 0;
+    loop {
+        queries::checkpoint()?;
+        let page = state
+            .hexad_store
+            .list_with(AGGREGATE_PAGE_SIZE, offset, mask)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        ids.extend(page.iter().filter(|h| matches(h)).map(|h| h.id.clone()));
+        if page.len() < AGGREGATE_PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+    Ok(ids)
+}
+
+/// A write already applied by a mutation, and how to take it back.
+enum Undo {
+    /// Delete the created entity
+    Created(HexadId),
+    /// Revert the entity to the version it had
+    Updated(HexadId, u64),
+    /// Restore the soft-deleted entity
+    SoftDeleted(HexadId),
+}
+
+/// Take back `applied`, newest first.  Failures are logged: the statement
+/// has failed already, and its error is the one to report.
+async fn undo_mutation(state: &AppState, applied: Vec<Undo>, actor: &str) {
+    for undo in applied.into_iter().rev() {
+        let result = match &undo {
+            Undo::Created(id) => state.hexad_store.delete(id).await.map(|_| ()),
+            Undo::Updated(id, version) => state.hexad_store.revert(id, *version, None).await.map(|_| ()),
+            Undo::SoftDeleted(id) => state.hexad_store.restore(id, actor).await.map(|_| ()),
+        };
+        if let Err(e) = result {
+            warn!(error = %e, "Failed to undo VQL mutation write");
+        }
+    }
+}
+
+fn mutation_error(e: verisim_hexad::HexadError) -> ApiError {
+    match e {
+        verisim_hexad::HexadError::NotFound(id) => ApiError::NotFound(format!("Hexad '{}' not found", id)),
+        verisim_hexad::HexadError::ValidationError(msg) => ApiError::BadRequest(msg),
+        e => ApiError::Internal(e.to_string()),
+    }
+}
+
+/// Apply one mutation write, recording how to undo it in `applied`.
+async fn apply_mutation_write(
     state: &AppState,
-    tokens: &[String],
+    mutation: &Mutation,
+    target: Option<&HexadId>,
+    actor: Option<&ActorIdentity>,
+    applied: &mut Vec<Undo>,
+) -> Result<Option<HexadId>, ApiError> {
+    let actor_name = actor.map_or("anonymous", |a| a.iri.as_str());
+    match (mutation, target) {
+        (Mutation::Insert(request), _) => {
+            let mut input = request.to_hexad_input();
+            attribute_actor(&mut input, actor, "created", "Created via VQL");
+            let hexad = match &request.collection {
+                Some(collection) => state.hexad_store.create_in(collection, input).await,
+                None => state.hexad_store.create(input).await,
+            }
+            .map_err(mutation_error)?;
+            applied.push(Undo::Created(hexad.id.clone()));
+            if request.expires_at.is_some() {
+                state
+                    .hexad_store
+                    .set_expiry(&hexad.id, request.expires_at)
+                    .await
+                    .map_err(mutation_error)?;
+            }
+            state.observe_embedding(hexad.embedding.as_ref());
+            Ok(Some(hexad.id))
+        }
+        (Mutation::Update(request, _), Some(id)) => {
+            let Some(status) = state.hexad_store.status(id).await.map_err(mutation_error)? else {
+                // Deleted since the targets were resolved.
+                return Ok(None);
+            };
+            let mut input = request.to_hexad_input();
+            attribute_actor(&mut input, actor, "modified", "Modified via VQL");
+            let hexad = state.hexad_store.update(id, input).await.map_err(mutation_error)?;
+            applied.push(Undo::Updated(id.clone(), status.version));
+            if request.expires_at.is_some() {
+                state
+                    .hexad_store
+                    .set_expiry(id, request.expires_at)
+                    .await
+                    .map_err(mutation_error)?;
+            }
+            if request.embedding.is_some() {
+                state.observe_embedding(hexad.embedding.as_ref());
+            }
+            Ok(Some(hexad.id))
+        }
+        (Mutation::Delete(_), Some(id)) => {
+            let deleted = if state.config.soft_delete {
+                state.hexad_store.soft_delete(id, actor_name).await.map(|_| ())
+            } else {
+                state.hexad_store.delete(id).await
+            };
+            match deleted {
+                Ok(()) => {}
+                Err(verisim_hexad::HexadError::NotFound(_)) => return Ok(None),
+                Err(e) => return Err(mutation_error(e)),
+            }
+            if state.config.soft_delete {
+                applied.push(Undo::SoftDeleted(id.clone()));
+            } else {
+                state
+                    .trajectories
+                    .delete(id.as_str())
+                    .await
+                    .map_err(|e| ApiError::Internal(e.to_string()))?;
+            }
+            Ok(Some(id.clone()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Execute an INSERT, UPDATE or DELETE (see [`parse_mutation`]) and
+/// return the IDs of the entities it wrote.
+///
+/// With a `transaction`, the writes are buffered in that transaction and
+/// applied when it commits; buffered inserts get their IDs then.
+/// Otherwise the statement is its own transaction: if a write fails, the
+/// writes already made are undone (created entities deleted, updated ones
+/// reverted, soft-deleted ones restored) before the error is returned.
+/// Hard deletes cannot be undone, so an entity deleted by another writer
+/// meanwhile is skipped rather than failing the statement.
+async fn execute_mutation(
+    state: &AppState,
+    raw: &str,
+    params: &std::collections::HashMap<String, Value>,
+    transaction: Option<&str>,
+    actor: Option<&ActorIdentity>,
 ) -> Result<VqlExecuteResponse, ApiError> {
-    let id = find_where_id(tokens).ok_or_else(|| {
-        ApiError::BadRequest(
-            "DELETE requires: DELETE FROM hexads WHERE id = '<id>'".to_string(),
-        )
-    })?;
+    state.replication.check_writable()?;
+    let mutation = parse_mutation(raw, params)?;
+    let (statement_type, verb, targets) = match &mutation {
+        Mutation::Insert(_) => ("INSERT", "Inserted", None),
+        Mutation::Update(_, target) => ("UPDATE", "Updated", Some(mutation_targets(state, target).await?)),
+        Mutation::Delete(target) => ("DELETE", "Deleted", Some(mutation_targets(state, target).await?)),
+    };
 
-    let hexad_id = HexadId::new(id);
+    if let Some(txn) = transaction {
+        let txn_id = TransactionId::from_str(txn);
+        let (operation, request) = match &mutation {
+            Mutation::Insert(request) => (OperationType::Create, Some(request)),
+            Mutation::Update(request, _) => (OperationType::Update, Some(request)),
+            Mutation::Delete(_) => (OperationType::Delete, None),
+        };
+        let payload = match request {
+            Some(request) => serde_json::to_vec(request).map_err(|e| ApiError::Serialization(e.to_string()))?,
+            None => Vec::new(),
+        };
+        // Inserts have no ID until the transaction commits.
+        let entity_ids: Vec<String> = match &targets {
+            Some(ids) => ids.iter().map(|id| id.to_string()).collect(),
+            None => vec![String::new()],
+        };
+        for entity_id in &entity_ids {
+            state
+                .transaction_manager
+                .buffer_operation(
+                    &txn_id,
+                    BufferedOperation {
+                        entity_id: entity_id.clone(),
+                        operation: operation.clone(),
+                        payload: payload.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    },
+                )
+                .await?;
+        }
+        let ids: Vec<&String> = entity_ids.iter().filter(|id| !id.is_empty()).collect();
+        return Ok(VqlExecuteResponse {
+            success: true,
+            statement_type: statement_type.to_string(),
+            row_count: entity_ids.len(),
+            data: json!({"ids": ids, "transaction": txn}),
+            message: Some(format!("Buffered {} operations in transaction '{}'", entity_ids.len(), txn)),
+        });
+    }
 
-    state
-        .hexad_store
-        .delete(&hexad_id)
-        .await
-        .map_err(|e| match e {
-            verisim_hexad::HexadError::NotFound(_) => {
-                ApiError::NotFound(format!("Hexad '{}' not found", id))
+    let actor_name = actor.map_or("anonymous", |a| a.iri.as_str());
+    let mut applied = Vec::new();
+    let mut ids = Vec::new();
+    let targets: Vec<Option<HexadId>> = match targets {
+        Some(targets) => targets.into_iter().map(Some).collect(),
+        None => vec![None],
+    };
+    for target in &targets {
+        let written = match queries::checkpoint() {
+            Ok(()) => apply_mutation_write(state, &mutation, target.as_ref(), actor, &mut applied).await,
+            Err(e) => Err(e),
+        };
+        match written {
+            Ok(Some(id)) => ids.push(id.to_string()),
+            Ok(None) => {}
+            Err(e) => {
+                undo_mutation(state, applied, actor_name).await;
+                return Err(e);
             }
-            _ => ApiError::Internal(e.to_string()),
-        })?;
+        }
+    }
+
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: statement_type.to_string(),
+        row_count: ids.len(),
+        message: Some(format!("{} {} hexads", verb, ids.len())),
+        data: json!({"ids": ids}),
+    })
+}
+
+/// Apply a committing transaction's buffered operations to the hexad
+/// store and return the IDs they wrote (inserts get theirs here), in
+/// the order written.
+///
+/// If the transaction has a snapshot, every entity in its read and write
+/// sets must be as the snapshot saw it (same version, or still absent);
+/// otherwise a commit since it began changed them, and it fails with
+/// [`TransactionError::Conflict`] naming them.  Every operation is then
+/// checked: payloads must decode and the entities an update or delete
+/// names must exist.  The writes then go in
+/// order; if one fails, or its entity has gone since the check, the
+/// writes already made are undone as for a single statement (see
+/// [`execute_mutation`]) and nothing is applied.  Hard deletes cannot be
+/// undone, so without soft delete they go after every other write.
+pub(crate) async fn apply_transaction(
+    state: &AppState,
+    transaction: Transaction,
+    actor: Option<&ActorIdentity>,
+) -> Result<Vec<String>, ApiError> {
+    check_conflicts(state, &transaction).await?;
+    let operations = transaction.operations.into_iter().enumerate().collect();
+    apply_operations(state, &transaction.id, operations, actor).await
+}
+
+/// Check a transaction being prepared for a two-phase commit as
+/// [`apply_transaction`] would, without writing anything.
+pub(crate) async fn validate_transaction(state: &AppState, transaction: Transaction) -> Result<(), ApiError> {
+    check_conflicts(state, &transaction).await?;
+    plan_writes(state, transaction.operations.into_iter().enumerate().collect()).await?;
+    Ok(())
+}
+
+/// Fail with [`TransactionError::Conflict`] if a commit since the
+/// transaction's snapshot (if it has one) changed an entity in its read or
+/// write set.
+async fn check_conflicts(state: &AppState, transaction: &Transaction) -> Result<(), ApiError> {
+    if let Some(snapshot) = &transaction.snapshot {
+        let mut conflicts = Vec::new();
+        for id in transaction.read_set.union(&transaction.write_set()) {
+            let hexad_id = HexadId::new(id);
+            let current = state
+                .hexad_store
+                .status(&hexad_id)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            if current.map(|s| s.version) != snapshot.status(&hexad_id).map(|s| s.version) {
+                conflicts.push(id.clone());
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(TransactionError::Conflict(conflicts).into());
+        }
+    }
+    Ok(())
+}
+
+/// Check and write a committing transaction's `operations`, each with its
+/// index in the transaction, logging the commit point once they check out
+/// and each write as it is made.  Returns the IDs written, in order.
+async fn apply_operations(
+    state: &AppState,
+    txn_id: &TransactionId,
+    operations: Vec<(usize, BufferedOperation)>,
+    actor: Option<&ActorIdentity>,
+) -> Result<Vec<String>, ApiError> {
+    let writes = plan_writes(state, operations).await?;
+    state.transaction_manager.log_commit_point(txn_id).await?;
+
+    let actor_name = actor.map_or("anonymous", |a| a.iri.as_str());
+    let mut applied = Vec::new();
+    let mut ids = Vec::with_capacity(writes.len());
+    for (index, mutation, target) in &writes {
+        let written = match apply_mutation_write(state, mutation, target.as_ref(), actor, &mut applied).await {
+            Ok(Some(id)) => Ok(id),
+            Ok(None) => Err(ApiError::NotFound(format!(
+                "Hexad '{}' not found",
+                target.as_ref().map_or("", |id| id.as_str())
+            ))),
+            Err(e) => Err(e),
+        };
+        let logged = match written {
+            Ok(id) => state
+                .transaction_manager
+                .log_applied(txn_id, *index, id.as_str())
+                .await
+                .map(|()| id)
+                .map_err(ApiError::from),
+            Err(e) => Err(e),
+        };
+        match logged {
+            Ok(id) => ids.push(id.to_string()),
+            Err(e) => {
+                undo_mutation(state, applied, actor_name).await;
+                return Err(e);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Decode and check a transaction's `operations` (each with its index in
+/// the transaction) into the writes that apply them, in the order they
+/// are to be made: payloads must decode and the entities an update or
+/// delete names must exist.
+async fn plan_writes(
+    state: &AppState,
+    operations: Vec<(usize, BufferedOperation)>,
+) -> Result<Vec<(usize, Mutation, Option<HexadId>)>, ApiError> {
+    let decode = |op: &BufferedOperation| {
+        serde_json::from_slice::<HexadRequest>(&op.payload)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid buffered {:?} payload: {}", op.operation, e)))
+    };
+    let mut writes = Vec::with_capacity(operations.len());
+    for (index, op) in &operations {
+        let target = match op.operation {
+            OperationType::Create => None,
+            OperationType::Update | OperationType::Delete if op.entity_id.is_empty() => {
+                return Err(ApiError::BadRequest(format!("Buffered {:?} has no entity ID", op.operation)));
+            }
+            OperationType::Update | OperationType::Delete => {
+                Some(mutation_targets(state, &MutationTarget::Id(op.entity_id.clone())).await?.remove(0))
+            }
+        };
+        let mutation = match op.operation {
+            OperationType::Create => Mutation::Insert(decode(op)?),
+            OperationType::Update => Mutation::Update(decode(op)?, MutationTarget::Id(op.entity_id.clone())),
+            OperationType::Delete => Mutation::Delete(MutationTarget::Id(op.entity_id.clone())),
+        };
+        writes.push((*index, mutation, target));
+    }
+    if !state.config.soft_delete {
+        writes.sort_by_key(|(_, mutation, _)| matches!(mutation, Mutation::Delete(_)));
+    }
+    Ok(writes)
+}
+
+/// Finish the transactions a crash interrupted, as found by
+/// [`crate::transaction::TransactionManager::recover`].  Those short of
+/// their commit point wrote nothing: prepared ones are restored to await
+/// their coordinator's decision, others are marked aborted.  The rest are
+/// committed: their unapplied operations are written now, without the
+/// conflict check their commit already passed.  A replay that fails
+/// leaves the operations written before the crash in place; it is logged
+/// and marked aborted.  Returns the number replayed.
+pub(crate) async fn replay_transactions(state: &AppState) -> Result<usize, ApiError> {
+    let mut replayed = 0;
+    for txn in state.transaction_manager.recover()? {
+        if !txn.committing && txn.prepared {
+            state.transaction_manager.restore_prepared(txn).await?;
+            continue;
+        }
+        if !txn.committing {
+            info!(txn_id = %txn.id, "Discarding transaction interrupted before its commit point");
+            state.transaction_manager.finish_recovered(&txn.id, false).await?;
+            continue;
+        }
+        let remaining: Vec<_> = txn
+            .operations
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !txn.applied.contains_key(index))
+            .collect();
+        let count = remaining.len();
+        match apply_operations(state, &txn.id, remaining, None).await {
+            Ok(_) => {
+                info!(txn_id = %txn.id, ops = count, "Replayed interrupted transaction commit");
+                state.transaction_manager.finish_recovered(&txn.id, true).await?;
+                replayed += 1;
+            }
+            Err(e) => {
+                warn!(
+                    txn_id = %txn.id,
+                    applied = txn.applied.len(),
+                    error = %e,
+                    "Could not replay interrupted transaction commit; its earlier writes remain"
+                );
+                state.transaction_manager.finish_recovered(&txn.id, false).await?;
+            }
+        }
+    }
+    Ok(replayed)
+}
 
+// ---------------------------------------------------------------------------
+// SUBSCRIBE
+// ---------------------------------------------------------------------------
+
+/// Parse a continuous query into the conditions its result set is kept
+/// to, binding `$name` values from `params`.
+///
+/// Grammar: `[SUBSCRIBE] [SELECT * FROM hexads] [WHERE field = value [AND ...]]`,
+/// where the fields are those of aggregate queries.
+pub(crate) fn parse_subscribe(
+    query: &str,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<Vec<(String, String)>, ApiError> {
+    let tokens = tokenize(query.trim().trim_end_matches(';'));
+    let mut rest = tokens.as_slice();
+    if rest.first().is_some_and(|t| t.eq_ignore_ascii_case("SUBSCRIBE")) {
+        rest = &rest[1..];
+    }
+    let keywords: Vec<String> = rest.iter().take(4).map(|t| t.to_uppercase()).collect();
+    if keywords.len() == 4 && keywords[..3] == ["SELECT", "*", "FROM"] && keywords[3] == "HEXADS" {
+        rest = &rest[4..];
+    }
+    match rest.first() {
+        None => Ok(Vec::new()),
+        Some(keyword) if keyword.eq_ignore_ascii_case("WHERE") && rest.len() > 1 => {
+            parse_bound_conditions(&rest[1..], params)
+        }
+        Some(_) => Err(ApiError::BadRequest(
+            "Expected SUBSCRIBE [SELECT * FROM hexads] [WHERE field = value [AND ...]]".to_string(),
+        )),
+    }
+}
+
+/// Execute a SUBSCRIBE statement: register the continuous query and
+/// return its handle.  Changes stream from `/subscriptions/{id}/events`.
+fn execute_subscribe(
+    state: &AppState,
+    query: &str,
+    params: &std::collections::HashMap<String, Value>,
+) -> Result<VqlExecuteResponse, ApiError> {
+    let conditions = parse_subscribe(query, params)?;
+    let subscription = state.subscriptions.subscribe(query, conditions);
+    let events = format!("/subscriptions/{}/events", subscription.id);
     Ok(VqlExecuteResponse {
         success: true,
-        statement_type: "DELETE".to_string(),
+        statement_type: "SUBSCRIBE".to_string(),
         row_count: 1,
-        data: json!(null),
-        message: Some(format!("Deleted hexad '{}'", id)),
+        data: json!({"subscription": subscription, "events": events}),
+        message: Some(format!("Subscribed; stream changes from {}", events)),
+    })
+}
+
+/// Execute `UNSUBSCRIBE '<id>'`.
+fn execute_unsubscribe(state: &AppState, tokens: &[String]) -> Result<VqlExecuteResponse, ApiError> {
+    let [_, id] = tokens else {
+        return Err(ApiError::BadRequest("Expected UNSUBSCRIBE '<id>'".to_string()));
+    };
+    let id = unquote(id);
+    let subscription = state
+        .subscriptions
+        .unsubscribe(id)
+        .ok_or_else(|| ApiError::NotFound(format!("Subscription {} not found", id)))?;
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "UNSUBSCRIBE".to_string(),
+        row_count: 1,
+        data: json!(subscription),
+        message: None,
     })
 }
 
@@ -523,6 +2454,30 @@ async fn execute_delete(
 // SHOW
 // ---------------------------------------------------------------------------
 
+/// Execute `ANALYZE`: refresh the planner's statistics from every store.
+async fn execute_analyze(
+    state: &AppState,
+    tokens: &[String],
+) -> Result<VqlExecuteResponse, ApiError> {
+    if tokens.len() > 1 {
+        return Err(ApiError::BadRequest(format!(
+            "ANALYZE takes no arguments, got '{}'",
+            tokens[1..].join(" ")
+        )));
+    }
+    let report = crate::analyze::analyze(state).await?;
+    Ok(VqlExecuteResponse {
+        success: true,
+        statement_type: "ANALYZE".to_string(),
+        row_count: report.stores.len(),
+        message: Some(format!(
+            "Analyzed {} hexads in {:.1}ms",
+            report.hexads_scanned, report.elapsed_ms
+        )),
+        data: serde_json::to_value(&report).map_err(|e| ApiError::Serialization(e.to_string()))?,
+    })
+}
+
 /// Execute a SHOW query.
 ///
 /// Supported forms:
@@ -616,7 +2571,7 @@ async fn execute_show(
             let (limit, _) = parse_limit(tokens);
             let hexads = state
                 .hexad_store
-                .list(limit, 0)
+                .list_with(limit, 0, ModalityMask::SUMMARY)
                 .await
                 .map_err(|e| ApiError::Internal(e.to_string()))?;
 
@@ -654,7 +2609,7 @@ async fn execute_count(
     // List with a large limit to count (in a real DB this would be a COUNT query).
     let hexads = state
         .hexad_store
-        .list(1000, 0)
+        .list_with(1000, 0, ModalityMask::STATUS)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
@@ -678,9 +2633,10 @@ async fn execute_count(
 /// Supported form:
 /// - `EXPLAIN <any VQL query>`
 async fn execute_explain(
-    _state: &AppState,
+    state: &AppState,
     tokens: &[String],
     raw: &str,
+    hints: &QueryHints,
 ) -> Result<VqlExecuteResponse, ApiError> {
     if tokens.len() < 2 {
         return Err(ApiError::BadRequest("EXPLAIN requires a query to explain".to_string()));
@@ -694,12 +2650,45 @@ async fn execute_explain(
     }
 
     let statement_type = inner_tokens[0].to_uppercase();
+    let (inner_tokens, temporal) = split_temporal(&inner_tokens)?;
+    let (inner_tokens, similar) = split_similar(&inner_tokens)?;
     let (limit, _) = parse_limit(&inner_tokens);
     let where_id = find_where_id(&inner_tokens);
 
     let plan = match statement_type.as_str() {
         "SELECT" => {
-            if where_id.is_some() {
+            if let Some(similar) = similar {
+                let (conditions, k) = similar_conditions(&inner_tokens)?;
+                let plan = LogicalPlan {
+                    hints: hints.clone(),
+                    ..similar_plan(&conditions, similar.model.as_deref(), k)
+                };
+                let physical = optimize_plan(state, &plan)?;
+                serde_json::to_value(physical).map_err(|e| ApiError::Serialization(e.to_string()))?
+            } else if let Some(temporal) = temporal {
+                let (method, cost) = match (&temporal, where_id.is_some()) {
+                    (TemporalClause::AsOf(_), true) => ("at_time", "O(versions)"),
+                    (TemporalClause::AsOf(_), false) => ("at_time per entity", "O(n * versions)"),
+                    (TemporalClause::Between(_), _) => ("in_range", "O(versions)"),
+                };
+                json!({
+                    "operation": "Temporal Lookup",
+                    "target": "temporal_store",
+                    "method": method,
+                    "cost": cost,
+                    "limit": limit,
+                })
+            } else if let Some(aggregate) = parse_aggregate(&inner_tokens)? {
+                json!({
+                    "operation": "Hash Aggregate",
+                    "target": "hexad_store",
+                    "method": "list",
+                    "group_by": aggregate.group_by,
+                    "aggregates": aggregate.items.iter().map(SelectItem::column).collect::<Vec<_>>(),
+                    "cost": "O(n)",
+                    "estimated_rows": if aggregate.group_by.is_empty() { 1 } else { limit },
+                })
+            } else if where_id.is_some() {
                 json!({
                     "operation": "Point Lookup",
                     "target": "hexad_store",
@@ -747,12 +2736,26 @@ async fn execute_explain(
                 _ => json!({"operation": "Unknown search type"}),
             }
         }
+        "MATCH" => {
+            let plan = LogicalPlan {
+                hints: hints.clone(),
+                ..match_plan(&parse_match(&inner_tokens)?)
+            };
+            let physical = optimize_plan(state, &plan)?;
+            serde_json::to_value(physical).map_err(|e| ApiError::Serialization(e.to_string()))?
+        }
         "INSERT" => json!({
             "operation": "Multi-Modal Insert",
             "targets": ["document_store", "graph_store", "vector_store", "semantic_store", "temporal_store"],
             "method": "create",
             "cost": "O(1) per modality",
         }),
+        "UPDATE" => json!({
+            "operation": "Multi-Modal Update",
+            "targets": ["all_modality_stores"],
+            "method": "update",
+            "cost": "O(n) scan unless WHERE id = ...",
+        }),
         "DELETE" => json!({
             "operation": "Multi-Modal Delete",
             "targets": ["all_modality_stores"],
@@ -828,6 +2831,239 @@ fn test_find_where_id() {
         assert_eq!(find_where_id(&tokens), Some("abc-123"));
     }
 
+    fn tokens(query: &str) -> Vec<String> {
+        tokenize(query)
+    }
+
+    #[test]
+    fn test_parse_match() {
+        let query = parse_match(&tokens(
+            "MATCH related(x, 'provedBy') WHERE text ~ 'induction' ORDER BY similarity(x.embedding, $v) LIMIT 5",
+        ))
+        .unwrap();
+        assert_eq!(query.variable, "x");
+        assert_eq!(
+            query.filters,
+            vec![
+                MatchFilter::Related {
+                    anchor: None,
+                    predicate: "provedBy".to_string(),
+                },
+                MatchFilter::Text("induction".to_string()),
+            ]
+        );
+        assert_eq!(
+            query.order_by,
+            Some(SimilarityOrder {
+                vector: VectorArg::Param("v".to_string()),
+                descending: true,
+            })
+        );
+        assert_eq!(query.limit, 5);
+
+        let query = parse_match(&tokens(
+            "MATCH related('thm-1', y, 'cites') AND y ORDER BY similarity(y.embedding, [1, 0]) ASC",
+        ))
+        .unwrap();
+        assert_eq!(
+            query.filters,
+            vec![MatchFilter::Related {
+                anchor: Some("thm-1".to_string()),
+                predicate: "cites".to_string(),
+            }]
+        );
+        assert_eq!(
+            query.order_by,
+            Some(SimilarityOrder {
+                vector: VectorArg::Literal(vec![1.0, 0.0]),
+                descending: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_match_errors() {
+        assert!(parse_match(&tokens("MATCH")).is_err());
+        assert!(parse_match(&tokens("MATCH related(x, 'a') AND related(y, 'b')")).is_err());
+        assert!(parse_match(&tokens("MATCH x WHERE title = 'a'")).is_err());
+        assert!(parse_match(&tokens("MATCH x ORDER BY similarity(y.embedding, $v)")).is_err());
+    }
+
+    #[test]
+    fn test_match_plan_joins_filters() {
+        let query = parse_match(&tokens(
+            "MATCH related(x, 'provedBy') WHERE text ~ 'induction' ORDER BY similarity(x.embedding, $v)",
+        ))
+        .unwrap();
+        let plan = match_plan(&query);
+        assert_eq!(plan.nodes.len(), 3);
+        assert_eq!(plan.nodes[2].modality, Modality::Vector);
+        assert_eq!(plan.joins, vec![Join { left: 0, right: 1 }]);
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let query = parse_aggregate(&tokens(
+            "SELECT collection, COUNT(*) AS n, AVG(version) FROM hexads WHERE has_vector = true GROUP BY collection LIMIT 5",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            query.items,
+            vec![
+                SelectItem::Field {
+                    field: "collection".to_string(),
+                    alias: None,
+                },
+                SelectItem::Aggregate {
+                    function: AggregateFunction::Count,
+                    field: None,
+                    alias: Some("n".to_string()),
+                },
+                SelectItem::Aggregate {
+                    function: AggregateFunction::Avg,
+                    field: Some("version".to_string()),
+                    alias: None,
+                },
+            ]
+        );
+        assert_eq!(query.items[2].column(), "avg(version)");
+        assert_eq!(query.filters, vec![("has_vector".to_string(), "true".to_string())]);
+        assert_eq!(query.group_by, vec!["collection"]);
+        assert_eq!(query.limit, 5);
+
+        assert_eq!(parse_aggregate(&tokens("SELECT * FROM hexads LIMIT 5")).unwrap(), None);
+        assert!(parse_aggregate(&tokens("SELECT title, COUNT(*) FROM hexads")).is_err());
+        assert!(parse_aggregate(&tokens("SELECT SUM(*) FROM hexads")).is_err());
+        assert!(parse_aggregate(&tokens("SELECT MAX(colour) FROM hexads")).is_err());
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let mut acc = Accumulator::default();
+        for value in [json!(3), json!("10"), Value::Null, json!(2.5)] {
+            acc.add(Some(value));
+        }
+        assert_eq!(acc.finish(AggregateFunction::Count), json!(3));
+        assert_eq!(acc.finish(AggregateFunction::Sum), json!(15.5));
+        assert_eq!(acc.finish(AggregateFunction::Min), json!(2.5));
+        assert_eq!(acc.finish(AggregateFunction::Max), json!("10"));
+        assert_eq!(Accumulator::default().finish(AggregateFunction::Avg), Value::Null);
+    }
+
+    #[test]
+    fn test_split_temporal() {
+        let (rest, clause) =
+            split_temporal(&tokens("SELECT * FROM hexads WHERE id = 'a' AS OF '2026-01-02T03:04:05Z'")).unwrap();
+        assert_eq!(rest, tokens("SELECT * FROM hexads WHERE id = 'a'"));
+        assert!(matches!(clause, Some(TemporalClause::AsOf(t)) if t.to_rfc3339() == "2026-01-02T03:04:05+00:00"));
+
+        let (rest, clause) =
+            split_temporal(&tokens("SELECT * FROM hexads VERSIONS BETWEEN '2026-01-01' AND '2026-02-01' LIMIT 5"))
+                .unwrap();
+        assert_eq!(rest, tokens("SELECT * FROM hexads LIMIT 5"));
+        assert!(matches!(clause, Some(TemporalClause::Between(r)) if r.start < r.end));
+
+        assert!(split_temporal(&tokens("SELECT * FROM hexads")).unwrap().1.is_none());
+        assert!(split_temporal(&tokens("SELECT * FROM hexads AS OF 'yesterday'")).is_err());
+        assert!(split_temporal(&tokens("SELECT * FROM hexads VERSIONS BETWEEN '2026-02-01' AND '2026-01-01'")).is_err());
+    }
+
+    #[test]
+    fn test_split_similar() {
+        let (rest, clause) = split_similar(&tokens(
+            "SELECT * FROM hexads WHERE has_document = true SIMILAR TO [1, 0, 0] USING 'minilm' LIMIT 3",
+        ))
+        .unwrap();
+        assert_eq!(rest, tokens("SELECT * FROM hexads WHERE has_document = true LIMIT 3"));
+        assert_eq!(
+            clause,
+            Some(SimilarClause {
+                vector: VectorArg::Literal(vec![1.0, 0.0, 0.0]),
+                model: Some("minilm".to_string()),
+            })
+        );
+        assert_eq!(
+            similar_conditions(&rest).unwrap(),
+            (vec![("has_document".to_string(), "true".to_string())], 3)
+        );
+
+        let (rest, clause) = split_similar(&tokens("SELECT * FROM hexads SIMILAR TO $v")).unwrap();
+        assert_eq!(rest, tokens("SELECT * FROM hexads"));
+        assert_eq!(clause.unwrap().vector, VectorArg::Param("v".to_string()));
+        assert_eq!(similar_conditions(&rest).unwrap(), (vec![], 10));
+
+        assert!(split_similar(&tokens("SELECT * FROM hexads SIMILAR TO LIMIT 3")).is_err());
+    }
+
+    #[test]
+    fn test_parse_subscribe() {
+        let params: std::collections::HashMap<String, Value> = [("kind".to_string(), json!("Paper"))].into();
+        assert_eq!(parse_subscribe("SUBSCRIBE", &params).unwrap(), vec![]);
+        assert_eq!(
+            parse_subscribe("SUBSCRIBE SELECT * FROM hexads WHERE type = $kind AND has_vector = true;", &params).unwrap(),
+            vec![("type".to_string(), "Paper".to_string()), ("has_vector".to_string(), "true".to_string())]
+        );
+        assert_eq!(
+            parse_subscribe("WHERE title = 'Rust'", &params).unwrap(),
+            vec![("title".to_string(), "Rust".to_string())]
+        );
+        assert!(parse_subscribe("SUBSCRIBE SELECT * FROM hexads LIMIT 5", &params).is_err());
+        assert!(parse_subscribe("SUBSCRIBE WHERE nonsense = 1", &params).is_err());
+        assert!(parse_subscribe("SUBSCRIBE WHERE type = $missing", &params).is_err());
+    }
+
+    #[test]
+    fn test_parse_mutation() {
+        let params: std::collections::HashMap<String, Value> =
+            [("v".to_string(), json!([0.5, 0.5])), ("kind".to_string(), json!("Paper"))].into();
+
+        let Mutation::Insert(request) = parse_mutation(
+            "INSERT HEXAD {title: 'A, b', \"body\": \"x {y}\", types: ['Paper'], embedding: $v, metadata: {k: 'v'}}",
+            &params,
+        )
+        .unwrap() else {
+            panic!("expected INSERT");
+        };
+        assert_eq!(request.title.as_deref(), Some("A, b"));
+        assert_eq!(request.body.as_deref(), Some("x {y}"));
+        assert_eq!(request.types, Some(vec!["Paper".to_string()]));
+        assert_eq!(request.embedding, Some(vec![0.5, 0.5]));
+        assert_eq!(request.metadata.unwrap()["k"], "v");
+
+        let Mutation::Update(request, target) =
+            parse_mutation("UPDATE HEXAD 'h-1' SET {title: 'New'}", &params).unwrap()
+        else {
+            panic!("expected UPDATE");
+        };
+        assert_eq!(request.title.as_deref(), Some("New"));
+        assert_eq!(target, MutationTarget::Id("h-1".to_string()));
+
+        let Mutation::Update(_, target) =
+            parse_mutation("UPDATE hexads SET {body: ''} WHERE type = $kind AND version = 2", &params).unwrap()
+        else {
+            panic!("expected UPDATE");
+        };
+        assert_eq!(
+            target,
+            MutationTarget::Where(vec![
+                ("type".to_string(), "Paper".to_string()),
+                ("version".to_string(), "2".to_string()),
+            ])
+        );
+
+        let Mutation::Delete(target) = parse_mutation("DELETE FROM hexads WHERE id = 'h-2'", &params).unwrap() else {
+            panic!("expected DELETE");
+        };
+        assert_eq!(target, MutationTarget::Where(vec![("id".to_string(), "h-2".to_string())]));
+
+        assert!(parse_mutation("INSERT HEXAD {colour: 'red'}", &params).is_err());
+        assert!(parse_mutation("INSERT HEXAD {title: 'unterminated}", &params).is_err());
+        assert!(parse_mutation("INSERT HEXAD {embedding: $missing}", &params).is_err());
+        assert!(parse_mutation("UPDATE hexads SET {title: 'x'}", &params).is_err());
+        assert!(parse_mutation("DELETE FROM hexads", &params).is_err());
+    }
+
     #[test]
     fn test_parse_vector() {
         let v = parse_vector("[0.1, 0.2, 0.3]").unwrap();
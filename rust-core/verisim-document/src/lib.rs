@@ -116,12 +116,52 @@ pub trait DocumentStore: Send + Sync {
     /// Search documents
     async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, DocumentError>;
 
+    /// Search among the documents whose ID starts with `prefix`.  The
+    /// default widens an unfiltered search until it finds `limit` matches
+    /// or runs out of hits.
+    async fn search_prefixed(
+        &self,
+        query: &str,
+        limit: usize,
+        prefix: &str,
+    ) -> Result<Vec<SearchResult>, DocumentError> {
+        let mut fetch = limit.max(1).saturating_mul(4);
+        loop {
+            let results = self.search(query, fetch).await?;
+            let exhausted = results.len() < fetch;
+            let mut matching: Vec<SearchResult> =
+                results.into_iter().filter(|r| r.id.starts_with(prefix)).collect();
+            if matching.len() >= limit || exhausted {
+                matching.truncate(limit);
+                return Ok(matching);
+            }
+            fetch = fetch.saturating_mul(4);
+        }
+    }
+
     /// Get document by ID
     async fn get(&self, id: &str) -> Result<Option<Document>, DocumentError>;
 
     /// Delete document by ID
     async fn delete(&self, id: &str) -> Result<(), DocumentError>;
 
+    /// Index several documents; like [`index`](Self::index), they become
+    /// searchable on the next commit
+    async fn index_batch(&self, docs: &[Document]) -> Result<(), DocumentError> {
+        for doc in docs {
+            self.index(doc).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete several documents by ID
+    async fn delete_batch(&self, ids: &[&str]) -> Result<(), DocumentError> {
+        for id in ids {
+            self.delete(id).await?;
+        }
+        Ok(())
+    }
+
     /// Commit pending changes
     async fn commit(&self) -> Result<(), DocumentError>;
 }
@@ -195,15 +235,21 @@ pub fn persistent(path: impl AsRef<Path>) -> Result<Self, DocumentError> {
             documents: Arc::new(RwLock::new(HashMap::new())),
         })
     }
-}
 
-#[async_trait]
-impl DocumentStore for TantivyDocumentStore {
-    async fn index(&self, doc: &Document) -> Result<(), DocumentError> {
+    /// Tantivy document for a stored document
+    fn tantivy_document(&self, doc: &Document) -> TantivyDocument {
         let mut tantivy_doc = TantivyDocument::default();
         tantivy_doc.add_text(self.schema.id, &doc.id);
         tantivy_doc.add_text(self.schema.title, &doc.title);
         tantivy_doc.add_text(self.schema.body, &doc.body);
+        tantivy_doc
+    }
+}
+
+#[async_trait]
+impl DocumentStore for TantivyDocumentStore {
+    async fn index(&self, doc: &Document) -> Result<(), DocumentError> {
+        let tantivy_doc = self.tantivy_document(doc);
 
         // Delete existing document with same ID
         let term = tantivy::Term::from_field_text(self.schema.id, &doc.id);
@@ -283,6 +329,38 @@ async fn delete(&self, id: &str) -> Result<(), DocumentError> {
         Ok(())
     }
 
+    async fn index_batch(&self, docs: &[Document]) -> Result<(), DocumentError> {
+        // One writer lock for the whole batch
+        {
+            let writer = self.writer.write().await;
+            for doc in docs {
+                writer.delete_term(tantivy::Term::from_field_text(self.schema.id, &doc.id));
+                writer.add_document(self.tantivy_document(doc))?;
+            }
+        }
+
+        let mut documents = self.documents.write().await;
+        for doc in docs {
+            documents.insert(doc.id.clone(), doc.clone());
+        }
+        Ok(())
+    }
+
+    async fn delete_batch(&self, ids: &[&str]) -> Result<(), DocumentError> {
+        {
+            let writer = self.writer.write().await;
+            for id in ids {
+                writer.delete_term(tantivy::Term::from_field_text(self.schema.id, id));
+            }
+        }
+
+        let mut documents = self.documents.write().await;
+        for id in ids {
+            documents.remove(*id);
+        }
+        Ok(())
+    }
+
     async fn commit(&self) -> Result<(), DocumentError> {
         self.writer.write().await.commit()?;
         self.reader.reload()?;
@@ -310,6 +388,42 @@ async fn test_index_and_search() {
         assert_eq!(results[0].id, "d1");
     }
 
+    #[tokio::test]
+    async fn test_index_and_delete_batch() {
+        let store = TantivyDocumentStore::in_memory().unwrap();
+        let docs = vec![
+            Document::new("d1", "Rust Programming", "Rust is a systems programming language"),
+            Document::new("d2", "Rust Async", "Async Rust with tokio"),
+            Document::new("d3", "Python Tutorial", "Python is great for beginners"),
+        ];
+        store.index_batch(&docs).await.unwrap();
+        store.commit().await.unwrap();
+        assert_eq!(store.search("Rust", 10).await.unwrap().len(), 2);
+
+        store.delete_batch(&["d1", "d3"]).await.unwrap();
+        store.commit().await.unwrap();
+        let results = store.search("Rust", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "d2");
+        assert!(store.get("d3").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_prefixed() {
+        let store = TantivyDocumentStore::in_memory().unwrap();
+        let docs = vec![
+            Document::new("a:d1", "Rust Programming", "Rust is a systems programming language"),
+            Document::new("b:d2", "Rust Async", "Async Rust with tokio"),
+            Document::new("b:d3", "Python Tutorial", "Python is great for beginners"),
+        ];
+        store.index_batch(&docs).await.unwrap();
+        store.commit().await.unwrap();
+
+        let results = store.search_prefixed("Rust", 10, "b:").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "b:d2");
+    }
+
     #[tokio::test]
     async fn test_search_with_snippets() {
         let store = TantivyDocumentStore::in_memory().unwrap();
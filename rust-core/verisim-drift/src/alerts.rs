@@ -0,0 +1,726 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift alert delivery
+//!
+//! Consumes [`DriftEvent`]s from the detector's event channel and forwards
+//! them to configured sinks (HTTP webhook, SMTP email, PagerDuty Events v2).
+//!
+//! Each sink has a minimum severity.  Repeats of the same drift type at the
+//! same severity are suppressed per sink for a cooldown window, so a scan
+//! that flags hundreds of entities produces one alert rather than hundreds;
+//! an escalation to a higher severity is a new alert.  Failed deliveries are
+//! retried with exponential backoff.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::{DriftError, DriftEvent, DriftSeverity, DriftType};
+
+/// PagerDuty Events API v2 endpoint.
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Where alerts are delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertSinkKind {
+    /// POST the drift event as JSON
+    Webhook {
+        url: String,
+        /// Extra request headers (e.g. authorization)
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Plain-text mail through an SMTP relay.  No TLS or authentication:
+    /// point this at a local or otherwise trusted relay.
+    Email {
+        smtp_host: String,
+        #[serde(default = "default_smtp_port")]
+        smtp_port: u16,
+        from: String,
+        to: Vec<String>,
+    },
+    /// Trigger a PagerDuty incident through the Events API v2
+    PagerDuty {
+        routing_key: String,
+        #[serde(default = "default_pagerduty_url")]
+        events_url: String,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+fn default_pagerduty_url() -> String {
+    PAGERDUTY_EVENTS_URL.to_string()
+}
+
+/// One configured sink
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertSinkConfig {
+    /// Name used in logs, statistics and deduplication
+    pub name: String,
+    /// Least severe drift this sink receives
+    pub min_severity: DriftSeverity,
+    #[serde(flatten)]
+    pub kind: AlertSinkKind,
+}
+
+/// Alert delivery configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    /// Configured sinks
+    pub sinks: Vec<AlertSinkConfig>,
+    /// Suppress repeats of the same (sink, drift type, severity) for this long
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// Delivery attempts after the first failure
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+}
+
+fn default_cooldown_secs() -> u64 {
+    900
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            cooldown_secs: default_cooldown_secs(),
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+        }
+    }
+}
+
+/// A destination for drift alerts
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    /// Sink name
+    fn name(&self) -> &str;
+
+    /// Deliver one alert.  Errors are retried by the dispatcher.
+    async fn deliver(&self, event: &DriftEvent) -> Result<(), DriftError>;
+}
+
+/// Delivery counters for one sink
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SinkStats {
+    /// Alerts delivered
+    pub delivered: u64,
+    /// Alerts dropped as repeats within the cooldown window
+    pub suppressed: u64,
+    /// Alerts that failed after every retry
+    pub failed: u64,
+    /// Time of the last successful delivery
+    pub last_delivered: Option<DateTime<Utc>>,
+    /// Error from the last failed delivery
+    pub last_error: Option<String>,
+}
+
+/// What happened to an event at one sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    /// Below the sink's minimum severity
+    Filtered,
+    /// Repeat within the cooldown window
+    Suppressed,
+    /// Delivered after the given number of attempts
+    Delivered { attempts: u32 },
+    /// Every attempt failed
+    Failed,
+}
+
+struct Route {
+    sink: Arc<dyn AlertSink>,
+    min_severity: DriftSeverity,
+}
+
+type DedupKey = (String, DriftType, DriftSeverity);
+
+/// Routes drift events to sinks with deduplication and retries
+pub struct AlertDispatcher {
+    routes: Vec<Route>,
+    cooldown: chrono::Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+    last_sent: Mutex<HashMap<DedupKey, DateTime<Utc>>>,
+    stats: Mutex<HashMap<String, SinkStats>>,
+}
+
+impl AlertDispatcher {
+    /// Create a dispatcher with no sinks
+    pub fn new(cooldown_secs: u64, max_retries: u32, retry_backoff_ms: u64) -> Self {
+        Self {
+            routes: Vec::new(),
+            cooldown: chrono::Duration::seconds(cooldown_secs as i64),
+            max_retries,
+            retry_backoff: Duration::from_millis(retry_backoff_ms),
+            last_sent: Mutex::new(HashMap::new()),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a dispatcher with the sinks described by `config`
+    pub fn from_config(config: &AlertConfig) -> Self {
+        // Built on first use so email-only configurations need no TLS provider.
+        let mut client: Option<reqwest::Client> = None;
+        let mut http_client = || {
+            client
+                .get_or_insert_with(|| {
+                    reqwest::Client::builder()
+                        .timeout(Duration::from_secs(10))
+                        .build()
+                        .unwrap_or_default()
+                })
+                .clone()
+        };
+        let mut dispatcher = Self::new(config.cooldown_secs, config.max_retries, config.retry_backoff_ms);
+        for sink in &config.sinks {
+            let name = sink.name.clone();
+            let built: Arc<dyn AlertSink> = match &sink.kind {
+                AlertSinkKind::Webhook { url, headers } => Arc::new(WebhookSink {
+                    name,
+                    client: http_client(),
+                    url: url.clone(),
+                    headers: headers.clone(),
+                }),
+                AlertSinkKind::Email {
+                    smtp_host,
+                    smtp_port,
+                    from,
+                    to,
+                } => Arc::new(EmailSink {
+                    name,
+                    smtp_host: smtp_host.clone(),
+                    smtp_port: *smtp_port,
+                    from: from.clone(),
+                    to: to.clone(),
+                }),
+                AlertSinkKind::PagerDuty {
+                    routing_key,
+                    events_url,
+                } => Arc::new(PagerDutySink {
+                    name,
+                    client: http_client(),
+                    routing_key: routing_key.clone(),
+                    events_url: events_url.clone(),
+                }),
+            };
+            dispatcher = dispatcher.with_sink(built, sink.min_severity);
+        }
+        dispatcher
+    }
+
+    /// Add a sink receiving events at or above `min_severity`
+    pub fn with_sink(mut self, sink: Arc<dyn AlertSink>, min_severity: DriftSeverity) -> Self {
+        self.routes.push(Route { sink, min_severity });
+        self
+    }
+
+    /// Delivery counters per sink
+    pub fn stats(&self) -> Result<HashMap<String, SinkStats>, DriftError> {
+        let mut stats = self.stats.lock().map_err(|_| DriftError::LockPoisoned)?.clone();
+        for route in &self.routes {
+            stats.entry(route.sink.name().to_string()).or_default();
+        }
+        Ok(stats)
+    }
+
+    /// Deliver an event to every sink it qualifies for.
+    pub async fn dispatch(&self, event: &DriftEvent) -> Result<Vec<DeliveryOutcome>, DriftError> {
+        let mut outcomes = Vec::with_capacity(self.routes.len());
+        for route in &self.routes {
+            let name = route.sink.name().to_string();
+            if event.severity < route.min_severity {
+                outcomes.push(DeliveryOutcome::Filtered);
+                continue;
+            }
+
+            let key = (name.clone(), event.drift_type, event.severity);
+            let suppressed = {
+                let last_sent = self.last_sent.lock().map_err(|_| DriftError::LockPoisoned)?;
+                last_sent
+                    .get(&key)
+                    .is_some_and(|sent| event.detected_at.signed_duration_since(*sent) < self.cooldown)
+            };
+            if suppressed {
+                self.update_stats(&name, |s| s.suppressed += 1)?;
+                outcomes.push(DeliveryOutcome::Suppressed);
+                continue;
+            }
+
+            let outcome = self.deliver_with_retry(route.sink.as_ref(), event).await;
+            match &outcome {
+                Ok(attempts) => {
+                    self.last_sent
+                        .lock()
+                        .map_err(|_| DriftError::LockPoisoned)?
+                        .insert(key, event.detected_at);
+                    self.update_stats(&name, |s| {
+                        s.delivered += 1;
+                        s.last_delivered = Some(Utc::now());
+                    })?;
+                    debug!(sink = %name, attempts, drift_type = %event.drift_type, "Drift alert delivered");
+                    outcomes.push(DeliveryOutcome::Delivered { attempts: *attempts });
+                }
+                Err(e) => {
+                    warn!(sink = %name, error = %e, drift_type = %event.drift_type, "Drift alert delivery failed");
+                    let message = e.to_string();
+                    self.update_stats(&name, |s| {
+                        s.failed += 1;
+                        s.last_error = Some(message);
+                    })?;
+                    outcomes.push(DeliveryOutcome::Failed);
+                }
+            }
+        }
+        Ok(outcomes)
+    }
+
+    /// Dispatch every event received on `events` until the channel closes.
+    pub fn spawn(self: Arc<Self>, mut events: mpsc::Receiver<DriftEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = self.dispatch(&event).await {
+                    warn!(error = %e, "Drift alert dispatch failed");
+                }
+            }
+            info!("Drift event channel closed; alert dispatcher stopping");
+        })
+    }
+
+    async fn deliver_with_retry(&self, sink: &dyn AlertSink, event: &DriftEvent) -> Result<u32, DriftError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match sink.deliver(event).await {
+                Ok(()) => return Ok(attempt),
+                Err(e) if attempt > self.max_retries => return Err(e),
+                Err(e) => {
+                    debug!(sink = %sink.name(), attempt, error = %e, "Retrying drift alert");
+                    tokio::time::sleep(self.retry_backoff * 2u32.saturating_pow(attempt - 1)).await;
+                }
+            }
+        }
+    }
+
+    fn update_stats(&self, sink: &str, f: impl FnOnce(&mut SinkStats)) -> Result<(), DriftError> {
+        let mut stats = self.stats.lock().map_err(|_| DriftError::LockPoisoned)?;
+        f(stats.entry(sink.to_string()).or_default());
+        Ok(())
+    }
+}
+
+/// One-line summary used as subject / incident title.
+fn summary(event: &DriftEvent) -> String {
+    format!(
+        "[{:?}] {} score {:.3} ({} entities)",
+        event.severity,
+        event.drift_type,
+        event.score,
+        event.affected_entities.len()
+    )
+}
+
+/// HTTP webhook sink
+struct WebhookSink {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, event: &DriftEvent) -> Result<(), DriftError> {
+        let mut request = self.client.post(&self.url).json(event);
+        for (k, v) in &self.headers {
+            request = request.header(k, v);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DriftError::AlertDelivery(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(DriftError::AlertDelivery(format!(
+                "webhook returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// PagerDuty Events API v2 sink
+struct PagerDutySink {
+    name: String,
+    client: reqwest::Client,
+    routing_key: String,
+    events_url: String,
+}
+
+/// Build a PagerDuty Events v2 trigger.  The dedup key groups repeats of a
+/// drift type into one incident.
+pub fn pagerduty_payload(routing_key: &str, event: &DriftEvent) -> serde_json::Value {
+    let severity = match event.severity {
+        DriftSeverity::Emergency => "critical",
+        DriftSeverity::Critical => "error",
+        DriftSeverity::Warning => "warning",
+        DriftSeverity::Info => "info",
+    };
+    serde_json::json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": format!("verisimdb-{}", event.drift_type),
+        "payload": {
+            "summary": summary(event),
+            "source": "verisimdb",
+            "severity": severity,
+            "timestamp": event.detected_at.to_rfc3339(),
+            "component": event.drift_type.to_string(),
+            "custom_details": event,
+        }
+    })
+}
+
+#[async_trait]
+impl AlertSink for PagerDutySink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, event: &DriftEvent) -> Result<(), DriftError> {
+        let response = self
+            .client
+            .post(&self.events_url)
+            .json(&pagerduty_payload(&self.routing_key, event))
+            .send()
+            .await
+            .map_err(|e| DriftError::AlertDelivery(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(DriftError::AlertDelivery(format!(
+                "PagerDuty returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// SMTP email sink
+struct EmailSink {
+    name: String,
+    smtp_host: String,
+    smtp_port: u16,
+    from: String,
+    to: Vec<String>,
+}
+
+impl EmailSink {
+    fn message(&self, event: &DriftEvent) -> String {
+        let mut body = format!(
+            "Drift type: {}\r\nSeverity: {:?}\r\nScore: {:.3}\r\nDetected: {}\r\n\r\n{}\r\n",
+            event.drift_type,
+            event.severity,
+            event.score,
+            event.detected_at.to_rfc3339(),
+            event.description
+        );
+        if let Some(remediation) = &event.remediation {
+            body.push_str(&format!("\r\nRemediation: {}\r\n", remediation));
+        }
+        if !event.affected_entities.is_empty() {
+            body.push_str("\r\nAffected entities:\r\n");
+            for id in &event.affected_entities {
+                body.push_str(&format!("  {}\r\n", id));
+            }
+        }
+        // Dot-stuffing (RFC 5321 §4.5.2)
+        let body = body
+            .split("\r\n")
+            .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: VeriSimDB drift alert: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+            self.from,
+            self.to.join(", "),
+            summary(event),
+            event.detected_at.to_rfc2822(),
+            body
+        )
+    }
+}
+
+/// Send one SMTP command and check the reply code class.
+async fn smtp_command(
+    reader: &mut BufReader<TcpStream>,
+    command: Option<&str>,
+    expect: char,
+) -> Result<(), DriftError> {
+    if let Some(command) = command {
+        reader
+            .get_mut()
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .await
+            .map_err(|e| DriftError::AlertDelivery(format!("SMTP write: {}", e)))?;
+    }
+    // Multi-line replies continue with "NNN-"; the last line is "NNN ".
+    loop {
+        let mut line = String::new();
+        let n = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| DriftError::AlertDelivery(format!("SMTP read: {}", e)))?;
+        if n == 0 {
+            return Err(DriftError::AlertDelivery("SMTP connection closed".to_string()));
+        }
+        if !line.starts_with(expect) {
+            return Err(DriftError::AlertDelivery(format!("SMTP: {}", line.trim_end())));
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for EmailSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, event: &DriftEvent) -> Result<(), DriftError> {
+        let stream = TcpStream::connect((self.smtp_host.as_str(), self.smtp_port))
+            .await
+            .map_err(|e| DriftError::AlertDelivery(format!("SMTP connect: {}", e)))?;
+        let mut reader = BufReader::new(stream);
+
+        smtp_command(&mut reader, None, '2').await?;
+        smtp_command(&mut reader, Some("EHLO verisimdb"), '2').await?;
+        smtp_command(&mut reader, Some(&format!("MAIL FROM:<{}>", self.from)), '2').await?;
+        for to in &self.to {
+            smtp_command(&mut reader, Some(&format!("RCPT TO:<{}>", to)), '2').await?;
+        }
+        smtp_command(&mut reader, Some("DATA"), '3').await?;
+        smtp_command(&mut reader, Some(&format!("{}\r\n.", self.message(event))), '2').await?;
+        smtp_command(&mut reader, Some("QUIT"), '2').await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Sink that fails a fixed number of times before succeeding
+    struct FlakySink {
+        failures_left: AtomicU32,
+        calls: AtomicU32,
+    }
+
+    impl FlakySink {
+        fn new(failures: u32) -> Arc<Self> {
+            Arc::new(Self {
+                failures_left: AtomicU32::new(failures),
+                calls: AtomicU32::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl AlertSink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn deliver(&self, _event: &DriftEvent) -> Result<(), DriftError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(DriftError::AlertDelivery("unavailable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    fn event(score: f64) -> DriftEvent {
+        DriftEvent::new(DriftType::GraphDocumentDrift, score, "test drift")
+    }
+
+    #[tokio::test]
+    async fn test_severity_filter_and_cooldown() {
+        let sink = FlakySink::new(0);
+        let dispatcher = AlertDispatcher::new(900, 0, 1).with_sink(sink.clone(), DriftSeverity::Critical);
+
+        assert_eq!(dispatcher.dispatch(&event(0.6)).await.unwrap(), vec![DeliveryOutcome::Filtered]);
+        assert_eq!(
+            dispatcher.dispatch(&event(0.8)).await.unwrap(),
+            vec![DeliveryOutcome::Delivered { attempts: 1 }]
+        );
+        assert_eq!(dispatcher.dispatch(&event(0.8)).await.unwrap(), vec![DeliveryOutcome::Suppressed]);
+        // Escalation is not a repeat.
+        assert_eq!(
+            dispatcher.dispatch(&event(0.95)).await.unwrap(),
+            vec![DeliveryOutcome::Delivered { attempts: 1 }]
+        );
+
+        let stats = dispatcher.stats().unwrap();
+        assert_eq!(stats["flaky"].delivered, 2);
+        assert_eq!(stats["flaky"].suppressed, 1);
+        assert_eq!(sink.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_gives_up() {
+        let sink = FlakySink::new(2);
+        let dispatcher = AlertDispatcher::new(0, 3, 1).with_sink(sink.clone(), DriftSeverity::Info);
+        assert_eq!(
+            dispatcher.dispatch(&event(0.8)).await.unwrap(),
+            vec![DeliveryOutcome::Delivered { attempts: 3 }]
+        );
+
+        let sink = FlakySink::new(10);
+        let dispatcher = AlertDispatcher::new(0, 2, 1).with_sink(sink.clone(), DriftSeverity::Info);
+        assert_eq!(dispatcher.dispatch(&event(0.8)).await.unwrap(), vec![DeliveryOutcome::Failed]);
+        assert_eq!(sink.calls.load(Ordering::SeqCst), 3);
+        let stats = dispatcher.stats().unwrap();
+        assert_eq!(stats["flaky"].failed, 1);
+        assert!(stats["flaky"].last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_consumes_detector_channel() {
+        let (tx, rx) = mpsc::channel(16);
+        let detector = crate::DriftDetector::with_defaults().with_event_channel(tx);
+        let sink = FlakySink::new(0);
+        let dispatcher = Arc::new(AlertDispatcher::new(900, 0, 1).with_sink(sink.clone(), DriftSeverity::Warning));
+        let handle = dispatcher.clone().spawn(rx);
+
+        detector.record(DriftType::TensorDrift, 0.8, vec!["e1".into()]).await.unwrap();
+        detector.record(DriftType::TensorDrift, 0.8, vec!["e2".into()]).await.unwrap();
+        drop(detector);
+        handle.await.unwrap();
+
+        let stats = dispatcher.stats().unwrap();
+        assert_eq!(stats["flaky"].delivered, 1);
+        assert_eq!(stats["flaky"].suppressed, 1);
+    }
+
+    #[test]
+    fn test_pagerduty_payload() {
+        let payload = pagerduty_payload("key-123", &event(0.95));
+        assert_eq!(payload["routing_key"], "key-123");
+        assert_eq!(payload["event_action"], "trigger");
+        assert_eq!(payload["payload"]["severity"], "critical");
+        assert_eq!(payload["dedup_key"], "verisimdb-graph_document_drift");
+    }
+
+    #[test]
+    fn test_sink_config_deserializes() {
+        let config: AlertConfig = serde_json::from_str(
+            r#"{"sinks": [
+                {"name": "ops", "min_severity": "Critical", "type": "pager_duty", "routing_key": "k"},
+                {"name": "mail", "min_severity": "Warning", "type": "email",
+                 "smtp_host": "localhost", "from": "a@example.org", "to": ["b@example.org"]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.cooldown_secs, 900);
+        assert!(matches!(
+            &config.sinks[0].kind,
+            AlertSinkKind::PagerDuty { events_url, .. } if events_url == PAGERDUTY_EVENTS_URL
+        ));
+        assert!(matches!(&config.sinks[1].kind, AlertSinkKind::Email { smtp_port: 25, .. }));
+
+        let email_only = AlertConfig {
+            sinks: config.sinks[1..].to_vec(),
+            ..config
+        };
+        assert_eq!(AlertDispatcher::from_config(&email_only).stats().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_email_sink_speaks_smtp() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            reader.get_mut().write_all(b"220 test ESMTP\r\n").await.unwrap();
+            let mut transcript = Vec::new();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        transcript.push(line);
+                        continue;
+                    }
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-test\r\n250 OK\r\n"
+                } else if line == "QUIT" {
+                    reader.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                reader.get_mut().write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+
+        let sink = EmailSink {
+            name: "mail".to_string(),
+            smtp_host: "127.0.0.1".to_string(),
+            smtp_port: port,
+            from: "verisimdb@example.org".to_string(),
+            to: vec!["ops@example.org".to_string()],
+        };
+        sink.deliver(&event(0.8).with_entities(vec!["e1".into()])).await.unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.iter().any(|l| l.starts_with("Subject: VeriSimDB drift alert: [Critical]")));
+        assert!(transcript.iter().any(|l| l == "  e1"));
+    }
+}
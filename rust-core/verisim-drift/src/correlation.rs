@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Cross-drift correlation
+//!
+//! Drift in one modality often follows drift in another: a schema change
+//! degrades quality scores an hour later, a re-embedding shifts vector drift
+//! before graph-document drift.  This module resamples the recorded drift
+//! history of each type onto a common grid of time buckets and computes the
+//! lagged correlation of every pair, so that a type whose rises reliably
+//! precede another's can be reported as a probable root cause.
+//!
+//! Correlation is not causation; the results rank candidates for a human to
+//! check, nothing acts on them automatically.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::stats;
+use crate::DriftType;
+
+/// Correlation analysis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationConfig {
+    /// Width of the buckets series are resampled onto (seconds)
+    #[serde(default = "default_bucket_secs")]
+    pub bucket_secs: u64,
+    /// Largest lead tried, in buckets
+    #[serde(default = "default_max_lag_buckets")]
+    pub max_lag_buckets: usize,
+    /// Buckets both series must have samples in for a coefficient to count
+    #[serde(default = "default_min_overlap")]
+    pub min_overlap: usize,
+    /// Weakest coefficient reported
+    #[serde(default = "default_min_coefficient")]
+    pub min_coefficient: f64,
+}
+
+fn default_bucket_secs() -> u64 {
+    300
+}
+
+fn default_max_lag_buckets() -> usize {
+    12
+}
+
+fn default_min_overlap() -> usize {
+    8
+}
+
+fn default_min_coefficient() -> f64 {
+    0.5
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: default_bucket_secs(),
+            max_lag_buckets: default_max_lag_buckets(),
+            min_overlap: default_min_overlap(),
+            min_coefficient: default_min_coefficient(),
+        }
+    }
+}
+
+/// Correlation between two drift types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftCorrelation {
+    /// Type whose changes come first
+    pub leader: DriftType,
+    /// Type whose changes follow
+    pub follower: DriftType,
+    /// How far the follower trails the leader; zero when they move together
+    pub lag_secs: u64,
+    /// Pearson coefficient at that lag
+    pub coefficient: f64,
+    /// Buckets the coefficient is based on
+    pub overlap: usize,
+}
+
+/// Correlate every pair of drift types over `[start, end)`.
+///
+/// For each ordered pair the lag (up to `config.max_lag_buckets`) with the
+/// strongest positive correlation is kept; pairs below
+/// `config.min_coefficient` are dropped.  Pairs moving together (lag zero)
+/// are reported once.  Results are strongest first.
+pub fn correlate(
+    series: &HashMap<DriftType, Vec<(DateTime<Utc>, f64)>>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    config: &CorrelationConfig,
+) -> Vec<DriftCorrelation> {
+    let width = i64::try_from(config.bucket_secs.max(1))
+        .ok()
+        .and_then(Duration::try_seconds)
+        .unwrap_or(Duration::MAX);
+    let span_ms = end.signed_duration_since(start).num_milliseconds().max(0);
+    let buckets = (span_ms / width.num_milliseconds()) as usize + 1;
+
+    let mut grids: Vec<(DriftType, Vec<Option<f64>>)> = series
+        .iter()
+        .map(|(t, s)| (*t, stats::bucket_means(s, start, width, buckets)))
+        .collect();
+    grids.sort_by_key(|(t, _)| t.to_string());
+
+    let mut correlations = Vec::new();
+    for (i, (leader, leader_grid)) in grids.iter().enumerate() {
+        for (j, (follower, follower_grid)) in grids.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            // Lag zero is symmetric; only the first ordering considers it.
+            let first_lag = if i < j { 0 } else { 1 };
+            let best = (first_lag..=config.max_lag_buckets)
+                .filter_map(|lag| {
+                    stats::lagged_correlation(leader_grid, follower_grid, lag)
+                        .filter(|(_, overlap)| *overlap >= config.min_overlap.max(2))
+                        .map(|(r, overlap)| (lag, r, overlap))
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((lag, coefficient, overlap)) = best.filter(|b| b.1 >= config.min_coefficient) {
+                correlations.push(DriftCorrelation {
+                    leader: *leader,
+                    follower: *follower,
+                    lag_secs: (lag as u64).saturating_mul(config.bucket_secs.max(1)),
+                    coefficient,
+                    overlap,
+                });
+            }
+        }
+    }
+    correlations.sort_by(|a, b| b.coefficient.total_cmp(&a.coefficient));
+    correlations
+}
+
+/// Probable root causes of `symptom`: types whose changes precede it,
+/// strongest first.
+pub fn root_causes(correlations: &[DriftCorrelation], symptom: DriftType) -> Vec<DriftCorrelation> {
+    correlations
+        .iter()
+        .filter(|c| c.follower == symptom && c.lag_secs > 0)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_drift_precedes_quality_drift() {
+        let start = Utc::now() - Duration::hours(4);
+        let pattern = [0.1, 0.6, 0.2, 0.8, 0.3, 0.5, 0.1, 0.9, 0.2, 0.7, 0.4, 0.3, 0.6, 0.2, 0.8, 0.1];
+        let at = |i: usize| start + Duration::minutes(5 * i as i64);
+
+        let schema: Vec<_> = pattern.iter().enumerate().map(|(i, s)| (at(i), *s)).collect();
+        // Quality drift repeats schema drift three buckets (15 minutes) later.
+        let quality: Vec<_> = pattern.iter().enumerate().map(|(i, s)| (at(i + 3), *s)).collect();
+        // A flat series correlates with nothing.
+        let tensor: Vec<_> = (0..16).map(|i| (at(i), 0.2)).collect();
+
+        let series = HashMap::from([
+            (DriftType::SchemaDrift, schema),
+            (DriftType::QualityDrift, quality),
+            (DriftType::TensorDrift, tensor),
+        ]);
+        let correlations = correlate(&series, start, at(20), &CorrelationConfig::default());
+
+        let causes = root_causes(&correlations, DriftType::QualityDrift);
+        assert_eq!(causes[0].leader, DriftType::SchemaDrift);
+        assert_eq!(causes[0].lag_secs, 900);
+        assert!(causes[0].coefficient > 0.99);
+        assert!(root_causes(&correlations, DriftType::SchemaDrift).is_empty());
+        assert!(correlations.iter().all(|c| c.leader != DriftType::TensorDrift && c.follower != DriftType::TensorDrift));
+
+        // Any bucket width resamples without overflowing
+        let config = CorrelationConfig {
+            bucket_secs: u64::MAX,
+            ..Default::default()
+        };
+        assert!(correlate(&series, start, at(20), &config).is_empty());
+    }
+
+    #[test]
+    fn test_sparse_series_are_ignored() {
+        let start = Utc::now();
+        let series = HashMap::from([
+            (DriftType::SchemaDrift, vec![(start, 0.1), (start + Duration::minutes(5), 0.9)]),
+            (DriftType::QualityDrift, vec![(start, 0.2), (start + Duration::minutes(5), 0.8)]),
+        ]);
+        let correlations = correlate(&series, start, start + Duration::hours(1), &CorrelationConfig::default());
+        assert!(correlations.is_empty());
+    }
+}
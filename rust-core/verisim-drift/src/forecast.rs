@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift forecasting
+//!
+//! Extrapolates the recent trend of a drift score to predict when it will
+//! cross its threshold, so an early warning can go out before the crossing
+//! rather than after it.
+//!
+//! Two estimators are available:
+//! - **Linear**: least-squares fit of score against time over a window of
+//!   recent samples.
+//! - **EWMA**: exponentially weighted level and slope (Holt's linear trend),
+//!   which reacts faster to a change of direction.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::DriftType;
+
+/// Trend estimator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForecastMethod {
+    /// Least-squares linear regression
+    #[default]
+    Linear,
+    /// Exponentially weighted level and slope
+    Ewma,
+}
+
+/// Forecasting configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastConfig {
+    /// Trend estimator
+    #[serde(default)]
+    pub method: ForecastMethod,
+    /// Warn when the threshold is predicted to be crossed within this many seconds
+    #[serde(default = "default_horizon_secs")]
+    pub horizon_secs: u64,
+    /// Samples needed before forecasting
+    #[serde(default = "default_min_samples")]
+    pub min_samples: usize,
+    /// Most recent samples considered
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// Smoothing factor for the EWMA estimator, in (0, 1]
+    #[serde(default = "default_ewma_alpha")]
+    pub ewma_alpha: f64,
+}
+
+fn default_horizon_secs() -> u64 {
+    3600
+}
+
+fn default_min_samples() -> usize {
+    5
+}
+
+fn default_window() -> usize {
+    20
+}
+
+fn default_ewma_alpha() -> f64 {
+    0.3
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            method: ForecastMethod::default(),
+            horizon_secs: default_horizon_secs(),
+            min_samples: default_min_samples(),
+            window: default_window(),
+            ewma_alpha: default_ewma_alpha(),
+        }
+    }
+}
+
+/// Predicted course of one drift type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftForecast {
+    pub drift_type: DriftType,
+    pub method: ForecastMethod,
+    /// Trend-adjusted current score
+    pub current_score: f64,
+    /// Threshold the forecast is measured against
+    pub threshold: f64,
+    /// Estimated change in score per second
+    pub slope_per_sec: f64,
+    /// Seconds the forecast looks ahead
+    pub horizon_secs: u64,
+    /// Score expected at the end of the horizon, clamped to [0, 1]
+    pub predicted_score: f64,
+    /// Seconds until the threshold is crossed; `None` if the trend never
+    /// reaches it, zero if it already has
+    pub time_to_threshold_secs: Option<f64>,
+    /// Samples the forecast is based on
+    pub samples: usize,
+}
+
+impl DriftForecast {
+    /// Whether the threshold is predicted to be crossed within the horizon
+    pub fn crosses_within_horizon(&self) -> bool {
+        self.time_to_threshold_secs
+            .is_some_and(|t| t <= self.horizon_secs as f64)
+    }
+}
+
+/// Forecast a drift type from its `(time, score)` history (oldest first).
+///
+/// Returns `None` with fewer than `config.min_samples` samples or when all
+/// samples share a timestamp.
+pub fn forecast(
+    drift_type: DriftType,
+    history: &[(DateTime<Utc>, f64)],
+    threshold: f64,
+    config: &ForecastConfig,
+) -> Option<DriftForecast> {
+    let window = &history[history.len().saturating_sub(config.window.max(2))..];
+    if window.len() < config.min_samples.max(2) {
+        return None;
+    }
+
+    let origin = window[0].0;
+    let points: Vec<(f64, f64)> = window
+        .iter()
+        .map(|(t, s)| ((*t - origin).num_milliseconds() as f64 / 1000.0, *s))
+        .collect();
+    let span = points.last()?.0;
+    if span <= 0.0 {
+        return None;
+    }
+
+    let (level, slope) = match config.method {
+        ForecastMethod::Linear => linear_trend(&points)?,
+        ForecastMethod::Ewma => ewma_trend(&points, config.ewma_alpha.clamp(f64::EPSILON, 1.0)),
+    };
+
+    let time_to_threshold_secs = if level >= threshold {
+        Some(0.0)
+    } else if slope > 0.0 {
+        Some((threshold - level) / slope)
+    } else {
+        None
+    };
+
+    Some(DriftForecast {
+        drift_type,
+        method: config.method,
+        current_score: level,
+        threshold,
+        slope_per_sec: slope,
+        horizon_secs: config.horizon_secs,
+        predicted_score: (level + slope * config.horizon_secs as f64).clamp(0.0, 1.0),
+        time_to_threshold_secs,
+        samples: window.len(),
+    })
+}
+
+/// Least-squares fit; returns the fitted value at the last sample and the slope.
+fn linear_trend(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_s = points.iter().map(|(_, s)| s).sum::<f64>() / n;
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), (t, s)| {
+        (cov + (t - mean_t) * (s - mean_s), var + (t - mean_t).powi(2))
+    });
+    if var == 0.0 {
+        return None;
+    }
+    let slope = cov / var;
+    let last_t = points.last()?.0;
+    Some((mean_s + slope * (last_t - mean_t), slope))
+}
+
+/// Holt's linear trend with irregular sample spacing.
+fn ewma_trend(points: &[(f64, f64)], alpha: f64) -> (f64, f64) {
+    let mut level = points[0].1;
+    let mut slope = 0.0;
+    for pair in points.windows(2) {
+        let dt = pair[1].0 - pair[0].0;
+        if dt <= 0.0 {
+            level = alpha * pair[1].1 + (1.0 - alpha) * level;
+            continue;
+        }
+        let predicted = level + slope * dt;
+        let new_level = alpha * pair[1].1 + (1.0 - alpha) * predicted;
+        slope = alpha * ((new_level - level) / dt) + (1.0 - alpha) * slope;
+        level = new_level;
+    }
+    (level, slope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rising(n: usize, start: f64, step: f64) -> Vec<(DateTime<Utc>, f64)> {
+        let t0 = Utc::now() - chrono::Duration::minutes(n as i64);
+        (0..n)
+            .map(|i| (t0 + chrono::Duration::minutes(i as i64), start + step * i as f64))
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_time_to_threshold() {
+        // +0.01 per minute from 0.1: reaches 0.3 ten minutes after the last sample (0.2).
+        let history = rising(11, 0.1, 0.01);
+        let f = forecast(DriftType::TensorDrift, &history, 0.3, &ForecastConfig::default()).unwrap();
+        assert!((f.current_score - 0.2).abs() < 1e-9);
+        assert!((f.time_to_threshold_secs.unwrap() - 600.0).abs() < 1e-6);
+        assert!(f.crosses_within_horizon());
+
+        let short = ForecastConfig {
+            horizon_secs: 300,
+            ..Default::default()
+        };
+        let f = forecast(DriftType::TensorDrift, &history, 0.3, &short).unwrap();
+        assert!(!f.crosses_within_horizon());
+    }
+
+    #[test]
+    fn test_ewma_follows_trend() {
+        let history = rising(20, 0.1, 0.01);
+        let config = ForecastConfig {
+            method: ForecastMethod::Ewma,
+            ..Default::default()
+        };
+        let f = forecast(DriftType::TensorDrift, &history, 0.5, &config).unwrap();
+        assert!(f.slope_per_sec > 0.0);
+        assert!(f.time_to_threshold_secs.is_some());
+    }
+
+    #[test]
+    fn test_flat_or_falling_never_crosses() {
+        let history = rising(10, 0.25, -0.01);
+        let f = forecast(DriftType::TensorDrift, &history, 0.3, &ForecastConfig::default()).unwrap();
+        assert!(f.time_to_threshold_secs.is_none());
+        assert!(forecast(DriftType::TensorDrift, &history[..3], 0.3, &ForecastConfig::default()).is_none());
+    }
+}
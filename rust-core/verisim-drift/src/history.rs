@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift history
+//!
+//! Every measurement the detector records is appended to a time-series store
+//! (one series per drift type), together with the event it raised, if any.
+//! With a persistent store the history outlives the in-memory window kept in
+//! [`crate::DriftMetrics`] and survives restarts.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use verisim_temporal::TimeSeriesStore;
+
+use crate::{DriftEvent, DriftType};
+
+/// One recorded drift measurement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftSample {
+    /// Measured drift score
+    pub score: f64,
+    /// Moving average after this measurement
+    pub moving_average: f64,
+    /// Threshold the score was compared against
+    pub threshold: f64,
+    /// Event raised by this measurement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<DriftEvent>,
+}
+
+/// A drift sample with its type and time, as returned by history queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftHistoryPoint {
+    pub drift_type: DriftType,
+    pub time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub sample: DriftSample,
+}
+
+/// Time-series store holding drift history
+pub type DriftHistoryStore = dyn TimeSeriesStore<Value = DriftSample>;
+
+/// Series id under which samples of a drift type are stored
+pub fn series_id(drift_type: DriftType) -> String {
+    format!("drift:{}", drift_type)
+}
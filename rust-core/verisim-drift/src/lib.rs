@@ -5,12 +5,49 @@
 //! This is the "early warning system" for data quality issues.
 
 use chrono::{DateTime, Utc};
-use prometheus::{Counter, Gauge, Registry};
+use prometheus::{Counter, CounterVec, Gauge, GaugeVec, Opts, Registry};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use thiserror::Error;
 use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use verisim_temporal::{TimePoint, TimeRange};
+
+// Alert delivery to external sinks
+pub mod alerts;
+pub use alerts::{AlertConfig, AlertDispatcher, AlertSink, AlertSinkConfig, AlertSinkKind, SinkStats};
+
+// Persistent measurement and event history
+pub mod history;
+pub use history::{DriftHistoryPoint, DriftHistoryStore, DriftSample};
+
+// Population-level embedding drift (PSI/KL)
+pub mod population;
+pub use population::{PopulationConfig, PopulationDrift, PopulationMonitor, PopulationStatus};
+
+// Maintenance windows that pause alerting and normalization
+pub mod suppression;
+pub use suppression::{SuppressionRegistry, SuppressionWindow};
+
+// Severity-to-action policy and entity quarantine
+pub mod policy;
+pub use policy::{ActionPolicy, DriftAction, PolicyRule, QuarantineRecord, QuarantineRegistry};
+
+// Trend extrapolation and early warnings
+pub mod forecast;
+pub use forecast::{DriftForecast, ForecastConfig, ForecastMethod};
+
+// Lagged correlation between drift types
+pub mod correlation;
+pub use correlation::{CorrelationConfig, DriftCorrelation};
+
+// Statistics helpers for drift analysis
+pub mod stats;
+
+// Custom drift types contributed by plugins
+pub mod plugin;
+pub use plugin::{CustomDriftType, DriftCalculatorPlugin, DEFAULT_CUSTOM_THRESHOLD};
 
 // Drift calculation algorithms
 mod calculator;
@@ -30,10 +67,19 @@ pub enum DriftError {
 
     #[error("Lock poisoned: internal concurrency error")]
     LockPoisoned,
+
+    #[error("Alert delivery failed: {0}")]
+    AlertDelivery(String),
+
+    #[error("History store error: {0}")]
+    HistoryError(String),
+
+    #[error("Drift plugin error: {0}")]
+    PluginError(String),
 }
 
 /// Types of drift that can be detected
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DriftType {
     /// Vector embeddings diverge from semantic meaning
     SemanticVectorDrift,
@@ -51,6 +97,43 @@ pub enum DriftType {
     SpatialDrift,
     /// Overall data quality degradation
     QualityDrift,
+    /// Drift scored by a registered [`DriftCalculatorPlugin`]
+    Custom(CustomDriftType),
+}
+
+impl DriftType {
+    /// Every drift type
+    pub const ALL: [DriftType; 8] = [
+        DriftType::SemanticVectorDrift,
+        DriftType::GraphDocumentDrift,
+        DriftType::TemporalConsistencyDrift,
+        DriftType::TensorDrift,
+        DriftType::SchemaDrift,
+        DriftType::ProvenanceDrift,
+        DriftType::SpatialDrift,
+        DriftType::QualityDrift,
+    ];
+}
+
+impl std::str::FromStr for DriftType {
+    type Err = DriftError;
+
+    /// Parse the snake_case name produced by `Display`; the `_drift` suffix
+    /// is optional (`tensor` and `tensor_drift` are the same type).  Custom
+    /// types (`custom:<name>`) must already be registered.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(custom) = s.trim().strip_prefix("custom:") {
+            return CustomDriftType::lookup(custom)
+                .map(DriftType::Custom)
+                .ok_or_else(|| DriftError::MetricNotFound(format!("unknown drift type: {}", s)));
+        }
+        let name = s.trim().to_ascii_lowercase();
+        let name = name.strip_suffix("_drift").unwrap_or(&name);
+        DriftType::ALL
+            .into_iter()
+            .find(|t| t.to_string().strip_suffix("_drift") == Some(name))
+            .ok_or_else(|| DriftError::MetricNotFound(format!("unknown drift type: {}", s)))
+    }
 }
 
 impl std::fmt::Display for DriftType {
@@ -64,12 +147,39 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             DriftType::ProvenanceDrift => write!(f, "provenance_drift"),
             DriftType::SpatialDrift => write!(f, "spatial_drift"),
             DriftType::QualityDrift => write!(f, "quality_drift"),
+            DriftType::Custom(custom) => write!(f, "custom:{}", custom.name()),
+        }
+    }
+}
+
+/// Built-in types serialize as their variant name (`TensorDrift`), custom
+/// types as `custom:<name>`, so drift types remain usable as map keys.
+impl Serialize for DriftType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DriftType::Custom(custom) => serializer.serialize_str(&format!("custom:{}", custom.name())),
+            builtin => serializer.serialize_str(&format!("{:?}", builtin)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DriftType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if let Some(custom) = name.strip_prefix("custom:") {
+            return CustomDriftType::new(custom)
+                .map(DriftType::Custom)
+                .map_err(serde::de::Error::custom);
         }
+        DriftType::ALL
+            .into_iter()
+            .find(|t| format!("{:?}", t) == name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown drift type: {}", name)))
     }
 }
 
 /// Severity levels for drift alerts
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DriftSeverity {
     Info,
     Warning,
@@ -94,6 +204,18 @@ pub struct DriftEvent {
     pub description: String,
     /// Suggested remediation
     pub remediation: Option<String>,
+    /// Set on early warnings: the forecast predicting a threshold crossing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forecast: Option<DriftForecast>,
+    /// Set while a suppression window covers the event: the id of that window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressed_by: Option<String>,
+    /// Actions the drift policy assigned to the event
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<DriftAction>,
+    /// Namespace the measurement was scoped to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
 }
 
 impl DriftEvent {
@@ -117,15 +239,63 @@ pub fn new(drift_type: DriftType, score: f64, description: impl Into<String>) ->
             detected_at: Utc::now(),
             description: description.into(),
             remediation: None,
+            forecast: None,
+            suppressed_by: None,
+            actions: Vec::new(),
+            namespace: None,
         }
     }
 
+    /// Create an early warning for a predicted threshold crossing
+    pub fn early_warning(forecast: DriftForecast) -> Self {
+        let eta = forecast.time_to_threshold_secs.unwrap_or_default();
+        let mut event = Self::new(
+            forecast.drift_type,
+            forecast.current_score,
+            format!(
+                "{} predicted to cross threshold {:.3} in {:.0}s (score {:.3}, {:+.2e}/s)",
+                forecast.drift_type, forecast.threshold, eta, forecast.current_score, forecast.slope_per_sec
+            ),
+        );
+        event.severity = DriftSeverity::Info;
+        event.forecast = Some(forecast);
+        event
+    }
+
+    /// Whether a suppression window covers this event; suppressed events are
+    /// recorded but neither alerted on nor normalized
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed_by.is_some()
+    }
+
+    /// Whether the drift policy assigned `action` to this event
+    pub fn requires(&self, action: DriftAction) -> bool {
+        self.actions.contains(&action)
+    }
+
+    /// Whether this is an early warning rather than a threshold crossing
+    pub fn is_early_warning(&self) -> bool {
+        self.forecast.is_some()
+    }
+
     /// Add affected entities
     pub fn with_entities(mut self, entities: Vec<String>) -> Self {
         self.affected_entities = entities;
         self
     }
 
+    /// Set the actions to take
+    pub fn with_actions(mut self, actions: Vec<DriftAction>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Scope the event to a namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
     /// Add remediation suggestion
     pub fn with_remediation(mut self, remediation: impl Into<String>) -> Self {
         self.remediation = Some(remediation.into());
@@ -195,6 +365,48 @@ fn default() -> Self {
 }
 
 impl DriftThresholds {
+    /// Check that every threshold can be compared against a drift score.
+    ///
+    /// Fixed thresholds (and adaptive bases) must lie in `[0, 1]`, the range
+    /// drift scores are reported in; adaptive sensitivities must be finite and
+    /// non-negative.
+    pub fn validate(&self) -> Result<(), DriftError> {
+        fn check_unit(name: &str, value: f64) -> Result<(), DriftError> {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(DriftError::InvalidThreshold(format!(
+                    "{} must be within [0, 1], got {}",
+                    name, value
+                )));
+            }
+            Ok(())
+        }
+
+        check_unit("semantic_vector", self.semantic_vector)?;
+        check_unit("graph_document", self.graph_document)?;
+        check_unit("temporal_consistency", self.temporal_consistency)?;
+        check_unit("tensor", self.tensor)?;
+        check_unit("schema", self.schema)?;
+        check_unit("provenance", self.provenance)?;
+        check_unit("spatial", self.spatial)?;
+        check_unit("quality", self.quality)?;
+
+        for (drift_type, policy) in &self.adaptive_policies {
+            match policy {
+                ThresholdPolicy::Fixed(v) => check_unit(&drift_type.to_string(), *v)?,
+                ThresholdPolicy::Adaptive { base, sensitivity } => {
+                    check_unit(&format!("{} base", drift_type), *base)?;
+                    if !sensitivity.is_finite() || *sensitivity < 0.0 {
+                        return Err(DriftError::InvalidThreshold(format!(
+                            "{} sensitivity must be finite and non-negative, got {}",
+                            drift_type, sensitivity
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Get the effective threshold for a drift type, considering adaptive policies
     pub fn effective_threshold(&self, drift_type: DriftType, moving_average: f64) -> f64 {
         if let Some(policy) = self.adaptive_policies.get(&drift_type) {
@@ -210,6 +422,7 @@ pub fn effective_threshold(&self, drift_type: DriftType, moving_average: f64) ->
             DriftType::ProvenanceDrift => self.provenance,
             DriftType::SpatialDrift => self.spatial,
             DriftType::QualityDrift => self.quality,
+            DriftType::Custom(_) => DEFAULT_CUSTOM_THRESHOLD,
         }
     }
 }
@@ -247,9 +460,14 @@ fn default() -> Self {
 impl DriftMetrics {
     /// Record a new measurement
     pub fn record(&mut self, score: f64) {
+        self.record_at(Utc::now(), score);
+    }
+
+    /// Record a measurement taken at `time` (used when replaying history)
+    pub fn record_at(&mut self, time: DateTime<Utc>, score: f64) {
         self.current_score = score;
         self.measurement_count += 1;
-        self.last_measured = Utc::now();
+        self.last_measured = time;
 
         if score > self.max_score {
             self.max_score = score;
@@ -260,7 +478,7 @@ pub fn record(&mut self, score: f64) {
         self.moving_average = alpha * score + (1.0 - alpha) * self.moving_average;
 
         // Keep last 100 measurements
-        self.history.push((Utc::now(), score));
+        self.history.push((time, score));
         if self.history.len() > 100 {
             self.history.remove(0);
         }
@@ -293,39 +511,56 @@ pub fn trend(&self) -> f64 {
 
 /// Drift detector - monitors and reports drift events
 pub struct DriftDetector {
-    thresholds: DriftThresholds,
+    thresholds: RwLock<DriftThresholds>,
     metrics: Arc<RwLock<HashMap<DriftType, DriftMetrics>>>,
+    /// Metrics per namespace, for measurements scoped to one
+    namespace_metrics: RwLock<HashMap<String, HashMap<DriftType, DriftMetrics>>>,
+    /// Threshold overrides per namespace
+    namespace_thresholds: RwLock<HashMap<String, DriftThresholds>>,
+    /// Registered custom drift calculators
+    plugins: RwLock<Vec<Arc<dyn DriftCalculatorPlugin>>>,
     event_sender: Option<mpsc::Sender<DriftEvent>>,
+    history: Option<Arc<DriftHistoryStore>>,
+    forecasting: Option<ForecastConfig>,
+    /// Drift types with an outstanding early warning
+    warned: RwLock<HashSet<DriftType>>,
+    suppressions: SuppressionRegistry,
+    policy: RwLock<ActionPolicy>,
+    quarantine: QuarantineRegistry,
     prometheus_registry: Option<Registry>,
     // Prometheus metrics
     drift_score_gauge: Option<HashMap<DriftType, Gauge>>,
     drift_event_counter: Option<HashMap<DriftType, Counter>>,
+    namespace_score_gauge: Option<GaugeVec>,
+    namespace_event_counter: Option<CounterVec>,
 }
 
 impl DriftDetector {
     /// Create a new drift detector
     pub fn new(thresholds: DriftThresholds) -> Self {
         let mut metrics = HashMap::new();
-        for drift_type in [
-            DriftType::SemanticVectorDrift,
-            DriftType::GraphDocumentDrift,
-            DriftType::TemporalConsistencyDrift,
-            DriftType::TensorDrift,
-            DriftType::SchemaDrift,
-            DriftType::ProvenanceDrift,
-            DriftType::SpatialDrift,
-            DriftType::QualityDrift,
-        ] {
+        for drift_type in DriftType::ALL {
             metrics.insert(drift_type, DriftMetrics::default());
         }
 
         Self {
-            thresholds,
+            thresholds: RwLock::new(thresholds),
             metrics: Arc::new(RwLock::new(metrics)),
+            namespace_metrics: RwLock::new(HashMap::new()),
+            namespace_thresholds: RwLock::new(HashMap::new()),
+            plugins: RwLock::new(Vec::new()),
             event_sender: None,
+            history: None,
+            forecasting: None,
+            warned: RwLock::new(HashSet::new()),
+            suppressions: SuppressionRegistry::new(),
+            policy: RwLock::new(ActionPolicy::default()),
+            quarantine: QuarantineRegistry::new(),
             prometheus_registry: None,
             drift_score_gauge: None,
             drift_event_counter: None,
+            namespace_score_gauge: None,
+            namespace_event_counter: None,
         }
     }
 
@@ -334,27 +569,233 @@ pub fn with_defaults() -> Self {
         Self::new(DriftThresholds::default())
     }
 
+    /// Current thresholds
+    pub fn thresholds(&self) -> Result<DriftThresholds, DriftError> {
+        Ok(self.thresholds.read().map_err(|_| DriftError::LockPoisoned)?.clone())
+    }
+
+    /// Replace the thresholds used for subsequent measurements.
+    ///
+    /// Returns the previous thresholds; invalid thresholds are rejected and
+    /// leave the current ones in place.
+    pub fn set_thresholds(&self, thresholds: DriftThresholds) -> Result<DriftThresholds, DriftError> {
+        thresholds.validate()?;
+        let mut current = self.thresholds.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(std::mem::replace(&mut *current, thresholds))
+    }
+
+    /// Threshold override for a namespace, if one is set
+    pub fn namespace_thresholds(&self, namespace: &str) -> Result<Option<DriftThresholds>, DriftError> {
+        let overrides = self.namespace_thresholds.read().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(overrides.get(namespace).cloned())
+    }
+
+    /// All namespace threshold overrides
+    pub fn all_namespace_thresholds(&self) -> Result<HashMap<String, DriftThresholds>, DriftError> {
+        Ok(self.namespace_thresholds.read().map_err(|_| DriftError::LockPoisoned)?.clone())
+    }
+
+    /// Use `thresholds` instead of the global ones for measurements scoped to
+    /// `namespace`.  Returns the previous override, if any.
+    pub fn set_namespace_thresholds(
+        &self,
+        namespace: &str,
+        thresholds: DriftThresholds,
+    ) -> Result<Option<DriftThresholds>, DriftError> {
+        thresholds.validate()?;
+        let mut overrides = self.namespace_thresholds.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(overrides.insert(namespace.to_string(), thresholds))
+    }
+
+    /// Return a namespace to the global thresholds
+    pub fn remove_namespace_thresholds(&self, namespace: &str) -> Result<Option<DriftThresholds>, DriftError> {
+        let mut overrides = self.namespace_thresholds.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(overrides.remove(namespace))
+    }
+
+    /// Register a custom drift calculator, returning the drift type its
+    /// scores are recorded under.  Plugin names must be unique.
+    pub fn register_plugin(&self, plugin: Arc<dyn DriftCalculatorPlugin>) -> Result<DriftType, DriftError> {
+        let drift_type = DriftType::Custom(CustomDriftType::new(plugin.name())?);
+        if !(0.0..=1.0).contains(&plugin.default_threshold()) {
+            return Err(DriftError::InvalidThreshold(format!(
+                "{} default threshold must be within [0, 1], got {}",
+                drift_type,
+                plugin.default_threshold()
+            )));
+        }
+        let mut plugins = self.plugins.write().map_err(|_| DriftError::LockPoisoned)?;
+        if plugins.iter().any(|p| p.name() == plugin.name()) {
+            return Err(DriftError::PluginError(format!("{} is already registered", drift_type)));
+        }
+        plugins.push(plugin);
+        self.metrics
+            .write()
+            .map_err(|_| DriftError::LockPoisoned)?
+            .entry(drift_type)
+            .or_default();
+        Ok(drift_type)
+    }
+
+    /// Registered custom drift calculators
+    pub fn plugins(&self) -> Result<Vec<Arc<dyn DriftCalculatorPlugin>>, DriftError> {
+        Ok(self.plugins.read().map_err(|_| DriftError::LockPoisoned)?.clone())
+    }
+
+    /// Built-in drift types followed by those of registered plugins
+    pub fn drift_types(&self) -> Result<Vec<DriftType>, DriftError> {
+        let plugins = self.plugins.read().map_err(|_| DriftError::LockPoisoned)?;
+        let mut types = DriftType::ALL.to_vec();
+        for plugin in plugins.iter() {
+            types.push(DriftType::Custom(CustomDriftType::new(plugin.name())?));
+        }
+        Ok(types)
+    }
+
+    /// Threshold for `drift_type` under `thresholds`; custom types without a
+    /// policy of their own use their plugin's default.
+    fn threshold_for(&self, thresholds: &DriftThresholds, drift_type: DriftType, moving_avg: f64) -> Result<f64, DriftError> {
+        if let DriftType::Custom(custom) = drift_type {
+            if !thresholds.adaptive_policies.contains_key(&drift_type) {
+                let plugins = self.plugins.read().map_err(|_| DriftError::LockPoisoned)?;
+                return Ok(plugins
+                    .iter()
+                    .find(|p| p.name() == custom.name())
+                    .map_or(DEFAULT_CUSTOM_THRESHOLD, |p| p.default_threshold()));
+            }
+        }
+        Ok(thresholds.effective_threshold(drift_type, moving_avg))
+    }
+
     /// Set event channel for drift notifications
     pub fn with_event_channel(mut self, sender: mpsc::Sender<DriftEvent>) -> Self {
         self.event_sender = Some(sender);
         self
     }
 
+    /// Append every measurement and event to a time-series store
+    pub fn with_history(mut self, store: Arc<DriftHistoryStore>) -> Self {
+        self.history = Some(store);
+        self
+    }
+
+    /// Suppression windows consulted for every event
+    pub fn suppressions(&self) -> &SuppressionRegistry {
+        &self.suppressions
+    }
+
+    /// Use `policy` to decide what happens when events fire
+    pub fn with_policy(self, policy: ActionPolicy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            ..self
+        }
+    }
+
+    /// Current action policy
+    pub fn policy(&self) -> Result<ActionPolicy, DriftError> {
+        Ok(self.policy.read().map_err(|_| DriftError::LockPoisoned)?.clone())
+    }
+
+    /// Replace the action policy for subsequent events, returning the previous one
+    pub fn set_policy(&self, policy: ActionPolicy) -> Result<ActionPolicy, DriftError> {
+        let mut current = self.policy.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(std::mem::replace(&mut *current, policy))
+    }
+
+    /// Entities quarantined by the `Quarantine` action
+    pub fn quarantine(&self) -> &QuarantineRegistry {
+        &self.quarantine
+    }
+
+    /// Emit early-warning events when a score is forecast to cross its
+    /// threshold within `config.horizon_secs`.
+    ///
+    /// One warning is sent per approach: it re-arms once the trend no longer
+    /// predicts a crossing or the threshold is actually crossed.
+    pub fn with_forecasting(mut self, config: ForecastConfig) -> Self {
+        self.forecasting = Some(config);
+        self
+    }
+
+    /// Forecasting configuration, if early warnings are enabled
+    pub fn forecasting(&self) -> Option<&ForecastConfig> {
+        self.forecasting.as_ref()
+    }
+
+    /// Forecast a drift type from its recent measurements.
+    ///
+    /// `None` until enough measurements have been recorded.
+    pub fn forecast(&self, drift_type: DriftType, config: &ForecastConfig) -> Result<Option<DriftForecast>, DriftError> {
+        let (history, moving_avg) = {
+            let metrics = self.metrics.read().map_err(|_| DriftError::LockPoisoned)?;
+            match metrics.get(&drift_type) {
+                Some(m) => (m.history.clone(), m.moving_average),
+                None => return Ok(None),
+            }
+        };
+        let threshold = self.threshold_for(&self.thresholds()?, drift_type, moving_avg)?;
+        Ok(forecast::forecast(drift_type, &history, threshold, config))
+    }
+
+    /// Recorded samples of one drift type within `range`, oldest first.
+    ///
+    /// Empty when no history store is attached.
+    pub async fn history(&self, drift_type: DriftType, range: &TimeRange) -> Result<Vec<DriftHistoryPoint>, DriftError> {
+        let Some(store) = &self.history else {
+            return Ok(Vec::new());
+        };
+        let points = store
+            .query(&history::series_id(drift_type), range)
+            .await
+            .map_err(|e| DriftError::HistoryError(e.to_string()))?;
+        Ok(points
+            .into_iter()
+            .map(|p| DriftHistoryPoint {
+                drift_type,
+                time: p.time,
+                sample: p.value,
+            })
+            .collect())
+    }
+
+    /// Correlate the recorded history of every drift type within `range`.
+    ///
+    /// Needs a history store; without one there is nothing to correlate.
+    pub async fn correlations(&self, range: &TimeRange, config: &CorrelationConfig) -> Result<Vec<DriftCorrelation>, DriftError> {
+        if self.history.is_none() {
+            return Err(DriftError::HistoryError("no drift history store configured".to_string()));
+        }
+        let mut series = HashMap::new();
+        for drift_type in self.drift_types()? {
+            let points = self.history(drift_type, range).await?;
+            series.insert(drift_type, points.iter().map(|p| (p.time, p.sample.score)).collect());
+        }
+        Ok(correlation::correlate(&series, range.start, range.end, config))
+    }
+
+    /// Rebuild the in-memory metrics from stored history within `range`,
+    /// e.g. after a restart.  Returns the number of samples replayed.
+    pub async fn restore_from_history(&self, range: &TimeRange) -> Result<usize, DriftError> {
+        let mut replayed = 0;
+        for drift_type in self.drift_types()? {
+            let points = self.history(drift_type, range).await?;
+            let mut metrics = self.metrics.write().map_err(|_| DriftError::LockPoisoned)?;
+            let m = metrics.entry(drift_type).or_default();
+            for point in &points {
+                m.record_at(point.time, point.sample.score);
+            }
+            replayed += points.len();
+        }
+        Ok(replayed)
+    }
+
     /// Register Prometheus metrics
     pub fn with_prometheus(mut self, registry: Registry) -> Result<Self, DriftError> {
         let mut gauges = HashMap::new();
         let mut counters = HashMap::new();
 
-        for drift_type in [
-            DriftType::SemanticVectorDrift,
-            DriftType::GraphDocumentDrift,
-            DriftType::TemporalConsistencyDrift,
-            DriftType::TensorDrift,
-            DriftType::SchemaDrift,
-            DriftType::ProvenanceDrift,
-            DriftType::SpatialDrift,
-            DriftType::QualityDrift,
-        ] {
+        for drift_type in DriftType::ALL {
             let gauge = Gauge::new(
                 format!("verisim_drift_score_{}", drift_type),
                 format!("Current drift score for {}", drift_type),
@@ -376,20 +817,61 @@ pub fn with_prometheus(mut self, registry: Registry) -> Result<Self, DriftError>
             counters.insert(drift_type, counter);
         }
 
+        let namespace_gauge = GaugeVec::new(
+            Opts::new("verisim_drift_namespace_score", "Current drift score per namespace"),
+            &["namespace", "drift_type"],
+        )
+        .map_err(|e| DriftError::InvalidThreshold(e.to_string()))?;
+        registry
+            .register(Box::new(namespace_gauge.clone()))
+            .map_err(|e| DriftError::InvalidThreshold(e.to_string()))?;
+        let namespace_counter = CounterVec::new(
+            Opts::new("verisim_drift_namespace_events", "Number of drift events per namespace"),
+            &["namespace", "drift_type"],
+        )
+        .map_err(|e| DriftError::InvalidThreshold(e.to_string()))?;
+        registry
+            .register(Box::new(namespace_counter.clone()))
+            .map_err(|e| DriftError::InvalidThreshold(e.to_string()))?;
+
         self.prometheus_registry = Some(registry);
         self.drift_score_gauge = Some(gauges);
         self.drift_event_counter = Some(counters);
+        self.namespace_score_gauge = Some(namespace_gauge);
+        self.namespace_event_counter = Some(namespace_counter);
         Ok(self)
     }
 
     /// Record a drift measurement
     pub async fn record(&self, drift_type: DriftType, score: f64, entities: Vec<String>) -> Result<Option<DriftEvent>, DriftError> {
+        self.record_in(None, drift_type, score, entities).await
+    }
+
+    /// Record a drift measurement scoped to a namespace.
+    ///
+    /// The score counts towards the global metrics as well as the
+    /// namespace's own, but is compared against the namespace's threshold
+    /// override (or the global thresholds) applied to the namespace's moving
+    /// average, so one degrading project is not diluted by healthy ones.
+    pub async fn record_in(
+        &self,
+        namespace: Option<&str>,
+        drift_type: DriftType,
+        score: f64,
+        entities: Vec<String>,
+    ) -> Result<Option<DriftEvent>, DriftError> {
         // Update metrics
-        {
+        let mut moving_avg = {
             let mut metrics = self.metrics.write().map_err(|_| DriftError::LockPoisoned)?;
-            if let Some(m) = metrics.get_mut(&drift_type) {
-                m.record(score);
-            }
+            let m = metrics.entry(drift_type).or_default();
+            m.record(score);
+            m.moving_average
+        };
+        if let Some(ns) = namespace {
+            let mut by_namespace = self.namespace_metrics.write().map_err(|_| DriftError::LockPoisoned)?;
+            let m = by_namespace.entry(ns.to_string()).or_default().entry(drift_type).or_default();
+            m.record(score);
+            moving_avg = m.moving_average;
         }
 
         // Update Prometheus gauge
@@ -398,27 +880,38 @@ pub async fn record(&self, drift_type: DriftType, score: f64, entities: Vec<Stri
                 gauge.set(score);
             }
         }
+        if let (Some(gauge), Some(ns)) = (&self.namespace_score_gauge, namespace) {
+            gauge.with_label_values(&[ns, &drift_type.to_string()]).set(score);
+        }
 
         // Check threshold (adaptive or fixed)
-        let moving_avg = {
-            let metrics = self.metrics.read().map_err(|_| DriftError::LockPoisoned)?;
-            metrics
-                .get(&drift_type)
-                .map(|m| m.moving_average)
-                .unwrap_or(0.0)
+        let override_thresholds = match namespace {
+            Some(ns) => self.namespace_thresholds(ns)?,
+            None => None,
+        };
+        let thresholds = match override_thresholds {
+            Some(thresholds) => thresholds,
+            None => self.thresholds()?,
         };
-        let threshold = self.thresholds.effective_threshold(drift_type, moving_avg);
+        let threshold = self.threshold_for(&thresholds, drift_type, moving_avg)?;
 
-        if score > threshold {
-            let event = DriftEvent::new(
+        let mut event = if score > threshold {
+            let mut event = DriftEvent::new(
                 drift_type,
                 score,
-                format!(
-                    "{} detected with score {:.3} (threshold: {:.3})",
-                    drift_type, score, threshold
-                ),
+                match namespace {
+                    Some(ns) => format!(
+                        "{} detected in namespace {} with score {:.3} (threshold: {:.3})",
+                        drift_type, ns, score, threshold
+                    ),
+                    None => format!(
+                        "{} detected with score {:.3} (threshold: {:.3})",
+                        drift_type, score, threshold
+                    ),
+                },
             )
             .with_entities(entities);
+            event.namespace = namespace.map(str::to_string);
 
             // Update Prometheus counter
             if let Some(ref counters) = self.drift_event_counter {
@@ -426,19 +919,95 @@ pub async fn record(&self, drift_type: DriftType, score: f64, entities: Vec<Stri
                     counter.inc();
                 }
             }
+            if let (Some(counter), Some(ns)) = (&self.namespace_event_counter, namespace) {
+                counter.with_label_values(&[ns, &drift_type.to_string()]).inc();
+            }
+
+            Some(event)
+        } else {
+            None
+        };
+
+        let mut early_warning = match (&event, &self.forecasting) {
+            (None, Some(config)) => self.early_warning(drift_type, config)?,
+            _ => {
+                self.warned.write().map_err(|_| DriftError::LockPoisoned)?.remove(&drift_type);
+                None
+            }
+        };
+
+        // Suppressed events are kept but not announced
+        if event.is_some() || early_warning.is_some() {
+            let suppressed_by = self.suppressions.active(drift_type, Utc::now())?.map(|w| w.id);
+            let policy = self.policy.read().map_err(|_| DriftError::LockPoisoned)?;
+            for e in event.iter_mut().chain(early_warning.iter_mut()) {
+                e.suppressed_by = suppressed_by.clone();
+                e.actions = policy.actions_for(drift_type, e.severity);
+                // Early warnings predict drift rather than report it, and a
+                // suppression window pauses everything but logging.
+                if e.is_suppressed() {
+                    e.actions.retain(|a| *a == DriftAction::Log);
+                } else if e.is_early_warning() {
+                    e.actions.retain(|a| matches!(a, DriftAction::Log | DriftAction::Alert));
+                }
+            }
+        }
+
+        for e in event.iter().chain(early_warning.iter()) {
+            if e.requires(DriftAction::Log) {
+                match e.severity {
+                    DriftSeverity::Info => info!(drift_type = %drift_type, score, "{}", e.description),
+                    DriftSeverity::Warning => warn!(drift_type = %drift_type, score, "{}", e.description),
+                    DriftSeverity::Critical | DriftSeverity::Emergency => {
+                        error!(drift_type = %drift_type, score, "{}", e.description)
+                    }
+                }
+            }
+            if e.requires(DriftAction::Quarantine) {
+                self.quarantine.quarantine(e)?;
+            }
+        }
 
-            // Send event notification
-            if let Some(ref sender) = self.event_sender {
+        // Send event notification
+        if let Some(ref sender) = self.event_sender {
+            for e in event.iter().chain(early_warning.iter()).filter(|e| e.requires(DriftAction::Alert)) {
                 sender
-                    .send(event.clone())
+                    .send(e.clone())
                     .await
                     .map_err(|e| DriftError::ChannelError(e.to_string()))?;
             }
+        }
 
-            return Ok(Some(event));
+        // Persist the sample; a history outage must not stop detection.
+        if let Some(ref store) = self.history {
+            let sample = DriftSample {
+                score,
+                moving_average: moving_avg,
+                threshold,
+                event: event.clone().or(early_warning),
+            };
+            if let Err(e) = store.append(&history::series_id(drift_type), TimePoint::now(sample)).await {
+                warn!(drift_type = %drift_type, error = %e, "Failed to persist drift sample");
+            }
         }
 
-        Ok(None)
+        Ok(event)
+    }
+
+    /// New early warning for `drift_type`, if one is due.
+    fn early_warning(&self, drift_type: DriftType, config: &ForecastConfig) -> Result<Option<DriftEvent>, DriftError> {
+        let predicted = self
+            .forecast(drift_type, config)?
+            .filter(|f| f.crosses_within_horizon());
+        let mut warned = self.warned.write().map_err(|_| DriftError::LockPoisoned)?;
+        match predicted {
+            Some(f) if warned.insert(drift_type) => Ok(Some(DriftEvent::early_warning(f))),
+            Some(_) => Ok(None),
+            None => {
+                warned.remove(&drift_type);
+                Ok(None)
+            }
+        }
     }
 
     /// Get current metrics for a drift type
@@ -453,6 +1022,12 @@ pub fn all_metrics(&self) -> Result<HashMap<DriftType, DriftMetrics>, DriftError
         Ok(metrics.clone())
     }
 
+    /// Metrics of every namespace that has been measured
+    pub fn namespace_metrics(&self) -> Result<HashMap<String, HashMap<DriftType, DriftMetrics>>, DriftError> {
+        let metrics = self.namespace_metrics.read().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(metrics.clone())
+    }
+
     /// Check overall health
     pub fn health_check(&self) -> Result<DriftHealthStatus, DriftError> {
         let metrics = self.metrics.read().map_err(|_| DriftError::LockPoisoned)?;
@@ -547,4 +1122,261 @@ fn test_health_check() {
         let status = detector.health_check().unwrap();
         assert_eq!(status.status, HealthStatus::Healthy);
     }
+
+    #[tokio::test]
+    async fn test_set_thresholds_at_runtime() {
+        let detector = DriftDetector::with_defaults();
+        let mut thresholds = detector.thresholds().unwrap();
+        thresholds.semantic_vector = 0.8;
+        let previous = detector.set_thresholds(thresholds).unwrap();
+        assert_eq!(previous.semantic_vector, 0.3);
+
+        let event = detector
+            .record(DriftType::SemanticVectorDrift, 0.6, vec![])
+            .await
+            .unwrap();
+        assert!(event.is_none());
+
+        let mut invalid = detector.thresholds().unwrap();
+        invalid.tensor = 1.5;
+        assert!(detector.set_thresholds(invalid).is_err());
+        invalid = detector.thresholds().unwrap();
+        invalid.adaptive_policies.insert(
+            DriftType::TensorDrift,
+            ThresholdPolicy::Adaptive { base: 0.2, sensitivity: f64::NAN },
+        );
+        assert!(detector.set_thresholds(invalid).is_err());
+        assert_eq!(detector.thresholds().unwrap().tensor, 0.35);
+    }
+
+    #[test]
+    fn test_drift_type_from_str() {
+        assert_eq!("tensor".parse::<DriftType>().unwrap(), DriftType::TensorDrift);
+        assert_eq!(
+            "semantic_vector_drift".parse::<DriftType>().unwrap(),
+            DriftType::SemanticVectorDrift
+        );
+        assert!("bogus".parse::<DriftType>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_history_is_recorded_and_restored() {
+        let store: Arc<DriftHistoryStore> = Arc::new(verisim_temporal::InMemoryTimeSeriesStore::new());
+        let detector = DriftDetector::with_defaults().with_history(store.clone());
+        detector.record(DriftType::TensorDrift, 0.1, vec![]).await.unwrap();
+        detector.record(DriftType::TensorDrift, 0.9, vec!["e1".to_string()]).await.unwrap();
+
+        let range = TimeRange::last(chrono::Duration::hours(1));
+        let range = TimeRange::new(range.start, range.end + chrono::Duration::minutes(1)).unwrap();
+        let history = detector.history(DriftType::TensorDrift, &range).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].sample.event.is_none());
+        assert_eq!(history[1].sample.event.as_ref().unwrap().affected_entities, vec!["e1"]);
+
+        // A fresh detector over the same store picks the history back up.
+        let restarted = DriftDetector::with_defaults().with_history(store);
+        assert_eq!(restarted.restore_from_history(&range).await.unwrap(), 2);
+        let metrics = restarted.get_metrics(DriftType::TensorDrift).unwrap().unwrap();
+        assert_eq!(metrics.measurement_count, 2);
+        assert_eq!(metrics.current_score, 0.9);
+    }
+
+    #[tokio::test]
+    async fn test_early_warning_before_threshold() {
+        // Tensor drift has been climbing 0.01/minute towards its 0.35 threshold.
+        let store: Arc<DriftHistoryStore> = Arc::new(verisim_temporal::InMemoryTimeSeriesStore::new());
+        let t0 = Utc::now() - chrono::Duration::minutes(10);
+        for i in 0..10 {
+            let sample = DriftSample {
+                score: 0.2 + 0.01 * i as f64,
+                moving_average: 0.0,
+                threshold: 0.35,
+                event: None,
+            };
+            store
+                .append(
+                    &history::series_id(DriftType::TensorDrift),
+                    TimePoint::new(t0 + chrono::Duration::minutes(i), sample),
+                )
+                .await
+                .unwrap();
+        }
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let detector = DriftDetector::with_defaults()
+            .with_history(store)
+            .with_event_channel(tx)
+            .with_forecasting(ForecastConfig::default());
+        let range = TimeRange::last(chrono::Duration::hours(1));
+        detector.restore_from_history(&range).await.unwrap();
+
+        let event = detector.record(DriftType::TensorDrift, 0.3, vec![]).await.unwrap();
+        assert!(event.is_none(), "below threshold: no drift event");
+        let warning = rx.try_recv().unwrap();
+        assert!(warning.is_early_warning());
+        assert_eq!(warning.severity, DriftSeverity::Info);
+        assert!(warning.forecast.unwrap().time_to_threshold_secs.unwrap() < 3600.0);
+
+        // Still approaching: no repeat warning.
+        detector.record(DriftType::TensorDrift, 0.31, vec![]).await.unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_suppressed_events_are_recorded_not_sent() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let store: Arc<DriftHistoryStore> = Arc::new(verisim_temporal::InMemoryTimeSeriesStore::new());
+        let detector = DriftDetector::with_defaults()
+            .with_event_channel(tx)
+            .with_history(store);
+        let now = Utc::now();
+        let window = SuppressionWindow::new(
+            vec![DriftType::SchemaDrift],
+            now - chrono::Duration::minutes(1),
+            now + chrono::Duration::hours(1),
+            "bulk import",
+            "ops",
+        )
+        .unwrap();
+        let window_id = window.id.clone();
+        detector.suppressions().add(window).unwrap();
+
+        let event = detector.record(DriftType::SchemaDrift, 0.8, vec![]).await.unwrap().unwrap();
+        assert_eq!(event.suppressed_by.as_deref(), Some(window_id.as_str()));
+        assert!(rx.try_recv().is_err());
+        let metrics = detector.get_metrics(DriftType::SchemaDrift).unwrap().unwrap();
+        assert_eq!(metrics.measurement_count, 1);
+        let range = TimeRange::new(now - chrono::Duration::minutes(1), Utc::now() + chrono::Duration::minutes(1)).unwrap();
+        let history = detector.history(DriftType::SchemaDrift, &range).await.unwrap();
+        assert!(history[0].sample.event.as_ref().unwrap().is_suppressed());
+        assert_eq!(event.actions, vec![DriftAction::Log]);
+
+        // Other drift types are unaffected.
+        let event = detector.record(DriftType::TensorDrift, 0.8, vec![]).await.unwrap().unwrap();
+        assert!(!event.is_suppressed());
+        assert_eq!(rx.try_recv().unwrap().drift_type, DriftType::TensorDrift);
+    }
+
+    #[tokio::test]
+    async fn test_policy_assigns_actions() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let mut policy = ActionPolicy::default();
+        policy.rules.push(PolicyRule {
+            drift_type: Some(DriftType::SchemaDrift),
+            min_severity: DriftSeverity::Critical,
+            actions: vec![DriftAction::Log, DriftAction::Quarantine],
+        });
+        let detector = DriftDetector::with_defaults()
+            .with_event_channel(tx)
+            .with_policy(policy);
+
+        let event = detector
+            .record(DriftType::SchemaDrift, 0.95, vec!["e1".to_string()])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.actions, vec![DriftAction::Log, DriftAction::Quarantine]);
+        assert!(detector.quarantine().get("e1").unwrap().is_some());
+        // No Alert action: nothing reaches the alert channel.
+        assert!(rx.try_recv().is_err());
+
+        let event = detector.record(DriftType::TensorDrift, 0.95, vec![]).await.unwrap().unwrap();
+        assert!(event.requires(DriftAction::Normalize));
+        assert!(rx.try_recv().is_ok());
+
+        let previous = detector.set_policy(ActionPolicy { rules: vec![] }).unwrap();
+        assert_eq!(previous.rules.len(), 3);
+        let event = detector.record(DriftType::TensorDrift, 0.95, vec![]).await.unwrap().unwrap();
+        assert_eq!(event.actions, vec![DriftAction::Log]);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_scoped_drift() {
+        let registry = Registry::new();
+        let detector = DriftDetector::with_defaults().with_prometheus(registry.clone()).unwrap();
+        let strict = DriftThresholds {
+            tensor: 0.1,
+            ..DriftThresholds::default()
+        };
+        assert!(detector.set_namespace_thresholds("alpha", strict).unwrap().is_none());
+
+        // 0.2 is below the global tensor threshold but above alpha's.
+        let event = detector
+            .record_in(Some("alpha"), DriftType::TensorDrift, 0.2, vec![])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.namespace.as_deref(), Some("alpha"));
+        assert!(detector
+            .record_in(Some("beta"), DriftType::TensorDrift, 0.2, vec![])
+            .await
+            .unwrap()
+            .is_none());
+
+        let by_namespace = detector.namespace_metrics().unwrap();
+        assert_eq!(by_namespace.len(), 2);
+        assert_eq!(by_namespace["alpha"][&DriftType::TensorDrift].measurement_count, 1);
+        let global = detector.get_metrics(DriftType::TensorDrift).unwrap().unwrap();
+        assert_eq!(global.measurement_count, 2);
+
+        let families = registry.gather();
+        let gauge = families
+            .iter()
+            .find(|f| f.name() == "verisim_drift_namespace_score")
+            .unwrap();
+        assert_eq!(gauge.get_metric().len(), 2);
+
+        assert!(detector.remove_namespace_thresholds("alpha").unwrap().is_some());
+        assert!(detector
+            .record_in(Some("alpha"), DriftType::TensorDrift, 0.2, vec![])
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    struct StaleProofs;
+
+    #[async_trait::async_trait]
+    impl DriftCalculatorPlugin for StaleProofs {
+        fn name(&self) -> &str {
+            "proof_staleness"
+        }
+
+        fn default_threshold(&self) -> f64 {
+            0.6
+        }
+
+        async fn measure(&self, _hexad: &verisim_hexad::Hexad) -> Result<Option<f64>, DriftError> {
+            Ok(Some(0.7))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_drift_type_uses_shared_machinery() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let detector = DriftDetector::with_defaults().with_event_channel(tx);
+        let drift_type = detector.register_plugin(Arc::new(StaleProofs)).unwrap();
+        assert_eq!(drift_type.to_string(), "custom:proof_staleness");
+        assert!(detector.register_plugin(Arc::new(StaleProofs)).is_err());
+        assert_eq!(detector.drift_types().unwrap().len(), DriftType::ALL.len() + 1);
+        assert_eq!("custom:proof_staleness".parse::<DriftType>().unwrap(), drift_type);
+
+        // Below the plugin's default threshold, then above it.
+        assert!(detector.record(drift_type, 0.5, vec![]).await.unwrap().is_none());
+        let event = detector.record(drift_type, 0.8, vec![]).await.unwrap().unwrap();
+        assert_eq!(event.drift_type, drift_type);
+        assert_eq!(rx.try_recv().unwrap().drift_type, drift_type);
+
+        // A configured policy replaces the plugin default.
+        let mut thresholds = detector.thresholds().unwrap();
+        thresholds.adaptive_policies.insert(drift_type, ThresholdPolicy::Fixed(0.9));
+        detector.set_thresholds(thresholds.clone()).unwrap();
+        assert!(detector.record(drift_type, 0.8, vec![]).await.unwrap().is_none());
+
+        // Custom types round-trip through JSON, including as map keys.
+        let json = serde_json::to_string(&thresholds).unwrap();
+        assert!(json.contains("\"custom:proof_staleness\""));
+        let back: DriftThresholds = serde_json::from_str(&json).unwrap();
+        assert!(back.adaptive_policies.contains_key(&drift_type));
+    }
 }
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift calculator plugins
+//!
+//! The built-in drift types cover cross-modal consistency.  Deployments with
+//! their own notion of decay (proof obligations going stale, an external
+//! catalogue falling out of sync) register a [`DriftCalculatorPlugin`] with
+//! the [`crate::DriftDetector`].  Each plugin contributes one named custom
+//! drift type, [`crate::DriftType::Custom`]; the background scanner asks every
+//! plugin to score each entity it visits, and the scores go through the same
+//! thresholds, action policy, suppression windows, alerting and history as
+//! the built-in types.
+//!
+//! Custom type names are interned so that `DriftType` stays `Copy`.  Names
+//! are short snake_case identifiers and at most [`MAX_CUSTOM_DRIFT_TYPES`]
+//! distinct names can exist in a process.
+
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+use async_trait::async_trait;
+use verisim_hexad::Hexad;
+
+use crate::DriftError;
+
+/// Most distinct custom drift type names per process
+pub const MAX_CUSTOM_DRIFT_TYPES: usize = 256;
+
+/// Threshold of a custom drift type with no threshold policy of its own
+pub const DEFAULT_CUSTOM_THRESHOLD: f64 = 0.5;
+
+/// Name of a drift type contributed by a plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CustomDriftType(&'static str);
+
+impl CustomDriftType {
+    /// Intern a custom type name: 1–64 characters of `[a-z0-9_]`.
+    pub fn new(name: &str) -> Result<Self, DriftError> {
+        if name.is_empty()
+            || name.len() > 64
+            || !name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+        {
+            return Err(DriftError::PluginError(format!(
+                "invalid custom drift type name '{}': use 1-64 characters of [a-z0-9_]",
+                name
+            )));
+        }
+        let mut names = interned_names().lock().map_err(|_| DriftError::LockPoisoned)?;
+        if let Some(existing) = names.get(name) {
+            return Ok(Self(existing));
+        }
+        if names.len() >= MAX_CUSTOM_DRIFT_TYPES {
+            return Err(DriftError::PluginError(format!(
+                "too many custom drift types (limit {})",
+                MAX_CUSTOM_DRIFT_TYPES
+            )));
+        }
+        let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+        names.insert(interned);
+        Ok(Self(interned))
+    }
+
+    /// An already interned custom type, without creating one
+    pub fn lookup(name: &str) -> Option<Self> {
+        let names = interned_names().lock().ok()?;
+        names.get(name).map(|n| Self(n))
+    }
+
+    /// The type's name
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+fn interned_names() -> &'static Mutex<HashSet<&'static str>> {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    NAMES.get_or_init(Default::default)
+}
+
+/// A source of custom drift scores
+#[async_trait]
+pub trait DriftCalculatorPlugin: Send + Sync {
+    /// Name of the custom drift type this plugin scores
+    fn name(&self) -> &str;
+
+    /// Threshold used until one is configured for the type
+    fn default_threshold(&self) -> f64 {
+        DEFAULT_CUSTOM_THRESHOLD
+    }
+
+    /// Score one entity in `[0, 1]` (higher = worse); `None` when the signal
+    /// does not apply to it
+    async fn measure(&self, hexad: &Hexad) -> Result<Option<f64>, DriftError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_names_are_interned_and_validated() {
+        let a = CustomDriftType::new("proof_staleness").unwrap();
+        let b = CustomDriftType::new("proof_staleness").unwrap();
+        assert_eq!(a, b);
+        assert!(std::ptr::eq(a.name(), b.name()));
+        assert!(CustomDriftType::new("Proof Staleness").is_err());
+        assert!(CustomDriftType::new("").is_err());
+        assert!(CustomDriftType::lookup("never_registered").is_none());
+    }
+}
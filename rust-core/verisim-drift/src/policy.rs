@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift response policy
+//!
+//! Maps a drift event's type and severity to the actions taken when it
+//! fires: log it, send it to the alert sinks, let the normalizer repair the
+//! affected entities, or quarantine them for manual review.
+//!
+//! A rule matches a drift type (or every type) at or above a severity.  For
+//! a given event the most specific rule wins: a rule naming the drift type
+//! beats a wildcard rule, and among those the rule with the highest matching
+//! severity applies.  Events no rule matches are only logged.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{DriftError, DriftEvent, DriftSeverity, DriftType};
+
+/// A response to a drift event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftAction {
+    /// Write the event to the service log
+    Log,
+    /// Send the event to the alert sinks
+    Alert,
+    /// Let the normalizer repair the affected entities
+    Normalize,
+    /// Hold the affected entities for manual review
+    Quarantine,
+}
+
+/// One row of the policy table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Drift type the rule applies to; every type when absent
+    #[serde(default)]
+    pub drift_type: Option<DriftType>,
+    /// Least severe event the rule applies to
+    pub min_severity: DriftSeverity,
+    /// Actions taken
+    pub actions: Vec<DriftAction>,
+}
+
+/// Policy table mapping (drift type, severity) to actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionPolicy {
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Default for ActionPolicy {
+    /// Log and alert everything; normalize from `Warning` up.  Quarantine is
+    /// opt-in.
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                PolicyRule {
+                    drift_type: None,
+                    min_severity: DriftSeverity::Info,
+                    actions: vec![DriftAction::Log, DriftAction::Alert],
+                },
+                PolicyRule {
+                    drift_type: None,
+                    min_severity: DriftSeverity::Warning,
+                    actions: vec![DriftAction::Log, DriftAction::Alert, DriftAction::Normalize],
+                },
+            ],
+        }
+    }
+}
+
+impl ActionPolicy {
+    /// Actions for an event of `drift_type` at `severity`
+    pub fn actions_for(&self, drift_type: DriftType, severity: DriftSeverity) -> Vec<DriftAction> {
+        self.rules
+            .iter()
+            .filter(|r| r.min_severity <= severity && r.drift_type.is_none_or(|t| t == drift_type))
+            .max_by_key(|r| (r.drift_type.is_some(), r.min_severity))
+            .map(|r| r.actions.clone())
+            .unwrap_or_else(|| vec![DriftAction::Log])
+    }
+}
+
+/// An entity held back for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub entity_id: String,
+    pub drift_type: DriftType,
+    pub severity: DriftSeverity,
+    pub score: f64,
+    /// Description of the event that quarantined the entity
+    pub reason: String,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// Entities quarantined by drift policy
+#[derive(Debug, Default)]
+pub struct QuarantineRegistry {
+    entries: RwLock<HashMap<String, QuarantineRecord>>,
+}
+
+impl QuarantineRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quarantine every entity an event affects; returns how many were added
+    pub fn quarantine(&self, event: &DriftEvent) -> Result<usize, DriftError> {
+        let mut entries = self.entries.write().map_err(|_| DriftError::LockPoisoned)?;
+        let before = entries.len();
+        for id in &event.affected_entities {
+            entries.entry(id.clone()).or_insert_with(|| QuarantineRecord {
+                entity_id: id.clone(),
+                drift_type: event.drift_type,
+                severity: event.severity,
+                score: event.score,
+                reason: event.description.clone(),
+                quarantined_at: event.detected_at,
+            });
+        }
+        Ok(entries.len() - before)
+    }
+
+    /// Quarantine record for an entity
+    pub fn get(&self, entity_id: &str) -> Result<Option<QuarantineRecord>, DriftError> {
+        let entries = self.entries.read().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(entries.get(entity_id).cloned())
+    }
+
+    /// Release an entity from quarantine
+    pub fn release(&self, entity_id: &str) -> Result<Option<QuarantineRecord>, DriftError> {
+        let mut entries = self.entries.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(entries.remove(entity_id))
+    }
+
+    /// All quarantined entities, oldest first
+    pub fn list(&self) -> Result<Vec<QuarantineRecord>, DriftError> {
+        let entries = self.entries.read().map_err(|_| DriftError::LockPoisoned)?;
+        let mut records: Vec<QuarantineRecord> = entries.values().cloned().collect();
+        records.sort_by_key(|r| r.quarantined_at);
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = ActionPolicy::default();
+        assert_eq!(
+            policy.actions_for(DriftType::TensorDrift, DriftSeverity::Info),
+            vec![DriftAction::Log, DriftAction::Alert]
+        );
+        assert!(policy
+            .actions_for(DriftType::TensorDrift, DriftSeverity::Emergency)
+            .contains(&DriftAction::Normalize));
+    }
+
+    #[test]
+    fn test_most_specific_rule_wins() {
+        let mut policy = ActionPolicy::default();
+        policy.rules.push(PolicyRule {
+            drift_type: Some(DriftType::SchemaDrift),
+            min_severity: DriftSeverity::Critical,
+            actions: vec![DriftAction::Log, DriftAction::Quarantine],
+        });
+        policy.rules.push(PolicyRule {
+            drift_type: Some(DriftType::SchemaDrift),
+            min_severity: DriftSeverity::Info,
+            actions: vec![DriftAction::Log],
+        });
+
+        assert_eq!(
+            policy.actions_for(DriftType::SchemaDrift, DriftSeverity::Warning),
+            vec![DriftAction::Log]
+        );
+        assert_eq!(
+            policy.actions_for(DriftType::SchemaDrift, DriftSeverity::Emergency),
+            vec![DriftAction::Log, DriftAction::Quarantine]
+        );
+        // Other types still use the wildcard rules.
+        assert!(policy
+            .actions_for(DriftType::TensorDrift, DriftSeverity::Critical)
+            .contains(&DriftAction::Normalize));
+
+        let empty = ActionPolicy { rules: vec![] };
+        assert_eq!(empty.actions_for(DriftType::TensorDrift, DriftSeverity::Emergency), vec![DriftAction::Log]);
+    }
+
+    #[test]
+    fn test_quarantine_registry() {
+        let registry = QuarantineRegistry::new();
+        let event = DriftEvent::new(DriftType::SchemaDrift, 0.95, "schema broken")
+            .with_entities(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(registry.quarantine(&event).unwrap(), 2);
+        assert_eq!(registry.quarantine(&event).unwrap(), 0);
+        assert_eq!(registry.get("a").unwrap().unwrap().severity, DriftSeverity::Emergency);
+        assert!(registry.release("a").unwrap().is_some());
+        assert_eq!(registry.list().unwrap().len(), 1);
+    }
+}
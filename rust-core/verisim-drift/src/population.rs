@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Population-level embedding drift
+//!
+//! Per-entity checks catch an embedding that disagrees with its neighbours;
+//! they miss the whole vector space shifting (a new embedding model, a change
+//! in what is being ingested).  The [`PopulationMonitor`] keeps a reference
+//! distribution of the embedding population and compares a sliding window of
+//! recent inserts against it.
+//!
+//! Each dimension is bucketed by the reference deciles (so every reference
+//! bucket holds ~10% of the mass) and the recent window's bucket proportions
+//! are compared with:
+//!
+//! - **PSI** (population stability index): `Σ (r - e) · ln(r / e)`
+//! - **KL divergence** of recent from reference: `Σ r · ln(r / e)`
+//!
+//! Both are averaged over dimensions.  The drift score is `psi / (psi + 0.25)`,
+//! mapping the conventional "significant shift" PSI of 0.25 to 0.5.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::DriftError;
+
+/// Proportion floor so empty buckets do not produce infinite divergence.
+const EPSILON: f64 = 1e-4;
+
+/// PSI at which the drift score reaches 0.5.
+const PSI_SCALE: f64 = 0.25;
+
+/// Population monitor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulationConfig {
+    /// Buckets per dimension
+    pub bins: usize,
+    /// Embeddings collected before the reference distribution is fitted
+    pub reference_size: usize,
+    /// Recent inserts kept for comparison
+    pub recent_window: usize,
+    /// Recent inserts needed before a measurement is made
+    pub min_recent: usize,
+}
+
+impl Default for PopulationConfig {
+    fn default() -> Self {
+        Self {
+            bins: 10,
+            reference_size: 1000,
+            recent_window: 500,
+            min_recent: 50,
+        }
+    }
+}
+
+/// Per-dimension bucket boundaries and proportions of the reference population
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceDistribution {
+    /// Embedding dimension
+    pub dimension: usize,
+    /// Interior bucket boundaries per dimension (ascending, deduplicated)
+    pub edges: Vec<Vec<f32>>,
+    /// Reference proportion per bucket per dimension
+    pub proportions: Vec<Vec<f64>>,
+    /// Embeddings the reference was fitted on
+    pub sample_size: usize,
+    /// When the reference was fitted
+    pub fitted_at: DateTime<Utc>,
+}
+
+impl ReferenceDistribution {
+    /// Fit quantile buckets to a set of equal-length embeddings.
+    pub fn fit(embeddings: &[Vec<f32>], bins: usize) -> Result<Self, DriftError> {
+        let dimension = embeddings
+            .first()
+            .map(Vec::len)
+            .ok_or_else(|| DriftError::InvalidThreshold("no embeddings to fit".to_string()))?;
+        if embeddings.iter().any(|e| e.len() != dimension) {
+            return Err(DriftError::InvalidThreshold("embeddings differ in dimension".to_string()));
+        }
+        let bins = bins.max(2);
+
+        let mut edges = Vec::with_capacity(dimension);
+        let mut proportions = Vec::with_capacity(dimension);
+        for d in 0..dimension {
+            let mut values: Vec<f32> = embeddings.iter().map(|e| e[d]).collect();
+            values.sort_by(f32::total_cmp);
+            let mut dim_edges: Vec<f32> = (1..bins)
+                .map(|i| values[(i * values.len() / bins).min(values.len() - 1)])
+                .collect();
+            dim_edges.dedup();
+            let counts = bucket_counts(&dim_edges, values.iter().copied());
+            proportions.push(normalise(&counts, values.len()));
+            edges.push(dim_edges);
+        }
+
+        Ok(Self {
+            dimension,
+            edges,
+            proportions,
+            sample_size: embeddings.len(),
+            fitted_at: Utc::now(),
+        })
+    }
+
+    /// Compare a set of embeddings against the reference.
+    pub fn compare<'a>(&self, embeddings: impl IntoIterator<Item = &'a Vec<f32>> + Clone) -> PopulationDrift {
+        let recent_size = embeddings.clone().into_iter().count();
+        let mut psi = 0.0;
+        let mut kl = 0.0;
+        for d in 0..self.dimension {
+            let counts = bucket_counts(&self.edges[d], embeddings.clone().into_iter().map(|e| e[d]));
+            let actual = normalise(&counts, recent_size);
+            for (a, e) in actual.iter().zip(&self.proportions[d]) {
+                let (a, e) = (a.max(EPSILON), e.max(EPSILON));
+                psi += (a - e) * (a / e).ln();
+                kl += a * (a / e).ln();
+            }
+        }
+        let dims = self.dimension.max(1) as f64;
+        let psi = psi / dims;
+        PopulationDrift {
+            psi,
+            kl_divergence: (kl / dims).max(0.0),
+            score: psi / (psi + PSI_SCALE),
+            dimension: self.dimension,
+            reference_size: self.sample_size,
+            recent_size,
+            measured_at: Utc::now(),
+        }
+    }
+}
+
+/// Count values per bucket; bucket `i` holds values below `edges[i]`.
+fn bucket_counts(edges: &[f32], values: impl Iterator<Item = f32>) -> Vec<usize> {
+    let mut counts = vec![0; edges.len() + 1];
+    for v in values {
+        counts[edges.partition_point(|e| *e <= v)] += 1;
+    }
+    counts
+}
+
+fn normalise(counts: &[usize], total: usize) -> Vec<f64> {
+    let total = total.max(1) as f64;
+    counts.iter().map(|c| *c as f64 / total).collect()
+}
+
+/// Result of comparing recent inserts with the reference population
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulationDrift {
+    /// Population stability index, averaged over dimensions
+    pub psi: f64,
+    /// KL divergence of recent from reference, averaged over dimensions
+    pub kl_divergence: f64,
+    /// Drift score in [0, 1)
+    pub score: f64,
+    pub dimension: usize,
+    pub reference_size: usize,
+    pub recent_size: usize,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Snapshot of the monitor's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulationStatus {
+    /// Fitted reference, if any (bucket detail omitted)
+    pub reference_size: Option<usize>,
+    pub reference_fitted_at: Option<DateTime<Utc>>,
+    pub dimension: Option<usize>,
+    /// Embeddings collected towards the first reference
+    pub pending_reference: usize,
+    /// Recent inserts in the comparison window
+    pub recent: usize,
+    /// Embeddings ignored for not matching the reference dimension
+    pub skipped: u64,
+    /// Most recent measurement
+    pub last: Option<PopulationDrift>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    reference: Option<ReferenceDistribution>,
+    pending: Vec<Vec<f32>>,
+    recent: VecDeque<Vec<f32>>,
+    skipped: u64,
+    last: Option<PopulationDrift>,
+}
+
+/// Maintains the reference distribution and the window of recent inserts
+pub struct PopulationMonitor {
+    config: PopulationConfig,
+    state: Mutex<MonitorState>,
+}
+
+impl PopulationMonitor {
+    /// Create a monitor with no reference yet
+    pub fn new(config: PopulationConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(MonitorState::default()),
+        }
+    }
+
+    /// Monitor configuration
+    pub fn config(&self) -> &PopulationConfig {
+        &self.config
+    }
+
+    /// Record a newly inserted embedding.
+    ///
+    /// Until a reference exists, inserts count towards it instead.
+    pub fn observe(&self, embedding: &[f32]) -> Result<(), DriftError> {
+        let mut state = self.state.lock().map_err(|_| DriftError::LockPoisoned)?;
+        match &state.reference {
+            None => self.add_pending(&mut state, embedding),
+            Some(reference) if reference.dimension != embedding.len() => state.skipped += 1,
+            Some(_) => {
+                state.recent.push_back(embedding.to_vec());
+                while state.recent.len() > self.config.recent_window {
+                    state.recent.pop_front();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Offer a stored embedding for the reference; ignored once fitted.
+    pub fn seed_reference(&self, embedding: &[f32]) -> Result<(), DriftError> {
+        let mut state = self.state.lock().map_err(|_| DriftError::LockPoisoned)?;
+        if state.reference.is_none() {
+            self.add_pending(&mut state, embedding);
+        }
+        Ok(())
+    }
+
+    /// Compare recent inserts with the reference.
+    ///
+    /// `None` until a reference exists and `min_recent` inserts have arrived.
+    pub fn measure(&self) -> Result<Option<PopulationDrift>, DriftError> {
+        let mut state = self.state.lock().map_err(|_| DriftError::LockPoisoned)?;
+        let Some(reference) = &state.reference else {
+            return Ok(None);
+        };
+        if state.recent.len() < self.config.min_recent.max(1) {
+            return Ok(None);
+        }
+        let drift = reference.compare(&state.recent);
+        state.last = Some(drift.clone());
+        Ok(Some(drift))
+    }
+
+    /// Accept the current population as normal: refit the reference on the
+    /// recent window and start a new one.
+    pub fn rebaseline(&self) -> Result<ReferenceDistribution, DriftError> {
+        let mut state = self.state.lock().map_err(|_| DriftError::LockPoisoned)?;
+        if state.recent.len() < self.config.min_recent.max(1) {
+            return Err(DriftError::InvalidThreshold(format!(
+                "rebaseline needs {} recent embeddings, have {}",
+                self.config.min_recent,
+                state.recent.len()
+            )));
+        }
+        let recent: Vec<Vec<f32>> = state.recent.drain(..).collect();
+        let reference = ReferenceDistribution::fit(&recent, self.config.bins)?;
+        state.reference = Some(reference.clone());
+        state.last = None;
+        Ok(reference)
+    }
+
+    /// Current state
+    pub fn status(&self) -> Result<PopulationStatus, DriftError> {
+        let state = self.state.lock().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(PopulationStatus {
+            reference_size: state.reference.as_ref().map(|r| r.sample_size),
+            reference_fitted_at: state.reference.as_ref().map(|r| r.fitted_at),
+            dimension: state.reference.as_ref().map(|r| r.dimension),
+            pending_reference: state.pending.len(),
+            recent: state.recent.len(),
+            skipped: state.skipped,
+            last: state.last.clone(),
+        })
+    }
+
+    fn add_pending(&self, state: &mut MonitorState, embedding: &[f32]) {
+        if state.pending.first().is_some_and(|p| p.len() != embedding.len()) {
+            state.skipped += 1;
+            return;
+        }
+        state.pending.push(embedding.to_vec());
+        if state.pending.len() >= self.config.reference_size.max(1) {
+            let pending = std::mem::take(&mut state.pending);
+            // Equal dimensions are enforced above, so fitting cannot fail.
+            state.reference = ReferenceDistribution::fit(&pending, self.config.bins).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random embeddings centred on `offset`.
+    fn population(n: usize, dim: usize, offset: f32, seed: u64) -> Vec<Vec<f32>> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                        offset + ((x >> 33) as f32 / (1u64 << 31) as f32 - 0.5)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn monitor() -> PopulationMonitor {
+        PopulationMonitor::new(PopulationConfig {
+            reference_size: 400,
+            recent_window: 200,
+            min_recent: 100,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_same_distribution_is_stable() {
+        let monitor = monitor();
+        for e in population(400, 8, 0.0, 1) {
+            monitor.seed_reference(&e).unwrap();
+        }
+        assert!(monitor.measure().unwrap().is_none());
+        for e in population(200, 8, 0.0, 2) {
+            monitor.observe(&e).unwrap();
+        }
+        let drift = monitor.measure().unwrap().unwrap();
+        assert!(drift.psi < 0.1, "psi {}", drift.psi);
+        assert!(drift.score < 0.3);
+    }
+
+    #[test]
+    fn test_shifted_distribution_drifts() {
+        let monitor = monitor();
+        for e in population(400, 8, 0.0, 1) {
+            monitor.observe(&e).unwrap();
+        }
+        assert_eq!(monitor.status().unwrap().reference_size, Some(400));
+        for e in population(200, 8, 0.3, 3) {
+            monitor.observe(&e).unwrap();
+        }
+        let drift = monitor.measure().unwrap().unwrap();
+        assert!(drift.psi > 0.25, "psi {}", drift.psi);
+        assert!(drift.kl_divergence > 0.0);
+        assert!(drift.score > 0.5);
+
+        // Accepting the shift makes it the new normal.
+        monitor.rebaseline().unwrap();
+        for e in population(200, 8, 0.3, 4) {
+            monitor.observe(&e).unwrap();
+        }
+        assert!(monitor.measure().unwrap().unwrap().psi < 0.1);
+    }
+
+    #[test]
+    fn test_dimension_mismatch_is_skipped() {
+        let monitor = monitor();
+        for e in population(400, 8, 0.0, 1) {
+            monitor.observe(&e).unwrap();
+        }
+        monitor.observe(&[0.0; 4]).unwrap();
+        let status = monitor.status().unwrap();
+        assert_eq!(status.skipped, 1);
+        assert_eq!(status.recent, 0);
+    }
+}
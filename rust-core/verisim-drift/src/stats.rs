@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Small statistics helpers for drift analysis
+//!
+//! Series are resampled onto a fixed grid of time buckets so that series
+//! recorded at different moments can be compared point by point.  A bucket
+//! with no samples is `None` and is skipped by the correlation functions.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Arithmetic mean; `None` for an empty slice
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// Pearson correlation coefficient of paired values.
+///
+/// `None` with fewer than two pairs or when either side is constant.
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (cov, var_x, var_y) = pairs.iter().fold((0.0, 0.0, 0.0), |(c, vx, vy), (x, y)| {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        (c + dx * dy, vx + dx * dx, vy + dy * dy)
+    });
+    if var_x <= f64::EPSILON || var_y <= f64::EPSILON {
+        return None;
+    }
+    Some((cov / (var_x.sqrt() * var_y.sqrt())).clamp(-1.0, 1.0))
+}
+
+/// Mean of the samples falling in each of `buckets` consecutive buckets of
+/// width `width` starting at `start`; samples outside the grid are ignored.
+pub fn bucket_means(
+    samples: &[(DateTime<Utc>, f64)],
+    start: DateTime<Utc>,
+    width: Duration,
+    buckets: usize,
+) -> Vec<Option<f64>> {
+    let width_ms = width.num_milliseconds().max(1);
+    let mut sums = vec![(0.0, 0usize); buckets];
+    for (time, value) in samples {
+        let offset = time.signed_duration_since(start).num_milliseconds();
+        if offset < 0 {
+            continue;
+        }
+        if let Some(slot) = sums.get_mut((offset / width_ms) as usize) {
+            slot.0 += value;
+            slot.1 += 1;
+        }
+    }
+    sums.into_iter()
+        .map(|(sum, count)| (count > 0).then(|| sum / count as f64))
+        .collect()
+}
+
+/// Correlation of `leader` with `follower` shifted `lag` buckets later,
+/// i.e. how well `leader[i]` predicts `follower[i + lag]`.
+///
+/// Returns the coefficient and the number of overlapping buckets.
+pub fn lagged_correlation(leader: &[Option<f64>], follower: &[Option<f64>], lag: usize) -> Option<(f64, usize)> {
+    let pairs: Vec<(f64, f64)> = leader
+        .iter()
+        .zip(follower.iter().skip(lag))
+        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+        .collect();
+    pearson(&pairs).map(|r| (r, pairs.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pearson() {
+        let up: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, 2.0 * i as f64 + 1.0)).collect();
+        assert!((pearson(&up).unwrap() - 1.0).abs() < 1e-12);
+        let down: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, -(i as f64))).collect();
+        assert!((pearson(&down).unwrap() + 1.0).abs() < 1e-12);
+        assert!(pearson(&[(1.0, 1.0), (2.0, 1.0)]).is_none());
+        assert_eq!(mean(&[]), None);
+    }
+
+    #[test]
+    fn test_bucket_means_and_lag() {
+        let start = Utc::now();
+        let samples = vec![
+            (start, 1.0),
+            (start + Duration::seconds(30), 3.0),
+            (start + Duration::seconds(150), 5.0),
+            (start - Duration::seconds(10), 100.0),
+        ];
+        let buckets = bucket_means(&samples, start, Duration::minutes(1), 3);
+        assert_eq!(buckets, vec![Some(2.0), None, Some(5.0)]);
+
+        // The follower repeats the leader two buckets later.
+        let leader: Vec<Option<f64>> = [0.1, 0.5, 0.2, 0.9, 0.3, 0.7, 0.4, 0.8].iter().map(|v| Some(*v)).collect();
+        let mut follower = vec![Some(0.0), Some(0.0)];
+        follower.extend(leader.iter().take(6));
+        let (r, overlap) = lagged_correlation(&leader, &follower, 2).unwrap();
+        assert!((r - 1.0).abs() < 1e-12);
+        assert_eq!(overlap, 6);
+        assert!(lagged_correlation(&leader, &follower, 0).unwrap().0 < 0.9);
+    }
+}
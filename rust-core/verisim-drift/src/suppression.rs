@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Drift suppression windows
+//!
+//! Planned bulk work (imports, re-embedding, migrations) predictably trips
+//! drift thresholds.  A suppression window names the drift types it covers,
+//! a time span and a reason.  While a window is active, drift is still
+//! measured and recorded, but the resulting events are marked suppressed:
+//! they are not sent to alert sinks and the normalizer ignores them.
+
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{DriftError, DriftType};
+
+/// A time-bounded pause of drift alerting and auto-normalization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressionWindow {
+    /// Window identifier
+    pub id: String,
+    /// Drift types covered; empty covers every type
+    pub drift_types: Vec<DriftType>,
+    /// Start of the window (inclusive)
+    pub starts_at: DateTime<Utc>,
+    /// End of the window (exclusive)
+    pub ends_at: DateTime<Utc>,
+    /// Why drift is being suppressed
+    pub reason: String,
+    /// Who opened the window
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SuppressionWindow {
+    /// Create a window; `ends_at` must follow `starts_at` and a reason is required.
+    pub fn new(
+        drift_types: Vec<DriftType>,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        reason: impl Into<String>,
+        created_by: impl Into<String>,
+    ) -> Result<Self, DriftError> {
+        let reason = reason.into();
+        if ends_at <= starts_at {
+            return Err(DriftError::InvalidThreshold(
+                "suppression window must end after it starts".to_string(),
+            ));
+        }
+        if reason.trim().is_empty() {
+            return Err(DriftError::InvalidThreshold(
+                "suppression window needs a reason".to_string(),
+            ));
+        }
+        Ok(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            drift_types,
+            starts_at,
+            ends_at,
+            reason,
+            created_by: created_by.into(),
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Whether the window suppresses `drift_type` at `time`
+    pub fn covers(&self, drift_type: DriftType, time: DateTime<Utc>) -> bool {
+        time >= self.starts_at
+            && time < self.ends_at
+            && (self.drift_types.is_empty() || self.drift_types.contains(&drift_type))
+    }
+
+    /// Whether the window has ended
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.ends_at
+    }
+}
+
+/// Set of suppression windows
+#[derive(Debug, Default)]
+pub struct SuppressionRegistry {
+    windows: RwLock<Vec<SuppressionWindow>>,
+}
+
+impl SuppressionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a window
+    pub fn add(&self, window: SuppressionWindow) -> Result<(), DriftError> {
+        let mut windows = self.windows.write().map_err(|_| DriftError::LockPoisoned)?;
+        let now = Utc::now();
+        windows.retain(|w| !w.is_expired(now));
+        windows.push(window);
+        Ok(())
+    }
+
+    /// Remove a window, returning it if it existed
+    pub fn remove(&self, id: &str) -> Result<Option<SuppressionWindow>, DriftError> {
+        let mut windows = self.windows.write().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(windows
+            .iter()
+            .position(|w| w.id == id)
+            .map(|i| windows.remove(i)))
+    }
+
+    /// Windows that have not yet ended, by start time
+    pub fn list(&self) -> Result<Vec<SuppressionWindow>, DriftError> {
+        let windows = self.windows.read().map_err(|_| DriftError::LockPoisoned)?;
+        let now = Utc::now();
+        let mut current: Vec<SuppressionWindow> =
+            windows.iter().filter(|w| !w.is_expired(now)).cloned().collect();
+        current.sort_by_key(|w| w.starts_at);
+        Ok(current)
+    }
+
+    /// The window suppressing `drift_type` at `time`, if any
+    pub fn active(&self, drift_type: DriftType, time: DateTime<Utc>) -> Result<Option<SuppressionWindow>, DriftError> {
+        let windows = self.windows.read().map_err(|_| DriftError::LockPoisoned)?;
+        Ok(windows.iter().find(|w| w.covers(drift_type, time)).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_window_coverage() {
+        let now = Utc::now();
+        let window = SuppressionWindow::new(
+            vec![DriftType::TensorDrift],
+            now,
+            now + Duration::hours(1),
+            "bulk import",
+            "ops",
+        )
+        .unwrap();
+        assert!(window.covers(DriftType::TensorDrift, now));
+        assert!(!window.covers(DriftType::SchemaDrift, now));
+        assert!(!window.covers(DriftType::TensorDrift, now + Duration::hours(1)));
+
+        let all = SuppressionWindow::new(vec![], now, now + Duration::hours(1), "migration", "ops").unwrap();
+        assert!(all.covers(DriftType::SchemaDrift, now));
+
+        assert!(SuppressionWindow::new(vec![], now, now, "empty", "ops").is_err());
+        assert!(SuppressionWindow::new(vec![], now, now + Duration::hours(1), " ", "ops").is_err());
+    }
+
+    #[test]
+    fn test_registry_add_remove_and_expiry() {
+        let registry = SuppressionRegistry::new();
+        let now = Utc::now();
+        let window = SuppressionWindow::new(vec![], now, now + Duration::hours(1), "import", "ops").unwrap();
+        let id = window.id.clone();
+        registry.add(window).unwrap();
+        registry
+            .add(
+                SuppressionWindow::new(
+                    vec![],
+                    now - Duration::hours(2),
+                    now - Duration::hours(1),
+                    "old",
+                    "ops",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(registry.list().unwrap().len(), 1);
+        assert!(registry.active(DriftType::QualityDrift, now).unwrap().is_some());
+        assert!(registry.remove(&id).unwrap().is_some());
+        assert!(registry.active(DriftType::QualityDrift, now).unwrap().is_none());
+        assert!(registry.remove(&id).unwrap().is_none());
+    }
+}
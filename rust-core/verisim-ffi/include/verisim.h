@@ -0,0 +1,78 @@
+/* SPDX-License-Identifier: PMPL-1.0-or-later */
+/*
+ * VeriSimDB C API — an embedded store for in-process use.
+ *
+ * Link against libverisim_ffi (built by `cargo build -p verisim-ffi`).
+ * Requests and results are the JSON bodies of the REST endpoints each
+ * function mirrors. Returned strings belong to the caller and are released
+ * with verisim_string_free(). A failed call returns NULL (or -1) and leaves
+ * a message for verisim_last_error(); so does a panic inside the engine,
+ * which is caught rather than unwound into the caller.
+ */
+
+#ifndef VERISIM_H
+#define VERISIM_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An embedded store; opaque. */
+typedef struct VerisimStore VerisimStore;
+
+/*
+ * Open a store configured by ApiConfig JSON, such as
+ * {"persistence_dir": "/var/lib/verisim", "vector_dimension": 384}; keys
+ * left out keep their defaults. NULL or "" opens an in-memory store.
+ */
+VerisimStore *verisim_store_open(const char *config_json);
+
+/*
+ * Close a store, stopping its background tasks and waiting up to ten
+ * seconds for blocking work, such as WAL writes, to finish.
+ */
+void verisim_store_close(VerisimStore *store);
+
+/* Create a hexad from a POST /hexads body; returns the hexad JSON. */
+char *verisim_hexad_create(const VerisimStore *store, const char *request_json);
+
+/* Get a hexad by ID; returns the hexad JSON. */
+char *verisim_hexad_get(const VerisimStore *store, const char *id);
+
+/* Update a hexad from a PUT /hexads/{id} body; returns the hexad JSON. */
+char *verisim_hexad_update(const VerisimStore *store, const char *id, const char *request_json);
+
+/* Delete a hexad; returns 0 on success and -1 on failure. */
+int verisim_hexad_delete(const VerisimStore *store, const char *id);
+
+/* List hexads, a page at a time; returns a JSON array. */
+char *verisim_hexad_list(const VerisimStore *store, size_t limit, size_t offset);
+
+/* Full-text search; returns a JSON array of results. */
+char *verisim_search_text(const VerisimStore *store, const char *query, size_t limit);
+
+/* The k hexads nearest the len floats at vector; returns a JSON array. */
+char *verisim_search_vector(const VerisimStore *store, const float *vector, size_t len, size_t k);
+
+/*
+ * Execute a VQL statement; returns the response JSON. With a non-NULL
+ * transaction_id, writes are buffered in that transaction.
+ */
+char *verisim_vql_execute(const VerisimStore *store, const char *query, const char *transaction_id);
+
+/*
+ * Message of the last failed call on this thread, or NULL. Valid until the
+ * next call on this thread; not to be freed.
+ */
+const char *verisim_last_error(void);
+
+/* Free a string returned by the API. */
+void verisim_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VERISIM_H */
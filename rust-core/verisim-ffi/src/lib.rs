@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <j.d.a.jewell@open.ac.uk>
+//!
+//! VeriSimDB C API — an embedded store for in-process use from any language.
+//!
+//! [`Store`] opens a VeriSimDB engine inside the calling process and runs the
+//! same handlers as the HTTP API against it, so requests and results are the
+//! JSON bodies of the REST endpoints they mirror. The `verisim_*` functions
+//! expose it over the C ABI (declared in `include/verisim.h`), and the
+//! `verisim-python` crate wraps it for Python.
+//!
+//! ## Operations
+//!
+//! - `verisim_store_open` / `verisim_store_close` — Open and close a store;
+//!   `open` takes `ApiConfig` JSON, whose unset keys keep their defaults
+//! - `verisim_hexad_create` / `_get` / `_update` / `_delete` / `_list` —
+//!   Hexad CRUD, as `/api/v1/hexads`
+//! - `verisim_search_text` / `verisim_search_vector` — Full-text and vector
+//!   similarity search
+//! - `verisim_vql_execute` — Run a VQL statement, optionally buffering its
+//!   writes in a transaction
+//!
+//! ## Memory and Errors
+//!
+//! Strings returned by the API are owned by the caller and released with
+//! `verisim_string_free`. A call that fails returns `NULL` (or `-1`) and
+//! leaves a message for `verisim_last_error`, which is per thread and valid
+//! until the next call on that thread. A panic inside the engine is caught
+//! at the boundary and reported the same way, never unwound into the caller.
+
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+pub use verisim_api::{ApiConfig, ApiError};
+use verisim_api::{vql, AppState, HexadRequest, ListQuery, SearchQuery, VectorSearchRequest};
+
+/// Errors of the embedded store.
+#[derive(Debug, Error)]
+pub enum FfiError {
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Runtime error: {0}")]
+    Runtime(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    #[error("Panic: {0}")]
+    Panic(String),
+}
+
+/// How long closing a store waits for blocking work to finish.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An embedded VeriSimDB engine and the runtime its work runs on.
+pub struct Store {
+    runtime: Runtime,
+    state: AppState,
+}
+
+impl Store {
+    /// Open a store configured by `config`.
+    pub fn open(config: ApiConfig) -> Result<Self, FfiError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        let state = runtime.block_on(AppState::new_async(config))?;
+        Ok(Self { runtime, state })
+    }
+
+    /// Open a store configured by `ApiConfig` JSON; keys left out keep
+    /// their defaults, and an empty string opens an in-memory store.
+    pub fn open_json(config_json: &str) -> Result<Self, FfiError> {
+        let mut config = serde_json::to_value(ApiConfig::default())?;
+        if !config_json.trim().is_empty() {
+            let serde_json::Value::Object(overrides) = serde_json::from_str(config_json)? else {
+                return Err(FfiError::InvalidArgument("config must be a JSON object".to_string()));
+            };
+            if let serde_json::Value::Object(defaults) = &mut config {
+                defaults.extend(overrides);
+            }
+        }
+        Self::open(serde_json::from_value(config)?)
+    }
+
+    /// Close the store: stop its background tasks and wait up to
+    /// [`CLOSE_TIMEOUT`] for blocking work, such as WAL writes, to finish.
+    pub fn close(self) {
+        let Self { runtime, state } = self;
+        drop(state);
+        runtime.shutdown_timeout(CLOSE_TIMEOUT);
+    }
+
+    fn state(&self) -> State<AppState> {
+        State(self.state.clone())
+    }
+
+    /// Create a hexad from a `POST /hexads` body, returning it.
+    pub fn create_hexad(&self, request_json: &str) -> Result<String, FfiError> {
+        let request: HexadRequest = serde_json::from_str(request_json)?;
+        let (_, Json(hexad)) = self
+            .runtime
+            .block_on(verisim_api::create_hexad_handler(self.state(), None, Json(request)))?;
+        Ok(serde_json::to_string(&hexad)?)
+    }
+
+    /// Get a hexad by ID.
+    pub fn get_hexad(&self, id: &str) -> Result<String, FfiError> {
+        let Json(hexad) = self
+            .runtime
+            .block_on(verisim_api::get_hexad_handler(self.state(), Path(id.to_string())))?;
+        Ok(serde_json::to_string(&hexad)?)
+    }
+
+    /// Update a hexad from a `PUT /hexads/{id}` body, returning it.
+    pub fn update_hexad(&self, id: &str, request_json: &str) -> Result<String, FfiError> {
+        let request: HexadRequest = serde_json::from_str(request_json)?;
+        let Json(hexad) = self.runtime.block_on(verisim_api::update_hexad_handler(
+            self.state(),
+            Path(id.to_string()),
+            None,
+            Json(request),
+        ))?;
+        Ok(serde_json::to_string(&hexad)?)
+    }
+
+    /// Delete a hexad.
+    pub fn delete_hexad(&self, id: &str) -> Result<(), FfiError> {
+        self.runtime
+            .block_on(verisim_api::delete_hexad_handler(self.state(), Path(id.to_string()), None))?;
+        Ok(())
+    }
+
+    /// List hexads, a page at a time.
+    pub fn list_hexads(&self, limit: usize, offset: usize) -> Result<String, FfiError> {
+        let query = ListQuery {
+            limit: Some(limit),
+            offset: Some(offset),
+            collection: None,
+            snapshot: None,
+        };
+        let Json(hexads) = self
+            .runtime
+            .block_on(verisim_api::list_hexads_handler(self.state(), Query(query)))?;
+        Ok(serde_json::to_string(&hexads)?)
+    }
+
+    /// Full-text search across the document modality.
+    pub fn search_text(&self, query: &str, limit: usize) -> Result<String, FfiError> {
+        let query = SearchQuery {
+            q: Some(query.to_string()),
+            limit: Some(limit),
+            collection: None,
+            snapshot: None,
+        };
+        let Json(results) = self
+            .runtime
+            .block_on(verisim_api::text_search_handler(self.state(), Query(query)))?;
+        Ok(serde_json::to_string(&results)?)
+    }
+
+    /// The `k` hexads whose embeddings are nearest `vector`.
+    pub fn search_vector(&self, vector: &[f32], k: usize) -> Result<String, FfiError> {
+        let request = VectorSearchRequest {
+            vector: vector.to_vec(),
+            k: Some(k),
+            collection: None,
+            snapshot: None,
+        };
+        let Json(results) = self
+            .runtime
+            .block_on(verisim_api::vector_search_handler(self.state(), Json(request)))?;
+        Ok(serde_json::to_string(&results)?)
+    }
+
+    /// Execute a VQL statement; with a `transaction`, its writes are
+    /// buffered there until it commits.
+    pub fn execute_vql(&self, query: &str, transaction: Option<&str>) -> Result<String, FfiError> {
+        let request = vql::VqlExecuteRequest {
+            query: query.to_string(),
+            params: Default::default(),
+            transaction: transaction.map(str::to_string),
+        };
+        let Json(response) = self.runtime.block_on(vql::vql_execute_handler(
+            self.state(),
+            None,
+            HeaderMap::new(),
+            Json(request),
+        ))?;
+        Ok(serde_json::to_string(&response)?)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// C API
+// ---------------------------------------------------------------------------
+
+thread_local! {
+    /// Message of the last failed call on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Borrow a C string argument.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::InvalidArgument(format!("{name} is NULL")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::InvalidArgument(format!("{name} is not UTF-8")))
+}
+
+/// Borrow the store argument.
+///
+/// # Safety
+///
+/// `store` must be null or a pointer returned by `verisim_store_open` and
+/// not yet closed.
+unsafe fn store_arg<'a>(store: *const Store) -> Result<&'a Store, FfiError> {
+    store
+        .as_ref()
+        .ok_or_else(|| FfiError::InvalidArgument("store is NULL".to_string()))
+}
+
+/// Run `call`, catching a panic so it never unwinds across the C ABI, and
+/// return its value, or record its error and return `failed`.
+fn guarded<T>(failed: T, call: impl FnOnce() -> Result<T, FfiError>) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(FfiError::Panic(message))
+    });
+    result.unwrap_or_else(|e| {
+        set_last_error(e.to_string());
+        failed
+    })
+}
+
+/// Run `call`, handing its string to the caller, or record its error and
+/// return null.
+fn string_result(call: impl FnOnce() -> Result<String, FfiError>) -> *mut c_char {
+    // JSON escapes NUL, so results never hold one.
+    guarded(ptr::null_mut(), || {
+        call().map(|s| CString::new(s).map_or(ptr::null_mut(), CString::into_raw))
+    })
+}
+
+/// Open a store configured by `ApiConfig` JSON (`NULL` or `""` for an
+/// in-memory store with the defaults).
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `config_json` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_store_open(config_json: *const c_char) -> *mut Store {
+    guarded(ptr::null_mut(), || {
+        let config = if config_json.is_null() {
+            ""
+        } else {
+            str_arg(config_json, "config_json")?
+        };
+        Ok(Box::into_raw(Box::new(Store::open_json(config)?)))
+    })
+}
+
+/// Close a store, stopping its background tasks and waiting up to ten
+/// seconds for blocking work, such as WAL writes, to finish.
+///
+/// # Safety
+///
+/// `store` must be null or a pointer returned by `verisim_store_open`, and
+/// is invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_store_close(store: *mut Store) {
+    if !store.is_null() {
+        let store = Box::from_raw(store);
+        guarded((), || {
+            store.close();
+            Ok(())
+        });
+    }
+}
+
+/// Create a hexad from a `POST /hexads` body; returns the hexad JSON.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `request_json` must be a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_hexad_create(store: *const Store, request_json: *const c_char) -> *mut c_char {
+    string_result(|| store_arg(store)?.create_hexad(str_arg(request_json, "request_json")?))
+}
+
+/// Get a hexad by ID; returns the hexad JSON.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `id` must be a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_hexad_get(store: *const Store, id: *const c_char) -> *mut c_char {
+    string_result(|| store_arg(store)?.get_hexad(str_arg(id, "id")?))
+}
+
+/// Update a hexad from a `PUT /hexads/{id}` body; returns the hexad JSON.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `id` and `request_json`
+/// must be NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_hexad_update(
+    store: *const Store,
+    id: *const c_char,
+    request_json: *const c_char,
+) -> *mut c_char {
+    string_result(|| {
+        store_arg(store)?.update_hexad(str_arg(id, "id")?, str_arg(request_json, "request_json")?)
+    })
+}
+
+/// Delete a hexad; returns 0 on success and -1 on failure.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `id` must be a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_hexad_delete(store: *const Store, id: *const c_char) -> c_int {
+    guarded(-1, || {
+        store_arg(store)?.delete_hexad(str_arg(id, "id")?)?;
+        Ok(0)
+    })
+}
+
+/// List hexads, a page at a time; returns a JSON array.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_hexad_list(store: *const Store, limit: usize, offset: usize) -> *mut c_char {
+    string_result(|| store_arg(store)?.list_hexads(limit, offset))
+}
+
+/// Full-text search; returns a JSON array of results.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `query` must be a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_search_text(store: *const Store, query: *const c_char, limit: usize) -> *mut c_char {
+    string_result(|| store_arg(store)?.search_text(str_arg(query, "query")?, limit))
+}
+
+/// Vector similarity search over `len` floats at `vector`; returns a JSON
+/// array of the `k` nearest results.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `vector` must point to `len`
+/// floats.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_search_vector(
+    store: *const Store,
+    vector: *const f32,
+    len: usize,
+    k: usize,
+) -> *mut c_char {
+    string_result(|| {
+        if vector.is_null() {
+            return Err(FfiError::InvalidArgument("vector is NULL".to_string()));
+        }
+        store_arg(store)?.search_vector(std::slice::from_raw_parts(vector, len), k)
+    })
+}
+
+/// Execute a VQL statement; returns the response JSON. With a non-null
+/// `transaction_id`, writes are buffered in that transaction.
+///
+/// # Safety
+///
+/// `store` must come from `verisim_store_open`; `query` must be a
+/// NUL-terminated string, and `transaction_id` null or one.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_vql_execute(
+    store: *const Store,
+    query: *const c_char,
+    transaction_id: *const c_char,
+) -> *mut c_char {
+    string_result(|| {
+        let transaction = if transaction_id.is_null() {
+            None
+        } else {
+            Some(str_arg(transaction_id, "transaction_id")?)
+        };
+        store_arg(store)?.execute_vql(str_arg(query, "query")?, transaction)
+    })
+}
+
+/// Message of the last failed call on this thread, or `NULL`.
+///
+/// The message stays valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn verisim_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Free a string returned by the API.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by a `verisim_*` function, not
+/// already freed.
+#[no_mangle]
+pub unsafe extern "C" fn verisim_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_store_crud_search_and_vql() {
+        let store = Store::open_json(r#"{"vector_dimension": 3}"#).unwrap();
+        let created: Value = serde_json::from_str(
+            &store
+                .create_hexad(r#"{"title": "Rust FFI", "body": "embedded stores", "embedding": [1.0, 0.0, 0.0]}"#)
+                .unwrap(),
+        )
+        .unwrap();
+        let id = created["id"].as_str().unwrap();
+
+        let fetched: Value = serde_json::from_str(&store.get_hexad(id).unwrap()).unwrap();
+        assert_eq!(fetched["id"], id);
+        let listed: Value = serde_json::from_str(&store.list_hexads(10, 0).unwrap()).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        let found: Value = serde_json::from_str(&store.search_text("embedded", 10).unwrap()).unwrap();
+        assert_eq!(found[0]["id"], id);
+        let nearest: Value = serde_json::from_str(&store.search_vector(&[0.9, 0.1, 0.0], 1).unwrap()).unwrap();
+        assert_eq!(nearest[0]["id"], id);
+        let rows: Value = serde_json::from_str(&store.execute_vql("SELECT * FROM hexads LIMIT 10", None).unwrap()).unwrap();
+        assert_eq!(rows["row_count"], 1);
+
+        store.delete_hexad(id).unwrap();
+        assert!(matches!(store.get_hexad(id), Err(FfiError::Api(ApiError::NotFound(_)))));
+        assert!(matches!(store.create_hexad("{"), Err(FfiError::Json(_))));
+    }
+
+    #[test]
+    fn test_c_api_round_trip_and_errors() {
+        unsafe {
+            let store = verisim_store_open(ptr::null());
+            assert!(!store.is_null());
+
+            let request = CString::new(r#"{"title": "From C"}"#).unwrap();
+            let created = verisim_hexad_create(store, request.as_ptr());
+            assert!(!created.is_null());
+            let hexad: Value = serde_json::from_str(CStr::from_ptr(created).to_str().unwrap()).unwrap();
+            verisim_string_free(created);
+
+            let id = CString::new(hexad["id"].as_str().unwrap()).unwrap();
+            assert_eq!(verisim_hexad_delete(store, id.as_ptr()), 0);
+            assert_eq!(verisim_hexad_delete(store, id.as_ptr()), -1);
+            let message = CStr::from_ptr(verisim_last_error()).to_str().unwrap();
+            assert!(message.starts_with("Not found"), "{message}");
+
+            assert!(verisim_hexad_get(store, ptr::null()).is_null());
+            assert_eq!(CStr::from_ptr(verisim_last_error()).to_str().unwrap(), "Invalid argument: id is NULL");
+
+            verisim_store_close(store);
+        }
+    }
+
+    #[test]
+    fn test_panics_are_reported_not_unwound() {
+        let result = string_result(|| panic!("engine bug"));
+        assert!(result.is_null());
+        let message = unsafe { CStr::from_ptr(verisim_last_error()) }.to_str().unwrap();
+        assert_eq!(message, "Panic: engine bug");
+        assert_eq!(guarded(-1, || -> Result<c_int, FfiError> { panic!("{}", 42) }), -1);
+        let message = unsafe { CStr::from_ptr(verisim_last_error()) }.to_str().unwrap();
+        assert_eq!(message, "Panic: 42");
+    }
+}
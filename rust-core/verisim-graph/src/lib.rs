@@ -97,6 +97,15 @@ pub trait GraphStore: Send + Sync {
     /// Insert a triple
     async fn insert(&self, edge: &GraphEdge) -> Result<(), GraphError>;
 
+    /// Insert several triples.  Backends with transactions write them in
+    /// one; the default inserts them one by one.
+    async fn insert_batch(&self, edges: &[GraphEdge]) -> Result<(), GraphError> {
+        for edge in edges {
+            self.insert(edge).await?;
+        }
+        Ok(())
+    }
+
     /// Query outgoing edges from a node
     async fn outgoing(&self, node: &GraphNode) -> Result<Vec<GraphEdge>, GraphError>;
 
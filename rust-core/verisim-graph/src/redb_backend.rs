@@ -210,51 +210,55 @@ fn scan_index_for_edges(
 #[async_trait]
 impl GraphStore for RedbGraphStore {
     async fn insert(&self, edge: &GraphEdge) -> Result<(), GraphError> {
+        self.insert_batch(std::slice::from_ref(edge)).await
+    }
+
+    async fn insert_batch(&self, edges: &[GraphEdge]) -> Result<(), GraphError> {
         let db = Arc::clone(&self.db);
-        let edge = edge.clone();
+        let edges = edges.to_vec();
 
         tokio::task::spawn_blocking(move || -> Result<(), GraphError> {
-            let tkey = Self::triple_key(&edge);
-            let edge_bytes = Self::serialise_edge(&edge)?;
-
             let txn = db.begin_write().map_err(|e| {
                 GraphError::StoreError(format!("write txn: {e}"))
             })?;
 
             {
-                // Insert the triple
                 let mut triples = txn.open_table(TRIPLES).map_err(|e| {
                     GraphError::StoreError(format!("open triples: {e}"))
                 })?;
-                triples.insert(tkey.as_slice(), edge_bytes.as_slice()).map_err(|e| {
-                    GraphError::StoreError(format!("insert triple: {e}"))
-                })?;
-            }
-
-            {
-                // Update subject index
                 let mut subject_idx = txn.open_table(SUBJECT_IDX).map_err(|e| {
                     GraphError::StoreError(format!("open subject_idx: {e}"))
                 })?;
-                let skey = Self::subject_index_key(&edge.subject.iri, &tkey);
-                subject_idx.insert(skey.as_slice(), &[] as &[u8]).map_err(|e| {
-                    GraphError::StoreError(format!("insert subject_idx: {e}"))
+                let mut object_idx = txn.open_table(OBJECT_IDX).map_err(|e| {
+                    GraphError::StoreError(format!("open object_idx: {e}"))
                 })?;
-            }
 
-            {
-                // Update object index (node objects only)
-                if let GraphObject::Node(n) = &edge.object {
-                    let mut object_idx = txn.open_table(OBJECT_IDX).map_err(|e| {
-                        GraphError::StoreError(format!("open object_idx: {e}"))
+                for edge in &edges {
+                    let tkey = Self::triple_key(edge);
+                    let edge_bytes = Self::serialise_edge(edge)?;
+
+                    // Insert the triple
+                    triples.insert(tkey.as_slice(), edge_bytes.as_slice()).map_err(|e| {
+                        GraphError::StoreError(format!("insert triple: {e}"))
                     })?;
-                    let okey = Self::object_index_key(&n.iri, &tkey);
-                    object_idx.insert(okey.as_slice(), &[] as &[u8]).map_err(|e| {
-                        GraphError::StoreError(format!("insert object_idx: {e}"))
+
+                    // Update subject index
+                    let skey = Self::subject_index_key(&edge.subject.iri, &tkey);
+                    subject_idx.insert(skey.as_slice(), &[] as &[u8]).map_err(|e| {
+                        GraphError::StoreError(format!("insert subject_idx: {e}"))
                     })?;
+
+                    // Update object index (node objects only)
+                    if let GraphObject::Node(n) = &edge.object {
+                        let okey = Self::object_index_key(&n.iri, &tkey);
+                        object_idx.insert(okey.as_slice(), &[] as &[u8]).map_err(|e| {
+                            GraphError::StoreError(format!("insert object_idx: {e}"))
+                        })?;
+                    }
                 }
             }
 
+            // One commit for the whole batch
             txn.commit().map_err(|e| {
                 GraphError::StoreError(format!("commit: {e}"))
             })?;
@@ -574,6 +578,21 @@ async fn test_deduplication() {
         assert_eq!(outgoing.len(), 1, "Duplicate edges should be deduplicated");
     }
 
+    #[tokio::test]
+    async fn test_insert_batch() {
+        let (store, _dir) = temp_store();
+        let edges = vec![
+            test_edge("https://example.org/Alice", "https://example.org/knows", "https://example.org/Bob"),
+            test_edge("https://example.org/Alice", "https://example.org/knows", "https://example.org/Carol"),
+            test_edge("https://example.org/Bob", "https://example.org/knows", "https://example.org/Carol"),
+        ];
+        store.insert_batch(&edges).await.unwrap();
+
+        assert_eq!(store.outgoing(&edges[0].subject).await.unwrap().len(), 2);
+        let carol = GraphNode::new("https://example.org/Carol");
+        assert_eq!(store.incoming(&carol).await.unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_persistence_across_reopen() {
         let dir = tempdir().unwrap();
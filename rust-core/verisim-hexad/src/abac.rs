@@ -0,0 +1,493 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Attribute-based access control expressions
+//!
+//! Roles say what a client may do anywhere; an [`Expression`] says which
+//! entities it may do it to, from attributes of the principal, the entity
+//! and the request, as in
+//!
+//! ```text
+//! principal.position == "phd_student" and request.action == "read"
+//!     and "project:alpha" in entity.tags and entity.created_at >= "2024"
+//! ```
+//!
+//! Paths name `principal.id`, `principal.groups` or any other principal
+//! attribute; `entity.id`, `entity.created_at`, `entity.modified_at` or any
+//! document field; and `request.action` (`read` or `write`),
+//! `request.time` or any other request attribute such as `request.method`
+//! and `request.path`.  Times are RFC 3339 in UTC, so they compare as
+//! strings.  A path with no value is null, which equals nothing.
+//!
+//! `==`, `!=`, `<`, `<=`, `>` and `>=` compare numbers numerically and
+//! anything else as strings.  `x in list` holds when `x` is an element of
+//! a `[...]` literal or of a comma-separated string.  `and`, `or` and `not`
+//! (or `&&`, `||` and `!`) combine conditions; a bare value holds when it
+//! is `true`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A value an expression reads or computes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::String(s) => s == "true",
+            _ => false,
+        }
+    }
+
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        match self {
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::String(s) => Some(s.clone()),
+            Value::Null | Value::List(_) => None,
+        }
+    }
+
+    pub(crate) fn elements(&self) -> Vec<Value> {
+        match self {
+            Value::List(items) => items.clone(),
+            Value::String(s) => s
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn equals(&self, other: &Value) -> bool {
+        if let (Some(a), Some(b)) = (self.number(), other.number()) {
+            return a == b;
+        }
+        match (self.text(), other.text()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Option<std::cmp::Ordering> {
+        if let (Some(a), Some(b)) = (self.number(), other.number()) {
+            return a.partial_cmp(&b);
+        }
+        Some(self.text()?.cmp(&other.text()?))
+    }
+}
+
+/// Where an expression's paths are looked up
+pub trait Attributes {
+    /// The value at `path`, as in `["entity", "created_at"]`
+    fn lookup(&self, path: &[String]) -> Value;
+}
+
+impl Attributes for HashMap<String, HashMap<String, String>> {
+    fn lookup(&self, path: &[String]) -> Value {
+        match path {
+            [scope, name] => self
+                .get(scope)
+                .and_then(|values| values.get(name))
+                .map_or(Value::Null, |v| Value::String(v.clone())),
+            _ => Value::Null,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Value),
+    Path(Vec<String>),
+    List(Vec<Node>),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Compare(Op, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, attributes: &dyn Attributes) -> Value {
+        match self {
+            Node::Literal(value) => value.clone(),
+            Node::Path(path) => attributes.lookup(path),
+            Node::List(items) => Value::List(items.iter().map(|item| item.eval(attributes)).collect()),
+            Node::Not(inner) => Value::Bool(!inner.eval(attributes).truthy()),
+            Node::And(a, b) => Value::Bool(a.eval(attributes).truthy() && b.eval(attributes).truthy()),
+            Node::Or(a, b) => Value::Bool(a.eval(attributes).truthy() || b.eval(attributes).truthy()),
+            Node::Compare(op, a, b) => {
+                let (a, b) = (a.eval(attributes), b.eval(attributes));
+                let holds = match op {
+                    Op::Eq => a.equals(&b),
+                    Op::Ne => !a.equals(&b),
+                    Op::Lt => a.compare(&b).is_some_and(|o| o.is_lt()),
+                    Op::Le => a.compare(&b).is_some_and(|o| o.is_le()),
+                    Op::Gt => a.compare(&b).is_some_and(|o| o.is_gt()),
+                    Op::Ge => a.compare(&b).is_some_and(|o| o.is_ge()),
+                    Op::In => b.elements().iter().any(|item| a.equals(item)),
+                };
+                Value::Bool(holds)
+            }
+        }
+    }
+}
+
+/// A parsed condition, kept with its source so it serializes as written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    /// Parse `source`, reporting where it stops making sense
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, at: 0 };
+        let root = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {token} in expression"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Whether the condition holds for `attributes`
+    pub fn holds(&self, attributes: &dyn Attributes) -> bool {
+        self.root.eval(attributes).truthy()
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Eq for Expression {}
+
+impl TryFrom<String> for Expression {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, String> {
+        Self::parse(&source)
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.source
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(f64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Dot,
+    Comma,
+    Open,
+    Close,
+    OpenList,
+    CloseList,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{name}'"),
+            Token::String(s) => write!(f, "\"{s}\""),
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Op(op) => write!(f, "{op:?}"),
+            Token::And => f.write_str("'and'"),
+            Token::Or => f.write_str("'or'"),
+            Token::Not => f.write_str("'not'"),
+            Token::Dot => f.write_str("'.'"),
+            Token::Comma => f.write_str("','"),
+            Token::Open => f.write_str("'('"),
+            Token::Close => f.write_str("')'"),
+            Token::OpenList => f.write_str("'['"),
+            Token::CloseList => f.write_str("']'"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            '[' => (Token::OpenList, 1),
+            ']' => (Token::CloseList, 1),
+            ',' => (Token::Comma, 1),
+            '.' => (Token::Dot, 1),
+            '=' if next == Some('=') => (Token::Op(Op::Eq), 2),
+            '!' if next == Some('=') => (Token::Op(Op::Ne), 2),
+            '!' => (Token::Not, 1),
+            '<' if next == Some('=') => (Token::Op(Op::Le), 2),
+            '<' => (Token::Op(Op::Lt), 1),
+            '>' if next == Some('=') => (Token::Op(Op::Ge), 2),
+            '>' => (Token::Op(Op::Gt), 1),
+            '&' if next == Some('&') => (Token::And, 2),
+            '|' if next == Some('|') => (Token::Or, 2),
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| "Unterminated string in expression".to_string())?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::String(text), end + 2)
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let len = 1 + chars[i + 1..]
+                    .iter()
+                    .take_while(|d| d.is_ascii_digit() || **d == '.')
+                    .count();
+                let text: String = chars[i..i + len].iter().collect();
+                let number = text.parse().map_err(|_| format!("Invalid number {text} in expression"))?;
+                (Token::Number(number), len)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|d| d.is_alphanumeric() || **d == '_' || **d == '-')
+                    .count();
+                let word: String = chars[i..i + len].iter().collect();
+                let token = match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "in" => Token::Op(Op::In),
+                    _ => Token::Ident(word),
+                };
+                (token, len)
+            }
+            c => return Err(format!("Unexpected '{c}' in expression")),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.at)
+            .cloned()
+            .ok_or_else(|| "Expression ends too soon".to_string())?;
+        self.at += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.at += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), String> {
+        match self.next()? {
+            found if found == *token => Ok(()),
+            found => Err(format!("Expected {token} but found {found} in expression")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut node = self.and()?;
+        while self.eat(&Token::Or) {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut node = self.not()?;
+        while self.eat(&Token::And) {
+            node = Node::And(Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+        let left = self.operand()?;
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.at += 1;
+                Ok(Node::Compare(op, Box::new(left), Box::new(self.operand()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Node, String> {
+        match self.next()? {
+            Token::String(s) => Ok(Node::Literal(Value::String(s))),
+            Token::Number(n) => Ok(Node::Literal(Value::Number(n))),
+            Token::Open => {
+                let node = self.or()?;
+                self.expect(&Token::Close)?;
+                Ok(node)
+            }
+            Token::OpenList => {
+                let mut items = Vec::new();
+                if !self.eat(&Token::CloseList) {
+                    loop {
+                        items.push(self.operand()?);
+                        if self.eat(&Token::CloseList) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                Ok(Node::List(items))
+            }
+            Token::Ident(word) => match word.as_str() {
+                "true" => Ok(Node::Literal(Value::Bool(true))),
+                "false" => Ok(Node::Literal(Value::Bool(false))),
+                "null" => Ok(Node::Literal(Value::Null)),
+                _ => {
+                    let mut path = vec![word];
+                    while self.eat(&Token::Dot) {
+                        match self.next()? {
+                            Token::Ident(segment) => path.push(segment),
+                            found => return Err(format!("Expected a name after '.' but found {found} in expression")),
+                        }
+                    }
+                    Ok(Node::Path(path))
+                }
+            },
+            found => Err(format!("Unexpected {found} in expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(scopes: &[(&str, &[(&str, &str)])]) -> HashMap<String, HashMap<String, String>> {
+        scopes
+            .iter()
+            .map(|(scope, values)| {
+                let values = values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+                (scope.to_string(), values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expressions_combine_principal_entity_and_request() {
+        let rule = Expression::parse(
+            r#"principal.position == "phd_student" and request.action == "read"
+               and "project:alpha" in entity.tags and entity.created_at >= "2024""#,
+        )
+        .unwrap();
+        let mut seen = attributes(&[
+            ("principal", &[("position", "phd_student")]),
+            ("entity", &[("tags", "project:alpha, project:beta"), ("created_at", "2024-06-01T00:00:00+00:00")]),
+            ("request", &[("action", "read")]),
+        ]);
+        assert!(rule.holds(&seen));
+
+        seen.get_mut("request").unwrap().insert("action".to_string(), "write".to_string());
+        assert!(!rule.holds(&seen));
+        seen.get_mut("request").unwrap().insert("action".to_string(), "read".to_string());
+        seen.get_mut("entity").unwrap().insert("created_at".to_string(), "2023-12-31T00:00:00+00:00".to_string());
+        assert!(!rule.holds(&seen));
+        seen.get_mut("entity").unwrap().remove("tags");
+        assert!(!rule.holds(&seen));
+    }
+
+    #[test]
+    fn test_operators_and_precedence() {
+        let seen = attributes(&[("principal", &[("level", "10"), ("staff", "true")])]);
+        let holds = |source: &str| Expression::parse(source).unwrap().holds(&seen);
+
+        assert!(holds("principal.level > 9"));
+        assert!(holds("principal.level <= 10.0"));
+        assert!(holds("principal.staff"));
+        assert!(holds("!(principal.level < 5) && principal.staff"));
+        assert!(holds("false and false or true"));
+        assert!(!holds("not true or false"));
+        assert!(holds("principal.missing != 'x'"));
+        assert!(!holds("principal.missing == null"));
+        assert!(holds("'b' in ['a', 'b']"));
+        assert!(!holds("'c' in ['a', 'b']"));
+    }
+
+    #[test]
+    fn test_parse_errors_and_round_trip() {
+        assert!(Expression::parse("principal.id ==").is_err());
+        assert!(Expression::parse("(true").is_err());
+        assert!(Expression::parse("'open").is_err());
+        assert!(Expression::parse("true true").is_err());
+        assert!(Expression::parse("principal. == 'x'").is_err());
+
+        let rule: Expression = serde_json::from_str(r#""entity.owner == principal.id""#).unwrap();
+        assert_eq!(serde_json::to_string(&rule).unwrap(), r#""entity.owner == principal.id""#);
+        assert!(serde_json::from_str::<Expression>(r#""entity.owner ==""#).is_err());
+    }
+}
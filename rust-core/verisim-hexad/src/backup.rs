@@ -0,0 +1,574 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Backups
+//!
+//! [`InMemoryHexadStore::backup`](crate::InMemoryHexadStore::backup) pauses
+//! writes and writes a consistent archive of the store to its own directory
+//! under a target directory:
+//!
+//! - `manifest.json`: the [`BackupManifest`], with the snapshot marker (the
+//!   last WAL sequence the archive covers) and a checksum of every file
+//! - `stores/`: one file per store, the registry, each modality, the
+//!   version histories and the provenance chains, holding the state as of a
+//!   WAL sequence
+//! - `wal/`: the WAL segments written since the previous archive
+//!
+//! A full backup holds the store files.  An incremental backup continues
+//! the latest archive in the directory with the WAL written since; when a
+//! checkpoint has truncated part of that, it carries the store files of
+//! the current state instead.
+//!
+//! [`load_archive`] reads the store files an archive starts from and the
+//! WAL written after them, across the archives it continues, and
+//! [`InMemoryHexadStore::restore_backup`](crate::InMemoryHexadStore::restore_backup)
+//! loads them into a fresh store: the histories and chains as archived,
+//! then the WAL replayed as ordinary writes.  [`load_backup`] rolls the
+//! same WAL forward over the store files without a store.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use verisim_provenance::ProvenanceChain;
+use verisim_temporal::Version;
+use verisim_wal::entry::compute_crc32;
+use verisim_wal::WalEntry;
+
+use crate::checkpoint::{SnapshotDeletedHexad, SnapshotHexad, StoreSnapshot};
+use crate::{DeletedHexad, HexadError, HexadInput, HexadSnapshot, HexadStatus, HexadTombstone};
+
+/// Name of an archive's manifest
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Modality fields of a [`HexadInput`], each kept in its own store file
+const MODALITY_FILES: [&str; 7] = ["graph", "vector", "tensor", "semantic", "document", "provenance", "spatial"];
+
+/// What a backup holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    /// The whole store
+    #[default]
+    Full,
+    /// The WAL written since the previous archive
+    Incremental,
+}
+
+/// A file in an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path relative to the archive
+    pub path: String,
+    pub bytes: u64,
+    pub crc32: u32,
+}
+
+/// Describes one archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub id: String,
+    pub kind: BackupKind,
+    /// Archive an incremental backup continues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Sequence the store files capture, when the archive has them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_sequence: Option<u64>,
+    /// Last WAL entry the archive covers
+    pub sequence: u64,
+    pub taken_at: DateTime<Utc>,
+    /// WAL segments copied
+    pub wal_segments: usize,
+    pub files: Vec<BackupFile>,
+}
+
+/// Version histories and provenance chains of a store's entities, by
+/// entity ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoreHistory {
+    /// Every version, oldest first
+    pub versions: BTreeMap<String, Vec<Version<HexadSnapshot>>>,
+    pub chains: BTreeMap<String, ProvenanceChain>,
+}
+
+/// What an archive chain holds: the store files it starts from and the
+/// WAL written after them
+#[derive(Debug, Clone)]
+pub struct ArchivedState {
+    pub snapshot: StoreSnapshot,
+    pub history: StoreHistory,
+    /// WAL entries after the snapshot, in sequence order
+    pub entries: Vec<WalEntry>,
+}
+
+/// Outcome of restoring a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// Archive restored
+    pub backup: String,
+    /// Last WAL entry of the backed-up store the restored state reflects
+    pub sequence: u64,
+    /// Live entities written
+    pub hexads: usize,
+    /// Soft-deleted entities written
+    pub deleted: usize,
+    /// Writes replayed from the archived WAL
+    #[serde(default)]
+    pub writes_replayed: usize,
+    /// Tombstones of merged-away entities
+    pub tombstones: usize,
+    /// Entities that could not be written, and why
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed: BTreeMap<String, String>,
+}
+
+/// The registry store file: entity statuses, soft deletes and tombstones
+#[derive(Debug, Serialize, Deserialize)]
+struct RegistryFile {
+    sequence: u64,
+    taken_at: DateTime<Utc>,
+    hexads: Vec<HexadStatus>,
+    deleted: Vec<DeletedHexad>,
+    tombstones: Vec<HexadTombstone>,
+    /// Entity metadata, by entity ID
+    metadata: BTreeMap<String, HashMap<String, String>>,
+}
+
+fn backup_error(message: impl Into<String>) -> HexadError {
+    HexadError::ModalityError {
+        modality: "backup".to_string(),
+        message: message.into(),
+    }
+}
+
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> HexadError + '_ {
+    move |e| backup_error(format!("{}: {e}", path.display()))
+}
+
+/// Write `data` to a new file at `path` and flush it to disk
+fn write_synced(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Flush the entries of directory `path` to disk
+fn sync_dir(path: &Path) -> Result<(), HexadError> {
+    fs::File::open(path).and_then(|dir| dir.sync_all()).map_err(io_error(path))
+}
+
+/// Split `snapshot` into its store files: the registry, then each modality
+/// as a map from entity ID to that modality's input, then `history`
+fn store_files(snapshot: &StoreSnapshot, history: &StoreHistory) -> Result<Vec<(String, Vec<u8>)>, HexadError> {
+    let inputs = snapshot
+        .hexads
+        .iter()
+        .map(|h| (h.status.id.as_str(), &h.input))
+        .chain(snapshot.deleted.iter().map(|d| (d.deleted.status.id.as_str(), &d.input)));
+    let mut modalities: BTreeMap<&str, BTreeMap<&str, serde_json::Value>> = BTreeMap::new();
+    let mut metadata = BTreeMap::new();
+    for (id, input) in inputs {
+        let serde_json::Value::Object(fields) = serde_json::to_value(input).map_err(|e| backup_error(e.to_string()))? else {
+            continue;
+        };
+        for modality in MODALITY_FILES {
+            if let Some(value) = fields.get(modality).filter(|v| !v.is_null()) {
+                modalities.entry(modality).or_default().insert(id, value.clone());
+            }
+        }
+        if !input.metadata.is_empty() {
+            metadata.insert(id.to_string(), input.metadata.clone());
+        }
+    }
+    let registry = RegistryFile {
+        sequence: snapshot.sequence,
+        taken_at: snapshot.taken_at,
+        hexads: snapshot.hexads.iter().map(|h| h.status.clone()).collect(),
+        deleted: snapshot.deleted.iter().map(|d| d.deleted.clone()).collect(),
+        tombstones: snapshot.tombstones.clone(),
+        metadata,
+    };
+    let json = |e: serde_json::Error| backup_error(e.to_string());
+    let mut files = vec![("stores/registry.json".to_string(), serde_json::to_vec(&registry).map_err(json)?)];
+    for modality in MODALITY_FILES {
+        let entries = modalities.remove(modality).unwrap_or_default();
+        files.push((format!("stores/{modality}.json"), serde_json::to_vec(&entries).map_err(json)?));
+    }
+    files.push(("stores/temporal.json".to_string(), serde_json::to_vec(&history.versions).map_err(json)?));
+    files.push(("stores/provenance-chains.json".to_string(), serde_json::to_vec(&history.chains).map_err(json)?));
+    Ok(files)
+}
+
+/// Join the store files of the archive in `dir` back into a snapshot and
+/// its history.  Archives written before histories were kept have none.
+fn read_store_files(dir: &Path) -> Result<(StoreSnapshot, StoreHistory), HexadError> {
+    let read = |name: &str| {
+        let path = dir.join("stores").join(name);
+        fs::read(&path).map_err(io_error(&path))
+    };
+    let corrupt = |name: &str, e: serde_json::Error| backup_error(format!("Corrupt store file {name}: {e}"));
+    let registry: RegistryFile =
+        serde_json::from_slice(&read("registry.json")?).map_err(|e| corrupt("registry.json", e))?;
+    let mut modalities = HashMap::new();
+    for modality in MODALITY_FILES {
+        let name = format!("{modality}.json");
+        let entries: HashMap<String, serde_json::Value> =
+            serde_json::from_slice(&read(&name)?).map_err(|e| corrupt(&name, e))?;
+        modalities.insert(modality, entries);
+    }
+    let input = |id: &str| -> Result<HexadInput, HexadError> {
+        let mut fields = serde_json::Map::new();
+        for modality in MODALITY_FILES {
+            let value = modalities[modality].get(id).cloned().unwrap_or(serde_json::Value::Null);
+            fields.insert(modality.to_string(), value);
+        }
+        let metadata = registry.metadata.get(id).cloned().unwrap_or_default();
+        fields.insert("metadata".to_string(), serde_json::to_value(metadata).unwrap_or_default());
+        serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| backup_error(format!("Corrupt stored state of {id}: {e}")))
+    };
+    let mut snapshot = StoreSnapshot {
+        sequence: registry.sequence,
+        taken_at: registry.taken_at,
+        hexads: Vec::with_capacity(registry.hexads.len()),
+        deleted: Vec::with_capacity(registry.deleted.len()),
+        tombstones: registry.tombstones.clone(),
+    };
+    for status in &registry.hexads {
+        snapshot.hexads.push(SnapshotHexad {
+            status: status.clone(),
+            input: input(status.id.as_str())?,
+        });
+    }
+    for deleted in &registry.deleted {
+        snapshot.deleted.push(SnapshotDeletedHexad {
+            deleted: deleted.clone(),
+            input: input(deleted.status.id.as_str())?,
+        });
+    }
+    let optional = |name: &str| -> Result<Option<Vec<u8>>, HexadError> {
+        let path = dir.join("stores").join(name);
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error(&path)(e)),
+        }
+    };
+    let mut history = StoreHistory::default();
+    if let Some(data) = optional("temporal.json")? {
+        history.versions = serde_json::from_slice(&data).map_err(|e| corrupt("temporal.json", e))?;
+    }
+    if let Some(data) = optional("provenance-chains.json")? {
+        history.chains = serde_json::from_slice(&data).map_err(|e| corrupt("provenance-chains.json", e))?;
+    }
+    Ok((snapshot, history))
+}
+
+/// Write an archive under `target`: the store files of `snapshot`, if any,
+/// and the segments of the WAL in `wal` holding entries from `from` on,
+/// covering up to `sequence`.  The archive only appears once complete, and
+/// is on disk when it does.
+pub(crate) fn write_archive(
+    target: &Path,
+    kind: BackupKind,
+    parent: Option<&BackupManifest>,
+    snapshot: Option<(&StoreSnapshot, &StoreHistory)>,
+    wal: Option<(&Path, u64)>,
+    sequence: u64,
+) -> Result<BackupManifest, HexadError> {
+    let taken_at = Utc::now();
+    let id = format!("{}-{}", taken_at.format("%Y%m%dT%H%M%S%6fZ"), kind_name(kind));
+    let staging = target.join(format!("{id}.tmp"));
+    fs::create_dir_all(staging.join("stores")).map_err(io_error(&staging))?;
+
+    let mut files = Vec::new();
+    let mut write = |path: String, data: &[u8]| -> Result<(), HexadError> {
+        let full = staging.join(&path);
+        write_synced(&full, data).map_err(io_error(&full))?;
+        files.push(BackupFile {
+            path,
+            bytes: data.len() as u64,
+            crc32: compute_crc32(data),
+        });
+        Ok(())
+    };
+    if let Some((snapshot, history)) = snapshot {
+        for (path, data) in store_files(snapshot, history)? {
+            write(path, &data)?;
+        }
+    }
+    let mut wal_segments = 0;
+    if let Some((wal_dir, from)) = wal {
+        let segments = verisim_wal::segment::list_segments(wal_dir).map_err(|e| backup_error(e.to_string()))?;
+        fs::create_dir_all(staging.join("wal")).map_err(io_error(&staging))?;
+        for (i, segment) in segments.iter().enumerate() {
+            // A segment holds entries from its start up to the next one's
+            let ends_before = segments.get(i + 1).is_some_and(|next| next.start_sequence <= from);
+            if ends_before || segment.start_sequence > sequence {
+                continue;
+            }
+            let data = fs::read(&segment.path).map_err(io_error(&segment.path))?;
+            let name = segment.path.file_name().unwrap_or_default().to_string_lossy();
+            write(format!("wal/{name}"), &data)?;
+            wal_segments += 1;
+        }
+    }
+
+    let manifest = BackupManifest {
+        id: id.clone(),
+        kind,
+        parent: parent.map(|p| p.id.clone()),
+        snapshot_sequence: snapshot.map(|(s, _)| s.sequence),
+        sequence,
+        taken_at,
+        wal_segments,
+        files,
+    };
+    let data = serde_json::to_vec_pretty(&manifest).map_err(|e| backup_error(e.to_string()))?;
+    let path = staging.join(MANIFEST_FILE);
+    write_synced(&path, &data).map_err(io_error(&path))?;
+    for dir in [staging.join("stores"), staging.join("wal")] {
+        if dir.is_dir() {
+            sync_dir(&dir)?;
+        }
+    }
+    sync_dir(&staging)?;
+    let archive = target.join(&id);
+    fs::rename(&staging, &archive).map_err(io_error(&archive))?;
+    sync_dir(target)?;
+    Ok(manifest)
+}
+
+fn kind_name(kind: BackupKind) -> &'static str {
+    match kind {
+        BackupKind::Full => "full",
+        BackupKind::Incremental => "incremental",
+    }
+}
+
+/// The archives under `target`, oldest first
+pub fn list_backups(target: impl AsRef<Path>) -> Result<Vec<BackupManifest>, HexadError> {
+    let target = target.as_ref();
+    if !target.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut manifests = Vec::new();
+    for entry in fs::read_dir(target).map_err(io_error(target))? {
+        let path = entry.map_err(io_error(target))?.path().join(MANIFEST_FILE);
+        // Archives still being written have no manifest yet
+        if !path.is_file() {
+            continue;
+        }
+        let data = fs::read(&path).map_err(io_error(&path))?;
+        let manifest: BackupManifest = serde_json::from_slice(&data)
+            .map_err(|e| backup_error(format!("Corrupt manifest {}: {e}", path.display())))?;
+        manifests.push(manifest);
+    }
+    manifests.sort_by(|a, b| a.taken_at.cmp(&b.taken_at).then_with(|| a.id.cmp(&b.id)));
+    Ok(manifests)
+}
+
+/// The manifest of archive `id` under `target`, after checking its files
+pub fn read_backup(target: impl AsRef<Path>, id: &str) -> Result<BackupManifest, HexadError> {
+    let dir = archive_dir(target.as_ref(), id)?;
+    let path = dir.join(MANIFEST_FILE);
+    if !path.is_file() {
+        return Err(HexadError::NotFound(format!("backup {id}")));
+    }
+    let data = fs::read(&path).map_err(io_error(&path))?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&data).map_err(|e| backup_error(format!("Corrupt manifest of backup {id}: {e}")))?;
+    for file in &manifest.files {
+        let path = dir.join(&file.path);
+        let data = fs::read(&path).map_err(io_error(&path))?;
+        if data.len() as u64 != file.bytes || compute_crc32(&data) != file.crc32 {
+            return Err(backup_error(format!("Backup {id} is corrupt: {} does not match its checksum", file.path)));
+        }
+    }
+    Ok(manifest)
+}
+
+/// Directory of archive `id`, refusing IDs that leave `target`
+fn archive_dir(target: &Path, id: &str) -> Result<PathBuf, HexadError> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(HexadError::ValidationError(format!("Invalid backup ID: {id}")));
+    }
+    Ok(target.join(id))
+}
+
+/// The state archive `id` under `target` captured: the store files of the
+/// latest archive in its chain that has them, rolled forward through the
+/// WAL of it and the archives after it
+pub fn load_backup(target: impl AsRef<Path>, id: &str) -> Result<StoreSnapshot, HexadError> {
+    let ArchivedState { mut snapshot, entries, .. } = load_archive(target, id)?;
+    snapshot.roll_forward(entries, DateTime::<Utc>::MAX_UTC);
+    Ok(snapshot)
+}
+
+/// The store files of the latest archive in the chain of archive `id`
+/// under `target` that has them, and the WAL of it and the archives after
+/// it
+pub fn load_archive(target: impl AsRef<Path>, id: &str) -> Result<ArchivedState, HexadError> {
+    let target = target.as_ref();
+    let mut chain = vec![read_backup(target, id)?];
+    let mut visited = HashSet::from([id.to_string()]);
+    while chain.last().is_some_and(|m| m.snapshot_sequence.is_none()) {
+        let child = chain.last().map(|m| m.id.clone()).unwrap_or_default();
+        let parent = chain
+            .last()
+            .and_then(|m| m.parent.clone())
+            .ok_or_else(|| backup_error(format!("Backup {child} has neither store files nor a parent")))?;
+        if !visited.insert(parent.clone()) {
+            return Err(backup_error(format!("Backup {child} continues {parent}, which the chain of {id} already holds")));
+        }
+        let manifest = read_backup(target, &parent).map_err(|e| match e {
+            HexadError::NotFound(_) => backup_error(format!("Backup {child} continues {parent}, which is missing")),
+            e => e,
+        })?;
+        chain.push(manifest);
+    }
+    chain.reverse();
+
+    let (snapshot, history) = read_store_files(&target.join(&chain[0].id))?;
+    let mut entries = Vec::new();
+    for manifest in &chain {
+        let wal_dir = target.join(&manifest.id).join("wal");
+        if manifest.wal_segments == 0 || !wal_dir.is_dir() {
+            continue;
+        }
+        let replayed = verisim_wal::WalReader::open(&wal_dir)
+            .and_then(|reader| reader.replay_from(0))
+            .map_err(|e| backup_error(format!("Backup {}: {e}", manifest.id)))?;
+        entries.extend(replayed);
+    }
+    entries.sort_by_key(|e| e.sequence);
+    entries.dedup_by_key(|e| e.sequence);
+    entries.retain(|e| e.sequence > snapshot.sequence);
+    Ok(ArchivedState {
+        snapshot,
+        history,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HexadBuilder, HexadId};
+
+    fn snapshot() -> StoreSnapshot {
+        let status = |id: &str| HexadStatus {
+            id: HexadId::new(id),
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            version: 2,
+            modality_status: Default::default(),
+            expires_at: None,
+        };
+        let mut input = HexadBuilder::new()
+            .with_document("Sensor", "Calibrated")
+            .with_embedding(vec![0.1, 0.2])
+            .build();
+        input.metadata.insert("site".to_string(), "north".to_string());
+        StoreSnapshot {
+            sequence: 7,
+            taken_at: Utc::now(),
+            hexads: vec![SnapshotHexad {
+                status: status("a"),
+                input,
+            }],
+            deleted: vec![SnapshotDeletedHexad {
+                deleted: DeletedHexad {
+                    status: status("b"),
+                    deleted_by: "alice".to_string(),
+                    deleted_at: Utc::now(),
+                },
+                input: HexadBuilder::new().with_document("Old", "Gone").build(),
+            }],
+            tombstones: Vec::new(),
+        }
+    }
+
+    fn history() -> StoreHistory {
+        let mut chain = ProvenanceChain::new("a");
+        chain.append(verisim_provenance::ProvenanceEventType::Created, "alice", None, "Created");
+        let snapshot = HexadSnapshot {
+            id: HexadId::new("a"),
+            input: HexadInput::default(),
+            modality_status: Default::default(),
+            timestamp: Utc::now(),
+        };
+        StoreHistory {
+            versions: BTreeMap::from([("a".to_string(), vec![Version::new(1, snapshot.clone(), "system"), Version::new(2, snapshot, "system")])]),
+            chains: BTreeMap::from([("a".to_string(), chain)]),
+        }
+    }
+
+    #[test]
+    fn test_store_files_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest =
+            write_archive(dir.path(), BackupKind::Full, None, Some((&snapshot(), &history())), None, 7).unwrap();
+        assert_eq!(manifest.snapshot_sequence, Some(7));
+        assert_eq!(manifest.files.len(), 3 + MODALITY_FILES.len());
+
+        let archived = load_archive(dir.path(), &manifest.id).unwrap();
+        assert_eq!(archived.history.versions["a"].len(), 2);
+        assert!(archived.history.chains["a"].verify().is_ok());
+        assert!(archived.entries.is_empty());
+
+        let restored = load_backup(dir.path(), &manifest.id).unwrap();
+        assert_eq!(restored.sequence, 7);
+        let a = &restored.hexads[0];
+        assert_eq!(a.status.version, 2);
+        assert_eq!(a.input.document.as_ref().unwrap().title, "Sensor");
+        assert_eq!(a.input.vector.as_ref().unwrap().embedding, vec![0.1, 0.2]);
+        assert_eq!(a.input.metadata["site"], "north");
+        assert!(a.input.graph.is_none());
+        assert_eq!(restored.deleted[0].deleted.deleted_by, "alice");
+        assert_eq!(restored.deleted[0].input.document.as_ref().unwrap().title, "Old");
+
+        assert_eq!(list_backups(dir.path()).unwrap().len(), 1);
+        assert!(list_backups(dir.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_and_missing_archives_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest =
+            write_archive(dir.path(), BackupKind::Full, None, Some((&snapshot(), &history())), None, 7).unwrap();
+        let path = dir.path().join(&manifest.id).join("stores/document.json");
+        fs::write(&path, b"{}").unwrap();
+        assert!(matches!(
+            load_backup(dir.path(), &manifest.id),
+            Err(HexadError::ModalityError { message, .. }) if message.contains("checksum")
+        ));
+        assert!(matches!(load_backup(dir.path(), "nope"), Err(HexadError::NotFound(_))));
+        assert!(matches!(load_backup(dir.path(), "../x"), Err(HexadError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_archive_chains_that_loop_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        // Archive `a` continues `b`, which continues `a`
+        let b = BackupManifest {
+            id: "b".to_string(),
+            kind: BackupKind::Incremental,
+            parent: None,
+            snapshot_sequence: None,
+            sequence: 0,
+            taken_at: Utc::now(),
+            wal_segments: 0,
+            files: Vec::new(),
+        };
+        let a = write_archive(dir.path(), BackupKind::Incremental, Some(&b), None, None, 1).unwrap();
+        let written = write_archive(dir.path(), BackupKind::Incremental, Some(&a), None, None, 2).unwrap();
+        fs::rename(dir.path().join(&written.id), dir.path().join("b")).unwrap();
+        assert!(matches!(
+            load_backup(dir.path(), &a.id),
+            Err(HexadError::ModalityError { message, .. }) if message.contains("already holds")
+        ));
+    }
+}
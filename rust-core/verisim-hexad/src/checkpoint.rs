@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! WAL checkpoints
+//!
+//! With a WAL enabled, every write is logged and the log never shrinks on
+//! its own.  [`InMemoryHexadStore::checkpoint`](crate::InMemoryHexadStore::checkpoint)
+//! pauses writes, serializes the store as a [`StoreSnapshot`] next to the
+//! WAL segments, and deletes the segments the snapshot covers.  Recovery
+//! starts from the latest snapshot and replays only the entries after its
+//! sequence.  [`WalLag`] reports how far the log has grown since.
+//!
+//! Keeping several snapshots also allows point-in-time recovery:
+//! [`InMemoryHexadStore::recover_to`](crate::InMemoryHexadStore::recover_to)
+//! rolls the latest snapshot taken before a target time forward through
+//! the writes committed before it, and brings the live store to that state.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use verisim_wal::{WalEntry, WalOperation};
+
+use crate::{DeletedHexad, HexadError, HexadGraphInput, HexadId, HexadInput, HexadStatus, HexadTombstone};
+
+/// Everything a store holds, as of one WAL sequence number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    /// Last WAL entry reflected in the snapshot
+    pub sequence: u64,
+    /// When it was taken
+    pub taken_at: DateTime<Utc>,
+    /// Live entities
+    pub hexads: Vec<SnapshotHexad>,
+    /// Soft-deleted entities
+    pub deleted: Vec<SnapshotDeletedHexad>,
+    /// Tombstones of merged-away entities
+    pub tombstones: Vec<HexadTombstone>,
+}
+
+/// A live entity: its status and the input that recreates its current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotHexad {
+    pub status: HexadStatus,
+    pub input: HexadInput,
+}
+
+/// A soft-deleted entity and the input that recreates it on restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDeletedHexad {
+    pub deleted: DeletedHexad,
+    pub input: HexadInput,
+}
+
+impl StoreSnapshot {
+    /// The latest snapshot in a WAL directory, if a checkpoint was taken
+    pub fn read_latest(wal_dir: impl AsRef<Path>) -> Result<Option<Self>, HexadError> {
+        let latest = verisim_wal::read_latest_snapshot(wal_dir.as_ref()).map_err(|e| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: format!("Failed to read snapshot: {e}"),
+        })?;
+        latest
+            .map(|(_, data)| {
+                serde_json::from_slice(&data).map_err(|e| HexadError::ModalityError {
+                    modality: "wal".to_string(),
+                    message: format!("Corrupt snapshot: {e}"),
+                })
+            })
+            .transpose()
+    }
+
+    /// The state of an empty store, for a WAL that has never been compacted
+    pub fn empty() -> Self {
+        Self {
+            sequence: 0,
+            taken_at: DateTime::<Utc>::MIN_UTC,
+            hexads: Vec::new(),
+            deleted: Vec::new(),
+            tombstones: Vec::new(),
+        }
+    }
+
+    /// The newest snapshot in a WAL directory taken before `target`
+    pub fn read_before(wal_dir: impl AsRef<Path>, target: DateTime<Utc>) -> Result<Option<Self>, HexadError> {
+        let snapshots = verisim_wal::snapshot::list_snapshots(wal_dir.as_ref()).map_err(|e| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: format!("Failed to list snapshots: {e}"),
+        })?;
+        for info in snapshots.iter().rev() {
+            let data = std::fs::read(&info.path).map_err(|e| HexadError::ModalityError {
+                modality: "wal".to_string(),
+                message: format!("Failed to read snapshot: {e}"),
+            })?;
+            let snapshot: Self = serde_json::from_slice(&data).map_err(|e| HexadError::ModalityError {
+                modality: "wal".to_string(),
+                message: format!("Corrupt snapshot: {e}"),
+            })?;
+            if snapshot.taken_at < target {
+                return Ok(Some(snapshot));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Apply the writes in `entries` that were committed before `until`.
+    ///
+    /// A write counts once its COMMITTED marker is logged; writes with no
+    /// marker never completed and are skipped.  Entries at or below the
+    /// snapshot's sequence are ignored.  Returns the number of writes
+    /// applied.
+    ///
+    /// Soft deletes are not logged, so an entity soft-deleted after the
+    /// snapshot is still live in the result.
+    pub fn roll_forward(
+        &mut self,
+        entries: impl IntoIterator<Item = WalEntry>,
+        until: DateTime<Utc>,
+    ) -> usize {
+        let writes = committed_writes(entries, self.sequence, until);
+        let applied = writes.len();
+        for (write, committed) in writes {
+            self.apply(write, committed.timestamp);
+            self.sequence = committed.sequence;
+        }
+        applied
+    }
+
+    /// Apply one committed write
+    fn apply(&mut self, write: WalEntry, at: DateTime<Utc>) {
+        let id = write.entity_id;
+        match write.operation {
+            WalOperation::Delete => {
+                self.hexads.retain(|h| h.status.id.as_str() != id);
+                self.deleted.retain(|d| d.deleted.status.id.as_str() != id);
+            }
+            WalOperation::Insert | WalOperation::Update => {
+                let Ok(update) = serde_json::from_slice::<HexadInput>(&write.payload) else {
+                    return;
+                };
+                // An update of a soft-deleted entity is its restore
+                if let Some(index) = self.deleted.iter().position(|d| d.deleted.status.id.as_str() == id) {
+                    let restored = self.deleted.remove(index);
+                    self.hexads.push(SnapshotHexad {
+                        status: restored.deleted.status,
+                        input: restored.input,
+                    });
+                }
+                match self.hexads.iter_mut().find(|h| h.status.id.as_str() == id) {
+                    Some(hexad) => {
+                        hexad.status.version += 1;
+                        hexad.status.modified_at = at;
+                        overlay(&mut hexad.input, update);
+                    }
+                    None => {
+                        let mut input = HexadInput::default();
+                        overlay(&mut input, update);
+                        self.hexads.push(SnapshotHexad {
+                            status: HexadStatus {
+                                id: HexadId::new(id),
+                                created_at: at,
+                                modified_at: at,
+                                version: 1,
+                                modality_status: Default::default(),
+                                expires_at: None,
+                            },
+                            input,
+                        });
+                    }
+                }
+            }
+            WalOperation::Checkpoint => {}
+        }
+    }
+}
+
+/// The writes in `entries` after sequence `after` that were committed
+/// before `until`, in commit order, each with its COMMITTED marker.
+///
+/// A write counts once its marker is logged; writes with no marker never
+/// completed and are skipped.
+pub(crate) fn committed_writes(
+    entries: impl IntoIterator<Item = WalEntry>,
+    after: u64,
+    until: DateTime<Utc>,
+) -> Vec<(WalEntry, WalEntry)> {
+    let mut pending: HashMap<String, WalEntry> = HashMap::new();
+    let mut writes = Vec::new();
+
+    for entry in entries {
+        if entry.sequence <= after || entry.entity_id.is_empty() {
+            continue;
+        }
+        let committed = entry.operation == WalOperation::Checkpoint && entry.payload == b"COMMITTED";
+        if !committed {
+            pending.insert(entry.entity_id.clone(), entry);
+            continue;
+        }
+        let Some(write) = pending.remove(&entry.entity_id) else {
+            continue;
+        };
+        if entry.timestamp >= until {
+            continue;
+        }
+        writes.push((write, entry));
+    }
+    writes
+}
+
+/// Merge an update into an entity's input the way the store applies it:
+/// the modalities it sets replace the current ones, relationships are added
+pub(crate) fn overlay(state: &mut HexadInput, update: HexadInput) {
+    if let Some(graph) = update.graph {
+        let relationships = &mut state.graph.get_or_insert_with(|| HexadGraphInput { relationships: Vec::new() }).relationships;
+        for relationship in graph.relationships {
+            if !relationships.contains(&relationship) {
+                relationships.push(relationship);
+            }
+        }
+    }
+    state.vector = update.vector.or(state.vector.take());
+    state.tensor = update.tensor.or(state.tensor.take());
+    state.semantic = update.semantic.or(state.semantic.take());
+    state.document = update.document.or(state.document.take());
+    state.spatial = update.spatial.or(state.spatial.take());
+    state.provenance = update.provenance.or(state.provenance.take());
+}
+
+/// Outcome of a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointReport {
+    /// Last WAL entry the snapshot covers
+    pub sequence: u64,
+    /// When the snapshot was taken
+    pub taken_at: DateTime<Utc>,
+    /// Live entities in the snapshot
+    pub hexads: usize,
+    /// Soft-deleted entities in the snapshot
+    pub deleted: usize,
+    /// Size of the snapshot file
+    pub snapshot_bytes: u64,
+    /// WAL segments removed
+    pub segments_removed: usize,
+    /// Bytes freed by removing them
+    pub bytes_removed: u64,
+    /// How long writes were paused
+    pub duration_ms: u64,
+}
+
+/// How far the WAL has grown since the last checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalLag {
+    /// Sequence the next WAL entry will get
+    pub next_sequence: u64,
+    /// Sequence covered by the last checkpoint
+    pub checkpoint_sequence: Option<u64>,
+    /// When the last checkpoint was taken
+    pub checkpoint_at: Option<DateTime<Utc>>,
+    /// Entries a recovery would replay
+    pub entries_since_checkpoint: u64,
+    /// WAL segment files on disk
+    pub segment_count: usize,
+    /// Size of those segments
+    pub segment_bytes: u64,
+}
+
+impl WalLag {
+    /// Seconds since the last checkpoint, as of `now`
+    pub fn checkpoint_age_secs(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.checkpoint_at.map(|at| (now - at).num_seconds().max(0))
+    }
+}
+
+/// An entity a point-in-time recovery changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredHexad {
+    pub id: HexadId,
+    /// Version before the recovery; `None` if the entity did not exist
+    pub current_version: Option<u64>,
+    /// Version as of the target time; `None` if it did not exist then
+    pub target_version: Option<u64>,
+    /// Why bringing this entity to its target state failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a point-in-time recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Time the store was recovered to
+    pub target: DateTime<Utc>,
+    /// Only computed, nothing written
+    pub dry_run: bool,
+    /// Sequence of the snapshot the recovery started from (0: empty store)
+    pub snapshot_sequence: u64,
+    /// Last WAL entry applied on top of it
+    pub replayed_through: u64,
+    /// Committed writes replayed from the WAL
+    pub writes_replayed: usize,
+    /// Entities with later changes undone, or created after the target and
+    /// removed
+    pub rolled_back: Vec<RecoveredHexad>,
+    /// Entities missing or behind their target state, brought up to it
+    pub rolled_forward: Vec<RecoveredHexad>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexadBuilder;
+    use chrono::Duration;
+    use verisim_wal::WalModality;
+
+    fn entry(sequence: u64, at: DateTime<Utc>, operation: WalOperation, id: &str, payload: Vec<u8>) -> WalEntry {
+        WalEntry {
+            sequence,
+            timestamp: at,
+            operation,
+            modality: WalModality::All,
+            entity_id: id.to_string(),
+            payload,
+        }
+    }
+
+    fn write(sequence: u64, at: DateTime<Utc>, operation: WalOperation, id: &str, title: &str) -> [WalEntry; 2] {
+        let input = HexadBuilder::new().with_document(title, "body").build();
+        [
+            entry(sequence, at, operation, id, serde_json::to_vec(&input).unwrap()),
+            entry(sequence + 1, at, WalOperation::Checkpoint, id, b"COMMITTED".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn test_roll_forward_stops_at_target() {
+        let start = Utc::now();
+        let mut entries = Vec::new();
+        entries.extend(write(1, start, WalOperation::Insert, "a", "A1"));
+        entries.extend(write(3, start + Duration::minutes(1), WalOperation::Update, "a", "A2"));
+        entries.extend(write(5, start + Duration::minutes(1), WalOperation::Insert, "b", "B1"));
+        // Never committed
+        entries.push(entry(7, start + Duration::minutes(1), WalOperation::Delete, "b", Vec::new()));
+        entries.extend(write(8, start + Duration::minutes(5), WalOperation::Update, "a", "A3"));
+
+        let mut snapshot = StoreSnapshot::empty();
+        let applied = snapshot.roll_forward(entries, start + Duration::minutes(2));
+
+        assert_eq!(applied, 3);
+        assert_eq!(snapshot.sequence, 6);
+        let a = snapshot.hexads.iter().find(|h| h.status.id.as_str() == "a").unwrap();
+        assert_eq!(a.status.version, 2);
+        assert_eq!(a.input.document.as_ref().unwrap().title, "A2");
+        assert!(snapshot.hexads.iter().any(|h| h.status.id.as_str() == "b"));
+    }
+}
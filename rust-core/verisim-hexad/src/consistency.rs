@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Cross-modal consistency checks
+//!
+//! The modality stores are written independently, so a crash, a failed
+//! rollback or a bug can leave them disagreeing with the registry.
+//! [`InMemoryHexadStore::verify_consistency`](crate::InMemoryHexadStore::verify_consistency)
+//! checks every entity against a set of invariants and reports the
+//! violations per entity, with per-check failure rates that can be fed to
+//! drift detection.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::HexadId;
+
+/// A cross-modal invariant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsistencyCheck {
+    /// An embedding is stored iff the vector modality is set, and has the
+    /// configured dimension
+    Embedding,
+    /// The graph node has outgoing edges iff the graph modality is set
+    Graph,
+    /// A document is indexed iff the document modality is set
+    Document,
+    /// The provenance chain exists iff the provenance modality is set, and
+    /// its hashes verify
+    Provenance,
+}
+
+impl ConsistencyCheck {
+    /// Every check, in the order they run
+    pub const ALL: [ConsistencyCheck; 4] = [
+        ConsistencyCheck::Embedding,
+        ConsistencyCheck::Graph,
+        ConsistencyCheck::Document,
+        ConsistencyCheck::Provenance,
+    ];
+}
+
+/// One failed check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsistencyViolation {
+    pub check: ConsistencyCheck,
+    /// What disagrees
+    pub message: String,
+}
+
+/// The checks one entity failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityConsistency {
+    pub id: HexadId,
+    pub violations: Vec<ConsistencyViolation>,
+}
+
+impl EntityConsistency {
+    /// Whether every check passed
+    pub fn is_consistent(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Whether `check` failed
+    pub fn fails(&self, check: ConsistencyCheck) -> bool {
+        self.violations.iter().any(|v| v.check == check)
+    }
+}
+
+/// Result of a consistency pass over the store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub checked_at: DateTime<Utc>,
+    /// Entities checked
+    pub entities_checked: usize,
+    /// Entities failing at least one check
+    pub inconsistent: Vec<EntityConsistency>,
+    /// Entities failing each check
+    pub failures: BTreeMap<ConsistencyCheck, usize>,
+}
+
+impl ConsistencyReport {
+    /// Build a report from the results of checking `entities_checked`
+    /// entities, keeping only the inconsistent ones
+    pub fn new(checked_at: DateTime<Utc>, entities_checked: usize, results: Vec<EntityConsistency>) -> Self {
+        let inconsistent: Vec<EntityConsistency> = results.into_iter().filter(|e| !e.is_consistent()).collect();
+        let failures = ConsistencyCheck::ALL
+            .into_iter()
+            .map(|check| (check, inconsistent.iter().filter(|e| e.fails(check)).count()))
+            .collect();
+        Self {
+            checked_at,
+            entities_checked,
+            inconsistent,
+            failures,
+        }
+    }
+
+    /// Fraction of checked entities failing `check`, from 0.0 to 1.0,
+    /// usable as a drift score
+    pub fn failure_rate(&self, check: ConsistencyCheck) -> f64 {
+        if self.entities_checked == 0 {
+            return 0.0;
+        }
+        self.failures.get(&check).copied().unwrap_or(0) as f64 / self.entities_checked as f64
+    }
+
+    /// IDs of the entities failing `check`
+    pub fn failing(&self, check: ConsistencyCheck) -> Vec<String> {
+        self.inconsistent
+            .iter()
+            .filter(|e| e.fails(check))
+            .map(|e| e.id.to_string())
+            .collect()
+    }
+}
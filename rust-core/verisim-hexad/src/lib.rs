@@ -10,20 +10,20 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 
 // Re-export modality types — all eight modalities
 pub use verisim_document::{Document, DocumentStore};
 pub use verisim_graph::{GraphEdge, GraphNode, GraphObject, GraphStore};
 pub use verisim_provenance::{
-    InMemoryProvenanceStore, ProvenanceChain, ProvenanceError, ProvenanceEventType,
+    ChainLink, InMemoryProvenanceStore, ProvenanceChain, ProvenanceError, ProvenanceEventType,
     ProvenanceRecord, ProvenanceStore,
 };
 pub use verisim_semantic::{ProofBlob, Provenance, SemanticAnnotation, SemanticStore, SemanticType, SemanticValue};
 pub use verisim_spatial::{
-    BoundingBox, Coordinates, GeometryType, InMemorySpatialStore, SpatialData,
-    SpatialSearchResult, SpatialStore,
+    BoundingBox, Coordinates, Geometry, GeometryType, InMemorySpatialStore, SpatialData,
+    SpatialPredicate, SpatialSearchResult, SpatialStore,
 };
 pub use verisim_tensor::{Tensor, TensorStore};
 pub use verisim_temporal::{TemporalStore, TimeRange, Version};
@@ -33,6 +33,40 @@
 mod store;
 pub use store::{HexadSnapshot, InMemoryHexadStore};
 
+// WAL checkpoints: snapshots and log lag
+pub mod checkpoint;
+pub use checkpoint::{
+    CheckpointReport, RecoveredHexad, RecoveryReport, SnapshotDeletedHexad, SnapshotHexad, StoreSnapshot, WalLag,
+};
+
+// Backups: consistent archives of the store, and restoring them
+pub mod backup;
+pub use backup::{BackupKind, BackupManifest, RestoreReport};
+
+// Cross-modal consistency checks
+pub mod consistency;
+pub use consistency::{ConsistencyCheck, ConsistencyReport, ConsistencyViolation, EntityConsistency};
+
+// Change hooks on store mutations
+pub mod listener;
+pub use listener::HexadListener;
+
+// Consistent read views for long lists and exports
+pub mod read_snapshot;
+pub use read_snapshot::ReadSnapshot;
+
+// Entity merge: policies and tombstones
+pub mod merge;
+pub use merge::{HexadMerge, HexadTombstone, MergePolicy, MergeRule};
+
+// Entity-level security: per-principal policies checked by the store
+pub mod security;
+pub use security::{Action, EntityPolicy, EntityView, PolicyDecision, PolicyRule, Principal};
+
+// Attribute-based access control expressions used by entity policies
+pub mod abac;
+pub use abac::Expression;
+
 // Homoiconicity: queries as hexads
 pub mod query_hexad;
 pub use query_hexad::{QueryHexadBuilder, QueryExecution};
@@ -42,7 +76,7 @@
 pub use transaction::{IsolationLevel, LockType, TransactionManager, TransactionError, TransactionState};
 
 // WAL types (re-exported for external use)
-pub use verisim_wal::{SyncMode, WalEntry, WalModality, WalOperation, WalWriter};
+pub use verisim_wal::{SyncMode, WalEntry, WalModality, WalOperation, WalReader, WalWriter};
 
 /// Hexad errors
 #[derive(Error, Debug)]
@@ -60,7 +94,15 @@ pub enum HexadError {
     ValidationError(String),
 }
 
+/// Separates a collection name from the rest of a Hexad ID, as in
+/// `people:alice`
+pub const COLLECTION_SEPARATOR: char = ':';
+
 /// Unique identifier for a Hexad entity
+///
+/// An ID may be scoped to a collection, a logically isolated dataset with
+/// its own listings, searches, stats and retention, by prefixing it with
+/// the collection name and [`COLLECTION_SEPARATOR`].
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct HexadId(pub String);
 
@@ -75,6 +117,45 @@ pub fn generate() -> Self {
         Self(uuid::Uuid::new_v4().to_string())
     }
 
+    /// Create an ID in `collection`
+    pub fn in_collection(collection: &str, id: &str) -> Self {
+        Self(format!("{collection}{COLLECTION_SEPARATOR}{id}"))
+    }
+
+    /// Generate a new UUID-based ID in `collection`
+    pub fn generate_in(collection: &str) -> Self {
+        Self::in_collection(collection, &uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Check that `name` can be used as a collection name: 1 to 64 ASCII
+    /// alphanumerics, dashes and underscores
+    pub fn validate_collection(name: &str) -> Result<(), HexadError> {
+        if name.is_empty() || name.len() > 64 {
+            return Err(HexadError::ValidationError(
+                "Collection name must be 1 to 64 characters".to_string(),
+            ));
+        }
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(HexadError::ValidationError(format!(
+                "Collection name '{}' must contain only alphanumeric characters, dashes, and underscores",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// The collection this ID belongs to, if any
+    pub fn collection(&self) -> Option<&str> {
+        self.0.split_once(COLLECTION_SEPARATOR).map(|(collection, _)| collection)
+    }
+
+    /// The ID without its collection prefix
+    pub fn local_id(&self) -> &str {
+        self.0
+            .split_once(COLLECTION_SEPARATOR)
+            .map_or(self.0.as_str(), |(_, id)| id)
+    }
+
     /// Get the ID as a string reference
     pub fn as_str(&self) -> &str {
         &self.0
@@ -117,6 +198,33 @@ pub struct HexadStatus {
     pub version: u64,
     /// Status per modality
     pub modality_status: ModalityStatus,
+    /// When the entity expires and is soft-deleted by the TTL sweeper;
+    /// `None` never expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Counts of entities with a TTL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpiryStats {
+    /// Live entities with an expiry set
+    pub scheduled: usize,
+    /// Of those, the ones already past it and awaiting the sweeper
+    pub overdue: usize,
+    /// Entities expired since the store was opened
+    pub expired_total: u64,
+}
+
+/// A soft-deleted Hexad.  It is hidden from reads, lists and searches but
+/// keeps its version history and provenance until restored or purged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedHexad {
+    /// Status when it was deleted
+    pub status: HexadStatus,
+    /// Who deleted it
+    pub deleted_by: String,
+    /// When it was deleted
+    pub deleted_at: DateTime<Utc>,
 }
 
 /// Status of each modality for an entity (octad: 8 modalities)
@@ -158,6 +266,27 @@ pub fn missing(&self) -> Vec<&'static str> {
         if !self.spatial { missing.push("spatial"); }
         missing
     }
+
+    /// Get list of populated modalities
+    pub fn populated(&self) -> Vec<&'static str> {
+        let missing = self.missing();
+        ["graph", "vector", "tensor", "semantic", "document", "temporal", "provenance", "spatial"]
+            .into_iter()
+            .filter(|name| !missing.contains(name))
+            .collect()
+    }
+
+    /// Whether every modality populated in `required` is populated here
+    pub fn covers(&self, required: &ModalityStatus) -> bool {
+        (!required.graph || self.graph)
+            && (!required.vector || self.vector)
+            && (!required.tensor || self.tensor)
+            && (!required.semantic || self.semantic)
+            && (!required.document || self.document)
+            && (!required.temporal || self.temporal)
+            && (!required.provenance || self.provenance)
+            && (!required.spatial || self.spatial)
+    }
 }
 
 /// Input data for creating/updating a Hexad
@@ -181,6 +310,21 @@ pub struct HexadInput {
     pub metadata: HashMap<String, String>,
 }
 
+impl HexadInput {
+    /// Modalities this input writes
+    pub fn modalities(&self) -> ModalityStatus {
+        ModalityStatus {
+            graph: self.graph.is_some(),
+            vector: self.vector.is_some(),
+            tensor: self.tensor.is_some(),
+            semantic: self.semantic.is_some(),
+            document: self.document.is_some(),
+            temporal: false,
+            provenance: self.provenance.is_some(),
+            spatial: self.spatial.is_some(),
+        }
+    }
+}
 
 /// Graph modality input
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -194,7 +338,7 @@ pub struct HexadGraphInput {
 pub struct HexadVectorInput {
     /// Embedding vector
     pub embedding: Vec<f32>,
-    /// Embedding model used
+    /// Embedding model used, kept as the embedding's `model` metadata
     pub model: Option<String>,
 }
 
@@ -238,6 +382,9 @@ pub struct HexadProvenanceInput {
     pub source: Option<String>,
     /// Human-readable description of the event
     pub description: String,
+    /// For a hand-off from another instance, the entity's chain there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<verisim_provenance::ChainLink>,
 }
 
 /// Spatial modality input — geospatial coordinates and geometry
@@ -253,6 +400,18 @@ pub struct HexadSpatialInput {
     pub geometry_type: Option<String>,
     /// Spatial Reference System Identifier — defaults to 4326 (WGS84)
     pub srid: Option<u32>,
+    /// Full geometry. When present it determines the geometry type, and its
+    /// centroid replaces `latitude`/`longitude` as the representative point.
+    #[serde(default)]
+    pub geometry: Option<Geometry>,
+    /// Geometry as WKT or PostGIS EWKT, used when `geometry` is absent.
+    /// An EWKT `SRID=n;` prefix applies when `srid` is not given.
+    #[serde(default)]
+    pub wkt: Option<String>,
+    /// Geometry as hex-encoded WKB or EWKB, used when `geometry` and `wkt`
+    /// are absent.
+    #[serde(default)]
+    pub wkb: Option<String>,
     /// Arbitrary spatial properties (address, region, accuracy, etc.)
     #[serde(default)]
     pub properties: HashMap<String, String>,
@@ -283,6 +442,114 @@ pub struct Hexad {
     pub spatial_data: Option<SpatialData>,
 }
 
+/// Modalities to load when reading a Hexad.
+///
+/// Status is always returned.  Fields for modalities outside the mask are
+/// left empty (`None`, or 0 for the temporal and provenance counts), so
+/// callers that only need status or counts skip the tensor, document and
+/// vector reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModalityMask {
+    pub graph: bool,
+    pub vector: bool,
+    pub tensor: bool,
+    pub semantic: bool,
+    pub document: bool,
+    /// Version count
+    pub temporal: bool,
+    /// Provenance chain length
+    pub provenance: bool,
+    pub spatial: bool,
+}
+
+impl ModalityMask {
+    /// Every modality
+    pub const ALL: Self = Self {
+        graph: true,
+        vector: true,
+        tensor: true,
+        semantic: true,
+        document: true,
+        temporal: true,
+        provenance: true,
+        spatial: true,
+    };
+
+    /// Status only
+    pub const STATUS: Self = Self {
+        graph: false,
+        vector: false,
+        tensor: false,
+        semantic: false,
+        document: false,
+        temporal: false,
+        provenance: false,
+        spatial: false,
+    };
+
+    /// Status plus the version and provenance counts, as list views show
+    pub const SUMMARY: Self = Self {
+        temporal: true,
+        provenance: true,
+        ..Self::STATUS
+    };
+
+    /// Clear the fields of a fully loaded Hexad that the mask excludes
+    pub fn apply(&self, mut hexad: Hexad) -> Hexad {
+        if !self.graph { hexad.graph_node = None; }
+        if !self.vector { hexad.embedding = None; }
+        if !self.tensor { hexad.tensor = None; }
+        if !self.semantic { hexad.semantic = None; }
+        if !self.document { hexad.document = None; }
+        if !self.temporal { hexad.version_count = 0; }
+        if !self.provenance { hexad.provenance_chain_length = 0; }
+        if !self.spatial { hexad.spatial_data = None; }
+        hexad
+    }
+}
+
+impl Default for ModalityMask {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Entity counts for one collection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionStats {
+    /// Collection name
+    pub name: String,
+    /// Live entities
+    pub hexads: usize,
+    /// Soft-deleted entities awaiting restore or purge
+    pub deleted: usize,
+    /// Live entities with each modality populated, keyed by modality name
+    pub modalities: HashMap<String, usize>,
+}
+
+/// Which live entities [`HexadStore::count`] counts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CountFilter {
+    /// Only entities in this collection
+    pub collection: Option<String>,
+    /// Only entities with every modality set here populated
+    pub modalities: ModalityStatus,
+}
+
+/// Entity counts kept up to date as entities are written and deleted, so
+/// reading them costs nothing.  They are adjusted just after the registry
+/// changes and may briefly trail a concurrent write.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EstimatedCounts {
+    /// Live entities
+    pub hexads: usize,
+    /// Soft-deleted entities awaiting restore or purge
+    pub deleted: usize,
+    /// Live entities per collection; entities outside a collection are
+    /// only in `hexads`
+    pub collections: BTreeMap<String, usize>,
+}
+
 /// Hexad store - manages entities across all modalities
 #[async_trait]
 pub trait HexadStore: Send + Sync {
@@ -295,9 +562,56 @@ pub trait HexadStore: Send + Sync {
     /// Get a Hexad by ID
     async fn get(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError>;
 
+    /// Get a Hexad with only the modalities in `mask` loaded.  The default
+    /// loads everything and clears the rest; stores override it to skip the
+    /// reads.
+    async fn get_with(&self, id: &HexadId, mask: ModalityMask) -> Result<Option<Hexad>, HexadError> {
+        Ok(self.get(id).await?.map(|hexad| mask.apply(hexad)))
+    }
+
+    /// Get several Hexads, one result per ID in input order, `None` where
+    /// the entity is missing or hidden.  Stores that can read a batch at
+    /// once override the default, which gets them one by one.
+    async fn get_many(&self, ids: &[HexadId]) -> Result<Vec<Option<Hexad>>, HexadError> {
+        let mut hexads = Vec::with_capacity(ids.len());
+        for id in ids {
+            hexads.push(self.get(id).await?);
+        }
+        Ok(hexads)
+    }
+
     /// Delete a Hexad
     async fn delete(&self, id: &HexadId) -> Result<(), HexadError>;
 
+    /// Create several Hexads.  Results are in input order, and a failed
+    /// item does not stop the others.  Stores that can group writes per
+    /// modality override the default, which creates them one by one.
+    async fn create_batch(&self, inputs: Vec<HexadInput>) -> Vec<Result<Hexad, HexadError>> {
+        let mut results = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            results.push(self.create(input).await);
+        }
+        results
+    }
+
+    /// Update several Hexads, with one result per update in input order
+    async fn update_batch(&self, updates: Vec<(HexadId, HexadInput)>) -> Vec<Result<Hexad, HexadError>> {
+        let mut results = Vec::with_capacity(updates.len());
+        for (id, input) in updates {
+            results.push(self.update(&id, input).await);
+        }
+        results
+    }
+
+    /// Delete several Hexads, with one result per ID in input order
+    async fn delete_batch(&self, ids: &[HexadId]) -> Vec<Result<(), HexadError>> {
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            results.push(self.delete(id).await);
+        }
+        results
+    }
+
     /// Get Hexad status
     async fn status(&self, id: &HexadId) -> Result<Option<HexadStatus>, HexadError>;
 
@@ -310,11 +624,165 @@ pub trait HexadStore: Send + Sync {
     /// Query by graph relationship
     async fn query_related(&self, id: &HexadId, predicate: &str) -> Result<Vec<Hexad>, HexadError>;
 
-    /// Get version at a specific point in time
+    /// The entity as it was at `time`, rebuilt from its version history;
+    /// `None` if it had not been created yet
     async fn at_time(&self, id: &HexadId, time: DateTime<Utc>) -> Result<Option<Hexad>, HexadError>;
 
+    /// Every version of the entity recorded in `range`, each as the entity
+    /// was at that version, oldest first
+    async fn versions_between(&self, id: &HexadId, range: &TimeRange) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Restore a Hexad to its state at `version`, recorded as a new version.
+    ///
+    /// Modalities written since `version` get their earlier values back;
+    /// graph relationships added since are removed, as are vector, tensor,
+    /// document and spatial data the entity did not have then.  Provenance
+    /// is append-only: `provenance`, when given, records the revert.
+    async fn revert(
+        &self,
+        id: &HexadId,
+        version: u64,
+        provenance: Option<HexadProvenanceInput>,
+    ) -> Result<Hexad, HexadError>;
+
+    /// Soft-delete a Hexad: it is removed from the search indexes and
+    /// hidden from reads, and a `deleted` provenance event by `actor` is
+    /// recorded.  History is kept so it can be restored.
+    async fn soft_delete(&self, id: &HexadId, actor: &str) -> Result<DeletedHexad, HexadError>;
+
+    /// Restore a soft-deleted Hexad as a new version
+    async fn restore(&self, id: &HexadId, actor: &str) -> Result<Hexad, HexadError>;
+
+    /// Soft-deleted Hexads, most recently deleted first
+    async fn deleted(&self, limit: usize, offset: usize) -> Result<Vec<DeletedHexad>, HexadError>;
+
+    /// Hard-delete Hexads soft-deleted before `before`, returning their IDs
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<Vec<HexadId>, HexadError>;
+
+    /// Merge `loser` into `winner`: the winner takes the loser's modalities
+    /// as `policy` directs, inbound graph edges to the loser are rewritten
+    /// to the winner, both get a `merged` provenance event by `actor`, and
+    /// the loser is deleted, leaving a tombstone.
+    async fn merge(
+        &self,
+        winner: &HexadId,
+        loser: &HexadId,
+        policy: &MergePolicy,
+        actor: &str,
+    ) -> Result<HexadMerge, HexadError>;
+
+    /// Tombstone of an entity that was merged into another
+    async fn tombstone(&self, id: &HexadId) -> Result<Option<HexadTombstone>, HexadError>;
+
     /// List hexads with pagination
     async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Hexad>, HexadError>;
+
+    /// List hexads with only the modalities in `mask` loaded
+    async fn list_with(&self, limit: usize, offset: usize, mask: ModalityMask) -> Result<Vec<Hexad>, HexadError> {
+        Ok(self
+            .list(limit, offset)
+            .await?
+            .into_iter()
+            .map(|hexad| mask.apply(hexad))
+            .collect())
+    }
+
+    /// Create a new Hexad in `collection`
+    async fn create_in(&self, collection: &str, input: HexadInput) -> Result<Hexad, HexadError>;
+
+    /// List the hexads of one collection
+    async fn list_in(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+        mask: ModalityMask,
+    ) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Search one collection by vector similarity
+    async fn search_similar_in(
+        &self,
+        collection: &str,
+        embedding: &[f32],
+        k: usize,
+    ) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Search one collection by document text
+    async fn search_text_in(&self, collection: &str, query: &str, limit: usize) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Names of the collections holding live or soft-deleted entities,
+    /// sorted
+    async fn collections(&self) -> Result<Vec<String>, HexadError>;
+
+    /// Entity counts for one collection
+    async fn collection_stats(&self, collection: &str) -> Result<CollectionStats, HexadError>;
+
+    /// Hard-delete the Hexads of one collection soft-deleted before `before`
+    async fn purge_deleted_in(&self, collection: &str, before: DateTime<Utc>) -> Result<Vec<HexadId>, HexadError>;
+
+    /// Set or clear when a Hexad expires.  The expiry is kept across
+    /// updates.
+    async fn set_expiry(&self, id: &HexadId, expires_at: Option<DateTime<Utc>>) -> Result<HexadStatus, HexadError>;
+
+    /// Soft-delete the Hexads whose expiry is at or before `now`, recording
+    /// an `expired` provenance event by `actor`, and return their IDs
+    async fn expire_due(&self, now: DateTime<Utc>, actor: &str) -> Result<Vec<HexadId>, HexadError>;
+
+    /// Counts of scheduled, overdue and expired entities
+    async fn expiry_stats(&self, now: DateTime<Utc>) -> Result<ExpiryStats, HexadError>;
+
+    /// Exact number of live entities matching `filter`, scanning the
+    /// registry
+    async fn count(&self, filter: &CountFilter) -> Result<usize, HexadError>;
+
+    /// Incrementally maintained counts, without a scan
+    async fn estimated_counts(&self) -> Result<EstimatedCounts, HexadError>;
+
+    /// Capture the committed version of every live entity, for reads that
+    /// must all see the same store
+    async fn read_snapshot(&self) -> Result<ReadSnapshot, HexadError>;
+
+    /// A Hexad as it was when `snapshot` was taken
+    async fn get_at(&self, snapshot: &ReadSnapshot, id: &HexadId) -> Result<Option<Hexad>, HexadError>;
+
+    /// A Hexad as it was when `snapshot` was taken with the `pending`
+    /// updates applied in order, as `update` would apply them, without
+    /// writing anything.  An entity the snapshot lacks starts empty, as a
+    /// create; `None` if it lacks the entity and nothing is pending.
+    async fn preview_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        id: &HexadId,
+        pending: Vec<HexadInput>,
+    ) -> Result<Option<Hexad>, HexadError>;
+
+    /// List hexads, optionally of one collection, as they were when
+    /// `snapshot` was taken, in ID order
+    async fn list_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Search by document text among the entities in `snapshot`
+    async fn search_text_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<Hexad>, HexadError>;
+
+    /// Search by vector similarity among the entities in `snapshot`
+    async fn search_similar_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        embedding: &[f32],
+        k: usize,
+    ) -> Result<Vec<Hexad>, HexadError>;
 }
 
 /// Configuration for Hexad store
@@ -407,6 +875,7 @@ pub fn with_provenance(mut self, event_type: &str, actor: &str, description: &st
             actor: actor.to_string(),
             source: None,
             description: description.to_string(),
+            link: None,
         });
         self
     }
@@ -419,6 +888,26 @@ pub fn with_spatial(mut self, latitude: f64, longitude: f64) -> Self {
             altitude: None,
             geometry_type: None,
             srid: None,
+            geometry: None,
+            wkt: None,
+            wkb: None,
+            properties: HashMap::new(),
+        });
+        self
+    }
+
+    /// Add a full spatial geometry (WGS84)
+    pub fn with_geometry(mut self, geometry: Geometry) -> Self {
+        let centroid = geometry.centroid();
+        self.input.spatial = Some(HexadSpatialInput {
+            latitude: centroid.latitude,
+            longitude: centroid.longitude,
+            altitude: None,
+            geometry_type: Some(geometry.geometry_type().to_string()),
+            srid: None,
+            geometry: Some(geometry),
+            wkt: None,
+            wkb: None,
             properties: HashMap::new(),
         });
         self
@@ -453,6 +942,23 @@ fn test_hexad_id() {
         assert_eq!(id.to_iri("https://example.org"), "https://example.org/test-123");
     }
 
+    #[test]
+    fn test_hexad_id_collection() {
+        let id = HexadId::in_collection("people", "alice");
+        assert_eq!(id.as_str(), "people:alice");
+        assert_eq!(id.collection(), Some("people"));
+        assert_eq!(id.local_id(), "alice");
+
+        let plain = HexadId::new("alice");
+        assert_eq!(plain.collection(), None);
+        assert_eq!(plain.local_id(), "alice");
+
+        assert_eq!(HexadId::generate_in("people").collection(), Some("people"));
+        assert!(HexadId::validate_collection("people_2024").is_ok());
+        assert!(HexadId::validate_collection("").is_err());
+        assert!(HexadId::validate_collection("a:b").is_err());
+    }
+
     #[test]
     fn test_hexad_builder() {
         let input = HexadBuilder::new()
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Change hooks
+//!
+//! Drift scanning, webhooks, change data capture and the normalizer all
+//! need to see mutations as they happen.  A [`HexadListener`] registered
+//! with [`InMemoryHexadStore::add_listener`](crate::InMemoryHexadStore::add_listener)
+//! is called after every successful write, with the entity as it was
+//! before and as it is after.
+//!
+//! Listeners run inline on the writing task, after the write has committed,
+//! so they should hand slow work off (to a channel or a spawned task).  A
+//! listener that panics is logged and skipped; the write and the other
+//! listeners are unaffected.
+//!
+//! Soft deletes are reported as deletes and restores as creates.  Purging a
+//! soft-deleted entity is not reported again.
+//!
+//! Every allow and deny of the [entity policy](crate::security) is reported
+//! too, for audit, whether or not the action goes on to succeed.
+
+use crate::{Hexad, PolicyDecision};
+
+/// Observer of hexad store mutations.  Every method defaults to doing
+/// nothing, so a listener implements only the changes it cares about.
+pub trait HexadListener: Send + Sync {
+    /// Name logged when the listener panics
+    fn name(&self) -> &str {
+        "hexad-listener"
+    }
+
+    /// An entity was created or restored
+    fn on_created(&self, _new: &Hexad) {}
+
+    /// An entity was updated (including reverts and merges into it)
+    fn on_updated(&self, _old: &Hexad, _new: &Hexad) {}
+
+    /// An entity was deleted, soft-deleted, expired or merged away
+    fn on_deleted(&self, _old: &Hexad) {}
+
+    /// The entity policy allowed or refused an action
+    fn on_decision(&self, _decision: &PolicyDecision) {}
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Entity merge
+//!
+//! Imports accumulate duplicates.  [`HexadStore::merge`](crate::HexadStore::merge)
+//! folds a loser entity into a winner: the winner is updated with the
+//! modalities the [`MergePolicy`] takes from the loser, graph edges that
+//! pointed at the loser are rewritten to point at the winner, both entities
+//! get a `merged` provenance event, and the loser is deleted and replaced by
+//! a [`HexadTombstone`] naming the winner.  The loser's version history and
+//! provenance chain are kept.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Hexad, HexadDocumentInput, HexadGraphInput, HexadId, HexadInput, HexadSemanticInput};
+
+/// How one modality is merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeRule {
+    /// Keep the winner's value; take the loser's only when the winner has none
+    #[default]
+    PreferWinner,
+    /// Take the loser's value when it has one
+    PreferLoser,
+    /// Union graph relationships, semantic types and properties, and
+    /// document fields, the winner's values winning conflicts.  Vector,
+    /// tensor and spatial values cannot be combined and are merged as
+    /// [`MergeRule::PreferWinner`].
+    Combine,
+}
+
+/// Merge rule per modality.  Temporal and provenance are not merged: the
+/// merge is one new winner version, and each entity keeps its own chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergePolicy {
+    #[serde(default = "combine")]
+    pub graph: MergeRule,
+    #[serde(default)]
+    pub vector: MergeRule,
+    #[serde(default)]
+    pub tensor: MergeRule,
+    #[serde(default = "combine")]
+    pub semantic: MergeRule,
+    #[serde(default)]
+    pub document: MergeRule,
+    #[serde(default)]
+    pub spatial: MergeRule,
+}
+
+fn combine() -> MergeRule {
+    MergeRule::Combine
+}
+
+impl Default for MergePolicy {
+    /// Relationships and semantic annotations are combined; everything else
+    /// prefers the winner
+    fn default() -> Self {
+        Self {
+            graph: MergeRule::Combine,
+            vector: MergeRule::PreferWinner,
+            tensor: MergeRule::PreferWinner,
+            semantic: MergeRule::Combine,
+            document: MergeRule::PreferWinner,
+            spatial: MergeRule::PreferWinner,
+        }
+    }
+}
+
+impl MergePolicy {
+    /// The update that applies the loser's contribution to the winner, given
+    /// both entities' current inputs.  Only modalities that change are set;
+    /// relationships to the winner itself are dropped.
+    pub fn merged_input(&self, winner_id: &HexadId, winner: &HexadInput, loser: &HexadInput) -> HexadInput {
+        let mut update = HexadInput::default();
+
+        let loser_relationships = loser
+            .graph
+            .iter()
+            .flat_map(|g| &g.relationships)
+            .filter(|(_, target)| target != winner_id.as_str());
+        let winner_relationships = winner.graph.as_ref().map(|g| g.relationships.as_slice()).unwrap_or_default();
+        let added: Vec<(String, String)> = match self.graph {
+            MergeRule::PreferWinner if !winner_relationships.is_empty() => Vec::new(),
+            _ => loser_relationships
+                .filter(|r| !winner_relationships.contains(r))
+                .cloned()
+                .collect(),
+        };
+        if !added.is_empty() {
+            update.graph = Some(HexadGraphInput { relationships: added });
+        }
+
+        update.vector = pick(self.vector, &winner.vector, &loser.vector);
+        update.tensor = pick(self.tensor, &winner.tensor, &loser.tensor);
+        update.spatial = pick(self.spatial, &winner.spatial, &loser.spatial);
+
+        update.semantic = match (self.semantic, &winner.semantic, &loser.semantic) {
+            (MergeRule::Combine, Some(w), Some(l)) => {
+                let mut types = w.types.clone();
+                types.extend(l.types.iter().filter(|t| !w.types.contains(t)).cloned());
+                let mut properties = l.properties.clone();
+                properties.extend(w.properties.clone());
+                let combined = HexadSemanticInput { types, properties };
+                (combined.types != w.types || combined.properties != w.properties).then_some(combined)
+            }
+            (rule, w, l) => pick(rule, w, l),
+        };
+
+        update.document = match (self.document, &winner.document, &loser.document) {
+            (MergeRule::Combine, Some(w), Some(l)) => {
+                let mut fields = l.fields.clone();
+                fields.extend(w.fields.clone());
+                (fields != w.fields).then(|| HexadDocumentInput {
+                    fields,
+                    ..w.clone()
+                })
+            }
+            (rule, w, l) => pick(rule, w, l),
+        };
+
+        update
+    }
+}
+
+/// The loser's value when the rule takes it, `None` to leave the winner's
+fn pick<T: Clone>(rule: MergeRule, winner: &Option<T>, loser: &Option<T>) -> Option<T> {
+    match rule {
+        MergeRule::PreferLoser => loser.clone(),
+        MergeRule::PreferWinner | MergeRule::Combine if winner.is_none() => loser.clone(),
+        MergeRule::PreferWinner | MergeRule::Combine => None,
+    }
+}
+
+/// Record left in place of an entity merged into another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HexadTombstone {
+    /// The merged-away entity
+    pub id: HexadId,
+    /// Entity it was merged into
+    pub merged_into: HexadId,
+    /// Its version when it was merged
+    pub last_version: u64,
+    /// Who merged it
+    pub merged_by: String,
+    /// When it was merged
+    pub merged_at: DateTime<Utc>,
+}
+
+/// Outcome of a merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HexadMerge {
+    /// The winner after the merge
+    pub hexad: Hexad,
+    /// Tombstone that replaced the loser
+    pub tombstone: HexadTombstone,
+    /// Inbound graph edges moved from the loser to the winner
+    pub rewritten_edges: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexadBuilder;
+
+    #[test]
+    fn test_merged_input_follows_rules() {
+        let winner_id = HexadId::new("winner");
+        let winner = HexadBuilder::new()
+            .with_document("Ada Lovelace", "Winner body")
+            .with_types(vec!["Person"])
+            .with_relationships(vec![("knows", "babbage")])
+            .build();
+        let loser = HexadBuilder::new()
+            .with_document("A. Lovelace", "Loser body")
+            .with_embedding(vec![0.1, 0.2])
+            .with_types(vec!["Person", "Mathematician"])
+            .with_relationships(vec![("knows", "babbage"), ("sameAs", "winner"), ("wrote", "notes")])
+            .build();
+
+        let update = MergePolicy::default().merged_input(&winner_id, &winner, &loser);
+        assert_eq!(update.graph.unwrap().relationships, vec![("wrote".to_string(), "notes".to_string())]);
+        assert_eq!(update.semantic.unwrap().types, vec!["Person", "Mathematician"]);
+        assert_eq!(update.vector.unwrap().embedding, vec![0.1, 0.2]);
+        assert!(update.document.is_none());
+
+        let policy = MergePolicy {
+            document: MergeRule::PreferLoser,
+            graph: MergeRule::PreferWinner,
+            ..Default::default()
+        };
+        let update = policy.merged_input(&winner_id, &winner, &loser);
+        assert_eq!(update.document.unwrap().body, "Loser body");
+        assert!(update.graph.is_none());
+    }
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Consistent read views
+//!
+//! A list or export that pages through the store while it is being written
+//! can see one entity before an update and the next one after it.  A
+//! [`ReadSnapshot`] records the committed version of every live entity at
+//! one instant.  Reads through it rebuild each entity from its version
+//! history at that version, so every page sees the same store however long
+//! the reader holds on.
+//!
+//! Searches still run against the current indexes: an entity is matched on
+//! its current content, but is returned as it was when the snapshot was
+//! taken, and entities created since are left out.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::{HexadId, HexadStatus};
+
+/// The committed state of the store at one instant.  Cheap to hold: it
+/// keeps statuses only, and entity contents are read from the version
+/// history on demand.
+#[derive(Debug, Clone)]
+pub struct ReadSnapshot {
+    taken_at: DateTime<Utc>,
+    statuses: BTreeMap<String, HexadStatus>,
+}
+
+impl ReadSnapshot {
+    pub(crate) fn new(taken_at: DateTime<Utc>, statuses: impl IntoIterator<Item = HexadStatus>) -> Self {
+        Self {
+            taken_at,
+            statuses: statuses
+                .into_iter()
+                .map(|status| (status.id.as_str().to_string(), status))
+                .collect(),
+        }
+    }
+
+    /// When the snapshot was taken
+    pub fn taken_at(&self) -> DateTime<Utc> {
+        self.taken_at
+    }
+
+    /// Number of entities live when the snapshot was taken
+    pub fn len(&self) -> usize {
+        self.statuses.len()
+    }
+
+    /// Whether the store was empty
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+
+    /// Status of `id` when the snapshot was taken
+    pub fn status(&self, id: &HexadId) -> Option<&HexadStatus> {
+        self.statuses.get(id.as_str())
+    }
+
+    /// Statuses whose IDs start with `prefix`, in ID order
+    pub(crate) fn statuses_prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a HexadStatus> + 'a {
+        self.statuses
+            .range(prefix.to_string()..)
+            .take_while(move |(id, _)| id.starts_with(prefix))
+            .map(|(_, status)| status)
+    }
+}
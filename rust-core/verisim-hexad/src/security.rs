@@ -0,0 +1,392 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Entity-level security
+//!
+//! Deployments shared by several teams need each team to see only its own
+//! entities, wherever the entities are reached from: a get, a listing, a
+//! text or vector search, a graph traversal or a historical read.  An
+//! [`EntityPolicy`] set with
+//! [`InMemoryHexadStore::with_policy`](crate::InMemoryHexadStore::with_policy)
+//! is checked by the store itself on every read and write made on behalf of
+//! a [`Principal`].
+//!
+//! The principal is carried by the task, not passed to each call: a caller
+//! runs its store calls inside [`scope`], and every read and write they
+//! make is checked against that principal.  A principal marked
+//! [`unrestricted`](Principal::unrestricted) bypasses the policy, as the
+//! database's own work (startup, background sweeps, recovery) does by
+//! running in a [`system`] scope.  Calls outside any scope are admitted to
+//! nothing once a policy is set, so a task that lost its caller's scope
+//! fails closed rather than seeing every entity.
+//!
+//! Rules are read from the entity's document fields and admit an entity if
+//! any one of them matches.  An entity no rule admits is reported as not
+//! found, so its existence is not disclosed either; a write that would
+//! leave the principal unable to see the entity is refused.  A policy with
+//! no rules restricts nothing.
+//!
+//! [`PolicyRule::Expression`] rules go further, admitting by an
+//! [ABAC expression](crate::abac) over the principal's attributes, the
+//! entity and the request, including whether it reads or writes: an entity
+//! may be readable but not writable.  A write to such an entity is refused
+//! rather than reported as not found.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::abac::{Attributes, Expression, Value};
+
+tokio::task_local! {
+    /// Who the current task's store calls are made for
+    static PRINCIPAL: Principal;
+}
+
+/// Name of the [`system`] principal
+pub const SYSTEM: &str = "system";
+
+/// Who a store call is made for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    /// Name matched against owner fields and group members
+    pub id: String,
+    /// Whether the policy is bypassed, as it is for administrators
+    pub unrestricted: bool,
+    /// Attributes expressions read as `principal.<name>`, such as its role
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    /// What the request is, read by expressions as `request.<name>`, such
+    /// as its method and path
+    #[serde(default)]
+    pub request: HashMap<String, String>,
+}
+
+impl Principal {
+    /// A principal the policy applies to
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            unrestricted: false,
+            attributes: HashMap::new(),
+            request: HashMap::new(),
+        }
+    }
+
+    /// A principal that bypasses the policy
+    pub fn unrestricted(id: impl Into<String>) -> Self {
+        Self {
+            unrestricted: true,
+            ..Self::new(id)
+        }
+    }
+
+    /// The database itself, doing its own work
+    pub fn system() -> Self {
+        Self::unrestricted(SYSTEM)
+    }
+
+    /// Whom calls outside any scope are made for, admitted to nothing
+    pub fn anonymous() -> Self {
+        Self::new("")
+    }
+
+    /// With principal attribute `name` set to `value`
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// With request attribute `name` set to `value`
+    pub fn with_request(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.insert(name.into(), value.into());
+        self
+    }
+}
+
+/// Whether a store call reads an entity or changes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Read,
+    Write,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Read => write!(f, "read"),
+            Action::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// What a policy sees of an entity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EntityView<'a> {
+    pub id: &'a str,
+    /// Document fields; `None` for an entity with no document
+    pub fields: Option<&'a HashMap<String, String>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> EntityView<'a> {
+    /// An entity known only by its document fields
+    pub fn fields(id: &'a str, fields: Option<&'a HashMap<String, String>>) -> Self {
+        Self {
+            id,
+            fields,
+            ..Default::default()
+        }
+    }
+}
+
+/// Run `future` with its store calls made for `principal`
+pub async fn scope<F: Future>(principal: Principal, future: F) -> F::Output {
+    PRINCIPAL.scope(principal, future).await
+}
+
+/// Run `future` with its store calls made for the database itself, as
+/// background work is
+pub async fn system<F: Future>(future: F) -> F::Output {
+    scope(Principal::system(), future).await
+}
+
+/// The principal the current task's store calls are made for, if any
+pub fn current() -> Option<Principal> {
+    PRINCIPAL.try_with(Principal::clone).ok()
+}
+
+/// One way an entity can be admitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PolicyRule {
+    /// The field names the principal, as in `owner == principal`
+    Owner { field: String },
+    /// The field names a group the principal is a member of, as in
+    /// namespace membership
+    Member { field: String },
+    /// The field has `value`, admitting every principal
+    Equals { field: String, value: String },
+    /// The expression holds, as in `principal.position == "phd_student"
+    /// and request.action == "read"`
+    Expression { expr: Expression },
+}
+
+impl std::fmt::Display for PolicyRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyRule::Owner { field } => write!(f, "owner({})", field),
+            PolicyRule::Member { field } => write!(f, "member({})", field),
+            PolicyRule::Equals { field, value } => write!(f, "equals({} = {})", field, value),
+            PolicyRule::Expression { expr } => write!(f, "expression({})", expr),
+        }
+    }
+}
+
+/// Which entities each principal may read and write
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityPolicy {
+    /// Rules, any one of which admits an entity
+    pub rules: Vec<PolicyRule>,
+    /// Members of each group, by group name
+    pub groups: HashMap<String, Vec<String>>,
+    /// Attributes of each principal, by principal name, added to those the
+    /// principal brings
+    pub attributes: HashMap<String, HashMap<String, String>>,
+}
+
+impl EntityPolicy {
+    /// Whether the policy restricts anything
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `principal` may take `action` on `entity`
+    pub fn admits(&self, principal: &Principal, action: Action, entity: &EntityView<'_>) -> bool {
+        principal.unrestricted || self.is_empty() || self.matching(principal, action, entity).is_some()
+    }
+
+    /// The first rule that admits `principal` to take `action` on
+    /// `entity`, ignoring whether the principal is unrestricted.  The
+    /// [`anonymous`](Principal::anonymous) principal matches none.
+    pub fn matching(&self, principal: &Principal, action: Action, entity: &EntityView<'_>) -> Option<&PolicyRule> {
+        if principal.id.is_empty() {
+            return None;
+        }
+        let subject = Subject {
+            policy: self,
+            principal,
+            action,
+            entity,
+        };
+        self.rules.iter().find(|rule| match (rule, entity.fields) {
+            (PolicyRule::Owner { field }, Some(fields)) => fields.get(field).is_some_and(|v| *v == principal.id),
+            (PolicyRule::Member { field }, Some(fields)) => fields
+                .get(field)
+                .and_then(|group| self.groups.get(group))
+                .is_some_and(|members| members.contains(&principal.id)),
+            (PolicyRule::Equals { field, value }, Some(fields)) => fields.get(field) == Some(value),
+            (PolicyRule::Expression { expr }, _) => expr.holds(&subject),
+            (_, None) => false,
+        })
+    }
+}
+
+/// A policy's verdict on one action, as reported to
+/// [`HexadListener::on_decision`](crate::HexadListener::on_decision)
+#[derive(Debug, Clone)]
+pub struct PolicyDecision {
+    pub principal: String,
+    pub action: Action,
+    pub entity: String,
+    /// The rule that admitted the entity; `None` when it was refused
+    pub rule: Option<PolicyRule>,
+    /// The request the action was made for
+    pub request: HashMap<String, String>,
+}
+
+impl PolicyDecision {
+    /// Whether the action was allowed
+    pub fn allowed(&self) -> bool {
+        self.rule.is_some()
+    }
+}
+
+/// Everything an expression can read while a policy judges one action
+struct Subject<'a> {
+    policy: &'a EntityPolicy,
+    principal: &'a Principal,
+    action: Action,
+    entity: &'a EntityView<'a>,
+}
+
+impl Attributes for Subject<'_> {
+    fn lookup(&self, path: &[String]) -> Value {
+        let text = |value: Option<&String>| value.map_or(Value::Null, |v| Value::String(v.clone()));
+        let time = |value: Option<DateTime<Utc>>| value.map_or(Value::Null, |t| Value::String(t.to_rfc3339()));
+        let [scope, name] = path else {
+            return Value::Null;
+        };
+        match (scope.as_str(), name.as_str()) {
+            ("principal", "id") => Value::String(self.principal.id.clone()),
+            ("principal", "groups") => {
+                let mut groups = text(self.principal.attributes.get("groups")).elements();
+                groups.extend(
+                    self.policy
+                        .groups
+                        .iter()
+                        .filter(|(_, members)| members.contains(&self.principal.id))
+                        .map(|(group, _)| Value::String(group.clone())),
+                );
+                Value::List(groups)
+            }
+            ("principal", name) => text(
+                self.policy
+                    .attributes
+                    .get(&self.principal.id)
+                    .and_then(|attributes| attributes.get(name))
+                    .or_else(|| self.principal.attributes.get(name)),
+            ),
+            ("entity", "id") => Value::String(self.entity.id.to_string()),
+            ("entity", "created_at") => time(self.entity.created_at),
+            ("entity", "modified_at") => time(self.entity.modified_at),
+            ("entity", name) => text(self.entity.fields.and_then(|fields| fields.get(name))),
+            ("request", "action") => Value::String(self.action.to_string()),
+            ("request", "time") => Value::String(Utc::now().to_rfc3339()),
+            ("request", name) => text(self.principal.request.get(name)),
+            _ => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_rules_admit_owners_members_and_public_entities() {
+        let policy = EntityPolicy {
+            rules: vec![
+                PolicyRule::Owner { field: "owner".to_string() },
+                PolicyRule::Member { field: "namespace".to_string() },
+                PolicyRule::Equals { field: "visibility".to_string(), value: "public".to_string() },
+            ],
+            groups: HashMap::from([("red".to_string(), vec!["alice".to_string()])]),
+            ..Default::default()
+        };
+        let alice = Principal::new("alice");
+        let bob = Principal::new("bob");
+
+        let read = |principal: &Principal, fields: Option<&HashMap<String, String>>| {
+            policy.admits(principal, Action::Read, &EntityView::fields("e", fields))
+        };
+
+        let owned = fields(&[("owner", "bob")]);
+        assert!(read(&bob, Some(&owned)));
+        assert!(!read(&alice, Some(&owned)));
+
+        let red = fields(&[("namespace", "red")]);
+        assert!(read(&alice, Some(&red)));
+        assert!(!read(&bob, Some(&red)));
+
+        let public = fields(&[("visibility", "public")]);
+        assert!(read(&bob, Some(&public)));
+
+        assert!(!read(&alice, None));
+        assert!(read(&Principal::unrestricted("root"), Some(&owned)));
+        assert!(!read(&Principal::anonymous(), Some(&public)));
+        assert!(EntityPolicy::default().admits(&bob, Action::Write, &EntityView::default()));
+    }
+
+    #[test]
+    fn test_expressions_read_principal_entity_and_request_attributes() {
+        let rule = |expr: &str| PolicyRule::Expression {
+            expr: Expression::parse(expr).unwrap(),
+        };
+        let policy = EntityPolicy {
+            rules: vec![
+                rule(r#"principal.position == "phd_student" and request.action == "read"
+                        and "project:alpha" in entity.tags and entity.created_at >= "2024""#),
+                rule(r#""staff" in principal.groups"#),
+            ],
+            groups: HashMap::from([("staff".to_string(), vec!["prof".to_string()])]),
+            attributes: HashMap::from([(
+                "dana".to_string(),
+                HashMap::from([("position".to_string(), "phd_student".to_string())]),
+            )]),
+        };
+        let tagged = fields(&[("tags", "project:alpha")]);
+        let entity = |created: &str| EntityView {
+            id: "paper",
+            fields: Some(&tagged),
+            created_at: Some(created.parse().unwrap()),
+            modified_at: None,
+        };
+        let recent = entity("2024-05-01T00:00:00Z");
+        let dana = Principal::new("dana");
+
+        assert!(policy.admits(&dana, Action::Read, &recent));
+        assert!(!policy.admits(&dana, Action::Write, &recent));
+        assert!(!policy.admits(&dana, Action::Read, &entity("2023-05-01T00:00:00Z")));
+        // The attribute may come with the principal instead of the policy
+        let eve = Principal::new("eve").with_attribute("position", "phd_student");
+        assert!(policy.admits(&eve, Action::Read, &recent));
+        assert!(!policy.admits(&Principal::new("mallory"), Action::Read, &recent));
+        assert!(policy.admits(&Principal::new("prof"), Action::Write, &EntityView::default()));
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_the_current_principal() {
+        assert_eq!(current(), None);
+        let seen = scope(Principal::new("alice"), async { current() }).await;
+        assert_eq!(seen, Some(Principal::new("alice")));
+        assert_eq!(current(), None);
+        assert_eq!(system(async { current() }).await, Some(Principal::system()));
+    }
+}
@@ -6,19 +6,27 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::{
-    Coordinates, Document, DocumentStore, Embedding, GeometryType, GraphEdge, GraphNode,
+    CollectionStats, Coordinates, CountFilter, DeletedHexad, EstimatedCounts, ExpiryStats, Document, DocumentStore, Embedding, GeometryType, GraphEdge, GraphNode,
     GraphObject, GraphStore, Hexad, HexadConfig, HexadDocumentInput, HexadError, HexadGraphInput,
-    HexadId, HexadInput, HexadProvenanceInput, HexadSemanticInput, HexadSpatialInput,
-    HexadStatus, HexadStore, HexadTensorInput, HexadVectorInput, ModalityStatus, Provenance,
+    HexadId, HexadInput, HexadListener, HexadMerge, HexadProvenanceInput, HexadSemanticInput, HexadSpatialInput,
+    HexadStatus, HexadStore, HexadTensorInput, ReadSnapshot, COLLECTION_SEPARATOR, HexadTombstone, HexadVectorInput, MergePolicy, ModalityMask, ModalityStatus, Provenance,
     ProvenanceEventType, ProvenanceStore, SemanticAnnotation, SemanticStore, SemanticValue,
-    SpatialData, SpatialStore, Tensor, TensorStore, TemporalStore, VectorStore,
+    SpatialData, SpatialStore, Tensor, TensorStore, TemporalStore, TimeRange, Version, VectorStore,
 };
+use crate::backup::{self, ArchivedState, BackupKind, BackupManifest, RestoreReport, StoreHistory};
+use crate::checkpoint::{
+    committed_writes, overlay, CheckpointReport, RecoveredHexad, RecoveryReport, SnapshotDeletedHexad, SnapshotHexad, StoreSnapshot, WalLag,
+};
+use crate::security::{self, Action, EntityPolicy, EntityView, PolicyDecision, PolicyRule, Principal};
+use crate::consistency::{ConsistencyCheck, ConsistencyReport, ConsistencyViolation, EntityConsistency};
 use crate::transaction::{IsolationLevel, LockType, TransactionManager};
 use verisim_wal::{WalEntry, WalModality, WalOperation, WalWriter, SyncMode};
 
@@ -51,11 +59,34 @@ pub struct InMemoryHexadStore<G, V, D, T, S, R, P, L>
     config: HexadConfig,
     /// Hexad status registry
     hexads: Arc<RwLock<HashMap<String, HexadStatus>>>,
+    /// Soft-deleted entities, hidden from the registry until restored
+    deleted: Arc<RwLock<HashMap<String, DeletedHexad>>>,
+    /// Tombstones of entities merged into others
+    tombstones: Arc<RwLock<HashMap<String, HexadTombstone>>>,
     /// ACID transaction manager for cross-modality atomicity
     txn_manager: Arc<TransactionManager>,
     /// Optional write-ahead log for crash recovery.
     /// When present, all modality writes are logged before execution.
     wal: Option<Arc<tokio::sync::Mutex<WalWriter>>>,
+    /// Held shared by every logged write from its PENDING entry to its
+    /// COMMITTED marker, and exclusively by a checkpoint, so a snapshot
+    /// never misses a write whose log entries it truncates
+    checkpoint_gate: Arc<tokio::sync::RwLock<()>>,
+    /// When the latest checkpoint snapshot was taken
+    checkpoint_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// Checkpoint snapshots kept for point-in-time recovery
+    checkpoint_retention: usize,
+    /// Entities expired by the TTL sweeper since the store was opened
+    expired_total: Arc<AtomicU64>,
+    /// Entity counts adjusted on every registry and soft-delete change
+    counts: Arc<std::sync::Mutex<EstimatedCounts>>,
+    /// Observers called after each successful write
+    listeners: Arc<std::sync::RwLock<Vec<Arc<dyn HexadListener>>>>,
+    /// Which entities each principal may read and write
+    policy: EntityPolicy,
+    /// Collections only unrestricted principals may read and write,
+    /// whatever the policy
+    private_collections: Vec<String>,
     /// Graph store
     graph: Arc<G>,
     /// Vector store
@@ -103,8 +134,18 @@ pub fn new(
         Self {
             config,
             hexads: Arc::new(RwLock::new(HashMap::new())),
+            deleted: Arc::new(RwLock::new(HashMap::new())),
+            tombstones: Arc::new(RwLock::new(HashMap::new())),
             txn_manager: Arc::new(TransactionManager::new()),
             wal: None,
+            checkpoint_gate: Arc::new(tokio::sync::RwLock::new(())),
+            checkpoint_at: Arc::new(RwLock::new(None)),
+            checkpoint_retention: 1,
+            expired_total: Arc::new(AtomicU64::new(0)),
+            counts: Arc::new(std::sync::Mutex::new(EstimatedCounts::default())),
+            listeners: Arc::new(std::sync::RwLock::new(Vec::new())),
+            policy: EntityPolicy::default(),
+            private_collections: Vec::new(),
             graph,
             vector,
             document,
@@ -137,10 +178,365 @@ pub fn with_wal(
                 message: format!("Failed to open WAL: {e}"),
             }
         })?;
+        // A snapshot left by an earlier run dates the last checkpoint
+        let checkpoint_at = verisim_wal::snapshot::list_snapshots(writer.wal_dir())
+            .ok()
+            .and_then(|snapshots| snapshots.last().and_then(|s| std::fs::metadata(&s.path).ok()))
+            .and_then(|metadata| metadata.modified().ok())
+            .map(DateTime::<Utc>::from);
+        self.checkpoint_at = Arc::new(RwLock::new(checkpoint_at));
         self.wal = Some(Arc::new(tokio::sync::Mutex::new(writer)));
         Ok(self)
     }
 
+    /// Keep the newest `keep` checkpoint snapshots, and the WAL after the
+    /// oldest of them, so [`recover_to`](Self::recover_to) can reach back
+    /// that many checkpoints.  Defaults to one.
+    pub fn with_checkpoint_retention(mut self, keep: usize) -> Self {
+        self.checkpoint_retention = keep.max(1);
+        self
+    }
+
+    /// Check reads and writes made within a [`security::scope`] against
+    /// `policy`
+    pub fn with_policy(mut self, policy: EntityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Keep the entities of `collection` from every principal the policy
+    /// restricts, such as those holding the instance's own records
+    pub fn with_private_collection(mut self, collection: impl Into<String>) -> Self {
+        self.private_collections.push(collection.into());
+        self
+    }
+
+    /// The principal reads and writes are checked for, when the policy or
+    /// a private collection restricts it: outside any scope, the anonymous
+    /// principal
+    fn restricted(&self) -> Option<Principal> {
+        if self.policy.is_empty() && self.private_collections.is_empty() {
+            return None;
+        }
+        Some(security::current().unwrap_or_else(Principal::anonymous)).filter(|p| !p.unrestricted)
+    }
+
+    /// Whether entity `id` is in a private collection
+    fn is_private(&self, id: &str) -> bool {
+        HexadId::new(id)
+            .collection()
+            .is_some_and(|collection| self.private_collections.iter().any(|c| c == collection))
+    }
+
+    /// Whether the current principal may see entity `id`, judged by its
+    /// current document
+    pub async fn admits(&self, id: &HexadId) -> Result<bool, HexadError> {
+        self.admits_to(id, Action::Read).await
+    }
+
+    /// Whether the current principal may see `hexad`, judged by the
+    /// document it carries rather than the entity's current one, as for a
+    /// change event of an entity since changed or deleted
+    pub fn admits_hexad(&self, hexad: &Hexad) -> bool {
+        let Some(principal) = self.restricted() else {
+            return true;
+        };
+        let entity = EntityView {
+            id: hexad.id.as_str(),
+            fields: hexad.document.as_ref().map(|d| &d.fields),
+            created_at: Some(hexad.status.created_at),
+            modified_at: Some(hexad.status.modified_at),
+        };
+        self.judge(&principal, Action::Read, &entity)
+    }
+
+    /// Whether the current principal may take `action` on entity `id`,
+    /// judged by its current document
+    pub async fn admits_to(&self, id: &HexadId, action: Action) -> Result<bool, HexadError> {
+        let Some(principal) = self.restricted() else {
+            return Ok(true);
+        };
+        // Private collections and an empty policy judge by ID alone
+        if self.policy.is_empty() || self.is_private(id.as_str()) {
+            let entity = EntityView {
+                id: id.as_str(),
+                fields: None,
+                created_at: None,
+                modified_at: None,
+            };
+            return Ok(self.judge(&principal, action, &entity));
+        }
+        let document = self.document.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
+            modality: "document".to_string(),
+            message: e.to_string(),
+        })?;
+        let times = self.hexads.read().await.get(id.as_str()).map(|s| (s.created_at, s.modified_at));
+        let entity = EntityView {
+            id: id.as_str(),
+            fields: document.as_ref().map(|d| &d.fields),
+            created_at: times.map(|t| t.0),
+            modified_at: times.map(|t| t.1),
+        };
+        Ok(self.judge(&principal, action, &entity))
+    }
+
+    /// Whether `principal` may take `action` on `entity`, reporting the
+    /// decision to the listeners
+    fn judge(&self, principal: &Principal, action: Action, entity: &EntityView<'_>) -> bool {
+        let private = self.is_private(entity.id);
+        if self.policy.is_empty() && !private {
+            return true;
+        }
+        let rule = self.policy.matching(principal, action, entity).cloned().filter(|_| !private);
+        let allowed = rule.is_some();
+        if self.listening() {
+            let decision = PolicyDecision {
+                principal: principal.id.clone(),
+                action,
+                entity: entity.id.to_string(),
+                rule,
+                request: principal.request.clone(),
+            };
+            self.notify("decision", |l| l.on_decision(&decision));
+        }
+        allowed
+    }
+
+    /// Refuse a change to entity `id` the current principal may not see, as
+    /// not found, or may see but not write
+    async fn check_writable(&self, id: &HexadId) -> Result<(), HexadError> {
+        if !self.admits(id).await? {
+            return Err(HexadError::NotFound(id.to_string()));
+        }
+        if !self.admits_to(id, Action::Write).await? {
+            return Err(refused_write(id));
+        }
+        Ok(())
+    }
+
+    /// `items` less those whose entity the current principal may not see
+    async fn retain_admitted<I>(&self, items: Vec<I>, id: impl Fn(&I) -> &str) -> Result<Vec<I>, HexadError> {
+        if self.restricted().is_none() {
+            return Ok(items);
+        }
+        let mut admitted = Vec::with_capacity(items.len());
+        for item in items {
+            if self.admits(&HexadId::new(id(&item))).await? {
+                admitted.push(item);
+            }
+        }
+        Ok(admitted)
+    }
+
+    /// Refuse a write to an entity the current principal may not see, or
+    /// one that would leave the entity out of its sight
+    async fn check_write(&self, id: &HexadId, input: &HexadInput, existing: bool) -> Result<(), HexadError> {
+        let Some(principal) = self.restricted() else {
+            return Ok(());
+        };
+        if existing {
+            self.check_writable(id).await?;
+        }
+        let fields = match &input.document {
+            Some(document) => Some(&document.fields),
+            None if existing => return Ok(()),
+            None => None,
+        };
+        let now = Utc::now();
+        let created_at = self.hexads.read().await.get(id.as_str()).map_or(now, |s| s.created_at);
+        let entity = EntityView {
+            id: id.as_str(),
+            fields,
+            created_at: Some(created_at),
+            modified_at: Some(now),
+        };
+        if self.judge(&principal, Action::Write, &entity) {
+            Ok(())
+        } else {
+            Err(HexadError::ValidationError(format!(
+                "Entity {} would be out of {}'s reach under the entity policy",
+                id, principal.id
+            )))
+        }
+    }
+
+    /// Make the current principal the owner of an entity it creates, in
+    /// each owner field the input leaves unset
+    fn claim(&self, input: &mut HexadInput) {
+        let (Some(principal), Some(document)) = (self.restricted(), input.document.as_mut()) else {
+            return;
+        };
+        for rule in &self.policy.rules {
+            if let PolicyRule::Owner { field } = rule {
+                document.fields.entry(field.clone()).or_insert_with(|| principal.id.clone());
+            }
+        }
+    }
+
+    /// Whether the current principal may take `action` on the soft-deleted
+    /// entity with `status`, judged by its document when it was deleted
+    async fn admits_deleted(&self, status: &HexadStatus, action: Action) -> Result<bool, HexadError> {
+        let Some(principal) = self.restricted() else {
+            return Ok(true);
+        };
+        let input = self.current_input(&status.id, status.version).await?;
+        let entity = EntityView {
+            id: status.id.as_str(),
+            fields: input.document.as_ref().map(|d| &d.fields),
+            created_at: Some(status.created_at),
+            modified_at: Some(status.modified_at),
+        };
+        Ok(self.judge(&principal, action, &entity))
+    }
+
+    /// Adjust the estimated counts for `id` entering (`delta` 1) or leaving
+    /// (-1) the registry
+    fn count_live(&self, id: &str, delta: isize) {
+        let mut counts = self.counts.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        counts.hexads = counts.hexads.saturating_add_signed(delta);
+        if let Some((collection, _)) = id.split_once(COLLECTION_SEPARATOR) {
+            let count = counts.collections.entry(collection.to_string()).or_default();
+            *count = count.saturating_add_signed(delta);
+            if *count == 0 {
+                counts.collections.remove(collection);
+            }
+        }
+    }
+
+    /// Adjust the estimated count of soft-deleted entities
+    fn count_deleted(&self, delta: isize) {
+        let mut counts = self.counts.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        counts.deleted = counts.deleted.saturating_add_signed(delta);
+    }
+
+    /// Register a listener to be called after every successful write
+    pub fn add_listener(&self, listener: Arc<dyn HexadListener>) {
+        self.listeners
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(listener);
+    }
+
+    /// Whether any listener is registered, and so whether the state before
+    /// a write is worth loading
+    fn listening(&self) -> bool {
+        !self
+            .listeners
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Call every listener, isolating the store and the other listeners
+    /// from one that panics
+    fn notify(&self, event: &str, call: impl Fn(&dyn HexadListener)) {
+        let listeners = self
+            .listeners
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        for listener in listeners {
+            if std::panic::catch_unwind(AssertUnwindSafe(|| call(listener.as_ref()))).is_err() {
+                warn!(listener = listener.name(), event, "Hexad listener panicked");
+            }
+        }
+    }
+
+    /// Check every live entity's modality stores against its registry
+    /// status; see [`ConsistencyCheck`] for the invariants
+    pub async fn verify_consistency(&self) -> Result<ConsistencyReport, HexadError> {
+        let statuses: Vec<HexadStatus> = self.hexads.read().await.values().cloned().collect();
+        let mut results = Vec::with_capacity(statuses.len());
+        for status in &statuses {
+            results.push(self.check_entity(status).await);
+        }
+        let report = ConsistencyReport::new(Utc::now(), statuses.len(), results);
+        info!(
+            checked = report.entities_checked,
+            inconsistent = report.inconsistent.len(),
+            "Verified cross-modal consistency"
+        );
+        Ok(report)
+    }
+
+    /// Check one entity; `None` if it does not exist
+    pub async fn verify_entity(&self, id: &HexadId) -> Result<Option<EntityConsistency>, HexadError> {
+        let status = self.hexads.read().await.get(id.as_str()).cloned();
+        match status {
+            Some(status) => Ok(Some(self.check_entity(&status).await)),
+            None => Ok(None),
+        }
+    }
+
+    async fn check_entity(&self, status: &HexadStatus) -> EntityConsistency {
+        let id = &status.id;
+        let present = &status.modality_status;
+        let mut violations = Vec::new();
+        let mut violation = |check: ConsistencyCheck, message: String| {
+            violations.push(ConsistencyViolation { check, message });
+        };
+        let flag = |set: bool| if set { "set" } else { "not set" };
+
+        match self.vector.get(id.as_str()).await {
+            Ok(Some(_)) if !present.vector => {
+                violation(ConsistencyCheck::Embedding, "embedding stored but vector modality not set".to_string())
+            }
+            Ok(Some(embedding)) if embedding.vector.len() != self.config.vector_dimension => violation(
+                ConsistencyCheck::Embedding,
+                format!(
+                    "embedding has dimension {}, expected {}",
+                    embedding.vector.len(),
+                    self.config.vector_dimension
+                ),
+            ),
+            Ok(None) if present.vector => {
+                violation(ConsistencyCheck::Embedding, "vector modality set but no embedding stored".to_string())
+            }
+            Err(e) => violation(ConsistencyCheck::Embedding, format!("vector store error: {e}")),
+            _ => {}
+        }
+
+        match self.graph.outgoing(&GraphNode::new(id.to_iri(&self.config.base_iri))).await {
+            Ok(edges) if edges.is_empty() == present.graph => violation(
+                ConsistencyCheck::Graph,
+                format!("{} outgoing edges but graph modality {}", edges.len(), flag(present.graph)),
+            ),
+            Err(e) => violation(ConsistencyCheck::Graph, format!("graph store error: {e}")),
+            _ => {}
+        }
+
+        match self.document.get(id.as_str()).await {
+            Ok(document) if document.is_some() != present.document => violation(
+                ConsistencyCheck::Document,
+                format!(
+                    "document {} but document modality {}",
+                    if document.is_some() { "indexed" } else { "not indexed" },
+                    flag(present.document)
+                ),
+            ),
+            Err(e) => violation(ConsistencyCheck::Document, format!("document store error: {e}")),
+            _ => {}
+        }
+
+        match self.provenance.verify_chain(id.as_str()).await {
+            Ok(true) if !present.provenance => violation(
+                ConsistencyCheck::Provenance,
+                "provenance chain recorded but provenance modality not set".to_string(),
+            ),
+            Ok(false) if present.provenance => violation(
+                ConsistencyCheck::Provenance,
+                "provenance modality set but no chain recorded".to_string(),
+            ),
+            Err(e) => violation(ConsistencyCheck::Provenance, format!("provenance chain does not verify: {e}")),
+            _ => {}
+        }
+
+        EntityConsistency {
+            id: id.clone(),
+            violations,
+        }
+    }
+
     /// Access the transaction manager for diagnostics or external coordination.
     pub fn transaction_manager(&self) -> &Arc<TransactionManager> {
         &self.txn_manager
@@ -184,6 +580,115 @@ async fn wal_checkpoint(&self) -> Result<(), HexadError> {
         Ok(())
     }
 
+    /// Snapshot the store and truncate the WAL up to the snapshot.
+    ///
+    /// Writes wait while the snapshot is taken.  Every live and soft-deleted
+    /// entity is stored with its status and the input that recreates its
+    /// current state, rebuilt from its version history.  Fails if the WAL
+    /// is not enabled.
+    #[instrument(skip(self))]
+    pub async fn checkpoint(&self) -> Result<CheckpointReport, HexadError> {
+        let wal = self.wal.as_ref().ok_or_else(wal_disabled)?;
+        let started = std::time::Instant::now();
+        let _gate = self.checkpoint_gate.write().await;
+
+        // No write is between PENDING and COMMITTED, so every entry up to
+        // here is reflected in the registries
+        let sequence = wal.lock().await.next_sequence().saturating_sub(1);
+        let snapshot = self.snapshot_state(sequence).await?;
+
+        let data = serde_json::to_vec(&snapshot).map_err(|e| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: format!("Failed to serialize snapshot: {e}"),
+        })?;
+        let compaction = wal
+            .lock()
+            .await
+            .compact_retaining(sequence, &data, self.checkpoint_retention)
+            .map_err(|e| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: format!("WAL compaction failed: {e}"),
+        })?;
+        *self.checkpoint_at.write().await = Some(snapshot.taken_at);
+
+        let report = CheckpointReport {
+            sequence,
+            taken_at: snapshot.taken_at,
+            hexads: snapshot.hexads.len(),
+            deleted: snapshot.deleted.len(),
+            snapshot_bytes: compaction.snapshot.file_size,
+            segments_removed: compaction.segments_removed,
+            bytes_removed: compaction.bytes_removed,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        info!(
+            sequence,
+            hexads = report.hexads,
+            segments_removed = report.segments_removed,
+            duration_ms = report.duration_ms,
+            "WAL checkpoint taken"
+        );
+        Ok(report)
+    }
+
+    /// Hold off logged writes and checkpoints until the guard is dropped.
+    ///
+    /// Waits for writes in flight to commit, so while the guard is held the
+    /// WAL and the document index writer are idle and the persistence
+    /// directory can be copied as of one moment.
+    pub async fn pause_writes(&self) -> tokio::sync::OwnedRwLockWriteGuard<()> {
+        self.checkpoint_gate.clone().write_owned().await
+    }
+
+    /// Every live and soft-deleted entity with its status and the input
+    /// that recreates its current state, labelled as of WAL `sequence`.
+    /// Callers hold the checkpoint gate so no write is half done.
+    async fn snapshot_state(&self, sequence: u64) -> Result<StoreSnapshot, HexadError> {
+        let statuses: Vec<HexadStatus> = self.hexads.read().await.values().cloned().collect();
+        let deleted: Vec<DeletedHexad> = self.deleted.read().await.values().cloned().collect();
+        let mut snapshot = StoreSnapshot {
+            sequence,
+            taken_at: Utc::now(),
+            hexads: Vec::with_capacity(statuses.len()),
+            deleted: Vec::with_capacity(deleted.len()),
+            tombstones: self.tombstones.read().await.values().cloned().collect(),
+        };
+        for status in statuses {
+            let input = self.current_input(&status.id, status.version).await?;
+            snapshot.hexads.push(SnapshotHexad { status, input });
+        }
+        for deleted in deleted {
+            let input = self.current_input(&deleted.status.id, deleted.status.version).await?;
+            snapshot.deleted.push(SnapshotDeletedHexad { deleted, input });
+        }
+        Ok(snapshot)
+    }
+
+    /// How far the WAL has grown since the last checkpoint, or `None` when
+    /// the WAL is not enabled
+    pub async fn wal_lag(&self) -> Result<Option<WalLag>, HexadError> {
+        let Some(wal) = &self.wal else {
+            return Ok(None);
+        };
+        let stats = wal.lock().await.stats().map_err(|e| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: format!("Failed to read WAL stats: {e}"),
+        })?;
+        Ok(Some(WalLag {
+            next_sequence: stats.next_sequence,
+            checkpoint_sequence: stats.snapshot_sequence,
+            checkpoint_at: *self.checkpoint_at.read().await,
+            entries_since_checkpoint: stats.entries_since_snapshot,
+            segment_count: stats.segment_count,
+            segment_bytes: stats.segment_bytes,
+        }))
+    }
+
+    /// Access the graph store for direct queries.
+    pub fn graph_store(&self) -> &Arc<G> {
+        &self.graph
+    }
+
     /// Access the provenance store for direct queries.
     pub fn provenance_store(&self) -> &Arc<P> {
         &self.provenance
@@ -194,39 +699,33 @@ pub fn spatial_store(&self) -> &Arc<L> {
         &self.spatial
     }
 
-    /// Process graph input for a hexad
-    async fn process_graph(
-        &self,
-        id: &HexadId,
-        input: &HexadGraphInput,
-    ) -> Result<GraphNode, HexadError> {
+    /// Access the semantic store for direct queries.
+    pub fn semantic_store(&self) -> &Arc<S> {
+        &self.semantic
+    }
+
+    /// Graph node of a hexad and the edges of its relationships
+    fn build_graph(&self, id: &HexadId, input: &HexadGraphInput) -> (GraphNode, Vec<GraphEdge>) {
         let node = GraphNode::new(id.to_iri(&self.config.base_iri));
+        let edges = input
+            .relationships
+            .iter()
+            .map(|(predicate, target_id)| self.relationship_edge(&node, predicate, target_id))
+            .collect();
+        (node, edges)
+    }
 
-        for (predicate, target_id) in &input.relationships {
-            let edge = GraphEdge {
-                subject: node.clone(),
-                predicate: GraphNode::new(format!("{}/{}", self.config.base_iri, predicate)),
-                object: GraphObject::Node(GraphNode::new(format!(
-                    "{}/{}",
-                    self.config.base_iri, target_id
-                ))),
-            };
-            self.graph.insert(&edge).await.map_err(|e| HexadError::ModalityError {
-                modality: "graph".to_string(),
-                message: e.to_string(),
-            })?;
+    /// Graph edge of one `(predicate, target)` relationship
+    fn relationship_edge(&self, node: &GraphNode, predicate: &str, target_id: &str) -> GraphEdge {
+        GraphEdge {
+            subject: node.clone(),
+            predicate: GraphNode::new(format!("{}/{}", self.config.base_iri, predicate)),
+            object: GraphObject::Node(GraphNode::new(format!("{}/{}", self.config.base_iri, target_id))),
         }
-
-        debug!(id = %id, relationships = input.relationships.len(), "Graph modality populated");
-        Ok(node)
     }
 
-    /// Process vector input for a hexad
-    async fn process_vector(
-        &self,
-        id: &HexadId,
-        input: &HexadVectorInput,
-    ) -> Result<Embedding, HexadError> {
+    /// Embedding of a hexad, checked against the configured dimension
+    fn build_vector(&self, id: &HexadId, input: &HexadVectorInput) -> Result<Embedding, HexadError> {
         if input.embedding.len() != self.config.vector_dimension {
             return Err(HexadError::ValidationError(format!(
                 "Vector dimension mismatch: expected {}, got {}",
@@ -234,94 +733,11 @@ async fn process_vector(
                 input.embedding.len()
             )));
         }
-
         let embedding = Embedding::new(id.as_str(), input.embedding.clone());
-        self.vector.upsert(&embedding).await.map_err(|e| HexadError::ModalityError {
-            modality: "vector".to_string(),
-            message: e.to_string(),
-        })?;
-
-        debug!(id = %id, dimension = input.embedding.len(), "Vector modality populated");
-        Ok(embedding)
-    }
-
-    /// Process document input for a hexad
-    async fn process_document(
-        &self,
-        id: &HexadId,
-        input: &HexadDocumentInput,
-    ) -> Result<Document, HexadError> {
-        let mut doc = Document::new(id.as_str(), &input.title, &input.body);
-        for (key, value) in &input.fields {
-            doc = doc.with_field(key, value);
-        }
-
-        self.document.index(&doc).await.map_err(|e| HexadError::ModalityError {
-            modality: "document".to_string(),
-            message: e.to_string(),
-        })?;
-        self.document.commit().await.map_err(|e| HexadError::ModalityError {
-            modality: "document".to_string(),
-            message: e.to_string(),
-        })?;
-
-        debug!(id = %id, title = %input.title, "Document modality populated");
-        Ok(doc)
-    }
-
-    /// Process tensor input for a hexad
-    async fn process_tensor(
-        &self,
-        id: &HexadId,
-        input: &HexadTensorInput,
-    ) -> Result<Tensor, HexadError> {
-        let tensor = Tensor::new(id.as_str(), input.shape.clone(), input.data.clone()).map_err(
-            |e| HexadError::ModalityError {
-                modality: "tensor".to_string(),
-                message: e.to_string(),
-            },
-        )?;
-
-        self.tensor.put(&tensor).await.map_err(|e| HexadError::ModalityError {
-            modality: "tensor".to_string(),
-            message: e.to_string(),
-        })?;
-
-        debug!(id = %id, shape = ?input.shape, "Tensor modality populated");
-        Ok(tensor)
-    }
-
-    /// Process semantic input for a hexad
-    async fn process_semantic(
-        &self,
-        id: &HexadId,
-        input: &HexadSemanticInput,
-    ) -> Result<SemanticAnnotation, HexadError> {
-        let mut properties = HashMap::new();
-        for (key, value) in &input.properties {
-            properties.insert(
-                key.clone(),
-                SemanticValue::TypedLiteral {
-                    value: value.clone(),
-                    datatype: "https://www.w3.org/2001/XMLSchema#string".to_string(),
-                },
-            );
-        }
-
-        let annotation = SemanticAnnotation {
-            entity_id: id.as_str().to_string(),
-            types: input.types.clone(),
-            properties,
-            provenance: Provenance::default(),
-        };
-
-        self.semantic.annotate(&annotation).await.map_err(|e| HexadError::ModalityError {
-            modality: "semantic".to_string(),
-            message: e.to_string(),
-        })?;
-
-        debug!(id = %id, types = ?input.types, "Semantic modality populated");
-        Ok(annotation)
+        Ok(match &input.model {
+            Some(model) => embedding.with_metadata("model", model.clone()),
+            None => embedding,
+        })
     }
 
     /// Process provenance input for a hexad — records a lineage event
@@ -341,13 +757,22 @@ async fn process_provenance(
             other => ProvenanceEventType::Custom(other.to_string()),
         };
 
-        self.provenance
-            .record_event(id.as_str(), event_type, &input.actor, input.source.clone(), &input.description)
-            .await
-            .map_err(|e| HexadError::ModalityError {
-                modality: "provenance".to_string(),
-                message: e.to_string(),
-            })?;
+        let recorded = match &input.link {
+            Some(link) => {
+                self.provenance
+                    .record_handoff(id.as_str(), event_type, &input.actor, input.source.clone(), &input.description, link.clone())
+                    .await
+            }
+            None => {
+                self.provenance
+                    .record_event(id.as_str(), event_type, &input.actor, input.source.clone(), &input.description)
+                    .await
+            }
+        };
+        recorded.map_err(|e| HexadError::ModalityError {
+            modality: "provenance".to_string(),
+            message: e.to_string(),
+        })?;
 
         let chain = self
             .provenance
@@ -362,61 +787,6 @@ async fn process_provenance(
         Ok(chain.len() as u64)
     }
 
-    /// Process spatial input for a hexad — indexes geospatial data
-    async fn process_spatial(
-        &self,
-        id: &HexadId,
-        input: &HexadSpatialInput,
-    ) -> Result<SpatialData, HexadError> {
-        let coordinates = Coordinates::new(input.latitude, input.longitude, input.altitude)
-            .map_err(|e| HexadError::ValidationError(e.to_string()))?;
-
-        let geometry_type = match input.geometry_type.as_deref() {
-            Some("LineString") => GeometryType::LineString,
-            Some("Polygon") => GeometryType::Polygon,
-            Some("MultiPoint") => GeometryType::MultiPoint,
-            Some("MultiPolygon") => GeometryType::MultiPolygon,
-            _ => GeometryType::Point,
-        };
-
-        let srid = input.srid.unwrap_or(4326);
-
-        let mut data = SpatialData::with_geometry(coordinates, geometry_type, srid);
-        data.properties = input.properties.clone();
-
-        self.spatial
-            .index(id.as_str(), data.clone())
-            .await
-            .map_err(|e| HexadError::ModalityError {
-                modality: "spatial".to_string(),
-                message: e.to_string(),
-            })?;
-
-        debug!(id = %id, lat = input.latitude, lon = input.longitude, "Spatial modality populated");
-        Ok(data)
-    }
-
-    /// Roll back modality writes that succeeded before a failure.
-    ///
-    /// Called when a `create()` operation partially succeeded — some modalities
-    /// were written before an error occurred. This method deletes the data that
-    /// was already written to restore consistency.
-    async fn rollback_create(&self, id: &HexadId, written_modalities: &ModalityStatus) {
-        if written_modalities.vector {
-            self.vector.delete(id.as_str()).await.ok();
-        }
-        if written_modalities.document {
-            self.document.delete(id.as_str()).await.ok();
-        }
-        if written_modalities.tensor {
-            self.tensor.delete(id.as_str()).await.ok();
-        }
-        // Graph and semantic don't have simple delete-by-id,
-        // but for atomicity we must attempt cleanup. The in-memory
-        // stores will GC orphaned data on next compaction.
-        debug!(id = %id, "Rolled back partially written modalities");
-    }
-
     /// Create a snapshot for versioning
     fn create_snapshot(&self, id: &HexadId, input: &HexadInput, status: &ModalityStatus) -> HexadSnapshot {
         HexadSnapshot {
@@ -427,41 +797,227 @@ fn create_snapshot(&self, id: &HexadId, input: &HexadInput, status: &ModalitySta
         }
     }
 
-    /// Load a complete Hexad from all stores
-    async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
-        let hexads = self.hexads.read().await;
-        let status = match hexads.get(id.as_str()) {
-            Some(s) => s.clone(),
-            None => return Ok(None),
-        };
-        drop(hexads);
+    /// The entity's current state as an input, replayed from its version
+    /// snapshots
+    async fn current_input(&self, id: &HexadId, version: u64) -> Result<HexadInput, HexadError> {
+        let mut state = HexadInput::default();
+        for v in 1..=version {
+            let snapshot = self
+                .temporal
+                .at_version(id.as_str(), v)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?;
+            if let Some(snapshot) = snapshot {
+                apply_snapshot(&mut state, snapshot.data);
+            }
+        }
+        Ok(state)
+    }
 
-        // Load each modality
-        let graph_node = if status.modality_status.graph {
-            Some(GraphNode::new(id.to_iri(&self.config.base_iri)))
-        } else {
-            None
-        };
+    /// An entity as it was at `status`, rebuilt from its version history
+    /// rather than read from the modality stores, which may have moved on
+    async fn load_at(&self, snapshot: &ReadSnapshot, status: &HexadStatus) -> Result<Hexad, HexadError> {
+        self.rebuild(status, snapshot.taken_at()).await
+    }
 
-        let embedding = if status.modality_status.vector {
-            self.vector.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
-                modality: "vector".to_string(),
-                message: e.to_string(),
-            })?
+    /// An entity at `status.version`, rebuilt from its version history, with
+    /// the provenance recorded up to `as_of`
+    async fn rebuild(&self, status: &HexadStatus, as_of: DateTime<Utc>) -> Result<Hexad, HexadError> {
+        let input = self.current_input(&status.id, status.version).await?;
+        let prepared = self.prepare(&status.id, &input)?;
+        let provenance_chain_length = if status.modality_status.provenance {
+            self.provenance
+                .get_chain(status.id.as_str())
+                .await
+                .map(|c| c.records.iter().filter(|r| r.timestamp <= as_of).count() as u64)
+                .unwrap_or(0)
         } else {
-            None
+            0
         };
+        Ok(Hexad {
+            id: status.id.clone(),
+            status: status.clone(),
+            graph_node: prepared.graph.map(|(node, _)| node),
+            embedding: prepared.embedding,
+            tensor: prepared.tensor,
+            semantic: prepared.semantic,
+            document: prepared.document,
+            version_count: status.version,
+            provenance_chain_length,
+            spatial_data: prepared.spatial,
+        })
+    }
 
-        let document = if status.modality_status.document {
-            self.document.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
-                modality: "document".to_string(),
-                message: e.to_string(),
-            })?
-        } else {
+    /// An entity as it was at a recorded version, with the provenance
+    /// recorded up to `as_of`
+    async fn load_version(
+        &self,
+        id: &HexadId,
+        version: Version<HexadSnapshot>,
+        as_of: DateTime<Utc>,
+    ) -> Result<Hexad, HexadError> {
+        let created_at = match version.version {
+            1 => version.timestamp,
+            _ => self
+                .temporal
+                .at_version(id.as_str(), 1)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?
+                .map_or(version.timestamp, |first| first.timestamp),
+        };
+        let status = HexadStatus {
+            id: id.clone(),
+            created_at,
+            modified_at: version.timestamp,
+            version: version.version,
+            modality_status: version.data.modality_status,
+            expires_at: None,
+        };
+        self.rebuild(&status, as_of).await
+    }
+
+    /// Load the search hits that are in `snapshot`, as they were then,
+    /// widening the search until `limit` are found or the hits run out
+    async fn load_hits_at<F, Fut>(
+        &self,
+        snapshot: &ReadSnapshot,
+        limit: usize,
+        search: F,
+    ) -> Result<Vec<Hexad>, HexadError>
+    where
+        F: Fn(usize) -> Fut + Send + Sync,
+        Fut: std::future::Future<Output = Result<Vec<String>, HexadError>> + Send,
+    {
+        let mut fetch = limit.max(1);
+        loop {
+            let hits = search(fetch).await?;
+            let exhausted = hits.len() < fetch;
+            let mut seen = BTreeSet::new();
+            let statuses: Vec<&HexadStatus> = hits
+                .iter()
+                .filter(|id| seen.insert(id.as_str()))
+                .filter_map(|id| snapshot.status(&HexadId::new(id)))
+                .collect();
+            let statuses: Vec<&HexadStatus> =
+                self.retain_admitted(statuses, |s| s.id.as_str()).await?.into_iter().take(limit).collect();
+            if statuses.len() >= limit || exhausted {
+                let mut hexads = Vec::with_capacity(statuses.len());
+                for status in statuses {
+                    hexads.push(self.load_at(snapshot, status).await?);
+                }
+                return Ok(hexads);
+            }
+            fetch = fetch.saturating_mul(4);
+        }
+    }
+
+    /// Load a complete Hexad from all stores
+    async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
+        self.load_hexad_with(id, ModalityMask::ALL).await
+    }
+
+    /// Load a Hexad, reading only the modality stores in `mask`
+    async fn load_hexad_with(&self, id: &HexadId, mask: ModalityMask) -> Result<Option<Hexad>, HexadError> {
+        let hexads = self.hexads.read().await;
+        let status = match hexads.get(id.as_str()) {
+            Some(s) => s.clone(),
+            None => return Ok(None),
+        };
+        drop(hexads);
+        if !self.admits(id).await? {
+            return Ok(None);
+        }
+        self.assemble(status, mask).await.map(Some)
+    }
+
+    /// Load several Hexads in `ids` order, reading the registry once and
+    /// skipping the entity policy for unrestricted callers
+    async fn load_hexads(&self, ids: &[HexadId]) -> Result<Vec<Option<Hexad>>, HexadError> {
+        let statuses: Vec<Option<HexadStatus>> = {
+            let hexads = self.hexads.read().await;
+            ids.iter().map(|id| hexads.get(id.as_str()).cloned()).collect()
+        };
+        let present: Vec<&HexadStatus> = statuses.iter().flatten().collect();
+        let admitted: BTreeSet<&str> = self
+            .retain_admitted(present, |s| s.id.as_str())
+            .await?
+            .into_iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        let mut hexads = Vec::with_capacity(ids.len());
+        for status in &statuses {
+            match status.as_ref().filter(|s| admitted.contains(s.id.as_str())) {
+                Some(status) => hexads.push(Some(self.assemble(status.clone(), ModalityMask::ALL).await?)),
+                None => hexads.push(None),
+            }
+        }
+        Ok(hexads)
+    }
+
+    /// IDs of the entities `id` links to by `predicate`
+    pub async fn related_ids(&self, id: &HexadId, predicate: &str) -> Result<Vec<HexadId>, HexadError> {
+        // A hidden entity's edges would disclose its neighbours
+        if !self.admits(id).await? {
+            return Ok(Vec::new());
+        }
+        let node = GraphNode::new(id.to_iri(&self.config.base_iri));
+        let edges = self.graph.outgoing(&node).await.map_err(|e| HexadError::ModalityError {
+            modality: "graph".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let predicate_iri = format!("{}/{}", self.config.base_iri, predicate);
+        let prefix = format!("{}/", self.config.base_iri);
+        Ok(edges
+            .into_iter()
+            .filter(|edge| edge.predicate.iri == predicate_iri)
+            .filter_map(|edge| match edge.object {
+                GraphObject::Node(target) => {
+                    Some(HexadId::new(target.iri.strip_prefix(&prefix).unwrap_or(&target.iri)))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Build a Hexad from its registry `status`, reading only the modality
+    /// stores in `mask`
+    async fn assemble(&self, status: HexadStatus, mask: ModalityMask) -> Result<Hexad, HexadError> {
+        let id = status.id.clone();
+        let present = &status.modality_status;
+
+        // Load each modality
+        let graph_node = if mask.graph && present.graph {
+            Some(GraphNode::new(id.to_iri(&self.config.base_iri)))
+        } else {
+            None
+        };
+
+        let embedding = if mask.vector && present.vector {
+            self.vector.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
+                modality: "vector".to_string(),
+                message: e.to_string(),
+            })?
+        } else {
+            None
+        };
+
+        let document = if mask.document && present.document {
+            self.document.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
+                modality: "document".to_string(),
+                message: e.to_string(),
+            })?
+        } else {
             None
         };
 
-        let tensor = if status.modality_status.tensor {
+        let tensor = if mask.tensor && present.tensor {
             self.tensor.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
                 modality: "tensor".to_string(),
                 message: e.to_string(),
@@ -470,7 +1026,7 @@ async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
             None
         };
 
-        let semantic = if status.modality_status.semantic {
+        let semantic = if mask.semantic && present.semantic {
             self.semantic.get_annotations(id.as_str()).await.map_err(|e| HexadError::ModalityError {
                 modality: "semantic".to_string(),
                 message: e.to_string(),
@@ -479,15 +1035,18 @@ async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
             None
         };
 
-        let version_count = self
-            .temporal
-            .history(id.as_str(), 1000)
-            .await
-            .map(|h| h.len() as u64)
-            .unwrap_or(0);
+        let version_count = if mask.temporal {
+            self.temporal
+                .history(id.as_str(), 1000)
+                .await
+                .map(|h| h.len() as u64)
+                .unwrap_or(0)
+        } else {
+            0
+        };
 
         // Load provenance chain length
-        let provenance_chain_length = if status.modality_status.provenance {
+        let provenance_chain_length = if mask.provenance && present.provenance {
             self.provenance
                 .get_chain(id.as_str())
                 .await
@@ -498,7 +1057,7 @@ async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
         };
 
         // Load spatial data
-        let spatial_data = if status.modality_status.spatial {
+        let spatial_data = if mask.spatial && present.spatial {
             self.spatial.get(id.as_str()).await.map_err(|e| HexadError::ModalityError {
                 modality: "spatial".to_string(),
                 message: e.to_string(),
@@ -507,8 +1066,8 @@ async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
             None
         };
 
-        Ok(Some(Hexad {
-            id: id.clone(),
+        Ok(Hexad {
+            id,
             status,
             graph_node,
             embedding,
@@ -518,510 +1077,463 @@ async fn load_hexad(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
             version_count,
             provenance_chain_length,
             spatial_data,
-        }))
+        })
     }
 }
 
-#[async_trait]
-impl<G, V, D, T, S, R, P, L> HexadStore for InMemoryHexadStore<G, V, D, T, S, R, P, L>
+/// Modality data of one batch item, built before anything is written
+struct PreparedHexad {
+    graph: Option<(GraphNode, Vec<GraphEdge>)>,
+    embedding: Option<Embedding>,
+    document: Option<Document>,
+    tensor: Option<Tensor>,
+    semantic: Option<SemanticAnnotation>,
+    spatial: Option<SpatialData>,
+}
+
+/// Modality values an entity write replaces, captured before the write
+/// starts so a failed write can put them back.  `Some(previous)` marks a
+/// modality the write touches; a `None` previous value is removed.
+#[derive(Default)]
+struct WriteUndo {
+    /// Edges the write adds that did not exist before
+    added_edges: Vec<GraphEdge>,
+    vector: Option<Option<Embedding>>,
+    document: Option<Option<Document>>,
+    tensor: Option<Option<Tensor>>,
+    semantic: Option<Option<SemanticAnnotation>>,
+    spatial: Option<Option<SpatialData>>,
+}
+
+/// One entity of a batch create or update
+struct BatchItem {
+    /// Position in the request
+    index: usize,
+    id: HexadId,
+    input: HexadInput,
+    /// Registry status before the write; `None` for creates
+    existing: Option<HexadStatus>,
+    prepared: PreparedHexad,
+    /// What the write replaces, for rolling back a failed item
+    undo: WriteUndo,
+    /// Modality flags, starting from the existing ones and set as writes succeed
+    modality_status: ModalityStatus,
+    provenance_chain_length: u64,
+    /// Version the snapshot was written as
+    version: u64,
+    /// Set once the item has failed; later stages skip it
+    error: Option<HexadError>,
+}
+
+impl BatchItem {
+    fn pending(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn fail(&mut self, modality: &str, message: impl std::fmt::Display) {
+        self.error = Some(HexadError::ModalityError {
+            modality: modality.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Batch writes.
+///
+/// A batch runs in one transaction and writes each modality for all of its
+/// entities before moving on to the next: graph edges go to the graph store
+/// in one `insert_batch` (one redb transaction), documents to the document
+/// store in one `index_batch` (one Tantivy writer lock) followed by a single
+/// commit.  When a grouped write fails, the group is retried entity by
+/// entity so the failure is reported against the entities that caused it.
+/// An entity that fails is left out of the remaining stages and has its
+/// earlier writes rolled back, as with [`HexadStore::create`].
+impl<G, V, D, T, S, R, P, L> InMemoryHexadStore<G, V, D, T, S, R, P, L>
 where
-    G: GraphStore + 'static,
-    V: VectorStore + 'static,
-    D: DocumentStore + 'static,
-    T: TensorStore + 'static,
-    S: SemanticStore + 'static,
-    R: TemporalStore<Data = HexadSnapshot> + 'static,
-    P: ProvenanceStore + 'static,
-    L: SpatialStore + 'static,
+    G: GraphStore,
+    V: VectorStore,
+    D: DocumentStore,
+    T: TensorStore,
+    S: SemanticStore,
+    R: TemporalStore<Data = HexadSnapshot>,
+    P: ProvenanceStore,
+    L: SpatialStore,
 {
-    #[instrument(skip(self, input))]
-    async fn create(&self, input: HexadInput) -> Result<Hexad, HexadError> {
-        let id = HexadId::generate();
-        let now = Utc::now();
-        let entity_id_str = id.as_str().to_string();
-
-        // Write PENDING intent to WAL before any modality writes.
-        // On crash recovery, PENDING entries without a matching COMMITTED
-        // entry indicate incomplete operations that need rollback.
-        let input_payload = serde_json::to_vec(&input).unwrap_or_default();
-        self.wal_append(WalOperation::Insert, WalModality::All, &entity_id_str, &input_payload).await?;
+    /// Build every modality of `input` without writing anything
+    fn prepare(&self, id: &HexadId, input: &HexadInput) -> Result<PreparedHexad, HexadError> {
+        Ok(PreparedHexad {
+            graph: input.graph.as_ref().map(|g| self.build_graph(id, g)),
+            embedding: input.vector.as_ref().map(|v| self.build_vector(id, v)).transpose()?,
+            document: input.document.as_ref().map(|d| build_document(id, d)),
+            tensor: input.tensor.as_ref().map(|t| build_tensor(id, t)).transpose()?,
+            semantic: input.semantic.as_ref().map(|s| build_semantic(id, s)),
+            spatial: input.spatial.as_ref().map(build_spatial).transpose()?,
+        })
+    }
 
-        // Begin ACID transaction — acquire exclusive locks on all requested
-        // modalities before writing, ensuring atomicity across the octad.
-        let txn_id = self.txn_manager.begin(IsolationLevel::ReadCommitted).await;
+    /// A batch item for `input`, or the validation error that rules it out
+    fn batch_item(
+        &self,
+        index: usize,
+        id: HexadId,
+        input: HexadInput,
+        existing: Option<HexadStatus>,
+    ) -> Result<BatchItem, HexadError> {
+        let prepared = self.prepare(&id, &input)?;
+        Ok(BatchItem {
+            index,
+            modality_status: existing.as_ref().map(|e| e.modality_status.clone()).unwrap_or_default(),
+            id,
+            input,
+            existing,
+            prepared,
+            undo: WriteUndo::default(),
+            provenance_chain_length: 0,
+            version: 0,
+            error: None,
+        })
+    }
 
-        // Acquire locks for all modalities that will be written.
-        // This prevents concurrent writes to the same entity from interleaving.
-        let modality_names: Vec<&str> = [
-            input.graph.as_ref().map(|_| "graph"),
-            input.vector.as_ref().map(|_| "vector"),
-            input.document.as_ref().map(|_| "document"),
-            input.tensor.as_ref().map(|_| "tensor"),
-            input.semantic.as_ref().map(|_| "semantic"),
-            input.provenance.as_ref().map(|_| "provenance"),
-            input.spatial.as_ref().map(|_| "spatial"),
-            Some("temporal"), // Always written (version snapshot)
-        ]
-        .into_iter()
-        .flatten()
-        .collect();
+    /// Write a batch of creates or updates.  `results` holds the outcome of
+    /// items already ruled out; the others are filled in.
+    async fn write_batch(
+        &self,
+        mut items: Vec<BatchItem>,
+        mut results: Vec<Option<Result<Hexad, HexadError>>>,
+    ) -> Vec<Result<Hexad, HexadError>> {
+        let _gate = self.checkpoint_gate.read().await;
+        let now = Utc::now();
 
-        for modality in &modality_names {
-            if let Err(e) = self
-                .txn_manager
-                .acquire_lock(txn_id, &entity_id_str, modality, LockType::Exclusive)
-                .await
-            {
-                self.txn_manager.rollback(txn_id).await.ok();
-                return Err(HexadError::ConsistencyViolation(format!(
-                    "Failed to acquire lock on {modality}: {e}"
-                )));
+        // PENDING intent for every entity before any modality write
+        for item in &mut items {
+            if let Err(e) = self.check_write(&item.id, &item.input, item.existing.is_some()).await {
+                item.error = Some(e);
+                continue;
+            }
+            let operation = if item.existing.is_some() { WalOperation::Update } else { WalOperation::Insert };
+            let payload = serde_json::to_vec(&item.input).unwrap_or_default();
+            if let Err(e) = self.wal_append(operation, WalModality::All, item.id.as_str(), &payload).await {
+                item.error = Some(e);
             }
         }
 
-        // Track which modalities have been successfully written so we can
-        // roll back on partial failure.
-        let mut modality_status = ModalityStatus::default();
-
-        // Process each modality — on failure, rollback everything
-        let mut graph_node = None;
-        if let Some(ref graph_input) = input.graph {
-            match self.process_graph(&id, graph_input).await {
-                Ok(node) => {
-                    graph_node = Some(node);
-                    modality_status.graph = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "graph", None, 0)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+        // One transaction for the batch, locking each entity's modalities
+        let txn_id = self.txn_manager.begin(IsolationLevel::ReadCommitted).await;
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            for modality in input_modalities(&item.input) {
+                if let Err(e) = self
+                    .txn_manager
+                    .acquire_lock(txn_id, item.id.as_str(), modality, LockType::Exclusive)
+                    .await
+                {
+                    item.error = Some(HexadError::ConsistencyViolation(format!(
+                        "Failed to acquire lock on {modality}: {e}"
+                    )));
+                    break;
                 }
             }
         }
 
-        let mut embedding = None;
-        if let Some(ref vector_input) = input.vector {
-            match self.process_vector(&id, vector_input).await {
-                Ok(emb) => {
-                    embedding = Some(emb);
-                    modality_status.vector = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "vector", None, 0)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            match self.capture_undo(&item.id, &item.prepared, item.existing.is_some()).await {
+                Ok(undo) => item.undo = undo,
+                Err(e) => item.error = Some(e),
             }
         }
 
-        let mut document = None;
-        if let Some(ref doc_input) = input.document {
-            match self.process_document(&id, doc_input).await {
-                Ok(doc) => {
-                    document = Some(doc);
-                    modality_status.document = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "document", None, 0)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+        // Graph: every entity's edges in one write
+        let edges: Vec<GraphEdge> = items
+            .iter()
+            .filter(|i| i.pending())
+            .filter_map(|i| i.prepared.graph.as_ref())
+            .flat_map(|(_, edges)| edges.iter().cloned())
+            .collect();
+        let grouped = edges.is_empty() || self.graph.insert_batch(&edges).await.is_ok();
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            let Some((_, edges)) = &item.prepared.graph else {
+                continue;
+            };
+            if !grouped {
+                if let Err(e) = self.graph.insert_batch(edges).await {
+                    item.fail("graph", e);
+                    continue;
                 }
             }
+            item.modality_status.graph = true;
         }
 
-        let mut tensor = None;
-        if let Some(ref tensor_input) = input.tensor {
-            match self.process_tensor(&id, tensor_input).await {
-                Ok(t) => {
-                    tensor = Some(t);
-                    modality_status.tensor = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "tensor", None, 0)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
+        // Vector
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            let Some(embedding) = &item.prepared.embedding else {
+                continue;
+            };
+            match self.vector.upsert(embedding).await {
+                Ok(()) => item.modality_status.vector = true,
+                Err(e) => item.fail("vector", e),
             }
         }
 
-        let mut semantic = None;
-        if let Some(ref sem_input) = input.semantic {
-            match self.process_semantic(&id, sem_input).await {
-                Ok(ann) => {
-                    semantic = Some(ann);
-                    modality_status.semantic = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "semantic", None, 0)
-                        .await
-                        .ok();
+        // Document: one writer lock for the batch, then a single commit
+        let docs: Vec<Document> = items
+            .iter()
+            .filter(|i| i.pending())
+            .filter_map(|i| i.prepared.document.clone())
+            .collect();
+        let grouped = docs.is_empty() || self.document.index_batch(&docs).await.is_ok();
+        for item in items.iter_mut().filter(|i| i.pending() && i.prepared.document.is_some()) {
+            if !grouped {
+                if let Err(e) = self.document.index(item.prepared.document.as_ref().unwrap()).await {
+                    item.fail("document", e);
+                    continue;
                 }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+            }
+            item.modality_status.document = true;
+        }
+        if !docs.is_empty() {
+            if let Err(e) = self.document.commit().await {
+                for item in items.iter_mut().filter(|i| i.pending() && i.prepared.document.is_some()) {
+                    item.fail("document", &e);
                 }
             }
         }
 
-        // Process provenance
-        let mut provenance_chain_length = 0;
-        if let Some(ref prov_input) = input.provenance {
-            match self.process_provenance(&id, prov_input).await {
-                Ok(chain_len) => {
-                    provenance_chain_length = chain_len;
-                    modality_status.provenance = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "provenance", None, 0)
-                        .await
-                        .ok();
+        // Tensor, semantic, spatial and provenance, entity by entity
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            if let Some(tensor) = &item.prepared.tensor {
+                match self.tensor.put(tensor).await {
+                    Ok(()) => item.modality_status.tensor = true,
+                    Err(e) => {
+                        item.fail("tensor", e);
+                        continue;
+                    }
                 }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+            }
+            if let Some(annotation) = &item.prepared.semantic {
+                match self.semantic.annotate(annotation).await {
+                    Ok(()) => item.modality_status.semantic = true,
+                    Err(e) => {
+                        item.fail("semantic", e);
+                        continue;
+                    }
                 }
             }
-        }
-
-        // Process spatial
-        let mut spatial_data = None;
-        if let Some(ref spatial_input) = input.spatial {
-            match self.process_spatial(&id, spatial_input).await {
-                Ok(data) => {
-                    spatial_data = Some(data);
-                    modality_status.spatial = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "spatial", None, 0)
-                        .await
-                        .ok();
+            if let Some(data) = &item.prepared.spatial {
+                match self.spatial.index(item.id.as_str(), data.clone()).await {
+                    Ok(()) => item.modality_status.spatial = true,
+                    Err(e) => {
+                        item.fail("spatial", e);
+                        continue;
+                    }
                 }
-                Err(e) => {
-                    self.rollback_create(&id, &modality_status).await;
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+            }
+            // Append-only, so after every reversible write
+            if let Some(provenance) = &item.input.provenance {
+                match self.process_provenance(&item.id, provenance).await {
+                    Ok(chain_len) => {
+                        item.provenance_chain_length = chain_len;
+                        item.modality_status.provenance = true;
+                    }
+                    Err(e) => item.error = Some(e),
                 }
             }
         }
 
-        // Create version snapshot
-        let snapshot = self.create_snapshot(&id, &input, &modality_status);
-        let version = match self
-            .temporal
-            .append(id.as_str(), snapshot, "system", Some("Initial creation"))
-            .await
-        {
-            Ok(v) => v,
-            Err(e) => {
-                self.rollback_create(&id, &modality_status).await;
-                self.txn_manager.rollback(txn_id).await.ok();
-                return Err(HexadError::ModalityError {
-                    modality: "temporal".to_string(),
-                    message: e.to_string(),
-                });
+        // Version snapshots
+        for item in items.iter_mut().filter(|i| i.pending()) {
+            let snapshot = self.create_snapshot(&item.id, &item.input, &item.modality_status);
+            let message = if item.existing.is_some() { "Update" } else { "Initial creation" };
+            match self.temporal.append(item.id.as_str(), snapshot, "system", Some(message)).await {
+                Ok(version) => {
+                    item.modality_status.temporal = true;
+                    item.version = version;
+                }
+                Err(e) => item.fail("temporal", e),
             }
-        };
-        modality_status.temporal = true;
+        }
 
-        // All modality writes succeeded — commit the transaction
+        for item in items.iter().filter(|i| i.pending()) {
+            let current_version = item.existing.as_ref().map_or(0, |e| e.version);
+            for modality in input_modalities(&item.input) {
+                self.txn_manager
+                    .record_undo(txn_id, item.id.as_str(), modality, None, current_version)
+                    .await
+                    .ok();
+            }
+        }
         if let Err(e) = self.txn_manager.commit(txn_id).await {
-            self.rollback_create(&id, &modality_status).await;
-            return Err(HexadError::ConsistencyViolation(format!(
-                "Transaction commit failed: {e}"
-            )));
+            for item in items.iter_mut().filter(|i| i.pending()) {
+                item.error = Some(HexadError::ConsistencyViolation(format!("Transaction commit failed: {e}")));
+            }
         }
 
-        // Create status
-        let status = HexadStatus {
-            id: id.clone(),
-            created_at: now,
-            modified_at: now,
-            version,
-            modality_status: modality_status.clone(),
-        };
+        // Undo the writes of entities that failed part-way
+        for item in items.iter().filter(|i| !i.pending()) {
+            self.undo_write(&item.id, &item.undo).await;
+        }
 
-        // Store in registry
-        self.hexads.write().await.insert(id.as_str().to_string(), status.clone());
+        let mut registry = self.hexads.write().await;
+        for item in items {
+            let result = match item.error {
+                Some(e) => Err(e),
+                None => {
+                    let status = HexadStatus {
+                        id: item.id.clone(),
+                        created_at: item.existing.as_ref().map_or(now, |e| e.created_at),
+                        modified_at: now,
+                        version: item.version,
+                        modality_status: item.modality_status,
+                        expires_at: item.existing.as_ref().and_then(|e| e.expires_at),
+                    };
+                    if registry.insert(item.id.as_str().to_string(), status.clone()).is_none() {
+                        self.count_live(item.id.as_str(), 1);
+                    }
+                    Ok(Hexad {
+                        id: item.id,
+                        status,
+                        graph_node: item.prepared.graph.map(|(node, _)| node),
+                        embedding: item.prepared.embedding,
+                        tensor: item.prepared.tensor,
+                        semantic: item.prepared.semantic,
+                        document: item.prepared.document,
+                        version_count: item.version,
+                        provenance_chain_length: item.provenance_chain_length,
+                        spatial_data: item.prepared.spatial,
+                    })
+                }
+            };
+            results[item.index] = Some(result);
+        }
+        drop(registry);
 
-        // Write COMMITTED marker to WAL and checkpoint for crash recovery.
-        self.wal_append(WalOperation::Checkpoint, WalModality::All, &entity_id_str, b"COMMITTED").await.ok();
-        self.wal_checkpoint().await.ok();
+        self.commit_batch_wal(results.iter().flatten().filter_map(|r| r.as_ref().ok()).map(|h| h.id.as_str()))
+            .await;
 
-        info!(id = %id, modalities = ?modality_status, "Created hexad (transaction committed)");
+        results.into_iter().map(|r| r.expect("every batch item has a result")).collect()
+    }
 
-        Ok(Hexad {
-            id,
-            status,
-            graph_node,
-            embedding,
-            tensor,
-            semantic,
-            document,
-            version_count: 1,
-            provenance_chain_length,
-            spatial_data,
-        })
+    /// COMMITTED markers for the entities a batch wrote, then one checkpoint
+    async fn commit_batch_wal<'a>(&self, ids: impl Iterator<Item = &'a str>) {
+        let mut wrote = false;
+        for id in ids {
+            self.wal_append(WalOperation::Checkpoint, WalModality::All, id, b"COMMITTED").await.ok();
+            wrote = true;
+        }
+        if wrote {
+            self.wal_checkpoint().await.ok();
+        }
     }
+}
 
-    #[instrument(skip(self, input))]
-    async fn update(&self, id: &HexadId, input: HexadInput) -> Result<Hexad, HexadError> {
-        // Check if exists
-        let existing = {
-            let hexads = self.hexads.read().await;
-            hexads.get(id.as_str()).cloned()
-        };
+/// Single-entity writes.
+///
+/// `create` and `update` build and validate every modality before writing
+/// anything, then read the values the write will replace.  If a modality
+/// store fails, the modalities already written get those values back (or
+/// are removed, for a create) and the caller gets the error with the entity
+/// as it was.  Provenance and the version snapshot are append-only, so they
+/// are written last, once every reversible write has succeeded.
+impl<G, V, D, T, S, R, P, L> InMemoryHexadStore<G, V, D, T, S, R, P, L>
+where
+    G: GraphStore,
+    V: VectorStore,
+    D: DocumentStore,
+    T: TensorStore,
+    S: SemanticStore,
+    R: TemporalStore<Data = HexadSnapshot>,
+    P: ProvenanceStore,
+    L: SpatialStore,
+{
+    /// Soft-delete one entity, recording `event` as the reason
+    async fn soft_delete_with(&self, id: &HexadId, event: HexadProvenanceInput) -> Result<DeletedHexad, HexadError> {
+        let old = self.states_before(&[id]).await;
+        let status = self
+            .hexads
+            .read()
+            .await
+            .get(id.as_str())
+            .cloned()
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        self.check_writable(id).await?;
 
-        let existing = existing.ok_or_else(|| HexadError::NotFound(id.to_string()))?;
-        let now = Utc::now();
-        let entity_id_str = id.as_str().to_string();
+        self.process_provenance(id, &event).await?;
+        // Out of the search indexes; restore rebuilds them from the history
+        if status.modality_status.vector {
+            self.vector.delete(id.as_str()).await.ok();
+        }
+        if status.modality_status.document {
+            self.document.delete(id.as_str()).await.ok();
+        }
+        if status.modality_status.spatial {
+            self.spatial.delete(id.as_str()).await.ok();
+        }
 
-        // Write PENDING intent to WAL before modality writes
-        let input_payload = serde_json::to_vec(&input).unwrap_or_default();
-        self.wal_append(WalOperation::Update, WalModality::All, &entity_id_str, &input_payload).await?;
+        let mut hexads = self.hexads.write().await;
+        let mut status = hexads
+            .remove(id.as_str())
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        status.modality_status.provenance = true;
+        let deleted = DeletedHexad {
+            status,
+            deleted_by: event.actor.clone(),
+            deleted_at: Utc::now(),
+        };
+        self.deleted.write().await.insert(id.to_string(), deleted.clone());
+        drop(hexads);
+        self.count_live(id.as_str(), -1);
+        self.count_deleted(1);
 
-        // Begin ACID transaction for atomic update across all modalities
-        let txn_id = self.txn_manager.begin(IsolationLevel::ReadCommitted).await;
-
-        // Acquire exclusive locks on all modalities that will be written
-        let modality_names: Vec<&str> = [
-            input.graph.as_ref().map(|_| "graph"),
-            input.vector.as_ref().map(|_| "vector"),
-            input.document.as_ref().map(|_| "document"),
-            input.tensor.as_ref().map(|_| "tensor"),
-            input.semantic.as_ref().map(|_| "semantic"),
-            input.provenance.as_ref().map(|_| "provenance"),
-            input.spatial.as_ref().map(|_| "spatial"),
-            Some("temporal"),
-        ]
-        .into_iter()
-        .flatten()
-        .collect();
-
-        for modality in &modality_names {
-            if let Err(e) = self
-                .txn_manager
-                .acquire_lock(txn_id, &entity_id_str, modality, LockType::Exclusive)
-                .await
-            {
-                self.txn_manager.rollback(txn_id).await.ok();
-                return Err(HexadError::ConsistencyViolation(format!(
-                    "Failed to acquire lock on {modality}: {e}"
-                )));
-            }
-        }
-
-        let mut modality_status = existing.modality_status.clone();
-
-        // Macro-like closure for recording undo + handling error with rollback.
-        // For updates, record the MVCC version so commit can detect conflicts.
-        let current_version = existing.version;
-
-        // Update each modality with transactional protection
-        let mut graph_node = None;
-        if let Some(ref graph_input) = input.graph {
-            match self.process_graph(id, graph_input).await {
-                Ok(node) => {
-                    graph_node = Some(node);
-                    modality_status.graph = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "graph", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
-        }
-
-        let mut embedding = None;
-        if let Some(ref vector_input) = input.vector {
-            match self.process_vector(id, vector_input).await {
-                Ok(emb) => {
-                    embedding = Some(emb);
-                    modality_status.vector = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "vector", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
-        }
-
-        let mut document = None;
-        if let Some(ref doc_input) = input.document {
-            match self.process_document(id, doc_input).await {
-                Ok(doc) => {
-                    document = Some(doc);
-                    modality_status.document = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "document", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
-        }
-
-        let mut tensor = None;
-        if let Some(ref tensor_input) = input.tensor {
-            match self.process_tensor(id, tensor_input).await {
-                Ok(t) => {
-                    tensor = Some(t);
-                    modality_status.tensor = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "tensor", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
-        }
-
-        let mut semantic = None;
-        if let Some(ref sem_input) = input.semantic {
-            match self.process_semantic(id, sem_input).await {
-                Ok(ann) => {
-                    semantic = Some(ann);
-                    modality_status.semantic = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "semantic", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
+        info!(id = %id, actor = %event.actor, reason = %event.event_type, "Soft-deleted hexad");
+        if let Some(old) = old.get(id.as_str()) {
+            self.notify("deleted", |l| l.on_deleted(old));
         }
+        Ok(deleted)
+    }
 
-        // Update provenance
-        let mut provenance_chain_length = 0;
-        if let Some(ref prov_input) = input.provenance {
-            match self.process_provenance(id, prov_input).await {
-                Ok(chain_len) => {
-                    provenance_chain_length = chain_len;
-                    modality_status.provenance = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "provenance", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
+    /// Current states of `ids` for listeners; empty when none is
+    /// registered
+    async fn states_before(&self, ids: &[&HexadId]) -> HashMap<String, Hexad> {
+        let mut states = HashMap::new();
+        if self.listening() {
+            for id in ids {
+                if let Ok(Some(hexad)) = self.load_hexad(id).await {
+                    states.insert(id.to_string(), hexad);
                 }
             }
         }
+        states
+    }
 
-        // Update spatial
-        let mut spatial_data = None;
-        if let Some(ref spatial_input) = input.spatial {
-            match self.process_spatial(id, spatial_input).await {
-                Ok(data) => {
-                    spatial_data = Some(data);
-                    modality_status.spatial = true;
-                    self.txn_manager
-                        .record_undo(txn_id, &entity_id_str, "spatial", None, current_version)
-                        .await
-                        .ok();
-                }
-                Err(e) => {
-                    self.txn_manager.rollback(txn_id).await.ok();
-                    return Err(e);
-                }
-            }
+    /// Report a write to the listeners: a create when there was no state
+    /// before it, an update otherwise
+    fn notify_written(&self, old: Option<&Hexad>, new: &Hexad) {
+        match old {
+            Some(old) => self.notify("updated", |l| l.on_updated(old, new)),
+            None => self.notify("created", |l| l.on_created(new)),
         }
+    }
 
-        // Create new version snapshot
-        let snapshot = self.create_snapshot(id, &input, &modality_status);
-        let version = match self
-            .temporal
-            .append(id.as_str(), snapshot, "system", Some("Update"))
-            .await
-        {
-            Ok(v) => v,
-            Err(e) => {
-                self.txn_manager.rollback(txn_id).await.ok();
-                return Err(HexadError::ModalityError {
-                    modality: "temporal".to_string(),
-                    message: e.to_string(),
-                });
-            }
-        };
-
-        // All modality writes succeeded — commit the transaction
-        if let Err(e) = self.txn_manager.commit(txn_id).await {
-            return Err(HexadError::ConsistencyViolation(format!(
-                "Transaction commit failed: {e}"
-            )));
+    /// [`write_hexad`](Self::write_hexad), reported to the listeners
+    async fn write_and_notify(
+        &self,
+        id: HexadId,
+        mut input: HexadInput,
+        existing: Option<HexadStatus>,
+    ) -> Result<Hexad, HexadError> {
+        if existing.is_none() {
+            self.claim(&mut input);
         }
-
-        // Update status
-        let status = HexadStatus {
-            id: id.clone(),
-            created_at: existing.created_at,
-            modified_at: now,
-            version,
-            modality_status: modality_status.clone(),
+        self.check_write(&id, &input, existing.is_some()).await?;
+        let mut old = match existing {
+            Some(_) => self.states_before(&[&id]).await,
+            None => HashMap::new(),
         };
-
-        // Update registry
-        self.hexads.write().await.insert(id.as_str().to_string(), status.clone());
-
-        // Write COMMITTED marker to WAL and checkpoint
-        self.wal_append(WalOperation::Checkpoint, WalModality::All, &entity_id_str, b"COMMITTED").await.ok();
-        self.wal_checkpoint().await.ok();
-
-        info!(id = %id, version = version, "Updated hexad (transaction committed)");
-
-        Ok(Hexad {
-            id: id.clone(),
-            status,
-            graph_node,
-            embedding,
-            tensor,
-            semantic,
-            document,
-            version_count: version,
-            provenance_chain_length,
-            spatial_data,
-        })
-    }
-
-    async fn get(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
-        self.load_hexad(id).await
+        let hexad = self.write_hexad(id, input, existing).await?;
+        self.notify_written(old.remove(hexad.id.as_str()).as_ref(), &hexad);
+        Ok(hexad)
     }
 
-    #[instrument(skip(self))]
-    async fn delete(&self, id: &HexadId) -> Result<(), HexadError> {
+    /// Hard-delete one entity
+    async fn delete_hexad(&self, id: &HexadId) -> Result<(), HexadError> {
         let entity_id_str = id.as_str().to_string();
 
         // Check existence before beginning transaction
@@ -1031,6 +1543,8 @@ async fn delete(&self, id: &HexadId) -> Result<(), HexadError> {
         };
 
         let existing = existing.ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        self.check_writable(id).await?;
+        let _gate = self.checkpoint_gate.read().await;
 
         // Write PENDING delete intent to WAL
         self.wal_append(WalOperation::Delete, WalModality::All, &entity_id_str, b"").await?;
@@ -1090,7 +1604,9 @@ async fn delete(&self, id: &HexadId) -> Result<(), HexadError> {
         }
 
         // Remove from registry only after successful commit
-        self.hexads.write().await.remove(id.as_str());
+        if self.hexads.write().await.remove(id.as_str()).is_some() {
+            self.count_live(id.as_str(), -1);
+        }
 
         // Write COMMITTED marker to WAL and checkpoint
         self.wal_append(WalOperation::Checkpoint, WalModality::All, &entity_id_str, b"COMMITTED").await.ok();
@@ -1100,92 +1616,1921 @@ async fn delete(&self, id: &HexadId) -> Result<(), HexadError> {
         Ok(())
     }
 
-    async fn status(&self, id: &HexadId) -> Result<Option<HexadStatus>, HexadError> {
-        Ok(self.hexads.read().await.get(id.as_str()).cloned())
-    }
+    /// Create (`existing` is `None`) or update one entity
+    async fn write_hexad(
+        &self,
+        id: HexadId,
+        input: HexadInput,
+        existing: Option<HexadStatus>,
+    ) -> Result<Hexad, HexadError> {
+        let prepared = self.prepare(&id, &input)?;
+        let _gate = self.checkpoint_gate.read().await;
+        let now = Utc::now();
+        let entity_id_str = id.as_str().to_string();
+        let (operation, message) = match existing {
+            Some(_) => (WalOperation::Update, "Update"),
+            None => (WalOperation::Insert, "Initial creation"),
+        };
 
-    async fn search_similar(&self, embedding: &[f32], k: usize) -> Result<Vec<Hexad>, HexadError> {
-        let results = self.vector.search(embedding, k).await.map_err(|e| HexadError::ModalityError {
-            modality: "vector".to_string(),
-            message: e.to_string(),
-        })?;
+        // Write PENDING intent to WAL before any modality writes.
+        // On crash recovery, PENDING entries without a matching COMMITTED
+        // entry indicate incomplete operations that need rollback.
+        let input_payload = serde_json::to_vec(&input).unwrap_or_default();
+        self.wal_append(operation, WalModality::All, &entity_id_str, &input_payload).await?;
 
-        let mut hexads = Vec::new();
-        for result in results {
-            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
-                hexads.push(hexad);
+        // Begin ACID transaction — acquire exclusive locks on all requested
+        // modalities before writing, ensuring atomicity across the octad.
+        let txn_id = self.txn_manager.begin(IsolationLevel::ReadCommitted).await;
+        let modality_names = input_modalities(&input);
+        for modality in &modality_names {
+            if let Err(e) = self
+                .txn_manager
+                .acquire_lock(txn_id, &entity_id_str, modality, LockType::Exclusive)
+                .await
+            {
+                self.txn_manager.rollback(txn_id).await.ok();
+                return Err(HexadError::ConsistencyViolation(format!(
+                    "Failed to acquire lock on {modality}: {e}"
+                )));
             }
         }
 
-        Ok(hexads)
+        let undo = match self.capture_undo(&id, &prepared, existing.is_some()).await {
+            Ok(undo) => undo,
+            Err(e) => {
+                self.txn_manager.rollback(txn_id).await.ok();
+                return Err(e);
+            }
+        };
+        let mut modality_status = existing.as_ref().map(|e| e.modality_status.clone()).unwrap_or_default();
+        let written = async {
+            self.apply_prepared(&id, &prepared, &mut modality_status).await?;
+            let provenance_chain_length = match &input.provenance {
+                Some(provenance) => {
+                    let chain_len = self.process_provenance(&id, provenance).await?;
+                    modality_status.provenance = true;
+                    chain_len
+                }
+                None => 0,
+            };
+            // The snapshot makes the write part of the entity's history
+            let snapshot = self.create_snapshot(&id, &input, &modality_status);
+            let version = self
+                .temporal
+                .append(id.as_str(), snapshot, "system", Some(message))
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?;
+            modality_status.temporal = true;
+            Ok::<_, HexadError>((provenance_chain_length, version))
+        }
+        .await;
+        let (provenance_chain_length, version) = match written {
+            Ok(written) => written,
+            Err(e) => {
+                self.undo_write(&id, &undo).await;
+                self.txn_manager.rollback(txn_id).await.ok();
+                return Err(e);
+            }
+        };
+
+        // Record the MVCC version of each written modality, then commit
+        let current_version = existing.as_ref().map_or(0, |e| e.version);
+        for modality in &modality_names {
+            self.txn_manager
+                .record_undo(txn_id, &entity_id_str, modality, None, current_version)
+                .await
+                .ok();
+        }
+        if let Err(e) = self.txn_manager.commit(txn_id).await {
+            self.undo_write(&id, &undo).await;
+            return Err(HexadError::ConsistencyViolation(format!(
+                "Transaction commit failed: {e}"
+            )));
+        }
+
+        let status = HexadStatus {
+            id: id.clone(),
+            created_at: existing.as_ref().map_or(now, |e| e.created_at),
+            modified_at: now,
+            version,
+            modality_status: modality_status.clone(),
+            expires_at: existing.as_ref().and_then(|e| e.expires_at),
+        };
+        if self.hexads.write().await.insert(entity_id_str.clone(), status.clone()).is_none() {
+            self.count_live(&entity_id_str, 1);
+        }
+
+        // Write COMMITTED marker to WAL and checkpoint for crash recovery.
+        self.wal_append(WalOperation::Checkpoint, WalModality::All, &entity_id_str, b"COMMITTED").await.ok();
+        self.wal_checkpoint().await.ok();
+
+        if existing.is_some() {
+            info!(id = %id, version = version, "Updated hexad (transaction committed)");
+        } else {
+            info!(id = %id, modalities = ?modality_status, "Created hexad (transaction committed)");
+        }
+
+        Ok(Hexad {
+            id,
+            status,
+            graph_node: prepared.graph.map(|(node, _)| node),
+            embedding: prepared.embedding,
+            tensor: prepared.tensor,
+            semantic: prepared.semantic,
+            document: prepared.document,
+            version_count: version,
+            provenance_chain_length,
+            spatial_data: prepared.spatial,
+        })
     }
 
-    async fn search_text(&self, query: &str, limit: usize) -> Result<Vec<Hexad>, HexadError> {
-        let results =
-            self.document.search(query, limit).await.map_err(|e| HexadError::ModalityError {
-                modality: "document".to_string(),
-                message: e.to_string(),
-            })?;
+    /// Read what writing `prepared` will replace.  A create replaces nothing,
+    /// so nothing is read.
+    async fn capture_undo(
+        &self,
+        id: &HexadId,
+        prepared: &PreparedHexad,
+        is_update: bool,
+    ) -> Result<WriteUndo, HexadError> {
+        let modality_error = |modality: &str, e: &dyn std::fmt::Display| HexadError::ModalityError {
+            modality: modality.to_string(),
+            message: e.to_string(),
+        };
+        let mut undo = WriteUndo::default();
+        if let Some((_, edges)) = &prepared.graph {
+            for edge in edges {
+                if !is_update || !self.graph.exists(edge).await.map_err(|e| modality_error("graph", &e))? {
+                    undo.added_edges.push(edge.clone());
+                }
+            }
+        }
+        if prepared.embedding.is_some() {
+            undo.vector = Some(match is_update {
+                true => self.vector.get(id.as_str()).await.map_err(|e| modality_error("vector", &e))?,
+                false => None,
+            });
+        }
+        if prepared.document.is_some() {
+            undo.document = Some(match is_update {
+                true => self.document.get(id.as_str()).await.map_err(|e| modality_error("document", &e))?,
+                false => None,
+            });
+        }
+        if prepared.tensor.is_some() {
+            undo.tensor = Some(match is_update {
+                true => self.tensor.get(id.as_str()).await.map_err(|e| modality_error("tensor", &e))?,
+                false => None,
+            });
+        }
+        if prepared.semantic.is_some() {
+            undo.semantic = Some(match is_update {
+                true => self
+                    .semantic
+                    .get_annotations(id.as_str())
+                    .await
+                    .map_err(|e| modality_error("semantic", &e))?,
+                false => None,
+            });
+        }
+        if prepared.spatial.is_some() {
+            undo.spatial = Some(match is_update {
+                true => self.spatial.get(id.as_str()).await.map_err(|e| modality_error("spatial", &e))?,
+                false => None,
+            });
+        }
+        Ok(undo)
+    }
 
-        let mut hexads = Vec::new();
-        for result in results {
-            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
-                hexads.push(hexad);
+    /// Write the reversible modalities concurrently.  Every write runs to
+    /// completion before the first failure, in modality order, is returned,
+    /// so the caller's undo sees no write still in flight.
+    async fn apply_prepared(
+        &self,
+        id: &HexadId,
+        prepared: &PreparedHexad,
+        modality_status: &mut ModalityStatus,
+    ) -> Result<(), HexadError> {
+        let modality_error = |modality: &str, e: &dyn std::fmt::Display| HexadError::ModalityError {
+            modality: modality.to_string(),
+            message: e.to_string(),
+        };
+        let (graph, vector, document, tensor, semantic, spatial) = tokio::join!(
+            async {
+                let Some((_, edges)) = &prepared.graph else { return Ok(false) };
+                self.graph.insert_batch(edges).await.map_err(|e| modality_error("graph", &e))?;
+                Ok::<_, HexadError>(true)
+            },
+            async {
+                let Some(embedding) = &prepared.embedding else { return Ok(false) };
+                self.vector.upsert(embedding).await.map_err(|e| modality_error("vector", &e))?;
+                Ok(true)
+            },
+            async {
+                let Some(doc) = &prepared.document else { return Ok(false) };
+                self.document.index(doc).await.map_err(|e| modality_error("document", &e))?;
+                self.document.commit().await.map_err(|e| modality_error("document", &e))?;
+                Ok(true)
+            },
+            async {
+                let Some(tensor) = &prepared.tensor else { return Ok(false) };
+                self.tensor.put(tensor).await.map_err(|e| modality_error("tensor", &e))?;
+                Ok(true)
+            },
+            async {
+                let Some(annotation) = &prepared.semantic else { return Ok(false) };
+                self.semantic.annotate(annotation).await.map_err(|e| modality_error("semantic", &e))?;
+                Ok(true)
+            },
+            async {
+                let Some(data) = &prepared.spatial else { return Ok(false) };
+                self.spatial
+                    .index(id.as_str(), data.clone())
+                    .await
+                    .map_err(|e| modality_error("spatial", &e))?;
+                Ok(true)
+            },
+        );
+
+        let mut first_error = None;
+        for (written, flag) in [
+            (graph, &mut modality_status.graph),
+            (vector, &mut modality_status.vector),
+            (document, &mut modality_status.document),
+            (tensor, &mut modality_status.tensor),
+            (semantic, &mut modality_status.semantic),
+            (spatial, &mut modality_status.spatial),
+        ] {
+            match written {
+                Ok(true) => *flag = true,
+                Ok(false) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
             }
         }
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+        debug!(id = %id, modalities = ?modality_status, "Modalities written");
+        Ok(())
+    }
 
-        Ok(hexads)
+    /// Put back what a failed write replaced, in reverse write order.
+    /// Modalities the write never reached get their current value again.
+    async fn undo_write(&self, id: &HexadId, undo: &WriteUndo) {
+        let key = id.as_str();
+        let mut failed = Vec::new();
+        if let Some(previous) = &undo.spatial {
+            let restored = match previous {
+                Some(data) => self.spatial.index(key, data.clone()).await,
+                None => self.spatial.delete(key).await,
+            };
+            if let Err(e) = restored {
+                failed.push(format!("spatial: {e}"));
+            }
+        }
+        if let Some(previous) = &undo.semantic {
+            let restored = match previous {
+                Some(annotation) => self.semantic.annotate(annotation).await,
+                None => self.semantic.delete_annotations(key).await,
+            };
+            if let Err(e) = restored {
+                failed.push(format!("semantic: {e}"));
+            }
+        }
+        if let Some(previous) = &undo.tensor {
+            let restored = match previous {
+                Some(tensor) => self.tensor.put(tensor).await,
+                None => self.tensor.delete(key).await,
+            };
+            if let Err(e) = restored {
+                failed.push(format!("tensor: {e}"));
+            }
+        }
+        if let Some(previous) = &undo.document {
+            let restored = match previous {
+                Some(doc) => self.document.index(doc).await,
+                None => self.document.delete(key).await,
+            };
+            if let Err(e) = restored.and(self.document.commit().await) {
+                failed.push(format!("document: {e}"));
+            }
+        }
+        if let Some(previous) = &undo.vector {
+            let restored = match previous {
+                Some(embedding) => self.vector.upsert(embedding).await,
+                None => self.vector.delete(key).await,
+            };
+            if let Err(e) = restored {
+                failed.push(format!("vector: {e}"));
+            }
+        }
+        for edge in &undo.added_edges {
+            if let Err(e) = self.graph.delete(edge).await {
+                failed.push(format!("graph: {e}"));
+            }
+        }
+
+        if failed.is_empty() {
+            debug!(id = %id, "Rolled back partially written modalities");
+        } else {
+            warn!(id = %id, failures = ?failed, "Rollback could not restore every modality");
+        }
     }
+}
 
-    async fn query_related(&self, id: &HexadId, predicate: &str) -> Result<Vec<Hexad>, HexadError> {
-        let node = GraphNode::new(id.to_iri(&self.config.base_iri));
-        let edges = self.graph.outgoing(&node).await.map_err(|e| HexadError::ModalityError {
-            modality: "graph".to_string(),
-            message: e.to_string(),
-        })?;
+/// Error for WAL operations on a store without a WAL
+fn wal_disabled() -> HexadError {
+    HexadError::ModalityError {
+        modality: "wal".to_string(),
+        message: "WAL is not enabled".to_string(),
+    }
+}
+
+/// ID prefix shared by the entities of a collection
+fn collection_prefix(collection: &str) -> Result<String, HexadError> {
+    HexadId::validate_collection(collection)?;
+    Ok(format!("{collection}{COLLECTION_SEPARATOR}"))
+}
+
+/// Error for an ID that appears more than once in a batch
+fn duplicate_in_batch(id: &HexadId) -> HexadError {
+    HexadError::ValidationError(format!("{id} appears more than once in the batch"))
+}
+
+/// Error for a write to an entity the entity policy lets its writer read
+/// but not change
+fn refused_write(id: &HexadId) -> HexadError {
+    HexadError::ValidationError(format!("Entity {id} may be read but not written under the entity policy"))
+}
+
+/// Soft-delete retention, which hard-deletes through [`HexadStore::delete`]
+impl<G, V, D, T, S, R, P, L> InMemoryHexadStore<G, V, D, T, S, R, P, L>
+where
+    G: GraphStore + 'static,
+    V: VectorStore + 'static,
+    D: DocumentStore + 'static,
+    T: TensorStore + 'static,
+    S: SemanticStore + 'static,
+    R: TemporalStore<Data = HexadSnapshot> + 'static,
+    P: ProvenanceStore + 'static,
+    L: SpatialStore + 'static,
+{
+    /// Hard-delete soft-deleted entities past their own cutoff: `cutoff`
+    /// gives the time before which an entity must have been deleted to be
+    /// purged, or `None` to keep it.  Lets each collection have its own
+    /// retention.
+    pub async fn purge_deleted_by<F>(&self, cutoff: F) -> Result<Vec<HexadId>, HexadError>
+    where
+        F: Fn(&HexadId) -> Option<DateTime<Utc>> + Send + Sync,
+    {
+        let expired: Vec<DeletedHexad> = {
+            let mut deleted = self.deleted.write().await;
+            let ids: Vec<String> = deleted
+                .iter()
+                .filter(|(_, d)| cutoff(&d.status.id).is_some_and(|before| d.deleted_at < before))
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| deleted.remove(id)).collect()
+        };
+        self.count_deleted(-(expired.len() as isize));
+
+        let mut purged = Vec::with_capacity(expired.len());
+        for entry in expired {
+            let id = entry.status.id.clone();
+            // Hard delete works on registered entities
+            self.hexads.write().await.insert(id.to_string(), entry.status.clone());
+            self.count_live(id.as_str(), 1);
+            // Already reported to listeners by the soft delete
+            if let Err(e) = self.delete_hexad(&id).await {
+                self.hexads.write().await.remove(id.as_str());
+                self.deleted.write().await.insert(id.to_string(), entry);
+                self.count_live(id.as_str(), -1);
+                self.count_deleted(1);
+                return Err(e);
+            }
+            purged.push(id);
+        }
+        if !purged.is_empty() {
+            info!(count = purged.len(), "Purged soft-deleted hexads");
+        }
+        Ok(purged)
+    }
+}
+
+/// Point-in-time recovery, which rewrites entities through the
+/// [`HexadStore`] operations
+impl<G, V, D, T, S, R, P, L> InMemoryHexadStore<G, V, D, T, S, R, P, L>
+where
+    G: GraphStore + 'static,
+    V: VectorStore + 'static,
+    D: DocumentStore + 'static,
+    T: TensorStore + 'static,
+    S: SemanticStore + 'static,
+    R: TemporalStore<Data = HexadSnapshot> + 'static,
+    P: ProvenanceStore + 'static,
+    L: SpatialStore + 'static,
+{
+    /// Bring the store back to its state just before `target`, from the
+    /// retained checkpoint snapshots and the WAL.
+    ///
+    /// The newest snapshot taken before `target` is rolled forward through
+    /// the writes committed before it.  Entities changed since are reverted
+    /// to their target version, and entities created since are deleted;
+    /// entities missing or behind are rewritten with their target state.
+    /// Each change is an ordinary logged write recorded with a `recovered`
+    /// provenance event, so the recovery can itself be recovered from.
+    /// With `dry_run` nothing is written.  Writes made while a recovery
+    /// runs may be undone by it.
+    #[instrument(skip(self))]
+    pub async fn recover_to(
+        &self,
+        target: DateTime<Utc>,
+        dry_run: bool,
+        actor: &str,
+    ) -> Result<RecoveryReport, HexadError> {
+        let wal = self.wal.as_ref().ok_or_else(wal_disabled)?;
+        let wal_dir = wal.lock().await.wal_dir().to_path_buf();
+        let wal_error = |e: verisim_wal::WalError| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: e.to_string(),
+        };
+
+        let mut state = match StoreSnapshot::read_before(&wal_dir, target)? {
+            Some(snapshot) => snapshot,
+            // Without a snapshot, replay starts from an empty store, which
+            // needs the log to be complete
+            None => {
+                let segments = verisim_wal::segment::list_segments(&wal_dir).map_err(wal_error)?;
+                if segments.first().is_some_and(|s| s.start_sequence > 0) {
+                    return Err(HexadError::ValidationError(format!(
+                        "No checkpoint taken before {target} is retained"
+                    )));
+                }
+                StoreSnapshot::empty()
+            }
+        };
+        let snapshot_sequence = state.sequence;
+        let entries = verisim_wal::WalReader::open(&wal_dir)
+            .and_then(|reader| reader.replay_from(snapshot_sequence + 1))
+            .map_err(wal_error)?;
+        let writes_replayed = state.roll_forward(entries, target);
+
+        let mut report = RecoveryReport {
+            target,
+            dry_run,
+            snapshot_sequence,
+            replayed_through: state.sequence,
+            writes_replayed,
+            rolled_back: Vec::new(),
+            rolled_forward: Vec::new(),
+        };
+        let provenance = || HexadProvenanceInput {
+            event_type: "recovered".to_string(),
+            actor: actor.to_string(),
+            source: None,
+            description: format!("Point-in-time recovery to {target}"),
+            link: None,
+        };
+        let current: HashMap<String, HexadStatus> = self.hexads.read().await.clone();
+        let current_deleted: HashMap<String, DeletedHexad> = self.deleted.read().await.clone();
+        let recovered = |id: &HexadId, current: Option<u64>, target: Option<u64>| RecoveredHexad {
+            id: id.clone(),
+            current_version: current,
+            target_version: target,
+            error: None,
+        };
+
+        let mut live_then = std::collections::HashSet::new();
+        for SnapshotHexad { status: then, mut input } in state.hexads {
+            let id = then.id.clone();
+            live_then.insert(id.to_string());
+            let unchanged = match current.get(id.as_str()) {
+                Some(now) => self.has_state(&id, now.version, &input).await?,
+                None => false,
+            };
+            input.provenance = Some(provenance());
+            if let Some(deleted) = current_deleted.get(id.as_str()) {
+                // Soft-deleted since: restore, then settle the version
+                let mut entry = recovered(&id, Some(deleted.status.version), Some(then.version));
+                if !dry_run {
+                    let result = match self.restore(&id, actor).await {
+                        Ok(restored) if restored.status.version > then.version => {
+                            self.revert(&id, then.version, Some(provenance())).await.map(|_| ())
+                        }
+                        Ok(_) => Ok(()),
+                        Err(e) => Err(e),
+                    };
+                    entry.error = result.err().map(|e| e.to_string());
+                }
+                if deleted.status.version >= then.version {
+                    report.rolled_back.push(entry);
+                } else {
+                    report.rolled_forward.push(entry);
+                }
+                continue;
+            }
+            match current.get(id.as_str()) {
+                Some(_) if unchanged => {}
+                Some(now) if now.version > then.version => {
+                    let mut entry = recovered(&id, Some(now.version), Some(then.version));
+                    if !dry_run {
+                        entry.error = self.revert(&id, then.version, Some(provenance())).await.err().map(|e| e.to_string());
+                    }
+                    report.rolled_back.push(entry);
+                }
+                now => {
+                    let mut entry = recovered(&id, now.map(|s| s.version), Some(then.version));
+                    if !dry_run {
+                        entry.error = self.write_and_notify(id, input, now.cloned()).await.err().map(|e| e.to_string());
+                    }
+                    report.rolled_forward.push(entry);
+                }
+            }
+        }
+
+        for SnapshotDeletedHexad { deleted: then, mut input } in state.deleted {
+            let id = then.status.id.clone();
+            live_then.insert(id.to_string());
+            if current_deleted.contains_key(id.as_str()) {
+                continue;
+            }
+            let now = current.get(id.as_str());
+            let mut entry = recovered(&id, now.map(|s| s.version), Some(then.status.version));
+            if !dry_run {
+                input.provenance = Some(provenance());
+                let result = match now {
+                    Some(_) => Ok(()),
+                    None => self.write_and_notify(id.clone(), input, None).await.map(|_| ()),
+                };
+                let result = match result {
+                    Ok(()) => self.soft_delete(&id, actor).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                entry.error = result.err().map(|e| e.to_string());
+            }
+            if now.is_some() {
+                report.rolled_back.push(entry);
+            } else {
+                report.rolled_forward.push(entry);
+            }
+        }
+
+        // Created after the target
+        for (id, now) in &current {
+            if live_then.contains(id) {
+                continue;
+            }
+            let mut entry = recovered(&now.id, Some(now.version), None);
+            if !dry_run {
+                entry.error = self.delete(&now.id).await.err().map(|e| e.to_string());
+            }
+            report.rolled_back.push(entry);
+        }
+
+        info!(
+            %target,
+            dry_run,
+            rolled_back = report.rolled_back.len(),
+            rolled_forward = report.rolled_forward.len(),
+            "Point-in-time recovery"
+        );
+        Ok(report)
+    }
+
+    /// Whether the entity's state at `version` is `input`, ignoring provenance
+    async fn has_state(&self, id: &HexadId, version: u64, input: &HexadInput) -> Result<bool, HexadError> {
+        let mut current = self.current_input(id, version).await?;
+        current.provenance = None;
+        let expected = HexadInput {
+            provenance: None,
+            ..input.clone()
+        };
+        Ok(serde_json::to_value(&current).ok() == serde_json::to_value(&expected).ok())
+    }
+
+    /// The entity's state at `version` as an input: everything written to it
+    /// up to then, which [`put`](Self::put) on another store reproduces
+    pub async fn input_at(&self, id: &HexadId, version: u64) -> Result<HexadInput, HexadError> {
+        self.current_input(id, version).await
+    }
+
+    /// Write `input` to the entity `id`, creating it with that ID if it does
+    /// not exist.  For replicas and imports, which keep the source's IDs.
+    #[instrument(skip(self, input))]
+    pub async fn put(&self, id: &HexadId, input: HexadInput) -> Result<Hexad, HexadError> {
+        let existing = self.hexads.read().await.get(id.as_str()).cloned();
+        self.write_and_notify(id.clone(), input, existing).await
+    }
+
+    /// The snapshot the write that made `version` of the entity recorded:
+    /// that write's input and time, and the modalities present after it
+    pub async fn version_snapshot(&self, id: &HexadId, version: u64) -> Result<Option<HexadSnapshot>, HexadError> {
+        self.temporal
+            .at_version(id.as_str(), version)
+            .await
+            .map(|v| v.map(|v| v.data))
+            .map_err(|e| HexadError::ModalityError {
+                modality: "temporal".to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Remove `(predicate, target)` relationships from the graph.  Version
+    /// history still lists them, as relationships accumulate there, so this
+    /// is for callers that track an entity's relationships themselves.
+    pub async fn unlink(&self, id: &HexadId, relationships: &[(String, String)]) -> Result<(), HexadError> {
+        let node = GraphNode::new(id.to_iri(&self.config.base_iri));
+        for (predicate, target_id) in relationships {
+            let edge = self.relationship_edge(&node, predicate, target_id);
+            self.graph.delete(&edge).await.map_err(|e| HexadError::ModalityError {
+                modality: "graph".to_string(),
+                message: e.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Backups, restored through [`put`](InMemoryHexadStore::put)
+impl<G, V, D, T, S, R, P, L> InMemoryHexadStore<G, V, D, T, S, R, P, L>
+where
+    G: GraphStore + 'static,
+    V: VectorStore + 'static,
+    D: DocumentStore + 'static,
+    T: TensorStore + 'static,
+    S: SemanticStore + 'static,
+    R: TemporalStore<Data = HexadSnapshot> + 'static,
+    P: ProvenanceStore + 'static,
+    L: SpatialStore + 'static,
+{
+    /// Write a backup archive of the store under `target`.
+    ///
+    /// Writes wait while it is taken.  A full backup holds the state as of
+    /// now, with every entity's version history and provenance chain.  An
+    /// incremental one holds the WAL written since the latest archive under
+    /// `target`, or the full state as of now when a checkpoint has
+    /// truncated part of that WAL; it needs the WAL enabled.
+    #[instrument(skip(self, target))]
+    pub async fn backup(
+        &self,
+        target: impl AsRef<std::path::Path>,
+        kind: BackupKind,
+    ) -> Result<BackupManifest, HexadError> {
+        let target = target.as_ref();
+        let started = std::time::Instant::now();
+        let _gate = self.checkpoint_gate.write().await;
+        let wal_error = |e: verisim_wal::WalError| HexadError::ModalityError {
+            modality: "wal".to_string(),
+            message: e.to_string(),
+        };
+
+        let manifest = match kind {
+            BackupKind::Full => {
+                let sequence = match &self.wal {
+                    Some(wal) => wal.lock().await.next_sequence().saturating_sub(1),
+                    None => 0,
+                };
+                let snapshot = self.snapshot_state(sequence).await?;
+                let history = self.history_state(&snapshot).await?;
+                backup::write_archive(target, kind, None, Some((&snapshot, &history)), None, sequence)?
+            }
+            BackupKind::Incremental => {
+                let wal = self.wal.as_ref().ok_or_else(wal_disabled)?;
+                let mut writer = wal.lock().await;
+                writer.sync().map_err(wal_error)?;
+                let sequence = writer.next_sequence().saturating_sub(1);
+                let parent = backup::list_backups(target)?.pop().ok_or_else(|| {
+                    HexadError::ValidationError(format!(
+                        "An incremental backup needs an earlier backup in {}",
+                        target.display()
+                    ))
+                })?;
+                if parent.sequence > sequence {
+                    return Err(HexadError::ValidationError(format!(
+                        "Backup {} is ahead of this store's WAL",
+                        parent.id
+                    )));
+                }
+                let wal_dir = writer.wal_dir().to_path_buf();
+                let segments = verisim_wal::segment::list_segments(&wal_dir).map_err(wal_error)?;
+                let truncated = parent.sequence < sequence
+                    && segments.first().is_none_or(|s| s.start_sequence > parent.sequence + 1);
+                // Writes are held off, so the state now is the state at
+                // `sequence`
+                let base = if truncated {
+                    let snapshot = self.snapshot_state(sequence).await?;
+                    let history = self.history_state(&snapshot).await?;
+                    Some((snapshot, history))
+                } else {
+                    None
+                };
+                let from = base.as_ref().map_or(parent.sequence, |(s, _)| s.sequence) + 1;
+                let store_files = base.as_ref().map(|(s, h)| (s, h));
+                backup::write_archive(target, kind, Some(&parent), store_files, Some((&wal_dir, from)), sequence)?
+            }
+        };
+        info!(
+            id = %manifest.id,
+            sequence = manifest.sequence,
+            files = manifest.files.len(),
+            duration_ms = started.elapsed().as_millis() as u64,
+            "Backup taken"
+        );
+        Ok(manifest)
+    }
+
+    /// Rebuild the state archive `id` under `target` captured in this
+    /// store, which must be empty.
+    ///
+    /// Entities in the archive's store files keep their status, version
+    /// history and provenance chain; soft-deleted ones are written and
+    /// deleted again, and tombstones are kept.  The archived WAL after the
+    /// store files is then replayed as ordinary writes, which extend those
+    /// histories as the original writes did, timed as of the restore.
+    /// Archives taken before histories were archived restore each entity
+    /// with a new history, starting with a `restored` provenance event.
+    #[instrument(skip(self, target))]
+    pub async fn restore_backup(
+        &self,
+        target: impl AsRef<std::path::Path>,
+        id: &str,
+        actor: &str,
+    ) -> Result<RestoreReport, HexadError> {
+        let existing = self.hexads.read().await.len() + self.deleted.read().await.len();
+        if existing > 0 {
+            return Err(HexadError::ValidationError(format!(
+                "A backup is restored into an empty store; this one holds {existing} entities"
+            )));
+        }
+        let ArchivedState {
+            snapshot: state,
+            mut history,
+            entries,
+        } = backup::load_archive(target, id)?;
+        let provenance = || HexadProvenanceInput {
+            event_type: "restored".to_string(),
+            actor: actor.to_string(),
+            source: None,
+            description: format!("Restored from backup {id}"),
+            link: None,
+        };
+        let mut report = RestoreReport {
+            backup: id.to_string(),
+            sequence: state.sequence,
+            hexads: 0,
+            deleted: 0,
+            writes_replayed: 0,
+            tombstones: state.tombstones.len(),
+            failed: Default::default(),
+        };
+        for SnapshotHexad { status, input } in state.hexads {
+            let entity = status.id.to_string();
+            match self.load_archived(status, input, None, &mut history, provenance()).await {
+                Ok(()) => report.hexads += 1,
+                Err(e) => {
+                    report.failed.insert(entity, e.to_string());
+                }
+            }
+        }
+        for SnapshotDeletedHexad { deleted, input } in state.deleted {
+            let entity = deleted.status.id.to_string();
+            let status = deleted.status.clone();
+            match self.load_archived(status, input, Some(deleted), &mut history, provenance()).await {
+                Ok(()) => report.deleted += 1,
+                Err(e) => {
+                    report.failed.insert(entity, e.to_string());
+                }
+            }
+        }
+
+        for (write, committed) in committed_writes(entries, state.sequence, DateTime::<Utc>::MAX_UTC) {
+            let entity = HexadId::new(write.entity_id.clone());
+            match self.replay_archived(&entity, write, actor).await {
+                Ok(()) => report.writes_replayed += 1,
+                Err(e) => {
+                    report.failed.insert(entity.to_string(), e.to_string());
+                }
+            }
+            report.sequence = committed.sequence;
+        }
+        self.tombstones
+            .write()
+            .await
+            .extend(state.tombstones.into_iter().map(|t| (t.id.to_string(), t)));
+
+        info!(
+            backup = %id,
+            hexads = report.hexads,
+            deleted = report.deleted,
+            writes_replayed = report.writes_replayed,
+            failed = report.failed.len(),
+            "Backup restored"
+        );
+        Ok(report)
+    }
+
+    /// Write an archived entity, soft-deleting it again if it was, and give
+    /// it its archived status, version history and provenance chain.  With
+    /// no history archived it starts a new one with `restored`.
+    async fn load_archived(
+        &self,
+        status: HexadStatus,
+        mut input: HexadInput,
+        deleted: Option<DeletedHexad>,
+        history: &mut StoreHistory,
+        restored: HexadProvenanceInput,
+    ) -> Result<(), HexadError> {
+        let id = status.id.clone();
+        let versions = history.versions.remove(id.as_str());
+        let chain = history.chains.remove(id.as_str());
+        let archived = versions.is_some() || chain.is_some();
+        input.provenance = (!archived).then_some(restored);
+        self.put(&id, input).await?;
+        if let Some(deleted) = &deleted {
+            self.soft_delete(&id, &deleted.deleted_by).await?;
+        }
+        if !archived {
+            return Ok(());
+        }
+
+        if let Some(versions) = versions {
+            self.temporal
+                .import_history(id.as_str(), versions)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?;
+        }
+        if let Some(chain) = chain {
+            self.provenance
+                .import_chain(chain)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "provenance".to_string(),
+                    message: e.to_string(),
+                })?;
+        }
+        match deleted {
+            Some(deleted) => {
+                self.deleted.write().await.insert(id.to_string(), deleted);
+            }
+            None => {
+                self.hexads.write().await.insert(id.to_string(), status);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply one committed write of an archived WAL the way it was first
+    /// made: an update of a soft-deleted entity restores it first
+    async fn replay_archived(&self, id: &HexadId, write: WalEntry, actor: &str) -> Result<(), HexadError> {
+        if self.deleted.read().await.contains_key(id.as_str()) {
+            self.restore(id, actor).await?;
+        }
+        match write.operation {
+            WalOperation::Delete => self.delete(id).await,
+            WalOperation::Insert | WalOperation::Update => {
+                let input: HexadInput = serde_json::from_slice(&write.payload).map_err(|e| HexadError::ModalityError {
+                    modality: "backup".to_string(),
+                    message: format!("Unreadable archived write of {id}: {e}"),
+                })?;
+                self.put(id, input).await.map(|_| ())
+            }
+            WalOperation::Checkpoint => Ok(()),
+        }
+    }
+
+    /// Version histories and provenance chains of the entities in
+    /// `snapshot`.  Callers hold the checkpoint gate.
+    async fn history_state(&self, snapshot: &StoreSnapshot) -> Result<StoreHistory, HexadError> {
+        let ids = snapshot
+            .hexads
+            .iter()
+            .map(|h| &h.status.id)
+            .chain(snapshot.deleted.iter().map(|d| &d.deleted.status.id));
+        let mut history = StoreHistory::default();
+        for id in ids {
+            let mut versions = self
+                .temporal
+                .history(id.as_str(), usize::MAX)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?;
+            if !versions.is_empty() {
+                versions.reverse();
+                history.versions.insert(id.to_string(), versions);
+            }
+            if let Ok(chain) = self.provenance.get_chain(id.as_str()).await {
+                history.chains.insert(id.to_string(), chain);
+            }
+        }
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl<G, V, D, T, S, R, P, L> HexadStore for InMemoryHexadStore<G, V, D, T, S, R, P, L>
+where
+    G: GraphStore + 'static,
+    V: VectorStore + 'static,
+    D: DocumentStore + 'static,
+    T: TensorStore + 'static,
+    S: SemanticStore + 'static,
+    R: TemporalStore<Data = HexadSnapshot> + 'static,
+    P: ProvenanceStore + 'static,
+    L: SpatialStore + 'static,
+{
+    #[instrument(skip(self, input))]
+    async fn create(&self, input: HexadInput) -> Result<Hexad, HexadError> {
+        self.write_and_notify(HexadId::generate(), input, None).await
+    }
+
+    #[instrument(skip(self, input))]
+    async fn update(&self, id: &HexadId, input: HexadInput) -> Result<Hexad, HexadError> {
+        let existing = self
+            .hexads
+            .read()
+            .await
+            .get(id.as_str())
+            .cloned()
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        self.write_and_notify(id.clone(), input, Some(existing)).await
+    }
+
+    async fn get(&self, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
+        self.load_hexad(id).await
+    }
+
+    async fn get_with(&self, id: &HexadId, mask: ModalityMask) -> Result<Option<Hexad>, HexadError> {
+        self.load_hexad_with(id, mask).await
+    }
+
+    async fn get_many(&self, ids: &[HexadId]) -> Result<Vec<Option<Hexad>>, HexadError> {
+        self.load_hexads(ids).await
+    }
+
+    #[instrument(skip(self, inputs), fields(count = inputs.len()))]
+    async fn create_batch(&self, inputs: Vec<HexadInput>) -> Vec<Result<Hexad, HexadError>> {
+        let mut results: Vec<Option<Result<Hexad, HexadError>>> = (0..inputs.len()).map(|_| None).collect();
+        let mut items = Vec::with_capacity(inputs.len());
+        for (index, mut input) in inputs.into_iter().enumerate() {
+            self.claim(&mut input);
+            match self.batch_item(index, HexadId::generate(), input, None) {
+                Ok(item) => items.push(item),
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+
+        let results = self.write_batch(items, results).await;
+        for hexad in results.iter().flatten() {
+            self.notify_written(None, hexad);
+        }
+        info!(
+            requested = results.len(),
+            created = results.iter().filter(|r| r.is_ok()).count(),
+            "Created hexad batch (transaction committed)"
+        );
+        results
+    }
+
+    #[instrument(skip(self, updates), fields(count = updates.len()))]
+    async fn update_batch(&self, updates: Vec<(HexadId, HexadInput)>) -> Vec<Result<Hexad, HexadError>> {
+        let mut results: Vec<Option<Result<Hexad, HexadError>>> = (0..updates.len()).map(|_| None).collect();
+        let mut items = Vec::with_capacity(updates.len());
+        {
+            let registry = self.hexads.read().await;
+            let mut seen = std::collections::HashSet::new();
+            for (index, (id, input)) in updates.into_iter().enumerate() {
+                let item = if !seen.insert(id.clone()) {
+                    Err(duplicate_in_batch(&id))
+                } else {
+                    match registry.get(id.as_str()).cloned() {
+                        Some(existing) => self.batch_item(index, id, input, Some(existing)),
+                        None => Err(HexadError::NotFound(id.to_string())),
+                    }
+                };
+                match item {
+                    Ok(item) => items.push(item),
+                    Err(e) => results[index] = Some(Err(e)),
+                }
+            }
+        }
+
+        let ids: Vec<&HexadId> = items.iter().map(|item| &item.id).collect();
+        let mut old = self.states_before(&ids).await;
+        let results = self.write_batch(items, results).await;
+        for hexad in results.iter().flatten() {
+            if let Some(old) = old.remove(hexad.id.as_str()) {
+                self.notify_written(Some(&old), hexad);
+            }
+        }
+        info!(
+            requested = results.len(),
+            updated = results.iter().filter(|r| r.is_ok()).count(),
+            "Updated hexad batch (transaction committed)"
+        );
+        results
+    }
+
+    #[instrument(skip(self, ids), fields(count = ids.len()))]
+    async fn delete_batch(&self, ids: &[HexadId]) -> Vec<Result<(), HexadError>> {
+        let _gate = self.checkpoint_gate.read().await;
+        let mut results: Vec<Option<Result<(), HexadError>>> = (0..ids.len()).map(|_| None).collect();
+        let mut targets: Vec<(usize, HexadStatus)> = Vec::with_capacity(ids.len());
+        {
+            let registry = self.hexads.read().await;
+            for (index, id) in ids.iter().enumerate() {
+                if ids[..index].contains(id) {
+                    results[index] = Some(Err(duplicate_in_batch(id)));
+                } else if let Some(existing) = registry.get(id.as_str()) {
+                    targets.push((index, existing.clone()));
+                } else {
+                    results[index] = Some(Err(HexadError::NotFound(id.to_string())));
+                }
+            }
+        }
+
+        let mut admitted = Vec::with_capacity(targets.len());
+        for (index, existing) in targets {
+            match self.check_writable(&ids[index]).await {
+                Ok(()) => admitted.push((index, existing)),
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+        let targets = admitted;
+
+        let target_ids: Vec<&HexadId> = targets.iter().map(|(index, _)| &ids[*index]).collect();
+        let old = self.states_before(&target_ids).await;
+
+        // PENDING delete intents, then one transaction for the batch
+        let mut deletes: Vec<(usize, HexadStatus)> = Vec::with_capacity(targets.len());
+        for (index, existing) in targets {
+            match self.wal_append(WalOperation::Delete, WalModality::All, ids[index].as_str(), b"").await {
+                Ok(()) => deletes.push((index, existing)),
+                Err(e) => results[index] = Some(Err(e)),
+            }
+        }
+        let txn_id = self.txn_manager.begin(IsolationLevel::ReadCommitted).await;
+        let mut locked: Vec<(usize, HexadStatus)> = Vec::with_capacity(deletes.len());
+        'entities: for (index, existing) in deletes {
+            let populated: Vec<&str> = [
+                existing.modality_status.graph.then_some("graph"),
+                existing.modality_status.vector.then_some("vector"),
+                existing.modality_status.document.then_some("document"),
+                existing.modality_status.tensor.then_some("tensor"),
+                existing.modality_status.semantic.then_some("semantic"),
+                existing.modality_status.provenance.then_some("provenance"),
+                existing.modality_status.spatial.then_some("spatial"),
+                Some("temporal"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            for modality in &populated {
+                if let Err(e) = self
+                    .txn_manager
+                    .acquire_lock(txn_id, ids[index].as_str(), modality, LockType::Exclusive)
+                    .await
+                {
+                    results[index] = Some(Err(HexadError::ConsistencyViolation(format!(
+                        "Failed to acquire lock on {modality} for delete: {e}"
+                    ))));
+                    continue 'entities;
+                }
+            }
+            for modality in &populated {
+                self.txn_manager
+                    .record_undo(txn_id, ids[index].as_str(), modality, None, existing.version)
+                    .await
+                    .ok();
+            }
+            locked.push((index, existing));
+        }
+
+        // Delete from each modality store; temporal keeps the history and
+        // graph and semantic have no delete-by-id, as in `delete`
+        let doomed: Vec<&str> = locked.iter().map(|(index, _)| ids[*index].as_str()).collect();
+        for id in &doomed {
+            self.vector.delete(id).await.ok();
+            self.tensor.delete(id).await.ok();
+        }
+        self.document.delete_batch(&doomed).await.ok();
+
+        if let Err(e) = self.txn_manager.commit(txn_id).await {
+            for (index, _) in &locked {
+                results[*index] = Some(Err(HexadError::ConsistencyViolation(format!(
+                    "Transaction commit failed during delete: {e}"
+                ))));
+            }
+        } else {
+            let mut registry = self.hexads.write().await;
+            for (index, _) in &locked {
+                if registry.remove(ids[*index].as_str()).is_some() {
+                    self.count_live(ids[*index].as_str(), -1);
+                }
+                results[*index] = Some(Ok(()));
+            }
+            drop(registry);
+            self.commit_batch_wal(doomed.iter().copied()).await;
+            for id in &doomed {
+                if let Some(old) = old.get(*id) {
+                    self.notify("deleted", |l| l.on_deleted(old));
+                }
+            }
+        }
+
+        info!(
+            requested = ids.len(),
+            deleted = results.iter().filter(|r| matches!(r, Some(Ok(())))).count(),
+            "Deleted hexad batch (transaction committed)"
+        );
+        results.into_iter().map(|r| r.expect("every batch item has a result")).collect()
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, id: &HexadId) -> Result<(), HexadError> {
+        let old = self.states_before(&[id]).await;
+        self.delete_hexad(id).await?;
+        if let Some(old) = old.get(id.as_str()) {
+            self.notify("deleted", |l| l.on_deleted(old));
+        }
+        Ok(())
+    }
+
+    async fn status(&self, id: &HexadId) -> Result<Option<HexadStatus>, HexadError> {
+        let status = self.hexads.read().await.get(id.as_str()).cloned();
+        match status {
+            Some(status) if self.admits(id).await? => Ok(Some(status)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn search_similar(&self, embedding: &[f32], k: usize) -> Result<Vec<Hexad>, HexadError> {
+        let results = self.vector.search(embedding, k).await.map_err(|e| HexadError::ModalityError {
+            modality: "vector".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let mut hexads = Vec::new();
+        for result in results {
+            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
+                hexads.push(hexad);
+            }
+        }
+
+        Ok(hexads)
+    }
+
+    async fn search_text(&self, query: &str, limit: usize) -> Result<Vec<Hexad>, HexadError> {
+        let results =
+            self.document.search(query, limit).await.map_err(|e| HexadError::ModalityError {
+                modality: "document".to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut hexads = Vec::new();
+        for result in results {
+            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
+                hexads.push(hexad);
+            }
+        }
+
+        Ok(hexads)
+    }
+
+    async fn query_related(&self, id: &HexadId, predicate: &str) -> Result<Vec<Hexad>, HexadError> {
+        let targets = self.related_ids(id, predicate).await?;
+        Ok(self.load_hexads(&targets).await?.into_iter().flatten().collect())
+    }
+
+    async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Hexad>, HexadError> {
+        self.list_with(limit, offset, ModalityMask::ALL).await
+    }
+
+    async fn list_with(&self, limit: usize, offset: usize, mask: ModalityMask) -> Result<Vec<Hexad>, HexadError> {
+        let ids: Vec<String> = if self.restricted().is_some() {
+            let ids = self.hexads.read().await.keys().cloned().collect();
+            self.retain_admitted(ids, String::as_str).await?.into_iter().skip(offset).take(limit).collect()
+        } else {
+            let hexads = self.hexads.read().await;
+            hexads.keys().skip(offset).take(limit).cloned().collect()
+        };
+
+        let mut result = Vec::with_capacity(ids.len());
+        for id_str in ids {
+            if let Some(hexad) = self.load_hexad_with(&HexadId::new(&id_str), mask).await? {
+                result.push(hexad);
+            }
+        }
+        Ok(result)
+    }
+
+    #[instrument(skip(self, provenance))]
+    async fn revert(
+        &self,
+        id: &HexadId,
+        version: u64,
+        provenance: Option<HexadProvenanceInput>,
+    ) -> Result<Hexad, HexadError> {
+        let existing = self
+            .hexads
+            .read()
+            .await
+            .get(id.as_str())
+            .cloned()
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        if version == 0 || version > existing.version {
+            return Err(HexadError::ValidationError(format!(
+                "Cannot revert {} to version {}: current version is {}",
+                id, version, existing.version
+            )));
+        }
+
+        // Replay the snapshots up to `version`; later ones show what changed since
+        let mut target = HexadInput::default();
+        let mut later = Vec::new();
+        for v in 1..=existing.version {
+            let snapshot = self
+                .temporal
+                .at_version(id.as_str(), v)
+                .await
+                .map_err(|e| HexadError::ModalityError {
+                    modality: "temporal".to_string(),
+                    message: e.to_string(),
+                })?;
+            let Some(snapshot) = snapshot else { continue };
+            if v <= version {
+                apply_snapshot(&mut target, snapshot.data);
+            } else {
+                later.push(snapshot.data);
+            }
+        }
+
+        let mut restore = HexadInput {
+            provenance,
+            ..Default::default()
+        };
+        let mut status = existing.modality_status.clone();
+        let modality_error = |modality: &str, e: &dyn std::fmt::Display| HexadError::ModalityError {
+            modality: modality.to_string(),
+            message: e.to_string(),
+        };
+        // A modality changed since `version` if a later snapshot wrote it or
+        // recorded it as present when it was absent then, or vice versa
+        let changed = |written: fn(&HexadInput) -> bool, present: fn(&ModalityStatus) -> bool, had: bool| {
+            later
+                .iter()
+                .any(|s| written(&s.input) || present(&s.modality_status) != had)
+        };
+
+        if changed(|i| i.vector.is_some(), |m| m.vector, target.vector.is_some()) {
+            match target.vector {
+                Some(vector) => restore.vector = Some(vector),
+                None if status.vector => {
+                    self.vector.delete(id.as_str()).await.map_err(|e| modality_error("vector", &e))?;
+                    status.vector = false;
+                }
+                None => {}
+            }
+        }
+        if changed(|i| i.tensor.is_some(), |m| m.tensor, target.tensor.is_some()) {
+            match target.tensor {
+                Some(tensor) => restore.tensor = Some(tensor),
+                None if status.tensor => {
+                    self.tensor.delete(id.as_str()).await.map_err(|e| modality_error("tensor", &e))?;
+                    status.tensor = false;
+                }
+                None => {}
+            }
+        }
+        if changed(|i| i.document.is_some(), |m| m.document, target.document.is_some()) {
+            match target.document {
+                Some(document) => restore.document = Some(document),
+                None if status.document => {
+                    self.document.delete(id.as_str()).await.map_err(|e| modality_error("document", &e))?;
+                    status.document = false;
+                }
+                None => {}
+            }
+        }
+        if changed(|i| i.spatial.is_some(), |m| m.spatial, target.spatial.is_some()) {
+            match target.spatial {
+                Some(spatial) => restore.spatial = Some(spatial),
+                None if status.spatial => {
+                    self.spatial.delete(id.as_str()).await.map_err(|e| modality_error("spatial", &e))?;
+                    status.spatial = false;
+                }
+                None => {}
+            }
+        }
+        // Semantic annotations cannot be removed, only replaced
+        if changed(|i| i.semantic.is_some(), |m| m.semantic, target.semantic.is_some()) {
+            restore.semantic = target.semantic;
+        }
+
+        if changed(|i| i.graph.is_some(), |m| m.graph, target.graph.is_some()) {
+            let kept: Vec<(String, String)> = target.graph.map(|g| g.relationships).unwrap_or_default();
+            let node = GraphNode::new(id.to_iri(&self.config.base_iri));
+            let added = later
+                .iter()
+                .filter_map(|s| s.input.graph.as_ref())
+                .flat_map(|g| &g.relationships);
+            for (predicate, target_id) in added {
+                if !kept.iter().any(|(p, t)| p == predicate && t == target_id) {
+                    let edge = self.relationship_edge(&node, predicate, target_id);
+                    self.graph.delete(&edge).await.map_err(|e| modality_error("graph", &e))?;
+                }
+            }
+            if kept.is_empty() {
+                status.graph = false;
+            } else {
+                // Re-insert relationships a previous revert may have removed
+                restore.graph = Some(HexadGraphInput { relationships: kept });
+            }
+        }
+
+        // Record cleared modalities before the update snapshots the status
+        if let Some(current) = self.hexads.write().await.get_mut(id.as_str()) {
+            current.modality_status = status;
+        }
+        let reverted = self.update(id, restore).await?;
+        info!(id = %id, to = version, version = reverted.status.version, "Reverted hexad");
+        Ok(reverted)
+    }
+
+    #[instrument(skip(self))]
+    async fn soft_delete(&self, id: &HexadId, actor: &str) -> Result<DeletedHexad, HexadError> {
+        let event = HexadProvenanceInput {
+            event_type: "deleted".to_string(),
+            actor: actor.to_string(),
+            source: None,
+            description: "Soft-deleted".to_string(),
+            link: None,
+        };
+        self.soft_delete_with(id, event).await
+    }
+
+    #[instrument(skip(self))]
+    async fn restore(&self, id: &HexadId, actor: &str) -> Result<Hexad, HexadError> {
+        let status = self.deleted.read().await.get(id.as_str()).map(|d| d.status.clone());
+        if let Some(status) = status {
+            if !self.admits_deleted(&status, Action::Read).await? {
+                return Err(HexadError::NotFound(id.to_string()));
+            }
+            if !self.admits_deleted(&status, Action::Write).await? {
+                return Err(refused_write(id));
+            }
+        }
+        let deleted = self
+            .deleted
+            .write()
+            .await
+            .remove(id.as_str())
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        self.count_deleted(-1);
+        let mut input = match self.current_input(id, deleted.status.version).await {
+            Ok(input) => input,
+            Err(e) => {
+                self.deleted.write().await.insert(id.to_string(), deleted);
+                self.count_deleted(1);
+                return Err(e);
+            }
+        };
+        input.provenance = Some(HexadProvenanceInput {
+            event_type: "restored".to_string(),
+            actor: actor.to_string(),
+            source: None,
+            description: "Restored after soft delete".to_string(),
+            link: None,
+        });
+
+        // An expiry that has passed would delete it again straight away
+        let mut status = deleted.status.clone();
+        if status.expires_at.is_some_and(|at| at <= Utc::now()) {
+            status.expires_at = None;
+        }
+        self.hexads.write().await.insert(id.to_string(), status.clone());
+        self.count_live(id.as_str(), 1);
+        if let Err(e) = self.write_hexad(id.clone(), input, Some(status)).await {
+            self.hexads.write().await.remove(id.as_str());
+            self.deleted.write().await.insert(id.to_string(), deleted);
+            self.count_live(id.as_str(), -1);
+            self.count_deleted(1);
+            return Err(e);
+        }
+
+        info!(id = %id, actor, "Restored hexad");
+        let restored = self
+            .load_hexad(id)
+            .await?
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        self.notify("created", |l| l.on_created(&restored));
+        Ok(restored)
+    }
+
+    async fn deleted(&self, limit: usize, offset: usize) -> Result<Vec<DeletedHexad>, HexadError> {
+        let mut deleted: Vec<DeletedHexad> = self.deleted.read().await.values().cloned().collect();
+        deleted.sort_by_key(|d| std::cmp::Reverse(d.deleted_at));
+        if self.restricted().is_some() {
+            let mut admitted = Vec::with_capacity(deleted.len());
+            for d in deleted {
+                if self.admits_deleted(&d.status, Action::Read).await? {
+                    admitted.push(d);
+                }
+            }
+            deleted = admitted;
+        }
+        Ok(deleted.into_iter().skip(offset).take(limit).collect())
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<Vec<HexadId>, HexadError> {
+        self.purge_deleted_by(|_| Some(before)).await
+    }
+
+    #[instrument(skip(self, policy))]
+    async fn merge(
+        &self,
+        winner: &HexadId,
+        loser: &HexadId,
+        policy: &MergePolicy,
+        actor: &str,
+    ) -> Result<HexadMerge, HexadError> {
+        if winner == loser {
+            return Err(HexadError::ValidationError(format!("Cannot merge {} into itself", winner)));
+        }
+        let (winner_status, loser_status) = {
+            let hexads = self.hexads.read().await;
+            let status = |id: &HexadId| {
+                hexads
+                    .get(id.as_str())
+                    .cloned()
+                    .ok_or_else(|| HexadError::NotFound(id.to_string()))
+            };
+            (status(winner)?, status(loser)?)
+        };
+        // Before the loser's data is read into the winner
+        for id in [winner, loser] {
+            self.check_writable(id).await?;
+        }
+
+        let winner_input = self.current_input(winner, winner_status.version).await?;
+        let loser_input = self.current_input(loser, loser_status.version).await?;
+        let mut update = policy.merged_input(winner, &winner_input, &loser_input);
+        update.provenance = Some(HexadProvenanceInput {
+            event_type: "merged".to_string(),
+            actor: actor.to_string(),
+            source: Some(loser.to_string()),
+            description: format!("Merged {} into this entity", loser),
+            link: None,
+        });
+        self.update(winner, update).await?;
+
+        // Edges into the loser now point at the winner; the winner's own
+        // edges to the loser would become self-loops and are dropped
+        let graph_error = |e: verisim_graph::GraphError| HexadError::ModalityError {
+            modality: "graph".to_string(),
+            message: e.to_string(),
+        };
+        let winner_node = GraphNode::new(winner.to_iri(&self.config.base_iri));
+        let loser_node = GraphNode::new(loser.to_iri(&self.config.base_iri));
+        let mut rewritten_edges = 0;
+        for edge in self.graph.incoming(&loser_node).await.map_err(graph_error)? {
+            self.graph.delete(&edge).await.map_err(graph_error)?;
+            if edge.subject.iri == winner_node.iri || edge.subject.iri == loser_node.iri {
+                continue;
+            }
+            let rewritten = GraphEdge {
+                object: GraphObject::Node(winner_node.clone()),
+                ..edge
+            };
+            self.graph.insert(&rewritten).await.map_err(graph_error)?;
+            rewritten_edges += 1;
+        }
+        for edge in self.graph.outgoing(&loser_node).await.map_err(graph_error)? {
+            self.graph.delete(&edge).await.map_err(graph_error)?;
+        }
+
+        self.process_provenance(
+            loser,
+            &HexadProvenanceInput {
+                event_type: "merged".to_string(),
+                actor: actor.to_string(),
+                source: Some(winner.to_string()),
+                description: format!("Merged into {}", winner),
+                link: None,
+            },
+        )
+        .await?;
+        if loser_status.modality_status.spatial {
+            self.spatial.delete(loser.as_str()).await.ok();
+        }
+        // Version history and the provenance chain outlive the delete
+        self.delete(loser).await?;
+
+        let tombstone = HexadTombstone {
+            id: loser.clone(),
+            merged_into: winner.clone(),
+            last_version: loser_status.version,
+            merged_by: actor.to_string(),
+            merged_at: Utc::now(),
+        };
+        self.tombstones
+            .write()
+            .await
+            .insert(loser.to_string(), tombstone.clone());
+
+        let hexad = self
+            .load_hexad(winner)
+            .await?
+            .ok_or_else(|| HexadError::NotFound(winner.to_string()))?;
+        info!(winner = %winner, loser = %loser, rewritten_edges, "Merged hexads");
+        Ok(HexadMerge {
+            hexad,
+            tombstone,
+            rewritten_edges,
+        })
+    }
+
+    async fn tombstone(&self, id: &HexadId) -> Result<Option<HexadTombstone>, HexadError> {
+        Ok(self.tombstones.read().await.get(id.as_str()).cloned())
+    }
+
+    #[instrument(skip(self, input))]
+    async fn create_in(&self, collection: &str, input: HexadInput) -> Result<Hexad, HexadError> {
+        HexadId::validate_collection(collection)?;
+        self.write_and_notify(HexadId::generate_in(collection), input, None).await
+    }
+
+    async fn list_in(
+        &self,
+        collection: &str,
+        limit: usize,
+        offset: usize,
+        mask: ModalityMask,
+    ) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection_prefix(collection)?;
+        let mut ids: Vec<String> = self
+            .hexads
+            .read()
+            .await
+            .keys()
+            .filter(|id| id.starts_with(&prefix))
+            .cloned()
+            .collect();
+        // Sorted so that pages are stable
+        ids.sort();
+        let ids = self.retain_admitted(ids, String::as_str).await?;
+
+        let mut result = Vec::new();
+        for id_str in ids.into_iter().skip(offset).take(limit) {
+            if let Some(hexad) = self.load_hexad_with(&HexadId::new(&id_str), mask).await? {
+                result.push(hexad);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn search_similar_in(
+        &self,
+        collection: &str,
+        embedding: &[f32],
+        k: usize,
+    ) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection_prefix(collection)?;
+        let results = self
+            .vector
+            .search_prefixed(embedding, k, &prefix)
+            .await
+            .map_err(|e| HexadError::ModalityError {
+                modality: "vector".to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut hexads = Vec::new();
+        for result in results {
+            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
+                hexads.push(hexad);
+            }
+        }
+
+        Ok(hexads)
+    }
+
+    async fn search_text_in(&self, collection: &str, query: &str, limit: usize) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection_prefix(collection)?;
+        let results = self
+            .document
+            .search_prefixed(query, limit, &prefix)
+            .await
+            .map_err(|e| HexadError::ModalityError {
+                modality: "document".to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut hexads = Vec::new();
+        for result in results {
+            if let Some(hexad) = self.load_hexad(&HexadId::new(&result.id)).await? {
+                hexads.push(hexad);
+            }
+        }
+
+        Ok(hexads)
+    }
+
+    async fn collections(&self) -> Result<Vec<String>, HexadError> {
+        let names = |ids: &mut dyn Iterator<Item = &String>| -> BTreeSet<String> {
+            ids.filter_map(|id| id.split_once(COLLECTION_SEPARATOR))
+                .map(|(collection, _)| collection.to_string())
+                .collect()
+        };
+        let mut collections = names(&mut self.hexads.read().await.keys());
+        collections.extend(names(&mut self.deleted.read().await.keys()));
+        Ok(collections.into_iter().collect())
+    }
+
+    async fn collection_stats(&self, collection: &str) -> Result<CollectionStats, HexadError> {
+        let prefix = collection_prefix(collection)?;
+        let mut stats = CollectionStats {
+            name: collection.to_string(),
+            ..CollectionStats::default()
+        };
+        for status in self.hexads.read().await.values() {
+            if !status.id.as_str().starts_with(&prefix) {
+                continue;
+            }
+            stats.hexads += 1;
+            let m = &status.modality_status;
+            for (modality, present) in [
+                ("graph", m.graph),
+                ("vector", m.vector),
+                ("tensor", m.tensor),
+                ("semantic", m.semantic),
+                ("document", m.document),
+                ("temporal", m.temporal),
+                ("provenance", m.provenance),
+                ("spatial", m.spatial),
+            ] {
+                if present {
+                    *stats.modalities.entry(modality.to_string()).or_default() += 1;
+                }
+            }
+        }
+        stats.deleted = self
+            .deleted
+            .read()
+            .await
+            .keys()
+            .filter(|id| id.starts_with(&prefix))
+            .count();
+        Ok(stats)
+    }
+
+    async fn set_expiry(&self, id: &HexadId, expires_at: Option<DateTime<Utc>>) -> Result<HexadStatus, HexadError> {
+        self.check_writable(id).await?;
+        let mut hexads = self.hexads.write().await;
+        let status = hexads
+            .get_mut(id.as_str())
+            .ok_or_else(|| HexadError::NotFound(id.to_string()))?;
+        status.expires_at = expires_at;
+        Ok(status.clone())
+    }
+
+    #[instrument(skip(self))]
+    async fn expire_due(&self, now: DateTime<Utc>, actor: &str) -> Result<Vec<HexadId>, HexadError> {
+        let due: Vec<(HexadId, DateTime<Utc>)> = self
+            .hexads
+            .read()
+            .await
+            .values()
+            .filter_map(|status| status.expires_at.filter(|at| *at <= now).map(|at| (status.id.clone(), at)))
+            .collect();
+
+        let mut expired = Vec::with_capacity(due.len());
+        for (id, at) in due {
+            let event = HexadProvenanceInput {
+                event_type: "expired".to_string(),
+                actor: actor.to_string(),
+                source: None,
+                description: format!("Expired at {}", at.to_rfc3339()),
+                link: None,
+            };
+            match self.soft_delete_with(&id, event).await {
+                Ok(_) => expired.push(id),
+                // Deleted since the scan
+                Err(HexadError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !expired.is_empty() {
+            self.expired_total.fetch_add(expired.len() as u64, Ordering::Relaxed);
+            info!(count = expired.len(), "Expired hexads");
+        }
+        Ok(expired)
+    }
+
+    async fn expiry_stats(&self, now: DateTime<Utc>) -> Result<ExpiryStats, HexadError> {
+        let mut stats = ExpiryStats {
+            expired_total: self.expired_total.load(Ordering::Relaxed),
+            ..ExpiryStats::default()
+        };
+        for at in self.hexads.read().await.values().filter_map(|status| status.expires_at) {
+            stats.scheduled += 1;
+            if at <= now {
+                stats.overdue += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    async fn count(&self, filter: &CountFilter) -> Result<usize, HexadError> {
+        let prefix = filter.collection.as_deref().map(collection_prefix).transpose()?;
+        Ok(self
+            .hexads
+            .read()
+            .await
+            .values()
+            .filter(|status| prefix.as_ref().is_none_or(|p| status.id.as_str().starts_with(p.as_str())))
+            .filter(|status| status.modality_status.covers(&filter.modalities))
+            .count())
+    }
+
+    async fn estimated_counts(&self) -> Result<EstimatedCounts, HexadError> {
+        Ok(self.counts.lock().unwrap_or_else(std::sync::PoisonError::into_inner).clone())
+    }
+
+    async fn read_snapshot(&self) -> Result<ReadSnapshot, HexadError> {
+        let statuses: Vec<HexadStatus> = self.hexads.read().await.values().cloned().collect();
+        Ok(ReadSnapshot::new(Utc::now(), statuses))
+    }
 
-        let predicate_iri = format!("{}/{}", self.config.base_iri, predicate);
-        let mut hexads = Vec::new();
+    async fn get_at(&self, snapshot: &ReadSnapshot, id: &HexadId) -> Result<Option<Hexad>, HexadError> {
+        match snapshot.status(id) {
+            Some(status) if self.admits(id).await? => self.load_at(snapshot, status).await.map(Some),
+            _ => Ok(None),
+        }
+    }
 
-        for edge in edges {
-            if edge.predicate.iri == predicate_iri {
-                if let GraphObject::Node(target) = edge.object {
-                    // Extract ID from IRI
-                    let target_id = target
-                        .iri
-                        .strip_prefix(&format!("{}/", self.config.base_iri))
-                        .unwrap_or(&target.iri);
-
-                    if let Some(hexad) = self.load_hexad(&HexadId::new(target_id)).await? {
-                        hexads.push(hexad);
-                    }
-                }
+    async fn preview_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        id: &HexadId,
+        pending: Vec<HexadInput>,
+    ) -> Result<Option<Hexad>, HexadError> {
+        if snapshot.status(id).is_some() && !self.admits(id).await? {
+            return Ok(None);
+        }
+        let base = match snapshot.status(id) {
+            Some(status) => Some(self.load_at(snapshot, status).await?),
+            None if pending.is_empty() => return Ok(None),
+            None => None,
+        };
+        if pending.is_empty() {
+            return Ok(base);
+        }
+
+        let now = Utc::now();
+        let (mut input, mut status, mut provenance_chain_length) = match base {
+            Some(hexad) => (
+                self.current_input(id, hexad.status.version).await?,
+                hexad.status,
+                hexad.provenance_chain_length,
+            ),
+            None => (
+                HexadInput::default(),
+                HexadStatus {
+                    id: id.clone(),
+                    created_at: now,
+                    modified_at: now,
+                    version: 0,
+                    modality_status: ModalityStatus::default(),
+                    expires_at: None,
+                },
+                0,
+            ),
+        };
+        for update in pending {
+            if update.provenance.is_some() {
+                provenance_chain_length += 1;
+                status.modality_status.provenance = true;
             }
+            overlay(&mut input, update);
+            status.version += 1;
         }
+        status.modified_at = now;
+
+        let prepared = self.prepare(id, &input)?;
+        let modalities = &mut status.modality_status;
+        modalities.graph |= prepared.graph.is_some();
+        modalities.vector |= prepared.embedding.is_some();
+        modalities.tensor |= prepared.tensor.is_some();
+        modalities.semantic |= prepared.semantic.is_some();
+        modalities.document |= prepared.document.is_some();
+        modalities.spatial |= prepared.spatial.is_some();
+        modalities.temporal = true;
+        Ok(Some(Hexad {
+            id: id.clone(),
+            version_count: status.version,
+            status,
+            graph_node: prepared.graph.map(|(node, _)| node),
+            embedding: prepared.embedding,
+            tensor: prepared.tensor,
+            semantic: prepared.semantic,
+            document: prepared.document,
+            provenance_chain_length,
+            spatial_data: prepared.spatial,
+        }))
+    }
 
+    async fn list_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection.map(collection_prefix).transpose()?.unwrap_or_default();
+        let statuses: Vec<&HexadStatus> = if self.restricted().is_some() {
+            let statuses = snapshot.statuses_prefixed(&prefix).collect();
+            self.retain_admitted(statuses, |s| s.id.as_str()).await?.into_iter().skip(offset).take(limit).collect()
+        } else {
+            snapshot.statuses_prefixed(&prefix).skip(offset).take(limit).collect()
+        };
+        let mut hexads = Vec::with_capacity(statuses.len());
+        for status in statuses {
+            hexads.push(self.load_at(snapshot, status).await?);
+        }
         Ok(hexads)
     }
 
-    async fn list(&self, limit: usize, offset: usize) -> Result<Vec<Hexad>, HexadError> {
-        let hexads = self.hexads.read().await;
-        let ids: Vec<String> = hexads
-            .keys()
-            .skip(offset)
-            .take(limit)
-            .cloned()
-            .collect();
-        drop(hexads);
+    async fn search_text_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection.map(collection_prefix).transpose()?.unwrap_or_default();
+        self.load_hits_at(snapshot, limit, |fetch| {
+            let prefix = &prefix;
+            async move {
+                let results = self
+                    .document
+                    .search_prefixed(query, fetch, prefix)
+                    .await
+                    .map_err(|e| HexadError::ModalityError {
+                        modality: "document".to_string(),
+                        message: e.to_string(),
+                    })?;
+                Ok(results.into_iter().map(|r| r.id).collect())
+            }
+        })
+        .await
+    }
 
-        let mut result = Vec::with_capacity(ids.len());
-        for id_str in ids {
-            if let Some(hexad) = self.load_hexad(&HexadId::new(&id_str)).await? {
-                result.push(hexad);
+    async fn search_similar_at(
+        &self,
+        snapshot: &ReadSnapshot,
+        collection: Option<&str>,
+        embedding: &[f32],
+        k: usize,
+    ) -> Result<Vec<Hexad>, HexadError> {
+        let prefix = collection.map(collection_prefix).transpose()?.unwrap_or_default();
+        self.load_hits_at(snapshot, k, |fetch| {
+            let prefix = &prefix;
+            async move {
+                let results = self
+                    .vector
+                    .search_prefixed(embedding, fetch, prefix)
+                    .await
+                    .map_err(|e| HexadError::ModalityError {
+                        modality: "vector".to_string(),
+                        message: e.to_string(),
+                    })?;
+                Ok(results.into_iter().map(|r| r.id).collect())
             }
-        }
-        Ok(result)
+        })
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn purge_deleted_in(&self, collection: &str, before: DateTime<Utc>) -> Result<Vec<HexadId>, HexadError> {
+        HexadId::validate_collection(collection)?;
+        self.purge_deleted_by(|id| (id.collection() == Some(collection)).then_some(before))
+            .await
     }
 
     async fn at_time(&self, id: &HexadId, time: DateTime<Utc>) -> Result<Option<Hexad>, HexadError> {
+        if !self.admits(id).await? {
+            return Ok(None);
+        }
         let version = self
             .temporal
             .at_time(id.as_str(), time)
@@ -1196,20 +3541,166 @@ async fn at_time(&self, id: &HexadId, time: DateTime<Utc>) -> Result<Option<Hexa
             })?;
 
         match version {
-            Some(v) => {
-                // Reconstruct hexad from snapshot
-                // For now, we just return current state with version info
-                // A full implementation would restore from snapshot
-                let mut hexad = self.load_hexad(id).await?;
-                if let Some(ref mut h) = hexad {
-                    h.status.version = v.version;
-                    h.status.modified_at = v.timestamp;
-                }
-                Ok(hexad)
-            }
+            Some(version) => Ok(Some(self.load_version(id, version, time).await?)),
             None => Ok(None),
         }
     }
+
+    async fn versions_between(&self, id: &HexadId, range: &TimeRange) -> Result<Vec<Hexad>, HexadError> {
+        if !self.admits(id).await? {
+            return Ok(Vec::new());
+        }
+        let versions = self
+            .temporal
+            .in_range(id.as_str(), range)
+            .await
+            .map_err(|e| HexadError::ModalityError {
+                modality: "temporal".to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut hexads = Vec::with_capacity(versions.len());
+        for version in versions {
+            let as_of = version.timestamp;
+            hexads.push(self.load_version(id, version, as_of).await?);
+        }
+        Ok(hexads)
+    }
+}
+
+/// Modalities a write of `input` locks: those it populates, plus temporal
+/// for the version snapshot
+fn input_modalities(input: &HexadInput) -> Vec<&'static str> {
+    [
+        input.graph.as_ref().map(|_| "graph"),
+        input.vector.as_ref().map(|_| "vector"),
+        input.document.as_ref().map(|_| "document"),
+        input.tensor.as_ref().map(|_| "tensor"),
+        input.semantic.as_ref().map(|_| "semantic"),
+        input.provenance.as_ref().map(|_| "provenance"),
+        input.spatial.as_ref().map(|_| "spatial"),
+        Some("temporal"), // Always written (version snapshot)
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Document of a hexad
+fn build_document(id: &HexadId, input: &HexadDocumentInput) -> Document {
+    let mut doc = Document::new(id.as_str(), &input.title, &input.body);
+    for (key, value) in &input.fields {
+        doc = doc.with_field(key, value);
+    }
+    doc
+}
+
+/// Tensor of a hexad, checked against its shape
+fn build_tensor(id: &HexadId, input: &HexadTensorInput) -> Result<Tensor, HexadError> {
+    Tensor::new(id.as_str(), input.shape.clone(), input.data.clone()).map_err(|e| HexadError::ModalityError {
+        modality: "tensor".to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Semantic annotation of a hexad; properties become string literals
+fn build_semantic(id: &HexadId, input: &HexadSemanticInput) -> SemanticAnnotation {
+    let mut properties = HashMap::new();
+    for (key, value) in &input.properties {
+        properties.insert(
+            key.clone(),
+            SemanticValue::TypedLiteral {
+                value: value.clone(),
+                datatype: "https://www.w3.org/2001/XMLSchema#string".to_string(),
+            },
+        );
+    }
+
+    SemanticAnnotation {
+        entity_id: id.as_str().to_string(),
+        types: input.types.clone(),
+        properties,
+        provenance: Provenance::default(),
+    }
+}
+
+/// Spatial data of a hexad, from a geometry, WKT/WKB or coordinates
+fn build_spatial(input: &HexadSpatialInput) -> Result<SpatialData, HexadError> {
+    let (geometry, encoded_srid) = if let Some(geometry) = &input.geometry {
+        (Some(geometry.clone()), None)
+    } else if let Some(wkt) = &input.wkt {
+        let (geometry, srid) = verisim_spatial::wkt::parse_ewkt(wkt)
+            .map_err(|e| HexadError::ValidationError(e.to_string()))?;
+        (Some(geometry), srid)
+    } else if let Some(wkb) = &input.wkb {
+        let (geometry, srid) = verisim_spatial::wkt::parse_ewkb_hex(wkb)
+            .map_err(|e| HexadError::ValidationError(e.to_string()))?;
+        (Some(geometry), srid)
+    } else {
+        (None, None)
+    };
+    let srid = input.srid.or(encoded_srid).unwrap_or(4326);
+
+    let mut data = if let Some(geometry) = geometry {
+        SpatialData::from_geometry(geometry, srid)
+            .map_err(|e| HexadError::ValidationError(e.to_string()))?
+    } else {
+        let coordinates = Coordinates::new(input.latitude, input.longitude, input.altitude)
+            .map_err(|e| HexadError::ValidationError(e.to_string()))?;
+
+        let geometry_type = match input.geometry_type.as_deref() {
+            Some("LineString") => GeometryType::LineString,
+            Some("Polygon") => GeometryType::Polygon,
+            Some("MultiPoint") => GeometryType::MultiPoint,
+            Some("MultiPolygon") => GeometryType::MultiPolygon,
+            _ => GeometryType::Point,
+        };
+
+        SpatialData::with_geometry(coordinates, geometry_type, srid)
+    };
+    data.properties = input.properties.clone();
+    Ok(data)
+}
+
+
+/// Fold one version snapshot into the state accumulated from earlier ones.
+/// Relationships accumulate; other modalities take the latest value, and
+/// modalities the snapshot records as absent are cleared.
+fn apply_snapshot(state: &mut HexadInput, snapshot: HexadSnapshot) {
+    let input = snapshot.input;
+    if let Some(graph) = input.graph {
+        let relationships = &mut state.graph.get_or_insert_with(|| HexadGraphInput { relationships: Vec::new() }).relationships;
+        for relationship in graph.relationships {
+            if !relationships.contains(&relationship) {
+                relationships.push(relationship);
+            }
+        }
+    }
+    state.vector = input.vector.or(state.vector.take());
+    state.tensor = input.tensor.or(state.tensor.take());
+    state.semantic = input.semantic.or(state.semantic.take());
+    state.document = input.document.or(state.document.take());
+    state.spatial = input.spatial.or(state.spatial.take());
+
+    let status = snapshot.modality_status;
+    if !status.graph {
+        state.graph = None;
+    }
+    if !status.vector {
+        state.vector = None;
+    }
+    if !status.tensor {
+        state.tensor = None;
+    }
+    if !status.semantic {
+        state.semantic = None;
+    }
+    if !status.document {
+        state.document = None;
+    }
+    if !status.spatial {
+        state.spatial = None;
+    }
 }
 
 #[cfg(test)]
@@ -1342,4 +3833,978 @@ async fn test_update_hexad() {
         assert_eq!(updated.status.version, 2);
         assert!(updated.document.as_ref().unwrap().title.contains("Updated"));
     }
+
+    #[tokio::test]
+    async fn test_create_batch_reports_per_item_results() {
+        let store = create_test_store();
+
+        let results = store
+            .create_batch(vec![
+                HexadBuilder::new()
+                    .with_document("Batch one", "Grouped tantivy writes")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .with_relationships(vec![("cites", "paper-1")])
+                    .build(),
+                HexadBuilder::new().with_embedding(vec![0.1, 0.2]).build(),
+                HexadBuilder::new().with_document("Batch three", "Grouped tantivy writes").build(),
+            ])
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[1], Err(HexadError::ValidationError(_))));
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.status.version, 1);
+        assert!(first.status.modality_status.graph && first.status.modality_status.vector);
+        assert!(store.get(&results[2].as_ref().unwrap().id).await.unwrap().is_some());
+        assert_eq!(store.search_text("grouped", 10).await.unwrap().len(), 2);
+        assert_eq!(store.list(10, 0).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_batch() {
+        let store = create_test_store();
+        let created: Vec<Hexad> = store
+            .create_batch(vec![
+                HexadBuilder::new().with_document("A", "first").build(),
+                HexadBuilder::new().with_document("B", "second").build(),
+            ])
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let (a, b) = (created[0].id.clone(), created[1].id.clone());
+
+        let results = store
+            .update_batch(vec![
+                (a.clone(), HexadBuilder::new().with_document("A", "revised").build()),
+                (HexadId::new("missing"), HexadBuilder::new().with_document("C", "third").build()),
+                (a.clone(), HexadBuilder::new().with_document("A", "again").build()),
+            ])
+            .await;
+        assert_eq!(results[0].as_ref().unwrap().status.version, 2);
+        assert!(matches!(results[1], Err(HexadError::NotFound(_))));
+        assert!(matches!(results[2], Err(HexadError::ValidationError(_))));
+        assert_eq!(store.get(&a).await.unwrap().unwrap().document.unwrap().body, "revised");
+
+        let results = store.delete_batch(&[a.clone(), HexadId::new("missing"), b.clone()]).await;
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[1], Err(HexadError::NotFound(_))));
+        assert!(store.get(&a).await.unwrap().is_none());
+        assert!(store.get(&b).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_masked_reads_skip_unrequested_modalities() {
+        let store = create_test_store();
+        let hexad = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Masked", "Only what was asked for")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .with_tensor(vec![2], vec![1.0, 2.0])
+                    .with_provenance("created", "tester", "masked read")
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let status = store.get_with(&hexad.id, ModalityMask::STATUS).await.unwrap().unwrap();
+        assert!(status.status.modality_status.document && status.status.modality_status.tensor);
+        assert!(status.document.is_none() && status.tensor.is_none() && status.embedding.is_none());
+        assert_eq!((status.version_count, status.provenance_chain_length), (0, 0));
+
+        let mask = ModalityMask {
+            document: true,
+            ..ModalityMask::SUMMARY
+        };
+        let partial = store.get_with(&hexad.id, mask).await.unwrap().unwrap();
+        assert_eq!(partial.document.unwrap().title, "Masked");
+        assert!(partial.tensor.is_none());
+        assert_eq!((partial.version_count, partial.provenance_chain_length), (1, 1));
+
+        let listed = store.list_with(10, 0, ModalityMask::SUMMARY).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].embedding.is_none());
+        assert_eq!(listed[0].version_count, 1);
+        assert!(store.get(&hexad.id).await.unwrap().unwrap().tensor.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_merge_rewrites_edges_and_tombstones_loser() {
+        let store = create_test_store();
+        let winner = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Ada Lovelace", "Canonical record")
+                    .with_types(vec!["Person"])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let loser = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("A. Lovelace", "Imported duplicate")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .with_types(vec!["Mathematician"])
+                    .with_relationships(vec![("wrote", "notes")])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let citing = store
+            .create(HexadBuilder::new().with_relationships(vec![("cites", loser.id.as_str())]).build())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store.merge(&winner.id, &winner.id, &MergePolicy::default(), "tester").await,
+            Err(HexadError::ValidationError(_))
+        ));
+        let merge = store
+            .merge(&winner.id, &loser.id, &MergePolicy::default(), "tester")
+            .await
+            .unwrap();
+        assert_eq!(merge.rewritten_edges, 1);
+        assert_eq!(merge.hexad.status.version, 2);
+        assert_eq!(merge.hexad.document.as_ref().unwrap().title, "Ada Lovelace");
+        assert!(merge.hexad.embedding.is_some());
+
+        assert!(store.get(&loser.id).await.unwrap().is_none());
+        let tombstone = store.tombstone(&loser.id).await.unwrap().unwrap();
+        assert_eq!((tombstone.merged_into, tombstone.last_version), (winner.id.clone(), 1));
+
+        let related = store.query_related(&citing.id, "cites").await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].id, winner.id);
+        assert_eq!(store.query_related(&winner.id, "wrote").await.unwrap().len(), 0);
+        let winner_node = GraphNode::new(winner.id.to_iri(&store.config.base_iri));
+        assert_eq!(store.graph.outgoing(&winner_node).await.unwrap().len(), 1);
+
+        for id in [&winner.id, &loser.id] {
+            let chain = store.provenance.get_chain(id.as_str()).await.unwrap();
+            assert_eq!(chain.records.last().unwrap().event_type, ProvenanceEventType::Merged);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_hides_and_restore_reindexes() {
+        let store = create_test_store();
+        let hexad = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Audit trail", "Soft deleted content")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let deleted = store.soft_delete(&hexad.id, "auditor").await.unwrap();
+        assert_eq!(deleted.deleted_by, "auditor");
+        assert!(store.get(&hexad.id).await.unwrap().is_none());
+        assert!(store.list(10, 0).await.unwrap().is_empty());
+        assert!(store.search_text("audit", 10).await.unwrap().is_empty());
+        assert!(store.search_similar(&[0.1, 0.2, 0.3], 5).await.unwrap().is_empty());
+        assert_eq!(store.deleted(10, 0).await.unwrap().len(), 1);
+        assert!(matches!(store.update(&hexad.id, HexadInput::default()).await, Err(HexadError::NotFound(_))));
+
+        let restored = store.restore(&hexad.id, "auditor").await.unwrap();
+        assert_eq!(restored.status.version, 2);
+        assert_eq!(restored.document.unwrap().title, "Audit trail");
+        assert_eq!(store.search_text("audit", 10).await.unwrap()[0].id, hexad.id);
+        assert!(store.deleted(10, 0).await.unwrap().is_empty());
+        let events: Vec<ProvenanceEventType> = store
+            .provenance
+            .get_chain(hexad.id.as_str())
+            .await
+            .unwrap()
+            .records
+            .into_iter()
+            .map(|r| r.event_type)
+            .collect();
+        assert_eq!(
+            events,
+            vec![ProvenanceEventType::Deleted, ProvenanceEventType::Custom("restored".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_snapshots_state_and_truncates_wal() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(create_test_store().checkpoint().await.is_err());
+        let store = create_test_store().with_wal(dir.path(), SyncMode::Async).unwrap();
+
+        let kept = store.create(HexadBuilder::new().with_document("Draft", "v1").build()).await.unwrap();
+        store.update(&kept.id, HexadBuilder::new().with_document("Final", "v2").build()).await.unwrap();
+        let gone = store.create(HexadBuilder::new().with_document("Gone", "deleted").build()).await.unwrap();
+        store.soft_delete(&gone.id, "auditor").await.unwrap();
+        assert!(store.wal_lag().await.unwrap().unwrap().entries_since_checkpoint > 0);
+
+        let report = store.checkpoint().await.unwrap();
+        assert_eq!((report.hexads, report.deleted), (1, 1));
+        assert_eq!(report.segments_removed, 1);
+
+        let lag = store.wal_lag().await.unwrap().unwrap();
+        assert_eq!(lag.checkpoint_sequence, Some(report.sequence));
+        assert_eq!(lag.entries_since_checkpoint, 0);
+        assert_eq!(lag.segment_count, 1);
+
+        let snapshot = StoreSnapshot::read_latest(dir.path()).unwrap().unwrap();
+        assert_eq!(snapshot.sequence, report.sequence);
+        assert_eq!(snapshot.hexads[0].status.version, 2);
+        assert_eq!(snapshot.hexads[0].input.document.as_ref().unwrap().title, "Final");
+        assert_eq!(snapshot.deleted[0].deleted.status.id, gone.id);
+
+        store.create(HexadBuilder::new().with_document("Later", "after").build()).await.unwrap();
+        assert!(store.wal_lag().await.unwrap().unwrap().entries_since_checkpoint > 0);
+    }
+
+    #[tokio::test]
+    async fn test_recover_to_rolls_back_changes_after_target() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = create_test_store()
+            .with_wal(dir.path(), SyncMode::Async)
+            .unwrap()
+            .with_checkpoint_retention(3);
+
+        let edited = store.create(HexadBuilder::new().with_document("Good", "v1").build()).await.unwrap();
+        let removed = store.create(HexadBuilder::new().with_document("Removed", "kept").build()).await.unwrap();
+        store.checkpoint().await.unwrap();
+        store.update(&edited.id, HexadBuilder::new().with_document("Still good", "v2").build()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let target = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        // The bad import
+        store.update(&edited.id, HexadBuilder::new().with_document("Bad", "v3").build()).await.unwrap();
+        let imported = store.create(HexadBuilder::new().with_document("Imported", "junk").build()).await.unwrap();
+        store.delete(&removed.id).await.unwrap();
+        store.checkpoint().await.unwrap();
+
+        let preview = store.recover_to(target, true, "operator").await.unwrap();
+        assert_eq!(preview.rolled_back.len(), 2);
+        assert_eq!(preview.rolled_forward.len(), 1);
+        assert_eq!(store.get(&edited.id).await.unwrap().unwrap().document.unwrap().title, "Bad");
+
+        let report = store.recover_to(target, false, "operator").await.unwrap();
+        assert!(report.rolled_back.iter().chain(&report.rolled_forward).all(|r| r.error.is_none()));
+        let back = report.rolled_back.iter().find(|r| r.id == edited.id).unwrap();
+        assert_eq!((back.current_version, back.target_version), (Some(3), Some(2)));
+        assert!(report.rolled_back.iter().any(|r| r.id == imported.id && r.target_version.is_none()));
+        assert!(report.rolled_forward.iter().any(|r| r.id == removed.id && r.current_version.is_none()));
+
+        assert_eq!(store.get(&edited.id).await.unwrap().unwrap().document.unwrap().title, "Still good");
+        assert!(store.get(&imported.id).await.unwrap().is_none());
+        assert_eq!(store.get(&removed.id).await.unwrap().unwrap().document.unwrap().title, "Removed");
+
+        // Recovering again finds nothing to change
+        let again = store.recover_to(target, true, "operator").await.unwrap();
+        assert!(again.rolled_back.is_empty() && again.rolled_forward.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_incremental_backups_restore_into_a_fresh_store() {
+        let wal = tempfile::TempDir::new().unwrap();
+        let target = tempfile::TempDir::new().unwrap();
+        let store = create_test_store().with_wal(wal.path(), SyncMode::Async).unwrap();
+        // Nothing to continue yet
+        assert!(store.backup(target.path(), BackupKind::Incremental).await.is_err());
+
+        let sensor = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Sensor", "v1")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .with_provenance("created", "alice", "Installed")
+                    .build(),
+            )
+            .await
+            .unwrap();
+        store
+            .update(&sensor.id, HexadBuilder::new().with_provenance("modified", "bob", "Calibrated").build())
+            .await
+            .unwrap();
+        let gone = store.create(HexadBuilder::new().with_document("Gone", "deleted").build()).await.unwrap();
+        store.soft_delete(&gone.id, "auditor").await.unwrap();
+        let full = store.backup(target.path(), BackupKind::Full).await.unwrap();
+        assert_eq!(full.snapshot_sequence, Some(full.sequence));
+
+        // The store files keep statuses, version histories and provenance
+        // chains as they were
+        let fresh = create_test_store();
+        fresh.restore_backup(target.path(), &full.id, "operator").await.unwrap();
+        let original = store.get(&sensor.id).await.unwrap().unwrap();
+        let restored = fresh.get(&sensor.id).await.unwrap().unwrap();
+        assert_eq!(restored.status.version, 2);
+        assert_eq!(restored.status.created_at, original.status.created_at);
+        assert_eq!(restored.status.modified_at, original.status.modified_at);
+        let versions = fresh.temporal.history(sensor.id.as_str(), 10).await.unwrap();
+        let original_versions = store.temporal.history(sensor.id.as_str(), 10).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[1].timestamp, original_versions[1].timestamp);
+        let chain = fresh.provenance.get_chain(sensor.id.as_str()).await.unwrap();
+        let original_chain = store.provenance.get_chain(sensor.id.as_str()).await.unwrap();
+        assert_eq!(chain.records.len(), 2);
+        assert_eq!(chain.latest().unwrap().content_hash, original_chain.latest().unwrap().content_hash);
+        assert_eq!(fresh.provenance.get_chain(gone.id.as_str()).await.unwrap().latest().unwrap().actor, "auditor");
+        assert!(fresh.get(&gone.id).await.unwrap().is_none());
+
+        store.update(&sensor.id, HexadBuilder::new().with_document("Sensor", "v2").build()).await.unwrap();
+        let added = store.create(HexadBuilder::new().with_document("Added", "v1").build()).await.unwrap();
+        let first = store.backup(target.path(), BackupKind::Incremental).await.unwrap();
+        assert_eq!(first.parent.as_deref(), Some(full.id.as_str()));
+        assert!(first.snapshot_sequence.is_none() && first.wal_segments > 0);
+
+        // A checkpoint truncates the WAL the next backup continues from
+        store.update(&added.id, HexadBuilder::new().with_document("Added", "v2").build()).await.unwrap();
+        store.checkpoint().await.unwrap();
+        store.update(&sensor.id, HexadBuilder::new().with_document("Sensor", "v3").build()).await.unwrap();
+        let second = store.backup(target.path(), BackupKind::Incremental).await.unwrap();
+        assert_eq!(second.parent.as_deref(), Some(first.id.as_str()));
+        assert!(second.snapshot_sequence.is_some_and(|s| s > first.sequence));
+
+        let fresh = create_test_store();
+        let report = fresh.restore_backup(target.path(), &first.id, "operator").await.unwrap();
+        assert_eq!((report.hexads, report.deleted, report.writes_replayed), (1, 1, 2));
+        assert!(report.failed.is_empty());
+        assert!(report.sequence > full.sequence && report.sequence <= first.sequence);
+        // The archived WAL extends the archived history
+        let restored = fresh.get(&sensor.id).await.unwrap().unwrap();
+        assert_eq!(restored.status.version, 3);
+        assert_eq!(restored.status.created_at, original.status.created_at);
+        assert_eq!(restored.document.unwrap().body, "v2");
+        assert!(fresh.provenance.verify_chain(sensor.id.as_str()).await.unwrap());
+        assert_eq!(fresh.get(&sensor.id).await.unwrap().unwrap().embedding.unwrap().vector, vec![0.1, 0.2, 0.3]);
+        assert_eq!(fresh.get(&added.id).await.unwrap().unwrap().document.unwrap().body, "v1");
+        assert!(fresh.get(&gone.id).await.unwrap().is_none());
+        // Only ever into an empty store
+        assert!(matches!(
+            fresh.restore_backup(target.path(), &full.id, "operator").await,
+            Err(HexadError::ValidationError(_))
+        ));
+
+        let fresh = create_test_store();
+        fresh.restore_backup(target.path(), &second.id, "operator").await.unwrap();
+        assert_eq!(fresh.get(&sensor.id).await.unwrap().unwrap().document.unwrap().body, "v3");
+        assert_eq!(fresh.get(&added.id).await.unwrap().unwrap().document.unwrap().body, "v2");
+    }
+
+    #[tokio::test]
+    async fn test_put_reproduces_an_entity_under_its_id() {
+        let source = create_test_store();
+        let original = source
+            .create(HexadBuilder::new().with_document("Replicated", "first").with_embedding(vec![1.0, 0.0, 0.0]).build())
+            .await
+            .unwrap();
+        source
+            .update(&original.id, HexadBuilder::new().with_document("Replicated", "second").build())
+            .await
+            .unwrap();
+        let input = source.input_at(&original.id, 2).await.unwrap();
+        assert_eq!(input.document.as_ref().unwrap().body, "second");
+        assert!(input.vector.is_some());
+
+        let replica = create_test_store();
+        let copy = replica.put(&original.id, input.clone()).await.unwrap();
+        assert_eq!(copy.id, original.id);
+        assert_eq!(copy.document.as_ref().unwrap().body, "second");
+        assert!(copy.embedding.is_some());
+        let again = replica.put(&original.id, input).await.unwrap();
+        assert_eq!(again.status.version, 2);
+        assert_eq!(replica.list(10, 0).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_deleted_hard_deletes_expired() {
+        let store = create_test_store();
+        let old = store.create(HexadBuilder::new().with_document("Old", "expired").build()).await.unwrap();
+        store.soft_delete(&old.id, "auditor").await.unwrap();
+        let cutoff = Utc::now();
+        let recent = store.create(HexadBuilder::new().with_document("Recent", "kept").build()).await.unwrap();
+        store.soft_delete(&recent.id, "auditor").await.unwrap();
+
+        assert_eq!(store.purge_deleted(cutoff).await.unwrap(), vec![old.id.clone()]);
+        assert!(matches!(store.restore(&old.id, "auditor").await, Err(HexadError::NotFound(_))));
+        assert!(store.get(&old.id).await.unwrap().is_none());
+        assert_eq!(store.deleted(10, 0).await.unwrap()[0].status.id, recent.id);
+    }
+
+    #[tokio::test]
+    async fn test_collections_are_isolated() {
+        let store = create_test_store();
+        let alice = store
+            .create_in(
+                "people",
+                HexadBuilder::new()
+                    .with_document("Alice", "Rust engineer")
+                    .with_embedding(vec![1.0, 0.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let paper = store
+            .create_in(
+                "papers",
+                HexadBuilder::new()
+                    .with_document("Rust paper", "Rust type systems")
+                    .with_embedding(vec![0.9, 0.1, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        store.create(HexadBuilder::new().with_document("Loose", "Rust").build()).await.unwrap();
+        assert_eq!(alice.id.collection(), Some("people"));
+
+        let people = store.list_in("people", 10, 0, ModalityMask::ALL).await.unwrap();
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].id, alice.id);
+        let hits = store.search_text_in("papers", "rust", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, paper.id);
+        let hits = store.search_similar_in("people", &[0.9, 0.1, 0.0], 5).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, alice.id);
+        assert_eq!(store.collections().await.unwrap(), vec!["papers".to_string(), "people".to_string()]);
+
+        store.soft_delete(&paper.id, "curator").await.unwrap();
+        let stats = store.collection_stats("papers").await.unwrap();
+        assert_eq!((stats.hexads, stats.deleted), (0, 1));
+        let stats = store.collection_stats("people").await.unwrap();
+        assert_eq!((stats.hexads, stats.deleted), (1, 0));
+        assert_eq!(stats.modalities.get("vector"), Some(&1));
+
+        assert!(store.purge_deleted_in("people", Utc::now()).await.unwrap().is_empty());
+        assert_eq!(store.purge_deleted_in("papers", Utc::now()).await.unwrap(), vec![paper.id]);
+        assert!(matches!(store.create_in("bad:name", HexadInput::default()).await, Err(HexadError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_at_time_and_versions_between_rebuild_history() {
+        let store = create_test_store();
+        let before = Utc::now();
+        let hexad = store.create(HexadBuilder::new().with_document("Draft", "v1").build()).await.unwrap();
+        let between = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        store
+            .update(&hexad.id, HexadBuilder::new().with_document("Final", "v2").build())
+            .await
+            .unwrap();
+
+        assert!(store.at_time(&hexad.id, before - chrono::Duration::seconds(1)).await.unwrap().is_none());
+        let then = store.at_time(&hexad.id, between).await.unwrap().unwrap();
+        assert_eq!(then.status.version, 1);
+        assert_eq!(then.document.unwrap().title, "Draft");
+
+        let range = TimeRange::new(before, Utc::now()).unwrap();
+        let versions = store.versions_between(&hexad.id, &range).await.unwrap();
+        let titles: Vec<String> = versions.into_iter().map(|h| h.document.unwrap().title).collect();
+        assert_eq!(titles, vec!["Draft", "Final"]);
+        let range = TimeRange::new(before, between).unwrap();
+        assert_eq!(store.versions_between(&hexad.id, &range).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_snapshot_is_unaffected_by_later_writes() {
+        let store = create_test_store();
+        let alice = store
+            .create_in(
+                "people",
+                HexadBuilder::new()
+                    .with_document("Alice", "Rust engineer")
+                    .with_embedding(vec![1.0, 0.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let bob = store.create_in("people", HexadBuilder::new().with_document("Bob", "Rust").build()).await.unwrap();
+        let snapshot = store.read_snapshot().await.unwrap();
+        assert_eq!(snapshot.len(), 2);
+
+        store
+            .update(
+                &alice.id,
+                HexadBuilder::new()
+                    .with_document("Alice", "Rust manager")
+                    .with_embedding(vec![0.0, 1.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        store.delete(&bob.id).await.unwrap();
+        store.create_in("people", HexadBuilder::new().with_document("Carol", "Rust").build()).await.unwrap();
+
+        let listed = store.list_at(&snapshot, Some("people"), 10, 0).await.unwrap();
+        let ids: Vec<&HexadId> = listed.iter().map(|h| &h.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&&alice.id) && ids.contains(&&bob.id));
+        let then = store.get_at(&snapshot, &alice.id).await.unwrap().unwrap();
+        assert_eq!(then.status.version, 1);
+        assert_eq!(then.document.unwrap().body, "Rust engineer");
+        assert_eq!(then.embedding.unwrap().vector, vec![1.0, 0.0, 0.0]);
+        assert_eq!(store.get(&alice.id).await.unwrap().unwrap().status.version, 2);
+        assert_eq!(store.list_at(&snapshot, Some("people"), 10, 1).await.unwrap().len(), 1);
+
+        // Hits outside the snapshot are dropped, the rest returned as they were
+        let hits = store.search_text_at(&snapshot, None, "rust", 10).await.unwrap();
+        assert!(hits.iter().all(|h| h.id == alice.id || h.id == bob.id));
+        let then = hits.iter().find(|h| h.id == alice.id).unwrap();
+        assert_eq!(then.document.as_ref().unwrap().body, "Rust engineer");
+        let hits = store.search_similar_at(&snapshot, Some("people"), &[0.0, 1.0, 0.0], 1).await.unwrap();
+        assert_eq!(hits[0].id, alice.id);
+        assert_eq!(hits[0].embedding.as_ref().unwrap().vector, vec![1.0, 0.0, 0.0]);
+    }
+
+    #[tokio::test]
+    async fn test_preview_at_overlays_pending_updates_on_snapshot() {
+        let store = create_test_store();
+        let alice = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Alice", "Rust engineer")
+                    .with_embedding(vec![1.0, 0.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let snapshot = store.read_snapshot().await.unwrap();
+        store
+            .update(&alice.id, HexadBuilder::new().with_document("Alice", "Committed later").build())
+            .await
+            .unwrap();
+
+        // Pending updates apply over the snapshot, not over later commits
+        let pending = vec![HexadBuilder::new().with_document("Alice", "Pending edit").build()];
+        let preview = store.preview_at(&snapshot, &alice.id, pending).await.unwrap().unwrap();
+        assert_eq!(preview.document.unwrap().body, "Pending edit");
+        assert_eq!(preview.embedding.unwrap().vector, vec![1.0, 0.0, 0.0]);
+        assert_eq!(preview.status.version, 2);
+        assert_eq!(store.get(&alice.id).await.unwrap().unwrap().document.unwrap().body, "Committed later");
+
+        let unseen = HexadId::new("not-yet-created");
+        assert!(store.preview_at(&snapshot, &unseen, Vec::new()).await.unwrap().is_none());
+        let pending = vec![HexadBuilder::new().with_document("Bob", "New").build()];
+        let created = store.preview_at(&snapshot, &unseen, pending).await.unwrap().unwrap();
+        assert_eq!(created.status.version, 1);
+        assert!(created.status.modality_status.document && !created.status.modality_status.vector);
+        assert!(store.get(&unseen).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_counts_track_writes_and_deletes() {
+        let store = create_test_store();
+        let alice = store
+            .create_in(
+                "people",
+                HexadBuilder::new()
+                    .with_document("Alice", "Rust engineer")
+                    .with_embedding(vec![1.0, 0.0, 0.0])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        store.create_in("people", HexadBuilder::new().with_document("Bob", "Go").build()).await.unwrap();
+        let loose = store.create(HexadBuilder::new().with_document("Loose", "Rust").build()).await.unwrap();
+        store.update(&loose.id, HexadBuilder::new().with_document("Loose", "v2").build()).await.unwrap();
+        let batch = store
+            .create_batch(vec![HexadBuilder::new().with_document("Batch", "one").build()])
+            .await;
+        store.delete_batch(&[batch[0].as_ref().unwrap().id.clone()]).await;
+
+        let all = CountFilter::default();
+        assert_eq!(store.count(&all).await.unwrap(), 3);
+        let people = CountFilter { collection: Some("people".to_string()), ..CountFilter::default() };
+        assert_eq!(store.count(&people).await.unwrap(), 2);
+        let with_vectors = CountFilter {
+            modalities: ModalityStatus { vector: true, ..ModalityStatus::default() },
+            ..people.clone()
+        };
+        assert_eq!(store.count(&with_vectors).await.unwrap(), 1);
+        let estimated = store.estimated_counts().await.unwrap();
+        assert_eq!((estimated.hexads, estimated.deleted), (3, 0));
+        assert_eq!(estimated.collections.get("people"), Some(&2));
+
+        store.soft_delete(&alice.id, "curator").await.unwrap();
+        let estimated = store.estimated_counts().await.unwrap();
+        assert_eq!((estimated.hexads, estimated.deleted), (2, 1));
+        store.restore(&alice.id, "curator").await.unwrap();
+        assert_eq!(store.estimated_counts().await.unwrap().deleted, 0);
+        store.soft_delete(&alice.id, "curator").await.unwrap();
+        store.purge_deleted(Utc::now()).await.unwrap();
+        store.delete(&loose.id).await.unwrap();
+
+        let estimated = store.estimated_counts().await.unwrap();
+        assert_eq!((estimated.hexads, estimated.deleted), (1, 0));
+        assert_eq!(estimated.collections.get("people"), Some(&1));
+        assert_eq!(store.count(&all).await.unwrap(), estimated.hexads);
+        let bad = CountFilter { collection: Some("a b".to_string()), ..CountFilter::default() };
+        assert!(matches!(store.count(&bad).await, Err(HexadError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expire_due_soft_deletes_expired_hexads() {
+        let store = create_test_store();
+        let scratch = store.create(HexadBuilder::new().with_document("Scratch", "temp").build()).await.unwrap();
+        let kept = store.create(HexadBuilder::new().with_document("Kept", "later").build()).await.unwrap();
+        let now = Utc::now();
+        store.set_expiry(&scratch.id, Some(now - chrono::Duration::seconds(1))).await.unwrap();
+        store.set_expiry(&kept.id, Some(now + chrono::Duration::hours(1))).await.unwrap();
+
+        // The expiry survives updates
+        store.update(&scratch.id, HexadBuilder::new().with_document("Scratch", "v2").build()).await.unwrap();
+        let stats = store.expiry_stats(now).await.unwrap();
+        assert_eq!((stats.scheduled, stats.overdue, stats.expired_total), (2, 1, 0));
+
+        assert_eq!(store.expire_due(now, "ttl-sweeper").await.unwrap(), vec![scratch.id.clone()]);
+        assert!(store.get(&scratch.id).await.unwrap().is_none());
+        assert!(store.get(&kept.id).await.unwrap().is_some());
+        let chain = store.provenance.get_chain(scratch.id.as_str()).await.unwrap();
+        let last = chain.records.last().unwrap();
+        assert_eq!(last.event_type, ProvenanceEventType::Custom("expired".to_string()));
+        assert_eq!(last.actor, "ttl-sweeper");
+        assert_eq!(store.expiry_stats(now).await.unwrap().expired_total, 1);
+
+        // Restoring drops the lapsed expiry
+        let restored = store.restore(&scratch.id, "curator").await.unwrap();
+        assert!(restored.status.expires_at.is_none());
+        assert!(store.expire_due(Utc::now(), "ttl-sweeper").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verify_consistency_reports_modality_mismatches() {
+        let store = create_test_store();
+        let input = || {
+            HexadBuilder::new()
+                .with_document("Checked", "consistent entity")
+                .with_embedding(vec![0.1, 0.2, 0.3])
+                .with_relationships(vec![("cites", "other")])
+                .with_types(vec!["https://example.org/Paper"])
+                .with_provenance("created", "tester", "seeded")
+                .build()
+        };
+        let healthy = store.create(input()).await.unwrap();
+        let broken = store.create(input()).await.unwrap();
+
+        let report = store.verify_consistency().await.unwrap();
+        assert_eq!(report.entities_checked, 2);
+        assert!(report.inconsistent.is_empty(), "{:?}", report.inconsistent);
+
+        store.vector.delete(broken.id.as_str()).await.unwrap();
+        store.document.delete(broken.id.as_str()).await.unwrap();
+        let report = store.verify_consistency().await.unwrap();
+        assert_eq!(report.inconsistent.len(), 1);
+        assert_eq!(report.failing(ConsistencyCheck::Embedding), vec![broken.id.to_string()]);
+        assert_eq!(report.failure_rate(ConsistencyCheck::Document), 0.5);
+        assert_eq!(report.failure_rate(ConsistencyCheck::Graph), 0.0);
+
+        assert!(store.verify_entity(&healthy.id).await.unwrap().unwrap().is_consistent());
+        let entity = store.verify_entity(&broken.id).await.unwrap().unwrap();
+        assert!(entity.fails(ConsistencyCheck::Document) && !entity.fails(ConsistencyCheck::Provenance));
+        assert!(store.verify_entity(&HexadId::new("missing")).await.unwrap().is_none());
+    }
+
+    /// Records every change as `kind:id`
+    #[derive(Default)]
+    struct RecordingListener {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl HexadListener for RecordingListener {
+        fn on_created(&self, new: &Hexad) {
+            self.events.lock().unwrap().push(format!("created:{}", new.id));
+        }
+
+        fn on_updated(&self, old: &Hexad, new: &Hexad) {
+            assert_eq!(old.status.version + 1, new.status.version);
+            self.events.lock().unwrap().push(format!("updated:{}", new.id));
+        }
+
+        fn on_deleted(&self, old: &Hexad) {
+            self.events.lock().unwrap().push(format!("deleted:{}", old.id));
+        }
+    }
+
+    struct PanickingListener;
+
+    impl HexadListener for PanickingListener {
+        fn on_created(&self, _new: &Hexad) {
+            panic!("listener failure");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entity_policy_hides_other_principals_entities() {
+        use crate::security::scope;
+
+        let store = create_test_store().with_policy(EntityPolicy {
+            rules: vec![
+                PolicyRule::Owner { field: "owner".to_string() },
+                PolicyRule::Member { field: "namespace".to_string() },
+            ],
+            groups: HashMap::from([("red".to_string(), vec!["alice".to_string(), "carol".to_string()])]),
+            ..Default::default()
+        });
+        let input = |title: &str, fields: &[(&str, &str)]| {
+            let mut input = HexadBuilder::new().with_document(title, "alpha project").build();
+            let document = input.document.as_mut().unwrap();
+            document.fields.extend(fields.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+            input
+        };
+        let alice = Principal::new("alice");
+        let bob = Principal::new("bob");
+
+        let secret = scope(alice.clone(), store.create(input("Secret", &[]))).await.unwrap();
+        assert_eq!(secret.document.as_ref().unwrap().fields.get("owner").map(String::as_str), Some("alice"));
+        let shared = scope(alice.clone(), store.create(input("Shared", &[("namespace", "red")]))).await.unwrap();
+        let mut linked = input("Bob's", &[]);
+        linked.graph = Some(HexadGraphInput {
+            relationships: vec![("cites".to_string(), secret.id.to_string())],
+        });
+        let own = scope(bob.clone(), store.create(linked)).await.unwrap();
+
+        // Bob sees only his own entity, however he looks
+        scope(bob.clone(), async {
+            assert!(store.get(&secret.id).await.unwrap().is_none());
+            assert!(store.status(&shared.id).await.unwrap().is_none());
+            let hits: Vec<HexadId> = store.search_text("alpha", 10).await.unwrap().into_iter().map(|h| h.id).collect();
+            assert_eq!(hits, vec![own.id.clone()]);
+            assert_eq!(store.list(10, 0).await.unwrap().len(), 1);
+            assert!(store.query_related(&own.id, "cites").await.unwrap().is_empty());
+            let many = store.get_many(&[secret.id.clone(), own.id.clone(), HexadId::new("missing")]).await.unwrap();
+            assert_eq!(many.iter().map(Option::is_some).collect::<Vec<_>>(), vec![false, true, false]);
+            assert!(store.at_time(&secret.id, Utc::now()).await.unwrap().is_none());
+            let snapshot = store.read_snapshot().await.unwrap();
+            assert_eq!(store.list_at(&snapshot, None, 10, 0).await.unwrap().len(), 1);
+
+            assert!(matches!(store.update(&secret.id, input("Mine", &[])).await, Err(HexadError::NotFound(_))));
+            assert!(matches!(store.delete(&shared.id).await, Err(HexadError::NotFound(_))));
+            assert!(matches!(
+                store.create(input("Planted", &[("owner", "alice")])).await,
+                Err(HexadError::ValidationError(_))
+            ));
+            assert!(matches!(
+                store.update(&own.id, input("Given away", &[("owner", "alice")])).await,
+                Err(HexadError::ValidationError(_))
+            ));
+        })
+        .await;
+
+        // Group members see the group's entities, not each other's own
+        scope(Principal::new("carol"), async {
+            assert!(store.get(&shared.id).await.unwrap().is_some());
+            assert!(store.get(&secret.id).await.unwrap().is_none());
+        })
+        .await;
+
+        // Unrestricted principals and the system see everything
+        assert_eq!(scope(Principal::unrestricted("admin"), store.list(10, 0)).await.unwrap().len(), 3);
+        security::system(async {
+            assert_eq!(store.query_related(&own.id, "cites").await.unwrap().len(), 1);
+            let many = store.get_many(&[secret.id.clone(), shared.id.clone()]).await.unwrap();
+            assert_eq!(many.iter().flatten().count(), 2);
+        })
+        .await;
+
+        // Calls outside any scope see and write nothing
+        assert!(store.list(10, 0).await.unwrap().is_empty());
+        assert!(store.get(&shared.id).await.unwrap().is_none());
+        assert!(matches!(store.delete(&secret.id).await, Err(HexadError::NotFound(_))));
+        assert!(store.create(input("Unowned", &[("visibility", "public")])).await.is_err());
+
+        // A deleted entity is judged by the document it had
+        security::system(store.delete(&own.id)).await.unwrap();
+        scope(bob, async {
+            assert!(store.admits_hexad(&own));
+            assert!(!store.admits_hexad(&secret));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_listeners_observe_writes_and_are_isolated() {
+        let store = create_test_store();
+        let recorder = Arc::new(RecordingListener::default());
+        store.add_listener(Arc::new(PanickingListener));
+        store.add_listener(recorder.clone());
+
+        let a = store.create(HexadBuilder::new().with_document("A", "first").build()).await.unwrap();
+        store.update(&a.id, HexadBuilder::new().with_document("A", "second").build()).await.unwrap();
+        let batch = store.create_batch(vec![HexadBuilder::new().with_document("B", "batch").build()]).await;
+        let b = batch.into_iter().next().unwrap().unwrap();
+        store.soft_delete(&a.id, "auditor").await.unwrap();
+        store.restore(&a.id, "auditor").await.unwrap();
+        store.delete_batch(std::slice::from_ref(&b.id)).await[0].as_ref().unwrap();
+        store.delete(&a.id).await.unwrap();
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                format!("created:{}", a.id),
+                format!("updated:{}", a.id),
+                format!("created:{}", b.id),
+                format!("deleted:{}", a.id),
+                format!("created:{}", a.id),
+                format!("deleted:{}", b.id),
+                format!("deleted:{}", a.id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_collections_are_kept_from_restricted_principals() {
+        use crate::security::{scope, system};
+
+        let store = create_test_store().with_private_collection("internal");
+        let record = HexadBuilder::new().with_document("Slow query", "MATCH x").build();
+        let hidden = system(store.create_in("internal", record.clone())).await.unwrap();
+        let open = store.create(HexadBuilder::new().with_document("Open", "notes").build()).await.unwrap();
+
+        // Without a policy, everything else stays open to everyone
+        scope(Principal::new("bob"), async {
+            assert!(store.get(&hidden.id).await.unwrap().is_none());
+            assert!(store.get(&open.id).await.unwrap().is_some());
+            assert!(store.list_in("internal", 10, 0, ModalityMask::ALL).await.unwrap().is_empty());
+            assert_eq!(store.list(10, 0).await.unwrap().len(), 1);
+            assert!(store.create_in("internal", record.clone()).await.is_err());
+        })
+        .await;
+        assert!(store.get(&hidden.id).await.unwrap().is_none());
+        assert_eq!(system(store.list_in("internal", 10, 0, ModalityMask::ALL)).await.unwrap().len(), 1);
+        assert!(scope(Principal::unrestricted("ops"), store.get(&hidden.id)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_policy_decisions_are_reported_to_listeners() {
+        use crate::security::scope;
+
+        #[derive(Default)]
+        struct DecisionListener(std::sync::Mutex<Vec<PolicyDecision>>);
+
+        impl HexadListener for DecisionListener {
+            fn on_decision(&self, decision: &PolicyDecision) {
+                self.0.lock().unwrap().push(decision.clone());
+            }
+        }
+
+        let store = create_test_store().with_policy(EntityPolicy {
+            rules: vec![PolicyRule::Owner { field: "owner".to_string() }],
+            ..Default::default()
+        });
+        let decisions = Arc::new(DecisionListener::default());
+        store.add_listener(decisions.clone());
+        let hexad = security::system(store.create(HexadBuilder::new().with_document("Mine", "x").build()))
+            .await
+            .unwrap();
+        // The system's calls are not judged
+        assert!(decisions.0.lock().unwrap().is_empty());
+        security::system(store.update(&hexad.id, {
+            let mut input = HexadBuilder::new().with_document("Mine", "x").build();
+            input.document.as_mut().unwrap().fields.insert("owner".to_string(), "alice".to_string());
+            input
+        }))
+        .await
+        .unwrap();
+
+        let alice = Principal::new("alice").with_request("path", "/hexads");
+        assert!(scope(alice, store.get(&hexad.id)).await.unwrap().is_some());
+        assert!(scope(Principal::new("bob"), store.get(&hexad.id)).await.unwrap().is_none());
+
+        let decisions = decisions.0.lock().unwrap();
+        assert_eq!(decisions.len(), 2);
+        assert!(decisions[0].allowed());
+        assert_eq!(decisions[0].principal, "alice");
+        assert_eq!(decisions[0].entity, hexad.id.as_str());
+        assert_eq!(decisions[0].rule.as_ref().unwrap().to_string(), "owner(owner)");
+        assert_eq!(decisions[0].request["path"], "/hexads");
+        assert!(!decisions[1].allowed());
+        assert_eq!(decisions[1].principal, "bob");
+        assert_eq!(decisions[1].action, Action::Read);
+    }
+
+    #[tokio::test]
+    async fn test_revert_hexad() {
+        let store = create_test_store();
+        let other = store.create(HexadBuilder::new().with_document("Other", "x").build()).await.unwrap();
+        let hexad = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Original", "Original content")
+                    .with_embedding(vec![0.1, 0.2, 0.3])
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        store
+            .update(
+                &hexad.id,
+                HexadBuilder::new()
+                    .with_document("Updated", "Updated content")
+                    .with_embedding(vec![0.9, 0.1, 0.0])
+                    .with_tensor(vec![2], vec![1.0, 2.0])
+                    .with_relationships(vec![("mentions", other.id.as_str())])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let node = GraphNode::new(hexad.id.to_iri(&store.config.base_iri));
+        assert_eq!(store.graph_store().outgoing(&node).await.unwrap().len(), 1);
+
+        let reverted = store.revert(&hexad.id, 1, None).await.unwrap();
+        assert_eq!(reverted.status.version, 3);
+        let current = store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(current.document.unwrap().title, "Original");
+        assert_eq!(current.embedding.unwrap().vector, vec![0.1, 0.2, 0.3]);
+        assert!(current.tensor.is_none());
+        assert!(store.graph_store().outgoing(&node).await.unwrap().is_empty());
+
+        // Reverting to the update restores what the revert undid
+        store.revert(&hexad.id, 2, None).await.unwrap();
+        let current = store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(current.document.unwrap().title, "Updated");
+        assert_eq!(current.tensor.unwrap().shape, vec![2]);
+        assert_eq!(store.graph_store().outgoing(&node).await.unwrap().len(), 1);
+
+        assert!(matches!(store.revert(&hexad.id, 9, None).await, Err(HexadError::ValidationError(_))));
+        assert!(matches!(
+            store.revert(&HexadId::new("missing"), 1, None).await,
+            Err(HexadError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_hexad_with_polygon_geometry() {
+        let store = create_test_store();
+        let ring = vec![
+            Coordinates::new_unchecked(0.0, 0.0, None),
+            Coordinates::new_unchecked(0.0, 2.0, None),
+            Coordinates::new_unchecked(2.0, 2.0, None),
+            Coordinates::new_unchecked(2.0, 0.0, None),
+            Coordinates::new_unchecked(0.0, 0.0, None),
+        ];
+
+        let input = HexadBuilder::new()
+            .with_document("Field", "A square field")
+            .with_geometry(crate::Geometry::Polygon(vec![ring]))
+            .build();
+        let hexad = store.create(input).await.unwrap();
+
+        let spatial = store.get(&hexad.id).await.unwrap().unwrap().spatial_data.unwrap();
+        assert_eq!(spatial.geometry_type, GeometryType::Polygon);
+        assert!(spatial.geometry.is_some());
+        assert!((spatial.coordinates.latitude - 1.0).abs() < 1e-9);
+        assert!(spatial.contains(&Coordinates::new_unchecked(1.5, 0.5, None)));
+
+        // An open ring is rejected
+        let open = vec![
+            Coordinates::new_unchecked(0.0, 0.0, None),
+            Coordinates::new_unchecked(0.0, 2.0, None),
+            Coordinates::new_unchecked(2.0, 2.0, None),
+            Coordinates::new_unchecked(2.0, 0.0, None),
+        ];
+        let input = HexadBuilder::new()
+            .with_geometry(crate::Geometry::Polygon(vec![open]))
+            .build();
+        assert!(store.create(input).await.is_err());
+    }
 }
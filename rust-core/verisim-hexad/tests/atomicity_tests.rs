@@ -8,12 +8,13 @@
 
 use std::sync::Arc;
 use verisim_hexad::{
-    HexadBuilder, HexadConfig, HexadId, HexadStore, InMemoryHexadStore,
+    GraphNode, GraphStore, HexadBuilder, HexadConfig, HexadError, HexadId, HexadStore,
+    InMemoryHexadStore, SemanticStore, SpatialStore, TensorStore, VectorStore,
 };
 use verisim_document::TantivyDocumentStore;
 use verisim_graph::SimpleGraphStore;
 use verisim_provenance::InMemoryProvenanceStore;
-use verisim_semantic::InMemorySemanticStore;
+use verisim_semantic::{Constraint, ConstraintKind, InMemorySemanticStore, SemanticType};
 use verisim_spatial::InMemorySpatialStore;
 use verisim_temporal::InMemoryVersionStore;
 use verisim_tensor::InMemoryTensorStore;
@@ -49,6 +50,53 @@ fn create_test_store(vector_dim: usize) -> TestHexadStore {
     )
 }
 
+/// Modality stores of a hexad store, kept so tests can inspect them directly
+struct ModalityStores {
+    graph: Arc<SimpleGraphStore>,
+    vector: Arc<BruteForceVectorStore>,
+    tensor: Arc<InMemoryTensorStore>,
+    semantic: Arc<InMemorySemanticStore>,
+    spatial: Arc<InMemorySpatialStore>,
+}
+
+/// A store whose semantic modality rejects `STRICT_TYPE` annotations
+/// without a name, failing a write while the other modalities are written
+async fn create_failing_store() -> (TestHexadStore, ModalityStores) {
+    let stores = ModalityStores {
+        graph: Arc::new(SimpleGraphStore::in_memory().unwrap()),
+        vector: Arc::new(BruteForceVectorStore::new(3, DistanceMetric::Cosine)),
+        tensor: Arc::new(InMemoryTensorStore::new()),
+        semantic: Arc::new(InMemorySemanticStore::new()),
+        spatial: Arc::new(InMemorySpatialStore::new()),
+    };
+    stores
+        .semantic
+        .register_type(&SemanticType::new(STRICT_TYPE, "Strict").with_constraint(Constraint {
+            name: "name_required".to_string(),
+            kind: ConstraintKind::Required("name".to_string()),
+            message: "Strict entities must have a name".to_string(),
+        }))
+        .await
+        .unwrap();
+    let store = InMemoryHexadStore::new(
+        HexadConfig {
+            vector_dimension: 3,
+            ..Default::default()
+        },
+        stores.graph.clone(),
+        stores.vector.clone(),
+        Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+        stores.tensor.clone(),
+        stores.semantic.clone(),
+        Arc::new(InMemoryVersionStore::new()),
+        Arc::new(InMemoryProvenanceStore::new()),
+        stores.spatial.clone(),
+    );
+    (store, stores)
+}
+
+const STRICT_TYPE: &str = "https://example.org/Strict";
+
 // ===========================================================================
 // Create atomicity tests
 // ===========================================================================
@@ -224,6 +272,79 @@ async fn test_update_with_invalid_vector_dimension_rolls_back() {
     );
 }
 
+// ===========================================================================
+// Modality failure rollback tests
+// ===========================================================================
+
+#[tokio::test]
+async fn test_create_modality_failure_removes_written_modalities() {
+    // The semantic write fails while graph, vector, document, tensor and
+    // spatial are written alongside it; all of them must be removed again.
+    let (store, stores) = create_failing_store().await;
+
+    let input = HexadBuilder::new()
+        .with_document("Half written", "Must not survive")
+        .with_embedding(vec![0.1, 0.2, 0.3])
+        .with_tensor(vec![2], vec![1.0, 2.0])
+        .with_relationships(vec![("related_to", "other-entity")])
+        .with_types(vec![STRICT_TYPE])
+        .with_spatial(51.5, -0.12)
+        .build();
+
+    let result = store.create(input).await;
+    assert!(
+        matches!(result, Err(HexadError::ModalityError { ref modality, .. }) if modality == "semantic"),
+        "Create should fail in the semantic modality"
+    );
+
+    let target = GraphNode::new("https://verisim.db/entity/other-entity");
+    assert!(stores.graph.incoming(&target).await.unwrap().is_empty(), "Graph edges should be removed");
+    assert!(stores.vector.search(&[0.1, 0.2, 0.3], 5).await.unwrap().is_empty(), "Embedding should be removed");
+    assert!(stores.tensor.list().await.unwrap().is_empty(), "Tensor should be removed");
+    assert!(stores.spatial.list(10, 0).await.unwrap().is_empty(), "Location should be removed");
+    assert!(store.list(100, 0).await.unwrap().is_empty(), "No hexad should be registered");
+    assert_eq!(store.transaction_manager().active_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_update_modality_failure_restores_previous_values() {
+    // A failed update must put back the values it overwrote, remove the
+    // edges it added, and leave the version unchanged.
+    let (store, stores) = create_failing_store().await;
+    let hexad = store
+        .create(
+            HexadBuilder::new()
+                .with_document("Original", "Should survive failed update")
+                .with_embedding(vec![1.0, 0.0, 0.0])
+                .with_tensor(vec![2], vec![1.0, 2.0])
+                .with_relationships(vec![("related_to", "kept")])
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let bad_update = HexadBuilder::new()
+        .with_document("Changed", "Must be rolled back")
+        .with_embedding(vec![0.0, 1.0, 0.0])
+        .with_tensor(vec![2], vec![9.0, 9.0])
+        .with_relationships(vec![("related_to", "kept"), ("related_to", "added")])
+        .with_types(vec![STRICT_TYPE])
+        .build();
+    assert!(store.update(&hexad.id, bad_update).await.is_err());
+
+    let current = store.get(&hexad.id).await.unwrap().unwrap();
+    assert_eq!(current.status.version, 1, "Version should not change on failed update");
+    assert_eq!(current.document.unwrap().title, "Original");
+    assert_eq!(current.embedding.unwrap().vector, vec![1.0, 0.0, 0.0]);
+    assert_eq!(current.tensor.unwrap().data, vec![1.0, 2.0]);
+    assert!(stores.semantic.get_annotations(hexad.id.as_str()).await.unwrap().is_none());
+
+    let node = GraphNode::new(hexad.id.to_iri("https://verisim.db/entity"));
+    let edges = stores.graph.outgoing(&node).await.unwrap();
+    assert_eq!(edges.len(), 1, "Only the pre-existing edge should remain");
+    assert!(stores.vector.get(hexad.id.as_str()).await.unwrap().is_some());
+}
+
 // ===========================================================================
 // Delete atomicity tests
 // ===========================================================================
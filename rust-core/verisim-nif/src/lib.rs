@@ -16,7 +16,30 @@
 //! - `search_vector/2` — Vector similarity search
 //! - `list_hexads/2` — Paginated entity listing
 //! - `get_drift_score/1` — Get drift scores for an entity
-//! - `trigger_normalise/1` — Trigger normalisation for a drifted entity
+//!
+//! ## Drift and Normalisation Control
+//!
+//! - `get_drift_status/0` — Drift metrics, global and per namespace
+//! - `set_thresholds/1` — Replace the drift thresholds; takes the JSON body
+//!   of `PUT /api/v1/drift/thresholds`, plus an optional `namespace`
+//! - `trigger_normalise/1` — Queue an entity for the normaliser's workers
+//! - `subscribe_drift_events/1` — Send `{:drift_event, json}` to a pid for
+//!   every drift detected, so a supervisor can drive self-healing
+//!
+//! ## Batches and Transactions
+//!
+//! - `create_hexads_batch/1` — Create many hexads atomically (all or none)
+//! - `begin_transaction/1` — Begin a transaction; takes the options JSON of
+//!   `POST /api/v1/transactions/begin`
+//! - `execute_vql/2` — Run a VQL statement, buffering its writes in a
+//!   transaction when one is given
+//! - `commit_transaction/1` — Apply a transaction's buffered writes atomically
+//! - `rollback_transaction/1` — Discard a transaction's buffered writes
+//!
+//! These and the drift controls run against an engine embedded in the node
+//! (persisted under `VERISIM_PERSISTENCE_DIR` when set), through the same
+//! transaction manager, drift detector and normaliser as the HTTP API, so
+//! they carry the same guarantees.
 //!
 //! ## Transport Selection
 //!
@@ -27,21 +50,39 @@
 //! VERISIM_TRANSPORT=auto   # NIF if available, HTTP fallback
 //! ```
 
-use rustler::{Env, Error, NifResult, Term};
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use rustler::{Atom, Env, Error, LocalPid, NifResult, OwnedEnv, Term};
+use serde::Serialize;
 use serde_json::Value;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
 use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use verisim_api::{
+    transaction, vql, ApiConfig, ApiError, AppState, DriftStatusQuery, DriftThresholdQuery, HexadRequest,
+    NormalizationTriggerQuery, ThresholdUpdateRequest,
+};
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        drift_event,
+    }
+}
 
 /// Shared Tokio runtime for executing async store operations from synchronous
 /// NIF entry points. Initialised on first NIF call.
-///
-/// Currently unused — will be activated when store operations are wired into
-/// the NIF functions (replacing the placeholder responses).
-#[allow(dead_code)]
 static RUNTIME: OnceLock<Runtime> = OnceLock::new();
 
+/// Embedded engine behind the drift, batch and transaction NIFs. Initialised on
+/// first use.
+static STATE: OnceLock<AppState> = OnceLock::new();
+
+/// Held while the embedded engine is initialised, so it is opened once.
+static STATE_INIT: Mutex<()> = Mutex::new(());
+
 /// Get or create the shared Tokio runtime.
-#[allow(dead_code)]
 fn runtime() -> &'static Runtime {
     RUNTIME.get_or_init(|| {
         tokio::runtime::Builder::new_multi_thread()
@@ -52,6 +93,32 @@ fn runtime() -> &'static Runtime {
     })
 }
 
+/// Get or open the embedded engine.
+fn state() -> NifResult<&'static AppState> {
+    if let Some(state) = STATE.get() {
+        return Ok(state);
+    }
+    let _init = STATE_INIT.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(state) = STATE.get() {
+        return Ok(state);
+    }
+    let config = ApiConfig {
+        persistence_dir: std::env::var("VERISIM_PERSISTENCE_DIR").ok(),
+        ..Default::default()
+    };
+    let state = runtime().block_on(AppState::new_async(config)).map_err(api_error)?;
+    Ok(STATE.get_or_init(|| state))
+}
+
+fn api_error(e: ApiError) -> Error {
+    Error::Term(Box::new(e.to_string()))
+}
+
+fn to_json<T: Serialize>(value: &T) -> NifResult<String> {
+    serde_json::to_string(value)
+        .map_err(|e| Error::Term(Box::new(format!("serialization error: {e}"))))
+}
+
 // ---------------------------------------------------------------------------
 // NIF functions
 // ---------------------------------------------------------------------------
@@ -181,19 +248,184 @@ fn get_drift_score(hexad_id: String) -> NifResult<String> {
         .map_err(|e| Error::Term(Box::new(format!("serialization error: {e}"))))
 }
 
-/// Trigger normalisation (self-repair) for a drifted entity.
+/// Queue normalisation (self-repair) of an entity for the background workers.
 ///
-/// Returns the normalisation result status.
-#[rustler::nif(schedule = "DirtyCpu")]
+/// Repairs quality drift across every modality, like
+/// `POST /api/v1/normalizer/trigger/{id}`. Returns once the job is queued;
+/// fails if the entity does not exist or the queue is full.
+#[rustler::nif(schedule = "DirtyIo")]
 fn trigger_normalise(hexad_id: String) -> NifResult<String> {
-    let result = serde_json::json!({
+    let state = state()?;
+    let query = NormalizationTriggerQuery {
+        drift_type: None,
+        namespace: None,
+    };
+    runtime()
+        .block_on(verisim_api::trigger_normalization_handler(
+            State(state.clone()),
+            Path(hexad_id.clone()),
+            Query(query),
+        ))
+        .map_err(api_error)?;
+
+    to_json(&serde_json::json!({
         "entity_id": hexad_id,
-        "status": "normalisation_triggered",
+        "status": "queued",
         "transport": "nif"
+    }))
+}
+
+/// Get drift status: the global metrics per drift type, followed by the
+/// breakdown for each measured namespace.
+#[rustler::nif(schedule = "DirtyIo")]
+fn get_drift_status() -> NifResult<String> {
+    let state = state()?;
+    let Json(status) = runtime()
+        .block_on(verisim_api::drift_status_handler(
+            State(state.clone()),
+            Query(DriftStatusQuery { namespace: None }),
+        ))
+        .map_err(api_error)?;
+
+    to_json(&status)
+}
+
+/// Replace the drift thresholds.
+///
+/// Accepts the `PUT /api/v1/drift/thresholds` body (the complete thresholds
+/// plus optional `actor` and `reason`); a `namespace` key sets that
+/// namespace's override instead of the global thresholds. The change is
+/// audited in provenance like one made over HTTP.
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_thresholds(thresholds_json: String) -> NifResult<String> {
+    let mut body: Value = serde_json::from_str(&thresholds_json)
+        .map_err(|e| Error::Term(Box::new(format!("invalid JSON: {e}"))))?;
+    let namespace = body
+        .as_object_mut()
+        .and_then(|o| o.remove("namespace"))
+        .and_then(|ns| ns.as_str().map(str::to_string));
+    let request: ThresholdUpdateRequest = serde_json::from_value(body)
+        .map_err(|e| Error::Term(Box::new(format!("invalid thresholds: {e}"))))?;
+    let state = state()?;
+    let Json(response) = runtime()
+        .block_on(verisim_api::drift_thresholds_put_handler(
+            State(state.clone()),
+            Query(DriftThresholdQuery { namespace }),
+            None,
+            Json(request),
+        ))
+        .map_err(api_error)?;
+
+    to_json(&response)
+}
+
+/// Send every drift event detected from now on to `pid`.
+///
+/// Each event arrives as `{:drift_event, json_string}`. The subscription
+/// ends when `pid` exits; events missed while `pid` lags behind are dropped.
+#[rustler::nif(schedule = "DirtyIo")]
+fn subscribe_drift_events(pid: LocalPid) -> NifResult<Atom> {
+    let state = state()?;
+    let mut events = state.graphql_events.drift_events();
+    runtime().spawn(async move {
+        let mut env = OwnedEnv::new();
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let Ok(json) = serde_json::to_string(&event) else {
+                continue;
+            };
+            if env.send_and_clear(&pid, |_| (atoms::drift_event(), json)).is_err() {
+                break;
+            }
+        }
     });
 
-    serde_json::to_string(&result)
-        .map_err(|e| Error::Term(Box::new(format!("serialization error: {e}"))))
+    Ok(atoms::ok())
+}
+
+/// Create hexads atomically from a JSON array of `HexadInput` objects.
+///
+/// Either every hexad is created or, if one fails, none is. Returns
+/// `{"ids": [...]}` with the new IDs in input order.
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_hexads_batch(json_inputs: String) -> NifResult<String> {
+    let requests: Vec<HexadRequest> = serde_json::from_str(&json_inputs)
+        .map_err(|e| Error::Term(Box::new(format!("invalid JSON: {e}"))))?;
+    let state = state()?;
+    let ids = runtime()
+        .block_on(verisim_api::create_hexads_batch(state, requests, None))
+        .map_err(api_error)?;
+
+    to_json(&serde_json::json!({ "ids": ids, "transport": "nif" }))
+}
+
+/// Begin a transaction.
+///
+/// Accepts the transaction options JSON (isolation level and timeouts; an
+/// empty string for the defaults) and returns the transaction's status,
+/// whose `id` names it in later calls.
+#[rustler::nif(schedule = "DirtyIo")]
+fn begin_transaction(options_json: String) -> NifResult<String> {
+    let options: transaction::TransactionOptions = if options_json.trim().is_empty() {
+        Default::default()
+    } else {
+        serde_json::from_str(&options_json)
+            .map_err(|e| Error::Term(Box::new(format!("invalid JSON: {e}"))))?
+    };
+    let state = state()?;
+    let status = runtime()
+        .block_on(verisim_api::begin_transaction(state, options))
+        .map_err(api_error)?;
+
+    to_json(&status)
+}
+
+/// Execute a VQL statement.
+///
+/// With a non-empty `transaction_id`, INSERT, UPDATE and DELETE writes are
+/// buffered in that transaction until it commits.
+#[rustler::nif(schedule = "DirtyIo")]
+fn execute_vql(query: String, transaction_id: String) -> NifResult<String> {
+    let request = vql::VqlExecuteRequest {
+        query,
+        params: Default::default(),
+        transaction: (!transaction_id.is_empty()).then_some(transaction_id),
+    };
+    let state = state()?;
+    let Json(response) = runtime()
+        .block_on(vql::vql_execute_handler(State(state.clone()), None, HeaderMap::new(), Json(request)))
+        .map_err(api_error)?;
+
+    to_json(&response)
+}
+
+/// Commit a transaction, applying its buffered writes atomically.
+///
+/// Returns the transaction's status with the IDs written. If any write
+/// fails, none is applied and the transaction ends rolled back.
+#[rustler::nif(schedule = "DirtyIo")]
+fn commit_transaction(transaction_id: String) -> NifResult<String> {
+    let state = state()?;
+    let committed = runtime()
+        .block_on(verisim_api::commit_transaction(state, &transaction_id, None))
+        .map_err(api_error)?;
+
+    to_json(&committed)
+}
+
+/// Roll back a transaction, discarding its buffered writes.
+#[rustler::nif(schedule = "DirtyIo")]
+fn rollback_transaction(transaction_id: String) -> NifResult<String> {
+    let state = state()?;
+    let status = runtime()
+        .block_on(verisim_api::rollback_transaction(state, &transaction_id))
+        .map_err(api_error)?;
+
+    to_json(&status)
 }
 
 // ---------------------------------------------------------------------------
@@ -211,5 +443,13 @@ fn trigger_normalise(hexad_id: String) -> NifResult<String> {
         list_hexads,
         get_drift_score,
         trigger_normalise,
+        get_drift_status,
+        set_thresholds,
+        subscribe_drift_events,
+        create_hexads_batch,
+        begin_transaction,
+        execute_vql,
+        commit_transaction,
+        rollback_transaction,
     ]
 );
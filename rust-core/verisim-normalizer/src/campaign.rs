@@ -0,0 +1,947 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Scheduled normalization campaigns
+//!
+//! A [`Campaign`] normalizes, on a cron-like [`CronSchedule`], every entity
+//! whose drift moving average exceeds a threshold, e.g. a nightly full
+//! reconciliation of entities in drifting namespaces.  Moving averages come
+//! from the [`DriftDetector`]: an entity is measured against its namespace's
+//! metrics when its namespace has any, and against the global metrics
+//! otherwise.
+//!
+//! Each execution is a [`CampaignRun`].  The run walks the store in batches
+//! and records its progress after each one, so a run cut short by a store
+//! error or a restart (with [`CampaignScheduler::with_persistence`]) resumes
+//! from the last completed batch the next time the campaign is started.  A
+//! finished run is its own summary report: entities scanned, selected,
+//! normalized and failed, and the changes made.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use verisim_drift::{DriftAction, DriftDetector, DriftEvent, DriftMetrics, DriftType};
+use verisim_hexad::{Hexad, HexadStore};
+
+use crate::Normalizer;
+
+/// Runs kept per campaign; the oldest finished runs are dropped beyond this
+pub const MAX_RUNS_PER_CAMPAIGN: usize = 50;
+
+/// Errors kept in a run report; later errors are only counted
+const MAX_RUN_ERRORS: usize = 20;
+
+/// How often the scheduler looks for due campaigns
+const SCHEDULER_TICK_SECS: u64 = 30;
+
+/// Campaign errors
+#[derive(Debug, Error)]
+pub enum CampaignError {
+    #[error("Campaign not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid schedule '{expression}': {message}")]
+    InvalidSchedule { expression: String, message: String },
+
+    #[error("Invalid campaign: {0}")]
+    Invalid(String),
+
+    #[error("Campaign {0} is already running")]
+    AlreadyRunning(String),
+
+    #[error("Campaign persistence failed: {0}")]
+    Persistence(String),
+}
+
+// ---------------------------------------------------------------------------
+// Schedule
+// ---------------------------------------------------------------------------
+
+/// A five-field cron expression: minute, hour, day of month, month and day
+/// of week, evaluated in UTC.
+///
+/// Fields accept `*`, values, ranges (`1-5`), lists (`1,15`) and steps
+/// (`*/15`, `0-30/10`); day of week counts from Sunday as 0 (7 is also
+/// Sunday).  As in cron, when both day fields are restricted a day matching
+/// either is due.  `@hourly`, `@daily` (`@midnight`), `@weekly`, `@monthly`
+/// and `@yearly` (`@annually`) are accepted as shorthands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression
+    pub fn parse(expression: &str) -> Result<Self, CampaignError> {
+        let invalid = |message: String| CampaignError::InvalidSchedule {
+            expression: expression.to_string(),
+            message,
+        };
+        let trimmed = expression.trim();
+        let expanded = match trimmed {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other if other.starts_with('@') => return Err(invalid("unknown shorthand".to_string())),
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(invalid(format!("expected 5 fields, found {}", fields.len())));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).map_err(|m| invalid(format!("day of week: {m}")))?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expression: trimmed.to_string(),
+            minutes: parse_field(minute, 0, 59).map_err(|m| invalid(format!("minute: {m}")))?,
+            hours: parse_field(hour, 0, 23).map_err(|m| invalid(format!("hour: {m}")))?,
+            days: parse_field(day, 1, 31).map_err(|m| invalid(format!("day of month: {m}")))?,
+            months: parse_field(month, 1, 12).map_err(|m| invalid(format!("month: {m}")))?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    /// The expression as written
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// First due minute strictly after `after`; `None` if the schedule
+    /// never fires (e.g. 31 February) within the next five years.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let limit = after + chrono::Duration::days(5 * 366);
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        while t <= limit {
+            if !bit(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !self.day_matches(t) {
+                t = t.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !bit(self.hours, t.hour()) {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+            } else if !bit(self.minutes, t.minute()) {
+                t += chrono::Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let day = bit(self.days, t.day());
+        let weekday = bit(self.weekdays, t.weekday().num_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = CampaignError;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        Self::parse(&expression)
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expression
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Bitmask of the values one cron field selects
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{step}'"))?;
+                if step == 0 {
+                    return Err("step must be positive".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let value = |s: &str| -> Result<u32, String> {
+            let v: u32 = s.parse().map_err(|_| format!("invalid value '{s}'"))?;
+            if v < min || v > max {
+                return Err(format!("{v} is outside {min}-{max}"));
+            }
+            Ok(v)
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            r => match r.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                None if part.contains('/') => (value(r)?, max),
+                None => (value(r)?, value(r)?),
+            },
+        };
+        if start > end {
+            return Err(format!("range {start}-{end} is reversed"));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+// ---------------------------------------------------------------------------
+// Campaigns and runs
+// ---------------------------------------------------------------------------
+
+/// Which entities a campaign normalizes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CampaignSelector {
+    /// Drift types whose moving averages are considered; all when empty
+    #[serde(default)]
+    pub drift_types: Vec<DriftType>,
+    /// Entities are selected when one of the moving averages exceeds this
+    #[serde(default)]
+    pub min_moving_average: f64,
+}
+
+/// A scheduled normalization campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    /// Campaign identifier; assigned when empty
+    #[serde(default)]
+    pub id: String,
+    /// Human-readable name
+    pub name: String,
+    /// When the campaign runs
+    pub schedule: CronSchedule,
+    /// Which entities are normalized
+    #[serde(default)]
+    pub selector: CampaignSelector,
+    /// Drift type the normalizations are run for; quality drift runs a
+    /// full reconciliation
+    #[serde(default = "default_drift_type")]
+    pub drift_type: DriftType,
+    /// Entities read from the store at a time
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Disabled campaigns only run when started by hand
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// When the most recent run started
+    #[serde(default)]
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// When the campaign is next due
+    #[serde(default)]
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+fn default_drift_type() -> DriftType {
+    DriftType::QualityDrift
+}
+
+fn default_batch_size() -> usize {
+    200
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Campaign {
+    /// Check the campaign's settings
+    pub fn validate(&self) -> Result<(), CampaignError> {
+        if self.name.trim().is_empty() {
+            return Err(CampaignError::Invalid("name must not be empty".to_string()));
+        }
+        if self.batch_size == 0 {
+            return Err(CampaignError::Invalid("batch_size must be positive".to_string()));
+        }
+        let threshold = self.selector.min_moving_average;
+        if !threshold.is_finite() || !(0.0..=1.0).contains(&threshold) {
+            return Err(CampaignError::Invalid(format!(
+                "min_moving_average must be within [0, 1], got {threshold}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How a run was started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    /// The schedule came due
+    Scheduled,
+    /// Started by hand
+    Manual,
+}
+
+/// State of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Working through the store
+    Running,
+    /// Stopped early; the next start of the campaign resumes it
+    Interrupted,
+    /// Every entity was examined
+    Completed,
+}
+
+/// Progress and summary report of one campaign run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignRun {
+    /// Run identifier
+    pub id: String,
+    /// Campaign the run belongs to
+    pub campaign_id: String,
+    /// How the run was started
+    pub trigger: RunTrigger,
+    /// Current state
+    pub status: RunStatus,
+    /// When the run started
+    pub started_at: DateTime<Utc>,
+    /// When the run completed
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Times the run was resumed after an interruption
+    pub resumed: u32,
+    /// Store offset of the next batch
+    pub cursor: usize,
+    /// Entities examined
+    pub scanned: usize,
+    /// Entities whose drift exceeded the threshold
+    pub selected: usize,
+    /// Normalizations performed
+    pub normalized: usize,
+    /// Normalizations written back to the store
+    pub applied: usize,
+    /// Selected entities no strategy acted on
+    pub skipped: usize,
+    /// Normalizations that failed
+    pub failed: usize,
+    /// Field-level changes across all normalizations
+    pub changes: usize,
+    /// First errors encountered
+    pub errors: Vec<String>,
+}
+
+impl CampaignRun {
+    fn new(campaign_id: &str, trigger: RunTrigger) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            campaign_id: campaign_id.to_string(),
+            trigger,
+            status: RunStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            resumed: 0,
+            cursor: 0,
+            scanned: 0,
+            selected: 0,
+            normalized: 0,
+            applied: 0,
+            skipped: 0,
+            failed: 0,
+            changes: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, message: String) {
+        if self.errors.len() < MAX_RUN_ERRORS {
+            self.errors.push(message);
+        }
+    }
+}
+
+/// Campaigns and their runs, as persisted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CampaignState {
+    campaigns: Vec<Campaign>,
+    runs: Vec<CampaignRun>,
+}
+
+// ---------------------------------------------------------------------------
+// Scheduler
+// ---------------------------------------------------------------------------
+
+/// Keeps campaigns, runs them when due, and records their runs.
+pub struct CampaignScheduler {
+    store: Arc<dyn HexadStore>,
+    detector: Arc<DriftDetector>,
+    normalizer: Arc<Normalizer>,
+    namespace_field: Option<String>,
+    persist_path: Option<PathBuf>,
+    state: RwLock<CampaignState>,
+    /// Campaigns with a run in progress
+    active: std::sync::Mutex<HashSet<String>>,
+}
+
+impl CampaignScheduler {
+    /// Create a scheduler normalizing entities of `store`
+    pub fn new(store: Arc<dyn HexadStore>, detector: Arc<DriftDetector>, normalizer: Arc<Normalizer>) -> Self {
+        Self {
+            store,
+            detector,
+            normalizer,
+            namespace_field: Some("namespace".to_string()),
+            persist_path: None,
+            state: RwLock::new(CampaignState::default()),
+            active: std::sync::Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Document field naming an entity's namespace; entities are only
+    /// measured against global metrics when `None`
+    pub fn with_namespace_field(mut self, field: Option<String>) -> Self {
+        self.namespace_field = field;
+        self
+    }
+
+    /// Keep campaigns and run progress in a JSON file, loading it if it
+    /// exists.  Runs that were in progress are marked interrupted.
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self, CampaignError> {
+        let path = path.into();
+        let mut state = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str::<CampaignState>(&json)
+                .map_err(|e| CampaignError::Persistence(format!("{}: {}", path.display(), e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CampaignState::default(),
+            Err(e) => return Err(CampaignError::Persistence(format!("{}: {}", path.display(), e))),
+        };
+        for run in state.runs.iter_mut().filter(|r| r.status == RunStatus::Running) {
+            run.status = RunStatus::Interrupted;
+        }
+        info!(path = %path.display(), campaigns = state.campaigns.len(), "Loaded normalization campaigns");
+        self.state = RwLock::new(state);
+        self.persist_path = Some(path);
+        Ok(self)
+    }
+
+    /// All campaigns
+    pub async fn campaigns(&self) -> Vec<Campaign> {
+        self.state.read().await.campaigns.clone()
+    }
+
+    /// One campaign
+    pub async fn campaign(&self, id: &str) -> Option<Campaign> {
+        self.state.read().await.campaigns.iter().find(|c| c.id == id).cloned()
+    }
+
+    /// Create or replace a campaign, scheduling its next run.  The run
+    /// history of a replaced campaign is kept.
+    pub async fn save_campaign(&self, mut campaign: Campaign) -> Result<Campaign, CampaignError> {
+        campaign.validate()?;
+        if campaign.id.is_empty() {
+            campaign.id = uuid::Uuid::new_v4().to_string();
+        }
+        campaign.next_run_at = campaign.schedule.next_after(Utc::now());
+
+        let mut state = self.state.write().await;
+        let previous = state.campaigns.iter().position(|c| c.id == campaign.id);
+        let replaced = match previous {
+            Some(i) => {
+                campaign.last_run_at = state.campaigns[i].last_run_at;
+                Some(std::mem::replace(&mut state.campaigns[i], campaign.clone()))
+            }
+            None => {
+                state.campaigns.push(campaign.clone());
+                None
+            }
+        };
+        if let Err(e) = self.persist(&state) {
+            match replaced {
+                Some(old) => {
+                    if let Some(slot) = state.campaigns.iter_mut().find(|c| c.id == old.id) {
+                        *slot = old;
+                    }
+                }
+                None => state.campaigns.retain(|c| c.id != campaign.id),
+            }
+            return Err(e);
+        }
+        Ok(campaign)
+    }
+
+    /// Delete a campaign and its run history
+    pub async fn remove_campaign(&self, id: &str) -> Result<Campaign, CampaignError> {
+        let mut state = self.state.write().await;
+        let index = state
+            .campaigns
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| CampaignError::NotFound(id.to_string()))?;
+        let removed = state.campaigns.remove(index);
+        state.runs.retain(|r| r.campaign_id != id);
+        self.persist(&state)?;
+        Ok(removed)
+    }
+
+    /// Runs of a campaign, newest first
+    pub async fn runs(&self, campaign_id: &str) -> Vec<CampaignRun> {
+        let mut runs: Vec<CampaignRun> = self
+            .state
+            .read()
+            .await
+            .runs
+            .iter()
+            .filter(|r| r.campaign_id == campaign_id)
+            .cloned()
+            .collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        runs
+    }
+
+    /// Whether a campaign has a run in progress
+    pub fn is_running(&self, campaign_id: &str) -> bool {
+        self.active.lock().map(|a| a.contains(campaign_id)).unwrap_or(false)
+    }
+
+    /// Run a campaign to the end of the store, resuming its interrupted run
+    /// if it has one.  The returned report is `Interrupted` when the store
+    /// could not be read; starting the campaign again continues from there.
+    pub async fn run(&self, campaign_id: &str, trigger: RunTrigger) -> Result<CampaignRun, CampaignError> {
+        let _guard = ActiveGuard::claim(&self.active, campaign_id)?;
+        let campaign = self
+            .campaign(campaign_id)
+            .await
+            .ok_or_else(|| CampaignError::NotFound(campaign_id.to_string()))?;
+
+        let unfinished = self
+            .state
+            .read()
+            .await
+            .runs
+            .iter()
+            .find(|r| r.campaign_id == campaign_id && r.status != RunStatus::Completed)
+            .cloned();
+        let mut run = match unfinished {
+            Some(mut run) => {
+                run.resumed += 1;
+                run.status = RunStatus::Running;
+                info!(campaign = %campaign.name, run = %run.id, cursor = run.cursor, "Resuming normalization campaign");
+                run
+            }
+            None => {
+                info!(campaign = %campaign.name, "Starting normalization campaign");
+                CampaignRun::new(campaign_id, trigger)
+            }
+        };
+        self.record(&run, None).await;
+
+        loop {
+            let batch = match self.store.list(campaign.batch_size, run.cursor).await {
+                Ok(batch) => batch,
+                Err(e) => {
+                    warn!(campaign = %campaign.name, run = %run.id, error = %e, "Campaign interrupted");
+                    run.status = RunStatus::Interrupted;
+                    run.error(format!("store: {e}"));
+                    self.record(&run, None).await;
+                    return Ok(run);
+                }
+            };
+            if batch.is_empty() {
+                break;
+            }
+            self.normalize_batch(&campaign, &batch, &mut run).await;
+            run.cursor += batch.len();
+            self.record(&run, None).await;
+        }
+
+        run.status = RunStatus::Completed;
+        run.finished_at = Some(Utc::now());
+        let next_run_at = campaign.schedule.next_after(Utc::now());
+        self.record(&run, Some((run.started_at, next_run_at))).await;
+        info!(
+            campaign = %campaign.name,
+            run = %run.id,
+            scanned = run.scanned,
+            selected = run.selected,
+            normalized = run.normalized,
+            failed = run.failed,
+            "Normalization campaign complete"
+        );
+        Ok(run)
+    }
+
+    /// Run every enabled campaign due at `now`, one after another
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Vec<CampaignRun> {
+        let due: Vec<String> = self
+            .state
+            .read()
+            .await
+            .campaigns
+            .iter()
+            .filter(|c| c.enabled && c.next_run_at.is_some_and(|t| t <= now))
+            .map(|c| c.id.clone())
+            .collect();
+        let mut runs = Vec::new();
+        for id in due {
+            match self.run(&id, RunTrigger::Scheduled).await {
+                Ok(run) => runs.push(run),
+                Err(CampaignError::AlreadyRunning(_)) => {}
+                Err(e) => warn!(campaign = %id, error = %e, "Scheduled campaign failed to start"),
+            }
+        }
+        runs
+    }
+
+    /// Start due campaigns until the task is aborted
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(verisim_hexad::security::system(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(SCHEDULER_TICK_SECS));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                self.run_due(Utc::now()).await;
+            }
+        }))
+    }
+
+    async fn normalize_batch(&self, campaign: &Campaign, batch: &[Hexad], run: &mut CampaignRun) {
+        let global = self.detector.all_metrics().unwrap_or_default();
+        let namespaces = self.detector.namespace_metrics().unwrap_or_default();
+        for hexad in batch {
+            run.scanned += 1;
+            let namespace = self.namespace_of(hexad);
+            let metrics = namespace
+                .and_then(|ns| namespaces.get(ns))
+                .filter(|m| !m.is_empty())
+                .unwrap_or(&global);
+            let Some(score) = selection_score(&campaign.selector, metrics) else {
+                continue;
+            };
+            run.selected += 1;
+
+            let mut event = DriftEvent::new(
+                campaign.drift_type,
+                score,
+                format!("Normalization campaign '{}'", campaign.name),
+            )
+            .with_entities(vec![hexad.id.to_string()])
+            .with_actions(vec![DriftAction::Normalize]);
+            if let Some(ns) = namespace {
+                event = event.with_namespace(ns);
+            }
+            match self.normalizer.handle_drift(hexad, &event).await {
+                Ok(Some(result)) => {
+                    run.normalized += 1;
+                    run.changes += result.changes.len();
+                    if result.applied {
+                        run.applied += 1;
+                    }
+                }
+                Ok(None) => run.skipped += 1,
+                Err(e) => {
+                    run.failed += 1;
+                    run.error(format!("{}: {}", hexad.id, e));
+                }
+            }
+        }
+    }
+
+    /// Namespace of an entity, from its document's namespace field
+    fn namespace_of<'a>(&self, hexad: &'a Hexad) -> Option<&'a str> {
+        let field = self.namespace_field.as_deref()?;
+        hexad
+            .document
+            .as_ref()?
+            .fields
+            .get(field)
+            .map(String::as_str)
+            .filter(|ns| !ns.is_empty())
+    }
+
+    /// Store a run's progress, and the campaign's schedule when the run
+    /// finished.  Persistence failures are logged; the run carries on.
+    async fn record(&self, run: &CampaignRun, finished: Option<(DateTime<Utc>, Option<DateTime<Utc>>)>) {
+        let mut state = self.state.write().await;
+        match state.runs.iter_mut().find(|r| r.id == run.id) {
+            Some(slot) => *slot = run.clone(),
+            None => state.runs.push(run.clone()),
+        }
+        let mut finished_runs: Vec<(DateTime<Utc>, String)> = state
+            .runs
+            .iter()
+            .filter(|r| r.campaign_id == run.campaign_id && r.status == RunStatus::Completed)
+            .map(|r| (r.started_at, r.id.clone()))
+            .collect();
+        if finished_runs.len() > MAX_RUNS_PER_CAMPAIGN {
+            finished_runs.sort();
+            let drop: HashSet<String> = finished_runs[..finished_runs.len() - MAX_RUNS_PER_CAMPAIGN]
+                .iter()
+                .map(|(_, id)| id.clone())
+                .collect();
+            state.runs.retain(|r| !drop.contains(&r.id));
+        }
+        if let Some((last_run_at, next_run_at)) = finished {
+            if let Some(campaign) = state.campaigns.iter_mut().find(|c| c.id == run.campaign_id) {
+                campaign.last_run_at = Some(last_run_at);
+                campaign.next_run_at = next_run_at;
+            }
+        }
+        if let Err(e) = self.persist(&state) {
+            warn!(run = %run.id, error = %e, "Failed to save campaign progress");
+        }
+    }
+
+    fn persist(&self, state: &CampaignState) -> Result<(), CampaignError> {
+        match &self.persist_path {
+            Some(path) => save_state(path, state),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Highest moving average among the selector's drift types, when it
+/// exceeds the threshold
+fn selection_score(selector: &CampaignSelector, metrics: &HashMap<DriftType, DriftMetrics>) -> Option<f64> {
+    metrics
+        .iter()
+        .filter(|(drift_type, _)| selector.drift_types.is_empty() || selector.drift_types.contains(drift_type))
+        .map(|(_, m)| m.moving_average)
+        .filter(|avg| *avg > selector.min_moving_average)
+        .max_by(f64::total_cmp)
+}
+
+fn save_state(path: &Path, state: &CampaignState) -> Result<(), CampaignError> {
+    let persistence = |e: &dyn std::fmt::Display| CampaignError::Persistence(format!("{}: {}", path.display(), e));
+    let json = serde_json::to_string_pretty(state).map_err(|e| persistence(&e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| persistence(&e))?;
+    std::fs::rename(&tmp, path).map_err(|e| persistence(&e))
+}
+
+/// Marks a campaign as running until dropped
+struct ActiveGuard<'a> {
+    active: &'a std::sync::Mutex<HashSet<String>>,
+    id: String,
+}
+
+impl<'a> ActiveGuard<'a> {
+    fn claim(active: &'a std::sync::Mutex<HashSet<String>>, id: &str) -> Result<Self, CampaignError> {
+        let mut set = active
+            .lock()
+            .map_err(|_| CampaignError::AlreadyRunning(id.to_string()))?;
+        if !set.insert(id.to_string()) {
+            return Err(CampaignError::AlreadyRunning(id.to_string()));
+        }
+        Ok(Self {
+            active,
+            id: id.to_string(),
+        })
+    }
+}
+
+impl Drop for ActiveGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut set) = self.active.lock() {
+            set.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_default_normalizer;
+    use chrono::TimeZone;
+    use verisim_document::TantivyDocumentStore;
+    use verisim_graph::SimpleGraphStore;
+    use verisim_hexad::{HexadBuilder, HexadConfig, HexadSnapshot, InMemoryHexadStore};
+    use verisim_provenance::InMemoryProvenanceStore;
+    use verisim_semantic::InMemorySemanticStore;
+    use verisim_spatial::InMemorySpatialStore;
+    use verisim_temporal::InMemoryVersionStore;
+    use verisim_tensor::InMemoryTensorStore;
+    use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+    fn store() -> Arc<dyn HexadStore> {
+        Arc::new(InMemoryHexadStore::<
+            SimpleGraphStore,
+            BruteForceVectorStore,
+            TantivyDocumentStore,
+            InMemoryTensorStore,
+            InMemorySemanticStore,
+            InMemoryVersionStore<HexadSnapshot>,
+            InMemoryProvenanceStore,
+            InMemorySpatialStore,
+        >::new(
+            HexadConfig {
+                vector_dimension: 3,
+                ..Default::default()
+            },
+            Arc::new(SimpleGraphStore::in_memory().unwrap()),
+            Arc::new(BruteForceVectorStore::new(3, DistanceMetric::Cosine)),
+            Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+            Arc::new(InMemoryTensorStore::new()),
+            Arc::new(InMemorySemanticStore::new()),
+            Arc::new(InMemoryVersionStore::new()),
+            Arc::new(InMemoryProvenanceStore::new()),
+            Arc::new(InMemorySpatialStore::new()),
+        ))
+    }
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn campaign(name: &str, min_moving_average: f64) -> Campaign {
+        Campaign {
+            id: String::new(),
+            name: name.to_string(),
+            schedule: CronSchedule::parse("@daily").unwrap(),
+            selector: CampaignSelector {
+                drift_types: vec![DriftType::TemporalConsistencyDrift],
+                min_moving_average,
+            },
+            drift_type: DriftType::TemporalConsistencyDrift,
+            batch_size: 2,
+            enabled: true,
+            last_run_at: None,
+            next_run_at: None,
+        }
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let nightly = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(nightly.next_after(at(2026, 3, 1, 1, 0)), Some(at(2026, 3, 1, 2, 30)));
+        assert_eq!(nightly.next_after(at(2026, 3, 1, 2, 30)), Some(at(2026, 3, 2, 2, 30)));
+        assert_eq!(nightly.next_after(at(2026, 12, 31, 23, 0)), Some(at(2027, 1, 1, 2, 30)));
+
+        let quarter_hours = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        // 2026-03-07 is a Saturday
+        assert_eq!(quarter_hours.next_after(at(2026, 3, 6, 17, 50)), Some(at(2026, 3, 9, 9, 0)));
+        assert_eq!(quarter_hours.next_after(at(2026, 3, 9, 9, 1)), Some(at(2026, 3, 9, 9, 15)));
+
+        // Restricted day-of-month and day-of-week match either
+        let either = CronSchedule::parse("0 0 1 * 0").unwrap();
+        assert_eq!(either.next_after(at(2026, 3, 2, 0, 0)), Some(at(2026, 3, 8, 0, 0)));
+        assert_eq!(CronSchedule::parse("0 0 * * 7").unwrap().next_after(at(2026, 3, 2, 0, 0)), Some(at(2026, 3, 8, 0, 0)));
+
+        assert!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(at(2026, 1, 1, 0, 0)).is_none());
+        for bad in ["", "* * * *", "60 * * * *", "5-1 * * * *", "*/0 * * * *", "@sometimes", "a * * * *"] {
+            assert!(CronSchedule::parse(bad).is_err(), "{bad}");
+        }
+
+        let json = serde_json::to_string(&nightly).unwrap();
+        assert_eq!(json, "\"30 2 * * *\"");
+        assert_eq!(serde_json::from_str::<CronSchedule>(&json).unwrap(), nightly);
+        assert!(serde_json::from_str::<CronSchedule>("\"61 * * * *\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_campaign_normalizes_selected_entities() {
+        let store = store();
+        for i in 0..5 {
+            store
+                .create(HexadBuilder::new().with_document(&format!("Doc {i}"), "body").build())
+                .await
+                .unwrap();
+        }
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let normalizer = Arc::new(create_default_normalizer(detector.clone()).await);
+        let scheduler = CampaignScheduler::new(store, detector.clone(), normalizer);
+
+        let quiet = scheduler.save_campaign(campaign("quiet", 0.5)).await.unwrap();
+        assert!(quiet.next_run_at.is_some());
+        let run = scheduler.run(&quiet.id, RunTrigger::Manual).await.unwrap();
+        assert_eq!((run.status, run.scanned, run.selected, run.normalized), (RunStatus::Completed, 5, 0, 0));
+
+        detector
+            .record(DriftType::TemporalConsistencyDrift, 0.9, Vec::new())
+            .await
+            .unwrap();
+        let noisy = scheduler.save_campaign(campaign("noisy", 0.05)).await.unwrap();
+        let run = scheduler.run(&noisy.id, RunTrigger::Manual).await.unwrap();
+        assert_eq!(run.status, RunStatus::Completed);
+        assert_eq!((run.scanned, run.selected, run.normalized, run.failed), (5, 5, 5, 0));
+        assert_eq!(run.cursor, 5);
+        assert!(scheduler.campaign(&noisy.id).await.unwrap().last_run_at.is_some());
+        assert_eq!(scheduler.runs(&noisy.id).await.len(), 1);
+
+        assert!(matches!(
+            scheduler.save_campaign(campaign("", 0.1)).await,
+            Err(CampaignError::Invalid(_))
+        ));
+        scheduler.remove_campaign(&noisy.id).await.unwrap();
+        assert!(scheduler.runs(&noisy.id).await.is_empty());
+        assert!(matches!(
+            scheduler.run(&noisy.id, RunTrigger::Manual).await,
+            Err(CampaignError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_run_resumes_after_restart() {
+        let path = std::env::temp_dir().join(format!("verisim-campaigns-{}.json", uuid::Uuid::new_v4()));
+        let store = store();
+        for i in 0..4 {
+            store
+                .create(HexadBuilder::new().with_document(&format!("Doc {i}"), "body").build())
+                .await
+                .unwrap();
+        }
+        let detector = Arc::new(DriftDetector::with_defaults());
+        detector
+            .record(DriftType::TemporalConsistencyDrift, 0.9, Vec::new())
+            .await
+            .unwrap();
+        let normalizer = Arc::new(create_default_normalizer(detector.clone()).await);
+
+        let scheduler = CampaignScheduler::new(store.clone(), detector.clone(), normalizer.clone())
+            .with_persistence(&path)
+            .unwrap();
+        let saved = scheduler.save_campaign(campaign("nightly", 0.05)).await.unwrap();
+        // A run that stopped after its first batch
+        let mut partial = CampaignRun::new(&saved.id, RunTrigger::Scheduled);
+        partial.cursor = 2;
+        partial.scanned = 2;
+        scheduler.record(&partial, None).await;
+        drop(scheduler);
+
+        let restarted = CampaignScheduler::new(store, detector, normalizer)
+            .with_persistence(&path)
+            .unwrap();
+        let runs = restarted.runs(&saved.id).await;
+        assert_eq!(runs[0].status, RunStatus::Interrupted);
+
+        let due = restarted.run_due(Utc::now() + chrono::Duration::days(2)).await;
+        assert_eq!(due.len(), 1);
+        let run = &due[0];
+        assert_eq!(run.id, partial.id);
+        assert_eq!((run.status, run.resumed, run.cursor), (RunStatus::Completed, 1, 4));
+        assert_eq!((run.scanned, run.normalized), (4, 2));
+        assert!(restarted.campaign(&saved.id).await.unwrap().next_run_at.unwrap() > Utc::now());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -14,6 +14,8 @@
 //!   those above [`ConflictConfig::require_manual_above`] are always escalated.
 //! - Per-modality-pair policy overrides for fine-grained control.
 //! - Full history tracking of resolved and dismissed conflicts.
+//! - Runtime reconfiguration ([`ConflictResolver::set_config`]), optionally
+//!   persisted to a JSON file so the policy survives a restart.
 //!
 //! ## Integration
 //!
@@ -28,6 +30,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
@@ -210,6 +213,10 @@ pub struct ConflictConfig {
     /// If a conflict involves modalities `(A, B)` and there is an entry for
     /// `(A, B)` **or** `(B, A)`, that policy takes precedence over
     /// `default_policy`.
+    ///
+    /// Serialised as a list of `{"modalities": [a, b], "policy": ...}`
+    /// entries, since JSON object keys cannot be tuples.
+    #[serde(default, with = "pair_policies")]
     pub per_modality_policies: HashMap<(Modality, Modality), ConflictPolicy>,
 
     /// Drift score at or below which conflicts are auto-resolved using the
@@ -254,6 +261,85 @@ pub fn policy_for_pair(&self, a: Modality, b: Modality) -> &ConflictPolicy {
             .or_else(|| self.per_modality_policies.get(&(b, a)))
             .unwrap_or(&self.default_policy)
     }
+
+    /// Check thresholds and policies before the configuration is applied.
+    pub fn validate(&self) -> Result<(), ConflictError> {
+        for (name, value) in [
+            ("auto_resolve_threshold", self.auto_resolve_threshold),
+            ("require_manual_above", self.require_manual_above),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConflictError::InvalidPolicy(format!(
+                    "{} must be between 0.0 and 1.0, got {}",
+                    name, value
+                )));
+            }
+        }
+        if self.auto_resolve_threshold > self.require_manual_above {
+            return Err(ConflictError::InvalidPolicy(
+                "auto_resolve_threshold must not exceed require_manual_above".to_string(),
+            ));
+        }
+        if self.max_history_entries == 0 {
+            return Err(ConflictError::InvalidPolicy(
+                "max_history_entries must be at least 1".to_string(),
+            ));
+        }
+        for policy in std::iter::once(&self.default_policy).chain(self.per_modality_policies.values()) {
+            match policy {
+                ConflictPolicy::ModalityPriority(order) if order.is_empty() => {
+                    return Err(ConflictError::InvalidPolicy(
+                        "modality_priority needs at least one modality".to_string(),
+                    ));
+                }
+                ConflictPolicy::Custom(name) if name.trim().is_empty() => {
+                    return Err(ConflictError::InvalidPolicy(
+                        "custom policy needs a resolver name".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serde representation of the per-pair policy map as a list.
+mod pair_policies {
+    use super::{ConflictPolicy, Modality};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct PairPolicy {
+        modalities: [Modality; 2],
+        policy: ConflictPolicy,
+    }
+
+    pub fn serialize<S: Serializer>(
+        map: &HashMap<(Modality, Modality), ConflictPolicy>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<PairPolicy> = map
+            .iter()
+            .map(|((a, b), policy)| PairPolicy {
+                modalities: [*a, *b],
+                policy: policy.clone(),
+            })
+            .collect();
+        entries.sort_by_key(|e| (e.modalities[0] as usize, e.modalities[1] as usize));
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(Modality, Modality), ConflictPolicy>, D::Error> {
+        let entries = Vec::<PairPolicy>::deserialize(deserializer)?;
+        Ok(entries
+            .into_iter()
+            .map(|e| ((e.modalities[0], e.modalities[1]), e.policy))
+            .collect())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -278,6 +364,10 @@ pub enum ConflictError {
     /// The specified modality is not one of the conflicting modalities.
     #[error("Modality not in conflict: {0}")]
     ModalityNotInConflict(String),
+
+    /// The configuration could not be read from or written to disk.
+    #[error("Conflict policy persistence failed: {0}")]
+    Persistence(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -292,7 +382,10 @@ pub enum ConflictError {
 /// Thread-safe: all mutable state is behind [`RwLock`] guards.
 pub struct ConflictResolver {
     /// Configuration controlling policies, thresholds, and history limits.
-    config: ConflictConfig,
+    config: std::sync::RwLock<ConflictConfig>,
+
+    /// File the configuration is saved to when it changes, if any.
+    persist_path: Option<PathBuf>,
 
     /// Active (Open or InProgress) conflicts.
     active_conflicts: RwLock<Vec<Conflict>>,
@@ -306,20 +399,63 @@ impl ConflictResolver {
     /// Create a new conflict resolver with the given configuration.
     pub fn new(config: ConflictConfig) -> Self {
         Self {
-            config,
+            config: std::sync::RwLock::new(config),
+            persist_path: None,
             active_conflicts: RwLock::new(Vec::new()),
             history: RwLock::new(Vec::new()),
         }
     }
 
+    /// Create a resolver whose configuration is kept in a JSON file.
+    ///
+    /// The file is read if it exists (the defaults are used otherwise) and
+    /// rewritten on every [`set_config`](Self::set_config).
+    pub fn persistent(path: impl Into<PathBuf>) -> Result<Self, ConflictError> {
+        let path = path.into();
+        let config = match std::fs::read_to_string(&path) {
+            Ok(json) => {
+                let config: ConflictConfig = serde_json::from_str(&json)
+                    .map_err(|e| ConflictError::Persistence(format!("{}: {}", path.display(), e)))?;
+                config.validate()?;
+                info!(path = %path.display(), policy = %config.default_policy, "Loaded conflict policy");
+                config
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ConflictConfig::default(),
+            Err(e) => return Err(ConflictError::Persistence(format!("{}: {}", path.display(), e))),
+        };
+        Ok(Self {
+            persist_path: Some(path),
+            ..Self::new(config)
+        })
+    }
+
     /// Create a resolver with default configuration.
     pub fn with_defaults() -> Self {
         Self::new(ConflictConfig::default())
     }
 
-    /// Access the underlying configuration.
-    pub fn config(&self) -> &ConflictConfig {
-        &self.config
+    /// The current configuration.
+    pub fn config(&self) -> ConflictConfig {
+        self.settings().clone()
+    }
+
+    /// Replace the configuration, saving it first when the resolver is
+    /// persistent.  Returns the previous configuration.
+    ///
+    /// Applies to conflicts resolved from now on; conflicts already resolved
+    /// keep the policy recorded in their resolution.
+    pub fn set_config(&self, config: ConflictConfig) -> Result<ConflictConfig, ConflictError> {
+        config.validate()?;
+        if let Some(path) = &self.persist_path {
+            save_config(path, &config)?;
+        }
+        let mut current = self.config.write().unwrap_or_else(|e| e.into_inner());
+        info!(policy = %config.default_policy, overrides = config.per_modality_policies.len(), "Conflict policy changed");
+        Ok(std::mem::replace(&mut *current, config))
+    }
+
+    fn settings(&self) -> std::sync::RwLockReadGuard<'_, ConflictConfig> {
+        self.config.read().unwrap_or_else(|e| e.into_inner())
     }
 
     // -- detection -----------------------------------------------------------
@@ -410,17 +546,18 @@ pub async fn resolve(
         }
 
         // Step 1: Force manual if drift is very high
-        if conflict.drift_score >= self.config.require_manual_above {
+        let require_manual_above = self.settings().require_manual_above;
+        if conflict.drift_score >= require_manual_above {
             conflict.status = ConflictStatus::InProgress;
             debug!(
                 conflict_id = %conflict_id,
                 drift_score = conflict.drift_score,
-                threshold = self.config.require_manual_above,
+                threshold = require_manual_above,
                 "Drift score above manual threshold -- forcing manual resolution"
             );
             return Err(ConflictError::InvalidPolicy(format!(
                 "Drift score {:.3} >= manual threshold {:.3} -- use resolve_manual()",
-                conflict.drift_score, self.config.require_manual_above
+                conflict.drift_score, require_manual_above
             )));
         }
 
@@ -656,15 +793,15 @@ pub async fn by_entity(&self, entity_id: &str) -> Vec<Conflict> {
     /// policy.
     fn select_policy(&self, conflict: &Conflict) -> ConflictPolicy {
         // Check per-pair overrides for the first matching pair
+        let config = self.settings();
         let modalities = &conflict.conflicting_modalities;
         for i in 0..modalities.len() {
             for j in (i + 1)..modalities.len() {
-                let pair_policy = self
-                    .config
+                let pair_policy = config
                     .per_modality_policies
                     .get(&(modalities[i], modalities[j]))
                     .or_else(|| {
-                        self.config
+                        config
                             .per_modality_policies
                             .get(&(modalities[j], modalities[i]))
                     });
@@ -679,7 +816,7 @@ fn select_policy(&self, conflict: &Conflict) -> ConflictPolicy {
             }
         }
 
-        self.config.default_policy.clone()
+        config.default_policy.clone()
     }
 
     /// Apply a policy to a conflict and produce a resolution.
@@ -757,7 +894,7 @@ async fn move_to_history(&self, conflict: Conflict) {
             history.push(conflict);
 
             // Evict oldest entries if history is too large
-            let max = self.config.max_history_entries;
+            let max = self.settings().max_history_entries;
             if history.len() > max {
                 let excess = history.len() - max;
                 history.drain(0..excess);
@@ -766,6 +903,16 @@ async fn move_to_history(&self, conflict: Conflict) {
     }
 }
 
+/// Write a configuration to `path` via a temporary file, so a crash never
+/// leaves a truncated policy behind.
+fn save_config(path: &Path, config: &ConflictConfig) -> Result<(), ConflictError> {
+    let persistence = |e: &dyn fmt::Display| ConflictError::Persistence(format!("{}: {}", path.display(), e));
+    let json = serde_json::to_string_pretty(config).map_err(|e| persistence(&e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| persistence(&e))?;
+    std::fs::rename(&tmp, path).map_err(|e| persistence(&e))
+}
+
 // ===========================================================================
 // Tests
 // ===========================================================================
@@ -1441,11 +1588,13 @@ async fn test_json_serialization_roundtrip() {
 
     #[test]
     fn test_conflict_config_json_roundtrip() {
-        // Note: per_modality_policies uses a (Modality, Modality) tuple key which
-        // does not serialise to JSON directly. We test the round-trip with an
-        // empty map (the common case for JSON configs). Binary formats (bincode,
-        // postcard, CBOR) can handle tuple keys natively.
-        let config = test_config();
+        // per_modality_policies is keyed by a (Modality, Modality) tuple and
+        // goes over the wire as a list of pair entries.
+        let mut config = test_config();
+        config.per_modality_policies.insert(
+            (Modality::Document, Modality::Vector),
+            ConflictPolicy::ModalityPriority(vec![Modality::Document]),
+        );
 
         let json = serde_json::to_string_pretty(&config).unwrap();
         let deserialized: ConflictConfig = serde_json::from_str(&json).unwrap();
@@ -1456,7 +1605,58 @@ fn test_conflict_config_json_roundtrip() {
         assert!((deserialized.require_manual_above - config.require_manual_above).abs()
             < f64::EPSILON);
         assert_eq!(deserialized.max_history_entries, config.max_history_entries);
-        assert!(deserialized.per_modality_policies.is_empty());
+        assert_eq!(deserialized.per_modality_policies, config.per_modality_policies);
+        assert!(json.contains(r#""modalities": ["#));
+    }
+
+    #[test]
+    fn test_conflict_config_validation() {
+        assert!(test_config().validate().is_ok());
+        let inverted = ConflictConfig {
+            auto_resolve_threshold: 0.9,
+            require_manual_above: 0.5,
+            ..test_config()
+        };
+        assert!(inverted.validate().is_err());
+        let empty_priority = ConflictConfig {
+            default_policy: ConflictPolicy::ModalityPriority(vec![]),
+            ..test_config()
+        };
+        assert!(empty_priority.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_persistent_config_survives_restart() {
+        let path = std::env::temp_dir().join(format!("verisim-conflict-{}.json", Uuid::new_v4()));
+        let resolver = ConflictResolver::persistent(&path).unwrap();
+        assert_eq!(resolver.config().default_policy, ConflictPolicy::LastWriterWins);
+
+        let mut config = test_config();
+        config.default_policy = ConflictPolicy::AutoMerge;
+        config
+            .per_modality_policies
+            .insert((Modality::Graph, Modality::Document), ConflictPolicy::ManualResolve);
+        let previous = resolver.set_config(config).unwrap();
+        assert_eq!(previous.default_policy, ConflictPolicy::LastWriterWins);
+        assert!(resolver
+            .set_config(ConflictConfig { max_history_entries: 0, ..test_config() })
+            .is_err());
+
+        let restarted = ConflictResolver::persistent(&path).unwrap();
+        assert_eq!(restarted.config().default_policy, ConflictPolicy::AutoMerge);
+        assert_eq!(
+            restarted.config().policy_for_pair(Modality::Document, Modality::Graph),
+            &ConflictPolicy::ManualResolve
+        );
+
+        let conflict = restarted
+            .detect_conflict("e1", vec![Modality::Graph, Modality::Document], 0.1, "pair override")
+            .await;
+        assert!(matches!(
+            restarted.resolve(&conflict.id, None).await,
+            Err(ConflictError::InvalidPolicy(_))
+        ));
+        std::fs::remove_file(&path).ok();
     }
 
     // -- AutoMerge resolution ------------------------------------------------
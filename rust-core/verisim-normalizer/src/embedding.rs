@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Embedders for vector regeneration
+//!
+//! When the semantic-vector strategy repairs an entity it re-embeds the
+//! entity's document and semantic text through an [`Embedder`] and upserts
+//! the result into the vector store.  Two implementations ship:
+//!
+//! - [`HashedEmbedder`]: a deterministic hashed bag-of-words embedding.  It
+//!   needs no model and is the default, but only captures word overlap.
+//! - [`HttpEmbedder`]: an OpenAI-compatible `/embeddings` endpoint (OpenAI,
+//!   Ollama, vLLM, text-embeddings-inference and similar services).
+//!
+//! The embedding must have the dimension the store was configured with; an
+//! embedder that returns anything else fails the repair.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::NormalizerError;
+
+/// A source of embeddings for document text
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Model name recorded with generated embeddings
+    fn model(&self) -> &str;
+
+    /// Embed `text` as a vector of `dimension` components
+    async fn embed(&self, text: &str, dimension: usize) -> Result<Vec<f32>, NormalizerError>;
+}
+
+/// Deterministic hashed bag-of-words embeddings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashedEmbedder;
+
+impl HashedEmbedder {
+    /// Model name of hashed embeddings
+    pub const MODEL: &'static str = "verisim-hashed-bow";
+}
+
+#[async_trait]
+impl Embedder for HashedEmbedder {
+    fn model(&self) -> &str {
+        Self::MODEL
+    }
+
+    async fn embed(&self, text: &str, dimension: usize) -> Result<Vec<f32>, NormalizerError> {
+        hashed_embedding(text, dimension)
+            .ok_or_else(|| NormalizerError::EmbeddingFailed("no words to embed".to_string()))
+    }
+}
+
+/// Hashed bag-of-words embedding of `text`, L2-normalised.
+///
+/// Each lower-cased word adds ±1 to the component its FNV-1a hash selects;
+/// the result is stable across processes and releases.  `None` when the
+/// text has no words or `dimension` is zero.
+pub fn hashed_embedding(text: &str, dimension: usize) -> Option<Vec<f32>> {
+    if dimension == 0 {
+        return None;
+    }
+    let mut vector = vec![0.0f32; dimension];
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let hash = fnv1a(&word.to_lowercase());
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % dimension as u64) as usize] += sign;
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return None;
+    }
+    vector.iter_mut().for_each(|v| *v /= norm);
+    Some(vector)
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Connection settings for an embedding service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpEmbedderConfig {
+    /// Full URL of the embeddings endpoint, e.g. `http://localhost:11434/v1/embeddings`
+    pub url: String,
+    /// Model the service should use
+    pub model: String,
+    /// Bearer token, if the service needs one
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Request timeout (seconds)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// Embeddings from an OpenAI-compatible HTTP service
+pub struct HttpEmbedder {
+    config: HttpEmbedderConfig,
+    client: reqwest::Client,
+}
+
+impl HttpEmbedder {
+    /// Create an embedder for the configured service
+    pub fn new(config: HttpEmbedderConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+}
+
+/// Body of an embeddings request.  `dimensions` lets models with
+/// adjustable output size match the store.
+pub fn embedding_request(model: &str, text: &str, dimension: usize) -> serde_json::Value {
+    serde_json::json!({
+        "model": model,
+        "input": text,
+        "dimensions": dimension,
+    })
+}
+
+/// The first embedding of an OpenAI-style response
+/// (`{"data": [{"embedding": [...]}]}`), checked against `dimension`.
+pub fn parse_embedding_response(body: &serde_json::Value, dimension: usize) -> Result<Vec<f32>, NormalizerError> {
+    let values = body
+        .pointer("/data/0/embedding")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| NormalizerError::EmbeddingFailed("response has no data[0].embedding".to_string()))?;
+    let embedding = values
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32))
+        .collect::<Option<Vec<f32>>>()
+        .ok_or_else(|| NormalizerError::EmbeddingFailed("embedding contains a non-number".to_string()))?;
+    if embedding.len() != dimension {
+        return Err(NormalizerError::EmbeddingFailed(format!(
+            "service returned {} dimensions, expected {}",
+            embedding.len(),
+            dimension
+        )));
+    }
+    if !embedding.iter().all(|v| v.is_finite()) {
+        return Err(NormalizerError::EmbeddingFailed("embedding contains NaN or Inf".to_string()));
+    }
+    Ok(embedding)
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn embed(&self, text: &str, dimension: usize) -> Result<Vec<f32>, NormalizerError> {
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .json(&embedding_request(&self.config.model, text, dimension));
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| NormalizerError::EmbeddingFailed(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(NormalizerError::EmbeddingFailed(format!(
+                "embedding service returned {}",
+                response.status()
+            )));
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| NormalizerError::EmbeddingFailed(e.to_string()))?;
+        parse_embedding_response(&body, dimension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_embedding_is_stable_and_normalised() {
+        let a = hashed_embedding("Rust drift repair", 16).unwrap();
+        let b = hashed_embedding("rust DRIFT repair", 16).unwrap();
+        assert_eq!(a, b);
+        let norm: f32 = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-6);
+        assert_ne!(a, hashed_embedding("Unrelated words entirely", 16).unwrap());
+        assert!(hashed_embedding("  --  ", 16).is_none());
+        assert!(hashed_embedding("text", 0).is_none());
+    }
+
+    #[test]
+    fn test_embedding_service_payloads() {
+        let request = embedding_request("nomic-embed-text", "Ada Lovelace", 3);
+        assert_eq!(request["model"], "nomic-embed-text");
+        assert_eq!(request["input"], "Ada Lovelace");
+        assert_eq!(request["dimensions"], 3);
+
+        let response = serde_json::json!({"data": [{"embedding": [0.5, -0.25, 1.0]}], "model": "m"});
+        assert_eq!(parse_embedding_response(&response, 3).unwrap(), vec![0.5, -0.25, 1.0]);
+        assert!(parse_embedding_response(&response, 4).is_err());
+        assert!(parse_embedding_response(&serde_json::json!({"data": []}), 3).is_err());
+        assert!(parse_embedding_response(&serde_json::json!({"data": [{"embedding": [0.1, "x", 0.2]}]}), 3).is_err());
+    }
+}
@@ -11,25 +11,67 @@
 //! - [`regeneration`]: Authority-ranked regeneration subsystem with configurable
 //!   strategies (`FromAuthoritative`, `Merge`, `UserResolve`), an audit event
 //!   trail, and a manual-resolution queue.
+//! - [`campaign`]: Cron-scheduled normalization campaigns over entities whose
+//!   drift moving average exceeds a threshold, with resumable, reported runs.
 //! - [`conflict`]: Policy-based conflict resolution between modalities, with
 //!   configurable policies (last-writer-wins, modality-priority, manual-resolve,
 //!   auto-merge, custom), threshold-gated escalation, and full history tracking.
+//! - [`embedding`]: The [`Embedder`](embedding::Embedder) drifted vectors are
+//!   regenerated with: hashed bag-of-words by default, or an HTTP embedding
+//!   service.
+//! - [`rollback`]: Reverting applied repairs to the entity version they
+//!   started from.
+//! - [`selection`]: Strategy priorities, enable flags and per-namespace
+//!   strategy sets, replaceable at runtime.
+//! - [`scanner`]: Scheduled drift scanning that measures cross-modal drift on
+//!   batches of entities and feeds detected drift back into the `Normalizer`.
+//! - [`preview`]: Dry-run normalization: proposed changes as a diff, kept
+//!   until an operator approves them.
+//! - [`report`]: Per-strategy run counts and latency, recent outcomes, and
+//!   whether repairs are bringing drift moving averages down.
+//! - [`repair`]: Helpers for turning normalization results into store writes
+//!   (repair context, merged repair inputs, provenance events).
+//! - [`worker`]: Bounded queue and worker pool that runs normalizations in
+//!   the background, retrying failures with exponential backoff.
+//!
+//! ## Applying repairs
+//!
+//! Strategies always report the changes a repair involves.  When the
+//! normalizer has a store ([`Normalizer::with_store`]) and
+//! [`NormalizerConfig::apply_repairs`] is set, the writes each strategy plans
+//! through [`NormalizationStrategy::repair`] are persisted with
+//! [`HexadStore::update`], together with a `drift_repaired` provenance event.
+//! Applied results are kept so [`Normalizer::rollback`] can undo them.
 
 #![allow(unused)] // Infrastructure code with planned future usage
 
+pub mod campaign;
 pub mod conflict;
+pub mod embedding;
+pub mod preview;
 pub mod regeneration;
+pub mod repair;
+pub mod report;
+pub mod rollback;
+pub mod scanner;
+pub mod selection;
+pub mod worker;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use thiserror::Error;
 use tokio::sync::{mpsc, RwLock};
+use tracing::info;
 
-use verisim_drift::{DriftDetector, DriftEvent, DriftType};
-use verisim_hexad::{Hexad, HexadId, HexadStore};
+use verisim_drift::{DriftAction, DriftDetector, DriftEvent, DriftType};
+use embedding::{Embedder, HashedEmbedder};
+use repair::RepairContext;
+use verisim_hexad::{
+    Hexad, HexadGraphInput, HexadId, HexadInput, HexadStore, HexadTensorInput, HexadVectorInput,
+};
 
 /// Normalizer errors
 #[derive(Error, Debug)]
@@ -45,6 +87,27 @@ pub enum NormalizerError {
 
     #[error("Channel error: {0}")]
     ChannelError(String),
+
+    #[error("Embedding failed: {0}")]
+    EmbeddingFailed(String),
+
+    #[error("Normalization result not found: {0}")]
+    ResultNotFound(String),
+
+    #[error("Cannot roll back {result_id}: repair wrote version {expected}, entity is at version {actual}")]
+    RollbackConflict { result_id: String, expected: u64, actual: u64 },
+
+    #[error("Normalization queue is full")]
+    QueueFull,
+
+    #[error("Persistence error: {0}")]
+    Persistence(String),
+
+    #[error("Preview not found: {0}")]
+    PreviewNotFound(String),
+
+    #[error("Preview {preview_id} is stale: computed at version {expected}, entity is at version {actual}")]
+    PreviewStale { preview_id: String, expected: u64, actual: u64 },
 }
 
 /// Result of a normalization operation
@@ -62,6 +125,18 @@ pub struct NormalizationResult {
     pub duration_ms: u64,
     /// When normalization completed
     pub completed_at: DateTime<Utc>,
+    /// Whether the changes were written back to the store
+    #[serde(default)]
+    pub applied: bool,
+    /// Result identifier, used to roll an applied repair back
+    #[serde(default)]
+    pub id: String,
+    /// Entity version the normalization started from
+    #[serde(default)]
+    pub base_version: u64,
+    /// Entity version the repair wrote, when it was applied
+    #[serde(default)]
+    pub repaired_version: Option<u64>,
 }
 
 /// Types of normalization
@@ -109,6 +184,19 @@ async fn normalize(
         hexad: &Hexad,
         drift_event: &DriftEvent,
     ) -> Result<NormalizationResult, NormalizerError>;
+
+    /// Plan the store writes that carry out the normalization.
+    ///
+    /// `None` leaves the result as a report.  The normalizer adds the
+    /// provenance event before applying the input.
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        drift_event: &DriftEvent,
+        ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        Ok(None)
+    }
 }
 
 /// Configuration for the normalizer
@@ -118,10 +206,25 @@ pub struct NormalizerConfig {
     pub auto_normalize: bool,
     /// Maximum concurrent normalizations
     pub max_concurrent: usize,
-    /// Minimum drift score to trigger normalization
-    pub min_score: f64,
     /// Backoff after failed normalization (seconds)
     pub failure_backoff_secs: u64,
+    /// Write repairs back to the store; when false, results are reports only
+    #[serde(default)]
+    pub apply_repairs: bool,
+    /// Jobs the background queue holds before `enqueue` is refused
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+    /// Retries of a failed background job before it is dropped
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_queue_capacity() -> usize {
+    1024
+}
+
+fn default_max_retries() -> u32 {
+    3
 }
 
 impl Default for NormalizerConfig {
@@ -129,8 +232,10 @@ fn default() -> Self {
         Self {
             auto_normalize: true,
             max_concurrent: 10,
-            min_score: 0.3,
             failure_backoff_secs: 60,
+            apply_repairs: false,
+            queue_capacity: default_queue_capacity(),
+            max_retries: default_max_retries(),
         }
     }
 }
@@ -148,6 +253,8 @@ pub struct NormalizerStatus {
     pub completed_count: u64,
     /// Total failures
     pub failure_count: u64,
+    /// Failed background jobs re-queued for another attempt
+    pub retry_count: u64,
     /// Last normalization time
     pub last_normalization: Option<DateTime<Utc>>,
 }
@@ -160,6 +267,21 @@ pub struct Normalizer {
     drift_detector: Arc<DriftDetector>,
     status: Arc<RwLock<NormalizerStatus>>,
     result_sender: Option<mpsc::Sender<NormalizationResult>>,
+    store: Option<Arc<dyn HexadStore>>,
+    /// Embedder vector repairs are regenerated with
+    embedder: Arc<dyn Embedder>,
+    /// Applied results that can be rolled back, by result ID
+    results: RwLock<HashMap<String, NormalizationResult>>,
+    /// Sender of the background queue, set once the workers are started
+    queue: OnceLock<mpsc::Sender<worker::NormalizationJob>>,
+    /// Dry-run results awaiting approval, by preview ID
+    previews: RwLock<HashMap<String, preview::NormalizationPreview>>,
+    /// Strategy priorities, enable flags and namespace sets
+    selection: RwLock<selection::StrategySelection>,
+    /// File the strategy selection is saved to
+    selection_path: Option<std::path::PathBuf>,
+    /// Per-strategy and per-drift-type run metrics, and recent outcomes
+    metrics: RwLock<report::NormalizerMetrics>,
 }
 
 impl Normalizer {
@@ -175,9 +297,18 @@ pub fn new(config: NormalizerConfig, drift_detector: Arc<DriftDetector>) -> Self
                 active_count: 0,
                 completed_count: 0,
                 failure_count: 0,
+                retry_count: 0,
                 last_normalization: None,
             })),
             result_sender: None,
+            store: None,
+            embedder: Arc::new(HashedEmbedder),
+            results: RwLock::new(HashMap::new()),
+            queue: OnceLock::new(),
+            previews: RwLock::new(HashMap::new()),
+            selection: RwLock::new(selection::StrategySelection::default()),
+            selection_path: None,
+            metrics: RwLock::new(report::NormalizerMetrics::default()),
         }
     }
 
@@ -192,7 +323,39 @@ pub fn with_result_channel(mut self, sender: mpsc::Sender<NormalizationResult>)
         self
     }
 
-    /// Register a normalization strategy
+    /// Replace the configuration
+    pub fn with_config(mut self, config: NormalizerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the store repairs are written to
+    pub fn with_store(mut self, store: Arc<dyn HexadStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Set the embedder vector repairs are regenerated with
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = embedder;
+        self
+    }
+
+    /// Context strategies plan repairs against
+    fn repair_context<'a>(&'a self, store: &'a dyn HexadStore) -> RepairContext<'a> {
+        RepairContext {
+            store,
+            embedder: self.embedder.as_ref(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &NormalizerConfig {
+        &self.config
+    }
+
+    /// Register a normalization strategy.  Among applicable strategies of
+    /// equal priority, the first registered runs; see [`selection`].
     pub async fn register_strategy(&self, strategy: Arc<dyn NormalizationStrategy>) {
         self.strategies.write().await.push(strategy);
     }
@@ -203,16 +366,14 @@ pub async fn handle_drift(
         hexad: &Hexad,
         event: &DriftEvent,
     ) -> Result<Option<NormalizationResult>, NormalizerError> {
-        if event.score < self.config.min_score {
+        // The drift policy decides which events are repaired; a maintenance
+        // window clears the action while it is open
+        if !event.requires(DriftAction::Normalize) || event.is_suppressed() {
             return Ok(None);
         }
 
-        // Find applicable strategy
-        let strategies = self.strategies.read().await;
-        let strategy = strategies
-            .iter()
-            .find(|s| s.applies_to(event.drift_type))
-            .cloned();
+        // Find the highest-priority strategy allowed in the event's namespace
+        let strategy = self.select_strategy(event.drift_type, event.namespace.as_deref()).await;
 
         let strategy = match strategy {
             Some(s) => s,
@@ -225,9 +386,16 @@ pub async fn handle_drift(
             status.active_count += 1;
         }
 
-        // Perform normalization
+        // Perform normalization, then persist it when repairs are enabled
         let start = std::time::Instant::now();
-        let result = strategy.normalize(hexad, event).await;
+        let mut result = strategy.normalize(hexad, event).await;
+        if let (Ok(normalized), Some(store)) = (&mut result, self.store.as_ref().filter(|_| self.config.apply_repairs)) {
+            if let Err(e) = self.apply_repair(store.as_ref(), strategy.as_ref(), hexad, event, normalized).await {
+                result = Err(e);
+            }
+        }
+
+        self.record_outcome(strategy.name(), event, &result, start.elapsed()).await;
 
         // Update status
         {
@@ -257,6 +425,31 @@ pub async fn handle_drift(
         Ok(Some(result))
     }
 
+    /// Write a strategy's planned repair with a provenance event
+    async fn apply_repair(
+        &self,
+        store: &dyn HexadStore,
+        strategy: &dyn NormalizationStrategy,
+        hexad: &Hexad,
+        event: &DriftEvent,
+        result: &mut NormalizationResult,
+    ) -> Result<(), NormalizerError> {
+        let ctx = self.repair_context(store);
+        let Some(mut input) = strategy.repair(hexad, event, &ctx).await? else {
+            return Ok(());
+        };
+        input.provenance = Some(repair::provenance_event(strategy.name(), event, &result.changes));
+        let repaired = store
+            .update(&hexad.id, input)
+            .await
+            .map_err(|e| NormalizerError::HexadError(e.to_string()))?;
+        result.applied = true;
+        result.repaired_version = Some(repaired.status.version);
+        self.record_result(result).await;
+        info!(id = %hexad.id, strategy = strategy.name(), "Normalization repair applied");
+        Ok(())
+    }
+
     /// Get current status
     pub async fn status(&self) -> NormalizerStatus {
         self.status.read().await.clone()
@@ -346,8 +539,39 @@ async fn normalize(
             changes,
             duration_ms,
             completed_at: Utc::now(),
+            applied: false,
+            id: uuid::Uuid::new_v4().to_string(),
+            base_version: hexad.status.version,
+            repaired_version: None,
         })
     }
+
+    /// Re-embed the document and semantic text with the normalizer's
+    /// embedder, at the dimension of the current embedding
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        _drift_event: &DriftEvent,
+        ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        let dimension = hexad
+            .embedding
+            .as_ref()
+            .map(|e| e.vector.len())
+            .unwrap_or(repair::DEFAULT_EMBEDDING_DIMENSION);
+        let text = repair::source_text(hexad);
+        if !text.chars().any(char::is_alphanumeric) {
+            return Ok(None);
+        }
+        let embedding = ctx.embedder.embed(&text, dimension).await?;
+        Ok(Some(HexadInput {
+            vector: Some(HexadVectorInput {
+                embedding,
+                model: Some(ctx.embedder.model().to_string()),
+            }),
+            ..Default::default()
+        }))
+    }
 }
 
 /// Default strategy for graph-document drift
@@ -430,8 +654,52 @@ async fn normalize(
             changes,
             duration_ms,
             completed_at: Utc::now(),
+            applied: false,
+            id: uuid::Uuid::new_v4().to_string(),
+            base_version: hexad.status.version,
+            repaired_version: None,
         })
     }
+
+    /// Link the entity to the entities its document mentions by title, and
+    /// create the graph node when it is missing.  A missing document is not
+    /// generated; that change stays a report.
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        _drift_event: &DriftEvent,
+        ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        let Some(doc) = &hexad.document else {
+            return Ok(None);
+        };
+        let text = format!("{}\n{}", doc.title, doc.body);
+        let mut relationships = Vec::new();
+        for mention in scanner::mentions(&text) {
+            let candidates = ctx
+                .store
+                .search_text(&mention, repair::MENTION_SEARCH_LIMIT)
+                .await
+                .map_err(|e| NormalizerError::HexadError(e.to_string()))?;
+            relationships.extend(
+                candidates
+                    .iter()
+                    .filter(|c| c.id != hexad.id)
+                    .filter(|c| c.document.as_ref().is_some_and(|d| d.title.eq_ignore_ascii_case(&mention)))
+                    .map(|c| (repair::MENTION_PREDICATE.to_string(), c.id.to_string())),
+            );
+        }
+        relationships.sort();
+        relationships.dedup();
+
+        if relationships.is_empty() && hexad.graph_node.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(HexadInput {
+            graph: Some(HexadGraphInput { relationships }),
+            ..Default::default()
+        }))
+    }
 }
 
 /// Strategy for tensor drift — regenerate tensor from vector embedding reshape or document TF-IDF
@@ -511,8 +779,31 @@ async fn normalize(
             changes,
             duration_ms,
             completed_at: Utc::now(),
+            applied: false,
+            id: uuid::Uuid::new_v4().to_string(),
+            base_version: hexad.status.version,
+            repaired_version: None,
         })
     }
+
+    /// Reshape the embedding into a `[1, d]` tensor
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        _drift_event: &DriftEvent,
+        _ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        let Some(emb) = &hexad.embedding else {
+            return Ok(None);
+        };
+        Ok(Some(HexadInput {
+            tensor: Some(HexadTensorInput {
+                shape: vec![1, emb.vector.len()],
+                data: emb.vector.iter().map(|v| f64::from(*v)).collect(),
+            }),
+            ..Default::default()
+        }))
+    }
 }
 
 /// Strategy for temporal drift — fix timestamp ordering, detect duplicates, fill gaps
@@ -595,8 +886,26 @@ async fn normalize(
             changes,
             duration_ms,
             completed_at: Utc::now(),
+            applied: false,
+            id: uuid::Uuid::new_v4().to_string(),
+            base_version: hexad.status.version,
+            repaired_version: None,
         })
     }
+
+    /// An empty update: the store appends a fresh snapshot, which resets
+    /// `modified_at` and re-reads the version from the version history
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        _drift_event: &DriftEvent,
+        _ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        let needs_repair = hexad.status.created_at > hexad.status.modified_at
+            || hexad.status.version == 0
+            || (hexad.version_count > 0 && hexad.status.version > hexad.version_count);
+        Ok(needs_repair.then(HexadInput::default))
+    }
 }
 
 /// Strategy for quality drift — cascades all strategies in priority order
@@ -670,8 +979,31 @@ async fn normalize(
             changes: all_changes,
             duration_ms,
             completed_at: Utc::now(),
+            applied: false,
+            id: uuid::Uuid::new_v4().to_string(),
+            base_version: hexad.status.version,
+            repaired_version: None,
         })
     }
+
+    /// The inner strategies' repairs, merged into one update
+    async fn repair(
+        &self,
+        hexad: &Hexad,
+        drift_event: &DriftEvent,
+        ctx: &RepairContext<'_>,
+    ) -> Result<Option<HexadInput>, NormalizerError> {
+        let mut merged: Option<HexadInput> = None;
+        for strategy in &self.inner {
+            if let Some(input) = strategy.repair(hexad, drift_event, ctx).await? {
+                merged = Some(match merged {
+                    Some(acc) => repair::merge_inputs(acc, input),
+                    None => input,
+                });
+            }
+        }
+        Ok(merged)
+    }
 }
 
 /// Create a normalizer with default strategies
@@ -712,6 +1044,7 @@ fn create_test_hexad() -> Hexad {
                 modified_at: Utc::now(),
                 version: 1,
                 modality_status: ModalityStatus::default(),
+                expires_at: None,
             },
             graph_node: None,
             embedding: Some(Embedding::new("test-1", vec![0.1, 0.2, 0.3])),
@@ -733,6 +1066,7 @@ fn create_empty_hexad() -> Hexad {
                 modified_at: Utc::now(),
                 version: 1,
                 modality_status: ModalityStatus::default(),
+                expires_at: None,
             },
             graph_node: None,
             embedding: None,
@@ -765,7 +1099,8 @@ async fn test_handle_drift() {
             DriftType::SemanticVectorDrift,
             0.5,
             "Test drift",
-        );
+        )
+        .with_actions(vec![DriftAction::Log, DriftAction::Normalize]);
 
         let result = normalizer.handle_drift(&hexad, &event).await.unwrap();
         assert!(result.is_some());
@@ -775,6 +1110,30 @@ async fn test_handle_drift() {
         assert!(result.changes[0].new_value.contains("Test Document"));
     }
 
+    #[tokio::test]
+    async fn test_handle_drift_skips_suppressed_events() {
+        let drift_detector = Arc::new(DriftDetector::new(DriftThresholds::default()));
+        let normalizer = create_default_normalizer(drift_detector).await;
+
+        let mut event = DriftEvent::new(DriftType::SemanticVectorDrift, 0.5, "Test drift")
+            .with_actions(vec![DriftAction::Normalize]);
+        event.suppressed_by = Some("bulk-import".to_string());
+
+        let result = normalizer.handle_drift(&create_test_hexad(), &event).await.unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handle_drift_requires_normalize_action() {
+        let drift_detector = Arc::new(DriftDetector::new(DriftThresholds::default()));
+        let normalizer = create_default_normalizer(drift_detector).await;
+
+        let event = DriftEvent::new(DriftType::SemanticVectorDrift, 0.95, "Test drift")
+            .with_actions(vec![DriftAction::Log, DriftAction::Alert]);
+        let result = normalizer.handle_drift(&create_test_hexad(), &event).await.unwrap();
+        assert!(result.is_none());
+    }
+
     #[tokio::test]
     async fn test_semantic_vector_strategy_empty_hexad_errors() {
         let strategy = SemanticVectorStrategy;
@@ -819,4 +1178,193 @@ async fn test_graph_document_strategy_with_document() {
         assert_eq!(result.changes[0].modality, "graph");
         assert!(result.changes[0].new_value.contains("Test Document"));
     }
+
+    mod store_backed {
+        use super::*;
+        use verisim_document::TantivyDocumentStore;
+        use verisim_graph::{GraphStore, SimpleGraphStore};
+        use verisim_hexad::{HexadBuilder, HexadConfig, HexadSnapshot, InMemoryHexadStore};
+        use verisim_provenance::InMemoryProvenanceStore;
+        use verisim_semantic::InMemorySemanticStore;
+        use verisim_spatial::InMemorySpatialStore;
+        use verisim_temporal::InMemoryVersionStore;
+        use verisim_tensor::InMemoryTensorStore;
+        use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+        type TestStore = InMemoryHexadStore<
+            SimpleGraphStore,
+            BruteForceVectorStore,
+            TantivyDocumentStore,
+            InMemoryTensorStore,
+            InMemorySemanticStore,
+            InMemoryVersionStore<HexadSnapshot>,
+            InMemoryProvenanceStore,
+            InMemorySpatialStore,
+        >;
+
+        fn store() -> (Arc<TestStore>, Arc<SimpleGraphStore>) {
+            let graph = Arc::new(SimpleGraphStore::in_memory().unwrap());
+            let store = InMemoryHexadStore::new(
+                HexadConfig {
+                    vector_dimension: 8,
+                    ..Default::default()
+                },
+                graph.clone(),
+                Arc::new(BruteForceVectorStore::new(8, DistanceMetric::Cosine)),
+                Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+                Arc::new(InMemoryTensorStore::new()),
+                Arc::new(InMemorySemanticStore::new()),
+                Arc::new(InMemoryVersionStore::new()),
+                Arc::new(InMemoryProvenanceStore::new()),
+                Arc::new(InMemorySpatialStore::new()),
+            );
+            (Arc::new(store), graph)
+        }
+
+        async fn normalizer(store: Arc<TestStore>, apply_repairs: bool) -> Normalizer {
+            let config = NormalizerConfig {
+                apply_repairs,
+                ..Default::default()
+            };
+            let normalizer = Normalizer::new(config, Arc::new(DriftDetector::with_defaults())).with_store(store);
+            normalizer.register_strategy(Arc::new(SemanticVectorStrategy)).await;
+            normalizer.register_strategy(Arc::new(GraphDocumentStrategy)).await;
+            normalizer
+        }
+
+        fn drift(drift_type: DriftType, id: &HexadId) -> DriftEvent {
+            DriftEvent::new(drift_type, 0.8, "drift")
+                .with_entities(vec![id.to_string()])
+                .with_actions(vec![DriftAction::Normalize])
+        }
+
+        #[tokio::test]
+        async fn test_repairs_are_reports_unless_enabled() {
+            let (store, _) = store();
+            let hexad = store
+                .create(
+                    HexadBuilder::new()
+                        .with_document("Ada", "Analytical engine notes")
+                        .with_embedding(vec![1.0; 8])
+                        .build(),
+                )
+                .await
+                .unwrap();
+
+            let normalizer = normalizer(store.clone(), false).await;
+            let result = normalizer
+                .handle_drift(&hexad, &drift(DriftType::SemanticVectorDrift, &hexad.id))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(!result.applied);
+            let stored = store.get(&hexad.id).await.unwrap().unwrap();
+            assert_eq!(stored.embedding.unwrap().vector, vec![1.0; 8]);
+        }
+
+        #[tokio::test]
+        async fn test_vector_regeneration_is_persisted_with_provenance() {
+            let (store, _) = store();
+            let hexad = store
+                .create(
+                    HexadBuilder::new()
+                        .with_document("Ada", "Analytical engine notes")
+                        .with_embedding(vec![1.0; 8])
+                        .build(),
+                )
+                .await
+                .unwrap();
+
+            let normalizer = normalizer(store.clone(), true).await;
+            let result = normalizer
+                .handle_drift(&hexad, &drift(DriftType::SemanticVectorDrift, &hexad.id))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(result.applied);
+
+            let stored = store.get(&hexad.id).await.unwrap().unwrap();
+            let expected = embedding::hashed_embedding(&repair::source_text(&hexad), 8).unwrap();
+            assert_eq!(stored.embedding.unwrap().vector, expected);
+            assert!(stored.provenance_chain_length > hexad.provenance_chain_length);
+            assert!(stored.status.version > hexad.status.version);
+        }
+
+        struct FixedEmbedder(std::sync::Mutex<Vec<(String, usize)>>);
+
+        #[async_trait]
+        impl Embedder for FixedEmbedder {
+            fn model(&self) -> &str {
+                "fixed"
+            }
+
+            async fn embed(&self, text: &str, dimension: usize) -> Result<Vec<f32>, NormalizerError> {
+                self.0.lock().unwrap().push((text.to_string(), dimension));
+                Ok((0..dimension).map(|i| i as f32).collect())
+            }
+        }
+
+        #[tokio::test]
+        async fn test_vector_regeneration_uses_configured_embedder() {
+            let (store, _) = store();
+            let hexad = store
+                .create(
+                    HexadBuilder::new()
+                        .with_document("Ada", "Analytical engine notes")
+                        .with_types(vec!["https://schema.org/Person"])
+                        .with_embedding(vec![1.0; 8])
+                        .build(),
+                )
+                .await
+                .unwrap();
+
+            let embedder = Arc::new(FixedEmbedder(Default::default()));
+            let normalizer = normalizer(store.clone(), true).await.with_embedder(embedder.clone());
+            let result = normalizer
+                .handle_drift(&hexad, &drift(DriftType::SemanticVectorDrift, &hexad.id))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(result.applied);
+
+            let calls = embedder.0.lock().unwrap().clone();
+            assert_eq!(calls.len(), 1);
+            assert!(calls[0].0.contains("Analytical engine notes"));
+            assert!(calls[0].0.contains("https://schema.org/Person"));
+            assert_eq!(calls[0].1, 8);
+            let expected: Vec<f32> = (0..8).map(|i| i as f32).collect();
+            let stored = store.get(&hexad.id).await.unwrap().unwrap();
+            assert_eq!(stored.embedding.unwrap().vector, expected);
+            let nearest = store.search_similar(&expected, 1).await.unwrap();
+            assert_eq!(nearest[0].id, hexad.id);
+        }
+
+        #[tokio::test]
+        async fn test_graph_reconstruction_links_mentioned_entities() {
+            let (store, graph) = store();
+            let ada = store
+                .create(HexadBuilder::new().with_document("Lovelace", "Mathematician").build())
+                .await
+                .unwrap();
+            let notes = store
+                .create(HexadBuilder::new().with_document("Notes", "Translated by Lovelace in 1843").build())
+                .await
+                .unwrap();
+
+            let normalizer = normalizer(store.clone(), true).await;
+            let result = normalizer
+                .handle_drift(&notes, &drift(DriftType::GraphDocumentDrift, &notes.id))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(result.applied);
+
+            let stored = store.get(&notes.id).await.unwrap().unwrap();
+            let edges = graph.outgoing(&stored.graph_node.unwrap()).await.unwrap();
+            assert!(edges.iter().any(|e| {
+                e.predicate.local_name == repair::MENTION_PREDICATE
+                    && matches!(&e.object, verisim_graph::GraphObject::Node(n) if n.local_name == ada.id.as_str())
+            }));
+        }
+    }
 }
@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Dry-run normalization
+//!
+//! [`Normalizer::preview`] runs the applicable strategies against one entity
+//! in report-only mode and keeps the result as a [`NormalizationPreview`]:
+//! the changes each strategy proposes, and the store writes that would carry
+//! them out.  Nothing is written until an operator approves the preview with
+//! [`Normalizer::approve_preview`], which applies all proposed writes as one
+//! update, whether or not automatic repairs are enabled.
+//!
+//! A preview is tied to the entity version it was computed from; approving
+//! it after the entity has changed fails, and a fresh preview is needed.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use verisim_drift::{DriftAction, DriftEvent, DriftType};
+use verisim_hexad::{Hexad, HexadId, HexadInput};
+
+use crate::{repair, NormalizationChange, NormalizationResult, NormalizationType, Normalizer, NormalizerError};
+
+/// Previews kept awaiting approval; the oldest is dropped beyond this
+pub const MAX_PENDING_PREVIEWS: usize = 1000;
+
+/// The changes one strategy proposes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedRepair {
+    /// Strategy that proposed the changes
+    pub strategy: String,
+    /// Drift type the strategy was run for
+    pub drift_type: DriftType,
+    /// Kind of normalization
+    pub normalization_type: NormalizationType,
+    /// Field-level diff: modality, field, current and proposed values
+    pub changes: Vec<NormalizationChange>,
+    /// Store writes approval applies; `None` when the changes are advisory
+    pub repair: Option<HexadInput>,
+}
+
+/// A dry-run normalization of one entity awaiting approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationPreview {
+    /// Preview identifier, used to approve it
+    pub id: String,
+    /// Entity the preview concerns
+    pub entity_id: HexadId,
+    /// Entity version the proposals were computed from
+    pub entity_version: u64,
+    /// Proposals, one per strategy that had something to report
+    pub proposals: Vec<ProposedRepair>,
+    /// When the preview was computed
+    pub created_at: DateTime<Utc>,
+}
+
+impl NormalizationPreview {
+    /// Whether approving the preview writes anything
+    pub fn has_repairs(&self) -> bool {
+        self.proposals.iter().any(|p| p.repair.is_some())
+    }
+}
+
+impl Normalizer {
+    /// Run the strategies for `drift_types` against `hexad` without writing,
+    /// and keep the result for approval.  Strategies are selected as for
+    /// drift in `namespace`; each runs at most once, and strategies that
+    /// cannot apply to the entity are left out.
+    pub async fn preview(
+        &self,
+        hexad: &Hexad,
+        drift_types: &[DriftType],
+        namespace: Option<&str>,
+    ) -> Result<NormalizationPreview, NormalizerError> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| NormalizerError::HexadError("normalizer has no hexad store".into()))?;
+
+        let ctx = self.repair_context(store.as_ref());
+        let mut proposals: Vec<ProposedRepair> = Vec::new();
+        for drift_type in drift_types {
+            let Some(strategy) = self.select_strategy(*drift_type, namespace).await else {
+                continue;
+            };
+            if proposals.iter().any(|p| p.strategy == strategy.name()) {
+                continue;
+            }
+            let mut event = DriftEvent::new(*drift_type, 1.0, "Normalization preview")
+                .with_entities(vec![hexad.id.to_string()])
+                .with_actions(vec![DriftAction::Normalize]);
+            if let Some(ns) = namespace {
+                event = event.with_namespace(ns);
+            }
+            let result = match strategy.normalize(hexad, &event).await {
+                Ok(result) => result,
+                Err(NormalizerError::NormalizationFailed { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+            if result.changes.is_empty() {
+                continue;
+            }
+            proposals.push(ProposedRepair {
+                strategy: strategy.name().to_string(),
+                drift_type: *drift_type,
+                normalization_type: result.normalization_type,
+                changes: result.changes,
+                repair: strategy.repair(hexad, &event, &ctx).await?,
+            });
+        }
+
+        let preview = NormalizationPreview {
+            id: uuid::Uuid::new_v4().to_string(),
+            entity_id: hexad.id.clone(),
+            entity_version: hexad.status.version,
+            proposals,
+            created_at: Utc::now(),
+        };
+
+        let mut previews = self.previews.write().await;
+        if previews.len() >= MAX_PENDING_PREVIEWS {
+            if let Some(oldest) = previews.values().min_by_key(|p| p.created_at).map(|p| p.id.clone()) {
+                previews.remove(&oldest);
+            }
+        }
+        previews.insert(preview.id.clone(), preview.clone());
+        Ok(preview)
+    }
+
+    /// A pending preview
+    pub async fn get_preview(&self, preview_id: &str) -> Option<NormalizationPreview> {
+        self.previews.read().await.get(preview_id).cloned()
+    }
+
+    /// Discard a pending preview
+    pub async fn discard_preview(&self, preview_id: &str) -> Option<NormalizationPreview> {
+        self.previews.write().await.remove(preview_id)
+    }
+
+    async fn restore_preview(&self, preview: NormalizationPreview) {
+        self.previews.write().await.insert(preview.id.clone(), preview);
+    }
+
+    /// Apply a preview's proposed writes as one update, recording `approver`
+    /// in the provenance event.  The preview is consumed unless the entity
+    /// is missing or has changed since it was computed.
+    pub async fn approve_preview(
+        &self,
+        preview_id: &str,
+        approver: &str,
+    ) -> Result<NormalizationResult, NormalizerError> {
+        let start = std::time::Instant::now();
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| NormalizerError::HexadError("normalizer has no hexad store".into()))?;
+        // Taking the preview out first keeps concurrent approvals from
+        // applying it twice; it is put back if it cannot be applied.
+        let preview = self
+            .discard_preview(preview_id)
+            .await
+            .ok_or_else(|| NormalizerError::PreviewNotFound(preview_id.to_string()))?;
+
+        let current = match store.get(&preview.entity_id).await {
+            Ok(Some(current)) => current,
+            Ok(None) => {
+                let message = format!("hexad {} not found", preview.entity_id);
+                self.restore_preview(preview).await;
+                return Err(NormalizerError::HexadError(message));
+            }
+            Err(e) => {
+                self.restore_preview(preview).await;
+                return Err(NormalizerError::HexadError(e.to_string()));
+            }
+        };
+        if current.status.version != preview.entity_version {
+            let stale = NormalizerError::PreviewStale {
+                preview_id: preview.id.clone(),
+                expected: preview.entity_version,
+                actual: current.status.version,
+            };
+            self.restore_preview(preview).await;
+            return Err(stale);
+        }
+
+        let merged = preview
+            .proposals
+            .iter()
+            .filter_map(|p| p.repair.clone())
+            .reduce(repair::merge_inputs);
+        let repaired_version = match merged {
+            Some(mut input) => {
+                input.provenance = Some(repair::approval_event(&preview, approver));
+                match store.update(&preview.entity_id, input).await {
+                    Ok(repaired) => Some(repaired.status.version),
+                    Err(e) => {
+                        self.restore_preview(preview).await;
+                        return Err(NormalizerError::HexadError(e.to_string()));
+                    }
+                }
+            }
+            None => None,
+        };
+        let applied = repaired_version.is_some();
+
+        {
+            let mut status = self.status.write().await;
+            status.completed_count += 1;
+            status.last_normalization = Some(Utc::now());
+        }
+        info!(preview = %preview.id, id = %preview.entity_id, approver, applied, "Normalization preview approved");
+
+        let normalization_type = match preview.proposals.as_slice() {
+            [single] => single.normalization_type,
+            _ => NormalizationType::FullReconciliation,
+        };
+        let result = NormalizationResult {
+            entity_id: preview.entity_id.clone(),
+            normalization_type,
+            success: true,
+            changes: preview.proposals.into_iter().flat_map(|p| p.changes).collect(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            completed_at: Utc::now(),
+            applied,
+            id: preview.id,
+            base_version: preview.entity_version,
+            repaired_version,
+        };
+        if applied {
+            self.record_result(&result).await;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_default_normalizer, GraphDocumentStrategy, NormalizationStrategy, SemanticVectorStrategy};
+    use std::sync::Arc;
+    use verisim_document::TantivyDocumentStore;
+    use verisim_drift::DriftDetector;
+    use verisim_graph::SimpleGraphStore;
+    use verisim_hexad::{HexadBuilder, HexadConfig, HexadSnapshot, HexadStore, InMemoryHexadStore};
+    use verisim_provenance::InMemoryProvenanceStore;
+    use verisim_semantic::InMemorySemanticStore;
+    use verisim_spatial::InMemorySpatialStore;
+    use verisim_temporal::InMemoryVersionStore;
+    use verisim_tensor::InMemoryTensorStore;
+    use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+    fn store() -> Arc<dyn HexadStore> {
+        Arc::new(InMemoryHexadStore::<
+            SimpleGraphStore,
+            BruteForceVectorStore,
+            TantivyDocumentStore,
+            InMemoryTensorStore,
+            InMemorySemanticStore,
+            InMemoryVersionStore<HexadSnapshot>,
+            InMemoryProvenanceStore,
+            InMemorySpatialStore,
+        >::new(
+            HexadConfig {
+                vector_dimension: 4,
+                ..Default::default()
+            },
+            Arc::new(SimpleGraphStore::in_memory().unwrap()),
+            Arc::new(BruteForceVectorStore::new(4, DistanceMetric::Cosine)),
+            Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+            Arc::new(InMemoryTensorStore::new()),
+            Arc::new(InMemorySemanticStore::new()),
+            Arc::new(InMemoryVersionStore::new()),
+            Arc::new(InMemoryProvenanceStore::new()),
+            Arc::new(InMemorySpatialStore::new()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_preview_writes_nothing_until_approved() {
+        let store = store();
+        let hexad = store
+            .create(HexadBuilder::new().with_document("Ada", "Engine notes").with_embedding(vec![1.0; 4]).build())
+            .await
+            .unwrap();
+        let normalizer = create_default_normalizer(Arc::new(DriftDetector::with_defaults()))
+            .await
+            .with_store(store.clone());
+
+        let preview = normalizer.preview(&hexad, &DriftType::ALL, None).await.unwrap();
+        assert!(preview.has_repairs());
+        let strategies: Vec<&str> = preview.proposals.iter().map(|p| p.strategy.as_str()).collect();
+        assert!(strategies.contains(&SemanticVectorStrategy.name()));
+        assert!(strategies.contains(&GraphDocumentStrategy.name()));
+        let unchanged = store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.status.version, hexad.status.version);
+        assert_eq!(unchanged.embedding.unwrap().vector, vec![1.0; 4]);
+
+        let result = normalizer.approve_preview(&preview.id, "operator").await.unwrap();
+        assert!(result.applied);
+        let repaired = store.get(&hexad.id).await.unwrap().unwrap();
+        assert_eq!(repaired.status.version, hexad.status.version + 1);
+        assert_ne!(repaired.embedding.unwrap().vector, vec![1.0; 4]);
+        assert!(matches!(
+            normalizer.approve_preview(&preview.id, "operator").await,
+            Err(NormalizerError::PreviewNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stale_preview_is_rejected() {
+        let store = store();
+        let hexad = store
+            .create(HexadBuilder::new().with_document("Ada", "Engine notes").with_embedding(vec![1.0; 4]).build())
+            .await
+            .unwrap();
+        let normalizer = create_default_normalizer(Arc::new(DriftDetector::with_defaults()))
+            .await
+            .with_store(store.clone());
+
+        let preview = normalizer.preview(&hexad, &[DriftType::SemanticVectorDrift], None).await.unwrap();
+        assert_eq!(preview.proposals.len(), 1);
+        store.update(&hexad.id, HexadInput::default()).await.unwrap();
+
+        assert!(matches!(
+            normalizer.approve_preview(&preview.id, "operator").await,
+            Err(NormalizerError::PreviewStale { .. })
+        ));
+        assert!(normalizer.get_preview(&preview.id).await.is_some());
+    }
+}
@@ -946,6 +946,7 @@ fn rich_hexad() -> Hexad {
                 modified_at: Utc::now(),
                 version: 1,
                 modality_status: ModalityStatus::default(),
+                expires_at: None,
             },
             graph_node: Some(GraphNode::new("https://verisim.db/entity/rich-1")),
             embedding: Some(Embedding::new("rich-1", vec![0.1, 0.2, 0.3])),
@@ -977,6 +978,7 @@ fn doc_only_hexad() -> Hexad {
                 modified_at: Utc::now(),
                 version: 1,
                 modality_status: ModalityStatus::default(),
+                expires_at: None,
             },
             graph_node: None,
             embedding: None,
@@ -999,6 +1001,7 @@ fn empty_hexad() -> Hexad {
                 modified_at: Utc::now(),
                 version: 1,
                 modality_status: ModalityStatus::default(),
+                expires_at: None,
             },
             graph_node: None,
             embedding: None,
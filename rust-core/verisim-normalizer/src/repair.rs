@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Repair helpers
+//!
+//! Building blocks the normalization strategies use to turn a result into
+//! a [`HexadInput`] the store can apply: the [`RepairContext`] strategies
+//! plan against, the source text vectors are regenerated from, merging of
+//! several strategies' inputs into a single update, and the provenance event
+//! recorded alongside every repair.
+
+use verisim_drift::DriftEvent;
+use verisim_hexad::{Hexad, HexadGraphInput, HexadInput, HexadProvenanceInput, HexadStore};
+
+use crate::embedding::Embedder;
+use crate::preview::NormalizationPreview;
+use crate::{NormalizationChange, NormalizationResult};
+
+/// Embedding dimension used when the entity has no embedding to match
+pub const DEFAULT_EMBEDDING_DIMENSION: usize = 384;
+
+/// Predicate of relationships reconstructed from document mentions
+pub const MENTION_PREDICATE: &str = "mentions";
+
+/// Candidates fetched per mention when resolving it to an entity
+pub const MENTION_SEARCH_LIMIT: usize = 5;
+
+/// Provenance event type of persisted repairs
+pub const REPAIR_EVENT_TYPE: &str = "drift_repaired";
+
+/// Provenance event type of rolled-back repairs
+pub const ROLLBACK_EVENT_TYPE: &str = "normalization_rolled_back";
+
+/// Text an entity's embedding is regenerated from: document title and body,
+/// then semantic type IRIs.
+pub fn source_text(hexad: &Hexad) -> String {
+    let mut parts = Vec::new();
+    if let Some(doc) = &hexad.document {
+        parts.push(doc.title.clone());
+        parts.push(doc.body.clone());
+    }
+    if let Some(sem) = &hexad.semantic {
+        parts.extend(sem.types.iter().cloned());
+    }
+    parts.join("\n")
+}
+
+/// What a strategy can use to plan a repair
+#[derive(Clone, Copy)]
+pub struct RepairContext<'a> {
+    /// Store the repair will be written to, for lookups
+    pub store: &'a dyn HexadStore,
+    /// Embedder for regenerating vectors
+    pub embedder: &'a dyn Embedder,
+}
+
+/// Combine two repair inputs.  Relationships and metadata are unioned; for
+/// every other modality the first input's value wins.
+pub fn merge_inputs(first: HexadInput, second: HexadInput) -> HexadInput {
+    let graph = match (first.graph, second.graph) {
+        (Some(mut a), Some(b)) => {
+            a.relationships.extend(b.relationships);
+            a.relationships.sort();
+            a.relationships.dedup();
+            Some(a)
+        }
+        (a, b) => a.or(b),
+    };
+    let mut metadata = second.metadata;
+    metadata.extend(first.metadata);
+    HexadInput {
+        graph,
+        vector: first.vector.or(second.vector),
+        tensor: first.tensor.or(second.tensor),
+        semantic: first.semantic.or(second.semantic),
+        document: first.document.or(second.document),
+        provenance: first.provenance.or(second.provenance),
+        spatial: first.spatial.or(second.spatial),
+        metadata,
+    }
+}
+
+/// Provenance event recorded with a repair made by `strategy`
+pub fn provenance_event(strategy: &str, event: &DriftEvent, changes: &[NormalizationChange]) -> HexadProvenanceInput {
+    let mut fields: Vec<String> = changes.iter().map(|c| format!("{}.{}", c.modality, c.field)).collect();
+    fields.dedup();
+    HexadProvenanceInput {
+        event_type: REPAIR_EVENT_TYPE.to_string(),
+        actor: format!("normalizer:{}", strategy),
+        source: None,
+        description: format!(
+            "Repaired {} (score {:.3}): {}",
+            event.drift_type,
+            event.score,
+            fields.join(", ")
+        ),
+        link: None,
+    }
+}
+
+/// Provenance event recorded when an operator approves a preview
+pub fn approval_event(preview: &NormalizationPreview, approver: &str) -> HexadProvenanceInput {
+    let strategies: Vec<&str> = preview
+        .proposals
+        .iter()
+        .filter(|p| p.repair.is_some())
+        .map(|p| p.strategy.as_str())
+        .collect();
+    HexadProvenanceInput {
+        event_type: REPAIR_EVENT_TYPE.to_string(),
+        actor: approver.to_string(),
+        source: Some(format!("normalizer-preview:{}", preview.id)),
+        description: format!("Approved normalization preview: {}", strategies.join(", ")),
+        link: None,
+    }
+}
+
+/// Provenance event recorded when `actor` rolls back an applied repair
+pub fn rollback_event(result: &NormalizationResult, actor: &str) -> HexadProvenanceInput {
+    HexadProvenanceInput {
+        event_type: ROLLBACK_EVENT_TYPE.to_string(),
+        actor: actor.to_string(),
+        source: Some(format!("normalizer-result:{}", result.id)),
+        description: format!(
+            "Rolled back {:?} normalization to version {}",
+            result.normalization_type, result.base_version
+        ),
+        link: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verisim_hexad::HexadVectorInput;
+
+    #[test]
+    fn test_merge_inputs() {
+        let first = HexadInput {
+            graph: Some(HexadGraphInput { relationships: vec![("mentions".into(), "a".into())] }),
+            vector: Some(HexadVectorInput { embedding: vec![1.0], model: None }),
+            ..Default::default()
+        };
+        let second = HexadInput {
+            graph: Some(HexadGraphInput {
+                relationships: vec![("mentions".into(), "a".into()), ("mentions".into(), "b".into())],
+            }),
+            vector: Some(HexadVectorInput { embedding: vec![2.0], model: None }),
+            ..Default::default()
+        };
+        let merged = merge_inputs(first, second);
+        assert_eq!(merged.graph.unwrap().relationships.len(), 2);
+        assert_eq!(merged.vector.unwrap().embedding, vec![1.0]);
+    }
+}
@@ -0,0 +1,400 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Normalization metrics and outcome reporting
+//!
+//! [`Normalizer::handle_drift`] records every run: counts, successes,
+//! failures and latency per strategy, and per drift type the score that
+//! triggered the repair alongside the drift moving average at the time.
+//! [`Normalizer::report`] puts these together with the queue status and the
+//! most recent outcomes.
+//!
+//! Whether auto-repair is helping shows in [`DriftRepairReport`]: the moving
+//! average when the first repair ran against the moving average now.  A
+//! falling average means drift is being repaired faster than it appears.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use verisim_drift::{DriftEvent, DriftType};
+use verisim_hexad::HexadId;
+
+use crate::{NormalizationResult, Normalizer, NormalizerError, NormalizerStatus};
+
+/// Outcomes kept for the report; the oldest is dropped beyond this
+pub const MAX_RECENT_OUTCOMES: usize = 100;
+
+/// Run counts and latency of one strategy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyMetrics {
+    /// Normalizations the strategy ran
+    pub runs: u64,
+    /// Runs that succeeded
+    pub successes: u64,
+    /// Runs that failed
+    pub failures: u64,
+    /// Successful runs whose repair was written to the store
+    pub applied: u64,
+    /// Total time spent, including writing repairs (milliseconds)
+    pub total_duration_ms: u64,
+    /// Slowest run (milliseconds)
+    pub max_duration_ms: u64,
+}
+
+impl StrategyMetrics {
+    /// Fraction of runs that succeeded; 0 before the first run
+    pub fn success_rate(&self) -> f64 {
+        ratio(self.successes, self.runs)
+    }
+
+    /// Mean run time (milliseconds); 0 before the first run
+    pub fn mean_duration_ms(&self) -> f64 {
+        ratio(self.total_duration_ms, self.runs)
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Repairs of one drift type
+#[derive(Debug, Clone, Default)]
+struct DriftRepairMetrics {
+    normalizations: u64,
+    successes: u64,
+    trigger_score_sum: f64,
+    first_moving_average: Option<f64>,
+}
+
+/// One normalization run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationOutcome {
+    /// Entity that was normalized
+    pub entity_id: HexadId,
+    /// Strategy that ran
+    pub strategy: String,
+    /// Drift type being repaired
+    pub drift_type: DriftType,
+    /// Namespace of the drift event
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Score of the drift event that triggered the run
+    pub trigger_score: f64,
+    /// Drift moving average when the run finished
+    #[serde(default)]
+    pub moving_average: Option<f64>,
+    /// Whether the run succeeded
+    pub success: bool,
+    /// Whether the repair was written to the store
+    pub applied: bool,
+    /// Result identifier, for rollback
+    #[serde(default)]
+    pub result_id: Option<String>,
+    /// Why the run failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Run time, including writing the repair (milliseconds)
+    pub duration_ms: u64,
+    /// When the run finished
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Per-strategy section of the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyReport {
+    /// Strategy name
+    pub strategy: String,
+    #[serde(flatten)]
+    pub metrics: StrategyMetrics,
+    /// Fraction of runs that succeeded
+    pub success_rate: f64,
+    /// Mean run time (milliseconds)
+    pub mean_duration_ms: f64,
+}
+
+/// Whether repairs of one drift type are bringing its drift down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftRepairReport {
+    /// Drift type
+    pub drift_type: DriftType,
+    /// Normalizations run for the drift type
+    pub normalizations: u64,
+    /// Normalizations that succeeded
+    pub successes: u64,
+    /// Mean score of the drift events that triggered them
+    pub mean_trigger_score: f64,
+    /// Moving average when the first normalization ran
+    pub moving_average_at_first_repair: Option<f64>,
+    /// Moving average now
+    pub current_moving_average: Option<f64>,
+    /// `current - at first repair`; negative when drift is going down
+    pub moving_average_change: Option<f64>,
+}
+
+/// Normalizer activity and outcomes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizerReport {
+    /// Queue and run counters
+    pub status: NormalizerStatus,
+    /// Fraction of all runs that succeeded
+    pub success_rate: f64,
+    /// Per-strategy counts and latency, by strategy name
+    pub strategies: Vec<StrategyReport>,
+    /// Per-drift-type repair effect, by drift type
+    pub drift: Vec<DriftRepairReport>,
+    /// Most recent outcomes, newest first
+    pub recent: Vec<NormalizationOutcome>,
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Recorded run metrics, held by the normalizer
+#[derive(Debug, Default)]
+pub(crate) struct NormalizerMetrics {
+    strategies: HashMap<String, StrategyMetrics>,
+    drift: HashMap<DriftType, DriftRepairMetrics>,
+    recent: VecDeque<NormalizationOutcome>,
+}
+
+impl Normalizer {
+    /// Record one run of `strategy` for `event`
+    pub(crate) async fn record_outcome(
+        &self,
+        strategy: &str,
+        event: &DriftEvent,
+        result: &Result<NormalizationResult, NormalizerError>,
+        elapsed: Duration,
+    ) {
+        let duration_ms = elapsed.as_millis() as u64;
+        let moving_average = self.moving_average(event.drift_type, event.namespace.as_deref());
+        let (entity_id, applied, result_id) = match result {
+            Ok(r) => (r.entity_id.clone(), r.applied, Some(r.id.clone())),
+            Err(_) => (
+                HexadId::new(event.affected_entities.first().map(String::as_str).unwrap_or_default()),
+                false,
+                None,
+            ),
+        };
+        let success = result.as_ref().is_ok_and(|r| r.success);
+
+        let mut metrics = self.metrics.write().await;
+        let stats = metrics.strategies.entry(strategy.to_string()).or_default();
+        stats.runs += 1;
+        if success {
+            stats.successes += 1;
+        } else {
+            stats.failures += 1;
+        }
+        if applied {
+            stats.applied += 1;
+        }
+        stats.total_duration_ms += duration_ms;
+        stats.max_duration_ms = stats.max_duration_ms.max(duration_ms);
+
+        let drift = metrics.drift.entry(event.drift_type).or_default();
+        drift.normalizations += 1;
+        drift.successes += u64::from(success);
+        drift.trigger_score_sum += event.score;
+        drift.first_moving_average = drift.first_moving_average.or(moving_average);
+
+        if metrics.recent.len() >= MAX_RECENT_OUTCOMES {
+            metrics.recent.pop_front();
+        }
+        metrics.recent.push_back(NormalizationOutcome {
+            entity_id,
+            strategy: strategy.to_string(),
+            drift_type: event.drift_type,
+            namespace: event.namespace.clone(),
+            trigger_score: event.score,
+            moving_average,
+            success,
+            applied,
+            result_id,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            duration_ms,
+            completed_at: Utc::now(),
+        });
+    }
+
+    /// Run counts and latency by strategy name
+    pub async fn strategy_metrics(&self) -> HashMap<String, StrategyMetrics> {
+        self.metrics.read().await.strategies.clone()
+    }
+
+    /// Status, per-strategy metrics, per-drift-type repair effect and the
+    /// `limit` most recent outcomes
+    pub async fn report(&self, limit: usize) -> NormalizerReport {
+        let status = self.status().await;
+        let metrics = self.metrics.read().await;
+
+        let mut strategies: Vec<StrategyReport> = metrics
+            .strategies
+            .iter()
+            .map(|(name, m)| StrategyReport {
+                strategy: name.clone(),
+                metrics: m.clone(),
+                success_rate: m.success_rate(),
+                mean_duration_ms: m.mean_duration_ms(),
+            })
+            .collect();
+        strategies.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+
+        let mut drift: Vec<DriftRepairReport> = metrics
+            .drift
+            .iter()
+            .map(|(drift_type, m)| {
+                let current = self.moving_average(*drift_type, None);
+                DriftRepairReport {
+                    drift_type: *drift_type,
+                    normalizations: m.normalizations,
+                    successes: m.successes,
+                    mean_trigger_score: if m.normalizations == 0 {
+                        0.0
+                    } else {
+                        m.trigger_score_sum / m.normalizations as f64
+                    },
+                    moving_average_at_first_repair: m.first_moving_average,
+                    current_moving_average: current,
+                    moving_average_change: m.first_moving_average.zip(current).map(|(first, now)| now - first),
+                }
+            })
+            .collect();
+        drift.sort_by_key(|d| d.drift_type.to_string());
+
+        let runs = status.completed_count + status.failure_count;
+        NormalizerReport {
+            success_rate: ratio(status.completed_count, runs),
+            status,
+            strategies,
+            drift,
+            recent: metrics.recent.iter().rev().take(limit).cloned().collect(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Drift moving average of `drift_type`, in `namespace` when it has
+    /// been measured there
+    fn moving_average(&self, drift_type: DriftType, namespace: Option<&str>) -> Option<f64> {
+        let scoped = namespace.and_then(|ns| {
+            self.drift_detector
+                .namespace_metrics()
+                .ok()?
+                .get(ns)?
+                .get(&drift_type)
+                .map(|m| m.moving_average)
+        });
+        scoped.or_else(|| {
+            self.drift_detector
+                .get_metrics(drift_type)
+                .ok()
+                .flatten()
+                .map(|m| m.moving_average)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NormalizationStrategy, NormalizationType, TemporalRepairStrategy};
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use verisim_drift::{DriftAction, DriftDetector};
+    use verisim_hexad::{Hexad, HexadStatus};
+
+    struct FailingStrategy;
+
+    #[async_trait]
+    impl NormalizationStrategy for FailingStrategy {
+        fn name(&self) -> &str {
+            "always-fails"
+        }
+
+        fn applies_to(&self, drift_type: DriftType) -> bool {
+            drift_type == DriftType::TensorDrift
+        }
+
+        async fn normalize(&self, hexad: &Hexad, _event: &DriftEvent) -> Result<NormalizationResult, NormalizerError> {
+            Err(NormalizerError::NormalizationFailed {
+                entity_id: hexad.id.to_string(),
+                message: "no tensor".to_string(),
+            })
+        }
+    }
+
+    fn hexad() -> Hexad {
+        Hexad {
+            id: HexadId::new("report-test"),
+            status: HexadStatus {
+                id: HexadId::new("report-test"),
+                created_at: Utc::now(),
+                modified_at: Utc::now(),
+                version: 1,
+                modality_status: Default::default(),
+                expires_at: None,
+            },
+            graph_node: None,
+            embedding: None,
+            tensor: None,
+            semantic: None,
+            document: None,
+            version_count: 1,
+            provenance_chain_length: 0,
+            spatial_data: None,
+        }
+    }
+
+    fn event(drift_type: DriftType, score: f64) -> DriftEvent {
+        DriftEvent::new(drift_type, score, "drift")
+            .with_entities(vec!["report-test".to_string()])
+            .with_actions(vec![DriftAction::Normalize])
+    }
+
+    #[tokio::test]
+    async fn test_report_tracks_strategy_outcomes_and_drift_change() {
+        let detector = Arc::new(DriftDetector::with_defaults());
+        detector
+            .record(DriftType::TemporalConsistencyDrift, 0.8, Vec::new())
+            .await
+            .unwrap();
+        let normalizer = Normalizer::with_defaults(detector.clone());
+        normalizer.register_strategy(Arc::new(TemporalRepairStrategy)).await;
+        normalizer.register_strategy(Arc::new(FailingStrategy)).await;
+
+        normalizer
+            .handle_drift(&hexad(), &event(DriftType::TemporalConsistencyDrift, 0.8))
+            .await
+            .unwrap();
+        assert!(normalizer.handle_drift(&hexad(), &event(DriftType::TensorDrift, 0.6)).await.is_err());
+        detector
+            .record(DriftType::TemporalConsistencyDrift, 0.0, Vec::new())
+            .await
+            .unwrap();
+
+        let report = normalizer.report(10).await;
+        assert_eq!(report.success_rate, 0.5);
+        assert_eq!(report.recent.len(), 2);
+        assert_eq!(report.recent[0].strategy, "always-fails");
+        assert!(report.recent[0].error.is_some());
+        assert!(report.recent[1].success);
+
+        let failing = report.strategies.iter().find(|s| s.strategy == "always-fails").unwrap();
+        assert_eq!((failing.metrics.runs, failing.metrics.failures), (1, 1));
+        assert_eq!(failing.success_rate, 0.0);
+
+        let temporal = report
+            .drift
+            .iter()
+            .find(|d| d.drift_type == DriftType::TemporalConsistencyDrift)
+            .unwrap();
+        assert_eq!(temporal.mean_trigger_score, 0.8);
+        assert!(temporal.moving_average_change.unwrap() < 0.0);
+
+        assert_eq!(normalizer.report(1).await.recent.len(), 1);
+    }
+}
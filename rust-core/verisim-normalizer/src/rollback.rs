@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Rolling back applied repairs
+//!
+//! Every [`NormalizationResult`] records the entity version it started from.
+//! The normalizer keeps the results of applied repairs, and
+//! [`Normalizer::rollback`] reverts the entity to that version through the
+//! store's version history ([`HexadStore::revert`]), with a
+//! `normalization_rolled_back` provenance event.
+//!
+//! A rollback is refused once the entity has been written again after the
+//! repair, since reverting would also discard those writes; it can be forced.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use verisim_hexad::{HexadId, HexadStore};
+
+use crate::{repair, NormalizationResult, Normalizer, NormalizerError};
+
+/// Applied results kept for rollback; the oldest is dropped beyond this
+pub const MAX_RECORDED_RESULTS: usize = 1000;
+
+/// Outcome of a rollback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationRollback {
+    /// Result that was rolled back
+    pub result_id: String,
+    /// Entity that was reverted
+    pub entity_id: HexadId,
+    /// Version whose state was restored
+    pub restored_version: u64,
+    /// Version the rollback wrote
+    pub version: u64,
+    /// Who rolled the repair back
+    pub rolled_back_by: String,
+    /// When the rollback happened
+    pub rolled_back_at: DateTime<Utc>,
+}
+
+impl Normalizer {
+    /// Keep an applied result for rollback
+    pub(crate) async fn record_result(&self, result: &NormalizationResult) {
+        let mut results = self.results.write().await;
+        if results.len() >= MAX_RECORDED_RESULTS {
+            if let Some(oldest) = results.values().min_by_key(|r| r.completed_at).map(|r| r.id.clone()) {
+                results.remove(&oldest);
+            }
+        }
+        results.insert(result.id.clone(), result.clone());
+    }
+
+    /// Applied results that can be rolled back, newest first
+    pub async fn applied_results(&self) -> Vec<NormalizationResult> {
+        let mut results: Vec<NormalizationResult> = self.results.read().await.values().cloned().collect();
+        results.sort_by_key(|r| std::cmp::Reverse(r.completed_at));
+        results
+    }
+
+    /// An applied result that can be rolled back
+    pub async fn applied_result(&self, result_id: &str) -> Option<NormalizationResult> {
+        self.results.read().await.get(result_id).cloned()
+    }
+
+    /// Revert the entity an applied repair changed to the version the
+    /// normalization started from.  Unless `force` is set, the entity must
+    /// still be at the version the repair wrote.  A result can be rolled back
+    /// once.
+    pub async fn rollback(
+        &self,
+        result_id: &str,
+        actor: &str,
+        force: bool,
+    ) -> Result<NormalizationRollback, NormalizerError> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| NormalizerError::HexadError("normalizer has no hexad store".into()))?;
+        // Taken out first so concurrent rollbacks cannot both revert
+        let result = self
+            .results
+            .write()
+            .await
+            .remove(result_id)
+            .ok_or_else(|| NormalizerError::ResultNotFound(result_id.to_string()))?;
+
+        let outcome = self.revert(store.as_ref(), &result, actor, force).await;
+        if outcome.is_err() {
+            self.results.write().await.insert(result.id.clone(), result);
+        }
+        outcome
+    }
+
+    async fn revert(
+        &self,
+        store: &dyn HexadStore,
+        result: &NormalizationResult,
+        actor: &str,
+        force: bool,
+    ) -> Result<NormalizationRollback, NormalizerError> {
+        let current = store
+            .get(&result.entity_id)
+            .await
+            .map_err(|e| NormalizerError::HexadError(e.to_string()))?
+            .ok_or_else(|| NormalizerError::HexadError(format!("hexad {} not found", result.entity_id)))?;
+        let repaired_version = result.repaired_version.unwrap_or(result.base_version);
+        if !force && current.status.version != repaired_version {
+            return Err(NormalizerError::RollbackConflict {
+                result_id: result.id.clone(),
+                expected: repaired_version,
+                actual: current.status.version,
+            });
+        }
+
+        let reverted = store
+            .revert(&result.entity_id, result.base_version, Some(repair::rollback_event(result, actor)))
+            .await
+            .map_err(|e| NormalizerError::HexadError(e.to_string()))?;
+        info!(
+            result = %result.id,
+            id = %result.entity_id,
+            restored_version = result.base_version,
+            actor,
+            "Normalization rolled back"
+        );
+        Ok(NormalizationRollback {
+            result_id: result.id.clone(),
+            entity_id: result.entity_id.clone(),
+            restored_version: result.base_version,
+            version: reverted.status.version,
+            rolled_back_by: actor.to_string(),
+            rolled_back_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NormalizerConfig, SemanticVectorStrategy};
+    use std::sync::Arc;
+    use verisim_document::TantivyDocumentStore;
+    use verisim_drift::{DriftAction, DriftDetector, DriftEvent, DriftType};
+    use verisim_graph::SimpleGraphStore;
+    use verisim_hexad::{HexadBuilder, HexadConfig, HexadSnapshot, InMemoryHexadStore};
+    use verisim_provenance::InMemoryProvenanceStore;
+    use verisim_semantic::InMemorySemanticStore;
+    use verisim_spatial::InMemorySpatialStore;
+    use verisim_temporal::InMemoryVersionStore;
+    use verisim_tensor::InMemoryTensorStore;
+    use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+    fn store() -> Arc<dyn HexadStore> {
+        Arc::new(InMemoryHexadStore::<
+            SimpleGraphStore,
+            BruteForceVectorStore,
+            TantivyDocumentStore,
+            InMemoryTensorStore,
+            InMemorySemanticStore,
+            InMemoryVersionStore<HexadSnapshot>,
+            InMemoryProvenanceStore,
+            InMemorySpatialStore,
+        >::new(
+            HexadConfig {
+                vector_dimension: 4,
+                ..Default::default()
+            },
+            Arc::new(SimpleGraphStore::in_memory().unwrap()),
+            Arc::new(BruteForceVectorStore::new(4, DistanceMetric::Cosine)),
+            Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+            Arc::new(InMemoryTensorStore::new()),
+            Arc::new(InMemorySemanticStore::new()),
+            Arc::new(InMemoryVersionStore::new()),
+            Arc::new(InMemoryProvenanceStore::new()),
+            Arc::new(InMemorySpatialStore::new()),
+        ))
+    }
+
+    async fn repaired(store: &Arc<dyn HexadStore>) -> (Normalizer, NormalizationResult) {
+        let hexad = store
+            .create(HexadBuilder::new().with_document("Ada", "Engine notes").with_embedding(vec![1.0; 4]).build())
+            .await
+            .unwrap();
+        let config = NormalizerConfig {
+            apply_repairs: true,
+            ..Default::default()
+        };
+        let normalizer = Normalizer::new(config, Arc::new(DriftDetector::with_defaults())).with_store(store.clone());
+        normalizer.register_strategy(Arc::new(SemanticVectorStrategy)).await;
+        let event = DriftEvent::new(DriftType::SemanticVectorDrift, 0.8, "drift")
+            .with_entities(vec![hexad.id.to_string()])
+            .with_actions(vec![DriftAction::Normalize]);
+        let result = normalizer.handle_drift(&hexad, &event).await.unwrap().unwrap();
+        assert!(result.applied);
+        assert_eq!((result.base_version, result.repaired_version), (1, Some(2)));
+        (normalizer, result)
+    }
+
+    #[tokio::test]
+    async fn test_rollback_restores_pre_normalization_version() {
+        let store = store();
+        let (normalizer, result) = repaired(&store).await;
+        assert_ne!(store.get(&result.entity_id).await.unwrap().unwrap().embedding.unwrap().vector, vec![1.0; 4]);
+        assert_eq!(normalizer.applied_results().await.len(), 1);
+
+        let rollback = normalizer.rollback(&result.id, "operator", false).await.unwrap();
+        assert_eq!((rollback.restored_version, rollback.version), (1, 3));
+        let current = store.get(&result.entity_id).await.unwrap().unwrap();
+        assert_eq!(current.embedding.unwrap().vector, vec![1.0; 4]);
+        assert!(matches!(
+            normalizer.rollback(&result.id, "operator", false).await,
+            Err(NormalizerError::ResultNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_refuses_after_later_writes_unless_forced() {
+        let store = store();
+        let (normalizer, result) = repaired(&store).await;
+        store
+            .update(&result.entity_id, HexadBuilder::new().with_document("Ada", "Revised notes").build())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            normalizer.rollback(&result.id, "operator", false).await,
+            Err(NormalizerError::RollbackConflict { expected: 2, actual: 3, .. })
+        ));
+        assert!(normalizer.applied_result(&result.id).await.is_some());
+
+        normalizer.rollback(&result.id, "operator", true).await.unwrap();
+        let current = store.get(&result.entity_id).await.unwrap().unwrap();
+        assert_eq!(current.embedding.unwrap().vector, vec![1.0; 4]);
+        assert_eq!(current.document.unwrap().body, "Engine notes");
+    }
+}
@@ -0,0 +1,718 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Background drift scanning
+//!
+//! The [`DriftScanner`] walks the hexad store in fixed-size batches, runs
+//! the [`DriftCalculator`] cross-modal comparisons on each entity, records
+//! the scores with the [`DriftDetector`], and hands any resulting drift
+//! events to the [`Normalizer`].  Each scan resumes where the previous one
+//! stopped, so a store larger than one batch is covered over successive
+//! scans.
+//!
+//! Comparisons need a reference point, which is taken from the batch
+//! itself:
+//!
+//! - **Vector vs semantic type**: an entity's embedding is compared with the
+//!   mean embedding of the other entities in the batch sharing its type.
+//! - **Graph vs document**: the labels of an entity's graph neighbours (their
+//!   document titles) are matched against names mentioned in its document.
+//! - **Tensor statistics**: an entity's tensor is compared with the most
+//!   common shape and the average mean/deviation across the batch.
+//!
+//! Every [`DriftCalculatorPlugin`] registered with the detector also scores
+//! each entity; its scores are recorded under the plugin's custom drift type.
+//!
+//! Entities whose document carries a namespace field (by default
+//! `namespace`) are measured in that namespace as well as globally, so the
+//! detector can report and threshold drift per project or collection.
+//!
+//! With a [`PopulationMonitor`] attached, each scan also compares recent
+//! inserts with the reference embedding distribution and records the result
+//! as semantic-vector drift.  Until the monitor has a reference, scanned
+//! embeddings are offered to it, so the stored population becomes the
+//! baseline.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use verisim_drift::{
+    CustomDriftType, DriftCalculator, DriftCalculatorPlugin, DriftDetector, DriftEvent, DriftType, PopulationDrift,
+    PopulationMonitor, TensorStats,
+};
+use verisim_graph::{GraphObject, GraphStore};
+use verisim_hexad::{Hexad, HexadId, HexadStore};
+
+use crate::{Normalizer, NormalizerError};
+
+/// Scanner configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannerConfig {
+    /// Entities examined per scan
+    pub batch_size: usize,
+    /// Time between scheduled scans (seconds)
+    pub interval_secs: u64,
+    /// Hand drift events to the normalizer
+    pub normalize: bool,
+    /// Document field naming an entity's namespace; drift is only measured
+    /// globally when unset
+    #[serde(default)]
+    pub namespace_field: Option<String>,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 200,
+            interval_secs: 300,
+            normalize: true,
+            namespace_field: Some("namespace".to_string()),
+        }
+    }
+}
+
+/// Outcome of one scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// When the scan started
+    pub started_at: DateTime<Utc>,
+    /// Wall-clock duration
+    pub duration_ms: u64,
+    /// Entities examined
+    pub scanned: usize,
+    /// Drift scores recorded with the detector
+    pub measurements: usize,
+    /// Drift events the detector emitted
+    pub events: Vec<DriftEvent>,
+    /// Normalizations performed in response
+    pub normalizations: usize,
+    /// Normalizations that failed
+    pub normalization_failures: usize,
+    /// Population-level embedding drift, when measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub population: Option<PopulationDrift>,
+}
+
+/// Periodically samples entities and records cross-modal drift.
+pub struct DriftScanner {
+    config: ScannerConfig,
+    store: Arc<dyn HexadStore>,
+    graph: Option<Arc<dyn GraphStore>>,
+    calculator: DriftCalculator,
+    detector: Arc<DriftDetector>,
+    normalizer: Option<Arc<Normalizer>>,
+    population: Option<Arc<PopulationMonitor>>,
+    /// Offset of the next batch; serialises concurrent scans
+    cursor: Mutex<usize>,
+    last_report: RwLock<Option<ScanReport>>,
+}
+
+impl DriftScanner {
+    /// Create a scanner over a hexad store
+    pub fn new(config: ScannerConfig, store: Arc<dyn HexadStore>, detector: Arc<DriftDetector>) -> Self {
+        Self {
+            config,
+            store,
+            graph: None,
+            calculator: DriftCalculator::default(),
+            detector,
+            normalizer: None,
+            population: None,
+            cursor: Mutex::new(0),
+            last_report: RwLock::new(None),
+        }
+    }
+
+    /// Read outgoing edges from this graph store for graph-document checks
+    pub fn with_graph(mut self, graph: Arc<dyn GraphStore>) -> Self {
+        self.graph = Some(graph);
+        self
+    }
+
+    /// Forward drift events to a normalizer
+    pub fn with_normalizer(mut self, normalizer: Arc<Normalizer>) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Measure population-level embedding drift on each scan
+    pub fn with_population(mut self, monitor: Arc<PopulationMonitor>) -> Self {
+        self.population = Some(monitor);
+        self
+    }
+
+    /// Scanner configuration
+    pub fn config(&self) -> &ScannerConfig {
+        &self.config
+    }
+
+    /// Report of the most recent scan
+    pub async fn last_report(&self) -> Option<ScanReport> {
+        self.last_report.read().await.clone()
+    }
+
+    /// Run scans every `interval_secs` until the task is aborted.
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let period = Duration::from_secs(self.config.interval_secs.max(1));
+        tokio::spawn(verisim_hexad::security::system(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.scan().await {
+                    warn!(error = %e, "Drift scan failed");
+                }
+            }
+        }))
+    }
+
+    /// Scan the next batch of entities.
+    pub async fn scan(&self) -> Result<ScanReport, NormalizerError> {
+        let started_at = Utc::now();
+        let start = std::time::Instant::now();
+        let mut cursor = self.cursor.lock().await;
+
+        let mut batch = self.list(*cursor).await?;
+        if batch.is_empty() && *cursor > 0 {
+            // Wrapped around the end of the store
+            *cursor = 0;
+            batch = self.list(0).await?;
+        }
+        *cursor += batch.len();
+
+        let mut report = ScanReport {
+            started_at,
+            duration_ms: 0,
+            scanned: batch.len(),
+            measurements: 0,
+            events: Vec::new(),
+            normalizations: 0,
+            normalization_failures: 0,
+            population: None,
+        };
+
+        let baseline = Baseline::from_batch(&batch);
+        let plugins = self
+            .detector
+            .plugins()
+            .map_err(|e| NormalizerError::ChannelError(e.to_string()))?;
+        for hexad in &batch {
+            let namespace = self.namespace_of(hexad);
+            let mut scores = self.measure(hexad, &baseline).await;
+            scores.extend(measure_plugins(&plugins, hexad).await);
+            for (drift_type, score) in scores {
+                report.measurements += 1;
+                let event = self
+                    .detector
+                    .record_in(namespace, drift_type, score, vec![hexad.id.to_string()])
+                    .await
+                    .map_err(|e| NormalizerError::ChannelError(e.to_string()))?;
+                let Some(event) = event else { continue };
+
+                if let Some(normalizer) = self.normalizer.as_ref().filter(|_| self.config.normalize) {
+                    match normalizer.handle_drift(hexad, &event).await {
+                        Ok(Some(_)) => report.normalizations += 1,
+                        Ok(None) => {}
+                        Err(e) => {
+                            report.normalization_failures += 1;
+                            warn!(id = %hexad.id, error = %e, "Normalization after drift scan failed");
+                        }
+                    }
+                }
+                report.events.push(event);
+            }
+        }
+
+        if let Some(monitor) = &self.population {
+            self.measure_population(monitor, &batch, &mut report).await?;
+        }
+
+        report.duration_ms = start.elapsed().as_millis() as u64;
+        info!(
+            scanned = report.scanned,
+            measurements = report.measurements,
+            events = report.events.len(),
+            "Drift scan complete"
+        );
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Compare recent inserts with the reference population.  The result
+    /// concerns no single entity, so it is recorded but not normalized.
+    async fn measure_population(
+        &self,
+        monitor: &PopulationMonitor,
+        batch: &[Hexad],
+        report: &mut ScanReport,
+    ) -> Result<(), NormalizerError> {
+        let drift_error = |e: verisim_drift::DriftError| NormalizerError::ChannelError(e.to_string());
+        for embedding in batch.iter().filter_map(|h| h.embedding.as_ref()) {
+            monitor.seed_reference(&embedding.vector).map_err(drift_error)?;
+        }
+        let Some(drift) = monitor.measure().map_err(drift_error)? else {
+            return Ok(());
+        };
+        debug!(psi = drift.psi, kl = drift.kl_divergence, "Population drift measured");
+        report.measurements += 1;
+        if let Some(event) = self
+            .detector
+            .record(DriftType::SemanticVectorDrift, drift.score, Vec::new())
+            .await
+            .map_err(drift_error)?
+        {
+            report.events.push(event);
+        }
+        report.population = Some(drift);
+        Ok(())
+    }
+
+    /// Namespace of an entity, from its document's namespace field
+    fn namespace_of<'a>(&self, hexad: &'a Hexad) -> Option<&'a str> {
+        let field = self.config.namespace_field.as_deref()?;
+        hexad
+            .document
+            .as_ref()?
+            .fields
+            .get(field)
+            .map(String::as_str)
+            .filter(|ns| !ns.is_empty())
+    }
+
+    async fn list(&self, offset: usize) -> Result<Vec<Hexad>, NormalizerError> {
+        self.store
+            .list(self.config.batch_size, offset)
+            .await
+            .map_err(|e| NormalizerError::HexadError(e.to_string()))
+    }
+
+    /// Every applicable drift score for one entity.
+    async fn measure(&self, hexad: &Hexad, baseline: &Baseline) -> Vec<(DriftType, f64)> {
+        let mut scores = Vec::new();
+
+        if let (Some(embedding), Some(semantic)) = (&hexad.embedding, &hexad.semantic) {
+            let type_embeddings = baseline.type_centroids(&semantic.types, &embedding.vector);
+            if !type_embeddings.is_empty() {
+                scores.push((
+                    DriftType::SemanticVectorDrift,
+                    self.calculator.semantic_vector_drift(
+                        &embedding.vector,
+                        &semantic.types,
+                        &type_embeddings,
+                    ),
+                ));
+            }
+        }
+
+        if let (Some(document), Some(node), Some(graph)) = (&hexad.document, &hexad.graph_node, &self.graph) {
+            match graph.outgoing(node).await {
+                Ok(edges) if !edges.is_empty() => {
+                    let mut relationships = Vec::with_capacity(edges.len());
+                    for edge in edges {
+                        let target = match edge.object {
+                            GraphObject::Node(target) => self.label(&target.local_name).await,
+                            GraphObject::Literal { value, .. } => value,
+                        };
+                        relationships.push((edge.predicate.local_name, target));
+                    }
+                    let text = format!("{}\n{}", document.title, document.body);
+                    scores.push((
+                        DriftType::GraphDocumentDrift,
+                        self.calculator.graph_document_drift(&text, &mentions(&text), &relationships),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => debug!(id = %hexad.id, error = %e, "Graph lookup failed during drift scan"),
+            }
+        }
+
+        if let (Some(tensor), Some((shape, stats))) = (&hexad.tensor, &baseline.tensor) {
+            scores.push((
+                DriftType::TensorDrift,
+                self.calculator
+                    .tensor_drift(&tensor.data, shape, &tensor.shape, Some(stats.clone())),
+            ));
+        }
+
+        scores
+    }
+
+    /// Document title of a graph neighbour, or its ID if it has none.
+    async fn label(&self, id: &str) -> String {
+        match self.store.get(&HexadId::new(id)).await {
+            Ok(Some(Hexad { document: Some(doc), .. })) => doc.title,
+            _ => id.to_string(),
+        }
+    }
+}
+
+/// Scores from custom drift calculators; a failing plugin is skipped.
+async fn measure_plugins(plugins: &[Arc<dyn DriftCalculatorPlugin>], hexad: &Hexad) -> Vec<(DriftType, f64)> {
+    let mut scores = Vec::new();
+    for plugin in plugins {
+        let drift_type = match CustomDriftType::new(plugin.name()) {
+            Ok(custom) => DriftType::Custom(custom),
+            Err(e) => {
+                warn!(plugin = plugin.name(), error = %e, "Invalid drift plugin");
+                continue;
+            }
+        };
+        match plugin.measure(hexad).await {
+            Ok(Some(score)) => scores.push((drift_type, score.clamp(0.0, 1.0))),
+            Ok(None) => {}
+            Err(e) => warn!(plugin = plugin.name(), id = %hexad.id, error = %e, "Drift plugin failed"),
+        }
+    }
+    scores
+}
+
+/// Capitalised words in a text, as a cheap stand-in for named entities.
+pub(crate) fn mentions(text: &str) -> Vec<String> {
+    let mut found: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() >= 3 && w.chars().next().is_some_and(char::is_uppercase))
+        .map(str::to_string)
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Batch-wide reference values for the comparisons.
+struct Baseline {
+    /// Per semantic type: summed embedding and member count
+    type_sums: HashMap<String, (Vec<f64>, usize)>,
+    /// Most common tensor shape with the average statistics of that shape
+    tensor: Option<(Vec<usize>, TensorStats)>,
+}
+
+impl Baseline {
+    fn from_batch(batch: &[Hexad]) -> Self {
+        let mut type_sums: HashMap<String, (Vec<f64>, usize)> = HashMap::new();
+        for hexad in batch {
+            let (Some(embedding), Some(semantic)) = (&hexad.embedding, &hexad.semantic) else {
+                continue;
+            };
+            for t in &semantic.types {
+                let (sum, n) = type_sums
+                    .entry(t.clone())
+                    .or_insert_with(|| (vec![0.0; embedding.vector.len()], 0));
+                if sum.len() == embedding.vector.len() {
+                    sum.iter_mut().zip(&embedding.vector).for_each(|(s, v)| *s += f64::from(*v));
+                    *n += 1;
+                }
+            }
+        }
+
+        let mut by_shape: HashMap<&[usize], Vec<TensorStats>> = HashMap::new();
+        for tensor in batch.iter().filter_map(|h| h.tensor.as_ref()) {
+            by_shape
+                .entry(tensor.shape.as_slice())
+                .or_default()
+                .push(TensorStats::compute(&tensor.data));
+        }
+        let tensor = by_shape
+            .into_iter()
+            .filter(|(_, stats)| stats.len() >= 2)
+            .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.cmp(a.0)))
+            .map(|(shape, stats)| {
+                let n = stats.len() as f64;
+                let mut avg = TensorStats::compute(&[]);
+                avg.mean = stats.iter().map(|s| s.mean).sum::<f64>() / n;
+                avg.std_dev = stats.iter().map(|s| s.std_dev).sum::<f64>() / n;
+                (shape.to_vec(), avg)
+            });
+
+        Self { type_sums, tensor }
+    }
+
+    /// Mean embedding of each given type, leaving the entity's own vector
+    /// out.  Types with no other member in the batch are skipped.
+    fn type_centroids(&self, types: &[String], own: &[f32]) -> Vec<(String, Vec<f32>)> {
+        types
+            .iter()
+            .filter_map(|t| {
+                let (sum, n) = self.type_sums.get(t)?;
+                if *n < 2 || sum.len() != own.len() {
+                    return None;
+                }
+                let centroid = sum
+                    .iter()
+                    .zip(own)
+                    .map(|(s, v)| ((s - f64::from(*v)) / (*n - 1) as f64) as f32)
+                    .collect();
+                Some((t.clone(), centroid))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verisim_document::TantivyDocumentStore;
+    use verisim_drift::DriftThresholds;
+    use verisim_graph::SimpleGraphStore;
+    use verisim_hexad::{HexadBuilder, HexadConfig, HexadSnapshot, InMemoryHexadStore};
+    use verisim_provenance::InMemoryProvenanceStore;
+    use verisim_semantic::InMemorySemanticStore;
+    use verisim_spatial::InMemorySpatialStore;
+    use verisim_temporal::InMemoryVersionStore;
+    use verisim_tensor::InMemoryTensorStore;
+    use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+    type TestStore = InMemoryHexadStore<
+        SimpleGraphStore,
+        BruteForceVectorStore,
+        TantivyDocumentStore,
+        InMemoryTensorStore,
+        InMemorySemanticStore,
+        InMemoryVersionStore<HexadSnapshot>,
+        InMemoryProvenanceStore,
+        InMemorySpatialStore,
+    >;
+
+    fn store() -> (Arc<TestStore>, Arc<SimpleGraphStore>) {
+        let graph = Arc::new(SimpleGraphStore::in_memory().unwrap());
+        let store = InMemoryHexadStore::new(
+            HexadConfig {
+                vector_dimension: 3,
+                ..Default::default()
+            },
+            graph.clone(),
+            Arc::new(BruteForceVectorStore::new(3, DistanceMetric::Cosine)),
+            Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+            Arc::new(InMemoryTensorStore::new()),
+            Arc::new(InMemorySemanticStore::new()),
+            Arc::new(InMemoryVersionStore::new()),
+            Arc::new(InMemoryProvenanceStore::new()),
+            Arc::new(InMemorySpatialStore::new()),
+        );
+        (Arc::new(store), graph)
+    }
+
+    #[test]
+    fn test_mentions_are_capitalised_words() {
+        assert_eq!(
+            mentions("Alice met Bob in Paris. She liked it."),
+            vec!["Alice", "Bob", "Paris", "She"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_records_vector_drift_for_outlier() {
+        let (store, graph) = store();
+        for embedding in [
+            vec![1.0, 0.0, 0.0],
+            vec![0.98, 0.05, 0.0],
+            vec![0.97, 0.0, 0.05],
+            vec![0.99, 0.02, 0.02],
+            vec![0.0, 0.0, 1.0],
+        ] {
+            store
+                .create(
+                    HexadBuilder::new()
+                        .with_embedding(embedding)
+                        .with_types(vec!["https://example.org/Sensor"])
+                        .build(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let detector = Arc::new(DriftDetector::new(DriftThresholds::default()));
+        let scanner = DriftScanner::new(ScannerConfig::default(), store, detector.clone()).with_graph(graph);
+        let report = scanner.scan().await.unwrap();
+
+        assert_eq!(report.scanned, 5);
+        assert_eq!(report.measurements, 5);
+        // Only the orthogonal embedding drifts past the 0.3 threshold.
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.events[0].drift_type, DriftType::SemanticVectorDrift);
+        let metrics = detector.get_metrics(DriftType::SemanticVectorDrift).unwrap().unwrap();
+        assert_eq!(metrics.measurement_count, 5);
+        assert!(scanner.last_report().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_scan_walks_store_in_batches() {
+        let (store, _) = store();
+        for i in 0..5 {
+            store
+                .create(HexadBuilder::new().with_document(&format!("Doc {}", i), "body").build())
+                .await
+                .unwrap();
+        }
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let config = ScannerConfig {
+            batch_size: 2,
+            ..Default::default()
+        };
+        let scanner = DriftScanner::new(config, store, detector);
+
+        let scanned: Vec<usize> = {
+            let mut v = Vec::new();
+            for _ in 0..4 {
+                v.push(scanner.scan().await.unwrap().scanned);
+            }
+            v
+        };
+        assert_eq!(scanned, vec![2, 2, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_scan_graph_document_drift_uses_neighbour_titles() {
+        let (store, graph) = store();
+        let paris = store
+            .create(HexadBuilder::new().with_document("Paris", "Capital of France").build())
+            .await
+            .unwrap();
+        let berlin = store
+            .create(HexadBuilder::new().with_document("Berlin", "Capital of Germany").build())
+            .await
+            .unwrap();
+        let consistent = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Trip", "Visited Paris last spring")
+                    .with_relationships(vec![("visited", paris.id.as_str())])
+                    .build(),
+            )
+            .await
+            .unwrap();
+        let inconsistent = store
+            .create(
+                HexadBuilder::new()
+                    .with_document("Trip", "Visited Paris last spring")
+                    .with_relationships(vec![("visited", berlin.id.as_str())])
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let scanner = DriftScanner::new(ScannerConfig::default(), store.clone(), detector).with_graph(graph);
+        let baseline = Baseline::from_batch(&[]);
+
+        let score = |scores: Vec<(DriftType, f64)>| {
+            scores
+                .into_iter()
+                .find(|(t, _)| *t == DriftType::GraphDocumentDrift)
+                .map(|(_, s)| s)
+                .unwrap()
+        };
+        let good = score(scanner.measure(&consistent, &baseline).await);
+        let bad = score(scanner.measure(&inconsistent, &baseline).await);
+        assert!(bad > good, "expected {} > {}", bad, good);
+    }
+
+    #[tokio::test]
+    async fn test_scan_measures_population_drift() {
+        let (store, _) = store();
+        for embedding in [vec![1.0, 0.1, 0.0], vec![0.9, 0.0, 0.1], vec![1.1, 0.05, 0.05], vec![0.95, 0.1, 0.1]] {
+            store
+                .create(HexadBuilder::new().with_embedding(embedding).build())
+                .await
+                .unwrap();
+        }
+        let monitor = Arc::new(PopulationMonitor::new(verisim_drift::PopulationConfig {
+            reference_size: 4,
+            min_recent: 4,
+            ..Default::default()
+        }));
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let scanner = DriftScanner::new(ScannerConfig::default(), store, detector).with_population(monitor.clone());
+
+        // The stored embeddings become the reference; nothing recent yet.
+        let report = scanner.scan().await.unwrap();
+        assert!(report.population.is_none());
+        assert_eq!(monitor.status().unwrap().reference_size, Some(4));
+
+        // Recent inserts point somewhere else entirely.
+        for embedding in [[0.0, 0.0, 1.0], [0.1, 0.0, 0.9], [0.0, 0.1, 1.1], [0.05, 0.05, 0.95]] {
+            monitor.observe(&embedding).unwrap();
+        }
+        let report = scanner.scan().await.unwrap();
+        let population = report.population.unwrap();
+        assert!(population.score > 0.5, "score {}", population.score);
+        assert!(report
+            .events
+            .iter()
+            .any(|e| e.drift_type == DriftType::SemanticVectorDrift && e.affected_entities.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_scan_scopes_drift_by_namespace() {
+        let (store, _) = store();
+        for (namespace, embedding) in [
+            ("alpha", vec![1.0, 0.0, 0.0]),
+            ("alpha", vec![0.98, 0.05, 0.0]),
+            ("beta", vec![0.97, 0.0, 0.05]),
+            ("beta", vec![0.0, 0.0, 1.0]),
+        ] {
+            let mut input = HexadBuilder::new()
+                .with_document("Sensor", "reading")
+                .with_embedding(embedding)
+                .with_types(vec!["https://example.org/Sensor"])
+                .build();
+            input
+                .document
+                .as_mut()
+                .unwrap()
+                .fields
+                .insert("namespace".to_string(), namespace.to_string());
+            store.create(input).await.unwrap();
+        }
+
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let scanner = DriftScanner::new(ScannerConfig::default(), store, detector.clone());
+        let report = scanner.scan().await.unwrap();
+
+        let by_namespace = detector.namespace_metrics().unwrap();
+        assert_eq!(by_namespace.len(), 2);
+        let beta = &by_namespace["beta"][&DriftType::SemanticVectorDrift];
+        let alpha = &by_namespace["alpha"][&DriftType::SemanticVectorDrift];
+        assert!(beta.max_score > alpha.max_score);
+        assert!(report.events.iter().all(|e| e.namespace.as_deref() == Some("beta")));
+    }
+
+    struct TitleLength;
+
+    #[async_trait::async_trait]
+    impl DriftCalculatorPlugin for TitleLength {
+        fn name(&self) -> &str {
+            "title_length"
+        }
+
+        async fn measure(&self, hexad: &Hexad) -> Result<Option<f64>, verisim_drift::DriftError> {
+            Ok(hexad.document.as_ref().map(|d| d.title.len() as f64 / 10.0))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_invokes_registered_plugins() {
+        let (store, _) = store();
+        for title in ["Short", "A considerably longer title"] {
+            store
+                .create(HexadBuilder::new().with_document(title, "body").build())
+                .await
+                .unwrap();
+        }
+        let detector = Arc::new(DriftDetector::with_defaults());
+        let drift_type = detector.register_plugin(Arc::new(TitleLength)).unwrap();
+        let scanner = DriftScanner::new(ScannerConfig::default(), store, detector.clone());
+
+        let report = scanner.scan().await.unwrap();
+        assert_eq!(report.measurements, 2);
+        let metrics = detector.get_metrics(drift_type).unwrap().unwrap();
+        assert_eq!(metrics.measurement_count, 2);
+        // The long title is clamped to 1.0 and crosses the default threshold.
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.events[0].drift_type, drift_type);
+    }
+}
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Strategy selection
+//!
+//! When several registered strategies apply to a drift type, the normalizer
+//! runs the one with the highest priority; strategies with equal priority
+//! keep their registration order.  A [`StrategySelection`] sets priorities,
+//! disables strategies, and gives namespaces their own strategy sets:
+//!
+//! - A strategy without settings has priority 0 and is enabled.
+//! - A disabled strategy is skipped, except in namespaces whose set names it.
+//! - A namespace with a set only uses the strategies in it, enabled or not,
+//!   so an experimental strategy can be registered disabled and trialled on
+//!   one project.
+//!
+//! The selection can be replaced at runtime with
+//! [`Normalizer::set_strategy_selection`]; with
+//! [`Normalizer::with_strategy_persistence`] it survives restarts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use verisim_drift::DriftType;
+
+use crate::{NormalizationStrategy, Normalizer, NormalizerError};
+
+/// Priority and enable flag of one strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrategySettings {
+    /// Higher priorities are tried first
+    #[serde(default)]
+    pub priority: i32,
+    /// Whether the strategy runs outside namespaces that name it
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for StrategySettings {
+    fn default() -> Self {
+        Self {
+            priority: 0,
+            enabled: true,
+        }
+    }
+}
+
+/// Runtime strategy configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategySelection {
+    /// Settings by strategy name; unlisted strategies use the defaults
+    #[serde(default)]
+    pub strategies: HashMap<String, StrategySettings>,
+    /// Strategy sets by namespace; events in a listed namespace only use
+    /// the strategies in its set
+    #[serde(default)]
+    pub namespaces: HashMap<String, Vec<String>>,
+}
+
+impl StrategySelection {
+    /// Settings of a strategy
+    pub fn settings(&self, strategy: &str) -> StrategySettings {
+        self.strategies.get(strategy).copied().unwrap_or_default()
+    }
+
+    /// Whether a strategy may run for an event in `namespace`
+    pub fn allows(&self, strategy: &str, namespace: Option<&str>) -> bool {
+        match namespace.and_then(|ns| self.namespaces.get(ns)) {
+            Some(set) => set.iter().any(|name| name == strategy),
+            None => self.settings(strategy).enabled,
+        }
+    }
+
+    /// Check that every strategy the selection names is registered
+    pub fn validate(&self, registered: &[String]) -> Result<(), NormalizerError> {
+        let named = self
+            .strategies
+            .keys()
+            .chain(self.namespaces.values().flatten());
+        for name in named {
+            if !registered.contains(name) {
+                return Err(NormalizerError::StrategyNotFound(name.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Normalizer {
+    /// Load the strategy selection from `path` and save changes there.  A
+    /// missing file leaves the current selection in place.
+    pub fn with_strategy_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self, NormalizerError> {
+        let path = path.into();
+        if path.exists() {
+            let persistence = |e: &dyn fmt::Display| NormalizerError::Persistence(format!("{}: {}", path.display(), e));
+            let json = std::fs::read_to_string(&path).map_err(|e| persistence(&e))?;
+            self.selection = tokio::sync::RwLock::new(serde_json::from_str(&json).map_err(|e| persistence(&e))?);
+        }
+        self.selection_path = Some(path);
+        Ok(self)
+    }
+
+    /// The current strategy selection
+    pub async fn strategy_selection(&self) -> StrategySelection {
+        self.selection.read().await.clone()
+    }
+
+    /// Replace the strategy selection, saving it first when the normalizer
+    /// is persistent.  Returns the previous selection.
+    pub async fn set_strategy_selection(
+        &self,
+        selection: StrategySelection,
+    ) -> Result<StrategySelection, NormalizerError> {
+        selection.validate(&self.strategies().await)?;
+        if let Some(path) = &self.selection_path {
+            save_selection(path, &selection)?;
+        }
+        info!(
+            configured = selection.strategies.len(),
+            namespaces = selection.namespaces.len(),
+            "Strategy selection changed"
+        );
+        Ok(std::mem::replace(&mut *self.selection.write().await, selection))
+    }
+
+    /// Names of the strategies that may run for events in `namespace`,
+    /// highest priority first
+    pub async fn strategy_order(&self, namespace: Option<&str>) -> Vec<String> {
+        self.candidates(namespace)
+            .await
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect()
+    }
+
+    /// The strategy that handles `drift_type` in `namespace`
+    pub(crate) async fn select_strategy(
+        &self,
+        drift_type: DriftType,
+        namespace: Option<&str>,
+    ) -> Option<Arc<dyn NormalizationStrategy>> {
+        self.candidates(namespace)
+            .await
+            .into_iter()
+            .find(|s| s.applies_to(drift_type))
+    }
+
+    /// Strategies allowed in `namespace`, highest priority first
+    async fn candidates(&self, namespace: Option<&str>) -> Vec<Arc<dyn NormalizationStrategy>> {
+        let selection = self.selection.read().await;
+        let mut candidates: Vec<Arc<dyn NormalizationStrategy>> = self
+            .strategies
+            .read()
+            .await
+            .iter()
+            .filter(|s| selection.allows(s.name(), namespace))
+            .cloned()
+            .collect();
+        // Stable, so equal priorities keep registration order
+        candidates.sort_by_key(|s| std::cmp::Reverse(selection.settings(s.name()).priority));
+        candidates
+    }
+}
+
+fn save_selection(path: &Path, selection: &StrategySelection) -> Result<(), NormalizerError> {
+    let persistence = |e: &dyn fmt::Display| NormalizerError::Persistence(format!("{}: {}", path.display(), e));
+    let json = serde_json::to_string_pretty(selection).map_err(|e| persistence(&e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| persistence(&e))?;
+    std::fs::rename(&tmp, path).map_err(|e| persistence(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GraphDocumentStrategy, NormalizationResult, NormalizationType, SemanticVectorStrategy};
+    use async_trait::async_trait;
+    use verisim_drift::{DriftDetector, DriftEvent};
+    use verisim_hexad::Hexad;
+
+    /// Experimental strategy for semantic-vector drift
+    struct TrialStrategy;
+
+    #[async_trait]
+    impl NormalizationStrategy for TrialStrategy {
+        fn name(&self) -> &str {
+            "trial-vector"
+        }
+
+        fn applies_to(&self, drift_type: DriftType) -> bool {
+            drift_type == DriftType::SemanticVectorDrift
+        }
+
+        async fn normalize(&self, hexad: &Hexad, _event: &DriftEvent) -> Result<NormalizationResult, NormalizerError> {
+            Ok(NormalizationResult {
+                id: uuid::Uuid::new_v4().to_string(),
+                entity_id: hexad.id.clone(),
+                normalization_type: NormalizationType::VectorRegeneration,
+                success: true,
+                changes: Vec::new(),
+                applied: false,
+                base_version: hexad.status.version,
+                repaired_version: None,
+                duration_ms: 0,
+                completed_at: chrono::Utc::now(),
+            })
+        }
+    }
+
+    async fn normalizer() -> Normalizer {
+        let normalizer = Normalizer::with_defaults(Arc::new(DriftDetector::with_defaults()));
+        normalizer.register_strategy(Arc::new(SemanticVectorStrategy)).await;
+        normalizer.register_strategy(Arc::new(GraphDocumentStrategy)).await;
+        normalizer.register_strategy(Arc::new(TrialStrategy)).await;
+        normalizer
+    }
+
+    fn trial_selection() -> StrategySelection {
+        StrategySelection {
+            strategies: HashMap::from([(
+                "trial-vector".to_string(),
+                StrategySettings {
+                    priority: 10,
+                    enabled: false,
+                },
+            )]),
+            namespaces: HashMap::from([("project-x".to_string(), vec!["trial-vector".to_string()])]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priorities_and_namespace_sets_select_strategy() {
+        let normalizer = normalizer().await;
+        let selected = |namespace: Option<&'static str>| {
+            let normalizer = &normalizer;
+            async move {
+                normalizer
+                    .select_strategy(DriftType::SemanticVectorDrift, namespace)
+                    .await
+                    .map(|s| s.name().to_string())
+            }
+        };
+        assert_eq!(selected(None).await.as_deref(), Some("semantic-vector-sync"));
+
+        normalizer.set_strategy_selection(trial_selection()).await.unwrap();
+        assert_eq!(selected(None).await.as_deref(), Some("semantic-vector-sync"));
+        assert_eq!(selected(Some("other")).await.as_deref(), Some("semantic-vector-sync"));
+        assert_eq!(selected(Some("project-x")).await.as_deref(), Some("trial-vector"));
+        assert_eq!(normalizer.strategy_order(Some("project-x")).await, vec!["trial-vector"]);
+
+        let mut enabled = trial_selection();
+        enabled.strategies.get_mut("trial-vector").unwrap().enabled = true;
+        normalizer.set_strategy_selection(enabled).await.unwrap();
+        assert_eq!(selected(None).await.as_deref(), Some("trial-vector"));
+        assert_eq!(
+            normalizer.strategy_order(None).await,
+            vec!["trial-vector", "semantic-vector-sync", "graph-document-sync"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strategy_selection_is_validated_and_persisted() {
+        let path = std::env::temp_dir().join(format!("verisim-strategies-{}.json", uuid::Uuid::new_v4()));
+        let normalizer = normalizer().await.with_strategy_persistence(&path).unwrap();
+
+        let mut unknown = trial_selection();
+        unknown.namespaces.insert("project-y".to_string(), vec!["no-such-strategy".to_string()]);
+        assert!(matches!(
+            normalizer.set_strategy_selection(unknown).await,
+            Err(NormalizerError::StrategyNotFound(name)) if name == "no-such-strategy"
+        ));
+        assert!(!path.exists());
+
+        normalizer.set_strategy_selection(trial_selection()).await.unwrap();
+        let reloaded = Normalizer::with_defaults(Arc::new(DriftDetector::with_defaults()))
+            .with_strategy_persistence(&path)
+            .unwrap();
+        assert_eq!(reloaded.strategy_selection().await, trial_selection());
+        std::fs::remove_file(&path).ok();
+    }
+}
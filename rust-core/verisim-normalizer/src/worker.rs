@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Background normalization workers
+//!
+//! [`Normalizer::enqueue`] puts a drift event for one entity on a bounded
+//! queue.  [`Normalizer::spawn_workers`] starts a dispatcher that takes jobs
+//! off the queue and runs each on a pool of at most
+//! [`NormalizerConfig::max_concurrent`](crate::NormalizerConfig) tasks.  A job
+//! loads the entity from the normalizer's store and goes through
+//! [`Normalizer::handle_drift`]; a failed job is re-queued after an
+//! exponential backoff starting at `failure_backoff_secs`, up to
+//! `max_retries` times.
+//!
+//! `NormalizerStatus::pending_count` counts queued jobs, including retries
+//! waiting out their backoff; `active_count` counts jobs being normalized.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use verisim_drift::DriftEvent;
+use verisim_hexad::HexadId;
+
+use crate::{Normalizer, NormalizerError};
+
+/// Longest retry delay, whatever the attempt count
+const MAX_BACKOFF_SECS: u64 = 3600;
+
+/// A queued normalization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationJob {
+    /// Entity to normalize
+    pub entity_id: HexadId,
+    /// Drift event that triggered the normalization
+    pub event: DriftEvent,
+    /// Failed attempts so far
+    pub attempts: u32,
+}
+
+impl NormalizationJob {
+    /// A first attempt at normalizing `entity_id` for `event`
+    pub fn new(entity_id: HexadId, event: DriftEvent) -> Self {
+        Self {
+            entity_id,
+            event,
+            attempts: 0,
+        }
+    }
+}
+
+impl Normalizer {
+    /// Queue a normalization for the worker pool.
+    ///
+    /// Fails when the workers have not been started or the queue is full.
+    pub async fn enqueue(&self, job: NormalizationJob) -> Result<(), NormalizerError> {
+        let sender = self
+            .queue
+            .get()
+            .ok_or_else(|| NormalizerError::ChannelError("normalization workers are not running".into()))?;
+        sender.try_send(job).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => NormalizerError::QueueFull,
+            mpsc::error::TrySendError::Closed(_) => NormalizerError::ChannelError("normalization queue closed".into()),
+        })?;
+        self.status.write().await.pending_count += 1;
+        Ok(())
+    }
+
+    /// Start the dispatcher and worker pool.  Requires a store
+    /// ([`Normalizer::with_store`]) and can only be called once.
+    pub fn spawn_workers(self: Arc<Self>) -> Result<JoinHandle<()>, NormalizerError> {
+        if self.store.is_none() {
+            return Err(NormalizerError::ChannelError("normalization workers need a hexad store".into()));
+        }
+        let (tx, mut rx) = mpsc::channel(self.config.queue_capacity.max(1));
+        self.queue
+            .set(tx)
+            .map_err(|_| NormalizerError::ChannelError("normalization workers already running".into()))?;
+
+        let permits = Arc::new(Semaphore::new(self.config.max_concurrent.max(1)));
+        Ok(tokio::spawn(async move {
+            self.status.write().await.running = true;
+            while let Some(job) = rx.recv().await {
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    break;
+                };
+                let normalizer = self.clone();
+                tokio::spawn(verisim_hexad::security::system(async move {
+                    normalizer.run_job(job).await;
+                    drop(permit);
+                }));
+            }
+            self.status.write().await.running = false;
+        }))
+    }
+
+    /// Normalize one queued entity, scheduling a retry on failure
+    async fn run_job(self: &Arc<Self>, job: NormalizationJob) {
+        self.status.write().await.pending_count -= 1;
+        let Some(store) = self.store.as_ref() else {
+            return;
+        };
+
+        let outcome = match store.get(&job.entity_id).await {
+            Ok(Some(hexad)) => self.handle_drift(&hexad, &job.event).await.map(|_| ()),
+            Ok(None) => {
+                debug!(id = %job.entity_id, "Queued entity no longer exists; dropping normalization");
+                return;
+            }
+            Err(e) => {
+                self.status.write().await.failure_count += 1;
+                Err(NormalizerError::HexadError(e.to_string()))
+            }
+        };
+
+        let Err(e) = outcome else {
+            return;
+        };
+        if job.attempts >= self.config.max_retries {
+            warn!(id = %job.entity_id, attempts = job.attempts + 1, error = %e, "Normalization failed; giving up");
+            return;
+        }
+
+        let delay = self
+            .config
+            .failure_backoff_secs
+            .saturating_mul(1u64 << job.attempts.min(16))
+            .min(MAX_BACKOFF_SECS);
+        warn!(id = %job.entity_id, attempt = job.attempts + 1, retry_in_secs = delay, error = %e, "Normalization failed; retrying");
+        {
+            let mut status = self.status.write().await;
+            status.pending_count += 1;
+            status.retry_count += 1;
+        }
+        let normalizer = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            let retry = NormalizationJob {
+                attempts: job.attempts + 1,
+                ..job
+            };
+            // The retry was already counted as pending.
+            let sent = match normalizer.queue.get() {
+                Some(sender) => sender.send(retry).await.is_ok(),
+                None => false,
+            };
+            if !sent {
+                normalizer.status.write().await.pending_count -= 1;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NormalizationResult, NormalizationStrategy, NormalizerConfig, TemporalRepairStrategy};
+    use async_trait::async_trait;
+    use verisim_document::TantivyDocumentStore;
+    use verisim_drift::{DriftAction, DriftDetector, DriftType};
+    use verisim_graph::SimpleGraphStore;
+    use verisim_hexad::{Hexad, HexadBuilder, HexadConfig, HexadSnapshot, HexadStore, InMemoryHexadStore};
+    use verisim_provenance::InMemoryProvenanceStore;
+    use verisim_semantic::InMemorySemanticStore;
+    use verisim_spatial::InMemorySpatialStore;
+    use verisim_temporal::InMemoryVersionStore;
+    use verisim_tensor::InMemoryTensorStore;
+    use verisim_vector::{BruteForceVectorStore, DistanceMetric};
+
+    fn store() -> Arc<dyn HexadStore> {
+        Arc::new(InMemoryHexadStore::<
+            SimpleGraphStore,
+            BruteForceVectorStore,
+            TantivyDocumentStore,
+            InMemoryTensorStore,
+            InMemorySemanticStore,
+            InMemoryVersionStore<HexadSnapshot>,
+            InMemoryProvenanceStore,
+            InMemorySpatialStore,
+        >::new(
+            HexadConfig {
+                vector_dimension: 3,
+                ..Default::default()
+            },
+            Arc::new(SimpleGraphStore::in_memory().unwrap()),
+            Arc::new(BruteForceVectorStore::new(3, DistanceMetric::Cosine)),
+            Arc::new(TantivyDocumentStore::in_memory().unwrap()),
+            Arc::new(InMemoryTensorStore::new()),
+            Arc::new(InMemorySemanticStore::new()),
+            Arc::new(InMemoryVersionStore::new()),
+            Arc::new(InMemoryProvenanceStore::new()),
+            Arc::new(InMemorySpatialStore::new()),
+        ))
+    }
+
+    struct FailingStrategy;
+
+    #[async_trait]
+    impl NormalizationStrategy for FailingStrategy {
+        fn name(&self) -> &str {
+            "always-fails"
+        }
+
+        fn applies_to(&self, _drift_type: DriftType) -> bool {
+            true
+        }
+
+        async fn normalize(&self, hexad: &Hexad, _event: &DriftEvent) -> Result<NormalizationResult, NormalizerError> {
+            Err(NormalizerError::NormalizationFailed {
+                entity_id: hexad.id.to_string(),
+                message: "broken".into(),
+            })
+        }
+    }
+
+    fn event(id: &HexadId) -> DriftEvent {
+        DriftEvent::new(DriftType::TemporalConsistencyDrift, 0.8, "drift")
+            .with_entities(vec![id.to_string()])
+            .with_actions(vec![DriftAction::Normalize])
+    }
+
+    async fn wait_until(normalizer: &Normalizer, done: impl Fn(&crate::NormalizerStatus) -> bool) {
+        for _ in 0..200 {
+            if done(&normalizer.status().await) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("timed out: {:?}", normalizer.status().await);
+    }
+
+    #[tokio::test]
+    async fn test_workers_drain_queue() {
+        let store = store();
+        let hexad = store.create(HexadBuilder::new().with_document("A", "a").build()).await.unwrap();
+        let normalizer = Arc::new(Normalizer::with_defaults(Arc::new(DriftDetector::with_defaults())).with_store(store));
+        normalizer.register_strategy(Arc::new(TemporalRepairStrategy)).await;
+
+        assert!(normalizer.enqueue(NormalizationJob::new(hexad.id.clone(), event(&hexad.id))).await.is_err());
+        normalizer.clone().spawn_workers().unwrap();
+        assert!(normalizer.clone().spawn_workers().is_err());
+
+        for _ in 0..5 {
+            normalizer
+                .enqueue(NormalizationJob::new(hexad.id.clone(), event(&hexad.id)))
+                .await
+                .unwrap();
+        }
+        wait_until(&normalizer, |s| s.completed_count == 5).await;
+        let status = normalizer.status().await;
+        assert!(status.running);
+        assert_eq!((status.pending_count, status.active_count, status.failure_count), (0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_failed_jobs_are_retried_with_backoff() {
+        let store = store();
+        let hexad = store.create(HexadBuilder::new().with_document("A", "a").build()).await.unwrap();
+        let config = NormalizerConfig {
+            failure_backoff_secs: 0,
+            max_retries: 2,
+            ..Default::default()
+        };
+        let normalizer = Arc::new(
+            Normalizer::new(config, Arc::new(DriftDetector::with_defaults())).with_store(store),
+        );
+        normalizer.register_strategy(Arc::new(FailingStrategy)).await;
+        normalizer.clone().spawn_workers().unwrap();
+
+        normalizer
+            .enqueue(NormalizationJob::new(hexad.id.clone(), event(&hexad.id)))
+            .await
+            .unwrap();
+        wait_until(&normalizer, |s| s.failure_count == 3 && s.pending_count == 0).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let status = normalizer.status().await;
+        assert_eq!((status.failure_count, status.retry_count, status.completed_count), (3, 2, 0));
+    }
+}
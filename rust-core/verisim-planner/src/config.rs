@@ -57,6 +57,15 @@ pub struct PlannerConfig {
     pub enable_adaptive: bool,
     /// Minimum number of modality nodes to trigger parallel execution.
     pub parallel_threshold: usize,
+    /// Cardinality error (ratio of actual to estimated rows, either way)
+    /// above which the executor re-plans the steps still to run.
+    /// 0 disables mid-query re-optimization.
+    #[serde(default = "default_reoptimize_threshold")]
+    pub reoptimize_threshold: f64,
+}
+
+fn default_reoptimize_threshold() -> f64 {
+    10.0
 }
 
 impl PlannerConfig {
@@ -80,6 +89,7 @@ impl Default for PlannerConfig {
     /// - statistics_weight: 0.7
     /// - enable_adaptive: true
     /// - parallel_threshold: 2
+    /// - reoptimize_threshold: 10.0
     fn default() -> Self {
         let mut overrides = HashMap::new();
         overrides.insert(Modality::Vector, OptimizationMode::Aggressive);
@@ -92,6 +102,7 @@ fn default() -> Self {
             statistics_weight: 0.7,
             enable_adaptive: true,
             parallel_threshold: 2,
+            reoptimize_threshold: default_reoptimize_threshold(),
         }
     }
 }
@@ -7,6 +7,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::config::PlannerConfig;
+use crate::hints::AccessPath;
 use crate::plan::{ConditionKind, PlanNode};
 use crate::stats::StoreStatistics;
 use crate::Modality;
@@ -233,8 +234,9 @@ pub fn estimate(pp: &crate::plan::PostProcessing, row_count: u64) -> CostEstimat
                 let group_time = n * 0.002 * fields.len() as f64;
                 let agg_time = n * 0.001 * aggregates.len().max(1) as f64;
                 let total = group_time + agg_time;
-                // Grouping typically reduces rows significantly
-                let est_groups = (n / 10.0).max(1.0) as u64;
+                // Grouping typically reduces rows significantly; with no
+                // grouping fields every row folds into one
+                let est_groups = if fields.is_empty() { 1 } else { (n / 10.0).max(1.0) as u64 };
                 CostEstimate {
                     time_ms: total,
                     estimated_rows: est_groups,
@@ -302,6 +304,13 @@ pub fn estimate(condition: &ConditionKind, row_count: u64) -> CostEstimate {
     }
 }
 
+/// Highest measured equality selectivity still served by an index lookup;
+/// less selective predicates are cheaper to evaluate during a scan.
+const INDEX_LOOKUP_MAX_SELECTIVITY: f64 = 0.2;
+
+/// Bytes of analyzed index read per millisecond of scan time (~1 GB/s).
+const SCAN_BYTES_PER_MS: f64 = 1_000_000.0;
+
 /// Cost model that estimates execution cost for plan nodes.
 pub struct CostModel;
 
@@ -311,7 +320,9 @@ impl CostModel {
     /// Factors:
     /// 1. Base cost for the modality (from VQLExplain.res)
     /// 2. Optimization mode multiplier (from query_planner_config.ex)
-    /// 3. Store statistics (if available)
+    /// 3. Store statistics (if available); analyzed field distributions
+    ///    replace the static equality/range selectivities, and the analyzed
+    ///    index size prices the scan
     /// 4. Early limit reduction
     /// 5. Condition-specific adjustments (including proof obligations)
     /// 6. Cross-modal condition overhead
@@ -319,6 +330,19 @@ pub fn estimate(
         node: &PlanNode,
         config: &PlannerConfig,
         stats: Option<&StoreStatistics>,
+    ) -> CostEstimate {
+        Self::estimate_with_access(node, config, stats, None)
+    }
+
+    /// Estimate the cost of a node whose access path may be forced by a
+    /// hint.  `Index` prices equality predicates as index lookups however
+    /// unselective they look; `Scan` reads the whole store, so no index
+    /// discount applies.
+    pub fn estimate_with_access(
+        node: &PlanNode,
+        config: &PlannerConfig,
+        stats: Option<&StoreStatistics>,
+        access: Option<AccessPath>,
     ) -> CostEstimate {
         let base = BaseCost::for_modality(node.modality);
         let mode = config.mode_for(node.modality);
@@ -330,12 +354,30 @@ pub fn estimate(
         let mut time_ms = base.time_ms * cost_mult;
         let mut selectivity = (base.selectivity * sel_mult).min(1.0);
 
+        // Selectivities measured by ANALYZE for this node's predicates
+        let analyzed = stats.filter(|s| s.is_analyzed());
+        let measured: Vec<Option<f64>> = node
+            .conditions
+            .iter()
+            .map(|c| match (c, analyzed) {
+                (ConditionKind::Equality { field, .. }, Some(s)) => s.equality_selectivity(field),
+                (ConditionKind::Range { field, low, high }, Some(s)) => s.range_selectivity(field, low, high),
+                _ => None,
+            })
+            .collect();
+        let has_measured = measured.iter().any(Option::is_some);
+        if has_measured {
+            // Measured predicates narrow a full scan of the store instead
+            // of the modality's typical result fraction
+            selectivity = 1.0;
+        }
+
         // Adjust for store statistics if available (weighted by statistics_weight)
         if let Some(s) = stats {
             if s.query_count > 0 {
                 let w = config.statistics_weight;
                 time_ms = time_ms * (1.0 - w) + s.avg_latency_ms * w;
-                if s.total_rows > 0 {
+                if s.total_rows > 0 && !has_measured {
                     let empirical_sel = s.avg_rows_returned as f64 / s.total_rows as f64;
                     selectivity = selectivity * (1.0 - w) + empirical_sel * w;
                 }
@@ -351,26 +393,47 @@ pub fn estimate(
 
         // Track proof obligation costs separately for accurate modeling
         let mut proof_time_ms = 0.0;
+        // Whether an index narrows the read, or the whole store is scanned
+        let mut indexed = false;
 
         // Condition-specific adjustments
-        for condition in &node.conditions {
-            match condition {
-                ConditionKind::Equality { .. } => {
+        for (condition, measured) in node.conditions.iter().zip(&measured) {
+            match (condition, measured) {
+                (ConditionKind::Equality { .. }, Some(sel)) => {
+                    selectivity *= sel;
+                    if *sel <= INDEX_LOOKUP_MAX_SELECTIVITY || access == Some(AccessPath::Index) {
+                        indexed = true;
+                        time_ms *= 0.7;
+                    }
+                }
+                (ConditionKind::Range { .. }, Some(sel)) => {
+                    selectivity *= sel;
+                    time_ms *= 0.8;
+                }
+                (ConditionKind::Equality { .. }, None) => {
                     selectivity *= 0.1; // Highly selective
                     time_ms *= 0.7;
                 }
-                ConditionKind::Range { .. } => {
+                (ConditionKind::Range { .. }, None) => {
                     selectivity *= 0.3;
                     time_ms *= 0.8;
                 }
-                ConditionKind::Similarity { k } => {
-                    selectivity = (*k as f64 / 10000.0).min(1.0);
+                (ConditionKind::Similarity { k }, _) => {
+                    indexed = true;
+                    selectivity = match analyzed {
+                        Some(s) if s.total_rows > 0 => (*k as f64 / s.total_rows as f64).min(1.0),
+                        _ => (*k as f64 / 10000.0).min(1.0),
+                    };
                 }
-                ConditionKind::Fulltext { .. } => {
+                (ConditionKind::Fulltext { .. }, _) => {
                     // Tantivy inverted index is fast
+                    indexed = true;
                     time_ms *= 0.6;
                 }
-                ConditionKind::ProofVerification { contract } => {
+                (ConditionKind::Traversal { .. }, _) => {
+                    indexed = true;
+                }
+                (ConditionKind::ProofVerification { contract }, _) => {
                     // Detailed proof costing based on proof type
                     let proof_type = extract_proof_type(contract);
                     let pcost = ProofCost::for_type(&proof_type);
@@ -380,13 +443,28 @@ pub fn estimate(
             }
         }
 
+        // A forced scan gives up the index discounts
+        if access == Some(AccessPath::Scan) {
+            indexed = false;
+            time_ms = time_ms.max(base.time_ms * cost_mult);
+        }
+
+        // Reading the analyzed index: a scan touches all of it, an index
+        // lookup only the fraction it returns
+        if let Some(s) = analyzed {
+            let fraction = if indexed { selectivity } else { 1.0 };
+            time_ms += s.index_bytes as f64 / SCAN_BYTES_PER_MS * fraction;
+        }
+
         // Add proof overhead to total time
         time_ms += proof_time_ms;
 
-        let estimated_rows = if let Some(s) = stats {
-            (s.total_rows as f64 * selectivity).max(1.0) as u64
-        } else {
-            (1000.0 * selectivity).max(1.0) as u64
+        // Stores with no recorded cardinality assume 1000 rows
+        let estimated_rows = match stats {
+            Some(s) if s.total_rows > 0 || s.is_analyzed() => {
+                (s.total_rows as f64 * selectivity).max(1.0) as u64
+            }
+            _ => (1000.0 * selectivity).max(1.0) as u64,
         };
 
         // Split cost: proofs are CPU-bound, modality queries are I/O-heavy
@@ -422,9 +500,20 @@ pub fn estimate_with_post_processing(
     }
 
     /// Generate an optimization hint string for a plan node.
-    pub fn optimization_hint(node: &PlanNode) -> Option<String> {
+    ///
+    /// With analyzed statistics, equality predicates too unselective for an
+    /// index lookup are reported as a sequential scan.  A forced access
+    /// path overrides that choice.
+    pub fn optimization_hint(
+        node: &PlanNode,
+        stats: Option<&StoreStatistics>,
+        access: Option<AccessPath>,
+    ) -> Option<String> {
         let base = BaseCost::for_modality(node.modality);
         let mut hint = base.hint.to_string();
+        if access == Some(AccessPath::Scan) {
+            return Some(format!("Full scan of {} store (FULL_SCAN hint)", node.modality));
+        }
 
         for condition in &node.conditions {
             match condition {
@@ -450,7 +539,13 @@ pub fn optimization_hint(node: &PlanNode) -> Option<String> {
                     hint = format!("ZKP verify: {}", contract);
                 }
                 ConditionKind::Equality { field, .. } => {
-                    hint = format!("Index lookup on {}", field);
+                    hint = match stats.and_then(|s| s.equality_selectivity(field)) {
+                        _ if access == Some(AccessPath::Index) => format!("Index lookup on {} (USE_INDEX hint)", field),
+                        Some(sel) if sel > INDEX_LOOKUP_MAX_SELECTIVITY => {
+                            format!("Sequential scan filtering {} (~{:.0}% of rows)", field, sel * 100.0)
+                        }
+                        _ => format!("Index lookup on {}", field),
+                    };
                 }
                 ConditionKind::AtTime { timestamp } => {
                     hint = format!("Temporal snapshot at {}", timestamp);
@@ -530,6 +625,59 @@ fn test_mode_multipliers_aggressive() {
         assert!((est.time_ms - 50.0 * 0.8).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_analyzed_statistics_replace_static_selectivity() {
+        use crate::stats::{FieldStatistics, StatisticsCollector, StoreAnalysis};
+        use std::collections::HashMap;
+
+        let config = PlannerConfig::default();
+        let node = PlanNode {
+            modality: Modality::Document,
+            conditions: vec![ConditionKind::Equality {
+                field: "author".to_string(),
+                value: "noether".to_string(),
+            }],
+            projections: vec![],
+            early_limit: None,
+        };
+        let static_est = CostModel::estimate(&node, &config, None);
+
+        let mut collector = StatisticsCollector::new();
+        let mut fields = HashMap::new();
+        fields.insert(
+            "author".to_string(),
+            FieldStatistics { non_null: 2000, distinct_values: 400, min: None, max: None },
+        );
+        collector.record_analysis(
+            Modality::Document,
+            StoreAnalysis { total_rows: 2000, index_bytes: 50_000_000, fields },
+        );
+        let stats = collector.get(Modality::Document);
+        let est = CostModel::estimate(&node, &config, stats);
+
+        assert!((est.selectivity - 1.0 / 400.0).abs() < 1e-9);
+        assert_eq!(est.estimated_rows, 5);
+        // The index lookup reads only the matching fraction of the index
+        assert!(est.time_ms > static_est.time_ms && est.time_ms < static_est.time_ms + 1.0);
+        assert_eq!(
+            CostModel::optimization_hint(&node, stats, None).as_deref(),
+            Some("Index lookup on author")
+        );
+
+        // An unanalyzed field falls back to the static guess
+        let other = PlanNode {
+            conditions: vec![ConditionKind::Equality {
+                field: "venue".to_string(),
+                value: "x".to_string(),
+            }],
+            ..node
+        };
+        let est = CostModel::estimate(&other, &config, stats);
+        assert!((est.selectivity - static_est.selectivity).abs() < 1e-9);
+        // ... but a full scan pays for the whole 50 MB index
+        assert!(est.time_ms > static_est.time_ms + 40.0);
+    }
+
     #[test]
     fn test_early_limit_reduces_selectivity() {
         let config = PlannerConfig::default();
@@ -729,6 +877,19 @@ fn test_post_processing_group_by_reduces_rows() {
         assert!(cost.estimated_rows < 1000, "GROUP BY should reduce rows");
     }
 
+    #[test]
+    fn test_post_processing_global_aggregate_is_one_row() {
+        use crate::plan::PostProcessing;
+        let cost = PostProcessingCost::estimate(
+            &PostProcessing::GroupBy {
+                fields: vec![],
+                aggregates: vec!["COUNT(*)".into()],
+            },
+            1000,
+        );
+        assert_eq!(cost.estimated_rows, 1);
+    }
+
     #[test]
     fn test_estimate_with_post_processing() {
         use crate::plan::PostProcessing;
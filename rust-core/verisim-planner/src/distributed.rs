@@ -0,0 +1,481 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+
+//! Distributed (scatter-gather) query planning for federations.
+//!
+//! A federated query runs the same logical plan on every peer store that
+//! can answer it and merges what comes back.  [`Planner::distribute`]
+//! produces the [`DistributedPlan`]:
+//!
+//! - **Scatter**: one [`SubPlan`] per peer that supports every modality the
+//!   plan touches.  The final LIMIT is pushed down to each peer, since any
+//!   peer's top rows may be the global top rows.
+//! - **Network cost**: each sub-plan's cost is the local estimate plus the
+//!   peer's round trip and the transfer of its rows ([`NetworkCost`]).
+//!   Peers whose estimate exceeds the caller's budget are left out rather
+//!   than holding up the whole query.  Peers run in parallel, so the plan
+//!   costs as much as its slowest sub-plan plus the merge.
+//! - **Gather**: partial results are merged by a [`MergeStrategy`] chosen
+//!   from the plan — top-k by score for vector similarity (cosine scores
+//!   are comparable across peers), reciprocal rank fusion for full-text
+//!   (BM25 scores are not), and a de-duplicating union otherwise.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cost::CostEstimate;
+use crate::error::PlannerError;
+use crate::optimizer::Planner;
+use crate::plan::{ConditionKind, LogicalPlan, PostProcessing};
+use crate::Modality;
+
+/// Result limit of a distributed plan without a LIMIT.
+pub const DEFAULT_DISTRIBUTED_LIMIT: usize = 100;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What the planner knows about a federation peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerProfile {
+    /// Peer store identifier.
+    pub store_id: String,
+    /// Modalities the peer supports.
+    pub modalities: Vec<Modality>,
+    /// Measured round-trip time, if the peer has been queried before.
+    pub round_trip_ms: Option<f64>,
+}
+
+/// Cost of moving a sub-plan and its results over the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkCost {
+    /// Round trip assumed for a peer with no measured response time.
+    pub default_round_trip_ms: f64,
+    /// Transfer time per result row.
+    pub per_row_ms: f64,
+    /// Coordinator time to merge one row.
+    pub merge_per_row_ms: f64,
+}
+
+impl Default for NetworkCost {
+    fn default() -> Self {
+        Self {
+            default_round_trip_ms: 25.0,
+            per_row_ms: 0.05,
+            merge_per_row_ms: 0.002,
+        }
+    }
+}
+
+/// How partial results from the peers are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Keep the highest-scoring rows, each entity once at its best score.
+    TopK,
+    /// Reciprocal rank fusion: an entity scores `Σ 1 / (k + rank)` over the
+    /// peers that returned it, so only each peer's ranking matters.
+    RankFusion { k: f64 },
+    /// Every entity once, in peer order.
+    Union,
+}
+
+impl MergeStrategy {
+    /// Rank fusion constant from the original RRF paper.
+    pub const RRF_K: f64 = 60.0;
+
+    /// The strategy for `plan`: top-k for vector similarity, rank fusion
+    /// for full-text search, union otherwise.
+    pub fn for_plan(plan: &LogicalPlan) -> Self {
+        let has = |matches: fn(&ConditionKind) -> bool| {
+            plan.nodes.iter().flat_map(|n| &n.conditions).any(matches)
+        };
+        if has(|c| matches!(c, ConditionKind::Similarity { .. })) {
+            Self::TopK
+        } else if has(|c| matches!(c, ConditionKind::Fulltext { .. })) {
+            Self::RankFusion { k: Self::RRF_K }
+        } else {
+            Self::Union
+        }
+    }
+
+    /// Merge `partials` into at most `limit` rows, best first.
+    pub fn merge(&self, partials: Vec<PartialResult>, limit: usize) -> Vec<MergedRow> {
+        let mut merged: Vec<MergedRow> = Vec::new();
+        let mut by_id: HashMap<String, usize> = HashMap::new();
+        for partial in partials {
+            for (rank, row) in partial.rows.into_iter().enumerate() {
+                let score = match self {
+                    Self::RankFusion { k } => 1.0 / (k + rank as f64 + 1.0),
+                    Self::TopK | Self::Union => row.score,
+                };
+                match by_id.get(&row.id) {
+                    Some(&i) => {
+                        let existing = &mut merged[i];
+                        match self {
+                            Self::RankFusion { .. } => existing.score += score,
+                            Self::TopK if score > existing.score => {
+                                existing.score = score;
+                                existing.data = row.data;
+                            }
+                            Self::TopK | Self::Union => {}
+                        }
+                        existing.sources.push(partial.store_id.clone());
+                    }
+                    None => {
+                        by_id.insert(row.id.clone(), merged.len());
+                        merged.push(MergedRow {
+                            id: row.id,
+                            score,
+                            sources: vec![partial.store_id.clone()],
+                            data: row.data,
+                        });
+                    }
+                }
+            }
+        }
+        if *self != Self::Union {
+            merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        merged.truncate(limit);
+        merged
+    }
+}
+
+/// The plan one peer runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubPlan {
+    /// Peer the sub-plan is sent to.
+    pub store_id: String,
+    /// Logical plan the peer optimizes and runs itself.
+    pub plan: LogicalPlan,
+    /// Estimated cost of running the plan on the peer.
+    pub local_cost: CostEstimate,
+    /// Estimated round trip and row transfer.
+    pub network_ms: f64,
+    /// `local_cost` plus `network_ms`.
+    pub estimated_ms: f64,
+    /// Rows the peer is expected to return.
+    pub estimated_rows: u64,
+}
+
+/// A peer left out of a distributed plan, and why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedPeer {
+    pub store_id: String,
+    pub reason: String,
+}
+
+/// A scatter-gather plan over federation peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributedPlan {
+    /// Sub-plans, one per participating peer, cheapest first.
+    pub sub_plans: Vec<SubPlan>,
+    /// Peers that could not or should not take part.
+    pub excluded: Vec<ExcludedPeer>,
+    /// How the peers' results are combined.
+    pub merge: MergeStrategy,
+    /// Rows kept after merging.
+    pub limit: usize,
+    /// Estimated cost of the whole plan: the slowest sub-plan plus the merge.
+    pub total_cost: CostEstimate,
+    /// Planning notes.
+    pub notes: Vec<String>,
+}
+
+/// One row returned by a peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteRow {
+    /// Entity ID
+    pub id: String,
+    /// Peer-local relevance score, 0.0 if unranked
+    pub score: f64,
+    /// The row as the peer returned it
+    pub data: Value,
+}
+
+impl RemoteRow {
+    /// Read a row from its JSON object, using `id` and `score` if present.
+    /// Rows without an `id` are kept apart under a per-peer key.
+    pub fn from_json(store_id: &str, index: usize, data: Value) -> Self {
+        Self {
+            id: data["id"]
+                .as_str()
+                .map_or_else(|| format!("{}#{}", store_id, index), str::to_string),
+            score: data["score"].as_f64().unwrap_or(0.0),
+            data,
+        }
+    }
+}
+
+/// The rows one peer returned, in its own order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialResult {
+    pub store_id: String,
+    pub rows: Vec<RemoteRow>,
+}
+
+/// A merged result row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedRow {
+    /// Entity ID
+    pub id: String,
+    /// Merged score (see [`MergeStrategy`])
+    pub score: f64,
+    /// Peers that returned the entity, in merge order
+    pub sources: Vec<String>,
+    /// The row from the peer with the best score, or the first peer
+    pub data: Value,
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+impl Planner {
+    /// Plan `logical` across `peers`.
+    ///
+    /// Peers lacking a modality the plan uses, and peers whose estimated
+    /// cost (including the network) exceeds `budget_ms`, are excluded.
+    /// Plans that group or order by a field cannot be merged from partial
+    /// results and are rejected.
+    pub fn distribute(
+        &self,
+        logical: &LogicalPlan,
+        peers: &[PeerProfile],
+        network: &NetworkCost,
+        budget_ms: Option<f64>,
+    ) -> Result<DistributedPlan, PlannerError> {
+        for post in &logical.post_processing {
+            match post {
+                PostProcessing::GroupBy { .. } => {
+                    return Err(PlannerError::Distribution(
+                        "GROUP BY cannot be merged from partial results".to_string(),
+                    ))
+                }
+                PostProcessing::OrderBy { fields }
+                    if fields.iter().any(|(field, _)| field != "similarity" && field != "score") =>
+                {
+                    return Err(PlannerError::Distribution(
+                        "only ORDER BY similarity or score can be merged from partial results".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        let local = self.optimize(logical)?;
+        let limit = logical
+            .post_processing
+            .iter()
+            .find_map(|post| match post {
+                PostProcessing::Limit { count } => Some(*count),
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_DISTRIBUTED_LIMIT);
+        let mut plan = logical.clone();
+        if !plan.post_processing.iter().any(|post| matches!(post, PostProcessing::Limit { .. })) {
+            plan.post_processing.push(PostProcessing::Limit { count: limit });
+        }
+        let estimated_rows = local.total_cost.estimated_rows.min(limit as u64);
+
+        let mut sub_plans = Vec::new();
+        let mut excluded = Vec::new();
+        for peer in peers {
+            if let Some(missing) = logical.nodes.iter().map(|n| n.modality).find(|m| !peer.modalities.contains(m)) {
+                excluded.push(ExcludedPeer {
+                    store_id: peer.store_id.clone(),
+                    reason: format!("does not support the {} modality", missing),
+                });
+                continue;
+            }
+            let network_ms = peer.round_trip_ms.unwrap_or(network.default_round_trip_ms)
+                + estimated_rows as f64 * network.per_row_ms;
+            let estimated_ms = local.total_cost.time_ms + network_ms;
+            if let Some(budget) = budget_ms.filter(|budget| estimated_ms > *budget) {
+                excluded.push(ExcludedPeer {
+                    store_id: peer.store_id.clone(),
+                    reason: format!("estimated {:.1}ms exceeds the {:.1}ms budget", estimated_ms, budget),
+                });
+                continue;
+            }
+            sub_plans.push(SubPlan {
+                store_id: peer.store_id.clone(),
+                plan: plan.clone(),
+                local_cost: local.total_cost.clone(),
+                network_ms,
+                estimated_ms,
+                estimated_rows,
+            });
+        }
+        sub_plans.sort_by(|a, b| a.estimated_ms.total_cmp(&b.estimated_ms));
+
+        let merge = MergeStrategy::for_plan(logical);
+        let gathered = estimated_rows * sub_plans.len() as u64;
+        let merge_ms = gathered as f64 * network.merge_per_row_ms;
+        let slowest = sub_plans.iter().map(|s| s.estimated_ms).fold(0.0, f64::max);
+        let total_cost = CostEstimate {
+            time_ms: slowest + merge_ms,
+            estimated_rows: gathered.min(limit as u64),
+            selectivity: local.total_cost.selectivity,
+            io_cost: sub_plans.iter().map(|s| s.local_cost.io_cost).sum(),
+            cpu_cost: sub_plans.iter().map(|s| s.local_cost.cpu_cost).sum::<f64>() + merge_ms,
+        };
+
+        let mut notes = vec![format!(
+            "Scatter to {} of {} peers, merge by {}",
+            sub_plans.len(),
+            peers.len(),
+            match merge {
+                MergeStrategy::TopK => "top-k score",
+                MergeStrategy::RankFusion { .. } => "reciprocal rank fusion",
+                MergeStrategy::Union => "union",
+            }
+        )];
+        if sub_plans.is_empty() && !peers.is_empty() {
+            notes.push("No peer can run the plan".to_string());
+        }
+
+        Ok(DistributedPlan {
+            sub_plans,
+            excluded,
+            merge,
+            limit,
+            total_cost,
+            notes,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PlannerConfig;
+    use crate::plan::{PlanNode, QuerySource};
+    use crate::QueryHints;
+
+    fn plan(modality: Modality, condition: ConditionKind, limit: usize) -> LogicalPlan {
+        LogicalPlan {
+            source: QuerySource::Hexad,
+            nodes: vec![PlanNode {
+                modality,
+                conditions: vec![condition],
+                projections: vec![],
+                early_limit: None,
+            }],
+            post_processing: vec![PostProcessing::Limit { count: limit }],
+            joins: vec![],
+            hints: QueryHints::default(),
+        }
+    }
+
+    fn peer(id: &str, modalities: &[Modality], round_trip_ms: Option<f64>) -> PeerProfile {
+        PeerProfile {
+            store_id: id.to_string(),
+            modalities: modalities.to_vec(),
+            round_trip_ms,
+        }
+    }
+
+    fn partial(store: &str, rows: &[(&str, f64)]) -> PartialResult {
+        PartialResult {
+            store_id: store.to_string(),
+            rows: rows
+                .iter()
+                .map(|(id, score)| RemoteRow {
+                    id: id.to_string(),
+                    score: *score,
+                    data: serde_json::json!({"id": id, "store": store}),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_distribute_excludes_unsupported_and_slow_peers() {
+        let planner = Planner::new(PlannerConfig::default());
+        let logical = plan(Modality::Vector, ConditionKind::Similarity { k: 10 }, 10);
+        let peers = [
+            peer("slow", &[Modality::Vector], Some(5_000.0)),
+            peer("fast", &[Modality::Vector, Modality::Graph], Some(2.0)),
+            peer("graph-only", &[Modality::Graph], None),
+            peer("unmeasured", &[Modality::Vector], None),
+        ];
+        let distributed = planner
+            .distribute(&logical, &peers, &NetworkCost::default(), Some(1_000.0))
+            .unwrap();
+
+        let ids: Vec<&str> = distributed.sub_plans.iter().map(|s| s.store_id.as_str()).collect();
+        assert_eq!(ids, ["fast", "unmeasured"]);
+        assert_eq!(distributed.excluded.len(), 2);
+        assert!(distributed.excluded.iter().any(|e| e.store_id == "graph-only" && e.reason.contains("vector")));
+        assert!(distributed.excluded.iter().any(|e| e.store_id == "slow" && e.reason.contains("budget")));
+
+        let fast = &distributed.sub_plans[0];
+        assert!(fast.network_ms >= 2.0);
+        assert_eq!(fast.estimated_ms, fast.local_cost.time_ms + fast.network_ms);
+        assert!(distributed.total_cost.time_ms >= distributed.sub_plans[1].estimated_ms);
+        assert_eq!((distributed.merge, distributed.limit), (MergeStrategy::TopK, 10));
+    }
+
+    #[test]
+    fn test_distribute_picks_merge_and_rejects_unmergeable_plans() {
+        let planner = Planner::new(PlannerConfig::default());
+        let network = NetworkCost::default();
+        let text = plan(Modality::Document, ConditionKind::Fulltext { query: "rust".into() }, 5);
+        let graph = plan(Modality::Graph, ConditionKind::Traversal { predicate: "cites".into(), depth: None }, 5);
+        assert!(matches!(
+            planner.distribute(&text, &[], &network, None).unwrap().merge,
+            MergeStrategy::RankFusion { .. }
+        ));
+        assert_eq!(planner.distribute(&graph, &[], &network, None).unwrap().merge, MergeStrategy::Union);
+
+        let mut grouped = graph.clone();
+        grouped.post_processing.push(PostProcessing::GroupBy {
+            fields: vec!["type".into()],
+            aggregates: vec![],
+        });
+        assert!(matches!(
+            planner.distribute(&grouped, &[], &network, None),
+            Err(PlannerError::Distribution(_))
+        ));
+
+        let mut unlimited = graph;
+        unlimited.post_processing.clear();
+        let distributed = planner
+            .distribute(&unlimited, &[peer("p", &[Modality::Graph], None)], &network, None)
+            .unwrap();
+        assert_eq!(distributed.limit, DEFAULT_DISTRIBUTED_LIMIT);
+        assert!(matches!(
+            distributed.sub_plans[0].plan.post_processing[..],
+            [PostProcessing::Limit { count: DEFAULT_DISTRIBUTED_LIMIT }]
+        ));
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let partials = || vec![partial("a", &[("x", 0.9), ("y", 0.5)]), partial("b", &[("y", 0.95), ("z", 0.1)])];
+
+        let top = MergeStrategy::TopK.merge(partials(), 2);
+        assert_eq!(top.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["y", "x"]);
+        assert_eq!(top[0].score, 0.95);
+        assert_eq!(top[0].sources, ["a", "b"]);
+        assert_eq!(top[0].data["store"], "b");
+
+        // y is ranked by both peers, so it beats x despite x's higher score
+        let fused = MergeStrategy::RankFusion { k: MergeStrategy::RRF_K }.merge(partials(), 10);
+        assert_eq!(fused.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["y", "x", "z"]);
+        assert!((fused[0].score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-12);
+
+        let union = MergeStrategy::Union.merge(partials(), 10);
+        assert_eq!(union.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), ["x", "y", "z"]);
+        assert_eq!(union[1].data["store"], "a");
+    }
+}
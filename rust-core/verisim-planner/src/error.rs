@@ -15,9 +15,18 @@ pub enum PlannerError {
     #[error("invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("invalid join: {0}")]
+    InvalidJoin(String),
+
+    #[error("invalid hint: {0}")]
+    InvalidHint(String),
+
     #[error("cost estimation failed: {0}")]
     CostEstimation(String),
 
+    #[error("cannot distribute plan: {0}")]
+    Distribution(String),
+
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 }
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
-//! EXPLAIN output rendering (text and JSON).
+//! EXPLAIN output rendering (text and JSON), and plan visualization as a
+//! nested tree or Graphviz DOT.
 
 use std::collections::HashMap;
 use std::fmt;
@@ -7,7 +8,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::config::PlannerConfig;
-use crate::plan::PhysicalPlan;
+use crate::plan::{ExecutionStrategy, PhysicalPlan, PlanStep};
 use crate::Modality;
 
 /// Cost breakdown for a single modality.
@@ -221,17 +222,153 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Output format of an EXPLAIN request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplainFormat {
+    /// [`ExplainOutput`] as JSON.
+    #[default]
+    Json,
+    /// The human-readable text rendering.
+    Text,
+    /// A [`PlanTreeNode`] tree as JSON.
+    Tree,
+    /// A Graphviz DOT digraph.
+    Dot,
+}
+
+/// A node in the tree rendering of a physical plan.
+///
+/// The root is the plan result.  Under a sequential strategy each step's
+/// child is the step that feeds it, so the first step is the deepest leaf;
+/// under a parallel strategy every step is a direct child of the root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanTreeNode {
+    /// Node identifier, unique within the tree (`result`, `step1`, ...).
+    pub id: String,
+    /// Operation description.
+    pub operation: String,
+    /// Step number, or `None` for the root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+    /// Target modality, or `None` for the root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modality: Option<Modality>,
+    pub estimated_cost_ms: f64,
+    pub estimated_selectivity: f64,
+    pub estimated_rows: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimization_hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pushed_predicates: Vec<String>,
+    /// Nodes whose output this node consumes.
+    #[serde(default)]
+    pub children: Vec<PlanTreeNode>,
+}
+
+impl PlanTreeNode {
+    /// Build the tree rendering of a physical plan.
+    pub fn from_physical_plan(plan: &PhysicalPlan) -> Self {
+        let children = match plan.strategy {
+            ExecutionStrategy::Parallel => plan.steps.iter().map(|step| Self::from_step(step, Vec::new())).collect(),
+            ExecutionStrategy::Sequential => plan
+                .steps
+                .iter()
+                .fold(None, |input: Option<PlanTreeNode>, step| {
+                    Some(Self::from_step(step, input.into_iter().collect()))
+                })
+                .into_iter()
+                .collect(),
+        };
+        let strategy = match plan.strategy {
+            ExecutionStrategy::Sequential => "sequential",
+            ExecutionStrategy::Parallel => "parallel",
+        };
+        Self {
+            id: "result".to_string(),
+            operation: format!("Result ({})", strategy),
+            step: None,
+            modality: None,
+            estimated_cost_ms: plan.total_cost.time_ms,
+            estimated_selectivity: plan.total_cost.selectivity,
+            estimated_rows: plan.total_cost.estimated_rows,
+            optimization_hint: None,
+            pushed_predicates: Vec::new(),
+            children,
+        }
+    }
+
+    fn from_step(step: &PlanStep, children: Vec<PlanTreeNode>) -> Self {
+        Self {
+            id: format!("step{}", step.step),
+            operation: step.operation.clone(),
+            step: Some(step.step),
+            modality: Some(step.modality),
+            estimated_cost_ms: step.cost.time_ms,
+            estimated_selectivity: step.cost.selectivity,
+            estimated_rows: step.cost.estimated_rows,
+            optimization_hint: step.optimization_hint.clone(),
+            pushed_predicates: step.pushed_predicates.clone(),
+            children,
+        }
+    }
+
+    /// Render the tree as a Graphviz DOT digraph.  Edges point from each
+    /// node to the node that consumes its output and are labelled with the
+    /// estimated row count.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph plan {\n  rankdir=BT;\n  node [shape=box, fontname=\"monospace\"];\n");
+        self.write_dot(&mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    fn write_dot(&self, out: &mut String) {
+        let mut label = match (self.step, self.modality) {
+            (Some(step), Some(modality)) => format!("Step {}: {} [{}]", step, self.operation, modality),
+            _ => self.operation.clone(),
+        };
+        label.push_str(&format!(
+            "\n{:.1}ms | sel {:.2}% | ~{} rows",
+            self.estimated_cost_ms,
+            self.estimated_selectivity * 100.0,
+            self.estimated_rows
+        ));
+        if let Some(hint) = &self.optimization_hint {
+            label.push_str(&format!("\n{}", hint));
+        }
+        let shape = if self.step.is_none() { ", shape=ellipse" } else { "" };
+        out.push_str(&format!("  {} [label=\"{}\"{}];\n", self.id, dot_escape(&label), shape));
+        for child in &self.children {
+            child.write_dot(out);
+            out.push_str(&format!(
+                "  {} -> {} [label=\"~{} rows\"];\n",
+                child.id, self.id, child.estimated_rows
+            ));
+        }
+    }
+}
+
+/// Escape a label for a double-quoted DOT string, keeping `\n` line breaks.
+fn dot_escape(label: &str) -> String {
+    label
+        .split('\n')
+        .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cost::CostEstimate;
-    use crate::plan::{ExecutionStrategy, PhysicalPlan, PlanStep};
 
     fn sample_physical_plan() -> PhysicalPlan {
         PhysicalPlan {
             steps: vec![
                 PlanStep {
                     step: 1,
+                    node: 0,
                     operation: "Vector similarity search (1 conditions)".to_string(),
                     modality: Modality::Vector,
                     cost: CostEstimate {
@@ -246,6 +383,7 @@ fn sample_physical_plan() -> PhysicalPlan {
                 },
                 PlanStep {
                     step: 2,
+                    node: 1,
                     operation: "Graph traversal (1 conditions)".to_string(),
                     modality: Modality::Graph,
                     cost: CostEstimate {
@@ -268,6 +406,7 @@ fn sample_physical_plan() -> PhysicalPlan {
                 cpu_cost: 106.0,
             },
             notes: vec!["Parallel execution across 2 modalities".to_string()],
+            join_order: vec![],
         }
     }
 
@@ -312,6 +451,7 @@ fn test_performance_hints_for_expensive() {
         let plan = PhysicalPlan {
             steps: vec![PlanStep {
                 step: 1,
+                node: 0,
                 operation: "Semantic verification".to_string(),
                 modality: Modality::Semantic,
                 cost: CostEstimate {
@@ -333,6 +473,7 @@ fn test_performance_hints_for_expensive() {
                 cpu_cost: 270.0,
             },
             notes: vec![],
+            join_order: vec![],
         };
 
         let explain = ExplainOutput::from_physical_plan(&plan, &PlannerConfig::default());
@@ -365,4 +506,38 @@ fn test_display_trait() {
         assert!(!display.is_empty());
         assert!(display.contains("VeriSimDB"));
     }
+
+    #[test]
+    fn test_plan_tree_nests_sequential_steps() {
+        let mut plan = sample_physical_plan();
+        let parallel = PlanTreeNode::from_physical_plan(&plan);
+        assert_eq!(parallel.operation, "Result (parallel)");
+        assert_eq!(parallel.children.len(), 2);
+        assert!(parallel.children.iter().all(|c| c.children.is_empty()));
+
+        plan.strategy = ExecutionStrategy::Sequential;
+        let tree = PlanTreeNode::from_physical_plan(&plan);
+        assert_eq!(tree.children.len(), 1);
+        let last = &tree.children[0];
+        assert_eq!((last.step, last.modality), (Some(2), Some(Modality::Graph)));
+        assert_eq!(last.children[0].id, "step1");
+        assert_eq!(last.children[0].estimated_cost_ms, 40.0);
+        assert!(last.children[0].children.is_empty());
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["children"][0]["children"][0]["modality"], "vector");
+    }
+
+    #[test]
+    fn test_plan_tree_dot() {
+        let mut plan = sample_physical_plan();
+        plan.strategy = ExecutionStrategy::Sequential;
+        plan.steps[0].operation = "Filter \"title\"".to_string();
+        let dot = PlanTreeNode::from_physical_plan(&plan).to_dot();
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("step1 -> step2 [label=\"~10 rows\"];"));
+        assert!(dot.contains("step2 -> result [label=\"~200 rows\"];"));
+        assert!(dot.contains("Step 1: Filter \\\"title\\\" [vector]\\n40.0ms"));
+    }
 }
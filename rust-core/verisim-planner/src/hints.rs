@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//!
+//! Optimizer hints.
+//!
+//! Hints override the cost model where it picks a bad plan.  In VQL they
+//! are written in a comment starting with `+`, anywhere in the query:
+//!
+//! ```text
+//! MATCH /*+ USE_INDEX(vector) NO_REORDER */ (h) WHERE ...
+//! ```
+//!
+//! - `USE_INDEX(m, ...)` — nodes of modality `m` use an index lookup even
+//!   when their predicates look too unselective for one
+//! - `FULL_SCAN(m, ...)` — nodes of modality `m` scan the whole store
+//! - `NO_REORDER` — nodes run in the order the query lists them, and the
+//!   executor does not re-plan mid-query
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PlannerError;
+use crate::Modality;
+
+/// Hints attached to a logical plan.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryHints {
+    /// Modalities forced to an index lookup.
+    #[serde(default)]
+    pub use_index: Vec<Modality>,
+    /// Modalities forced to a full scan.
+    #[serde(default)]
+    pub full_scan: Vec<Modality>,
+    /// Keep the query's node order.
+    #[serde(default)]
+    pub no_reorder: bool,
+}
+
+/// How a node reads its store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessPath {
+    /// Look matching entities up in the modality's index.
+    Index,
+    /// Read every entity in the store.
+    Scan,
+}
+
+impl QueryHints {
+    /// Whether no hint is set.
+    pub fn is_empty(&self) -> bool {
+        self.use_index.is_empty() && self.full_scan.is_empty() && !self.no_reorder
+    }
+
+    /// The access path forced on a modality, if any.
+    pub fn access_for(&self, modality: Modality) -> Option<AccessPath> {
+        if self.use_index.contains(&modality) {
+            Some(AccessPath::Index)
+        } else if self.full_scan.contains(&modality) {
+            Some(AccessPath::Scan)
+        } else {
+            None
+        }
+    }
+
+    /// Parse the body of a hint comment, e.g. `USE_INDEX(vector) NO_REORDER`.
+    pub fn parse(body: &str) -> Result<Self, PlannerError> {
+        let mut hints = Self::default();
+        let mut rest = body.trim();
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| c == '(' || c.is_whitespace()).unwrap_or(rest.len());
+            let name = rest[..end].to_uppercase();
+            rest = rest[end..].trim_start();
+
+            let mut args = Vec::new();
+            if let Some(inner) = rest.strip_prefix('(') {
+                let close = inner
+                    .find(')')
+                    .ok_or_else(|| PlannerError::InvalidHint(format!("unclosed argument list of {}", name)))?;
+                args = inner[..close].split(',').map(str::trim).filter(|a| !a.is_empty()).collect();
+                rest = inner[close + 1..].trim_start();
+            }
+
+            match name.as_str() {
+                "USE_INDEX" | "FULL_SCAN" => {
+                    if args.is_empty() {
+                        return Err(PlannerError::InvalidHint(format!("{} needs at least one modality", name)));
+                    }
+                    let modalities = args
+                        .iter()
+                        .map(|arg| {
+                            arg.parse::<Modality>()
+                                .map_err(|_| PlannerError::InvalidHint(format!("unknown modality '{}' in {}", arg, name)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    hints.merge(if name == "USE_INDEX" {
+                        Self { use_index: modalities, ..Self::default() }
+                    } else {
+                        Self { full_scan: modalities, ..Self::default() }
+                    })?;
+                }
+                "NO_REORDER" if args.is_empty() => hints.no_reorder = true,
+                "NO_REORDER" => {
+                    return Err(PlannerError::InvalidHint("NO_REORDER takes no arguments".to_string()));
+                }
+                other => return Err(PlannerError::InvalidHint(format!("unknown hint '{}'", other))),
+            }
+        }
+        Ok(hints)
+    }
+
+    /// Remove every `/*+ ... */` hint comment from `query`, returning the
+    /// combined hints and the query without them.
+    pub fn extract(query: &str) -> Result<(Self, String), PlannerError> {
+        let mut hints = Self::default();
+        let mut stripped = String::with_capacity(query.len());
+        let mut rest = query;
+        while let Some(start) = rest.find("/*+") {
+            let body_start = start + 3;
+            let len = rest[body_start..]
+                .find("*/")
+                .ok_or_else(|| PlannerError::InvalidHint("unterminated hint comment".to_string()))?;
+            let parsed = Self::parse(&rest[body_start..body_start + len])?;
+            hints.merge(parsed)?;
+            stripped.push_str(&rest[..start]);
+            stripped.push(' ');
+            rest = &rest[body_start + len + 2..];
+        }
+        stripped.push_str(rest);
+        Ok((hints, stripped))
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), PlannerError> {
+        for m in other.use_index {
+            if self.full_scan.contains(&m) {
+                return Err(PlannerError::InvalidHint(format!("{} is given both USE_INDEX and FULL_SCAN", m)));
+            }
+            if !self.use_index.contains(&m) {
+                self.use_index.push(m);
+            }
+        }
+        for m in other.full_scan {
+            if self.use_index.contains(&m) {
+                return Err(PlannerError::InvalidHint(format!("{} is given both USE_INDEX and FULL_SCAN", m)));
+            }
+            if !self.full_scan.contains(&m) {
+                self.full_scan.push(m);
+            }
+        }
+        self.no_reorder |= other.no_reorder;
+        Ok(())
+    }
+}
+
+impl fmt::Display for QueryHints {
+    /// Hint comment body, e.g. `USE_INDEX(vector) NO_REORDER`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |ms: &[Modality]| ms.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(", ");
+        let mut parts = Vec::new();
+        if !self.use_index.is_empty() {
+            parts.push(format!("USE_INDEX({})", list(&self.use_index)));
+        }
+        if !self.full_scan.is_empty() {
+            parts.push(format!("FULL_SCAN({})", list(&self.full_scan)));
+        }
+        if self.no_reorder {
+            parts.push("NO_REORDER".to_string());
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hints() {
+        let hints = QueryHints::parse("use_index(vector, document) NO_REORDER").unwrap();
+        assert_eq!(hints.use_index, vec![Modality::Vector, Modality::Document]);
+        assert!(hints.no_reorder);
+        assert_eq!(hints.access_for(Modality::Vector), Some(AccessPath::Index));
+        assert_eq!(hints.access_for(Modality::Graph), None);
+
+        assert_eq!(hints.to_string(), "USE_INDEX(vector, document) NO_REORDER");
+
+        let hints = QueryHints::parse("FULL_SCAN( graph )").unwrap();
+        assert_eq!(hints.access_for(Modality::Graph), Some(AccessPath::Scan));
+        assert!(QueryHints::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_hints() {
+        for bad in ["USE_INDEX", "USE_INDEX(vectr)", "FAST", "NO_REORDER(1)", "USE_INDEX(graph", "USE_INDEX(graph) FULL_SCAN(graph)"] {
+            assert!(matches!(QueryHints::parse(bad), Err(PlannerError::InvalidHint(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_extract_strips_hint_comments() {
+        let (hints, query) =
+            QueryHints::extract("MATCH /*+ USE_INDEX(vector) */ (h) /*+NO_REORDER*/ LIMIT 5").unwrap();
+        assert_eq!(hints.use_index, vec![Modality::Vector]);
+        assert!(hints.no_reorder);
+        assert_eq!(query.split_whitespace().collect::<Vec<_>>(), vec!["MATCH", "(h)", "LIMIT", "5"]);
+
+        let (hints, query) = QueryHints::extract("SELECT * FROM hexads").unwrap();
+        assert!(hints.is_empty());
+        assert_eq!(query, "SELECT * FROM hexads");
+        assert!(QueryHints::extract("MATCH /*+ NO_REORDER").is_err());
+    }
+}
@@ -7,12 +7,15 @@
 
 pub mod config;
 pub mod cost;
+pub mod distributed;
 pub mod error;
 pub mod explain;
+pub mod hints;
 pub mod optimizer;
 pub mod plan;
 pub mod prepared;
 pub mod profiler;
+pub mod result_cache;
 pub mod slow_query;
 pub mod stats;
 pub mod vql_bridge;
@@ -23,14 +26,23 @@
 
 pub use config::{OptimizationMode, PlannerConfig};
 pub use cost::{CostEstimate, CostModel, CrossModalCost, PostProcessingCost, ProofCost};
+pub use distributed::{
+    DistributedPlan, ExcludedPeer, MergeStrategy, MergedRow, NetworkCost, PartialResult, PeerProfile, RemoteRow, SubPlan,
+};
 pub use error::PlannerError;
-pub use explain::ExplainOutput;
+pub use explain::{ExplainFormat, ExplainOutput, PlanTreeNode};
+pub use hints::{AccessPath, QueryHints};
 pub use optimizer::Planner;
-pub use plan::{LogicalPlan, PhysicalPlan};
+pub use plan::{Join, LogicalPlan, PhysicalPlan};
 pub use profiler::{ExplainAnalyzeOutput, Profiler, ProfileStep, QueryProfile};
 pub use prepared::{CacheConfig, CacheError, CacheStats, ParamValue, PlanCache, PreparedId, PreparedStatement};
-pub use slow_query::{SlowQueryConfig, SlowQueryEntry, SlowQueryLog, SlowQuerySummary};
-pub use stats::{AdaptiveTuner, StatisticsCollector, StoreStatistics};
+pub use result_cache::{ResultCache, ResultCacheStats, ResultKey};
+pub use slow_query::{
+    QueryRun, ReplanInfo, SlowQueryConfig, SlowQueryEntry, SlowQueryFilter, SlowQueryLog, SlowQuerySummary, StepTiming,
+};
+pub use stats::{
+    AdaptiveTuner, FieldProfile, FieldStatistics, StatisticsCollector, StoreAnalysis, StoreStatistics,
+};
 
 /// The six modalities of VeriSimDB.
 ///
@@ -14,7 +14,8 @@
 ///
 /// Transforms a `LogicalPlan` into an optimized `PhysicalPlan` by:
 /// 1. Estimating cost per modality node
-/// 2. Reordering by execution priority + cost
+/// 2. Reordering by execution priority + cost, or for joined nodes by
+///    estimated rows, so the most selective join input runs first
 /// 3. Selecting sequential vs parallel strategy
 /// 4. Generating optimization hints
 pub struct Planner {
@@ -57,8 +58,21 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
             return Err(PlannerError::EmptyPlan);
         }
 
+        for join in &logical.joins {
+            if join.left == join.right || join.left.max(join.right) >= logical.nodes.len() {
+                return Err(PlannerError::InvalidJoin(format!(
+                    "nodes {} and {} in a plan of {} nodes",
+                    join.left,
+                    join.right,
+                    logical.nodes.len()
+                )));
+            }
+        }
+        let joined = logical.joined_nodes();
+
         debug!(
             node_count = logical.nodes.len(),
+            join_count = logical.joins.len(),
             "Optimizing logical plan"
         );
 
@@ -69,23 +83,48 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
             .enumerate()
             .map(|(i, node)| {
                 let stats = self.stats.get(node.modality);
-                let cost = CostModel::estimate(node, &self.config, stats);
-                let hint = CostModel::optimization_hint(node);
+                let access = logical.hints.access_for(node.modality);
+                let cost = CostModel::estimate_with_access(node, &self.config, stats, access);
+                let hint = CostModel::optimization_hint(node, stats, access);
                 (i, cost, hint)
             })
             .collect();
 
-        // 2. Sort by execution priority first, then by total cost within same priority
+        // 2. Joined nodes first, fewest estimated rows (then most selective)
+        //    first, so each later input only probes the candidates found so
+        //    far; then the rest by execution priority.  Ties go to the
+        //    cheaper node.  NO_REORDER keeps the query's order.
         node_costs.sort_by(|a, b| {
-            let pri_a = logical.nodes[a.0].modality.execution_priority();
-            let pri_b = logical.nodes[b.0].modality.execution_priority();
-            pri_a
-                .cmp(&pri_b)
+            if logical.hints.no_reorder {
+                return a.0.cmp(&b.0);
+            }
+            let joined_a = joined.contains(&a.0);
+            let joined_b = joined.contains(&b.0);
+            let order = if joined_a && joined_b {
+                a.1.estimated_rows.cmp(&b.1.estimated_rows).then_with(|| {
+                    a.1.selectivity
+                        .partial_cmp(&b.1.selectivity)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+            } else {
+                let pri_a = logical.nodes[a.0].modality.execution_priority();
+                let pri_b = logical.nodes[b.0].modality.execution_priority();
+                pri_a.cmp(&pri_b)
+            };
+            joined_b
+                .cmp(&joined_a)
+                .then(order)
                 .then_with(|| a.1.time_ms.partial_cmp(&b.1.time_ms).unwrap_or(std::cmp::Ordering::Equal))
         });
+        let join_order: Vec<usize> = node_costs
+            .iter()
+            .map(|(i, _, _)| *i)
+            .filter(|i| joined.contains(i))
+            .collect();
 
-        // 3. Select execution strategy
-        let strategy = if logical.nodes.len() >= self.config.parallel_threshold {
+        // 3. Select execution strategy.  Join inputs probe one another's
+        //    results, so a plan with joins runs sequentially.
+        let strategy = if logical.joins.is_empty() && logical.nodes.len() >= self.config.parallel_threshold {
             ExecutionStrategy::Parallel
         } else {
             ExecutionStrategy::Sequential
@@ -124,6 +163,7 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
 
             steps.push(PlanStep {
                 step: step_num + 1,
+                node: node_idx,
                 operation,
                 modality: node.modality,
                 cost: cost.clone(),
@@ -143,7 +183,16 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
         );
 
         // 6. Generate optimization notes
-        if is_parallel {
+        if !join_order.is_empty() {
+            let inputs: Vec<String> = join_order
+                .iter()
+                .map(|i| {
+                    let selectivity = node_costs.iter().find(|(n, _, _)| n == i).map_or(1.0, |(_, c, _)| c.selectivity);
+                    format!("{} ({:.1}%)", logical.nodes[*i].modality, selectivity * 100.0)
+                })
+                .collect();
+            notes.push(format!("Join order: {}", inputs.join(" ⋈ ")));
+        } else if is_parallel {
             notes.push(format!(
                 "Parallel execution across {} modalities",
                 steps.len()
@@ -151,6 +200,9 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
         } else {
             notes.push("Sequential execution — single modality".to_string());
         }
+        if !logical.hints.is_empty() {
+            notes.push(format!("Hints applied: {}", logical.hints));
+        }
 
         if total_cost.time_ms > 500.0 {
             notes.push("High estimated cost — consider adding LIMIT or more selective predicates".to_string());
@@ -173,6 +225,76 @@ pub fn optimize(&self, logical: &LogicalPlan) -> Result<PhysicalPlan, PlannerErr
             strategy,
             total_cost,
             notes,
+            join_order,
+        })
+    }
+
+    /// Re-plan the steps of `physical` that have not run yet.
+    ///
+    /// Called by the executor when the first `completed` steps left
+    /// `observed_rows` candidates and that count is far from the
+    /// estimate.  Every remaining step now probes those candidates, so
+    /// each is re-costed as keeping its selectivity's share of them and
+    /// they are reordered to keep the fewest rows first (ties go to the
+    /// cheaper step) unless the plan is hinted `NO_REORDER`.  The
+    /// completed steps are returned as they ran.
+    pub fn reoptimize(
+        &self,
+        logical: &LogicalPlan,
+        physical: &PhysicalPlan,
+        completed: usize,
+        observed_rows: u64,
+    ) -> Result<PhysicalPlan, PlannerError> {
+        let completed = completed.min(physical.steps.len());
+        let mut remaining = Vec::with_capacity(physical.steps.len() - completed);
+        for step in &physical.steps[completed..] {
+            let node = logical.nodes.get(step.node).ok_or_else(|| {
+                PlannerError::CostEstimation(format!("plan step {} runs unknown node {}", step.step, step.node))
+            })?;
+            let stats = self.stats.get(node.modality);
+            let access = logical.hints.access_for(node.modality);
+            let mut cost = CostModel::estimate_with_access(node, &self.config, stats, access);
+            cost.estimated_rows = (observed_rows as f64 * cost.selectivity).ceil().max(1.0) as u64;
+            let mut step = step.clone();
+            step.cost = cost;
+            step.optimization_hint = CostModel::optimization_hint(node, stats, access);
+            remaining.push(step);
+        }
+        remaining.sort_by(|a, b| {
+            if logical.hints.no_reorder {
+                return std::cmp::Ordering::Equal;
+            }
+            a.cost
+                .estimated_rows
+                .cmp(&b.cost.estimated_rows)
+                .then_with(|| a.cost.time_ms.partial_cmp(&b.cost.time_ms).unwrap_or(std::cmp::Ordering::Equal))
+        });
+
+        let mut steps = physical.steps[..completed].to_vec();
+        steps.extend(remaining);
+        for (i, step) in steps.iter_mut().enumerate() {
+            step.step = i + 1;
+        }
+
+        let costs: Vec<CostEstimate> = steps.iter().map(|s| s.cost.clone()).collect();
+        let total_cost = CostModel::estimate_with_post_processing(
+            &CostEstimate::combine(&costs, false),
+            &logical.post_processing,
+        );
+        let joined = logical.joined_nodes();
+        let join_order = steps.iter().map(|s| s.node).filter(|n| joined.contains(n)).collect();
+        let mut notes = physical.notes.clone();
+        notes.push(format!(
+            "Re-optimized after step {}: {} candidate rows",
+            completed, observed_rows
+        ));
+
+        Ok(PhysicalPlan {
+            steps,
+            strategy: ExecutionStrategy::Sequential,
+            total_cost,
+            notes,
+            join_order,
         })
     }
 
@@ -186,8 +308,11 @@ pub fn explain(&self, logical: &LogicalPlan) -> Result<ExplainOutput, PlannerErr
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::plan::{ConditionKind, LogicalPlan, PlanNode, QuerySource};
+    use crate::plan::{ConditionKind, Join, LogicalPlan, PlanNode, QuerySource};
     use crate::Modality;
+    use crate::hints::QueryHints;
+    use crate::stats::{FieldStatistics, StoreAnalysis};
+    use std::collections::HashMap;
 
     fn graph_vector_plan() -> LogicalPlan {
         LogicalPlan {
@@ -210,6 +335,8 @@ fn graph_vector_plan() -> LogicalPlan {
                 },
             ],
             post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
         }
     }
 
@@ -227,6 +354,8 @@ fn test_single_modality_sequential() {
                 early_limit: None,
             }],
             post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
         };
 
         let physical = planner.optimize(&plan).unwrap();
@@ -280,6 +409,8 @@ fn test_semantic_always_last() {
                 },
             ],
             post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
         };
 
         let physical = planner.optimize(&plan).unwrap();
@@ -309,6 +440,8 @@ fn test_temporal_always_first() {
                 },
             ],
             post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
         };
 
         let physical = planner.optimize(&plan).unwrap();
@@ -322,6 +455,8 @@ fn test_empty_plan_error() {
             source: QuerySource::Hexad,
             nodes: vec![],
             post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
         };
 
         let result = planner.optimize(&plan);
@@ -337,6 +472,159 @@ fn test_explain_generates_output() {
         assert!(!explain.text_output.is_empty());
     }
 
+    fn join_plan(anchored: bool) -> LogicalPlan {
+        let mut graph_conditions = vec![ConditionKind::Traversal {
+            predicate: "provedBy".to_string(),
+            depth: Some(1),
+        }];
+        if anchored {
+            graph_conditions.push(ConditionKind::Equality {
+                field: "source".to_string(),
+                value: "thm-1".to_string(),
+            });
+        }
+        LogicalPlan {
+            source: QuerySource::Hexad,
+            nodes: vec![
+                PlanNode {
+                    modality: Modality::Graph,
+                    conditions: graph_conditions,
+                    projections: vec![],
+                    early_limit: None,
+                },
+                PlanNode {
+                    modality: Modality::Document,
+                    conditions: vec![ConditionKind::Fulltext {
+                        query: "induction".to_string(),
+                    }],
+                    projections: vec![],
+                    early_limit: None,
+                },
+                PlanNode {
+                    modality: Modality::Vector,
+                    conditions: vec![ConditionKind::Similarity { k: 10 }],
+                    projections: vec![],
+                    early_limit: None,
+                },
+            ],
+            post_processing: vec![],
+            joins: vec![Join { left: 0, right: 1 }],
+            hints: QueryHints::default(),
+        }
+    }
+
+    #[test]
+    fn test_join_order_by_estimated_rows() {
+        let planner = Planner::new(PlannerConfig::default());
+
+        // An unanchored traversal matches more rows than a text search
+        let physical = planner.optimize(&join_plan(false)).unwrap();
+        assert_eq!(physical.join_order, vec![1, 0]);
+        assert_eq!(physical.strategy, ExecutionStrategy::Sequential);
+        assert_eq!(physical.steps[0].modality, Modality::Document);
+        assert_eq!(physical.steps[1].modality, Modality::Graph);
+        assert_eq!(physical.steps[2].modality, Modality::Vector);
+        assert!(physical.notes.iter().any(|n| n.starts_with("Join order: document")));
+
+        // Anchoring it at one source makes the traversal drive the join
+        let physical = planner.optimize(&join_plan(true)).unwrap();
+        assert_eq!(physical.join_order, vec![0, 1]);
+        assert_eq!(physical.steps[0].modality, Modality::Graph);
+    }
+
+    #[test]
+    fn test_join_order_uses_analyzed_statistics() {
+        let mut planner = Planner::new(PlannerConfig::default());
+        let mut fields = HashMap::new();
+        fields.insert(
+            "source".to_string(),
+            FieldStatistics { non_null: 10_000, distinct_values: 2, min: None, max: None },
+        );
+        planner.stats_mut().record_analysis(
+            Modality::Graph,
+            StoreAnalysis { total_rows: 10_000, index_bytes: 0, fields },
+        );
+        planner.stats_mut().record_analysis(
+            Modality::Document,
+            StoreAnalysis { total_rows: 100, index_bytes: 0, fields: HashMap::new() },
+        );
+
+        // The anchor matches half the graph, so the small document store
+        // drives the join and the anchor is evaluated during a scan
+        let physical = planner.optimize(&join_plan(true)).unwrap();
+        assert_eq!(physical.join_order, vec![1, 0]);
+        assert_eq!(physical.steps[1].cost.estimated_rows, 5000);
+        assert!(physical.steps[1]
+            .optimization_hint
+            .as_deref()
+            .unwrap()
+            .starts_with("Sequential scan filtering source"));
+    }
+
+    #[test]
+    fn test_reoptimize_reorders_remaining_steps() {
+        let mut planner = Planner::new(PlannerConfig::default());
+        planner.stats_mut().record_analysis(
+            Modality::Graph,
+            StoreAnalysis { total_rows: 10, index_bytes: 0, fields: HashMap::new() },
+        );
+        let mut plan = join_plan(false);
+        plan.nodes[2].conditions = vec![ConditionKind::Equality {
+            field: "model".to_string(),
+            value: "m".to_string(),
+        }];
+        plan.joins.push(Join { left: 1, right: 2 });
+
+        // The small graph store looks cheaper than the text search
+        let physical = planner.optimize(&plan).unwrap();
+        assert_eq!(physical.join_order, vec![2, 0, 1]);
+
+        // ... but once the first step leaves 5000 candidates, the traversal
+        // keeps more of them than the text search, so the search runs next
+        let replanned = planner.reoptimize(&plan, &physical, 1, 5000).unwrap();
+        assert_eq!(replanned.join_order, vec![2, 1, 0]);
+        assert_eq!(replanned.steps.iter().map(|s| s.step).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(replanned.steps[0].cost.estimated_rows, physical.steps[0].cost.estimated_rows);
+        assert_eq!(replanned.steps[1].cost.estimated_rows, 250);
+        assert_eq!(replanned.strategy, ExecutionStrategy::Sequential);
+        assert!(replanned.notes.last().unwrap().starts_with("Re-optimized after step 1"));
+    }
+
+    #[test]
+    fn test_hints_override_order_and_access() {
+        let planner = Planner::new(PlannerConfig::default());
+        let mut plan = join_plan(false);
+        let physical = planner.optimize(&plan).unwrap();
+        assert_eq!(physical.join_order, vec![1, 0]);
+
+        plan.hints = QueryHints::parse("NO_REORDER FULL_SCAN(document)").unwrap();
+        let hinted = planner.optimize(&plan).unwrap();
+        assert_eq!(hinted.join_order, vec![0, 1]);
+        assert_eq!(hinted.steps.iter().map(|s| s.node).collect::<Vec<_>>(), vec![0, 1, 2]);
+        let document = &hinted.steps[1];
+        assert!(document.cost.time_ms > physical.steps[0].cost.time_ms, "the scan loses the index discount");
+        assert_eq!(
+            document.optimization_hint.as_deref(),
+            Some("Full scan of document store (FULL_SCAN hint)")
+        );
+        assert!(hinted.notes.iter().any(|n| n == "Hints applied: FULL_SCAN(document) NO_REORDER"));
+
+        // Re-planning keeps the hinted order too
+        let replanned = planner.reoptimize(&plan, &hinted, 1, 100_000).unwrap();
+        assert_eq!(replanned.join_order, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_invalid_join_error() {
+        let planner = Planner::new(PlannerConfig::default());
+        let mut plan = join_plan(false);
+        plan.joins = vec![Join { left: 0, right: 3 }];
+        assert!(matches!(planner.optimize(&plan), Err(PlannerError::InvalidJoin(_))));
+
+        plan.joins = vec![Join { left: 1, right: 1 }];
+        assert!(matches!(planner.optimize(&plan), Err(PlannerError::InvalidJoin(_))));
+    }
+
     #[test]
     fn test_integration_graph_vector() {
         let planner = Planner::new(PlannerConfig::default());
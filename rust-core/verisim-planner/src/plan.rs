@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::cost::CostEstimate;
+use crate::hints::QueryHints;
 use crate::Modality;
 
 /// Source of data for the query.
@@ -69,6 +70,16 @@ pub enum PostProcessing {
     Project { columns: Vec<String> },
 }
 
+/// Inner join of two plan nodes on entity identity: an entity is kept
+/// only when both nodes produce it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Join {
+    /// Index of the left node in [`LogicalPlan::nodes`].
+    pub left: usize,
+    /// Index of the right node in [`LogicalPlan::nodes`].
+    pub right: usize,
+}
+
 /// A logical plan — the unoptimized query representation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogicalPlan {
@@ -78,6 +89,24 @@ pub struct LogicalPlan {
     pub nodes: Vec<PlanNode>,
     /// Post-processing steps.
     pub post_processing: Vec<PostProcessing>,
+    /// Joins between nodes.  Without joins, node results are combined
+    /// per modality; with them, joined nodes filter one another
+    /// and the remaining nodes apply to the joined rows.
+    #[serde(default)]
+    pub joins: Vec<Join>,
+    /// Optimizer hints overriding the cost model.
+    #[serde(default, skip_serializing_if = "QueryHints::is_empty")]
+    pub hints: QueryHints,
+}
+
+impl LogicalPlan {
+    /// Indices of the nodes taking part in a join, in node order.
+    pub fn joined_nodes(&self) -> Vec<usize> {
+        let mut joined: Vec<usize> = self.joins.iter().flat_map(|j| [j.left, j.right]).collect();
+        joined.sort_unstable();
+        joined.dedup();
+        joined
+    }
 }
 
 /// Execution strategy for the physical plan.
@@ -93,6 +122,9 @@ pub enum ExecutionStrategy {
 pub struct PlanStep {
     /// Step number (1-indexed).
     pub step: usize,
+    /// Index of the logical plan node this step runs.
+    #[serde(default)]
+    pub node: usize,
     /// Operation description.
     pub operation: String,
     /// Target modality.
@@ -116,6 +148,11 @@ pub struct PhysicalPlan {
     pub total_cost: CostEstimate,
     /// Optimization notes.
     pub notes: Vec<String>,
+    /// Logical node indices of the joined nodes, in the order they run:
+    /// the first produces candidate entities and each later one filters
+    /// them.  Empty when the plan has no joins.
+    #[serde(default)]
+    pub join_order: Vec<usize>,
 }
 
 #[cfg(test)]
@@ -143,6 +180,8 @@ fn sample_logical_plan() -> LogicalPlan {
                 },
             ],
             post_processing: vec![PostProcessing::Limit { count: 10 }],
+            joins: vec![],
+            hints: QueryHints::default(),
         }
     }
 
@@ -161,6 +200,7 @@ fn test_physical_plan_json_roundtrip() {
         let plan = PhysicalPlan {
             steps: vec![PlanStep {
                 step: 1,
+                node: 0,
                 operation: "Vector similarity search".to_string(),
                 modality: Modality::Vector,
                 cost: CostEstimate {
@@ -182,6 +222,7 @@ fn test_physical_plan_json_roundtrip() {
                 cpu_cost: 20.0,
             },
             notes: vec!["Single modality — sequential execution".to_string()],
+            join_order: vec![],
         };
         let json = serde_json::to_string(&plan).unwrap();
         let parsed: PhysicalPlan = serde_json::from_str(&json).unwrap();
@@ -127,9 +127,9 @@ pub struct CacheConfig {
     pub ttl_seconds: u64,
     /// Whether to cache optimized physical plans alongside logical plans.
     pub enable_plan_cache: bool,
-    /// Whether to cache query result sets (reserved for future use).
+    /// Whether to cache query result sets (see [`ResultCache`](crate::ResultCache)).
     pub enable_result_cache: bool,
-    /// Maximum bytes allowed for the result cache (reserved for future use).
+    /// Maximum bytes allowed for the result cache.
     pub max_result_cache_bytes: usize,
 }
 
@@ -139,7 +139,7 @@ fn default() -> Self {
             max_entries: 1024,
             ttl_seconds: 3600,
             enable_plan_cache: true,
-            enable_result_cache: false,
+            enable_result_cache: true,
             max_result_cache_bytes: 64 * 1024 * 1024, // 64 MiB
         }
     }
@@ -213,6 +213,7 @@ impl std::error::Error for CacheError {}
 /// ```rust,no_run
 /// use verisim_planner::prepared::{PlanCache, CacheConfig};
 /// use verisim_planner::plan::{LogicalPlan, QuerySource};
+/// use verisim_planner::QueryHints;
 ///
 /// # async fn example() {
 /// let cache = PlanCache::new(CacheConfig::default());
@@ -221,6 +222,8 @@ impl std::error::Error for CacheError {}
 ///     source: QuerySource::Hexad,
 ///     nodes: vec![],
 ///     post_processing: vec![],
+///     joins: vec![],
+///     hints: QueryHints::default(),
 /// };
 ///
 /// let id = cache.prepare("SEARCH graph WHERE type = $t", plan).await;
@@ -673,6 +676,7 @@ fn extract_parameters(query: &str) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hints::QueryHints;
     use crate::plan::{ConditionKind, LogicalPlan, PlanNode, PostProcessing, QuerySource};
     use crate::Modality;
 
@@ -690,6 +694,8 @@ fn sample_logical_plan() -> LogicalPlan {
                 early_limit: None,
             }],
             post_processing: vec![PostProcessing::Limit { count: 10 }],
+            joins: vec![],
+            hints: QueryHints::default(),
         }
     }
 
@@ -701,6 +707,7 @@ fn sample_physical_plan() -> PhysicalPlan {
         PhysicalPlan {
             steps: vec![PlanStep {
                 step: 1,
+                node: 0,
                 operation: "Graph traversal (1 conditions)".to_string(),
                 modality: Modality::Graph,
                 cost: CostEstimate {
@@ -722,6 +729,7 @@ fn sample_physical_plan() -> PhysicalPlan {
                 cpu_cost: 10.0,
             },
             notes: vec!["Sequential execution — single modality".to_string()],
+            join_order: vec![],
         }
     }
 
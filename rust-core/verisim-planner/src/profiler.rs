@@ -446,6 +446,7 @@ fn two_step_plan() -> PhysicalPlan {
             steps: vec![
                 PlanStep {
                     step: 1,
+                    node: 0,
                     operation: "Vector similarity search (1 conditions)".to_string(),
                     modality: Modality::Vector,
                     cost: CostEstimate {
@@ -460,6 +461,7 @@ fn two_step_plan() -> PhysicalPlan {
                 },
                 PlanStep {
                     step: 2,
+                    node: 1,
                     operation: "Graph traversal (1 conditions)".to_string(),
                     modality: Modality::Graph,
                     cost: CostEstimate {
@@ -482,6 +484,7 @@ fn two_step_plan() -> PhysicalPlan {
                 cpu_cost: 106.0,
             },
             notes: vec!["Parallel execution across 2 modalities".to_string()],
+            join_order: vec![],
         }
     }
 
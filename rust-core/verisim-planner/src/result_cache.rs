@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+
+//! Query result caching.
+//!
+//! Identical read queries are common (dashboards, polling clients, repeated
+//! prepared statements).  A [`ResultCache`] keeps the results of recent plan
+//! executions so that running the same plan with the same parameters again
+//! returns the stored result instead of touching the stores.
+//!
+//! - **Keys**: a [`ResultKey`] is the SHA-256 of the normalized logical plan
+//!   and parameter bindings (both serialized with sorted keys), together with
+//!   the store version epoch the result was read at.
+//! - **Invalidation**: the store's change hooks call
+//!   [`ResultCache::invalidate`] on every write, which bumps the epoch and
+//!   drops every entry.  A result computed before the write but stored after
+//!   it carries the old epoch and is discarded, so a stale result is never
+//!   returned.
+//! - **Budget**: entries are bounded by `max_result_cache_bytes` of
+//!   [`CacheConfig`]; the least-recently-used entries are evicted to make
+//!   room.
+//! - **Statistics**: hits, misses, evictions and invalidations are counted
+//!   and reported by [`ResultCache::stats`].
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::plan::LogicalPlan;
+use crate::prepared::CacheConfig;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Identifies one cached result: a plan, its bindings and the store epoch.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct ResultKey {
+    fingerprint: String,
+    epoch: u64,
+}
+
+impl ResultKey {
+    /// Key for running `plan` with `bindings` at store version `epoch`.
+    ///
+    /// Plans and bindings are normalized by serializing them to JSON with
+    /// sorted object keys, so map ordering does not change the key.
+    pub fn new<B: Serialize>(plan: &LogicalPlan, bindings: &B, epoch: u64) -> Self {
+        let normalize = |value: serde_json::Result<serde_json::Value>| value.map(|v| v.to_string()).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(normalize(serde_json::to_value(plan)).as_bytes());
+        hasher.update([0]);
+        hasher.update(normalize(serde_json::to_value(bindings)).as_bytes());
+        let fingerprint = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+        Self { fingerprint, epoch }
+    }
+
+    /// The store version epoch the key was taken at.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+/// Aggregate statistics about result cache performance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCacheStats {
+    /// Whether results are being cached.
+    pub enabled: bool,
+    /// Number of results currently cached.
+    pub entries: usize,
+    /// Approximate size of the cached results, in bytes.
+    pub bytes: usize,
+    /// Size budget, in bytes.
+    pub max_bytes: usize,
+    /// Lookups that returned a cached result.
+    pub hit_count: u64,
+    /// Lookups that found no current result.
+    pub miss_count: u64,
+    /// Hit ratio: `hit_count / (hit_count + miss_count)`, or 0.0 if no lookups.
+    pub hit_ratio: f64,
+    /// Entries evicted to stay within the size budget.
+    pub eviction_count: u64,
+    /// Store writes that invalidated the cache.
+    pub invalidation_count: u64,
+    /// Current store version epoch.
+    pub epoch: u64,
+}
+
+/// A cached result and its bookkeeping.
+struct CachedResult<V> {
+    value: V,
+    bytes: usize,
+    /// Lookup tick of the most recent use, for LRU eviction.
+    last_used: u64,
+}
+
+/// Cached results and their total size, guarded together.
+struct Entries<V> {
+    results: HashMap<ResultKey, CachedResult<V>>,
+    bytes: usize,
+    tick: u64,
+}
+
+// ---------------------------------------------------------------------------
+// ResultCache
+// ---------------------------------------------------------------------------
+
+/// Cache of query results, invalidated by store writes.
+///
+/// Thread-safe via a `std::sync::Mutex`; no lock is held across an await.
+pub struct ResultCache<V> {
+    enabled: bool,
+    max_bytes: usize,
+    entries: Mutex<Entries<V>>,
+    /// Store version epoch; bumped by every invalidation.
+    epoch: AtomicU64,
+    hit_count: AtomicU64,
+    miss_count: AtomicU64,
+    eviction_count: AtomicU64,
+    invalidation_count: AtomicU64,
+}
+
+impl<V: Clone> ResultCache<V> {
+    /// Create a result cache from the `enable_result_cache` and
+    /// `max_result_cache_bytes` settings of `config`.
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            enabled: config.enable_result_cache && config.max_result_cache_bytes > 0,
+            max_bytes: config.max_result_cache_bytes,
+            entries: Mutex::new(Entries {
+                results: HashMap::new(),
+                bytes: 0,
+                tick: 0,
+            }),
+            epoch: AtomicU64::new(0),
+            hit_count: AtomicU64::new(0),
+            miss_count: AtomicU64::new(0),
+            eviction_count: AtomicU64::new(0),
+            invalidation_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether results are being cached.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Key for running `plan` with `bindings` at the current epoch.
+    pub fn key<B: Serialize>(&self, plan: &LogicalPlan, bindings: &B) -> ResultKey {
+        ResultKey::new(plan, bindings, self.epoch.load(Ordering::Acquire))
+    }
+
+    /// The cached result for `key`, if it is still current.
+    pub fn get(&self, key: &ResultKey) -> Option<V> {
+        if !self.enabled {
+            return None;
+        }
+        let mut entries = self.lock();
+        entries.tick += 1;
+        let tick = entries.tick;
+        match entries.results.get_mut(key) {
+            Some(cached) => {
+                cached.last_used = tick;
+                self.hit_count.fetch_add(1, Ordering::Relaxed);
+                Some(cached.value.clone())
+            }
+            None => {
+                self.miss_count.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Store the result for `key`, approximately `bytes` large.
+    ///
+    /// Results read before the latest invalidation, and results larger than
+    /// the whole budget, are not stored.  Returns whether it was stored.
+    pub fn insert(&self, key: ResultKey, value: V, bytes: usize) -> bool {
+        if !self.enabled || bytes > self.max_bytes {
+            return false;
+        }
+        let mut entries = self.lock();
+        // Checked under the lock: invalidate() bumps the epoch before
+        // clearing, so a stale key is either rejected here or cleared there.
+        if key.epoch != self.epoch.load(Ordering::Acquire) {
+            return false;
+        }
+        if let Some(previous) = entries.results.remove(&key) {
+            entries.bytes -= previous.bytes;
+        }
+        while entries.bytes + bytes > self.max_bytes {
+            let Some(oldest) = entries
+                .results
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = entries.results.remove(&oldest) {
+                entries.bytes -= evicted.bytes;
+                self.eviction_count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        entries.tick += 1;
+        let last_used = entries.tick;
+        entries.bytes += bytes;
+        entries.results.insert(key, CachedResult { value, bytes, last_used });
+        true
+    }
+
+    /// Invalidate every cached result: the stores have changed.
+    pub fn invalidate(&self) {
+        let mut entries = self.lock();
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+        self.invalidation_count.fetch_add(1, Ordering::Relaxed);
+        entries.results.clear();
+        entries.bytes = 0;
+    }
+
+    /// Return aggregate cache statistics.
+    pub fn stats(&self) -> ResultCacheStats {
+        let (entries, bytes) = {
+            let entries = self.lock();
+            (entries.results.len(), entries.bytes)
+        };
+        let hits = self.hit_count.load(Ordering::Relaxed);
+        let misses = self.miss_count.load(Ordering::Relaxed);
+        let total_lookups = hits + misses;
+        ResultCacheStats {
+            enabled: self.enabled,
+            entries,
+            bytes,
+            max_bytes: self.max_bytes,
+            hit_count: hits,
+            miss_count: misses,
+            hit_ratio: if total_lookups > 0 { hits as f64 / total_lookups as f64 } else { 0.0 },
+            eviction_count: self.eviction_count.load(Ordering::Relaxed),
+            invalidation_count: self.invalidation_count.load(Ordering::Relaxed),
+            epoch: self.epoch.load(Ordering::Acquire),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries<V>> {
+        self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::QuerySource;
+    use crate::QueryHints;
+
+    fn plan() -> LogicalPlan {
+        LogicalPlan {
+            source: QuerySource::Hexad,
+            nodes: vec![],
+            post_processing: vec![],
+            joins: vec![],
+            hints: QueryHints::default(),
+        }
+    }
+
+    fn cache(max_bytes: usize) -> ResultCache<String> {
+        ResultCache::new(&CacheConfig {
+            enable_result_cache: true,
+            max_result_cache_bytes: max_bytes,
+            ..CacheConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_keys_normalize_bindings() {
+        let a: HashMap<&str, i64> = [("x", 1), ("y", 2)].into_iter().collect();
+        let b: HashMap<&str, i64> = [("y", 2), ("x", 1)].into_iter().collect();
+        assert_eq!(ResultKey::new(&plan(), &a, 0), ResultKey::new(&plan(), &b, 0));
+        assert_ne!(ResultKey::new(&plan(), &a, 0), ResultKey::new(&plan(), &a, 1));
+        assert_ne!(ResultKey::new(&plan(), &a, 0), ResultKey::new(&plan(), &[("x", 2)], 0));
+    }
+
+    #[test]
+    fn test_hit_miss_and_invalidation() {
+        let cache = cache(1024);
+        let key = cache.key(&plan(), &());
+        assert_eq!(cache.get(&key), None);
+        assert!(cache.insert(key.clone(), "rows".to_string(), 4));
+        assert_eq!(cache.get(&key).as_deref(), Some("rows"));
+
+        cache.invalidate();
+        assert_eq!(cache.get(&cache.key(&plan(), &())), None);
+        // A result read before the write is not stored after it
+        assert!(!cache.insert(key, "stale".to_string(), 5));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hit_count, stats.miss_count), (1, 2));
+        assert_eq!((stats.entries, stats.invalidation_count, stats.epoch), (0, 1, 1));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = cache(10);
+        let keys: Vec<ResultKey> = (0..3).map(|i| cache.key(&plan(), &i)).collect();
+        assert!(cache.insert(keys[0].clone(), "a".to_string(), 4));
+        assert!(cache.insert(keys[1].clone(), "b".to_string(), 4));
+        cache.get(&keys[0]);
+        assert!(cache.insert(keys[2].clone(), "c".to_string(), 4));
+        assert!(cache.get(&keys[0]).is_some());
+        assert!(cache.get(&keys[1]).is_none());
+        assert!(!cache.insert(keys[1].clone(), "too big".to_string(), 11));
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.bytes, stats.eviction_count), (2, 8, 1));
+    }
+
+    #[test]
+    fn test_disabled_cache_stores_nothing() {
+        let cache: ResultCache<String> = ResultCache::new(&CacheConfig {
+            enable_result_cache: false,
+            ..CacheConfig::default()
+        });
+        let key = cache.key(&plan(), &());
+        assert!(!cache.insert(key.clone(), "rows".to_string(), 4));
+        assert_eq!(cache.get(&key), None);
+        assert!(!cache.stats().enabled);
+    }
+}
@@ -6,14 +6,21 @@
 //! Records queries that exceed a configurable duration threshold.
 //! Integrates with the `tracing` framework to emit structured log events
 //! and maintains an in-memory ring buffer for recent slow queries.
+//!
+//! Each entry keeps the query text, the plan that ran, the actual time and
+//! rows of every step and the parameter bindings, and optionally an
+//! EXPLAIN ANALYZE built from those actuals.  Entries are returned by
+//! [`SlowQueryLog::record_run`] so the caller can persist them beyond the
+//! ring buffer; [`SlowQueryFilter`] selects entries from either.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::RwLock;
 use tracing::warn;
 
 use crate::plan::{ExecutionStrategy, PhysicalPlan};
+use crate::profiler::ExplainAnalyzeOutput;
 use crate::Modality;
 
 /// Configuration for the slow query log.
@@ -35,6 +42,21 @@ pub struct SlowQueryConfig {
     /// Set to 0 to disable this check.
     /// Default: 0 (disabled).
     pub multi_modality_threshold: usize,
+
+    /// Persist entries beyond the ring buffer (the caller stores them).
+    /// Default: true.
+    #[serde(default = "default_persist")]
+    pub persist: bool,
+
+    /// Attach an EXPLAIN ANALYZE of each logged query, built from its
+    /// actual step timings.
+    /// Default: false.
+    #[serde(default)]
+    pub capture_explain: bool,
+}
+
+fn default_persist() -> bool {
+    true
 }
 
 impl Default for SlowQueryConfig {
@@ -44,6 +66,8 @@ fn default() -> Self {
             max_entries: 1000,
             enabled: true,
             multi_modality_threshold: 0,
+            persist: default_persist(),
+            capture_explain: false,
         }
     }
 }
@@ -77,6 +101,99 @@ pub struct SlowQueryEntry {
 
     /// Which step was the bottleneck.
     pub bottleneck: Option<BottleneckInfo>,
+
+    /// Mid-query re-plans, in the order they happened.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replans: Vec<ReplanInfo>,
+
+    /// The physical plan that ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<PhysicalPlan>,
+
+    /// Actual time and rows of each step, in run order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepTiming>,
+
+    /// Parameter bindings the query ran with.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, serde_json::Value>,
+
+    /// EXPLAIN ANALYZE of the run, when capture is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ExplainAnalyzeOutput>,
+}
+
+/// Actual cost of one step of a logged query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepTiming {
+    /// Modality of the step.
+    pub modality: Modality,
+
+    /// Time spent on the step (ms).
+    pub time_ms: f64,
+
+    /// Rows the step produced.
+    pub rows: usize,
+}
+
+/// A finished query, offered to [`SlowQueryLog::record_run`].
+#[derive(Debug, Clone)]
+pub struct QueryRun<'a> {
+    /// The VQL query text (if available).
+    pub query_text: Option<&'a str>,
+
+    /// Actual execution time in milliseconds.
+    pub actual_ms: f64,
+
+    /// The physical plan that ran.
+    pub plan: &'a PhysicalPlan,
+
+    /// `(modality, time_ms, rows)` of each step, in run order.
+    pub step_times: &'a [(Modality, f64, usize)],
+
+    /// Mid-query re-plans.
+    pub replans: &'a [ReplanInfo],
+
+    /// Parameter bindings.
+    pub parameters: BTreeMap<String, serde_json::Value>,
+
+    /// EXPLAIN ANALYZE of the run, attached when capture is enabled.
+    pub explain: Option<ExplainAnalyzeOutput>,
+}
+
+/// Criteria for selecting slow-query entries.  Unset criteria match all.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlowQueryFilter {
+    /// Only entries at least this slow (ms).
+    pub min_ms: Option<f64>,
+
+    /// Only entries with a step on this modality.
+    pub modality: Option<Modality>,
+
+    /// Only entries recorded at or after this time.
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only entries recorded before this time.
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only entries whose query text contains this (case-insensitive).
+    pub query: Option<String>,
+}
+
+impl SlowQueryFilter {
+    /// Whether `entry` meets every set criterion.
+    pub fn matches(&self, entry: &SlowQueryEntry) -> bool {
+        self.min_ms.is_none_or(|min| entry.actual_ms >= min)
+            && self.modality.is_none_or(|m| entry.modalities.contains(&m))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+            && self.query.as_deref().is_none_or(|needle| {
+                entry
+                    .query_text
+                    .as_deref()
+                    .is_some_and(|text| text.to_lowercase().contains(&needle.to_lowercase()))
+            })
+    }
 }
 
 /// Information about the slowest step in a query.
@@ -95,6 +212,28 @@ pub struct BottleneckInfo {
     pub percentage: f64,
 }
 
+/// A mid-query re-plan triggered by a cardinality misestimate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplanInfo {
+    /// Step after which the plan was re-optimized.
+    pub after_step: usize,
+
+    /// Rows the planner expected that step to produce.
+    pub estimated_rows: u64,
+
+    /// Rows it actually produced.
+    pub actual_rows: u64,
+
+    /// Cardinality error (larger of actual/estimated and estimated/actual).
+    pub error_ratio: f64,
+
+    /// Logical nodes still to run, in the old order.
+    pub previous_order: Vec<usize>,
+
+    /// The same nodes in the re-optimized order.
+    pub new_order: Vec<usize>,
+}
+
 /// Slow query log — ring buffer with tracing integration.
 pub struct SlowQueryLog {
     config: RwLock<SlowQueryConfig>,
@@ -125,18 +264,58 @@ pub fn record(
         plan: &PhysicalPlan,
         step_times: &[(Modality, f64, usize)], // (modality, time_ms, rows)
     ) -> bool {
-        let config = self.config.read().unwrap();
-        if !config.enabled {
-            return false;
-        }
+        self.record_with_replans(query_text, actual_ms, plan, step_times, &[])
+    }
+
+    /// Record a query execution that may have been re-planned mid-query.
+    ///
+    /// A re-planned query is always logged (when the log is enabled), as
+    /// its estimates were far enough off to be worth investigating.
+    pub fn record_with_replans(
+        &self,
+        query_text: Option<&str>,
+        actual_ms: f64,
+        plan: &PhysicalPlan,
+        step_times: &[(Modality, f64, usize)], // (modality, time_ms, rows)
+        replans: &[ReplanInfo],
+    ) -> bool {
+        self.record_run(QueryRun {
+            query_text,
+            actual_ms,
+            plan,
+            step_times,
+            replans,
+            parameters: BTreeMap::new(),
+            explain: None,
+        })
+        .is_some()
+    }
 
+    /// Whether a query would be logged: it is slow, spans enough
+    /// modalities, or was re-planned.
+    pub fn is_offender(&self, actual_ms: f64, plan: &PhysicalPlan, replans: &[ReplanInfo]) -> bool {
+        let config = self.config.read().unwrap();
         let is_slow = actual_ms >= config.threshold_ms;
         let is_multi = config.multi_modality_threshold > 0
             && plan.steps.len() >= config.multi_modality_threshold;
+        config.enabled && (is_slow || is_multi || !replans.is_empty())
+    }
 
-        if !is_slow && !is_multi {
-            return false;
+    /// Record a finished query, returning its entry if it was logged.
+    pub fn record_run(&self, run: QueryRun<'_>) -> Option<SlowQueryEntry> {
+        let QueryRun {
+            query_text,
+            actual_ms,
+            plan,
+            step_times,
+            replans,
+            parameters,
+            explain,
+        } = run;
+        if !self.is_offender(actual_ms, plan, replans) {
+            return None;
         }
+        let config = self.config.read().unwrap();
 
         let estimated_ms = plan.total_cost.time_ms;
         let slowdown_ratio = if estimated_ms > 0.0 {
@@ -181,6 +360,14 @@ pub fn record(
             modalities: modalities.clone(),
             rows_returned: total_rows,
             bottleneck: bottleneck.clone(),
+            replans: replans.to_vec(),
+            plan: Some(plan.clone()),
+            steps: step_times
+                .iter()
+                .map(|&(modality, time_ms, rows)| StepTiming { modality, time_ms, rows })
+                .collect(),
+            parameters,
+            explain: explain.filter(|_| config.capture_explain),
         };
 
         // Emit tracing warning
@@ -200,18 +387,38 @@ pub fn record(
             query = query_text.unwrap_or("<unknown>"),
             "Slow query detected"
         );
+        for replan in replans {
+            warn!(
+                after_step = replan.after_step,
+                estimated_rows = replan.estimated_rows,
+                actual_rows = replan.actual_rows,
+                error_ratio = replan.error_ratio,
+                previous_order = ?replan.previous_order,
+                new_order = ?replan.new_order,
+                "Query re-optimized mid-execution"
+            );
+        }
 
         // Insert into ring buffer
         let max_entries = config.max_entries;
         drop(config);
 
         let mut entries = self.entries.write().unwrap();
-        entries.push_back(entry);
+        entries.push_back(entry.clone());
         while entries.len() > max_entries {
             entries.pop_front();
         }
 
-        true
+        Some(entry)
+    }
+
+    /// Entries matching `filter`, newest first, skipping `offset` and
+    /// returning at most `limit`, with the total number matching.
+    pub fn query(&self, filter: &SlowQueryFilter, offset: usize, limit: usize) -> (usize, Vec<SlowQueryEntry>) {
+        let entries = self.entries.read().unwrap();
+        let matching: Vec<&SlowQueryEntry> = entries.iter().rev().filter(|e| filter.matches(e)).collect();
+        let page = matching.iter().skip(offset).take(limit).map(|e| (*e).clone()).collect();
+        (matching.len(), page)
     }
 
     /// Get recent slow queries.
@@ -313,6 +520,7 @@ fn make_plan(steps: Vec<(Modality, f64)>) -> PhysicalPlan {
                 .enumerate()
                 .map(|(i, (m, ms))| PlanStep {
                     step: i + 1,
+                    node: i,
                     operation: format!("{} query", m),
                     modality: *m,
                     cost: CostEstimate {
@@ -339,6 +547,7 @@ fn make_plan(steps: Vec<(Modality, f64)>) -> PhysicalPlan {
                 cpu_cost: total_ms * 0.4,
             },
             notes: vec![],
+            join_order: vec![],
         }
     }
 
@@ -370,6 +579,27 @@ fn test_slow_query_logged() {
         assert_eq!(entries[0].modalities, vec![Modality::Semantic]);
     }
 
+    #[test]
+    fn test_replanned_query_logged_even_when_fast() {
+        let log = SlowQueryLog::with_defaults();
+        let plan = make_plan(vec![(Modality::Vector, 5.0), (Modality::Document, 5.0)]);
+        let step_times = vec![(Modality::Vector, 5.0, 4000), (Modality::Document, 5.0, 20)];
+        let replan = ReplanInfo {
+            after_step: 1,
+            estimated_rows: 10,
+            actual_rows: 4000,
+            error_ratio: 400.0,
+            previous_order: vec![1],
+            new_order: vec![1],
+        };
+
+        assert!(log.record_with_replans(None, 10.0, &plan, &step_times, &[replan]));
+        let entry = &log.recent(1)[0];
+        assert_eq!(entry.replans.len(), 1);
+        assert_eq!(entry.replans[0].actual_rows, 4000);
+        assert!(!log.record_with_replans(None, 10.0, &plan, &step_times, &[]));
+    }
+
     #[test]
     fn test_custom_threshold() {
         let config = SlowQueryConfig {
@@ -546,5 +776,84 @@ fn test_json_serialization() {
         let json = serde_json::to_string(&entries[0]).unwrap();
         let parsed: SlowQueryEntry = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.query_text, Some("SELECT TEMPORAL FROM HEXAD".to_string()));
+        assert_eq!(parsed.steps.len(), 1);
+        assert!(parsed.plan.is_some());
+    }
+
+    #[test]
+    fn test_record_run_keeps_parameters_and_explain() {
+        let log = SlowQueryLog::new(SlowQueryConfig {
+            threshold_ms: 10.0,
+            ..Default::default()
+        });
+        let plan = make_plan(vec![(Modality::Document, 5.0)]);
+        let step_times = vec![(Modality::Document, 20.0, 3)];
+        let explain = || {
+            let mut profiler = crate::Profiler::new("p", &plan);
+            profiler.record_step(0, 20.0, 3, Utc::now(), Utc::now());
+            let profile = profiler.finish(&mut crate::StatisticsCollector::new());
+            crate::ExplainOutput::from_physical_plan(&plan, &crate::PlannerConfig::default()).with_profile(&profile)
+        };
+        let run = |explain| QueryRun {
+            query_text: Some("MATCH (h)"),
+            actual_ms: 20.0,
+            plan: &plan,
+            step_times: &step_times,
+            replans: &[],
+            parameters: [("q".to_string(), serde_json::json!("rust"))].into_iter().collect(),
+            explain,
+        };
+
+        // Explain is only kept when capture is enabled
+        let entry = log.record_run(run(Some(explain()))).unwrap();
+        assert_eq!(entry.parameters["q"], "rust");
+        assert!(entry.explain.is_none());
+        log.set_config(SlowQueryConfig {
+            threshold_ms: 10.0,
+            capture_explain: true,
+            ..Default::default()
+        });
+        let entry = log.record_run(run(Some(explain()))).unwrap();
+        assert!(entry.explain.unwrap().text_output.contains("EXPLAIN ANALYZE"));
+        assert!(!log.is_offender(5.0, &plan, &[]));
+    }
+
+    #[test]
+    fn test_query_filters_and_pages() {
+        let log = SlowQueryLog::new(SlowQueryConfig {
+            threshold_ms: 10.0,
+            ..Default::default()
+        });
+        for (text, modality, ms) in [
+            ("MATCH vector", Modality::Vector, 20.0),
+            ("MATCH graph", Modality::Graph, 200.0),
+            ("match vector again", Modality::Vector, 300.0),
+        ] {
+            let plan = make_plan(vec![(modality, 5.0)]);
+            log.record(Some(text), ms, &plan, &[(modality, ms, 1)]);
+        }
+
+        let filter = SlowQueryFilter {
+            modality: Some(Modality::Vector),
+            ..Default::default()
+        };
+        let (total, page) = log.query(&filter, 0, 10);
+        assert_eq!(total, 2);
+        assert_eq!(page[0].query_text.as_deref(), Some("match vector again"));
+
+        let filter = SlowQueryFilter {
+            min_ms: Some(100.0),
+            query: Some("Match".to_string()),
+            ..Default::default()
+        };
+        let (total, page) = log.query(&filter, 1, 1);
+        assert_eq!(total, 2);
+        assert_eq!(page[0].query_text.as_deref(), Some("MATCH graph"));
+
+        let filter = SlowQueryFilter {
+            until: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&filter, 0, 10).0, 0);
     }
 }
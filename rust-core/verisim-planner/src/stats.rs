@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: PMPL-1.0-or-later
 //! Store statistics collection and tracking.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,15 @@ pub struct StoreStatistics {
     pub query_count: u64,
     /// When statistics were last updated.
     pub last_updated: DateTime<Utc>,
+    /// Approximate size of the store's indexed payload in bytes (from ANALYZE).
+    #[serde(default)]
+    pub index_bytes: u64,
+    /// Per-field value distributions (from ANALYZE).
+    #[serde(default)]
+    pub fields: HashMap<String, FieldStatistics>,
+    /// When ANALYZE last ran against this store (`None` = never analyzed).
+    #[serde(default)]
+    pub analyzed_at: Option<DateTime<Utc>>,
 }
 
 impl StoreStatistics {
@@ -35,8 +44,144 @@ fn new(modality: Modality) -> Self {
             avg_rows_returned: 0,
             query_count: 0,
             last_updated: Utc::now(),
+            index_bytes: 0,
+            fields: HashMap::new(),
+            analyzed_at: None,
         }
     }
+
+    /// Whether ANALYZE has populated these statistics.
+    pub fn is_analyzed(&self) -> bool {
+        self.analyzed_at.is_some()
+    }
+
+    /// Fraction of rows expected to satisfy `field = value`, if the field
+    /// was analyzed.
+    pub fn equality_selectivity(&self, field: &str) -> Option<f64> {
+        let f = self.fields.get(field)?;
+        if self.total_rows == 0 || f.distinct_values == 0 {
+            return Some(0.0);
+        }
+        let present = f.non_null as f64 / self.total_rows as f64;
+        Some((present / f.distinct_values as f64).min(1.0))
+    }
+
+    /// Fraction of rows expected to satisfy `field BETWEEN low AND high`,
+    /// interpolated over the analyzed numeric bounds of the field.
+    pub fn range_selectivity(&self, field: &str, low: &str, high: &str) -> Option<f64> {
+        let f = self.fields.get(field)?;
+        let (min, max) = (f.min?, f.max?);
+        if self.total_rows == 0 {
+            return Some(0.0);
+        }
+        let low = low.trim().parse::<f64>().unwrap_or(min).max(min);
+        let high = high.trim().parse::<f64>().unwrap_or(max).min(max);
+        let present = f.non_null as f64 / self.total_rows as f64;
+        if high < low {
+            return Some(0.0);
+        }
+        if max <= min {
+            return Some(present);
+        }
+        Some(((high - low) / (max - min) * present).clamp(0.0, 1.0))
+    }
+}
+
+/// Value distribution of a single field, collected by ANALYZE.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldStatistics {
+    /// Number of rows carrying a value for the field.
+    pub non_null: u64,
+    /// Number of distinct values (estimated once the sample saturates).
+    pub distinct_values: u64,
+    /// Smallest value, when every value is numeric.
+    pub min: Option<f64>,
+    /// Largest value, when every value is numeric.
+    pub max: Option<f64>,
+}
+
+/// Accumulates a [`FieldStatistics`] one value at a time.
+///
+/// Distinct values are tracked exactly up to [`FieldProfile::DISTINCT_CAP`];
+/// past that the count is extrapolated from the rate at which new values
+/// were still appearing when the sample filled.
+#[derive(Debug, Clone)]
+pub struct FieldProfile {
+    non_null: u64,
+    distinct: HashSet<String>,
+    seen_at_cap: Option<u64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    numeric: bool,
+}
+
+impl FieldProfile {
+    /// Distinct values tracked exactly before extrapolating.
+    pub const DISTINCT_CAP: usize = 10_000;
+
+    /// Create an empty profile.
+    pub fn new() -> Self {
+        Self {
+            non_null: 0,
+            distinct: HashSet::new(),
+            seen_at_cap: None,
+            min: None,
+            max: None,
+            numeric: true,
+        }
+    }
+
+    /// Record one non-null value.
+    pub fn observe(&mut self, value: &str) {
+        self.non_null += 1;
+        if self.seen_at_cap.is_none() {
+            self.distinct.insert(value.to_string());
+            if self.distinct.len() >= Self::DISTINCT_CAP {
+                self.seen_at_cap = Some(self.non_null);
+            }
+        }
+        match value.trim().parse::<f64>() {
+            Ok(n) if self.numeric && n.is_finite() => {
+                self.min = Some(self.min.map_or(n, |m| m.min(n)));
+                self.max = Some(self.max.map_or(n, |m| m.max(n)));
+            }
+            _ => self.numeric = false,
+        }
+    }
+
+    /// Finish the profile.
+    pub fn finish(self) -> FieldStatistics {
+        let distinct_values = match self.seen_at_cap {
+            Some(seen) => {
+                (self.distinct.len() as f64 * self.non_null as f64 / seen as f64) as u64
+            }
+            None => self.distinct.len() as u64,
+        };
+        let (min, max) = if self.numeric { (self.min, self.max) } else { (None, None) };
+        FieldStatistics {
+            non_null: self.non_null,
+            distinct_values,
+            min,
+            max,
+        }
+    }
+}
+
+impl Default for FieldProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What ANALYZE measured for one modality store.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoreAnalysis {
+    /// Number of entities present in the store.
+    pub total_rows: u64,
+    /// Approximate indexed payload size in bytes.
+    pub index_bytes: u64,
+    /// Per-field value distributions.
+    pub fields: HashMap<String, FieldStatistics>,
 }
 
 /// Collects and maintains statistics across all modality stores.
@@ -98,6 +243,20 @@ pub fn update_row_count(&mut self, modality: Modality, total_rows: u64) {
             entry.last_updated = Utc::now();
         }
     }
+
+    /// Replace a modality's cardinality, index size and field
+    /// distributions with the results of an ANALYZE run.
+    ///
+    /// Execution history (latency and rows-returned averages) is kept.
+    pub fn record_analysis(&mut self, modality: Modality, analysis: StoreAnalysis) {
+        let entry = self.stats.entry(modality).or_insert_with(|| StoreStatistics::new(modality));
+        let now = Utc::now();
+        entry.total_rows = analysis.total_rows;
+        entry.index_bytes = analysis.index_bytes;
+        entry.fields = analysis.fields;
+        entry.analyzed_at = Some(now);
+        entry.last_updated = now;
+    }
 }
 
 impl Default for StatisticsCollector {
@@ -264,6 +423,50 @@ fn test_update_row_count() {
         assert_eq!(collector.get(Modality::Document).unwrap().total_rows, 5000);
     }
 
+    #[test]
+    fn test_field_profile_counts_distinct_and_bounds() {
+        let mut profile = FieldProfile::default();
+        for v in ["3", "1", "3", "7"] {
+            profile.observe(v);
+        }
+        let f = profile.finish();
+        assert_eq!(f.non_null, 4);
+        assert_eq!(f.distinct_values, 3);
+        assert_eq!((f.min, f.max), (Some(1.0), Some(7.0)));
+
+        let mut text = FieldProfile::new();
+        text.observe("4");
+        text.observe("alpha");
+        assert_eq!(text.finish().min, None, "mixed values have no numeric bounds");
+    }
+
+    #[test]
+    fn test_record_analysis_drives_selectivity() {
+        let mut collector = StatisticsCollector::new();
+        collector.record_execution(Modality::Document, 12.0, 3);
+        let mut fields = HashMap::new();
+        fields.insert(
+            "year".to_string(),
+            FieldStatistics { non_null: 500, distinct_values: 50, min: Some(2000.0), max: Some(2020.0) },
+        );
+        collector.record_analysis(
+            Modality::Document,
+            StoreAnalysis { total_rows: 1000, index_bytes: 4096, fields },
+        );
+
+        let s = collector.get(Modality::Document).unwrap();
+        assert!(s.is_analyzed());
+        assert_eq!(s.total_rows, 1000);
+        assert_eq!(s.index_bytes, 4096);
+        assert_eq!(s.query_count, 1, "execution history is kept");
+        // Half the rows carry the field, spread over 50 values
+        assert!((s.equality_selectivity("year").unwrap() - 0.01).abs() < 1e-9);
+        // A quarter of the value range, over the half that carry it
+        assert!((s.range_selectivity("year", "2000", "2005").unwrap() - 0.125).abs() < 1e-9);
+        assert_eq!(s.range_selectivity("year", "2030", "2040"), Some(0.0));
+        assert_eq!(s.equality_selectivity("missing"), None);
+    }
+
     #[test]
     fn test_snapshot_returns_all() {
         let collector = StatisticsCollector::new();
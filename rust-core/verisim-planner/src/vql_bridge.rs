@@ -25,6 +25,7 @@
 use std::fmt;
 
 use crate::error::PlannerError;
+use crate::hints::QueryHints;
 use crate::plan::{ConditionKind, LogicalPlan, PlanNode, PostProcessing, QuerySource};
 use crate::Modality;
 
@@ -709,8 +710,13 @@ pub fn to_logical_plan(&self) -> Result<LogicalPlan, PlannerError> {
         // 7. Build post-processing pipeline.
         let mut post_processing = Vec::new();
 
-        if let Some(ref group_fields) = query.group_by {
-            let fields: Vec<String> = group_fields.iter().map(field_ref_name).collect();
+        // Aggregates without GROUP BY fold every row into one group.
+        if query.group_by.is_some() || query.aggregates.as_ref().is_some_and(|a| !a.is_empty()) {
+            let fields: Vec<String> = query
+                .group_by
+                .as_ref()
+                .map(|g| g.iter().map(field_ref_name).collect())
+                .unwrap_or_default();
             let aggregates: Vec<String> = query
                 .aggregates
                 .as_ref()
@@ -789,6 +795,8 @@ pub fn to_logical_plan(&self) -> Result<LogicalPlan, PlannerError> {
             source,
             nodes,
             post_processing,
+            joins: vec![],
+            hints: QueryHints::default(),
         })
     }
 }
@@ -1321,6 +1329,33 @@ fn test_aggregation_group_by_order_by() {
         assert!(has_offset, "should have offset marker in post-processing");
     }
 
+    #[test]
+    fn test_aggregation_without_group_by() {
+        let json = r#"{
+            "TAG": "Query",
+            "_0": {
+                "modalities": [{"TAG": "Document"}],
+                "source": {"TAG": "Hexad", "_0": null},
+                "where": null,
+                "projections": null,
+                "aggregates": [{"function": "SUM", "field": {"field": "score"}, "alias": null}],
+                "groupBy": null,
+                "having": null,
+                "proof": null,
+                "orderBy": null,
+                "limit": null,
+                "offset": null
+            }
+        }"#;
+
+        let plan = parse_and_plan(json).expect("should parse global aggregate");
+        assert!(plan.post_processing.iter().any(|p| matches!(
+            p,
+            PostProcessing::GroupBy { fields, aggregates }
+                if fields.is_empty() && aggregates == &["SUM(score)".to_string()]
+        )));
+    }
+
     #[test]
     fn test_all_modality_expansion() {
         let json = r#"{
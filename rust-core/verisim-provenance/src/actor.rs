@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Actor identity registry
+//!
+//! Maps authenticated principals (API keys, bearer-token subjects, internal
+//! components) to canonical actor IRIs.  Provenance records written on behalf
+//! of an authenticated caller use the canonical IRI as their `actor`, so
+//! "everything key X ever touched" becomes a single
+//! [`ProvenanceStore::search_by_actor`](crate::ProvenanceStore::search_by_actor)
+//! lookup instead of a guess over free-form strings.
+//!
+//! A registry given a file with [`ActorRegistry::with_persistence`] is saved
+//! there whenever a principal is registered or linked, so the mappings
+//! provenance records were written under survive restarts alongside them.
+//! Refreshed `last_seen` times are saved at most every
+//! [`LAST_SEEN_SAVE_INTERVAL`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::ProvenanceError;
+
+/// Default base IRI under which actor identities are minted.
+pub const DEFAULT_ACTOR_BASE_IRI: &str = "https://verisim.db/actor";
+
+/// Shortest time between saves that only refresh `last_seen`.
+pub const LAST_SEEN_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How a principal authenticated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PrincipalKind {
+    /// Static API key (principal is the SHA-256 hash of the key)
+    ApiKey,
+    /// Bearer token (principal is the token subject)
+    Token,
+    /// Service account key (principal is the account name)
+    ServiceAccount,
+    /// Client certificate (principal is the mapped subject alternative name)
+    Certificate,
+    /// Internal system component (normalizer, drift scanner, ...)
+    System,
+}
+
+impl std::fmt::Display for PrincipalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrincipalKind::ApiKey => write!(f, "api_key"),
+            PrincipalKind::Token => write!(f, "token"),
+            PrincipalKind::ServiceAccount => write!(f, "service_account"),
+            PrincipalKind::Certificate => write!(f, "certificate"),
+            PrincipalKind::System => write!(f, "system"),
+        }
+    }
+}
+
+/// Canonical identity of an actor, as recorded in provenance chains.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActorIdentity {
+    /// Canonical actor IRI written into provenance records
+    pub iri: String,
+    /// Principal identifier as seen by the authentication layer
+    pub principal: String,
+    /// How the principal authenticates
+    pub kind: PrincipalKind,
+    /// Optional human-readable name (API key label, token display name)
+    pub display_name: Option<String>,
+    /// First time the principal was seen
+    pub first_seen: DateTime<Utc>,
+    /// Most recent time the principal was seen
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Registry of principal → actor identity mappings.
+///
+/// Principals are registered lazily on first use via [`ActorRegistry::resolve`].
+/// Several principals can be linked to the same canonical IRI with
+/// [`ActorRegistry::link`] (e.g. a person holding more than one key).
+#[derive(Debug, Clone)]
+pub struct ActorRegistry {
+    base_iri: String,
+    actors: Arc<RwLock<HashMap<String, ActorIdentity>>>,
+    persistence: Option<Arc<Persistence>>,
+}
+
+/// File a registry is saved to, and when it last was.
+#[derive(Debug)]
+struct Persistence {
+    path: PathBuf,
+    saved_at: std::sync::Mutex<Option<Instant>>,
+}
+
+impl ActorRegistry {
+    /// Create an empty registry minting IRIs under `base_iri`.
+    pub fn new(base_iri: impl Into<String>) -> Self {
+        Self {
+            base_iri: base_iri.into().trim_end_matches('/').to_string(),
+            actors: Arc::new(RwLock::new(HashMap::new())),
+            persistence: None,
+        }
+    }
+
+    /// Load the actors saved at `path`, if the file exists, and save the
+    /// registry there from now on.
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self, ProvenanceError> {
+        let path = path.into();
+        let io = |e: &dyn std::fmt::Display| {
+            ProvenanceError::IoError(format!("{}: {}", path.display(), e))
+        };
+        let saved: Vec<ActorIdentity> = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| io(&e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(io(&e)),
+        };
+        info!(path = %path.display(), actors = saved.len(), "Loaded actor registry");
+        self.actors = Arc::new(RwLock::new(
+            saved
+                .into_iter()
+                .map(|a| (a.principal.clone(), a))
+                .collect(),
+        ));
+        self.persistence = Some(Arc::new(Persistence {
+            path,
+            saved_at: std::sync::Mutex::new(None),
+        }));
+        Ok(self)
+    }
+
+    /// The IRI a principal is assigned when it has not been explicitly linked.
+    pub fn mint_iri(&self, kind: PrincipalKind, principal: &str) -> String {
+        format!("{}/{}/{}", self.base_iri, kind, principal)
+    }
+
+    /// Resolve a principal to its actor identity, registering it on first use
+    /// and refreshing `last_seen` otherwise.
+    pub async fn resolve(
+        &self,
+        kind: PrincipalKind,
+        principal: &str,
+        display_name: Option<&str>,
+    ) -> ActorIdentity {
+        let now = Utc::now();
+        let mut actors = self.actors.write().await;
+        let mut changed = false;
+        let actor = actors.entry(principal.to_string()).or_insert_with(|| {
+            debug!(principal = %principal, kind = %kind, "Registered new actor");
+            changed = true;
+            ActorIdentity {
+                iri: self.mint_iri(kind, principal),
+                principal: principal.to_string(),
+                kind,
+                display_name: display_name.map(str::to_string),
+                first_seen: now,
+                last_seen: now,
+            }
+        });
+        actor.last_seen = now;
+        if actor.display_name.is_none() && display_name.is_some() {
+            actor.display_name = display_name.map(str::to_string);
+            changed = true;
+        }
+        let actor = actor.clone();
+        self.save(&actors, changed);
+        actor
+    }
+
+    /// Explicitly map a principal to an existing canonical IRI.
+    ///
+    /// Replaces any previous mapping for the principal.
+    pub async fn link(
+        &self,
+        kind: PrincipalKind,
+        principal: &str,
+        iri: impl Into<String>,
+        display_name: Option<&str>,
+    ) -> ActorIdentity {
+        let now = Utc::now();
+        let mut actors = self.actors.write().await;
+        let first_seen = actors.get(principal).map(|a| a.first_seen).unwrap_or(now);
+        let actor = ActorIdentity {
+            iri: iri.into(),
+            principal: principal.to_string(),
+            kind,
+            display_name: display_name.map(str::to_string),
+            first_seen,
+            last_seen: now,
+        };
+        actors.insert(principal.to_string(), actor.clone());
+        self.save(&actors, true);
+        actor
+    }
+
+    /// Look up a principal without registering it.
+    pub async fn get(&self, principal: &str) -> Option<ActorIdentity> {
+        self.actors.read().await.get(principal).cloned()
+    }
+
+    /// All principals mapped to the given canonical IRI.
+    pub async fn principals_for(&self, iri: &str) -> Vec<ActorIdentity> {
+        self.actors
+            .read()
+            .await
+            .values()
+            .filter(|a| a.iri == iri)
+            .cloned()
+            .collect()
+    }
+
+    /// List all registered actors.
+    pub async fn list(&self) -> Vec<ActorIdentity> {
+        let mut actors: Vec<ActorIdentity> = self.actors.read().await.values().cloned().collect();
+        actors.sort_by(|a, b| a.iri.cmp(&b.iri));
+        actors
+    }
+}
+
+impl ActorRegistry {
+    /// Save `actors` when the registry is persistent: always after a
+    /// mapping `changed`, otherwise only if the last save is older than
+    /// [`LAST_SEEN_SAVE_INTERVAL`].  A failed save is logged, and the
+    /// registry keeps working from memory.
+    fn save(&self, actors: &HashMap<String, ActorIdentity>, changed: bool) {
+        let Some(persistence) = &self.persistence else {
+            return;
+        };
+        let mut saved_at = persistence
+            .saved_at
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !changed && saved_at.is_some_and(|at| at.elapsed() < LAST_SEEN_SAVE_INTERVAL) {
+            return;
+        }
+        let mut list: Vec<&ActorIdentity> = actors.values().collect();
+        list.sort_by(|a, b| a.principal.cmp(&b.principal));
+        let saved = serde_json::to_vec_pretty(&list)
+            .map_err(std::io::Error::other)
+            .and_then(|data| write_durably(&persistence.path, &data));
+        match saved {
+            Ok(()) => *saved_at = Some(Instant::now()),
+            Err(e) => {
+                warn!(path = %persistence.path.display(), error = %e, "Failed to save actor registry")
+            }
+        }
+    }
+}
+
+/// Replace the file at `path` with `data` via a temporary file, flushing
+/// both the file and its directory, so a crash leaves the old contents or
+/// the new ones.
+fn write_durably(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::File::open(dir)?.sync_all()
+}
+
+impl Default for ActorRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTOR_BASE_IRI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryProvenanceStore, ProvenanceEventType, ProvenanceStore};
+
+    #[tokio::test]
+    async fn test_resolve_registers_once() {
+        let registry = ActorRegistry::default();
+
+        let first = registry.resolve(PrincipalKind::ApiKey, "abc123", Some("ci key")).await;
+        assert_eq!(first.iri, "https://verisim.db/actor/api_key/abc123");
+        assert_eq!(first.display_name.as_deref(), Some("ci key"));
+
+        let second = registry.resolve(PrincipalKind::ApiKey, "abc123", None).await;
+        assert_eq!(second.iri, first.iri);
+        assert_eq!(second.first_seen, first.first_seen);
+        assert!(second.last_seen >= first.last_seen);
+        assert_eq!(registry.list().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_link_shares_canonical_iri() {
+        let registry = ActorRegistry::new("https://example.org/people/");
+        registry
+            .link(PrincipalKind::ApiKey, "key-hash", "https://example.org/people/alice", Some("alice laptop"))
+            .await;
+        registry
+            .link(PrincipalKind::Token, "alice@example.org", "https://example.org/people/alice", None)
+            .await;
+
+        let principals = registry.principals_for("https://example.org/people/alice").await;
+        assert_eq!(principals.len(), 2);
+
+        // Resolving a linked principal keeps the linked IRI.
+        let resolved = registry.resolve(PrincipalKind::Token, "alice@example.org", None).await;
+        assert_eq!(resolved.iri, "https://example.org/people/alice");
+    }
+
+    #[tokio::test]
+    async fn test_persistent_registry_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actors.json");
+        let open = || ActorRegistry::default().with_persistence(&path).unwrap();
+
+        let registry = open();
+        let key = registry.resolve(PrincipalKind::ApiKey, "abc123", Some("ci key")).await;
+        registry
+            .link(PrincipalKind::Token, "alice@example.org", "https://example.org/people/alice", None)
+            .await;
+
+        let reopened = open();
+        assert_eq!(reopened.list().await.len(), 2);
+        let restored = reopened.get("abc123").await.unwrap();
+        assert_eq!(restored, key);
+        let resolved = reopened.resolve(PrincipalKind::Token, "alice@example.org", None).await;
+        assert_eq!(resolved.iri, "https://example.org/people/alice");
+
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(matches!(
+            ActorRegistry::default().with_persistence(&path),
+            Err(ProvenanceError::IoError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_actor_iri_queryable_in_provenance() {
+        let registry = ActorRegistry::default();
+        let store = InMemoryProvenanceStore::new();
+        let actor = registry.resolve(PrincipalKind::Token, "bob", None).await;
+
+        store
+            .record_event("e1", ProvenanceEventType::Created, &actor.iri, None, "Created")
+            .await
+            .unwrap();
+        store
+            .record_event("e2", ProvenanceEventType::Modified, &actor.iri, None, "Modified")
+            .await
+            .unwrap();
+        store
+            .record_event("e3", ProvenanceEventType::Created, "system", None, "Created")
+            .await
+            .unwrap();
+
+        let touched = store.search_by_actor(&actor.iri).await.unwrap();
+        assert_eq!(touched.len(), 2);
+    }
+}
@@ -15,6 +15,11 @@
 //!   querying provenance data.
 //! - **InMemoryProvenanceStore**: Reference implementation backed by a
 //!   `HashMap<String, Vec<ProvenanceRecord>>`.
+//! - **ActorRegistry**: Maps authenticated principals to canonical actor IRIs
+//!   used as the `actor` of provenance records.
+//! - **ChainLink**: Stitches chains kept on different instances — a record
+//!   made when an entity is handed off from another instance carries the
+//!   head hash of the entity's chain there, covered by the record's own hash.
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -26,6 +31,10 @@
 use tokio::sync::RwLock;
 use tracing::{debug, instrument};
 
+// Principal → canonical actor IRI mapping
+pub mod actor;
+pub use actor::{ActorIdentity, ActorRegistry, PrincipalKind, DEFAULT_ACTOR_BASE_IRI};
+
 /// Provenance-specific errors
 #[derive(Error, Debug)]
 pub enum ProvenanceError {
@@ -93,6 +102,18 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// The chain an entity's history continues from on another instance, as of
+/// a hand-off
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainLink {
+    /// Instance the entity came from (API endpoint or sync node ID)
+    pub instance: String,
+    /// Entity's ID there
+    pub entity_id: String,
+    /// `content_hash` of the latest record of its chain there
+    pub head_hash: String,
+}
+
 /// A single provenance record — one event in an entity's lineage chain.
 ///
 /// Records are linked by `parent_hash`: the SHA-256 of the serialized
@@ -114,6 +135,9 @@ pub struct ProvenanceRecord {
     pub parent_hash: String,
     /// SHA-256 hex digest of this record's canonical serialization
     pub content_hash: String,
+    /// For a hand-off from another instance, the chain there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<ChainLink>,
 }
 
 impl ProvenanceRecord {
@@ -128,7 +152,21 @@ pub fn compute_hash(
         description: &str,
         parent_hash: &str,
     ) -> String {
-        let canonical = serde_json::json!({
+        Self::compute_linked_hash(event_type, actor, timestamp, source, description, parent_hash, None)
+    }
+
+    /// [`compute_hash`](Self::compute_hash) of a record that may carry a
+    /// [`ChainLink`]; a record without one hashes as before links existed.
+    pub fn compute_linked_hash(
+        event_type: &ProvenanceEventType,
+        actor: &str,
+        timestamp: &DateTime<Utc>,
+        source: &Option<String>,
+        description: &str,
+        parent_hash: &str,
+        link: Option<&ChainLink>,
+    ) -> String {
+        let mut canonical = serde_json::json!({
             "event_type": event_type,
             "actor": actor,
             "timestamp": timestamp.to_rfc3339(),
@@ -136,6 +174,9 @@ pub fn compute_hash(
             "description": description,
             "parent_hash": parent_hash,
         });
+        if let Some(link) = link {
+            canonical["link"] = serde_json::json!(link);
+        }
         let bytes = canonical.to_string().into_bytes();
         let digest = Sha256::digest(&bytes);
         format!("{:x}", digest)
@@ -171,19 +212,36 @@ pub fn new(
             description,
             parent_hash,
             content_hash,
+            link: None,
         }
     }
 
+    /// This record carrying `link`, its `content_hash` recomputed to cover it.
+    pub fn with_link(mut self, link: ChainLink) -> Self {
+        self.content_hash = Self::compute_linked_hash(
+            &self.event_type,
+            &self.actor,
+            &self.timestamp,
+            &self.source,
+            &self.description,
+            &self.parent_hash,
+            Some(&link),
+        );
+        self.link = Some(link);
+        self
+    }
+
     /// Verify that `content_hash` matches the re-computed hash of this
     /// record's fields.
     pub fn verify(&self) -> bool {
-        let expected = Self::compute_hash(
+        let expected = Self::compute_linked_hash(
             &self.event_type,
             &self.actor,
             &self.timestamp,
             &self.source,
             &self.description,
             &self.parent_hash,
+            self.link.as_ref(),
         );
         self.content_hash == expected
     }
@@ -270,6 +328,16 @@ pub fn latest(&self) -> Option<&ProvenanceRecord> {
         self.records.last()
     }
 
+    /// Whether a record with this `content_hash` is in the chain.
+    pub fn contains_hash(&self, hash: &str) -> bool {
+        self.records.iter().any(|r| r.content_hash == hash)
+    }
+
+    /// Links to chains on other instances, oldest first.
+    pub fn links(&self) -> impl Iterator<Item = &ChainLink> {
+        self.records.iter().filter_map(|r| r.link.as_ref())
+    }
+
     /// Append a new record to the chain.
     ///
     /// The `parent_hash` is set automatically from the previous record's
@@ -280,6 +348,19 @@ pub fn append(
         actor: impl Into<String>,
         source: Option<String>,
         description: impl Into<String>,
+    ) -> &ProvenanceRecord {
+        self.append_linked(event_type, actor, source, description, None)
+    }
+
+    /// Append a new record, linked to the chain the entity was handed off
+    /// from if `link` is given.
+    pub fn append_linked(
+        &mut self,
+        event_type: ProvenanceEventType,
+        actor: impl Into<String>,
+        source: Option<String>,
+        description: impl Into<String>,
+        link: Option<ChainLink>,
     ) -> &ProvenanceRecord {
         let parent_hash = self
             .records
@@ -287,7 +368,10 @@ pub fn append(
             .map(|r| r.content_hash.clone())
             .unwrap_or_else(Self::genesis_hash);
 
-        let record = ProvenanceRecord::new(event_type, actor, source, description, parent_hash);
+        let mut record = ProvenanceRecord::new(event_type, actor, source, description, parent_hash);
+        if let Some(link) = link {
+            record = record.with_link(link);
+        }
         self.records.push(record);
         self.records.last().unwrap()
     }
@@ -312,6 +396,18 @@ async fn record_event(
         description: &str,
     ) -> Result<ProvenanceRecord, ProvenanceError>;
 
+    /// Record the hand-off of an entity from another instance, linking its
+    /// chain here to the one there.
+    async fn record_handoff(
+        &self,
+        entity_id: &str,
+        event_type: ProvenanceEventType,
+        actor: &str,
+        source: Option<String>,
+        description: &str,
+        link: ChainLink,
+    ) -> Result<ProvenanceRecord, ProvenanceError>;
+
     /// Retrieve the full provenance chain for an entity.
     async fn get_chain(&self, entity_id: &str) -> Result<ProvenanceChain, ProvenanceError>;
 
@@ -332,6 +428,11 @@ async fn record_event(
 
     /// Delete the provenance chain for an entity (for testing / admin use).
     async fn delete_chain(&self, entity_id: &str) -> Result<(), ProvenanceError>;
+
+    /// Replace an entity's chain with `chain`, as read from a backup.
+    ///
+    /// The chain is verified first; a corrupted one is refused.
+    async fn import_chain(&self, chain: ProvenanceChain) -> Result<(), ProvenanceError>;
 }
 
 /// In-memory implementation of [`ProvenanceStore`].
@@ -351,40 +452,66 @@ pub fn new() -> Self {
     }
 }
 
-impl Default for InMemoryProvenanceStore {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-#[async_trait]
-impl ProvenanceStore for InMemoryProvenanceStore {
-    #[instrument(skip(self))]
-    async fn record_event(
+impl InMemoryProvenanceStore {
+    async fn append(
         &self,
         entity_id: &str,
         event_type: ProvenanceEventType,
         actor: &str,
         source: Option<String>,
         description: &str,
+        link: Option<ChainLink>,
     ) -> Result<ProvenanceRecord, ProvenanceError> {
         let mut chains = self.chains.write().await;
         let chain = chains
             .entry(entity_id.to_string())
             .or_insert_with(|| ProvenanceChain::new(entity_id));
 
-        chain.append(event_type, actor, source, description);
-
-        let record = chain.records.last().unwrap().clone();
+        let record = chain.append_linked(event_type, actor, source, description, link).clone();
         debug!(
             entity_id = %entity_id,
             event = %record.event_type,
             actor = %record.actor,
             chain_length = chain.len(),
+            linked = record.link.is_some(),
             "Provenance event recorded"
         );
         Ok(record)
     }
+}
+
+impl Default for InMemoryProvenanceStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProvenanceStore for InMemoryProvenanceStore {
+    #[instrument(skip(self))]
+    async fn record_event(
+        &self,
+        entity_id: &str,
+        event_type: ProvenanceEventType,
+        actor: &str,
+        source: Option<String>,
+        description: &str,
+    ) -> Result<ProvenanceRecord, ProvenanceError> {
+        self.append(entity_id, event_type, actor, source, description, None).await
+    }
+
+    #[instrument(skip(self))]
+    async fn record_handoff(
+        &self,
+        entity_id: &str,
+        event_type: ProvenanceEventType,
+        actor: &str,
+        source: Option<String>,
+        description: &str,
+        link: ChainLink,
+    ) -> Result<ProvenanceRecord, ProvenanceError> {
+        self.append(entity_id, event_type, actor, source, description, Some(link)).await
+    }
 
     async fn get_chain(&self, entity_id: &str) -> Result<ProvenanceChain, ProvenanceError> {
         let chains = self.chains.read().await;
@@ -433,6 +560,13 @@ async fn delete_chain(&self, entity_id: &str) -> Result<(), ProvenanceError> {
         chains.remove(entity_id);
         Ok(())
     }
+
+    async fn import_chain(&self, chain: ProvenanceChain) -> Result<(), ProvenanceError> {
+        chain.verify()?;
+        let mut chains = self.chains.write().await;
+        chains.insert(chain.entity_id.clone(), chain);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -626,6 +760,64 @@ async fn test_in_memory_store_delete_chain() {
         assert!(store.get_chain("e1").await.is_err());
     }
 
+    #[test]
+    fn test_linked_record_hash_covers_link() {
+        let link = ChainLink {
+            instance: "https://primary.example.com".to_string(),
+            entity_id: "e1".to_string(),
+            head_hash: "abc123".to_string(),
+        };
+        let plain = ProvenanceRecord::new(ProvenanceEventType::Imported, "replica", None, "Replicated", "0000000000000000");
+        let mut linked = plain.clone().with_link(link);
+        assert!(linked.verify());
+        assert_ne!(linked.content_hash, plain.content_hash);
+
+        // Repointing the link breaks the record's hash
+        linked.link.as_mut().unwrap().head_hash = "forged".to_string();
+        assert!(!linked.verify());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_record_handoff() {
+        let store = InMemoryProvenanceStore::new();
+        let link = ChainLink {
+            instance: "node-a".to_string(),
+            entity_id: "e1".to_string(),
+            head_hash: "abc123".to_string(),
+        };
+        store
+            .record_handoff("e1", ProvenanceEventType::Imported, "crdt-sync", None, "Merged", link.clone())
+            .await
+            .unwrap();
+        store
+            .record_event("e1", ProvenanceEventType::Modified, "bob", None, "Modified")
+            .await
+            .unwrap();
+
+        let chain = store.get_chain("e1").await.unwrap();
+        assert!(chain.verify().is_ok());
+        assert_eq!(chain.links().collect::<Vec<_>>(), vec![&link]);
+        assert!(chain.contains_hash(&chain.latest().unwrap().content_hash));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_import_chain() {
+        let source = InMemoryProvenanceStore::new();
+        source.record_event("e1", ProvenanceEventType::Created, "alice", None, "Created").await.unwrap();
+        source.record_event("e1", ProvenanceEventType::Modified, "bob", None, "Modified").await.unwrap();
+        let chain = source.get_chain("e1").await.unwrap();
+
+        let store = InMemoryProvenanceStore::new();
+        store.import_chain(chain.clone()).await.unwrap();
+        let imported = store.get_chain("e1").await.unwrap();
+        assert_eq!(imported.latest().unwrap().content_hash, chain.latest().unwrap().content_hash);
+
+        let mut forged = chain;
+        forged.records[0].actor = "mallory".to_string();
+        assert!(matches!(store.import_chain(forged).await, Err(ProvenanceError::HashMismatch { index: 0, .. })));
+        assert!(store.verify_chain("e1").await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_in_memory_store_not_found() {
         let store = InMemoryProvenanceStore::new();
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <j.d.a.jewell@open.ac.uk>
+//!
+//! VeriSimDB Python bindings — the embedded store of `verisim-ffi` as the
+//! `verisim` module.
+//!
+//! ```python
+//! import verisim
+//!
+//! store = verisim.Store(vector_dimension=3)   # persistence_dir="..." to persist
+//! hexad = store.create_hexad({"title": "Drift", "embedding": [1.0, 0.0, 0.0]})
+//! store.search_text("drift")
+//! store.search_vector([0.9, 0.1, 0.0], k=5)
+//! store.execute_vql("SELECT * FROM hexads LIMIT 10")
+//! ```
+//!
+//! Requests and results are the dicts and lists of the JSON bodies of the
+//! REST endpoints each method mirrors. Calls release the GIL while the store
+//! works. Failures raise `verisim.VeriSimError`, or its subclass
+//! `verisim.NotFoundError` for a missing hexad.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use verisim_ffi::FfiError;
+
+create_exception!(verisim, VeriSimError, PyException, "A VeriSimDB call failed.");
+create_exception!(verisim, NotFoundError, VeriSimError, "The hexad does not exist.");
+
+fn py_error(e: FfiError) -> PyErr {
+    match e {
+        FfiError::Api(verisim_ffi::ApiError::NotFound(_)) => NotFoundError::new_err(e.to_string()),
+        e => VeriSimError::new_err(e.to_string()),
+    }
+}
+
+fn dumps(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    PyModule::import(value.py(), "json")?.call_method1("dumps", (value,))?.extract()
+}
+
+fn loads<'py>(py: Python<'py>, json: &str) -> PyResult<Bound<'py, PyAny>> {
+    PyModule::import(py, "json")?.call_method1("loads", (json,))
+}
+
+/// An embedded VeriSimDB store.
+///
+/// Keyword arguments set `ApiConfig` fields, such as `persistence_dir` and
+/// `vector_dimension`; without `persistence_dir` the store lives in memory.
+#[pyclass(module = "verisim", frozen)]
+struct Store {
+    inner: verisim_ffi::Store,
+}
+
+impl Store {
+    /// Run `call` on the store without the GIL and decode its JSON result.
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        call: impl FnOnce(&verisim_ffi::Store) -> Result<String, FfiError> + Send,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let json = py.detach(|| call(&self.inner)).map_err(py_error)?;
+        loads(py, &json)
+    }
+}
+
+#[pymethods]
+impl Store {
+    #[new]
+    #[pyo3(signature = (**config))]
+    fn new(py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let config = match config {
+            Some(config) => dumps(config.as_any())?,
+            None => String::new(),
+        };
+        let inner = py.detach(|| verisim_ffi::Store::open_json(&config)).map_err(py_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Create a hexad from a `POST /hexads` body and return it.
+    fn create_hexad<'py>(&self, py: Python<'py>, request: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let request = dumps(request)?;
+        self.call(py, |store| store.create_hexad(&request))
+    }
+
+    /// Get a hexad by ID.
+    fn get_hexad<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.call(py, |store| store.get_hexad(id))
+    }
+
+    /// Update a hexad from a `PUT /hexads/{id}` body and return it.
+    fn update_hexad<'py>(&self, py: Python<'py>, id: &str, request: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let request = dumps(request)?;
+        self.call(py, |store| store.update_hexad(id, &request))
+    }
+
+    /// Delete a hexad.
+    fn delete_hexad(&self, py: Python<'_>, id: &str) -> PyResult<()> {
+        py.detach(|| self.inner.delete_hexad(id)).map_err(py_error)
+    }
+
+    /// List hexads, a page at a time.
+    #[pyo3(signature = (limit = 100, offset = 0))]
+    fn list_hexads<'py>(&self, py: Python<'py>, limit: usize, offset: usize) -> PyResult<Bound<'py, PyAny>> {
+        self.call(py, |store| store.list_hexads(limit, offset))
+    }
+
+    /// Full-text search across the document modality.
+    #[pyo3(signature = (query, limit = 10))]
+    fn search_text<'py>(&self, py: Python<'py>, query: &str, limit: usize) -> PyResult<Bound<'py, PyAny>> {
+        self.call(py, |store| store.search_text(query, limit))
+    }
+
+    /// The `k` hexads whose embeddings are nearest `vector`.
+    #[pyo3(signature = (vector, k = 10))]
+    fn search_vector<'py>(&self, py: Python<'py>, vector: Vec<f32>, k: usize) -> PyResult<Bound<'py, PyAny>> {
+        self.call(py, |store| store.search_vector(&vector, k))
+    }
+
+    /// Execute a VQL statement; with a `transaction`, its writes are
+    /// buffered there until it commits.
+    #[pyo3(signature = (query, transaction = None))]
+    fn execute_vql<'py>(&self, py: Python<'py>, query: &str, transaction: Option<&str>) -> PyResult<Bound<'py, PyAny>> {
+        self.call(py, |store| store.execute_vql(query, transaction))
+    }
+}
+
+#[pymodule]
+fn verisim(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Store>()?;
+    m.add("VeriSimError", m.py().get_type::<VeriSimError>())?;
+    m.add("NotFoundError", m.py().get_type::<NotFoundError>())?;
+    Ok(())
+}
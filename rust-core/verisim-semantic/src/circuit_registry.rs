@@ -4,10 +4,17 @@
 //! In-memory registry mapping circuit names to compiled verification functions.
 //! Custom circuits allow VQL queries to include `PROOF CUSTOM "circuit-name"
 //! WITH (param=value, ...)` clauses that verify application-specific properties.
+//!
+//! Each circuit is registered under a semantic version (`MAJOR.MINOR.PATCH`),
+//! and a registered version never changes.  New proofs use the latest version
+//! that is not deprecated unless they name one; deprecated versions stay in
+//! the registry so that proofs generated against them can still be verified.
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
 use std::sync::RwLock;
 use thiserror::Error;
 
@@ -31,6 +38,12 @@ pub enum CircuitError {
     #[error("Invalid witness: {0}")]
     InvalidWitness(String),
 
+    #[error("Invalid circuit version: {0}")]
+    InvalidVersion(String),
+
+    #[error("Circuit deprecated: {0}")]
+    Deprecated(String),
+
     #[error("Lock poisoned")]
     LockPoisoned,
 }
@@ -144,9 +157,121 @@ fn eval_linear(terms: &HashMap<usize, f64>, assignment: &[f64]) -> f64 {
         .sum()
 }
 
-/// The circuit registry — manages named circuits
+/// Semantic version of a circuit, `MAJOR.MINOR.PATCH`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CircuitVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl CircuitVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl Default for CircuitVersion {
+    fn default() -> Self {
+        Self::new(1, 0, 0)
+    }
+}
+
+impl fmt::Display for CircuitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for CircuitVersion {
+    type Err = CircuitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CircuitError::InvalidVersion(format!("'{}' is not MAJOR.MINOR.PATCH", s));
+        let parts: Vec<u64> = s
+            .trim()
+            .split('.')
+            .map(|part| part.parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        match parts[..] {
+            [major, minor, patch] => Ok(Self::new(major, minor, patch)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for CircuitVersion {
+    type Error = CircuitError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CircuitVersion> for String {
+    fn from(version: CircuitVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// One registered version of a circuit
+#[derive(Debug, Clone)]
+struct RegisteredCircuit {
+    circuit: CompiledCircuit,
+    description: Option<String>,
+    registered_at: String,
+    deprecated_at: Option<String>,
+    deprecation_reason: Option<String>,
+}
+
+/// A registered circuit version as listed to clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitInfo {
+    pub name: String,
+    pub version: CircuitVersion,
+    /// SHA-256 of the compiled circuit, which proofs record
+    pub circuit_hash: String,
+    /// Parameters settable from a VQL `WITH` clause
+    pub parameters: Vec<String>,
+    pub num_public_inputs: usize,
+    pub num_witness_wires: usize,
+    pub num_constraints: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub registered_at: String,
+    pub deprecated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation_reason: Option<String>,
+}
+
+impl CircuitInfo {
+    fn new(name: &str, version: CircuitVersion, registered: &RegisteredCircuit) -> Self {
+        let ir = &registered.circuit.ir;
+        let mut parameters: Vec<String> = ir.parameter_map.keys().cloned().collect();
+        parameters.sort();
+        Self {
+            name: name.to_string(),
+            version,
+            circuit_hash: registered.circuit.circuit_hash.clone(),
+            parameters,
+            num_public_inputs: ir.num_public_inputs,
+            num_witness_wires: ir.num_witness_wires,
+            num_constraints: ir.constraints.len(),
+            description: registered.description.clone(),
+            registered_at: registered.registered_at.clone(),
+            deprecated: registered.deprecated_at.is_some(),
+            deprecated_at: registered.deprecated_at.clone(),
+            deprecation_reason: registered.deprecation_reason.clone(),
+        }
+    }
+}
+
+/// The circuit registry — manages named, versioned circuits
 pub struct CircuitRegistry {
-    circuits: RwLock<HashMap<String, CompiledCircuit>>,
+    circuits: RwLock<HashMap<String, BTreeMap<CircuitVersion, RegisteredCircuit>>>,
 }
 
 impl CircuitRegistry {
@@ -157,42 +282,154 @@ pub fn new() -> Self {
         }
     }
 
-    /// Register a compiled circuit
+    /// Register a compiled circuit as version 1.0.0 of a new circuit
     pub fn register_circuit(
         &self,
         name: &str,
         circuit: CompiledCircuit,
     ) -> Result<(), CircuitError> {
-        let mut circuits = self.circuits.write().map_err(|_| CircuitError::LockPoisoned)?;
-
-        if circuits.contains_key(name) {
+        if self.circuits.read().map_err(|_| CircuitError::LockPoisoned)?.contains_key(name) {
             return Err(CircuitError::AlreadyExists(name.to_string()));
         }
+        self.register_version(name, CircuitVersion::default(), circuit, None)
+            .map(|_| ())
+    }
 
-        circuits.insert(name.to_string(), circuit);
-        Ok(())
+    /// Register `version` of circuit `name`; a version, once registered,
+    /// cannot be replaced
+    pub fn register_version(
+        &self,
+        name: &str,
+        version: CircuitVersion,
+        circuit: CompiledCircuit,
+        description: Option<String>,
+    ) -> Result<CircuitInfo, CircuitError> {
+        let mut circuits = self.circuits.write().map_err(|_| CircuitError::LockPoisoned)?;
+        let versions = circuits.entry(name.to_string()).or_default();
+        if versions.contains_key(&version) {
+            return Err(CircuitError::AlreadyExists(format!("{}@{}", name, version)));
+        }
+        let registered = RegisteredCircuit {
+            circuit,
+            description,
+            registered_at: chrono::Utc::now().to_rfc3339(),
+            deprecated_at: None,
+            deprecation_reason: None,
+        };
+        let info = CircuitInfo::new(name, version, &registered);
+        versions.insert(version, registered);
+        Ok(info)
     }
 
-    /// Get a circuit by name
+    /// The version of `name` new proofs use: `version` when given, else the
+    /// latest one not deprecated.  Deprecated versions are refused.
+    pub fn resolve(
+        &self,
+        name: &str,
+        version: Option<CircuitVersion>,
+    ) -> Result<(CircuitVersion, CompiledCircuit), CircuitError> {
+        let circuits = self.circuits.read().map_err(|_| CircuitError::LockPoisoned)?;
+        let versions = circuits
+            .get(name)
+            .filter(|versions| !versions.is_empty())
+            .ok_or_else(|| CircuitError::NotFound(name.to_string()))?;
+        let (version, registered) = match version {
+            Some(version) => {
+                let registered = versions
+                    .get(&version)
+                    .ok_or_else(|| CircuitError::NotFound(format!("{}@{}", name, version)))?;
+                if registered.deprecated_at.is_some() {
+                    return Err(CircuitError::Deprecated(format!("{}@{}", name, version)));
+                }
+                (version, registered)
+            }
+            None => versions
+                .iter()
+                .rev()
+                .find(|(_, registered)| registered.deprecated_at.is_none())
+                .map(|(version, registered)| (*version, registered))
+                .ok_or_else(|| CircuitError::Deprecated(format!("{} (every version)", name)))?,
+        };
+        Ok((version, registered.circuit.clone()))
+    }
+
+    /// Get a circuit by name, at the version new proofs use
     pub fn get_circuit(&self, name: &str) -> Result<Option<CompiledCircuit>, CircuitError> {
+        match self.resolve(name, None) {
+            Ok((_, circuit)) => Ok(Some(circuit)),
+            Err(CircuitError::NotFound(_) | CircuitError::Deprecated(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get `version` of a circuit, deprecated or not, as proofs made with
+    /// it are verified against
+    pub fn get_version(
+        &self,
+        name: &str,
+        version: CircuitVersion,
+    ) -> Result<Option<CompiledCircuit>, CircuitError> {
         let circuits = self.circuits.read().map_err(|_| CircuitError::LockPoisoned)?;
-        Ok(circuits.get(name).cloned())
+        Ok(circuits
+            .get(name)
+            .and_then(|versions| versions.get(&version))
+            .map(|registered| registered.circuit.clone()))
     }
 
-    /// Verify with a named circuit
+    /// Verify with a named circuit, at the version new proofs use
     pub fn verify_with_circuit(
         &self,
         name: &str,
         witness: &[f64],
         public_inputs: &[f64],
     ) -> Result<bool, CircuitError> {
-        let circuits = self.circuits.read().map_err(|_| CircuitError::LockPoisoned)?;
+        let (_, circuit) = self.resolve(name, None)?;
+        circuit.verify(witness, public_inputs)
+    }
+
+    /// Deprecate `version` of `name`: new proofs no longer use it, but
+    /// proofs made with it still verify
+    pub fn deprecate(
+        &self,
+        name: &str,
+        version: CircuitVersion,
+        reason: Option<String>,
+    ) -> Result<CircuitInfo, CircuitError> {
+        let mut circuits = self.circuits.write().map_err(|_| CircuitError::LockPoisoned)?;
+        let registered = circuits
+            .get_mut(name)
+            .and_then(|versions| versions.get_mut(&version))
+            .ok_or_else(|| CircuitError::NotFound(format!("{}@{}", name, version)))?;
+        if registered.deprecated_at.is_none() {
+            registered.deprecated_at = Some(chrono::Utc::now().to_rfc3339());
+            registered.deprecation_reason = reason;
+        }
+        Ok(CircuitInfo::new(name, version, registered))
+    }
 
-        let circuit = circuits
+    /// Every registered version of `name`, oldest first
+    pub fn versions(&self, name: &str) -> Result<Vec<CircuitInfo>, CircuitError> {
+        let circuits = self.circuits.read().map_err(|_| CircuitError::LockPoisoned)?;
+        Ok(circuits
             .get(name)
-            .ok_or_else(|| CircuitError::NotFound(name.to_string()))?;
+            .map(|versions| {
+                versions
+                    .iter()
+                    .map(|(version, registered)| CircuitInfo::new(name, *version, registered))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
 
-        circuit.verify(witness, public_inputs)
+    /// Every registered version of every circuit, by name then version
+    pub fn list_versions(&self) -> Result<Vec<CircuitInfo>, CircuitError> {
+        let mut names = self.list_circuits()?;
+        names.sort();
+        let mut infos = Vec::new();
+        for name in names {
+            infos.extend(self.versions(&name)?);
+        }
+        Ok(infos)
     }
 
     /// List all registered circuit names
@@ -201,7 +438,7 @@ pub fn list_circuits(&self) -> Result<Vec<String>, CircuitError> {
         Ok(circuits.keys().cloned().collect())
     }
 
-    /// Remove a circuit
+    /// Remove a circuit, every version of it
     pub fn unregister_circuit(&self, name: &str) -> Result<bool, CircuitError> {
         let mut circuits = self.circuits.write().map_err(|_| CircuitError::LockPoisoned)?;
         Ok(circuits.remove(name).is_some())
@@ -298,6 +535,41 @@ fn test_duplicate_registration() {
         assert!(matches!(result, Err(CircuitError::AlreadyExists(_))));
     }
 
+    #[test]
+    fn test_versions_and_deprecation() {
+        let registry = CircuitRegistry::new();
+        let v1 = CircuitVersion::new(1, 0, 0);
+        let v2: CircuitVersion = "1.1.0".parse().unwrap();
+        registry.register_version("multiply", v1, make_test_circuit(), None).unwrap();
+        let info = registry
+            .register_version("multiply", v2, make_test_circuit(), Some("Faster".to_string()))
+            .unwrap();
+        assert_eq!(info.parameters, vec!["x", "z"]);
+        assert!(matches!(
+            registry.register_version("multiply", v2, make_test_circuit(), None),
+            Err(CircuitError::AlreadyExists(_))
+        ));
+        assert_eq!(registry.resolve("multiply", None).unwrap().0, v2);
+
+        // Deprecated versions are skipped or refused for new proofs, but
+        // stay available to verify old ones
+        registry.deprecate("multiply", v2, Some("Unsound".to_string())).unwrap();
+        assert_eq!(registry.resolve("multiply", None).unwrap().0, v1);
+        assert!(matches!(registry.resolve("multiply", Some(v2)), Err(CircuitError::Deprecated(_))));
+        assert!(registry.get_version("multiply", v2).unwrap().is_some());
+        registry.deprecate("multiply", v1, None).unwrap();
+        assert!(registry.get_circuit("multiply").unwrap().is_none());
+
+        let listed = registry.list_versions().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed[1].deprecated);
+        assert_eq!(listed[1].deprecation_reason.as_deref(), Some("Unsound"));
+        assert_eq!(serde_json::to_value(&listed[1]).unwrap()["version"], "1.1.0");
+
+        assert!(matches!("1.2".parse::<CircuitVersion>(), Err(CircuitError::InvalidVersion(_))));
+        assert!(matches!("1.x.0".parse::<CircuitVersion>(), Err(CircuitError::InvalidVersion(_))));
+    }
+
     #[test]
     fn test_list_and_unregister() {
         let registry = CircuitRegistry::new();
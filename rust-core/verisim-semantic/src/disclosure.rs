@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Selective disclosure over semantic annotations
+//!
+//! An annotation is committed to field by field: each field (the entity,
+//! every type, every property, the provenance metadata) becomes a salted
+//! leaf `H(name || value || salt)` of a Merkle tree, and the root is the
+//! annotation's commitment.  A [`DisclosureProof`] proves a predicate about
+//! the annotation by opening only the leaves the predicate needs:
+//!
+//! - **`confidence_above`** opens the confidence;
+//! - **`subtype_of`** opens one type and gives its path up the ontology to
+//!   the target type.
+//!
+//! The entity leaf is always opened, binding the proof to its entity.  The
+//! other fields stay hidden: the salts, derived from the whole annotation,
+//! stop them being guessed from their hashes.  The commitment is anchored in
+//! the entity's provenance chain by the caller, see [`anchor_source`].
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
+
+use super::zkp::{merkle_proof, merkle_root, verify_merkle_proof, MerkleProof};
+use super::{SemanticAnnotation, SemanticError, SemanticStore};
+
+/// Prefix of the provenance `source` that anchors a commitment
+const ANCHOR_PREFIX: &str = "annotation-commitment:";
+
+/// A predicate about an annotation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "predicate", rename_all = "snake_case")]
+pub enum AnnotationPredicate {
+    /// The annotation's provenance confidence is above `threshold`
+    ConfidenceAbove { threshold: f64 },
+    /// One of the annotation's types is `type_iri` or a subtype of it
+    SubtypeOf { type_iri: String },
+}
+
+/// An opened field of a committed annotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosedField {
+    pub name: String,
+    pub value: String,
+    pub salt: Vec<u8>,
+    /// The field's leaf, included under the commitment
+    pub inclusion: MerkleProof,
+}
+
+/// Proof of a predicate about an annotation that discloses only the fields
+/// the predicate needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosureProof {
+    pub entity_id: String,
+    pub predicate: AnnotationPredicate,
+    /// Merkle root over the annotation's salted fields
+    pub commitment: [u8; 32],
+    /// Opened fields: the entity, then those the predicate needs
+    pub disclosed: Vec<DisclosedField>,
+    /// For `subtype_of`, the disclosed type and its supertypes up to the
+    /// target, each a direct supertype of the one before
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub type_path: Vec<String>,
+    /// `content_hash` of the provenance record anchoring the commitment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// Timestamp of proof generation
+    pub generated_at: String,
+}
+
+/// The committed fields of an annotation, in commitment order
+pub fn annotation_fields(annotation: &SemanticAnnotation) -> Vec<(String, String)> {
+    let mut fields = vec![("entity_id".to_string(), annotation.entity_id.clone())];
+    fields.extend(annotation.types.iter().map(|t| ("type".to_string(), t.clone())));
+    let mut properties: Vec<_> = annotation.properties.iter().collect();
+    properties.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in properties {
+        let value = serde_json::to_string(value).unwrap_or_default();
+        fields.push((format!("property:{}", key), value));
+    }
+    let provenance = &annotation.provenance;
+    fields.push(("confidence".to_string(), provenance.confidence.to_string()));
+    fields.push(("created_by".to_string(), provenance.created_by.clone()));
+    fields.push(("created_at".to_string(), provenance.created_at.clone()));
+    if let Some(source) = &provenance.source {
+        fields.push(("source".to_string(), source.clone()));
+    }
+    fields
+}
+
+fn field_leaf(name: &str, value: &str, salt: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for part in [name.as_bytes(), value.as_bytes(), salt] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Salted leaves of `fields`, with their salts
+fn salted_leaves(fields: &[(String, String)]) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let secret = serde_json::to_vec(fields).unwrap_or_default();
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value))| {
+            let mut hasher = Sha256::new();
+            hasher.update(b"verisimdb-disclosure-salt-v1");
+            hasher.update(&secret);
+            hasher.update((i as u64).to_be_bytes());
+            let salt = hasher.finalize().to_vec();
+            (field_leaf(name, value, &salt), salt)
+        })
+        .unzip()
+}
+
+/// Commitment to an annotation: the Merkle root over its salted fields
+pub fn commit_annotation(annotation: &SemanticAnnotation) -> [u8; 32] {
+    merkle_root(&salted_leaves(&annotation_fields(annotation)).0)
+}
+
+/// Provenance `source` recording `commitment` as anchored
+pub fn anchor_source(commitment: &[u8; 32]) -> String {
+    format!("{}{}", ANCHOR_PREFIX, hex::encode(commitment))
+}
+
+/// Shortest path from `from` up the ontology to `to`, both included
+async fn subtype_path<S: SemanticStore + ?Sized>(
+    store: &S,
+    from: &str,
+    to: &str,
+) -> Result<Option<Vec<String>>, SemanticError> {
+    let mut queue = VecDeque::from([vec![from.to_string()]]);
+    let mut seen = HashSet::from([from.to_string()]);
+    while let Some(path) = queue.pop_front() {
+        let last = &path[path.len() - 1];
+        if last == to {
+            return Ok(Some(path));
+        }
+        let Some(typ) = store.get_type(last).await? else { continue };
+        for supertype in typ.supertypes {
+            if seen.insert(supertype.clone()) {
+                let mut next = path.clone();
+                next.push(supertype);
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Prove `predicate` about `annotation`, disclosing only what it needs.
+///
+/// Fails with [`SemanticError::ConstraintViolation`] if the predicate does
+/// not hold.  The proof carries no anchor; the caller sets it once the
+/// commitment is recorded in the entity's provenance.
+pub async fn prove_annotation<S: SemanticStore + ?Sized>(
+    store: &S,
+    annotation: &SemanticAnnotation,
+    predicate: &AnnotationPredicate,
+) -> Result<DisclosureProof, SemanticError> {
+    let fields = annotation_fields(annotation);
+    let (leaves, salts) = salted_leaves(&fields);
+    let unmet = || SemanticError::ConstraintViolation(format!("{:?} does not hold for {}", predicate, annotation.entity_id));
+
+    let mut type_path = Vec::new();
+    let opened = match predicate {
+        AnnotationPredicate::ConfidenceAbove { threshold } => {
+            if annotation.provenance.confidence <= *threshold {
+                return Err(unmet());
+            }
+            fields.iter().position(|(name, _)| name == "confidence")
+        }
+        AnnotationPredicate::SubtypeOf { type_iri } => {
+            let mut found = None;
+            for (i, (name, value)) in fields.iter().enumerate() {
+                if name != "type" {
+                    continue;
+                }
+                if let Some(path) = subtype_path(store, value, type_iri).await? {
+                    if found.as_ref().is_none_or(|(_, shortest): &(usize, Vec<String>)| path.len() < shortest.len()) {
+                        found = Some((i, path));
+                    }
+                }
+            }
+            found.map(|(i, path)| {
+                type_path = path;
+                i
+            })
+        }
+    }
+    .ok_or_else(unmet)?;
+
+    let disclosed = [0, opened]
+        .into_iter()
+        .map(|i| {
+            let inclusion = merkle_proof(&leaves, i)
+                .ok_or_else(|| SemanticError::InvalidProof(format!("No leaf {} in the commitment", i)))?;
+            Ok(DisclosedField {
+                name: fields[i].0.clone(),
+                value: fields[i].1.clone(),
+                salt: salts[i].clone(),
+                inclusion,
+            })
+        })
+        .collect::<Result<Vec<_>, SemanticError>>()?;
+
+    Ok(DisclosureProof {
+        entity_id: annotation.entity_id.clone(),
+        predicate: predicate.clone(),
+        commitment: merkle_root(&leaves),
+        disclosed,
+        type_path,
+        anchor: None,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Verify a disclosure proof on its own: every opened field is a leaf under
+/// the commitment, the entity field names the proof's entity, and the
+/// opened fields satisfy the predicate.
+///
+/// A `subtype_of` path is only checked to start at the opened type and end
+/// at the target; [`verify_type_path`] checks its steps against an ontology.
+pub fn verify_disclosure(proof: &DisclosureProof) -> bool {
+    let included = proof.disclosed.iter().all(|field| {
+        field.inclusion.root == proof.commitment
+            && field.inclusion.leaf == field_leaf(&field.name, &field.value, &field.salt)
+            && verify_merkle_proof(&field.inclusion)
+    });
+    let opened = |name: &'static str| proof.disclosed.iter().filter(move |f| f.name == name);
+    let bound = opened("entity_id").any(|f| f.value == proof.entity_id);
+    let holds = match &proof.predicate {
+        AnnotationPredicate::ConfidenceAbove { threshold } => opened("confidence")
+            .any(|f| f.value.parse::<f64>().is_ok_and(|confidence| confidence > *threshold)),
+        AnnotationPredicate::SubtypeOf { type_iri } => {
+            proof.type_path.last() == Some(type_iri)
+                && opened("type").any(|f| proof.type_path.first() == Some(&f.value))
+        }
+    };
+    included && bound && holds
+}
+
+/// Whether each type of `path` is a direct supertype, in `store`, of the
+/// one before
+pub async fn verify_type_path<S: SemanticStore + ?Sized>(store: &S, path: &[String]) -> Result<bool, SemanticError> {
+    for step in path.windows(2) {
+        let Some(typ) = store.get_type(&step[0]).await? else { return Ok(false) };
+        if !typ.supertypes.contains(&step[1]) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemorySemanticStore, Provenance, SemanticType, SemanticValue};
+    use std::collections::HashMap;
+
+    fn annotation() -> SemanticAnnotation {
+        SemanticAnnotation {
+            entity_id: "lemma-4".to_string(),
+            types: vec!["https://example.org/Lemma".to_string()],
+            properties: HashMap::from([(
+                "statement".to_string(),
+                SemanticValue::TypedLiteral {
+                    value: "every bounded sequence has a convergent subsequence".to_string(),
+                    datatype: "xsd:string".to_string(),
+                },
+            )]),
+            provenance: Provenance {
+                created_by: "did:example:prover".to_string(),
+                confidence: 0.95,
+                ..Default::default()
+            },
+        }
+    }
+
+    async fn ontology() -> InMemorySemanticStore {
+        let store = InMemorySemanticStore::new();
+        store.register_type(&SemanticType::new("https://example.org/Theorem", "Theorem")).await.unwrap();
+        store
+            .register_type(
+                &SemanticType::new("https://example.org/Lemma", "Lemma").with_supertype("https://example.org/Theorem"),
+            )
+            .await
+            .unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_subtype_disclosure_hides_the_rest() {
+        let store = ontology().await;
+        let annotation = annotation();
+        let predicate = AnnotationPredicate::SubtypeOf { type_iri: "https://example.org/Theorem".to_string() };
+
+        let proof = prove_annotation(&store, &annotation, &predicate).await.unwrap();
+        assert_eq!(proof.commitment, commit_annotation(&annotation));
+        assert!(verify_disclosure(&proof));
+        assert!(verify_type_path(&store, &proof.type_path).await.unwrap());
+        let published = serde_json::to_string(&proof).unwrap();
+        assert!(!published.contains("convergent") && !published.contains("did:example"));
+
+        // Claiming another type breaks the leaf
+        let mut forged = proof.clone();
+        forged.disclosed[1].value = "https://example.org/Axiom".to_string();
+        forged.type_path[0] = "https://example.org/Axiom".to_string();
+        assert!(!verify_disclosure(&forged));
+
+        let unrelated = AnnotationPredicate::SubtypeOf { type_iri: "https://example.org/Axiom".to_string() };
+        assert!(matches!(
+            prove_annotation(&store, &annotation, &unrelated).await,
+            Err(SemanticError::ConstraintViolation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_confidence_disclosure() {
+        let store = InMemorySemanticStore::new();
+        let annotation = annotation();
+
+        let above = AnnotationPredicate::ConfidenceAbove { threshold: 0.9 };
+        let mut proof = prove_annotation(&store, &annotation, &above).await.unwrap();
+        assert!(verify_disclosure(&proof));
+        proof.predicate = AnnotationPredicate::ConfidenceAbove { threshold: 0.99 };
+        assert!(!verify_disclosure(&proof));
+
+        let too_high = AnnotationPredicate::ConfidenceAbove { threshold: 0.95 };
+        assert!(prove_annotation(&store, &annotation, &too_high).await.is_err());
+    }
+}
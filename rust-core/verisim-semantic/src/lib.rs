@@ -11,6 +11,9 @@
 pub mod circuit_registry;
 pub mod circuit_compiler;
 pub mod verification_keys;
+pub mod proof_cache;
+pub mod disclosure;
+pub mod revocation;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -199,6 +202,13 @@ pub fn from_cbor(data: &[u8]) -> Result<Self, SemanticError> {
             .map_err(|e| SemanticError::SerializationError(e.to_string()))
     }
 
+    /// SHA-256 of the CBOR encoding, hex-encoded; identifies the blob among
+    /// the proofs of its claim
+    pub fn hash(&self) -> Result<String, SemanticError> {
+        use sha2::{Digest, Sha256};
+        Ok(hex::encode(Sha256::digest(self.to_cbor()?)))
+    }
+
     /// Verify the proof data against the claim.
     ///
     /// Interprets `self.data` as CBOR-encoded [`zkp::VerifiableProofData`] and
@@ -230,6 +240,46 @@ pub fn verify(&self) -> Result<bool, SemanticError> {
     }
 }
 
+/// A proof attached to the entity its claim is about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityProof {
+    /// Entity the claim is about
+    pub entity_id: String,
+    /// Claim proven; the proof blob is stored under it
+    pub claim: String,
+    /// [`ProofBlob::hash`] of the proof blob
+    pub proof_hash: String,
+    /// Type of proof
+    pub proof_type: ProofType,
+    /// Entity version the proof was generated against
+    pub entity_version: u64,
+    /// When the proof was attached
+    pub linked_at: String,
+    /// When the entity was first seen changed since the proof, if it has been
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_since: Option<String>,
+}
+
+impl EntityProof {
+    /// Link `proof` to version `entity_version` of `entity_id`
+    pub fn new(entity_id: impl Into<String>, entity_version: u64, proof: &ProofBlob) -> Result<Self, SemanticError> {
+        Ok(Self {
+            entity_id: entity_id.into(),
+            claim: proof.claim.clone(),
+            proof_hash: proof.hash()?,
+            proof_type: proof.proof_type.clone(),
+            entity_version,
+            linked_at: chrono::Utc::now().to_rfc3339(),
+            stale_since: None,
+        })
+    }
+
+    /// Whether the entity has changed since the proof was generated
+    pub fn is_stale(&self) -> bool {
+        self.stale_since.is_some()
+    }
+}
+
 /// Semantic store trait for cross-modal consistency
 #[async_trait]
 pub trait SemanticStore: Send + Sync {
@@ -245,6 +295,9 @@ pub trait SemanticStore: Send + Sync {
     /// Get annotations for an entity
     async fn get_annotations(&self, entity_id: &str) -> Result<Option<SemanticAnnotation>, SemanticError>;
 
+    /// Remove an entity's annotations
+    async fn delete_annotations(&self, entity_id: &str) -> Result<(), SemanticError>;
+
     /// Validate an annotation against type constraints
     async fn validate(&self, annotation: &SemanticAnnotation) -> Result<Vec<String>, SemanticError>;
 
@@ -254,6 +307,17 @@ pub trait SemanticStore: Send + Sync {
     /// Retrieve proofs for a claim
     async fn get_proofs(&self, claim: &str) -> Result<Vec<ProofBlob>, SemanticError>;
 
+    /// Attach a proof to an entity, replacing an earlier link to the same
+    /// proof blob
+    async fn link_proof(&self, link: &EntityProof) -> Result<(), SemanticError>;
+
+    /// Proofs attached to an entity, oldest first
+    async fn entity_proofs(&self, entity_id: &str) -> Result<Vec<EntityProof>, SemanticError>;
+
+    /// Mark the entity's proofs generated against a version older than
+    /// `version` stale, returning the ones newly marked
+    async fn mark_proofs_stale(&self, entity_id: &str, version: u64) -> Result<Vec<EntityProof>, SemanticError>;
+
     /// Verify all proofs for a claim, returning (valid_count, total_count).
     async fn verify_proofs(&self, claim: &str) -> Result<(usize, usize), SemanticError> {
         let proofs = self.get_proofs(claim).await?;
@@ -268,6 +332,7 @@ pub struct InMemorySemanticStore {
     types: Arc<RwLock<HashMap<String, SemanticType>>>,
     annotations: Arc<RwLock<HashMap<String, SemanticAnnotation>>>,
     proofs: Arc<RwLock<HashMap<String, Vec<ProofBlob>>>>,
+    entity_proofs: Arc<RwLock<HashMap<String, Vec<EntityProof>>>>,
 }
 
 impl InMemorySemanticStore {
@@ -276,6 +341,7 @@ pub fn new() -> Self {
             types: Arc::new(RwLock::new(HashMap::new())),
             annotations: Arc::new(RwLock::new(HashMap::new())),
             proofs: Arc::new(RwLock::new(HashMap::new())),
+            entity_proofs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -311,6 +377,11 @@ async fn get_annotations(&self, entity_id: &str) -> Result<Option<SemanticAnnota
         Ok(self.annotations.read().map_err(|_| SemanticError::LockPoisoned)?.get(entity_id).cloned())
     }
 
+    async fn delete_annotations(&self, entity_id: &str) -> Result<(), SemanticError> {
+        self.annotations.write().map_err(|_| SemanticError::LockPoisoned)?.remove(entity_id);
+        Ok(())
+    }
+
     async fn validate(&self, annotation: &SemanticAnnotation) -> Result<Vec<String>, SemanticError> {
         let types = self.types.read().map_err(|_| SemanticError::LockPoisoned)?;
         let mut violations = Vec::new();
@@ -354,6 +425,31 @@ async fn store_proof(&self, proof: &ProofBlob) -> Result<(), SemanticError> {
     async fn get_proofs(&self, claim: &str) -> Result<Vec<ProofBlob>, SemanticError> {
         Ok(self.proofs.read().map_err(|_| SemanticError::LockPoisoned)?.get(claim).cloned().unwrap_or_default())
     }
+
+    async fn link_proof(&self, link: &EntityProof) -> Result<(), SemanticError> {
+        let mut entity_proofs = self.entity_proofs.write().map_err(|_| SemanticError::LockPoisoned)?;
+        let links = entity_proofs.entry(link.entity_id.clone()).or_default();
+        links.retain(|existing| existing.proof_hash != link.proof_hash);
+        links.push(link.clone());
+        Ok(())
+    }
+
+    async fn entity_proofs(&self, entity_id: &str) -> Result<Vec<EntityProof>, SemanticError> {
+        Ok(self.entity_proofs.read().map_err(|_| SemanticError::LockPoisoned)?.get(entity_id).cloned().unwrap_or_default())
+    }
+
+    async fn mark_proofs_stale(&self, entity_id: &str, version: u64) -> Result<Vec<EntityProof>, SemanticError> {
+        let mut entity_proofs = self.entity_proofs.write().map_err(|_| SemanticError::LockPoisoned)?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut marked = Vec::new();
+        for link in entity_proofs.get_mut(entity_id).into_iter().flatten() {
+            if link.stale_since.is_none() && link.entity_version < version {
+                link.stale_since = Some(now.clone());
+                marked.push(link.clone());
+            }
+        }
+        Ok(marked)
+    }
 }
 
 #[cfg(test)]
@@ -391,4 +487,20 @@ fn test_proof_blob_cbor() {
 
         assert_eq!(decoded.claim, proof.claim);
     }
+
+    #[tokio::test]
+    async fn test_entity_proofs_go_stale() {
+        let store = InMemorySemanticStore::new();
+        let proof = ProofBlob::new("entity:123 is-a Person", ProofType::TypeAssignment, vec![1, 2, 3]);
+        let link = EntityProof::new("entity:123", 1, &proof).unwrap();
+        store.link_proof(&link).await.unwrap();
+        store.link_proof(&link).await.unwrap();
+        assert_eq!(store.entity_proofs("entity:123").await.unwrap().len(), 1);
+
+        assert!(store.mark_proofs_stale("entity:123", 1).await.unwrap().is_empty());
+        let marked = store.mark_proofs_stale("entity:123", 2).await.unwrap();
+        assert_eq!(marked.len(), 1);
+        assert!(store.mark_proofs_stale("entity:123", 3).await.unwrap().is_empty());
+        assert!(store.entity_proofs("entity:123").await.unwrap()[0].is_stale());
+    }
 }
@@ -0,0 +1,453 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Proof caching
+//!
+//! Proving is the expensive step of a ZKP request, and identical claims are
+//! proven again and again.  A [`ProofCache`] keeps generated proofs so that
+//! repeating a request returns the stored proof instead of proving again.
+//!
+//! - **Keys**: a [`ProofKey`] is the claim hash, the privacy level and the
+//!   circuit name, version and definition hash.  The claim hash covers the
+//!   claim and every other input the proof depends on (membership set and
+//!   index, witness and public inputs).
+//! - **Expiry**: proofs are dropped `ttl_secs` after they were generated.
+//! - **Invalidation**: when a lookup finds that a circuit version's
+//!   definition has changed, every proof made with the old one is dropped;
+//!   [`ProofCache::invalidate_circuit`] drops them on demand, and
+//!   [`ProofCache::generate_unless`] replaces cached proofs the caller
+//!   rejects.
+//! - **Budget**: at most `max_entries` proofs are kept, the oldest evicted
+//!   first.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::circuit_registry::{CircuitError, CircuitRegistry, CircuitVersion};
+use super::zkp_bridge::{generate_zkp_with_circuit, PrivacyLevel, ZkpProof, ZkpProofRequest};
+
+/// Proof cache settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProofCacheConfig {
+    /// Whether generated proofs are cached
+    pub enabled: bool,
+    /// Seconds a proof is reused after it was generated
+    pub ttl_secs: u64,
+    /// Most proofs kept at once
+    pub max_entries: usize,
+}
+
+impl Default for ProofCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 3600,
+            max_entries: 10_000,
+        }
+    }
+}
+
+/// Identifies one cached proof
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ProofKey {
+    claim_hash: String,
+    privacy_level: PrivacyLevel,
+    /// Circuit name, version and the hash of its definition
+    circuit: Option<(String, CircuitVersion, String)>,
+}
+
+impl ProofKey {
+    /// Key for proving `request` with `circuit`, the version of its circuit
+    /// and that version's hash, if it names one
+    pub fn new(request: &ZkpProofRequest, circuit: Option<(CircuitVersion, &str)>) -> Self {
+        let inputs = serde_json::json!([
+            request.membership_set,
+            request.membership_index,
+            request.witness,
+            request.public_inputs,
+        ]);
+        let mut hasher = Sha256::new();
+        hasher.update(&request.claim);
+        hasher.update([0]);
+        hasher.update(inputs.to_string().as_bytes());
+        Self {
+            claim_hash: hex::encode(hasher.finalize()),
+            privacy_level: request.privacy_level,
+            circuit: request.circuit_name.clone().map(|name| {
+                let (version, hash) = circuit.unwrap_or_default();
+                (name, version, hash.to_string())
+            }),
+        }
+    }
+}
+
+/// Proof cache counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofCacheStats {
+    pub enabled: bool,
+    /// Proofs currently cached
+    pub entries: usize,
+    pub max_entries: usize,
+    pub ttl_secs: u64,
+    /// Requests answered with a cached proof
+    pub hit_count: u64,
+    /// Requests that had to be proven
+    pub miss_count: u64,
+    /// `hit_count / (hit_count + miss_count)`, or 0.0 before any request
+    pub hit_ratio: f64,
+    /// Proofs evicted to stay within `max_entries`
+    pub eviction_count: u64,
+    /// Proofs dropped because their circuit changed
+    pub invalidation_count: u64,
+}
+
+struct CachedProof {
+    proof: ZkpProof,
+    generated: Instant,
+}
+
+#[derive(Default)]
+struct Entries {
+    proofs: HashMap<ProofKey, CachedProof>,
+    /// Last seen hash of each circuit version proofs were made with
+    circuit_hashes: HashMap<(String, CircuitVersion), String>,
+}
+
+impl Entries {
+    /// Drop the proofs of circuit `name`, or of one version of it, returning
+    /// how many
+    fn remove_circuit(&mut self, name: &str, version: Option<CircuitVersion>) -> usize {
+        let before = self.proofs.len();
+        self.proofs.retain(|key, _| {
+            key.circuit.as_ref().is_none_or(|(circuit, circuit_version, _)| {
+                circuit != name || version.is_some_and(|version| version != *circuit_version)
+            })
+        });
+        before - self.proofs.len()
+    }
+}
+
+/// Cache of generated proofs
+pub struct ProofCache {
+    config: ProofCacheConfig,
+    entries: Mutex<Entries>,
+    hit_count: AtomicU64,
+    miss_count: AtomicU64,
+    eviction_count: AtomicU64,
+    invalidation_count: AtomicU64,
+}
+
+impl ProofCache {
+    pub fn new(config: ProofCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::default(),
+            hit_count: AtomicU64::new(0),
+            miss_count: AtomicU64::new(0),
+            eviction_count: AtomicU64::new(0),
+            invalidation_count: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.config.ttl_secs)
+    }
+
+    /// Prove `request`, checking its circuit (if any) in `registry`, or
+    /// reuse the cached proof of an identical request.  Returns the proof
+    /// and whether it came from the cache.
+    pub fn generate(
+        &self,
+        request: &ZkpProofRequest,
+        registry: &CircuitRegistry,
+    ) -> Result<(ZkpProof, bool), CircuitError> {
+        self.generate_unless(request, registry, |_| false)
+    }
+
+    /// Like [`generate`](Self::generate), but proves again instead of
+    /// reusing a cached proof `discard` rejects, such as a revoked one
+    pub fn generate_unless(
+        &self,
+        request: &ZkpProofRequest,
+        registry: &CircuitRegistry,
+        discard: impl Fn(&ZkpProof) -> bool,
+    ) -> Result<(ZkpProof, bool), CircuitError> {
+        if !self.config.enabled || self.config.max_entries == 0 {
+            return generate_zkp_with_circuit(request, registry).map(|proof| (proof, false));
+        }
+        let circuit = match &request.circuit_name {
+            Some(name) => {
+                let version = request
+                    .circuit_version
+                    .as_deref()
+                    .map(str::parse)
+                    .transpose()?;
+                let (version, circuit) = registry.resolve(name, version)?;
+                self.observe_hash(name, version, &circuit.circuit_hash);
+                Some((version, circuit.circuit_hash))
+            }
+            None => None,
+        };
+        let key = ProofKey::new(request, circuit.as_ref().map(|(v, hash)| (*v, hash.as_str())));
+        if let Some(proof) = self.lookup(&key, discard) {
+            return Ok((proof, true));
+        }
+        let proof = generate_zkp_with_circuit(request, registry)?;
+        self.insert(key, proof.clone());
+        Ok((proof, false))
+    }
+
+    /// Drop proofs of an earlier definition when `version` of circuit
+    /// `name` has hash `hash`
+    fn observe_hash(&self, name: &str, version: CircuitVersion, hash: &str) {
+        let mut entries = self.lock();
+        let previous = entries
+            .circuit_hashes
+            .insert((name.to_string(), version), hash.to_string());
+        if previous.is_some_and(|previous| previous != hash) {
+            let removed = entries.remove_circuit(name, Some(version));
+            self.invalidation_count.fetch_add(removed as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// The cached proof for `key`, if it has not expired
+    pub fn get(&self, key: &ProofKey) -> Option<ZkpProof> {
+        self.lookup(key, |_| false)
+    }
+
+    /// The cached proof for `key`, if it has not expired and `discard`
+    /// does not reject it
+    fn lookup(&self, key: &ProofKey, discard: impl Fn(&ZkpProof) -> bool) -> Option<ZkpProof> {
+        let mut entries = self.lock();
+        let proof = match entries.proofs.get(key) {
+            Some(cached) if cached.generated.elapsed() < self.ttl() && !discard(&cached.proof) => {
+                Some(cached.proof.clone())
+            }
+            Some(_) => {
+                entries.proofs.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if proof.is_some() { &self.hit_count } else { &self.miss_count };
+        counter.fetch_add(1, Ordering::Relaxed);
+        proof
+    }
+
+    /// Cache `proof` under `key`, evicting the oldest proofs when full
+    pub fn insert(&self, key: ProofKey, proof: ZkpProof) {
+        if !self.config.enabled || self.config.max_entries == 0 {
+            return;
+        }
+        let ttl = self.ttl();
+        let mut entries = self.lock();
+        if entries.proofs.len() >= self.config.max_entries && !entries.proofs.contains_key(&key) {
+            entries.proofs.retain(|_, cached| cached.generated.elapsed() < ttl);
+        }
+        while entries.proofs.len() >= self.config.max_entries && !entries.proofs.contains_key(&key) {
+            let Some(oldest) = entries
+                .proofs
+                .iter()
+                .min_by_key(|(_, cached)| cached.generated)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.proofs.remove(&oldest);
+            self.eviction_count.fetch_add(1, Ordering::Relaxed);
+        }
+        entries.proofs.insert(
+            key,
+            CachedProof {
+                proof,
+                generated: Instant::now(),
+            },
+        );
+    }
+
+    /// Drop every proof made with circuit `name`, returning how many
+    pub fn invalidate_circuit(&self, name: &str) -> usize {
+        let mut entries = self.lock();
+        entries.circuit_hashes.retain(|(circuit, _), _| circuit != name);
+        let removed = entries.remove_circuit(name, None);
+        self.invalidation_count.fetch_add(removed as u64, Ordering::Relaxed);
+        removed
+    }
+
+    pub fn stats(&self) -> ProofCacheStats {
+        let hit_count = self.hit_count.load(Ordering::Relaxed);
+        let miss_count = self.miss_count.load(Ordering::Relaxed);
+        let lookups = hit_count + miss_count;
+        ProofCacheStats {
+            enabled: self.config.enabled,
+            entries: self.lock().proofs.len(),
+            max_entries: self.config.max_entries,
+            ttl_secs: self.config.ttl_secs,
+            hit_count,
+            miss_count,
+            hit_ratio: if lookups == 0 { 0.0 } else { hit_count as f64 / lookups as f64 },
+            eviction_count: self.eviction_count.load(Ordering::Relaxed),
+            invalidation_count: self.invalidation_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for ProofCache {
+    fn default() -> Self {
+        Self::new(ProofCacheConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_registry::{sha256_hex, CircuitIR, CompiledCircuit, R1CSConstraint};
+
+    fn request(claim: &str) -> ZkpProofRequest {
+        ZkpProofRequest {
+            claim: claim.as_bytes().to_vec(),
+            privacy_level: PrivacyLevel::Private,
+            circuit_name: None,
+            circuit_version: None,
+            witness: None,
+            public_inputs: None,
+            membership_set: None,
+            membership_index: None,
+        }
+    }
+
+    /// `x * y = z`, with `extra` constraints that always hold so
+    /// definitions can differ
+    fn multiply(extra: usize) -> CompiledCircuit {
+        let constraint = R1CSConstraint {
+            a: HashMap::from([(0, 1.0)]),
+            b: HashMap::from([(2, 1.0)]),
+            c: HashMap::from([(1, 1.0)]),
+        };
+        let ir = CircuitIR {
+            name: "multiply".to_string(),
+            num_public_inputs: 2,
+            num_witness_wires: 1,
+            num_wires: 3,
+            constraints: vec![constraint; 1 + extra],
+            parameter_map: HashMap::new(),
+        };
+        CompiledCircuit {
+            circuit_hash: sha256_hex(&serde_json::to_vec(&ir).unwrap()),
+            ir,
+            verification_key: vec![0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_identical_requests_reuse_the_proof() {
+        let cache = ProofCache::default();
+        let registry = CircuitRegistry::new();
+
+        let (first, cached) = cache.generate(&request("age >= 18"), &registry).unwrap();
+        assert!(!cached);
+        let (again, cached) = cache.generate(&request("age >= 18"), &registry).unwrap();
+        assert!(cached);
+        assert_eq!(again.generated_at, first.generated_at);
+
+        // Another claim, privacy level or membership set is another proof
+        let (_, cached) = cache.generate(&request("age >= 21"), &registry).unwrap();
+        assert!(!cached);
+        let public = ZkpProofRequest {
+            privacy_level: PrivacyLevel::Public,
+            ..request("age >= 18")
+        };
+        assert!(!cache.generate(&public, &registry).unwrap().1);
+        let member = ZkpProofRequest {
+            membership_set: Some(vec![b"age >= 18".to_vec(), b"other".to_vec()]),
+            membership_index: Some(0),
+            ..request("age >= 18")
+        };
+        assert!(!cache.generate(&member, &registry).unwrap().1);
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hit_count, stats.miss_count), (4, 1, 4));
+    }
+
+    #[test]
+    fn test_updated_circuit_invalidates_its_proofs() {
+        let cache = ProofCache::default();
+        let registry = CircuitRegistry::new();
+        registry.register_circuit("multiply", multiply(0)).unwrap();
+        let circuit_request = ZkpProofRequest {
+            circuit_name: Some("multiply".to_string()),
+            witness: Some(vec![4.0]),
+            public_inputs: Some(vec![3.0, 12.0]),
+            ..request("3 * y = 12")
+        };
+
+        let (proof, _) = cache.generate(&circuit_request, &registry).unwrap();
+        assert!(proof.circuit_result.unwrap().satisfied);
+        cache.generate(&request("unrelated"), &registry).unwrap();
+        assert!(cache.generate(&circuit_request, &registry).unwrap().1);
+
+        registry.unregister_circuit("multiply").unwrap();
+        registry.register_circuit("multiply", multiply(1)).unwrap();
+        let (proof, cached) = cache.generate(&circuit_request, &registry).unwrap();
+        assert!(!cached);
+        assert_eq!(proof.circuit_result.unwrap().constraints_checked, 2);
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.invalidation_count), (2, 1));
+
+        assert_eq!(cache.invalidate_circuit("multiply"), 1);
+        assert!(!cache.generate(&circuit_request, &registry).unwrap().1);
+
+        registry.unregister_circuit("multiply").unwrap();
+        assert!(matches!(
+            cache.generate(&circuit_request, &registry),
+            Err(CircuitError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_expiry_and_eviction() {
+        let registry = CircuitRegistry::new();
+        let expired = ProofCache::new(ProofCacheConfig {
+            ttl_secs: 0,
+            ..Default::default()
+        });
+        expired.generate(&request("a"), &registry).unwrap();
+        assert!(!expired.generate(&request("a"), &registry).unwrap().1);
+
+        let small = ProofCache::new(ProofCacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        for claim in ["a", "b", "c"] {
+            small.generate(&request(claim), &registry).unwrap();
+        }
+        assert_eq!((small.stats().entries, small.stats().eviction_count), (2, 1));
+        assert!(!small.generate(&request("a"), &registry).unwrap().1);
+        assert!(small.generate(&request("c"), &registry).unwrap().1);
+    }
+
+    #[test]
+    fn test_discarded_proofs_are_proven_again() {
+        let cache = ProofCache::default();
+        let registry = CircuitRegistry::new();
+        let (first, _) = cache.generate(&request("a"), &registry).unwrap();
+
+        let revoked = |proof: &ZkpProof| proof.generated_at == first.generated_at;
+        std::thread::sleep(Duration::from_millis(2));
+        let (second, cached) = cache.generate_unless(&request("a"), &registry, revoked).unwrap();
+        assert!(!cached);
+        assert_ne!(second.generated_at, first.generated_at);
+        // The new proof replaced the discarded one
+        let (again, cached) = cache.generate_unless(&request("a"), &registry, revoked).unwrap();
+        assert!(cached);
+        assert_eq!(again.generated_at, second.generated_at);
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
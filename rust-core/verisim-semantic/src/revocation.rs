@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Proof expiry and revocation
+//!
+//! A proof about a mutable entity says nothing once the entity changes.  A
+//! [`ProofRegistry`] remembers the proofs it issued, keyed by
+//! [`ZkpProof::fingerprint`]:
+//!
+//! - **Expiry**: when each proof expires.  A proof registered more than once
+//!   keeps the earliest expiry, so removing `expires_at` from a proof does
+//!   not extend it.
+//! - **Revocation**: which entity each proof is about and which of its
+//!   modalities the proof depends on.  [`ProofRegistry::revoke_for_change`]
+//!   revokes the proofs that depend on a modality that changed.
+//!
+//! [`ProofRegistry::status`] tells a verifier whether a proof still stands.
+//!
+//! [`ZkpProof::fingerprint`]: super::zkp_bridge::ZkpProof::fingerprint
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A proof the registry issued
+#[derive(Debug, Clone)]
+struct IssuedProof {
+    entity_id: Option<String>,
+    /// Modalities the proof depends on; empty for all of them
+    depends_on: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// A revoked proof
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revocation {
+    pub proof_id: String,
+    /// Entity the proof was about, if any
+    pub entity_id: Option<String>,
+    pub reason: String,
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// Whether a proof still stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProofStatus {
+    /// Neither expired nor revoked (or never registered here)
+    Valid,
+    Expired { expired_at: DateTime<Utc> },
+    Revoked { reason: String, revoked_at: DateTime<Utc> },
+}
+
+#[derive(Default)]
+struct Entries {
+    issued: HashMap<String, IssuedProof>,
+    revoked: HashMap<String, Revocation>,
+}
+
+/// Registry of issued and revoked proofs
+#[derive(Default)]
+pub struct ProofRegistry {
+    entries: Mutex<Entries>,
+}
+
+impl ProofRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Record that proof `proof_id` was issued, about `entity_id` and
+    /// depending on its `depends_on` modalities (all if empty), expiring at
+    /// `expires_at`
+    pub fn issue(
+        &self,
+        proof_id: &str,
+        entity_id: Option<&str>,
+        depends_on: &[&str],
+        expires_at: Option<DateTime<Utc>>,
+    ) {
+        let mut entries = self.lock();
+        let issued = entries
+            .issued
+            .entry(proof_id.to_string())
+            .or_insert_with(|| IssuedProof {
+                entity_id: None,
+                depends_on: Vec::new(),
+                expires_at,
+            });
+        issued.expires_at = match (issued.expires_at, expires_at) {
+            (Some(earlier), Some(later)) => Some(earlier.min(later)),
+            (earlier, later) => earlier.or(later),
+        };
+        let Some(entity_id) = entity_id else {
+            return;
+        };
+        if issued.entity_id.as_deref() != Some(entity_id) {
+            issued.entity_id = Some(entity_id.to_string());
+            issued.depends_on = depends_on.iter().map(|m| m.to_string()).collect();
+        } else if depends_on.is_empty() {
+            issued.depends_on.clear();
+        } else if !issued.depends_on.is_empty() {
+            // Issued again with other dependencies: any of them revokes it
+            for modality in depends_on {
+                if !issued.depends_on.iter().any(|m| m == modality) {
+                    issued.depends_on.push(modality.to_string());
+                }
+            }
+        }
+    }
+
+    /// Revoke proof `proof_id`.  Revoking it again keeps the first
+    /// revocation.
+    pub fn revoke(&self, proof_id: &str, reason: &str) -> Revocation {
+        let mut entries = self.lock();
+        let entity_id = entries
+            .issued
+            .get(proof_id)
+            .and_then(|issued| issued.entity_id.clone());
+        entries
+            .revoked
+            .entry(proof_id.to_string())
+            .or_insert_with(|| Revocation {
+                proof_id: proof_id.to_string(),
+                entity_id,
+                reason: reason.to_string(),
+                revoked_at: Utc::now(),
+            })
+            .clone()
+    }
+
+    /// Revoke every proof about `entity_id` that depends on one of the
+    /// `changed` modalities, returning the new revocations
+    pub fn revoke_for_change(&self, entity_id: &str, changed: &[&str]) -> Vec<Revocation> {
+        self.revoke_where(entity_id, |proof| {
+            let affected: Vec<&str> = changed
+                .iter()
+                .copied()
+                .filter(|modality| {
+                    proof.depends_on.is_empty() || proof.depends_on.iter().any(|m| m == modality)
+                })
+                .collect();
+            (!affected.is_empty()).then(|| format!("{} changed", affected.join(", ")))
+        })
+    }
+
+    /// Revoke every proof about `entity_id`, returning the new revocations
+    pub fn revoke_entity(&self, entity_id: &str, reason: &str) -> Vec<Revocation> {
+        self.revoke_where(entity_id, |_| Some(reason.to_string()))
+    }
+
+    /// Revoke the proofs about `entity_id` not yet revoked that `reason`
+    /// gives a reason to
+    fn revoke_where(
+        &self,
+        entity_id: &str,
+        reason: impl Fn(&IssuedProof) -> Option<String>,
+    ) -> Vec<Revocation> {
+        let mut entries = self.lock();
+        let Entries { issued, revoked } = &mut *entries;
+        let now = Utc::now();
+        let mut revocations = Vec::new();
+        for (proof_id, proof) in issued.iter() {
+            if proof.entity_id.as_deref() != Some(entity_id) || revoked.contains_key(proof_id) {
+                continue;
+            }
+            let Some(reason) = reason(proof) else {
+                continue;
+            };
+            let revocation = Revocation {
+                proof_id: proof_id.clone(),
+                entity_id: Some(entity_id.to_string()),
+                reason,
+                revoked_at: now,
+            };
+            revoked.insert(proof_id.clone(), revocation.clone());
+            revocations.push(revocation);
+        }
+        revocations.sort_by(|a, b| a.proof_id.cmp(&b.proof_id));
+        revocations
+    }
+
+    /// Whether proof `proof_id`, which says it expires at `expires_at`,
+    /// still stands at `now`.  Revocation wins over expiry.
+    pub fn status(
+        &self,
+        proof_id: &str,
+        expires_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> ProofStatus {
+        let entries = self.lock();
+        if let Some(revocation) = entries.revoked.get(proof_id) {
+            return ProofStatus::Revoked {
+                reason: revocation.reason.clone(),
+                revoked_at: revocation.revoked_at,
+            };
+        }
+        let registered = entries.issued.get(proof_id).and_then(|issued| issued.expires_at);
+        let expiry = match (registered, expires_at) {
+            (Some(registered), Some(claimed)) => Some(registered.min(claimed)),
+            (registered, claimed) => registered.or(claimed),
+        };
+        match expiry {
+            Some(expired_at) if expired_at <= now => ProofStatus::Expired { expired_at },
+            _ => ProofStatus::Valid,
+        }
+    }
+
+    /// Revoked proofs, most recent first, optionally only those about
+    /// `entity_id`
+    pub fn revocations(&self, entity_id: Option<&str>) -> Vec<Revocation> {
+        let mut revocations: Vec<Revocation> = self
+            .lock()
+            .revoked
+            .values()
+            .filter(|revocation| {
+                entity_id.is_none_or(|id| revocation.entity_id.as_deref() == Some(id))
+            })
+            .cloned()
+            .collect();
+        revocations.sort_by(|a, b| {
+            b.revoked_at.cmp(&a.revoked_at).then_with(|| a.proof_id.cmp(&b.proof_id))
+        });
+        revocations
+    }
+
+    /// Forget issued proofs that expired before `now`, returning how many.
+    /// Revocations are kept.
+    pub fn purge_expired(&self, now: DateTime<Utc>) -> usize {
+        let mut entries = self.lock();
+        let before = entries.issued.len();
+        entries
+            .issued
+            .retain(|_, issued| issued.expires_at.is_none_or(|expiry| expiry > now));
+        before - entries.issued.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_expiry_keeps_the_earliest() {
+        let registry = ProofRegistry::new();
+        let now = Utc::now();
+        let soon = now + Duration::seconds(60);
+        registry.issue("p1", None, &[], Some(soon));
+        registry.issue("p1", None, &[], None);
+        registry.issue("p1", None, &[], Some(now + Duration::hours(1)));
+
+        assert_eq!(registry.status("p1", None, now), ProofStatus::Valid);
+        // Dropping the proof's own expiry does not help
+        let later = soon + Duration::seconds(1);
+        assert_eq!(
+            registry.status("p1", None, later),
+            ProofStatus::Expired { expired_at: soon }
+        );
+        // A proof issued elsewhere expires by its own claim
+        assert_eq!(
+            registry.status("unknown", Some(soon), later),
+            ProofStatus::Expired { expired_at: soon }
+        );
+        assert_eq!(registry.status("unknown", None, later), ProofStatus::Valid);
+
+        assert_eq!(registry.purge_expired(later), 1);
+        assert_eq!(registry.purge_expired(later), 0);
+    }
+
+    #[test]
+    fn test_change_revokes_dependent_proofs() {
+        let registry = ProofRegistry::new();
+        registry.issue("doc", Some("e1"), &["document"], None);
+        registry.issue("any", Some("e1"), &[], None);
+        registry.issue("vec", Some("e1"), &["vector", "graph"], None);
+        registry.issue("other", Some("e2"), &[], None);
+
+        let revoked = registry.revoke_for_change("e1", &["document", "temporal"]);
+        let ids: Vec<&str> = revoked.iter().map(|r| r.proof_id.as_str()).collect();
+        assert_eq!(ids, ["any", "doc"]);
+        assert_eq!(revoked[0].reason, "document, temporal changed");
+        assert_eq!(revoked[1].reason, "document changed");
+
+        let now = Utc::now();
+        assert!(matches!(registry.status("doc", None, now), ProofStatus::Revoked { .. }));
+        assert_eq!(registry.status("vec", None, now), ProofStatus::Valid);
+        assert_eq!(registry.status("other", None, now), ProofStatus::Valid);
+
+        // Already revoked proofs are not revoked again
+        let revoked = registry.revoke_for_change("e1", &["graph", "document"]);
+        let ids: Vec<&str> = revoked.iter().map(|r| r.proof_id.as_str()).collect();
+        assert_eq!(ids, ["vec"]);
+        assert_eq!(registry.revocations(Some("e1")).len(), 3);
+        assert!(registry.revocations(Some("e2")).is_empty());
+
+        let revoked = registry.revoke_entity("e2", "entity deleted");
+        assert_eq!(revoked[0].reason, "entity deleted");
+        assert!(registry.revoke_entity("e2", "entity deleted").is_empty());
+    }
+
+    #[test]
+    fn test_manual_revocation_keeps_the_first() {
+        let registry = ProofRegistry::new();
+        registry.issue("p1", Some("e1"), &[], Some(Utc::now() - Duration::seconds(1)));
+        let first = registry.revoke("p1", "key compromised");
+        let again = registry.revoke("p1", "duplicate");
+        assert_eq!(again, first);
+        assert_eq!(first.entity_id.as_deref(), Some("e1"));
+
+        // Revocation wins over expiry
+        assert_eq!(
+            registry.status("p1", None, Utc::now()),
+            ProofStatus::Revoked {
+                reason: "key compromised".to_string(),
+                revoked_at: first.revoked_at,
+            }
+        );
+        assert_eq!(registry.revocations(None), vec![first]);
+    }
+}
@@ -9,6 +9,10 @@
 //! - **ZeroKnowledge**: Blinded Merkle proof with committed witnesses.
 //!   (Full ZK-SNARK via sanctify is designed but not yet compiled in.)
 //!
+//! Provenance proofs ([`generate_provenance_proof`]) attest that an entity's
+//! provenance chain is intact and holds an event of a given type by an
+//! authorized actor, revealing neither actors nor descriptions.
+//!
 //! # Architecture
 //!
 //! ```text
@@ -30,14 +34,15 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::circuit_registry::{CircuitError, CircuitRegistry};
+use super::circuit_registry::{CircuitError, CircuitRegistry, CircuitVersion};
+use super::{ProofBlob, ProofType, SemanticError};
 use super::zkp::{
     commit, hash, merkle_proof, merkle_root, verify_merkle_proof,
-    verify_proof, VerifiableProofData,
+    verify_proof, MerkleProof, VerifiableProofData,
 };
 
 /// Privacy level for proof generation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PrivacyLevel {
     /// Data and proof are both visible to the verifier.
     Public,
@@ -75,6 +80,9 @@ pub struct ZkpProofRequest {
     pub privacy_level: PrivacyLevel,
     /// Optional circuit name for CUSTOM proofs
     pub circuit_name: Option<String>,
+    /// Circuit version (`MAJOR.MINOR.PATCH`); the latest active one if unset
+    #[serde(default)]
+    pub circuit_version: Option<String>,
     /// Optional witness data (private inputs for circuit proofs)
     pub witness: Option<Vec<f64>>,
     /// Optional public inputs (for circuit proofs)
@@ -102,6 +110,69 @@ pub struct ZkpProof {
     pub circuit_result: Option<CircuitVerificationResult>,
     /// Timestamp of proof generation
     pub generated_at: String,
+    /// When the proof stops verifying (RFC 3339), if it expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+impl ZkpProof {
+    /// Package the proof of `claim` as a [`ProofBlob`] for the semantic
+    /// store: a constraint satisfaction proof if it was checked against a
+    /// circuit, an attestation otherwise
+    pub fn to_blob(&self, claim: &str) -> Result<ProofBlob, SemanticError> {
+        let mut data = Vec::new();
+        ciborium::into_writer(&self.proof_data, &mut data)
+            .map_err(|e| SemanticError::SerializationError(e.to_string()))?;
+        let proof_type = if self.circuit_result.is_some() {
+            ProofType::ConstraintSatisfaction
+        } else {
+            ProofType::Attestation
+        };
+        Ok(ProofBlob {
+            claim: claim.to_string(),
+            proof_type,
+            data,
+            timestamp: self.generated_at.clone(),
+        })
+    }
+
+    /// Identifies the proof in a [`ProofRegistry`]: a hash of everything but
+    /// its expiry, so that dropping `expires_at` does not make it a new proof
+    ///
+    /// [`ProofRegistry`]: super::revocation::ProofRegistry
+    pub fn fingerprint(&self) -> String {
+        let unexpiring = Self {
+            expires_at: None,
+            ..self.clone()
+        };
+        let bytes = serde_json::to_vec(&unexpiring).unwrap_or_default();
+        use sha2::Digest;
+        hex::encode(sha2::Sha256::digest(bytes))
+    }
+
+    /// The proof, expiring `ttl_secs` after it was generated
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        let generated = chrono::DateTime::parse_from_rfc3339(&self.generated_at)
+            .map(|at| at.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now());
+        let ttl = chrono::Duration::seconds(i64::try_from(ttl_secs).unwrap_or(i64::MAX));
+        let expires = generated.checked_add_signed(ttl).unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC);
+        self.expires_at = Some(expires.to_rfc3339());
+        self
+    }
+
+    /// When the proof expires; an unparseable `expires_at` has already
+    pub fn expiry(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.expires_at.as_ref().map(|at| {
+            chrono::DateTime::parse_from_rfc3339(at)
+                .map(|at| at.with_timezone(&chrono::Utc))
+                .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC)
+        })
+    }
+
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expiry().is_some_and(|expiry| expiry <= now)
+    }
 }
 
 /// Result of circuit-based verification
@@ -109,6 +180,12 @@ pub struct ZkpProof {
 pub struct CircuitVerificationResult {
     /// Circuit name
     pub circuit_name: String,
+    /// Version of the circuit the proof was checked against
+    #[serde(default)]
+    pub circuit_version: Option<CircuitVersion>,
+    /// Hash of that version's compiled definition
+    #[serde(default)]
+    pub circuit_hash: Option<String>,
     /// Whether the circuit constraints were satisfied
     pub satisfied: bool,
     /// Number of constraints checked
@@ -156,23 +233,57 @@ pub fn generate_zkp_with_circuit(
         let witness = request.witness.as_deref().unwrap_or(&[]);
         let public_inputs = request.public_inputs.as_deref().unwrap_or(&[]);
 
-        let satisfied = registry.verify_with_circuit(circuit_name, witness, public_inputs)?;
-
-        let circuit = registry.get_circuit(circuit_name)?;
-        let constraints_checked = circuit
-            .map(|c| c.ir.constraints.len())
-            .unwrap_or(0);
+        let version = request
+            .circuit_version
+            .as_deref()
+            .map(str::parse)
+            .transpose()?;
+        let (version, circuit) = registry.resolve(circuit_name, version)?;
+        let satisfied = circuit.verify(witness, public_inputs)?;
 
         proof.circuit_result = Some(CircuitVerificationResult {
             circuit_name: circuit_name.clone(),
+            circuit_version: Some(version),
+            circuit_hash: Some(circuit.circuit_hash),
             satisfied,
-            constraints_checked,
+            constraints_checked: circuit.ir.constraints.len(),
         });
     }
 
     Ok(proof)
 }
 
+/// Verify a proof, and that the circuit version it was checked against is
+/// still registered unchanged.
+///
+/// Deprecated versions still verify: deprecation stops new proofs, not old
+/// ones.
+pub fn verify_zkp_with_circuit(
+    proof: &ZkpProof,
+    claim: &[u8],
+    registry: &CircuitRegistry,
+) -> Result<bool, CircuitError> {
+    if !verify_zkp(proof, claim) {
+        return Ok(false);
+    }
+    let Some(ref result) = proof.circuit_result else {
+        return Ok(true);
+    };
+    // Proofs from before circuits were versioned name no version
+    let circuit = match result.circuit_version {
+        Some(version) => registry.get_version(&result.circuit_name, version)?,
+        None => registry.get_circuit(&result.circuit_name)?,
+    };
+    let Some(circuit) = circuit else {
+        return Err(CircuitError::NotFound(result.circuit_name.clone()));
+    };
+    let unchanged = result
+        .circuit_hash
+        .as_ref()
+        .is_none_or(|hash| *hash == circuit.circuit_hash);
+    Ok(unchanged && result.satisfied)
+}
+
 // ---------------------------------------------------------------------------
 // Public proof: standard proof with data visible
 // ---------------------------------------------------------------------------
@@ -190,6 +301,7 @@ fn generate_public_proof(request: &ZkpProofRequest) -> Result<ZkpProof, CircuitE
         merkle_root: None,
         circuit_result: None,
         generated_at: chrono::Utc::now().to_rfc3339(),
+        expires_at: None,
     })
 }
 
@@ -237,6 +349,7 @@ fn generate_private_proof(request: &ZkpProofRequest) -> Result<ZkpProof, Circuit
         merkle_root: root,
         circuit_result: None,
         generated_at: chrono::Utc::now().to_rfc3339(),
+        expires_at: None,
     })
 }
 
@@ -296,9 +409,170 @@ fn generate_zk_proof(request: &ZkpProofRequest) -> Result<ZkpProof, CircuitError
         merkle_root: root,
         circuit_result: None,
         generated_at: chrono::Utc::now().to_rfc3339(),
+        expires_at: None,
     })
 }
 
+// ---------------------------------------------------------------------------
+// Provenance proof: chain validity and an authorized event, actors hidden
+// ---------------------------------------------------------------------------
+
+/// One record of a provenance chain, as a provenance proof sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceRecordWitness {
+    /// Event type, as the provenance store displays it (`created`,
+    /// `custom:<name>`, ...)
+    pub event_type: String,
+    /// Who caused the event
+    pub actor: String,
+    /// `content_hash` of the previous record
+    pub parent_hash: String,
+    /// SHA-256 hex digest of the record
+    pub content_hash: String,
+}
+
+/// A request to prove that an entity's provenance chain is valid and
+/// contains an event of `event_type` by one of `authorized_actors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceProofRequest {
+    pub entity_id: String,
+    pub event_type: String,
+    pub authorized_actors: Vec<String>,
+    /// The entity's chain, oldest first; never part of the proof
+    pub records: Vec<ProvenanceRecordWitness>,
+}
+
+/// Zero-knowledge proof of a provenance claim.
+///
+/// Reveals the entity, the event type and the chain's record hashes'
+/// Merkle root, but no actor or description: the matching record appears
+/// only as its content hash, and its actor only blinded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceProof {
+    pub entity_id: String,
+    pub event_type: String,
+    /// Records in the chain
+    pub chain_length: usize,
+    /// Merkle root over the content hashes of the chain's records
+    pub chain_root: [u8; 32],
+    /// The matching record's content hash, included under `chain_root`
+    pub record_inclusion: MerkleProof,
+    /// The matching record's blinded actor, included in the blinded set of
+    /// authorized actors
+    pub actor_inclusion: MerkleProof,
+    /// Binds the entity, event type, record and blinded actor together
+    pub binding: [u8; 32],
+    /// Timestamp of proof generation
+    pub generated_at: String,
+}
+
+/// Content hash of the record before the first: SHA-256 of ""
+fn provenance_genesis_hash() -> String {
+    hex::encode(hash(b""))
+}
+
+fn provenance_binding(entity_id: &str, event_type: &str, record: &[u8], blinded_actor: &[u8]) -> [u8; 32] {
+    let mut hasher = sha2::Sha256::new();
+    use sha2::Digest;
+    for part in [entity_id.as_bytes(), event_type.as_bytes(), record, blinded_actor] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Prove that the chain in `request` is linked end to end and holds an
+/// event of the requested type by an authorized actor.
+///
+/// The latest matching event is used.  The caller is responsible for
+/// checking each record's content hash against its content, which this
+/// proof does not see.
+pub fn generate_provenance_proof(request: &ProvenanceProofRequest) -> Result<ProvenanceProof, CircuitError> {
+    let mut expected_parent = provenance_genesis_hash();
+    for (i, record) in request.records.iter().enumerate() {
+        if record.parent_hash != expected_parent {
+            return Err(CircuitError::VerificationFailed(format!(
+                "Provenance chain of {} is broken at record {}",
+                request.entity_id, i
+            )));
+        }
+        expected_parent = record.content_hash.clone();
+    }
+
+    let mut authorized = request.authorized_actors.clone();
+    authorized.sort();
+    authorized.dedup();
+    let (index, record) = request
+        .records
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, r)| r.event_type == request.event_type && authorized.binary_search(&r.actor).is_ok())
+        .ok_or_else(|| {
+            CircuitError::InvalidWitness(format!(
+                "No {} event by an authorized actor in the provenance of {}",
+                request.event_type, request.entity_id
+            ))
+        })?;
+
+    // The nonce blinds actors, so it is derived from what the proof keeps
+    // private: the actors themselves, and the time of proving
+    let generated_at = chrono::Utc::now().to_rfc3339();
+    let mut secret = generated_at.clone().into_bytes();
+    for r in &request.records {
+        secret.extend_from_slice(r.actor.as_bytes());
+        secret.push(0);
+    }
+    let nonce = generate_nonce(&secret);
+
+    let record_hashes: Vec<Vec<u8>> = request
+        .records
+        .iter()
+        .map(|r| r.content_hash.clone().into_bytes())
+        .collect();
+    let blinded_actors: Vec<Vec<u8>> = authorized
+        .iter()
+        .map(|actor| commit(actor.as_bytes(), &nonce).commitment.to_vec())
+        .collect();
+    let actor_index = authorized.binary_search(&record.actor).unwrap_or_default();
+    let (Some(record_inclusion), Some(actor_inclusion)) =
+        (merkle_proof(&record_hashes, index), merkle_proof(&blinded_actors, actor_index))
+    else {
+        return Err(CircuitError::InvalidWitness("Empty provenance chain".to_string()));
+    };
+
+    Ok(ProvenanceProof {
+        entity_id: request.entity_id.clone(),
+        event_type: request.event_type.clone(),
+        chain_length: request.records.len(),
+        chain_root: merkle_root(&record_hashes),
+        binding: provenance_binding(
+            &request.entity_id,
+            &request.event_type,
+            &record_inclusion.leaf,
+            &actor_inclusion.leaf,
+        ),
+        record_inclusion,
+        actor_inclusion,
+        generated_at,
+    })
+}
+
+/// Verify a provenance proof without access to the chain: both inclusion
+/// paths lead to their roots, and the binding covers exactly this entity,
+/// event type, record and blinded actor.
+pub fn verify_provenance_proof(proof: &ProvenanceProof) -> bool {
+    proof.record_inclusion.root == proof.chain_root
+        && verify_merkle_proof(&proof.record_inclusion)
+        && verify_merkle_proof(&proof.actor_inclusion)
+        && provenance_binding(
+            &proof.entity_id,
+            &proof.event_type,
+            &proof.record_inclusion.leaf,
+            &proof.actor_inclusion.leaf,
+        ) == proof.binding
+}
+
 // ---------------------------------------------------------------------------
 // Verification helpers
 // ---------------------------------------------------------------------------
@@ -375,6 +649,7 @@ fn test_public_proof_roundtrip() {
             claim: claim.to_vec(),
             privacy_level: PrivacyLevel::Public,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: None,
@@ -395,6 +670,7 @@ fn test_public_proof_rejects_wrong_claim() {
             claim: claim.to_vec(),
             privacy_level: PrivacyLevel::Public,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: None,
@@ -413,6 +689,7 @@ fn test_private_proof_commitment() {
             claim: claim.to_vec(),
             privacy_level: PrivacyLevel::Private,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: None,
@@ -434,6 +711,7 @@ fn test_private_proof_wrong_claim_fails() {
             claim: claim.to_vec(),
             privacy_level: PrivacyLevel::Private,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: None,
@@ -457,6 +735,7 @@ fn test_private_proof_with_membership_set() {
             claim: claims[1].clone(),
             privacy_level: PrivacyLevel::Private,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: Some(claims.clone()),
@@ -476,6 +755,7 @@ fn test_zk_proof_generation() {
             claim: claim.to_vec(),
             privacy_level: PrivacyLevel::ZeroKnowledge,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: None,
@@ -502,6 +782,7 @@ fn test_zk_proof_with_blinded_membership() {
             claim: claims[2].clone(),
             privacy_level: PrivacyLevel::ZeroKnowledge,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: Some(claims.clone()),
@@ -528,6 +809,7 @@ fn test_zk_proof_blinded_root_differs_from_plain() {
             claim: claims[0].clone(),
             privacy_level: PrivacyLevel::Private,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: Some(claims.clone()),
@@ -540,6 +822,7 @@ fn test_zk_proof_blinded_root_differs_from_plain() {
             claim: claims[0].clone(),
             privacy_level: PrivacyLevel::ZeroKnowledge,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: Some(claims.clone()),
@@ -559,6 +842,7 @@ fn test_membership_index_out_of_bounds() {
             claim: claims[0].clone(),
             privacy_level: PrivacyLevel::Private,
             circuit_name: None,
+            circuit_version: None,
             witness: None,
             public_inputs: None,
             membership_set: Some(claims),
@@ -604,6 +888,7 @@ fn test_generate_with_circuit_registry() {
             claim: b"verified-computation".to_vec(),
             privacy_level: PrivacyLevel::Public,
             circuit_name: Some("test-mul".to_string()),
+            circuit_version: None,
             witness: Some(vec![4.0]),           // y = 4
             public_inputs: Some(vec![3.0, 12.0]), // x = 3, z = 12
             membership_set: None,
@@ -613,10 +898,63 @@ fn test_generate_with_circuit_registry() {
         let proof = generate_zkp_with_circuit(&request, &registry).unwrap();
         assert!(proof.circuit_result.is_some());
 
-        let cr = proof.circuit_result.unwrap();
+        let cr = proof.circuit_result.clone().unwrap();
         assert!(cr.satisfied);
         assert_eq!(cr.circuit_name, "test-mul");
+        assert_eq!(cr.circuit_version, Some(CircuitVersion::new(1, 0, 0)));
         assert_eq!(cr.constraints_checked, 1);
+
+        // A deprecated version refuses new proofs but still verifies old ones
+        registry.deprecate("test-mul", CircuitVersion::new(1, 0, 0), None).unwrap();
+        assert!(verify_zkp_with_circuit(&proof, &request.claim, &registry).unwrap());
+        let pinned = ZkpProofRequest {
+            circuit_version: Some("1.0.0".to_string()),
+            ..request.clone()
+        };
+        assert!(matches!(
+            generate_zkp_with_circuit(&pinned, &registry),
+            Err(CircuitError::Deprecated(_))
+        ));
+
+        registry.unregister_circuit("test-mul").unwrap();
+        assert!(matches!(
+            verify_zkp_with_circuit(&proof, &request.claim, &registry),
+            Err(CircuitError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_expiry_is_not_part_of_the_fingerprint() {
+        let request = ZkpProofRequest {
+            claim: b"entity:123 has-type Person".to_vec(),
+            privacy_level: PrivacyLevel::Private,
+            circuit_name: None,
+            circuit_version: None,
+            witness: None,
+            public_inputs: None,
+            membership_set: None,
+            membership_index: None,
+        };
+        let proof = generate_zkp(&request).unwrap();
+        assert!(proof.expiry().is_none());
+
+        let expiring = proof.clone().with_ttl(60);
+        let generated = chrono::DateTime::parse_from_rfc3339(&proof.generated_at).unwrap();
+        assert_eq!(expiring.expiry().unwrap(), generated + chrono::Duration::seconds(60));
+        assert!(!expiring.is_expired(generated.with_timezone(&chrono::Utc)));
+        assert!(expiring.is_expired(expiring.expiry().unwrap()));
+        assert_eq!(expiring.fingerprint(), proof.fingerprint());
+
+        let garbled = ZkpProof {
+            expires_at: Some("soon".to_string()),
+            ..proof.clone()
+        };
+        assert!(garbled.is_expired(chrono::Utc::now()));
+        let other = ZkpProof {
+            generated_at: "2020-01-01T00:00:00+00:00".to_string(),
+            ..proof.clone()
+        };
+        assert_ne!(other.fingerprint(), proof.fingerprint());
     }
 
     #[test]
@@ -625,4 +963,77 @@ fn test_privacy_level_display() {
         assert_eq!(PrivacyLevel::Private.to_string(), "Private");
         assert_eq!(PrivacyLevel::ZeroKnowledge.to_string(), "ZeroKnowledge");
     }
+
+    fn provenance_chain(events: &[(&str, &str)]) -> Vec<ProvenanceRecordWitness> {
+        let mut parent = provenance_genesis_hash();
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, (event_type, actor))| {
+                let content_hash = hex::encode(hash(format!("{}{}{}", i, event_type, actor).as_bytes()));
+                ProvenanceRecordWitness {
+                    event_type: event_type.to_string(),
+                    actor: actor.to_string(),
+                    parent_hash: std::mem::replace(&mut parent, content_hash.clone()),
+                    content_hash,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_provenance_proof_hides_actors() {
+        let request = ProvenanceProofRequest {
+            entity_id: "entity-1".to_string(),
+            event_type: "custom:approved".to_string(),
+            authorized_actors: vec!["did:example:auditor".to_string(), "did:example:board".to_string()],
+            records: provenance_chain(&[
+                ("created", "did:example:alice"),
+                ("custom:approved", "did:example:board"),
+                ("modified", "did:example:alice"),
+            ]),
+        };
+
+        let proof = generate_provenance_proof(&request).unwrap();
+        assert!(verify_provenance_proof(&proof));
+        assert_eq!(proof.chain_length, 3);
+        let published = serde_json::to_string(&proof).unwrap();
+        assert!(!published.contains("did:example"));
+
+        // Tampering with the statement breaks the binding
+        let forged = ProvenanceProof {
+            event_type: "created".to_string(),
+            ..proof.clone()
+        };
+        assert!(!verify_provenance_proof(&forged));
+        let forged = ProvenanceProof {
+            chain_root: [0u8; 32],
+            ..proof
+        };
+        assert!(!verify_provenance_proof(&forged));
+    }
+
+    #[test]
+    fn test_provenance_proof_requires_authorized_event_and_intact_chain() {
+        let mut request = ProvenanceProofRequest {
+            entity_id: "entity-1".to_string(),
+            event_type: "custom:approved".to_string(),
+            authorized_actors: vec!["did:example:board".to_string()],
+            records: provenance_chain(&[
+                ("created", "did:example:alice"),
+                ("custom:approved", "did:example:alice"),
+            ]),
+        };
+        assert!(matches!(
+            generate_provenance_proof(&request),
+            Err(CircuitError::InvalidWitness(_))
+        ));
+
+        request.records[1].actor = "did:example:board".to_string();
+        request.records[1].parent_hash = "0".repeat(64);
+        assert!(matches!(
+            generate_provenance_proof(&request),
+            Err(CircuitError::VerificationFailed(_))
+        ));
+    }
 }
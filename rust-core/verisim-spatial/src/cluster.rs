@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Spatial clustering for map display
+//!
+//! Groups entities by their representative points so a map can draw one
+//! marker per cluster instead of one per entity.  Two methods are offered:
+//!
+//! - **Grid**: bins points into lat/lon cells sized for a web-map zoom
+//!   level.  O(n), stable across pans, the usual choice for marker layers.
+//! - **DBSCAN**: density-based clusters with a distance threshold in
+//!   kilometres.  Follows the actual shape of dense areas; isolated points
+//!   are returned as single-entity clusters.
+
+use crate::{haversine_distance, BoundingBox, Coordinates, SpatialData, EARTH_RADIUS_KM};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Clusters per map tile edge used to size grid cells (a 256 px tile with
+/// 64 px marker spacing).
+const CLUSTERS_PER_TILE: f64 = 4.0;
+
+/// Highest supported web-map zoom level.
+pub const MAX_ZOOM: u8 = 22;
+
+/// Clustering method and its parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClusterMethod {
+    /// Fixed grid sized for a web-map zoom level (0 = whole world)
+    Grid { zoom: u8 },
+    /// DBSCAN with neighbourhood radius `eps_km`; a cluster needs at least
+    /// `min_points` entities within `eps_km` of a core entity
+    Dbscan { eps_km: f64, min_points: usize },
+}
+
+/// A group of nearby entities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    /// Mean position of the members
+    pub centroid: Coordinates,
+    /// Number of members
+    pub count: usize,
+    /// Members closest to the centroid, at most the requested number
+    pub representatives: Vec<String>,
+    /// Extent of the members
+    pub bounds: BoundingBox,
+}
+
+/// Grid cell edge in degrees for a zoom level.
+pub fn grid_cell_degrees(zoom: u8) -> f64 {
+    360.0 / 2f64.powi(i32::from(zoom.min(MAX_ZOOM))) / CLUSTERS_PER_TILE
+}
+
+/// Cluster entities, returning clusters ordered by descending size.
+pub fn cluster(
+    entities: &[(String, SpatialData)],
+    method: ClusterMethod,
+    representatives: usize,
+) -> Vec<Cluster> {
+    let groups = match method {
+        ClusterMethod::Grid { zoom } => grid_groups(entities, grid_cell_degrees(zoom)),
+        ClusterMethod::Dbscan { eps_km, min_points } => dbscan_groups(entities, eps_km, min_points),
+    };
+
+    let mut clusters: Vec<Cluster> = groups
+        .into_iter()
+        .map(|members| summarise(entities, &members, representatives))
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.count.cmp(&a.count).then_with(|| {
+            a.centroid
+                .latitude
+                .partial_cmp(&b.centroid.latitude)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+    clusters
+}
+
+fn grid_groups(entities: &[(String, SpatialData)], cell: f64) -> Vec<Vec<usize>> {
+    let mut cells: BTreeMap<(i64, i64), Vec<usize>> = BTreeMap::new();
+    for (i, (_, data)) in entities.iter().enumerate() {
+        let key = (
+            ((data.coordinates.latitude + 90.0) / cell).floor() as i64,
+            ((data.coordinates.longitude + 180.0) / cell).floor() as i64,
+        );
+        cells.entry(key).or_default().push(i);
+    }
+    cells.into_values().collect()
+}
+
+fn dbscan_groups(entities: &[(String, SpatialData)], eps_km: f64, min_points: usize) -> Vec<Vec<usize>> {
+    // Sort by latitude so neighbour search can stop at the latitude band.
+    let mut order: Vec<usize> = (0..entities.len()).collect();
+    order.sort_by(|&a, &b| {
+        entities[a]
+            .1
+            .coordinates
+            .latitude
+            .partial_cmp(&entities[b].1.coordinates.latitude)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let band_deg = (eps_km / EARTH_RADIUS_KM).to_degrees();
+    let position: Vec<usize> = {
+        let mut p = vec![0; entities.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            p[i] = rank;
+        }
+        p
+    };
+
+    let neighbours = |i: usize| -> Vec<usize> {
+        let here = &entities[i].1.coordinates;
+        let rank = position[i];
+        let mut found = Vec::new();
+        for &j in order[..rank].iter().rev() {
+            let there = &entities[j].1.coordinates;
+            if here.latitude - there.latitude > band_deg {
+                break;
+            }
+            if haversine_distance(here, there) <= eps_km {
+                found.push(j);
+            }
+        }
+        for &j in &order[rank + 1..] {
+            let there = &entities[j].1.coordinates;
+            if there.latitude - here.latitude > band_deg {
+                break;
+            }
+            if haversine_distance(here, there) <= eps_km {
+                found.push(j);
+            }
+        }
+        found
+    };
+
+    const UNVISITED: usize = usize::MAX;
+    const NOISE: usize = usize::MAX - 1;
+    let mut label = vec![UNVISITED; entities.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+
+    for i in 0..entities.len() {
+        if label[i] != UNVISITED {
+            continue;
+        }
+        let seeds = neighbours(i);
+        // The point itself counts towards min_points.
+        if seeds.len() + 1 < min_points {
+            label[i] = NOISE;
+            continue;
+        }
+        let id = groups.len();
+        label[i] = id;
+        let mut members = vec![i];
+        let mut queue: VecDeque<usize> = seeds.into();
+        while let Some(j) = queue.pop_front() {
+            if label[j] == NOISE {
+                // Border point: joins the cluster but does not expand it.
+                label[j] = id;
+                members.push(j);
+                continue;
+            }
+            if label[j] != UNVISITED {
+                continue;
+            }
+            label[j] = id;
+            members.push(j);
+            let more = neighbours(j);
+            if more.len() + 1 >= min_points {
+                queue.extend(more);
+            }
+        }
+        groups.push(members);
+    }
+
+    // Isolated points are still drawn, as clusters of one.
+    groups.extend(
+        label
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| **l == NOISE)
+            .map(|(i, _)| vec![i]),
+    );
+    groups
+}
+
+fn summarise(entities: &[(String, SpatialData)], members: &[usize], representatives: usize) -> Cluster {
+    let n = members.len() as f64;
+    let lat = members.iter().map(|&i| entities[i].1.coordinates.latitude).sum::<f64>() / n;
+    let lon = members.iter().map(|&i| entities[i].1.coordinates.longitude).sum::<f64>() / n;
+    let centroid = Coordinates::new_unchecked(lat, lon, None);
+
+    let mut bounds = BoundingBox {
+        min_lat: f64::INFINITY,
+        min_lon: f64::INFINITY,
+        max_lat: f64::NEG_INFINITY,
+        max_lon: f64::NEG_INFINITY,
+    };
+    for &i in members {
+        let c = &entities[i].1.coordinates;
+        bounds.min_lat = bounds.min_lat.min(c.latitude);
+        bounds.min_lon = bounds.min_lon.min(c.longitude);
+        bounds.max_lat = bounds.max_lat.max(c.latitude);
+        bounds.max_lon = bounds.max_lon.max(c.longitude);
+    }
+
+    let mut by_distance: Vec<(f64, &String)> = members
+        .iter()
+        .map(|&i| (haversine_distance(&centroid, &entities[i].1.coordinates), &entities[i].0))
+        .collect();
+    by_distance.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.1.cmp(b.1))
+    });
+
+    Cluster {
+        centroid,
+        count: members.len(),
+        representatives: by_distance
+            .into_iter()
+            .take(representatives)
+            .map(|(_, id)| id.clone())
+            .collect(),
+        bounds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(id: &str, lat: f64, lon: f64) -> (String, SpatialData) {
+        (id.to_string(), SpatialData::point(lat, lon, None).unwrap())
+    }
+
+    fn sample() -> Vec<(String, SpatialData)> {
+        vec![
+            point("l1", 51.5074, -0.1278),
+            point("l2", 51.5155, -0.0922),
+            point("l3", 51.5007, -0.1246),
+            point("p1", 48.8566, 2.3522),
+            point("p2", 48.8606, 2.3376),
+            point("nyc", 40.7128, -74.0060),
+        ]
+    }
+
+    #[test]
+    fn test_grid_low_zoom_merges_nearby_cities() {
+        let mut entities = sample();
+        entities.push(point("lyon", 45.7640, 4.8357));
+        let clusters = cluster(&entities, ClusterMethod::Grid { zoom: 2 }, 2);
+        let total: usize = clusters.iter().map(|c| c.count).sum();
+        assert_eq!(total, 7);
+        // Paris and Lyon share a 22.5° cell at zoom 2.
+        let counts: Vec<usize> = clusters.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![3, 3, 1]);
+        assert!(clusters.iter().all(|c| c.representatives.len() <= 2));
+    }
+
+    #[test]
+    fn test_grid_city_zoom_separates_cities() {
+        let clusters = cluster(&sample(), ClusterMethod::Grid { zoom: 6 }, 1);
+        let counts: Vec<usize> = clusters.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_dbscan_clusters_and_noise() {
+        let clusters = cluster(
+            &sample(),
+            ClusterMethod::Dbscan { eps_km: 5.0, min_points: 2 },
+            5,
+        );
+        let counts: Vec<usize> = clusters.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![3, 2, 1]);
+        let london = &clusters[0];
+        assert!((london.centroid.latitude - 51.5).abs() < 0.1);
+        assert_eq!(clusters[2].representatives, vec!["nyc".to_string()]);
+    }
+
+    #[test]
+    fn test_grid_cell_shrinks_with_zoom() {
+        assert_eq!(grid_cell_degrees(0), 90.0);
+        assert!(grid_cell_degrees(10) < grid_cell_degrees(9));
+        assert_eq!(grid_cell_degrees(40), grid_cell_degrees(MAX_ZOOM));
+    }
+}
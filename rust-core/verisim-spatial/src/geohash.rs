@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Geohash cell encoding
+//!
+//! A geohash names a rectangular lat/lon cell; each extra character
+//! subdivides the cell 32 ways, and every cell's hash is a prefix of the
+//! hashes of the cells inside it.  That prefix property is what makes cell
+//! lookups and per-cell aggregation cheap range scans over an ordered index.
+
+use crate::{BoundingBox, Coordinates, SpatialError};
+use serde::{Deserialize, Serialize};
+
+/// Geohash base-32 alphabet (omits a, i, l, o).
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Longest supported geohash (~3.7 cm × 1.9 cm cells).
+pub const MAX_PRECISION: usize = 12;
+
+/// Number of entities whose representative point falls in one cell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellCount {
+    /// Cell geohash
+    pub geohash: String,
+    /// Number of entities in the cell
+    pub count: usize,
+    /// Extent of the cell
+    pub bounds: BoundingBox,
+}
+
+/// Check a requested precision is within `1..=MAX_PRECISION`.
+pub fn validate_precision(precision: usize) -> Result<(), SpatialError> {
+    if (1..=MAX_PRECISION).contains(&precision) {
+        Ok(())
+    } else {
+        Err(SpatialError::InvalidCoordinates(format!(
+            "Geohash precision {} out of range [1, {}]",
+            precision, MAX_PRECISION
+        )))
+    }
+}
+
+/// Encode a position as a geohash of `precision` characters.
+///
+/// `precision` is clamped to `1..=MAX_PRECISION`.
+pub fn encode(point: &Coordinates, precision: usize) -> String {
+    let precision = precision.clamp(1, MAX_PRECISION);
+    let (mut lat_lo, mut lat_hi) = (-90.0, 90.0);
+    let (mut lon_lo, mut lon_hi) = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision);
+    let mut even = true;
+    let mut bits = 0u8;
+    let mut value = 0usize;
+
+    while hash.len() < precision {
+        let (lo, hi, v) = if even {
+            (&mut lon_lo, &mut lon_hi, point.longitude)
+        } else {
+            (&mut lat_lo, &mut lat_hi, point.latitude)
+        };
+        let mid = (*lo + *hi) / 2.0;
+        value <<= 1;
+        if v >= mid {
+            value |= 1;
+            *lo = mid;
+        } else {
+            *hi = mid;
+        }
+        even = !even;
+        bits += 1;
+        if bits == 5 {
+            hash.push(BASE32[value] as char);
+            bits = 0;
+            value = 0;
+        }
+    }
+    hash
+}
+
+/// Decode a geohash into the cell it names.
+pub fn decode_bounds(geohash: &str) -> Result<BoundingBox, SpatialError> {
+    if geohash.is_empty() || geohash.len() > MAX_PRECISION {
+        return Err(SpatialError::ParseError(format!(
+            "Geohash must be 1 to {} characters",
+            MAX_PRECISION
+        )));
+    }
+    let (mut lat_lo, mut lat_hi) = (-90.0, 90.0);
+    let (mut lon_lo, mut lon_hi) = (-180.0, 180.0);
+    let mut even = true;
+
+    for c in geohash.bytes() {
+        let value = BASE32
+            .iter()
+            .position(|b| *b == c.to_ascii_lowercase())
+            .ok_or_else(|| {
+                SpatialError::ParseError(format!("Invalid geohash character '{}'", c as char))
+            })?;
+        for shift in (0..5).rev() {
+            let bit = (value >> shift) & 1 == 1;
+            let (lo, hi) = if even {
+                (&mut lon_lo, &mut lon_hi)
+            } else {
+                (&mut lat_lo, &mut lat_hi)
+            };
+            let mid = (*lo + *hi) / 2.0;
+            if bit {
+                *lo = mid;
+            } else {
+                *hi = mid;
+            }
+            even = !even;
+        }
+    }
+
+    Ok(BoundingBox {
+        min_lat: lat_lo,
+        min_lon: lon_lo,
+        max_lat: lat_hi,
+        max_lon: lon_hi,
+    })
+}
+
+/// Centre point of a geohash cell.
+pub fn decode(geohash: &str) -> Result<Coordinates, SpatialError> {
+    let b = decode_bounds(geohash)?;
+    Ok(Coordinates::new_unchecked(
+        (b.min_lat + b.max_lat) / 2.0,
+        (b.min_lon + b.max_lon) / 2.0,
+        None,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_known_values() {
+        // Reference values from the original geohash.org implementation
+        let point = Coordinates::new_unchecked(57.64911, 10.40744, None);
+        assert_eq!(encode(&point, 11), "u4pruydqqvj");
+        let london = Coordinates::new_unchecked(51.5074, -0.1278, None);
+        assert_eq!(encode(&london, 5), "gcpvj");
+    }
+
+    #[test]
+    fn test_decode_contains_point() {
+        let point = Coordinates::new_unchecked(-33.8688, 151.2093, None);
+        for precision in 1..=MAX_PRECISION {
+            let hash = encode(&point, precision);
+            let b = decode_bounds(&hash).unwrap();
+            assert!(b.min_lat <= point.latitude && point.latitude <= b.max_lat);
+            assert!(b.min_lon <= point.longitude && point.longitude <= b.max_lon);
+        }
+    }
+
+    #[test]
+    fn test_prefix_property() {
+        let point = Coordinates::new_unchecked(40.7128, -74.0060, None);
+        let long = encode(&point, 9);
+        assert!(long.starts_with(&encode(&point, 4)));
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid() {
+        assert!(matches!(decode_bounds("u4pa"), Err(SpatialError::ParseError(_))));
+        assert!(matches!(decode_bounds(""), Err(SpatialError::ParseError(_))));
+        assert!(validate_precision(0).is_err());
+        assert!(validate_precision(13).is_err());
+    }
+}
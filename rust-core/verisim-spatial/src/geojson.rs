@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! GeoJSON (RFC 7946) conversion
+//!
+//! Converts [`Geometry`] and [`SpatialData`] to and from GeoJSON geometries
+//! and features.  Positions follow the GeoJSON axis order `[longitude,
+//! latitude, altitude?]`.  The owning entity id travels in the feature's
+//! `properties.entity_id` (and the feature `id`), so collections exported
+//! from VeriSimDB can be edited in GIS tooling and imported back.
+
+use crate::{Coordinates, Geometry, SpatialData, SpatialError};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Property key carrying the entity id of a feature.
+pub const ENTITY_ID_PROPERTY: &str = "entity_id";
+
+/// Property key carrying the SRID of a feature.
+pub const SRID_PROPERTY: &str = "srid";
+
+/// A GeoJSON feature decoded into spatial data.
+#[derive(Debug, Clone)]
+pub struct GeoJsonFeature {
+    /// Entity id from `properties.entity_id` or the feature `id`, if any
+    pub entity_id: Option<String>,
+    /// Decoded geometry, SRID and remaining properties
+    pub data: SpatialData,
+}
+
+impl Geometry {
+    /// Encode as a GeoJSON geometry object.
+    pub fn to_geojson(&self) -> Value {
+        let coordinates = match self {
+            Geometry::Point(p) => position(p),
+            Geometry::LineString(points) | Geometry::MultiPoint(points) => positions(points),
+            Geometry::Polygon(rings) => Value::Array(rings.iter().map(|r| positions(r)).collect()),
+            Geometry::MultiPolygon(polygons) => Value::Array(
+                polygons
+                    .iter()
+                    .map(|p| Value::Array(p.iter().map(|r| positions(r)).collect()))
+                    .collect(),
+            ),
+        };
+        json!({ "type": self.geometry_type().to_string(), "coordinates": coordinates })
+    }
+
+    /// Decode a GeoJSON geometry object.
+    pub fn from_geojson(value: &Value) -> Result<Self, SpatialError> {
+        let kind = value
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| parse_error("geometry has no \"type\""))?;
+        let coordinates = value
+            .get("coordinates")
+            .ok_or_else(|| parse_error("geometry has no \"coordinates\""))?;
+
+        let geometry = match kind {
+            "Point" => Geometry::Point(parse_position(coordinates)?),
+            "LineString" => Geometry::LineString(parse_positions(coordinates)?),
+            "MultiPoint" => Geometry::MultiPoint(parse_positions(coordinates)?),
+            "Polygon" => Geometry::Polygon(parse_rings(coordinates)?),
+            "MultiPolygon" => Geometry::MultiPolygon(
+                as_array(coordinates)?
+                    .iter()
+                    .map(parse_rings)
+                    .collect::<Result<_, _>>()?,
+            ),
+            other => return Err(parse_error(&format!("unsupported geometry type {}", other))),
+        };
+        geometry.validate()?;
+        Ok(geometry)
+    }
+}
+
+/// Encode an entity's spatial data as a GeoJSON feature.
+pub fn to_feature(entity_id: &str, data: &SpatialData) -> Value {
+    let mut properties: Map<String, Value> = data
+        .properties
+        .iter()
+        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+        .collect();
+    properties.insert(ENTITY_ID_PROPERTY.to_string(), json!(entity_id));
+    properties.insert(SRID_PROPERTY.to_string(), json!(data.srid));
+
+    json!({
+        "type": "Feature",
+        "id": entity_id,
+        "geometry": data.shape().to_geojson(),
+        "properties": properties,
+    })
+}
+
+/// Decode a GeoJSON feature.
+///
+/// Non-string property values are kept as their JSON text.
+pub fn from_feature(value: &Value) -> Result<GeoJsonFeature, SpatialError> {
+    if value.get("type").and_then(Value::as_str) != Some("Feature") {
+        return Err(parse_error("expected a Feature"));
+    }
+    let geometry = value
+        .get("geometry")
+        .filter(|g| !g.is_null())
+        .ok_or_else(|| parse_error("feature has no geometry"))?;
+    let geometry = Geometry::from_geojson(geometry)?;
+
+    let mut entity_id = value.get("id").and_then(value_as_string);
+    let mut srid = 4326;
+    let mut properties = HashMap::new();
+    if let Some(props) = value.get("properties").and_then(Value::as_object) {
+        for (key, v) in props {
+            match key.as_str() {
+                ENTITY_ID_PROPERTY => entity_id = value_as_string(v).or(entity_id),
+                SRID_PROPERTY => {
+                    srid = v
+                        .as_u64()
+                        .and_then(|s| u32::try_from(s).ok())
+                        .ok_or_else(|| parse_error("srid must be a positive integer"))?;
+                }
+                _ => {
+                    if let Some(s) = value_as_string(v) {
+                        properties.insert(key.clone(), s);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut data = SpatialData::from_geometry(geometry, srid)?;
+    data.properties = properties;
+    Ok(GeoJsonFeature { entity_id, data })
+}
+
+/// Decode a FeatureCollection, returning one result per feature so a single
+/// bad feature does not abort the whole import.
+pub fn from_feature_collection(
+    value: &Value,
+) -> Result<Vec<Result<GeoJsonFeature, SpatialError>>, SpatialError> {
+    if value.get("type").and_then(Value::as_str) != Some("FeatureCollection") {
+        return Err(parse_error("expected a FeatureCollection"));
+    }
+    let features = value
+        .get("features")
+        .and_then(Value::as_array)
+        .ok_or_else(|| parse_error("FeatureCollection has no \"features\" array"))?;
+    Ok(features.iter().map(from_feature).collect())
+}
+
+fn parse_error(message: &str) -> SpatialError {
+    SpatialError::ParseError(format!("GeoJSON: {}", message))
+}
+
+fn value_as_string(v: &Value) -> Option<String> {
+    match v {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn position(c: &Coordinates) -> Value {
+    match c.altitude {
+        Some(alt) => json!([c.longitude, c.latitude, alt]),
+        None => json!([c.longitude, c.latitude]),
+    }
+}
+
+fn positions(points: &[Coordinates]) -> Value {
+    Value::Array(points.iter().map(position).collect())
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>, SpatialError> {
+    value
+        .as_array()
+        .ok_or_else(|| parse_error("expected an array of coordinates"))
+}
+
+fn parse_position(value: &Value) -> Result<Coordinates, SpatialError> {
+    let numbers = as_array(value)?;
+    let get = |i: usize| numbers.get(i).and_then(Value::as_f64);
+    match (get(0), get(1)) {
+        (Some(lon), Some(lat)) => Coordinates::new(lat, lon, get(2)),
+        _ => Err(parse_error("position must be [longitude, latitude, altitude?]")),
+    }
+}
+
+fn parse_positions(value: &Value) -> Result<Vec<Coordinates>, SpatialError> {
+    as_array(value)?.iter().map(parse_position).collect()
+}
+
+fn parse_rings(value: &Value) -> Result<Vec<Vec<Coordinates>>, SpatialError> {
+    as_array(value)?.iter().map(parse_positions).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometry_axis_order() {
+        let point = Geometry::Point(Coordinates::new_unchecked(51.5, -0.12, Some(11.0)));
+        let encoded = point.to_geojson();
+        assert_eq!(encoded["coordinates"], json!([-0.12, 51.5, 11.0]));
+        assert_eq!(Geometry::from_geojson(&encoded).unwrap(), point);
+    }
+
+    #[test]
+    fn test_feature_roundtrip() {
+        let polygon = Geometry::from_geojson(&json!({
+            "type": "Polygon",
+            "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]]
+        }))
+        .unwrap();
+        let data = SpatialData::from_geometry(polygon, 4326)
+            .unwrap()
+            .with_property("name", "field");
+
+        let feature = to_feature("entity-1", &data);
+        assert_eq!(feature["properties"]["entity_id"], "entity-1");
+
+        let decoded = from_feature(&feature).unwrap();
+        assert_eq!(decoded.entity_id.as_deref(), Some("entity-1"));
+        assert_eq!(decoded.data.geometry, data.geometry);
+        assert_eq!(decoded.data.properties.get("name").map(String::as_str), Some("field"));
+        assert!(!decoded.data.properties.contains_key("entity_id"));
+    }
+
+    #[test]
+    fn test_collection_reports_bad_features_individually() {
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [
+                { "type": "Feature", "geometry": { "type": "Point", "coordinates": [2.35, 48.85] },
+                  "properties": { "population": 2100000 } },
+                { "type": "Feature", "geometry": { "type": "Point", "coordinates": [0.0, 95.0] },
+                  "properties": {} },
+                { "type": "Feature", "geometry": { "type": "Circle", "coordinates": [] } }
+            ]
+        });
+
+        let features = from_feature_collection(&collection).unwrap();
+        assert_eq!(features.len(), 3);
+        let first = features[0].as_ref().unwrap();
+        assert!(first.entity_id.is_none());
+        assert_eq!(first.data.properties["population"], "2100000");
+        assert!(matches!(features[1], Err(SpatialError::InvalidCoordinates(_))));
+        assert!(matches!(features[2], Err(SpatialError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_rejects_non_collection() {
+        let result = from_feature_collection(&json!({ "type": "Feature" }));
+        assert!(matches!(result, Err(SpatialError::ParseError(_))));
+    }
+}
@@ -0,0 +1,569 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Full geometries and geometric operations
+//!
+//! [`Geometry`] carries the complete coordinate sequences for every OGC
+//! geometry type that [`GeometryType`] names.  Predicates (point-in-polygon,
+//! intersection) are evaluated in the planar lon/lat plane, which is exact
+//! for the small-to-regional shapes hexads typically describe.  Measurements
+//! (length, area) are geodesic on a spherical Earth.
+
+use crate::{haversine_distance, BoundingBox, Coordinates, GeometryType, SpatialError, EARTH_RADIUS_KM};
+use serde::{Deserialize, Serialize};
+
+/// Tolerance (in degrees) used for collinearity and boundary checks.
+const EPSILON: f64 = 1e-12;
+
+/// A complete geometry.
+///
+/// Polygons are a list of rings: the first ring is the exterior boundary,
+/// any further rings are holes.  Every ring must be closed (first point
+/// equal to last) and have at least four points.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum Geometry {
+    /// A single position
+    Point(Coordinates),
+    /// An ordered sequence of at least two positions
+    LineString(Vec<Coordinates>),
+    /// Exterior ring followed by zero or more holes
+    Polygon(Vec<Vec<Coordinates>>),
+    /// A collection of positions
+    MultiPoint(Vec<Coordinates>),
+    /// A collection of polygons
+    MultiPolygon(Vec<Vec<Vec<Coordinates>>>),
+}
+
+impl Geometry {
+    /// The [`GeometryType`] of this geometry.
+    pub fn geometry_type(&self) -> GeometryType {
+        match self {
+            Geometry::Point(_) => GeometryType::Point,
+            Geometry::LineString(_) => GeometryType::LineString,
+            Geometry::Polygon(_) => GeometryType::Polygon,
+            Geometry::MultiPoint(_) => GeometryType::MultiPoint,
+            Geometry::MultiPolygon(_) => GeometryType::MultiPolygon,
+        }
+    }
+
+    /// Check coordinate ranges and structural rules (minimum point counts,
+    /// closed rings).
+    pub fn validate(&self) -> Result<(), SpatialError> {
+        for c in self.positions() {
+            Coordinates::new(c.latitude, c.longitude, c.altitude)?;
+        }
+        match self {
+            Geometry::Point(_) => Ok(()),
+            Geometry::LineString(points) => {
+                if points.len() < 2 {
+                    return Err(SpatialError::InvalidCoordinates(
+                        "LineString needs at least 2 points".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Geometry::Polygon(rings) => validate_polygon(rings),
+            Geometry::MultiPoint(points) => {
+                if points.is_empty() {
+                    return Err(SpatialError::InvalidCoordinates(
+                        "MultiPoint needs at least 1 point".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Geometry::MultiPolygon(polygons) => {
+                if polygons.is_empty() {
+                    return Err(SpatialError::InvalidCoordinates(
+                        "MultiPolygon needs at least 1 polygon".to_string(),
+                    ));
+                }
+                polygons.iter().try_for_each(|p| validate_polygon(p))
+            }
+        }
+    }
+
+    /// Every position in the geometry, in order.
+    pub fn positions(&self) -> Vec<&Coordinates> {
+        match self {
+            Geometry::Point(p) => vec![p],
+            Geometry::LineString(points) | Geometry::MultiPoint(points) => points.iter().collect(),
+            Geometry::Polygon(rings) => rings.iter().flatten().collect(),
+            Geometry::MultiPolygon(polygons) => polygons.iter().flatten().flatten().collect(),
+        }
+    }
+
+    /// Representative point: the area-weighted centroid for polygons, the
+    /// length-weighted centroid for lines, the mean position otherwise.
+    pub fn centroid(&self) -> Coordinates {
+        match self {
+            Geometry::Point(p) => p.clone(),
+            Geometry::LineString(points) => line_centroid(points),
+            Geometry::Polygon(rings) => polygon_centroid(rings).0,
+            Geometry::MultiPoint(points) => mean_position(points.iter()),
+            Geometry::MultiPolygon(polygons) => {
+                let weighted: Vec<(Coordinates, f64)> =
+                    polygons.iter().map(|p| polygon_centroid(p)).collect();
+                let total: f64 = weighted.iter().map(|(_, w)| w).sum();
+                if total <= EPSILON {
+                    return mean_position(self.positions().into_iter());
+                }
+                let lat = weighted.iter().map(|(c, w)| c.latitude * w).sum::<f64>() / total;
+                let lon = weighted.iter().map(|(c, w)| c.longitude * w).sum::<f64>() / total;
+                Coordinates::new_unchecked(lat, lon, None)
+            }
+        }
+    }
+
+    /// Smallest lat/lon box containing every position.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let positions = self.positions();
+        let mut bounds = BoundingBox {
+            min_lat: f64::INFINITY,
+            min_lon: f64::INFINITY,
+            max_lat: f64::NEG_INFINITY,
+            max_lon: f64::NEG_INFINITY,
+        };
+        for c in positions {
+            bounds.min_lat = bounds.min_lat.min(c.latitude);
+            bounds.min_lon = bounds.min_lon.min(c.longitude);
+            bounds.max_lat = bounds.max_lat.max(c.latitude);
+            bounds.max_lon = bounds.max_lon.max(c.longitude);
+        }
+        bounds
+    }
+
+    /// Geodesic length in kilometres.
+    ///
+    /// Lines report their length, polygons their perimeter (all rings),
+    /// points zero.
+    pub fn length_km(&self) -> f64 {
+        match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => 0.0,
+            Geometry::LineString(points) => path_length_km(points),
+            Geometry::Polygon(rings) => rings.iter().map(|r| path_length_km(r)).sum(),
+            Geometry::MultiPolygon(polygons) => polygons
+                .iter()
+                .flatten()
+                .map(|r| path_length_km(r))
+                .sum(),
+        }
+    }
+
+    /// Geodesic area in square kilometres (holes subtracted).
+    ///
+    /// Zero for points and lines.
+    pub fn area_km2(&self) -> f64 {
+        match self {
+            Geometry::Polygon(rings) => polygon_area_km2(rings),
+            Geometry::MultiPolygon(polygons) => polygons.iter().map(|p| polygon_area_km2(p)).sum(),
+            _ => 0.0,
+        }
+    }
+
+    /// Whether `point` lies inside or on the boundary of this geometry.
+    pub fn contains_point(&self, point: &Coordinates) -> bool {
+        match self {
+            Geometry::Point(p) => same_position(p, point),
+            Geometry::MultiPoint(points) => points.iter().any(|p| same_position(p, point)),
+            Geometry::LineString(points) => points
+                .windows(2)
+                .any(|s| on_segment(&s[0], &s[1], point)),
+            Geometry::Polygon(rings) => polygon_contains(rings, point),
+            Geometry::MultiPolygon(polygons) => polygons.iter().any(|p| polygon_contains(p, point)),
+        }
+    }
+
+    /// Whether this geometry shares at least one point with `other`.
+    pub fn intersects(&self, other: &Geometry) -> bool {
+        if !boxes_overlap(&self.bounding_box(), &other.bounding_box()) {
+            return false;
+        }
+        if other.positions().into_iter().any(|p| self.contains_point(p))
+            || self.positions().into_iter().any(|p| other.contains_point(p))
+        {
+            return true;
+        }
+        let ours = self.segments();
+        let theirs = other.segments();
+        ours.iter().any(|(a1, a2)| {
+            theirs
+                .iter()
+                .any(|(b1, b2)| segments_intersect(a1, a2, b1, b2))
+        })
+    }
+
+    /// Whether this geometry lies entirely inside `other` (boundary contact
+    /// allowed).
+    ///
+    /// Every position must be inside `other`, no edge may cross `other`'s
+    /// boundary, and no hole of `other` may lie inside this geometry.
+    pub fn within(&self, other: &Geometry) -> bool {
+        if !self.positions().into_iter().all(|p| other.contains_point(p)) {
+            return false;
+        }
+        let theirs = other.segments();
+        let crosses = self.segments().iter().any(|(a1, a2)| {
+            theirs
+                .iter()
+                .any(|(b1, b2)| segments_cross(a1, a2, b1, b2))
+        });
+        if crosses {
+            return false;
+        }
+        let holes: Vec<&Coordinates> = match other {
+            Geometry::Polygon(rings) => rings.iter().skip(1).flatten().collect(),
+            Geometry::MultiPolygon(polygons) => polygons
+                .iter()
+                .flat_map(|p| p.iter().skip(1).flatten())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let boundary = self.segments();
+        !holes.into_iter().any(|h| {
+            self.contains_point(h) && !boundary.iter().any(|(a, b)| on_segment(a, b, h))
+        })
+    }
+
+    /// Whether this is a Polygon or MultiPolygon.
+    pub fn is_areal(&self) -> bool {
+        matches!(self, Geometry::Polygon(_) | Geometry::MultiPolygon(_))
+    }
+
+    /// All edges of lines and polygon rings.
+    fn segments(&self) -> Vec<(&Coordinates, &Coordinates)> {
+        let rings: Vec<&Vec<Coordinates>> = match self {
+            Geometry::Point(_) | Geometry::MultiPoint(_) => Vec::new(),
+            Geometry::LineString(points) => vec![points],
+            Geometry::Polygon(rings) => rings.iter().collect(),
+            Geometry::MultiPolygon(polygons) => polygons.iter().flatten().collect(),
+        };
+        rings
+            .into_iter()
+            .flat_map(|r| r.windows(2).map(|s| (&s[0], &s[1])))
+            .collect()
+    }
+}
+
+fn validate_polygon(rings: &[Vec<Coordinates>]) -> Result<(), SpatialError> {
+    if rings.is_empty() {
+        return Err(SpatialError::InvalidCoordinates(
+            "Polygon needs an exterior ring".to_string(),
+        ));
+    }
+    for ring in rings {
+        if ring.len() < 4 {
+            return Err(SpatialError::InvalidCoordinates(
+                "Polygon ring needs at least 4 points".to_string(),
+            ));
+        }
+        if !same_position(&ring[0], &ring[ring.len() - 1]) {
+            return Err(SpatialError::InvalidCoordinates(
+                "Polygon ring must be closed (first point equal to last)".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn same_position(a: &Coordinates, b: &Coordinates) -> bool {
+    (a.latitude - b.latitude).abs() <= EPSILON && (a.longitude - b.longitude).abs() <= EPSILON
+}
+
+fn mean_position<'a>(points: impl Iterator<Item = &'a Coordinates>) -> Coordinates {
+    let (mut lat, mut lon, mut n) = (0.0, 0.0, 0usize);
+    for p in points {
+        lat += p.latitude;
+        lon += p.longitude;
+        n += 1;
+    }
+    if n == 0 {
+        return Coordinates::new_unchecked(0.0, 0.0, None);
+    }
+    Coordinates::new_unchecked(lat / n as f64, lon / n as f64, None)
+}
+
+fn path_length_km(points: &[Coordinates]) -> f64 {
+    points
+        .windows(2)
+        .map(|s| haversine_distance(&s[0], &s[1]))
+        .sum()
+}
+
+fn line_centroid(points: &[Coordinates]) -> Coordinates {
+    let (mut lat, mut lon, mut total) = (0.0, 0.0, 0.0);
+    for s in points.windows(2) {
+        let w = haversine_distance(&s[0], &s[1]);
+        lat += (s[0].latitude + s[1].latitude) / 2.0 * w;
+        lon += (s[0].longitude + s[1].longitude) / 2.0 * w;
+        total += w;
+    }
+    if total <= EPSILON {
+        return mean_position(points.iter());
+    }
+    Coordinates::new_unchecked(lat / total, lon / total, None)
+}
+
+/// Signed planar (lon/lat) area and centroid of a single ring.
+fn ring_planar(ring: &[Coordinates]) -> (f64, f64, f64) {
+    let (mut area, mut cx, mut cy) = (0.0, 0.0, 0.0);
+    for s in ring.windows(2) {
+        let (x0, y0) = (s[0].longitude, s[0].latitude);
+        let (x1, y1) = (s[1].longitude, s[1].latitude);
+        let cross = x0 * y1 - x1 * y0;
+        area += cross;
+        cx += (x0 + x1) * cross;
+        cy += (y0 + y1) * cross;
+    }
+    (area / 2.0, cx, cy)
+}
+
+/// Centroid of a polygon and its planar area (used as a weight).
+fn polygon_centroid(rings: &[Vec<Coordinates>]) -> (Coordinates, f64) {
+    let Some(exterior) = rings.first() else {
+        return (Coordinates::new_unchecked(0.0, 0.0, None), 0.0);
+    };
+    let (ext_area, ext_cx, ext_cy) = ring_planar(exterior);
+    // Holes contribute with the opposite orientation to the exterior.
+    let (mut area, mut cx, mut cy) = (ext_area, ext_cx, ext_cy);
+    for hole in &rings[1..] {
+        let (a, x, y) = ring_planar(hole);
+        let sign = if a.signum() == ext_area.signum() { -1.0 } else { 1.0 };
+        area += sign * a;
+        cx += sign * x;
+        cy += sign * y;
+    }
+    if area.abs() <= EPSILON {
+        return (mean_position(exterior.iter()), 0.0);
+    }
+    (
+        Coordinates::new_unchecked(cy / (6.0 * area), cx / (6.0 * area), None),
+        area.abs(),
+    )
+}
+
+/// Area of a ring on a sphere of radius [`EARTH_RADIUS_KM`].
+fn ring_area_km2(ring: &[Coordinates]) -> f64 {
+    let sum: f64 = ring
+        .windows(2)
+        .map(|s| {
+            (s[1].longitude - s[0].longitude).to_radians()
+                * (2.0 + s[0].latitude.to_radians().sin() + s[1].latitude.to_radians().sin())
+        })
+        .sum();
+    (sum * EARTH_RADIUS_KM * EARTH_RADIUS_KM / 2.0).abs()
+}
+
+fn polygon_area_km2(rings: &[Vec<Coordinates>]) -> f64 {
+    let Some(exterior) = rings.first() else {
+        return 0.0;
+    };
+    let holes: f64 = rings[1..].iter().map(|r| ring_area_km2(r)).sum();
+    (ring_area_km2(exterior) - holes).max(0.0)
+}
+
+/// Ray-casting test; points on the boundary count as inside.
+fn ring_contains(ring: &[Coordinates], point: &Coordinates) -> bool {
+    if ring.windows(2).any(|s| on_segment(&s[0], &s[1], point)) {
+        return true;
+    }
+    let (x, y) = (point.longitude, point.latitude);
+    let mut inside = false;
+    for s in ring.windows(2) {
+        let (xi, yi) = (s[0].longitude, s[0].latitude);
+        let (xj, yj) = (s[1].longitude, s[1].latitude);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn polygon_contains(rings: &[Vec<Coordinates>], point: &Coordinates) -> bool {
+    let Some(exterior) = rings.first() else {
+        return false;
+    };
+    if !ring_contains(exterior, point) {
+        return false;
+    }
+    // Inside a hole means outside the polygon, unless on the hole's boundary.
+    !rings[1..].iter().any(|hole| {
+        ring_contains(hole, point) && !hole.windows(2).any(|s| on_segment(&s[0], &s[1], point))
+    })
+}
+
+/// Cross product of (b - a) × (c - a) in the lon/lat plane.
+fn orientation(a: &Coordinates, b: &Coordinates, c: &Coordinates) -> f64 {
+    (b.longitude - a.longitude) * (c.latitude - a.latitude)
+        - (b.latitude - a.latitude) * (c.longitude - a.longitude)
+}
+
+fn on_segment(a: &Coordinates, b: &Coordinates, p: &Coordinates) -> bool {
+    orientation(a, b, p).abs() <= EPSILON
+        && p.longitude >= a.longitude.min(b.longitude) - EPSILON
+        && p.longitude <= a.longitude.max(b.longitude) + EPSILON
+        && p.latitude >= a.latitude.min(b.latitude) - EPSILON
+        && p.latitude <= a.latitude.max(b.latitude) + EPSILON
+}
+
+/// Whether two segments cross at a single interior point of both.
+fn segments_cross(a1: &Coordinates, a2: &Coordinates, b1: &Coordinates, b2: &Coordinates) -> bool {
+    let d1 = orientation(b1, b2, a1);
+    let d2 = orientation(b1, b2, a2);
+    let d3 = orientation(a1, a2, b1);
+    let d4 = orientation(a1, a2, b2);
+    ((d1 > EPSILON && d2 < -EPSILON) || (d1 < -EPSILON && d2 > EPSILON))
+        && ((d3 > EPSILON && d4 < -EPSILON) || (d3 < -EPSILON && d4 > EPSILON))
+}
+
+fn segments_intersect(a1: &Coordinates, a2: &Coordinates, b1: &Coordinates, b2: &Coordinates) -> bool {
+    if segments_cross(a1, a2, b1, b2) {
+        return true;
+    }
+    on_segment(b1, b2, a1) || on_segment(b1, b2, a2) || on_segment(a1, a2, b1) || on_segment(a1, a2, b2)
+}
+
+fn boxes_overlap(a: &BoundingBox, b: &BoundingBox) -> bool {
+    a.min_lat <= b.max_lat && b.min_lat <= a.max_lat && a.min_lon <= b.max_lon && b.min_lon <= a.max_lon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(lat: f64, lon: f64) -> Coordinates {
+        Coordinates::new_unchecked(lat, lon, None)
+    }
+
+    /// Axis-aligned square ring with south-west corner (lat, lon).
+    fn square(lat: f64, lon: f64, size: f64) -> Vec<Coordinates> {
+        vec![
+            c(lat, lon),
+            c(lat, lon + size),
+            c(lat + size, lon + size),
+            c(lat + size, lon),
+            c(lat, lon),
+        ]
+    }
+
+    #[test]
+    fn test_validate_rejects_open_ring() {
+        let ring = vec![c(0.0, 0.0), c(0.0, 1.0), c(1.0, 1.0), c(1.0, 0.0)];
+        let result = Geometry::Polygon(vec![ring]).validate();
+        assert!(matches!(result, Err(SpatialError::InvalidCoordinates(_))));
+    }
+
+    #[test]
+    fn test_validate_rejects_short_line() {
+        let result = Geometry::LineString(vec![c(0.0, 0.0)]).validate();
+        assert!(matches!(result, Err(SpatialError::InvalidCoordinates(_))));
+    }
+
+    #[test]
+    fn test_point_in_polygon_with_hole() {
+        let polygon = Geometry::Polygon(vec![square(0.0, 0.0, 10.0), square(4.0, 4.0, 2.0)]);
+        assert!(polygon.validate().is_ok());
+
+        assert!(polygon.contains_point(&c(1.0, 1.0)));
+        assert!(!polygon.contains_point(&c(5.0, 5.0)), "point in hole is outside");
+        assert!(polygon.contains_point(&c(4.0, 5.0)), "hole boundary belongs to polygon");
+        assert!(polygon.contains_point(&c(0.0, 5.0)), "exterior boundary is inside");
+        assert!(!polygon.contains_point(&c(11.0, 5.0)));
+    }
+
+    #[test]
+    fn test_polygon_intersections() {
+        let a = Geometry::Polygon(vec![square(0.0, 0.0, 2.0)]);
+        let overlapping = Geometry::Polygon(vec![square(1.0, 1.0, 2.0)]);
+        let disjoint = Geometry::Polygon(vec![square(5.0, 5.0, 1.0)]);
+        let inner = Geometry::Polygon(vec![square(0.5, 0.5, 0.5)]);
+        // Crossing edges with no vertex of either inside the other
+        let cross = Geometry::Polygon(vec![vec![
+            c(0.5, -1.0),
+            c(0.5, 3.0),
+            c(1.5, 3.0),
+            c(1.5, -1.0),
+            c(0.5, -1.0),
+        ]]);
+
+        assert!(a.intersects(&overlapping));
+        assert!(!a.intersects(&disjoint));
+        assert!(a.intersects(&inner) && inner.intersects(&a));
+        assert!(a.intersects(&cross));
+    }
+
+    #[test]
+    fn test_within() {
+        let outer = Geometry::Polygon(vec![square(0.0, 0.0, 10.0)]);
+        let inner = Geometry::Polygon(vec![square(1.0, 1.0, 2.0)]);
+        let straddling = Geometry::Polygon(vec![square(9.0, 9.0, 2.0)]);
+        let line = Geometry::LineString(vec![c(1.0, 1.0), c(9.0, 9.0)]);
+
+        assert!(inner.within(&outer));
+        assert!(!outer.within(&inner));
+        assert!(!straddling.within(&outer));
+        assert!(straddling.intersects(&outer));
+        assert!(line.within(&outer));
+
+        // Concave "U": both ends of the line are inside but it crosses the gap.
+        let u_shape = Geometry::Polygon(vec![vec![
+            c(0.0, 0.0),
+            c(0.0, 3.0),
+            c(3.0, 3.0),
+            c(3.0, 2.0),
+            c(1.0, 2.0),
+            c(1.0, 1.0),
+            c(3.0, 1.0),
+            c(3.0, 0.0),
+            c(0.0, 0.0),
+        ]]);
+        let across_gap = Geometry::LineString(vec![c(2.0, 0.5), c(2.0, 2.5)]);
+        assert!(!across_gap.within(&u_shape));
+
+        // A polygon surrounding the hole of another is not within it.
+        let holed = Geometry::Polygon(vec![square(0.0, 0.0, 10.0), square(4.0, 4.0, 2.0)]);
+        let around_hole = Geometry::Polygon(vec![square(3.0, 3.0, 4.0)]);
+        assert!(!around_hole.within(&holed));
+        assert!(inner.within(&holed));
+    }
+
+    #[test]
+    fn test_line_length() {
+        let london = c(51.5074, -0.1278);
+        let paris = c(48.8566, 2.3522);
+        let line = Geometry::LineString(vec![london.clone(), paris.clone(), london.clone()]);
+        let one_way = haversine_distance(&london, &paris);
+        assert!((line.length_km() - 2.0 * one_way).abs() < 1e-9);
+        assert_eq!(line.area_km2(), 0.0);
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        // One degree square on the equator is ~111.19 km on a side.
+        let cell = Geometry::Polygon(vec![square(0.0, 0.0, 1.0)]);
+        let area = cell.area_km2();
+        assert!((12_300.0..12_400.0).contains(&area), "got {} km²", area);
+
+        let holed = Geometry::Polygon(vec![square(0.0, 0.0, 1.0), square(0.25, 0.25, 0.5)]);
+        assert!(holed.area_km2() < area * 0.8);
+        assert!(holed.length_km() > cell.length_km());
+    }
+
+    #[test]
+    fn test_centroids() {
+        let polygon = Geometry::Polygon(vec![square(0.0, 0.0, 2.0)]);
+        let centroid = polygon.centroid();
+        assert!((centroid.latitude - 1.0).abs() < 1e-9);
+        assert!((centroid.longitude - 1.0).abs() < 1e-9);
+
+        let line = Geometry::LineString(vec![c(0.0, 0.0), c(0.0, 4.0)]);
+        assert!((line.centroid().longitude - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let polygon = Geometry::Polygon(vec![square(0.0, 0.0, 1.0)]);
+        let json = serde_json::to_string(&polygon).unwrap();
+        assert!(json.contains("\"type\":\"Polygon\""));
+        let back: Geometry = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, polygon);
+    }
+}
@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Spatial joins between two entity sets
+//!
+//! Pairs every entity of a left set with the entities of a right set that
+//! satisfy a [`JoinPredicate`].  Candidates are pruned with a latitude band
+//! (distance joins) or bounding box (containment joins) before the exact
+//! test, so joins stay cheap when the sets are spread out, and only as many
+//! pairs as were asked for are held at a time.
+
+use crate::{haversine_distance, SpatialData, EARTH_RADIUS_KM};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Condition a (left, right) pair must satisfy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JoinPredicate {
+    /// Representative points are at most `distance_km` apart
+    WithinDistance { distance_km: f64 },
+    /// The right entity's shape lies inside the left entity's shape
+    Contains,
+}
+
+/// A matching (left, right) pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinPair {
+    /// Entity from the left set
+    pub left_id: String,
+    /// Entity from the right set
+    pub right_id: String,
+    /// Distance between the representative points in kilometres
+    pub distance_km: f64,
+}
+
+/// Join two entity sets, returning at most `limit` pairs ordered by
+/// distance.  An entity appearing in both sets is never paired with itself.
+///
+/// The right set is sorted by latitude once, so each left entity tests only
+/// the right entities in its latitude band (distance joins) or bounding
+/// box's latitude span (containment joins).  Only the `limit` nearest pairs
+/// found so far are kept.
+pub fn spatial_join(
+    left: &[(String, SpatialData)],
+    right: &[(String, SpatialData)],
+    predicate: JoinPredicate,
+    limit: usize,
+) -> Vec<JoinPair> {
+    let mut right_shapes: Vec<_> = right
+        .iter()
+        .map(|(id, data)| {
+            let shape = data.shape();
+            let bounds = shape.bounding_box();
+            let key = match predicate {
+                JoinPredicate::WithinDistance { .. } => data.coordinates.latitude,
+                JoinPredicate::Contains => bounds.min_lat,
+            };
+            (key, id, data, shape, bounds)
+        })
+        .collect();
+    right_shapes.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut nearest = NearestPairs::new(limit);
+    for (left_id, left_data) in left {
+        let left_shape = left_data.shape();
+        let left_bounds = left_shape.bounding_box();
+
+        // Right entities whose sort key lies in [low, high]
+        let (low, high) = match predicate {
+            JoinPredicate::WithinDistance { distance_km } => {
+                // One degree of latitude is the same distance everywhere,
+                // so a latitude gap alone can rule a pair out.
+                let band = (distance_km / EARTH_RADIUS_KM).to_degrees();
+                let latitude = left_data.coordinates.latitude;
+                (latitude - band, latitude + band)
+            }
+            JoinPredicate::Contains => (left_bounds.min_lat, left_bounds.max_lat),
+        };
+        let first = right_shapes.partition_point(|r| r.0 < low);
+
+        for (key, right_id, right_data, right_shape, right_bounds) in &right_shapes[first..] {
+            if *key > high {
+                break;
+            }
+            if left_id == *right_id {
+                continue;
+            }
+            let distance_km = haversine_distance(&left_data.coordinates, &right_data.coordinates);
+            let matched = match predicate {
+                JoinPredicate::WithinDistance { distance_km: within } => distance_km <= within,
+                JoinPredicate::Contains => {
+                    right_bounds.max_lat <= left_bounds.max_lat
+                        && right_bounds.min_lon >= left_bounds.min_lon
+                        && right_bounds.max_lon <= left_bounds.max_lon
+                        && right_shape.within(&left_shape)
+                }
+            };
+            if matched {
+                nearest.offer(JoinPair {
+                    left_id: left_id.clone(),
+                    right_id: (*right_id).clone(),
+                    distance_km,
+                });
+            }
+        }
+    }
+    nearest.into_sorted()
+}
+
+/// The `limit` smallest pairs offered, by distance then ids
+struct NearestPairs {
+    limit: usize,
+    heap: BinaryHeap<Ranked>,
+}
+
+impl NearestPairs {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit.min(1024)),
+        }
+    }
+
+    fn offer(&mut self, pair: JoinPair) {
+        let pair = Ranked(pair);
+        if self.heap.len() < self.limit {
+            self.heap.push(pair);
+        } else if self.heap.peek().is_some_and(|worst| pair < *worst) {
+            self.heap.pop();
+            self.heap.push(pair);
+        }
+    }
+
+    fn into_sorted(self) -> Vec<JoinPair> {
+        self.heap.into_sorted_vec().into_iter().map(|r| r.0).collect()
+    }
+}
+
+/// A pair ordered by distance, then left and right id
+struct Ranked(JoinPair);
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .distance_km
+            .total_cmp(&other.0.distance_km)
+            .then_with(|| self.0.left_id.cmp(&other.0.left_id))
+            .then_with(|| self.0.right_id.cmp(&other.0.right_id))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coordinates, Geometry};
+
+    fn point(id: &str, lat: f64, lon: f64) -> (String, SpatialData) {
+        (id.to_string(), SpatialData::point(lat, lon, None).unwrap())
+    }
+
+    fn square(id: &str, lat: f64, lon: f64, size: f64) -> (String, SpatialData) {
+        let c = |la: f64, lo: f64| Coordinates::new_unchecked(la, lo, None);
+        let ring = vec![
+            c(lat, lon),
+            c(lat, lon + size),
+            c(lat + size, lon + size),
+            c(lat + size, lon),
+            c(lat, lon),
+        ];
+        (
+            id.to_string(),
+            SpatialData::from_geometry(Geometry::Polygon(vec![ring]), 4326).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_within_distance_join() {
+        let warehouses = vec![point("w-london", 51.5074, -0.1278), point("w-paris", 48.8566, 2.3522)];
+        let sensors = vec![
+            point("s-1", 51.51, -0.12),
+            point("s-2", 48.85, 2.35),
+            point("s-3", 40.71, -74.00),
+        ];
+
+        let pairs = spatial_join(
+            &warehouses,
+            &sensors,
+            JoinPredicate::WithinDistance { distance_km: 5.0 },
+            100,
+        );
+        let ids: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|p| (p.left_id.as_str(), p.right_id.as_str()))
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&("w-london", "s-1")));
+        assert!(ids.contains(&("w-paris", "s-2")));
+    }
+
+    #[test]
+    fn test_contains_join() {
+        let sites = vec![square("site-a", 0.0, 0.0, 1.0), square("site-b", 5.0, 5.0, 1.0)];
+        let sensors = vec![
+            point("s-in-a", 0.5, 0.5),
+            point("s-in-b", 5.5, 5.5),
+            point("s-outside", 3.0, 3.0),
+            square("zone-in-a", 0.2, 0.2, 0.3),
+        ];
+
+        let pairs = spatial_join(&sites, &sensors, JoinPredicate::Contains, 100);
+        let mut ids: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|p| (p.left_id.as_str(), p.right_id.as_str()))
+            .collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![("site-a", "s-in-a"), ("site-a", "zone-in-a"), ("site-b", "s-in-b")]
+        );
+    }
+
+    #[test]
+    fn test_join_skips_self_pairs_and_limits() {
+        let set = vec![point("a", 0.0, 0.0), point("b", 0.0, 0.001), point("c", 0.0, 0.002)];
+        let pairs = spatial_join(&set, &set, JoinPredicate::WithinDistance { distance_km: 1.0 }, 3);
+        assert_eq!(pairs.len(), 3);
+        assert!(pairs.iter().all(|p| p.left_id != p.right_id));
+    }
+
+    #[test]
+    fn test_limited_join_keeps_the_nearest_pairs_in_order() {
+        let set: Vec<_> = (0..40)
+            .map(|i| point(&format!("p{i:02}"), (i % 7) as f64 * 0.01, (i / 7) as f64 * 0.013))
+            .collect();
+        let predicate = JoinPredicate::WithinDistance { distance_km: 50.0 };
+        let all = spatial_join(&set, &set, predicate, usize::MAX);
+        assert_eq!(all.len(), 40 * 39);
+        let nearest = spatial_join(&set, &set, predicate, 25);
+        let ids = |pairs: &[JoinPair]| -> Vec<(String, String)> {
+            pairs.iter().map(|p| (p.left_id.clone(), p.right_id.clone())).collect()
+        };
+        assert_eq!(ids(&nearest), ids(&all[..25]));
+        assert!(spatial_join(&set, &set, predicate, 0).is_empty());
+    }
+}
@@ -9,6 +9,14 @@
 //!
 //! - **Coordinates**: Latitude/longitude/altitude tuple in WGS84.
 //! - **GeometryType**: Point, LineString, Polygon, MultiPoint, MultiPolygon.
+//! - **Geometry**: Full coordinate sequences for each geometry type, with
+//!   point-in-polygon, intersection, length and area operations.
+//! - **geojson**: GeoJSON geometry/feature conversion for import and export.
+//! - **geohash**: Cell encoding used for cell lookups and density tiles.
+//! - **join**: Distance and containment joins between two entity sets.
+//! - **cluster**: Grid and DBSCAN clustering for map marker layers.
+//! - **trajectory**: Timestamped position series with point-in-time and
+//!   pass-through queries.
 //! - **SpatialData**: Full spatial description of an entity including
 //!   coordinates, geometry type, SRID, and arbitrary properties.
 //! - **SpatialStore** trait: Async storage with radius search, bounding box
@@ -19,12 +27,28 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::RwLock;
 use tracing::{debug, instrument};
 
+pub mod cluster;
+pub mod geohash;
+pub mod geojson;
+pub use cluster::{Cluster, ClusterMethod};
+pub use geohash::CellCount;
+pub mod geometry;
+pub mod join;
+pub mod trajectory;
+pub mod wkt;
+pub use geometry::Geometry;
+pub use join::{spatial_join, JoinPair, JoinPredicate};
+pub use trajectory::{InMemoryTrajectoryStore, PositionFix, TrajectoryMatch, TrajectoryStore};
+
+/// Mean Earth radius in kilometres (spherical model).
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
 /// Spatial-specific errors
 #[derive(Error, Debug)]
 pub enum SpatialError {
@@ -43,6 +67,10 @@ pub enum SpatialError {
     /// Generic I/O or storage error
     #[error("Spatial I/O error: {0}")]
     IoError(String),
+
+    /// Malformed interchange input (GeoJSON, etc.)
+    #[error("Spatial parse error: {0}")]
+    ParseError(String),
 }
 
 /// A geographic coordinate in WGS84.
@@ -130,8 +158,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 ///
 /// The `coordinates` field holds the representative point (centroid for
 /// complex geometries).  The `geometry_type` and `srid` describe the
-/// coordinate reference context.  Arbitrary `properties` can hold extra
-/// spatial metadata (e.g., address, region name, accuracy).
+/// coordinate reference context.  The full shape, when known, is kept in
+/// `geometry`.  Arbitrary `properties` can hold extra spatial metadata
+/// (e.g., address, region name, accuracy).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpatialData {
     /// Representative coordinates (centroid for complex geometries)
@@ -140,6 +169,9 @@ pub struct SpatialData {
     pub geometry_type: GeometryType,
     /// Spatial Reference System Identifier (default: 4326 = WGS84)
     pub srid: u32,
+    /// Full geometry (absent for plain points indexed by coordinates only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Geometry>,
     /// Arbitrary spatial properties (address, region, accuracy, etc.)
     pub properties: HashMap<String, String>,
 }
@@ -151,6 +183,21 @@ pub fn point(latitude: f64, longitude: f64, altitude: Option<f64>) -> Result<Sel
             coordinates: Coordinates::new(latitude, longitude, altitude)?,
             geometry_type: GeometryType::Point,
             srid: 4326,
+            geometry: None,
+            properties: HashMap::new(),
+        })
+    }
+
+    /// Create spatial data from a full geometry.
+    ///
+    /// The representative coordinates are the geometry's centroid.
+    pub fn from_geometry(geometry: Geometry, srid: u32) -> Result<Self, SpatialError> {
+        geometry.validate()?;
+        Ok(Self {
+            coordinates: geometry.centroid(),
+            geometry_type: geometry.geometry_type(),
+            srid,
+            geometry: Some(geometry),
             properties: HashMap::new(),
         })
     }
@@ -165,6 +212,7 @@ pub fn with_geometry(
             coordinates,
             geometry_type,
             srid,
+            geometry: None,
             properties: HashMap::new(),
         }
     }
@@ -174,6 +222,33 @@ pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>)
         self.properties.insert(key.into(), value.into());
         self
     }
+
+    /// The entity's shape: its full geometry, or its representative point.
+    pub fn shape(&self) -> Geometry {
+        self.geometry
+            .clone()
+            .unwrap_or_else(|| Geometry::Point(self.coordinates.clone()))
+    }
+
+    /// Geodesic length (perimeter for polygons) in kilometres.
+    pub fn length_km(&self) -> f64 {
+        self.geometry.as_ref().map_or(0.0, Geometry::length_km)
+    }
+
+    /// Geodesic area in square kilometres.
+    pub fn area_km2(&self) -> f64 {
+        self.geometry.as_ref().map_or(0.0, Geometry::area_km2)
+    }
+
+    /// Whether `point` lies inside or on the boundary of this entity's shape.
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        self.shape().contains_point(point)
+    }
+
+    /// Whether this entity's shape intersects another's.
+    pub fn intersects(&self, other: &SpatialData) -> bool {
+        self.shape().intersects(&other.shape())
+    }
 }
 
 /// A bounding box for spatial queries.
@@ -202,6 +277,79 @@ pub struct SpatialSearchResult {
     pub distance_km: f64,
 }
 
+/// How entities are matched against a search polygon.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialPredicate {
+    /// The entity's shape lies entirely inside the polygon
+    #[default]
+    Within,
+    /// The entity's shape shares at least one point with the polygon
+    Intersects,
+}
+
+/// How search distances are measured.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMode {
+    /// Great-circle distance along the surface; altitude is ignored
+    #[default]
+    Surface,
+    /// Straight-line distance combining surface distance and altitude
+    /// difference (see [`distance_3d`])
+    #[serde(rename = "3d")]
+    ThreeD,
+}
+
+/// Distance mode and altitude filter for radius and nearest searches.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct SearchOptions {
+    /// How distances are measured
+    #[serde(default)]
+    pub distance_mode: DistanceMode,
+    /// Lowest admitted altitude in metres
+    #[serde(default)]
+    pub min_altitude: Option<f64>,
+    /// Highest admitted altitude in metres
+    #[serde(default)]
+    pub max_altitude: Option<f64>,
+}
+
+impl SearchOptions {
+    /// Check the altitude range is well-formed.
+    pub fn validate(&self) -> Result<(), SpatialError> {
+        if let (Some(min), Some(max)) = (self.min_altitude, self.max_altitude) {
+            if min > max {
+                return Err(SpatialError::InvalidCoordinates(format!(
+                    "Altitude range [{}, {}] is empty",
+                    min, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a point passes the altitude filter.  Points without an
+    /// altitude are excluded whenever either bound is set.
+    pub fn admits(&self, point: &Coordinates) -> bool {
+        if self.min_altitude.is_none() && self.max_altitude.is_none() {
+            return true;
+        }
+        point.altitude.is_some_and(|alt| {
+            self.min_altitude.is_none_or(|min| alt >= min)
+                && self.max_altitude.is_none_or(|max| alt <= max)
+        })
+    }
+
+    /// Distance between two points in kilometres under this mode.
+    pub fn distance(&self, a: &Coordinates, b: &Coordinates) -> f64 {
+        match self.distance_mode {
+            DistanceMode::Surface => haversine_distance(a, b),
+            DistanceMode::ThreeD => distance_3d(a, b),
+        }
+    }
+}
+
 /// Async trait for spatial storage backends.
 ///
 /// Implementations must be `Send + Sync` for safe sharing across Tokio tasks.
@@ -216,13 +364,24 @@ pub trait SpatialStore: Send + Sync {
     /// Delete spatial data for an entity.
     async fn delete(&self, entity_id: &str) -> Result<(), SpatialError>;
 
+    /// List indexed entities ordered by entity ID, for paging through the
+    /// whole index (e.g. export).
+    async fn list(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<(String, SpatialData)>, SpatialError>;
+
     /// Search for entities within a given radius (km) of a point.
     async fn search_radius(
         &self,
         center: &Coordinates,
         radius_km: f64,
         limit: usize,
-    ) -> Result<Vec<SpatialSearchResult>, SpatialError>;
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        self.search_radius_with(center, radius_km, &SearchOptions::default(), limit)
+            .await
+    }
 
     /// Search for entities within a bounding box.
     async fn search_within(
@@ -236,6 +395,53 @@ async fn nearest(
         &self,
         point: &Coordinates,
         k: usize,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        self.nearest_with(point, k, &SearchOptions::default()).await
+    }
+
+    /// Radius search with a distance mode and altitude filter.  In 3D mode
+    /// `radius_km` bounds the straight-line distance.
+    async fn search_radius_with(
+        &self,
+        center: &Coordinates,
+        radius_km: f64,
+        options: &SearchOptions,
+        limit: usize,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError>;
+
+    /// Nearest-neighbour search with a distance mode and altitude filter.
+    async fn nearest_with(
+        &self,
+        point: &Coordinates,
+        k: usize,
+        options: &SearchOptions,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError>;
+
+    /// Find entities whose representative point falls in a geohash cell
+    /// (any prefix length).  Distances are measured from the cell centre.
+    async fn search_cell(
+        &self,
+        geohash: &str,
+        limit: usize,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError>;
+
+    /// Count entities per geohash cell at `precision`, optionally restricted
+    /// to a bounding box.  Cells are returned in geohash order; empty cells
+    /// are omitted.
+    async fn cell_counts(
+        &self,
+        precision: usize,
+        bounds: Option<&BoundingBox>,
+    ) -> Result<Vec<CellCount>, SpatialError>;
+
+    /// Search for entities whose shape is within (or intersects) an
+    /// arbitrary Polygon or MultiPolygon.  Distances are measured from the
+    /// polygon's centroid.
+    async fn search_polygon(
+        &self,
+        polygon: &Geometry,
+        predicate: SpatialPredicate,
+        limit: usize,
     ) -> Result<Vec<SpatialSearchResult>, SpatialError>;
 }
 
@@ -244,8 +450,6 @@ async fn nearest(
 ///
 /// This is accurate to within ~0.5% for most distances on Earth.
 pub fn haversine_distance(a: &Coordinates, b: &Coordinates) -> f64 {
-    const EARTH_RADIUS_KM: f64 = 6371.0;
-
     let lat1 = a.latitude.to_radians();
     let lat2 = b.latitude.to_radians();
     let dlat = (b.latitude - a.latitude).to_radians();
@@ -258,13 +462,32 @@ pub fn haversine_distance(a: &Coordinates, b: &Coordinates) -> f64 {
     EARTH_RADIUS_KM * c
 }
 
+/// Straight-line distance in kilometres between two points, combining the
+/// Haversine surface distance with the altitude difference.
+///
+/// Adequate for the separations radius searches deal in, where Earth's
+/// curvature over the altitude difference is negligible.  If either point
+/// has no altitude this is the surface distance.
+pub fn distance_3d(a: &Coordinates, b: &Coordinates) -> f64 {
+    let surface = haversine_distance(a, b);
+    match (a.altitude, b.altitude) {
+        (Some(x), Some(y)) => surface.hypot((x - y) / 1000.0),
+        _ => surface,
+    }
+}
+
 /// In-memory implementation of [`SpatialStore`].
 ///
 /// Uses brute-force distance computation for searches.  Suitable for
 /// development, testing, and small-to-medium datasets.  A production
 /// deployment should use an R-tree or similar spatial index.
+///
+/// Every entity's representative point is also kept in an ordered geohash
+/// index, so cell lookups and per-cell counts are prefix range scans.
 pub struct InMemorySpatialStore {
     data: Arc<RwLock<HashMap<String, SpatialData>>>,
+    /// (full-precision geohash, entity id), ordered for prefix scans
+    cells: Arc<RwLock<BTreeSet<(String, String)>>>,
 }
 
 impl InMemorySpatialStore {
@@ -272,6 +495,7 @@ impl InMemorySpatialStore {
     pub fn new() -> Self {
         Self {
             data: Arc::new(RwLock::new(HashMap::new())),
+            cells: Arc::new(RwLock::new(BTreeSet::new())),
         }
     }
 }
@@ -295,9 +519,18 @@ async fn index(&self, entity_id: &str, data: SpatialData) -> Result<(), SpatialE
                 data.coordinates.latitude, data.coordinates.longitude
             )));
         }
+        if let Some(geometry) = &data.geometry {
+            geometry.validate()?;
+        }
 
+        let cell = geohash::encode(&data.coordinates, geohash::MAX_PRECISION);
         let mut store = self.data.write().await;
-        store.insert(entity_id.to_string(), data);
+        let mut cells = self.cells.write().await;
+        if let Some(previous) = store.insert(entity_id.to_string(), data) {
+            let old = geohash::encode(&previous.coordinates, geohash::MAX_PRECISION);
+            cells.remove(&(old, entity_id.to_string()));
+        }
+        cells.insert((cell, entity_id.to_string()));
         debug!(entity_id = %entity_id, "Spatial data indexed");
         Ok(())
     }
@@ -309,21 +542,44 @@ async fn get(&self, entity_id: &str) -> Result<Option<SpatialData>, SpatialError
 
     async fn delete(&self, entity_id: &str) -> Result<(), SpatialError> {
         let mut store = self.data.write().await;
-        store.remove(entity_id);
+        let mut cells = self.cells.write().await;
+        if let Some(previous) = store.remove(entity_id) {
+            let old = geohash::encode(&previous.coordinates, geohash::MAX_PRECISION);
+            cells.remove(&(old, entity_id.to_string()));
+        }
         Ok(())
     }
 
-    async fn search_radius(
+    async fn list(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<(String, SpatialData)>, SpatialError> {
+        let store = self.data.read().await;
+        let mut ids: Vec<&String> = store.keys().collect();
+        ids.sort();
+        Ok(ids
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|id| (id.clone(), store[id].clone()))
+            .collect())
+    }
+
+    async fn search_radius_with(
         &self,
         center: &Coordinates,
         radius_km: f64,
+        options: &SearchOptions,
         limit: usize,
     ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        options.validate()?;
         let store = self.data.read().await;
         let mut results: Vec<SpatialSearchResult> = store
             .iter()
+            .filter(|(_, data)| options.admits(&data.coordinates))
             .filter_map(|(id, data)| {
-                let dist = haversine_distance(center, &data.coordinates);
+                let dist = options.distance(center, &data.coordinates);
                 if dist <= radius_km {
                     Some(SpatialSearchResult {
                         entity_id: id.clone(),
@@ -389,18 +645,21 @@ async fn search_within(
         Ok(results)
     }
 
-    async fn nearest(
+    async fn nearest_with(
         &self,
         point: &Coordinates,
         k: usize,
+        options: &SearchOptions,
     ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        options.validate()?;
         let store = self.data.read().await;
         let mut results: Vec<SpatialSearchResult> = store
             .iter()
+            .filter(|(_, data)| options.admits(&data.coordinates))
             .map(|(id, data)| SpatialSearchResult {
                 entity_id: id.clone(),
                 data: data.clone(),
-                distance_km: haversine_distance(point, &data.coordinates),
+                distance_km: options.distance(point, &data.coordinates),
             })
             .collect();
 
@@ -412,6 +671,121 @@ async fn nearest(
         results.truncate(k);
         Ok(results)
     }
+
+    async fn search_cell(
+        &self,
+        geohash: &str,
+        limit: usize,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        let center = geohash::decode(geohash)?;
+        let prefix = geohash.to_ascii_lowercase();
+        let store = self.data.read().await;
+        let cells = self.cells.read().await;
+
+        let mut results: Vec<SpatialSearchResult> = cells
+            .range((prefix.clone(), String::new())..)
+            .take_while(|(cell, _)| cell.starts_with(&prefix))
+            .filter_map(|(_, id)| {
+                store.get(id).map(|data| SpatialSearchResult {
+                    entity_id: id.clone(),
+                    data: data.clone(),
+                    distance_km: haversine_distance(&center, &data.coordinates),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            a.distance_km
+                .partial_cmp(&b.distance_km)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn cell_counts(
+        &self,
+        precision: usize,
+        bounds: Option<&BoundingBox>,
+    ) -> Result<Vec<CellCount>, SpatialError> {
+        geohash::validate_precision(precision)?;
+        let store = self.data.read().await;
+        let cells = self.cells.read().await;
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for (cell, id) in cells.iter() {
+            if let Some(b) = bounds {
+                let Some(data) = store.get(id) else { continue };
+                let (lat, lon) = (data.coordinates.latitude, data.coordinates.longitude);
+                if lat < b.min_lat || lat > b.max_lat || lon < b.min_lon || lon > b.max_lon {
+                    continue;
+                }
+            }
+            *counts.entry(&cell[..precision]).or_insert(0) += 1;
+        }
+
+        counts
+            .into_iter()
+            .map(|(cell, count)| {
+                Ok(CellCount {
+                    geohash: cell.to_string(),
+                    count,
+                    bounds: geohash::decode_bounds(cell)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn search_polygon(
+        &self,
+        polygon: &Geometry,
+        predicate: SpatialPredicate,
+        limit: usize,
+    ) -> Result<Vec<SpatialSearchResult>, SpatialError> {
+        if !polygon.is_areal() {
+            return Err(SpatialError::InvalidCoordinates(format!(
+                "Search area must be a Polygon or MultiPolygon, got {}",
+                polygon.geometry_type()
+            )));
+        }
+        polygon.validate()?;
+
+        let center = polygon.centroid();
+        let bounds = polygon.bounding_box();
+        let store = self.data.read().await;
+        let mut results: Vec<SpatialSearchResult> = store
+            .iter()
+            .filter_map(|(id, data)| {
+                let shape = data.shape();
+                // Cheap rejection before the exact predicate
+                let b = shape.bounding_box();
+                if b.min_lat > bounds.max_lat
+                    || b.max_lat < bounds.min_lat
+                    || b.min_lon > bounds.max_lon
+                    || b.max_lon < bounds.min_lon
+                {
+                    return None;
+                }
+                let matched = match predicate {
+                    SpatialPredicate::Within => shape.within(polygon),
+                    SpatialPredicate::Intersects => shape.intersects(polygon),
+                };
+                matched.then(|| SpatialSearchResult {
+                    entity_id: id.clone(),
+                    data: data.clone(),
+                    distance_km: haversine_distance(&center, &data.coordinates),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            a.distance_km
+                .partial_cmp(&b.distance_km)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+        Ok(results)
+    }
 }
 
 #[cfg(test)]
@@ -531,6 +905,61 @@ async fn test_in_memory_store_radius_search() {
         assert_eq!(results[0].entity_id, "london", "London should be closest");
     }
 
+    #[test]
+    fn test_distance_3d() {
+        let ground = Coordinates::new_unchecked(51.5, -0.12, Some(0.0));
+        let drone = Coordinates::new_unchecked(51.5, -0.12, Some(3000.0));
+        assert!((distance_3d(&ground, &drone) - 3.0).abs() < 1e-9);
+        // Without altitude on either side it falls back to surface distance.
+        let flat = Coordinates::new_unchecked(51.5, -0.12, None);
+        assert_eq!(distance_3d(&flat, &drone), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_radius_and_nearest_with_altitude() {
+        let store = InMemorySpatialStore::new();
+        for (id, alt) in [("ground", Some(0.0)), ("mast", Some(120.0)), ("aircraft", Some(9000.0)), ("unknown", None)] {
+            store
+                .index(id, SpatialData::point(51.5, -0.12, alt).unwrap())
+                .await
+                .unwrap();
+        }
+        let observer = Coordinates::new(51.5, -0.12, Some(0.0)).unwrap();
+
+        let three_d = SearchOptions {
+            distance_mode: DistanceMode::ThreeD,
+            ..Default::default()
+        };
+        let results = store.search_radius_with(&observer, 1.0, &three_d, 10).await.unwrap();
+        let mut ids: Vec<&str> = results.iter().map(|r| r.entity_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["ground", "mast", "unknown"]);
+
+        // Surface mode ignores altitude entirely.
+        let results = store.search_radius(&observer, 1.0, 10).await.unwrap();
+        assert_eq!(results.len(), 4);
+
+        let low_level = SearchOptions {
+            min_altitude: Some(50.0),
+            max_altitude: Some(500.0),
+            ..three_d
+        };
+        let results = store.nearest_with(&observer, 10, &low_level).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entity_id, "mast");
+        assert!((results[0].distance_km - 0.12).abs() < 1e-9);
+
+        let empty = SearchOptions {
+            min_altitude: Some(10.0),
+            max_altitude: Some(0.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            store.nearest_with(&observer, 10, &empty).await,
+            Err(SpatialError::InvalidCoordinates(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_in_memory_store_bounding_box() {
         let store = InMemorySpatialStore::new();
@@ -596,10 +1025,160 @@ async fn test_in_memory_store_invalid_coordinates() {
             coordinates: Coordinates::new_unchecked(999.0, 0.0, None),
             geometry_type: GeometryType::Point,
             srid: 4326,
+            geometry: None,
             properties: HashMap::new(),
         };
 
         let result = store.index("bad", data).await;
         assert!(matches!(result, Err(SpatialError::InvalidCoordinates(_))));
     }
+
+    #[tokio::test]
+    async fn test_in_memory_store_polygon_geometry() {
+        let store = InMemorySpatialStore::new();
+        let ring = vec![
+            Coordinates::new_unchecked(51.0, -1.0, None),
+            Coordinates::new_unchecked(51.0, 1.0, None),
+            Coordinates::new_unchecked(52.0, 1.0, None),
+            Coordinates::new_unchecked(52.0, -1.0, None),
+            Coordinates::new_unchecked(51.0, -1.0, None),
+        ];
+        let data = SpatialData::from_geometry(Geometry::Polygon(vec![ring]), 4326).unwrap();
+        assert_eq!(data.geometry_type, GeometryType::Polygon);
+        assert!((data.coordinates.latitude - 51.5).abs() < 1e-9);
+
+        store.index("region", data).await.unwrap();
+        let retrieved = store.get("region").await.unwrap().unwrap();
+        let london = Coordinates::new_unchecked(51.5074, -0.1278, None);
+        let paris = Coordinates::new_unchecked(48.8566, 2.3522, None);
+        assert!(retrieved.contains(&london));
+        assert!(!retrieved.contains(&paris));
+        assert!(retrieved.area_km2() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_list_pages_in_id_order() {
+        let store = InMemorySpatialStore::new();
+        for id in ["c", "a", "b"] {
+            store
+                .index(id, SpatialData::point(10.0, 10.0, None).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let first: Vec<String> = store.list(2, 0).await.unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(first, vec!["a", "b"]);
+        let rest = store.list(2, 2).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].0, "c");
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_polygon_search() {
+        let store = InMemorySpatialStore::new();
+        let ring = |min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64| {
+            vec![
+                Coordinates::new_unchecked(min_lat, min_lon, None),
+                Coordinates::new_unchecked(min_lat, max_lon, None),
+                Coordinates::new_unchecked(max_lat, max_lon, None),
+                Coordinates::new_unchecked(max_lat, min_lon, None),
+                Coordinates::new_unchecked(min_lat, min_lon, None),
+            ]
+        };
+
+        store
+            .index("london", SpatialData::point(51.5074, -0.1278, None).unwrap())
+            .await
+            .unwrap();
+        store
+            .index("paris", SpatialData::point(48.8566, 2.3522, None).unwrap())
+            .await
+            .unwrap();
+        // A region straddling the English Channel
+        store
+            .index(
+                "channel",
+                SpatialData::from_geometry(Geometry::Polygon(vec![ring(49.5, -1.0, 51.0, 1.5)]), 4326)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Triangle over south-east England
+        let england = Geometry::Polygon(vec![vec![
+            Coordinates::new_unchecked(50.5, -2.0, None),
+            Coordinates::new_unchecked(50.5, 1.5, None),
+            Coordinates::new_unchecked(53.0, -1.0, None),
+            Coordinates::new_unchecked(50.5, -2.0, None),
+        ]]);
+
+        let within = store
+            .search_polygon(&england, SpatialPredicate::Within, 10)
+            .await
+            .unwrap();
+        assert_eq!(within.len(), 1);
+        assert_eq!(within[0].entity_id, "london");
+
+        let intersecting = store
+            .search_polygon(&england, SpatialPredicate::Intersects, 10)
+            .await
+            .unwrap();
+        let mut ids: Vec<&str> = intersecting.iter().map(|r| r.entity_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["channel", "london"]);
+
+        let not_areal = Geometry::Point(Coordinates::new_unchecked(0.0, 0.0, None));
+        assert!(matches!(
+            store.search_polygon(&not_areal, SpatialPredicate::Within, 10).await,
+            Err(SpatialError::InvalidCoordinates(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_geohash_cells() {
+        let store = InMemorySpatialStore::new();
+        // Two points in central London, one in Paris
+        store
+            .index("london-a", SpatialData::point(51.5074, -0.1278, None).unwrap())
+            .await
+            .unwrap();
+        store
+            .index("london-b", SpatialData::point(51.5155, -0.0922, None).unwrap())
+            .await
+            .unwrap();
+        store
+            .index("paris", SpatialData::point(48.8566, 2.3522, None).unwrap())
+            .await
+            .unwrap();
+
+        let london = store.search_cell("gcpv", 10).await.unwrap();
+        assert_eq!(london.len(), 2);
+
+        let counts = store.cell_counts(3, None).await.unwrap();
+        let summary: Vec<(&str, usize)> = counts.iter().map(|c| (c.geohash.as_str(), c.count)).collect();
+        assert_eq!(summary, vec![("gcp", 2), ("u09", 1)]);
+
+        // Re-indexing moves the entity to its new cell.
+        store
+            .index("london-b", SpatialData::point(48.86, 2.35, None).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(store.search_cell("gcpv", 10).await.unwrap().len(), 1);
+
+        store.delete("paris").await.unwrap();
+        let europe = BoundingBox {
+            min_lat: 45.0,
+            min_lon: -5.0,
+            max_lat: 50.0,
+            max_lon: 10.0,
+        };
+        let counts = store.cell_counts(1, Some(&europe)).await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].count, 1);
+
+        assert!(matches!(
+            store.cell_counts(0, None).await,
+            Err(SpatialError::InvalidCoordinates(_))
+        ));
+    }
 }
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Trajectories: timestamped position series
+//!
+//! A trajectory is the ordered sequence of position fixes recorded for one
+//! entity, stored as temporal [`TimePoint`]s so the same [`TimeRange`]
+//! vocabulary used for version history applies to movement.  Between two
+//! fixes an entity is assumed to move in a straight line at constant speed,
+//! which is what "where was it at 14:03" and "did it pass through this box"
+//! are answered against.
+
+use crate::{Coordinates, Geometry, SpatialError};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument};
+use verisim_temporal::{TimePoint, TimeRange};
+
+/// One timestamped position fix.
+pub type PositionFix = TimePoint<Coordinates>;
+
+/// Fixes of one entity keyed by time.
+type Series = BTreeMap<DateTime<Utc>, PositionFix>;
+
+/// An entity whose trajectory met a query area.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrajectoryMatch {
+    /// Entity ID
+    pub entity_id: String,
+    /// Time of the first fix of the first segment that met the area
+    pub first_seen: DateTime<Utc>,
+    /// Time of the last fix of the last segment that met the area
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Async trait for trajectory storage backends.
+#[async_trait]
+pub trait TrajectoryStore: Send + Sync {
+    /// Record a position fix.  A fix at an existing timestamp replaces it.
+    async fn record(&self, entity_id: &str, fix: PositionFix) -> Result<(), SpatialError>;
+
+    /// Fixes for an entity within a time range, in time order.
+    async fn trajectory(
+        &self,
+        entity_id: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<PositionFix>, SpatialError>;
+
+    /// Estimated position at `time`, interpolated between the surrounding
+    /// fixes.  `None` before the first fix; the last fix after the end.
+    async fn position_at(
+        &self,
+        entity_id: &str,
+        time: DateTime<Utc>,
+    ) -> Result<Option<Coordinates>, SpatialError>;
+
+    /// Entities whose path met `area` during `range`.
+    async fn passed_through(
+        &self,
+        area: &Geometry,
+        range: &TimeRange,
+        limit: usize,
+    ) -> Result<Vec<TrajectoryMatch>, SpatialError>;
+
+    /// Drop every fix recorded for an entity.
+    async fn delete(&self, entity_id: &str) -> Result<(), SpatialError>;
+}
+
+/// Linear interpolation between two fixes at `time` (which must lie between them).
+fn interpolate(a: &PositionFix, b: &PositionFix, time: DateTime<Utc>) -> Coordinates {
+    let span = (b.time - a.time).num_milliseconds() as f64;
+    if span <= 0.0 {
+        return a.value.clone();
+    }
+    let t = (time - a.time).num_milliseconds() as f64 / span;
+    let altitude = match (a.value.altitude, b.value.altitude) {
+        (Some(x), Some(y)) => Some(x + (y - x) * t),
+        _ => None,
+    };
+    Coordinates::new_unchecked(
+        a.value.latitude + (b.value.latitude - a.value.latitude) * t,
+        a.value.longitude + (b.value.longitude - a.value.longitude) * t,
+        altitude,
+    )
+}
+
+/// In-memory implementation of [`TrajectoryStore`].
+#[derive(Default)]
+pub struct InMemoryTrajectoryStore {
+    series: Arc<RwLock<HashMap<String, Series>>>,
+}
+
+impl InMemoryTrajectoryStore {
+    /// Create a new empty trajectory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fixes of one series clipped to `range`, extended with the
+    /// interpolated positions at the range boundaries so that segments
+    /// crossing a boundary are still considered.
+    fn clip(series: &Series, range: &TimeRange) -> Vec<PositionFix> {
+        let mut fixes: Vec<PositionFix> = series
+            .range(range.start..range.end)
+            .map(|(_, f)| f.clone())
+            .collect();
+
+        let before = series.range(..range.start).next_back().map(|(_, f)| f);
+        if let (Some(before), Some(first)) = (before, series.range(range.start..).next().map(|(_, f)| f)) {
+            if first.time > range.start {
+                fixes.insert(0, PositionFix::new(range.start, interpolate(before, first, range.start)));
+            }
+        }
+        let last = series.range(..range.end).next_back().map(|(_, f)| f);
+        if let (Some(last), Some(after)) = (last, series.range(range.end..).next().map(|(_, f)| f)) {
+            if !fixes.is_empty() {
+                fixes.push(PositionFix::new(range.end, interpolate(last, after, range.end)));
+            }
+        }
+        fixes
+    }
+}
+
+#[async_trait]
+impl TrajectoryStore for InMemoryTrajectoryStore {
+    #[instrument(skip(self, fix))]
+    async fn record(&self, entity_id: &str, fix: PositionFix) -> Result<(), SpatialError> {
+        let c = &fix.value;
+        Coordinates::new(c.latitude, c.longitude, c.altitude)?;
+
+        let mut series = self.series.write().await;
+        series
+            .entry(entity_id.to_string())
+            .or_default()
+            .insert(fix.time, fix);
+        debug!(entity_id = %entity_id, "Position fix recorded");
+        Ok(())
+    }
+
+    async fn trajectory(
+        &self,
+        entity_id: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<PositionFix>, SpatialError> {
+        let series = self.series.read().await;
+        Ok(series
+            .get(entity_id)
+            .map(|s| {
+                s.range(range.start..range.end)
+                    .map(|(_, f)| f.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn position_at(
+        &self,
+        entity_id: &str,
+        time: DateTime<Utc>,
+    ) -> Result<Option<Coordinates>, SpatialError> {
+        let series = self.series.read().await;
+        let Some(s) = series.get(entity_id) else {
+            return Ok(None);
+        };
+        let before = s.range(..=time).next_back().map(|(_, f)| f);
+        let after = s.range(time..).next().map(|(_, f)| f);
+        Ok(match (before, after) {
+            (Some(a), Some(b)) => Some(interpolate(a, b, time)),
+            (Some(a), None) => Some(a.value.clone()),
+            (None, _) => None,
+        })
+    }
+
+    async fn passed_through(
+        &self,
+        area: &Geometry,
+        range: &TimeRange,
+        limit: usize,
+    ) -> Result<Vec<TrajectoryMatch>, SpatialError> {
+        area.validate()?;
+        let series = self.series.read().await;
+
+        let mut matches: Vec<TrajectoryMatch> = series
+            .iter()
+            .filter_map(|(id, s)| {
+                let fixes = Self::clip(s, range);
+                let hits: Vec<(DateTime<Utc>, DateTime<Utc>)> = if fixes.len() == 1 {
+                    area.contains_point(&fixes[0].value)
+                        .then(|| (fixes[0].time, fixes[0].time))
+                        .into_iter()
+                        .collect()
+                } else {
+                    fixes
+                        .windows(2)
+                        .filter(|w| {
+                            Geometry::LineString(vec![w[0].value.clone(), w[1].value.clone()])
+                                .intersects(area)
+                        })
+                        .map(|w| (w[0].time, w[1].time))
+                        .collect()
+                };
+                let first = hits.first()?.0;
+                let last = hits.last()?.1;
+                Some(TrajectoryMatch {
+                    entity_id: id.clone(),
+                    first_seen: first,
+                    last_seen: last,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| a.first_seen.cmp(&b.first_seen).then_with(|| a.entity_id.cmp(&b.entity_id)));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    async fn delete(&self, entity_id: &str) -> Result<(), SpatialError> {
+        self.series.write().await.remove(entity_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 14, minute, 0).unwrap()
+    }
+
+    fn fix(minute: u32, lat: f64, lon: f64) -> PositionFix {
+        PositionFix::new(at(minute), Coordinates::new_unchecked(lat, lon, None))
+    }
+
+    fn square(lat: f64, lon: f64, size: f64) -> Geometry {
+        let c = |la: f64, lo: f64| Coordinates::new_unchecked(la, lo, None);
+        Geometry::Polygon(vec![vec![
+            c(lat, lon),
+            c(lat, lon + size),
+            c(lat + size, lon + size),
+            c(lat + size, lon),
+            c(lat, lon),
+        ]])
+    }
+
+    #[tokio::test]
+    async fn test_position_at_interpolates() {
+        let store = InMemoryTrajectoryStore::new();
+        store.record("sensor-7", fix(0, 0.0, 0.0)).await.unwrap();
+        store.record("sensor-7", fix(10, 1.0, 2.0)).await.unwrap();
+
+        let mid = store.position_at("sensor-7", at(3)).await.unwrap().unwrap();
+        assert!((mid.latitude - 0.3).abs() < 1e-9);
+        assert!((mid.longitude - 0.6).abs() < 1e-9);
+
+        assert!(store.position_at("sensor-7", at(0) - Duration::minutes(1)).await.unwrap().is_none());
+        let after = store.position_at("sensor-7", at(30)).await.unwrap().unwrap();
+        assert_eq!(after.latitude, 1.0);
+        assert!(store.position_at("unknown", at(3)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trajectory_range() {
+        let store = InMemoryTrajectoryStore::new();
+        for m in 0..6 {
+            store.record("truck", fix(m * 10, m as f64, 0.0)).await.unwrap();
+        }
+        let range = TimeRange::new(at(10), at(40)).unwrap();
+        let fixes = store.trajectory("truck", &range).await.unwrap();
+        let times: Vec<_> = fixes.iter().map(|f| f.time).collect();
+        assert_eq!(times, vec![at(10), at(20), at(30)]);
+    }
+
+    #[tokio::test]
+    async fn test_passed_through_detects_crossing_between_fixes() {
+        let store = InMemoryTrajectoryStore::new();
+        // Crosses the box between two fixes without a fix inside it.
+        store.record("crosser", fix(0, 0.5, -1.0)).await.unwrap();
+        store.record("crosser", fix(10, 0.5, 2.0)).await.unwrap();
+        // Stays well away.
+        store.record("bystander", fix(0, 5.0, 5.0)).await.unwrap();
+        store.record("bystander", fix(10, 6.0, 6.0)).await.unwrap();
+        // Enters the box only after the query window.
+        store.record("late", fix(20, 3.0, 0.5)).await.unwrap();
+        store.record("late", fix(40, 0.5, 0.5)).await.unwrap();
+
+        let area = square(0.0, 0.0, 1.0);
+        let range = TimeRange::new(at(0), at(25)).unwrap();
+        let matches = store.passed_through(&area, &range, 10).await.unwrap();
+        let ids: Vec<&str> = matches.iter().map(|m| m.entity_id.as_str()).collect();
+        assert_eq!(ids, vec!["crosser"]);
+        assert_eq!(matches[0].first_seen, at(0));
+
+        let whole_day = TimeRange::new(at(0), at(59)).unwrap();
+        let matches = store.passed_through(&area, &whole_day, 10).await.unwrap();
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_rejects_invalid_coordinates() {
+        let store = InMemoryTrajectoryStore::new();
+        let result = store.record("x", fix(0, 91.0, 0.0)).await;
+        assert!(matches!(result, Err(SpatialError::InvalidCoordinates(_))));
+    }
+}
@@ -0,0 +1,634 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//! Well-Known Text and Well-Known Binary
+//!
+//! Reads and writes the OGC WKT/WKB encodings used by PostGIS and most GIS
+//! tooling.  Positions are `x y [z]`, i.e. longitude, latitude, altitude.
+//! The PostGIS extended forms are accepted on input: an `SRID=n;` prefix on
+//! WKT, and the EWKB Z/SRID flag bits on WKB.  Output is plain ISO WKT and
+//! little-endian ISO WKB, with Z only when every position has an altitude.
+//!
+//! Malformed input is reported as [`SpatialError::InvalidCoordinates`].
+
+use crate::{Coordinates, Geometry, SpatialError};
+
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOINT: u32 = 4;
+const WKB_MULTIPOLYGON: u32 = 6;
+/// ISO WKB adds 1000 to the type code for Z geometries.
+const WKB_ISO_Z_OFFSET: u32 = 1000;
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+fn invalid(message: impl Into<String>) -> SpatialError {
+    SpatialError::InvalidCoordinates(message.into())
+}
+
+impl Geometry {
+    /// Encode as WKT.
+    pub fn to_wkt(&self) -> String {
+        let z = self.has_z();
+        let tag = if z { " Z " } else { " " };
+        let body = match self {
+            Geometry::Point(p) => format!("({})", wkt_position(p, z)),
+            Geometry::LineString(points) => wkt_sequence(points, z),
+            Geometry::MultiPoint(points) => format!(
+                "({})",
+                points
+                    .iter()
+                    .map(|p| format!("({})", wkt_position(p, z)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Geometry::Polygon(rings) => wkt_rings(rings, z),
+            Geometry::MultiPolygon(polygons) => format!(
+                "({})",
+                polygons
+                    .iter()
+                    .map(|p| wkt_rings(p, z))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        format!("{}{}{}", self.geometry_type().to_string().to_uppercase(), tag, body)
+    }
+
+    /// Decode WKT (or PostGIS EWKT, whose SRID is ignored; see
+    /// [`parse_ewkt`] to keep it).
+    pub fn from_wkt(text: &str) -> Result<Self, SpatialError> {
+        parse_ewkt(text).map(|(geometry, _)| geometry)
+    }
+
+    /// Encode as little-endian ISO WKB.
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_wkb(self, self.has_z(), &mut out);
+        out
+    }
+
+    /// Decode WKB or PostGIS EWKB (SRID ignored; see [`parse_ewkb`]).
+    pub fn from_wkb(bytes: &[u8]) -> Result<Self, SpatialError> {
+        parse_ewkb(bytes).map(|(geometry, _)| geometry)
+    }
+
+    /// Encode as hex-encoded WKB, the form PostGIS prints for geometry columns.
+    pub fn to_wkb_hex(&self) -> String {
+        hex::encode_upper(self.to_wkb())
+    }
+
+    /// Decode hex-encoded WKB or EWKB (SRID ignored; see [`parse_ewkb_hex`]).
+    pub fn from_wkb_hex(text: &str) -> Result<Self, SpatialError> {
+        parse_ewkb_hex(text).map(|(geometry, _)| geometry)
+    }
+
+    fn has_z(&self) -> bool {
+        let positions = self.positions();
+        !positions.is_empty() && positions.iter().all(|p| p.altitude.is_some())
+    }
+}
+
+/// Decode WKT, returning the SRID from an `SRID=n;` prefix if present.
+pub fn parse_ewkt(text: &str) -> Result<(Geometry, Option<u32>), SpatialError> {
+    let text = text.trim();
+    let (srid, wkt) = match text.split_once(';') {
+        Some((prefix, rest)) if prefix.trim().to_ascii_uppercase().starts_with("SRID=") => {
+            let srid = prefix.trim()[5..]
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| invalid(format!("WKT: invalid SRID prefix '{}'", prefix)))?;
+            (Some(srid), rest)
+        }
+        _ => (None, text),
+    };
+
+    let mut parser = WktParser::new(wkt);
+    let geometry = parser.geometry()?;
+    parser.skip_ws();
+    if parser.pos < parser.input.len() {
+        return Err(invalid(format!(
+            "WKT: unexpected trailing input at offset {}",
+            parser.pos
+        )));
+    }
+    geometry.validate()?;
+    Ok((geometry, srid))
+}
+
+/// Decode WKB, returning the SRID from EWKB if present.
+pub fn parse_ewkb(bytes: &[u8]) -> Result<(Geometry, Option<u32>), SpatialError> {
+    let mut reader = WkbReader { bytes, pos: 0 };
+    let (geometry, srid) = reader.geometry()?;
+    if reader.pos != bytes.len() {
+        return Err(invalid("WKB: trailing bytes after geometry"));
+    }
+    geometry.validate()?;
+    Ok((geometry, srid))
+}
+
+/// Decode hex-encoded WKB, returning the SRID from EWKB if present.
+pub fn parse_ewkb_hex(text: &str) -> Result<(Geometry, Option<u32>), SpatialError> {
+    let bytes = hex::decode(text.trim()).map_err(|e| invalid(format!("WKB: invalid hex: {}", e)))?;
+    parse_ewkb(&bytes)
+}
+
+// ---------------------------------------------------------------------------
+// WKT output
+// ---------------------------------------------------------------------------
+
+fn wkt_position(p: &Coordinates, z: bool) -> String {
+    match (z, p.altitude) {
+        (true, Some(alt)) => format!("{} {} {}", p.longitude, p.latitude, alt),
+        _ => format!("{} {}", p.longitude, p.latitude),
+    }
+}
+
+fn wkt_sequence(points: &[Coordinates], z: bool) -> String {
+    format!(
+        "({})",
+        points
+            .iter()
+            .map(|p| wkt_position(p, z))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn wkt_rings(rings: &[Vec<Coordinates>], z: bool) -> String {
+    format!(
+        "({})",
+        rings
+            .iter()
+            .map(|r| wkt_sequence(r, z))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// WKT input
+// ---------------------------------------------------------------------------
+
+struct WktParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WktParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn word(&mut self) -> String {
+        self.skip_ws();
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.input[start..self.pos]).to_ascii_uppercase()
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), SpatialError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(invalid(format!(
+                "WKT: expected '{}' at offset {}",
+                c as char, self.pos
+            )))
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, SpatialError> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.pos < self.input.len()
+            && matches!(self.input[self.pos], b'0'..=b'9' | b'.' | b'-' | b'+' | b'e' | b'E')
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .ok_or_else(|| invalid(format!("WKT: expected a number at offset {}", start)))
+    }
+
+    /// `x y [z] [m]`.  Untagged positions with three ordinates are XYZ;
+    /// measures are read and discarded.
+    fn position(&mut self, measured: bool) -> Result<Coordinates, SpatialError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        let mut extra = Vec::new();
+        while extra.len() < 2 && matches!(self.peek(), Some(b'0'..=b'9' | b'.' | b'-' | b'+')) {
+            extra.push(self.number()?);
+        }
+        let altitude = match (measured, extra.len()) {
+            (true, 1) => None,
+            _ => extra.first().copied(),
+        };
+        Coordinates::new(y, x, altitude)
+    }
+
+    fn sequence(&mut self, measured: bool) -> Result<Vec<Coordinates>, SpatialError> {
+        self.expect(b'(')?;
+        let mut points = vec![self.position(measured)?];
+        while self.peek() == Some(b',') {
+            self.pos += 1;
+            points.push(self.position(measured)?);
+        }
+        self.expect(b')')?;
+        Ok(points)
+    }
+
+    fn rings(&mut self, measured: bool) -> Result<Vec<Vec<Coordinates>>, SpatialError> {
+        self.expect(b'(')?;
+        let mut rings = vec![self.sequence(measured)?];
+        while self.peek() == Some(b',') {
+            self.pos += 1;
+            rings.push(self.sequence(measured)?);
+        }
+        self.expect(b')')?;
+        Ok(rings)
+    }
+
+    /// MultiPoint members may be written `(1 2, 3 4)` or `((1 2), (3 4))`.
+    fn multipoint(&mut self, measured: bool) -> Result<Vec<Coordinates>, SpatialError> {
+        self.expect(b'(')?;
+        let mut points = Vec::new();
+        loop {
+            if self.peek() == Some(b'(') {
+                self.pos += 1;
+                points.push(self.position(measured)?);
+                self.expect(b')')?;
+            } else {
+                points.push(self.position(measured)?);
+            }
+            if self.peek() == Some(b',') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        self.expect(b')')?;
+        Ok(points)
+    }
+
+    fn geometry(&mut self) -> Result<Geometry, SpatialError> {
+        let kind = self.word();
+        let measured = match self.word().as_str() {
+            "" | "Z" | "ZM" => false,
+            "M" => true,
+            other => return Err(invalid(format!("WKT: unknown dimension tag '{}'", other))),
+        };
+        if self.word() == "EMPTY" {
+            return Err(invalid(format!("WKT: empty {} is not supported", kind)));
+        }
+
+        match kind.as_str() {
+            "POINT" => {
+                self.expect(b'(')?;
+                let p = self.position(measured)?;
+                self.expect(b')')?;
+                Ok(Geometry::Point(p))
+            }
+            "LINESTRING" => Ok(Geometry::LineString(self.sequence(measured)?)),
+            "MULTIPOINT" => Ok(Geometry::MultiPoint(self.multipoint(measured)?)),
+            "POLYGON" => Ok(Geometry::Polygon(self.rings(measured)?)),
+            "MULTIPOLYGON" => {
+                self.expect(b'(')?;
+                let mut polygons = vec![self.rings(measured)?];
+                while self.peek() == Some(b',') {
+                    self.pos += 1;
+                    polygons.push(self.rings(measured)?);
+                }
+                self.expect(b')')?;
+                Ok(Geometry::MultiPolygon(polygons))
+            }
+            "" => Err(invalid("WKT: missing geometry type")),
+            other => Err(invalid(format!("WKT: unsupported geometry type {}", other))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WKB
+// ---------------------------------------------------------------------------
+
+fn write_wkb(geometry: &Geometry, z: bool, out: &mut Vec<u8>) {
+    let base = match geometry {
+        Geometry::Point(_) => WKB_POINT,
+        Geometry::LineString(_) => WKB_LINESTRING,
+        Geometry::Polygon(_) => WKB_POLYGON,
+        Geometry::MultiPoint(_) => WKB_MULTIPOINT,
+        Geometry::MultiPolygon(_) => WKB_MULTIPOLYGON,
+    };
+    out.push(1); // little-endian
+    let code = if z { base + WKB_ISO_Z_OFFSET } else { base };
+    out.extend_from_slice(&code.to_le_bytes());
+
+    let position = |p: &Coordinates, out: &mut Vec<u8>| {
+        out.extend_from_slice(&p.longitude.to_le_bytes());
+        out.extend_from_slice(&p.latitude.to_le_bytes());
+        if z {
+            out.extend_from_slice(&p.altitude.unwrap_or(0.0).to_le_bytes());
+        }
+    };
+    let count = |n: usize, out: &mut Vec<u8>| out.extend_from_slice(&(n as u32).to_le_bytes());
+
+    match geometry {
+        Geometry::Point(p) => position(p, out),
+        Geometry::LineString(points) => {
+            count(points.len(), out);
+            points.iter().for_each(|p| position(p, out));
+        }
+        Geometry::Polygon(rings) => {
+            count(rings.len(), out);
+            for ring in rings {
+                count(ring.len(), out);
+                ring.iter().for_each(|p| position(p, out));
+            }
+        }
+        Geometry::MultiPoint(points) => {
+            count(points.len(), out);
+            points
+                .iter()
+                .for_each(|p| write_wkb(&Geometry::Point(p.clone()), z, out));
+        }
+        Geometry::MultiPolygon(polygons) => {
+            count(polygons.len(), out);
+            polygons
+                .iter()
+                .for_each(|p| write_wkb(&Geometry::Polygon(p.clone()), z, out));
+        }
+    }
+}
+
+/// Byte order, base type, dimensions and SRID read ahead of a WKB geometry
+struct WkbHeader {
+    little: bool,
+    base: u32,
+    z: bool,
+    m: bool,
+    srid: Option<u32>,
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], SpatialError> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| invalid("WKB: unexpected end of input"))?;
+        self.pos += N;
+        Ok(slice.try_into().expect("slice length checked"))
+    }
+
+    fn u32(&mut self, little: bool) -> Result<u32, SpatialError> {
+        let b = self.take::<4>()?;
+        Ok(if little { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn f64(&mut self, little: bool) -> Result<f64, SpatialError> {
+        let b = self.take::<8>()?;
+        Ok(if little { f64::from_le_bytes(b) } else { f64::from_be_bytes(b) })
+    }
+
+    /// Element counts are bounded by the remaining input so corrupt headers
+    /// cannot trigger huge allocations.
+    fn count(&mut self, little: bool) -> Result<usize, SpatialError> {
+        let n = self.u32(little)? as usize;
+        if n > self.bytes.len() - self.pos {
+            return Err(invalid("WKB: element count exceeds input size"));
+        }
+        Ok(n)
+    }
+
+    fn position(&mut self, little: bool, z: bool, m: bool) -> Result<Coordinates, SpatialError> {
+        let x = self.f64(little)?;
+        let y = self.f64(little)?;
+        let altitude = if z { Some(self.f64(little)?) } else { None };
+        if m {
+            self.f64(little)?;
+        }
+        Coordinates::new(y, x, altitude)
+    }
+
+    fn positions(&mut self, little: bool, z: bool, m: bool) -> Result<Vec<Coordinates>, SpatialError> {
+        let n = self.count(little)?;
+        (0..n).map(|_| self.position(little, z, m)).collect()
+    }
+
+    fn rings(&mut self, little: bool, z: bool, m: bool) -> Result<Vec<Vec<Coordinates>>, SpatialError> {
+        let n = self.count(little)?;
+        (0..n).map(|_| self.positions(little, z, m)).collect()
+    }
+
+    fn header(&mut self) -> Result<WkbHeader, SpatialError> {
+        let little = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            other => return Err(invalid(format!("WKB: invalid byte order marker {}", other))),
+        };
+        let raw = self.u32(little)?;
+        let srid = if raw & EWKB_SRID_FLAG != 0 {
+            Some(self.u32(little)?)
+        } else {
+            None
+        };
+        let flags_z = raw & EWKB_Z_FLAG != 0;
+        let flags_m = raw & EWKB_M_FLAG != 0;
+        let iso = raw & 0x0FFF_FFFF;
+        let (base, iso_z, iso_m) = match iso / 1000 {
+            0 => (iso, false, false),
+            1 => (iso % 1000, true, false),
+            2 => (iso % 1000, false, true),
+            3 => (iso % 1000, true, true),
+            _ => return Err(invalid(format!("WKB: unknown geometry type code {}", raw))),
+        };
+        Ok(WkbHeader {
+            little,
+            base,
+            z: flags_z || iso_z,
+            m: flags_m || iso_m,
+            srid,
+        })
+    }
+
+    /// Members of a multi-geometry are read as their one permitted type
+    /// rather than as any geometry, so nesting cannot recurse.
+    fn member(&mut self, expected: u32, message: &str) -> Result<WkbHeader, SpatialError> {
+        let header = self.header()?;
+        if header.base != expected {
+            return Err(invalid(message));
+        }
+        Ok(header)
+    }
+
+    fn geometry(&mut self) -> Result<(Geometry, Option<u32>), SpatialError> {
+        let WkbHeader { little, base, z, m, srid } = self.header()?;
+
+        let geometry = match base {
+            WKB_POINT => Geometry::Point(self.position(little, z, m)?),
+            WKB_LINESTRING => Geometry::LineString(self.positions(little, z, m)?),
+            WKB_POLYGON => Geometry::Polygon(self.rings(little, z, m)?),
+            WKB_MULTIPOINT => {
+                let n = self.count(little)?;
+                let mut points = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let member = self.member(WKB_POINT, "WKB: MultiPoint member is not a Point")?;
+                    points.push(self.position(member.little, member.z, member.m)?);
+                }
+                Geometry::MultiPoint(points)
+            }
+            WKB_MULTIPOLYGON => {
+                let n = self.count(little)?;
+                let mut polygons = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let member = self.member(WKB_POLYGON, "WKB: MultiPolygon member is not a Polygon")?;
+                    polygons.push(self.rings(member.little, member.z, member.m)?);
+                }
+                Geometry::MultiPolygon(polygons)
+            }
+            other => return Err(invalid(format!("WKB: unsupported geometry type {}", other))),
+        };
+        Ok((geometry, srid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wkt_roundtrip() {
+        let polygon = Geometry::from_wkt(
+            "POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0), (4 4, 6 4, 6 6, 4 6, 4 4))",
+        )
+        .unwrap();
+        match &polygon {
+            Geometry::Polygon(rings) => {
+                assert_eq!(rings.len(), 2);
+                assert_eq!(rings[0][1].longitude, 10.0);
+                assert_eq!(rings[0][1].latitude, 0.0);
+            }
+            other => panic!("expected polygon, got {:?}", other),
+        }
+        assert_eq!(Geometry::from_wkt(&polygon.to_wkt()).unwrap(), polygon);
+    }
+
+    #[test]
+    fn test_wkt_z_and_ewkt_srid() {
+        let (point, srid) = parse_ewkt("SRID=4326;POINT Z (-0.1278 51.5074 35)").unwrap();
+        assert_eq!(srid, Some(4326));
+        assert_eq!(point, Geometry::Point(Coordinates::new_unchecked(51.5074, -0.1278, Some(35.0))));
+        assert_eq!(point.to_wkt(), "POINT Z (-0.1278 51.5074 35)");
+
+        // Measures are not altitudes.
+        let line = Geometry::from_wkt("LINESTRING M (0 0 7, 1 1 8)").unwrap();
+        assert!(line.positions().iter().all(|p| p.altitude.is_none()));
+    }
+
+    #[test]
+    fn test_wkt_multipoint_forms() {
+        let a = Geometry::from_wkt("MULTIPOINT (1 2, 3 4)").unwrap();
+        let b = Geometry::from_wkt("MULTIPOINT ((1 2), (3 4))").unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_wkt_errors_are_invalid_coordinates() {
+        for bad in [
+            "POINT (1)",
+            "POLYGON ((0 0, 1 0, 1 1))",
+            "CIRCLE (0 0 1)",
+            "POINT (0 95)",
+            "POINT (1 2) trailing",
+            "POINT EMPTY",
+        ] {
+            assert!(
+                matches!(Geometry::from_wkt(bad), Err(SpatialError::InvalidCoordinates(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_wkb_roundtrip() {
+        for wkt in [
+            "POINT (2.3522 48.8566)",
+            "POINT Z (2.3522 48.8566 35)",
+            "LINESTRING (0 0, 1 1, 2 0)",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))",
+            "MULTIPOINT ((1 2), (3 4))",
+        ] {
+            let geometry = Geometry::from_wkt(wkt).unwrap();
+            assert_eq!(Geometry::from_wkb(&geometry.to_wkb()).unwrap(), geometry, "{}", wkt);
+        }
+    }
+
+    #[test]
+    fn test_ewkb_big_endian_with_srid() {
+        // SRID=4326;POINT(1 2) as big-endian EWKB
+        let mut bytes = vec![0u8];
+        bytes.extend_from_slice(&(WKB_POINT | EWKB_SRID_FLAG).to_be_bytes());
+        bytes.extend_from_slice(&4326u32.to_be_bytes());
+        bytes.extend_from_slice(&1.0f64.to_be_bytes());
+        bytes.extend_from_slice(&2.0f64.to_be_bytes());
+
+        let (point, srid) = parse_ewkb(&bytes).unwrap();
+        assert_eq!(Geometry::from_wkb_hex(&hex::encode(&bytes)).unwrap(), point);
+        assert_eq!(srid, Some(4326));
+        assert_eq!(point, Geometry::Point(Coordinates::new_unchecked(2.0, 1.0, None)));
+    }
+
+    #[test]
+    fn test_wkb_errors_are_invalid_coordinates() {
+        assert!(matches!(Geometry::from_wkb(&[1, 1, 0]), Err(SpatialError::InvalidCoordinates(_))));
+        let mut huge = vec![1u8];
+        huge.extend_from_slice(&WKB_LINESTRING.to_le_bytes());
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(Geometry::from_wkb(&huge), Err(SpatialError::InvalidCoordinates(_))));
+    }
+
+    #[test]
+    fn test_wkb_nested_multi_geometries_are_rejected() {
+        // A MultiPoint whose member is a MultiPoint, nested far deeper than
+        // the stack could follow
+        let mut nested = Vec::new();
+        for _ in 0..1_000_000 {
+            nested.push(1u8);
+            nested.extend_from_slice(&WKB_MULTIPOINT.to_le_bytes());
+            nested.extend_from_slice(&1u32.to_le_bytes());
+        }
+        assert!(matches!(Geometry::from_wkb(&nested), Err(SpatialError::InvalidCoordinates(_))));
+
+        let mut polygons = vec![1u8];
+        polygons.extend_from_slice(&WKB_MULTIPOLYGON.to_le_bytes());
+        polygons.extend_from_slice(&1u32.to_le_bytes());
+        polygons.push(1);
+        polygons.extend_from_slice(&WKB_MULTIPOLYGON.to_le_bytes());
+        polygons.extend_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(Geometry::from_wkb(&polygons), Err(SpatialError::InvalidCoordinates(_))));
+    }
+}
@@ -0,0 +1,395 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// Local disk cache for VeriSimDB storage backends.
+//
+// Wraps a slow or remote `StorageBackend` (typically the object store) with
+// a read-through, write-through cache of values in a local directory. The
+// inner backend stays authoritative; the cache only saves round trips, and
+// survives restarts when its directory does.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::backend::StorageBackend;
+use crate::error::StorageError;
+
+/// Keys longer than this bypass the cache, so their file names stay within
+/// common file-system limits.
+pub const MAX_CACHED_KEY_LEN: usize = 100;
+
+/// A cached value on disk.
+#[derive(Debug, Clone, Copy)]
+struct CachedEntry {
+    /// Value size in bytes.
+    size: u64,
+    /// When the value was last used, as a tick of [`CacheIndex::clock`].
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    entries: HashMap<Vec<u8>, CachedEntry>,
+    /// Total size of the cached values in bytes.
+    total_bytes: u64,
+    clock: u64,
+}
+
+/// Cache statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cached values.
+    pub entries: usize,
+    /// Total size of the cached values in bytes.
+    pub bytes: u64,
+}
+
+/// A storage backend wrapper that caches values in a local directory.
+///
+/// Reads are served from the cache when possible and fill it on a miss;
+/// writes and deletes go to the inner backend first and then to the cache.
+/// Prefix scans always go to the inner backend. With a capacity set, the
+/// least recently used values are evicted to stay within it.
+///
+/// The cache assumes it is the only writer to the inner backend: values
+/// changed there by someone else are served stale until evicted.
+///
+/// # Example
+///
+/// ```rust
+/// use verisim_storage::cache::CachedBackend;
+/// use verisim_storage::memory::InMemoryBackend;
+/// use verisim_storage::backend::StorageBackend;
+///
+/// # tokio_test::block_on(async {
+/// let dir = tempfile::tempdir().unwrap();
+/// let cached = CachedBackend::new(InMemoryBackend::new(), dir.path()).unwrap();
+///
+/// cached.put(b"key", b"value").await.unwrap();
+/// assert_eq!(cached.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+/// assert_eq!(cached.stats().await.entries, 1);
+/// # });
+/// ```
+pub struct CachedBackend<B: StorageBackend> {
+    /// The authoritative backend.
+    inner: B,
+    /// Directory cached values are kept in, one file per key.
+    dir: PathBuf,
+    /// Maximum total size of cached values in bytes, if limited.
+    capacity: Option<u64>,
+    index: Mutex<CacheIndex>,
+    name: String,
+}
+
+impl<B: StorageBackend> CachedBackend<B> {
+    /// Cache `inner` in `dir`, creating it if needed.
+    ///
+    /// Values already cached in `dir` by an earlier run are used.
+    pub fn new(inner: B, dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let mut index = CacheIndex::default();
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(key) = name.to_str().and_then(decode_key) else {
+                // Not a cache file, e.g. a write interrupted by a crash.
+                continue;
+            };
+            let size = entry.metadata()?.len();
+            index.total_bytes += size;
+            index.entries.insert(key, CachedEntry { size, last_used: 0 });
+        }
+        let name = format!("cached-{}", inner.name());
+        Ok(Self {
+            inner,
+            dir,
+            capacity: None,
+            index: Mutex::new(index),
+            name,
+        })
+    }
+
+    /// Keep at most `bytes` of cached values.
+    pub fn with_capacity(mut self, bytes: u64) -> Self {
+        self.capacity = Some(bytes);
+        self
+    }
+
+    /// Return a reference to the inner backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Return the number and total size of cached values.
+    pub async fn stats(&self) -> CacheStats {
+        let index = self.index.lock().await;
+        CacheStats {
+            entries: index.entries.len(),
+            bytes: index.total_bytes,
+        }
+    }
+
+    /// Drop every cached value. The inner backend is untouched.
+    pub async fn clear(&self) -> Result<(), StorageError> {
+        let mut index = self.index.lock().await;
+        for key in index.entries.keys() {
+            remove_file(&self.path(key))?;
+        }
+        *index = CacheIndex::default();
+        Ok(())
+    }
+
+    fn path(&self, key: &[u8]) -> PathBuf {
+        self.dir.join(encode_key(key))
+    }
+
+    /// Read a cached value, if there is one.
+    async fn read_cached(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut index = self.index.lock().await;
+        index.clock += 1;
+        let clock = index.clock;
+        let entry = index.entries.get_mut(key)?;
+        entry.last_used = clock;
+        match std::fs::read(self.path(key)) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // Removed behind our back: forget it and read through.
+                warn!(error = %e, "Cached value unreadable");
+                if let Some(entry) = index.entries.remove(key) {
+                    index.total_bytes -= entry.size;
+                }
+                None
+            }
+        }
+    }
+
+    /// Cache `value` under `key`, evicting older values past the capacity.
+    ///
+    /// Failing to cache is not an error: the value is in the inner backend.
+    async fn write_cached(&self, key: &[u8], value: &[u8]) {
+        if key.len() > MAX_CACHED_KEY_LEN || self.capacity.is_some_and(|capacity| value.len() as u64 > capacity) {
+            return;
+        }
+        let mut index = self.index.lock().await;
+        let path = self.path(key);
+        let tmp = self.dir.join(format!(".{}.tmp", encode_key(key)));
+        if let Err(e) = std::fs::write(&tmp, value).and_then(|()| std::fs::rename(&tmp, &path)) {
+            warn!(error = %e, "Failed to cache value");
+            let _ = std::fs::remove_file(&tmp);
+            return;
+        }
+        index.clock += 1;
+        let entry = CachedEntry {
+            size: value.len() as u64,
+            last_used: index.clock,
+        };
+        index.total_bytes += entry.size;
+        if let Some(previous) = index.entries.insert(key.to_vec(), entry) {
+            index.total_bytes -= previous.size;
+        }
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while index.total_bytes > capacity {
+            let Some(oldest) = index
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(entry) = index.entries.remove(&oldest) {
+                index.total_bytes -= entry.size;
+            }
+            if let Err(e) = remove_file(&self.path(&oldest)) {
+                warn!(error = %e, "Failed to evict cached value");
+            }
+        }
+    }
+
+    async fn remove_cached(&self, key: &[u8]) -> Result<(), StorageError> {
+        let mut index = self.index.lock().await;
+        if let Some(entry) = index.entries.remove(key) {
+            index.total_bytes -= entry.size;
+            remove_file(&self.path(key))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<B: StorageBackend> StorageBackend for CachedBackend<B> {
+    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        if let Some(value) = self.read_cached(key).await {
+            return Ok(Some(value));
+        }
+        let value = self.inner.get(key).await?;
+        if let Some(value) = &value {
+            self.write_cached(key, value).await;
+        }
+        Ok(value)
+    }
+
+    async fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.inner.put(key, value).await?;
+        self.write_cached(key, value).await;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<bool, StorageError> {
+        let existed = self.inner.delete(key).await?;
+        self.remove_cached(key).await?;
+        Ok(existed)
+    }
+
+    async fn exists(&self, key: &[u8]) -> Result<bool, StorageError> {
+        if self.index.lock().await.entries.contains_key(key) {
+            return Ok(true);
+        }
+        self.inner.exists(key).await
+    }
+
+    async fn scan_prefix(
+        &self,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        self.inner.scan_prefix(prefix, limit).await
+    }
+
+    async fn multi_get(&self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StorageError> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
+
+    async fn batch_put(&self, entries: &[(&[u8], &[u8])]) -> Result<(), StorageError> {
+        self.inner.batch_put(entries).await?;
+        for (key, value) in entries {
+            self.write_cached(key, value).await;
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), StorageError> {
+        self.inner.flush().await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn approximate_size(&self) -> Result<Option<u64>, StorageError> {
+        self.inner.approximate_size().await
+    }
+}
+
+/// File name a key is cached under: the key in lowercase hex.
+fn encode_key(key: &[u8]) -> String {
+    key.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_key(name: &str) -> Option<Vec<u8>> {
+    if name.is_empty() || !name.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..name.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(name.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn remove_file(path: &Path) -> Result<(), StorageError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryBackend;
+    use crate::metrics::MetricsBackend;
+
+    #[tokio::test]
+    async fn test_reads_through_and_writes_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = InMemoryBackend::new();
+        inner.put(b"remote", b"value").await.unwrap();
+        let cached = CachedBackend::new(MetricsBackend::new(inner), dir.path()).unwrap();
+        assert_eq!(cached.name(), "cached-in-memory");
+
+        // A miss fills the cache; the next read does not reach the inner backend.
+        assert_eq!(cached.get(b"remote").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cached.get(b"remote").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cached.inner().stats().await.get_count, 1);
+
+        cached.put(b"local", b"written").await.unwrap();
+        assert_eq!(cached.inner().inner().get(b"local").await.unwrap(), Some(b"written".to_vec()));
+        assert_eq!(cached.stats().await, CacheStats { entries: 2, bytes: 12 });
+
+        assert!(cached.delete(b"local").await.unwrap());
+        assert!(!cached.exists(b"local").await.unwrap());
+        assert_eq!(cached.get(b"missing").await.unwrap(), None);
+        assert_eq!(cached.stats().await.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = CachedBackend::new(InMemoryBackend::new(), dir.path())
+            .unwrap()
+            .with_capacity(10);
+        cached.put(b"a", b"1234").await.unwrap();
+        cached.put(b"b", b"1234").await.unwrap();
+        cached.get(b"a").await.unwrap();
+        cached.put(b"c", b"1234").await.unwrap();
+
+        assert_eq!(cached.stats().await, CacheStats { entries: 2, bytes: 8 });
+        assert!(!dir.path().join(encode_key(b"b")).exists());
+        // Evicted values are still in the inner backend.
+        assert_eq!(cached.get(b"b").await.unwrap(), Some(b"1234".to_vec()));
+
+        // Values larger than the whole cache are not cached.
+        cached.put(b"big", &[0; 11]).await.unwrap();
+        assert!(!dir.path().join(encode_key(b"big")).exists());
+    }
+
+    #[tokio::test]
+    async fn test_cache_survives_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = InMemoryBackend::new();
+        {
+            let cached = CachedBackend::new(inner.clone(), dir.path()).unwrap();
+            cached.put(b"kept", b"value").await.unwrap();
+        }
+        std::fs::write(dir.path().join("not-hex"), b"ignored").unwrap();
+
+        let cached = CachedBackend::new(MetricsBackend::new(inner), dir.path()).unwrap();
+        assert_eq!(cached.stats().await, CacheStats { entries: 1, bytes: 5 });
+        assert_eq!(cached.get(b"kept").await.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(cached.inner().stats().await.get_count, 0);
+
+        cached.clear().await.unwrap();
+        assert_eq!(cached.stats().await, CacheStats::default());
+        assert_eq!(cached.get(b"kept").await.unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_key_encoding_round_trips() {
+        for key in [&b"wal/wal-0001.log"[..], &[0, 255, 16]] {
+            assert_eq!(decode_key(&encode_key(key)).unwrap(), key);
+        }
+        assert!(decode_key("abc").is_none());
+        assert!(decode_key("zz").is_none());
+    }
+}
@@ -16,6 +16,9 @@
 //   ephemeral workloads.
 // - [`typed`] -- A serde-based typed wrapper with namespace prefixing.
 // - [`metrics`] -- A transparent wrapper that collects operation statistics.
+// - [`cache`] -- A wrapper that caches values of a remote backend on local disk.
+// - `object_store` -- An S3-compatible object-store backend (feature
+//   `object-store`).
 //
 // # Example
 //
@@ -40,6 +43,7 @@
 // ```
 
 pub mod backend;
+pub mod cache;
 pub mod error;
 pub mod memory;
 pub mod metrics;
@@ -48,9 +52,12 @@
 // Optional persistent backends — feature-gated to keep the default build lean.
 #[cfg(feature = "redb-backend")]
 pub mod redb_backend;
+#[cfg(feature = "object-store")]
+pub mod object_store;
 
 // Re-export the most commonly used types at the crate root for convenience.
 pub use backend::StorageBackend;
+pub use cache::{CacheStats, CachedBackend};
 pub use error::StorageError;
 pub use memory::InMemoryBackend;
 pub use metrics::{BackendStats, MetricsBackend};
@@ -58,3 +65,5 @@
 
 #[cfg(feature = "redb-backend")]
 pub use redb_backend::RedbBackend;
+#[cfg(feature = "object-store")]
+pub use object_store::{ObjectInfo, ObjectStoreBackend, ObjectStoreConfig};
@@ -6,6 +6,12 @@
 
 pub mod diff;
 
+// Re-export redb backend when feature is enabled
+#[cfg(feature = "redb-backend")]
+mod redb_backend;
+#[cfg(feature = "redb-backend")]
+pub use redb_backend::RedbTimeSeriesStore;
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -30,6 +36,9 @@ pub enum TemporalError {
 
     #[error("Lock poisoned: internal concurrency error")]
     LockPoisoned,
+
+    #[error("Storage error: {0}")]
+    StoreError(String),
 }
 
 /// A timestamped version of an entity
@@ -158,6 +167,10 @@ pub trait TemporalStore: Send + Sync {
     /// Get version history
     async fn history(&self, entity_id: &str, limit: usize) -> Result<Vec<Version<Self::Data>>, TemporalError>;
 
+    /// Replace an entity's history with `versions`, keeping their numbers
+    /// and timestamps, e.g. when restoring a backup
+    async fn import_history(&self, entity_id: &str, versions: Vec<Version<Self::Data>>) -> Result<(), TemporalError>;
+
     /// Diff two versions
     async fn diff(&self, entity_id: &str, v1: u64, v2: u64) -> Result<diff::Diff<Self::Data>, TemporalError>
     where
@@ -283,6 +296,15 @@ async fn history(&self, entity_id: &str, limit: usize) -> Result<Vec<Version<Sel
             })
             .unwrap_or_default())
     }
+
+    async fn import_history(&self, entity_id: &str, versions: Vec<Version<Self::Data>>) -> Result<(), TemporalError> {
+        let mut store = self.versions.write().map_err(|_| TemporalError::LockPoisoned)?;
+        store.insert(
+            entity_id.to_string(),
+            versions.into_iter().map(|v| (v.version, v)).collect(),
+        );
+        Ok(())
+    }
 }
 
 /// Time-series store for metrics
@@ -298,6 +320,10 @@ pub trait TimeSeriesStore: Send + Sync {
 
     /// Get the latest point
     async fn latest(&self, series_id: &str) -> Result<Option<TimePoint<Self::Value>>, TemporalError>;
+
+    /// Remove the points of a series older than `before`, returning how
+    /// many were removed
+    async fn prune(&self, series_id: &str, before: DateTime<Utc>) -> Result<usize, TemporalError>;
 }
 
 /// In-memory time series store
@@ -347,6 +373,18 @@ async fn latest(&self, series_id: &str) -> Result<Option<TimePoint<Self::Value>>
         let store = self.series.read().map_err(|_| TemporalError::LockPoisoned)?;
         Ok(store.get(series_id).and_then(|points| points.last().cloned()))
     }
+
+    async fn prune(&self, series_id: &str, before: DateTime<Utc>) -> Result<usize, TemporalError> {
+        let mut store = self.series.write().map_err(|_| TemporalError::LockPoisoned)?;
+        Ok(store
+            .get_mut(series_id)
+            .map(|points| {
+                let count = points.len();
+                points.retain(|p| p.time >= before);
+                count - points.len()
+            })
+            .unwrap_or(0))
+    }
 }
 
 #[cfg(test)]
@@ -371,6 +409,20 @@ async fn test_version_store() {
         assert_eq!(v1_data.data, "data v1");
     }
 
+    #[tokio::test]
+    async fn test_import_history_keeps_numbering_and_timestamps() {
+        let store: InMemoryVersionStore<String> = InMemoryVersionStore::new();
+        store.append("entity1", "data v1".to_string(), "alice", Some("initial")).await.unwrap();
+        store.append("entity1", "data v2".to_string(), "bob", Some("update")).await.unwrap();
+
+        let history: Vec<_> = store.history("entity1", usize::MAX).await.unwrap().into_iter().rev().collect();
+        let restored: InMemoryVersionStore<String> = InMemoryVersionStore::new();
+        restored.import_history("entity1", history).await.unwrap();
+        let latest = restored.latest("entity1").await.unwrap().unwrap();
+        assert_eq!((latest.version, latest.timestamp), (2, store.latest("entity1").await.unwrap().unwrap().timestamp));
+        assert_eq!(restored.append("entity1", "data v3".to_string(), "carol", None).await.unwrap(), 3);
+    }
+
     #[tokio::test]
     async fn test_time_series() {
         let store: InMemoryTimeSeriesStore<f64> = InMemoryTimeSeriesStore::new();
@@ -381,5 +433,12 @@ async fn test_time_series() {
 
         let latest = store.latest("cpu").await.unwrap().unwrap();
         assert!((latest.value - 0.6).abs() < f64::EPSILON);
+
+        let old = Utc::now() - chrono::Duration::hours(2);
+        store.append("cpu", TimePoint::new(old, 0.1)).await.unwrap();
+        let cutoff = Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(store.prune("cpu", cutoff).await.unwrap(), 1);
+        assert_eq!(store.query("cpu", &TimeRange::last(chrono::Duration::hours(3))).await.unwrap().len(), 3);
+        assert_eq!(store.prune("missing", cutoff).await.unwrap(), 0);
     }
 }
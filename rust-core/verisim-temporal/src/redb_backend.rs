@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <j.d.a.jewell@open.ac.uk>
+//
+// redb-backed persistent time-series store.
+//
+// This module is only compiled when the `redb-backend` feature is enabled.
+//
+// # Storage Design
+//
+// A single redb table holds every series:
+//
+//   Key: `"{series_id}\0{time}{seq}"` where `time` is the point's timestamp in
+//        nanoseconds (8 bytes, big-endian, sign bit flipped so pre-epoch
+//        times sort first) and `seq` is an 8-byte tie-breaker so points with
+//        the same timestamp do not overwrite each other.
+//   Value: JSON-serialised `TimePoint<T>`
+//
+// Keys sort by series then time, so a range query is one ordered scan.
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redb::{Database, ReadableDatabase, TableDefinition};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{TemporalError, TimePoint, TimeRange, TimeSeriesStore};
+
+/// Time-series points: composite series/time key → serialised TimePoint.
+const POINTS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("points");
+
+/// Separator byte between the series id and the time key.
+const SEP: u8 = 0x00;
+
+/// A persistent time-series store backed by redb.
+///
+/// Same `TimeSeriesStore` interface as [`crate::InMemoryTimeSeriesStore`],
+/// with points surviving restarts.
+pub struct RedbTimeSeriesStore<T> {
+    db: Arc<Database>,
+    #[allow(dead_code)]
+    path: PathBuf,
+    seq: AtomicU64,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> RedbTimeSeriesStore<T> {
+    /// Open or create a persistent time-series store at the given path.
+    pub fn persistent(path: impl AsRef<Path>) -> Result<Self, TemporalError> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| TemporalError::StoreError(format!("create dirs: {e}")))?;
+        }
+
+        let db = Database::create(&path)
+            .map_err(|e| TemporalError::StoreError(format!("open redb: {e}")))?;
+
+        // Seed the tie-breaker from the clock so it keeps increasing across
+        // restarts without a stored counter.
+        let seed = Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+
+        Ok(Self {
+            db: Arc::new(db),
+            path,
+            seq: AtomicU64::new(seed),
+            _value: PhantomData,
+        })
+    }
+
+    /// Order-preserving encoding of a timestamp.
+    fn time_key(time: &DateTime<Utc>) -> Result<[u8; 8], TemporalError> {
+        let nanos = time.timestamp_nanos_opt().ok_or_else(|| {
+            TemporalError::InvalidTimeRange(format!("{time} is outside the storable range"))
+        })?;
+        Ok(((nanos as u64) ^ (1 << 63)).to_be_bytes())
+    }
+
+    /// Build a key prefix `"{series_id}\0{time}"`.
+    fn series_time_key(series_id: &str, time: &DateTime<Utc>) -> Result<Vec<u8>, TemporalError> {
+        let mut key = Vec::with_capacity(series_id.len() + 17);
+        key.extend_from_slice(series_id.as_bytes());
+        key.push(SEP);
+        key.extend_from_slice(&Self::time_key(time)?);
+        Ok(key)
+    }
+
+    /// Build the prefix shared by every key of a series: `"{series_id}\0"`.
+    fn series_prefix(series_id: &str) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(series_id.len() + 1);
+        prefix.extend_from_slice(series_id.as_bytes());
+        prefix.push(SEP);
+        prefix
+    }
+}
+
+impl<T: DeserializeOwned> RedbTimeSeriesStore<T> {
+    /// Deserialise a TimePoint from JSON bytes.
+    fn deserialise_point(bytes: &[u8]) -> Result<TimePoint<T>, TemporalError> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| TemporalError::StoreError(format!("deserialise point: {e}")))
+    }
+}
+
+#[async_trait]
+impl<T> TimeSeriesStore for RedbTimeSeriesStore<T>
+where
+    T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Value = T;
+
+    async fn append(&self, series_id: &str, point: TimePoint<Self::Value>) -> Result<(), TemporalError> {
+        let db = Arc::clone(&self.db);
+        let mut key = Self::series_time_key(series_id, &point.time)?;
+        key.extend_from_slice(&self.seq.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+        let bytes = serde_json::to_vec(&point)
+            .map_err(|e| TemporalError::StoreError(format!("serialise point: {e}")))?;
+
+        tokio::task::spawn_blocking(move || -> Result<(), TemporalError> {
+            let txn = db
+                .begin_write()
+                .map_err(|e| TemporalError::StoreError(format!("write txn: {e}")))?;
+            {
+                let mut points = txn
+                    .open_table(POINTS)
+                    .map_err(|e| TemporalError::StoreError(format!("open points: {e}")))?;
+                points
+                    .insert(key.as_slice(), bytes.as_slice())
+                    .map_err(|e| TemporalError::StoreError(format!("insert point: {e}")))?;
+            }
+            txn.commit()
+                .map_err(|e| TemporalError::StoreError(format!("commit: {e}")))?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| TemporalError::StoreError(format!("spawn_blocking: {e}")))?
+    }
+
+    async fn query(&self, series_id: &str, range: &TimeRange) -> Result<Vec<TimePoint<Self::Value>>, TemporalError> {
+        let db = Arc::clone(&self.db);
+        let start = Self::series_time_key(series_id, &range.start)?;
+        let end = Self::series_time_key(series_id, &range.end)?;
+
+        tokio::task::spawn_blocking(move || -> Result<Vec<TimePoint<T>>, TemporalError> {
+            let txn = db
+                .begin_read()
+                .map_err(|e| TemporalError::StoreError(format!("read txn: {e}")))?;
+            let points = match txn.open_table(POINTS) {
+                Ok(t) => t,
+                Err(_) => return Ok(Vec::new()),
+            };
+            // `end` sorts before every key carrying the end timestamp, which
+            // keeps the range end-exclusive like `TimeRange::contains`.
+            let iter = points
+                .range(start.as_slice()..end.as_slice())
+                .map_err(|e| TemporalError::StoreError(format!("range scan: {e}")))?;
+            let mut result = Vec::new();
+            for entry in iter {
+                let entry = entry.map_err(|e| TemporalError::StoreError(format!("range entry: {e}")))?;
+                result.push(Self::deserialise_point(entry.1.value())?);
+            }
+            Ok(result)
+        })
+        .await
+        .map_err(|e| TemporalError::StoreError(format!("spawn_blocking: {e}")))?
+    }
+
+    async fn latest(&self, series_id: &str) -> Result<Option<TimePoint<Self::Value>>, TemporalError> {
+        let db = Arc::clone(&self.db);
+        let prefix = Self::series_prefix(series_id);
+
+        tokio::task::spawn_blocking(move || -> Result<Option<TimePoint<T>>, TemporalError> {
+            let txn = db
+                .begin_read()
+                .map_err(|e| TemporalError::StoreError(format!("read txn: {e}")))?;
+            let points = match txn.open_table(POINTS) {
+                Ok(t) => t,
+                Err(_) => return Ok(None),
+            };
+            // Every key of the series sorts below the prefix with SEP bumped.
+            let mut upper = prefix.clone();
+            if let Some(last) = upper.last_mut() {
+                *last = SEP + 1;
+            }
+            let mut iter = points
+                .range(prefix.as_slice()..upper.as_slice())
+                .map_err(|e| TemporalError::StoreError(format!("range scan: {e}")))?;
+            match iter.next_back() {
+                Some(entry) => {
+                    let entry = entry.map_err(|e| TemporalError::StoreError(format!("range entry: {e}")))?;
+                    Ok(Some(Self::deserialise_point(entry.1.value())?))
+                }
+                None => Ok(None),
+            }
+        })
+        .await
+        .map_err(|e| TemporalError::StoreError(format!("spawn_blocking: {e}")))?
+    }
+
+    async fn prune(&self, series_id: &str, before: DateTime<Utc>) -> Result<usize, TemporalError> {
+        let db = Arc::clone(&self.db);
+        let start = Self::series_prefix(series_id);
+        let end = Self::series_time_key(series_id, &before)?;
+
+        tokio::task::spawn_blocking(move || -> Result<usize, TemporalError> {
+            let txn = db
+                .begin_write()
+                .map_err(|e| TemporalError::StoreError(format!("write txn: {e}")))?;
+            let removed = {
+                let mut points = txn
+                    .open_table(POINTS)
+                    .map_err(|e| TemporalError::StoreError(format!("open points: {e}")))?;
+                // As in `query`, `end` sorts before every key carrying the
+                // cutoff itself, so points at `before` are kept.
+                let mut removed = 0;
+                for entry in points
+                    .extract_from_if(start.as_slice()..end.as_slice(), |_, _| true)
+                    .map_err(|e| TemporalError::StoreError(format!("range scan: {e}")))?
+                {
+                    entry.map_err(|e| TemporalError::StoreError(format!("remove point: {e}")))?;
+                    removed += 1;
+                }
+                removed
+            };
+            txn.commit()
+                .map_err(|e| TemporalError::StoreError(format!("commit: {e}")))?;
+            Ok(removed)
+        })
+        .await
+        .map_err(|e| TemporalError::StoreError(format!("spawn_blocking: {e}")))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_points_survive_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("series.redb");
+        let now = Utc::now();
+
+        {
+            let store: RedbTimeSeriesStore<f64> = RedbTimeSeriesStore::persistent(&path).unwrap();
+            store.append("cpu", TimePoint::new(now - chrono::Duration::minutes(2), 0.5)).await.unwrap();
+            store.append("cpu", TimePoint::new(now - chrono::Duration::minutes(1), 0.7)).await.unwrap();
+            store.append("cpu", TimePoint::new(now - chrono::Duration::minutes(1), 0.8)).await.unwrap();
+            store.append("cpu2", TimePoint::new(now, 9.0)).await.unwrap();
+        }
+
+        let store: RedbTimeSeriesStore<f64> = RedbTimeSeriesStore::persistent(&path).unwrap();
+        let points = store
+            .query("cpu", &TimeRange::last(chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        assert_eq!(points.iter().map(|p| p.value).collect::<Vec<_>>(), vec![0.5, 0.7, 0.8]);
+
+        let recent = store
+            .query("cpu", &TimeRange::new(now - chrono::Duration::seconds(90), now).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+
+        assert_eq!(store.latest("cpu").await.unwrap().unwrap().value, 0.8);
+        assert!(store.latest("missing").await.unwrap().is_none());
+
+        assert_eq!(store.prune("cpu", now - chrono::Duration::seconds(90)).await.unwrap(), 1);
+        assert_eq!(store.query("cpu", &TimeRange::last(chrono::Duration::hours(1))).await.unwrap().len(), 2);
+        assert_eq!(store.latest("cpu2").await.unwrap().unwrap().value, 9.0);
+    }
+}
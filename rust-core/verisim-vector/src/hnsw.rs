@@ -532,6 +532,24 @@ async fn test_hnsw_basic_insert_and_search() {
         assert_eq!(results[1].id, "e2");
     }
 
+    #[tokio::test]
+    async fn test_hnsw_search_prefixed() {
+        let store = HnswVectorStore::with_defaults(3, DistanceMetric::Cosine);
+
+        for i in 0..20 {
+            let prefix = if i % 10 == 0 { "b" } else { "a" };
+            let v = vec![1.0, i as f32 * 0.01, 0.0];
+            store.upsert(&Embedding::new(format!("{prefix}:e{i}"), v)).await.unwrap();
+        }
+
+        let results = store.search_prefixed(&[1.0, 0.0, 0.0], 1, "b:").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "b:e0");
+
+        let results = store.search_prefixed(&[1.0, 0.0, 0.0], 5, "b:").await.unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_hnsw_upsert_updates_vector() {
         let store = HnswVectorStore::with_defaults(3, DistanceMetric::Cosine);
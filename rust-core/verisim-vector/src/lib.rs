@@ -99,6 +99,24 @@ pub trait VectorStore: Send + Sync {
     /// Search for similar vectors
     async fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>, VectorError>;
 
+    /// Search among the vectors whose ID starts with `prefix`.  The default
+    /// widens an unfiltered search until it finds `k` matches or runs out of
+    /// vectors; stores that can filter during the scan override it.
+    async fn search_prefixed(&self, query: &[f32], k: usize, prefix: &str) -> Result<Vec<SearchResult>, VectorError> {
+        let mut fetch = k.max(1).saturating_mul(4);
+        loop {
+            let results = self.search(query, fetch).await?;
+            let exhausted = results.len() < fetch;
+            let mut matching: Vec<SearchResult> =
+                results.into_iter().filter(|r| r.id.starts_with(prefix)).collect();
+            if matching.len() >= k || exhausted {
+                matching.truncate(k);
+                return Ok(matching);
+            }
+            fetch = fetch.saturating_mul(4);
+        }
+    }
+
     /// Get embedding by ID
     async fn get(&self, id: &str) -> Result<Option<Embedding>, VectorError>;
 
@@ -177,6 +195,10 @@ async fn upsert(&self, embedding: &Embedding) -> Result<(), VectorError> {
     }
 
     async fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>, VectorError> {
+        self.search_prefixed(query, k, "").await
+    }
+
+    async fn search_prefixed(&self, query: &[f32], k: usize, prefix: &str) -> Result<Vec<SearchResult>, VectorError> {
         if query.len() != self.dimension {
             return Err(VectorError::DimensionMismatch {
                 expected: self.dimension,
@@ -189,6 +211,7 @@ async fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>, Vec
         // Compute similarities for all embeddings (brute-force)
         let mut scored: Vec<_> = embeddings
             .iter()
+            .filter(|(id, _)| id.starts_with(prefix))
             .map(|(id, emb)| {
                 let score = self.similarity(query, &emb.vector);
                 SearchResult {
@@ -252,4 +275,17 @@ async fn test_upsert_and_search() {
         assert_eq!(results.len(), 2);
         assert_eq!(results[0].id, "e1");
     }
+
+    #[tokio::test]
+    async fn test_search_prefixed() {
+        let store = BruteForceVectorStore::new(3, DistanceMetric::Cosine);
+
+        store.upsert(&Embedding::new("a:e1", vec![1.0, 0.0, 0.0])).await.unwrap();
+        store.upsert(&Embedding::new("b:e2", vec![0.9, 0.1, 0.0])).await.unwrap();
+        store.upsert(&Embedding::new("b:e3", vec![0.0, 1.0, 0.0])).await.unwrap();
+
+        let results = store.search_prefixed(&[1.0, 0.0, 0.0], 5, "b:").await.unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["b:e2", "b:e3"]);
+    }
 }
@@ -73,6 +73,16 @@ pub enum WalError {
     /// Attempted to read past the end of a segment file.
     #[error("Unexpected end of segment at offset {0}")]
     UnexpectedEof(u64),
+
+    /// A snapshot was offered for a sequence number the log has not
+    /// reached yet.
+    #[error("Snapshot at sequence {sequence} is ahead of the last WAL entry {last}")]
+    SnapshotAhead {
+        /// The sequence number the snapshot claims to cover.
+        sequence: u64,
+        /// The last sequence number appended to the WAL.
+        last: u64,
+    },
 }
 
 /// Convenience type alias for WAL results.
@@ -8,6 +8,11 @@
 // the WAL is replayed from the last checkpoint to bring the database back
 // to a consistent state.
 //
+// To keep the log from growing forever, the owner periodically compacts it:
+// a snapshot of the applied state is written next to the segments, and the
+// segments it covers are deleted. Recovery then starts from the latest
+// snapshot and replays only the entries after it.
+//
 // # Architecture
 //
 // The WAL is organized as a sequence of **segment files** in a dedicated
@@ -53,6 +58,9 @@
 // // Write a checkpoint.
 // writer.checkpoint().unwrap();
 //
+// // Snapshot the applied state and drop the segments it covers.
+// writer.compact(seq, b"serialized state").unwrap();
+//
 // // Read back.
 // let reader = WalReader::open("/tmp/verisim-wal").unwrap();
 // for entry in reader.replay_all().unwrap() {
@@ -64,6 +72,7 @@
 pub mod error;
 pub mod reader;
 pub mod segment;
+pub mod snapshot;
 pub mod writer;
 
 // Re-export the primary public API for ergonomic imports.
@@ -71,4 +80,5 @@
 pub use error::{WalError, WalResult};
 pub use reader::{WalEntryIterator, WalReader};
 pub use segment::{SegmentInfo, DEFAULT_MAX_SEGMENT_SIZE};
-pub use writer::{SyncMode, WalWriter};
+pub use snapshot::{read_latest_snapshot, SnapshotInfo};
+pub use writer::{SyncMode, WalCompaction, WalStats, WalWriter};
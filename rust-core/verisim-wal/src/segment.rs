@@ -156,6 +156,33 @@ pub fn prune_segments_before(wal_dir: &Path, checkpoint_sequence: u64) -> WalRes
     Ok(removed)
 }
 
+/// Remove segment files all of whose entries have a sequence number at or
+/// below `sequence`, i.e. every segment followed by one starting at or
+/// before `sequence + 1`. The last segment is never removed.
+///
+/// Returns the number of segments removed and the bytes they occupied.
+pub fn prune_segments_through(wal_dir: &Path, sequence: u64) -> WalResult<(usize, u64)> {
+    let segments = list_segments(wal_dir)?;
+    let mut removed = 0;
+    let mut bytes = 0;
+
+    for pair in segments.windows(2) {
+        let (segment, next) = (&pair[0], &pair[1]);
+        if next.start_sequence <= sequence.saturating_add(1) {
+            debug!(
+                path = %segment.path.display(),
+                start_sequence = segment.start_sequence,
+                "Pruning WAL segment (covered by snapshot at {sequence})"
+            );
+            fs::remove_file(&segment.path)?;
+            removed += 1;
+            bytes += segment.file_size;
+        }
+    }
+
+    Ok((removed, bytes))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,4 +315,24 @@ fn test_prune_does_not_remove_only_segment() {
         let remaining = list_segments(&dir.path).unwrap();
         assert_eq!(remaining.len(), 1);
     }
+
+    #[test]
+    fn test_prune_segments_through_keeps_segment_with_later_entries() {
+        let dir = TestDir::new();
+        dir.create_segment(0, 100);
+        dir.create_segment(50, 100);
+        dir.create_segment(100, 100);
+
+        // Sequence 60 lives in the segment starting at 50, so only the
+        // first segment is entirely covered.
+        let (removed, bytes) = prune_segments_through(&dir.path, 60).unwrap();
+        assert_eq!((removed, bytes), (1, 100));
+
+        // Everything up to 99 is covered; the last segment always stays.
+        let (removed, _) = prune_segments_through(&dir.path, 500).unwrap();
+        assert_eq!(removed, 1);
+        let remaining = list_segments(&dir.path).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].start_sequence, 100);
+    }
 }
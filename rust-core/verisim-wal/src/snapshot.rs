@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: PMPL-1.0-or-later
+//
+// VeriSimDB Write-Ahead Log - Checkpoint snapshots
+// Copyright (c) 2026 Jonathan D.A. Jewell (hyperpolymath) <jonathan.jewell@open.ac.uk>
+//
+// A snapshot is an opaque blob holding the state the log had produced up to
+// and including a given sequence number. It lives next to the segments as
+// `snapshot-{sequence:016}.bin`. Once a snapshot is durable, every segment
+// whose entries all precede it can be removed: recovery loads the latest
+// snapshot and replays only the entries after its sequence.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use tracing::debug;
+
+use crate::error::{WalError, WalResult};
+
+/// The file extension used for snapshot files.
+pub const SNAPSHOT_EXTENSION: &str = "bin";
+
+/// The prefix used for snapshot file names.
+pub const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+/// Metadata about a single snapshot file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    /// The full path to the snapshot file on disk.
+    pub path: PathBuf,
+
+    /// The last WAL sequence number the snapshot covers.
+    pub sequence: u64,
+
+    /// File size in bytes.
+    pub file_size: u64,
+}
+
+/// Build the canonical file name for a snapshot covering the given sequence.
+///
+/// Format: `snapshot-0000000000000042.bin`
+pub fn snapshot_filename(sequence: u64) -> String {
+    format!("{SNAPSHOT_PREFIX}{sequence:016}.{SNAPSHOT_EXTENSION}")
+}
+
+/// Parse the covered sequence number from a snapshot file name.
+///
+/// Returns `None` if the name does not match the expected pattern.
+pub fn parse_snapshot_filename(name: &str) -> Option<u64> {
+    let stripped = name.strip_prefix(SNAPSHOT_PREFIX)?;
+    let num_str = stripped.strip_suffix(&format!(".{SNAPSHOT_EXTENSION}"))?;
+    num_str.parse::<u64>().ok()
+}
+
+/// Scan a WAL directory and return all snapshot files, sorted by sequence
+/// number (ascending).
+pub fn list_snapshots(wal_dir: &Path) -> WalResult<Vec<SnapshotInfo>> {
+    if !wal_dir.is_dir() {
+        return Err(WalError::DirectoryNotFound(
+            wal_dir.display().to_string(),
+        ));
+    }
+
+    let mut snapshots = Vec::new();
+    for dir_entry in fs::read_dir(wal_dir)? {
+        let dir_entry = dir_entry?;
+        let file_name = dir_entry.file_name();
+        if let Some(sequence) = parse_snapshot_filename(&file_name.to_string_lossy()) {
+            snapshots.push(SnapshotInfo {
+                path: dir_entry.path(),
+                sequence,
+                file_size: dir_entry.metadata()?.len(),
+            });
+        }
+    }
+    snapshots.sort_by_key(|s| s.sequence);
+    Ok(snapshots)
+}
+
+/// Durably write a snapshot covering `sequence`.
+///
+/// The data is written to a temporary file, fsynced, and renamed into
+/// place, so a crash never leaves a partial snapshot under a valid name.
+pub fn write_snapshot(wal_dir: &Path, sequence: u64, data: &[u8]) -> WalResult<SnapshotInfo> {
+    let path = wal_dir.join(snapshot_filename(sequence));
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, &path)?;
+    // Make the rename itself durable.
+    File::open(wal_dir)?.sync_all()?;
+
+    debug!(path = %path.display(), sequence, "Wrote WAL snapshot");
+
+    Ok(SnapshotInfo {
+        path,
+        sequence,
+        file_size: data.len() as u64,
+    })
+}
+
+/// Read the most recent snapshot, returning its sequence number and data.
+///
+/// Returns `None` if no snapshot has been written.
+pub fn read_latest_snapshot(wal_dir: &Path) -> WalResult<Option<(u64, Vec<u8>)>> {
+    match list_snapshots(wal_dir)?.pop() {
+        Some(latest) => Ok(Some((latest.sequence, fs::read(&latest.path)?))),
+        None => Ok(None),
+    }
+}
+
+/// Remove snapshot files covering a sequence strictly less than the given
+/// one. Returns the number of snapshots removed.
+pub fn prune_snapshots_before(wal_dir: &Path, sequence: u64) -> WalResult<usize> {
+    let mut removed = 0;
+    for snapshot in list_snapshots(wal_dir)? {
+        if snapshot.sequence < sequence {
+            fs::remove_file(&snapshot.path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_filename_roundtrip() {
+        assert_eq!(snapshot_filename(42), "snapshot-0000000000000042.bin");
+        assert_eq!(parse_snapshot_filename(&snapshot_filename(42)), Some(42));
+        assert_eq!(parse_snapshot_filename("wal-0000000000000042.log"), None);
+        assert_eq!(parse_snapshot_filename("snapshot-0000000000000042.tmp"), None);
+    }
+
+    #[test]
+    fn test_read_latest_snapshot() {
+        let dir = TempDir::new().unwrap();
+        assert!(read_latest_snapshot(dir.path()).unwrap().is_none());
+
+        write_snapshot(dir.path(), 10, b"first").unwrap();
+        write_snapshot(dir.path(), 25, b"second").unwrap();
+
+        let (sequence, data) = read_latest_snapshot(dir.path()).unwrap().unwrap();
+        assert_eq!(sequence, 25);
+        assert_eq!(data, b"second");
+    }
+
+    #[test]
+    fn test_prune_snapshots_before() {
+        let dir = TempDir::new().unwrap();
+        write_snapshot(dir.path(), 10, b"old").unwrap();
+        write_snapshot(dir.path(), 25, b"new").unwrap();
+
+        assert_eq!(prune_snapshots_before(dir.path(), 25).unwrap(), 1);
+        let remaining = list_snapshots(dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].sequence, 25);
+    }
+}
@@ -18,8 +18,9 @@
 use crate::entry::{WalEntry, WalModality, WalOperation};
 use crate::error::{WalError, WalResult};
 use crate::segment::{
-    list_segments, segment_path, DEFAULT_MAX_SEGMENT_SIZE, SegmentInfo,
+    list_segments, prune_segments_through, segment_path, DEFAULT_MAX_SEGMENT_SIZE, SegmentInfo,
 };
+use crate::snapshot::{list_snapshots, prune_snapshots_before, write_snapshot, SnapshotInfo};
 
 // ---------------------------------------------------------------------------
 // SyncMode
@@ -45,6 +46,43 @@ pub enum SyncMode {
     Async,
 }
 
+// ---------------------------------------------------------------------------
+// Compaction and stats
+// ---------------------------------------------------------------------------
+
+/// Outcome of [`WalWriter::compact`].
+#[derive(Debug, Clone)]
+pub struct WalCompaction {
+    /// The snapshot that now covers the log up to its sequence.
+    pub snapshot: SnapshotInfo,
+
+    /// Number of segment files removed.
+    pub segments_removed: usize,
+
+    /// Bytes freed by removing those segments.
+    pub bytes_removed: u64,
+}
+
+/// Size of the on-disk log, for monitoring how far it has grown since the
+/// last snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalStats {
+    /// Sequence number the next entry will get.
+    pub next_sequence: u64,
+
+    /// Sequence covered by the latest snapshot, if one has been written.
+    pub snapshot_sequence: Option<u64>,
+
+    /// Entries appended since the latest snapshot (all entries if none).
+    pub entries_since_snapshot: u64,
+
+    /// Number of segment files on disk.
+    pub segment_count: usize,
+
+    /// Total size of the segment files in bytes.
+    pub segment_bytes: u64,
+}
+
 // ---------------------------------------------------------------------------
 // WalWriter
 // ---------------------------------------------------------------------------
@@ -75,6 +113,9 @@ pub struct WalWriter {
 
     /// Timestamp of the last fsync call (for `SyncMode::Periodic`).
     last_sync: Instant,
+
+    /// Sequence covered by the latest snapshot in the directory.
+    snapshot_sequence: Option<u64>,
 }
 
 impl WalWriter {
@@ -137,6 +178,8 @@ pub fn open_with_max_size(
             (last, file, next_seq)
         };
 
+        let snapshot_sequence = list_snapshots(&wal_dir)?.last().map(|s| s.sequence);
+
         Ok(Self {
             wal_dir,
             current_file,
@@ -145,6 +188,7 @@ pub fn open_with_max_size(
             max_segment_size,
             sync_mode,
             last_sync: Instant::now(),
+            snapshot_sequence,
         })
     }
 
@@ -240,6 +284,73 @@ pub fn rotate(&mut self) -> WalResult<()> {
         Ok(())
     }
 
+    /// Write a snapshot of the state produced by every entry up to and
+    /// including `sequence`, then delete the segments and older snapshots
+    /// it makes redundant.
+    ///
+    /// The caller must have applied all those entries, and no entry after
+    /// `sequence`, to the state it serialized. If the current segment holds
+    /// covered entries it is rotated first, so that it can be removed too.
+    pub fn compact(&mut self, sequence: u64, snapshot: &[u8]) -> WalResult<WalCompaction> {
+        self.compact_retaining(sequence, snapshot, 1)
+    }
+
+    /// Like [`compact`](Self::compact), but keep the newest `retain`
+    /// snapshots (at least one) and every segment after the oldest of them,
+    /// so the log can still be replayed from any retained snapshot.
+    pub fn compact_retaining(
+        &mut self,
+        sequence: u64,
+        snapshot: &[u8],
+        retain: usize,
+    ) -> WalResult<WalCompaction> {
+        let last = self.next_sequence.saturating_sub(1);
+        if sequence > last {
+            return Err(WalError::SnapshotAhead { sequence, last });
+        }
+
+        let snapshot = write_snapshot(&self.wal_dir, sequence, snapshot)?;
+        self.snapshot_sequence = Some(sequence);
+
+        if self.current_segment.file_size > 0 && self.current_segment.start_sequence <= sequence {
+            self.rotate()?;
+        }
+        let snapshots = list_snapshots(&self.wal_dir)?;
+        let oldest_kept = snapshots
+            .get(snapshots.len().saturating_sub(retain.max(1)))
+            .map_or(sequence, |s| s.sequence);
+        let (segments_removed, bytes_removed) = prune_segments_through(&self.wal_dir, oldest_kept)?;
+        prune_snapshots_before(&self.wal_dir, oldest_kept)?;
+
+        info!(
+            sequence,
+            oldest_kept,
+            segments_removed,
+            bytes_removed,
+            "WAL compacted to snapshot"
+        );
+
+        Ok(WalCompaction {
+            snapshot,
+            segments_removed,
+            bytes_removed,
+        })
+    }
+
+    /// Current size of the log and how far it has grown since the latest
+    /// snapshot.
+    pub fn stats(&self) -> WalResult<WalStats> {
+        let segments = list_segments(&self.wal_dir)?;
+        let last = self.next_sequence.saturating_sub(1);
+        Ok(WalStats {
+            next_sequence: self.next_sequence,
+            snapshot_sequence: self.snapshot_sequence,
+            entries_since_snapshot: last.saturating_sub(self.snapshot_sequence.unwrap_or(0)),
+            segment_count: segments.len(),
+            segment_bytes: segments.iter().map(|s| s.file_size).sum(),
+        })
+    }
+
     /// Returns the sequence number that will be assigned to the next entry.
     pub fn next_sequence(&self) -> u64 {
         self.next_sequence
@@ -469,4 +580,83 @@ fn test_periodic_sync_mode() {
         // Explicit sync should always work.
         writer.sync().unwrap();
     }
+
+    #[test]
+    fn test_compact_removes_covered_segments() {
+        let dir = TempDir::new().unwrap();
+        let mut writer =
+            WalWriter::open_with_max_size(dir.path(), SyncMode::Async, 100).unwrap();
+
+        let mut last = 0;
+        for _ in 0..10 {
+            last = writer.append(test_entry(WalModality::Document)).unwrap();
+        }
+        assert!(list_segments(dir.path()).unwrap().len() > 1);
+
+        let compaction = writer.compact(last, b"state").unwrap();
+        assert!(compaction.segments_removed > 0);
+        assert_eq!(compaction.snapshot.sequence, last);
+
+        // Only the fresh (empty) segment after the snapshot remains.
+        let segments = list_segments(dir.path()).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].start_sequence, last + 1);
+
+        let stats = writer.stats().unwrap();
+        assert_eq!(stats.snapshot_sequence, Some(last));
+        assert_eq!(stats.entries_since_snapshot, 0);
+
+        writer.append(test_entry(WalModality::Graph)).unwrap();
+        assert_eq!(writer.stats().unwrap().entries_since_snapshot, 1);
+    }
+
+    #[test]
+    fn test_compact_rejects_future_sequence() {
+        let dir = TempDir::new().unwrap();
+        let mut writer = WalWriter::open(dir.path(), SyncMode::Async).unwrap();
+        writer.append(test_entry(WalModality::Graph)).unwrap();
+
+        assert!(matches!(
+            writer.compact(5, b"state"),
+            Err(WalError::SnapshotAhead { sequence: 5, last: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_sequence_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open(dir.path(), SyncMode::Fsync).unwrap();
+            let seq = writer.append(test_entry(WalModality::Graph)).unwrap();
+            writer.compact(seq, b"state").unwrap();
+            writer.append(test_entry(WalModality::Vector)).unwrap();
+        }
+
+        let writer = WalWriter::open(dir.path(), SyncMode::Fsync).unwrap();
+        let stats = writer.stats().unwrap();
+        assert_eq!(stats.snapshot_sequence, Some(1));
+        assert_eq!(stats.next_sequence, 3);
+        assert_eq!(stats.entries_since_snapshot, 1);
+    }
+
+    #[test]
+    fn test_compact_retaining_keeps_replayable_history() {
+        let dir = TempDir::new().unwrap();
+        let mut writer = WalWriter::open(dir.path(), SyncMode::Async).unwrap();
+
+        let first = writer.append(test_entry(WalModality::Graph)).unwrap();
+        writer.compact_retaining(first, b"one", 2).unwrap();
+        let second = writer.append(test_entry(WalModality::Vector)).unwrap();
+        writer.compact_retaining(second, b"two", 2).unwrap();
+        let third = writer.append(test_entry(WalModality::Tensor)).unwrap();
+        writer.compact_retaining(third, b"three", 2).unwrap();
+
+        // The two newest snapshots survive, with every entry after the older one
+        let snapshots = crate::snapshot::list_snapshots(dir.path()).unwrap();
+        let kept: Vec<u64> = snapshots.iter().map(|s| s.sequence).collect();
+        assert_eq!(kept, vec![second, third]);
+        let reader = crate::reader::WalReader::open(dir.path()).unwrap();
+        let replayed: Vec<u64> = reader.replay_from(second + 1).unwrap().map(|e| e.sequence).collect();
+        assert_eq!(replayed, vec![third]);
+    }
 }